@@ -0,0 +1,130 @@
+use core::{hint::black_box, time::Duration};
+
+use benches::bench;
+use bevy_ecs::{
+    component::Component,
+    reflect::{AppTypeRegistry, ReflectComponent},
+    world::World,
+};
+use bevy_reflect::Reflect;
+use bevy_scene::{deserialize_binary_scene, ron, serde::SceneDeserializer, DynamicScene};
+use criterion::{
+    criterion_group, measurement::Measurement, AxisScale, BenchmarkGroup, BenchmarkId, Criterion,
+    PlotConfiguration, Throughput,
+};
+use serde::de::DeserializeSeed;
+
+criterion_group!(benches, load_ron_scene, load_binary_scene);
+
+const WARM_UP_TIME: Duration = Duration::from_millis(500);
+const MEASUREMENT_TIME: Duration = Duration::from_secs(4);
+
+/// An array of scene sizes, in entities, used in benchmarks.
+///
+/// This scales logarithmically.
+const SIZES: [usize; 3] = [100, 1000, 10000];
+
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+struct Position {
+    x: f32,
+    y: f32,
+    z: f32,
+}
+
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+struct Label {
+    name: String,
+    layers: Vec<u32>,
+}
+
+/// Creates a [`BenchmarkGroup`] with common configuration shared by all benchmarks within this
+/// module.
+fn create_group<'a, M: Measurement>(c: &'a mut Criterion<M>, name: &str) -> BenchmarkGroup<'a, M> {
+    let mut group = c.benchmark_group(name);
+
+    group
+        .warm_up_time(WARM_UP_TIME)
+        .measurement_time(MEASUREMENT_TIME)
+        // Make the plots logarithmic, matching `SIZES`' scale.
+        .plot_config(PlotConfiguration::default().summary_scale(AxisScale::Logarithmic));
+
+    group
+}
+
+/// Creates a scene with `size` entities, and the type registry of its components.
+fn create_scene(size: usize) -> (DynamicScene, AppTypeRegistry) {
+    let type_registry = AppTypeRegistry::default();
+    {
+        let mut type_registry = type_registry.write();
+        type_registry.register::<Position>();
+        type_registry.register::<Label>();
+    }
+
+    let mut world = World::new();
+    world.insert_resource(type_registry.clone());
+    for i in 0..size {
+        world.spawn((
+            Position {
+                x: i as f32,
+                y: 0.5,
+                z: -(i as f32),
+            },
+            Label {
+                name: format!("Entity {i}"),
+                layers: vec![1, 2, 3],
+            },
+        ));
+    }
+
+    (DynamicScene::from_world(&world), type_registry)
+}
+
+fn load_ron_scene(criterion: &mut Criterion) {
+    let mut group = create_group(criterion, bench!("load_ron_scene"));
+
+    for size in SIZES {
+        let (scene, type_registry) = create_scene(size);
+        let type_registry = type_registry.read();
+        let serialized = scene.serialize(&type_registry).unwrap();
+
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(size),
+            &serialized,
+            |b, input| {
+                b.iter(|| {
+                    let mut deserializer = ron::de::Deserializer::from_str(input).unwrap();
+                    let scene_deserializer = SceneDeserializer::new(&type_registry);
+                    black_box(scene_deserializer.deserialize(&mut deserializer).unwrap())
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn load_binary_scene(criterion: &mut Criterion) {
+    let mut group = create_group(criterion, bench!("load_binary_scene"));
+
+    for size in SIZES {
+        let (scene, type_registry) = create_scene(size);
+        let type_registry = type_registry.read();
+        let serialized = scene.serialize_binary(&type_registry).unwrap();
+
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(size),
+            &serialized,
+            |b, input| {
+                b.iter(|| {
+                    black_box(deserialize_binary_scene(input, &type_registry, None).unwrap())
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
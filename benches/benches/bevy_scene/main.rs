@@ -0,0 +1,5 @@
+use criterion::criterion_main;
+
+mod load;
+
+criterion_main!(load::benches);
@@ -16,6 +16,7 @@ use bevy_ecs::{
     reflect::ReflectComponent,
     system::{Res, ResMut, Resource},
 };
+use bevy_math::Vec2;
 use bevy_reflect::{prelude::ReflectDefault, Reflect, ReflectSerialize};
 use bevy_utils::HashMap;
 use derive_more::derive::From;
@@ -41,12 +42,14 @@ use crate::{AnimationClip, AnimationTargetId};
 /// the root and blends the animations together in a bottom-up fashion to
 /// produce the final pose.
 ///
-/// There are three types of nodes: *blend nodes*, *add nodes*, and *clip
-/// nodes*, all of which can have an associated weight. Blend nodes and add
-/// nodes have no associated animation clip and combine the animations of their
-/// children according to those children's weights. Clip nodes specify an
-/// animation clip to play. When a graph is created, it starts with only a
-/// single blend node, the root node.
+/// There are four types of nodes: *blend nodes*, *add nodes*, *blend space
+/// nodes*, and *clip nodes*, all of which can have an associated weight. Blend
+/// nodes and add nodes have no associated animation clip and combine the
+/// animations of their children according to those children's weights. Blend
+/// space nodes are blend nodes that additionally weight their children by
+/// their distance to a parameter that the [`AnimationPlayer`] controls. Clip
+/// nodes specify an animation clip to play. When a graph is created, it starts
+/// with only a single blend node, the root node.
 ///
 /// For example, consider the following graph:
 ///
@@ -98,7 +101,11 @@ use crate::{AnimationClip, AnimationTargetId};
 /// continue to be depicted as holding the object.
 ///
 /// Animation graphs are assets and can be serialized to and loaded from [RON]
-/// files. Canonically, such files have an `.animgraph.ron` extension.
+/// files. Canonically, such files have an `.animgraph.ron` extension. This
+/// allows graphs to be authored outside of code, and, with the `file_watcher`
+/// feature of `bevy_asset`, to be hot reloaded while the application runs.
+///
+/// [`AnimationPlayer`]: crate::AnimationPlayer
 ///
 /// The animation graph implements [RFC 51]. See that document for more
 /// information.
@@ -178,6 +185,17 @@ pub struct AnimationGraphNode {
     /// this node and its descendants *cannot* animate mask group N.
     pub mask: AnimationMask,
 
+    /// An optional mask that scales the contribution of this node to each
+    /// animation target (bone) individually.
+    ///
+    /// Unlike [`Self::mask`], which either enables or disables whole mask
+    /// groups for this node and its descendants, a joint mask assigns each
+    /// target a weight that multiplies the weight of this node when its output
+    /// is blended into its parent. This allows, for example, an upper-body
+    /// aiming clip to be blended over a running clip without affecting the
+    /// legs, with a gradual transition along the spine.
+    pub joint_mask: Option<Handle<AnimationJointMask>>,
+
     /// The weight of this node, which signifies its contribution in blending.
     ///
     /// Note that this does not propagate down the graph hierarchy; rather,
@@ -229,6 +247,125 @@ pub enum AnimationNodeType {
     /// top of a running animation to produce an animation of a character
     /// attacking while running.
     Add,
+
+    /// A *one-dimensional blend space node*, which blends its children like a
+    /// blend node, but additionally weights each child by its position
+    /// relative to the node's [blend space parameter].
+    ///
+    /// The two children on either side of the parameter are interpolated
+    /// linearly, and all other children have a weight of zero. Blend spaces
+    /// are typically used to blend locomotion animations by speed, such as
+    /// walking at 1.0 and running at 3.0.
+    ///
+    /// [blend space parameter]: crate::AnimationPlayer::set_blend_space_parameter
+    BlendSpace1d(BlendSpace1d),
+
+    /// A *two-dimensional blend space node*, which blends its children like a
+    /// blend node, but additionally weights each child by its position
+    /// relative to the node's [blend space parameter].
+    ///
+    /// The weights are computed with gradient band interpolation, which
+    /// produces smooth results for arbitrary arrangements of children. Blend
+    /// spaces are typically used to blend directional locomotion animations,
+    /// with the parameter being the character's velocity on the ground plane.
+    ///
+    /// [blend space parameter]: crate::AnimationPlayer::set_blend_space_parameter
+    BlendSpace2d(BlendSpace2d),
+}
+
+/// The children of a [one-dimensional blend space node] and their positions.
+///
+/// [one-dimensional blend space node]: AnimationNodeType::BlendSpace1d
+#[derive(Clone, Default, Reflect, Debug, Serialize, Deserialize)]
+pub struct BlendSpace1d {
+    /// The child nodes, each paired with its position along the parameter
+    /// axis.
+    ///
+    /// Children of the node that aren't listed here have a weight of zero.
+    pub points: Vec<(AnimationNodeIndex, f32)>,
+}
+
+/// The children of a [two-dimensional blend space node] and their positions.
+///
+/// [two-dimensional blend space node]: AnimationNodeType::BlendSpace2d
+#[derive(Clone, Default, Reflect, Debug, Serialize, Deserialize)]
+pub struct BlendSpace2d {
+    /// The child nodes, each paired with its position in the parameter space.
+    ///
+    /// Children of the node that aren't listed here have a weight of zero.
+    pub points: Vec<(AnimationNodeIndex, Vec2)>,
+}
+
+impl BlendSpace1d {
+    /// Computes the weight of each point for the given parameter.
+    ///
+    /// The weights of all points sum to 1, unless there are no points.
+    pub fn weights(&self, parameter: f32) -> SmallVec<[(AnimationNodeIndex, f32); 8]> {
+        // Find the nearest points on either side of the parameter.
+        let mut below: Option<(AnimationNodeIndex, f32)> = None;
+        let mut above: Option<(AnimationNodeIndex, f32)> = None;
+        for &(node, position) in &self.points {
+            if position <= parameter && below.is_none_or(|(_, below)| position > below) {
+                below = Some((node, position));
+            }
+            if position >= parameter && above.is_none_or(|(_, above)| position < above) {
+                above = Some((node, position));
+            }
+        }
+
+        let mut weights: SmallVec<_> = self.points.iter().map(|&(node, _)| (node, 0.0)).collect();
+        let mut set_weight = |node: AnimationNodeIndex, weight: f32| {
+            if let Some(entry) = weights.iter_mut().find(|(other, _)| *other == node) {
+                entry.1 += weight;
+            }
+        };
+        match (below, above) {
+            (Some((below, below_position)), Some((above, above_position)))
+                if above_position > below_position =>
+            {
+                let t = (parameter - below_position) / (above_position - below_position);
+                set_weight(below, 1.0 - t);
+                set_weight(above, t);
+            }
+            (Some((node, _)), _) | (None, Some((node, _))) => set_weight(node, 1.0),
+            (None, None) => {}
+        }
+        weights
+    }
+}
+
+impl BlendSpace2d {
+    /// Computes the weight of each point for the given parameter, using
+    /// gradient band interpolation.
+    ///
+    /// The weights of all points sum to 1, unless there are no points.
+    pub fn weights(&self, parameter: Vec2) -> SmallVec<[(AnimationNodeIndex, f32); 8]> {
+        let mut weights: SmallVec<[(AnimationNodeIndex, f32); 8]> = self
+            .points
+            .iter()
+            .map(|&(node, position)| {
+                let weight = self
+                    .points
+                    .iter()
+                    .filter(|(_, other)| *other != position)
+                    .map(|&(_, other)| {
+                        let edge = other - position;
+                        1.0 - (parameter - position).dot(edge) / edge.length_squared()
+                    })
+                    .fold(1.0, f32::min)
+                    .max(0.0);
+                (node, weight)
+            })
+            .collect();
+
+        let total: f32 = weights.iter().map(|(_, weight)| weight).sum();
+        if total > 0.0 {
+            for (_, weight) in &mut weights {
+                *weight /= total;
+            }
+        }
+        weights
+    }
 }
 
 /// An [`AssetLoader`] that can load [`AnimationGraph`]s as assets.
@@ -375,6 +512,9 @@ pub struct SerializedAnimationGraphNode {
     pub node_type: SerializedAnimationNodeType,
     /// Corresponds to the `mask` field on [`AnimationGraphNode`].
     pub mask: AnimationMask,
+    /// Corresponds to the `joint_mask` field on [`AnimationGraphNode`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub joint_mask: Option<SerializedAnimationJointMask>,
     /// Corresponds to the `weight` field on [`AnimationGraphNode`].
     pub weight: f32,
 }
@@ -389,6 +529,10 @@ pub enum SerializedAnimationNodeType {
     Blend,
     /// Corresponds to [`AnimationNodeType::Add`].
     Add,
+    /// Corresponds to [`AnimationNodeType::BlendSpace1d`].
+    BlendSpace1d(BlendSpace1d),
+    /// Corresponds to [`AnimationNodeType::BlendSpace2d`].
+    BlendSpace2d(BlendSpace2d),
 }
 
 /// A version of `Handle<AnimationClip>` suitable for serializing as an asset.
@@ -406,6 +550,101 @@ pub enum SerializedAnimationClip {
     AssetId(AssetId<AnimationClip>),
 }
 
+/// A version of `Handle<AnimationJointMask>` suitable for serializing as an
+/// asset.
+///
+/// Like [`SerializedAnimationClip`], this records an asset path if the handle
+/// has one, and falls back to the asset ID otherwise.
+#[derive(Serialize, Deserialize)]
+pub enum SerializedAnimationJointMask {
+    /// Records an asset path.
+    AssetPath(AssetPath<'static>),
+    /// The fallback that records an asset ID.
+    ///
+    /// Because asset IDs can change, this should not be relied upon. Prefer to
+    /// use asset paths where possible.
+    AssetId(AssetId<AnimationJointMask>),
+}
+
+/// A set of per-target weights that scales the contribution of an animation
+/// graph node to each animation target (bone).
+///
+/// Assign a joint mask to a node with [`AnimationGraphNode::joint_mask`].
+/// Targets that aren't listed in the mask use [`Self::default_weight`]. For
+/// example, a mask for an upper-body layer would give the spine and arms a
+/// weight of 1.0 and use a default weight of 0.0, so that the legs keep
+/// playing the animation of the layers below.
+///
+/// Joint masks can be loaded from RON files with the `.jointmask.ron`
+/// extension, which contain a serialized instance of this type.
+#[derive(Asset, Clone, Debug, Reflect, Serialize, Deserialize)]
+#[reflect(Debug, Default)]
+pub struct AnimationJointMask {
+    /// The weight of each animation target that is listed in the mask.
+    pub weights: HashMap<AnimationTargetId, f32>,
+    /// The weight of the animation targets that aren't listed in the mask.
+    #[serde(default = "default_joint_weight")]
+    pub default_weight: f32,
+}
+
+fn default_joint_weight() -> f32 {
+    1.0
+}
+
+impl Default for AnimationJointMask {
+    fn default() -> Self {
+        Self {
+            weights: HashMap::default(),
+            default_weight: 1.0,
+        }
+    }
+}
+
+impl AnimationJointMask {
+    /// Creates a joint mask in which all targets have the given weight, until
+    /// they're assigned another one.
+    pub fn new(default_weight: f32) -> Self {
+        Self {
+            weights: HashMap::default(),
+            default_weight,
+        }
+    }
+
+    /// Sets the weight of the given animation target, returning the mask for
+    /// chaining.
+    pub fn with_weight(mut self, target: AnimationTargetId, weight: f32) -> Self {
+        self.set_weight(target, weight);
+        self
+    }
+
+    /// Sets the weight of the given animation target.
+    pub fn set_weight(&mut self, target: AnimationTargetId, weight: f32) -> &mut Self {
+        self.weights.insert(target, weight);
+        self
+    }
+
+    /// Removes the weight of the given animation target, so that it uses the
+    /// default weight again.
+    pub fn remove_weight(&mut self, target: AnimationTargetId) -> &mut Self {
+        self.weights.remove(&target);
+        self
+    }
+
+    /// Returns the weight of the given animation target.
+    pub fn weight(&self, target: AnimationTargetId) -> f32 {
+        self.weights
+            .get(&target)
+            .copied()
+            .unwrap_or(self.default_weight)
+    }
+}
+
+/// An [`AssetLoader`] that can load [`AnimationJointMask`]s as assets.
+///
+/// The canonical extension for [`AnimationJointMask`]s is `.jointmask.ron`.
+#[derive(Default)]
+pub struct AnimationJointMaskLoader;
+
 /// The type of an animation mask bitfield.
 ///
 /// Bit N corresponds to mask group N.
@@ -468,6 +707,7 @@ impl AnimationGraph {
         let node_index = self.graph.add_node(AnimationGraphNode {
             node_type: AnimationNodeType::Clip(clip),
             mask: 0,
+            joint_mask: None,
             weight,
         });
         self.graph.add_edge(parent, node_index, ());
@@ -488,6 +728,7 @@ impl AnimationGraph {
         let node_index = self.graph.add_node(AnimationGraphNode {
             node_type: AnimationNodeType::Clip(clip),
             mask,
+            joint_mask: None,
             weight,
         });
         self.graph.add_edge(parent, node_index, ());
@@ -527,6 +768,7 @@ impl AnimationGraph {
         let node_index = self.graph.add_node(AnimationGraphNode {
             node_type: AnimationNodeType::Blend,
             mask: 0,
+            joint_mask: None,
             weight,
         });
         self.graph.add_edge(parent, node_index, ());
@@ -550,6 +792,7 @@ impl AnimationGraph {
         let node_index = self.graph.add_node(AnimationGraphNode {
             node_type: AnimationNodeType::Blend,
             mask,
+            joint_mask: None,
             weight,
         });
         self.graph.add_edge(parent, node_index, ());
@@ -571,6 +814,7 @@ impl AnimationGraph {
         let node_index = self.graph.add_node(AnimationGraphNode {
             node_type: AnimationNodeType::Add,
             mask: 0,
+            joint_mask: None,
             weight,
         });
         self.graph.add_edge(parent, node_index, ());
@@ -594,12 +838,95 @@ impl AnimationGraph {
         let node_index = self.graph.add_node(AnimationGraphNode {
             node_type: AnimationNodeType::Add,
             mask,
+            joint_mask: None,
             weight,
         });
         self.graph.add_edge(parent, node_index, ());
         node_index
     }
 
+    /// Adds a one-dimensional blend space node to the animation graph with
+    /// the given weight and returns its index.
+    ///
+    /// The blend space node will be placed under the supplied `parent` node
+    /// and will have no mask. Add children to it with
+    /// [`AnimationGraph::add_clip_to_blend_space_1d`].
+    pub fn add_blend_space_1d(
+        &mut self,
+        weight: f32,
+        parent: AnimationNodeIndex,
+    ) -> AnimationNodeIndex {
+        let node_index = self.graph.add_node(AnimationGraphNode {
+            node_type: AnimationNodeType::BlendSpace1d(BlendSpace1d::default()),
+            mask: 0,
+            joint_mask: None,
+            weight,
+        });
+        self.graph.add_edge(parent, node_index, ());
+        node_index
+    }
+
+    /// Adds a two-dimensional blend space node to the animation graph with
+    /// the given weight and returns its index.
+    ///
+    /// The blend space node will be placed under the supplied `parent` node
+    /// and will have no mask. Add children to it with
+    /// [`AnimationGraph::add_clip_to_blend_space_2d`].
+    pub fn add_blend_space_2d(
+        &mut self,
+        weight: f32,
+        parent: AnimationNodeIndex,
+    ) -> AnimationNodeIndex {
+        let node_index = self.graph.add_node(AnimationGraphNode {
+            node_type: AnimationNodeType::BlendSpace2d(BlendSpace2d::default()),
+            mask: 0,
+            joint_mask: None,
+            weight,
+        });
+        self.graph.add_edge(parent, node_index, ());
+        node_index
+    }
+
+    /// Adds an [`AnimationClip`] with weight 1.0 to a one-dimensional blend
+    /// space node at the given position, and returns its index.
+    ///
+    /// If `blend_space` isn't a [`AnimationNodeType::BlendSpace1d`] node, the
+    /// clip is added as a regular child.
+    pub fn add_clip_to_blend_space_1d(
+        &mut self,
+        clip: Handle<AnimationClip>,
+        position: f32,
+        blend_space: AnimationNodeIndex,
+    ) -> AnimationNodeIndex {
+        let node_index = self.add_clip(clip, 1.0, blend_space);
+        if let AnimationNodeType::BlendSpace1d(ref mut blend_space) =
+            self.graph[blend_space].node_type
+        {
+            blend_space.points.push((node_index, position));
+        }
+        node_index
+    }
+
+    /// Adds an [`AnimationClip`] with weight 1.0 to a two-dimensional blend
+    /// space node at the given position, and returns its index.
+    ///
+    /// If `blend_space` isn't a [`AnimationNodeType::BlendSpace2d`] node, the
+    /// clip is added as a regular child.
+    pub fn add_clip_to_blend_space_2d(
+        &mut self,
+        clip: Handle<AnimationClip>,
+        position: Vec2,
+        blend_space: AnimationNodeIndex,
+    ) -> AnimationNodeIndex {
+        let node_index = self.add_clip(clip, 1.0, blend_space);
+        if let AnimationNodeType::BlendSpace2d(ref mut blend_space) =
+            self.graph[blend_space].node_type
+        {
+            blend_space.points.push((node_index, position));
+        }
+        node_index
+    }
+
     /// Adds an edge from the edge `from` to `to`, making `to` a child of
     /// `from`.
     ///
@@ -700,6 +1027,30 @@ impl AnimationGraphNode {
     pub fn remove_mask_group(&mut self, group: u32) -> &mut Self {
         self.remove_mask(1 << group)
     }
+
+    /// Sets the [joint mask] of this node.
+    ///
+    /// [joint mask]: Self::joint_mask
+    pub fn set_joint_mask(&mut self, joint_mask: Handle<AnimationJointMask>) -> &mut Self {
+        self.joint_mask = Some(joint_mask);
+        self
+    }
+
+    /// Returns the weight that the [joint mask] of this node assigns to the
+    /// given animation target, or 1.0 if the node has no joint mask or it
+    /// hasn't loaded yet.
+    ///
+    /// [joint mask]: Self::joint_mask
+    pub fn joint_weight(
+        &self,
+        target: AnimationTargetId,
+        joint_masks: &Assets<AnimationJointMask>,
+    ) -> f32 {
+        self.joint_mask
+            .as_ref()
+            .and_then(|joint_mask| joint_masks.get(joint_mask))
+            .map_or(1.0, |joint_mask| joint_mask.weight(target))
+    }
 }
 
 impl Index<AnimationNodeIndex> for AnimationGraph {
@@ -721,6 +1072,7 @@ impl Default for AnimationGraphNode {
         Self {
             node_type: Default::default(),
             mask: 0,
+            joint_mask: None,
             weight: 1.0,
         }
     }
@@ -770,8 +1122,24 @@ impl AssetLoader for AnimationGraphAssetLoader {
                         },
                         SerializedAnimationNodeType::Blend => AnimationNodeType::Blend,
                         SerializedAnimationNodeType::Add => AnimationNodeType::Add,
+                        SerializedAnimationNodeType::BlendSpace1d(ref blend_space) => {
+                            AnimationNodeType::BlendSpace1d(blend_space.clone())
+                        }
+                        SerializedAnimationNodeType::BlendSpace2d(ref blend_space) => {
+                            AnimationNodeType::BlendSpace2d(blend_space.clone())
+                        }
                     },
                     mask: serialized_node.mask,
+                    joint_mask: serialized_node.joint_mask.as_ref().map(|joint_mask| {
+                        match joint_mask {
+                            SerializedAnimationJointMask::AssetId(asset_id) => {
+                                Handle::Weak(*asset_id)
+                            }
+                            SerializedAnimationJointMask::AssetPath(asset_path) => {
+                                load_context.load(asset_path)
+                            }
+                        }
+                    }),
                     weight: serialized_node.weight,
                 },
                 |_, _| (),
@@ -786,6 +1154,31 @@ impl AssetLoader for AnimationGraphAssetLoader {
     }
 }
 
+impl AssetLoader for AnimationJointMaskLoader {
+    type Asset = AnimationJointMask;
+
+    type Settings = ();
+
+    type Error = AnimationGraphLoadError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _: &Self::Settings,
+        _: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let mut deserializer = ron::de::Deserializer::from_bytes(&bytes)?;
+        AnimationJointMask::deserialize(&mut deserializer)
+            .map_err(|err| deserializer.span_error(err).into())
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["jointmask.ron"]
+    }
+}
+
 impl From<AnimationGraph> for SerializedAnimationGraph {
     fn from(animation_graph: AnimationGraph) -> Self {
         // If any of the animation clips have paths, then serialize them as
@@ -796,6 +1189,12 @@ impl From<AnimationGraph> for SerializedAnimationGraph {
                 |_, node| SerializedAnimationGraphNode {
                     weight: node.weight,
                     mask: node.mask,
+                    joint_mask: node.joint_mask.as_ref().map(|joint_mask| {
+                        match joint_mask.path() {
+                            Some(path) => SerializedAnimationJointMask::AssetPath(path.clone()),
+                            None => SerializedAnimationJointMask::AssetId(joint_mask.id()),
+                        }
+                    }),
                     node_type: match node.node_type {
                         AnimationNodeType::Clip(ref clip) => match clip.path() {
                             Some(path) => SerializedAnimationNodeType::Clip(
@@ -807,6 +1206,12 @@ impl From<AnimationGraph> for SerializedAnimationGraph {
                         },
                         AnimationNodeType::Blend => SerializedAnimationNodeType::Blend,
                         AnimationNodeType::Add => SerializedAnimationNodeType::Add,
+                        AnimationNodeType::BlendSpace1d(ref blend_space) => {
+                            SerializedAnimationNodeType::BlendSpace1d(blend_space.clone())
+                        }
+                        AnimationNodeType::BlendSpace2d(ref blend_space) => {
+                            SerializedAnimationNodeType::BlendSpace2d(blend_space.clone())
+                        }
                     },
                 },
                 |_, _| (),
@@ -925,3 +1330,105 @@ impl ThreadedAnimationGraph {
         self.threaded_graph.push(node_index);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy_math::Vec2;
+    use petgraph::graph::NodeIndex;
+
+    use bevy_asset::Handle;
+
+    use super::{
+        AnimationGraph, AnimationJointMask, BlendSpace1d, BlendSpace2d, SerializedAnimationGraph,
+        SerializedAnimationNodeType,
+    };
+    use crate::AnimationTargetId;
+
+    #[test]
+    fn blend_space_1d_weights() {
+        let blend_space = BlendSpace1d {
+            points: vec![
+                (NodeIndex::new(1), 0.0),
+                (NodeIndex::new(2), 3.0),
+                (NodeIndex::new(3), 1.0),
+            ],
+        };
+
+        let weights = blend_space.weights(2.0);
+        assert_eq!(
+            weights.as_slice(),
+            [
+                (NodeIndex::new(1), 0.0),
+                (NodeIndex::new(2), 0.5),
+                (NodeIndex::new(3), 0.5)
+            ]
+        );
+
+        // Parameters outside of the points use the nearest point.
+        let weights = blend_space.weights(-1.0);
+        assert_eq!(weights[0], (NodeIndex::new(1), 1.0));
+        assert_eq!(weights[1].1 + weights[2].1, 0.0);
+    }
+
+    #[test]
+    fn blend_space_2d_weights() {
+        let blend_space = BlendSpace2d {
+            points: vec![
+                (NodeIndex::new(1), Vec2::ZERO),
+                (NodeIndex::new(2), Vec2::X),
+                (NodeIndex::new(3), Vec2::Y),
+            ],
+        };
+
+        // A parameter on a point only plays that point.
+        let weights = blend_space.weights(Vec2::X);
+        assert_eq!(weights[1], (NodeIndex::new(2), 1.0));
+
+        // The weights always add up to 1.
+        let weights = blend_space.weights(Vec2::new(0.25, 0.5));
+        let total: f32 = weights.iter().map(|(_, weight)| weight).sum();
+        assert!((total - 1.0).abs() < 1e-6);
+        assert!(weights.iter().all(|&(_, weight)| weight > 0.0));
+    }
+
+    #[test]
+    fn joint_mask_weights() {
+        let spine = AnimationTargetId::from_iter(["Armature", "Hips", "Spine"]);
+        let leg = AnimationTargetId::from_iter(["Armature", "Hips", "Leg"]);
+        let mask = AnimationJointMask::new(0.0).with_weight(spine, 0.5);
+        assert_eq!(mask.weight(spine), 0.5);
+        assert_eq!(mask.weight(leg), 0.0);
+
+        let mask: AnimationJointMask = ron::from_str(&ron::to_string(&mask).unwrap()).unwrap();
+        assert_eq!(mask.weight(spine), 0.5);
+        assert_eq!(mask.default_weight, 0.0);
+    }
+
+    #[test]
+    fn serialized_graph_layers() {
+        let spine = AnimationTargetId::from_iter(["Armature", "Hips", "Spine"]);
+        let mut graph = AnimationGraph::new();
+        graph.add_target_to_mask_group(spine, 1);
+        let locomotion = graph.add_blend_space_1d(1.0, graph.root);
+        graph.add_clip_to_blend_space_1d(Handle::default(), 0.0, locomotion);
+        graph.add_clip_to_blend_space_1d(Handle::default(), 3.0, locomotion);
+        let layer = graph.add_additive_blend_with_mask(0b10, 0.5, graph.root);
+        graph.add_clip(Handle::default(), 1.0, layer);
+
+        let serialized = ron::to_string(&graph).unwrap();
+        let graph: SerializedAnimationGraph = ron::from_str(&serialized).unwrap();
+        assert_eq!(graph.graph.node_count(), 5);
+        assert_eq!(graph.mask_groups.get(&spine), Some(&0b10));
+
+        let SerializedAnimationNodeType::BlendSpace1d(ref blend_space) =
+            graph.graph[locomotion].node_type
+        else {
+            panic!("expected a blend space node");
+        };
+        assert_eq!(blend_space.points.len(), 2);
+        let layer = &graph.graph[layer];
+        assert!(matches!(layer.node_type, SerializedAnimationNodeType::Add));
+        assert_eq!(layer.mask, 0b10);
+        assert_eq!(layer.weight, 0.5);
+    }
+}
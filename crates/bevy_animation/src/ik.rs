@@ -0,0 +1,405 @@
+//! Inverse kinematics.
+//!
+//! Inverse kinematics (IK) constraints adjust the rotations of a chain of
+//! bones after the animations have been evaluated, so that the end of the
+//! chain reaches a target. This is typically used to plant feet on uneven
+//! terrain or to make hands reach for objects.
+//!
+//! Two solvers are available:
+//!
+//! * [`TwoBoneIkConstraint`] solves chains of exactly two bones, such as legs
+//!   and arms, analytically. A pole entity controls the direction in which the
+//!   middle joint (knee or elbow) bends.
+//!
+//! * [`FabrikConstraint`] solves chains of any length iteratively with the
+//!   FABRIK (forward and backward reaching inverse kinematics) algorithm, which
+//!   suits tails, tentacles, and spines.
+//!
+//! The constraints are solved after animation sampling and root motion
+//! extraction, but before transform propagation, in the
+//! [`Animation`](bevy_app::Animation) system set.
+
+use bevy_ecs::{
+    component::Component,
+    entity::{Entity, VisitEntities, VisitEntitiesMut},
+    reflect::{
+        ReflectComponent, ReflectMapEntities, ReflectVisitEntities, ReflectVisitEntitiesMut,
+    },
+    system::Query,
+};
+use bevy_hierarchy::Parent;
+use bevy_math::{Quat, Vec3};
+use bevy_reflect::Reflect;
+use bevy_transform::components::{GlobalTransform, Transform};
+
+/// The smallest distance that the solvers consider when comparing positions.
+const IK_EPSILON: f32 = 1e-5;
+
+/// Rotates a chain of two bones so that the end of the second bone reaches a
+/// target.
+///
+/// The `chain` lists the joints of the chain from the root to the tip: for a
+/// leg, that's the hip, the knee, and the ankle. Each joint must be a child of
+/// the previous one. The constraint can be placed on any entity, typically the
+/// character itself.
+///
+/// If the target is out of reach, the chain is stretched straight towards it.
+#[derive(Component, Clone, Copy, Debug, Reflect, VisitEntities, VisitEntitiesMut)]
+#[reflect(Component, Debug, MapEntities, VisitEntities, VisitEntitiesMut)]
+pub struct TwoBoneIkConstraint {
+    /// The entity that the tip of the chain reaches for.
+    pub target: Entity,
+    /// An entity that the middle joint bends towards.
+    ///
+    /// If this is `None`, the middle joint keeps bending in the direction in
+    /// which the animation bends it.
+    pub pole: Option<Entity>,
+    /// The root, middle, and tip joints of the chain.
+    pub chain: [Entity; 3],
+    /// How much the constraint overrides the animated pose, from 0.0 (not at
+    /// all) to 1.0 (completely).
+    #[visit_entities(ignore)]
+    pub weight: f32,
+}
+
+impl TwoBoneIkConstraint {
+    /// Creates a constraint that makes the tip of the given chain reach the
+    /// given target, without a pole.
+    pub fn new(chain: [Entity; 3], target: Entity) -> Self {
+        Self {
+            target,
+            pole: None,
+            chain,
+            weight: 1.0,
+        }
+    }
+
+    /// Sets the entity that the middle joint bends towards.
+    pub fn with_pole(mut self, pole: Entity) -> Self {
+        self.pole = Some(pole);
+        self
+    }
+
+    /// Sets how much the constraint overrides the animated pose.
+    pub fn with_weight(mut self, weight: f32) -> Self {
+        self.weight = weight;
+        self
+    }
+}
+
+/// Rotates a chain of bones of any length so that the end of the chain
+/// reaches a target, using the FABRIK algorithm.
+///
+/// The `chain` lists the joints of the chain from the root to the tip. Each
+/// joint must be a child of the previous one. The constraint can be placed on
+/// any entity, typically the character itself.
+#[derive(Component, Clone, Debug, Reflect, VisitEntities, VisitEntitiesMut)]
+#[reflect(Component, Debug, MapEntities, VisitEntities, VisitEntitiesMut)]
+pub struct FabrikConstraint {
+    /// The entity that the tip of the chain reaches for.
+    pub target: Entity,
+    /// The joints of the chain, from the root to the tip.
+    pub chain: Vec<Entity>,
+    /// The maximum number of iterations of the solver per frame.
+    #[visit_entities(ignore)]
+    pub iterations: u32,
+    /// The distance between the tip and the target below which the solver
+    /// stops iterating.
+    #[visit_entities(ignore)]
+    pub tolerance: f32,
+    /// How much the constraint overrides the animated pose, from 0.0 (not at
+    /// all) to 1.0 (completely).
+    #[visit_entities(ignore)]
+    pub weight: f32,
+}
+
+impl FabrikConstraint {
+    /// Creates a constraint that makes the tip of the given chain reach the
+    /// given target, with 10 iterations and a tolerance of 1 millimeter.
+    pub fn new(chain: Vec<Entity>, target: Entity) -> Self {
+        Self {
+            target,
+            chain,
+            iterations: 10,
+            tolerance: 0.001,
+            weight: 1.0,
+        }
+    }
+
+    /// Sets how much the constraint overrides the animated pose.
+    pub fn with_weight(mut self, weight: f32) -> Self {
+        self.weight = weight;
+        self
+    }
+}
+
+/// A system that solves all [`TwoBoneIkConstraint`]s and
+/// [`FabrikConstraint`]s.
+pub fn solve_ik_constraints(
+    two_bone_constraints: Query<&TwoBoneIkConstraint>,
+    fabrik_constraints: Query<&FabrikConstraint>,
+    mut transforms: Query<&mut Transform>,
+    parents: Query<&Parent>,
+) {
+    for constraint in &two_bone_constraints {
+        if constraint.weight <= 0.0 {
+            continue;
+        }
+        let Some(target) = global_transform(constraint.target, &transforms, &parents) else {
+            continue;
+        };
+        let pole = constraint
+            .pole
+            .and_then(|pole| global_transform(pole, &transforms, &parents));
+        let Some(chain) = ChainPose::new(&constraint.chain, &transforms, &parents) else {
+            continue;
+        };
+
+        let solved = solve_two_bone(
+            [chain.positions[0], chain.positions[1], chain.positions[2]],
+            target.translation(),
+            pole.map(|pole| pole.translation()),
+        );
+        chain.apply(&solved, constraint.weight, &mut transforms);
+    }
+
+    for constraint in &fabrik_constraints {
+        if constraint.weight <= 0.0 || constraint.chain.len() < 2 {
+            continue;
+        }
+        let Some(target) = global_transform(constraint.target, &transforms, &parents) else {
+            continue;
+        };
+        let Some(chain) = ChainPose::new(&constraint.chain, &transforms, &parents) else {
+            continue;
+        };
+
+        let mut solved = chain.positions.clone();
+        solve_fabrik(
+            &mut solved,
+            target.translation(),
+            constraint.iterations,
+            constraint.tolerance,
+        );
+        chain.apply(&solved, constraint.weight, &mut transforms);
+    }
+}
+
+/// Computes the up-to-date global transform of an entity from the local
+/// transforms of its ancestors.
+///
+/// [`GlobalTransform`] can't be used, because transform propagation hasn't run
+/// yet for this frame.
+fn global_transform(
+    entity: Entity,
+    transforms: &Query<&mut Transform>,
+    parents: &Query<&Parent>,
+) -> Option<GlobalTransform> {
+    let mut global = GlobalTransform::from(*transforms.get(entity).ok()?);
+    let mut current = entity;
+    while let Ok(parent) = parents.get(current) {
+        current = parent.get();
+        let Ok(transform) = transforms.get(current) else {
+            break;
+        };
+        global = GlobalTransform::from(*transform) * global;
+    }
+    Some(global)
+}
+
+/// The animated pose of a chain of joints.
+struct ChainPose<'a> {
+    entities: &'a [Entity],
+    /// The global rotation of the parent of the root joint.
+    parent_rotation: Quat,
+    /// The global positions of the joints.
+    positions: Vec<Vec3>,
+    /// The global rotations of the joints.
+    rotations: Vec<Quat>,
+}
+
+impl<'a> ChainPose<'a> {
+    fn new(
+        entities: &'a [Entity],
+        transforms: &Query<&mut Transform>,
+        parents: &Query<&Parent>,
+    ) -> Option<Self> {
+        let root_parent = parents
+            .get(entities[0])
+            .ok()
+            .and_then(|parent| global_transform(parent.get(), transforms, parents))
+            .unwrap_or_default();
+
+        let mut positions = Vec::with_capacity(entities.len());
+        let mut rotations = Vec::with_capacity(entities.len());
+        let mut global = root_parent;
+        for &entity in entities {
+            global = global.mul_transform(*transforms.get(entity).ok()?);
+            let (_, rotation, translation) = global.to_scale_rotation_translation();
+            positions.push(translation);
+            rotations.push(rotation);
+        }
+
+        Some(Self {
+            entities,
+            parent_rotation: root_parent.to_scale_rotation_translation().1,
+            positions,
+            rotations,
+        })
+    }
+
+    /// Rotates the joints so that they move to the given positions, blending
+    /// the result with the animated pose by `weight`.
+    fn apply(&self, solved: &[Vec3], weight: f32, transforms: &mut Query<&mut Transform>) {
+        let weight = weight.min(1.0);
+        let mut parent_rotation = self.parent_rotation;
+        for (index, &entity) in self.entities.iter().enumerate() {
+            // The tip of the chain keeps its local rotation.
+            let Some(&solved_child) = solved.get(index + 1) else {
+                break;
+            };
+            let Ok(mut transform) = transforms.get_mut(entity) else {
+                return;
+            };
+
+            // Rotate the bone from the direction it points to in the rotated
+            // parent to the solved direction.
+            let rotation = parent_rotation * transform.rotation;
+            let bone = (rotation * self.rotations[index].inverse())
+                * (self.positions[index + 1] - self.positions[index]);
+            let solved_bone = solved_child - solved[index];
+            let local_rotation = match (bone.try_normalize(), solved_bone.try_normalize()) {
+                (Some(from), Some(to)) => {
+                    parent_rotation.inverse() * Quat::from_rotation_arc(from, to) * rotation
+                }
+                _ => transform.rotation,
+            };
+
+            transform.rotation = transform.rotation.slerp(local_rotation.normalize(), weight);
+            parent_rotation *= transform.rotation;
+        }
+    }
+}
+
+/// Solves a two-bone chain analytically, returning the new positions of the
+/// root, middle, and tip joints.
+fn solve_two_bone(joints: [Vec3; 3], target: Vec3, pole: Option<Vec3>) -> [Vec3; 3] {
+    let [root, middle, tip] = joints;
+    let upper_length = root.distance(middle);
+    let lower_length = middle.distance(tip);
+    if upper_length < IK_EPSILON || lower_length < IK_EPSILON {
+        return joints;
+    }
+
+    let to_target = target - root;
+    let Some(direction) = to_target.try_normalize() else {
+        return joints;
+    };
+    let distance = to_target.length().clamp(
+        (upper_length - lower_length).abs() + IK_EPSILON,
+        upper_length + lower_length - IK_EPSILON,
+    );
+
+    // Bend the middle joint towards the pole, or in the plane that it's
+    // already bent in.
+    let hint = pole.unwrap_or(middle) - root;
+    let bend = (hint - direction * hint.dot(direction))
+        .try_normalize()
+        .unwrap_or_else(|| direction.any_orthonormal_vector());
+
+    // The law of cosines gives the angle between the upper bone and the
+    // direction to the target.
+    let cos_angle = ((upper_length * upper_length + distance * distance
+        - lower_length * lower_length)
+        / (2.0 * upper_length * distance))
+        .clamp(-1.0, 1.0);
+    let sin_angle = (1.0 - cos_angle * cos_angle).sqrt();
+
+    [
+        root,
+        root + upper_length * (direction * cos_angle + bend * sin_angle),
+        root + direction * distance,
+    ]
+}
+
+/// Solves a chain of any length with the FABRIK algorithm, moving the given
+/// joint positions in place.
+fn solve_fabrik(joints: &mut [Vec3], target: Vec3, iterations: u32, tolerance: f32) {
+    let lengths: Vec<f32> = joints
+        .windows(2)
+        .map(|pair| pair[0].distance(pair[1]))
+        .collect();
+    let root = joints[0];
+    let last = joints.len() - 1;
+
+    // If the target is out of reach, stretch the chain towards it.
+    if root.distance(target) >= lengths.iter().sum::<f32>() {
+        let Some(direction) = (target - root).try_normalize() else {
+            return;
+        };
+        for (index, &length) in lengths.iter().enumerate() {
+            joints[index + 1] = joints[index] + direction * length;
+        }
+        return;
+    }
+
+    for _ in 0..iterations {
+        if joints[last].distance(target) <= tolerance {
+            break;
+        }
+
+        // Backward pass: move the tip to the target, and every joint towards
+        // its child.
+        joints[last] = target;
+        for (index, &length) in lengths.iter().enumerate().rev() {
+            let direction = (joints[index] - joints[index + 1]).normalize_or_zero();
+            joints[index] = joints[index + 1] + direction * length;
+        }
+
+        // Forward pass: move the root back to its place, and every joint
+        // towards its parent.
+        joints[0] = root;
+        for (index, &length) in lengths.iter().enumerate() {
+            let direction = (joints[index + 1] - joints[index]).normalize_or_zero();
+            joints[index + 1] = joints[index] + direction * length;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_math::Vec3;
+
+    use super::{solve_fabrik, solve_two_bone};
+
+    #[test]
+    fn two_bone_reaches_target() {
+        let joints = [
+            Vec3::ZERO,
+            Vec3::new(0.0, -1.0, 0.1),
+            Vec3::new(0.0, -2.0, 0.0),
+        ];
+        let target = Vec3::new(0.5, -1.5, 0.0);
+        let [root, middle, tip] = solve_two_bone(joints, target, Some(Vec3::Z));
+
+        assert_eq!(root, Vec3::ZERO);
+        assert!(tip.distance(target) < 1e-4);
+        assert!((root.distance(middle) - joints[0].distance(joints[1])).abs() < 1e-4);
+        assert!((middle.distance(tip) - joints[1].distance(joints[2])).abs() < 1e-4);
+        // The knee bends towards the pole.
+        assert!(middle.z > 0.0);
+    }
+
+    #[test]
+    fn fabrik_preserves_lengths() {
+        let mut joints = [Vec3::ZERO, Vec3::X, Vec3::X * 2.0, Vec3::X * 3.0];
+        let target = Vec3::new(1.0, 1.5, 0.0);
+        solve_fabrik(&mut joints, target, 20, 1e-4);
+
+        assert_eq!(joints[0], Vec3::ZERO);
+        assert!(joints[3].distance(target) < 1e-3);
+        for pair in joints.windows(2) {
+            assert!((pair[0].distance(pair[1]) - 1.0).abs() < 1e-4);
+        }
+    }
+}
@@ -13,6 +13,8 @@ pub mod animatable;
 pub mod animation_curves;
 pub mod gltf_curves;
 pub mod graph;
+pub mod ik;
+pub mod root_motion;
 pub mod transition;
 mod util;
 
@@ -21,7 +23,7 @@ use core::{
     cell::RefCell,
     fmt::Debug,
     hash::{Hash, Hasher},
-    iter, slice,
+    iter,
 };
 use graph::AnimationNodeType;
 use prelude::AnimationCurveEvaluator;
@@ -39,7 +41,7 @@ use bevy_ecs::{
     reflect::{ReflectMapEntities, ReflectVisitEntities, ReflectVisitEntitiesMut},
     world::EntityMutExcept,
 };
-use bevy_math::FloatOrd;
+use bevy_math::{FloatOrd, Vec2};
 use bevy_reflect::{prelude::ReflectDefault, Reflect, TypePath};
 use bevy_time::Time;
 use bevy_transform::TransformSystem;
@@ -56,14 +58,19 @@ use uuid::Uuid;
 pub mod prelude {
     #[doc(hidden)]
     pub use crate::{
-        animatable::*, animation_curves::*, graph::*, transition::*, AnimationClip,
-        AnimationPlayer, AnimationPlugin, VariableCurve,
+        animatable::*, animation_curves::*, graph::*, ik::*, root_motion::*, transition::*,
+        AnimationClip, AnimationPlayer, AnimationPlugin, VariableCurve,
     };
 }
 
 use crate::{
     animation_curves::AnimationCurve,
-    graph::{AnimationGraph, AnimationGraphAssetLoader, AnimationNodeIndex},
+    graph::{
+        AnimationGraph, AnimationGraphAssetLoader, AnimationJointMask, AnimationJointMaskLoader,
+        AnimationNodeIndex,
+    },
+    ik::{solve_ik_constraints, FabrikConstraint, TwoBoneIkConstraint},
+    root_motion::{extract_root_motion, RootMotion},
     transition::{advance_transitions, expire_completed_transitions, AnimationTransitions},
 };
 use alloc::sync::Arc;
@@ -111,6 +118,9 @@ pub struct AnimationClip {
 #[derive(Reflect, Debug, Clone)]
 struct TimedAnimationEvent {
     time: f32,
+    /// The time of the event as a fraction of the clip duration, if it was
+    /// added with a normalized time.
+    normalized_time: Option<f32>,
     event: AnimationEvent,
 }
 
@@ -264,6 +274,7 @@ impl AnimationClip {
     #[inline]
     pub fn set_duration(&mut self, duration_sec: f32) {
         self.duration = duration_sec;
+        self.update_normalized_events();
     }
 
     /// Adds an [`AnimationCurve`] to an [`AnimationTarget`] named by an
@@ -293,8 +304,9 @@ impl AnimationClip {
     ) {
         // Update the duration of the animation by this curve duration if it's longer
         let end = curve.domain().end();
-        if end.is_finite() {
-            self.duration = self.duration.max(end);
+        if end.is_finite() && end > self.duration {
+            self.duration = end;
+            self.update_normalized_events();
         }
         self.curves
             .entry(target_id)
@@ -313,8 +325,9 @@ impl AnimationClip {
         variable_curve: VariableCurve,
     ) {
         let end = variable_curve.0.domain().end();
-        if end.is_finite() {
-            self.duration = self.duration.max(end);
+        if end.is_finite() && end > self.duration {
+            self.duration = end;
+            self.update_normalized_events();
         }
         self.curves
             .entry(target_id)
@@ -378,7 +391,7 @@ impl AnimationClip {
         time: f32,
         func: impl Fn(&mut Commands, Entity, f32, f32) + Send + Sync + 'static,
     ) {
-        self.add_event_internal(AnimationEventTarget::Root, time, func);
+        self.add_event_internal(AnimationEventTarget::Root, time, None, func);
     }
 
     /// Add an event function to an [`AnimationTarget`] named by an [`AnimationTargetId`].
@@ -402,22 +415,112 @@ impl AnimationClip {
         time: f32,
         func: impl Fn(&mut Commands, Entity, f32, f32) + Send + Sync + 'static,
     ) {
-        self.add_event_internal(AnimationEventTarget::Node(target_id), time, func);
+        self.add_event_internal(AnimationEventTarget::Node(target_id), time, None, func);
+    }
+
+    /// Add a untargeted [`Event`] to this [`AnimationClip`], at a time relative
+    /// to the duration of the clip.
+    ///
+    /// The `event` will be cloned and triggered on the [`AnimationPlayer`] entity once the
+    /// `normalized_time` is reached in the animation, where `0.0` is the start and `1.0` is the
+    /// end of the clip. The event keeps its relative position if the duration of the clip changes.
+    ///
+    /// See also [`add_event`](Self::add_event).
+    pub fn add_normalized_event(&mut self, normalized_time: f32, event: impl Event + Clone) {
+        self.add_normalized_event_fn(
+            normalized_time,
+            move |commands: &mut Commands, entity: Entity, _time: f32, _weight: f32| {
+                commands.entity(entity).trigger(event.clone());
+            },
+        );
+    }
+
+    /// Add an [`Event`] to an [`AnimationTarget`] named by an [`AnimationTargetId`], at a time
+    /// relative to the duration of the clip.
+    ///
+    /// See [`add_normalized_event`](Self::add_normalized_event) and
+    /// [`add_event_to_target`](Self::add_event_to_target) for details.
+    pub fn add_normalized_event_to_target(
+        &mut self,
+        target_id: AnimationTargetId,
+        normalized_time: f32,
+        event: impl Event + Clone,
+    ) {
+        self.add_normalized_event_fn_to_target(
+            target_id,
+            normalized_time,
+            move |commands: &mut Commands, entity: Entity, _time: f32, _weight: f32| {
+                commands.entity(entity).trigger(event.clone());
+            },
+        );
+    }
+
+    /// Add a untargeted event function to this [`AnimationClip`], at a time relative to the
+    /// duration of the clip.
+    ///
+    /// The `func` receives the time of the event in seconds.
+    ///
+    /// ```
+    /// # use bevy_animation::AnimationClip;
+    /// # let mut clip = AnimationClip::default();
+    /// # clip.set_duration(2.0);
+    /// // Triggered halfway through the clip.
+    /// clip.add_normalized_event_fn(0.5, |commands, entity, time, weight| {
+    ///   println!("Animation Event Triggered {entity:#?} at time {time} with weight {weight}");
+    /// })
+    /// ```
+    pub fn add_normalized_event_fn(
+        &mut self,
+        normalized_time: f32,
+        func: impl Fn(&mut Commands, Entity, f32, f32) + Send + Sync + 'static,
+    ) {
+        let normalized_time = normalized_time.clamp(0.0, 1.0);
+        self.add_event_internal(
+            AnimationEventTarget::Root,
+            normalized_time * self.duration,
+            Some(normalized_time),
+            func,
+        );
+    }
+
+    /// Add an event function to an [`AnimationTarget`] named by an [`AnimationTargetId`], at a
+    /// time relative to the duration of the clip.
+    ///
+    /// See [`add_normalized_event_fn`](Self::add_normalized_event_fn) and
+    /// [`add_event_fn_to_target`](Self::add_event_fn_to_target) for details.
+    pub fn add_normalized_event_fn_to_target(
+        &mut self,
+        target_id: AnimationTargetId,
+        normalized_time: f32,
+        func: impl Fn(&mut Commands, Entity, f32, f32) + Send + Sync + 'static,
+    ) {
+        let normalized_time = normalized_time.clamp(0.0, 1.0);
+        self.add_event_internal(
+            AnimationEventTarget::Node(target_id),
+            normalized_time * self.duration,
+            Some(normalized_time),
+            func,
+        );
     }
 
     fn add_event_internal(
         &mut self,
         target: AnimationEventTarget,
         time: f32,
+        normalized_time: Option<f32>,
         trigger_fn: impl Fn(&mut Commands, Entity, f32, f32) + Send + Sync + 'static,
     ) {
-        self.duration = self.duration.max(time);
+        if time > self.duration {
+            self.duration = time;
+            self.update_normalized_events();
+        }
         let triggers = self.events.entry(target).or_default();
         match triggers.binary_search_by_key(&FloatOrd(time), |e| FloatOrd(e.time)) {
             Ok(index) | Err(index) => triggers.insert(
                 index,
                 TimedAnimationEvent {
                     time,
+                    normalized_time,
                     event: AnimationEvent {
                         trigger: AnimationEventFn(Arc::new(trigger_fn)),
                     },
@@ -425,6 +528,22 @@ impl AnimationClip {
             ),
         }
     }
+
+    /// Moves the events with a normalized time to match the duration of the clip.
+    fn update_normalized_events(&mut self) {
+        for events in self.events.values_mut() {
+            let mut moved = false;
+            for event in events.iter_mut() {
+                if let Some(normalized_time) = event.normalized_time {
+                    event.time = normalized_time * self.duration;
+                    moved = true;
+                }
+            }
+            if moved {
+                events.sort_by_key(|event| FloatOrd(event.time));
+            }
+        }
+    }
 }
 
 /// Repetition behavior of an animation.
@@ -486,6 +605,10 @@ pub struct ActiveAnimation {
     completions: u32,
     /// `true` if the animation was completed at least once this tick.
     just_completed: bool,
+    /// The number of times the animation was played in its entirety this tick,
+    /// in addition to the loop that was completed, because the tick was longer
+    /// than the clip.
+    skipped_loops: u32,
     paused: bool,
 }
 
@@ -500,6 +623,7 @@ impl Default for ActiveAnimation {
             last_seek_time: None,
             completions: 0,
             just_completed: false,
+            skipped_loops: 0,
             paused: false,
         }
     }
@@ -522,6 +646,7 @@ impl ActiveAnimation {
     #[inline]
     fn update(&mut self, delta: f32, clip_duration: f32) {
         self.just_completed = false;
+        self.skipped_loops = 0;
         self.last_seek_time = Some(self.seek_time);
 
         if self.is_finished() {
@@ -535,8 +660,24 @@ impl ActiveAnimation {
         let under_time = self.speed < 0.0 && self.seek_time < 0.0;
 
         if over_time || under_time {
+            // The tick may be long enough to pass the end of the clip several times.
+            let loops = if clip_duration <= 0.0 {
+                1
+            } else if over_time {
+                (self.seek_time / clip_duration).floor() as u32
+            } else {
+                (-self.seek_time / clip_duration).ceil() as u32
+            };
+            let remaining = match self.repeat {
+                RepeatAnimation::Forever => u32::MAX,
+                RepeatAnimation::Never => 1u32.saturating_sub(self.completions),
+                RepeatAnimation::Count(n) => n.saturating_sub(self.completions),
+            };
+            let loops = loops.clamp(1, remaining.max(1));
+
             self.just_completed = true;
-            self.completions += 1;
+            self.completions = self.completions.saturating_add(loops);
+            self.skipped_loops = loops - 1;
 
             if self.is_finished() {
                 return;
@@ -545,15 +686,15 @@ impl ActiveAnimation {
         if self.seek_time >= clip_duration {
             self.seek_time %= clip_duration;
         }
-        // Note: assumes delta is never lower than -clip_duration
         if self.seek_time < 0.0 {
-            self.seek_time += clip_duration;
+            self.seek_time += clip_duration * (-self.seek_time / clip_duration).ceil();
         }
     }
 
     /// Reset back to the initial state as if no time has elapsed.
     pub fn replay(&mut self) {
         self.just_completed = false;
+        self.skipped_loops = 0;
         self.completions = 0;
         self.elapsed = 0.0;
         self.last_seek_time = None;
@@ -681,7 +822,10 @@ impl ActiveAnimation {
 #[reflect(Component, Default)]
 pub struct AnimationPlayer {
     active_animations: HashMap<AnimationNodeIndex, ActiveAnimation>,
+    /// The weights that blend space nodes assign to their children, which are
+    /// recomputed every frame from the blend space parameters.
     blend_weights: HashMap<AnimationNodeIndex, f32>,
+    blend_space_parameters: HashMap<AnimationNodeIndex, Vec2>,
 }
 
 // This is needed since `#[derive(Clone)]` does not generate optimized `clone_from`.
@@ -690,12 +834,15 @@ impl Clone for AnimationPlayer {
         Self {
             active_animations: self.active_animations.clone(),
             blend_weights: self.blend_weights.clone(),
+            blend_space_parameters: self.blend_space_parameters.clone(),
         }
     }
 
     fn clone_from(&mut self, source: &Self) {
         self.active_animations.clone_from(&source.active_animations);
         self.blend_weights.clone_from(&source.blend_weights);
+        self.blend_space_parameters
+            .clone_from(&source.blend_space_parameters);
     }
 }
 
@@ -936,6 +1083,39 @@ impl AnimationPlayer {
     pub fn animation_mut(&mut self, animation: AnimationNodeIndex) -> Option<&mut ActiveAnimation> {
         self.active_animations.get_mut(&animation)
     }
+
+    /// Sets the parameter of the given blend space node, which determines the
+    /// weights of its children.
+    ///
+    /// One-dimensional blend spaces only use the `x` component of the
+    /// parameter. Blend spaces whose parameter was never set use zero.
+    pub fn set_blend_space_parameter(
+        &mut self,
+        blend_space: AnimationNodeIndex,
+        parameter: impl Into<Vec2>,
+    ) -> &mut Self {
+        self.blend_space_parameters
+            .insert(blend_space, parameter.into());
+        self
+    }
+
+    /// Returns the parameter of the given blend space node.
+    pub fn blend_space_parameter(&self, blend_space: AnimationNodeIndex) -> Vec2 {
+        self.blend_space_parameters
+            .get(&blend_space)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the weight that the parent blend space node of the given node
+    /// assigned to it this frame.
+    ///
+    /// Children of a blend space that aren't among its points have a weight of
+    /// zero, and nodes that aren't children of a blend space have a weight of
+    /// one.
+    pub fn blend_weight(&self, node: AnimationNodeIndex) -> f32 {
+        self.blend_weights.get(&node).copied().unwrap_or(1.0)
+    }
 }
 
 /// A system that triggers untargeted animation events for the currently-playing animations.
@@ -960,7 +1140,10 @@ fn trigger_untargeted_animation_events(
                 .get(*index)
                 .and_then(|node| match &node.node_type {
                     AnimationNodeType::Clip(handle) => Some(handle),
-                    AnimationNodeType::Blend | AnimationNodeType::Add => None,
+                    AnimationNodeType::Blend
+                    | AnimationNodeType::Add
+                    | AnimationNodeType::BlendSpace1d(_)
+                    | AnimationNodeType::BlendSpace2d(_) => None,
                 })
                 .and_then(|id| clips.get(id))
             else {
@@ -973,7 +1156,7 @@ fn trigger_untargeted_animation_events(
                 continue;
             };
 
-            for TimedAnimationEvent { time, event } in triggered_events.iter() {
+            for TimedAnimationEvent { time, event, .. } in triggered_events.iter() {
                 event.trigger(&mut commands, entity, *time, active_animation.weight);
             }
         }
@@ -999,12 +1182,43 @@ pub fn advance_animations(
 
             let AnimationPlayer {
                 ref mut active_animations,
-                ..
+                ref mut blend_weights,
+                ref blend_space_parameters,
             } = *player;
 
+            blend_weights.clear();
+
             for node_index in animation_graph.graph.node_indices() {
                 let node = &animation_graph[node_index];
 
+                // Weight the children of blend spaces by the parameter.
+                let parameter = || {
+                    blend_space_parameters
+                        .get(&node_index)
+                        .copied()
+                        .unwrap_or_default()
+                };
+                let weights = match node.node_type {
+                    AnimationNodeType::BlendSpace1d(ref blend_space) => {
+                        Some(blend_space.weights(parameter().x))
+                    }
+                    AnimationNodeType::BlendSpace2d(ref blend_space) => {
+                        Some(blend_space.weights(parameter()))
+                    }
+                    _ => None,
+                };
+                if let Some(weights) = weights {
+                    // Children that aren't points of the blend space have a
+                    // weight of zero.
+                    blend_weights.extend(
+                        animation_graph
+                            .graph
+                            .neighbors(node_index)
+                            .map(|child| (child, 0.0)),
+                    );
+                    blend_weights.extend(weights);
+                }
+
                 if let Some(active_animation) = active_animations.get_mut(&node_index) {
                     // Tick the animation if necessary.
                     if !active_animation.paused {
@@ -1029,6 +1243,7 @@ pub fn animate_targets(
     par_commands: ParallelCommands,
     clips: Res<Assets<AnimationClip>>,
     graphs: Res<Assets<AnimationGraph>>,
+    joint_masks: Res<Assets<AnimationJointMask>>,
     threaded_animation_graphs: Res<ThreadedAnimationGraphs>,
     players: Query<(&AnimationPlayer, &AnimationGraphHandle)>,
     mut targets: Query<(Entity, &AnimationTarget, AnimationEntityMut)>,
@@ -1086,7 +1301,9 @@ pub fn animate_targets(
                 };
 
                 match animation_graph_node.node_type {
-                    AnimationNodeType::Blend => {
+                    AnimationNodeType::Blend
+                    | AnimationNodeType::BlendSpace1d(_)
+                    | AnimationNodeType::BlendSpace2d(_) => {
                         // This is a blend node.
                         for edge_index in threaded_animation_graph.sorted_edge_ranges
                             [animation_graph_node_index.index()]
@@ -1100,7 +1317,9 @@ pub fn animate_targets(
                         }
 
                         if let Err(err) = evaluation_state.push_blend_register_all(
-                            animation_graph_node.weight,
+                            animation_graph_node.weight
+                                * animation_player.blend_weight(animation_graph_node_index)
+                                * animation_graph_node.joint_weight(target_id, &joint_masks),
                             animation_graph_node_index,
                         ) {
                             warn!("Animation blending failed: {:?}", err);
@@ -1121,7 +1340,9 @@ pub fn animate_targets(
                         }
 
                         if let Err(err) = evaluation_state.push_blend_register_all(
-                            animation_graph_node.weight,
+                            animation_graph_node.weight
+                                * animation_player.blend_weight(animation_graph_node_index)
+                                * animation_graph_node.joint_weight(target_id, &joint_masks),
                             animation_graph_node_index,
                         ) {
                             warn!("Animation blending failed: {:?}", err);
@@ -1139,7 +1360,10 @@ pub fn animate_targets(
 
                         // If the weight is zero or the current animation target is
                         // masked out, stop here.
+                        let joint_weight =
+                            animation_graph_node.joint_weight(target_id, &joint_masks);
                         if active_animation.weight == 0.0
+                            || joint_weight == 0.0
                             || (target_mask
                                 & threaded_animation_graph.computed_masks
                                     [animation_graph_node_index.index()])
@@ -1161,7 +1385,7 @@ pub fn animate_targets(
                             ) {
                                 if !triggered_events.is_empty() {
                                     par_commands.command_scope(move |mut commands| {
-                                        for TimedAnimationEvent { time, event } in
+                                        for TimedAnimationEvent { time, event, .. } in
                                             triggered_events.iter()
                                         {
                                             event.trigger(
@@ -1180,7 +1404,10 @@ pub fn animate_targets(
                             continue;
                         };
 
-                        let weight = active_animation.weight * animation_graph_node.weight;
+                        let weight = active_animation.weight
+                            * animation_graph_node.weight
+                            * animation_player.blend_weight(animation_graph_node_index)
+                            * joint_weight;
                         let seek_time = active_animation.seek_time;
 
                         for curve in curves {
@@ -1230,12 +1457,18 @@ impl Plugin for AnimationPlugin {
     fn build(&self, app: &mut App) {
         app.init_asset::<AnimationClip>()
             .init_asset::<AnimationGraph>()
+            .init_asset::<AnimationJointMask>()
             .init_asset_loader::<AnimationGraphAssetLoader>()
+            .init_asset_loader::<AnimationJointMaskLoader>()
             .register_asset_reflect::<AnimationClip>()
             .register_asset_reflect::<AnimationGraph>()
+            .register_asset_reflect::<AnimationJointMask>()
             .register_type::<AnimationPlayer>()
             .register_type::<AnimationTarget>()
             .register_type::<AnimationTransitions>()
+            .register_type::<RootMotion>()
+            .register_type::<TwoBoneIkConstraint>()
+            .register_type::<FabrikConstraint>()
             .register_type::<AnimationGraphHandle>()
             .register_type::<NodeIndex>()
             .register_type::<ThreadedAnimationGraphs>()
@@ -1255,6 +1488,8 @@ impl Plugin for AnimationPlugin {
                     animate_targets
                         .before(bevy_render::mesh::inherit_weights)
                         .ambiguous_with_all(),
+                    extract_root_motion,
+                    solve_ik_constraints,
                     trigger_untargeted_animation_events,
                     expire_completed_transitions,
                 )
@@ -1381,9 +1616,20 @@ impl AnimationEvaluationState {
 /// All the events from an [`AnimationClip`] that occurred this tick.
 #[derive(Debug, Clone)]
 struct TriggeredEvents<'a> {
-    direction: TriggeredEventsDir,
-    lower: &'a [TimedAnimationEvent],
-    upper: &'a [TimedAnimationEvent],
+    /// Whether the animation is playing in reverse, in which case every slice
+    /// of events is iterated in reverse.
+    reverse: bool,
+    /// The events between the previous time and the end of the clip (or the
+    /// start, if the animation is playing in reverse), or the current time if
+    /// the animation didn't loop.
+    before_loop: &'a [TimedAnimationEvent],
+    /// All the events of the clip, which occurred `loops` times because the
+    /// tick was longer than the clip.
+    full_loop: &'a [TimedAnimationEvent],
+    loops: u32,
+    /// The events between the start of the clip (or the end, if the animation
+    /// is playing in reverse) and the current time, if the animation looped.
+    after_loop: &'a [TimedAnimationEvent],
 }
 
 impl<'a> TriggeredEvents<'a> {
@@ -1403,19 +1649,13 @@ impl<'a> TriggeredEvents<'a> {
 
         // The animation completed this tick, while still playing.
         let looping = active_animation.just_completed && !is_finished;
-        let direction = match (reverse, looping) {
-            (false, false) => TriggeredEventsDir::Forward,
-            (false, true) => TriggeredEventsDir::ForwardLooping,
-            (true, false) => TriggeredEventsDir::Reverse,
-            (true, true) => TriggeredEventsDir::ReverseLooping,
-        };
 
         let last_time = active_animation.last_seek_time?;
         let this_time = active_animation.seek_time;
 
-        let (lower, upper) = match direction {
+        let (before_loop, after_loop) = match (reverse, looping) {
             // Return all events where last_time <= event.time < this_time.
-            TriggeredEventsDir::Forward => {
+            (false, false) => {
                 let start = events.partition_point(|event| event.time < last_time);
                 // The animation finished this tick, return any remaining events.
                 if is_finished {
@@ -1426,7 +1666,7 @@ impl<'a> TriggeredEvents<'a> {
                 }
             }
             // Return all events where this_time < event.time <= last_time.
-            TriggeredEventsDir::Reverse => {
+            (true, false) => {
                 let end = events.partition_point(|event| event.time <= last_time);
                 // The animation finished, return any remaining events.
                 if is_finished {
@@ -1438,92 +1678,51 @@ impl<'a> TriggeredEvents<'a> {
             }
             // The animation is looping this tick and we have to return events where
             // either last_tick <= event.time or event.time < this_tick.
-            TriggeredEventsDir::ForwardLooping => {
+            (false, true) => {
                 let upper_start = events.partition_point(|event| event.time < last_time);
                 let lower_end = events.partition_point(|event| event.time < this_time);
-
-                let upper = &events[upper_start..];
-                let lower = &events[..lower_end];
-                (lower, upper)
+                (&events[upper_start..], &events[..lower_end])
             }
             // The animation is looping this tick and we have to return events where
             // either last_tick >= event.time or event.time > this_tick.
-            TriggeredEventsDir::ReverseLooping => {
+            (true, true) => {
                 let lower_end = events.partition_point(|event| event.time <= last_time);
                 let upper_start = events.partition_point(|event| event.time <= this_time);
-
-                let upper = &events[upper_start..];
-                let lower = &events[..lower_end];
-                (lower, upper)
+                (&events[..lower_end], &events[upper_start..])
             }
         };
         Some(Self {
-            direction,
-            lower,
-            upper,
+            reverse,
+            before_loop,
+            full_loop: events,
+            loops: active_animation.skipped_loops,
+            after_loop,
         })
     }
 
     fn is_empty(&self) -> bool {
-        self.lower.is_empty() && self.upper.is_empty()
-    }
-
-    fn iter(&self) -> TriggeredEventsIter {
-        match self.direction {
-            TriggeredEventsDir::Forward => TriggeredEventsIter::Forward(self.lower.iter()),
-            TriggeredEventsDir::Reverse => TriggeredEventsIter::Reverse(self.lower.iter().rev()),
-            TriggeredEventsDir::ForwardLooping => TriggeredEventsIter::ForwardLooping {
-                upper: self.upper.iter(),
-                lower: self.lower.iter(),
-            },
-            TriggeredEventsDir::ReverseLooping => TriggeredEventsIter::ReverseLooping {
-                lower: self.lower.iter().rev(),
-                upper: self.upper.iter().rev(),
-            },
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy)]
-enum TriggeredEventsDir {
-    /// The animation is playing normally
-    Forward,
-    /// The animation is playing in reverse
-    Reverse,
-    /// The animation is looping this tick
-    ForwardLooping,
-    /// The animation playing in reverse and looping this tick
-    ReverseLooping,
-}
-
-#[derive(Debug, Clone)]
-enum TriggeredEventsIter<'a> {
-    Forward(slice::Iter<'a, TimedAnimationEvent>),
-    Reverse(iter::Rev<slice::Iter<'a, TimedAnimationEvent>>),
-    ForwardLooping {
-        upper: slice::Iter<'a, TimedAnimationEvent>,
-        lower: slice::Iter<'a, TimedAnimationEvent>,
-    },
-    ReverseLooping {
-        lower: iter::Rev<slice::Iter<'a, TimedAnimationEvent>>,
-        upper: iter::Rev<slice::Iter<'a, TimedAnimationEvent>>,
-    },
-}
-
-impl<'a> Iterator for TriggeredEventsIter<'a> {
-    type Item = &'a TimedAnimationEvent;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        match self {
-            TriggeredEventsIter::Forward(iter) => iter.next(),
-            TriggeredEventsIter::Reverse(rev) => rev.next(),
-            TriggeredEventsIter::ForwardLooping { upper, lower } => {
-                upper.next().or_else(|| lower.next())
-            }
-            TriggeredEventsIter::ReverseLooping { lower, upper } => {
-                lower.next().or_else(|| upper.next())
-            }
-        }
+        self.before_loop.is_empty()
+            && (self.loops == 0 || self.full_loop.is_empty())
+            && self.after_loop.is_empty()
+    }
+
+    /// Iterates over the events in the order in which they occurred.
+    fn iter(&self) -> impl Iterator<Item = &'a TimedAnimationEvent> + 'a {
+        let reverse = self.reverse;
+        iter::once(self.before_loop)
+            .chain(iter::repeat(self.full_loop).take(self.loops as usize))
+            .chain(iter::once(self.after_loop))
+            .flat_map(move |events| {
+                let (forward, backward) = if reverse {
+                    (None, Some(events.iter().rev()))
+                } else {
+                    (Some(events.iter()), None)
+                };
+                forward
+                    .into_iter()
+                    .flatten()
+                    .chain(backward.into_iter().flatten())
+            })
     }
 }
 
@@ -1660,4 +1859,57 @@ mod tests {
         active_animation.update(clip.duration, clip.duration); // 0.3 : 0.0
         assert_triggered_events_with(&active_animation, &clip, [0.3, 0.2]);
     }
+
+    #[test]
+    fn test_events_triggers_large_delta() {
+        let mut active_animation = ActiveAnimation {
+            repeat: RepeatAnimation::Forever,
+            ..Default::default()
+        };
+        let mut clip = AnimationClip::default();
+        clip.add_event(0.1, A);
+        clip.add_event(0.3, A);
+        assert_eq!(0.3, clip.duration);
+
+        active_animation.update(0.2, clip.duration); // 0.0 : 0.2
+        assert_triggered_events_with(&active_animation, &clip, [0.1]);
+        active_animation.update(0.75, clip.duration); // 0.2 : 0.05, looping 3 times
+        assert_triggered_events_with(&active_animation, &clip, [0.3, 0.1, 0.3, 0.1, 0.3]);
+        assert_eq!(3, active_animation.completions());
+
+        active_animation.speed = -1.0;
+        active_animation.update(0.5, clip.duration); // 0.05 : 0.15, looping twice
+        assert_triggered_events_with(&active_animation, &clip, [0.3, 0.1, 0.3]);
+
+        // The animation stops at the last allowed completion.
+        let mut active_animation = ActiveAnimation {
+            repeat: RepeatAnimation::Count(2),
+            ..Default::default()
+        };
+        active_animation.update(2.0, clip.duration);
+        assert!(active_animation.is_finished());
+        assert_eq!(2, active_animation.completions());
+        assert_triggered_events_with(&active_animation, &clip, [0.1, 0.3, 0.1, 0.3]);
+    }
+
+    #[test]
+    fn test_normalized_events() {
+        let mut clip = AnimationClip::default();
+        clip.set_duration(2.0);
+        clip.add_normalized_event(0.5, A);
+        clip.add_event(1.5, A);
+        assert_eq!(2.0, clip.duration);
+
+        let mut active_animation = ActiveAnimation::default();
+        active_animation.update(1.2, clip.duration); // 0.0 : 1.2
+        assert_triggered_events_with(&active_animation, &clip, [1.0]);
+
+        // Normalized events follow the duration of the clip.
+        clip.set_duration(4.0);
+        let mut active_animation = ActiveAnimation::default();
+        active_animation.update(1.8, clip.duration); // 0.0 : 1.8
+        assert_triggered_events_with(&active_animation, &clip, [1.5]);
+        active_animation.update(0.4, clip.duration); // 1.8 : 2.2
+        assert_triggered_events_with(&active_animation, &clip, [2.0]);
+    }
 }
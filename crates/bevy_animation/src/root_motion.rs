@@ -0,0 +1,398 @@
+//! Root motion extraction.
+//!
+//! Many animation clips move the character as a whole by animating its root
+//! bone: a walk cycle moves the hips forward, and a turn animation rotates
+//! them. Playing such a clip on its own moves the mesh away from the entity
+//! that represents the character, which then has to be moved separately.
+//! Root motion extraction strips this motion from the root bone and hands it
+//! to gameplay code instead, which can apply it to the character's
+//! [`Transform`] or drive a physics body with it.
+
+use bevy_ecs::{
+    component::Component,
+    entity::Entity,
+    reflect::ReflectComponent,
+    system::{Query, Res},
+};
+use bevy_math::{Quat, Vec3};
+use bevy_reflect::Reflect;
+use bevy_time::Time;
+use bevy_transform::components::Transform;
+
+use crate::{AnimationPlayer, AnimationTarget, AnimationTargetId};
+
+/// Extracts the motion of a root bone from the animations played by an
+/// [`AnimationPlayer`].
+///
+/// To use this component, place it on the same entity as the
+/// [`AnimationPlayer`], and set [`RootMotion::target`] to the ID of the root
+/// bone. Every frame, after the animations have been evaluated, the motion of
+/// the root bone since the previous frame is removed from its [`Transform`]
+/// and stored in [`RootMotion::translation`] and [`RootMotion::rotation`]. The
+/// bone itself stays in place, at the pose it had when root motion extraction
+/// started, except for the parts of its motion that aren't extracted.
+///
+/// A gameplay system that runs after the [`Animation`](bevy_app::Animation)
+/// system set, or in the following frame, can then apply the motion to the
+/// character, either with [`RootMotion::apply`] or, for a physics body, by
+/// turning [`RootMotion::velocity`] into a velocity.
+///
+/// The motion is expressed in the space of the root bone's parent, which is
+/// assumed to be the space of the character. If the root bone's parent has a
+/// rotation or scale relative to the character, the motion has to be
+/// transformed by it before being applied.
+///
+/// Note that the motion of frames in which one of the player's animations
+/// loops or completes is discarded, because the root bone jumps back to the
+/// start of the clip. Call [`RootMotion::reset`] after jumping to another time
+/// in a clip for the same reason.
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component)]
+pub struct RootMotion {
+    /// The ID of the root bone whose motion is extracted.
+    pub target: AnimationTargetId,
+    /// Whether the horizontal translation of the root bone is extracted.
+    ///
+    /// This is `true` by default.
+    pub extract_translation: bool,
+    /// Whether the vertical translation of the root bone is extracted, in
+    /// addition to its horizontal translation.
+    ///
+    /// This is `false` by default, so that jumps and crouches stay part of
+    /// the animation.
+    pub extract_vertical_translation: bool,
+    /// Whether the rotation of the root bone around the vertical axis is
+    /// extracted.
+    ///
+    /// This is `true` by default. Rotations around the other axes always stay
+    /// part of the animation.
+    pub extract_rotation: bool,
+    /// The translation of the root bone during the last frame, relative to the
+    /// heading of the character at the start of the frame.
+    pub translation: Vec3,
+    /// The rotation of the root bone around the vertical axis during the last
+    /// frame.
+    pub rotation: Quat,
+    /// The length of the last frame, in seconds.
+    delta_secs: f32,
+    /// The entity of the root bone, once found.
+    #[reflect(ignore)]
+    bone: Option<Entity>,
+    /// The pose of the root bone when root motion extraction started.
+    #[reflect(ignore)]
+    reference: Option<Transform>,
+    /// The animated pose of the root bone in the previous frame, before its
+    /// motion was extracted.
+    #[reflect(ignore)]
+    previous: Option<Transform>,
+}
+
+impl RootMotion {
+    /// Creates a new [`RootMotion`] component that extracts the horizontal
+    /// translation and the heading of the given root bone.
+    pub fn new(target: AnimationTargetId) -> Self {
+        Self {
+            target,
+            extract_translation: true,
+            extract_vertical_translation: false,
+            extract_rotation: true,
+            translation: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+            delta_secs: 0.0,
+            bone: None,
+            reference: None,
+            previous: None,
+        }
+    }
+
+    /// Sets whether the translation of the root bone is extracted.
+    pub fn with_translation(mut self, horizontal: bool, vertical: bool) -> Self {
+        self.extract_translation = horizontal;
+        self.extract_vertical_translation = vertical;
+        self
+    }
+
+    /// Sets whether the rotation of the root bone around the vertical axis is
+    /// extracted.
+    pub fn with_rotation(mut self, rotation: bool) -> Self {
+        self.extract_rotation = rotation;
+        self
+    }
+
+    /// Clears the motion of the last frame and discards the animated pose of
+    /// the previous frame, so that no motion is extracted in the next frame
+    /// either.
+    ///
+    /// Call this after seeking within an animation, or after starting an
+    /// animation that doesn't continue from the current pose.
+    pub fn reset(&mut self) {
+        self.translation = Vec3::ZERO;
+        self.rotation = Quat::IDENTITY;
+        self.previous = None;
+    }
+
+    /// Moves and rotates the given transform by the motion of the last frame.
+    ///
+    /// The translation is applied in the local space of the transform, so that
+    /// a character walking forward in its animation walks in the direction
+    /// it's facing.
+    pub fn apply(&self, transform: &mut Transform) {
+        transform.translation += transform.rotation * self.translation;
+        transform.rotation = (transform.rotation * self.rotation).normalize();
+    }
+
+    /// Returns the average linear velocity of the root bone during the last
+    /// frame, in units per second, relative to the heading of the character.
+    pub fn velocity(&self) -> Vec3 {
+        if self.delta_secs > 0.0 {
+            self.translation / self.delta_secs
+        } else {
+            Vec3::ZERO
+        }
+    }
+
+    /// Returns the average angular velocity of the root bone around the
+    /// vertical axis during the last frame, in radians per second.
+    pub fn angular_velocity(&self) -> f32 {
+        if self.delta_secs > 0.0 {
+            heading_angle(self.rotation) / self.delta_secs
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns the part of the bone's rotation, relative to its reference
+    /// pose, that is extracted.
+    fn heading(&self, rotation: Quat, reference: &Transform) -> Quat {
+        if self.extract_rotation {
+            vertical_twist(rotation * reference.rotation.inverse())
+        } else {
+            Quat::IDENTITY
+        }
+    }
+
+    /// Returns the part of the bone's translation, relative to its reference
+    /// pose, that is extracted.
+    fn extracted_offset(&self, offset: Vec3) -> Vec3 {
+        match (self.extract_translation, self.extract_vertical_translation) {
+            (false, _) => Vec3::ZERO,
+            (true, false) => offset.with_y(0.0),
+            (true, true) => offset,
+        }
+    }
+}
+
+/// Returns the rotation around the Y axis that the given rotation contains,
+/// using a swing-twist decomposition.
+fn vertical_twist(rotation: Quat) -> Quat {
+    let twist = Quat::from_xyzw(0.0, rotation.y, 0.0, rotation.w);
+    if twist.length_squared() < f32::EPSILON {
+        Quat::IDENTITY
+    } else {
+        twist.normalize()
+    }
+}
+
+/// Returns the angle of a rotation around the Y axis.
+fn heading_angle(rotation: Quat) -> f32 {
+    2.0 * rotation.y.atan2(rotation.w)
+}
+
+/// A system that extracts the motion of root bones into [`RootMotion`]
+/// components.
+pub fn extract_root_motion(
+    mut players: Query<(Entity, &AnimationPlayer, &mut RootMotion)>,
+    mut bones: Query<(Entity, &AnimationTarget, &mut Transform)>,
+    time: Res<Time>,
+) {
+    for (player_entity, player, mut root_motion) in &mut players {
+        let root_motion = &mut *root_motion;
+        root_motion.translation = Vec3::ZERO;
+        root_motion.rotation = Quat::IDENTITY;
+        root_motion.delta_secs = time.delta_secs();
+
+        let target_id = root_motion.target;
+        let is_root_bone =
+            |target: &AnimationTarget| target.player == player_entity && target.id == target_id;
+        let cached_bone = root_motion.bone.filter(|&bone| {
+            bones
+                .get(bone)
+                .is_ok_and(|(_, target, _)| is_root_bone(target))
+        });
+        if cached_bone.is_none() {
+            root_motion.reference = None;
+            root_motion.previous = None;
+        }
+        root_motion.bone = cached_bone.or_else(|| {
+            bones
+                .iter()
+                .find(|(_, target, _)| is_root_bone(target))
+                .map(|(bone, _, _)| bone)
+        });
+        let Some(Ok((_, _, mut transform))) = root_motion.bone.map(|bone| bones.get_mut(bone))
+        else {
+            continue;
+        };
+
+        // If nothing animated the bone this frame, it stays at its extracted
+        // pose, and there's no motion to extract.
+        if root_motion.reference.is_some() && !transform.is_changed() {
+            root_motion.previous = None;
+            continue;
+        }
+
+        let current = *transform;
+        let reference = *root_motion.reference.get_or_insert(current);
+
+        let discontinuous = player
+            .playing_animations()
+            .any(|(_, animation)| animation.just_completed);
+        if let Some(previous) = root_motion.previous.filter(|_| !discontinuous) {
+            let previous_heading = root_motion.heading(previous.rotation, &reference);
+            let current_heading = root_motion.heading(current.rotation, &reference);
+            let offset = root_motion.extracted_offset(current.translation - previous.translation);
+            root_motion.translation = previous_heading.inverse() * offset;
+            root_motion.rotation = (previous_heading.inverse() * current_heading).normalize();
+        }
+        root_motion.previous = Some(current);
+
+        // Put the bone back into the space of the character, without the
+        // extracted motion.
+        let heading = root_motion.heading(current.rotation, &reference);
+        let offset = current.translation - reference.translation;
+        let remaining_offset = offset - root_motion.extracted_offset(offset);
+        transform.translation = reference.translation + heading.inverse() * remaining_offset;
+        transform.rotation = (heading.inverse() * current.rotation).normalize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{f32::consts::FRAC_PI_2, time::Duration};
+
+    use bevy_ecs::{entity::Entity, schedule::Schedule, world::World};
+    use bevy_math::{Quat, Vec3};
+    use bevy_time::Time;
+    use bevy_transform::components::Transform;
+
+    use super::{extract_root_motion, heading_angle, vertical_twist, RootMotion};
+    use crate::{graph::AnimationNodeIndex, AnimationPlayer, AnimationTarget, AnimationTargetId};
+
+    /// Plays a looping clip, three seconds long, in which the root bone walks
+    /// forward one unit per second.
+    struct WalkCycle {
+        world: World,
+        schedule: Schedule,
+        player: Entity,
+        bone: Entity,
+    }
+
+    impl WalkCycle {
+        const CLIP_DURATION: f32 = 3.0;
+
+        fn new() -> Self {
+            let target = AnimationTargetId::from_name(&"root".into());
+            let mut world = World::new();
+            world.insert_resource(Time::<()>::default());
+            let mut player = AnimationPlayer::default();
+            player.play(AnimationNodeIndex::new(0)).repeat();
+            let player = world.spawn((player, RootMotion::new(target))).id();
+            let bone = world
+                .spawn((AnimationTarget { id: target, player }, Transform::default()))
+                .id();
+            let mut schedule = Schedule::default();
+            schedule.add_systems(extract_root_motion);
+            Self {
+                world,
+                schedule,
+                player,
+                bone,
+            }
+        }
+
+        /// Advances the clip by one second, and returns the extracted
+        /// translation.
+        fn update(&mut self) -> Vec3 {
+            self.world
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_secs(1));
+            let mut player = self.world.get_mut::<AnimationPlayer>(self.player).unwrap();
+            let animation = player.play(AnimationNodeIndex::new(0));
+            animation.update(1.0, Self::CLIP_DURATION);
+            let seek_time = animation.seek_time();
+            *self.world.get_mut::<Transform>(self.bone).unwrap() =
+                Transform::from_xyz(0.0, 0.0, seek_time);
+
+            self.schedule.run(&mut self.world);
+            self.root_motion().translation
+        }
+
+        fn root_motion(&mut self) -> &mut RootMotion {
+            self.world
+                .get_mut::<RootMotion>(self.player)
+                .unwrap()
+                .into_inner()
+        }
+    }
+
+    #[test]
+    fn vertical_twist_ignores_swing() {
+        let rotation = Quat::from_rotation_y(FRAC_PI_2) * Quat::from_rotation_x(0.3);
+        let twist = vertical_twist(rotation);
+        assert!((heading_angle(twist) - FRAC_PI_2).abs() < 1e-5);
+        assert!(vertical_twist(Quat::from_rotation_x(0.3)).abs_diff_eq(Quat::IDENTITY, 1e-5));
+    }
+
+    #[test]
+    fn apply_moves_along_heading() {
+        let mut root_motion = RootMotion::new(AnimationTargetId::from_name(&"root".into()));
+        root_motion.translation = Vec3::Z;
+        root_motion.rotation = Quat::from_rotation_y(FRAC_PI_2);
+
+        let mut transform = Transform::from_rotation(Quat::from_rotation_y(FRAC_PI_2));
+        root_motion.apply(&mut transform);
+        assert!(transform.translation.abs_diff_eq(Vec3::X, 1e-5));
+        assert!(transform
+            .rotation
+            .abs_diff_eq(Quat::from_rotation_y(2.0 * FRAC_PI_2), 1e-5));
+    }
+
+    #[test]
+    fn extract_across_loops() {
+        let mut walk_cycle = WalkCycle::new();
+        // There's no motion to extract on the first frame.
+        assert_eq!(walk_cycle.update(), Vec3::ZERO);
+        assert_eq!(walk_cycle.update(), Vec3::Z);
+        // The clip loops back to its start on the third second, so the motion
+        // of that frame is discarded instead of jumping backward.
+        assert_eq!(walk_cycle.update(), Vec3::ZERO);
+        assert_eq!(walk_cycle.update(), Vec3::Z);
+        assert_eq!(walk_cycle.update(), Vec3::Z);
+        assert_eq!(walk_cycle.update(), Vec3::ZERO);
+        // The bone stays where it was on the first frame.
+        assert_eq!(
+            *walk_cycle.world.get::<Transform>(walk_cycle.bone).unwrap(),
+            Transform::from_xyz(0.0, 0.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn reset_clears_motion() {
+        let mut walk_cycle = WalkCycle::new();
+        for _ in 0..3 {
+            walk_cycle.update();
+        }
+        assert_eq!(walk_cycle.update(), Vec3::Z);
+
+        let root_motion = walk_cycle.root_motion();
+        root_motion.reset();
+        assert_eq!(root_motion.translation, Vec3::ZERO);
+        assert_eq!(root_motion.rotation, Quat::IDENTITY);
+        assert_eq!(root_motion.velocity(), Vec3::ZERO);
+
+        // The next frame has no motion either, as if the clip had been seeked.
+        assert_eq!(walk_cycle.update(), Vec3::ZERO);
+        walk_cycle.update();
+        assert_eq!(walk_cycle.update(), Vec3::Z);
+    }
+}
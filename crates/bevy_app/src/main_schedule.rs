@@ -316,7 +316,12 @@ impl Plugin for MainSchedulePlugin {
         #[cfg(feature = "bevy_debug_stepping")]
         {
             use bevy_ecs::schedule::{IntoSystemConfigs, Stepping};
-            app.add_systems(Main, Stepping::begin_frame.before(Main::run_main));
+            app.add_systems(
+                Main,
+                (Stepping::begin_frame, Stepping::check_continue_until)
+                    .chain()
+                    .before(Main::run_main),
+            );
         }
     }
 }
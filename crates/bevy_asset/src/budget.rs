@@ -0,0 +1,221 @@
+//! Memory budgets for the assets of a given type.
+//!
+//! By default, an asset loaded by the [`AssetServer`] is unloaded as soon as its last strong
+//! [`Handle`] is dropped, so an asset that gets used again shortly after has to be loaded again.
+//! Setting a budget with [`AssetApp::set_asset_memory_budget`] changes this policy for one asset
+//! type: the loaded assets are kept around until the memory they use exceeds the budget, at which
+//! point the unreferenced ones are evicted, least recently used first.
+//!
+//! The memory used by each asset is given by its [`AssetMemoryUsage`] implementation, and the
+//! current usage is reported as a diagnostic at [`AssetBudget::diagnostic_path`].
+//!
+//! [`AssetApp::set_asset_memory_budget`]: crate::AssetApp::set_asset_memory_budget
+
+use crate::{Asset, AssetEvent, AssetId, AssetServer, Assets, Handle};
+use alloc::{collections::VecDeque, sync::Arc};
+use bevy_diagnostic::{DiagnosticPath, Diagnostics};
+use bevy_ecs::prelude::*;
+use bevy_utils::HashMap;
+use tracing::warn;
+
+/// An [`Asset`] that can report how much memory it uses, so that it can be subject to an
+/// [`AssetBudget`].
+pub trait AssetMemoryUsage: Asset {
+    /// Returns the number of bytes used by this asset.
+    ///
+    /// This is called every time the asset is added or modified, so it should be cheap to compute.
+    /// An estimate is fine.
+    fn memory_usage(&self) -> usize;
+}
+
+/// The memory budget of the assets of type `A`.
+///
+/// This resource is inserted by [`AssetApp::set_asset_memory_budget`], and can be used to change
+/// the budget at runtime and inspect the current usage.
+///
+/// Only the assets loaded by the [`AssetServer`] are kept alive and evicted by the budget, since
+/// the others can't be loaded again once evicted. Their memory usage is still accounted for.
+///
+/// [`AssetApp::set_asset_memory_budget`]: crate::AssetApp::set_asset_memory_budget
+#[derive(Resource)]
+pub struct AssetBudget<A: Asset> {
+    max_bytes: usize,
+    usage: usize,
+    sizes: HashMap<AssetId<A>, usize>,
+    /// The handles keeping the loaded assets alive.
+    retained: HashMap<AssetId<A>, RetainedAsset<A>>,
+    /// The retained assets that aren't referenced elsewhere, least recently used first.
+    unreferenced: VecDeque<AssetId<A>>,
+    /// The assets whose handle was dropped by the last eviction.
+    evicted: Vec<AssetId<A>>,
+    over_budget: bool,
+    diagnostic_path: DiagnosticPath,
+}
+
+struct RetainedAsset<A: Asset> {
+    handle: Handle<A>,
+    unreferenced: bool,
+}
+
+impl<A: Asset> AssetBudget<A> {
+    /// Creates a budget of `max_bytes` for the assets of type `A`.
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            usage: 0,
+            sizes: HashMap::default(),
+            retained: HashMap::default(),
+            unreferenced: VecDeque::new(),
+            evicted: Vec::new(),
+            over_budget: false,
+            diagnostic_path: DiagnosticPath::new(format!("asset_memory/{}", A::short_type_path())),
+        }
+    }
+
+    /// Returns the maximum number of bytes the assets of type `A` should use.
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    /// Sets the maximum number of bytes the assets of type `A` should use.
+    ///
+    /// If the budget is lowered, the unreferenced assets that don't fit anymore are evicted the
+    /// next time the budget is updated.
+    pub fn set_max_bytes(&mut self, max_bytes: usize) {
+        self.max_bytes = max_bytes;
+    }
+
+    /// Returns the number of bytes used by the assets of type `A`.
+    pub fn usage(&self) -> usize {
+        self.usage
+    }
+
+    /// Returns `true` if the assets of type `A` use more memory than their budget.
+    ///
+    /// This can only happen when the assets that don't fit are all referenced.
+    pub fn is_over_budget(&self) -> bool {
+        self.usage > self.max_bytes
+    }
+
+    /// Returns the number of assets that are only kept alive by this budget, and would be evicted
+    /// first if memory runs out.
+    pub fn unreferenced_len(&self) -> usize {
+        self.unreferenced.len()
+    }
+
+    /// Returns the path of the diagnostic reporting [`Self::usage`], in bytes.
+    pub fn diagnostic_path(&self) -> &DiagnosticPath {
+        &self.diagnostic_path
+    }
+
+    /// Starts accounting for the asset with the given `id`, and keeps it alive if it was loaded
+    /// by the [`AssetServer`].
+    fn track(&mut self, id: AssetId<A>, asset: &A, asset_server: &AssetServer)
+    where
+        A: AssetMemoryUsage,
+    {
+        let size = asset.memory_usage();
+        if let Some(previous) = self.sizes.insert(id, size) {
+            self.usage -= previous;
+        }
+        self.usage += size;
+        if !self.retained.contains_key(&id) {
+            if let Some(handle) = asset_server.get_id_handle(id) {
+                self.retained.insert(
+                    id,
+                    RetainedAsset {
+                        handle,
+                        unreferenced: false,
+                    },
+                );
+            }
+        }
+    }
+
+    fn forget(&mut self, id: AssetId<A>) {
+        if let Some(size) = self.sizes.remove(&id) {
+            self.usage -= size;
+        }
+        if let Some(retained) = self.retained.remove(&id) {
+            if retained.unreferenced {
+                self.unreferenced.retain(|&unreferenced| unreferenced != id);
+            }
+        }
+    }
+
+    /// Updates the usage and evicts the least recently used assets that don't fit in the budget.
+    pub(crate) fn update(
+        mut budget: ResMut<Self>,
+        mut events: EventReader<AssetEvent<A>>,
+        assets: Res<Assets<A>>,
+        asset_server: Res<AssetServer>,
+        mut diagnostics: Diagnostics,
+    ) where
+        A: AssetMemoryUsage,
+    {
+        let budget = &mut *budget;
+
+        // An evicted asset survives if it got referenced again before its handle drop was
+        // processed, in which case it has to be accounted for again.
+        for id in core::mem::take(&mut budget.evicted) {
+            if let Some(asset) = assets.get(id) {
+                budget.track(id, asset, &asset_server);
+            }
+        }
+
+        for event in events.read() {
+            match *event {
+                AssetEvent::Added { id } | AssetEvent::Modified { id } => {
+                    if let Some(asset) = assets.get(id) {
+                        budget.track(id, asset, &asset_server);
+                    }
+                }
+                AssetEvent::Removed { id } => budget.forget(id),
+                _ => {}
+            }
+        }
+
+        for (&id, retained) in &mut budget.retained {
+            let Handle::Strong(handle) = &retained.handle else {
+                continue;
+            };
+            let unreferenced = Arc::strong_count(handle) == 1;
+            if unreferenced == retained.unreferenced {
+                continue;
+            }
+            retained.unreferenced = unreferenced;
+            if unreferenced {
+                budget.unreferenced.push_back(id);
+            } else {
+                budget
+                    .unreferenced
+                    .retain(|&unreferenced| unreferenced != id);
+            }
+        }
+
+        while budget.usage > budget.max_bytes {
+            let Some(id) = budget.unreferenced.pop_front() else {
+                break;
+            };
+            // Dropping the last handle unloads the asset.
+            budget.retained.remove(&id);
+            if let Some(size) = budget.sizes.remove(&id) {
+                budget.usage -= size;
+            }
+            budget.evicted.push(id);
+        }
+
+        let over_budget = budget.is_over_budget();
+        if over_budget && !budget.over_budget {
+            warn!(
+                "The {} assets use {} bytes, which is over their budget of {} bytes, but none of them can be evicted because they are all in use",
+                A::short_type_path(),
+                budget.usage,
+                budget.max_bytes
+            );
+        }
+        budget.over_budget = over_budget;
+
+        diagnostics.add_measurement(&budget.diagnostic_path, || budget.usage as f64);
+    }
+}
@@ -42,6 +42,18 @@ impl<A: Asset> From<&AssetLoadFailedEvent<A>> for UntypedAssetLoadFailedEvent {
     }
 }
 
+/// An event emitted when a partially loaded version of an asset is inserted, before its final
+/// version is loaded.
+///
+/// Partially loaded versions are sent by asset loaders with
+/// [`LoadContext::send_partial`](crate::LoadContext::send_partial).
+#[derive(Event, Clone, Debug)]
+pub struct AssetPartiallyLoadedEvent {
+    pub id: UntypedAssetId,
+    /// The number of partially loaded versions inserted for this asset so far, starting at 1.
+    pub stage: u32,
+}
+
 /// Events that occur for a specific loaded [`Asset`], such as "value changed" events and "dependency" events.
 #[derive(Event, Reflect)]
 pub enum AssetEvent<A: Asset> {
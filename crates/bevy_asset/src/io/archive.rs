@@ -0,0 +1,607 @@
+//! An asset source reading assets from a single archive file.
+//!
+//! Shipping assets in an archive, rather than as a loose folder, keeps them from being
+//! browsed and modified as easily, and avoids opening many small files. Two formats are
+//! supported:
+//!
+//! - `.zip` archives, with entries that are either stored, compressed with deflate, or
+//!   compressed with zstd (which requires the `zstd` feature). Encrypted entries and ZIP64
+//!   archives aren't supported.
+//! - `.pak` archives, a simple format written by [`PakWriter`], described below.
+//!
+//! The index of the archive is read once when it's opened, so finding an asset doesn't depend
+//! on the number of entries. The contents of the entries are read on demand.
+//!
+//! ```no_run
+//! # use bevy_app::App;
+//! # use bevy_asset::{io::{archive::ArchiveAssetReader, AssetSource}, AssetApp};
+//! # let mut app = App::new();
+//! let reader = ArchiveAssetReader::open("assets.pak").expect("failed to open the archive");
+//! app.register_asset_source(
+//!     "pak",
+//!     AssetSource::build().with_reader(move || Box::new(reader.clone())),
+//! );
+//! // Assets can now be loaded with paths like `pak://models/model.glb`.
+//! ```
+//!
+//! # The `.pak` format
+//!
+//! All integers are little endian.
+//!
+//! | Field           | Type    | Description                                   |
+//! |-----------------|---------|-----------------------------------------------|
+//! | magic           | `[u8]`  | `b"BPAK"`                                     |
+//! | version         | `u32`   | `1`                                           |
+//! | entry count     | `u32`   |                                               |
+//! | entries         |         | `entry count` entries, see below              |
+//! | data            | `[u8]`  | The contents of the entries                   |
+//!
+//! Each entry is made of:
+//!
+//! | Field           | Type    | Description                                   |
+//! |-----------------|---------|-----------------------------------------------|
+//! | path length     | `u32`   |                                               |
+//! | path            | `[u8]`  | UTF-8, with `/` separators                    |
+//! | compression     | `u8`    | `0` for none, `1` for zstd                    |
+//! | offset          | `u64`   | Offset of the contents from the start of file |
+//! | stored size     | `u64`   | Size of the contents in the archive           |
+//! | size            | `u64`   | Size of the contents once decompressed        |
+
+use crate::io::{AssetReader, AssetReaderError, PathStream, Reader, VecReader};
+use alloc::sync::Arc;
+use bevy_utils::{HashMap, HashSet};
+use std::{
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+#[cfg(not(target_arch = "wasm32"))]
+use {
+    parking_lot::Mutex,
+    std::{
+        fs::File,
+        io::{Seek, SeekFrom},
+    },
+};
+
+const PAK_MAGIC: &[u8; 4] = b"BPAK";
+const PAK_VERSION: u32 = 1;
+
+const ZIP_END_OF_CENTRAL_DIRECTORY: u32 = 0x06054b50;
+const ZIP_CENTRAL_DIRECTORY_HEADER: u32 = 0x02014b50;
+const ZIP_LOCAL_FILE_HEADER: u32 = 0x04034b50;
+
+/// An error encountered while opening an archive.
+#[derive(Error, Debug)]
+pub enum ArchiveError {
+    /// The archive couldn't be read.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// The archive is neither a `.zip` nor a `.pak` archive, or is corrupted.
+    #[error("Invalid archive: {0}")]
+    Invalid(&'static str),
+    /// The archive uses a feature that isn't supported.
+    #[error("Unsupported archive: {0}")]
+    Unsupported(&'static str),
+}
+
+/// How the contents of an archive entry are compressed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    /// The contents are stored as is.
+    None,
+    /// The contents are compressed with deflate. This is only used by `.zip` archives.
+    Deflate,
+    /// The contents are compressed with zstd. Reading them requires the `zstd` feature.
+    Zstd,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct ArchiveEntry {
+    offset: u64,
+    stored_size: u64,
+    size: u64,
+    compression: Compression,
+}
+
+#[derive(Debug)]
+enum Storage {
+    #[cfg(not(target_arch = "wasm32"))]
+    File(Mutex<File>),
+    Memory(Arc<[u8]>),
+}
+
+impl Storage {
+    fn len(&self) -> io::Result<u64> {
+        match self {
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::File(file) => Ok(file.lock().metadata()?.len()),
+            Self::Memory(bytes) => Ok(bytes.len() as u64),
+        }
+    }
+
+    fn read_at(&self, offset: u64, len: u64) -> io::Result<Vec<u8>> {
+        let len = usize::try_from(len).map_err(|_| io::ErrorKind::OutOfMemory)?;
+        match self {
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::File(file) => {
+                let mut file = file.lock();
+                file.seek(SeekFrom::Start(offset))?;
+                let mut bytes = vec![0; len];
+                file.read_exact(&mut bytes)?;
+                Ok(bytes)
+            }
+            Self::Memory(bytes) => usize::try_from(offset)
+                .ok()
+                .and_then(|start| bytes.get(start..start.checked_add(len)?))
+                .map(<[u8]>::to_vec)
+                .ok_or_else(|| io::ErrorKind::UnexpectedEof.into()),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ArchiveInternal {
+    storage: Storage,
+    entries: HashMap<PathBuf, ArchiveEntry>,
+    directories: HashMap<PathBuf, HashSet<PathBuf>>,
+}
+
+/// Reader implementation for loading assets from a `.zip` or `.pak` archive.
+///
+/// This is clone-able (internally Arc-ed), so that an archive can be opened once and shared by
+/// the readers of an [`AssetSource`](crate::io::AssetSource). See the
+/// [module docs](self) for more information.
+#[derive(Clone, Debug)]
+pub struct ArchiveAssetReader(Arc<ArchiveInternal>);
+
+impl ArchiveAssetReader {
+    /// Opens the archive at `path`, and reads its index.
+    ///
+    /// Relative paths are relative to the base path of the
+    /// [`FileAssetReader`](crate::io::file::FileAssetReader).
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ArchiveError> {
+        let path = crate::io::file::get_base_path().join(path);
+        Self::new(Storage::File(Mutex::new(File::open(path)?)))
+    }
+
+    /// Reads the index of an archive held in memory, for example with [`include_bytes`].
+    pub fn from_bytes(bytes: impl Into<Arc<[u8]>>) -> Result<Self, ArchiveError> {
+        Self::new(Storage::Memory(bytes.into()))
+    }
+
+    fn new(storage: Storage) -> Result<Self, ArchiveError> {
+        let entries = match storage.read_at(0, 4) {
+            Ok(magic) if magic == PAK_MAGIC => read_pak_index(&storage)?,
+            _ => read_zip_index(&storage)?,
+        };
+
+        let mut directories = HashMap::<PathBuf, HashSet<PathBuf>>::default();
+        for path in entries.keys() {
+            let mut child = path.as_path();
+            while let Some(parent) = child.parent() {
+                let is_new = directories
+                    .entry(parent.to_owned())
+                    .or_default()
+                    .insert(child.to_owned());
+                if !is_new || parent.as_os_str().is_empty() {
+                    break;
+                }
+                child = parent;
+            }
+        }
+
+        Ok(Self(Arc::new(ArchiveInternal {
+            storage,
+            entries,
+            directories,
+        })))
+    }
+
+    /// Returns the paths of the files in the archive.
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.0.entries.keys().map(PathBuf::as_path)
+    }
+
+    fn read_entry(&self, path: &Path) -> Result<Vec<u8>, AssetReaderError> {
+        let entry = self
+            .0
+            .entries
+            .get(path)
+            .ok_or_else(|| AssetReaderError::NotFound(path.to_owned()))?;
+        let stored = self.0.storage.read_at(entry.offset, entry.stored_size)?;
+        let bytes = decompress(stored, entry)?;
+        if bytes.len() as u64 != entry.size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} has an unexpected size in the archive", path.display()),
+            )
+            .into());
+        }
+        Ok(bytes)
+    }
+}
+
+fn decompress(stored: Vec<u8>, entry: &ArchiveEntry) -> io::Result<Vec<u8>> {
+    match entry.compression {
+        Compression::None => Ok(stored),
+        Compression::Deflate => {
+            let mut bytes = Vec::with_capacity(entry.size as usize);
+            flate2::read::DeflateDecoder::new(stored.as_slice()).read_to_end(&mut bytes)?;
+            Ok(bytes)
+        }
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => {
+            let mut bytes = Vec::with_capacity(entry.size as usize);
+            let mut source = stored.as_slice();
+            ruzstd::StreamingDecoder::new(&mut source)
+                .map_err(|err| io::Error::other(err.to_string()))?
+                .read_to_end(&mut bytes)?;
+            Ok(bytes)
+        }
+        #[cfg(not(feature = "zstd"))]
+        Compression::Zstd => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "reading zstd compressed assets requires the `zstd` feature",
+        )),
+    }
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> Result<u16, ArchiveError> {
+    bytes
+        .get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or(ArchiveError::Invalid("truncated header"))
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Result<u32, ArchiveError> {
+    bytes
+        .get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or(ArchiveError::Invalid("truncated header"))
+}
+
+fn u64_at(bytes: &[u8], offset: usize) -> Result<u64, ArchiveError> {
+    bytes
+        .get(offset..offset + 8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or(ArchiveError::Invalid("truncated header"))
+}
+
+fn entry_path(name: &[u8]) -> Result<PathBuf, ArchiveError> {
+    let name = core::str::from_utf8(name).map_err(|_| ArchiveError::Invalid("non-utf8 path"))?;
+    Ok(name.split('/').filter(|part| !part.is_empty()).collect())
+}
+
+fn read_pak_index(storage: &Storage) -> Result<HashMap<PathBuf, ArchiveEntry>, ArchiveError> {
+    let header = storage.read_at(0, 12)?;
+    if u32_at(&header, 4)? != PAK_VERSION {
+        return Err(ArchiveError::Unsupported("unknown .pak version"));
+    }
+    let count = u32_at(&header, 8)?;
+    let len = storage.len()?;
+
+    let mut entries = HashMap::default();
+    let mut offset = 12;
+    for _ in 0..count {
+        let path_len = u32_at(&storage.read_at(offset, 4)?, 0)? as u64;
+        // The length is checked before allocating the entry, as it comes from the file.
+        if path_len + 25 > len.saturating_sub(offset + 4) {
+            return Err(ArchiveError::Invalid("truncated pak index"));
+        }
+        let entry = storage.read_at(offset + 4, path_len + 25)?;
+        let path_len = path_len as usize;
+        let compression = match entry[path_len] {
+            0 => Compression::None,
+            1 => Compression::Zstd,
+            _ => return Err(ArchiveError::Unsupported("unknown compression method")),
+        };
+        entries.insert(
+            entry_path(&entry[..path_len])?,
+            ArchiveEntry {
+                compression,
+                offset: u64_at(&entry, path_len + 1)?,
+                stored_size: u64_at(&entry, path_len + 9)?,
+                size: u64_at(&entry, path_len + 17)?,
+            },
+        );
+        offset += 4 + entry.len() as u64;
+    }
+    Ok(entries)
+}
+
+fn read_zip_index(storage: &Storage) -> Result<HashMap<PathBuf, ArchiveEntry>, ArchiveError> {
+    // The end of central directory record is at least 22 bytes long, and ends with a comment of
+    // up to 65535 bytes.
+    let len = storage.len()?;
+    let tail_len = len.min(22 + u16::MAX as u64);
+    let tail = storage.read_at(len - tail_len, tail_len)?;
+    let end = (0..tail.len().saturating_sub(21))
+        .rev()
+        .find(|&offset| u32_at(&tail, offset).ok() == Some(ZIP_END_OF_CENTRAL_DIRECTORY))
+        .ok_or(ArchiveError::Invalid("no zip end of central directory"))?;
+    let count = u16_at(&tail, end + 10)?;
+    let directory_size = u32_at(&tail, end + 12)?;
+    let directory_offset = u32_at(&tail, end + 16)?;
+    if count == u16::MAX || directory_offset == u32::MAX {
+        return Err(ArchiveError::Unsupported("ZIP64 archives"));
+    }
+
+    if directory_offset as u64 + directory_size as u64 > len {
+        return Err(ArchiveError::Invalid("truncated zip central directory"));
+    }
+    let directory = storage.read_at(directory_offset as u64, directory_size as u64)?;
+    let mut entries = HashMap::default();
+    let mut offset = 0;
+    for _ in 0..count {
+        if u32_at(&directory, offset)? != ZIP_CENTRAL_DIRECTORY_HEADER {
+            return Err(ArchiveError::Invalid("corrupted zip central directory"));
+        }
+        let flags = u16_at(&directory, offset + 8)?;
+        let method = u16_at(&directory, offset + 10)?;
+        let stored_size = u32_at(&directory, offset + 20)?;
+        let size = u32_at(&directory, offset + 24)?;
+        let name_len = u16_at(&directory, offset + 28)? as usize;
+        let extra_len = u16_at(&directory, offset + 30)? as usize;
+        let comment_len = u16_at(&directory, offset + 32)? as usize;
+        let header_offset = u32_at(&directory, offset + 42)?;
+        let name = directory
+            .get(offset + 46..offset + 46 + name_len)
+            .ok_or(ArchiveError::Invalid("truncated header"))?;
+        offset += 46 + name_len + extra_len + comment_len;
+
+        if name.ends_with(b"/") {
+            continue;
+        }
+        if flags & 1 != 0 {
+            return Err(ArchiveError::Unsupported("encrypted zip entries"));
+        }
+        if stored_size == u32::MAX || size == u32::MAX || header_offset == u32::MAX {
+            return Err(ArchiveError::Unsupported("ZIP64 archives"));
+        }
+        let compression = match method {
+            0 => Compression::None,
+            8 => Compression::Deflate,
+            93 => Compression::Zstd,
+            _ => return Err(ArchiveError::Unsupported("unknown compression method")),
+        };
+
+        // The local header has its own extra field, which may differ from the one in the
+        // central directory.
+        let local_header = storage.read_at(header_offset as u64, 30)?;
+        if u32_at(&local_header, 0)? != ZIP_LOCAL_FILE_HEADER {
+            return Err(ArchiveError::Invalid("corrupted zip local header"));
+        }
+        let local_name_len = u16_at(&local_header, 26)? as u64;
+        let local_extra_len = u16_at(&local_header, 28)? as u64;
+
+        entries.insert(
+            entry_path(name)?,
+            ArchiveEntry {
+                offset: header_offset as u64 + 30 + local_name_len + local_extra_len,
+                stored_size: stored_size as u64,
+                size: size as u64,
+                compression,
+            },
+        );
+    }
+    Ok(entries)
+}
+
+/// Writes `.pak` archives, which can be read by an [`ArchiveAssetReader`].
+///
+/// ```
+/// # use bevy_asset::io::archive::{ArchiveAssetReader, PakWriter};
+/// let mut writer = PakWriter::default();
+/// writer.add("models/model.glb", b"glTF".to_vec());
+/// let mut bytes = Vec::new();
+/// writer.write(&mut bytes).unwrap();
+///
+/// let reader = ArchiveAssetReader::from_bytes(bytes).unwrap();
+/// assert_eq!(reader.paths().count(), 1);
+/// ```
+#[derive(Default, Debug)]
+pub struct PakWriter {
+    entries: Vec<(String, Compression, u64, Vec<u8>)>,
+}
+
+impl PakWriter {
+    /// Adds a file with the given contents, stored without compression.
+    pub fn add(&mut self, path: impl Into<String>, bytes: Vec<u8>) -> &mut Self {
+        let size = bytes.len() as u64;
+        self.entries
+            .push((path.into(), Compression::None, size, bytes));
+        self
+    }
+
+    /// Adds a file whose contents were already compressed with zstd. `size` is the size of the
+    /// decompressed contents.
+    pub fn add_zstd_compressed(
+        &mut self,
+        path: impl Into<String>,
+        compressed: Vec<u8>,
+        size: u64,
+    ) -> &mut Self {
+        self.entries
+            .push((path.into(), Compression::Zstd, size, compressed));
+        self
+    }
+
+    /// Writes the archive.
+    pub fn write(&self, mut writer: impl Write) -> io::Result<()> {
+        let index_len: usize = self
+            .entries
+            .iter()
+            .map(|(path, ..)| 4 + path.len() + 25)
+            .sum();
+        let count = u32::try_from(self.entries.len()).map_err(io::Error::other)?;
+
+        writer.write_all(PAK_MAGIC)?;
+        writer.write_all(&PAK_VERSION.to_le_bytes())?;
+        writer.write_all(&count.to_le_bytes())?;
+        let mut offset = 12 + index_len as u64;
+        for (path, compression, size, bytes) in &self.entries {
+            let path_len = u32::try_from(path.len()).map_err(io::Error::other)?;
+            writer.write_all(&path_len.to_le_bytes())?;
+            writer.write_all(path.as_bytes())?;
+            writer.write_all(&[match compression {
+                Compression::Zstd => 1,
+                _ => 0,
+            }])?;
+            writer.write_all(&offset.to_le_bytes())?;
+            writer.write_all(&(bytes.len() as u64).to_le_bytes())?;
+            writer.write_all(&size.to_le_bytes())?;
+            offset += bytes.len() as u64;
+        }
+        for (.., bytes) in &self.entries {
+            writer.write_all(bytes)?;
+        }
+        Ok(())
+    }
+}
+
+impl AssetReader for ArchiveAssetReader {
+    async fn read<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        self.read_entry(path).map(VecReader::new)
+    }
+
+    async fn read_meta<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        self.read_entry(&crate::io::get_meta_path(path))
+            .map(VecReader::new)
+    }
+
+    async fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> Result<Box<PathStream>, AssetReaderError> {
+        let children = self
+            .0
+            .directories
+            .get(path)
+            .ok_or_else(|| AssetReaderError::NotFound(path.to_owned()))?
+            .iter()
+            .filter(|child| {
+                child
+                    .extension()
+                    .is_none_or(|extension| extension != "meta")
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        let stream: Box<PathStream> = Box::new(futures_lite::stream::iter(children));
+        Ok(stream)
+    }
+
+    async fn is_directory<'a>(&'a self, path: &'a Path) -> Result<bool, AssetReaderError> {
+        Ok(self.0.directories.contains_key(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArchiveAssetReader, ArchiveError, PakWriter};
+    use crate::io::{AssetReader, AssetReaderError, Reader};
+    use futures_lite::{future::block_on, StreamExt};
+    use std::path::{Path, PathBuf};
+
+    fn read(reader: &ArchiveAssetReader, path: &str) -> Result<Vec<u8>, AssetReaderError> {
+        block_on(async {
+            let mut bytes = Vec::new();
+            reader
+                .read(Path::new(path))
+                .await?
+                .read_to_end(&mut bytes)
+                .await?;
+            Ok(bytes)
+        })
+    }
+
+    /// Builds a zip archive with stored entries.
+    fn zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut archive = Vec::new();
+        let mut directory = Vec::new();
+        for (name, contents) in files {
+            let offset = archive.len() as u32;
+            let mut header = Vec::new();
+            header.extend_from_slice(&[0; 10]);
+            header.extend_from_slice(&0u32.to_le_bytes()); // crc, not checked
+            header.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+            header.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+            header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            header.extend_from_slice(&0u16.to_le_bytes());
+
+            archive.extend_from_slice(&0x04034b50u32.to_le_bytes());
+            archive.extend_from_slice(&header);
+            archive.extend_from_slice(name.as_bytes());
+            archive.extend_from_slice(contents);
+
+            directory.extend_from_slice(&0x02014b50u32.to_le_bytes());
+            directory.extend_from_slice(&[0; 2]);
+            directory.extend_from_slice(&header);
+            directory.extend_from_slice(&[0; 10]);
+            directory.extend_from_slice(&offset.to_le_bytes());
+            directory.extend_from_slice(name.as_bytes());
+        }
+        let directory_offset = archive.len() as u32;
+        archive.extend_from_slice(&directory);
+        archive.extend_from_slice(&0x06054b50u32.to_le_bytes());
+        archive.extend_from_slice(&[0; 4]);
+        archive.extend_from_slice(&(files.len() as u16).to_le_bytes());
+        archive.extend_from_slice(&(files.len() as u16).to_le_bytes());
+        archive.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+        archive.extend_from_slice(&directory_offset.to_le_bytes());
+        archive.extend_from_slice(&0u16.to_le_bytes());
+        archive
+    }
+
+    #[test]
+    fn read_pak() {
+        let mut writer = PakWriter::default();
+        writer
+            .add("a.txt", b"a".to_vec())
+            .add("dir/b.txt", b"bb".to_vec())
+            .add("dir/b.txt.meta", b"meta".to_vec());
+        let mut bytes = Vec::new();
+        writer.write(&mut bytes).unwrap();
+        let reader = ArchiveAssetReader::from_bytes(bytes).unwrap();
+
+        assert_eq!(read(&reader, "a.txt").unwrap(), b"a");
+        assert_eq!(read(&reader, "dir/b.txt").unwrap(), b"bb");
+        assert!(matches!(
+            read(&reader, "c.txt"),
+            Err(AssetReaderError::NotFound(_))
+        ));
+
+        assert!(block_on(reader.is_directory(Path::new("dir"))).unwrap());
+        let children = block_on(async {
+            let stream = reader.read_directory(Path::new("dir")).await.unwrap();
+            stream.collect::<Vec<_>>().await
+        });
+        assert_eq!(children, [PathBuf::from("dir/b.txt")]);
+    }
+
+    #[test]
+    fn reject_truncated_pak() {
+        let mut writer = PakWriter::default();
+        writer.add("a.txt", b"a".to_vec());
+        let mut bytes = Vec::new();
+        writer.write(&mut bytes).unwrap();
+        // The path length of the first entry claims more bytes than the archive has.
+        bytes[12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            ArchiveAssetReader::from_bytes(bytes),
+            Err(ArchiveError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn read_zip() {
+        let archive = zip(&[("a.txt", b"a"), ("dir/b.txt", b"bb")]);
+        let reader = ArchiveAssetReader::from_bytes(archive).unwrap();
+
+        assert_eq!(read(&reader, "a.txt").unwrap(), b"a");
+        assert_eq!(read(&reader, "dir/b.txt").unwrap(), b"bb");
+        assert!(block_on(reader.is_directory(Path::new(""))).unwrap());
+    }
+}
@@ -7,6 +7,8 @@ compile_error!(
 
 #[cfg(target_os = "android")]
 pub mod android;
+#[cfg(feature = "archive")]
+pub mod archive;
 pub mod embedded;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod file;
@@ -15,6 +17,8 @@ pub mod memory;
 pub mod processor_gated;
 #[cfg(target_arch = "wasm32")]
 pub mod wasm;
+#[cfg(feature = "http")]
+pub mod web;
 
 mod source;
 
@@ -1,7 +1,8 @@
 use crate::io::{
     get_meta_path, AssetReader, AssetReaderError, EmptyPathStream, PathStream, Reader, VecReader,
 };
-use js_sys::{Uint8Array, JSON};
+use core::time::Duration;
+use js_sys::{Promise, Uint8Array, JSON};
 use std::path::{Path, PathBuf};
 use tracing::error;
 use wasm_bindgen::{prelude::wasm_bindgen, JsCast, JsValue};
@@ -50,51 +51,68 @@ fn js_value_to_err(context: &str) -> impl FnOnce(JsValue) -> std::io::Error + '_
     }
 }
 
-impl HttpWasmAssetReader {
-    async fn fetch_bytes<'a>(&self, path: PathBuf) -> Result<impl Reader, AssetReaderError> {
-        // The JS global scope includes a self-reference via a specializing name, which can be used to determine the type of global context available.
-        let global: Global = js_sys::global().unchecked_into();
-        let promise = if !global.window().is_undefined() {
-            let window: web_sys::Window = global.unchecked_into();
-            window.fetch_with_str(path.to_str().unwrap())
+/// Fetches the bytes at `path`, which must be a URL, using the JS `fetch` API.
+pub(crate) async fn fetch_bytes(path: PathBuf) -> Result<VecReader, AssetReaderError> {
+    // The JS global scope includes a self-reference via a specializing name, which can be used to determine the type of global context available.
+    let global: Global = js_sys::global().unchecked_into();
+    let promise = if !global.window().is_undefined() {
+        let window: web_sys::Window = global.unchecked_into();
+        window.fetch_with_str(path.to_str().unwrap())
+    } else if !global.worker().is_undefined() {
+        let worker: web_sys::WorkerGlobalScope = global.unchecked_into();
+        worker.fetch_with_str(path.to_str().unwrap())
+    } else {
+        let error = std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "Unsupported JavaScript global context",
+        );
+        return Err(AssetReaderError::Io(error.into()));
+    };
+    let resp_value = JsFuture::from(promise)
+        .await
+        .map_err(js_value_to_err("fetch path"))?;
+    let resp = resp_value
+        .dyn_into::<Response>()
+        .map_err(js_value_to_err("convert fetch to Response"))?;
+    match resp.status() {
+        200 => {
+            let data = JsFuture::from(resp.array_buffer().unwrap()).await.unwrap();
+            let bytes = Uint8Array::new(&data).to_vec();
+            let reader = VecReader::new(bytes);
+            Ok(reader)
+        }
+        404 => Err(AssetReaderError::NotFound(path)),
+        status => Err(AssetReaderError::HttpError(status)),
+    }
+}
+
+/// Waits for `duration` using the JS `setTimeout` API.
+pub(crate) async fn sleep(duration: Duration) {
+    let global: Global = js_sys::global().unchecked_into();
+    let millis = i32::try_from(duration.as_millis()).unwrap_or(i32::MAX);
+    let promise = Promise::new(&mut |resolve, _reject| {
+        if !global.window().is_undefined() {
+            let window: &web_sys::Window = global.unchecked_ref();
+            let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, millis);
         } else if !global.worker().is_undefined() {
-            let worker: web_sys::WorkerGlobalScope = global.unchecked_into();
-            worker.fetch_with_str(path.to_str().unwrap())
+            let worker: &web_sys::WorkerGlobalScope = global.unchecked_ref();
+            let _ = worker.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, millis);
         } else {
-            let error = std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "Unsupported JavaScript global context",
-            );
-            return Err(AssetReaderError::Io(error.into()));
-        };
-        let resp_value = JsFuture::from(promise)
-            .await
-            .map_err(js_value_to_err("fetch path"))?;
-        let resp = resp_value
-            .dyn_into::<Response>()
-            .map_err(js_value_to_err("convert fetch to Response"))?;
-        match resp.status() {
-            200 => {
-                let data = JsFuture::from(resp.array_buffer().unwrap()).await.unwrap();
-                let bytes = Uint8Array::new(&data).to_vec();
-                let reader = VecReader::new(bytes);
-                Ok(reader)
-            }
-            404 => Err(AssetReaderError::NotFound(path)),
-            status => Err(AssetReaderError::HttpError(status)),
+            let _ = resolve.call0(&JsValue::UNDEFINED);
         }
-    }
+    });
+    let _ = JsFuture::from(promise).await;
 }
 
 impl AssetReader for HttpWasmAssetReader {
     async fn read<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
         let path = self.root_path.join(path);
-        self.fetch_bytes(path).await
+        fetch_bytes(path).await
     }
 
     async fn read_meta<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
         let meta_path = get_meta_path(&self.root_path.join(path));
-        self.fetch_bytes(meta_path).await
+        fetch_bytes(meta_path).await
     }
 
     async fn read_directory<'a>(
@@ -0,0 +1,417 @@
+//! Asset sources that load assets from the web.
+//!
+//! Adding the [`WebAssetPlugin`] before the [`AssetPlugin`](crate::AssetPlugin) registers the
+//! `http` source (and the `https` source when the `https` feature is enabled), so that assets
+//! can be loaded from a server or a CDN by their URL:
+//!
+//! ```no_run
+//! # use bevy_asset::{prelude::*, io::web::WebAssetPlugin};
+//! # use bevy_app::App;
+//! # #[derive(Asset, TypePath)]
+//! # struct Model;
+//! # let mut app = App::new();
+//! app.add_plugins((WebAssetPlugin::default(), AssetPlugin::default()));
+//! # let asset_server = app.world().resource::<AssetServer>();
+//! let model: Handle<Model> = asset_server.load("https://example.com/models/model.glb");
+//! ```
+//!
+//! Requests that fail because of the network or because of a server error are retried with an
+//! exponential backoff. On native platforms, downloaded assets are cached on disk and revalidated
+//! with their `ETag`, and the cached version is used when the server can't be reached. On the
+//! web, caching is left to the browser.
+
+use crate::io::{
+    get_meta_path, AssetReader, AssetReaderError, AssetSource, AssetSourceBuilders,
+    EmptyPathStream, PathStream, Reader, VecReader,
+};
+use bevy_app::{App, Plugin};
+use core::time::Duration;
+use std::path::{Path, PathBuf};
+use tracing::{error, warn};
+
+/// Registers the `http` and `https` asset sources.
+///
+/// This must be added before the [`AssetPlugin`](crate::AssetPlugin), which builds the asset
+/// sources.
+#[derive(Clone, Debug)]
+pub struct WebAssetPlugin {
+    /// The directory where downloaded assets are cached, relative to the base path of the
+    /// [`FileAssetReader`](crate::io::file::FileAssetReader). Set to [`None`] to disable caching.
+    ///
+    /// This is ignored on the web, where the browser caches requests.
+    pub cache_path: Option<PathBuf>,
+    /// The number of times a failed request is retried before giving up.
+    pub max_retries: u32,
+    /// The delay before the first retry. It doubles with each following retry.
+    pub retry_delay: Duration,
+}
+
+impl Default for WebAssetPlugin {
+    fn default() -> Self {
+        Self {
+            cache_path: Some(PathBuf::from(".web-asset-cache")),
+            max_retries: 3,
+            retry_delay: Duration::from_millis(250),
+        }
+    }
+}
+
+impl Plugin for WebAssetPlugin {
+    fn build(&self, app: &mut App) {
+        if app.is_plugin_added::<crate::AssetPlugin>() {
+            error!("WebAssetPlugin must be added before AssetPlugin, the web asset sources won't be available");
+            return;
+        }
+
+        let mut sources = app
+            .world_mut()
+            .get_resource_or_init::<AssetSourceBuilders>();
+        let protocols = [
+            WebProtocol::Http,
+            #[cfg(feature = "https")]
+            WebProtocol::Https,
+        ];
+        for protocol in protocols {
+            let plugin = self.clone();
+            sources.insert(
+                protocol.as_str(),
+                AssetSource::build().with_reader(move || {
+                    let mut reader = WebAssetReader::new(protocol)
+                        .with_retries(plugin.max_retries, plugin.retry_delay);
+                    if let Some(cache_path) = &plugin.cache_path {
+                        reader = reader.with_cache(cache_path);
+                    }
+                    Box::new(reader)
+                }),
+            );
+        }
+    }
+}
+
+/// The protocol used by a [`WebAssetReader`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WebProtocol {
+    /// Unencrypted HTTP.
+    Http,
+    /// HTTP over TLS. This requires the `https` feature on native platforms.
+    Https,
+}
+
+impl WebProtocol {
+    /// Returns the URL scheme of this protocol, which is also the name of its asset source.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Http => "http",
+            Self::Https => "https",
+        }
+    }
+}
+
+/// Reader implementation for loading assets from the web by their URL.
+///
+/// The path of the assets is the URL without the scheme, which is provided by the
+/// [`WebProtocol`].
+pub struct WebAssetReader {
+    protocol: WebProtocol,
+    #[cfg_attr(
+        target_arch = "wasm32",
+        expect(dead_code, reason = "The browser caches requests on the web.")
+    )]
+    cache_path: Option<PathBuf>,
+    max_retries: u32,
+    retry_delay: Duration,
+}
+
+impl WebAssetReader {
+    /// Creates a new `WebAssetReader` for the given protocol, without caching or retries.
+    pub fn new(protocol: WebProtocol) -> Self {
+        Self {
+            protocol,
+            cache_path: None,
+            max_retries: 0,
+            retry_delay: Duration::ZERO,
+        }
+    }
+
+    /// Caches the downloaded assets in the given directory, relative to the base path of the
+    /// [`FileAssetReader`](crate::io::file::FileAssetReader).
+    pub fn with_cache(self, path: impl AsRef<Path>) -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        let cache_path = Some(crate::io::file::get_base_path().join(path));
+        #[cfg(target_arch = "wasm32")]
+        let cache_path = {
+            let _ = path;
+            None
+        };
+        Self { cache_path, ..self }
+    }
+
+    /// Retries failed requests up to `max_retries` times, waiting `delay` before the first retry
+    /// and twice as long before each following one.
+    pub fn with_retries(mut self, max_retries: u32, delay: Duration) -> Self {
+        self.max_retries = max_retries;
+        self.retry_delay = delay;
+        self
+    }
+
+    fn url(&self, path: &Path) -> Result<String, AssetReaderError> {
+        let path_str = path.to_str().ok_or_else(|| {
+            AssetReaderError::Io(
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("non-utf8 path: {}", path.display()),
+                )
+                .into(),
+            )
+        })?;
+        Ok(format!(
+            "{}://{}",
+            self.protocol.as_str(),
+            path_str.replace('\\', "/")
+        ))
+    }
+
+    async fn get(&self, path: &Path) -> Result<VecReader, AssetReaderError> {
+        let url = self.url(path)?;
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(cache_path) = &self.cache_path {
+            return self.get_cached(cache_path, url).await.map(VecReader::new);
+        }
+
+        match self.fetch_with_retries(&url, None).await? {
+            Fetched::Body { bytes, .. } => Ok(VecReader::new(bytes)),
+            Fetched::NotModified => Err(unexpected_not_modified(&url)),
+        }
+    }
+
+    /// Returns the cached asset at `url` if the server says it's still up to date, downloads and
+    /// caches it otherwise.
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn get_cached(
+        &self,
+        cache_path: &Path,
+        url: String,
+    ) -> Result<Vec<u8>, AssetReaderError> {
+        let entry = CacheEntry::new(cache_path, &url);
+        let cached = entry.read().await;
+        let etag = cached.as_ref().and_then(|(_, etag)| etag.clone());
+
+        match self.fetch_with_retries(&url, etag).await {
+            Ok(Fetched::NotModified) => match cached {
+                Some((bytes, _)) => Ok(bytes),
+                None => Err(unexpected_not_modified(&url)),
+            },
+            Ok(Fetched::Body { bytes, etag }) => {
+                if let Err(err) = entry.write(&bytes, etag.as_deref()).await {
+                    warn!("Failed to cache {url}: {err}");
+                }
+                Ok(bytes)
+            }
+            Err(err @ AssetReaderError::NotFound(_)) => Err(err),
+            Err(err) => match cached {
+                Some((bytes, _)) => {
+                    warn!("Failed to download {url} ({err}), using the cached version");
+                    Ok(bytes)
+                }
+                None => Err(err),
+            },
+        }
+    }
+
+    async fn fetch_with_retries(
+        &self,
+        url: &str,
+        etag: Option<String>,
+    ) -> Result<Fetched, AssetReaderError> {
+        let mut delay = self.retry_delay;
+        let mut retries = 0;
+        loop {
+            match fetch(url, etag.clone()).await {
+                Err(err) if retries < self.max_retries && is_transient(&err) => {
+                    warn!("Failed to download {url} ({err}), retrying in {delay:?}");
+                    sleep(delay).await;
+                    delay *= 2;
+                    retries += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// The error returned when the server answers `304 Not Modified` to a request that has no
+/// cached version to fall back to, which a well-behaved server never does.
+fn unexpected_not_modified(url: &str) -> AssetReaderError {
+    AssetReaderError::Io(
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("unexpected 304 Not Modified response for {url}, which isn't cached"),
+        )
+        .into(),
+    )
+}
+
+/// Returns `true` if retrying the request that failed with `error` may succeed.
+fn is_transient(error: &AssetReaderError) -> bool {
+    match error {
+        AssetReaderError::NotFound(_) => false,
+        AssetReaderError::Io(_) => true,
+        // Too Many Requests, Request Timeout, and server errors.
+        AssetReaderError::HttpError(status) => *status == 408 || *status == 429 || *status >= 500,
+    }
+}
+
+/// A successful response.
+enum Fetched {
+    /// The asset, and its `ETag` if the server sent one.
+    Body {
+        bytes: Vec<u8>,
+        #[cfg_attr(
+            target_arch = "wasm32",
+            expect(dead_code, reason = "The browser caches requests on the web.")
+        )]
+        etag: Option<String>,
+    },
+    /// The asset didn't change since the version with the `ETag` sent with the request.
+    #[cfg_attr(
+        target_arch = "wasm32",
+        expect(dead_code, reason = "The browser caches requests on the web.")
+    )]
+    NotModified,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn fetch(url: &str, etag: Option<String>) -> Result<Fetched, AssetReaderError> {
+    use std::{io::Read, sync::LazyLock};
+
+    static AGENT: LazyLock<ureq::Agent> = LazyLock::new(ureq::Agent::new);
+
+    let url = url.to_owned();
+    // Run the request on a separate thread, so that it doesn't block the async executor.
+    blocking::unblock(move || {
+        let mut request = AGENT.get(&url);
+        if let Some(etag) = &etag {
+            request = request.set("If-None-Match", etag);
+        }
+        match request.call() {
+            Ok(response) if response.status() == 304 => Ok(Fetched::NotModified),
+            Ok(response) => {
+                let etag = response.header("ETag").map(ToOwned::to_owned);
+                let mut bytes = Vec::new();
+                response.into_reader().read_to_end(&mut bytes)?;
+                Ok(Fetched::Body { bytes, etag })
+            }
+            Err(ureq::Error::Status(404, _)) => Err(AssetReaderError::NotFound(url.into())),
+            Err(ureq::Error::Status(status, _)) => Err(AssetReaderError::HttpError(status)),
+            Err(ureq::Error::Transport(err)) => Err(std::io::Error::other(err).into()),
+        }
+    })
+    .await
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn fetch(url: &str, _etag: Option<String>) -> Result<Fetched, AssetReaderError> {
+    let mut bytes = Vec::new();
+    let mut reader = crate::io::wasm::fetch_bytes(url.into()).await?;
+    reader.read_to_end(&mut bytes).await?;
+    Ok(Fetched::Body { bytes, etag: None })
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn sleep(duration: Duration) {
+    blocking::unblock(move || std::thread::sleep(duration)).await;
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn sleep(duration: Duration) {
+    crate::io::wasm::sleep(duration).await;
+}
+
+/// The files caching the asset downloaded from a URL.
+#[cfg(not(target_arch = "wasm32"))]
+struct CacheEntry {
+    data_path: PathBuf,
+    etag_path: PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl CacheEntry {
+    fn new(cache_path: &Path, url: &str) -> Self {
+        let name = blake3::hash(url.as_bytes()).to_hex();
+        Self {
+            data_path: cache_path.join(name.as_str()),
+            etag_path: cache_path.join(format!("{name}.etag")),
+        }
+    }
+
+    /// Returns the cached asset and its `ETag`, if any.
+    async fn read(&self) -> Option<(Vec<u8>, Option<String>)> {
+        let bytes = async_fs::read(&self.data_path).await.ok()?;
+        let etag = async_fs::read_to_string(&self.etag_path).await.ok();
+        Some((bytes, etag))
+    }
+
+    async fn write(&self, bytes: &[u8], etag: Option<&str>) -> std::io::Result<()> {
+        if let Some(parent) = self.data_path.parent() {
+            async_fs::create_dir_all(parent).await?;
+        }
+        async_fs::write(&self.data_path, bytes).await?;
+        match etag {
+            Some(etag) => async_fs::write(&self.etag_path, etag).await,
+            None => match async_fs::remove_file(&self.etag_path).await {
+                Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err),
+                _ => Ok(()),
+            },
+        }
+    }
+}
+
+impl AssetReader for WebAssetReader {
+    async fn read<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        self.get(path).await
+    }
+
+    async fn read_meta<'a>(&'a self, path: &'a Path) -> Result<impl Reader + 'a, AssetReaderError> {
+        self.get(&get_meta_path(path)).await
+    }
+
+    async fn read_directory<'a>(
+        &'a self,
+        _path: &'a Path,
+    ) -> Result<Box<PathStream>, AssetReaderError> {
+        let stream: Box<PathStream> = Box::new(EmptyPathStream);
+        error!("Reading directories is not supported with the WebAssetReader");
+        Ok(stream)
+    }
+
+    async fn is_directory<'a>(&'a self, _path: &'a Path) -> Result<bool, AssetReaderError> {
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_transient, WebAssetReader, WebProtocol};
+    use crate::io::AssetReaderError;
+    use std::path::Path;
+
+    #[test]
+    fn builds_urls() {
+        let reader = WebAssetReader::new(WebProtocol::Https);
+        assert_eq!(
+            reader
+                .url(Path::new("example.com/models/model.glb"))
+                .unwrap(),
+            "https://example.com/models/model.glb"
+        );
+    }
+
+    #[test]
+    fn retries_transient_errors() {
+        assert!(is_transient(&AssetReaderError::HttpError(503)));
+        assert!(is_transient(&AssetReaderError::HttpError(429)));
+        assert!(!is_transient(&AssetReaderError::HttpError(403)));
+        assert!(!is_transient(&AssetReaderError::NotFound("a".into())));
+    }
+}
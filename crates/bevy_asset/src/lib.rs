@@ -170,6 +170,7 @@ pub mod prelude {
 
 mod asset_changed;
 mod assets;
+mod budget;
 mod direct_access_ext;
 mod event;
 mod folder;
@@ -184,6 +185,7 @@ mod server;
 
 pub use assets::*;
 pub use bevy_asset_macros::Asset;
+pub use budget::*;
 pub use direct_access_ext::DirectAssetAccessExt;
 pub use event::*;
 pub use folder::*;
@@ -208,6 +210,7 @@ use crate::{
 };
 use alloc::sync::Arc;
 use bevy_app::{App, Last, Plugin, PreUpdate};
+use bevy_diagnostic::{Diagnostic, RegisterDiagnostic};
 use bevy_ecs::prelude::Component;
 use bevy_ecs::{
     reflect::AppTypeRegistry,
@@ -381,6 +384,7 @@ impl Plugin for AssetPlugin {
             .init_asset::<LoadedUntypedAsset>()
             .init_asset::<()>()
             .add_event::<UntypedAssetLoadFailedEvent>()
+            .add_event::<AssetPartiallyLoadedEvent>()
             .configure_sets(PreUpdate, TrackAssets.after(handle_internal_asset_events))
             // `handle_internal_asset_events` requires the use of `&mut World`,
             // and as a result has ambiguous system ordering with all other systems in `PreUpdate`.
@@ -503,6 +507,15 @@ pub trait AssetApp {
     /// Preregisters a loader for the given extensions, that will block asset loads until a real loader
     /// is registered.
     fn preregister_asset_loader<L: AssetLoader>(&mut self, extensions: &[&str]) -> &mut Self;
+    /// Sets the memory budget of the assets of type `A` to `max_bytes`, inserting the
+    /// [`AssetBudget`] resource and the systems maintaining it if needed.
+    ///
+    /// Loaded assets of this type are then kept alive until they don't fit in the budget anymore,
+    /// instead of being unloaded as soon as their last handle is dropped. See the [`AssetBudget`]
+    /// docs for more information.
+    ///
+    /// The [`Asset`] must have been initialized with [`AssetApp::init_asset`] before.
+    fn set_asset_memory_budget<A: AssetMemoryUsage>(&mut self, max_bytes: usize) -> &mut Self;
 }
 
 impl AssetApp for App {
@@ -574,6 +587,7 @@ impl AssetApp for App {
             .add_event::<AssetEvent<A>>()
             .add_event::<AssetLoadFailedEvent<A>>()
             .register_type::<Handle<A>>()
+            .register_type_data::<Handle<A>, ReflectHandle>()
             .add_systems(
                 Last,
                 Assets::<A>::asset_events
@@ -606,6 +620,19 @@ impl AssetApp for App {
             .preregister_loader::<L>(extensions);
         self
     }
+
+    fn set_asset_memory_budget<A: AssetMemoryUsage>(&mut self, max_bytes: usize) -> &mut Self {
+        if let Some(mut budget) = self.world_mut().get_resource_mut::<AssetBudget<A>>() {
+            budget.set_max_bytes(max_bytes);
+            return self;
+        }
+        let budget = AssetBudget::<A>::new(max_bytes);
+        self.register_diagnostic(
+            Diagnostic::new(budget.diagnostic_path().clone()).with_suffix(" bytes"),
+        )
+        .insert_resource(budget)
+        .add_systems(Last, AssetBudget::<A>::update.after(AssetEvents))
+    }
 }
 
 /// A system set that holds all "track asset" operations.
@@ -630,8 +657,8 @@ mod tests {
             AssetReader, AssetReaderError, AssetSource, AssetSourceId, Reader,
         },
         loader::{AssetLoader, LoadContext},
-        Asset, AssetApp, AssetEvent, AssetId, AssetLoadError, AssetLoadFailedEvent, AssetPath,
-        AssetPlugin, AssetServer, Assets,
+        Asset, AssetApp, AssetBudget, AssetEvent, AssetId, AssetLoadError, AssetLoadFailedEvent,
+        AssetMemoryUsage, AssetPath, AssetPlugin, AssetServer, Assets,
     };
     use alloc::sync::Arc;
     use bevy_app::{App, TaskPoolPlugin, Update};
@@ -1763,10 +1790,245 @@ mod tests {
         app.world_mut().run_schedule(Update);
     }
 
+    /// Sends the first line of the file as a partial [`CoolText`], then loads the file named on
+    /// the second line to produce the final one.
+    struct StreamingTextLoader;
+
+    impl AssetLoader for StreamingTextLoader {
+        type Asset = CoolText;
+
+        type Settings = ();
+
+        type Error = std::io::Error;
+
+        async fn load(
+            &self,
+            reader: &mut dyn Reader,
+            _settings: &Self::Settings,
+            load_context: &mut LoadContext<'_>,
+        ) -> Result<Self::Asset, Self::Error> {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let text = String::from_utf8(bytes).unwrap();
+            let (low, high_path) = text.split_once('\n').unwrap();
+            load_context.send_partial(CoolText {
+                text: low.to_string(),
+                ..Default::default()
+            });
+            let high = load_context
+                .read_asset_bytes(high_path.to_string())
+                .await
+                .map_err(std::io::Error::other)?;
+            Ok(CoolText {
+                text: String::from_utf8(high).unwrap(),
+                ..Default::default()
+            })
+        }
+
+        fn extensions(&self) -> &[&str] {
+            &["streamed"]
+        }
+    }
+
+    #[test]
+    fn partial_loads() {
+        // The particular usage of GatedReader in this test will cause deadlocking if running single-threaded
+        #[cfg(not(feature = "multi_threaded"))]
+        panic!("This test requires the \"multi_threaded\" feature, otherwise it will deadlock.\ncargo test --package bevy_asset --features multi_threaded");
+
+        let dir = Dir::default();
+        dir.insert_asset_text(Path::new("a.streamed"), "low\nhigh.txt");
+        dir.insert_asset_text(Path::new("high.txt"), "high");
+
+        let (mut app, gate_opener) = test_app(dir);
+        app.init_asset::<CoolText>()
+            .register_asset_loader(StreamingTextLoader);
+        let asset_server = app.world().resource::<AssetServer>().clone();
+        let handle: Handle<CoolText> = asset_server.load("a.streamed");
+
+        gate_opener.open("a.streamed");
+        run_app_until(&mut app, |world| {
+            let text = get::<CoolText>(world, handle.id())?;
+            assert_eq!(text.text, "low");
+            Some(())
+        });
+        assert_eq!(asset_server.partial_load_stage(&handle), Some(1));
+        assert!(asset_server.load_state(&handle).is_loading());
+
+        gate_opener.open("high.txt");
+        run_app_until(&mut app, |world| {
+            let text = get::<CoolText>(world, handle.id())?;
+            (text.text == "high").then_some(())
+        });
+        assert_eq!(asset_server.partial_load_stage(&handle), None);
+        assert!(asset_server.load_state(&handle).is_loaded());
+    }
+
+    impl AssetMemoryUsage for CoolText {
+        fn memory_usage(&self) -> usize {
+            self.text.len()
+        }
+    }
+
+    #[test]
+    fn memory_budget_evicts_unreferenced_assets() {
+        // The particular usage of GatedReader in this test will cause deadlocking if running single-threaded
+        #[cfg(not(feature = "multi_threaded"))]
+        panic!("This test requires the \"multi_threaded\" feature, otherwise it will deadlock.\ncargo test --package bevy_asset --features multi_threaded");
+
+        let dir = Dir::default();
+        for (path, text) in [("a.cool.ron", "aaaaaa"), ("b.cool.ron", "bbbbbb")] {
+            dir.insert_asset_text(
+                Path::new(path),
+                &format!(
+                    "(text: \"{text}\", dependencies: [], embedded_dependencies: [], sub_texts: [])"
+                ),
+            );
+        }
+
+        let (mut app, gate_opener) = test_app(dir);
+        app.init_asset::<CoolText>()
+            .register_asset_loader(CoolTextLoader)
+            .set_asset_memory_budget::<CoolText>(10);
+        let asset_server = app.world().resource::<AssetServer>().clone();
+        let a: Handle<CoolText> = asset_server.load("a.cool.ron");
+        let b: Handle<CoolText> = asset_server.load("b.cool.ron");
+        let (a_id, b_id) = (a.id(), b.id());
+        gate_opener.open("a.cool.ron");
+        gate_opener.open("b.cool.ron");
+        run_app_until(&mut app, |world| {
+            (world.resource::<AssetBudget<CoolText>>().usage() == 12).then_some(())
+        });
+        // Both assets are in use, so nothing can be evicted.
+        assert!(app
+            .world()
+            .resource::<AssetBudget<CoolText>>()
+            .is_over_budget());
+
+        drop(a);
+        run_app_until(&mut app, |world| {
+            (!world.resource::<Assets<CoolText>>().contains(a_id)).then_some(())
+        });
+        let budget = app.world().resource::<AssetBudget<CoolText>>();
+        assert_eq!(budget.usage(), 6);
+        assert!(!budget.is_over_budget());
+
+        // "b" fits in the budget, so it stays loaded after its handle is dropped.
+        drop(b);
+        for _ in 0..10 {
+            app.update();
+        }
+        assert!(app.world().resource::<Assets<CoolText>>().contains(b_id));
+        assert_eq!(
+            app.world()
+                .resource::<AssetBudget<CoolText>>()
+                .unreferenced_len(),
+            1
+        );
+        let b: Handle<CoolText> = asset_server.load("b.cool.ron");
+        assert_eq!(b.id(), b_id);
+        assert!(asset_server.is_loaded(&b));
+    }
+
     // validate the Asset derive macro for various asset types
     #[derive(Asset, TypePath)]
     pub struct TestAsset;
 
+    #[test]
+    fn reload_dependents_of_modified_asset() {
+        let dir = Dir::default();
+        let a_path = "a.cool.ron";
+        let b_path = "b.cool.ron";
+        let cool_text = |text: &str, dependencies: &str| {
+            format!(
+                "(text: {text:?}, dependencies: [{dependencies}], embedded_dependencies: [], sub_texts: [])"
+            )
+        };
+        dir.insert_asset_text(Path::new(a_path), &cool_text("a", "\"b.cool.ron\""));
+        dir.insert_asset_text(Path::new(b_path), &cool_text("b", ""));
+
+        struct TestWatcher;
+        impl crate::io::AssetWatcher for TestWatcher {}
+
+        let event_sender = Arc::new(std::sync::Mutex::new(None));
+        let watcher_sender = event_sender.clone();
+        let mut app = App::new();
+        let reader_dir = dir.clone();
+        app.register_asset_source(
+            AssetSourceId::Default,
+            AssetSource::build()
+                .with_reader(move || {
+                    Box::new(MemoryAssetReader {
+                        root: reader_dir.clone(),
+                    })
+                })
+                .with_watcher(move |sender| {
+                    *watcher_sender.lock().unwrap() = Some(sender);
+                    Some(Box::new(TestWatcher))
+                }),
+        )
+        .add_plugins((
+            TaskPoolPlugin::default(),
+            LogPlugin::default(),
+            AssetPlugin {
+                watch_for_changes_override: Some(true),
+                ..Default::default()
+            },
+        ))
+        .init_asset::<CoolText>()
+        .init_asset::<SubText>()
+        .init_resource::<StoredEvents>()
+        .register_asset_loader(CoolTextLoader)
+        .add_systems(Update, store_asset_events);
+
+        let asset_server = app.world().resource::<AssetServer>().clone();
+        let handle: Handle<CoolText> = asset_server.load(a_path);
+        let a_id = handle.id();
+        run_app_until(&mut app, |_| {
+            asset_server.is_loaded_with_dependencies(a_id).then_some(())
+        });
+
+        // The reverse index records that "a" holds a handle to "b".
+        {
+            let infos = asset_server.data.infos.read();
+            let dependents = infos.dependents.get(&AssetPath::from(b_path)).unwrap();
+            assert!(dependents.contains(&AssetPath::from(a_path)));
+            assert!(!infos.dependents.contains_key(&AssetPath::from(a_path)));
+        }
+
+        // Modifying "b" reloads "a" as well.
+        app.world_mut().resource_mut::<StoredEvents>().0.clear();
+        dir.insert_asset_text(Path::new(b_path), &cool_text("b2", ""));
+        event_sender
+            .lock()
+            .unwrap()
+            .as_ref()
+            .expect("the watcher should have been created")
+            .send(crate::io::AssetSourceEvent::ModifiedAsset(b_path.into()))
+            .unwrap();
+        run_app_until(&mut app, |world| {
+            world
+                .resource::<StoredEvents>()
+                .0
+                .contains(&AssetEvent::Modified { id: a_id })
+                .then_some(())
+        });
+        let b_id = get::<CoolText>(app.world(), a_id).unwrap().dependencies[0].id();
+        run_app_until(&mut app, |world| {
+            (get::<CoolText>(world, b_id)?.text == "b2").then_some(())
+        });
+
+        // Freeing "a" removes it from the reverse index.
+        drop(handle);
+        app.update();
+        app.update();
+        let infos = asset_server.data.infos.read();
+        assert!(infos
+            .dependents
+            .get(&AssetPath::from(b_path))
+            .is_none_or(|dependents| dependents.is_empty()));
+    }
+
     #[derive(Asset, TypePath)]
     #[expect(
         dead_code,
@@ -3,6 +3,7 @@ use crate::{
     loader_builders::{Deferred, NestedLoader, StaticTyped},
     meta::{AssetHash, AssetMeta, AssetMetaDyn, ProcessedInfoMinimal, Settings},
     path::AssetPath,
+    server::InternalAssetEvent,
     Asset, AssetLoadError, AssetServer, AssetServerMode, Assets, Handle, UntypedAssetId,
     UntypedHandle,
 };
@@ -454,6 +455,50 @@ impl<'a> LoadContext<'a> {
         }
     }
 
+    /// Makes `asset` available as a partially loaded version of the asset being loaded, before the
+    /// loader returns the final version.
+    ///
+    /// This lets loaders of large assets yield a lower quality version first, such as a mesh with
+    /// fewer vertices or a texture without its largest mips, and progressively refine it by
+    /// calling this again as more data arrives. Each version is inserted in the [`Assets`]
+    /// collection, which emits [`AssetEvent::Added`](crate::AssetEvent::Added) or
+    /// [`AssetEvent::Modified`](crate::AssetEvent::Modified) so that the rendering can swap it in,
+    /// and an [`AssetPartiallyLoadedEvent`](crate::AssetPartiallyLoadedEvent) is sent. The asset
+    /// keeps the [`LoadState::Loading`](crate::LoadState::Loading) state until the final version
+    /// is loaded.
+    ///
+    /// This does nothing if `A` isn't the type of an asset that was requested for this path, for
+    /// example when the loader is run by the [`AssetProcessor`](crate::processor::AssetProcessor)
+    /// or by an immediate nested load.
+    pub fn send_partial<A: Asset>(&self, asset: A) {
+        self.send_partial_for_path(self.asset_path.clone(), asset);
+    }
+
+    /// Makes `asset` available as a partially loaded version of the labeled asset with the given
+    /// `label`, before it's added with [`LoadContext::add_labeled_asset`] or similar.
+    ///
+    /// See [`LoadContext::send_partial`] for more information.
+    pub fn send_labeled_partial<A: Asset>(&self, label: impl Into<CowArc<'static, str>>, asset: A) {
+        self.send_partial_for_path(self.asset_path.clone().with_label(label), asset);
+    }
+
+    fn send_partial_for_path<A: Asset>(&self, path: AssetPath<'static>, asset: A) {
+        let Some(handle) = self
+            .asset_server
+            .data
+            .infos
+            .read()
+            .get_path_and_type_id_handle(&path, TypeId::of::<A>())
+        else {
+            return;
+        };
+        self.asset_server
+            .send_asset_event(InternalAssetEvent::PartiallyLoaded {
+                id: handle.id(),
+                value: Box::new(asset),
+            });
+    }
+
     /// Gets the source path for this load context.
     pub fn path(&self) -> &Path {
         self.asset_path.path()
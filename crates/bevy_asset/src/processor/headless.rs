@@ -0,0 +1,232 @@
+use crate::{processor::AssetProcessor, AssetMode, AssetPlugin};
+use bevy_app::{App, AppExit, Plugin, PluginsState};
+use thiserror::Error;
+use tracing::{error, info};
+
+/// The options of a headless asset processing run, usually parsed from the command line with
+/// [`ProcessAssetsArgs::with_args`].
+///
+/// Use [`ProcessAssetsArgs::asset_plugin`] to configure the [`AssetPlugin`] of the app, and
+/// [`ProcessAssetsArgs::plugin`] to process the assets instead of running the app.
+#[derive(Clone, Debug)]
+pub struct ProcessAssetsArgs {
+    /// The folder of the unprocessed assets.
+    ///
+    /// Defaults to `assets`, and is set with `--assets <path>`.
+    pub file_path: String,
+    /// The folder that the processed assets of each platform are written to a subfolder of.
+    ///
+    /// Defaults to `imported_assets`, and is set with `--output <path>`.
+    pub processed_root: String,
+    /// The platform to process the assets for, which names the subfolder of
+    /// [`ProcessAssetsArgs::processed_root`] that the processed assets are written to.
+    ///
+    /// Processors that depend on the platform, for example to pick a texture compression format,
+    /// should be registered with the settings for this platform.
+    ///
+    /// Defaults to `Default`, and is set with `--platform <name>`.
+    pub platform: String,
+    /// The names of the processors to run, as accepted by [`AssetProcessor::select_processors`].
+    /// All the processors run if this is empty.
+    ///
+    /// Set with `--processor <name>`, which can be repeated.
+    pub processors: Vec<String>,
+}
+
+impl Default for ProcessAssetsArgs {
+    fn default() -> Self {
+        Self {
+            file_path: AssetPlugin::DEFAULT_UNPROCESSED_FILE_PATH.to_string(),
+            processed_root: "imported_assets".to_string(),
+            platform: "Default".to_string(),
+            processors: Vec::new(),
+        }
+    }
+}
+
+impl ProcessAssetsArgs {
+    /// The description of the command line arguments read by [`ProcessAssetsArgs::with_args`].
+    pub const USAGE: &'static str = "\
+Options:
+    --assets <path>       the folder of the unprocessed assets
+    --output <path>       the folder to write the processed assets of each platform to
+    --platform <name>     the platform to process the assets for
+    --processor <name>    only run this processor, can be repeated";
+
+    /// Overrides these options with the ones given in `args`, such as
+    /// `std::env::args().skip(1)`. See [`ProcessAssetsArgs::USAGE`] for the arguments.
+    pub fn with_args(
+        mut self,
+        args: impl IntoIterator<Item = String>,
+    ) -> Result<Self, ProcessAssetsArgsError> {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if !matches!(
+                arg.as_str(),
+                "--assets" | "--output" | "--platform" | "--processor"
+            ) {
+                return Err(ProcessAssetsArgsError::UnknownArgument(arg));
+            }
+            let Some(value) = args.next() else {
+                return Err(ProcessAssetsArgsError::MissingValue(arg));
+            };
+            match arg.as_str() {
+                "--assets" => self.file_path = value,
+                "--output" => self.processed_root = value,
+                "--platform" => self.platform = value,
+                _ => self.processors.push(value),
+            }
+        }
+        Ok(self)
+    }
+
+    /// The folder that the processed assets of [`ProcessAssetsArgs::platform`] are written to.
+    pub fn processed_file_path(&self) -> String {
+        format!("{}/{}", self.processed_root, self.platform)
+    }
+
+    /// An [`AssetPlugin`] that processes the assets of [`ProcessAssetsArgs::file_path`] into
+    /// [`ProcessAssetsArgs::processed_file_path`], without watching for changes.
+    pub fn asset_plugin(&self) -> AssetPlugin {
+        AssetPlugin {
+            file_path: self.file_path.clone(),
+            processed_file_path: self.processed_file_path(),
+            watch_for_changes_override: Some(false),
+            mode: AssetMode::Processed,
+            ..Default::default()
+        }
+    }
+
+    /// A [`ProcessAssetsPlugin`] that runs the processors of [`ProcessAssetsArgs::processors`].
+    pub fn plugin(&self) -> ProcessAssetsPlugin {
+        ProcessAssetsPlugin {
+            processors: self.processors.clone(),
+        }
+    }
+}
+
+/// An error returned by [`ProcessAssetsArgs::with_args`].
+#[derive(Error, Debug)]
+pub enum ProcessAssetsArgsError {
+    #[error("Unknown argument `{0}`")]
+    UnknownArgument(String),
+    #[error("Missing the value of `{0}`")]
+    MissingValue(String),
+}
+
+/// Makes the [`App`] process its assets once and exit, instead of running.
+///
+/// The [`AssetProcessor`] processes all the assets of the processed asset sources with the
+/// processors registered by the plugins of the app, without opening a window or rendering, so
+/// assets can be baked by build pipelines on machines without a GPU or a display. The app exits
+/// with [`AppExit::error`] if an asset fails to be processed.
+///
+/// This requires the [`AssetPlugin`] to use [`AssetMode::Processed`] and the `asset_processor`
+/// feature, and a [`TaskPoolPlugin`](bevy_app::TaskPoolPlugin). It replaces the runner of the
+/// app, so it must be added after the plugins that set a runner, such as the
+/// `ScheduleRunnerPlugin` or the `WinitPlugin`.
+///
+/// ```no_run
+/// # use bevy_app::{prelude::*, TaskPoolPlugin};
+/// # use bevy_asset::processor::ProcessAssetsArgs;
+/// fn main() -> AppExit {
+///     let args = match ProcessAssetsArgs::default().with_args(std::env::args().skip(1)) {
+///         Ok(args) => args,
+///         Err(err) => {
+///             eprintln!("{err}\n{}", ProcessAssetsArgs::USAGE);
+///             return AppExit::error();
+///         }
+///     };
+///     App::new()
+///         .add_plugins((TaskPoolPlugin::default(), args.asset_plugin()))
+///         // Add the plugins that register asset loaders and processors here.
+///         .add_plugins(args.plugin())
+///         .run()
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ProcessAssetsPlugin {
+    /// The names of the processors to run, as accepted by [`AssetProcessor::select_processors`].
+    /// All the processors run if this is empty.
+    pub processors: Vec<String>,
+}
+
+impl Plugin for ProcessAssetsPlugin {
+    fn build(&self, app: &mut App) {
+        let processors = self.processors.clone();
+        app.set_runner(move |app| process_assets(app, &processors));
+    }
+}
+
+fn process_assets(mut app: App, processors: &[String]) -> AppExit {
+    while app.plugins_state() == PluginsState::Adding {
+        bevy_tasks::tick_global_task_pools_on_main_thread();
+    }
+    app.finish();
+    app.cleanup();
+
+    let Some(processor) = app.world().get_resource::<AssetProcessor>().cloned() else {
+        error!(
+            "Can't process assets: the AssetPlugin must use AssetMode::Processed, and the \
+             `asset_processor` feature must be enabled"
+        );
+        return AppExit::error();
+    };
+    if let Err(err) = processor.select_processors(processors.iter().map(String::as_str)) {
+        error!("Can't process assets: {err}");
+        return AppExit::error();
+    }
+
+    processor.process_assets();
+
+    let failed = bevy_tasks::block_on(processor.failed_assets());
+    if failed.is_empty() {
+        info!("Processed all assets");
+        AppExit::Success
+    } else {
+        error!("Failed to process {} asset(s):", failed.len());
+        for path in failed {
+            error!("    {path}");
+        }
+        AppExit::error()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ProcessAssetsArgs, ProcessAssetsArgsError};
+
+    fn args(args: &[&str]) -> Result<ProcessAssetsArgs, ProcessAssetsArgsError> {
+        ProcessAssetsArgs::default().with_args(args.iter().map(ToString::to_string))
+    }
+
+    #[test]
+    fn parse_args() {
+        let args = args(&[
+            "--platform",
+            "Android",
+            "--processor",
+            "A",
+            "--output",
+            "baked",
+            "--processor",
+            "B",
+        ])
+        .unwrap();
+        assert_eq!(args.file_path, "assets");
+        assert_eq!(args.processed_file_path(), "baked/Android");
+        assert_eq!(args.processors, ["A", "B"]);
+    }
+
+    #[test]
+    fn parse_invalid_args() {
+        assert!(matches!(
+            args(&["--platform"]),
+            Err(ProcessAssetsArgsError::MissingValue(arg)) if arg == "--platform"
+        ));
+        assert!(matches!(
+            args(&["--verbose"]),
+            Err(ProcessAssetsArgsError::UnknownArgument(arg)) if arg == "--verbose"
+        ));
+    }
+}
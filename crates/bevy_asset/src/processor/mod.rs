@@ -37,9 +37,13 @@
 //!
 //! In most cases, [`LoadTransformAndSave`] should be sufficient.
 
+#[cfg(all(not(target_arch = "wasm32"), feature = "multi_threaded"))]
+mod headless;
 mod log;
 mod process;
 
+#[cfg(all(not(target_arch = "wasm32"), feature = "multi_threaded"))]
+pub use headless::*;
 pub use log::*;
 pub use process::*;
 
@@ -62,6 +66,7 @@ use bevy_ecs::prelude::*;
 use bevy_tasks::ConditionalSendFuture;
 use bevy_tasks::IoTaskPool;
 use bevy_utils::{HashMap, HashSet};
+use disqualified::ShortName;
 use futures_io::ErrorKind;
 use futures_lite::{AsyncReadExt, AsyncWriteExt, StreamExt};
 use parking_lot::RwLock;
@@ -99,6 +104,8 @@ pub struct AssetProcessorData {
     processors: RwLock<HashMap<&'static str, Arc<dyn ErasedProcessor>>>,
     /// Default processors for file extensions
     default_processors: RwLock<HashMap<Box<str>, &'static str>>,
+    /// The processors selected with [`AssetProcessor::select_processors`], if any.
+    selected_processors: RwLock<Option<Vec<Arc<dyn ErasedProcessor>>>>,
     state: async_lock::RwLock<ProcessorState>,
     sources: AssetSources,
     initialized_sender: async_broadcast::Sender<()>,
@@ -547,6 +554,61 @@ impl AssetProcessor {
         processors.get(processor_type_name).cloned()
     }
 
+    /// Only processes the assets that are processed by one of the given processors, and leaves
+    /// the other assets as they are in the processed [`AssetSource`]s. Assets that are only
+    /// copied to the processed [`AssetSource`]s, without a processor, are always copied.
+    ///
+    /// An asset whose processor isn't selected keeps the output of a previous run: it's
+    /// [`ProcessStatus::Processed`] if there is one, and [`ProcessStatus::NonExistent`] otherwise,
+    /// so that assets depending on it fail to process instead of waiting for it.
+    ///
+    /// Each name is either the full type name of a [registered](AssetProcessor::register_processor)
+    /// processor, or its name without module paths, such as
+    /// `LoadTransformAndSave<ImageLoader, CompressImage, ImageSaver>`. An empty list of names
+    /// selects all the processors again.
+    pub fn select_processors<'a>(
+        &self,
+        names: impl IntoIterator<Item = &'a str>,
+    ) -> Result<(), UnknownProcessorError> {
+        let processors = self.data.processors.read();
+        let mut selected = Vec::new();
+        for name in names {
+            let processor = processors
+                .iter()
+                .find(|(type_name, _)| {
+                    **type_name == *name || ShortName(type_name).to_string() == name
+                })
+                .map(|(_, processor)| processor.clone())
+                .ok_or_else(|| UnknownProcessorError(name.to_string()))?;
+            selected.push(processor);
+        }
+        *self.data.selected_processors.write() = (!selected.is_empty()).then_some(selected);
+        Ok(())
+    }
+
+    /// Returns whether assets processed by `processor`, or just copied if it's [`None`], should be
+    /// processed, given the [selected processors](AssetProcessor::select_processors).
+    fn is_selected(&self, processor: Option<&Arc<dyn ErasedProcessor>>) -> bool {
+        match (&*self.data.selected_processors.read(), processor) {
+            (None, _) => true,
+            (Some(selected), Some(processor)) => selected
+                .iter()
+                .any(|selected| Arc::ptr_eq(selected, processor)),
+            (Some(_), None) => true,
+        }
+    }
+
+    /// Returns the paths of the assets that failed to be processed.
+    pub async fn failed_assets(&self) -> Vec<AssetPath<'static>> {
+        let infos = self.data.asset_infos.read().await;
+        infos
+            .infos
+            .iter()
+            .filter(|(_, info)| info.status == Some(ProcessStatus::Failed))
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
     /// Populates the initial view of each asset by scanning the unprocessed and processed asset folders.
     /// This info will later be used to determine whether or not to re-process an asset
     ///
@@ -813,6 +875,10 @@ impl AssetProcessor {
             }
         };
 
+        if !self.is_selected(processor.as_ref()) {
+            return Ok(ProcessResult::SkippedNotSelected);
+        }
+
         let processed_writer = source.processed_writer()?;
 
         let mut asset_bytes = Vec::new();
@@ -1026,6 +1092,7 @@ impl AssetProcessorData {
             processors: Default::default(),
             asset_infos: Default::default(),
             default_processors: Default::default(),
+            selected_processors: Default::default(),
         }
     }
 
@@ -1120,6 +1187,8 @@ impl<T: Process> Process for InstrumentedAssetProcessor<T> {
 pub enum ProcessResult {
     Processed(ProcessedInfo),
     SkippedNotChanged,
+    /// The asset's processor isn't [selected](AssetProcessor::select_processors).
+    SkippedNotSelected,
     Ignored,
 }
 
@@ -1269,6 +1338,18 @@ impl ProcessorAssetInfos {
                 // "block until first pass finished" mode
                 info.update_status(ProcessStatus::Processed).await;
             }
+            Ok(ProcessResult::SkippedNotSelected) => {
+                debug!("Skipping processing (not selected) \"{}\"", asset_path);
+                let info = self.get_or_insert(asset_path);
+                // Keep the output of a previous run, if any. Otherwise there's nothing to load, and
+                // the assets depending on this one must not wait for it.
+                let status = if info.processed_info.is_some() {
+                    ProcessStatus::Processed
+                } else {
+                    ProcessStatus::NonExistent
+                };
+                info.update_status(status).await;
+            }
             Ok(ProcessResult::Ignored) => {
                 debug!("Skipping processing (ignored) \"{}\"", asset_path);
             }
@@ -1411,6 +1492,12 @@ pub enum ProcessorState {
     Finished,
 }
 
+/// An error returned by [`AssetProcessor::select_processors`] when no registered processor has
+/// the given name.
+#[derive(Error, Debug)]
+#[error("No asset processor named `{0}` is registered")]
+pub struct UnknownProcessorError(pub String);
+
 /// An error that occurs when initializing the [`AssetProcessor`].
 #[derive(Error, Debug)]
 pub enum InitializeError {
@@ -1421,3 +1508,134 @@ pub enum InitializeError {
     #[error("Failed to validate asset log: {0}")]
     ValidateLogError(#[from] ValidateLogError),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AssetProcessor, Process, ProcessContext, ProcessError, ProcessResult, ProcessStatus,
+        ProcessorState,
+    };
+    use crate::{
+        io::{
+            memory::{Dir, MemoryAssetReader},
+            AssetSource, AssetSourceBuilders, AssetSourceId, Writer,
+        },
+        meta::{AssetMeta, ProcessedInfo},
+        AssetPath,
+    };
+    use bevy_tasks::block_on;
+    use std::path::Path;
+
+    struct TestProcessor<const ID: u8>;
+
+    impl<const ID: u8> Process for TestProcessor<ID> {
+        type Settings = ();
+        type OutputLoader = ();
+
+        async fn process(
+            &self,
+            _context: &mut ProcessContext<'_>,
+            _meta: AssetMeta<(), Self>,
+            _writer: &mut Writer,
+        ) -> Result<(), ProcessError> {
+            unreachable!("unselected processors must not run")
+        }
+    }
+
+    fn processor(dir: Dir) -> AssetProcessor {
+        let mut sources = AssetSourceBuilders::default();
+        sources.insert(
+            AssetSourceId::Default,
+            AssetSource::build()
+                .with_reader(move || Box::new(MemoryAssetReader { root: dir.clone() })),
+        );
+        let processor = AssetProcessor::new(&mut sources);
+        processor.register_processor(TestProcessor::<1>);
+        processor.register_processor(TestProcessor::<2>);
+        processor
+    }
+
+    #[test]
+    fn select_processors() {
+        let processor = processor(Dir::default());
+        let first = processor
+            .get_processor(core::any::type_name::<TestProcessor<1>>())
+            .unwrap();
+        let second = processor
+            .get_processor(core::any::type_name::<TestProcessor<2>>())
+            .unwrap();
+        assert!(processor.is_selected(Some(&first)));
+        assert!(processor.is_selected(Some(&second)));
+        assert!(processor.is_selected(None));
+
+        // Processors are selected by their full or short type names.
+        processor.select_processors(["TestProcessor<1>"]).unwrap();
+        assert!(processor.is_selected(Some(&first)));
+        assert!(!processor.is_selected(Some(&second)));
+        // Assets without a processor are always copied.
+        assert!(processor.is_selected(None));
+
+        processor
+            .select_processors([core::any::type_name::<TestProcessor<2>>()])
+            .unwrap();
+        assert!(!processor.is_selected(Some(&first)));
+        assert!(processor.is_selected(Some(&second)));
+
+        assert!(processor.select_processors(["TestProcessor<3>"]).is_err());
+        // A failed selection leaves the previous one in place.
+        assert!(processor.is_selected(Some(&second)));
+
+        processor.select_processors([]).unwrap();
+        assert!(processor.is_selected(Some(&first)));
+        assert!(processor.is_selected(Some(&second)));
+    }
+
+    #[test]
+    fn dependency_with_unselected_processor_does_not_block() {
+        let dir = Dir::default();
+        let dependency = Path::new("dependency.txt");
+        dir.insert_asset_text(dependency, "dependency");
+        dir.insert_meta_text(
+            dependency,
+            &format!(
+                "(meta_format_version: \"1.0\", asset: Process(processor: \"{}\", settings: ()))",
+                core::any::type_name::<TestProcessor<2>>()
+            ),
+        );
+        let processor = processor(dir);
+        processor.select_processors(["TestProcessor<1>"]).unwrap();
+        let path = AssetPath::from_path(dependency).into_owned();
+
+        block_on(async {
+            processor.set_state(ProcessorState::Processing).await;
+            let source = processor.get_source(AssetSourceId::Default).unwrap();
+            let result = processor.process_asset_internal(source, &path).await;
+            assert!(matches!(result, Ok(ProcessResult::SkippedNotSelected)));
+
+            // Without the output of a previous run, there's nothing for dependents to load.
+            processor
+                .data
+                .asset_infos
+                .write()
+                .await
+                .finish_processing(path.clone(), result)
+                .await;
+            assert_eq!(
+                processor.data.wait_until_processed(path.clone()).await,
+                ProcessStatus::NonExistent
+            );
+
+            // With it, dependents load the previous output.
+            let mut infos = processor.data.asset_infos.write().await;
+            infos.get_mut(&path).unwrap().processed_info = Some(ProcessedInfo::default());
+            infos
+                .finish_processing(path.clone(), Ok(ProcessResult::SkippedNotSelected))
+                .await;
+            drop(infos);
+            assert_eq!(
+                processor.data.wait_until_processed(path).await,
+                ProcessStatus::Processed
+            );
+        });
+    }
+}
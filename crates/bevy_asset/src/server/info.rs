@@ -34,11 +34,17 @@ pub(crate) struct AssetInfo {
     ///
     /// [`LoadedAsset`]: crate::loader::LoadedAsset
     loader_dependencies: HashMap<AssetPath<'static>, AssetHash>,
+    /// The paths of the assets this asset holds handles to, without their labels.
+    /// This will only be populated if [`AssetInfos::watching_for_changes`] is set to `true` to
+    /// save memory.
+    dependency_paths: HashSet<AssetPath<'static>>,
     /// The number of handle drops to skip for this asset.
     /// See usage (and comments) in `get_or_create_path_handle` for context.
     handle_drops_to_skip: usize,
     /// List of tasks waiting for this asset to complete loading
     pub(crate) waiting_tasks: Vec<Waker>,
+    /// The number of partially loaded versions of this asset inserted while it's loading.
+    pub(crate) partial_stage: Option<u32>,
 }
 
 impl AssetInfo {
@@ -54,10 +60,12 @@ impl AssetInfo {
             loading_rec_dependencies: HashSet::default(),
             failed_rec_dependencies: HashSet::default(),
             loader_dependencies: HashMap::default(),
+            dependency_paths: HashSet::default(),
             dependents_waiting_on_load: HashSet::default(),
             dependents_waiting_on_recursive_dep_load: HashSet::default(),
             handle_drops_to_skip: 0,
             waiting_tasks: Vec::new(),
+            partial_stage: None,
         }
     }
 }
@@ -72,6 +80,10 @@ pub(crate) struct AssetInfos {
     /// Tracks assets that depend on the "key" asset path inside their asset loaders ("loader dependencies")
     /// This should only be set when watching for changes to avoid unnecessary work.
     pub(crate) loader_dependents: HashMap<AssetPath<'static>, HashSet<AssetPath<'static>>>,
+    /// Tracks assets that hold handles to assets loaded from the "key" asset path ("dependencies"),
+    /// including the labeled assets of that path. The key never has a label, but the dependents do.
+    /// This should only be set when watching for changes to avoid unnecessary work.
+    pub(crate) dependents: HashMap<AssetPath<'static>, HashSet<AssetPath<'static>>>,
     /// Tracks living labeled assets for a given source asset.
     /// This should only be set when watching for changes to avoid unnecessary work.
     pub(crate) living_labeled_assets: HashMap<AssetPath<'static>, HashSet<Box<str>>>,
@@ -370,6 +382,7 @@ impl AssetInfos {
             &mut self.infos,
             &mut self.path_to_id,
             &mut self.loader_dependents,
+            &mut self.dependents,
             &mut self.living_labeled_assets,
             &mut self.pending_tasks,
             self.watching_for_changes,
@@ -391,6 +404,9 @@ impl AssetInfos {
         }
 
         loaded_asset.value.insert(loaded_asset_id, world);
+        if self.watching_for_changes {
+            self.track_dependents(loaded_asset_id, &loaded_asset.dependencies);
+        }
         let mut loading_deps = loaded_asset.dependencies;
         let mut failed_deps = <HashSet<_>>::default();
         let mut dep_error = None;
@@ -492,6 +508,7 @@ impl AssetInfos {
             info.loading_rec_dependencies = loading_rec_deps;
             info.failed_rec_dependencies = failed_rec_deps;
             info.load_state = LoadState::Loaded;
+            info.partial_stage = None;
             info.dep_load_state = dep_load_state;
             info.rec_dep_load_state = rec_dep_load_state.clone();
             if watching_for_changes {
@@ -543,6 +560,43 @@ impl AssetInfos {
         }
     }
 
+    /// Records `loaded_asset_id` as a dependent of the paths of `dependencies`, replacing the
+    /// dependencies recorded by a previous load of the same asset.
+    fn track_dependents(
+        &mut self,
+        loaded_asset_id: UntypedAssetId,
+        dependencies: &HashSet<UntypedAssetId>,
+    ) {
+        let dependency_paths = dependencies
+            .iter()
+            .filter_map(|dep_id| self.infos.get(dep_id)?.path.as_ref())
+            .map(|path| path.without_label().into_owned())
+            .collect::<HashSet<_>>();
+        let Some(info) = self.infos.get_mut(&loaded_asset_id) else {
+            return;
+        };
+        let Some(asset_path) = info.path.clone() else {
+            return;
+        };
+        for old_path in info.dependency_paths.drain() {
+            if let Some(dependents) = self.dependents.get_mut(&old_path) {
+                dependents.remove(&asset_path);
+            }
+        }
+        let own_path = asset_path.without_label();
+        for dependency_path in dependency_paths {
+            // Labeled assets of the same file are reloaded along with it.
+            if dependency_path == own_path {
+                continue;
+            }
+            self.dependents
+                .entry(dependency_path.clone())
+                .or_default()
+                .insert(asset_path.clone());
+            info.dependency_paths.insert(dependency_path);
+        }
+    }
+
     /// Recursively propagates loaded state up the dependency tree.
     fn propagate_loaded_state(
         infos: &mut AssetInfos,
@@ -614,6 +668,7 @@ impl AssetInfos {
                 return;
             };
             info.load_state = LoadState::Failed(error.clone());
+            info.partial_stage = None;
             info.dep_load_state = DependencyLoadState::Failed(error.clone());
             info.rec_dep_load_state = RecursiveDependencyLoadState::Failed(error.clone());
             for waker in info.waiting_tasks.drain(..) {
@@ -644,6 +699,7 @@ impl AssetInfos {
     fn remove_dependents_and_labels(
         info: &AssetInfo,
         loader_dependents: &mut HashMap<AssetPath<'static>, HashSet<AssetPath<'static>>>,
+        dependents: &mut HashMap<AssetPath<'static>, HashSet<AssetPath<'static>>>,
         path: &AssetPath<'static>,
         living_labeled_assets: &mut HashMap<AssetPath<'static>, HashSet<Box<str>>>,
     ) {
@@ -653,6 +709,15 @@ impl AssetInfos {
             }
         }
 
+        for dependency_path in &info.dependency_paths {
+            if let Entry::Occupied(mut entry) = dependents.entry(dependency_path.clone()) {
+                entry.get_mut().remove(path);
+                if entry.get().is_empty() {
+                    entry.remove();
+                }
+            }
+        }
+
         let Some(label) = path.label() else {
             return;
         };
@@ -674,6 +739,7 @@ impl AssetInfos {
         infos: &mut HashMap<UntypedAssetId, AssetInfo>,
         path_to_id: &mut HashMap<AssetPath<'static>, TypeIdMap<UntypedAssetId>>,
         loader_dependents: &mut HashMap<AssetPath<'static>, HashSet<AssetPath<'static>>>,
+        dependents: &mut HashMap<AssetPath<'static>, HashSet<AssetPath<'static>>>,
         living_labeled_assets: &mut HashMap<AssetPath<'static>, HashSet<Box<str>>>,
         pending_tasks: &mut HashMap<UntypedAssetId, Task<()>>,
         watching_for_changes: bool,
@@ -703,6 +769,7 @@ impl AssetInfos {
             Self::remove_dependents_and_labels(
                 &info,
                 loader_dependents,
+                dependents,
                 path,
                 living_labeled_assets,
             );
@@ -733,6 +800,7 @@ impl AssetInfos {
                         &mut self.infos,
                         &mut self.path_to_id,
                         &mut self.loader_dependents,
+                        &mut self.dependents,
                         &mut self.living_labeled_assets,
                         &mut self.pending_tasks,
                         self.watching_for_changes,
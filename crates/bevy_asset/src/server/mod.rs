@@ -7,15 +7,15 @@ use crate::{
         AssetReaderError, AssetSource, AssetSourceEvent, AssetSourceId, AssetSources,
         ErasedAssetReader, MissingAssetSourceError, MissingProcessedAssetReaderError, Reader,
     },
-    loader::{AssetLoader, ErasedAssetLoader, LoadContext, LoadedAsset},
+    loader::{AssetContainer, AssetLoader, ErasedAssetLoader, LoadContext, LoadedAsset},
     meta::{
         loader_settings_meta_transform, AssetActionMinimal, AssetMetaDyn, AssetMetaMinimal,
         MetaTransform, Settings,
     },
     path::AssetPath,
-    Asset, AssetEvent, AssetHandleProvider, AssetId, AssetLoadFailedEvent, AssetMetaCheck, Assets,
-    DeserializeMetaError, ErasedLoadedAsset, Handle, LoadedUntypedAsset, UntypedAssetId,
-    UntypedAssetLoadFailedEvent, UntypedHandle,
+    Asset, AssetEvent, AssetHandleProvider, AssetId, AssetLoadFailedEvent, AssetMetaCheck,
+    AssetPartiallyLoadedEvent, Assets, DeserializeMetaError, ErasedLoadedAsset, Handle,
+    LoadedUntypedAsset, UntypedAssetId, UntypedAssetLoadFailedEvent, UntypedHandle,
 };
 use alloc::sync::Arc;
 use atomicow::CowArc;
@@ -383,6 +383,20 @@ impl AssetServer {
         handle
     }
 
+    /// Begins loading an [`Asset`] of the type with the given [`TypeId`] stored at `path`.
+    ///
+    /// This is the same as [`AssetServer::load`], for asset types that are only known at runtime,
+    /// for example from a [`ReflectHandle`](crate::ReflectHandle). The returned [`UntypedHandle`]
+    /// can be converted to a [`Handle<A>`] with [`UntypedHandle::typed`].
+    #[must_use = "not using the returned strong handle may result in the unexpected release of the asset"]
+    pub fn load_erased<'a>(
+        &self,
+        type_id: TypeId,
+        path: impl Into<AssetPath<'a>>,
+    ) -> UntypedHandle {
+        self.load_erased_with_meta_transform(path, type_id, None, ())
+    }
+
     pub(crate) fn load_erased_with_meta_transform<'a, G: Send + Sync + 'static>(
         &self,
         path: impl Into<AssetPath<'a>>,
@@ -942,7 +956,7 @@ impl AssetServer {
             .detach();
     }
 
-    fn send_asset_event(&self, event: InternalAssetEvent) {
+    pub(crate) fn send_asset_event(&self, event: InternalAssetEvent) {
         self.data.asset_event_sender.send(event).unwrap();
     }
 
@@ -973,6 +987,17 @@ impl AssetServer {
             .map(|i| i.load_state.clone())
     }
 
+    /// Returns the number of partially loaded versions of the asset with the given `id` that
+    /// were made available with [`LoadContext::send_partial`], or [`None`] if there are none or
+    /// if the final version is loaded.
+    pub fn partial_load_stage(&self, id: impl Into<UntypedAssetId>) -> Option<u32> {
+        self.data
+            .infos
+            .read()
+            .get(id.into())
+            .and_then(|info| info.partial_stage)
+    }
+
     /// Retrieves the [`DependencyLoadState`] of a given asset `id`'s dependencies.
     ///
     /// Note that this is only the load state of direct dependencies of the root asset. To get
@@ -1473,6 +1498,7 @@ pub fn handle_internal_asset_events(world: &mut World) {
     world.resource_scope(|world, server: Mut<AssetServer>| {
         let mut infos = server.data.infos.write();
         let mut untyped_failures = vec![];
+        let mut partial_loads = vec![];
         for event in server.data.asset_event_receiver.try_iter() {
             match event {
                 InternalAssetEvent::Loaded { id, loaded_asset } => {
@@ -1495,6 +1521,19 @@ pub fn handle_internal_asset_events(world: &mut World) {
                         }
                     }
                 }
+                InternalAssetEvent::PartiallyLoaded { id, value } => {
+                    // Partial versions arriving after the final version are dropped.
+                    let Some(info) = infos.get_mut(id) else {
+                        continue;
+                    };
+                    if !info.load_state.is_loading() {
+                        continue;
+                    }
+                    let stage = info.partial_stage.map_or(1, |stage| stage + 1);
+                    info.partial_stage = Some(stage);
+                    value.insert(id, world);
+                    partial_loads.push(AssetPartiallyLoadedEvent { id, stage });
+                }
                 InternalAssetEvent::Failed { id, path, error } => {
                     infos.process_asset_fail(id, error.clone());
 
@@ -1518,6 +1557,9 @@ pub fn handle_internal_asset_events(world: &mut World) {
         if !untyped_failures.is_empty() {
             world.send_event_batch(untyped_failures);
         }
+        if !partial_loads.is_empty() {
+            world.send_event_batch(partial_loads);
+        }
 
         fn queue_ancestors(
             asset_path: &AssetPath,
@@ -1526,8 +1568,19 @@ pub fn handle_internal_asset_events(world: &mut World) {
         ) {
             if let Some(dependents) = infos.loader_dependents.get(asset_path) {
                 for dependent in dependents {
-                    paths_to_reload.insert(dependent.to_owned());
-                    queue_ancestors(dependent, infos, paths_to_reload);
+                    if paths_to_reload.insert(dependent.to_owned()) {
+                        queue_ancestors(dependent, infos, paths_to_reload);
+                    }
+                }
+            }
+            // Assets holding handles to the changed asset (or to one of its labeled assets) are
+            // reloaded too. Labeled dependents are reloaded through the file they come from.
+            if let Some(dependents) = infos.dependents.get(&asset_path.without_label()) {
+                for dependent in dependents {
+                    let dependent = dependent.without_label().into_owned();
+                    if paths_to_reload.insert(dependent.clone()) {
+                        queue_ancestors(&dependent, infos, paths_to_reload);
+                    }
                 }
             }
         }
@@ -1609,6 +1662,10 @@ pub(crate) enum InternalAssetEvent {
     LoadedWithDependencies {
         id: UntypedAssetId,
     },
+    PartiallyLoaded {
+        id: UntypedAssetId,
+        value: Box<dyn AssetContainer>,
+    },
     Failed {
         id: UntypedAssetId,
         path: AssetPath<'static>,
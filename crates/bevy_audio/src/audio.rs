@@ -48,7 +48,9 @@ pub struct PlaybackSettings {
     pub muted: bool,
     /// Enables spatial audio for this source.
     ///
-    /// See also: [`SpatialListener`].
+    /// See also: [`SpatialListener`], and [`SpatialAttenuation`](crate::SpatialAttenuation) and
+    /// [`AudioOcclusion`](crate::AudioOcclusion) to filter the sound with its distance and
+    /// occlusion.
     ///
     /// Note: Bevy does not currently support HRTF or any other high-quality 3D sound rendering
     /// features. Spatial audio is implemented via simple left-right stereo panning.
@@ -1,9 +1,10 @@
 use crate::{
+    effects::EffectChainSource, AudioBus, AudioEffectChain, AudioMixer, AudioOcclusion,
     AudioPlayer, Decodable, DefaultSpatialScale, GlobalVolume, PlaybackMode, PlaybackSettings,
-    SpatialAudioSink, SpatialListener,
+    SpatialAttenuation, SpatialAudioSink, SpatialListener,
 };
 use bevy_asset::{Asset, Assets};
-use bevy_ecs::{prelude::*, system::SystemParam};
+use bevy_ecs::{entity::EntityHashSet, prelude::*, system::SystemParam};
 use bevy_hierarchy::DespawnRecursiveExt;
 use bevy_math::Vec3;
 use bevy_transform::prelude::GlobalTransform;
@@ -98,12 +99,16 @@ pub(crate) fn play_queued_audio_system<Source: Asset + Decodable>(
     audio_output: Res<AudioOutput>,
     audio_sources: Res<Assets<Source>>,
     global_volume: Res<GlobalVolume>,
+    mut mixer: ResMut<AudioMixer>,
     query_nonplaying: Query<
         (
             Entity,
             &AudioPlayer<Source>,
             &PlaybackSettings,
             Option<&GlobalTransform>,
+            Option<&AudioBus>,
+            Option<&AudioEffectChain>,
+            (Option<&SpatialAttenuation>, Option<&AudioOcclusion>),
         ),
         (Without<AudioSink>, Without<SpatialAudioSink>),
     >,
@@ -118,10 +123,26 @@ pub(crate) fn play_queued_audio_system<Source: Asset + Decodable>(
         return;
     };
 
-    for (entity, source_handle, settings, maybe_emitter_transform) in &query_nonplaying {
+    for (
+        entity,
+        source_handle,
+        settings,
+        maybe_emitter_transform,
+        bus,
+        effects,
+        (attenuation, occlusion),
+    ) in &query_nonplaying
+    {
         let Some(audio_source) = audio_sources.get(&source_handle.0) else {
             continue;
         };
+        // The effects of the entity are applied first, then the ones of its bus and the buses it
+        // is routed to.
+        let mut chains: Vec<AudioEffectChain> = effects.cloned().into_iter().collect();
+        if let Some(bus) = bus {
+            mixer.bus_mut(bus.clone());
+            chains.extend(mixer.route(bus));
+        }
         // audio data is available (has loaded), begin playback and insert sink component
         if settings.spatial {
             let (left_ear, right_ear) = ear_positions.get();
@@ -138,15 +159,15 @@ pub(crate) fn play_queued_audio_system<Source: Asset + Decodable>(
             let scale = settings.spatial_scale.unwrap_or(default_spatial_scale.0).0;
 
             let emitter_translation = if let Some(emitter_transform) = maybe_emitter_transform {
-                (emitter_transform.translation() * scale).into()
+                emitter_transform.translation() * scale
             } else {
                 warn!("Spatial AudioPlayer with no GlobalTransform component. Using zero.");
-                Vec3::ZERO.into()
+                Vec3::ZERO
             };
 
             let sink = match SpatialSink::try_new(
                 stream_handle,
-                emitter_translation,
+                emitter_translation.into(),
                 (left_ear * scale).into(),
                 (right_ear * scale).into(),
             ) {
@@ -157,14 +178,19 @@ pub(crate) fn play_queued_audio_system<Source: Asset + Decodable>(
                 }
             };
 
-            match settings.mode {
-                PlaybackMode::Loop => sink.append(audio_source.decoder().repeat_infinite()),
-                PlaybackMode::Once | PlaybackMode::Despawn | PlaybackMode::Remove => {
-                    sink.append(audio_source.decoder());
-                }
-            };
-
             let mut sink = SpatialAudioSink::new(sink);
+            sink.set_spatial(
+                emitter_translation,
+                left_ear * scale,
+                right_ear * scale,
+                attenuation.copied().unwrap_or_default(),
+                occlusion.copied().unwrap_or_default(),
+            );
+
+            // The sound is filtered by its distance and occlusion before going through the buses.
+            chains.insert(usize::from(effects.is_some()), sink.filter.clone());
+            sink.sink
+                .append(playback_source(audio_source, settings.mode, chains));
 
             if settings.muted {
                 sink.mute();
@@ -197,12 +223,7 @@ pub(crate) fn play_queued_audio_system<Source: Asset + Decodable>(
                 }
             };
 
-            match settings.mode {
-                PlaybackMode::Loop => sink.append(audio_source.decoder().repeat_infinite()),
-                PlaybackMode::Once | PlaybackMode::Despawn | PlaybackMode::Remove => {
-                    sink.append(audio_source.decoder());
-                }
-            };
+            sink.append(playback_source(audio_source, settings.mode, chains));
 
             let mut sink = AudioSink::new(sink);
 
@@ -232,6 +253,24 @@ pub(crate) fn play_queued_audio_system<Source: Asset + Decodable>(
     }
 }
 
+/// Builds the source played by the sink of an audio entity, applying the given effect chains.
+fn playback_source<T: Decodable>(
+    audio_source: &T,
+    mode: PlaybackMode,
+    chains: Vec<AudioEffectChain>,
+) -> EffectChainSource<Box<dyn Source<Item = f32> + Send>>
+where
+    f32: rodio::cpal::FromSample<T::DecoderItem>,
+{
+    let source: Box<dyn Source<Item = f32> + Send> = match mode {
+        PlaybackMode::Loop => Box::new(audio_source.looping_decoder().convert_samples()),
+        PlaybackMode::Once | PlaybackMode::Despawn | PlaybackMode::Remove => {
+            Box::new(audio_source.decoder().convert_samples())
+        }
+    };
+    EffectChainSource::new(source, chains)
+}
+
 pub(crate) fn cleanup_finished_audio<T: Decodable + Asset>(
     mut commands: Commands,
     query_nonspatial_despawn: Query<
@@ -288,48 +327,56 @@ pub(crate) fn audio_output_available(audio_output: Res<AudioOutput>) -> bool {
     audio_output.stream_handle.is_some()
 }
 
-/// Updates spatial audio sinks when emitter positions change.
-pub(crate) fn update_emitter_positions(
-    mut emitters: Query<
-        (&GlobalTransform, &SpatialAudioSink, &PlaybackSettings),
-        Or<(Changed<GlobalTransform>, Changed<PlaybackSettings>)>,
-    >,
-    default_spatial_scale: Res<DefaultSpatialScale>,
-) {
-    for (transform, sink, settings) in emitters.iter_mut() {
-        let scale = settings.spatial_scale.unwrap_or(default_spatial_scale.0).0;
-
-        let translation = transform.translation() * scale;
-        sink.set_emitter_position(translation);
-    }
-}
-
-/// Updates spatial audio sink ear positions when spatial listeners change.
-pub(crate) fn update_listener_positions(
-    mut emitters: Query<(&SpatialAudioSink, &PlaybackSettings)>,
+/// Updates the positions and the filtering of spatial audio sinks when their emitter, their
+/// settings or the spatial listeners change.
+pub(crate) fn update_spatial_audio(
+    emitters: Query<(
+        Entity,
+        Ref<GlobalTransform>,
+        &SpatialAudioSink,
+        Ref<PlaybackSettings>,
+        Option<Ref<SpatialAttenuation>>,
+        Option<Ref<AudioOcclusion>>,
+    )>,
     changed_listener: Query<
         (),
         (
-            Or<(
-                Changed<SpatialListener>,
-                Changed<GlobalTransform>,
-                Changed<PlaybackSettings>,
-            )>,
+            Or<(Changed<SpatialListener>, Changed<GlobalTransform>)>,
             With<SpatialListener>,
         ),
     >,
+    mut removed_attenuations: RemovedComponents<SpatialAttenuation>,
+    mut removed_occlusions: RemovedComponents<AudioOcclusion>,
     ear_positions: EarPositions,
     default_spatial_scale: Res<DefaultSpatialScale>,
 ) {
-    if !default_spatial_scale.is_changed() && changed_listener.is_empty() {
-        return;
-    }
+    let listener_changed = default_spatial_scale.is_changed() || !changed_listener.is_empty();
+    let removed: EntityHashSet = removed_attenuations
+        .read()
+        .chain(removed_occlusions.read())
+        .collect();
 
     let (left_ear, right_ear) = ear_positions.get();
 
-    for (sink, settings) in emitters.iter_mut() {
+    for (entity, transform, sink, settings, attenuation, occlusion) in &emitters {
+        let changed = listener_changed
+            || transform.is_changed()
+            || settings.is_changed()
+            || attenuation.as_ref().is_some_and(DetectChanges::is_changed)
+            || occlusion.as_ref().is_some_and(DetectChanges::is_changed)
+            || removed.contains(&entity);
+        if !changed {
+            continue;
+        }
+
         let scale = settings.spatial_scale.unwrap_or(default_spatial_scale.0).0;
 
-        sink.set_ears_position(left_ear * scale, right_ear * scale);
+        sink.set_spatial(
+            transform.translation() * scale,
+            left_ear * scale,
+            right_ear * scale,
+            attenuation.as_deref().copied().unwrap_or_default(),
+            occlusion.as_deref().copied().unwrap_or_default(),
+        );
     }
 }
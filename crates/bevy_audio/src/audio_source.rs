@@ -1,6 +1,7 @@
 use alloc::sync::Arc;
-use bevy_asset::{io::Reader, Asset, AssetLoader, LoadContext};
+use bevy_asset::{io::Reader, Asset, AssetLoader, AssetMemoryUsage, LoadContext};
 use bevy_reflect::TypePath;
+use rodio::Source;
 use std::io::Cursor;
 
 /// A source of audio data
@@ -20,6 +21,12 @@ pub struct AudioSource {
     pub bytes: Arc<[u8]>,
 }
 
+impl AssetMemoryUsage for AudioSource {
+    fn memory_usage(&self) -> usize {
+        self.bytes.len()
+    }
+}
+
 impl AsRef<[u8]> for AudioSource {
     fn as_ref(&self) -> &[u8] {
         &self.bytes
@@ -56,23 +63,26 @@ impl AssetLoader for AudioLoader {
     }
 
     fn extensions(&self) -> &[&str] {
-        &[
-            #[cfg(feature = "mp3")]
-            "mp3",
-            #[cfg(feature = "flac")]
-            "flac",
-            #[cfg(feature = "wav")]
-            "wav",
-            #[cfg(feature = "vorbis")]
-            "oga",
-            #[cfg(feature = "vorbis")]
-            "ogg",
-            #[cfg(feature = "vorbis")]
-            "spx",
-        ]
+        AUDIO_EXTENSIONS
     }
 }
 
+/// The extensions of the audio files that can be decoded with the enabled features.
+pub(crate) const AUDIO_EXTENSIONS: &[&str] = &[
+    #[cfg(feature = "mp3")]
+    "mp3",
+    #[cfg(feature = "flac")]
+    "flac",
+    #[cfg(feature = "wav")]
+    "wav",
+    #[cfg(feature = "vorbis")]
+    "oga",
+    #[cfg(feature = "vorbis")]
+    "ogg",
+    #[cfg(feature = "vorbis")]
+    "spx",
+];
+
 /// A type implementing this trait can be converted to a [`rodio::Source`] type.
 ///
 /// It must be [`Send`] and [`Sync`] in order to be registered.
@@ -92,6 +102,15 @@ pub trait Decodable: Send + Sync + 'static {
 
     /// Build and return a [`Self::Decoder`] of the implementing type
     fn decoder(&self) -> Self::Decoder;
+
+    /// Build and return a source repeating the audio forever, used for
+    /// [`PlaybackMode::Loop`](crate::PlaybackMode::Loop).
+    ///
+    /// By default, the samples of [`decoder`](Self::decoder) are kept in memory as they are played
+    /// and repeated from there.
+    fn looping_decoder(&self) -> Box<dyn Source<Item = Self::DecoderItem> + Send> {
+        Box::new(self.decoder().repeat_infinite())
+    }
 }
 
 impl Decodable for AudioSource {
@@ -0,0 +1,450 @@
+use alloc::sync::Arc;
+use bevy_ecs::prelude::*;
+use bevy_math::ops;
+use bevy_reflect::prelude::*;
+use core::{
+    f32::consts::TAU,
+    sync::atomic::{AtomicU32, Ordering},
+    time::Duration,
+};
+use rodio::{source::SeekError, Source};
+use std::sync::{PoisonError, RwLock};
+
+/// An audio effect of an [`AudioEffectChain`].
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Debug, PartialEq)]
+pub enum AudioEffect {
+    /// Attenuates the frequencies above the cutoff frequency.
+    LowPass {
+        /// The cutoff frequency, in hertz.
+        cutoff_frequency: f32,
+    },
+    /// Sends the sound to a feedback delay, and mixes the delayed sound back in.
+    Reverb {
+        /// The delay between the echoes.
+        delay: Duration,
+        /// The amplitude of each echo relative to the previous one, below `1.0`.
+        feedback: f32,
+        /// The amplitude of the delayed sound mixed into the output.
+        send: f32,
+    },
+    /// Reduces the amplitude of the sound above a threshold.
+    Compressor {
+        /// The amplitude above which the sound is compressed.
+        threshold: f32,
+        /// The ratio by which the amplitude above the threshold is reduced.
+        ratio: f32,
+        /// How fast the compressor reacts to the sound getting louder.
+        attack: Duration,
+        /// How fast the compressor recovers when the sound gets quieter.
+        release: Duration,
+    },
+}
+
+#[derive(Debug)]
+struct EffectChainState {
+    effects: RwLock<Vec<AudioEffect>>,
+    version: AtomicU32,
+    gain: AtomicU32,
+}
+
+/// A chain of [`AudioEffect`]s applied in order to the sound of an audio entity, or of all the
+/// audio entities of a [`MixerBus`](crate::MixerBus).
+///
+/// Insert it on an entity with an [`AudioPlayer`](crate::AudioPlayer) before it starts playing to
+/// apply the effects to its sound. The chain is shared with the audio thread: cloning it returns
+/// a handle to the same chain, and changing its effects from any handle, for example in a system
+/// querying the component, updates the sound that is playing.
+///
+/// Each sound that a chain is applied to is processed by its own copy of the effects, with its
+/// own state.
+#[derive(Component, Clone, Debug)]
+pub struct AudioEffectChain(Arc<EffectChainState>);
+
+impl Default for AudioEffectChain {
+    fn default() -> Self {
+        Self::new([])
+    }
+}
+
+impl AudioEffectChain {
+    /// Creates a chain of the given effects.
+    pub fn new(effects: impl IntoIterator<Item = AudioEffect>) -> Self {
+        Self(Arc::new(EffectChainState {
+            effects: RwLock::new(effects.into_iter().collect()),
+            version: AtomicU32::new(0),
+            gain: AtomicU32::new(1.0f32.to_bits()),
+        }))
+    }
+
+    /// Returns a copy of the effects of the chain.
+    pub fn effects(&self) -> Vec<AudioEffect> {
+        self.0
+            .effects
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Changes the effects of the chain.
+    ///
+    /// Changing the parameters of an effect keeps its state, so that it can be changed every frame
+    /// without artifacts, while adding, removing or reordering effects resets them.
+    pub fn update(&self, f: impl FnOnce(&mut Vec<AudioEffect>)) {
+        f(&mut self
+            .0
+            .effects
+            .write()
+            .unwrap_or_else(PoisonError::into_inner));
+        self.0.version.fetch_add(1, Ordering::Release);
+    }
+
+    /// Replaces the effects of the chain.
+    pub fn set_effects(&self, effects: impl IntoIterator<Item = AudioEffect>) {
+        self.update(|chain| {
+            chain.clear();
+            chain.extend(effects);
+        });
+    }
+
+    /// Adds an effect at the end of the chain.
+    pub fn push(&self, effect: AudioEffect) {
+        self.update(|chain| chain.push(effect));
+    }
+
+    /// Removes all the effects of the chain.
+    pub fn clear(&self) {
+        self.update(Vec::clear);
+    }
+
+    /// Returns the amplitude multiplier applied after the effects.
+    pub(crate) fn gain(&self) -> f32 {
+        f32::from_bits(self.0.gain.load(Ordering::Relaxed))
+    }
+
+    /// Sets the amplitude multiplier applied after the effects.
+    pub(crate) fn set_gain(&self, gain: f32) {
+        self.0.gain.store(gain.to_bits(), Ordering::Relaxed);
+    }
+}
+
+/// The state of an [`AudioEffect`] processing a sound.
+enum EffectProcessor {
+    LowPass {
+        alpha: f32,
+        outputs: Vec<f32>,
+    },
+    Reverb {
+        feedback: f32,
+        send: f32,
+        buffers: Vec<Vec<f32>>,
+        position: usize,
+    },
+    Compressor {
+        threshold: f32,
+        ratio: f32,
+        attack: f32,
+        release: f32,
+        envelopes: Vec<f32>,
+    },
+}
+
+impl EffectProcessor {
+    fn new(effect: &AudioEffect, channels: u16, sample_rate: u32) -> Self {
+        let channels = channels.max(1) as usize;
+        let mut processor = match effect {
+            AudioEffect::LowPass { .. } => Self::LowPass {
+                alpha: 1.0,
+                outputs: vec![0.0; channels],
+            },
+            AudioEffect::Reverb { delay, .. } => {
+                let len = ((delay.as_secs_f32() * sample_rate as f32) as usize).max(1);
+                Self::Reverb {
+                    feedback: 0.0,
+                    send: 0.0,
+                    buffers: vec![vec![0.0; len]; channels],
+                    position: 0,
+                }
+            }
+            AudioEffect::Compressor { .. } => Self::Compressor {
+                threshold: 1.0,
+                ratio: 1.0,
+                attack: 0.0,
+                release: 0.0,
+                envelopes: vec![0.0; channels],
+            },
+        };
+        processor.update(effect, sample_rate);
+        processor
+    }
+
+    /// Updates the parameters of the processor, and returns `false` if it processes another kind
+    /// of effect.
+    fn update(&mut self, effect: &AudioEffect, sample_rate: u32) -> bool {
+        let sample_rate = sample_rate.max(1) as f32;
+        // The coefficient of a one-pole smoothing filter with the given time constant.
+        let smoothing = |time: Duration| {
+            let samples = time.as_secs_f32() * sample_rate;
+            if samples > 0.0 {
+                ops::exp(-1.0 / samples)
+            } else {
+                0.0
+            }
+        };
+        match (self, effect) {
+            (Self::LowPass { alpha, .. }, AudioEffect::LowPass { cutoff_frequency }) => {
+                *alpha = 1.0 - ops::exp(-TAU * cutoff_frequency.max(0.0) / sample_rate);
+                true
+            }
+            (
+                Self::Reverb {
+                    feedback,
+                    send,
+                    buffers,
+                    ..
+                },
+                AudioEffect::Reverb {
+                    delay,
+                    feedback: new_feedback,
+                    send: new_send,
+                },
+            ) => {
+                *feedback = new_feedback.clamp(0.0, 0.99);
+                *send = new_send.max(0.0);
+                let len = ((delay.as_secs_f32() * sample_rate) as usize).max(1);
+                buffers.first().is_some_and(|buffer| buffer.len() == len)
+            }
+            (
+                Self::Compressor {
+                    threshold,
+                    ratio,
+                    attack,
+                    release,
+                    ..
+                },
+                AudioEffect::Compressor {
+                    threshold: new_threshold,
+                    ratio: new_ratio,
+                    attack: new_attack,
+                    release: new_release,
+                },
+            ) => {
+                *threshold = new_threshold.max(f32::EPSILON);
+                *ratio = new_ratio.max(1.0);
+                *attack = smoothing(*new_attack);
+                *release = smoothing(*new_release);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn process(&mut self, sample: f32, channel: usize) -> f32 {
+        match self {
+            Self::LowPass { alpha, outputs } => {
+                let output = &mut outputs[channel];
+                *output += *alpha * (sample - *output);
+                *output
+            }
+            Self::Reverb {
+                feedback,
+                send,
+                buffers,
+                position,
+            } => {
+                let buffer = &mut buffers[channel];
+                let index = *position % buffer.len();
+                let delayed = buffer[index];
+                buffer[index] = sample + delayed * *feedback;
+                // The position moves once all the channels of a frame were processed.
+                if channel + 1 == buffers.len() {
+                    *position = (index + 1) % buffers[0].len();
+                }
+                sample + delayed * *send
+            }
+            Self::Compressor {
+                threshold,
+                ratio,
+                attack,
+                release,
+                envelopes,
+            } => {
+                let envelope = &mut envelopes[channel];
+                let level = sample.abs();
+                let coefficient = if level > *envelope { *attack } else { *release };
+                *envelope = coefficient * *envelope + (1.0 - coefficient) * level;
+                if *envelope > *threshold {
+                    let compressed = *threshold + (*envelope - *threshold) / *ratio;
+                    sample * compressed / *envelope
+                } else {
+                    sample
+                }
+            }
+        }
+    }
+}
+
+/// An [`AudioEffectChain`] processing a sound.
+struct ChainProcessor {
+    chain: AudioEffectChain,
+    version: Option<u32>,
+    gain: f32,
+    processors: Vec<EffectProcessor>,
+}
+
+impl ChainProcessor {
+    /// Reads the changes made to the chain since the last refresh.
+    fn refresh(&mut self, channels: u16, sample_rate: u32) {
+        self.gain = self.chain.gain();
+        let version = self.chain.0.version.load(Ordering::Acquire);
+        if self.version == Some(version) {
+            return;
+        }
+        // Don't block the audio thread while the chain is changed, it'll be read on the next
+        // refresh.
+        let Ok(effects) = self.chain.0.effects.try_read() else {
+            return;
+        };
+        let unchanged = self.version.is_some()
+            && effects.len() == self.processors.len()
+            && self
+                .processors
+                .iter_mut()
+                .zip(effects.iter())
+                .all(|(processor, effect)| processor.update(effect, sample_rate));
+        if !unchanged {
+            self.processors = effects
+                .iter()
+                .map(|effect| EffectProcessor::new(effect, channels, sample_rate))
+                .collect();
+        }
+        self.version = Some(version);
+    }
+}
+
+/// The number of samples processed between two reads of the effect chains.
+const REFRESH_INTERVAL: u32 = 512;
+
+/// A [`Source`] applying [`AudioEffectChain`]s to the samples of another source.
+pub(crate) struct EffectChainSource<S> {
+    input: S,
+    chains: Vec<ChainProcessor>,
+    channels: u16,
+    sample_rate: u32,
+    channel: u16,
+    samples_until_refresh: u32,
+}
+
+impl<S: Source<Item = f32>> EffectChainSource<S> {
+    /// Applies the given chains to the `input` source, in order.
+    pub(crate) fn new(input: S, chains: impl IntoIterator<Item = AudioEffectChain>) -> Self {
+        Self {
+            channels: input.channels(),
+            sample_rate: input.sample_rate(),
+            input,
+            chains: chains
+                .into_iter()
+                .map(|chain| ChainProcessor {
+                    chain,
+                    version: None,
+                    gain: 1.0,
+                    processors: Vec::new(),
+                })
+                .collect(),
+            channel: 0,
+            samples_until_refresh: 0,
+        }
+    }
+
+    fn refresh(&mut self) {
+        let (channels, sample_rate) = (self.input.channels(), self.input.sample_rate());
+        if channels != self.channels || sample_rate != self.sample_rate {
+            // The processors have per-channel states and sample rate dependent parameters.
+            self.channels = channels;
+            self.sample_rate = sample_rate;
+            self.channel = 0;
+            for chain in &mut self.chains {
+                chain.version = None;
+            }
+        }
+        for chain in &mut self.chains {
+            chain.refresh(channels, sample_rate);
+        }
+        self.samples_until_refresh = REFRESH_INTERVAL;
+    }
+}
+
+impl<S: Source<Item = f32>> Iterator for EffectChainSource<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let mut sample = self.input.next()?;
+        if self.chains.is_empty() {
+            return Some(sample);
+        }
+        if self.samples_until_refresh == 0 {
+            self.refresh();
+        }
+        self.samples_until_refresh -= 1;
+
+        let channel = self.channel as usize;
+        for chain in &mut self.chains {
+            for processor in &mut chain.processors {
+                sample = processor.process(sample, channel);
+            }
+            sample *= chain.gain;
+        }
+        self.channel = (self.channel + 1) % self.channels.max(1);
+        Some(sample)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<S: Source<Item = f32>> Source for EffectChainSource<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::buffer::SamplesBuffer;
+
+    #[test]
+    fn effect_chain_source() {
+        let input = SamplesBuffer::new(1, 44100, vec![1.0; 4096]);
+        let chain = AudioEffectChain::new([AudioEffect::LowPass {
+            cutoff_frequency: 100.0,
+        }]);
+        let mut source = EffectChainSource::new(input, [chain.clone()]);
+
+        // The low-pass filter smooths the step from silence to the signal.
+        let first = source.next().unwrap();
+        assert!(first > 0.0 && first < 0.1);
+
+        chain.set_gain(0.0);
+        chain.set_effects([]);
+        let samples: Vec<f32> = source.by_ref().skip(REFRESH_INTERVAL as usize).collect();
+        assert!(samples.iter().all(|&sample| sample == 0.0));
+    }
+}
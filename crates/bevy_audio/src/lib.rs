@@ -32,8 +32,14 @@ extern crate alloc;
 mod audio;
 mod audio_output;
 mod audio_source;
+mod effects;
+mod mixer;
 mod pitch;
 mod sinks;
+mod sound_event;
+mod spatial;
+#[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+mod streaming;
 mod volume;
 
 /// The audio prelude.
@@ -42,17 +48,29 @@ mod volume;
 pub mod prelude {
     #[doc(hidden)]
     pub use crate::{
-        AudioPlayer, AudioSink, AudioSinkPlayback, AudioSource, Decodable, GlobalVolume, Pitch,
-        PlaybackSettings, SpatialAudioSink, SpatialListener,
+        AudioBus, AudioEffect, AudioEffectChain, AudioMixer, AudioOcclusion, AudioPlayer,
+        AudioSink, AudioSinkPlayback, AudioSource, Decodable, GlobalVolume, Pitch, PlaySoundEvent,
+        PlaybackSettings, SoundEvent, SpatialAttenuation, SpatialAudioSink, SpatialListener,
     };
 }
 
 pub use audio::*;
 pub use audio_source::*;
+pub use effects::*;
+pub use mixer::*;
 pub use pitch::*;
+pub use sound_event::*;
+pub use spatial::*;
 pub use volume::*;
 
-pub use rodio::{cpal::Sample as CpalSample, source::Source, Sample};
+#[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+pub use streaming::*;
+
+pub use rodio::{
+    cpal::Sample as CpalSample,
+    source::{SeekError, Source},
+    Sample,
+};
 pub use sinks::*;
 
 use bevy_app::prelude::*;
@@ -86,7 +104,17 @@ impl Plugin for AudioPlugin {
             .register_type::<DefaultSpatialScale>()
             .register_type::<PlaybackMode>()
             .register_type::<PlaybackSettings>()
+            .register_type::<AudioBus>()
+            .register_type::<AudioEffect>()
+            .register_type::<SpatialAttenuation>()
+            .register_type::<AudioOcclusion>()
+            .register_type::<VoiceStealing>()
             .insert_resource(self.global_volume)
+            .init_resource::<AudioMixer>()
+            .init_asset::<SoundEvent>()
+            .init_asset_loader::<SoundEventLoader>()
+            .init_resource::<SoundEventPlayback>()
+            .add_event::<PlaySoundEvent>()
             .insert_resource(DefaultSpatialScale(self.default_spatial_scale))
             .configure_sets(
                 PostUpdate,
@@ -94,12 +122,37 @@ impl Plugin for AudioPlugin {
                     .run_if(audio_output_available)
                     .after(TransformSystem::TransformPropagate), // For spatial audio transforms
             )
+            .add_systems(PostUpdate, update_spatial_audio.in_set(AudioPlaySet))
             .add_systems(
                 PostUpdate,
-                (update_emitter_positions, update_listener_positions).in_set(AudioPlaySet),
+                play_sound_events
+                    .run_if(audio_output_available)
+                    .before(AudioPlaySet),
             )
             .init_resource::<AudioOutput>();
 
+        #[cfg(all(
+            any(feature = "mp3", feature = "flac", feature = "wav", feature = "vorbis"),
+            not(any(target_arch = "wasm32", target_os = "android"))
+        ))]
+        {
+            // Registered before the `AudioLoader` so that untyped loads keep using it.
+            // In processed mode, the default asset source reads the processed files, which the
+            // streamed files are copied to.
+            let file_path = app
+                .get_added_plugins::<bevy_asset::AssetPlugin>()
+                .first()
+                .map(|plugin| match plugin.mode {
+                    bevy_asset::AssetMode::Processed => plugin.processed_file_path.clone(),
+                    bevy_asset::AssetMode::Unprocessed => plugin.file_path.clone(),
+                })
+                .unwrap_or_else(|| bevy_asset::AssetPlugin::default().file_path);
+            app.add_audio_source::<StreamingAudioSource>();
+            app.register_asset_loader(StreamingAudioLoader::new(
+                bevy_asset::io::file::FileAssetReader::get_base_path().join(file_path),
+            ));
+        }
+
         #[cfg(any(feature = "mp3", feature = "flac", feature = "wav", feature = "vorbis"))]
         {
             app.add_audio_source::<AudioSource>();
@@ -0,0 +1,231 @@
+use crate::{AudioEffectChain, Volume};
+use alloc::borrow::Cow;
+use bevy_ecs::prelude::*;
+use bevy_reflect::prelude::*;
+use bevy_utils::HashMap;
+
+/// Routes the sound of an audio entity through a bus of the [`AudioMixer`].
+///
+/// Insert it on an entity with an [`AudioPlayer`](crate::AudioPlayer) before it starts playing.
+/// The volume, mute and effects of the bus, and of the buses it is routed to, are then applied to
+/// the sound of the entity while it plays.
+#[derive(Component, Clone, Debug, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Component, Debug, PartialEq, Hash)]
+pub struct AudioBus(pub Cow<'static, str>);
+
+impl AudioBus {
+    /// The bus for music.
+    pub const MUSIC: Self = Self::new("music");
+    /// The bus for sound effects.
+    pub const SFX: Self = Self::new("sfx");
+    /// The bus for voices and dialogue.
+    pub const VOICE: Self = Self::new("voice");
+
+    /// Creates a bus with the given name.
+    pub const fn new(name: &'static str) -> Self {
+        Self(Cow::Borrowed(name))
+    }
+}
+
+/// The settings of a bus of the [`AudioMixer`].
+///
+/// Unlike [`PlaybackSettings`](crate::PlaybackSettings), changes to these settings are applied to
+/// the audio that is already playing through the bus.
+///
+/// Note: the sounds routed through a bus aren't mixed before its effects, each sound goes through
+/// its own copy of the effects of the bus, and of the buses it is routed to. The volume and the
+/// stateless effects like [`AudioEffect::LowPass`](crate::AudioEffect::LowPass) sound the same,
+/// but a [`Compressor`](crate::AudioEffect::Compressor) reacts to the amplitude of each sound
+/// instead of the amplitude of their mix, and a [`Reverb`](crate::AudioEffect::Reverb) costs as
+/// much as one reverb per sound.
+#[derive(Debug)]
+pub struct MixerBus {
+    volume: Volume,
+    muted: bool,
+    parent: Option<AudioBus>,
+    effects: AudioEffectChain,
+}
+
+impl Default for MixerBus {
+    fn default() -> Self {
+        Self {
+            volume: Volume::default(),
+            muted: false,
+            parent: None,
+            effects: AudioEffectChain::default(),
+        }
+    }
+}
+
+impl MixerBus {
+    /// Returns the volume of the bus.
+    pub fn volume(&self) -> Volume {
+        self.volume
+    }
+
+    /// Sets the volume of the bus.
+    pub fn set_volume(&mut self, volume: Volume) {
+        self.volume = volume;
+        self.update_gain();
+    }
+
+    /// Returns `true` if the bus is muted.
+    pub fn is_muted(&self) -> bool {
+        self.muted
+    }
+
+    /// Mutes the bus, keeping its volume.
+    pub fn mute(&mut self) {
+        self.muted = true;
+        self.update_gain();
+    }
+
+    /// Unmutes the bus.
+    pub fn unmute(&mut self) {
+        self.muted = false;
+        self.update_gain();
+    }
+
+    /// Toggles whether the bus is muted or not.
+    pub fn toggle_mute(&mut self) {
+        self.muted = !self.muted;
+        self.update_gain();
+    }
+
+    /// Returns the bus the sound of this bus is routed to after its effects, if any.
+    pub fn parent(&self) -> Option<&AudioBus> {
+        self.parent.as_ref()
+    }
+
+    /// Routes the sound of this bus to the `parent` bus after its effects, or only to the output
+    /// if it's `None`.
+    ///
+    /// Note: only the audio that starts playing after this change is routed to the new parent.
+    pub fn set_parent(&mut self, parent: Option<AudioBus>) {
+        self.parent = parent;
+    }
+
+    /// Returns the effects applied to the sound of the bus.
+    ///
+    /// Changes to the chain are applied to the audio that is playing through the bus.
+    pub fn effects(&self) -> &AudioEffectChain {
+        &self.effects
+    }
+
+    fn update_gain(&self) {
+        let gain = if self.muted { 0.0 } else { self.volume.get() };
+        self.effects.set_gain(gain);
+    }
+}
+
+/// The buses of the audio mixer, which the sound of audio entities with an [`AudioBus`] is routed
+/// through.
+///
+/// The [`AudioBus::MUSIC`], [`AudioBus::SFX`] and [`AudioBus::VOICE`] buses are created by
+/// default, and other buses are created when they are first used.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_audio::{AudioBus, AudioEffect, AudioMixer, Volume};
+/// fn duck_music(mut mixer: ResMut<AudioMixer>) {
+///     let music = mixer.bus_mut(AudioBus::MUSIC);
+///     music.set_volume(Volume::new(0.3));
+///     music.effects().set_effects([AudioEffect::LowPass {
+///         cutoff_frequency: 800.0,
+///     }]);
+/// }
+/// ```
+#[derive(Resource, Debug)]
+pub struct AudioMixer {
+    buses: HashMap<AudioBus, MixerBus>,
+}
+
+impl Default for AudioMixer {
+    fn default() -> Self {
+        Self {
+            buses: [AudioBus::MUSIC, AudioBus::SFX, AudioBus::VOICE]
+                .into_iter()
+                .map(|bus| (bus, MixerBus::default()))
+                .collect(),
+        }
+    }
+}
+
+impl AudioMixer {
+    /// Returns the settings of the given bus, if it exists.
+    pub fn bus(&self, bus: &AudioBus) -> Option<&MixerBus> {
+        self.buses.get(bus)
+    }
+
+    /// Returns the settings of the given bus, creating it if it doesn't exist.
+    pub fn bus_mut(&mut self, bus: AudioBus) -> &mut MixerBus {
+        self.buses.entry(bus).or_default()
+    }
+
+    /// Returns an iterator over the buses and their settings.
+    pub fn iter(&self) -> impl Iterator<Item = (&AudioBus, &MixerBus)> {
+        self.buses.iter()
+    }
+
+    /// Returns the effect chains that the sound routed through the given bus goes through, from
+    /// the bus to its last parent.
+    pub(crate) fn route(&self, bus: &AudioBus) -> Vec<AudioEffectChain> {
+        let mut chains = Vec::new();
+        let mut visited = Vec::new();
+        let mut next = Some(bus);
+        while let Some(bus) = next {
+            // Buses routed in a cycle are only applied once.
+            if visited.contains(&bus) {
+                break;
+            }
+            visited.push(bus);
+            let Some(settings) = self.buses.get(bus) else {
+                break;
+            };
+            chains.push(settings.effects.clone());
+            next = settings.parent.as_ref();
+        }
+        chains
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn route() {
+        let mut mixer = AudioMixer::default();
+        assert_eq!(mixer.route(&AudioBus::MUSIC).len(), 1);
+        assert!(mixer.route(&AudioBus::new("unknown")).is_empty());
+
+        let master = AudioBus::new("master");
+        mixer.bus_mut(master.clone()).set_volume(Volume::new(0.5));
+        mixer
+            .bus_mut(AudioBus::MUSIC)
+            .set_parent(Some(master.clone()));
+        let route = mixer.route(&AudioBus::MUSIC);
+        assert_eq!(route.len(), 2);
+        assert_eq!(route[0].gain(), 1.0);
+        assert_eq!(route[1].gain(), 0.5);
+
+        // The route shares the chains of the buses, so it follows their changes.
+        mixer.bus_mut(AudioBus::MUSIC).mute();
+        assert_eq!(route[0].gain(), 0.0);
+        mixer.bus_mut(AudioBus::MUSIC).toggle_mute();
+        assert_eq!(route[0].gain(), 1.0);
+        mixer.bus_mut(master.clone()).set_volume(Volume::new(0.25));
+        assert_eq!(route[1].gain(), 0.25);
+
+        // Buses routed in a cycle are only applied once.
+        mixer
+            .bus_mut(master.clone())
+            .set_parent(Some(AudioBus::MUSIC));
+        assert_eq!(mixer.route(&AudioBus::MUSIC).len(), 2);
+        assert_eq!(mixer.route(&master).len(), 2);
+        mixer
+            .bus_mut(master.clone())
+            .set_parent(Some(master.clone()));
+        assert_eq!(mixer.route(&master).len(), 1);
+    }
+}
@@ -1,7 +1,11 @@
+use crate::{
+    spatial::sink_positions, AudioEffect, AudioEffectChain, AudioOcclusion, SpatialAttenuation,
+};
 use bevy_ecs::component::Component;
 use bevy_math::Vec3;
 use bevy_transform::prelude::Transform;
-use rodio::{Sink, SpatialSink};
+use core::time::Duration;
+use rodio::{source::SeekError, Sink, SpatialSink};
 
 /// Common interactions with an audio sink.
 pub trait AudioSinkPlayback {
@@ -75,6 +79,18 @@ pub trait AudioSinkPlayback {
     /// Sinks can be paused and resumed using [`pause`](Self::pause) and [`play`](Self::play).
     fn is_paused(&self) -> bool;
 
+    /// Returns the position of the sound that is playing.
+    ///
+    /// This is the duration of the sound played since it started, or since it was last seeked,
+    /// taking its speed into account.
+    fn position(&self) -> Duration;
+
+    /// Moves the playback of the sound to the given position.
+    ///
+    /// This fails if the sound doesn't support seeking. The position is clamped to the duration
+    /// of the sound when it's known.
+    fn try_seek(&self, position: Duration) -> Result<(), SeekError>;
+
     /// Stops the sink.
     ///
     /// It won't be possible to restart it afterwards.
@@ -178,6 +194,14 @@ impl AudioSinkPlayback for AudioSink {
         self.sink.is_paused()
     }
 
+    fn position(&self) -> Duration {
+        self.sink.get_pos()
+    }
+
+    fn try_seek(&self, position: Duration) -> Result<(), SeekError> {
+        self.sink.try_seek(position)
+    }
+
     fn stop(&self) {
         self.sink.stop();
     }
@@ -216,6 +240,9 @@ impl AudioSinkPlayback for AudioSink {
 pub struct SpatialAudioSink {
     pub(crate) sink: SpatialSink,
 
+    /// The filtering of the sound by its distance to the listener and its occlusion.
+    pub(crate) filter: AudioEffectChain,
+
     /// Managed volume allows the sink to be muted without losing the user's
     /// intended volume setting.
     ///
@@ -235,6 +262,7 @@ impl SpatialAudioSink {
     pub fn new(sink: SpatialSink) -> Self {
         Self {
             sink,
+            filter: AudioEffectChain::default(),
             managed_volume: None,
         }
     }
@@ -273,6 +301,14 @@ impl AudioSinkPlayback for SpatialAudioSink {
         self.sink.is_paused()
     }
 
+    fn position(&self) -> Duration {
+        self.sink.get_pos()
+    }
+
+    fn try_seek(&self, position: Duration) -> Result<(), SeekError> {
+        self.sink.try_seek(position)
+    }
+
     fn stop(&self) {
         self.sink.stop();
     }
@@ -316,6 +352,33 @@ impl SpatialAudioSink {
     pub fn set_emitter_position(&self, position: Vec3) {
         self.sink.set_emitter_position(position.to_array());
     }
+
+    /// Sets the positions of the emitter and the ears, and filters the sound by the distance
+    /// between them and the occlusion of the emitter.
+    pub(crate) fn set_spatial(
+        &self,
+        emitter: Vec3,
+        left_ear: Vec3,
+        right_ear: Vec3,
+        attenuation: SpatialAttenuation,
+        occlusion: AudioOcclusion,
+    ) {
+        let (emitter_position, left_ear_position, right_ear_position) =
+            sink_positions(emitter, left_ear, right_ear);
+        self.sink.set_emitter_position(emitter_position);
+        self.sink.set_left_ear_position(left_ear_position);
+        self.sink.set_right_ear_position(right_ear_position);
+
+        let distance = emitter.distance((left_ear + right_ear) / 2.0);
+        let (volume, cutoff_frequency) = attenuation.filter(distance, &occlusion);
+        self.filter.set_gain(volume);
+        // The low-pass filter is kept when the sound isn't filtered, with an infinite cutoff
+        // frequency, so that its state isn't reset when the filtering starts again.
+        let effects = [AudioEffect::LowPass { cutoff_frequency }];
+        if self.filter.effects() != effects {
+            self.filter.set_effects(effects);
+        }
+    }
 }
 
 #[cfg(test)]
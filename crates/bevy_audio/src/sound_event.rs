@@ -0,0 +1,517 @@
+use crate::{AudioBus, AudioPlayer, AudioSource, PlaybackSettings, Volume};
+use alloc::borrow::Cow;
+use bevy_asset::{io::Reader, Asset, AssetId, AssetLoader, AssetPath, Assets, Handle, LoadContext};
+use bevy_ecs::prelude::*;
+use bevy_hierarchy::DespawnRecursiveExt;
+use bevy_math::Vec3;
+use bevy_reflect::prelude::*;
+use bevy_time::{Real, Time};
+use bevy_transform::prelude::{GlobalTransform, Transform};
+use bevy_utils::{HashMap, Instant, RandomState};
+use core::{
+    hash::BuildHasher,
+    ops::RangeInclusive,
+    time::{Duration, TryFromFloatSecsError},
+};
+use ron::de::SpannedError;
+use serde::Deserialize;
+use std::io;
+use thiserror::Error;
+use tracing::warn;
+
+/// What a [`SoundEvent`] does when it's played while its maximum number of instances is playing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect, Deserialize)]
+#[reflect(Default, Debug, PartialEq)]
+pub enum VoiceStealing {
+    /// Stop the oldest instance to play the new one.
+    #[default]
+    Oldest,
+    /// Stop the quietest instance to play the new one, unless the new one is quieter.
+    Quietest,
+    /// Don't play the new instance.
+    Reject,
+}
+
+/// A sound that is played in response to gameplay events, like footsteps or impacts, with a
+/// [`PlaySoundEvent`].
+///
+/// Each time it's played, one of its variations is chosen at random and played at a random volume
+/// and speed within the ranges of the event. The cooldown and the maximum number of instances
+/// limit how many sounds are played when the event is sent repeatedly.
+///
+/// Sound events can be created in code, or loaded from `.sound_event.ron` files by the
+/// [`SoundEventLoader`].
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_asset::{AssetServer, Assets};
+/// # use bevy_audio::{PlaySoundEvent, SoundEvent, VoiceStealing};
+/// # use core::time::Duration;
+/// fn setup(
+///     asset_server: Res<AssetServer>,
+///     mut sound_events: ResMut<Assets<SoundEvent>>,
+///     mut commands: Commands,
+/// ) {
+///     let footstep = SoundEvent::new([
+///         asset_server.load("sounds/footstep_1.ogg"),
+///         asset_server.load("sounds/footstep_2.ogg"),
+///     ])
+///     .with_volume(0.8..=1.0)
+///     .with_speed(0.9..=1.1)
+///     .with_cooldown(Duration::from_millis(100))
+///     .with_max_instances(4, VoiceStealing::Oldest);
+///     commands.send_event(PlaySoundEvent::new(sound_events.add(footstep)));
+/// }
+/// ```
+#[derive(Asset, Clone, Debug, TypePath)]
+pub struct SoundEvent {
+    /// The sounds that can be played by the event.
+    #[dependency]
+    pub variations: Vec<Handle<AudioSource>>,
+    /// The range of the volume the sound is played at.
+    pub volume: RangeInclusive<f32>,
+    /// The range of the speed the sound is played at, which also changes its pitch.
+    pub speed: RangeInclusive<f32>,
+    /// The minimum duration between two plays of the event, in real time.
+    pub cooldown: Duration,
+    /// The maximum number of instances of the event playing at the same time.
+    pub max_instances: usize,
+    /// What to do when the event is played while [`max_instances`](Self::max_instances)
+    /// instances are playing.
+    pub stealing: VoiceStealing,
+    /// The bus of the [`AudioMixer`](crate::AudioMixer) the sound is played through.
+    pub bus: Option<AudioBus>,
+}
+
+impl SoundEvent {
+    /// Creates an event playing one of the given variations, at full volume and normal speed,
+    /// without limits.
+    pub fn new(variations: impl IntoIterator<Item = Handle<AudioSource>>) -> Self {
+        Self {
+            variations: variations.into_iter().collect(),
+            volume: 1.0..=1.0,
+            speed: 1.0..=1.0,
+            cooldown: Duration::ZERO,
+            max_instances: usize::MAX,
+            stealing: VoiceStealing::default(),
+            bus: None,
+        }
+    }
+
+    /// Helper to set the range of the volume.
+    pub fn with_volume(mut self, volume: RangeInclusive<f32>) -> Self {
+        self.volume = volume;
+        self
+    }
+
+    /// Helper to set the range of the speed.
+    pub fn with_speed(mut self, speed: RangeInclusive<f32>) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Helper to set the cooldown.
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Helper to set the maximum number of instances, and what to do when it's reached.
+    pub fn with_max_instances(mut self, max_instances: usize, stealing: VoiceStealing) -> Self {
+        self.max_instances = max_instances;
+        self.stealing = stealing;
+        self
+    }
+
+    /// Helper to set the bus.
+    pub fn with_bus(mut self, bus: AudioBus) -> Self {
+        self.bus = Some(bus);
+        self
+    }
+}
+
+/// Plays a [`SoundEvent`] when sent.
+///
+/// The sound is played by a new entity, with a [`SoundEventInstance`], that is despawned when the
+/// sound finishes playing or when its voice is stolen by a new instance.
+#[derive(Event, Clone, Debug)]
+pub struct PlaySoundEvent {
+    /// The event to play.
+    pub sound: Handle<SoundEvent>,
+    /// The position to play the sound at using spatial audio, if any.
+    pub position: Option<Vec3>,
+}
+
+impl PlaySoundEvent {
+    /// Plays the given event without spatial audio.
+    pub fn new(sound: Handle<SoundEvent>) -> Self {
+        Self {
+            sound,
+            position: None,
+        }
+    }
+
+    /// Helper to play the sound at the given position using spatial audio.
+    pub fn at(mut self, position: Vec3) -> Self {
+        self.position = Some(position);
+        self
+    }
+}
+
+/// Marks an entity playing an instance of a [`SoundEvent`].
+#[derive(Component, Clone, Debug)]
+pub struct SoundEventInstance {
+    sound: AssetId<SoundEvent>,
+    order: u64,
+}
+
+impl SoundEventInstance {
+    /// Returns the event this is an instance of.
+    pub fn sound(&self) -> AssetId<SoundEvent> {
+        self.sound
+    }
+}
+
+/// The state of the played [`SoundEvent`]s.
+#[derive(Resource)]
+pub(crate) struct SoundEventPlayback {
+    rng: u64,
+    played: u64,
+    history: HashMap<AssetId<SoundEvent>, SoundEventHistory>,
+}
+
+struct SoundEventHistory {
+    /// The [`Time<Real>`] elapsed when the event was last played.
+    last_played: Duration,
+    last_variation: usize,
+}
+
+impl Default for SoundEventPlayback {
+    fn default() -> Self {
+        Self {
+            // The seed must not be zero.
+            rng: RandomState::default().hash_one(Instant::now()) | 1,
+            played: 0,
+            history: HashMap::default(),
+        }
+    }
+}
+
+impl SoundEventPlayback {
+    /// Returns a pseudo-random number in `[0, 1)`, with a xorshift generator.
+    fn random(&mut self) -> f32 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        (self.rng >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    fn random_in(&mut self, range: &RangeInclusive<f32>) -> f32 {
+        range.start() + (range.end() - range.start()) * self.random()
+    }
+}
+
+/// A playing instance of a [`SoundEvent`].
+struct Voice {
+    entity: Entity,
+    order: u64,
+    volume: f32,
+}
+
+/// Spawns the audio entities of the [`PlaySoundEvent`]s, applying the cooldowns and polyphony
+/// limits of their [`SoundEvent`].
+pub(crate) fn play_sound_events(
+    mut commands: Commands,
+    mut events: EventReader<PlaySoundEvent>,
+    sound_events: Res<Assets<SoundEvent>>,
+    mut playback: ResMut<SoundEventPlayback>,
+    instances: Query<(Entity, &SoundEventInstance, &PlaybackSettings)>,
+    time: Res<Time<Real>>,
+) {
+    if events.is_empty() {
+        return;
+    }
+
+    let mut voices = HashMap::<AssetId<SoundEvent>, Vec<Voice>>::default();
+    for (entity, instance, settings) in &instances {
+        voices.entry(instance.sound).or_default().push(Voice {
+            entity,
+            order: instance.order,
+            volume: settings.volume.get(),
+        });
+    }
+
+    let now = time.elapsed();
+    for event in events.read() {
+        let Some(sound) = sound_events.get(&event.sound) else {
+            warn!("Tried to play a SoundEvent that isn't loaded.");
+            continue;
+        };
+        if sound.variations.is_empty() {
+            continue;
+        }
+        let id = event.sound.id();
+        let last_play = playback
+            .history
+            .get(&id)
+            .map(|history| (history.last_played, history.last_variation));
+        if last_play.is_some_and(|(last_played, _)| now - last_played < sound.cooldown) {
+            continue;
+        }
+
+        let volume = playback.random_in(&sound.volume).max(0.0);
+        let playing = voices.entry(id).or_default();
+        if playing.len() >= sound.max_instances {
+            let stolen = match sound.stealing {
+                VoiceStealing::Oldest => playing
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, voice)| voice.order)
+                    .map(|(index, _)| index),
+                VoiceStealing::Quietest => playing
+                    .iter()
+                    .enumerate()
+                    .min_by(|(_, a), (_, b)| a.volume.total_cmp(&b.volume))
+                    .filter(|(_, voice)| voice.volume <= volume)
+                    .map(|(index, _)| index),
+                VoiceStealing::Reject => None,
+            };
+            let Some(stolen) = stolen else {
+                continue;
+            };
+            commands
+                .entity(playing.swap_remove(stolen).entity)
+                .despawn_recursive();
+        }
+
+        // Another variation than the last one is played, so that repeated sounds vary.
+        let mut variation = (playback.random() * sound.variations.len() as f32) as usize;
+        if sound.variations.len() > 1 && last_play.is_some_and(|(_, last)| last == variation) {
+            variation = (variation + 1) % sound.variations.len();
+        }
+        let speed = playback.random_in(&sound.speed).max(0.0);
+
+        let order = playback.played;
+        playback.played += 1;
+        playback.history.insert(
+            id,
+            SoundEventHistory {
+                last_played: now,
+                last_variation: variation,
+            },
+        );
+
+        let settings = PlaybackSettings::DESPAWN
+            .with_volume(Volume::new(volume))
+            .with_speed(speed)
+            .with_spatial(event.position.is_some());
+        let mut entity = commands.spawn((
+            AudioPlayer(sound.variations[variation].clone()),
+            settings,
+            SoundEventInstance { sound: id, order },
+        ));
+        if let Some(bus) = &sound.bus {
+            entity.insert(bus.clone());
+        }
+        if let Some(position) = event.position {
+            // The global transform is set right away, as the sound starts playing before the
+            // transforms are propagated.
+            entity.insert((
+                Transform::from_translation(position),
+                GlobalTransform::from_translation(position),
+            ));
+        }
+        playing.push(Voice {
+            entity: entity.id(),
+            order,
+            volume,
+        });
+    }
+}
+
+/// A [`SoundEvent`] in a `.sound_event.ron` file, with the paths of its variations.
+///
+/// The fields that are left out have the values of [`SoundEvent::new`].
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SerializedSoundEvent {
+    variations: Vec<AssetPath<'static>>,
+    #[serde(default = "full_range")]
+    volume: RangeInclusive<f32>,
+    #[serde(default = "full_range")]
+    speed: RangeInclusive<f32>,
+    /// The cooldown, in seconds.
+    #[serde(default)]
+    cooldown: f32,
+    #[serde(default)]
+    max_instances: Option<usize>,
+    #[serde(default)]
+    stealing: VoiceStealing,
+    #[serde(default)]
+    bus: Option<String>,
+}
+
+fn full_range() -> RangeInclusive<f32> {
+    1.0..=1.0
+}
+
+/// Loads [`SoundEvent`]s from `.sound_event.ron` files, and the [`AudioSource`]s of their
+/// variations.
+///
+/// The fields of the file are the fields of [`SoundEvent`], with the asset paths of the
+/// variations and the cooldown in seconds. Only the variations are required:
+///
+/// ```ron
+/// (
+///     variations: ["sounds/footstep_1.ogg", "sounds/footstep_2.ogg"],
+///     volume: (start: 0.8, end: 1.0),
+///     speed: (start: 0.9, end: 1.1),
+///     cooldown: 0.1,
+///     max_instances: Some(4),
+///     stealing: Oldest,
+///     bus: Some("sfx"),
+/// )
+/// ```
+#[derive(Default)]
+pub struct SoundEventLoader;
+
+/// An error when loading a [`SoundEvent`] with the [`SoundEventLoader`].
+#[derive(Error, Debug)]
+pub enum SoundEventLoadError {
+    /// The file couldn't be read.
+    #[error("failed to read the sound event: {0}")]
+    Io(#[from] io::Error),
+    /// The file isn't a valid sound event.
+    #[error("failed to parse the sound event: {0}")]
+    Ron(#[from] SpannedError),
+    /// The cooldown is negative or too large.
+    #[error("invalid cooldown: {0}")]
+    Cooldown(#[from] TryFromFloatSecsError),
+}
+
+impl AssetLoader for SoundEventLoader {
+    type Asset = SoundEvent;
+    type Settings = ();
+    type Error = SoundEventLoadError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<SoundEvent, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let serialized: SerializedSoundEvent = ron::de::from_bytes(&bytes)?;
+        Ok(SoundEvent {
+            variations: serialized
+                .variations
+                .into_iter()
+                .map(|path| load_context.load(path))
+                .collect(),
+            volume: serialized.volume,
+            speed: serialized.speed,
+            cooldown: Duration::try_from_secs_f32(serialized.cooldown)?,
+            max_instances: serialized.max_instances.unwrap_or(usize::MAX),
+            stealing: serialized.stealing,
+            bus: serialized.bus.map(|bus| AudioBus(Cow::Owned(bus))),
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["sound_event.ron"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_app::{App, Update};
+
+    fn instance_count(app: &mut App) -> usize {
+        app.world_mut()
+            .query::<&SoundEventInstance>()
+            .iter(app.world())
+            .count()
+    }
+
+    #[test]
+    fn sound_event_limits() {
+        let mut app = App::new();
+        app.init_resource::<Assets<SoundEvent>>()
+            .init_resource::<SoundEventPlayback>()
+            .init_resource::<Time<Real>>()
+            .add_event::<PlaySoundEvent>()
+            .add_systems(Update, play_sound_events);
+
+        let mut sound_events = app.world_mut().resource_mut::<Assets<SoundEvent>>();
+        let limited = sound_events.add(
+            SoundEvent::new([Handle::default(), Handle::default()])
+                .with_max_instances(2, VoiceStealing::Oldest),
+        );
+        let rejected = sound_events
+            .add(SoundEvent::new([Handle::default()]).with_max_instances(1, VoiceStealing::Reject));
+        let cooled_down = sound_events
+            .add(SoundEvent::new([Handle::default()]).with_cooldown(Duration::from_secs(3600)));
+
+        for _ in 0..5 {
+            app.world_mut()
+                .send_event(PlaySoundEvent::new(limited.clone()));
+        }
+        app.update();
+        assert_eq!(instance_count(&mut app), 2);
+
+        for _ in 0..3 {
+            app.world_mut()
+                .send_event(PlaySoundEvent::new(rejected.clone()));
+            app.world_mut()
+                .send_event(PlaySoundEvent::new(cooled_down.clone()));
+        }
+        app.update();
+        assert_eq!(instance_count(&mut app), 4);
+
+        // The oldest instances of the limited event were stolen.
+        let mut orders: Vec<_> = app
+            .world_mut()
+            .query::<&SoundEventInstance>()
+            .iter(app.world())
+            .filter(|instance| instance.sound() == limited.id())
+            .map(|instance| instance.order)
+            .collect();
+        orders.sort();
+        assert_eq!(orders, [3, 4]);
+
+        // The cooldown is measured in real time.
+        let mut time = app.world_mut().resource_mut::<Time<Real>>();
+        time.update_with_duration(Duration::ZERO);
+        time.update_with_duration(Duration::from_secs(3600));
+        app.world_mut()
+            .send_event(PlaySoundEvent::new(cooled_down.clone()));
+        app.update();
+        assert_eq!(instance_count(&mut app), 5);
+    }
+
+    #[test]
+    fn deserialize_sound_event() {
+        let serialized: SerializedSoundEvent = ron::de::from_str(
+            r#"(
+                variations: ["sounds/step.ogg"],
+                speed: (start: 0.9, end: 1.1),
+                cooldown: 0.5,
+                stealing: Quietest,
+            )"#,
+        )
+        .unwrap();
+        assert_eq!(serialized.variations, [AssetPath::from("sounds/step.ogg")]);
+        assert_eq!(serialized.volume, 1.0..=1.0);
+        assert_eq!(serialized.speed, 0.9..=1.1);
+        assert_eq!(serialized.cooldown, 0.5);
+        assert_eq!(serialized.max_instances, None);
+        assert_eq!(serialized.stealing, VoiceStealing::Quietest);
+        assert_eq!(serialized.bus, None);
+
+        assert!(
+            ron::de::from_str::<SerializedSoundEvent>("(volume: (start: 0.5, end: 1.0))").is_err()
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use crate::Volume;
+use bevy_ecs::prelude::*;
+use bevy_math::{ops, Vec3};
+use bevy_reflect::prelude::*;
+
+/// How the volume of a spatial audio emitter decreases with its distance to the listener.
+///
+/// The distance is clamped between the [`min_distance`](SpatialAttenuation::min_distance) and
+/// the [`max_distance`](SpatialAttenuation::max_distance) of the [`SpatialAttenuation`].
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Debug, PartialEq)]
+pub enum AttenuationCurve {
+    /// The volume doesn't change with the distance.
+    None,
+    /// The volume decreases linearly, from full volume at the minimum distance to silence at the
+    /// maximum distance.
+    Linear,
+    /// The volume is inversely proportional to the distance beyond the minimum distance,
+    /// multiplied by `rolloff`.
+    Inverse {
+        /// How fast the volume decreases, `1.0` being a realistic rolloff.
+        rolloff: f32,
+    },
+    /// The volume is inversely proportional to the square of the distance.
+    InverseSquare,
+    /// The volume is inversely proportional to the distance raised to the power of `rolloff`.
+    Exponential {
+        /// How fast the volume decreases.
+        rolloff: f32,
+    },
+}
+
+/// How the sound of a spatial audio source gets muffled when it's occluded or obstructed, with
+/// an amount of `1.0` in its [`AudioOcclusion`].
+#[derive(Clone, Copy, Debug, Reflect)]
+#[reflect(Debug)]
+pub struct OcclusionFilter {
+    /// The volume multiplier.
+    pub volume: Volume,
+    /// The cutoff frequency of the low-pass filter, in hertz.
+    pub cutoff_frequency: f32,
+}
+
+/// Configures how the sound of a spatial audio entity changes with its distance to the
+/// listener, and with its [`AudioOcclusion`].
+///
+/// Spatial audio entities without this component use its default values, in which the volume
+/// follows the inverse square of the distance, without air absorption.
+///
+/// Distances are measured between the emitter and the middle of the ears of the
+/// [`SpatialListener`](crate::SpatialListener), after applying the
+/// [`SpatialScale`](crate::SpatialScale). Changes are applied to the audio that is playing.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component, Default, Debug)]
+pub struct SpatialAttenuation {
+    /// How the volume decreases with the distance.
+    pub curve: AttenuationCurve,
+    /// The distance under which the sound is played at full volume.
+    pub min_distance: f32,
+    /// The distance beyond which the sound doesn't get quieter.
+    pub max_distance: f32,
+    /// How fast the high frequencies of the sound are absorbed by the air with the distance
+    /// beyond the minimum distance.
+    ///
+    /// The cutoff frequency of the low-pass filter is halved every `1.0 / air_absorption` units
+    /// of distance, from 20 kHz. `0.0` disables the air absorption.
+    pub air_absorption: f32,
+    /// How the sound is muffled when it's fully occluded.
+    pub occlusion: OcclusionFilter,
+    /// How the sound is muffled when it's fully obstructed.
+    pub obstruction: OcclusionFilter,
+}
+
+impl Default for SpatialAttenuation {
+    fn default() -> Self {
+        Self {
+            curve: AttenuationCurve::InverseSquare,
+            min_distance: 1.0,
+            max_distance: f32::INFINITY,
+            air_absorption: 0.0,
+            occlusion: OcclusionFilter {
+                volume: Volume(0.3),
+                cutoff_frequency: 800.0,
+            },
+            obstruction: OcclusionFilter {
+                volume: Volume(0.8),
+                cutoff_frequency: 2000.0,
+            },
+        }
+    }
+}
+
+/// The highest cutoff frequency of the low-pass filter, at which air absorption starts.
+const MAX_CUTOFF_FREQUENCY: f32 = 20_000.0;
+
+impl SpatialAttenuation {
+    /// Returns the volume multiplier of a sound at the given distance.
+    pub fn volume(&self, distance: f32) -> f32 {
+        let min = self.min_distance.max(f32::EPSILON);
+        let max = self.max_distance.max(min);
+        let distance = distance.clamp(min, max);
+        match self.curve {
+            AttenuationCurve::None => 1.0,
+            AttenuationCurve::Linear if max.is_finite() && max > min => {
+                1.0 - (distance - min) / (max - min)
+            }
+            AttenuationCurve::Linear => 1.0,
+            AttenuationCurve::Inverse { rolloff } => {
+                min / (min + rolloff.max(0.0) * (distance - min))
+            }
+            AttenuationCurve::InverseSquare => (min / distance) * (min / distance),
+            AttenuationCurve::Exponential { rolloff } => {
+                ops::powf(distance / min, -rolloff.max(0.0))
+            }
+        }
+    }
+
+    /// Returns the volume multiplier and the cutoff frequency of the low-pass filter of a sound
+    /// at the given distance, with the given occlusion.
+    ///
+    /// The cutoff frequency is infinite when the sound isn't filtered.
+    pub(crate) fn filter(&self, distance: f32, occlusion: &AudioOcclusion) -> (f32, f32) {
+        let mut cutoff_frequency = f32::INFINITY;
+        if self.air_absorption > 0.0 {
+            let absorbed = (distance.min(self.max_distance) - self.min_distance).max(0.0);
+            cutoff_frequency = MAX_CUTOFF_FREQUENCY * ops::exp2(-self.air_absorption * absorbed);
+        }
+        let mut volume = self.volume(distance);
+        for (amount, filter) in [
+            (occlusion.occlusion, self.occlusion),
+            (occlusion.obstruction, self.obstruction),
+        ] {
+            let amount = amount.clamp(0.0, 1.0);
+            if amount == 0.0 {
+                continue;
+            }
+            volume *= 1.0 + (filter.volume.get() - 1.0) * amount;
+            // The cutoff frequency is interpolated on a logarithmic scale, as frequencies are
+            // perceived.
+            let filter_cutoff = MAX_CUTOFF_FREQUENCY
+                * ops::powf(
+                    filter.cutoff_frequency.max(1.0) / MAX_CUTOFF_FREQUENCY,
+                    amount,
+                );
+            cutoff_frequency = cutoff_frequency.min(filter_cutoff);
+        }
+        (volume, cutoff_frequency)
+    }
+}
+
+/// How much the sound of a spatial audio entity is blocked on its way to the listener.
+///
+/// The audio engine doesn't compute it: a game or a physics integration sets it for each emitter,
+/// for example by casting rays from the listener, and the sound is filtered according to the
+/// [`SpatialAttenuation`] of the entity. Changes are applied to the audio that is playing.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq)]
+pub struct AudioOcclusion {
+    /// How much the emitter is occluded, from `0.0` to `1.0`: the sound goes through an obstacle,
+    /// like a wall between two rooms, which muffles it and makes it quieter.
+    pub occlusion: f32,
+    /// How much the emitter is obstructed, from `0.0` to `1.0`: the direct sound is blocked by an
+    /// obstacle, like a pillar, but the sound still reaches the listener around it.
+    pub obstruction: f32,
+}
+
+/// The positions of the emitter and the ears given to a [`SpatialSink`](rodio::SpatialSink).
+///
+/// The sink attenuates the sound of an emitter that is more than a unit away from an ear, so the
+/// positions are scaled down around the listener to keep the attenuation to the
+/// [`SpatialAttenuation`] while keeping the panning.
+pub(crate) fn sink_positions(
+    emitter: Vec3,
+    left_ear: Vec3,
+    right_ear: Vec3,
+) -> ([f32; 3], [f32; 3], [f32; 3]) {
+    let center = (left_ear + right_ear) / 2.0;
+    let extent = emitter.distance(center).max(left_ear.distance(center));
+    // Every distance is then at most `0.5`.
+    let scale = if extent > 0.0 { 0.25 / extent } else { 1.0 };
+    (
+        ((emitter - center) * scale).to_array(),
+        ((left_ear - center) * scale).to_array(),
+        ((right_ear - center) * scale).to_array(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attenuation_curves() {
+        let attenuation = |curve| SpatialAttenuation {
+            curve,
+            min_distance: 2.0,
+            max_distance: 10.0,
+            ..Default::default()
+        };
+        let assert_volume = |curve, distance, expected: f32| {
+            let volume = attenuation(curve).volume(distance);
+            assert!(
+                (volume - expected).abs() < 1e-6,
+                "{curve:?} at {distance}: {volume} != {expected}"
+            );
+        };
+
+        assert_volume(AttenuationCurve::InverseSquare, 1.0, 1.0);
+        assert_volume(AttenuationCurve::InverseSquare, 4.0, 0.25);
+        assert_volume(AttenuationCurve::InverseSquare, 20.0, 0.04);
+        assert_volume(AttenuationCurve::Linear, 6.0, 0.5);
+        assert_volume(AttenuationCurve::Linear, 20.0, 0.0);
+        assert_volume(AttenuationCurve::Inverse { rolloff: 1.0 }, 4.0, 0.5);
+        assert_volume(AttenuationCurve::None, 20.0, 1.0);
+
+        let occlusion = AudioOcclusion {
+            occlusion: 1.0,
+            obstruction: 0.0,
+        };
+        let (volume, cutoff_frequency) =
+            attenuation(AttenuationCurve::None).filter(20.0, &occlusion);
+        assert!((volume - 0.3).abs() < 1e-6);
+        assert!((cutoff_frequency - 800.0).abs() < 1e-2);
+        let (volume, cutoff_frequency) =
+            attenuation(AttenuationCurve::None).filter(20.0, &AudioOcclusion::default());
+        assert_eq!(volume, 1.0);
+        assert_eq!(cutoff_frequency, f32::INFINITY);
+    }
+}
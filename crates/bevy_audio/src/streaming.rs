@@ -0,0 +1,510 @@
+use crate::{Decodable, AUDIO_EXTENSIONS};
+use async_channel::{Receiver, Sender, TryRecvError};
+use bevy_asset::{
+    io::{AssetSourceId, Reader},
+    Asset, AssetLoader, LoadContext,
+};
+use bevy_reflect::TypePath;
+use bevy_tasks::{IoTaskPool, Task};
+use core::time::Duration;
+use rodio::{source::SeekError, Source};
+use std::{
+    fs::File,
+    io::{self, BufReader},
+    path::{Path, PathBuf},
+};
+use tracing::warn;
+
+type FileDecoder = rodio::Decoder<BufReader<File>>;
+type StreamSample = <FileDecoder as Iterator>::Item;
+
+/// The number of frames decoded at once by the decoding task.
+const CHUNK_FRAMES: usize = 8192;
+/// The number of decoded chunks buffered ahead of the playback.
+const BUFFERED_CHUNKS: usize = 4;
+
+/// A source of audio data that is decoded from its file while it plays, instead of being loaded
+/// into memory like an [`AudioSource`](crate::AudioSource).
+///
+/// Use it for long music or ambience tracks: only a few chunks of the track are held in memory
+/// for each playback. They are decoded ahead of the playback on the [`IoTaskPool`], and silence is
+/// played until the first one is decoded.
+///
+/// Load it from the default asset source with a typed load, the file formats are the same as the
+/// ones of [`AudioSource`](crate::AudioSource). The default asset source must read the file
+/// system, since the file is opened again for each playback:
+///
+/// ```no_run
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_asset::AssetServer;
+/// # use bevy_audio::{AudioPlayer, PlaybackSettings, StreamingAudioSource};
+/// fn play_ambience(asset_server: Res<AssetServer>, mut commands: Commands) {
+///     commands.spawn((
+///         AudioPlayer::<StreamingAudioSource>(asset_server.load("sounds/forest.ogg")),
+///         PlaybackSettings::LOOP,
+///     ));
+/// }
+/// ```
+///
+/// The playback can be moved with [`AudioSinkPlayback::try_seek`](crate::AudioSinkPlayback::try_seek).
+#[derive(Asset, Debug, Clone, TypePath)]
+pub struct StreamingAudioSource {
+    path: PathBuf,
+    channels: u16,
+    sample_rate: u32,
+    total_duration: Option<Duration>,
+}
+
+impl StreamingAudioSource {
+    /// Creates a source streaming the audio file at the given path of the file system.
+    ///
+    /// The file is opened to read its format, which fails if it can't be decoded.
+    pub fn new(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let decoder = open_file(&path)?;
+        Ok(Self {
+            channels: decoder.channels(),
+            sample_rate: decoder.sample_rate(),
+            total_duration: decoder.total_duration(),
+            path,
+        })
+    }
+
+    /// Returns the path of the streamed audio file on the file system.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Decodable for StreamingAudioSource {
+    type DecoderItem = StreamSample;
+    type Decoder = StreamingDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        StreamingDecoder::new(self, false)
+    }
+
+    fn looping_decoder(&self) -> Box<dyn Source<Item = Self::DecoderItem> + Send> {
+        // Rewinding the file avoids repeating the track from memory.
+        Box::new(StreamingDecoder::new(self, true))
+    }
+}
+
+fn open_file(path: &Path) -> io::Result<FileDecoder> {
+    FileDecoder::new(BufReader::new(File::open(path)?))
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Loads files from the default asset source as [`StreamingAudioSource`]
+/// [`Assets`](bevy_asset::Assets).
+///
+/// It reads the format of the file, but not its audio data. The files are opened on the file
+/// system from the directory of the default asset source, so loading fails if the default asset
+/// source doesn't read them from there.
+pub struct StreamingAudioLoader {
+    root_path: PathBuf,
+}
+
+impl StreamingAudioLoader {
+    /// Creates a loader for the files of the default asset source, which is in `root_path` on the
+    /// file system.
+    pub fn new(root_path: impl Into<PathBuf>) -> Self {
+        Self {
+            root_path: root_path.into(),
+        }
+    }
+}
+
+impl AssetLoader for StreamingAudioLoader {
+    type Asset = StreamingAudioSource;
+    type Settings = ();
+    type Error = io::Error;
+
+    async fn load(
+        &self,
+        _reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<StreamingAudioSource, Self::Error> {
+        let source = load_context.asset_path().source();
+        if !matches!(source, AssetSourceId::Default) {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("audio can only be streamed from the default asset source, not {source}"),
+            ));
+        }
+        let path = self.root_path.join(load_context.path());
+        StreamingAudioSource::new(&path).map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "{} isn't on the file system, audio can only be streamed from a default asset \
+                    source that reads the file system",
+                    path.display()
+                ),
+            ),
+            _ => err,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        AUDIO_EXTENSIONS
+    }
+}
+
+/// Decoded samples sent by the decoding task.
+struct Chunk {
+    /// The number of seeks requested before the chunk was decoded.
+    generation: u32,
+    samples: Vec<StreamSample>,
+    /// Whether this is the last chunk of the file.
+    end: bool,
+}
+
+/// A seek requested by the playback.
+struct Seek {
+    position: Duration,
+    generation: u32,
+}
+
+/// The [`Decodable::Decoder`] of [`StreamingAudioSource`], playing the samples decoded by its
+/// decoding task.
+///
+/// If the task falls behind the playback, silence is played until the next chunk is decoded.
+pub struct StreamingDecoder {
+    channels: u16,
+    sample_rate: u32,
+    total_duration: Option<Duration>,
+    samples: std::vec::IntoIter<StreamSample>,
+    generation: u32,
+    finished: bool,
+    chunks: Receiver<Chunk>,
+    seeks: Sender<Seek>,
+    // Dropping the decoder cancels the task.
+    _task: Option<Task<()>>,
+}
+
+impl StreamingDecoder {
+    fn new(source: &StreamingAudioSource, looping: bool) -> Self {
+        let (chunk_sender, chunks) = async_channel::bounded(BUFFERED_CHUNKS);
+        let (seeks, seek_receiver) = async_channel::unbounded();
+        let (path, channels) = (source.path.clone(), source.channels);
+        // The file is opened by the task, so that starting the playback doesn't block on it.
+        let task = IoTaskPool::get().spawn(async move {
+            match open_file(&path) {
+                Ok(decoder) if decoder.channels() == channels => {
+                    decode_chunks(decoder, looping, chunk_sender, seek_receiver, &path).await;
+                }
+                Ok(_) => warn!(
+                    "Failed to stream audio from {}: the file changed since it was loaded",
+                    path.display()
+                ),
+                Err(err) => warn!("Failed to stream audio from {}: {err}", path.display()),
+            }
+        });
+        Self::from_chunks(
+            channels,
+            source.sample_rate,
+            (!looping).then_some(source.total_duration).flatten(),
+            chunks,
+            seeks,
+            Some(task),
+        )
+    }
+
+    fn from_chunks(
+        channels: u16,
+        sample_rate: u32,
+        total_duration: Option<Duration>,
+        chunks: Receiver<Chunk>,
+        seeks: Sender<Seek>,
+        task: Option<Task<()>>,
+    ) -> Self {
+        Self {
+            channels,
+            sample_rate,
+            total_duration,
+            samples: Vec::new().into_iter(),
+            generation: 0,
+            finished: false,
+            chunks,
+            seeks,
+            _task: task,
+        }
+    }
+}
+
+/// Sends the chunks of `decoder` to the playback, until it is dropped.
+async fn decode_chunks(
+    mut decoder: FileDecoder,
+    looping: bool,
+    chunks: Sender<Chunk>,
+    seeks: Receiver<Seek>,
+    path: &Path,
+) {
+    let chunk_len = CHUNK_FRAMES * decoder.channels() as usize;
+    let mut generation = 0;
+    let mut end = false;
+    let mut rewound = false;
+    loop {
+        // After the last chunk, wait for a seek to decode again.
+        let mut seek = if end {
+            match seeks.recv().await {
+                Ok(seek) => Some(seek),
+                Err(_) => return,
+            }
+        } else {
+            None
+        };
+        // Only the latest seek matters.
+        while let Ok(next) = seeks.try_recv() {
+            seek = Some(next);
+        }
+        if let Some(seek) = seek {
+            if let Err(err) = decoder.try_seek(seek.position) {
+                warn!("Failed to seek in {}: {err}", path.display());
+            }
+            generation = seek.generation;
+            rewound = false;
+        }
+
+        let mut chunk = decode_chunk(&mut decoder, chunk_len, looping, &mut rewound, path);
+        chunk.generation = generation;
+        end = chunk.end;
+        if chunks.send(chunk).await.is_err() {
+            // The decoder was dropped.
+            return;
+        }
+    }
+}
+
+/// Decodes the next chunk of `decoder`, rewinding it at the end of the file if `looping`.
+fn decode_chunk(
+    decoder: &mut FileDecoder,
+    chunk_len: usize,
+    looping: bool,
+    rewound: &mut bool,
+    path: &Path,
+) -> Chunk {
+    let samples: Vec<_> = decoder.by_ref().take(chunk_len).collect();
+    let mut end = samples.len() < chunk_len;
+    // A file without samples would be rewound forever.
+    if end && looping && !(samples.is_empty() && *rewound) {
+        match decoder.try_seek(Duration::ZERO) {
+            Ok(()) => end = false,
+            Err(err) => warn!("Failed to loop {}: {err}", path.display()),
+        }
+        *rewound = true;
+    } else if !samples.is_empty() {
+        *rewound = false;
+    }
+    Chunk {
+        generation: 0,
+        samples,
+        end,
+    }
+}
+
+impl Iterator for StreamingDecoder {
+    type Item = StreamSample;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(sample) = self.samples.next() {
+                return Some(sample);
+            }
+            if self.finished {
+                return None;
+            }
+            match self.chunks.try_recv() {
+                // Chunks decoded before the last seek are skipped.
+                Ok(chunk) if chunk.generation != self.generation => {}
+                Ok(chunk) => {
+                    self.finished = chunk.end;
+                    self.samples = chunk.samples.into_iter();
+                }
+                Err(TryRecvError::Empty) => {
+                    // Chunks are made of whole frames, so a frame of silence keeps the channels
+                    // in order.
+                    self.samples = vec![0; self.channels as usize].into_iter();
+                }
+                Err(TryRecvError::Closed) => {
+                    self.finished = true;
+                }
+            }
+        }
+    }
+}
+
+impl Source for StreamingDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.total_duration
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        let generation = self.generation.wrapping_add(1);
+        self.seeks
+            .try_send(Seek {
+                position: pos,
+                generation,
+            })
+            .map_err(|_| SeekError::NotSupported {
+                underlying_source: core::any::type_name::<Self>(),
+            })?;
+        self.generation = generation;
+        self.samples = Vec::new().into_iter();
+        self.finished = false;
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "wav"))]
+mod tests {
+    use super::*;
+    use bevy_tasks::{tick_global_task_pools_on_main_thread, TaskPool};
+
+    /// Writes a mono 16-bit wav file with the given samples to the temporary directory.
+    fn write_wav(name: &str, samples: &[i16]) -> PathBuf {
+        let data_len = 2 * samples.len() as u32;
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        // PCM, 1 channel, 44100 Hz, 88200 bytes per second, 2 bytes per frame, 16 bits.
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&44100u32.to_le_bytes());
+        wav.extend_from_slice(&88200u32.to_le_bytes());
+        wav.extend_from_slice(&2u16.to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_len.to_le_bytes());
+        for sample in samples {
+            wav.extend_from_slice(&sample.to_le_bytes());
+        }
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, wav).unwrap();
+        path
+    }
+
+    /// Returns the first `len` samples of `source` that aren't silent, or all of them if there are
+    /// fewer, running the decoding task until they are decoded.
+    fn stream(mut source: impl Iterator<Item = i16>, len: usize) -> Vec<i16> {
+        let mut samples = Vec::new();
+        while samples.len() < len {
+            tick_global_task_pools_on_main_thread();
+            match source.next() {
+                Some(0) => {}
+                Some(sample) => samples.push(sample),
+                None => break,
+            }
+        }
+        samples
+    }
+
+    fn decoder() -> (StreamingDecoder, Sender<Chunk>, Receiver<Seek>) {
+        let (chunk_sender, chunks) = async_channel::bounded(BUFFERED_CHUNKS);
+        let (seeks, seek_receiver) = async_channel::unbounded();
+        let decoder = StreamingDecoder::from_chunks(1, 44100, None, chunks, seeks, None);
+        (decoder, chunk_sender, seek_receiver)
+    }
+
+    #[test]
+    fn stream_wav() {
+        IoTaskPool::get_or_init(TaskPool::new);
+        let samples: Vec<i16> = (1..=100).collect();
+        let path = write_wav("bevy_audio_stream_wav.wav", &samples);
+        let source = StreamingAudioSource::new(&path).unwrap();
+        assert_eq!((source.channels, source.sample_rate), (1, 44100));
+
+        // The silence played until the first chunk is decoded is skipped.
+        assert_eq!(stream(source.decoder(), usize::MAX), samples);
+
+        let looped: Vec<i16> = samples.iter().copied().cycle().take(250).collect();
+        assert_eq!(stream(source.looping_decoder(), 250), looped);
+    }
+
+    #[test]
+    fn rewind_looping_file() {
+        let path = write_wav("bevy_audio_rewind_looping_file.wav", &[1, 2, 3]);
+        let mut decoder = open_file(&path).unwrap();
+        let mut rewound = false;
+
+        let chunk = decode_chunk(&mut decoder, 2, true, &mut rewound, &path);
+        assert_eq!(
+            (chunk.samples, chunk.end, rewound),
+            (vec![1, 2], false, false)
+        );
+        let chunk = decode_chunk(&mut decoder, 2, true, &mut rewound, &path);
+        assert_eq!((chunk.samples, chunk.end, rewound), (vec![3], false, true));
+        let chunk = decode_chunk(&mut decoder, 2, true, &mut rewound, &path);
+        assert_eq!(
+            (chunk.samples, chunk.end, rewound),
+            (vec![1, 2], false, false)
+        );
+    }
+
+    #[test]
+    fn play_silence_on_underrun() {
+        let (mut decoder, chunks, _seeks) = decoder();
+        assert_eq!(decoder.next(), Some(0));
+
+        chunks
+            .try_send(Chunk {
+                generation: 0,
+                samples: vec![1],
+                end: false,
+            })
+            .unwrap();
+        assert_eq!(decoder.next(), Some(1));
+        assert_eq!(decoder.next(), Some(0));
+
+        drop(chunks);
+        assert_eq!(decoder.next(), None);
+    }
+
+    #[test]
+    fn skip_chunks_decoded_before_seek() {
+        let (mut decoder, chunks, seeks) = decoder();
+        chunks
+            .try_send(Chunk {
+                generation: 0,
+                samples: vec![1, 2],
+                end: false,
+            })
+            .unwrap();
+        assert_eq!(decoder.next(), Some(1));
+
+        decoder.try_seek(Duration::from_secs(1)).unwrap();
+        let seek = seeks.try_recv().unwrap();
+        assert_eq!(
+            (seek.position, seek.generation),
+            (Duration::from_secs(1), 1)
+        );
+
+        for (generation, sample) in [(0, 3), (1, 4)] {
+            chunks
+                .try_send(Chunk {
+                    generation,
+                    samples: vec![sample],
+                    end: true,
+                })
+                .unwrap();
+        }
+        assert_eq!(decoder.next(), Some(4));
+        assert_eq!(decoder.next(), None);
+    }
+}
@@ -3,7 +3,37 @@
 @group(0) @binding(0) var in_texture: texture_2d<f32>;
 @group(0) @binding(1) var in_sampler: sampler;
 
+#ifdef HDR_OUTPUT_PQ
+// Converts linear BT.709 colors to BT.2020 primaries.
+const BT709_TO_BT2020 = mat3x3<f32>(
+    vec3(0.6274, 0.0691, 0.0164),
+    vec3(0.3293, 0.9195, 0.0880),
+    vec3(0.0433, 0.0114, 0.8956),
+);
+
+// The PQ (SMPTE ST 2084) inverse EOTF, from a luminance in nits.
+fn pq_encode(nits: vec3<f32>) -> vec3<f32> {
+    let m1 = 0.1593017578125;
+    let m2 = 78.84375;
+    let c1 = 0.8359375;
+    let c2 = 18.8515625;
+    let c3 = 18.6875;
+    let y = pow(clamp(nits / 10000.0, vec3(0.0), vec3(1.0)), vec3(m1));
+    return pow((c1 + c2 * y) / (1.0 + c3 * y), vec3(m2));
+}
+#endif
+
 @fragment
 fn fs_main(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
-    return textureSample(in_texture, in_sampler, in.uv);
+    let color = textureSample(in_texture, in_sampler, in.uv);
+#ifdef HDR_OUTPUT_PQ
+    // SDR white is shown at the paper white.
+    let nits = BT709_TO_BT2020 * color.rgb * f32(#{HDR_OUTPUT_PAPER_WHITE});
+    return vec4(pq_encode(nits), color.a);
+#else ifdef HDR_OUTPUT_PAPER_WHITE
+    // Extended linear sRGB shows 1.0 at 80 nits, and SDR white is shown at the paper white.
+    return vec4(color.rgb * (f32(#{HDR_OUTPUT_PAPER_WHITE}) / 80.0), color.a);
+#else
+    return color;
+#endif
 }
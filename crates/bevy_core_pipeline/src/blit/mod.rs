@@ -9,6 +9,7 @@ use bevy_render::{
     renderer::RenderDevice,
     RenderApp,
 };
+use bevy_window::HdrOutputFormat;
 
 use crate::fullscreen_vertex_shader::fullscreen_shader_vertex_state;
 
@@ -71,19 +72,37 @@ pub struct BlitPipelineKey {
     pub texture_format: TextureFormat,
     pub blend_state: Option<BlendState>,
     pub samples: u32,
+    /// When writing to a window presented in HDR, the luminance of SDR white in nits, from
+    /// [`HdrOutput::paper_white`](bevy_window::HdrOutput::paper_white).
+    ///
+    /// The output is then encoded in [`BlitPipelineKey::hdr_output_format`].
+    pub hdr_paper_white_nits: Option<u32>,
+    /// The format the output is encoded in when [`BlitPipelineKey::hdr_paper_white_nits`] is set.
+    pub hdr_output_format: HdrOutputFormat,
 }
 
 impl SpecializedRenderPipeline for BlitPipeline {
     type Key = BlitPipelineKey;
 
     fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mut shader_defs = Vec::new();
+        if let Some(paper_white) = key.hdr_paper_white_nits {
+            shader_defs.push(ShaderDefVal::UInt(
+                "HDR_OUTPUT_PAPER_WHITE".into(),
+                paper_white,
+            ));
+            if key.hdr_output_format == HdrOutputFormat::Hdr10 {
+                shader_defs.push("HDR_OUTPUT_PQ".into());
+            }
+        }
+
         RenderPipelineDescriptor {
             label: Some("blit pipeline".into()),
             layout: vec![self.texture_bind_group.clone()],
             vertex: fullscreen_shader_vertex_state(),
             fragment: Some(FragmentState {
                 shader: BLIT_SHADER_HANDLE,
-                shader_defs: vec![],
+                shader_defs,
                 entry_point: "fs_main".into(),
                 targets: vec![Some(ColorTargetState {
                     format: key.texture_format,
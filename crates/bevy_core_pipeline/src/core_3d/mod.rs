@@ -26,6 +26,7 @@ pub mod graph {
         MainTransparentPass,
         EndMainPass,
         Taa,
+        TemporalUpscaling,
         MotionBlur,
         Bloom,
         AutoExposure,
@@ -114,9 +115,10 @@ use crate::{
     },
     dof::DepthOfFieldNode,
     prepass::{
-        node::PrepassNode, AlphaMask3dPrepass, DeferredPrepass, DepthPrepass, MotionVectorPrepass,
-        NormalPrepass, Opaque3dPrepass, OpaqueNoLightmap3dBatchSetKey, OpaqueNoLightmap3dBinKey,
-        ViewPrepassTextures, MOTION_VECTOR_PREPASS_FORMAT, NORMAL_PREPASS_FORMAT,
+        node::PrepassNode, AlphaMask3dPrepass, DeferredPrepass, DepthPrepass, EntityIndexPrepass,
+        MotionVectorPrepass, NormalPrepass, Opaque3dPrepass, OpaqueNoLightmap3dBatchSetKey,
+        OpaqueNoLightmap3dBinKey, ViewPrepassTextures, ENTITY_INDEX_PREPASS_FORMAT,
+        MOTION_VECTOR_PREPASS_FORMAT, NORMAL_PREPASS_FORMAT,
     },
     skybox::SkyboxPlugin,
     tonemapping::TonemappingNode,
@@ -652,6 +654,7 @@ pub fn extract_camera_prepass_phase(
                 Has<NormalPrepass>,
                 Has<MotionVectorPrepass>,
                 Has<DeferredPrepass>,
+                Has<EntityIndexPrepass>,
             ),
             With<Camera3d>,
         >,
@@ -670,6 +673,7 @@ pub fn extract_camera_prepass_phase(
         normal_prepass,
         motion_vector_prepass,
         deferred_prepass,
+        entity_index_prepass,
     ) in cameras_3d.iter()
     {
         if !camera.is_active {
@@ -687,7 +691,7 @@ pub fn extract_camera_prepass_phase(
         // This is the main 3D camera, so we use the first subview index (0).
         let retained_view_entity = RetainedViewEntity::new(main_entity.into(), None, 0);
 
-        if depth_prepass || normal_prepass || motion_vector_prepass {
+        if depth_prepass || normal_prepass || motion_vector_prepass || entity_index_prepass {
             opaque_3d_prepass_phases.insert_or_clear(retained_view_entity, gpu_preprocessing_mode);
             alpha_mask_3d_prepass_phases
                 .insert_or_clear(retained_view_entity, gpu_preprocessing_mode);
@@ -712,7 +716,8 @@ pub fn extract_camera_prepass_phase(
             .insert_if(DepthPrepass, || depth_prepass)
             .insert_if(NormalPrepass, || normal_prepass)
             .insert_if(MotionVectorPrepass, || motion_vector_prepass)
-            .insert_if(DeferredPrepass, || deferred_prepass);
+            .insert_if(DeferredPrepass, || deferred_prepass)
+            .insert_if(EntityIndexPrepass, || entity_index_prepass);
     }
 
     opaque_3d_prepass_phases.retain(|view_entity, _| live_entities.contains(view_entity));
@@ -929,6 +934,7 @@ pub fn prepare_prepass_textures(
         Has<NormalPrepass>,
         Has<MotionVectorPrepass>,
         Has<DeferredPrepass>,
+        Has<EntityIndexPrepass>,
     )>,
 ) {
     let mut depth_textures = <HashMap<_, _>>::default();
@@ -936,6 +942,7 @@ pub fn prepare_prepass_textures(
     let mut deferred_textures = <HashMap<_, _>>::default();
     let mut deferred_lighting_id_textures = <HashMap<_, _>>::default();
     let mut motion_vectors_textures = <HashMap<_, _>>::default();
+    let mut entity_index_textures = <HashMap<_, _>>::default();
     for (
         entity,
         camera,
@@ -945,6 +952,7 @@ pub fn prepare_prepass_textures(
         normal_prepass,
         motion_vector_prepass,
         deferred_prepass,
+        entity_index_prepass,
     ) in &views_3d
     {
         if !opaque_3d_prepass_phases.contains_key(&view.retained_view_entity)
@@ -977,6 +985,7 @@ pub fn prepare_prepass_textures(
                         dimension: TextureDimension::D2,
                         format: CORE_3D_DEPTH_FORMAT,
                         usage: TextureUsages::COPY_DST
+                            | TextureUsages::COPY_SRC
                             | TextureUsages::RENDER_ATTACHMENT
                             | TextureUsages::TEXTURE_BINDING,
                         view_formats: &[],
@@ -1074,6 +1083,29 @@ pub fn prepare_prepass_textures(
                 .clone()
         });
 
+        let cached_entity_index_texture = entity_index_prepass.then(|| {
+            entity_index_textures
+                .entry(camera.target.clone())
+                .or_insert_with(|| {
+                    texture_cache.get(
+                        &render_device,
+                        TextureDescriptor {
+                            label: Some("prepass_entity_index_texture"),
+                            size,
+                            mip_level_count: 1,
+                            sample_count: msaa.samples(),
+                            dimension: TextureDimension::D2,
+                            format: ENTITY_INDEX_PREPASS_FORMAT,
+                            usage: TextureUsages::RENDER_ATTACHMENT
+                                | TextureUsages::TEXTURE_BINDING
+                                | TextureUsages::COPY_SRC,
+                            view_formats: &[],
+                        },
+                    )
+                })
+                .clone()
+        });
+
         commands.entity(entity).insert(ViewPrepassTextures {
             depth: cached_depth_texture
                 .map(|t| ColorAttachment::new(t, None, Some(LinearRgba::BLACK))),
@@ -1088,6 +1120,9 @@ pub fn prepare_prepass_textures(
                 .map(|t| ColorAttachment::new(t, None, Some(LinearRgba::BLACK))),
             deferred_lighting_pass_id: cached_deferred_lighting_pass_id_texture
                 .map(|t| ColorAttachment::new(t, None, Some(LinearRgba::BLACK))),
+            // Cleared to 0, which is never the high bits of an entity
+            entity_index: cached_entity_index_texture
+                .map(|t| ColorAttachment::new(t, None, Some(LinearRgba::BLACK))),
             size,
         });
     }
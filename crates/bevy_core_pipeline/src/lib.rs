@@ -20,10 +20,12 @@ pub mod motion_blur;
 pub mod msaa_writeback;
 pub mod oit;
 pub mod post_process;
+pub mod post_process_stack;
 pub mod prepass;
 mod skybox;
 pub mod smaa;
 mod taa;
+mod temporal_upscaling;
 pub mod tonemapping;
 pub mod upscaling;
 
@@ -38,6 +40,11 @@ pub mod experimental {
             TemporalAntiAliasNode, TemporalAntiAliasPlugin, TemporalAntiAliasing,
         };
     }
+    pub mod temporal_upscaling {
+        pub use crate::temporal_upscaling::{
+            TemporalUpscalingNode, TemporalUpscalingPlugin, Upscaling, UpscalingQuality,
+        };
+    }
 }
 
 /// The core pipeline prelude.
@@ -61,7 +68,10 @@ use crate::{
     motion_blur::MotionBlurPlugin,
     msaa_writeback::MsaaWritebackPlugin,
     post_process::PostProcessingPlugin,
-    prepass::{DeferredPrepass, DepthPrepass, MotionVectorPrepass, NormalPrepass},
+    post_process_stack::PostProcessStackPlugin,
+    prepass::{
+        DeferredPrepass, DepthPrepass, EntityIndexPrepass, MotionVectorPrepass, NormalPrepass,
+    },
     smaa::SmaaPlugin,
     tonemapping::TonemappingPlugin,
     upscaling::UpscalingPlugin,
@@ -87,6 +97,7 @@ impl Plugin for CorePipelinePlugin {
             .register_type::<NormalPrepass>()
             .register_type::<MotionVectorPrepass>()
             .register_type::<DeferredPrepass>()
+            .register_type::<EntityIndexPrepass>()
             .add_plugins((
                 Core2dPlugin,
                 Core3dPlugin,
@@ -103,6 +114,7 @@ impl Plugin for CorePipelinePlugin {
                 SmaaPlugin,
                 PostProcessingPlugin,
                 OrderIndependentTransparencyPlugin,
-            ));
+            ))
+            .add_plugins(PostProcessStackPlugin);
     }
 }
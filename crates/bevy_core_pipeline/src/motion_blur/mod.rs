@@ -37,9 +37,13 @@ pub mod pipeline;
 ///   have their edges blurred.
 /// - Transparent objects do not write to depth or motion vectors, so they cannot be blurred.
 ///
-/// Other approaches, such as *A Reconstruction Filter for Plausible Motion Blur* produce more
-/// correct results, but are more expensive and complex, and have other kinds of artifacts. This
-/// implementation is relatively inexpensive and effective.
+/// The [`MotionBlurQuality::Medium`] and [`MotionBlurQuality::High`] tiers address the first
+/// artifact by dilating the motion vectors of fast objects over their surroundings, using *A
+/// Reconstruction Filter for Plausible Motion Blur*. They are more expensive, see
+/// [`MotionBlurQuality`], which is added to the camera along with this component.
+///
+/// Skinned and morphed meshes are blurred along the motion of their vertices, as the motion vector
+/// prepass computes their previous positions from last frame's joints and morph weights.
 ///
 /// # Usage
 ///
@@ -59,7 +63,7 @@ pub mod pipeline;
 #[derive(Reflect, Component, Clone, ExtractComponent, ShaderType)]
 #[reflect(Component, Default)]
 #[extract_component_filter(With<Camera>)]
-#[require(DepthPrepass, MotionVectorPrepass)]
+#[require(DepthPrepass, MotionVectorPrepass, MotionBlurQuality)]
 pub struct MotionBlur {
     /// The strength of motion blur from `0.0` to `1.0`.
     ///
@@ -107,8 +111,45 @@ impl Default for MotionBlur {
     }
 }
 
+/// A component that selects how [`MotionBlur`] handles objects that move differently from their
+/// surroundings, from the cheapest to the most correct tier.
+///
+/// It's added to cameras with [`MotionBlur`], at the [`MotionBlurQuality::Low`] tier by default.
+#[derive(
+    Reflect, Component, Clone, Copy, Debug, Default, PartialEq, Eq, Hash, ExtractComponent,
+)]
+#[reflect(Component, Default, Debug, PartialEq, Hash)]
+#[extract_component_filter(With<Camera>)]
+pub enum MotionBlurQuality {
+    /// Each pixel is blurred along its own motion vector.
+    ///
+    /// The blur of fast objects stops at their silhouette, as the pixels around them don't move.
+    #[default]
+    Low,
+    /// The motion vectors are dilated: the screen is split into tiles of [`MOTION_BLUR_TILE_SIZE`]
+    /// pixels, and each pixel is blurred along the fastest motion of its neighboring tiles. Samples
+    /// are then weighted by depth and motion, so that fast objects blur over what's behind them.
+    ///
+    /// This costs two extra passes over the motion vectors.
+    Medium,
+    /// Like [`MotionBlurQuality::Medium`], but every other sample follows the pixel's own motion
+    /// vector rather than the dominant motion of its tile.
+    ///
+    /// This keeps the blur of pixels that move along a different path than their tile, such as
+    /// limbs swinging around a body or spinning wheels, close to the curve they follow.
+    High,
+}
+
+/// The size of the tiles that [`MotionBlurQuality::Medium`] and [`MotionBlurQuality::High`]
+/// dilate motion vectors over, in pixels.
+///
+/// The blur of an object extends up to one tile beyond its silhouette.
+pub const MOTION_BLUR_TILE_SIZE: u32 = 16;
+
 pub const MOTION_BLUR_SHADER_HANDLE: Handle<Shader> =
     Handle::weak_from_u128(987457899187986082347921);
+pub const VELOCITY_DILATION_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(198238603712584466930187);
 
 /// Adds support for per-object motion blur to the app. See [`MotionBlur`] for details.
 pub struct MotionBlurPlugin;
@@ -120,10 +161,19 @@ impl Plugin for MotionBlurPlugin {
             "motion_blur.wgsl",
             Shader::from_wgsl
         );
-        app.add_plugins((
-            ExtractComponentPlugin::<MotionBlur>::default(),
-            UniformComponentPlugin::<MotionBlur>::default(),
-        ));
+        load_internal_asset!(
+            app,
+            VELOCITY_DILATION_SHADER_HANDLE,
+            "velocity_dilation.wgsl",
+            Shader::from_wgsl
+        );
+        app.register_type::<MotionBlur>()
+            .register_type::<MotionBlurQuality>()
+            .add_plugins((
+                ExtractComponentPlugin::<MotionBlur>::default(),
+                ExtractComponentPlugin::<MotionBlurQuality>::default(),
+                UniformComponentPlugin::<MotionBlur>::default(),
+            ));
 
         let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
             return;
@@ -131,9 +181,14 @@ impl Plugin for MotionBlurPlugin {
 
         render_app
             .init_resource::<SpecializedRenderPipelines<pipeline::MotionBlurPipeline>>()
+            .init_resource::<SpecializedRenderPipelines<pipeline::VelocityDilationPipeline>>()
             .add_systems(
                 Render,
-                pipeline::prepare_motion_blur_pipelines.in_set(RenderSet::Prepare),
+                (
+                    pipeline::prepare_motion_blur_pipelines.in_set(RenderSet::Prepare),
+                    pipeline::prepare_velocity_dilation_textures
+                        .in_set(RenderSet::PrepareResources),
+                ),
             );
 
         render_app
@@ -156,6 +211,34 @@ impl Plugin for MotionBlurPlugin {
             return;
         };
 
-        render_app.init_resource::<pipeline::MotionBlurPipeline>();
+        render_app
+            .init_resource::<pipeline::MotionBlurPipeline>()
+            .init_resource::<pipeline::VelocityDilationPipeline>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::world::World;
+
+    use super::{MotionBlur, MotionBlurQuality};
+
+    #[test]
+    fn motion_blur_defaults_to_low_quality() {
+        let mut world = World::new();
+        let camera = world.spawn(MotionBlur::default()).id();
+        assert_eq!(
+            world.get::<MotionBlurQuality>(camera),
+            Some(&MotionBlurQuality::Low)
+        );
+
+        // An explicit tier is kept.
+        let camera = world
+            .spawn((MotionBlur::default(), MotionBlurQuality::High))
+            .id();
+        assert_eq!(
+            world.get::<MotionBlurQuality>(camera),
+            Some(&MotionBlurQuality::High)
+        );
     }
 }
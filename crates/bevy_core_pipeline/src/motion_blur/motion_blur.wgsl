@@ -24,6 +24,116 @@ struct MotionBlur {
 @group(0) @binding(4) var<uniform> settings: MotionBlur;
 @group(0) @binding(5) var<uniform> globals: Globals;
 
+#ifdef VELOCITY_DILATION
+// The longest motion vector around each tile, see `velocity_dilation.wgsl`.
+@group(1) @binding(0) var neighbor_max: texture_2d<f32>;
+
+const TILE_SIZE: u32 = #{MOTION_BLUR_TILE_SIZE}u;
+
+// The relative difference of depth over which a sample goes from being in front of the blurred
+// pixel to being behind it.
+const SOFT_DEPTH_EXTENT: f32 = 0.05;
+
+// A value proportional to the view space depth of a perspective camera, from the reverse-Z depth.
+// Fragments without depth, like the skybox, are infinitely far.
+fn depth_to_distance(depth: f32) -> f32 {
+    return 1.0 / max(depth, 1e-7);
+}
+
+// 1.0 if the fragment at distance `a` is in front of the one at distance `b`, fading to 0.0 as it
+// goes behind it.
+fn soft_depth_compare(a: f32, b: f32) -> f32 {
+    return clamp(1.0 - (a - b) / (SOFT_DEPTH_EXTENT * min(a, b)), 0.0, 1.0);
+}
+
+// The fraction of the time that an object blurred over `radius` pixels covers a point `distance`
+// pixels away.
+fn cone(distance: f32, radius: f32) -> f32 {
+    return clamp(1.0 - distance / radius, 0.0, 1.0);
+}
+
+// Whether a point `distance` pixels away is within `radius` pixels, with a smooth edge.
+fn cylinder(distance: f32, radius: f32) -> f32 {
+    return 1.0 - smoothstep(0.95 * radius, 1.05 * radius, distance);
+}
+
+// Blurs the pixel with the reconstruction filter of *A Reconstruction Filter for Plausible Motion
+// Blur*, McGuire et al. 2012: samples are taken along the dilated motion of the tile, and weighted
+// by whether they are in front of the pixel and by how far their blur reaches.
+fn reconstruct(
+    uv: vec2<f32>,
+    frag_coords: vec2<i32>,
+    msaa_sample: u32,
+    base_color: vec4<f32>,
+    this_motion_vector: vec2<f32>,
+    this_depth: f32,
+) -> vec4<f32> {
+    let texture_size = vec2<f32>(textureDimensions(screen_texture));
+    // The radii of the blur, which extends half of the exposure on each side of the pixel.
+    let blur_scale = 0.5 * settings.shutter_angle * texture_size;
+    let tile_motion = textureLoad(neighbor_max, vec2<u32>(frag_coords) / TILE_SIZE, 0).rg;
+    let tile_radius = tile_motion * blur_scale;
+    let this_radius = this_motion_vector * blur_scale;
+
+    // Keep small motion sharp, to not break antialiasing.
+    if dot(tile_motion * texture_size, tile_motion * texture_size) < 1.0 {
+        return base_color;
+    }
+
+    let this_speed = max(length(this_radius), 0.5);
+    let this_distance = depth_to_distance(this_depth);
+    let n_samples = max(settings.samples * 2u, 2u);
+    let noise = utils::interleaved_gradient_noise(vec2<f32>(frag_coords), globals.frame_count);
+
+    // The pixel itself is weighted as if it covered itself for the whole exposure, which is less
+    // likely the faster it moves.
+    var weight_total = f32(n_samples) * 0.5 / this_speed;
+    var accumulator = base_color * weight_total;
+
+    for (var i = 0u; i < n_samples; i++) {
+        var direction = tile_radius;
+#ifdef CURVED_MOTION
+        // Alternate with the pixel's own motion, so that pixels that move along a different path
+        // than their tile are blurred along the curve they follow.
+        if (i & 1u) == 1u && length(this_radius) >= 0.5 {
+            direction = this_radius;
+        }
+#endif
+        let t = mix(-1.0, 1.0, (f32(i) + noise) / f32(n_samples));
+        let offset = direction * t;
+        let sample_uv = uv + offset / texture_size;
+        if any(sample_uv < vec2(0.0)) || any(sample_uv > vec2(1.0)) {
+            continue;
+        }
+        let sample_coords = vec2<i32>(sample_uv * texture_size);
+
+        let sample_color = textureSampleLevel(screen_texture, texture_sampler, sample_uv, 0.0);
+        let sample_motion = textureLoad(motion_vectors, sample_coords, i32(msaa_sample)).rg;
+#ifdef NO_DEPTH_TEXTURE_SUPPORT
+        let sample_depth = 0.0;
+#else
+        let sample_depth = textureLoad(depth, sample_coords, i32(msaa_sample));
+#endif
+
+        let distance = length(offset);
+        let sample_speed = max(length(sample_motion * blur_scale), 0.5);
+        let sample_distance = depth_to_distance(sample_depth);
+        let in_front = soft_depth_compare(sample_distance, this_distance);
+        let behind = soft_depth_compare(this_distance, sample_distance);
+
+        // A blurry sample in front of the pixel, a sample behind the blurry pixel, and a sample
+        // blurred together with the pixel.
+        let weight = in_front * cone(distance, sample_speed)
+            + behind * cone(distance, this_speed)
+            + cylinder(distance, sample_speed) * cylinder(distance, this_speed) * 2.0;
+        weight_total += weight;
+        accumulator += weight * sample_color;
+    }
+
+    return accumulator / weight_total;
+}
+#endif  // VELOCITY_DILATION
+
 @fragment
 fn fragment(
     #ifdef MULTISAMPLED
@@ -59,7 +169,16 @@ fn fragment(
     let this_depth = textureSample(depth, texture_sampler, in.uv);
 #endif
 #endif
-    
+
+#ifdef VELOCITY_DILATION
+#ifdef MULTISAMPLED
+    let msaa_sample = sample_index;
+#else
+    let msaa_sample = 0u;
+#endif
+    return reconstruct(in.uv, frag_coords, msaa_sample, base_color, this_motion_vector, this_depth);
+#else   // VELOCITY_DILATION
+
     // The exposure vector is the distance that this fragment moved while the camera shutter was
     // open. This is the motion vector (total distance traveled) multiplied by the shutter angle (a
     // fraction). In film, the shutter angle is commonly 0.5 or "180 degrees" (out of 360 total).
@@ -155,4 +274,5 @@ fn fragment(
         weight_total = 1.0;
     }
     return accumulator / weight_total;
-}
\ No newline at end of file
+#endif  // VELOCITY_DILATION
+}
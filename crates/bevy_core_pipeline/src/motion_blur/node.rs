@@ -14,8 +14,11 @@ use bevy_render::{
 use crate::prepass::ViewPrepassTextures;
 
 use super::{
-    pipeline::{MotionBlurPipeline, MotionBlurPipelineId},
-    MotionBlur,
+    pipeline::{
+        MotionBlurPipeline, MotionBlurPipelineId, VelocityDilationPipeline,
+        VelocityDilationPipelineIds, VelocityDilationTextures,
+    },
+    MotionBlur, MotionBlurQuality,
 };
 
 #[derive(Default)]
@@ -27,13 +30,25 @@ impl ViewNode for MotionBlurNode {
         &'static MotionBlurPipelineId,
         &'static ViewPrepassTextures,
         &'static MotionBlur,
+        &'static MotionBlurQuality,
         &'static Msaa,
+        Option<&'static VelocityDilationPipelineIds>,
+        Option<&'static VelocityDilationTextures>,
     );
     fn run(
         &self,
         _graph: &mut RenderGraphContext,
         render_context: &mut RenderContext,
-        (view_target, pipeline_id, prepass_textures, motion_blur, msaa): QueryItem<Self::ViewQuery>,
+        (
+            view_target,
+            pipeline_id,
+            prepass_textures,
+            motion_blur,
+            quality,
+            msaa,
+            dilation_pipeline_ids,
+            dilation_textures,
+        ): QueryItem<Self::ViewQuery>,
         world: &World,
     ) -> Result<(), NodeRunError> {
         if motion_blur.samples == 0 || motion_blur.shutter_angle <= 0.0 {
@@ -59,6 +74,77 @@ impl ViewNode for MotionBlurNode {
             return Ok(());
         };
 
+        // Dilate the motion vectors before blurring along them.
+        let dilation_bind_group = if *quality != MotionBlurQuality::Low {
+            let (Some(dilation_pipeline_ids), Some(dilation_textures)) =
+                (dilation_pipeline_ids, dilation_textures)
+            else {
+                return Ok(());
+            };
+            let (Some(tile_max_pipeline), Some(neighbor_max_pipeline)) = (
+                pipeline_cache.get_render_pipeline(dilation_pipeline_ids.tile_max),
+                pipeline_cache.get_render_pipeline(dilation_pipeline_ids.neighbor_max),
+            ) else {
+                return Ok(());
+            };
+            let dilation_pipeline = world.resource::<VelocityDilationPipeline>();
+
+            let tile_max_layout = if msaa.samples() == 1 {
+                &dilation_pipeline.tile_max_layout
+            } else {
+                &dilation_pipeline.tile_max_layout_msaa
+            };
+            let tile_max_bind_group = render_context.render_device().create_bind_group(
+                Some("motion_blur_tile_max_bind_group"),
+                tile_max_layout,
+                &BindGroupEntries::single(&prepass_motion_vectors_texture.texture.default_view),
+            );
+            let neighbor_max_bind_group = render_context.render_device().create_bind_group(
+                Some("motion_blur_neighbor_max_bind_group"),
+                &dilation_pipeline.neighbor_max_layout,
+                &BindGroupEntries::single(&dilation_textures.tile_max.default_view),
+            );
+
+            for (label, pipeline, bind_group, destination) in [
+                (
+                    "motion_blur_tile_max_pass",
+                    tile_max_pipeline,
+                    &tile_max_bind_group,
+                    &dilation_textures.tile_max.default_view,
+                ),
+                (
+                    "motion_blur_neighbor_max_pass",
+                    neighbor_max_pipeline,
+                    &neighbor_max_bind_group,
+                    &dilation_textures.neighbor_max.default_view,
+                ),
+            ] {
+                let mut render_pass =
+                    render_context.begin_tracked_render_pass(RenderPassDescriptor {
+                        label: Some(label),
+                        color_attachments: &[Some(RenderPassColorAttachment {
+                            view: destination,
+                            resolve_target: None,
+                            ops: Operations::default(),
+                        })],
+                        depth_stencil_attachment: None,
+                        timestamp_writes: None,
+                        occlusion_query_set: None,
+                    });
+                render_pass.set_render_pipeline(pipeline);
+                render_pass.set_bind_group(0, bind_group, &[]);
+                render_pass.draw(0..3, 0..1);
+            }
+
+            Some(render_context.render_device().create_bind_group(
+                Some("motion_blur_dilation_bind_group"),
+                &motion_blur_pipeline.dilation_layout,
+                &BindGroupEntries::single(&dilation_textures.neighbor_max.default_view),
+            ))
+        } else {
+            None
+        };
+
         let post_process = view_target.post_process_write();
 
         let layout = if msaa.samples() == 1 {
@@ -94,6 +180,9 @@ impl ViewNode for MotionBlurNode {
 
         render_pass.set_render_pipeline(pipeline);
         render_pass.set_bind_group(0, &bind_group, &[]);
+        if let Some(dilation_bind_group) = &dilation_bind_group {
+            render_pass.set_bind_group(1, dilation_bind_group, &[]);
+        }
         render_pass.draw(0..3, 0..1);
 
         Ok(())
@@ -7,6 +7,7 @@ use bevy_ecs::{
 };
 use bevy_image::BevyDefault as _;
 use bevy_render::{
+    camera::ExtractedCamera,
     globals::GlobalsUniform,
     render_resource::{
         binding_types::{
@@ -14,24 +15,34 @@ use bevy_render::{
             texture_depth_2d_multisampled, uniform_buffer_sized,
         },
         BindGroupLayout, BindGroupLayoutEntries, CachedRenderPipelineId, ColorTargetState,
-        ColorWrites, FragmentState, MultisampleState, PipelineCache, PrimitiveState,
+        ColorWrites, Extent3d, FragmentState, MultisampleState, PipelineCache, PrimitiveState,
         RenderPipelineDescriptor, Sampler, SamplerBindingType, SamplerDescriptor, ShaderDefVal,
         ShaderStages, ShaderType, SpecializedRenderPipeline, SpecializedRenderPipelines,
-        TextureFormat, TextureSampleType,
+        TextureDescriptor, TextureDimension, TextureFormat, TextureSampleType, TextureUsages,
     },
     renderer::RenderDevice,
+    texture::{CachedTexture, TextureCache},
     view::{ExtractedView, Msaa, ViewTarget},
 };
 
 use crate::fullscreen_vertex_shader::fullscreen_shader_vertex_state;
 
-use super::{MotionBlur, MOTION_BLUR_SHADER_HANDLE};
+use super::{
+    MotionBlur, MotionBlurQuality, MOTION_BLUR_SHADER_HANDLE, MOTION_BLUR_TILE_SIZE,
+    VELOCITY_DILATION_SHADER_HANDLE,
+};
+
+/// The format of the tile textures, which hold one motion vector per tile.
+const VELOCITY_TILE_FORMAT: TextureFormat = TextureFormat::Rg16Float;
 
 #[derive(Resource)]
 pub struct MotionBlurPipeline {
     pub(crate) sampler: Sampler,
     pub(crate) layout: BindGroupLayout,
     pub(crate) layout_msaa: BindGroupLayout,
+    /// The layout of the dilated motion vectors, for [`MotionBlurQuality::Medium`] and
+    /// [`MotionBlurQuality::High`].
+    pub(crate) dilation_layout: BindGroupLayout,
 }
 
 impl MotionBlurPipeline {
@@ -72,15 +83,24 @@ impl MotionBlurPipeline {
             ),
         );
 
+        let dilation_layout = &BindGroupLayoutEntries::single(
+            ShaderStages::FRAGMENT,
+            // Neighbor max motion vectors
+            texture_2d(TextureSampleType::Float { filterable: false }),
+        );
+
         let sampler = render_device.create_sampler(&SamplerDescriptor::default());
         let layout = render_device.create_bind_group_layout("motion_blur_layout", mb_layout);
         let layout_msaa =
             render_device.create_bind_group_layout("motion_blur_layout_msaa", mb_layout_msaa);
+        let dilation_layout =
+            render_device.create_bind_group_layout("motion_blur_dilation_layout", dilation_layout);
 
         Self {
             sampler,
             layout,
             layout_msaa,
+            dilation_layout,
         }
     }
 }
@@ -96,29 +116,52 @@ impl FromWorld for MotionBlurPipeline {
 pub struct MotionBlurPipelineKey {
     hdr: bool,
     samples: u32,
+    quality: MotionBlurQuality,
 }
 
-impl SpecializedRenderPipeline for MotionBlurPipeline {
-    type Key = MotionBlurPipelineKey;
-
-    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
-        let layout = match key.samples {
-            1 => vec![self.layout.clone()],
-            _ => vec![self.layout_msaa.clone()],
-        };
-
+impl MotionBlurPipelineKey {
+    fn shader_defs(&self) -> Vec<ShaderDefVal> {
         let mut shader_defs = vec![];
 
-        if key.samples > 1 {
+        if self.samples > 1 {
             shader_defs.push(ShaderDefVal::from("MULTISAMPLED"));
         }
 
+        if self.quality != MotionBlurQuality::Low {
+            shader_defs.push("VELOCITY_DILATION".into());
+            shader_defs.push(ShaderDefVal::UInt(
+                "MOTION_BLUR_TILE_SIZE".into(),
+                MOTION_BLUR_TILE_SIZE,
+            ));
+        }
+
+        if self.quality == MotionBlurQuality::High {
+            shader_defs.push("CURVED_MOTION".into());
+        }
+
         #[cfg(all(feature = "webgl", target_arch = "wasm32", not(feature = "webgpu")))]
         {
             shader_defs.push("NO_DEPTH_TEXTURE_SUPPORT".into());
             shader_defs.push("SIXTEEN_BYTE_ALIGNMENT".into());
         }
 
+        shader_defs
+    }
+}
+
+impl SpecializedRenderPipeline for MotionBlurPipeline {
+    type Key = MotionBlurPipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mut layout = match key.samples {
+            1 => vec![self.layout.clone()],
+            _ => vec![self.layout_msaa.clone()],
+        };
+        if key.quality != MotionBlurQuality::Low {
+            layout.push(self.dilation_layout.clone());
+        }
+        let shader_defs = key.shader_defs();
+
         RenderPipelineDescriptor {
             label: Some("motion_blur_pipeline".into()),
             layout,
@@ -146,28 +189,254 @@ impl SpecializedRenderPipeline for MotionBlurPipeline {
     }
 }
 
+/// The pipelines that dilate motion vectors for [`MotionBlurQuality::Medium`] and
+/// [`MotionBlurQuality::High`].
+///
+/// The tile max pass writes the longest motion vector of each tile, and the neighbor max pass the
+/// longest of each tile and its 8 neighbors.
+#[derive(Resource)]
+pub struct VelocityDilationPipeline {
+    pub(crate) tile_max_layout: BindGroupLayout,
+    pub(crate) tile_max_layout_msaa: BindGroupLayout,
+    pub(crate) neighbor_max_layout: BindGroupLayout,
+}
+
+impl FromWorld for VelocityDilationPipeline {
+    fn from_world(render_world: &mut bevy_ecs::world::World) -> Self {
+        let render_device = render_world.resource::<RenderDevice>();
+
+        let tile_max_layout = render_device.create_bind_group_layout(
+            "motion_blur_tile_max_layout",
+            &BindGroupLayoutEntries::single(
+                ShaderStages::FRAGMENT,
+                // Motion Vectors
+                texture_2d(TextureSampleType::Float { filterable: false }),
+            ),
+        );
+        let tile_max_layout_msaa = render_device.create_bind_group_layout(
+            "motion_blur_tile_max_layout_msaa",
+            &BindGroupLayoutEntries::single(
+                ShaderStages::FRAGMENT,
+                // Motion Vectors
+                texture_2d_multisampled(TextureSampleType::Float { filterable: false }),
+            ),
+        );
+        let neighbor_max_layout = render_device.create_bind_group_layout(
+            "motion_blur_neighbor_max_layout",
+            &BindGroupLayoutEntries::single(
+                ShaderStages::FRAGMENT,
+                // Tile max motion vectors
+                texture_2d(TextureSampleType::Float { filterable: false }),
+            ),
+        );
+
+        Self {
+            tile_max_layout,
+            tile_max_layout_msaa,
+            neighbor_max_layout,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+pub enum VelocityDilationPipelineKey {
+    TileMax { multisampled: bool },
+    NeighborMax,
+}
+
+impl SpecializedRenderPipeline for VelocityDilationPipeline {
+    type Key = VelocityDilationPipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mut shader_defs = vec![ShaderDefVal::UInt(
+            "MOTION_BLUR_TILE_SIZE".into(),
+            MOTION_BLUR_TILE_SIZE,
+        )];
+        let (label, layout, entry_point) = match key {
+            VelocityDilationPipelineKey::TileMax { multisampled } => {
+                if multisampled {
+                    shader_defs.push("MULTISAMPLED".into());
+                }
+                let layout = if multisampled {
+                    &self.tile_max_layout_msaa
+                } else {
+                    &self.tile_max_layout
+                };
+                ("motion_blur_tile_max_pipeline", layout, "tile_max")
+            }
+            VelocityDilationPipelineKey::NeighborMax => {
+                shader_defs.push("NEIGHBOR_MAX".into());
+                (
+                    "motion_blur_neighbor_max_pipeline",
+                    &self.neighbor_max_layout,
+                    "neighbor_max",
+                )
+            }
+        };
+
+        RenderPipelineDescriptor {
+            label: Some(label.into()),
+            layout: vec![layout.clone()],
+            vertex: fullscreen_shader_vertex_state(),
+            fragment: Some(FragmentState {
+                shader: VELOCITY_DILATION_SHADER_HANDLE,
+                shader_defs,
+                entry_point: entry_point.into(),
+                targets: vec![Some(ColorTargetState {
+                    format: VELOCITY_TILE_FORMAT,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: vec![],
+            zero_initialize_workgroup_memory: false,
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct MotionBlurPipelineId(pub CachedRenderPipelineId);
 
+/// The pipelines of the velocity dilation passes of a view.
+#[derive(Component)]
+pub struct VelocityDilationPipelineIds {
+    pub tile_max: CachedRenderPipelineId,
+    pub neighbor_max: CachedRenderPipelineId,
+}
+
 pub(crate) fn prepare_motion_blur_pipelines(
     mut commands: Commands,
     pipeline_cache: Res<PipelineCache>,
     mut pipelines: ResMut<SpecializedRenderPipelines<MotionBlurPipeline>>,
+    mut dilation_pipelines: ResMut<SpecializedRenderPipelines<VelocityDilationPipeline>>,
     pipeline: Res<MotionBlurPipeline>,
-    views: Query<(Entity, &ExtractedView, &Msaa), With<MotionBlur>>,
+    dilation_pipeline: Res<VelocityDilationPipeline>,
+    views: Query<(Entity, &ExtractedView, &Msaa, &MotionBlurQuality), With<MotionBlur>>,
 ) {
-    for (entity, view, msaa) in &views {
+    for (entity, view, msaa, &quality) in &views {
         let pipeline_id = pipelines.specialize(
             &pipeline_cache,
             &pipeline,
             MotionBlurPipelineKey {
                 hdr: view.hdr,
                 samples: msaa.samples(),
+                quality,
             },
         );
 
-        commands
-            .entity(entity)
-            .insert(MotionBlurPipelineId(pipeline_id));
+        let mut entity_commands = commands.entity(entity);
+        entity_commands.insert(MotionBlurPipelineId(pipeline_id));
+
+        if quality != MotionBlurQuality::Low {
+            entity_commands.insert(VelocityDilationPipelineIds {
+                tile_max: dilation_pipelines.specialize(
+                    &pipeline_cache,
+                    &dilation_pipeline,
+                    VelocityDilationPipelineKey::TileMax {
+                        multisampled: msaa.samples() > 1,
+                    },
+                ),
+                neighbor_max: dilation_pipelines.specialize(
+                    &pipeline_cache,
+                    &dilation_pipeline,
+                    VelocityDilationPipelineKey::NeighborMax,
+                ),
+            });
+        }
+    }
+}
+
+/// The textures of the velocity dilation passes of a view, with one texel per tile.
+#[derive(Component)]
+pub struct VelocityDilationTextures {
+    pub tile_max: CachedTexture,
+    pub neighbor_max: CachedTexture,
+}
+
+pub(crate) fn prepare_velocity_dilation_textures(
+    mut commands: Commands,
+    mut texture_cache: ResMut<TextureCache>,
+    render_device: Res<RenderDevice>,
+    views: Query<
+        (Entity, &ExtractedCamera, &MotionBlurQuality),
+        (With<ExtractedView>, With<MotionBlur>),
+    >,
+) {
+    for (entity, camera, quality) in &views {
+        if *quality == MotionBlurQuality::Low {
+            continue;
+        }
+        let Some(physical_target_size) = camera.physical_target_size else {
+            continue;
+        };
+
+        let tile_count =
+            (physical_target_size + (MOTION_BLUR_TILE_SIZE - 1)) / MOTION_BLUR_TILE_SIZE;
+        let mut texture_descriptor = TextureDescriptor {
+            label: None,
+            size: Extent3d {
+                width: tile_count.x,
+                height: tile_count.y,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: VELOCITY_TILE_FORMAT,
+            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        };
+
+        texture_descriptor.label = Some("motion_blur_tile_max_texture");
+        let tile_max = texture_cache.get(&render_device, texture_descriptor.clone());
+
+        texture_descriptor.label = Some("motion_blur_neighbor_max_texture");
+        let neighbor_max = texture_cache.get(&render_device, texture_descriptor);
+
+        commands.entity(entity).insert(VelocityDilationTextures {
+            tile_max,
+            neighbor_max,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_render::render_resource::ShaderDefVal;
+
+    use super::MotionBlurPipelineKey;
+    use crate::motion_blur::MotionBlurQuality;
+
+    fn has_def(key: MotionBlurPipelineKey, name: &str) -> bool {
+        key.shader_defs().iter().any(|def| match def {
+            ShaderDefVal::Bool(def_name, enabled) => def_name == name && *enabled,
+            ShaderDefVal::UInt(def_name, _) | ShaderDefVal::Int(def_name, _) => def_name == name,
+        })
+    }
+
+    #[test]
+    fn quality_shader_defs() {
+        let key = |quality| MotionBlurPipelineKey {
+            hdr: false,
+            samples: 1,
+            quality,
+        };
+
+        let low = key(MotionBlurQuality::Low);
+        assert!(!has_def(low, "VELOCITY_DILATION"));
+        assert!(!has_def(low, "MOTION_BLUR_TILE_SIZE"));
+        assert!(!has_def(low, "CURVED_MOTION"));
+
+        let medium = key(MotionBlurQuality::Medium);
+        assert!(has_def(medium, "VELOCITY_DILATION"));
+        assert!(has_def(medium, "MOTION_BLUR_TILE_SIZE"));
+        assert!(!has_def(medium, "CURVED_MOTION"));
+
+        let high = key(MotionBlurQuality::High);
+        assert!(has_def(high, "VELOCITY_DILATION"));
+        assert!(has_def(high, "CURVED_MOTION"));
     }
 }
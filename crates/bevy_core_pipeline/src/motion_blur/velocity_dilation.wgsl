@@ -0,0 +1,67 @@
+// Dilates motion vectors for the `Medium` and `High` motion blur qualities.
+//
+// The tile max pass writes the longest motion vector of each tile of `MOTION_BLUR_TILE_SIZE`
+// pixels, and the neighbor max pass the longest motion vector of each tile and its 8 neighbors.
+// This is how far, and in which direction, the fastest object that could cover a pixel moves.
+//
+// See *A Reconstruction Filter for Plausible Motion Blur*, McGuire et al. 2012.
+
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+const TILE_SIZE: u32 = #{MOTION_BLUR_TILE_SIZE}u;
+
+// Returns the motion vector that covers more pixels. Motion vectors are in UV space, so they are
+// scaled to pixels first, which weighs both axes the same.
+fn longest(a: vec2<f32>, b: vec2<f32>, size: vec2<f32>) -> vec2<f32> {
+    let a_pixels = a * size;
+    let b_pixels = b * size;
+    return select(a, b, dot(b_pixels, b_pixels) > dot(a_pixels, a_pixels));
+}
+
+#ifdef NEIGHBOR_MAX
+
+@group(0) @binding(0) var tile_max_texture: texture_2d<f32>;
+
+@fragment
+fn neighbor_max(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let tile_count = vec2<i32>(textureDimensions(tile_max_texture));
+    let tile = vec2<i32>(in.position.xy);
+    let size = vec2<f32>(tile_count) * f32(TILE_SIZE);
+
+    var result = vec2(0.0);
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let neighbor = clamp(tile + vec2(x, y), vec2(0), tile_count - 1);
+            result = longest(result, textureLoad(tile_max_texture, neighbor, 0).rg, size);
+        }
+    }
+    return vec4(result, 0.0, 0.0);
+}
+
+#else   // NEIGHBOR_MAX
+
+#ifdef MULTISAMPLED
+@group(0) @binding(0) var motion_vectors: texture_multisampled_2d<f32>;
+#else
+@group(0) @binding(0) var motion_vectors: texture_2d<f32>;
+#endif
+
+@fragment
+fn tile_max(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let size = textureDimensions(motion_vectors);
+    let first_pixel = vec2<u32>(in.position.xy) * TILE_SIZE;
+    let last_pixel = min(first_pixel + TILE_SIZE, size);
+
+    var result = vec2(0.0);
+    for (var y = first_pixel.y; y < last_pixel.y; y++) {
+        for (var x = first_pixel.x; x < last_pixel.x; x++) {
+            // With MSAA, this is the first sample of each pixel, which is enough to find the fastest
+            // motion of the tile.
+            let motion_vector = textureLoad(motion_vectors, vec2(x, y), 0).rg;
+            result = longest(result, motion_vector, vec2<f32>(size));
+        }
+    }
+    return vec4(result, 0.0, 0.0);
+}
+
+#endif  // NEIGHBOR_MAX
@@ -14,6 +14,7 @@ use bevy_render::{
     view::{Msaa, ViewTarget},
     Render, RenderApp, RenderSet,
 };
+use bevy_window::HdrOutputFormat;
 
 /// This enables "msaa writeback" support for the `core_2d` and `core_3d` pipelines, which can be enabled on cameras
 /// using [`bevy_render::camera::Camera::msaa_writeback`]. See the docs on that field for more information.
@@ -135,6 +136,8 @@ fn prepare_msaa_writeback_pipelines(
                 texture_format: view_target.main_texture_format(),
                 samples: msaa.samples(),
                 blend_state: None,
+                hdr_paper_white_nits: None,
+                hdr_output_format: HdrOutputFormat::default(),
             };
 
             let pipeline = pipelines.specialize(&pipeline_cache, &blit_pipeline, key);
@@ -4,7 +4,7 @@ use bevy_app::prelude::*;
 use bevy_asset::{load_internal_asset, Handle};
 use bevy_ecs::{component::*, prelude::*};
 use bevy_math::UVec2;
-use bevy_reflect::Reflect;
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
 use bevy_render::{
     camera::{Camera, ExtractedCamera},
     extract_component::{ExtractComponent, ExtractComponentPlugin},
@@ -24,9 +24,12 @@ use resolve::{
 };
 use tracing::{trace, warn};
 
-use crate::core_3d::{
-    graph::{Core3d, Node3d},
-    Camera3d,
+use crate::{
+    core_3d::{
+        graph::{Core3d, Node3d},
+        Camera3d,
+    },
+    prepass::DepthPrepass,
 };
 
 /// Module that defines the necessary systems to resolve the OIT buffer and render it to the screen.
@@ -37,15 +40,19 @@ pub const OIT_DRAW_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(404252
 
 /// Used to identify which camera will use OIT to render transparent meshes
 /// and to configure OIT.
-// TODO consider supporting multiple OIT techniques like WBOIT, Moment Based OIT,
+///
+/// The technique used to blend the transparent fragments can be chosen by adding an
+/// [`OrderIndependentTransparencyMethod`] to the camera.
+// TODO consider supporting more OIT techniques like Moment Based OIT,
 // depth peeling, stochastic transparency, ray tracing etc.
-// This should probably be done by adding an enum to this component.
 // We use the same struct to pass on the settings to the drawing shader.
 #[derive(Clone, Copy, ExtractComponent, Reflect, ShaderType)]
 pub struct OrderIndependentTransparencySettings {
     /// Controls how many layers will be used to compute the blending.
     /// The more layers you use the more memory it will use but it will also give better results.
     /// 8 is generally recommended, going above 32 is probably not worth it in the vast majority of cases
+    ///
+    /// This is ignored by [`OrderIndependentTransparencyMethod::WeightedBlended`].
     pub layer_count: i32,
     /// Threshold for which fragments will be added to the blending layers.
     /// This can be tweaked to optimize quality / layers count. Higher values will
@@ -62,6 +69,44 @@ impl Default for OrderIndependentTransparencySettings {
     }
 }
 
+/// The technique used by a camera to render transparent meshes with OIT.
+///
+/// Add this component next to the [`OrderIndependentTransparencySettings`] of a camera to
+/// change it. Cameras without it use [`OrderIndependentTransparencyMethod::PerPixelLists`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Component, ExtractComponent, Reflect)]
+#[reflect(Component, Default, PartialEq)]
+pub enum OrderIndependentTransparencyMethod {
+    /// Stores up to [`OrderIndependentTransparencySettings::layer_count`] fragments per pixel,
+    /// then sorts them by depth and blends them in the resolve pass.
+    ///
+    /// This gives the exact result as long as no pixel is covered by more fragments
+    /// than there are layers, at the cost of memory and sorting time.
+    #[default]
+    PerPixelLists,
+    /// Weighted blended OIT, as described in <https://jcgt.org/published/0002/02/09/>.
+    ///
+    /// Every fragment is accumulated with a weight that decreases with its distance to the
+    /// camera, so the memory use doesn't depend on the number of overlapping fragments and
+    /// nothing needs to be sorted. The result is an approximation: surfaces close to each
+    /// other blend as if they had the same depth.
+    ///
+    /// This method needs a depth prepass to discard the fragments behind opaque meshes, so
+    /// a [`DepthPrepass`] is added to cameras that use it, and removed once they stop using it.
+    WeightedBlended,
+}
+
+impl OrderIndependentTransparencySettings {
+    /// Returns the number of layers allocated per pixel in [`OitBuffers::layers`] with the
+    /// given method.
+    pub fn layers_per_pixel(&self, method: OrderIndependentTransparencyMethod) -> usize {
+        match method {
+            OrderIndependentTransparencyMethod::PerPixelLists => self.layer_count.max(0) as usize,
+            // The weighted color, the weighted alpha and the revealage take 5 `u32`s per pixel.
+            OrderIndependentTransparencyMethod::WeightedBlended => 3,
+        }
+    }
+}
+
 // OrderIndependentTransparencySettings is also a Component. We explicitly implement the trait so
 // we can hook on_add to issue a warning in case `layer_count` is seemingly too high.
 impl Component for OrderIndependentTransparencySettings {
@@ -96,6 +141,10 @@ impl Component for OrderIndependentTransparencySettings {
 ///
 /// The second pass is a single fullscreen triangle pass that sorts all the fragments then blends them together
 /// and outputs the result to the screen.
+///
+/// With [`OrderIndependentTransparencyMethod::WeightedBlended`], the first pass instead accumulates
+/// the weighted colors and the revealage of the fragments in the buffer, and the second pass
+/// normalizes them and blends the result onto the screen.
 pub struct OrderIndependentTransparencyPlugin;
 impl Plugin for OrderIndependentTransparencyPlugin {
     fn build(&self, app: &mut App) {
@@ -108,11 +157,13 @@ impl Plugin for OrderIndependentTransparencyPlugin {
 
         app.add_plugins((
             ExtractComponentPlugin::<OrderIndependentTransparencySettings>::default(),
+            ExtractComponentPlugin::<OrderIndependentTransparencyMethod>::default(),
             OitResolvePlugin,
         ))
-        .add_systems(Update, check_msaa)
+        .add_systems(Update, (check_msaa, update_weighted_blended_depth_prepass))
         .add_systems(Last, configure_depth_texture_usages)
-        .register_type::<OrderIndependentTransparencySettings>();
+        .register_type::<OrderIndependentTransparencySettings>()
+        .register_type::<OrderIndependentTransparencyMethod>();
 
         let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
             return;
@@ -183,6 +234,39 @@ fn check_msaa(cameras: Query<&Msaa, With<OrderIndependentTransparencySettings>>)
     }
 }
 
+/// Marks a camera whose [`DepthPrepass`] was added for weighted blended OIT, so that it's
+/// removed when the camera stops using it.
+#[derive(Component)]
+struct WeightedBlendedDepthPrepass;
+
+// Weighted blended OIT tests the fragments against the depth prepass, as the
+// fragments behind opaque meshes have already been accumulated by the time the
+// resolve pass runs.
+fn update_weighted_blended_depth_prepass(
+    mut commands: Commands,
+    cameras: Query<(
+        Entity,
+        Has<OrderIndependentTransparencySettings>,
+        Option<&OrderIndependentTransparencyMethod>,
+        Has<DepthPrepass>,
+        Has<WeightedBlendedDepthPrepass>,
+    )>,
+) {
+    for (entity, has_oit, method, has_depth_prepass, added_depth_prepass) in &cameras {
+        let weighted_blended =
+            has_oit && method == Some(&OrderIndependentTransparencyMethod::WeightedBlended);
+        if weighted_blended && !has_depth_prepass {
+            commands
+                .entity(entity)
+                .insert((DepthPrepass, WeightedBlendedDepthPrepass));
+        } else if !weighted_blended && added_depth_prepass {
+            commands
+                .entity(entity)
+                .remove::<(DepthPrepass, WeightedBlendedDepthPrepass)>();
+        }
+    }
+}
+
 /// Holds the buffers that contain the data of all OIT layers.
 /// We use one big buffer for the entire app. Each camera will reuse it so it will
 /// always be the size of the biggest OIT enabled camera.
@@ -191,6 +275,8 @@ pub struct OitBuffers {
     /// The OIT layers containing depth and color for each fragments.
     /// This is essentially used as a 3d array where xy is the screen coordinate and z is
     /// the list of fragments rendered with OIT.
+    ///
+    /// With weighted blended OIT, the layers contain the accumulated color and revealage instead.
     pub layers: BufferVec<UVec2>,
     /// Buffer containing the index of the last layer that was written for each fragment.
     pub layer_ids: BufferVec<i32>,
@@ -238,7 +324,11 @@ pub fn prepare_oit_buffers(
     render_device: Res<RenderDevice>,
     render_queue: Res<RenderQueue>,
     cameras: Query<
-        (&ExtractedCamera, &OrderIndependentTransparencySettings),
+        (
+            &ExtractedCamera,
+            &OrderIndependentTransparencySettings,
+            Option<&OrderIndependentTransparencyMethod>,
+        ),
         (
             Changed<ExtractedCamera>,
             Changed<OrderIndependentTransparencySettings>,
@@ -250,15 +340,15 @@ pub fn prepare_oit_buffers(
     // Get the max buffer size for any OIT enabled camera
     let mut max_layer_ids_size = usize::MIN;
     let mut max_layers_size = usize::MIN;
-    for (camera, settings) in &cameras {
+    for (camera, settings, method) in &cameras {
         let Some(size) = camera.physical_target_size else {
             continue;
         };
 
-        let layer_count = settings.layer_count as usize;
+        let layers_per_pixel = settings.layers_per_pixel(method.copied().unwrap_or_default());
         let size = (size.x * size.y) as usize;
         max_layer_ids_size = max_layer_ids_size.max(size);
-        max_layers_size = max_layers_size.max(size * layer_count);
+        max_layers_size = max_layers_size.max(size * layers_per_pixel);
     }
 
     // Create or update the layers buffer based on the max size
@@ -310,3 +400,67 @@ pub fn prepare_oit_buffers(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weighted_blended_depth_prepass() {
+        let mut app = App::new();
+        app.add_systems(Update, update_weighted_blended_depth_prepass);
+
+        let weighted_blended = app
+            .world_mut()
+            .spawn((
+                OrderIndependentTransparencySettings::default(),
+                OrderIndependentTransparencyMethod::WeightedBlended,
+            ))
+            .id();
+        let user_prepass = app
+            .world_mut()
+            .spawn((
+                OrderIndependentTransparencySettings::default(),
+                OrderIndependentTransparencyMethod::WeightedBlended,
+                DepthPrepass,
+            ))
+            .id();
+        app.update();
+        assert!(app
+            .world()
+            .entity(weighted_blended)
+            .contains::<DepthPrepass>());
+
+        // Switching to another method removes the prepass that was added for OIT, but not the
+        // one added by the user.
+        for entity in [weighted_blended, user_prepass] {
+            app.world_mut()
+                .entity_mut(entity)
+                .insert(OrderIndependentTransparencyMethod::PerPixelLists);
+        }
+        app.update();
+        assert!(!app
+            .world()
+            .entity(weighted_blended)
+            .contains::<DepthPrepass>());
+        assert!(app.world().entity(user_prepass).contains::<DepthPrepass>());
+
+        // Disabling OIT removes it too.
+        app.world_mut()
+            .entity_mut(weighted_blended)
+            .insert(OrderIndependentTransparencyMethod::WeightedBlended);
+        app.update();
+        assert!(app
+            .world()
+            .entity(weighted_blended)
+            .contains::<DepthPrepass>());
+        app.world_mut()
+            .entity_mut(weighted_blended)
+            .remove::<OrderIndependentTransparencySettings>();
+        app.update();
+        assert!(!app
+            .world()
+            .entity(weighted_blended)
+            .contains::<DepthPrepass>());
+    }
+}
@@ -3,6 +3,65 @@
 #import bevy_pbr::mesh_view_bindings::{view, oit_layers, oit_layer_ids, oit_settings}
 
 #ifdef OIT_ENABLED
+#ifdef OIT_WEIGHTED_BLENDED
+// Accumulate the fragment in the oit buffer, using weighted blended OIT.
+// See https://jcgt.org/published/0002/02/09/
+//
+// The layers are used as 5 arrays of floats the size of the screen: the first 4 contain the
+// sum of the weighted premultiplied colors, and the last one the sum of `-log(1 - alpha)`,
+// so that the revealage is the exponential of its opposite.
+// `oit_layer_ids` only counts the fragments, so that untouched pixels can be skipped.
+fn oit_draw(position: vec4f, color: vec4f) {
+    if color.a < oit_settings.alpha_threshold {
+        return;
+    }
+
+#ifdef DEPTH_PREPASS
+    // Nothing is sorted or depth tested in the resolve pass, so the fragments behind opaque
+    // meshes have to be discarded here.
+    if position.z < bevy_pbr::prepass_utils::prepass_depth(position, 0u) {
+        return;
+    }
+#endif // DEPTH_PREPASS
+
+    let screen_index = i32(floor(position.x) + floor(position.y) * view.viewport.z);
+    let buffer_size = i32(view.viewport.z * view.viewport.w);
+
+    // A fully opaque fragment would make the revealage 0 and the log infinite.
+    let alpha = min(color.a, 0.999);
+    let view_z = abs(bevy_pbr::view_transformations::depth_ndc_to_view_z(position.z));
+    // Equation 10 of the paper
+    let weight = clamp(
+        10.0 / (1e-5 + pow(view_z / 5.0, 2.0) + pow(view_z / 200.0, 6.0)),
+        1e-2,
+        3e3
+    );
+
+    let weighted_color = vec4(color.rgb * alpha, alpha) * weight;
+    for (var i = 0; i < 4; i += 1) {
+        oit_atomic_add_f32(screen_index + buffer_size * i, weighted_color[i]);
+    }
+    oit_atomic_add_f32(screen_index + buffer_size * 4, -log(1.0 - alpha));
+
+    atomicAdd(&oit_layer_ids[screen_index], 1);
+}
+
+// There are no floating point atomics in WGSL, so they are emulated with a compare exchange loop.
+fn oit_atomic_add_f32(index: i32, value: f32) {
+    var old = atomicLoad(&oit_layers[index]);
+    loop {
+        let result = atomicCompareExchangeWeak(
+            &oit_layers[index],
+            old,
+            bitcast<u32>(bitcast<f32>(old) + value)
+        );
+        if result.exchanged {
+            break;
+        }
+        old = result.old_value;
+    }
+}
+#else // OIT_WEIGHTED_BLENDED
 // Add the fragment to the oit buffer
 fn oit_draw(position: vec4f, color: vec4f) {
     // Don't add fully transparent fragments to the list
@@ -33,6 +92,7 @@ fn oit_draw(position: vec4f, color: vec4f) {
     let depth_alpha = pack_24bit_depth_8bit_alpha(position.z, color.a);
     oit_layers[layer_index] = vec2(rgb9e5_color, depth_alpha);
 }
+#endif // OIT_WEIGHTED_BLENDED
 #endif // OIT_ENABLED
 
 fn pack_24bit_depth_8bit_alpha(depth: f32, alpha: f32) -> u32 {
@@ -1,6 +1,6 @@
 use crate::{
     fullscreen_vertex_shader::fullscreen_shader_vertex_state,
-    oit::OrderIndependentTransparencySettings,
+    oit::{OrderIndependentTransparencyMethod, OrderIndependentTransparencySettings},
 };
 use bevy_app::Plugin;
 use bevy_asset::{load_internal_asset, Handle};
@@ -122,6 +122,7 @@ pub struct OitResolvePipelineId(pub CachedRenderPipelineId);
 pub struct OitResolvePipelineKey {
     hdr: bool,
     layer_count: i32,
+    weighted_blended: bool,
 }
 
 pub fn queue_oit_resolve_pipeline(
@@ -133,6 +134,7 @@ pub fn queue_oit_resolve_pipeline(
             Entity,
             &ExtractedView,
             &OrderIndependentTransparencySettings,
+            Option<&OrderIndependentTransparencyMethod>,
         ),
         With<OrderIndependentTransparencySettings>,
     >,
@@ -141,11 +143,13 @@ pub fn queue_oit_resolve_pipeline(
     mut cached_pipeline_id: Local<EntityHashMap<(OitResolvePipelineKey, CachedRenderPipelineId)>>,
 ) {
     let mut current_view_entities = EntityHashSet::default();
-    for (e, view, oit_settings) in &views {
+    for (e, view, oit_settings, oit_method) in &views {
         current_view_entities.insert(e);
         let key = OitResolvePipelineKey {
             hdr: view.hdr,
             layer_count: oit_settings.layer_count,
+            weighted_blended: oit_method
+                == Some(&OrderIndependentTransparencyMethod::WeightedBlended),
         };
 
         if let Some((cached_key, id)) = cached_pipeline_id.get(&e) {
@@ -180,6 +184,14 @@ fn specialize_oit_resolve_pipeline(
         TextureFormat::bevy_default()
     };
 
+    let mut shader_defs = vec![ShaderDefVal::UInt(
+        "LAYER_COUNT".into(),
+        key.layer_count as u32,
+    )];
+    if key.weighted_blended {
+        shader_defs.push("WEIGHTED_BLENDED".into());
+    }
+
     RenderPipelineDescriptor {
         label: Some("oit_resolve_pipeline".into()),
         layout: vec![
@@ -189,10 +201,7 @@ fn specialize_oit_resolve_pipeline(
         fragment: Some(FragmentState {
             entry_point: "fragment".into(),
             shader: OIT_RESOLVE_SHADER_HANDLE,
-            shader_defs: vec![ShaderDefVal::UInt(
-                "LAYER_COUNT".into(),
-                key.layer_count as u32,
-            )],
+            shader_defs,
             targets: vec![Some(ColorTargetState {
                 format,
                 blend: Some(BlendState {
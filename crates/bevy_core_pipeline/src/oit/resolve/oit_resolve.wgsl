@@ -1,11 +1,16 @@
 #import bevy_render::view::View
 
 @group(0) @binding(0) var<uniform> view: View;
+#ifdef WEIGHTED_BLENDED
+@group(0) @binding(1) var<storage, read_write> layers: array<u32>;
+#else
 @group(0) @binding(1) var<storage, read_write> layers: array<vec2<u32>>;
+#endif
 @group(0) @binding(2) var<storage, read_write> layer_ids: array<atomic<i32>>;
 
 @group(1) @binding(0) var depth: texture_depth_2d;
 
+#ifndef WEIGHTED_BLENDED
 struct OitFragment {
     color: vec3<f32>,
     alpha: f32,
@@ -13,6 +18,7 @@ struct OitFragment {
 }
 // Contains all the colors and depth for this specific fragment
 var<private> fragment_list: array<OitFragment, #{LAYER_COUNT}>;
+#endif // WEIGHTED_BLENDED
 
 struct FullscreenVertexOutput {
     @builtin(position) position: vec4<f32>,
@@ -26,7 +32,12 @@ fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
 
     let counter = atomicLoad(&layer_ids[screen_index]);
     if counter == 0 {
+#ifdef WEIGHTED_BLENDED
+        // The layers may still contain the fragments of another method.
+        reset_weighted_blended(screen_index, buffer_size);
+#else
         reset_indices(screen_index);
+#endif // WEIGHTED_BLENDED
 
         // https://github.com/gfx-rs/wgpu/issues/4416
         if true {
@@ -34,6 +45,13 @@ fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
         }
         return vec4(0.0);
     } else {
+#ifdef WEIGHTED_BLENDED
+        // The fragments have already been depth tested against the depth prepass.
+        let result = resolve_weighted_blended(screen_index, buffer_size);
+        reset_weighted_blended(screen_index, buffer_size);
+
+        return result;
+#else
         // Load depth for manual depth testing.
         // This is necessary because early z doesn't seem to trigger in the transparent pass.
         // This should be done during the draw pass so those fragments simply don't exist in the list,
@@ -43,9 +61,36 @@ fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
         reset_indices(screen_index);
 
         return result.color;
+#endif // WEIGHTED_BLENDED
+    }
+}
+
+#ifdef WEIGHTED_BLENDED
+// Normalizes the accumulated color, so that it can be blended onto the screen
+// with the OVER operator using the revealage as the transmittance.
+// See `oit_draw.wgsl` for the layout of the layers.
+fn resolve_weighted_blended(screen_index: i32, buffer_size: i32) -> vec4f {
+    let accum = vec4(
+        bitcast<f32>(layers[screen_index]),
+        bitcast<f32>(layers[screen_index + buffer_size]),
+        bitcast<f32>(layers[screen_index + buffer_size * 2]),
+        bitcast<f32>(layers[screen_index + buffer_size * 3]),
+    );
+    let revealage = exp(-bitcast<f32>(layers[screen_index + buffer_size * 4]));
+    let alpha = 1.0 - revealage;
+    return vec4(accum.rgb / max(accum.a, 1e-5) * alpha, alpha);
+}
+
+// Clears the accumulated values for the next frame.
+fn reset_weighted_blended(screen_index: i32, buffer_size: i32) {
+    atomicStore(&layer_ids[screen_index], 0);
+    for (var i = 0; i < 5; i += 1) {
+        layers[screen_index + buffer_size * i] = 0u;
     }
 }
+#endif // WEIGHTED_BLENDED
 
+#ifndef WEIGHTED_BLENDED
 // Resets all indices to 0.
 // This means we don't have to clear the entire layers buffer
 fn reset_indices(screen_index: i32) {
@@ -108,6 +153,8 @@ fn sort(screen_index: i32, buffer_size: i32, opaque_depth: f32) -> SortResult {
     return result;
 }
 
+#endif // WEIGHTED_BLENDED
+
 // OVER operator using premultiplied alpha
 // see: https://en.wikipedia.org/wiki/Alpha_compositing
 fn blend(color_a: vec4<f32>, color_b: vec4<f32>) -> vec4<f32> {
@@ -0,0 +1,191 @@
+//! Per-camera post-processing stacks.
+//!
+//! By default, every camera runs all the post-processing effects of its render
+//! graph that it has settings for. A [`PostProcessStack`] on a camera limits it
+//! to the effects it lists instead, so that, for instance, a UI camera can skip
+//! bloom and TAA while a minimap camera renders with a stylized LUT.
+
+use bevy_app::{App, Plugin};
+use bevy_ecs::prelude::*;
+use bevy_render::{
+    camera::Camera,
+    render_graph::{InternedRenderLabel, RenderLabel, SkippedRenderGraphNodes},
+    sync_world::RenderEntity,
+    Extract, ExtractSchedule, RenderApp,
+};
+use bevy_utils::HashSet;
+
+use crate::{core_2d::graph::Node2d, core_3d::graph::Node3d};
+
+/// The post-processing effects that run for a camera.
+///
+/// Each effect is the label of the render graph node that applies it, such as
+/// [`Node3d::Bloom`] or [`Node2d::Tonemapping`]. The nodes of
+/// [`PostProcessNodes`] that aren't in the stack are skipped for this camera,
+/// even if it has the settings component of the effect. Effects still run in
+/// the order of the render graph. Nodes with output slots can't be skipped,
+/// see [`SkippedRenderGraphNodes`].
+///
+/// ```
+/// # use bevy_core_pipeline::{core_3d::graph::Node3d, post_process_stack::PostProcessStack};
+/// // Only tonemap and sharpen the view of this camera.
+/// let stack = PostProcessStack::default()
+///     .with(Node3d::Tonemapping)
+///     .with(Node3d::ContrastAdaptiveSharpening);
+/// ```
+///
+/// Skipping an effect only skips its render graph node: work that other parts
+/// of the engine do because the camera has the settings component, such as the
+/// jitter of [`TemporalJitter`](bevy_render::camera::TemporalJitter), still
+/// happens.
+#[derive(Component, Clone, Debug, Default)]
+pub struct PostProcessStack {
+    effects: HashSet<InternedRenderLabel>,
+}
+
+impl PostProcessStack {
+    /// Adds an effect to the stack.
+    pub fn with(mut self, effect: impl RenderLabel) -> Self {
+        self.add(effect);
+        self
+    }
+
+    /// Adds an effect to the stack.
+    pub fn add(&mut self, effect: impl RenderLabel) -> &mut Self {
+        self.effects.insert(effect.intern());
+        self
+    }
+
+    /// Removes an effect from the stack.
+    pub fn remove(&mut self, effect: impl RenderLabel) -> &mut Self {
+        self.effects.remove(&effect.intern());
+        self
+    }
+
+    /// Returns true if the effect is in the stack.
+    pub fn contains(&self, effect: impl RenderLabel) -> bool {
+        self.effects.contains(&effect.intern())
+    }
+}
+
+/// The render graph nodes that apply post-processing effects, which a
+/// [`PostProcessStack`] can leave out.
+///
+/// This resource lives in the render app and starts with the post-processing
+/// nodes of the core 2D and 3D graphs. Plugins that add their own effect nodes
+/// should add them here, so that stacks can leave them out too. Changing it
+/// updates the nodes skipped by every stack.
+#[derive(Resource, Clone, Debug)]
+pub struct PostProcessNodes(pub HashSet<InternedRenderLabel>);
+
+impl Default for PostProcessNodes {
+    fn default() -> Self {
+        let nodes_3d = [
+            Node3d::Taa,
+            Node3d::TemporalUpscaling,
+            Node3d::MotionBlur,
+            Node3d::Bloom,
+            Node3d::AutoExposure,
+            Node3d::DepthOfField,
+            Node3d::PostProcessing,
+            Node3d::Tonemapping,
+            Node3d::Fxaa,
+            Node3d::Smaa,
+            Node3d::ContrastAdaptiveSharpening,
+        ]
+        .into_iter()
+        .map(RenderLabel::intern);
+        let nodes_2d = [
+            Node2d::Bloom,
+            Node2d::PostProcessing,
+            Node2d::Tonemapping,
+            Node2d::Fxaa,
+            Node2d::Smaa,
+            Node2d::ContrastAdaptiveSharpening,
+        ]
+        .into_iter()
+        .map(RenderLabel::intern);
+        Self(nodes_3d.chain(nodes_2d).collect())
+    }
+}
+
+/// Makes cameras honor their [`PostProcessStack`].
+pub struct PostProcessStackPlugin;
+
+impl Plugin for PostProcessStackPlugin {
+    fn build(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .init_resource::<PostProcessNodes>()
+            .add_systems(ExtractSchedule, extract_post_process_stacks);
+    }
+}
+
+impl PostProcessNodes {
+    /// The nodes that a camera with the given stack skips.
+    pub fn skipped_by(&self, stack: &PostProcessStack) -> SkippedRenderGraphNodes {
+        SkippedRenderGraphNodes(self.0.difference(&stack.effects).copied().collect())
+    }
+}
+
+/// Tells the render graph which post-processing nodes to skip for each camera.
+///
+/// The skipped nodes are only computed again when the stack or the
+/// [`PostProcessNodes`] change.
+fn extract_post_process_stacks(
+    mut commands: Commands,
+    cameras: Extract<Query<(RenderEntity, Ref<PostProcessStack>), With<Camera>>>,
+    mut removed_stacks: Extract<RemovedComponents<PostProcessStack>>,
+    render_entities: Extract<Query<&RenderEntity>>,
+    post_process_nodes: Res<PostProcessNodes>,
+) {
+    for entity in removed_stacks.read() {
+        let Ok(render_entity) = render_entities.get(entity) else {
+            continue;
+        };
+        if let Some(mut entity_commands) = commands.get_entity(render_entity.id()) {
+            entity_commands.remove::<SkippedRenderGraphNodes>();
+        }
+    }
+
+    for (entity, stack) in &cameras {
+        if !stack.is_changed() && !post_process_nodes.is_changed() {
+            continue;
+        }
+        if let Some(mut entity_commands) = commands.get_entity(entity) {
+            entity_commands.insert(post_process_nodes.skipped_by(&stack));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_render::render_graph::RenderLabel;
+
+    use super::{PostProcessNodes, PostProcessStack};
+    use crate::core_3d::graph::Node3d;
+
+    #[test]
+    fn stacks_skip_the_other_effects() {
+        let nodes = PostProcessNodes::default();
+        assert!(nodes.0.contains(&Node3d::TemporalUpscaling.intern()));
+
+        let mut stack = PostProcessStack::default()
+            .with(Node3d::Tonemapping)
+            .with(Node3d::Bloom);
+        stack.remove(Node3d::Bloom);
+        assert!(stack.contains(Node3d::Tonemapping));
+        assert!(!stack.contains(Node3d::Bloom));
+
+        let skipped = nodes.skipped_by(&stack);
+        assert!(!skipped.contains(&Node3d::Tonemapping.intern()));
+        assert!(skipped.contains(&Node3d::Bloom.intern()));
+        assert!(skipped.contains(&Node3d::TemporalUpscaling.intern()));
+        // Nodes that aren't effects are never skipped.
+        assert!(!skipped.contains(&Node3d::MainOpaquePass.intern()));
+        assert_eq!(skipped.len(), nodes.0.len() - 1);
+    }
+}
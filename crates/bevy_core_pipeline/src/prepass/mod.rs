@@ -8,6 +8,7 @@
 //! [`DepthPrepass`]
 //! [`NormalPrepass`]
 //! [`MotionVectorPrepass`]
+//! [`EntityIndexPrepass`]
 //!
 //! The textures are automatically added to the default mesh view bindings. You can also get the raw textures
 //! by querying the [`ViewPrepassTextures`] component on any camera with a prepass component.
@@ -51,6 +52,7 @@ use bevy_render::{
 
 pub const NORMAL_PREPASS_FORMAT: TextureFormat = TextureFormat::Rgb10a2Unorm;
 pub const MOTION_VECTOR_PREPASS_FORMAT: TextureFormat = TextureFormat::Rg16Float;
+pub const ENTITY_INDEX_PREPASS_FORMAT: TextureFormat = TextureFormat::Rg32Uint;
 
 /// If added to a [`crate::prelude::Camera3d`] then depth values will be copied to a separate texture available to the main pass.
 #[derive(Component, Default, Reflect, Clone)]
@@ -68,6 +70,18 @@ pub struct NormalPrepass;
 #[reflect(Component, Default)]
 pub struct MotionVectorPrepass;
 
+/// If added to a [`crate::prelude::Camera3d`] then the main world entity of the mesh drawn at each pixel will be copied to
+/// a separate texture, as the low and high 32 bits of [`Entity::to_bits`](bevy_ecs::entity::Entity::to_bits).
+/// Pixels where no mesh was drawn are `0`, which is never the high 32 bits of an entity.
+///
+/// This is used for GPU picking. The texture can only be copied to the CPU if the camera has MSAA
+/// turned off, since multisampled textures can't be copied.
+///
+/// Custom prepass shaders must write the `entity` output when `ENTITY_INDEX_PREPASS` is defined.
+#[derive(Component, Default, Reflect, Clone)]
+#[reflect(Component, Default)]
+pub struct EntityIndexPrepass;
+
 /// If added to a [`crate::prelude::Camera3d`] then deferred materials will be rendered to the deferred gbuffer texture and will be available to subsequent passes.
 /// Note the default deferred lighting plugin also requires `DepthPrepass` to work correctly.
 #[derive(Component, Default, Reflect)]
@@ -104,6 +118,9 @@ pub struct ViewPrepassTextures {
     /// The motion vectors texture generated by the prepass.
     /// Exists only if [`MotionVectorPrepass`] is added to the `ViewTarget`
     pub motion_vectors: Option<ColorAttachment>,
+    /// The entity index texture generated by the prepass.
+    /// Exists only if [`EntityIndexPrepass`] is added to the `ViewTarget`
+    pub entity_index: Option<ColorAttachment>,
     /// The deferred gbuffer generated by the deferred pass.
     /// Exists only if [`DeferredPrepass`] is added to the `ViewTarget`
     pub deferred: Option<ColorAttachment>,
@@ -132,6 +149,10 @@ impl ViewPrepassTextures {
     pub fn deferred_view(&self) -> Option<&TextureView> {
         self.deferred.as_ref().map(|t| &t.texture.default_view)
     }
+
+    pub fn entity_index_view(&self) -> Option<&TextureView> {
+        self.entity_index.as_ref().map(|t| &t.texture.default_view)
+    }
 }
 
 /// Opaque phase of the 3D prepass.
@@ -351,6 +372,7 @@ pub fn prepass_target_descriptors(
     normal_prepass: bool,
     motion_vector_prepass: bool,
     deferred_prepass: bool,
+    entity_index_prepass: bool,
 ) -> Vec<Option<ColorTargetState>> {
     vec![
         normal_prepass.then_some(ColorTargetState {
@@ -373,5 +395,10 @@ pub fn prepass_target_descriptors(
             blend: None,
             write_mask: ColorWrites::ALL,
         }),
+        entity_index_prepass.then_some(ColorTargetState {
+            format: ENTITY_INDEX_PREPASS_FORMAT,
+            blend: None,
+            write_mask: ColorWrites::ALL,
+        }),
     ]
 }
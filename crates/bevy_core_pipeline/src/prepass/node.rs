@@ -1,6 +1,6 @@
 use bevy_ecs::{prelude::*, query::QueryItem};
 use bevy_render::{
-    camera::ExtractedCamera,
+    camera::{ExtractedCamera, MainPassResolutionOverride, Viewport},
     diagnostic::RecordDiagnostics,
     render_graph::{NodeRunError, RenderGraphContext, ViewNode},
     render_phase::{TrackedRenderPass, ViewBinnedRenderPhases},
@@ -36,6 +36,7 @@ impl ViewNode for PrepassNode {
         Option<&'static RenderSkyboxPrepassPipeline>,
         Option<&'static SkyboxPrepassBindGroup>,
         Option<&'static PreviousViewUniformOffset>,
+        Option<&'static MainPassResolutionOverride>,
     );
 
     fn run<'w>(
@@ -52,6 +53,7 @@ impl ViewNode for PrepassNode {
             skybox_prepass_pipeline,
             skybox_prepass_bind_group,
             view_prev_uniform_offset,
+            resolution_override,
         ): QueryItem<'w, Self::ViewQuery>,
         world: &'w World,
     ) -> Result<(), NodeRunError> {
@@ -83,6 +85,10 @@ impl ViewNode for PrepassNode {
             // Use None in place of deferred attachments
             None,
             None,
+            view_prepass_textures
+                .entity_index
+                .as_ref()
+                .map(|entity_index_texture| entity_index_texture.get_attachment()),
         ];
 
         // If all color attachments are none: clear the color attachment list so that no fragment shader is required
@@ -115,8 +121,10 @@ impl ViewNode for PrepassNode {
             let mut render_pass = TrackedRenderPass::new(&render_device, render_pass);
             let pass_span = diagnostics.pass_span(&mut render_pass, "prepass");
 
-            if let Some(viewport) = camera.viewport.as_ref() {
-                render_pass.set_camera_viewport(viewport);
+            if let Some(viewport) =
+                Viewport::from_viewport_and_override(camera.viewport.as_ref(), resolution_override)
+            {
+                render_pass.set_camera_viewport(&viewport);
             }
 
             // Opaque draws
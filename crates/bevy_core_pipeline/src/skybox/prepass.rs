@@ -11,9 +11,10 @@ use bevy_ecs::{
 use bevy_render::{
     render_resource::{
         binding_types::uniform_buffer, BindGroup, BindGroupEntries, BindGroupLayout,
-        BindGroupLayoutEntries, CachedRenderPipelineId, CompareFunction, DepthStencilState,
-        FragmentState, MultisampleState, PipelineCache, RenderPipelineDescriptor, Shader,
-        ShaderStages, SpecializedRenderPipeline, SpecializedRenderPipelines,
+        BindGroupLayoutEntries, CachedRenderPipelineId, ColorWrites, CompareFunction,
+        DepthStencilState, FragmentState, MultisampleState, PipelineCache,
+        RenderPipelineDescriptor, Shader, ShaderStages, SpecializedRenderPipeline,
+        SpecializedRenderPipelines,
     },
     renderer::RenderDevice,
     view::{Msaa, ViewUniform, ViewUniforms},
@@ -23,8 +24,8 @@ use bevy_utils::prelude::default;
 use crate::{
     core_3d::CORE_3D_DEPTH_FORMAT,
     prepass::{
-        prepass_target_descriptors, MotionVectorPrepass, NormalPrepass, PreviousViewData,
-        PreviousViewUniforms,
+        prepass_target_descriptors, EntityIndexPrepass, MotionVectorPrepass, NormalPrepass,
+        PreviousViewData, PreviousViewUniforms,
     },
     Skybox,
 };
@@ -46,6 +47,7 @@ pub struct SkyboxPrepassPipeline {
 pub struct SkyboxPrepassPipelineKey {
     samples: u32,
     normal_prepass: bool,
+    entity_index_prepass: bool,
 }
 
 /// Stores the ID for a camera's specialized pipeline, so it can be retrieved from the
@@ -81,6 +83,14 @@ impl SpecializedRenderPipeline for SkyboxPrepassPipeline {
     type Key = SkyboxPrepassPipelineKey;
 
     fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mut targets =
+            prepass_target_descriptors(key.normal_prepass, true, false, key.entity_index_prepass);
+        // The skybox shader doesn't output an entity index, so the background keeps the value the
+        // texture is cleared to, which means that no entity was drawn.
+        if let Some(Some(entity_index_target)) = targets.last_mut() {
+            entity_index_target.write_mask = ColorWrites::empty();
+        }
+
         RenderPipelineDescriptor {
             label: Some("skybox_prepass_pipeline".into()),
             layout: vec![self.bind_group_layout.clone()],
@@ -103,7 +113,7 @@ impl SpecializedRenderPipeline for SkyboxPrepassPipeline {
                 shader: SKYBOX_PREPASS_SHADER_HANDLE,
                 shader_defs: vec![],
                 entry_point: "fragment".into(),
-                targets: prepass_target_descriptors(key.normal_prepass, true, false),
+                targets,
             }),
             zero_initialize_workgroup_memory: false,
         }
@@ -116,12 +126,16 @@ pub fn prepare_skybox_prepass_pipelines(
     pipeline_cache: Res<PipelineCache>,
     mut pipelines: ResMut<SpecializedRenderPipelines<SkyboxPrepassPipeline>>,
     pipeline: Res<SkyboxPrepassPipeline>,
-    views: Query<(Entity, Has<NormalPrepass>, &Msaa), (With<Skybox>, With<MotionVectorPrepass>)>,
+    views: Query<
+        (Entity, Has<NormalPrepass>, Has<EntityIndexPrepass>, &Msaa),
+        (With<Skybox>, With<MotionVectorPrepass>),
+    >,
 ) {
-    for (entity, normal_prepass, msaa) in &views {
+    for (entity, normal_prepass, entity_index_prepass, msaa) in &views {
         let pipeline_key = SkyboxPrepassPipelineKey {
             samples: msaa.samples(),
             normal_prepass,
+            entity_index_prepass,
         };
 
         let render_skybox_prepass_pipeline =
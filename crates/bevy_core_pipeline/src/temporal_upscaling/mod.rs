@@ -0,0 +1,642 @@
+use crate::{
+    core_3d::graph::{Core3d, Node3d},
+    fullscreen_vertex_shader::fullscreen_shader_vertex_state,
+    prelude::Camera3d,
+    prepass::{DepthPrepass, MotionVectorPrepass, ViewPrepassTextures},
+};
+use bevy_app::{App, Plugin};
+use bevy_asset::{load_internal_asset, Handle};
+use bevy_diagnostic::FrameCount;
+use bevy_ecs::{
+    prelude::{require, Component, Entity, ReflectComponent},
+    query::{Has, QueryItem, With, Without},
+    schedule::IntoSystemConfigs,
+    system::{Commands, Query, Res, ResMut, Resource},
+    world::{FromWorld, World},
+};
+use bevy_image::BevyDefault as _;
+use bevy_math::{ops, UVec2, Vec2};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::{
+    camera::{ExtractedCamera, MainPassResolutionOverride, MipBias, TemporalJitter},
+    extract_component::{ComponentUniforms, DynamicUniformIndex, UniformComponentPlugin},
+    prelude::{Camera, Projection},
+    render_graph::{NodeRunError, RenderGraphApp, RenderGraphContext, ViewNode, ViewNodeRunner},
+    render_resource::{
+        binding_types::{sampler, texture_2d, texture_depth_2d, uniform_buffer},
+        BindGroupEntries, BindGroupLayout, BindGroupLayoutEntries, CachedRenderPipelineId,
+        ColorTargetState, ColorWrites, Extent3d, FilterMode, FragmentState, MultisampleState,
+        Operations, PipelineCache, PrimitiveState, RenderPassColorAttachment, RenderPassDescriptor,
+        RenderPipelineDescriptor, Sampler, SamplerBindingType, SamplerDescriptor, Shader,
+        ShaderStages, ShaderType, SpecializedRenderPipeline, SpecializedRenderPipelines,
+        TextureDescriptor, TextureDimension, TextureFormat, TextureSampleType, TextureUsages,
+    },
+    renderer::{RenderContext, RenderDevice},
+    sync_component::SyncComponentPlugin,
+    sync_world::RenderEntity,
+    texture::{CachedTexture, TextureCache},
+    view::{ExtractedView, Msaa, ViewTarget},
+    ExtractSchedule, MainWorld, Render, RenderApp, RenderSet,
+};
+use tracing::warn;
+
+const TEMPORAL_UPSCALING_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(3425467890329384671);
+
+/// Plugin for temporal upscaling.
+///
+/// See [`Upscaling`] for more details.
+pub struct TemporalUpscalingPlugin;
+
+impl Plugin for TemporalUpscalingPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            TEMPORAL_UPSCALING_SHADER_HANDLE,
+            "temporal_upscaling.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.register_type::<Upscaling>();
+
+        app.add_plugins((
+            SyncComponentPlugin::<Upscaling>::default(),
+            UniformComponentPlugin::<TemporalUpscalingUniform>::default(),
+        ));
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .init_resource::<SpecializedRenderPipelines<TemporalUpscalingPipeline>>()
+            .add_systems(ExtractSchedule, extract_temporal_upscaling_settings)
+            .add_systems(
+                Render,
+                (
+                    prepare_temporal_upscaling_jitter_and_mip_bias.in_set(RenderSet::ManageViews),
+                    prepare_temporal_upscaling_pipelines.in_set(RenderSet::Prepare),
+                    prepare_temporal_upscaling_history_textures.in_set(RenderSet::PrepareResources),
+                ),
+            )
+            .add_render_graph_node::<ViewNodeRunner<TemporalUpscalingNode>>(
+                Core3d,
+                Node3d::TemporalUpscaling,
+            )
+            .add_render_graph_edges(
+                Core3d,
+                (
+                    Node3d::EndMainPass,
+                    // Everything after the upscaler runs at the full resolution
+                    Node3d::TemporalUpscaling,
+                    Node3d::MotionBlur,
+                    Node3d::Bloom,
+                    Node3d::Tonemapping,
+                ),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app.init_resource::<TemporalUpscalingPipeline>();
+    }
+}
+
+/// Component to render the main passes of a 3D perspective camera at a lower resolution, and
+/// upscale them to the resolution of its viewport with a temporal upscaler.
+///
+/// Like [`TemporalAntiAliasing`](crate::experimental::taa::TemporalAntiAliasing), the upscaler
+/// jitters the projection of the camera, and accumulates the jittered frames in a history texture
+/// reprojected with motion vectors. Because each frame covers different sub-pixel positions, the
+/// history converges to an anti-aliased image at the output resolution, at the rendering cost of
+/// the lower one. The upscaler replaces TAA, and both shouldn't be added to the same camera.
+///
+/// # Usage Notes
+///
+/// The [`TemporalUpscalingPlugin`] must be added to your app.
+/// Any camera with this component must also disable [`Msaa`] by setting it to [`Msaa::Off`].
+///
+/// The same limitations as TAA apply: the upscaler doesn't work with
+/// [`bevy_render::camera::OrthographicProjection`], alpha-blended meshes don't write motion
+/// vectors, and everything on screen must write correct motion vectors to avoid ghosting.
+///
+/// The main passes, including the prepasses and deferred lighting, run at the lower resolution,
+/// and the post-processing effects that run after the upscaler, like bloom, tonemapping and FXAA,
+/// at the full resolution. Effects that read the prepass textures after the upscaler, like motion
+/// blur and depth of field, as well as screen space ambient occlusion, screen space reflections
+/// and meshlets, don't support the lower resolution yet.
+///
+/// If no [`MipBias`] component is attached to the camera, the upscaler adds one that matches the
+/// [`UpscalingQuality`], so that textures keep the sharpness of the output resolution. A
+/// [`MipBias`] added by anything else is left as it is.
+#[derive(Component, Reflect, Clone)]
+#[reflect(Component, Default)]
+#[require(TemporalJitter, DepthPrepass, MotionVectorPrepass)]
+#[doc(alias = "TemporalUpscaling", alias = "Fsr2")]
+pub struct Upscaling {
+    /// The ratio between the resolution of the viewport and the one the main passes render at.
+    pub quality: UpscalingQuality,
+    /// Set to true to delete the saved temporal history (past frames).
+    ///
+    /// Useful for preventing ghosting when the history is no longer
+    /// representative of the current frame, such as in sudden camera cuts.
+    ///
+    /// After setting this to true, it will automatically be toggled
+    /// back to false at the end of the frame.
+    pub reset: bool,
+}
+
+impl Default for Upscaling {
+    fn default() -> Self {
+        Self {
+            quality: UpscalingQuality::default(),
+            reset: true,
+        }
+    }
+}
+
+/// The resolution that the main passes of a camera with [`Upscaling`] render at.
+///
+/// The presets divide each dimension of the viewport by the same factors as FSR 2.
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq)]
+#[reflect(Default, Debug, PartialEq)]
+pub enum UpscalingQuality {
+    /// Renders at the full resolution, which only uses the upscaler for anti-aliasing.
+    NativeAntiAliasing,
+    /// Divides the resolution by 1.5.
+    #[default]
+    Quality,
+    /// Divides the resolution by 1.7.
+    Balanced,
+    /// Divides the resolution by 2.
+    Performance,
+    /// Divides the resolution by 3.
+    UltraPerformance,
+    /// Divides the resolution by a custom factor, which must be at least 1.
+    Custom(f32),
+}
+
+impl UpscalingQuality {
+    /// Returns the factor that each dimension of the viewport is divided by.
+    pub fn scale_factor(&self) -> f32 {
+        match *self {
+            Self::NativeAntiAliasing => 1.0,
+            Self::Quality => 1.5,
+            Self::Balanced => 1.7,
+            Self::Performance => 2.0,
+            Self::UltraPerformance => 3.0,
+            Self::Custom(scale_factor) => scale_factor.max(1.0),
+        }
+    }
+
+    /// Returns the resolution that the main passes render at for a viewport of the given size.
+    pub fn render_size(&self, viewport_size: UVec2) -> UVec2 {
+        (viewport_size.as_vec2() / self.scale_factor())
+            .round()
+            .as_uvec2()
+            .max(UVec2::ONE)
+    }
+}
+
+/// Render [`bevy_render::render_graph::Node`] used by temporal upscaling.
+#[derive(Default)]
+pub struct TemporalUpscalingNode;
+
+impl ViewNode for TemporalUpscalingNode {
+    type ViewQuery = (
+        &'static ExtractedCamera,
+        &'static ViewTarget,
+        &'static TemporalUpscalingHistoryTextures,
+        &'static ViewPrepassTextures,
+        &'static TemporalUpscalingPipelineId,
+        &'static DynamicUniformIndex<TemporalUpscalingUniform>,
+        &'static Msaa,
+    );
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (camera, view_target, history_textures, prepass_textures, pipeline_id, uniform_index, msaa): QueryItem<
+            Self::ViewQuery,
+        >,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        if *msaa != Msaa::Off {
+            warn!("Temporal upscaling requires MSAA to be disabled");
+            return Ok(());
+        }
+
+        let (Some(pipelines), Some(pipeline_cache), Some(uniforms)) = (
+            world.get_resource::<TemporalUpscalingPipeline>(),
+            world.get_resource::<PipelineCache>(),
+            world.get_resource::<ComponentUniforms<TemporalUpscalingUniform>>(),
+        ) else {
+            return Ok(());
+        };
+        let (
+            Some(pipeline),
+            Some(prepass_motion_vectors_texture),
+            Some(prepass_depth_texture),
+            Some(uniforms),
+        ) = (
+            pipeline_cache.get_render_pipeline(pipeline_id.0),
+            &prepass_textures.motion_vectors,
+            &prepass_textures.depth,
+            uniforms.binding(),
+        )
+        else {
+            return Ok(());
+        };
+        let view_target = view_target.post_process_write();
+
+        let bind_group = render_context.render_device().create_bind_group(
+            "temporal_upscaling_bind_group",
+            &pipelines.bind_group_layout,
+            &BindGroupEntries::sequential((
+                view_target.source,
+                &history_textures.read.default_view,
+                &prepass_motion_vectors_texture.texture.default_view,
+                &prepass_depth_texture.texture.default_view,
+                &pipelines.linear_sampler,
+                uniforms,
+            )),
+        );
+
+        {
+            let mut pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+                label: Some("temporal_upscaling_pass"),
+                color_attachments: &[
+                    Some(RenderPassColorAttachment {
+                        view: view_target.destination,
+                        resolve_target: None,
+                        ops: Operations::default(),
+                    }),
+                    Some(RenderPassColorAttachment {
+                        view: &history_textures.write.default_view,
+                        resolve_target: None,
+                        ops: Operations::default(),
+                    }),
+                ],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            pass.set_render_pipeline(pipeline);
+            pass.set_bind_group(0, &bind_group, &[uniform_index.index()]);
+            if let Some(viewport) = camera.viewport.as_ref() {
+                pass.set_camera_viewport(viewport);
+            }
+            pass.draw(0..3, 0..1);
+        }
+
+        Ok(())
+    }
+}
+
+/// The sizes and jitter of a view with [`Upscaling`], in physical pixels.
+#[derive(Component, ShaderType, Clone)]
+pub struct TemporalUpscalingUniform {
+    /// The position of the viewport in the view target.
+    viewport_origin: Vec2,
+    /// The size that the main passes render at, in the top-left corner of the viewport.
+    render_size: Vec2,
+    /// The size of the viewport.
+    output_size: Vec2,
+    /// The offset of the projection, in pixels of the render size.
+    jitter: Vec2,
+}
+
+#[derive(Resource)]
+struct TemporalUpscalingPipeline {
+    bind_group_layout: BindGroupLayout,
+    linear_sampler: Sampler,
+}
+
+impl FromWorld for TemporalUpscalingPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let linear_sampler = render_device.create_sampler(&SamplerDescriptor {
+            label: Some("temporal_upscaling_linear_sampler"),
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..SamplerDescriptor::default()
+        });
+
+        let bind_group_layout = render_device.create_bind_group_layout(
+            "temporal_upscaling_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    // View target (read)
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    // History (read)
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    // Motion Vectors
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    // Depth
+                    texture_depth_2d(),
+                    // Linear sampler
+                    sampler(SamplerBindingType::Filtering),
+                    // Sizes and jitter
+                    uniform_buffer::<TemporalUpscalingUniform>(true),
+                ),
+            ),
+        );
+
+        TemporalUpscalingPipeline {
+            bind_group_layout,
+            linear_sampler,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Clone)]
+struct TemporalUpscalingPipelineKey {
+    hdr: bool,
+    reset: bool,
+}
+
+impl SpecializedRenderPipeline for TemporalUpscalingPipeline {
+    type Key = TemporalUpscalingPipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mut shader_defs = vec![];
+
+        let format = if key.hdr {
+            shader_defs.push("TONEMAP".into());
+            ViewTarget::TEXTURE_FORMAT_HDR
+        } else {
+            TextureFormat::bevy_default()
+        };
+
+        if key.reset {
+            shader_defs.push("RESET".into());
+        }
+
+        RenderPipelineDescriptor {
+            label: Some("temporal_upscaling_pipeline".into()),
+            layout: vec![self.bind_group_layout.clone()],
+            vertex: fullscreen_shader_vertex_state(),
+            fragment: Some(FragmentState {
+                shader: TEMPORAL_UPSCALING_SHADER_HANDLE,
+                shader_defs,
+                entry_point: "temporal_upscaling".into(),
+                targets: vec![
+                    Some(ColorTargetState {
+                        format,
+                        blend: None,
+                        write_mask: ColorWrites::ALL,
+                    }),
+                    Some(ColorTargetState {
+                        format,
+                        blend: None,
+                        write_mask: ColorWrites::ALL,
+                    }),
+                ],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            push_constant_ranges: Vec::new(),
+            zero_initialize_workgroup_memory: false,
+        }
+    }
+}
+
+fn extract_temporal_upscaling_settings(mut commands: Commands, mut main_world: ResMut<MainWorld>) {
+    let mut cameras_3d = main_world
+        .query_filtered::<(RenderEntity, &Camera, &Projection, &mut Upscaling), (
+            With<Camera3d>,
+            With<TemporalJitter>,
+            With<DepthPrepass>,
+            With<MotionVectorPrepass>,
+        )>();
+
+    for (entity, camera, camera_projection, mut settings) in cameras_3d.iter_mut(&mut main_world) {
+        let has_perspective_projection = matches!(camera_projection, Projection::Perspective(_));
+        let mut entity_commands = commands
+            .get_entity(entity)
+            .expect("Camera entity wasn't synced.");
+        match camera.physical_viewport_size() {
+            Some(viewport_size) if camera.is_active && has_perspective_projection => {
+                entity_commands.insert((
+                    settings.clone(),
+                    MainPassResolutionOverride(settings.quality.render_size(viewport_size)),
+                ));
+                settings.reset = false;
+            }
+            _ => {
+                // TODO: needs better strategy for cleaning up
+                entity_commands.remove::<(
+                    Upscaling,
+                    MainPassResolutionOverride,
+                    // components added in prepare systems (because `TemporalUpscalingNode` does not query extracted components)
+                    TemporalUpscalingUniform,
+                    TemporalUpscalingHistoryTextures,
+                    TemporalUpscalingPipelineId,
+                )>();
+            }
+        }
+    }
+}
+
+fn prepare_temporal_upscaling_jitter_and_mip_bias(
+    frame_count: Res<FrameCount>,
+    mut query: Query<(
+        Entity,
+        &ExtractedCamera,
+        &Upscaling,
+        &MainPassResolutionOverride,
+        &mut TemporalJitter,
+        Option<&MipBias>,
+        Has<UpscalingMipBias>,
+    )>,
+    removed_upscalers: Query<Entity, (With<UpscalingMipBias>, Without<Upscaling>)>,
+    mut commands: Commands,
+) {
+    for entity in &removed_upscalers {
+        commands
+            .entity(entity)
+            .remove::<(MipBias, UpscalingMipBias)>();
+    }
+
+    for (entity, camera, settings, resolution_override, mut jitter, mip_bias, own_mip_bias) in
+        &mut query
+    {
+        let Some(output_size) = camera.physical_viewport_size else {
+            continue;
+        };
+        let scale_factor = settings.quality.scale_factor();
+
+        // Each output pixel needs about 8 samples, so the sequence grows with the scale factor,
+        // as recommended by FSR 2
+        let phase_count = (8.0 * scale_factor * scale_factor).ceil() as u32;
+        let index = (frame_count.0 % phase_count) + 1;
+        jitter.offset = Vec2::new(halton(index, 2), halton(index, 3)) - 0.5;
+
+        // Only the mip bias added by the upscaler follows changes to the quality
+        let bias = -ops::log2(scale_factor) - 1.0;
+        if mip_bias.is_none()
+            || (own_mip_bias && mip_bias.is_some_and(|mip_bias| mip_bias.0 != bias))
+        {
+            commands
+                .entity(entity)
+                .insert((MipBias(bias), UpscalingMipBias));
+        }
+
+        commands.entity(entity).insert(TemporalUpscalingUniform {
+            viewport_origin: camera
+                .viewport
+                .as_ref()
+                .map_or(Vec2::ZERO, |viewport| viewport.physical_position.as_vec2()),
+            render_size: resolution_override.0.as_vec2(),
+            output_size: output_size.as_vec2(),
+            jitter: jitter.offset,
+        });
+    }
+}
+
+/// Marks a [`MipBias`] added by the upscaler.
+#[derive(Component)]
+struct UpscalingMipBias;
+
+/// Returns the element of the Halton sequence of the given base at the given index.
+fn halton(mut index: u32, base: u32) -> f32 {
+    let mut fraction = 1.0;
+    let mut result = 0.0;
+    while index > 0 {
+        fraction /= base as f32;
+        result += fraction * (index % base) as f32;
+        index /= base;
+    }
+    result
+}
+
+#[derive(Component)]
+pub struct TemporalUpscalingHistoryTextures {
+    write: CachedTexture,
+    read: CachedTexture,
+}
+
+fn prepare_temporal_upscaling_history_textures(
+    mut commands: Commands,
+    mut texture_cache: ResMut<TextureCache>,
+    render_device: Res<RenderDevice>,
+    frame_count: Res<FrameCount>,
+    views: Query<(Entity, &ExtractedCamera, &ExtractedView), With<Upscaling>>,
+) {
+    for (entity, camera, view) in &views {
+        if let Some(physical_target_size) = camera.physical_target_size {
+            let mut texture_descriptor = TextureDescriptor {
+                label: None,
+                size: Extent3d {
+                    depth_or_array_layers: 1,
+                    width: physical_target_size.x,
+                    height: physical_target_size.y,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: if view.hdr {
+                    ViewTarget::TEXTURE_FORMAT_HDR
+                } else {
+                    TextureFormat::bevy_default()
+                },
+                usage: TextureUsages::TEXTURE_BINDING | TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            };
+
+            texture_descriptor.label = Some("temporal_upscaling_history_1_texture");
+            let history_1_texture = texture_cache.get(&render_device, texture_descriptor.clone());
+
+            texture_descriptor.label = Some("temporal_upscaling_history_2_texture");
+            let history_2_texture = texture_cache.get(&render_device, texture_descriptor);
+
+            let textures = if frame_count.0 % 2 == 0 {
+                TemporalUpscalingHistoryTextures {
+                    write: history_1_texture,
+                    read: history_2_texture,
+                }
+            } else {
+                TemporalUpscalingHistoryTextures {
+                    write: history_2_texture,
+                    read: history_1_texture,
+                }
+            };
+
+            commands.entity(entity).insert(textures);
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct TemporalUpscalingPipelineId(CachedRenderPipelineId);
+
+fn prepare_temporal_upscaling_pipelines(
+    mut commands: Commands,
+    pipeline_cache: Res<PipelineCache>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<TemporalUpscalingPipeline>>,
+    pipeline: Res<TemporalUpscalingPipeline>,
+    views: Query<(Entity, &ExtractedView, &Upscaling)>,
+) {
+    for (entity, view, settings) in &views {
+        let mut pipeline_key = TemporalUpscalingPipelineKey {
+            hdr: view.hdr,
+            reset: settings.reset,
+        };
+        let pipeline_id = pipelines.specialize(&pipeline_cache, &pipeline, pipeline_key.clone());
+
+        // Prepare non-reset pipeline anyways - it will be necessary next frame
+        if pipeline_key.reset {
+            pipeline_key.reset = false;
+            pipelines.specialize(&pipeline_cache, &pipeline, pipeline_key);
+        }
+
+        commands
+            .entity(entity)
+            .insert(TemporalUpscalingPipelineId(pipeline_id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_size() {
+        let viewport_size = UVec2::new(1920, 1080);
+        assert_eq!(
+            UpscalingQuality::NativeAntiAliasing.render_size(viewport_size),
+            viewport_size
+        );
+        assert_eq!(
+            UpscalingQuality::Quality.render_size(viewport_size),
+            UVec2::new(1280, 720)
+        );
+        assert_eq!(
+            UpscalingQuality::Performance.render_size(viewport_size),
+            UVec2::new(960, 540)
+        );
+        assert_eq!(
+            UpscalingQuality::Custom(0.5).render_size(viewport_size),
+            viewport_size
+        );
+        assert_eq!(
+            UpscalingQuality::UltraPerformance.render_size(UVec2::ONE),
+            UVec2::ONE
+        );
+    }
+
+    #[test]
+    fn halton_sequence() {
+        assert_eq!(halton(1, 2), 0.5);
+        assert_eq!(halton(2, 2), 0.25);
+        assert_eq!(halton(3, 2), 0.75);
+        assert_eq!(halton(1, 3), 1.0 / 3.0);
+        assert_eq!(halton(2, 3), 2.0 / 3.0);
+    }
+}
@@ -0,0 +1,220 @@
+// A temporal upscaler in the spirit of FSR 2: each output pixel reconstructs the current frame
+// from the jittered samples of the lower resolution main passes around it, and accumulates it with
+// the reprojected history.
+//
+// References:
+// https://gpuopen.com/fidelityfx-superresolution-2/
+// https://advances.realtimerendering.com/s2014/index.html#_HIGH-QUALITY_TEMPORAL_SUPERSAMPLING
+// https://www.activision.com/cdn/research/Dynamic_Temporal_Antialiasing_and_Upsampling_in_Call_of_Duty_v4.pdf
+
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+// The number of frames that can be accumulated in a pixel of the history, controls how much
+// smoothing happens when there is no motion
+const MAX_CONFIDENCE: f32 = 32.0;
+// The number of frames that can be accumulated in a moving pixel, to reduce ghosting
+const MAX_MOVING_CONFIDENCE: f32 = 4.0;
+const PI: f32 = 3.141592653589793;
+
+struct TemporalUpscalingUniform {
+    viewport_origin: vec2<f32>,
+    render_size: vec2<f32>,
+    output_size: vec2<f32>,
+    jitter: vec2<f32>,
+};
+
+@group(0) @binding(0) var view_target: texture_2d<f32>;
+@group(0) @binding(1) var history: texture_2d<f32>;
+@group(0) @binding(2) var motion_vectors: texture_2d<f32>;
+@group(0) @binding(3) var depth: texture_depth_2d;
+@group(0) @binding(4) var linear_sampler: sampler;
+@group(0) @binding(5) var<uniform> settings: TemporalUpscalingUniform;
+
+struct Output {
+    @location(0) view_target: vec4<f32>,
+    @location(1) history: vec4<f32>,
+};
+
+// The history is accumulated between a reversible tonemap and its inverse, to keep bright
+// samples from dominating the reconstruction, see taa.wgsl
+fn rcp(x: f32) -> f32 { return 1.0 / x; }
+fn max3(x: vec3<f32>) -> f32 { return max(x.r, max(x.g, x.b)); }
+fn tonemap(color: vec3<f32>) -> vec3<f32> { return color * rcp(max3(color) + 1.0); }
+fn reverse_tonemap(color: vec3<f32>) -> vec3<f32> { return color * rcp(1.0 - max3(color)); }
+
+fn RGB_to_YCoCg(rgb: vec3<f32>) -> vec3<f32> {
+    let y = (rgb.r / 4.0) + (rgb.g / 2.0) + (rgb.b / 4.0);
+    let co = (rgb.r / 2.0) - (rgb.b / 2.0);
+    let cg = (-rgb.r / 4.0) + (rgb.g / 2.0) - (rgb.b / 4.0);
+    return vec3(y, co, cg);
+}
+
+fn YCoCg_to_RGB(ycocg: vec3<f32>) -> vec3<f32> {
+    let r = ycocg.x + ycocg.y - ycocg.z;
+    let g = ycocg.x + ycocg.z;
+    let b = ycocg.x - ycocg.y - ycocg.z;
+    return saturate(vec3(r, g, b));
+}
+
+fn clip_towards_aabb_center(history_color: vec3<f32>, aabb_min: vec3<f32>, aabb_max: vec3<f32>) -> vec3<f32> {
+    let p_clip = 0.5 * (aabb_max + aabb_min);
+    let e_clip = 0.5 * (aabb_max - aabb_min) + 0.00000001;
+    let v_clip = history_color - p_clip;
+    let v_unit = v_clip / e_clip;
+    let a_unit = abs(v_unit);
+    let ma_unit = max3(a_unit);
+    if ma_unit > 1.0 {
+        return p_clip + (v_clip / ma_unit);
+    } else {
+        return history_color;
+    }
+}
+
+// Lanczos 2 kernel, which is what FSR 2 approximates for its reconstruction
+fn lanczos2(x: f32) -> f32 {
+    if x < 0.0001 {
+        return 1.0;
+    }
+    if x >= 2.0 {
+        return 0.0;
+    }
+    let pi_x = PI * x;
+    return 2.0 * sin(pi_x) * sin(pi_x * 0.5) / (pi_x * pi_x);
+}
+
+// Loads a pixel of the main passes, relative to the top-left corner of the viewport
+fn render_texel(texel: vec2<f32>) -> vec2<i32> {
+    return vec2<i32>(settings.viewport_origin + clamp(texel, vec2(0.0), settings.render_size - 1.0));
+}
+
+fn load_view_target(texel: vec2<f32>) -> vec4<f32> {
+    var sample = textureLoad(view_target, render_texel(texel), 0);
+#ifdef TONEMAP
+    sample = vec4(tonemap(sample.rgb), sample.a);
+#endif
+    return sample;
+}
+
+fn sample_history(u: f32, v: f32) -> vec3<f32> {
+    return textureSampleLevel(history, linear_sampler, vec2(u, v), 0.0).rgb;
+}
+
+@fragment
+fn temporal_upscaling(in: FullscreenVertexOutput) -> Output {
+    let texture_size = vec2<f32>(textureDimensions(view_target));
+    let texel_size = 1.0 / texture_size;
+
+    // The position of the pixel in the viewport, and in the pixels of the main passes
+    let output_uv = (in.position.xy - settings.viewport_origin) / settings.output_size;
+    let render_position = output_uv * settings.render_size;
+    let render_center = floor(render_position);
+
+    // Reconstruct the current frame from the 3x3 samples around the pixel, weighted by the
+    // distance from where they were rendered, with the jitter of the frame
+    var color_sum = vec3(0.0);
+    var weight_sum = 0.0;
+    var max_weight = 0.0;
+    var moment_1 = vec3(0.0);
+    var moment_2 = vec3(0.0);
+    var neighborhood_min = vec3(1.0);
+    var neighborhood_max = vec3(0.0);
+    var closest_texel = render_center;
+    var closest_depth = 0.0;
+    for (var y = -1.0; y <= 1.0; y += 1.0) {
+        for (var x = -1.0; x <= 1.0; x += 1.0) {
+            let texel = render_center + vec2(x, y);
+            let color = load_view_target(texel).rgb;
+            let sample_position = texel + 0.5 - settings.jitter;
+            let weight = lanczos2(length(sample_position - render_position));
+            color_sum += color * weight;
+            weight_sum += weight;
+            max_weight = max(max_weight, weight);
+
+            let ycocg = RGB_to_YCoCg(color);
+            moment_1 += ycocg;
+            moment_2 += ycocg * ycocg;
+            neighborhood_min = min(neighborhood_min, color);
+            neighborhood_max = max(neighborhood_max, color);
+
+            // Pick the closest motion vector (reduces aliasing on the edges of moving entities)
+            let texel_depth = textureLoad(depth, render_texel(texel), 0);
+            if texel_depth > closest_depth {
+                closest_depth = texel_depth;
+                closest_texel = texel;
+            }
+        }
+    }
+
+    let original_color = load_view_target(render_center);
+    var current_color = original_color.rgb;
+    if weight_sum > 0.0001 {
+        // The negative lobes of the kernel can overshoot, so clamp to the neighborhood (reduces
+        // ringing)
+        current_color = clamp(color_sum / weight_sum, neighborhood_min, neighborhood_max);
+    }
+
+#ifdef RESET
+    let confidence = 1.0;
+#else
+    let closest_motion_vector = textureLoad(motion_vectors, render_texel(closest_texel), 0).rg;
+
+    // Reproject to find the equivalent sample from the past, with 5-sample Catmull-Rom filtering,
+    // see taa.wgsl. The history has the resolution of the output.
+    let history_uv = output_uv - closest_motion_vector;
+    let sample_position = (settings.viewport_origin + history_uv * settings.output_size);
+    let texel_center = floor(sample_position - 0.5) + 0.5;
+    let f = sample_position - texel_center;
+    let w0 = f * (-0.5 + f * (1.0 - 0.5 * f));
+    let w1 = 1.0 + f * f * (-2.5 + 1.5 * f);
+    let w2 = f * (0.5 + f * (2.0 - 1.5 * f));
+    let w3 = f * f * (-0.5 + 0.5 * f);
+    let w12 = w1 + w2;
+    let texel_position_0 = (texel_center - 1.0) * texel_size;
+    let texel_position_3 = (texel_center + 2.0) * texel_size;
+    let texel_position_12 = (texel_center + (w2 / w12)) * texel_size;
+    var history_color = sample_history(texel_position_12.x, texel_position_0.y) * w12.x * w0.y;
+    history_color += sample_history(texel_position_0.x, texel_position_12.y) * w0.x * w12.y;
+    history_color += sample_history(texel_position_12.x, texel_position_12.y) * w12.x * w12.y;
+    history_color += sample_history(texel_position_3.x, texel_position_12.y) * w3.x * w12.y;
+    history_color += sample_history(texel_position_12.x, texel_position_3.y) * w12.x * w3.y;
+
+    // Constrain past sample with 3x3 YCoCg variance clipping (reduces ghosting)
+    let mean = moment_1 / 9.0;
+    let variance = (moment_2 / 9.0) - (mean * mean);
+    let std_deviation = sqrt(max(variance, vec3(0.0)));
+    history_color = RGB_to_YCoCg(history_color);
+    history_color = clip_towards_aabb_center(history_color, mean - std_deviation, mean + std_deviation);
+    history_color = YCoCg_to_RGB(history_color);
+
+    // How many frames are accumulated in the history, stored normalized so that it fits in the
+    // alpha channel of LDR textures
+    let history_texel = vec2<i32>(in.position.xy);
+    var confidence = textureLoad(history, history_texel, 0).a * MAX_CONFIDENCE;
+    let pixel_motion_vector = abs(closest_motion_vector) * settings.output_size;
+    if pixel_motion_vector.x >= 0.01 || pixel_motion_vector.y >= 0.01 {
+        confidence = min(confidence, MAX_MOVING_CONFIDENCE);
+    }
+
+    // Samples that were rendered close to the pixel count as a whole frame, and the others as a
+    // fraction of one, so that the history converges to the output resolution
+    confidence = min(confidence + max_weight, MAX_CONFIDENCE);
+    var current_color_factor = max_weight / max(confidence, 0.0001);
+
+    // Reject history when motion vectors point off screen
+    if any(saturate(history_uv) != history_uv) {
+        current_color_factor = 1.0;
+        confidence = 1.0;
+    }
+
+    current_color = mix(history_color, current_color, current_color_factor);
+#endif // RESET
+
+    // Write output to history and view target
+    var out: Output;
+    out.history = vec4(current_color, confidence / MAX_CONFIDENCE);
+#ifdef TONEMAP
+    current_color = reverse_tonemap(current_color);
+#endif
+    out.view_target = vec4(current_color, original_color.a);
+    return out;
+}
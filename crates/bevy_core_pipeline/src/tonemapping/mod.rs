@@ -136,6 +136,10 @@ pub struct TonemappingPipeline {
 }
 
 /// Optionally enables a tonemapping shader that attempts to map linear input stimulus into a perceptually uniform image for a given [`Camera`] entity.
+///
+/// When an HDR camera renders to a window presented in HDR, see
+/// [`HdrOutput`](bevy_window::HdrOutput), any method other than [`Tonemapping::None`] is replaced
+/// by a curve that only compresses the highlights that the display can't show.
 #[derive(
     Component, Debug, Hash, Clone, Copy, Reflect, Default, ExtractComponent, PartialEq, Eq,
 )]
@@ -214,6 +218,9 @@ pub struct TonemappingPipelineKey {
     deband_dither: DebandDither,
     tonemapping: Tonemapping,
     flags: TonemappingPipelineKeyFlags,
+    /// The ratio of the peak luminance of the display to SDR white, in thousandths, when the view
+    /// is presented in HDR.
+    hdr_output_max_white: Option<u32>,
 }
 
 impl SpecializedRenderPipeline for TonemappingPipeline {
@@ -252,6 +259,12 @@ impl SpecializedRenderPipeline for TonemappingPipeline {
             shader_defs.push("SECTIONAL_COLOR_GRADING".into());
         }
 
+        // When presented in HDR, only the highlights that the display can't show are compressed,
+        // instead of mapping the whole image to the SDR range.
+        if let Some(max_white) = key.hdr_output_max_white {
+            shader_defs.push(ShaderDefVal::UInt("HDR_OUTPUT_MAX_WHITE".into(), max_white));
+        }
+
         match key.tonemapping {
             Tonemapping::None => shader_defs.push("TONEMAP_METHOD_NONE".into()),
             Tonemapping::Reinhard => shader_defs.push("TONEMAP_METHOD_REINHARD".into()),
@@ -351,17 +364,15 @@ pub fn prepare_view_tonemapping_pipelines(
     pipeline_cache: Res<PipelineCache>,
     mut pipelines: ResMut<SpecializedRenderPipelines<TonemappingPipeline>>,
     upscaling_pipeline: Res<TonemappingPipeline>,
-    view_targets: Query<
-        (
-            Entity,
-            &ExtractedView,
-            Option<&Tonemapping>,
-            Option<&DebandDither>,
-        ),
-        With<ViewTarget>,
-    >,
+    view_targets: Query<(
+        Entity,
+        &ExtractedView,
+        &ViewTarget,
+        Option<&Tonemapping>,
+        Option<&DebandDither>,
+    )>,
 ) {
-    for (entity, view, tonemapping, dither) in view_targets.iter() {
+    for (entity, view, view_target, tonemapping, dither) in view_targets.iter() {
         // As an optimization, we omit parts of the shader that are unneeded.
         let mut flags = TonemappingPipelineKeyFlags::empty();
         flags.set(
@@ -383,6 +394,9 @@ pub fn prepare_view_tonemapping_pipelines(
             deband_dither: *dither.unwrap_or(&DebandDither::Disabled),
             tonemapping: *tonemapping.unwrap_or(&Tonemapping::None),
             flags,
+            hdr_output_max_white: view_target.hdr_output().map(|hdr_output| {
+                (hdr_output.max_luminance / hdr_output.paper_white * 1000.0).round() as u32
+            }),
         };
         let pipeline = pipelines.specialize(&pipeline_cache, &upscaling_pipeline, key);
 
@@ -265,6 +265,18 @@ fn tonemapping_reinhard_luminance(color: vec3<f32>) -> vec3<f32> {
     return tonemapping_change_luminance(color, l_new);
 }
 
+// Keeps colors up to SDR white unchanged, and compresses the luminance of brighter colors so that
+// it rolls off towards `max_white`, the brightest value that an HDR display can show.
+fn tonemapping_hdr_output(color: vec3<f32>, max_white: f32) -> vec3<f32> {
+    let l_old = tonemapping_luminance(color);
+    if l_old <= 1.0 {
+        return color;
+    }
+    let excess = l_old - 1.0;
+    let l_new = 1.0 + excess / (1.0 + excess / max(max_white - 1.0, 1e-4));
+    return tonemapping_change_luminance(color, l_new);
+}
+
 fn rgb_to_srgb_simple(color: vec3<f32>) -> vec3<f32> {
     return pow(color, vec3<f32>(1.0 / 2.2));
 }
@@ -369,7 +381,9 @@ fn tone_mapping(in: vec4<f32>, in_color_grading: ColorGrading) -> vec4<f32> {
 #endif
 
     // tone_mapping
-#ifdef TONEMAP_METHOD_NONE
+#ifdef HDR_OUTPUT_MAX_WHITE
+    color = tonemapping_hdr_output(color, f32(#{HDR_OUTPUT_MAX_WHITE}) / 1000.0);
+#else ifdef TONEMAP_METHOD_NONE
     color = color;
 #else ifdef TONEMAP_METHOD_REINHARD
     color = tonemapping_reinhard(color.rgb);
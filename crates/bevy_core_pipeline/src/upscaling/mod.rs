@@ -77,6 +77,13 @@ fn prepare_view_upscaling_pipelines(
             texture_format: view_target.out_texture_format(),
             blend_state,
             samples: 1,
+            hdr_paper_white_nits: view_target
+                .hdr_output()
+                .map(|hdr_output| hdr_output.paper_white.round() as u32),
+            hdr_output_format: view_target
+                .hdr_output()
+                .map(|hdr_output| hdr_output.format)
+                .unwrap_or_default(),
         };
         let pipeline = pipelines.specialize(&pipeline_cache, &blit_pipeline, key);
 
@@ -0,0 +1,203 @@
+//! Module containing logic for the GPU timings overlay.
+
+use bevy_app::{App, Plugin, Startup, Update};
+use bevy_asset::Handle;
+use bevy_color::{palettes::css::RED, Color};
+use bevy_diagnostic::DiagnosticsStore;
+use bevy_ecs::{
+    change_detection::DetectChangesMut,
+    component::Component,
+    entity::Entity,
+    query::With,
+    schedule::{common_conditions::resource_changed, IntoSystemConfigs},
+    system::{Commands, Query, Res, Resource},
+};
+use bevy_hierarchy::BuildChildren;
+use bevy_render::{
+    diagnostic::{RenderBudgets, RenderNodeDiagnosticsPlugin, RENDER_GRAPH_ELAPSED_GPU},
+    view::Visibility,
+};
+use bevy_text::{Font, TextColor, TextFont};
+use bevy_ui::{
+    widget::{Text, TextUiWriter},
+    GlobalZIndex, Node, PositionType, Val,
+};
+use core::fmt::Write;
+
+/// [`GlobalZIndex`] used to render the GPU timings overlay.
+pub const GPU_TIMINGS_OVERLAY_ZINDEX: i32 = i32::MAX - 34;
+
+/// A plugin that shows the GPU time spent in the slowest render graph nodes, as recorded by
+/// [`RenderNodeDiagnosticsPlugin`], along with their [`RenderBudgets`].
+///
+/// This plugin will add the [`RenderNodeDiagnosticsPlugin`] if it wasn't added before. GPU times
+/// are only available on platforms that support timestamp queries.
+#[derive(Default)]
+pub struct GpuTimingsOverlayPlugin {
+    /// Starting configuration of overlay, this can be later be changed through
+    /// [`GpuTimingsOverlayConfig`] resource.
+    pub config: GpuTimingsOverlayConfig,
+}
+
+impl Plugin for GpuTimingsOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<RenderNodeDiagnosticsPlugin>() {
+            app.add_plugins(RenderNodeDiagnosticsPlugin);
+        }
+        app.insert_resource(self.config.clone())
+            .add_systems(Startup, setup)
+            .add_systems(
+                Update,
+                (
+                    (customize_text, toggle_display)
+                        .run_if(resource_changed::<GpuTimingsOverlayConfig>),
+                    update_text,
+                ),
+            );
+    }
+}
+
+/// Configuration options for the GPU timings overlay.
+#[derive(Resource, Clone)]
+pub struct GpuTimingsOverlayConfig {
+    /// Configuration of text in the overlay.
+    pub text_config: TextFont,
+    /// Color of text in the overlay.
+    pub text_color: Color,
+    /// Color of text in the overlay when a budget is exceeded.
+    pub over_budget_color: Color,
+    /// The number of nodes listed in the overlay, from the slowest one.
+    pub max_nodes: usize,
+    /// Displays the overlay if true.
+    pub enabled: bool,
+}
+
+impl Default for GpuTimingsOverlayConfig {
+    fn default() -> Self {
+        GpuTimingsOverlayConfig {
+            text_config: TextFont {
+                font: Handle::<Font>::default(),
+                font_size: 14.0,
+                ..Default::default()
+            },
+            text_color: Color::WHITE,
+            over_budget_color: RED.into(),
+            max_nodes: 10,
+            enabled: true,
+        }
+    }
+}
+
+#[derive(Component)]
+struct GpuTimingsText;
+
+fn setup(mut commands: Commands, overlay_config: Res<GpuTimingsOverlayConfig>) {
+    commands
+        .spawn((
+            Node {
+                // We need to make sure the overlay doesn't affect the position of other UI nodes
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(0.0),
+                right: Val::Px(0.0),
+                ..Default::default()
+            },
+            // Render overlay on top of everything
+            GlobalZIndex(GPU_TIMINGS_OVERLAY_ZINDEX),
+        ))
+        .with_child((
+            Text::default(),
+            overlay_config.text_config.clone(),
+            TextColor(overlay_config.text_color),
+            GpuTimingsText,
+        ));
+}
+
+fn update_text(
+    overlay_config: Res<GpuTimingsOverlayConfig>,
+    diagnostics: Res<DiagnosticsStore>,
+    budgets: Res<RenderBudgets>,
+    query: Query<Entity, With<GpuTimingsText>>,
+    mut writer: TextUiWriter,
+) {
+    if !overlay_config.enabled {
+        return;
+    }
+
+    let mut over_budget = false;
+    let mut line = |text: &mut String, name: &str, value: f64, budget: Option<f64>| {
+        write!(text, "{value:.3} ms").unwrap();
+        if let Some(budget) = budget {
+            write!(text, " / {budget:.3} ms").unwrap();
+            over_budget |= value > budget;
+        }
+        writeln!(text, "  {name}").unwrap();
+    };
+
+    let mut text = String::new();
+    match diagnostics
+        .get(&RENDER_GRAPH_ELAPSED_GPU)
+        .and_then(|diagnostic| diagnostic.smoothed())
+    {
+        Some(total) => line(
+            &mut text,
+            "Render graph (GPU)",
+            total,
+            budgets.get(&RENDER_GRAPH_ELAPSED_GPU),
+        ),
+        None => text.push_str("GPU timings unavailable\n"),
+    }
+
+    let mut nodes: Vec<_> = diagnostics
+        .iter()
+        .filter_map(|diagnostic| {
+            let name = diagnostic
+                .path()
+                .as_str()
+                .strip_prefix("render/graph/")?
+                .strip_suffix("/elapsed_gpu")?;
+            Some((name, diagnostic.path(), diagnostic.smoothed()?))
+        })
+        .collect();
+    nodes.sort_by(|(_, _, a), (_, _, b)| b.total_cmp(a));
+
+    for (name, path, value) in nodes.into_iter().take(overlay_config.max_nodes) {
+        line(&mut text, name, value, budgets.get(path));
+    }
+
+    let color = match over_budget {
+        true => overlay_config.over_budget_color,
+        false => overlay_config.text_color,
+    };
+    for entity in &query {
+        *writer.text(entity, 0) = text.clone();
+        let mut text_color = writer.color(entity, 0);
+        if text_color.0 != color {
+            text_color.0 = color;
+        }
+    }
+}
+
+fn customize_text(
+    overlay_config: Res<GpuTimingsOverlayConfig>,
+    query: Query<Entity, With<GpuTimingsText>>,
+    mut writer: TextUiWriter,
+) {
+    for entity in &query {
+        writer.for_each_font(entity, |mut font| {
+            *font = overlay_config.text_config.clone();
+        });
+        writer.for_each_color(entity, |mut color| color.0 = overlay_config.text_color);
+    }
+}
+
+fn toggle_display(
+    overlay_config: Res<GpuTimingsOverlayConfig>,
+    mut query: Query<&mut Visibility, With<GpuTimingsText>>,
+) {
+    for mut visibility in &mut query {
+        visibility.set_if_neq(match overlay_config.enabled {
+            true => Visibility::Visible,
+            false => Visibility::Hidden,
+        });
+    }
+}
@@ -15,8 +15,16 @@ pub mod ci_testing;
 
 pub mod fps_overlay;
 
+pub mod gpu_timings_overlay;
+
 pub mod picking_debug;
 
+pub mod render_graph_inspector;
+
+pub mod shader_error_overlay;
+
+pub mod skeleton_gizmos;
+
 pub mod states;
 
 /// Enables developer tools in an [`App`]. This plugin is added automatically with `bevy_dev_tools`
@@ -0,0 +1,269 @@
+//! Module containing logic for the render graph inspector.
+
+use std::sync::{Arc, Mutex};
+
+use bevy_app::{App, Plugin, Startup, Update};
+use bevy_asset::Handle;
+use bevy_color::Color;
+use bevy_ecs::{
+    change_detection::DetectChangesMut,
+    component::Component,
+    entity::Entity,
+    query::With,
+    schedule::{common_conditions::resource_changed, IntoSystemConfigs},
+    system::{Commands, Query, Res, Resource},
+};
+use bevy_hierarchy::BuildChildren;
+use bevy_render::{
+    render_graph::{
+        RenderGraph, RenderGraphDescription, RenderGraphTimings, RenderNodeTiming,
+        RenderViewAttachments,
+    },
+    sync_world::MainEntity,
+    view::{ViewDepthTexture, ViewTarget, Visibility},
+    Render, RenderApp, RenderSet,
+};
+use bevy_text::{Font, TextColor, TextFont};
+use bevy_ui::{
+    widget::{Text, TextUiWriter},
+    GlobalZIndex, Node, PositionType, Val,
+};
+use core::{fmt::Write, time::Duration};
+
+/// [`GlobalZIndex`] used to render the render graph overlay.
+pub const RENDER_GRAPH_OVERLAY_ZINDEX: i32 = i32::MAX - 33;
+
+/// A plugin that exposes the structure of the [`RenderGraph`] and the time spent in its nodes to
+/// the main world, through the [`RenderGraphInspector`] resource, and shows the slowest nodes in
+/// an overlay.
+///
+/// This helps to find out whether a custom node runs at all, and where the CPU time of the
+/// render graph goes. The structure can also be exported to Graphviz with
+/// [`RenderGraphInspector::to_dot`].
+#[derive(Default)]
+pub struct RenderGraphInspectorPlugin {
+    /// Starting configuration of overlay, this can be later be changed through
+    /// [`RenderGraphOverlayConfig`] resource.
+    pub config: RenderGraphOverlayConfig,
+}
+
+impl Plugin for RenderGraphInspectorPlugin {
+    fn build(&self, app: &mut App) {
+        let inspector = RenderGraphInspector::default();
+
+        app.insert_resource(self.config.clone())
+            .insert_resource(inspector.clone())
+            .add_systems(Startup, setup)
+            .add_systems(
+                Update,
+                (
+                    (customize_text, toggle_display)
+                        .run_if(resource_changed::<RenderGraphOverlayConfig>),
+                    update_text,
+                ),
+            );
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .init_resource::<RenderGraphTimings>()
+            .insert_resource(inspector)
+            .add_systems(Render, update_inspector.in_set(RenderSet::Cleanup));
+    }
+}
+
+/// Gives access to the [`RenderGraph`] of the render world from the main world.
+///
+/// The contents are updated at the end of each frame by [`RenderGraphInspectorPlugin`].
+#[derive(Resource, Clone, Default)]
+pub struct RenderGraphInspector(Arc<Mutex<RenderGraphSnapshot>>);
+
+#[derive(Default)]
+struct RenderGraphSnapshot {
+    /// The description of the graph, along with the [`RenderGraph::structure_hash`] it was made
+    /// for.
+    description: Option<(u64, RenderGraphDescription)>,
+    timings: Vec<RenderNodeTiming>,
+    view_attachments: Vec<RenderViewAttachments>,
+}
+
+impl RenderGraphInspector {
+    /// Returns the structure of the render graph on the last rendered frame, or `None` if no
+    /// frame was rendered yet.
+    pub fn description(&self) -> Option<RenderGraphDescription> {
+        self.0
+            .lock()
+            .unwrap()
+            .description
+            .as_ref()
+            .map(|(_, description)| description.clone())
+    }
+
+    /// Returns the CPU time spent in each node that ran on the last rendered frame, in the order
+    /// that they ran.
+    ///
+    /// See [`RenderGraphTimings`] for what the timings include.
+    pub fn timings(&self) -> Vec<RenderNodeTiming> {
+        self.0.lock().unwrap().timings.clone()
+    }
+
+    /// Returns the textures that each view rendered to on the last rendered frame.
+    pub fn view_attachments(&self) -> Vec<RenderViewAttachments> {
+        self.0.lock().unwrap().view_attachments.clone()
+    }
+
+    /// Returns the structure of the render graph on the last rendered frame in the DOT format of
+    /// Graphviz, or `None` if no frame was rendered yet.
+    pub fn to_dot(&self) -> Option<String> {
+        self.0
+            .lock()
+            .unwrap()
+            .description
+            .as_ref()
+            .map(|(_, description)| description.to_dot())
+    }
+}
+
+/// Configuration options for the render graph overlay.
+#[derive(Resource, Clone)]
+pub struct RenderGraphOverlayConfig {
+    /// Configuration of text in the overlay.
+    pub text_config: TextFont,
+    /// Color of text in the overlay.
+    pub text_color: Color,
+    /// The number of nodes listed in the overlay, from the slowest one.
+    pub max_nodes: usize,
+    /// Displays the overlay if true.
+    pub enabled: bool,
+}
+
+impl Default for RenderGraphOverlayConfig {
+    fn default() -> Self {
+        RenderGraphOverlayConfig {
+            text_config: TextFont {
+                font: Handle::<Font>::default(),
+                font_size: 14.0,
+                ..Default::default()
+            },
+            text_color: Color::WHITE,
+            max_nodes: 10,
+            enabled: true,
+        }
+    }
+}
+
+#[derive(Component)]
+struct RenderGraphText;
+
+fn update_inspector(
+    graph: Res<RenderGraph>,
+    timings: Res<RenderGraphTimings>,
+    inspector: Res<RenderGraphInspector>,
+    views: Query<(Entity, &MainEntity, &ViewTarget, Option<&ViewDepthTexture>)>,
+) {
+    let mut snapshot = inspector.0.lock().unwrap();
+
+    // The graph rarely changes once the app is running, so it's only described again when it
+    // does.
+    let structure_hash = graph.structure_hash();
+    if snapshot
+        .description
+        .as_ref()
+        .is_none_or(|(hash, _)| *hash != structure_hash)
+    {
+        snapshot.description = Some((structure_hash, graph.describe()));
+    }
+
+    snapshot.timings = timings.last_frame();
+    snapshot.view_attachments = views
+        .iter()
+        .map(|(entity, main_entity, target, depth_texture)| {
+            RenderViewAttachments::new(entity, main_entity, target, depth_texture)
+        })
+        .collect();
+}
+
+fn setup(mut commands: Commands, overlay_config: Res<RenderGraphOverlayConfig>) {
+    commands
+        .spawn((
+            Node {
+                // We need to make sure the overlay doesn't affect the position of other UI nodes
+                position_type: PositionType::Absolute,
+                top: Val::Px(0.0),
+                right: Val::Px(0.0),
+                ..Default::default()
+            },
+            // Render overlay on top of everything
+            GlobalZIndex(RENDER_GRAPH_OVERLAY_ZINDEX),
+        ))
+        .with_child((
+            Text::default(),
+            overlay_config.text_config.clone(),
+            TextColor(overlay_config.text_color),
+            RenderGraphText,
+        ));
+}
+
+fn update_text(
+    overlay_config: Res<RenderGraphOverlayConfig>,
+    inspector: Res<RenderGraphInspector>,
+    query: Query<Entity, With<RenderGraphText>>,
+    mut writer: TextUiWriter,
+) {
+    if !overlay_config.enabled {
+        return;
+    }
+
+    let mut timings = inspector.timings();
+    let total: Duration = timings.iter().map(|timing| timing.duration).sum();
+    timings.sort_by(|a, b| b.duration.cmp(&a.duration));
+
+    let mut text = format!(
+        "Render graph: {} nodes, {:.2} ms\n",
+        timings.len(),
+        total.as_secs_f64() * 1000.0
+    );
+    for timing in timings.iter().take(overlay_config.max_nodes) {
+        write!(
+            &mut text,
+            "\n{:.3} ms  {:?}",
+            timing.duration.as_secs_f64() * 1000.0,
+            timing.label
+        )
+        .unwrap();
+        if let Some(sub_graph) = timing.sub_graph {
+            write!(&mut text, " ({sub_graph:?})").unwrap();
+        }
+    }
+
+    for entity in &query {
+        *writer.text(entity, 0) = text.clone();
+    }
+}
+
+fn customize_text(
+    overlay_config: Res<RenderGraphOverlayConfig>,
+    query: Query<Entity, With<RenderGraphText>>,
+    mut writer: TextUiWriter,
+) {
+    for entity in &query {
+        writer.for_each_font(entity, |mut font| {
+            *font = overlay_config.text_config.clone();
+        });
+        writer.for_each_color(entity, |mut color| color.0 = overlay_config.text_color);
+    }
+}
+
+fn toggle_display(
+    overlay_config: Res<RenderGraphOverlayConfig>,
+    mut query: Query<&mut Visibility, With<RenderGraphText>>,
+) {
+    for mut visibility in &mut query {
+        visibility.set_if_neq(match overlay_config.enabled {
+            true => Visibility::Visible,
+            false => Visibility::Hidden,
+        });
+    }
+}
@@ -0,0 +1,225 @@
+//! Module containing logic for the shader error overlay.
+
+use std::sync::{Arc, Mutex};
+
+use bevy_app::{App, Plugin, Startup, Update};
+use bevy_asset::Handle;
+use bevy_color::Color;
+use bevy_ecs::{
+    change_detection::{DetectChanges, DetectChangesMut},
+    component::Component,
+    entity::Entity,
+    query::With,
+    schedule::{common_conditions::resource_changed, IntoSystemConfigs},
+    system::{Commands, Local, Query, Res, Resource},
+};
+use bevy_hierarchy::BuildChildren;
+use bevy_render::{
+    render_resource::{PipelineCache, PipelineError},
+    view::Visibility,
+    Render, RenderApp, RenderSet,
+};
+use bevy_text::{Font, TextColor, TextFont};
+use bevy_ui::{
+    widget::{Text, TextUiWriter},
+    BackgroundColor, GlobalZIndex, Node, PositionType, UiRect, Val,
+};
+
+/// [`GlobalZIndex`] used to render the shader error overlay.
+///
+/// This is just above [`FPS_OVERLAY_ZINDEX`](crate::fps_overlay::FPS_OVERLAY_ZINDEX), so errors
+/// aren't hidden by the FPS counter.
+pub const SHADER_ERROR_OVERLAY_ZINDEX: i32 = i32::MAX - 31;
+
+/// A plugin that shows the errors of the shaders that fail to compile on screen, for example
+/// after a hot reload.
+///
+/// While a shader is broken, the render pipelines that use it draw in magenta instead of not
+/// drawing at all, so the affected materials are easy to spot. This only works for errors in
+/// fragment shaders, see [`PipelineCache::set_error_fallback`]. The overlay disappears once the
+/// shaders are fixed.
+#[derive(Default)]
+pub struct ShaderErrorOverlayPlugin {
+    /// Starting configuration of overlay, this can be later be changed through
+    /// [`ShaderErrorOverlayConfig`] resource.
+    pub config: ShaderErrorOverlayConfig,
+}
+
+impl Plugin for ShaderErrorOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        let shader_errors = ShaderErrors::default();
+
+        app.insert_resource(self.config.clone())
+            .insert_resource(shader_errors.clone())
+            .add_systems(Startup, setup)
+            .add_systems(
+                Update,
+                (
+                    customize_text.run_if(resource_changed::<ShaderErrorOverlayConfig>),
+                    update_text,
+                )
+                    .chain(),
+            );
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .insert_resource(shader_errors)
+            .add_systems(Render, collect_pipeline_errors.in_set(RenderSet::Cleanup));
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        if let Some(mut pipeline_cache) = render_app.world_mut().get_resource_mut::<PipelineCache>()
+        {
+            pipeline_cache.set_error_fallback(true);
+        }
+    }
+}
+
+/// Configuration options for the shader error overlay.
+#[derive(Resource, Clone)]
+pub struct ShaderErrorOverlayConfig {
+    /// Configuration of text in the overlay.
+    pub text_config: TextFont,
+    /// Color of text in the overlay.
+    pub text_color: Color,
+    /// Color of the background behind the text.
+    pub background_color: Color,
+    /// Displays the overlay when there are errors if true.
+    pub enabled: bool,
+}
+
+impl Default for ShaderErrorOverlayConfig {
+    fn default() -> Self {
+        ShaderErrorOverlayConfig {
+            text_config: TextFont {
+                font: Handle::<Font>::default(),
+                font_size: 16.0,
+                ..Default::default()
+            },
+            text_color: Color::WHITE,
+            background_color: Color::srgba(0.3, 0.0, 0.0, 0.85),
+            enabled: true,
+        }
+    }
+}
+
+/// The pipeline errors copied from the render world, shared by both worlds.
+#[derive(Resource, Clone, Default)]
+struct ShaderErrors(Arc<Mutex<Vec<PipelineError>>>);
+
+#[derive(Component)]
+struct ShaderErrorOverlay;
+
+#[derive(Component)]
+struct ShaderErrorText;
+
+fn setup(mut commands: Commands, overlay_config: Res<ShaderErrorOverlayConfig>) {
+    commands
+        .spawn((
+            Node {
+                // We need to make sure the overlay doesn't affect the position of other UI nodes
+                position_type: PositionType::Absolute,
+                left: Val::Px(0.0),
+                bottom: Val::Px(0.0),
+                width: Val::Percent(100.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                ..Default::default()
+            },
+            BackgroundColor(overlay_config.background_color),
+            // Render overlay on top of everything
+            GlobalZIndex(SHADER_ERROR_OVERLAY_ZINDEX),
+            Visibility::Hidden,
+            ShaderErrorOverlay,
+        ))
+        .with_child((
+            Text::default(),
+            overlay_config.text_config.clone(),
+            TextColor(overlay_config.text_color),
+            ShaderErrorText,
+        ));
+}
+
+fn collect_pipeline_errors(pipeline_cache: Res<PipelineCache>, shader_errors: Res<ShaderErrors>) {
+    let mut errors = shader_errors.0.lock().unwrap();
+    if !errors.iter().eq(pipeline_cache.pipeline_errors()) {
+        *errors = pipeline_cache.pipeline_errors().cloned().collect();
+    }
+}
+
+fn update_text(
+    overlay_config: Res<ShaderErrorOverlayConfig>,
+    shader_errors: Res<ShaderErrors>,
+    mut shown_errors: Local<Vec<PipelineError>>,
+    text_query: Query<Entity, With<ShaderErrorText>>,
+    mut overlay_query: Query<&mut Visibility, With<ShaderErrorOverlay>>,
+    mut writer: TextUiWriter,
+) {
+    {
+        let errors = shader_errors.0.lock().unwrap();
+        if *errors != *shown_errors || overlay_config.is_changed() {
+            shown_errors.clone_from(&errors);
+        } else {
+            return;
+        }
+    }
+
+    // A broken shader usually breaks many pipelines with the same error, so each message is only
+    // shown once.
+    let mut messages: Vec<(&str, usize)> = Vec::new();
+    for error in shown_errors.iter() {
+        match messages
+            .iter_mut()
+            .find(|(message, _)| *message == error.message)
+        {
+            Some((_, count)) => *count += 1,
+            None => messages.push((&error.message, 1)),
+        }
+    }
+
+    let mut text = String::new();
+    for (message, count) in &messages {
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        text.push_str(&match count {
+            1 => "1 pipeline failed to compile:\n".to_owned(),
+            _ => format!("{count} pipelines failed to compile:\n"),
+        });
+        text.push_str(message.trim_end());
+        text.push('\n');
+    }
+
+    for entity in &text_query {
+        *writer.text(entity, 0) = text.clone();
+    }
+    for mut visibility in &mut overlay_query {
+        visibility.set_if_neq(match overlay_config.enabled && !messages.is_empty() {
+            true => Visibility::Visible,
+            false => Visibility::Hidden,
+        });
+    }
+}
+
+fn customize_text(
+    overlay_config: Res<ShaderErrorOverlayConfig>,
+    text_query: Query<Entity, With<ShaderErrorText>>,
+    mut background_query: Query<&mut BackgroundColor, With<ShaderErrorOverlay>>,
+    mut writer: TextUiWriter,
+) {
+    for entity in &text_query {
+        writer.for_each_font(entity, |mut font| {
+            *font = overlay_config.text_config.clone();
+        });
+        writer.for_each_color(entity, |mut color| color.0 = overlay_config.text_color);
+    }
+    for mut background in &mut background_query {
+        background.0 = overlay_config.background_color;
+    }
+}
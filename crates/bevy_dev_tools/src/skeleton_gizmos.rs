@@ -0,0 +1,292 @@
+//! Debug visualization of the joint hierarchies of skinned meshes.
+//!
+//! Add the [`SkeletonGizmoPlugin`] and either insert [`ShowSkeletonGizmo`] on
+//! the entities with a [`SkinnedMesh`] whose skeleton you want to see, or set
+//! [`SkeletonGizmoConfigGroup::draw_all`] to draw every skeleton. Each bone is
+//! drawn as an octahedron pointing from a joint to its child, and each joint as
+//! a small sphere, optionally labeled with its [`Name`].
+//!
+//! The plugin requires the [`GizmoPlugin`](bevy_gizmos::GizmoPlugin).
+
+use bevy_app::{App, Plugin, PostUpdate};
+use bevy_color::{
+    palettes::css::{ORANGE, YELLOW},
+    Color,
+};
+use bevy_ecs::{entity::EntityHashMap, prelude::*};
+use bevy_gizmos::{config::GizmoConfigStore, prelude::*};
+use bevy_hierarchy::Parent;
+use bevy_math::Vec3;
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::{camera::Camera, mesh::skinning::SkinnedMesh, view::Visibility};
+use bevy_text::{TextColor, TextFont};
+use bevy_transform::{components::GlobalTransform, TransformSystem};
+use bevy_ui::{
+    prelude::{Node, Text},
+    PositionType, TargetCamera, Val,
+};
+
+/// A [`Plugin`] that draws the joint hierarchies of [`SkinnedMesh`]es for
+/// debugging.
+pub struct SkeletonGizmoPlugin;
+
+impl Plugin for SkeletonGizmoPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<SkeletonGizmoConfigGroup>()
+            .register_type::<ShowSkeletonGizmo>()
+            .init_gizmo_group::<SkeletonGizmoConfigGroup>()
+            .add_systems(
+                PostUpdate,
+                (draw_skeletons, update_joint_labels).after(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// The [`GizmoConfigGroup`] used to configure the visualization of skeletons.
+#[derive(Clone, Reflect, GizmoConfigGroup)]
+pub struct SkeletonGizmoConfigGroup {
+    /// Draw the skeletons of all skinned meshes if true.
+    ///
+    /// Defaults to `false`.
+    pub draw_all: bool,
+    /// Label every joint with its [`Name`] if true.
+    ///
+    /// Defaults to `false`.
+    pub joint_names: bool,
+    /// The color of the bones.
+    ///
+    /// Defaults to [`YELLOW`].
+    pub bone_color: Color,
+    /// The color of the joints and of their names.
+    ///
+    /// Defaults to [`ORANGE`].
+    pub joint_color: Color,
+    /// The width of the bone octahedrons, relative to the length of the bones.
+    ///
+    /// Defaults to 0.1.
+    pub bone_width: f32,
+    /// The font size of the joint names.
+    ///
+    /// Defaults to 12.0.
+    pub joint_name_font_size: f32,
+}
+
+impl Default for SkeletonGizmoConfigGroup {
+    fn default() -> Self {
+        Self {
+            draw_all: false,
+            joint_names: false,
+            bone_color: YELLOW.into(),
+            joint_color: ORANGE.into(),
+            bone_width: 0.1,
+            joint_name_font_size: 12.0,
+        }
+    }
+}
+
+/// Add this [`Component`] to an entity with a [`SkinnedMesh`] to draw its
+/// skeleton.
+#[derive(Component, Reflect, Default, Debug)]
+#[reflect(Component, Default, Debug)]
+pub struct ShowSkeletonGizmo {
+    /// The color of the bones. If [`None`], use the one provided by
+    /// [`SkeletonGizmoConfigGroup`].
+    ///
+    /// Defaults to [`None`].
+    pub color: Option<Color>,
+    /// Whether to label the joints with their names. If [`None`], use the
+    /// setting of [`SkeletonGizmoConfigGroup`].
+    ///
+    /// Defaults to [`None`].
+    pub joint_names: Option<bool>,
+}
+
+/// Returns the parent of each joint of a skin that's also one of its joints.
+fn joint_parents(
+    skinned_mesh: &SkinnedMesh,
+    parents: &Query<&Parent>,
+) -> EntityHashMap<Option<Entity>> {
+    let mut joint_parents: EntityHashMap<Option<Entity>> = skinned_mesh
+        .joints
+        .iter()
+        .map(|&joint| (joint, None))
+        .collect();
+    for &joint in &skinned_mesh.joints {
+        let parent = parents
+            .get(joint)
+            .ok()
+            .map(Parent::get)
+            .filter(|parent| joint_parents.contains_key(parent));
+        joint_parents.insert(joint, parent);
+    }
+    joint_parents
+}
+
+/// Draws a bone as an octahedron from `start` to `end`.
+fn bone_gizmo(
+    start: Vec3,
+    end: Vec3,
+    width: f32,
+    color: Color,
+    gizmos: &mut Gizmos<SkeletonGizmoConfigGroup>,
+) {
+    let axis = end - start;
+    let length = axis.length();
+    let Some(direction) = axis.try_normalize() else {
+        return;
+    };
+    let (side, up) = direction.any_orthonormal_pair();
+    let center = start + axis * 0.1;
+    let (side, up) = (side * length * width, up * length * width);
+    let corners = [center + side, center + up, center - side, center - up];
+    for (index, &corner) in corners.iter().enumerate() {
+        gizmos.line(start, corner, color);
+        gizmos.line(corner, end, color);
+        gizmos.line(corner, corners[(index + 1) % corners.len()], color);
+    }
+}
+
+fn draw_skeletons(
+    skinned_meshes: Query<(&SkinnedMesh, Option<&ShowSkeletonGizmo>)>,
+    joints: Query<&GlobalTransform>,
+    parents: Query<&Parent>,
+    mut gizmos: Gizmos<SkeletonGizmoConfigGroup>,
+) {
+    let config = gizmos.config_ext.clone();
+    for (skinned_mesh, show) in &skinned_meshes {
+        if show.is_none() && !config.draw_all {
+            continue;
+        }
+        let bone_color = show
+            .and_then(|show| show.color)
+            .unwrap_or(config.bone_color);
+
+        let joint_parents = joint_parents(skinned_mesh, &parents);
+        let bones: Vec<(Vec3, Vec3)> = joint_parents
+            .iter()
+            .filter_map(|(&joint, &parent)| {
+                let end = joints.get(joint).ok()?.translation();
+                let start = joints.get(parent?).ok()?.translation();
+                Some((start, end))
+            })
+            .collect();
+        for &(start, end) in &bones {
+            bone_gizmo(start, end, config.bone_width, bone_color, &mut gizmos);
+        }
+
+        // Size the joints after the average bone, so that they're visible
+        // regardless of the scale of the skeleton.
+        let average_length = if bones.is_empty() {
+            0.1
+        } else {
+            bones
+                .iter()
+                .map(|(start, end)| start.distance(*end))
+                .sum::<f32>()
+                / bones.len() as f32
+        };
+        for &joint in &skinned_mesh.joints {
+            if let Ok(transform) = joints.get(joint) {
+                gizmos
+                    .sphere(
+                        transform.translation(),
+                        average_length * config.bone_width * 0.5,
+                        config.joint_color,
+                    )
+                    .resolution(8);
+            }
+        }
+    }
+}
+
+/// Marks the UI text that labels a joint with its name.
+#[derive(Component)]
+struct JointLabel {
+    joint: Entity,
+}
+
+/// Spawns, moves, and despawns the labels of the joints whose names are shown.
+fn update_joint_labels(
+    mut commands: Commands,
+    config_store: Res<GizmoConfigStore>,
+    skinned_meshes: Query<(&SkinnedMesh, Option<&ShowSkeletonGizmo>)>,
+    joints: Query<(&GlobalTransform, Option<&Name>)>,
+    cameras: Query<(Entity, &Camera, &GlobalTransform)>,
+    mut labels: Query<(Entity, &JointLabel, &mut Node, &mut Visibility)>,
+) {
+    let (gizmo_config, config) = config_store.config::<SkeletonGizmoConfigGroup>();
+
+    // Collect the named joints of every skeleton whose names are shown.
+    let mut names = EntityHashMap::default();
+    if gizmo_config.enabled {
+        for (skinned_mesh, show) in &skinned_meshes {
+            let shown = match show {
+                Some(show) => show.joint_names.unwrap_or(config.joint_names),
+                None => config.draw_all && config.joint_names,
+            };
+            if !shown {
+                continue;
+            }
+            for &joint in &skinned_mesh.joints {
+                if let Ok((_, Some(name))) = joints.get(joint) {
+                    names.insert(joint, name.as_str().to_owned());
+                }
+            }
+        }
+    }
+
+    // Project the labels with the active camera that renders last.
+    let camera = cameras
+        .iter()
+        .filter(|(_, camera, _)| camera.is_active)
+        .max_by_key(|(_, camera, _)| camera.order);
+
+    let mut labeled = EntityHashMap::default();
+    for (entity, label, mut node, mut visibility) in &mut labels {
+        if !names.contains_key(&label.joint) {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        labeled.insert(label.joint, ());
+
+        let position = camera.and_then(|(_, camera, camera_transform)| {
+            let (joint_transform, _) = joints.get(label.joint).ok()?;
+            camera
+                .world_to_viewport(camera_transform, joint_transform.translation())
+                .ok()
+        });
+        match position {
+            Some(position) => {
+                node.left = Val::Px(position.x);
+                node.top = Val::Px(position.y);
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+
+    let Some((camera_entity, _, _)) = camera else {
+        return;
+    };
+    for (joint, name) in names {
+        if labeled.contains_key(&joint) {
+            continue;
+        }
+        // The label is moved into place on the next frame.
+        commands.spawn((
+            JointLabel { joint },
+            Text::new(name),
+            TextFont {
+                font_size: config.joint_name_font_size,
+                ..Default::default()
+            },
+            TextColor(config.joint_color),
+            Node {
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+            Visibility::Hidden,
+            TargetCamera(camera_entity),
+        ));
+    }
+}
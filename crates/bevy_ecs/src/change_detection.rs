@@ -3,6 +3,7 @@
 use crate::{
     component::{Tick, TickCells},
     ptr::PtrMut,
+    storage::ColumnTick,
     system::Resource,
 };
 use alloc::borrow::ToOwned;
@@ -375,7 +376,7 @@ macro_rules! change_detection_mut_impl {
             #[inline]
             #[track_caller]
             fn set_changed(&mut self) {
-                *self.ticks.changed = self.ticks.this_run;
+                self.ticks.set_changed();
                 #[cfg(feature = "track_location")]
                 {
                     *self.changed_by = Location::caller();
@@ -385,7 +386,7 @@ macro_rules! change_detection_mut_impl {
             #[inline]
             #[track_caller]
             fn set_last_changed(&mut self, last_changed: Tick) {
-                *self.ticks.changed = last_changed;
+                self.ticks.set_last_changed(last_changed);
                 #[cfg(feature = "track_location")]
                 {
                     *self.changed_by = Location::caller();
@@ -441,6 +442,7 @@ macro_rules! impl_methods {
                     ticks: TicksMut {
                         added: self.ticks.added,
                         changed: self.ticks.changed,
+                        column_changed: self.ticks.column_changed,
                         last_run: self.ticks.last_run,
                         this_run: self.ticks.this_run,
                     },
@@ -552,6 +554,9 @@ impl<'w> Ticks<'w> {
 pub(crate) struct TicksMut<'w> {
     pub(crate) added: &'w mut Tick,
     pub(crate) changed: &'w mut Tick,
+    /// The newest changed tick of the table column that stores the value, which must be kept
+    /// up to date with `changed` for queries to be able to skip unchanged tables.
+    pub(crate) column_changed: Option<&'w ColumnTick>,
     pub(crate) last_run: Tick,
     pub(crate) this_run: Tick,
 }
@@ -570,10 +575,27 @@ impl<'w> TicksMut<'w> {
             added: unsafe { cells.added.deref_mut() },
             // SAFETY: Caller ensures there is no alias to the cell.
             changed: unsafe { cells.changed.deref_mut() },
+            column_changed: cells.column_changed,
             last_run,
             this_run,
         }
     }
+
+    #[inline]
+    pub(crate) fn set_changed(&mut self) {
+        *self.changed = self.this_run;
+        if let Some(column_changed) = self.column_changed {
+            column_changed.set(self.this_run);
+        }
+    }
+
+    #[inline]
+    pub(crate) fn set_last_changed(&mut self, last_changed: Tick) {
+        *self.changed = last_changed;
+        if let Some(column_changed) = self.column_changed {
+            column_changed.record(last_changed, self.this_run);
+        }
+    }
 }
 
 impl<'w> From<TicksMut<'w>> for Ticks<'w> {
@@ -957,6 +979,7 @@ impl<'w, T: ?Sized> Mut<'w, T> {
             ticks: TicksMut {
                 added,
                 changed: last_changed,
+                column_changed: None,
                 last_run,
                 this_run,
             },
@@ -1041,6 +1064,7 @@ impl<'w> MutUntyped<'w> {
             ticks: TicksMut {
                 added: self.ticks.added,
                 changed: self.ticks.changed,
+                column_changed: self.ticks.column_changed,
                 last_run: self.ticks.last_run,
                 this_run: self.ticks.this_run,
             },
@@ -1150,7 +1174,7 @@ impl<'w> DetectChangesMut for MutUntyped<'w> {
     #[inline]
     #[track_caller]
     fn set_changed(&mut self) {
-        *self.ticks.changed = self.ticks.this_run;
+        self.ticks.set_changed();
         #[cfg(feature = "track_location")]
         {
             *self.changed_by = Location::caller();
@@ -1160,7 +1184,7 @@ impl<'w> DetectChangesMut for MutUntyped<'w> {
     #[inline]
     #[track_caller]
     fn set_last_changed(&mut self, last_changed: Tick) {
-        *self.ticks.changed = last_changed;
+        self.ticks.set_last_changed(last_changed);
         #[cfg(feature = "track_location")]
         {
             *self.changed_by = Location::caller();
@@ -1378,6 +1402,7 @@ mod tests {
         let ticks = TicksMut {
             added: &mut component_ticks.added,
             changed: &mut component_ticks.changed,
+            column_changed: None,
             last_run: Tick::new(3),
             this_run: Tick::new(4),
         };
@@ -1432,6 +1457,7 @@ mod tests {
         let ticks = TicksMut {
             added: &mut component_ticks.added,
             changed: &mut component_ticks.changed,
+            column_changed: None,
             last_run: Tick::new(3),
             this_run: Tick::new(4),
         };
@@ -1467,6 +1493,7 @@ mod tests {
         let ticks = TicksMut {
             added: &mut component_ticks.added,
             changed: &mut component_ticks.changed,
+            column_changed: None,
             last_run,
             this_run,
         };
@@ -1557,6 +1584,7 @@ mod tests {
         let ticks = TicksMut {
             added: &mut component_ticks.added,
             changed: &mut component_ticks.changed,
+            column_changed: None,
             last_run,
             this_run,
         };
@@ -1596,6 +1624,7 @@ mod tests {
         let ticks = TicksMut {
             added: &mut component_ticks.added,
             changed: &mut component_ticks.changed,
+            column_changed: None,
             last_run: Tick::new(3),
             this_run: Tick::new(4),
         };
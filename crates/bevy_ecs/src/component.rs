@@ -7,7 +7,7 @@ use crate::{
     change_detection::MAX_CHANGE_AGE,
     entity::{ComponentCloneCtx, Entity},
     query::DebugCheckedUnwrap,
-    storage::{SparseSetIndex, SparseSets, Storages, Table, TableRow},
+    storage::{ColumnTick, SparseSetIndex, SparseSets, Storages, Table, TableRow},
     system::{Local, Resource, SystemParam},
     world::{DeferredWorld, FromWorld, World},
 };
@@ -1766,6 +1766,11 @@ pub struct TickCells<'a> {
     pub added: &'a UnsafeCell<Tick>,
     /// The tick indicating the last time the value was modified.
     pub changed: &'a UnsafeCell<Tick>,
+    /// The newest changed tick of the table column that stores the value, if it's stored in a
+    /// table.
+    ///
+    /// It's updated along with `changed` when the value is mutated through a [`Mut`](crate::change_detection::Mut).
+    pub column_changed: Option<&'a ColumnTick>,
 }
 
 impl<'a> TickCells<'a> {
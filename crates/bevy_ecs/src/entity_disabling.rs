@@ -0,0 +1,144 @@
+//! Disabled entities do not show up in queries.
+//!
+//! Adding the [`Disabled`] component to an entity hides it from every [`Query`] that doesn't
+//! explicitly ask for it, without despawning it or removing any of its other components.
+//! This is useful to keep entities around for later, for example in object pools, or to
+//! temporarily take parts of a scene out of the game.
+//!
+//! A query "explicitly asks" for a disabling component when it accesses or filters on it, for
+//! example with `Has<Disabled>`, `With<Disabled>`, `Option<&Disabled>`, or when it reads every
+//! component, like [`EntityRef`](crate::world::EntityRef) does. Direct access through the
+//! [`World`], such as [`World::entity`], is not affected.
+//!
+//! Other crates can register their own disabling components with
+//! [`World::register_disabling_component`], to add to the set of components that hide entities
+//! from queries by default.
+//!
+//! [`Query`]: crate::system::Query
+
+use crate::{
+    self as bevy_ecs,
+    component::{Component, ComponentId, Components, StorageType},
+    query::FilteredAccess,
+};
+use alloc::vec::Vec;
+
+#[cfg(feature = "bevy_reflect")]
+use {
+    crate::reflect::ReflectComponent,
+    bevy_reflect::{std_traits::ReflectDefault, Reflect},
+};
+
+#[cfg(doc)]
+use crate::world::World;
+
+/// A marker component for disabled entities.
+///
+/// Entities with this component are excluded from every query that doesn't explicitly mention
+/// it. See the [module docs] for more information.
+///
+/// [module docs]: crate::entity_disabling
+#[derive(Component, Clone, Copy, Debug, Default)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(Reflect),
+    reflect(Component, Default, Debug)
+)]
+pub struct Disabled;
+
+/// The set of components that hide entities from queries unless those queries explicitly ask
+/// for them.
+///
+/// It always contains [`Disabled`]. More components can be added with
+/// [`World::register_disabling_component`]. Queries that are already initialized keep the
+/// filters they were created with.
+#[derive(Debug, Default, Clone)]
+pub struct DefaultQueryFilters {
+    disabling: Vec<ComponentId>,
+}
+
+impl DefaultQueryFilters {
+    /// Adds a component to the set of components that hide entities from queries.
+    pub fn register_disabling_component(&mut self, component_id: ComponentId) {
+        if !self.disabling.contains(&component_id) {
+            self.disabling.push(component_id);
+        }
+    }
+
+    /// Returns the components that hide entities from queries.
+    pub fn disabling_ids(&self) -> impl Iterator<Item = ComponentId> + '_ {
+        self.disabling.iter().copied()
+    }
+
+    /// Adds a `Without` filter to `component_access` for every disabling component that it
+    /// doesn't already mention.
+    pub(crate) fn modify_access(&self, component_access: &mut FilteredAccess<ComponentId>) {
+        for &component_id in &self.disabling {
+            if !component_access.contains(component_id) {
+                component_access.and_without(component_id);
+            }
+        }
+    }
+
+    /// Returns `true` if filtering out all disabling components keeps a query dense.
+    ///
+    /// This is the case as long as all of them are stored in tables.
+    pub(crate) fn is_dense(&self, components: &Components) -> bool {
+        self.disabling.iter().all(|&component_id| {
+            components
+                .get_info(component_id)
+                .is_some_and(|info| info.storage_type() == StorageType::Table)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Disabled;
+    use crate::{
+        self as bevy_ecs,
+        component::Component,
+        query::{Has, With},
+        world::World,
+    };
+
+    #[derive(Component)]
+    struct A;
+
+    #[derive(Component)]
+    #[component(storage = "SparseSet")]
+    struct Hidden;
+
+    #[test]
+    fn disabled_entities_are_filtered_by_default() {
+        let mut world = World::new();
+        world.spawn(A);
+        let disabled = world.spawn((A, Disabled)).id();
+
+        assert_eq!(world.query::<&A>().iter(&world).count(), 1);
+        assert_eq!(world.query::<Has<Disabled>>().iter(&world).count(), 2);
+        assert_eq!(
+            world
+                .query_filtered::<(), With<Disabled>>()
+                .iter(&world)
+                .count(),
+            1
+        );
+        assert!(world.query::<&A>().get(&world, disabled).is_err());
+
+        world.entity_mut(disabled).remove::<Disabled>();
+        assert_eq!(world.query::<&A>().iter(&world).count(), 2);
+    }
+
+    #[test]
+    fn custom_disabling_component() {
+        let mut world = World::new();
+        world.register_disabling_component::<Hidden>();
+        world.spawn(A);
+        world.spawn((A, Hidden));
+        world.spawn((A, Disabled));
+
+        assert_eq!(world.query::<&A>().iter(&world).count(), 1);
+        assert_eq!(world.query::<(&A, Has<Hidden>)>().iter(&world).count(), 2);
+    }
+}
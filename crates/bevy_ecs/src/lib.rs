@@ -39,6 +39,7 @@ pub mod bundle;
 pub mod change_detection;
 pub mod component;
 pub mod entity;
+pub mod entity_disabling;
 pub mod event;
 pub mod identifier;
 pub mod intern;
@@ -48,7 +49,10 @@ pub mod observer;
 pub mod query;
 #[cfg(feature = "bevy_reflect")]
 pub mod reflect;
+pub mod relationship;
 pub mod removal_detection;
+#[cfg(feature = "bevy_reflect")]
+pub mod replication;
 pub mod result;
 pub mod schedule;
 pub mod storage;
@@ -72,10 +76,12 @@ pub mod prelude {
         change_detection::{DetectChanges, DetectChangesMut, Mut, Ref},
         component::{require, Component},
         entity::{Entity, EntityBorrow, EntityMapper},
+        entity_disabling::Disabled,
         event::{Event, EventMutator, EventReader, EventWriter, Events},
         name::{Name, NameOrEntity},
         observer::{CloneEntityWithObserversExt, Observer, Trigger},
         query::{Added, AnyOf, Changed, Has, Or, QueryBuilder, QueryState, With, Without},
+        relationship::{RelatedBy, Relation, RelationCommandsExt, Relations},
         removal_detection::RemovedComponents,
         result::{Error, Result},
         schedule::{
@@ -125,7 +131,7 @@ mod tests {
     use crate as bevy_ecs;
     use crate::{
         bundle::Bundle,
-        change_detection::Ref,
+        change_detection::{DetectChangesMut, Ref},
         component::{require, Component, ComponentId, RequiredComponents, RequiredComponentsError},
         entity::Entity,
         prelude::Or,
@@ -1084,6 +1090,68 @@ mod tests {
         );
     }
 
+    #[test]
+    fn changed_trackers_skip_tables() {
+        let mut world = World::default();
+        let e1 = world.spawn(A(0)).id();
+        let e2 = world.spawn((A(0), B(0))).id();
+        let e3 = world.spawn((A(0), B(0))).id();
+
+        fn table_changed(world: &World, entity: Entity) -> bool {
+            let table_id = world.entity(entity).location().table_id;
+            let component_id = world.component_id::<A>().unwrap();
+            world.storages().tables[table_id]
+                .get_column(component_id)
+                .unwrap()
+                .newest_changed_tick()
+                .get()
+                .is_newer_than(world.last_change_tick(), world.read_change_tick())
+        }
+
+        assert!(table_changed(&world, e1));
+        assert!(table_changed(&world, e2));
+
+        world.clear_trackers();
+        assert!(!table_changed(&world, e1));
+        assert!(!table_changed(&world, e2));
+
+        world.get_mut::<A>(e3).unwrap().0 += 1;
+        assert!(!table_changed(&world, e1));
+        assert!(table_changed(&world, e2));
+        let changed = world
+            .query_filtered::<Entity, Changed<A>>()
+            .iter(&world)
+            .collect::<Vec<_>>();
+        assert_eq!(changed, vec![e3]);
+
+        // Moving a changed component to another table marks that table as changed.
+        world.clear_trackers();
+        world.get_mut::<A>(e3).unwrap().0 += 1;
+        world.entity_mut(e3).remove::<B>();
+        assert!(table_changed(&world, e1));
+        let changed = world
+            .query_filtered::<Entity, (Changed<A>, Without<B>)>()
+            .iter(&world)
+            .collect::<Vec<_>>();
+        assert_eq!(changed, vec![e3]);
+
+        // Bypassing change detection doesn't mark the table as changed.
+        world.clear_trackers();
+        world
+            .query::<&mut A>()
+            .iter_mut(&mut world)
+            .for_each(|mut a| {
+                a.bypass_change_detection().0 += 1;
+            });
+        assert!(!table_changed(&world, e1));
+        assert!(!table_changed(&world, e2));
+        assert!(world
+            .query_filtered::<Entity, Or<(Changed<A>, Added<B>)>>()
+            .iter(&world)
+            .next()
+            .is_none());
+    }
+
     #[test]
     fn changed_trackers_sparse() {
         let mut world = World::default();
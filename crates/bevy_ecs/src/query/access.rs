@@ -982,6 +982,17 @@ impl<T: SparseSetIndex> FilteredAccess<T> {
         self.required.grow_and_insert(index.sparse_set_index());
     }
 
+    /// Returns `true` if this reads, writes, tests the presence of, or filters on the component
+    /// given by `index`.
+    pub fn contains(&self, index: T) -> bool {
+        let sparse_set_index = index.sparse_set_index();
+        self.access.has_component_read(index.clone())
+            || self.access.has_archetypal(index)
+            || self.filter_sets.iter().any(|filter| {
+                filter.with.contains(sparse_set_index) || filter.without.contains(sparse_set_index)
+            })
+    }
+
     /// Adds a `With` filter: corresponds to a conjunction (AND) operation.
     ///
     /// Suppose we begin with `Or<(With<A>, With<B>)>`, which is represented by an array of two `AccessFilter` instances.
@@ -103,6 +103,24 @@ pub unsafe trait QueryFilter: WorldQuery {
         entity: Entity,
         table_row: TableRow,
     ) -> bool;
+
+    /// Returns false if no entity of the current [`Table`] or [`Archetype`] can be included in
+    /// the query results, which lets the whole table or archetype be skipped without calling
+    /// [`QueryFilter::filter_fetch`] for each of its entities.
+    ///
+    /// Returning true doesn't mean that any entity will be included. The default implementation
+    /// always returns true.
+    ///
+    /// For example, [`Changed`] returns false for tables in which no component has been changed
+    /// since the last run of the system.
+    ///
+    /// # Safety
+    ///
+    /// Must always be called _after_ [`WorldQuery::set_table`] or [`WorldQuery::set_archetype`].
+    #[inline(always)]
+    unsafe fn filter_table(_fetch: &Self::Fetch<'_>) -> bool {
+        true
+    }
 }
 
 /// Filter that selects entities with a component `T`.
@@ -524,6 +542,13 @@ macro_rules! impl_or_query_filter {
                 // SAFETY: The invariants are uphold by the caller.
                 unsafe { Self::fetch(fetch, entity, table_row) }
             }
+
+            #[inline(always)]
+            unsafe fn filter_table(fetch: &Self::Fetch<'_>) -> bool {
+                let ($($filter,)*) = fetch;
+                // SAFETY: The invariants are uphold by the caller.
+                false $(|| ($filter.matches && unsafe { $filter::filter_table(&$filter.fetch) }))*
+            }
         }
     };
 }
@@ -557,6 +582,13 @@ macro_rules! impl_tuple_query_filter {
                 // SAFETY: The invariants are uphold by the caller.
                 true $(&& unsafe { $name::filter_fetch($name, entity, table_row) })*
             }
+
+            #[inline(always)]
+            unsafe fn filter_table(fetch: &Self::Fetch<'_>) -> bool {
+                let ($($name,)*) = fetch;
+                // SAFETY: The invariants are uphold by the caller.
+                true $(&& unsafe { $name::filter_table($name) })*
+            }
         }
 
     };
@@ -653,6 +685,8 @@ pub struct AddedFetch<'w, T: Component> {
         // T::STORAGE_TYPE = StorageType::SparseSet
         &'w ComponentSparseSet,
     >,
+    // Whether a component may have been added to the current table since `last_run`.
+    table_added: bool,
     last_run: Tick,
     this_run: Tick,
 }
@@ -661,6 +695,7 @@ impl<T: Component> Clone for AddedFetch<'_, T> {
     fn clone(&self) -> Self {
         Self {
             ticks: self.ticks,
+            table_added: self.table_added,
             last_run: self.last_run,
             this_run: self.this_run,
         }
@@ -703,6 +738,7 @@ unsafe impl<T: Component> WorldQuery for Added<T> {
                     unsafe { world.storages().sparse_sets.get(id).debug_checked_unwrap() }
                 },
             ),
+            table_added: true,
             last_run,
             this_run,
         }
@@ -742,6 +778,12 @@ unsafe impl<T: Component> WorldQuery for Added<T> {
                 .debug_checked_unwrap()
                 .into(),
         );
+        fetch.table_added = table
+            .get_column(component_id)
+            .debug_checked_unwrap()
+            .newest_added_tick()
+            .get()
+            .is_newer_than(fetch.last_run, fetch.this_run);
         // SAFETY: set_table is only called when T::STORAGE_TYPE = StorageType::Table
         unsafe { fetch.ticks.set_table(table_ticks) };
     }
@@ -808,6 +850,11 @@ unsafe impl<T: Component> QueryFilter for Added<T> {
         // SAFETY: The invariants are uphold by the caller.
         unsafe { Self::fetch(fetch, entity, table_row) }
     }
+
+    #[inline]
+    unsafe fn filter_table(fetch: &Self::Fetch<'_>) -> bool {
+        fetch.table_added
+    }
 }
 
 /// A filter on a component that only retains results the first time after they have been added or mutably dereferenced.
@@ -886,6 +933,8 @@ pub struct Changed<T>(PhantomData<T>);
 #[doc(hidden)]
 pub struct ChangedFetch<'w, T: Component> {
     ticks: StorageSwitch<T, Option<ThinSlicePtr<'w, UnsafeCell<Tick>>>, &'w ComponentSparseSet>,
+    // Whether a component of the current table may have changed since `last_run`.
+    table_changed: bool,
     last_run: Tick,
     this_run: Tick,
 }
@@ -894,6 +943,7 @@ impl<T: Component> Clone for ChangedFetch<'_, T> {
     fn clone(&self) -> Self {
         Self {
             ticks: self.ticks,
+            table_changed: self.table_changed,
             last_run: self.last_run,
             this_run: self.this_run,
         }
@@ -936,6 +986,7 @@ unsafe impl<T: Component> WorldQuery for Changed<T> {
                     unsafe { world.storages().sparse_sets.get(id).debug_checked_unwrap() }
                 },
             ),
+            table_changed: true,
             last_run,
             this_run,
         }
@@ -975,6 +1026,12 @@ unsafe impl<T: Component> WorldQuery for Changed<T> {
                 .debug_checked_unwrap()
                 .into(),
         );
+        fetch.table_changed = table
+            .get_column(component_id)
+            .debug_checked_unwrap()
+            .newest_changed_tick()
+            .get()
+            .is_newer_than(fetch.last_run, fetch.this_run);
         // SAFETY: set_table is only called when T::STORAGE_TYPE = StorageType::Table
         unsafe { fetch.ticks.set_table(table_ticks) };
     }
@@ -1042,6 +1099,11 @@ unsafe impl<T: Component> QueryFilter for Changed<T> {
         // SAFETY: The invariants are uphold by the caller.
         unsafe { Self::fetch(fetch, entity, table_row) }
     }
+
+    #[inline]
+    unsafe fn filter_table(fetch: &Self::Fetch<'_>) -> bool {
+        fetch.table_changed
+    }
 }
 
 /// A marker trait to indicate that the filter works at an archetype level.
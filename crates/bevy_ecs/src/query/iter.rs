@@ -218,6 +218,10 @@ impl<'w, 's, D: QueryData, F: QueryFilter> QueryIter<'w, 's, D, F> {
             &self.query_state.filter_state,
             table,
         );
+        // SAFETY: set_table was called prior.
+        if !unsafe { F::filter_table(&self.cursor.filter) } {
+            return accum;
+        }
 
         let entities = table.entities();
         for row in rows {
@@ -275,6 +279,10 @@ impl<'w, 's, D: QueryData, F: QueryFilter> QueryIter<'w, 's, D, F> {
             archetype,
             table,
         );
+        // SAFETY: set_archetype was called prior.
+        if !unsafe { F::filter_table(&self.cursor.filter) } {
+            return accum;
+        }
 
         let entities = archetype.entities();
         for index in indices {
@@ -353,6 +361,10 @@ impl<'w, 's, D: QueryData, F: QueryFilter> QueryIter<'w, 's, D, F> {
             archetype,
             table,
         );
+        // SAFETY: set_archetype was called prior.
+        if !unsafe { F::filter_table(&self.cursor.filter) } {
+            return accum;
+        }
         let entities = table.entities();
         for row in rows {
             // SAFETY: Caller assures `row` in range of the current archetype.
@@ -2843,6 +2855,10 @@ impl<'w, 's, D: QueryData, F: QueryFilter> QueryIterationCursor<'w, 's, D, F> {
                         D::set_table(&mut self.fetch, &query_state.fetch_state, table);
                         F::set_table(&mut self.filter, &query_state.filter_state, table);
                     }
+                    // SAFETY: set_table was called prior.
+                    if !unsafe { F::filter_table(&self.filter) } {
+                        continue;
+                    }
                     self.table_entities = table.entities();
                     self.current_len = table.entity_count();
                     self.current_row = 0;
@@ -2892,6 +2908,10 @@ impl<'w, 's, D: QueryData, F: QueryFilter> QueryIterationCursor<'w, 's, D, F> {
                             table,
                         );
                     }
+                    // SAFETY: set_archetype was called prior.
+                    if !unsafe { F::filter_table(&self.filter) } {
+                        continue;
+                    }
                     self.archetype_entities = archetype.entities();
                     self.current_len = archetype.len();
                     self.current_row = 0;
@@ -0,0 +1,470 @@
+//! Many-to-many relations between entities.
+//!
+//! A [`Relation`] is a marker type, such as `Likes` or `MemberOf`, that names a
+//! kind of directed link between entities. An entity stores the entities it is
+//! related to in a [`Relations`] component, and every related entity
+//! automatically gets a [`RelatedBy`] component that lists the entities that
+//! point to it, so that relations can be looked up in both directions with
+//! ordinary queries.
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! # use bevy_ecs::relationship::{Relation, RelationCommandsExt, Relations, RelatedBy};
+//! struct Likes;
+//!
+//! impl Relation for Likes {}
+//!
+//! let mut world = World::new();
+//! let bob = world.spawn_empty().id();
+//! let alice = world.spawn_empty().id();
+//! world.entity_mut(alice).relate::<Likes>(bob);
+//!
+//! assert_eq!(&**world.get::<Relations<Likes>>(alice).unwrap(), &[bob]);
+//! assert_eq!(&**world.get::<RelatedBy<Likes>>(bob).unwrap(), &[alice]);
+//!
+//! // Despawning an entity removes it from the relations of every other entity.
+//! world.despawn(bob);
+//! assert!(world.get::<Relations<Likes>>(alice).unwrap().is_empty());
+//! ```
+//!
+//! Both components are kept in sync through [component hooks](crate::component::ComponentHooks):
+//! inserting, replacing, or removing a [`Relations`] component updates the
+//! [`RelatedBy`] components of its targets, and despawning a target removes it
+//! from the [`Relations`] of every entity that pointed to it. Both components
+//! are [immutable](crate::component::Immutable), so that they can't get out of
+//! sync: use [`EntityWorldMut::relate`] and [`EntityWorldMut::unrelate`], or
+//! their [`RelationCommandsExt`] counterparts, to edit them, or insert a new
+//! [`Relations`] component to replace all the relations of an entity.
+
+use crate::{
+    change_detection::Mut,
+    component::{Component, ComponentHooks, ComponentId, Immutable, StorageType},
+    entity::Entity,
+    system::EntityCommands,
+    world::{DeferredWorld, EntityWorldMut, World},
+};
+use alloc::vec::Vec;
+use core::{fmt, marker::PhantomData, ops::Deref};
+
+/// A kind of directed, many-to-many link between entities.
+///
+/// Implement this trait for a marker type to be able to use it with
+/// [`Relations`] and [`RelatedBy`].
+pub trait Relation: Send + Sync + 'static {}
+
+/// The entities that this entity is related to through the relation `R`.
+///
+/// The targets are listed in the order in which they were added, and each
+/// target is listed only once. An entity whose relations have all been removed
+/// keeps an empty [`Relations`] component.
+pub struct Relations<R: Relation> {
+    targets: Vec<Entity>,
+    marker: PhantomData<fn() -> R>,
+}
+
+impl<R: Relation> Relations<R> {
+    /// Creates a set of relations to the given targets, ignoring duplicates.
+    pub fn new(targets: impl IntoIterator<Item = Entity>) -> Self {
+        let mut relations = Self {
+            targets: Vec::new(),
+            marker: PhantomData,
+        };
+        for target in targets {
+            relations.add(target);
+        }
+        relations
+    }
+
+    /// Returns `true` if this entity is related to `target`.
+    pub fn contains(&self, target: Entity) -> bool {
+        self.targets.contains(&target)
+    }
+
+    /// Adds `target`, returning `false` if it was already present.
+    fn add(&mut self, target: Entity) -> bool {
+        if self.contains(target) {
+            return false;
+        }
+        self.targets.push(target);
+        true
+    }
+
+    /// Removes `target`, returning `false` if it wasn't present.
+    fn remove(&mut self, target: Entity) -> bool {
+        let len = self.targets.len();
+        self.targets.retain(|&entity| entity != target);
+        self.targets.len() != len
+    }
+
+    fn on_insert(mut world: DeferredWorld, source: Entity, _: ComponentId) {
+        let targets = world.get::<Self>(source).unwrap().targets.clone();
+        for target in targets {
+            if let Some(mut related_by) = index_mut::<RelatedBy<R>>(&mut world, target) {
+                related_by.add(source);
+            } else {
+                // The target needs a new component, which requires a structural
+                // change.
+                world
+                    .commands()
+                    .queue(move |world: &mut World| add_source::<R>(world, target, source));
+            }
+        }
+    }
+
+    fn on_replace(mut world: DeferredWorld, source: Entity, _: ComponentId) {
+        let targets = world.get::<Self>(source).unwrap().targets.clone();
+        for target in targets {
+            if let Some(mut related_by) = index_mut::<RelatedBy<R>>(&mut world, target) {
+                related_by.remove(source);
+            }
+        }
+    }
+}
+
+impl<R: Relation> Component for Relations<R> {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+    type Mutability = Immutable;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks
+            .on_insert(Self::on_insert)
+            .on_replace(Self::on_replace);
+    }
+}
+
+impl<R: Relation> Deref for Relations<R> {
+    type Target = [Entity];
+
+    fn deref(&self) -> &Self::Target {
+        &self.targets
+    }
+}
+
+impl<'a, R: Relation> IntoIterator for &'a Relations<R> {
+    type Item = &'a Entity;
+    type IntoIter = core::slice::Iter<'a, Entity>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.targets.iter()
+    }
+}
+
+impl<R: Relation> Clone for Relations<R> {
+    fn clone(&self) -> Self {
+        Self {
+            targets: self.targets.clone(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<R: Relation> fmt::Debug for Relations<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Relations").field(&self.targets).finish()
+    }
+}
+
+/// The entities that are related to this entity through the relation `R`.
+///
+/// This component is the reverse index of [`Relations`], and is maintained
+/// automatically: it's added to an entity the first time another entity
+/// relates to it, and it's kept, possibly empty, afterwards.
+pub struct RelatedBy<R: Relation> {
+    sources: Vec<Entity>,
+    marker: PhantomData<fn() -> R>,
+}
+
+impl<R: Relation> RelatedBy<R> {
+    /// Returns `true` if `source` is related to this entity.
+    pub fn contains(&self, source: Entity) -> bool {
+        self.sources.contains(&source)
+    }
+
+    fn add(&mut self, source: Entity) {
+        if !self.contains(source) {
+            self.sources.push(source);
+        }
+    }
+
+    fn remove(&mut self, source: Entity) {
+        self.sources.retain(|&entity| entity != source);
+    }
+
+    fn on_replace(mut world: DeferredWorld, target: Entity, _: ComponentId) {
+        let sources = world.get::<Self>(target).unwrap().sources.clone();
+        for source in sources {
+            if let Some(mut relations) = index_mut::<Relations<R>>(&mut world, source) {
+                relations.remove(target);
+            }
+        }
+    }
+}
+
+impl<R: Relation> Component for RelatedBy<R> {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+    type Mutability = Immutable;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks.on_replace(Self::on_replace);
+    }
+}
+
+impl<R: Relation> Deref for RelatedBy<R> {
+    type Target = [Entity];
+
+    fn deref(&self) -> &Self::Target {
+        &self.sources
+    }
+}
+
+impl<'a, R: Relation> IntoIterator for &'a RelatedBy<R> {
+    type Item = &'a Entity;
+    type IntoIter = core::slice::Iter<'a, Entity>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.sources.iter()
+    }
+}
+
+impl<R: Relation> fmt::Debug for RelatedBy<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RelatedBy").field(&self.sources).finish()
+    }
+}
+
+/// Returns mutable access to the [`Relations`] or [`RelatedBy`] component `T`
+/// of `entity`, without triggering its hooks.
+///
+/// Only the hooks use it, to keep both components in sync.
+fn index_mut<'w, T: Component>(world: &'w mut DeferredWorld, entity: Entity) -> Option<Mut<'w, T>> {
+    let entity = world.get_entity_mut(entity).ok()?;
+    // SAFETY: the components are immutable so that they can only be changed
+    // here, in sync with each other.
+    unsafe { entity.into_mut_assume_mutable::<T>() }
+}
+
+/// Records that `source` is related to `target` in the [`RelatedBy`] of
+/// `target`, if the relation still exists.
+fn add_source<R: Relation>(world: &mut World, target: Entity, source: Entity) {
+    if !world
+        .get::<Relations<R>>(source)
+        .is_some_and(|relations| relations.contains(target))
+    {
+        return;
+    }
+    let Ok(mut target) = world.get_entity_mut(target) else {
+        return;
+    };
+    // SAFETY: the reverse index is kept in sync with the relations.
+    if let Some(mut related_by) = unsafe { target.get_mut_assume_mutable::<RelatedBy<R>>() } {
+        related_by.add(source);
+    } else {
+        target.insert(RelatedBy::<R> {
+            sources: alloc::vec![source],
+            marker: PhantomData,
+        });
+    }
+}
+
+impl<'w> EntityWorldMut<'w> {
+    /// Relates this entity to `target` through the relation `R`.
+    ///
+    /// Does nothing if the entities are already related.
+    ///
+    /// # Panics
+    ///
+    /// If the entity has been despawned while this `EntityWorldMut` is still alive.
+    pub fn relate<R: Relation>(&mut self, target: Entity) -> &mut Self {
+        let source = self.id();
+        // SAFETY: the reverse index of the target is updated right after.
+        match unsafe { self.get_mut_assume_mutable::<Relations<R>>() }
+            .map(|mut relations| relations.add(target))
+        {
+            Some(true) => self.world_scope(|world| add_source::<R>(world, target, source)),
+            Some(false) => {}
+            None => {
+                self.insert(Relations::<R>::new([target]));
+            }
+        }
+        self
+    }
+
+    /// Removes the relation `R` from this entity to `target`.
+    ///
+    /// Does nothing if the entities aren't related.
+    ///
+    /// # Panics
+    ///
+    /// If the entity has been despawned while this `EntityWorldMut` is still alive.
+    pub fn unrelate<R: Relation>(&mut self, target: Entity) -> &mut Self {
+        let source = self.id();
+        // SAFETY: the reverse index of the target is updated right after.
+        let removed = unsafe { self.get_mut_assume_mutable::<Relations<R>>() }
+            .is_some_and(|mut relations| relations.remove(target));
+        if removed {
+            self.world_scope(|world| {
+                let Ok(mut target) = world.get_entity_mut(target) else {
+                    return;
+                };
+                // SAFETY: the source was removed from the relations above.
+                if let Some(mut related_by) =
+                    unsafe { target.get_mut_assume_mutable::<RelatedBy<R>>() }
+                {
+                    related_by.remove(source);
+                }
+            });
+        }
+        self
+    }
+
+    /// Removes all the relations `R` from this entity to other entities.
+    ///
+    /// # Panics
+    ///
+    /// If the entity has been despawned while this `EntityWorldMut` is still alive.
+    pub fn unrelate_all<R: Relation>(&mut self) -> &mut Self {
+        self.remove::<Relations<R>>()
+    }
+}
+
+/// An extension trait for [`EntityCommands`] to manage [`Relations`].
+pub trait RelationCommandsExt {
+    /// Relates this entity to `target` through the relation `R`.
+    ///
+    /// See [`EntityWorldMut::relate`].
+    fn relate<R: Relation>(&mut self, target: Entity) -> &mut Self;
+
+    /// Removes the relation `R` from this entity to `target`.
+    ///
+    /// See [`EntityWorldMut::unrelate`].
+    fn unrelate<R: Relation>(&mut self, target: Entity) -> &mut Self;
+
+    /// Removes all the relations `R` from this entity to other entities.
+    ///
+    /// See [`EntityWorldMut::unrelate_all`].
+    fn unrelate_all<R: Relation>(&mut self) -> &mut Self;
+}
+
+impl RelationCommandsExt for EntityCommands<'_> {
+    fn relate<R: Relation>(&mut self, target: Entity) -> &mut Self {
+        self.queue(move |mut entity: EntityWorldMut| {
+            entity.relate::<R>(target);
+        })
+    }
+
+    fn unrelate<R: Relation>(&mut self, target: Entity) -> &mut Self {
+        self.queue(move |mut entity: EntityWorldMut| {
+            entity.unrelate::<R>(target);
+        })
+    }
+
+    fn unrelate_all<R: Relation>(&mut self) -> &mut Self {
+        self.queue(move |mut entity: EntityWorldMut| {
+            entity.unrelate_all::<R>();
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RelatedBy, Relation, RelationCommandsExt, Relations};
+    use crate::{entity::Entity, query::With, world::World};
+    use alloc::vec::Vec;
+
+    struct Likes;
+
+    impl Relation for Likes {}
+
+    struct Owns;
+
+    impl Relation for Owns {}
+
+    fn related_by(world: &World, entity: Entity) -> Vec<Entity> {
+        world
+            .get::<RelatedBy<Likes>>(entity)
+            .map(|related_by| related_by.to_vec())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn relate_and_unrelate() {
+        let mut world = World::new();
+        let [a, b, c] = core::array::from_fn(|_| world.spawn_empty().id());
+
+        world.entity_mut(a).relate::<Likes>(b).relate::<Likes>(c);
+        world.entity_mut(b).relate::<Likes>(c).relate::<Likes>(c);
+
+        assert_eq!(&**world.get::<Relations<Likes>>(a).unwrap(), &[b, c]);
+        assert_eq!(&**world.get::<Relations<Likes>>(b).unwrap(), &[c]);
+        assert_eq!(related_by(&world, b), [a]);
+        assert_eq!(related_by(&world, c), [a, b]);
+        assert!(world.get::<RelatedBy<Owns>>(c).is_none());
+
+        world.entity_mut(a).unrelate::<Likes>(c);
+        assert_eq!(&**world.get::<Relations<Likes>>(a).unwrap(), &[b]);
+        assert_eq!(related_by(&world, c), [b]);
+
+        world.entity_mut(a).unrelate_all::<Likes>();
+        assert!(world.get::<Relations<Likes>>(a).is_none());
+        assert!(related_by(&world, b).is_empty());
+    }
+
+    #[test]
+    fn insert_and_replace_relations() {
+        let mut world = World::new();
+        let [a, b, c] = core::array::from_fn(|_| world.spawn_empty().id());
+
+        world
+            .entity_mut(a)
+            .insert(Relations::<Likes>::new([b, c, b]));
+        assert_eq!(&**world.get::<Relations<Likes>>(a).unwrap(), &[b, c]);
+        assert_eq!(related_by(&world, b), [a]);
+        assert_eq!(related_by(&world, c), [a]);
+
+        world.entity_mut(a).insert(Relations::<Likes>::new([c]));
+        assert!(related_by(&world, b).is_empty());
+        assert_eq!(related_by(&world, c), [a]);
+    }
+
+    #[test]
+    fn despawn_cleans_up_relations() {
+        let mut world = World::new();
+        let [a, b, c] = core::array::from_fn(|_| world.spawn_empty().id());
+        world.entity_mut(a).relate::<Likes>(b).relate::<Likes>(c);
+        world.entity_mut(c).relate::<Likes>(b);
+
+        world.despawn(b);
+        assert_eq!(&**world.get::<Relations<Likes>>(a).unwrap(), &[c]);
+        assert!(world.get::<Relations<Likes>>(c).unwrap().is_empty());
+
+        world.despawn(a);
+        assert!(related_by(&world, c).is_empty());
+    }
+
+    #[test]
+    fn relation_commands_and_queries() {
+        let mut world = World::new();
+        let [a, b, c] = core::array::from_fn(|_| world.spawn_empty().id());
+
+        let mut commands = world.commands();
+        commands.entity(a).relate::<Likes>(c);
+        commands.entity(b).relate::<Likes>(c).relate::<Owns>(a);
+        world.flush();
+
+        let mut likers = world
+            .query_filtered::<Entity, With<Relations<Likes>>>()
+            .iter(&world)
+            .collect::<Vec<_>>();
+        likers.sort();
+        assert_eq!(likers, [a, b]);
+
+        let mut query = world.query::<(Entity, &RelatedBy<Owns>)>();
+        let (owned, owners) = query.single(&world);
+        assert_eq!(owned, a);
+        assert_eq!(&**owners, &[b]);
+
+        world.commands().entity(b).unrelate::<Likes>(c);
+        world.flush();
+        assert_eq!(related_by(&world, c), [a]);
+    }
+}
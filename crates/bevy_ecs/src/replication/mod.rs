@@ -0,0 +1,663 @@
+//! Low-level primitives for replicating entities from one [`World`] to another, for example from
+//! a server to its clients.
+//!
+//! Mark the entities to replicate with [`Replicated`], and register the components to replicate
+//! with [`World::register_replicated_component`] in both worlds. The server then collects the
+//! components that changed, were removed or were despawned since a [`Tick`] into a [`WorldDiff`]
+//! with [`WorldDiff::collect`], or the whole replicated state with [`WorldDiff::snapshot`], and
+//! the client applies it to its own world with [`WorldDiff::apply`], which only writes the
+//! components that the client replicates too.
+//!
+//! The components are read and written through reflection, so they must be registered in the
+//! [`AppTypeRegistry`] with `#[reflect(Component)]`. With the `serialize` feature,
+//! [`WorldDiffSerializer`] and [`WorldDiffDeserializer`] turn diffs into bytes and back with any
+//! `serde` format.
+//!
+//! This module doesn't send anything: transports decide how and when diffs are sent. Each
+//! [`WorldDiff::collect`] consumes the removals recorded since the previous one, so transports
+//! collect one diff per update and send it to every client that received the previous one, and
+//! send a [`WorldDiff::snapshot`] to new clients.
+//!
+//! ```
+//! # use bevy_ecs::{prelude::*, entity::EntityHashMap, replication::{Replicated, WorldDiff}};
+//! # use bevy_reflect::Reflect;
+//! #[derive(Component, Reflect)]
+//! #[reflect(Component)]
+//! struct Health(u32);
+//!
+//! let mut server = World::new();
+//! server.init_resource::<AppTypeRegistry>();
+//! server.resource::<AppTypeRegistry>().write().register::<Health>();
+//! server.register_replicated_component::<Health>();
+//! let player = server.spawn((Replicated, Health(100))).id();
+//!
+//! let mut client = World::new();
+//! client.insert_resource(server.resource::<AppTypeRegistry>().clone());
+//! client.register_replicated_component::<Health>();
+//! let mut entity_map = EntityHashMap::default();
+//!
+//! // A new client receives the whole replicated state first...
+//! let snapshot = WorldDiff::snapshot(&mut server).unwrap();
+//! snapshot.apply(&mut client, &mut entity_map).unwrap();
+//!
+//! // ...and then only what changed since the last diff it received.
+//! server.get_mut::<Health>(player).unwrap().0 = 90;
+//! let diff = WorldDiff::collect(&mut server, snapshot.tick).unwrap();
+//! diff.apply(&mut client, &mut entity_map).unwrap();
+//!
+//! assert_eq!(client.get::<Health>(entity_map[&player]).unwrap().0, 90);
+//! ```
+
+#[cfg(feature = "serialize")]
+mod serde;
+
+#[cfg(feature = "serialize")]
+pub use self::serde::{WorldDiffDeserializer, WorldDiffSerializer};
+
+use crate as bevy_ecs;
+use crate::{
+    component::{Component, ComponentId, Tick},
+    entity::{Entity, EntityHashMap, EntityHashSet, SceneEntityMapper},
+    observer::Trigger,
+    query::With,
+    reflect::{AppTypeRegistry, ReflectComponent, ReflectMapEntities},
+    system::{Query, ResMut, Resource, SystemChangeTick},
+    world::{EntityRef, OnRemove, World},
+};
+use alloc::{borrow::ToOwned, boxed::Box, string::String, vec::Vec};
+use bevy_reflect::{std_traits::ReflectDefault, PartialReflect, Reflect, TypePath, TypeRegistry};
+use core::any::TypeId;
+use thiserror::Error;
+
+/// Marks an entity to be replicated, with the components registered with
+/// [`World::register_replicated_component`].
+///
+/// Removing this component from an entity replicates it as if it was despawned.
+#[derive(Component, Reflect, Clone, Copy, Default, Debug)]
+#[reflect(Component, Default, Debug)]
+pub struct Replicated;
+
+/// The components that are replicated, along with the removals recorded since the last
+/// [`WorldDiff::collect`].
+///
+/// Components are added with [`World::register_replicated_component`].
+#[derive(Resource, Default)]
+pub struct ReplicationRegistry {
+    components: Vec<ReplicatedComponent>,
+    removals: Vec<Removal>,
+}
+
+#[derive(Clone, Copy)]
+struct ReplicatedComponent {
+    id: ComponentId,
+    type_id: TypeId,
+    type_path: &'static str,
+}
+
+struct Removal {
+    tick: Tick,
+    entity: Entity,
+    /// The type of the removed component, or [`None`] if the entity stopped being replicated.
+    component: Option<TypeId>,
+}
+
+impl ReplicationRegistry {
+    /// Returns `true` if the components of the given type are replicated.
+    pub fn contains(&self, type_id: TypeId) -> bool {
+        self.components
+            .iter()
+            .any(|component| component.type_id == type_id)
+    }
+
+    /// Returns the type paths of the replicated components, in the order they were registered in.
+    pub fn type_paths(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.components.iter().map(|component| component.type_path)
+    }
+
+    fn get(&self, type_id: TypeId) -> Option<&ReplicatedComponent> {
+        self.components
+            .iter()
+            .find(|component| component.type_id == type_id)
+    }
+}
+
+impl World {
+    /// Replicates the components of type `C` of the entities marked with [`Replicated`].
+    ///
+    /// This must be called in both the world the diffs are collected from and the worlds they're
+    /// applied to, as [`WorldDiff::apply`] rejects the components that aren't replicated. `C` must
+    /// also be registered in the [`AppTypeRegistry`] with `#[reflect(Component)]` on both sides of
+    /// the replication. Registering a component more than once does nothing.
+    ///
+    /// See the [`replication`](crate::replication) module for more information.
+    pub fn register_replicated_component<C: Component + Reflect + TypePath>(
+        &mut self,
+    ) -> ComponentId {
+        let id = self.register_component::<C>();
+        if self.get_resource::<ReplicationRegistry>().is_none() {
+            self.init_resource::<ReplicationRegistry>();
+            self.add_observer(record_unreplicated_entity);
+        }
+
+        let mut registry = self.resource_mut::<ReplicationRegistry>();
+        if registry.contains(TypeId::of::<C>()) {
+            return id;
+        }
+        registry.components.push(ReplicatedComponent {
+            id,
+            type_id: TypeId::of::<C>(),
+            type_path: C::type_path(),
+        });
+        drop(registry);
+        self.add_observer(record_removed_component::<C>);
+        id
+    }
+}
+
+fn record_removed_component<C: Component>(
+    trigger: Trigger<OnRemove, C>,
+    ticks: SystemChangeTick,
+    replicated: Query<(), With<Replicated>>,
+    mut registry: ResMut<ReplicationRegistry>,
+) {
+    if replicated.contains(trigger.target()) {
+        registry.removals.push(Removal {
+            tick: ticks.this_run(),
+            entity: trigger.target(),
+            component: Some(TypeId::of::<C>()),
+        });
+    }
+}
+
+fn record_unreplicated_entity(
+    trigger: Trigger<OnRemove, Replicated>,
+    ticks: SystemChangeTick,
+    mut registry: ResMut<ReplicationRegistry>,
+) {
+    registry.removals.push(Removal {
+        tick: ticks.this_run(),
+        entity: trigger.target(),
+        component: None,
+    });
+}
+
+/// The changes made to the replicated components of one entity.
+#[derive(Debug)]
+pub struct EntityDiff {
+    /// The entity, in the world the diff was collected from.
+    pub entity: Entity,
+    /// The values of the components that were inserted or changed.
+    pub components: Vec<Box<dyn PartialReflect>>,
+    /// The type paths of the components that were removed.
+    pub removed: Vec<String>,
+}
+
+/// The changes made to the replicated entities of a [`World`] since a [`Tick`].
+///
+/// See the [`replication`](crate::replication) module for more information.
+#[derive(Debug, Default)]
+pub struct WorldDiff {
+    /// The tick the diff was collected at, which the next diff for the same receiver should be
+    /// collected since.
+    pub tick: Tick,
+    /// The entities with replicated components that were inserted, changed or removed.
+    pub entities: Vec<EntityDiff>,
+    /// The entities that were despawned, or stopped being [`Replicated`].
+    pub despawned: Vec<Entity>,
+}
+
+impl WorldDiff {
+    /// Collects the replicated components that were inserted, changed or removed, and the
+    /// replicated entities that were despawned, since `since`.
+    ///
+    /// All the replicated components of the entities that started being [`Replicated`] since
+    /// `since` are collected, even if they didn't change.
+    ///
+    /// The removals and despawns recorded so far are consumed, so a later diff doesn't contain
+    /// them anymore, even if it's collected since an older tick. Send each diff to every receiver
+    /// that received the previous one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the world doesn't have an [`AppTypeRegistry`].
+    pub fn collect(world: &mut World, since: Tick) -> Result<Self, ReplicationError> {
+        let diff = Self::collect_since(world, Some(since))?;
+        if let Some(mut registry) = world.get_resource_mut::<ReplicationRegistry>() {
+            registry.removals.clear();
+        }
+        Ok(diff)
+    }
+
+    /// Collects all the replicated components of all the replicated entities, to send to a
+    /// receiver that doesn't have any of them yet.
+    ///
+    /// Unlike [`WorldDiff::collect`], this doesn't consume the recorded removals.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the world doesn't have an [`AppTypeRegistry`].
+    pub fn snapshot(world: &mut World) -> Result<Self, ReplicationError> {
+        Self::collect_since(world, None)
+    }
+
+    fn collect_since(world: &mut World, since: Option<Tick>) -> Result<Self, ReplicationError> {
+        // Like a system, collect the changes up to a tick that later changes will be newer than.
+        let this_run = world.increment_change_tick();
+        let mut diff = WorldDiff {
+            tick: this_run,
+            ..Default::default()
+        };
+        let mut entities = world.query_filtered::<EntityRef, With<Replicated>>();
+        let world = &*world;
+        let Some(registry) = world.get_resource::<ReplicationRegistry>() else {
+            return Ok(diff);
+        };
+        let type_registry = world.resource::<AppTypeRegistry>().read();
+        let components = registry
+            .components
+            .iter()
+            .map(|component| {
+                reflect_component(&type_registry, component.type_id, component.type_path)
+                    .map(|reflect_component| (component.id, reflect_component))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut indices = EntityHashMap::<usize>::default();
+        for entity in entities.iter(world) {
+            let newly_replicated = since.is_none_or(|since| {
+                entity
+                    .get_change_ticks::<Replicated>()
+                    .is_some_and(|ticks| ticks.is_added(since, this_run))
+            });
+            let changed = components
+                .iter()
+                .filter(|(id, _)| {
+                    entity.get_change_ticks_by_id(*id).is_some_and(|ticks| {
+                        newly_replicated
+                            || since.is_none_or(|since| ticks.is_changed(since, this_run))
+                    })
+                })
+                .filter_map(|(_, reflect_component)| reflect_component.reflect(entity))
+                .map(|component| component.clone_value())
+                .collect::<Vec<_>>();
+            if !changed.is_empty() {
+                indices.insert(entity.id(), diff.entities.len());
+                diff.entities.push(EntityDiff {
+                    entity: entity.id(),
+                    components: changed,
+                    removed: Vec::new(),
+                });
+            }
+        }
+
+        let Some(since) = since else {
+            return Ok(diff);
+        };
+        let mut despawned = EntityHashSet::default();
+        for removal in &registry.removals {
+            if !removal.tick.is_newer_than(since, this_run) {
+                continue;
+            }
+            let Ok(entity) = world.get_entity(removal.entity) else {
+                despawned.insert(removal.entity);
+                continue;
+            };
+            if !entity.contains::<Replicated>() {
+                despawned.insert(removal.entity);
+                continue;
+            }
+            let Some(component) = removal.component.and_then(|type_id| registry.get(type_id))
+            else {
+                continue;
+            };
+            // The component was inserted again after it was removed, so it's already collected.
+            if entity.contains_id(component.id) {
+                continue;
+            }
+            let index = *indices.entry(removal.entity).or_insert_with(|| {
+                diff.entities.push(EntityDiff {
+                    entity: removal.entity,
+                    components: Vec::new(),
+                    removed: Vec::new(),
+                });
+                diff.entities.len() - 1
+            });
+            let removed = &mut diff.entities[index].removed;
+            if !removed
+                .iter()
+                .any(|type_path| type_path == component.type_path)
+            {
+                removed.push(component.type_path.to_owned());
+            }
+        }
+        // Despawned entities are sent in the order they were despawned in, once.
+        diff.despawned = registry
+            .removals
+            .iter()
+            .map(|removal| removal.entity)
+            .filter(|entity| despawned.remove(entity))
+            .collect();
+
+        Ok(diff)
+    }
+
+    /// Returns `true` if nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty() && self.despawned.is_empty()
+    }
+
+    /// Applies the diff to `world`.
+    ///
+    /// `entity_map` maps the entities of the world the diff was collected from to the entities of
+    /// `world`, and must be kept between calls. The entities that aren't in it yet are spawned,
+    /// and the entities referenced by the components are mapped with [`ReflectMapEntities`].
+    ///
+    /// Nothing is applied if the diff contains a component that `world` doesn't
+    /// [replicate](World::register_replicated_component), so that a sender can't insert arbitrary
+    /// components.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the world doesn't have an [`AppTypeRegistry`].
+    pub fn apply(
+        &self,
+        world: &mut World,
+        entity_map: &mut EntityHashMap<Entity>,
+    ) -> Result<(), ReplicationError> {
+        let type_registry = world.resource::<AppTypeRegistry>().clone();
+        let type_registry = type_registry.read();
+
+        // Check every component first, so that a rejected diff isn't applied partially.
+        let replication_registry = world.get_resource::<ReplicationRegistry>();
+        let is_replicated =
+            |type_id| replication_registry.is_some_and(|registry| registry.contains(type_id));
+        for entity_diff in &self.entities {
+            for type_path in &entity_diff.removed {
+                let replicated = type_registry
+                    .get_with_type_path(type_path)
+                    .is_some_and(|registration| is_replicated(registration.type_id()));
+                if !replicated {
+                    return Err(ReplicationError::NotReplicated {
+                        type_path: type_path.clone(),
+                    });
+                }
+            }
+            for component in &entity_diff.components {
+                let type_info = component.get_represented_type_info().ok_or_else(|| {
+                    ReplicationError::NoRepresentedType {
+                        type_path: component.reflect_type_path().to_owned(),
+                    }
+                })?;
+                if !is_replicated(type_info.type_id()) {
+                    return Err(ReplicationError::NotReplicated {
+                        type_path: type_info.type_path().to_owned(),
+                    });
+                }
+            }
+        }
+
+        for entity in &self.despawned {
+            if let Some(entity) = entity_map.remove(entity) {
+                world.despawn(entity);
+            }
+        }
+
+        // Map every entity first, so that the components can reference any of them.
+        for entity_diff in &self.entities {
+            let exists = entity_map
+                .get(&entity_diff.entity)
+                .is_some_and(|entity| world.get_entity(*entity).is_ok());
+            if !exists {
+                entity_map.insert(entity_diff.entity, world.spawn_empty().id());
+            }
+        }
+
+        for entity_diff in &self.entities {
+            let entity = entity_map[&entity_diff.entity];
+
+            for type_path in &entity_diff.removed {
+                let registration =
+                    type_registry.get_with_type_path(type_path).ok_or_else(|| {
+                        ReplicationError::UnregisteredComponent {
+                            type_path: type_path.clone(),
+                        }
+                    })?;
+                reflect_component(&type_registry, registration.type_id(), type_path)?
+                    .remove(&mut world.entity_mut(entity));
+            }
+
+            for component in &entity_diff.components {
+                let mut component = component.clone_value();
+                let type_info = component.get_represented_type_info().ok_or_else(|| {
+                    ReplicationError::NoRepresentedType {
+                        type_path: component.reflect_type_path().to_owned(),
+                    }
+                })?;
+                let reflect_component =
+                    reflect_component(&type_registry, type_info.type_id(), type_info.type_path())?;
+
+                if let Some(map_entities) = type_registry
+                    .get(type_info.type_id())
+                    .and_then(|registration| registration.data::<ReflectMapEntities>())
+                {
+                    SceneEntityMapper::world_scope(entity_map, world, |_, mapper| {
+                        map_entities.map_entities(component.as_partial_reflect_mut(), mapper);
+                    });
+                }
+
+                reflect_component.apply_or_insert(
+                    &mut world.entity_mut(entity),
+                    component.as_partial_reflect(),
+                    &type_registry,
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn reflect_component<'a>(
+    type_registry: &'a TypeRegistry,
+    type_id: TypeId,
+    type_path: &str,
+) -> Result<&'a ReflectComponent, ReplicationError> {
+    type_registry
+        .get_type_data::<ReflectComponent>(type_id)
+        .ok_or_else(|| ReplicationError::UnregisteredComponent {
+            type_path: type_path.to_owned(),
+        })
+}
+
+/// An error returned while collecting or applying a [`WorldDiff`].
+#[derive(Error, Debug)]
+pub enum ReplicationError {
+    /// A replicated component isn't registered in the [`AppTypeRegistry`] with
+    /// `#[reflect(Component)]`.
+    #[error("the replicated component `{type_path}` isn't registered in the type registry with `#[reflect(Component)]`")]
+    UnregisteredComponent {
+        /// The type path of the component.
+        type_path: String,
+    },
+    /// A component of a diff isn't replicated by the world it's applied to.
+    #[error("the component `{type_path}` isn't replicated by the world the diff is applied to")]
+    NotReplicated {
+        /// The type path of the component.
+        type_path: String,
+    },
+    /// A dynamic component doesn't represent a concrete type.
+    #[error("the dynamic component `{type_path}` doesn't represent a concrete type")]
+    NoRepresentedType {
+        /// The type path of the dynamic component.
+        type_path: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Replicated, ReplicationError, ReplicationRegistry, WorldDiff};
+    use crate as bevy_ecs;
+    use crate::{
+        component::Component,
+        entity::{Entity, EntityHashMap, VisitEntities, VisitEntitiesMut},
+        reflect::{AppTypeRegistry, ReflectComponent, ReflectMapEntities},
+        world::World,
+    };
+    use bevy_reflect::{Reflect, TypePath};
+
+    #[derive(Component, Reflect, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct A(u32);
+
+    #[derive(Component, Reflect, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct B;
+
+    #[derive(Component, Reflect, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct Unreplicated;
+
+    #[derive(Component, Reflect, VisitEntities, VisitEntitiesMut)]
+    #[reflect(Component, MapEntities)]
+    struct Target(Entity);
+
+    fn worlds() -> (World, World, EntityHashMap<Entity>) {
+        let registry = AppTypeRegistry::default();
+        {
+            let mut registry = registry.write();
+            registry.register::<A>();
+            registry.register::<B>();
+            registry.register::<Unreplicated>();
+            registry.register::<Target>();
+        }
+        let mut server = World::new();
+        server.insert_resource(registry.clone());
+        server.register_replicated_component::<A>();
+        server.register_replicated_component::<B>();
+        server.register_replicated_component::<Target>();
+        let mut client = World::new();
+        client.insert_resource(registry);
+        client.register_replicated_component::<A>();
+        client.register_replicated_component::<B>();
+        client.register_replicated_component::<Target>();
+        (server, client, EntityHashMap::default())
+    }
+
+    #[test]
+    fn replicate_changes() {
+        let (mut server, mut client, mut entity_map) = worlds();
+        let entity = server.spawn((Replicated, A(1), B, Unreplicated)).id();
+        server.spawn((A(2), B));
+
+        let diff = WorldDiff::snapshot(&mut server).unwrap();
+        assert_eq!(diff.entities.len(), 1);
+        assert_eq!(diff.entities[0].components.len(), 2);
+        diff.apply(&mut client, &mut entity_map).unwrap();
+        let client_entity = entity_map[&entity];
+        assert_eq!(client.get::<A>(client_entity), Some(&A(1)));
+        assert_eq!(client.get::<B>(client_entity), Some(&B));
+        assert_eq!(client.get::<Unreplicated>(client_entity), None);
+
+        // Nothing changed.
+        let mut tick = diff.tick;
+        let diff = WorldDiff::collect(&mut server, tick).unwrap();
+        assert!(diff.is_empty());
+        tick = diff.tick;
+
+        server.get_mut::<A>(entity).unwrap().0 = 3;
+        server.entity_mut(entity).remove::<B>();
+        let diff = WorldDiff::collect(&mut server, tick).unwrap();
+        assert_eq!(diff.entities.len(), 1);
+        assert_eq!(diff.entities[0].components.len(), 1);
+        assert_eq!(diff.entities[0].removed.len(), 1);
+        diff.apply(&mut client, &mut entity_map).unwrap();
+        assert_eq!(client.get::<A>(client_entity), Some(&A(3)));
+        assert_eq!(client.get::<B>(client_entity), None);
+        tick = diff.tick;
+
+        server.despawn(entity);
+        let diff = WorldDiff::collect(&mut server, tick).unwrap();
+        assert!(diff.entities.is_empty());
+        assert_eq!(diff.despawned, [entity]);
+        diff.apply(&mut client, &mut entity_map).unwrap();
+        assert!(client.get_entity(client_entity).is_err());
+        assert!(entity_map.is_empty());
+    }
+
+    #[test]
+    fn replicate_newly_replicated_entities() {
+        let (mut server, mut client, mut entity_map) = worlds();
+        let entity = server.spawn(A(1)).id();
+        let tick = WorldDiff::snapshot(&mut server).unwrap().tick;
+
+        server.entity_mut(entity).insert(Replicated);
+        let diff = WorldDiff::collect(&mut server, tick).unwrap();
+        diff.apply(&mut client, &mut entity_map).unwrap();
+        assert_eq!(client.get::<A>(entity_map[&entity]), Some(&A(1)));
+
+        server.entity_mut(entity).remove::<Replicated>();
+        let diff = WorldDiff::collect(&mut server, diff.tick).unwrap();
+        assert_eq!(diff.despawned, [entity]);
+    }
+
+    #[test]
+    fn map_replicated_entities() {
+        let (mut server, mut client, mut entity_map) = worlds();
+        let target = server.spawn((Replicated, A(1))).id();
+        let entity = server.spawn((Replicated, Target(target))).id();
+
+        WorldDiff::snapshot(&mut server)
+            .unwrap()
+            .apply(&mut client, &mut entity_map)
+            .unwrap();
+        assert_eq!(
+            client.get::<Target>(entity_map[&entity]).unwrap().0,
+            entity_map[&target]
+        );
+    }
+
+    #[test]
+    fn removals_are_consumed_by_diffs() {
+        let (mut server, _, _) = worlds();
+        let entity = server.spawn((Replicated, A(1), B)).id();
+        let tick = WorldDiff::snapshot(&mut server).unwrap().tick;
+        server.entity_mut(entity).remove::<B>();
+        let despawned = server.spawn((Replicated, A(2))).id();
+        server.despawn(despawned);
+
+        // Snapshots don't consume the removals.
+        assert!(WorldDiff::snapshot(&mut server)
+            .unwrap()
+            .despawned
+            .is_empty());
+        let diff = WorldDiff::collect(&mut server, tick).unwrap();
+        assert_eq!(diff.entities[0].removed.len(), 1);
+        assert_eq!(diff.despawned, [despawned]);
+        assert!(server.resource::<ReplicationRegistry>().removals.is_empty());
+
+        let diff = WorldDiff::collect(&mut server, tick).unwrap();
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn reject_unreplicated_components() {
+        let (mut server, _, mut entity_map) = worlds();
+        let entity = server.spawn((Replicated, A(1), B)).id();
+        let diff = WorldDiff::snapshot(&mut server).unwrap();
+
+        let mut client = World::new();
+        client.insert_resource(server.resource::<AppTypeRegistry>().clone());
+        client.register_replicated_component::<A>();
+        assert!(matches!(
+            diff.apply(&mut client, &mut entity_map),
+            Err(ReplicationError::NotReplicated { type_path }) if type_path == B::type_path()
+        ));
+        // Nothing was applied.
+        assert!(entity_map.is_empty());
+        assert_eq!(client.query::<&A>().iter(&client).count(), 0);
+
+        client.register_replicated_component::<B>();
+        diff.apply(&mut client, &mut entity_map).unwrap();
+        assert_eq!(client.get::<A>(entity_map[&entity]), Some(&A(1)));
+    }
+}
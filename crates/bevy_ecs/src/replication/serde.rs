@@ -0,0 +1,446 @@
+//! `serde` serialization and deserialization of [`WorldDiff`]s.
+
+use super::{EntityDiff, WorldDiff};
+use crate::{component::Tick, entity::Entity};
+use alloc::{boxed::Box, string::String, vec::Vec};
+use bevy_reflect::{
+    serde::{TypeRegistrationDeserializer, TypedReflectDeserializer, TypedReflectSerializer},
+    PartialReflect, ReflectFromReflect, TypeRegistry,
+};
+use core::fmt::Formatter;
+use serde::{
+    de::{DeserializeSeed, Error, MapAccess, SeqAccess, Visitor},
+    ser::{SerializeMap, SerializeSeq, SerializeStruct},
+    Deserializer, Serialize, Serializer,
+};
+
+const WORLD_DIFF_STRUCT: &str = "WorldDiff";
+const WORLD_DIFF_TICK: &str = "tick";
+const WORLD_DIFF_ENTITIES: &str = "entities";
+const WORLD_DIFF_DESPAWNED: &str = "despawned";
+const WORLD_DIFF_FIELDS: &[&str] = &[WORLD_DIFF_TICK, WORLD_DIFF_ENTITIES, WORLD_DIFF_DESPAWNED];
+
+const ENTITY_DIFF_STRUCT: &str = "EntityDiff";
+const ENTITY_DIFF_ENTITY: &str = "entity";
+const ENTITY_DIFF_COMPONENTS: &str = "components";
+const ENTITY_DIFF_REMOVED: &str = "removed";
+const ENTITY_DIFF_FIELDS: &[&str] = &[
+    ENTITY_DIFF_ENTITY,
+    ENTITY_DIFF_COMPONENTS,
+    ENTITY_DIFF_REMOVED,
+];
+
+/// Serializer for a [`WorldDiff`].
+///
+/// The components are serialized with their type paths, as in scenes, so the receiver must have
+/// the same types registered in its [`TypeRegistry`].
+pub struct WorldDiffSerializer<'a> {
+    /// The diff to serialize.
+    pub diff: &'a WorldDiff,
+    /// The type registry containing the types of the components in the diff.
+    pub registry: &'a TypeRegistry,
+}
+
+impl<'a> WorldDiffSerializer<'a> {
+    /// Creates a new serializer from a [`WorldDiff`] and an associated [`TypeRegistry`].
+    pub fn new(diff: &'a WorldDiff, registry: &'a TypeRegistry) -> Self {
+        Self { diff, registry }
+    }
+}
+
+impl Serialize for WorldDiffSerializer<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct(WORLD_DIFF_STRUCT, 3)?;
+        state.serialize_field(WORLD_DIFF_TICK, &self.diff.tick.get())?;
+        state.serialize_field(
+            WORLD_DIFF_ENTITIES,
+            &EntityDiffsSerializer {
+                entities: &self.diff.entities,
+                registry: self.registry,
+            },
+        )?;
+        state.serialize_field(WORLD_DIFF_DESPAWNED, &self.diff.despawned)?;
+        state.end()
+    }
+}
+
+struct EntityDiffsSerializer<'a> {
+    entities: &'a [EntityDiff],
+    registry: &'a TypeRegistry,
+}
+
+impl Serialize for EntityDiffsSerializer<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_seq(Some(self.entities.len()))?;
+        for entity in self.entities {
+            state.serialize_element(&EntityDiffSerializer {
+                entity,
+                registry: self.registry,
+            })?;
+        }
+        state.end()
+    }
+}
+
+struct EntityDiffSerializer<'a> {
+    entity: &'a EntityDiff,
+    registry: &'a TypeRegistry,
+}
+
+impl Serialize for EntityDiffSerializer<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct(ENTITY_DIFF_STRUCT, 3)?;
+        state.serialize_field(ENTITY_DIFF_ENTITY, &self.entity.entity)?;
+        state.serialize_field(
+            ENTITY_DIFF_COMPONENTS,
+            &ComponentsSerializer {
+                components: &self.entity.components,
+                registry: self.registry,
+            },
+        )?;
+        state.serialize_field(ENTITY_DIFF_REMOVED, &self.entity.removed)?;
+        state.end()
+    }
+}
+
+struct ComponentsSerializer<'a> {
+    components: &'a [Box<dyn PartialReflect>],
+    registry: &'a TypeRegistry,
+}
+
+impl Serialize for ComponentsSerializer<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_map(Some(self.components.len()))?;
+        for component in self.components {
+            let type_path = component
+                .get_represented_type_info()
+                .map_or_else(|| component.reflect_type_path(), |info| info.type_path());
+            state.serialize_entry(
+                type_path,
+                &TypedReflectSerializer::new(component.as_partial_reflect(), self.registry),
+            )?;
+        }
+        state.end()
+    }
+}
+
+/// Deserializer for a [`WorldDiff`] serialized with [`WorldDiffSerializer`].
+pub struct WorldDiffDeserializer<'a> {
+    /// The type registry containing the types of the components in the diff.
+    pub registry: &'a TypeRegistry,
+}
+
+impl<'a> WorldDiffDeserializer<'a> {
+    /// Creates a new deserializer with an associated [`TypeRegistry`].
+    pub fn new(registry: &'a TypeRegistry) -> Self {
+        Self { registry }
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for WorldDiffDeserializer<'_> {
+    type Value = WorldDiff;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_struct(
+            WORLD_DIFF_STRUCT,
+            WORLD_DIFF_FIELDS,
+            WorldDiffVisitor {
+                registry: self.registry,
+            },
+        )
+    }
+}
+
+struct WorldDiffVisitor<'a> {
+    registry: &'a TypeRegistry,
+}
+
+impl<'de> Visitor<'de> for WorldDiffVisitor<'_> {
+    type Value = WorldDiff;
+
+    fn expecting(&self, formatter: &mut Formatter) -> core::fmt::Result {
+        formatter.write_str("world diff struct")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let tick = seq
+            .next_element::<u32>()?
+            .ok_or_else(|| Error::missing_field(WORLD_DIFF_TICK))?;
+        let entities = seq
+            .next_element_seed(EntityDiffsDeserializer {
+                registry: self.registry,
+            })?
+            .ok_or_else(|| Error::missing_field(WORLD_DIFF_ENTITIES))?;
+        let despawned = seq
+            .next_element::<Vec<Entity>>()?
+            .ok_or_else(|| Error::missing_field(WORLD_DIFF_DESPAWNED))?;
+
+        Ok(WorldDiff {
+            tick: Tick::new(tick),
+            entities,
+            despawned,
+        })
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut tick = None;
+        let mut entities = None;
+        let mut despawned = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                WORLD_DIFF_TICK if tick.is_none() => tick = Some(map.next_value::<u32>()?),
+                WORLD_DIFF_ENTITIES if entities.is_none() => {
+                    entities = Some(map.next_value_seed(EntityDiffsDeserializer {
+                        registry: self.registry,
+                    })?);
+                }
+                WORLD_DIFF_DESPAWNED if despawned.is_none() => {
+                    despawned = Some(map.next_value::<Vec<Entity>>()?);
+                }
+                WORLD_DIFF_TICK | WORLD_DIFF_ENTITIES | WORLD_DIFF_DESPAWNED => {
+                    return Err(Error::custom(format_args!("duplicate field `{key}`")));
+                }
+                _ => return Err(Error::unknown_field(&key, WORLD_DIFF_FIELDS)),
+            }
+        }
+
+        Ok(WorldDiff {
+            tick: Tick::new(tick.ok_or_else(|| Error::missing_field(WORLD_DIFF_TICK))?),
+            entities: entities.ok_or_else(|| Error::missing_field(WORLD_DIFF_ENTITIES))?,
+            despawned: despawned.ok_or_else(|| Error::missing_field(WORLD_DIFF_DESPAWNED))?,
+        })
+    }
+}
+
+struct EntityDiffsDeserializer<'a> {
+    registry: &'a TypeRegistry,
+}
+
+impl<'de> DeserializeSeed<'de> for EntityDiffsDeserializer<'_> {
+    type Value = Vec<EntityDiff>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for EntityDiffsDeserializer<'_> {
+    type Value = Vec<EntityDiff>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> core::fmt::Result {
+        formatter.write_str("sequence of entity diffs")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut entities = Vec::new();
+        while let Some(entity) = seq.next_element_seed(EntityDiffDeserializer {
+            registry: self.registry,
+        })? {
+            entities.push(entity);
+        }
+        Ok(entities)
+    }
+}
+
+struct EntityDiffDeserializer<'a> {
+    registry: &'a TypeRegistry,
+}
+
+impl<'de> DeserializeSeed<'de> for EntityDiffDeserializer<'_> {
+    type Value = EntityDiff;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_struct(ENTITY_DIFF_STRUCT, ENTITY_DIFF_FIELDS, self)
+    }
+}
+
+impl<'de> Visitor<'de> for EntityDiffDeserializer<'_> {
+    type Value = EntityDiff;
+
+    fn expecting(&self, formatter: &mut Formatter) -> core::fmt::Result {
+        formatter.write_str("entity diff struct")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let entity = seq
+            .next_element::<Entity>()?
+            .ok_or_else(|| Error::missing_field(ENTITY_DIFF_ENTITY))?;
+        let components = seq
+            .next_element_seed(ComponentsDeserializer {
+                registry: self.registry,
+            })?
+            .ok_or_else(|| Error::missing_field(ENTITY_DIFF_COMPONENTS))?;
+        let removed = seq
+            .next_element::<Vec<String>>()?
+            .ok_or_else(|| Error::missing_field(ENTITY_DIFF_REMOVED))?;
+
+        Ok(EntityDiff {
+            entity,
+            components,
+            removed,
+        })
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut entity = None;
+        let mut components = None;
+        let mut removed = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                ENTITY_DIFF_ENTITY if entity.is_none() => {
+                    entity = Some(map.next_value::<Entity>()?);
+                }
+                ENTITY_DIFF_COMPONENTS if components.is_none() => {
+                    components = Some(map.next_value_seed(ComponentsDeserializer {
+                        registry: self.registry,
+                    })?);
+                }
+                ENTITY_DIFF_REMOVED if removed.is_none() => {
+                    removed = Some(map.next_value::<Vec<String>>()?);
+                }
+                ENTITY_DIFF_ENTITY | ENTITY_DIFF_COMPONENTS | ENTITY_DIFF_REMOVED => {
+                    return Err(Error::custom(format_args!("duplicate field `{key}`")));
+                }
+                _ => return Err(Error::unknown_field(&key, ENTITY_DIFF_FIELDS)),
+            }
+        }
+
+        Ok(EntityDiff {
+            entity: entity.ok_or_else(|| Error::missing_field(ENTITY_DIFF_ENTITY))?,
+            components: components.ok_or_else(|| Error::missing_field(ENTITY_DIFF_COMPONENTS))?,
+            removed: removed.ok_or_else(|| Error::missing_field(ENTITY_DIFF_REMOVED))?,
+        })
+    }
+}
+
+struct ComponentsDeserializer<'a> {
+    registry: &'a TypeRegistry,
+}
+
+impl<'de> DeserializeSeed<'de> for ComponentsDeserializer<'_> {
+    type Value = Vec<Box<dyn PartialReflect>>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ComponentsDeserializer<'_> {
+    type Value = Vec<Box<dyn PartialReflect>>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> core::fmt::Result {
+        formatter.write_str("map of components")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut components = Vec::new();
+        while let Some(registration) =
+            map.next_key_seed(TypeRegistrationDeserializer::new(self.registry))?
+        {
+            let component =
+                map.next_value_seed(TypedReflectDeserializer::new(registration, self.registry))?;
+
+            // Attempt to convert using FromReflect.
+            let component = registration
+                .data::<ReflectFromReflect>()
+                .and_then(|from_reflect| from_reflect.from_reflect(component.as_partial_reflect()))
+                .map(PartialReflect::into_partial_reflect)
+                .unwrap_or(component);
+
+            components.push(component);
+        }
+        Ok(components)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{WorldDiffDeserializer, WorldDiffSerializer};
+    use crate as bevy_ecs;
+    use crate::{
+        component::Component,
+        entity::EntityHashMap,
+        reflect::{AppTypeRegistry, ReflectComponent},
+        replication::{Replicated, WorldDiff},
+        world::World,
+    };
+    use bevy_reflect::Reflect;
+    use serde::de::DeserializeSeed;
+
+    #[derive(Component, Reflect, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct A(u32);
+
+    #[test]
+    fn round_trip() {
+        let registry = AppTypeRegistry::default();
+        registry.write().register::<A>();
+        let mut server = World::new();
+        server.insert_resource(registry.clone());
+        server.register_replicated_component::<A>();
+        let entity = server.spawn((Replicated, A(1))).id();
+        let removed = server.spawn((Replicated, A(2))).id();
+        let tick = WorldDiff::snapshot(&mut server).unwrap().tick;
+        server.get_mut::<A>(entity).unwrap().0 = 3;
+        server.despawn(removed);
+        let diff = WorldDiff::collect(&mut server, tick).unwrap();
+
+        let registry = registry.read();
+        let serialized = ron::ser::to_string(&WorldDiffSerializer::new(&diff, &registry)).unwrap();
+        let mut deserializer = ron::de::Deserializer::from_str(&serialized).unwrap();
+        let deserialized = WorldDiffDeserializer::new(&registry)
+            .deserialize(&mut deserializer)
+            .unwrap();
+        assert_eq!(deserialized.tick, diff.tick);
+        assert_eq!(deserialized.despawned, [removed]);
+
+        let mut client = World::new();
+        client.insert_resource(server.resource::<AppTypeRegistry>().clone());
+        client.register_replicated_component::<A>();
+        let mut entity_map = EntityHashMap::default();
+        deserialized.apply(&mut client, &mut entity_map).unwrap();
+        assert_eq!(client.get::<A>(entity_map[&entity]), Some(&A(3)));
+    }
+}
@@ -1,9 +1,13 @@
 use crate::{
-    schedule::{InternedScheduleLabel, NodeId, Schedule, ScheduleLabel},
-    system::{IntoSystem, ResMut, Resource},
+    schedule::{
+        BoxedCondition, Condition, InternedScheduleLabel, InternedSystemSet, NodeId, Schedule,
+        ScheduleLabel, SystemSet,
+    },
+    system::{IntoSystem, ResMut, Resource, System},
+    world::World,
 };
-use alloc::vec::Vec;
-use bevy_utils::{HashMap, TypeIdMap};
+use alloc::{boxed::Box, vec, vec::Vec};
+use bevy_utils::{HashMap, HashSet, TypeIdMap};
 use core::any::TypeId;
 use fixedbitset::FixedBitSet;
 use log::{info, warn};
@@ -64,10 +68,12 @@ struct Cursor {
     pub system: usize,
 }
 
-// Two methods of referring to Systems, via TypeId, or per-Schedule NodeId
+// Methods of referring to Systems, via TypeId, per-Schedule NodeId, or the
+// first system of a SystemSet
 enum SystemIdentifier {
     Type(TypeId),
     Node(NodeId),
+    Set(InternedSystemSet),
 }
 
 /// Updates to [`Stepping.schedule_states`] that will be applied at the start
@@ -85,6 +91,16 @@ enum Update {
     SetBehavior(InternedScheduleLabel, SystemIdentifier, SystemBehavior),
     /// Clear any system-specific behavior for this schedule & system
     ClearBehavior(InternedScheduleLabel, SystemIdentifier),
+    /// Continue every frame until the condition is met
+    ContinueUntil(BoxedCondition),
+    /// Stop continuing until a condition is met
+    CancelContinueUntil,
+}
+
+/// The condition of [`Stepping::continue_until()`]
+struct ContinueUntil {
+    condition: BoxedCondition,
+    initialized: bool,
 }
 
 #[derive(Error, Debug)]
@@ -111,6 +127,12 @@ pub struct Stepping {
 
     // Updates apply at the start of the next render frame
     updates: Vec<Update>,
+
+    // condition that stops continuing frames started with `continue_until()`
+    continue_until: Option<ContinueUntil>,
+
+    // system whose breakpoint stopped the last continued frame
+    breakpoint_hit: Option<(InternedScheduleLabel, NodeId)>,
 }
 
 impl core::fmt::Debug for Stepping {
@@ -148,6 +170,37 @@ impl Stepping {
         }
     }
 
+    /// Exclusive system that evaluates the condition passed to
+    /// [`Stepping::continue_until()`], and continues the frame if it isn't met
+    ///
+    /// Note: This system is automatically added to the default `MainSchedule`,
+    /// after [`Stepping::begin_frame()`].
+    pub fn check_continue_until(world: &mut World) {
+        let Some(mut continue_until) = world
+            .get_resource_mut::<Self>()
+            .and_then(|mut stepping| stepping.continue_until.take())
+        else {
+            return;
+        };
+
+        if !continue_until.initialized {
+            continue_until.condition.initialize(world);
+            continue_until.initialized = true;
+        }
+        let met = continue_until.condition.run((), world);
+
+        let Some(mut stepping) = world.get_resource_mut::<Self>() else {
+            return;
+        };
+        if met {
+            info!("continue_until() condition met; stopping");
+        } else if stepping.action == Action::Waiting {
+            stepping.action = Action::Continue;
+            stepping.breakpoint_hit = None;
+            stepping.continue_until = Some(continue_until);
+        }
+    }
+
     /// Return the list of schedules with stepping enabled in the order
     /// they are executed in.
     pub fn schedules(&self) -> Result<&Vec<InternedScheduleLabel>, NotReady> {
@@ -237,6 +290,61 @@ impl Stepping {
         self
     }
 
+    /// Run all remaining systems in the stepping frame, and keep running full
+    /// frames, until the `condition` is met
+    ///
+    /// The condition is evaluated at the start of every frame, before any
+    /// stepped system runs, so it sees the state of the world left by the
+    /// previous frame.  Use it to run until a query matches or an event
+    /// fires, for example with [`any_with_component`] or [`on_event`].
+    ///
+    /// Hitting a breakpoint, [`Stepping::disable()`], and
+    /// [`Stepping::cancel_continue_until()`] stop continuing as well.
+    ///
+    /// NOTE: This will have no impact unless stepping has been enabled
+    ///
+    /// [`any_with_component`]: crate::schedule::common_conditions::any_with_component
+    /// [`on_event`]: crate::schedule::common_conditions::on_event
+    pub fn continue_until<M>(&mut self, condition: impl Condition<M>) -> &mut Self {
+        let condition: BoxedCondition = Box::new(IntoSystem::into_system(condition));
+        self.updates.push(Update::ContinueUntil(condition));
+        self
+    }
+
+    /// Stop continuing frames started with [`Stepping::continue_until()`]
+    pub fn cancel_continue_until(&mut self) -> &mut Self {
+        self.updates.push(Update::CancelContinueUntil);
+        self
+    }
+
+    /// Check if frames are being continued until the condition passed to
+    /// [`Stepping::continue_until()`] is met
+    pub fn is_continuing_until(&self) -> bool {
+        self.continue_until.is_some()
+    }
+
+    /// Return the system whose breakpoint stopped the last continued frame
+    ///
+    /// This is reset once systems are stepped or continued again.
+    pub fn breakpoint_hit(&self) -> Option<(InternedScheduleLabel, NodeId)> {
+        self.breakpoint_hit
+    }
+
+    /// Return the systems with a breakpoint in the provided schedule
+    ///
+    /// NOTE: Breakpoints set with [`Stepping::set_breakpoint()`] and
+    /// [`Stepping::set_breakpoint_set()`] will only be listed once the
+    /// schedule has run with stepping enabled.
+    pub fn breakpoints(&self, schedule: impl ScheduleLabel) -> impl Iterator<Item = NodeId> + '_ {
+        self.schedule_states
+            .get(&schedule.intern())
+            .into_iter()
+            .flat_map(|state| state.behaviors.iter())
+            .filter_map(|(node_id, behavior)| {
+                matches!(behavior, SystemBehavior::Break).then_some(*node_id)
+            })
+    }
+
     /// Ensure this system always runs when stepping is enabled
     ///
     /// Note: if the system is run multiple times in the [`Schedule`], this
@@ -318,6 +426,37 @@ impl Stepping {
         self
     }
 
+    /// Add a breakpoint for the first system of the system set
+    ///
+    /// When continuing, execution stops before the first system of the set,
+    /// in the order the schedule runs its systems, that hasn't already run in
+    /// this stepping frame.
+    pub fn set_breakpoint_set(
+        &mut self,
+        schedule: impl ScheduleLabel,
+        set: impl SystemSet,
+    ) -> &mut Self {
+        self.updates.push(Update::SetBehavior(
+            schedule.intern(),
+            SystemIdentifier::Set(set.intern()),
+            SystemBehavior::Break,
+        ));
+        self
+    }
+
+    /// Clear a breakpoint for the system set
+    pub fn clear_breakpoint_set(
+        &mut self,
+        schedule: impl ScheduleLabel,
+        set: impl SystemSet,
+    ) -> &mut Self {
+        self.updates.push(Update::ClearBehavior(
+            schedule.intern(),
+            SystemIdentifier::Set(set.intern()),
+        ));
+        self
+    }
+
     /// Clear a breakpoint for the system
     pub fn clear_breakpoint<Marker>(
         &mut self,
@@ -407,6 +546,8 @@ impl Stepping {
             match update {
                 Update::SetAction(Action::RunAll) => {
                     self.action = Action::RunAll;
+                    self.continue_until = None;
+                    self.breakpoint_hit = None;
                     reset_cursor = true;
                 }
                 Update::SetAction(action) => {
@@ -460,7 +601,22 @@ impl Stepping {
 
                     // permitted action transition; make the change
                     self.action = action;
+                    self.breakpoint_hit = None;
+                }
+                Update::ContinueUntil(condition) => {
+                    if self.action == Action::RunAll {
+                        warn!(
+                            "stepping not enabled; call Stepping::enable() \
+                            before continue_until()"
+                        );
+                        continue;
+                    }
+                    self.continue_until = Some(ContinueUntil {
+                        condition,
+                        initialized: false,
+                    });
                 }
+                Update::CancelContinueUntil => self.continue_until = None,
                 Update::AddSchedule(l) => {
                     self.schedule_states.insert(l, ScheduleState::default());
                 }
@@ -559,9 +715,18 @@ impl Stepping {
         // cursor schedule, we'll run the schedule with the waiting action.
         let cursor = self.cursor;
         let (skip_list, next_system) = if index == cursor.schedule {
-            let (skip_list, next_system) =
+            let (skip_list, next_system, breakpoint) =
                 state.skipped_systems(schedule, cursor.system, self.action);
 
+            // if we stopped at a breakpoint, record it, and stop continuing
+            // frames until a condition is met
+            if let Some(node_id) = breakpoint.and_then(|i| state.node_ids.get(i)) {
+                self.breakpoint_hit = Some((label, *node_id));
+                if self.continue_until.take().is_some() {
+                    info!("hit breakpoint; stopping continue_until()");
+                }
+            }
+
             // if we just stepped this schedule, then we'll switch the action
             // to be waiting
             if self.action == Action::Step {
@@ -571,7 +736,7 @@ impl Stepping {
         } else {
             // we're not supposed to run any systems in this schedule, so pull
             // the skip list, but ignore any changes it makes to the cursor.
-            let (skip_list, _) = state.skipped_systems(schedule, 0, Action::Waiting);
+            let (skip_list, _, _) = state.skipped_systems(schedule, 0, Action::Waiting);
             (skip_list, Some(cursor.system))
         };
 
@@ -615,6 +780,10 @@ struct ScheduleState {
     /// [`ScheduleState::skipped_systems()`] is called
     behavior_updates: TypeIdMap<Option<SystemBehavior>>,
 
+    /// changes to the behavior of the first system of system sets that should
+    /// be applied the next time [`ScheduleState::skipped_systems()`] is called
+    set_behavior_updates: Vec<(InternedSystemSet, Option<SystemBehavior>)>,
+
     /// This field contains the first steppable system in the schedule.
     first: Option<usize>,
 }
@@ -633,6 +802,10 @@ impl ScheduleState {
             SystemIdentifier::Type(type_id) => {
                 self.behavior_updates.insert(type_id, Some(behavior));
             }
+            // Same for system sets, which we need the `Schedule` to look into.
+            SystemIdentifier::Set(set) => {
+                self.set_behavior_updates.push((set, Some(behavior)));
+            }
         }
     }
 
@@ -647,6 +820,9 @@ impl ScheduleState {
             SystemIdentifier::Type(type_id) => {
                 self.behavior_updates.insert(type_id, None);
             }
+            SystemIdentifier::Set(set) => {
+                self.set_behavior_updates.push((set, None));
+            }
         }
     }
 
@@ -654,6 +830,7 @@ impl ScheduleState {
     fn clear_behaviors(&mut self) {
         self.behaviors.clear();
         self.behavior_updates.clear();
+        self.set_behavior_updates.clear();
         self.first = None;
     }
 
@@ -679,6 +856,21 @@ impl ScheduleState {
         }
         self.behavior_updates.clear();
 
+        for (set, behavior) in self.set_behavior_updates.drain(..) {
+            let Some(node_id) = first_system_in_set(schedule, set) else {
+                warn!(
+                    "system set {:?} has no systems in schedule {:?}",
+                    set,
+                    schedule.label()
+                );
+                continue;
+            };
+            match behavior {
+                None => self.behaviors.remove(&node_id),
+                Some(behavior) => self.behaviors.insert(node_id, behavior),
+            };
+        }
+
         #[cfg(test)]
         debug!("apply_updates(): {:?}", self.behaviors);
     }
@@ -688,7 +880,7 @@ impl ScheduleState {
         schedule: &Schedule,
         start: usize,
         mut action: Action,
-    ) -> (FixedBitSet, Option<usize>) {
+    ) -> (FixedBitSet, Option<usize>, Option<usize>) {
         use core::cmp::Ordering;
 
         // if our NodeId list hasn't been populated, copy it over from the
@@ -700,7 +892,7 @@ impl ScheduleState {
         // Now that we have the schedule, apply any pending system behavior
         // updates.  The schedule is required to map from system `TypeId` to
         // `NodeId`.
-        if !self.behavior_updates.is_empty() {
+        if !self.behavior_updates.is_empty() || !self.set_behavior_updates.is_empty() {
             self.apply_behavior_updates(schedule);
         }
 
@@ -719,6 +911,7 @@ impl ScheduleState {
 
         let mut skip = FixedBitSet::with_capacity(schedule.systems_len());
         let mut pos = start;
+        let mut breakpoint = None;
 
         for (i, (node_id, _system)) in schedule.systems().unwrap().enumerate() {
             let behavior = self
@@ -797,6 +990,7 @@ impl ScheduleState {
                         // system under the cursor.
                         if i > start {
                             action = Action::Waiting;
+                            breakpoint = Some(i);
                         }
                     }
                 }
@@ -812,16 +1006,42 @@ impl ScheduleState {
             }
         }
 
-        // output is the skip list, and the index of the next system to run in
-        // this schedule.
+        // output is the skip list, the index of the next system to run in
+        // this schedule, and the index of the breakpoint we stopped at.
         if pos >= schedule.systems_len() {
-            (skip, None)
+            (skip, None, breakpoint)
         } else {
-            (skip, Some(pos))
+            (skip, Some(pos), breakpoint)
         }
     }
 }
 
+/// Find the first system, in the order the schedule runs them, that is part of
+/// the system set, either directly or through nested sets
+fn first_system_in_set(schedule: &Schedule, set: InternedSystemSet) -> Option<NodeId> {
+    let graph = schedule.graph();
+    let (set_id, _, _) = graph
+        .system_sets()
+        .find(|&(_, system_set, _)| system_set == &*set)?;
+
+    let hierarchy = graph.hierarchy().graph();
+    let mut members = HashSet::default();
+    let mut stack = vec![set_id];
+    while let Some(node) = stack.pop() {
+        for child in hierarchy.neighbors(node) {
+            if members.insert(child) {
+                stack.push(child);
+            }
+        }
+    }
+
+    schedule
+        .systems()
+        .ok()?
+        .map(|(node_id, _)| node_id)
+        .find(|node_id| members.contains(node_id))
+}
+
 #[cfg(all(test, feature = "bevy_debug_stepping"))]
 mod tests {
     use super::*;
@@ -1146,6 +1366,105 @@ mod tests {
         assert_schedule_runs!(&schedule, &mut stepping, first_system);
     }
 
+    #[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+    struct TestSet;
+
+    #[test]
+    fn continue_breakpoint_set() {
+        let mut world = World::new();
+        let mut schedule = Schedule::new(TestSchedule);
+        schedule.add_systems((first_system, (second_system, third_system).in_set(TestSet)).chain());
+        schedule.initialize(&mut world).unwrap();
+
+        let mut stepping = Stepping::new();
+        stepping
+            .add_schedule(TestSchedule)
+            .enable()
+            .set_breakpoint_set(TestSchedule, TestSet)
+            .continue_frame();
+
+        assert_schedule_runs!(&schedule, &mut stepping, first_system);
+        let second = schedule.executable().system_ids[1];
+        assert_eq!(stepping.breakpoint_hit(), Some((schedule.label(), second)));
+        assert_eq!(
+            stepping.breakpoints(TestSchedule).collect::<Vec<_>>(),
+            [second]
+        );
+
+        stepping.continue_frame();
+        assert_schedule_runs!(&schedule, &mut stepping, second_system, third_system);
+        assert_eq!(stepping.breakpoint_hit(), None);
+
+        stepping
+            .clear_breakpoint_set(TestSchedule, TestSet)
+            .continue_frame();
+        assert_schedule_runs!(
+            &schedule,
+            &mut stepping,
+            first_system,
+            second_system,
+            third_system
+        );
+    }
+
+    #[derive(Resource, Default)]
+    struct Stop(bool);
+
+    #[test]
+    fn continue_until() {
+        // run a stepping frame the way the main schedule does
+        fn run_frame(world: &mut World, schedule: &Schedule) -> Option<FixedBitSet> {
+            world.resource_mut::<Stepping>().next_frame();
+            Stepping::check_continue_until(world);
+            world.resource_mut::<Stepping>().skipped_systems(schedule)
+        }
+
+        let (schedule, mut world) = setup();
+        world.init_resource::<Stop>();
+
+        let mut stepping = Stepping::new();
+        stepping
+            .add_schedule(TestSchedule)
+            .enable()
+            .continue_until(|stop: Res<Stop>| stop.0);
+        world.insert_resource(stepping);
+
+        for _ in 0..2 {
+            assert_systems_run!(
+                &schedule,
+                run_frame(&mut world, &schedule),
+                first_system,
+                second_system
+            );
+            assert!(world.resource::<Stepping>().is_continuing_until());
+        }
+
+        world.resource_mut::<Stop>().0 = true;
+        assert_systems_run!(&schedule, run_frame(&mut world, &schedule),);
+        assert!(!world.resource::<Stepping>().is_continuing_until());
+    }
+
+    #[test]
+    fn continue_until_stops_at_breakpoint() {
+        let (schedule, mut world) = setup();
+        world.init_resource::<Stop>();
+
+        let mut stepping = Stepping::new();
+        stepping
+            .add_schedule(TestSchedule)
+            .enable()
+            .set_breakpoint(TestSchedule, second_system)
+            .continue_until(|stop: Res<Stop>| stop.0);
+        world.insert_resource(stepping);
+
+        world.resource_mut::<Stepping>().next_frame();
+        Stepping::check_continue_until(&mut world);
+        let mut stepping = world.resource_mut::<Stepping>();
+        assert_systems_run!(&schedule, stepping.skipped_systems(&schedule), first_system);
+        assert!(!stepping.is_continuing_until());
+        assert_schedule_runs!(&schedule, &mut stepping,);
+    }
+
     /// regression test for issue encountered while writing `system_stepping`
     /// example
     #[test]
@@ -145,6 +145,7 @@ impl<const SEND: bool> ResourceData<SEND> {
                 TickCells {
                     added: &self.added_ticks,
                     changed: &self.changed_ticks,
+                    column_changed: None,
                 },
                 #[cfg(feature = "track_location")]
                 &self.changed_by,
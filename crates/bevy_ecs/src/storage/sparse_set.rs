@@ -249,6 +249,7 @@ impl ComponentSparseSet {
                 TickCells {
                     added: self.dense.get_added_tick_unchecked(dense_index),
                     changed: self.dense.get_changed_tick_unchecked(dense_index),
+                    column_changed: None,
                 },
                 #[cfg(feature = "track_location")]
                 self.dense.get_changed_by_unchecked(dense_index),
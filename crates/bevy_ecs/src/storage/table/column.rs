@@ -6,6 +6,62 @@ use crate::{
 use alloc::vec::Vec;
 use bevy_ptr::PtrMut;
 
+#[cfg(not(feature = "portable-atomic"))]
+use core::sync::atomic::{AtomicU32, Ordering};
+
+#[cfg(feature = "portable-atomic")]
+use portable_atomic::{AtomicU32, Ordering};
+
+/// The newest [`Tick`] among the rows of a [`ThinColumn`].
+///
+/// Queries filtered with [`Added`](crate::query::Added) or
+/// [`Changed`](crate::query::Changed) compare it with the last run of their system to skip
+/// whole tables in which no component has been added or changed.
+///
+/// The tick is stored atomically, so that it can be updated through the shared references to a
+/// table that are handed out to queries, including parallel ones.
+pub struct ColumnTick(AtomicU32);
+
+impl ColumnTick {
+    fn new(tick: Tick) -> Self {
+        Self(AtomicU32::new(tick.get()))
+    }
+
+    /// Returns the newest tick among the rows of the column.
+    #[inline]
+    pub fn get(&self) -> Tick {
+        Tick::new(self.0.load(Ordering::Relaxed))
+    }
+
+    /// Records that a row of the column was written to at `tick`, which must be at least as new
+    /// as any tick previously recorded.
+    #[inline]
+    pub(crate) fn set(&self, tick: Tick) {
+        self.0.store(tick.get(), Ordering::Relaxed);
+    }
+
+    /// Records `tick` if it's newer than the current tick, relative to `this_run`.
+    #[inline]
+    pub(crate) fn record(&self, tick: Tick, this_run: Tick) {
+        if tick.is_newer_than(self.get(), this_run) {
+            self.set(tick);
+        }
+    }
+
+    #[inline]
+    fn check_tick(&mut self, change_tick: Tick) {
+        let mut tick = self.get();
+        tick.check_tick(change_tick);
+        *self.0.get_mut() = tick.get();
+    }
+}
+
+impl core::fmt::Debug for ColumnTick {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("ColumnTick").field(&self.get()).finish()
+    }
+}
+
 /// Very similar to a normal [`Column`], but with the capacities and lengths cut out for performance reasons.
 ///
 /// This type is used by [`Table`], because all of the capacities and lengths of the [`Table`]'s columns must match.
@@ -19,6 +75,8 @@ pub struct ThinColumn {
     pub(super) changed_ticks: ThinArrayPtr<UnsafeCell<Tick>>,
     #[cfg(feature = "track_location")]
     pub(super) changed_by: ThinArrayPtr<UnsafeCell<&'static Location<'static>>>,
+    pub(super) newest_added_tick: ColumnTick,
+    pub(super) newest_changed_tick: ColumnTick,
 }
 
 impl ThinColumn {
@@ -33,9 +91,23 @@ impl ThinColumn {
             changed_ticks: ThinArrayPtr::with_capacity(capacity),
             #[cfg(feature = "track_location")]
             changed_by: ThinArrayPtr::with_capacity(capacity),
+            newest_added_tick: ColumnTick::new(Tick::new(0)),
+            newest_changed_tick: ColumnTick::new(Tick::new(0)),
         }
     }
 
+    /// Returns the newest added tick among the rows of this column.
+    #[inline]
+    pub fn newest_added_tick(&self) -> &ColumnTick {
+        &self.newest_added_tick
+    }
+
+    /// Returns the newest changed tick among the rows of this column.
+    #[inline]
+    pub fn newest_changed_tick(&self) -> &ColumnTick {
+        &self.newest_changed_tick
+    }
+
     /// Swap-remove and drop the removed element, but the component at `row` must not be the last element.
     ///
     /// # Safety
@@ -152,6 +224,8 @@ impl ThinColumn {
             .changed_ticks
             .get_unchecked_mut(row.as_usize())
             .get_mut() = tick;
+        self.newest_added_tick.set(tick);
+        self.newest_changed_tick.set(tick);
         #[cfg(feature = "track_location")]
         {
             *self.changed_by.get_unchecked_mut(row.as_usize()).get_mut() = caller;
@@ -176,6 +250,7 @@ impl ThinColumn {
             .changed_ticks
             .get_unchecked_mut(row.as_usize())
             .get_mut() = change_tick;
+        self.newest_changed_tick.set(change_tick);
         #[cfg(feature = "track_location")]
         {
             *self.changed_by.get_unchecked_mut(row.as_usize()).get_mut() = caller;
@@ -192,6 +267,7 @@ impl ThinColumn {
     ///  - `dst_row` must be in bounds for `self`
     ///  - `other[src_row]` must be initialized to a valid value.
     ///  - `self[dst_row]` must not be initialized yet.
+    ///  - `change_tick` must be at least as new as the ticks of `other[src_row]`.
     #[inline]
     pub(crate) unsafe fn initialize_from_unchecked(
         &mut self,
@@ -199,6 +275,7 @@ impl ThinColumn {
         other_last_element_index: usize,
         src_row: TableRow,
         dst_row: TableRow,
+        change_tick: Tick,
     ) {
         debug_assert!(self.data.layout() == other.data.layout());
         // Init the data
@@ -207,15 +284,19 @@ impl ThinColumn {
             .swap_remove_unchecked(src_row.as_usize(), other_last_element_index);
         self.data.initialize_unchecked(dst_row.as_usize(), src_val);
         // Init added_ticks
-        let added_tick = other
+        let mut added_tick = other
             .added_ticks
             .swap_remove_unchecked(src_row.as_usize(), other_last_element_index);
+        self.newest_added_tick
+            .record(*added_tick.get_mut(), change_tick);
         self.added_ticks
             .initialize_unchecked(dst_row.as_usize(), added_tick);
         // Init changed_ticks
-        let changed_tick = other
+        let mut changed_tick = other
             .changed_ticks
             .swap_remove_unchecked(src_row.as_usize(), other_last_element_index);
+        self.newest_changed_tick
+            .record(*changed_tick.get_mut(), change_tick);
         self.changed_ticks
             .initialize_unchecked(dst_row.as_usize(), changed_tick);
         #[cfg(feature = "track_location")]
@@ -233,6 +314,8 @@ impl ThinColumn {
     /// `len` is the actual length of this column
     #[inline]
     pub(crate) unsafe fn check_change_ticks(&mut self, len: usize, change_tick: Tick) {
+        self.newest_added_tick.check_tick(change_tick);
+        self.newest_changed_tick.check_tick(change_tick);
         for i in 0..len {
             // SAFETY:
             // - `i` < `len`
@@ -522,6 +605,7 @@ impl Column {
                     TickCells {
                         added: self.added_ticks.get_unchecked(row.as_usize()),
                         changed: self.changed_ticks.get_unchecked(row.as_usize()),
+                        column_changed: None,
                     },
                 )
             })
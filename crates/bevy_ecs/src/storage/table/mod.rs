@@ -265,10 +265,12 @@ impl Table {
     ///
     /// # Safety
     /// - `row` must be in-bounds
+    /// - `change_tick` must be at least as new as the ticks of the moved components
     pub(crate) unsafe fn move_to_and_forget_missing_unchecked(
         &mut self,
         row: TableRow,
         new_table: &mut Table,
+        change_tick: Tick,
     ) -> TableMoveResult {
         debug_assert!(row.as_usize() < self.entity_count());
         let last_element_index = self.entity_count() - 1;
@@ -276,7 +278,13 @@ impl Table {
         let new_row = new_table.allocate(self.entities.swap_remove(row.as_usize()));
         for (component_id, column) in self.columns.iter_mut() {
             if let Some(new_column) = new_table.get_column_mut(*component_id) {
-                new_column.initialize_from_unchecked(column, last_element_index, row, new_row);
+                new_column.initialize_from_unchecked(
+                    column,
+                    last_element_index,
+                    row,
+                    new_row,
+                    change_tick,
+                );
             } else {
                 // It's the caller's responsibility to drop these cases.
                 column.swap_remove_and_forget_unchecked(last_element_index, row);
@@ -297,11 +305,13 @@ impl Table {
     /// to replace it (if an entity was swapped in).
     ///
     /// # Safety
-    /// row must be in-bounds
+    /// - `row` must be in-bounds
+    /// - `change_tick` must be at least as new as the ticks of the moved components
     pub(crate) unsafe fn move_to_and_drop_missing_unchecked(
         &mut self,
         row: TableRow,
         new_table: &mut Table,
+        change_tick: Tick,
     ) -> TableMoveResult {
         debug_assert!(row.as_usize() < self.entity_count());
         let last_element_index = self.entity_count() - 1;
@@ -309,7 +319,13 @@ impl Table {
         let new_row = new_table.allocate(self.entities.swap_remove(row.as_usize()));
         for (component_id, column) in self.columns.iter_mut() {
             if let Some(new_column) = new_table.get_column_mut(*component_id) {
-                new_column.initialize_from_unchecked(column, last_element_index, row, new_row);
+                new_column.initialize_from_unchecked(
+                    column,
+                    last_element_index,
+                    row,
+                    new_row,
+                    change_tick,
+                );
             } else {
                 column.swap_remove_and_drop_unchecked(last_element_index, row);
             }
@@ -331,10 +347,12 @@ impl Table {
     /// # Safety
     /// - `row` must be in-bounds
     /// - `new_table` must contain every component this table has
+    /// - `change_tick` must be at least as new as the ticks of the moved components
     pub(crate) unsafe fn move_to_superset_unchecked(
         &mut self,
         row: TableRow,
         new_table: &mut Table,
+        change_tick: Tick,
     ) -> TableMoveResult {
         debug_assert!(row.as_usize() < self.entity_count());
         let last_element_index = self.entity_count() - 1;
@@ -344,7 +362,7 @@ impl Table {
             new_table
                 .get_column_mut(*component_id)
                 .debug_checked_unwrap()
-                .initialize_from_unchecked(column, last_element_index, row, new_row);
+                .initialize_from_unchecked(column, last_element_index, row, new_row, change_tick);
         }
         TableMoveResult {
             new_row,
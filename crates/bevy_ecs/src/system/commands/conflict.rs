@@ -0,0 +1,287 @@
+//! Detection of commands that conflict with each other.
+//!
+//! Commands queued by different systems are applied one after the other, so a system can end up
+//! mutating an entity that a command of another system despawned. These commands then fail when
+//! they're applied, either silently, with a warning, or with a panic that doesn't tell which
+//! systems were involved.
+//!
+//! Inserting the [`CommandConflictDetection`] resource records the system that queued each
+//! despawn, so that the failures of the commands that later target the despawned entities are
+//! reported as [`CommandConflict`]s naming both systems.
+//!
+//! This is the only kind of conflict that is detected. Commands of different systems that insert
+//! or remove the same component on an entity that is still alive overwrite each other in the
+//! order the systems' commands are applied, and aren't reported.
+
+use alloc::{borrow::Cow, vec::Vec};
+use core::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+use log::warn;
+
+use crate::{
+    self as bevy_ecs,
+    entity::{Entity, EntityHashMap},
+    system::Resource,
+    world::World,
+};
+
+/// Enables command conflict detection when present in the [`World`].
+///
+/// While this resource exists, the name of the system that queued each command is recorded as
+/// the command is applied. When a command fails because its entity was despawned by a command of
+/// another system, a [`CommandConflict`] is logged and stored in this resource.
+///
+/// The despawned entities are forgotten when [`World::clear_trackers`] is called, usually once
+/// per frame.
+///
+/// Only the commands queued by systems (including observers) are attributed to a system. Despawns
+/// done through exclusive [`World`] access aren't recorded.
+///
+/// Only commands that fail because their entity was despawned are reported. Conflicting inserts or
+/// removals of components on an entity that still exists aren't detected: the command applied
+/// last wins, as usual.
+///
+/// # Example
+///
+/// ```
+/// # use bevy_ecs::{prelude::*, system::CommandConflictDetection};
+/// let mut world = World::new();
+/// world.init_resource::<CommandConflictDetection>();
+///
+/// // ... run schedules ...
+///
+/// for conflict in world.resource_mut::<CommandConflictDetection>().drain_conflicts() {
+///     println!("{conflict}");
+/// }
+/// ```
+#[derive(Resource, Default, Debug)]
+pub struct CommandConflictDetection {
+    /// The names of the systems whose commands are being applied, innermost last.
+    sources: Vec<Cow<'static, str>>,
+    /// The system that queued the despawn of each entity.
+    despawned_by: EntityHashMap<Cow<'static, str>>,
+    conflicts: Vec<CommandConflict>,
+}
+
+/// A command that failed because its entity was despawned by a command of another system.
+///
+/// This is the only kind of conflict that [`CommandConflictDetection`] reports.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommandConflict {
+    /// The entity targeted by both commands.
+    pub entity: Entity,
+    /// The name of the system that queued the command that despawned the entity.
+    pub despawned_by: Cow<'static, str>,
+    /// The name of the system that queued the command that failed, if it was queued by a system.
+    pub queued_by: Option<Cow<'static, str>>,
+}
+
+impl fmt::Display for CommandConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.queued_by {
+            Some(queued_by) => write!(
+                f,
+                "A command queued by system `{queued_by}` targeted entity {}, which was despawned by a command queued by system `{}`",
+                self.entity, self.despawned_by
+            ),
+            None => write!(
+                f,
+                "A command targeted entity {}, which was despawned by a command queued by system `{}`",
+                self.entity, self.despawned_by
+            ),
+        }
+    }
+}
+
+impl CommandConflictDetection {
+    /// Returns the conflicts detected since the last call to [`Self::drain_conflicts`].
+    pub fn conflicts(&self) -> &[CommandConflict] {
+        &self.conflicts
+    }
+
+    /// Removes and returns the detected conflicts.
+    pub fn drain_conflicts(&mut self) -> impl Iterator<Item = CommandConflict> + '_ {
+        self.conflicts.drain(..)
+    }
+
+    /// Returns the name of the system whose commands are being applied.
+    pub fn current_source(&self) -> Option<&str> {
+        self.sources.last().map(|source| &**source)
+    }
+
+    /// Marks the application of the commands queued by the given system, until the returned guard
+    /// is dropped, even if applying the commands panics.
+    ///
+    /// The guard dereferences to the world to apply the commands to.
+    pub(crate) fn scope(world: &mut World, source: Cow<'static, str>) -> SourceScope<'_> {
+        Self::push_source(world, source);
+        SourceScope { world }
+    }
+
+    /// Marks the start of the application of the commands queued by the given system.
+    ///
+    /// Does nothing if conflict detection is disabled, but callers check whether it's enabled
+    /// first, to avoid copying the name of the system for every command queue.
+    pub(crate) fn push_source(world: &mut World, source: Cow<'static, str>) {
+        if let Some(mut detection) = world.get_resource_mut::<Self>() {
+            detection.bypass_change_detection().sources.push(source);
+        }
+    }
+
+    /// Marks the end of the application of the commands queued by a system.
+    pub(crate) fn pop_source(world: &mut World) {
+        if let Some(mut detection) = world.get_resource_mut::<Self>() {
+            detection.bypass_change_detection().sources.pop();
+        }
+    }
+
+    /// Records that the current system despawned `entity`.
+    pub(crate) fn record_despawn(world: &mut World, entity: Entity) {
+        let Some(mut detection) = world.get_resource_mut::<Self>() else {
+            return;
+        };
+        let detection = detection.bypass_change_detection();
+        if let Some(source) = detection.sources.last() {
+            detection.despawned_by.insert(entity, source.clone());
+        }
+    }
+
+    /// Reports a conflict if a command of the current system can't be applied because `entity`
+    /// was despawned by another command.
+    pub(crate) fn check_entity(world: &mut World, entity: Entity) {
+        if world.entities().contains(entity) {
+            return;
+        }
+        let Some(mut detection) = world.get_resource_mut::<Self>() else {
+            return;
+        };
+        let Some(despawned_by) = detection.despawned_by.get(&entity).cloned() else {
+            return;
+        };
+        let conflict = CommandConflict {
+            entity,
+            despawned_by,
+            queued_by: detection.sources.last().cloned(),
+        };
+        warn!("{conflict}");
+        detection.conflicts.push(conflict);
+    }
+
+    /// Forgets the despawned entities.
+    pub(crate) fn clear_despawned(world: &mut World) {
+        if let Some(mut detection) = world.get_resource_mut::<Self>() {
+            detection.bypass_change_detection().despawned_by.clear();
+        }
+    }
+}
+
+/// Pops the source pushed by [`CommandConflictDetection::scope`] when dropped.
+pub(crate) struct SourceScope<'w> {
+    world: &'w mut World,
+}
+
+impl Deref for SourceScope<'_> {
+    type Target = World;
+
+    fn deref(&self) -> &World {
+        self.world
+    }
+}
+
+impl DerefMut for SourceScope<'_> {
+    fn deref_mut(&mut self) -> &mut World {
+        self.world
+    }
+}
+
+impl Drop for SourceScope<'_> {
+    fn drop(&mut self) {
+        CommandConflictDetection::pop_source(self.world);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CommandConflictDetection;
+    use crate::{self as bevy_ecs, prelude::*};
+
+    #[derive(Component)]
+    struct A;
+
+    #[derive(Resource)]
+    struct Target(Entity);
+
+    fn despawner(mut commands: Commands, target: Res<Target>) {
+        commands.entity(target.0).despawn();
+    }
+
+    fn inserter(mut commands: Commands, target: Res<Target>) {
+        commands.entity(target.0).try_insert(A);
+    }
+
+    #[test]
+    fn reports_both_systems() {
+        let mut world = World::new();
+        world.init_resource::<CommandConflictDetection>();
+        let target = world.spawn_empty().id();
+        world.insert_resource(Target(target));
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems((despawner, inserter).chain());
+        schedule.run(&mut world);
+
+        let detection = world.resource::<CommandConflictDetection>();
+        let [conflict] = detection.conflicts() else {
+            panic!("expected one conflict, got {:?}", detection.conflicts());
+        };
+        assert_eq!(conflict.entity, target);
+        assert!(conflict.despawned_by.ends_with("despawner"));
+        assert!(conflict
+            .queued_by
+            .as_deref()
+            .is_some_and(|name| name.ends_with("inserter")));
+        assert_eq!(detection.current_source(), None);
+
+        // Once the despawns are forgotten, failing commands aren't reported anymore.
+        world.clear_trackers();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(inserter);
+        schedule.run(&mut world);
+        assert_eq!(
+            world
+                .resource::<CommandConflictDetection>()
+                .conflicts()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn pops_source_when_command_panics() {
+        use crate::schedule::ExecutorKind;
+        use core::panic::AssertUnwindSafe;
+
+        fn panicker(mut commands: Commands) {
+            commands.queue(|_: &mut World| panic!("command panicked"));
+        }
+
+        let mut world = World::new();
+        world.init_resource::<CommandConflictDetection>();
+        let mut schedule = Schedule::default();
+        schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+        schedule.add_systems(panicker);
+
+        std::panic::set_hook(Box::new(|_| {}));
+        let result = std::panic::catch_unwind(AssertUnwindSafe(|| schedule.run(&mut world)));
+        let _ = std::panic::take_hook();
+        assert!(result.is_err());
+        assert_eq!(
+            world
+                .resource::<CommandConflictDetection>()
+                .current_source(),
+            None
+        );
+    }
+}
@@ -16,7 +16,7 @@ use crate::{
     entity::{Entity, EntityCloneBuilder},
     event::Event,
     result::Result,
-    system::{command::HandleError, Command, IntoObserverSystem},
+    system::{command::HandleError, Command, CommandConflictDetection, IntoObserverSystem},
     world::{error::EntityFetchError, EntityWorldMut, FromWorld, World},
 };
 use bevy_ptr::OwningPtr;
@@ -105,6 +105,7 @@ impl<C: EntityCommand> CommandWithEntity<Result<(), EntityFetchError>> for C {
     ) -> impl Command<Result<(), EntityFetchError>> + HandleError<Result<(), EntityFetchError>>
     {
         move |world: &mut World| -> Result<(), EntityFetchError> {
+            CommandConflictDetection::check_entity(world, entity);
             let entity = world.get_entity_mut(entity)?;
             self.apply(entity);
             Ok(())
@@ -124,6 +125,7 @@ impl<
     ) -> impl Command<Result<T, EntityCommandError<Err>>> + HandleError<Result<T, EntityCommandError<Err>>>
     {
         move |world: &mut World| {
+            CommandConflictDetection::check_entity(world, entity);
             let entity = world.get_entity_mut(entity)?;
             self.apply(entity)
                 .map_err(EntityCommandError::CommandFailed)
@@ -259,7 +261,14 @@ pub fn retain<T: Bundle>() -> impl EntityCommand {
 pub fn despawn() -> impl EntityCommand {
     #[cfg(feature = "track_location")]
     let caller = Location::caller();
-    move |entity: EntityWorldMut| {
+    move |mut entity: EntityWorldMut| {
+        if entity
+            .world()
+            .contains_resource::<CommandConflictDetection>()
+        {
+            let id = entity.id();
+            entity.world_scope(|world| CommandConflictDetection::record_despawn(world, id));
+        }
         entity.despawn_with_caller(
             #[cfg(feature = "track_location")]
             caller,
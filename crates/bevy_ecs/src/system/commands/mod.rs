@@ -1,4 +1,5 @@
 pub mod command;
+pub mod conflict;
 pub mod entity_command;
 pub mod error_handler;
 
@@ -6,6 +7,7 @@ pub mod error_handler;
 mod parallel_scope;
 
 pub use command::Command;
+pub use conflict::{CommandConflict, CommandConflictDetection};
 pub use entity_command::EntityCommand;
 
 #[cfg(feature = "std")]
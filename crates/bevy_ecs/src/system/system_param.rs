@@ -1047,6 +1047,7 @@ unsafe impl<'a, T: Resource> SystemParam for ResMut<'a, T> {
             ticks: TicksMut {
                 added: value.ticks.added,
                 changed: value.ticks.changed,
+                column_changed: None,
                 last_run: system_meta.last_run,
                 this_run: change_tick,
             },
@@ -1079,6 +1080,7 @@ unsafe impl<'a, T: Resource> SystemParam for Option<ResMut<'a, T>> {
                 ticks: TicksMut {
                     added: value.ticks.added,
                     changed: value.ticks.changed,
+                    column_changed: None,
                     last_run: system_meta.last_run,
                     this_run: change_tick,
                 },
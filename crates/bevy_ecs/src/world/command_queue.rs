@@ -1,5 +1,5 @@
 use crate::{
-    system::{Command, SystemBuffer, SystemMeta},
+    system::{Command, CommandConflictDetection, SystemBuffer, SystemMeta},
     world::{DeferredWorld, World},
 };
 use alloc::{boxed::Box, vec::Vec};
@@ -320,15 +320,31 @@ impl Drop for CommandQueue {
 
 impl SystemBuffer for CommandQueue {
     #[inline]
-    fn apply(&mut self, _system_meta: &SystemMeta, world: &mut World) {
+    fn apply(&mut self, system_meta: &SystemMeta, world: &mut World) {
         #[cfg(feature = "trace")]
-        let _span_guard = _system_meta.commands_span.enter();
-        self.apply(world);
+        let _span_guard = system_meta.commands_span.enter();
+        if !world.contains_resource::<CommandConflictDetection>() {
+            self.apply(world);
+            return;
+        }
+        let mut world = CommandConflictDetection::scope(world, system_meta.name.clone());
+        self.apply(&mut world);
     }
 
     #[inline]
-    fn queue(&mut self, _system_meta: &SystemMeta, mut world: DeferredWorld) {
-        world.commands().append(self);
+    fn queue(&mut self, system_meta: &SystemMeta, mut world: DeferredWorld) {
+        if !world.contains_resource::<CommandConflictDetection>() {
+            world.commands().append(self);
+            return;
+        }
+        // Attribute the commands to this system once they're applied.
+        let source = system_meta.name.clone();
+        let mut commands = world.commands();
+        commands.queue(move |world: &mut World| {
+            CommandConflictDetection::push_source(world, source);
+        });
+        commands.append(self);
+        commands.queue(CommandConflictDetection::pop_source);
     }
 }
 
@@ -2,7 +2,7 @@ use crate::{
     archetype::{Archetype, ArchetypeId, Archetypes},
     bundle::{Bundle, BundleId, BundleInfo, BundleInserter, DynamicBundle, InsertMode},
     change_detection::MutUntyped,
-    component::{Component, ComponentId, ComponentTicks, Components, Mutable, StorageType},
+    component::{Component, ComponentId, ComponentTicks, Components, Mutable, StorageType, Tick},
     entity::{
         Entities, Entity, EntityBorrow, EntityCloneBuilder, EntityLocation, TrustedEntityBorrow,
     },
@@ -1728,6 +1728,7 @@ impl<'w> EntityWorldMut<'w> {
             );
         }
 
+        let change_tick = world.change_tick();
         let archetypes = &mut world.archetypes;
         let storages = &mut world.storages;
         let components = &mut world.components;
@@ -1770,6 +1771,7 @@ impl<'w> EntityWorldMut<'w> {
                 archetypes,
                 storages,
                 new_archetype_id,
+                change_tick,
             );
         }
         self.world.flush();
@@ -1795,6 +1797,7 @@ impl<'w> EntityWorldMut<'w> {
         archetypes: &mut Archetypes,
         storages: &mut Storages,
         new_archetype_id: ArchetypeId,
+        change_tick: Tick,
     ) {
         let old_archetype = &mut archetypes[old_archetype_id];
         let remove_result = old_archetype.swap_remove(old_location.archetype_row);
@@ -1825,10 +1828,22 @@ impl<'w> EntityWorldMut<'w> {
 
             let move_result = if DROP {
                 // SAFETY: old_table_row exists
-                unsafe { old_table.move_to_and_drop_missing_unchecked(old_table_row, new_table) }
+                unsafe {
+                    old_table.move_to_and_drop_missing_unchecked(
+                        old_table_row,
+                        new_table,
+                        change_tick,
+                    )
+                }
             } else {
                 // SAFETY: old_table_row exists
-                unsafe { old_table.move_to_and_forget_missing_unchecked(old_table_row, new_table) }
+                unsafe {
+                    old_table.move_to_and_forget_missing_unchecked(
+                        old_table_row,
+                        new_table,
+                        change_tick,
+                    )
+                }
             };
 
             // SAFETY: move_result.new_row is a valid position in new_archetype's table
@@ -1932,6 +1947,7 @@ impl<'w> EntityWorldMut<'w> {
         // SAFETY: `new_archetype_id` is a subset of the components in `old_location.archetype_id`
         // because it is created by removing a bundle from these components.
         let mut new_location = location;
+        let change_tick = world.change_tick();
         Self::move_entity_from_remove::<true>(
             entity,
             &mut new_location,
@@ -1941,6 +1957,7 @@ impl<'w> EntityWorldMut<'w> {
             &mut world.archetypes,
             &mut world.storages,
             new_archetype_id,
+            change_tick,
         );
 
         new_location
@@ -39,6 +39,7 @@ use crate::{
         RequiredComponentsError, Tick,
     },
     entity::{AllocAtWithoutReplacement, Entities, Entity, EntityLocation},
+    entity_disabling::{DefaultQueryFilters, Disabled},
     event::{Event, EventId, Events, SendBatchIds},
     observer::Observers,
     query::{DebugCheckedUnwrap, QueryData, QueryFilter, QueryState},
@@ -46,7 +47,7 @@ use crate::{
     result::Result,
     schedule::{Schedule, ScheduleLabel, Schedules},
     storage::{ResourceData, Storages},
-    system::{Commands, Resource},
+    system::{CommandConflictDetection, Commands, Resource},
     world::{
         command_queue::RawCommandQueue,
         error::{EntityFetchError, TryRunScheduleError},
@@ -103,6 +104,7 @@ pub struct World {
     pub(crate) last_check_tick: Tick,
     pub(crate) last_trigger_id: u32,
     pub(crate) command_queue: RawCommandQueue,
+    pub(crate) default_query_filters: DefaultQueryFilters,
 }
 
 impl Default for World {
@@ -123,6 +125,7 @@ impl Default for World {
             last_check_tick: Tick::new(0),
             last_trigger_id: 0,
             command_queue: RawCommandQueue::new(),
+            default_query_filters: DefaultQueryFilters::default(),
         };
         world.bootstrap();
         world
@@ -159,6 +162,8 @@ impl World {
 
         let on_remove = OnRemove::register_component_id(self);
         assert_eq!(ON_REMOVE, on_remove);
+
+        self.register_disabling_component::<Disabled>();
     }
     /// Creates a new empty [`World`].
     ///
@@ -249,6 +254,24 @@ impl World {
         self.components.register_component::<T>(&mut self.storages)
     }
 
+    /// Registers a [`Component`] type that hides the entities that have it from queries that
+    /// don't explicitly ask for it, like [`Disabled`] does.
+    ///
+    /// Only affects queries that are initialized after this call.
+    /// See [`entity_disabling`](crate::entity_disabling) for more information.
+    pub fn register_disabling_component<C: Component>(&mut self) {
+        let component_id = self.register_component::<C>();
+        self.default_query_filters
+            .register_disabling_component(component_id);
+    }
+
+    /// Returns the [`DefaultQueryFilters`] that are applied to the queries created from this
+    /// world.
+    #[inline]
+    pub fn default_query_filters(&self) -> &DefaultQueryFilters {
+        &self.default_query_filters
+    }
+
     /// Returns a mutable reference to the [`ComponentHooks`] for a [`Component`] type.
     ///
     /// Will panic if `T` exists in any archetypes.
@@ -1361,6 +1384,7 @@ impl World {
     /// [`RemovedComponents`]: crate::removal_detection::RemovedComponents
     pub fn clear_trackers(&mut self) {
         self.removed_components.update();
+        CommandConflictDetection::clear_despawned(self);
         self.last_change_tick = self.increment_change_tick();
     }
 
@@ -2688,6 +2712,7 @@ impl World {
             ticks: TicksMut {
                 added: &mut ticks.added,
                 changed: &mut ticks.changed,
+                column_changed: None,
                 last_run: last_change_tick,
                 this_run: change_tick,
             },
@@ -11,7 +11,7 @@ use crate::{
     prelude::Component,
     query::{DebugCheckedUnwrap, ReadOnlyQueryData},
     removal_detection::RemovedComponentEvents,
-    storage::{ComponentSparseSet, Storages, Table},
+    storage::{ComponentSparseSet, Storages, Table, ThinColumn},
     system::Resource,
     world::RawCommandQueue,
 };
@@ -1140,6 +1140,9 @@ unsafe fn get_component_and_ticks(
                     changed: table
                         .get_changed_tick(component_id, location.table_row)
                         .debug_checked_unwrap(),
+                    column_changed: table
+                        .get_column(component_id)
+                        .map(ThinColumn::newest_changed_tick),
                 },
                 #[cfg(feature = "track_location")]
                 table
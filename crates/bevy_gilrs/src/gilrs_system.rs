@@ -8,11 +8,20 @@ use bevy_ecs::prelude::Commands;
 use bevy_ecs::system::NonSendMut;
 use bevy_ecs::system::ResMut;
 use bevy_input::gamepad::{
-    GamepadConnection, GamepadConnectionEvent, RawGamepadAxisChangedEvent,
+    GamepadCapabilities, GamepadConnection, GamepadConnectionEvent, RawGamepadAxisChangedEvent,
     RawGamepadButtonChangedEvent, RawGamepadEvent,
 };
 use gilrs::{ev::filter::axis_dpad_to_button, EventType, Filter};
 
+/// The features of `gamepad` that gilrs supports.
+fn capabilities(gamepad: &gilrs::Gamepad) -> GamepadCapabilities {
+    GamepadCapabilities {
+        rumble: gamepad.is_ff_supported(),
+        // gilrs can't drive adaptive triggers, so requests for them are ignored.
+        adaptive_triggers: false,
+    }
+}
+
 pub fn gilrs_event_startup_system(
     mut commands: Commands,
     #[cfg(target_arch = "wasm32")] mut gilrs: NonSendMut<Gilrs>,
@@ -22,7 +31,7 @@ pub fn gilrs_event_startup_system(
 ) {
     for (id, gamepad) in gilrs.0.get().gamepads() {
         // Create entity and add to mapping
-        let entity = commands.spawn_empty().id();
+        let entity = commands.spawn(capabilities(&gamepad)).id();
         gamepads.id_to_entity.insert(id, entity);
         gamepads.entity_to_id.insert(entity, id);
 
@@ -59,6 +68,7 @@ pub fn gilrs_event_system(
                     gamepads.entity_to_id.insert(entity, gilrs_event.id);
                     entity
                 });
+                commands.entity(entity).insert(capabilities(&pad));
 
                 let event = GamepadConnectionEvent::new(
                     entity,
@@ -3,29 +3,33 @@ use crate::{Gilrs, GilrsGamepads};
 use bevy_ecs::prelude::{EventReader, Res, ResMut, Resource};
 #[cfg(target_arch = "wasm32")]
 use bevy_ecs::system::NonSendMut;
-use bevy_input::gamepad::{GamepadRumbleIntensity, GamepadRumbleRequest};
+use bevy_input::gamepad::{GamepadRumbleIntensity, GamepadRumbleRequest, RumbleMixer};
 use bevy_time::{Real, Time};
 use bevy_utils::{synccell::SyncCell, HashMap};
-use core::time::Duration;
 use gilrs::{
-    ff::{self, BaseEffect, BaseEffectType, Repeat, Replay},
+    ff::{self, BaseEffect, BaseEffectType, Repeat},
     GamepadId,
 };
 use thiserror::Error;
 use tracing::{debug, warn};
 
-/// A rumble effect that is currently in effect.
-struct RunningRumble {
-    /// Duration from app startup when this effect will be finished
-    deadline: Duration,
-    /// A ref-counted handle to the specific force-feedback effect
-    ///
-    /// Dropping it will cause the effect to stop
-    #[expect(
-        dead_code,
-        reason = "We don't need to read this field, as its purpose is to keep the rumble effect going until the field is dropped."
-    )]
-    effect: SyncCell<ff::Effect>,
+/// The force-feedback motors of a gamepad that is rumbling.
+///
+/// Each motor is driven by an effect that plays at full magnitude forever, with its gain set to
+/// the intensity of the motor. Dropping the effects stops them.
+struct RumbleMotors {
+    strong: SyncCell<ff::Effect>,
+    weak: SyncCell<ff::Effect>,
+}
+
+/// The rumble effects of a gamepad, and the motors that play them.
+#[derive(Default)]
+struct GamepadRumble {
+    mixer: RumbleMixer,
+    /// The motors, created once the gamepad first rumbles.
+    motors: Option<RumbleMotors>,
+    /// The intensity that the motors were last set to.
+    intensity: Option<GamepadRumbleIntensity>,
 }
 
 #[derive(Error, Debug)]
@@ -36,95 +40,92 @@ enum RumbleError {
     GilrsError(#[from] ff::Error),
 }
 
-/// Contains the gilrs rumble effects that are currently running for each gamepad
+/// Contains the rumble effects that are currently running for each gamepad
 #[derive(Default, Resource)]
 pub(crate) struct RunningRumbleEffects {
     /// If multiple rumbles are running at the same time, their resulting rumble
-    /// will be the saturated sum of their strengths up until [`u16::MAX`]
-    rumbles: HashMap<GamepadId, Vec<RunningRumble>>,
+    /// will be the saturated sum of their strengths, as mixed by a [`RumbleMixer`]
+    rumbles: HashMap<GamepadId, GamepadRumble>,
 }
 
-/// gilrs uses magnitudes from 0 to [`u16::MAX`], while ours go from `0.0` to `1.0` ([`f32`])
-fn to_gilrs_magnitude(ratio: f32) -> u16 {
-    (ratio * u16::MAX as f32) as u16
+/// Creates an effect that plays `kind` on a gamepad forever, silent until its gain is set.
+fn motor_effect(
+    kind: BaseEffectType,
+    gilrs: &mut gilrs::Gilrs,
+    gamepad_id: GamepadId,
+) -> Result<ff::Effect, ff::Error> {
+    let effect = ff::EffectBuilder::new()
+        .add_effect(BaseEffect {
+            kind,
+            ..Default::default()
+        })
+        .repeat(Repeat::Infinitely)
+        .gamepads(&[gamepad_id])
+        .finish(gilrs)?;
+    effect.set_gain(0.0)?;
+    effect.play()?;
+    Ok(effect)
 }
 
-fn get_base_effects(
-    GamepadRumbleIntensity {
-        weak_motor,
-        strong_motor,
-    }: GamepadRumbleIntensity,
-    duration: Duration,
-) -> Vec<BaseEffect> {
-    let mut effects = Vec::new();
-    if strong_motor > 0. {
-        effects.push(BaseEffect {
-            kind: BaseEffectType::Strong {
-                magnitude: to_gilrs_magnitude(strong_motor),
-            },
-            scheduling: Replay {
-                play_for: duration.into(),
-                ..Default::default()
-            },
-            ..Default::default()
-        });
-    }
-    if weak_motor > 0. {
-        effects.push(BaseEffect {
-            kind: BaseEffectType::Strong {
-                magnitude: to_gilrs_magnitude(weak_motor),
+impl GamepadRumble {
+    /// Sets the motors of the gamepad to `intensity`, creating them if needed.
+    fn apply(
+        &mut self,
+        intensity: GamepadRumbleIntensity,
+        gilrs: &mut gilrs::Gilrs,
+        gamepad_id: GamepadId,
+    ) -> Result<(), RumbleError> {
+        if self.intensity == Some(intensity) {
+            return Ok(());
+        }
+        let motors = match self.motors.take() {
+            Some(motors) => motors,
+            None => RumbleMotors {
+                strong: SyncCell::new(motor_effect(
+                    BaseEffectType::Strong {
+                        magnitude: u16::MAX,
+                    },
+                    gilrs,
+                    gamepad_id,
+                )?),
+                weak: SyncCell::new(motor_effect(
+                    BaseEffectType::Weak {
+                        magnitude: u16::MAX,
+                    },
+                    gilrs,
+                    gamepad_id,
+                )?),
             },
-            ..Default::default()
-        });
+        };
+        let motors = self.motors.insert(motors);
+        motors.strong.get().set_gain(intensity.strong_motor)?;
+        motors.weak.get().set_gain(intensity.weak_motor)?;
+        self.intensity = Some(intensity);
+        Ok(())
     }
-    effects
 }
 
 fn handle_rumble_request(
     running_rumbles: &mut RunningRumbleEffects,
     gilrs: &mut gilrs::Gilrs,
     gamepads: &GilrsGamepads,
-    rumble: GamepadRumbleRequest,
-    current_time: Duration,
+    rumble: &GamepadRumbleRequest,
 ) -> Result<(), RumbleError> {
-    let gamepad = rumble.gamepad();
-
-    let (gamepad_id, _) = gilrs
-        .gamepads()
-        .find(|(pad_id, _)| *pad_id == gamepads.get_gamepad_id(gamepad).unwrap())
+    let gamepad_id = gamepads
+        .get_gamepad_id(rumble.gamepad())
+        .filter(|gamepad_id| gilrs.connected_gamepad(*gamepad_id).is_some())
         .ok_or(RumbleError::GamepadNotFound)?;
 
-    match rumble {
-        GamepadRumbleRequest::Stop { .. } => {
-            // `ff::Effect` uses RAII, dropping = deactivating
-            running_rumbles.rumbles.remove(&gamepad_id);
-        }
-        GamepadRumbleRequest::Add {
-            duration,
-            intensity,
-            ..
-        } => {
-            let mut effect_builder = ff::EffectBuilder::new();
-
-            for effect in get_base_effects(intensity, duration) {
-                effect_builder.add_effect(effect);
-                effect_builder.repeat(Repeat::For(duration.into()));
-            }
-
-            let effect = effect_builder.gamepads(&[gamepad_id]).finish(gilrs)?;
-            effect.play()?;
-
-            let gamepad_rumbles = running_rumbles.rumbles.entry(gamepad_id).or_default();
-            let deadline = current_time + duration;
-            gamepad_rumbles.push(RunningRumble {
-                deadline,
-                effect: SyncCell::new(effect),
-            });
-        }
-    }
+    running_rumbles
+        .rumbles
+        .entry(gamepad_id)
+        .or_default()
+        .mixer
+        .handle_request(rumble);
 
     Ok(())
 }
+
 pub(crate) fn play_gilrs_rumble(
     time: Res<Time<Real>>,
     #[cfg(target_arch = "wasm32")] mut gilrs: NonSendMut<Gilrs>,
@@ -134,53 +135,42 @@ pub(crate) fn play_gilrs_rumble(
     mut running_rumbles: ResMut<RunningRumbleEffects>,
 ) {
     let gilrs = gilrs.0.get();
-    let current_time = time.elapsed();
-    // Remove outdated rumble effects.
-    for rumbles in running_rumbles.rumbles.values_mut() {
-        // `ff::Effect` uses RAII, dropping = deactivating
-        rumbles.retain(|RunningRumble { deadline, .. }| *deadline >= current_time);
-    }
-    running_rumbles
-        .rumbles
-        .retain(|_gamepad, rumbles| !rumbles.is_empty());
 
-    // Add new effects.
-    for rumble in requests.read().cloned() {
+    // Add new effects, and stop the effects of gamepads that were asked to stop.
+    for rumble in requests.read() {
         let gamepad = rumble.gamepad();
-        match handle_rumble_request(&mut running_rumbles, gilrs, &gamepads, rumble, current_time) {
+        match handle_rumble_request(&mut running_rumbles, gilrs, &gamepads, rumble) {
             Ok(()) => {}
-            Err(RumbleError::GilrsError(err)) => {
-                if let ff::Error::FfNotSupported(_) = err {
-                    debug!("Tried to rumble {gamepad:?}, but it doesn't support force feedback");
-                } else {
-                    warn!(
-                    "Tried to handle rumble request for {gamepad:?} but an error occurred: {err}"
-                    );
-                }
-            }
             Err(RumbleError::GamepadNotFound) => {
                 warn!("Tried to handle rumble request {gamepad:?} but it doesn't exist!");
             }
+            Err(RumbleError::GilrsError(err)) => {
+                warn!(
+                    "Tried to handle rumble request for {gamepad:?} but an error occurred: {err}"
+                );
+            }
         };
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::to_gilrs_magnitude;
-
-    #[test]
-    fn magnitude_conversion() {
-        assert_eq!(to_gilrs_magnitude(1.0), u16::MAX);
-        assert_eq!(to_gilrs_magnitude(0.0), 0);
 
-        // bevy magnitudes of 2.0 don't really make sense, but just make sure
-        // they convert to something sensible in gilrs anyway.
-        assert_eq!(to_gilrs_magnitude(2.0), u16::MAX);
-
-        // negative bevy magnitudes don't really make sense, but just make sure
-        // they convert to something sensible in gilrs anyway.
-        assert_eq!(to_gilrs_magnitude(-1.0), 0);
-        assert_eq!(to_gilrs_magnitude(-0.1), 0);
-    }
+    // Mix the running effects of each gamepad into the intensity of its motors, and remove
+    // the gamepads whose effects are all finished.
+    let delta = time.delta();
+    running_rumbles.rumbles.retain(|gamepad_id, rumble| {
+        let intensity = rumble.mixer.advance(delta);
+        if rumble.mixer.is_empty() {
+            // `ff::Effect` uses RAII, dropping = deactivating
+            return false;
+        }
+        match rumble.apply(intensity, gilrs, *gamepad_id) {
+            Ok(()) => true,
+            Err(RumbleError::GilrsError(ff::Error::FfNotSupported(_))) => {
+                debug!("Tried to rumble {gamepad_id:?}, but it doesn't support force feedback");
+                false
+            }
+            Err(err) => {
+                warn!("Tried to rumble {gamepad_id:?} but an error occurred: {err}");
+                false
+            }
+        }
+    });
 }
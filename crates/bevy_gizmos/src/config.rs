@@ -87,7 +87,8 @@ pub trait GizmoConfigGroup: Reflect + TypePath + Default {}
 pub struct DefaultGizmoConfigGroup;
 
 /// Used when the gizmo config group needs to be type-erased.
-/// Also used for retained gizmos, which can't have a gizmo config group.
+/// Also used for the assets of retained gizmos, which are put in a config group with a
+/// [`GizmoLayer`](crate::retained::GizmoLayer) instead.
 #[derive(Default, Reflect, GizmoConfigGroup, Debug, Clone)]
 pub struct ErasedGizmoConfigGroup;
 
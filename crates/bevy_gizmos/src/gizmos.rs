@@ -21,6 +21,7 @@ use bevy_utils::default;
 use crate::{
     config::{DefaultGizmoConfigGroup, GizmoConfigGroup, GizmoConfigStore},
     prelude::GizmoConfig,
+    text::GizmoText,
 };
 
 /// Storage of gizmo primitives.
@@ -30,6 +31,7 @@ pub struct GizmoStorage<Config, Clear> {
     pub(crate) list_colors: Vec<LinearRgba>,
     pub(crate) strip_positions: Vec<Vec3>,
     pub(crate) strip_colors: Vec<LinearRgba>,
+    pub(crate) texts: Vec<GizmoText>,
     marker: PhantomData<(Config, Clear)>,
 }
 
@@ -40,6 +42,7 @@ impl<Config, Clear> Default for GizmoStorage<Config, Clear> {
             list_colors: default(),
             strip_positions: default(),
             strip_colors: default(),
+            texts: default(),
             marker: PhantomData,
         }
     }
@@ -59,6 +62,7 @@ where
         self.list_colors.extend(other.list_colors.iter());
         self.strip_positions.extend(other.strip_positions.iter());
         self.strip_colors.extend(other.strip_colors.iter());
+        self.texts.extend(other.texts.iter().cloned());
     }
 
     pub(crate) fn swap<OtherConfig, OtherClear>(
@@ -69,6 +73,7 @@ where
         mem::swap(&mut self.list_colors, &mut other.list_colors);
         mem::swap(&mut self.strip_positions, &mut other.strip_positions);
         mem::swap(&mut self.strip_colors, &mut other.strip_colors);
+        mem::swap(&mut self.texts, &mut other.texts);
     }
 
     /// Clear this gizmo storage of any requested gizmos.
@@ -77,6 +82,7 @@ where
         self.list_colors.clear();
         self.strip_positions.clear();
         self.strip_colors.clear();
+        self.texts.clear();
     }
 }
 
@@ -284,6 +290,8 @@ where
     pub(crate) strip_positions: Vec<Vec3>,
     pub(crate) strip_colors: Vec<LinearRgba>,
     #[reflect(ignore)]
+    pub(crate) texts: Vec<GizmoText>,
+    #[reflect(ignore)]
     pub(crate) marker: PhantomData<(Config, Clear)>,
 }
 
@@ -299,6 +307,7 @@ where
             list_colors: Vec::new(),
             strip_positions: Vec::new(),
             strip_colors: Vec::new(),
+            texts: Vec::new(),
             marker: PhantomData,
         }
     }
@@ -327,6 +336,7 @@ where
         storage.list_colors.append(&mut self.list_colors);
         storage.strip_positions.append(&mut self.strip_positions);
         storage.strip_colors.append(&mut self.strip_colors);
+        storage.texts.append(&mut self.texts);
     }
 }
 
@@ -341,6 +351,7 @@ where
         self.list_colors.clear();
         self.strip_positions.clear();
         self.strip_colors.clear();
+        self.texts.clear();
     }
 
     /// Read-only view into the buffers data.
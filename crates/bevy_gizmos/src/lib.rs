@@ -43,6 +43,7 @@ pub mod grid;
 pub mod primitives;
 pub mod retained;
 pub mod rounded_box;
+pub mod text;
 
 #[cfg(all(feature = "bevy_pbr", feature = "bevy_render"))]
 pub mod light;
@@ -67,7 +68,8 @@ pub mod prelude {
         },
         gizmos::Gizmos,
         primitives::{dim2::GizmoPrimitive2d, dim3::GizmoPrimitive3d},
-        retained::Gizmo,
+        retained::{Gizmo, GizmoLayer, GizmoLifetime, RetainedGizmos},
+        text::GizmoTextStyle,
         AppGizmoBuilder, GizmoAsset,
     };
 
@@ -164,7 +166,9 @@ impl Plugin for GizmoPlugin {
         app.register_type::<GizmoConfig>()
             .register_type::<GizmoConfigStore>()
             .init_asset::<GizmoAsset>()
+            .register_type::<retained::GizmoLifetime>()
             .init_resource::<GizmoHandles>()
+            .add_systems(Last, retained::despawn_expired_gizmos)
             // We insert the Resource GizmoConfigStore into the world implicitly here if it does not exist.
             .init_gizmo_group::<DefaultGizmoConfigGroup>();
 
@@ -275,7 +279,12 @@ impl AppGizmoBuilder for App {
             .add_systems(
                 Last,
                 (
-                    propagate_gizmos::<Config, Fixed>.before(UpdateGizmoMeshes),
+                    (
+                        propagate_gizmos::<Config, Fixed>,
+                        text::draw_gizmo_texts::<Config>,
+                    )
+                        .chain()
+                        .before(UpdateGizmoMeshes),
                     update_gizmo_meshes::<Config>.in_set(UpdateGizmoMeshes),
                 ),
             );
@@ -404,6 +413,7 @@ fn update_gizmo_meshes<Config: GizmoConfigGroup>(
                     list_colors: mem::take(&mut storage.list_colors),
                     strip_positions: mem::take(&mut storage.strip_positions),
                     strip_colors: mem::take(&mut storage.strip_colors),
+                    texts: Vec::new(),
                     marker: PhantomData,
                 },
             };
@@ -1,28 +1,35 @@
 //! This module is for 'retained' alternatives to the 'immediate mode' [`Gizmos`](crate::gizmos::Gizmos) system parameter.
 
-use core::ops::{Deref, DerefMut};
+use core::{
+    any::TypeId,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
 
-use bevy_asset::Handle;
+use bevy_asset::{Assets, Handle};
 use bevy_ecs::{
     component::{require, Component},
+    entity::Entity,
     reflect::ReflectComponent,
+    system::{Commands, EntityCommands, Query, Res, ResMut, SystemParam},
 };
 use bevy_reflect::Reflect;
+use bevy_time::{Time, Timer, TimerMode};
 use bevy_transform::components::Transform;
 
 #[cfg(feature = "bevy_render")]
 use {
     crate::{config::GizmoLineJoint, LineGizmoUniform},
-    bevy_ecs::{
-        entity::Entity,
-        system::{Commands, Local, Query},
-    },
+    bevy_ecs::system::Local,
     bevy_render::{view::RenderLayers, Extract},
     bevy_transform::components::GlobalTransform,
 };
 
 use crate::{
-    config::{ErasedGizmoConfigGroup, GizmoLineConfig},
+    config::{
+        DefaultGizmoConfigGroup, ErasedGizmoConfigGroup, GizmoConfigGroup, GizmoConfigStore,
+        GizmoLineConfig,
+    },
     gizmos::GizmoBuffer,
     GizmoAsset,
 };
@@ -98,11 +105,152 @@ pub struct Gizmo {
     pub depth_bias: f32,
 }
 
+/// Puts a retained [`Gizmo`] in the layer of a [`GizmoConfigGroup`].
+///
+/// The gizmos of a layer are drawn with the `enabled`, `depth_bias` and `render_layers` of the
+/// [`GizmoConfig`](crate::config::GizmoConfig) of the group, instead of the settings of each
+/// gizmo. A `RenderLayers` component on the gizmo entity still takes precedence over the
+/// `render_layers` of the group. This allows toggling a whole layer of debug drawing, or drawing it on top of the scene
+/// with a `depth_bias` of `-1`, from the [`GizmoConfigStore`].
+///
+/// Gizmos spawned with [`RetainedGizmos`] are put in the layer of its config group.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GizmoLayer {
+    config_ty: TypeId,
+}
+
+impl GizmoLayer {
+    /// The layer of the config group `Config`.
+    pub fn of<Config: GizmoConfigGroup>() -> Self {
+        Self {
+            config_ty: TypeId::of::<Config>(),
+        }
+    }
+
+    /// The type of the layer's configuration group.
+    pub fn config_typeid(&self) -> TypeId {
+        self.config_ty
+    }
+}
+
+/// Despawns the entity of a retained [`Gizmo`] when the timer finishes.
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component)]
+pub struct GizmoLifetime(pub Timer);
+
+impl GizmoLifetime {
+    /// A lifetime of `seconds` seconds.
+    pub fn from_seconds(seconds: f32) -> Self {
+        Self(Timer::from_seconds(seconds, TimerMode::Once))
+    }
+}
+
+/// Ticks the [`GizmoLifetime`]s of gizmos, and despawns the gizmos whose lifetime ended.
+pub fn despawn_expired_gizmos(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut gizmos: Query<(Entity, &mut GizmoLifetime)>,
+) {
+    for (entity, mut lifetime) in &mut gizmos {
+        if lifetime.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// A system parameter that spawns retained [`Gizmo`]s in the [`GizmoLayer`] of `Config`.
+///
+/// Unlike the lines drawn with [`Gizmos`], which only last a frame, these gizmos persist until
+/// their entity is despawned or their [`GizmoLifetime`] ends. This suits debug drawing from
+/// systems that run rarely, such as on events or in a fixed schedule.
+///
+/// ## Example
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_gizmos::{prelude::*, retained::RetainedGizmos};
+/// # use bevy_color::palettes::css::*;
+/// # use bevy_math::prelude::*;
+/// #[derive(Event)]
+/// struct Explosion(Vec3);
+///
+/// fn draw_explosions(mut explosions: EventReader<Explosion>, mut gizmos: RetainedGizmos) {
+///     for explosion in explosions.read() {
+///         gizmos.spawn_timed(2., |gizmo| {
+///             gizmo.sphere(explosion.0, 1., RED);
+///         });
+///     }
+/// }
+/// # bevy_ecs::system::assert_is_system(draw_explosions);
+/// ```
+///
+/// [`Gizmos`]: crate::gizmos::Gizmos
+#[derive(SystemParam)]
+pub struct RetainedGizmos<'w, 's, Config = DefaultGizmoConfigGroup>
+where
+    Config: GizmoConfigGroup,
+{
+    commands: Commands<'w, 's>,
+    gizmo_assets: ResMut<'w, Assets<GizmoAsset>>,
+    config_store: Res<'w, GizmoConfigStore>,
+    marker: PhantomData<Config>,
+}
+
+impl<Config: GizmoConfigGroup> RetainedGizmos<'_, '_, Config> {
+    /// Spawns a gizmo with the lines drawn by `draw`, that persists until it is despawned.
+    ///
+    /// The line settings of the gizmo are copied from the [`GizmoConfig`](crate::config::GizmoConfig)
+    /// of `Config`.
+    pub fn spawn(&mut self, draw: impl FnOnce(&mut GizmoAsset)) -> EntityCommands<'_> {
+        let mut gizmo = GizmoAsset::new();
+        draw(&mut gizmo);
+        let (config, _) = self.config_store.config::<Config>();
+        let gizmo = Gizmo {
+            handle: self.gizmo_assets.add(gizmo),
+            line_config: config.line.clone(),
+            depth_bias: config.depth_bias,
+        };
+        self.commands.spawn((gizmo, GizmoLayer::of::<Config>()))
+    }
+
+    /// Spawns a gizmo with the lines drawn by `draw`, that is despawned after `seconds` seconds.
+    pub fn spawn_timed(
+        &mut self,
+        seconds: f32,
+        draw: impl FnOnce(&mut GizmoAsset),
+    ) -> EntityCommands<'_> {
+        let mut entity = self.spawn(draw);
+        entity.insert(GizmoLifetime::from_seconds(seconds));
+        entity
+    }
+}
+
+/// Returns the render layers of a retained gizmo: its own [`RenderLayers`] if it has any, otherwise
+/// the render layers of its [`GizmoLayer`].
+#[cfg(feature = "bevy_render")]
+fn gizmo_render_layers(
+    render_layers: Option<&RenderLayers>,
+    layer_config: Option<&crate::config::GizmoConfig>,
+) -> RenderLayers {
+    render_layers
+        .or(layer_config.map(|config| &config.render_layers))
+        .cloned()
+        .unwrap_or_default()
+}
+
 #[cfg(feature = "bevy_render")]
 pub(crate) fn extract_linegizmos(
     mut commands: Commands,
     mut previous_len: Local<usize>,
-    query: Extract<Query<(Entity, &Gizmo, &GlobalTransform, Option<&RenderLayers>)>>,
+    config_store: Extract<Res<GizmoConfigStore>>,
+    query: Extract<
+        Query<(
+            Entity,
+            &Gizmo,
+            &GlobalTransform,
+            Option<&RenderLayers>,
+            Option<&GizmoLayer>,
+        )>,
+    >,
 ) {
     use bevy_math::Affine3;
     use bevy_render::sync_world::{MainEntity, TemporaryRenderEntity};
@@ -112,7 +260,14 @@ pub(crate) fn extract_linegizmos(
     use crate::config::GizmoLineStyle;
 
     let mut values = Vec::with_capacity(*previous_len);
-    for (entity, gizmo, transform, render_layers) in &query {
+    for (entity, gizmo, transform, render_layers, layer) in &query {
+        let layer_config = layer
+            .and_then(|layer| config_store.get_config_dyn(&layer.config_ty))
+            .map(|(config, _)| config);
+        if layer_config.is_some_and(|config| !config.enabled) {
+            continue;
+        }
+
         let joints_resolution = if let GizmoLineJoint::Round(resolution) = gizmo.line_config.joints
         {
             resolution
@@ -139,7 +294,7 @@ pub(crate) fn extract_linegizmos(
             LineGizmoUniform {
                 world_from_local: Affine3::from(&transform.affine()).to_transpose(),
                 line_width: gizmo.line_config.width,
-                depth_bias: gizmo.depth_bias,
+                depth_bias: layer_config.map_or(gizmo.depth_bias, |config| config.depth_bias),
                 joints_resolution,
                 gap_scale,
                 line_scale,
@@ -151,7 +306,7 @@ pub(crate) fn extract_linegizmos(
                 line_perspective: gizmo.line_config.perspective,
                 line_style: gizmo.line_config.style,
                 line_joints: gizmo.line_config.joints,
-                render_layers: render_layers.cloned().unwrap_or_default(),
+                render_layers: gizmo_render_layers(render_layers, layer_config),
                 handle: gizmo.handle.clone_weak(),
             },
             MainEntity::from(entity),
@@ -161,3 +316,94 @@ pub(crate) fn extract_linegizmos(
     *previous_len = values.len();
     commands.spawn_batch(values);
 }
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use bevy_ecs::{schedule::Schedule, world::World};
+
+    use super::*;
+    use crate::config::GizmoConfig;
+
+    #[derive(Default, Reflect, Debug, Clone)]
+    struct TestLayer;
+
+    impl GizmoConfigGroup for TestLayer {}
+
+    fn setup() -> World {
+        let mut world = World::new();
+        world.init_resource::<Assets<GizmoAsset>>();
+        world.init_resource::<Time>();
+        let mut config_store = GizmoConfigStore::default();
+        config_store.insert(
+            GizmoConfig {
+                depth_bias: -1.,
+                ..Default::default()
+            },
+            TestLayer,
+        );
+        world.insert_resource(config_store);
+        world
+    }
+
+    #[test]
+    fn retained_gizmos_are_spawned_in_their_layer() {
+        let mut world = setup();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(|mut gizmos: RetainedGizmos<TestLayer>| {
+            gizmos.spawn(|_| {});
+        });
+        schedule.run(&mut world);
+
+        let mut query = world.query::<(&Gizmo, &GizmoLayer)>();
+        let (gizmo, layer) = query.single(&world);
+        assert_eq!(*layer, GizmoLayer::of::<TestLayer>());
+        assert_eq!(layer.config_typeid(), TypeId::of::<TestLayer>());
+        assert_ne!(*layer, GizmoLayer::of::<DefaultGizmoConfigGroup>());
+        assert_eq!(gizmo.depth_bias, -1.);
+    }
+
+    #[test]
+    fn timed_gizmos_are_despawned_when_their_lifetime_ends() {
+        let mut world = setup();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(despawn_expired_gizmos);
+        let timed = world
+            .spawn((Gizmo::default(), GizmoLifetime::from_seconds(1.)))
+            .id();
+        let retained = world.spawn(Gizmo::default()).id();
+
+        world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_millis(600));
+        schedule.run(&mut world);
+        assert!(world.get_entity(timed).is_ok());
+
+        world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_millis(600));
+        schedule.run(&mut world);
+        assert!(world.get_entity(timed).is_err());
+        assert!(world.get_entity(retained).is_ok());
+    }
+
+    #[cfg(feature = "bevy_render")]
+    #[test]
+    fn gizmo_render_layers_override_their_layer() {
+        let config = GizmoConfig {
+            render_layers: RenderLayers::layer(1),
+            ..Default::default()
+        };
+        let own_layers = RenderLayers::layer(2);
+        assert_eq!(
+            gizmo_render_layers(Some(&own_layers), Some(&config)),
+            own_layers
+        );
+        assert_eq!(
+            gizmo_render_layers(None, Some(&config)),
+            RenderLayers::layer(1)
+        );
+        assert_eq!(gizmo_render_layers(None, None), RenderLayers::default());
+    }
+}
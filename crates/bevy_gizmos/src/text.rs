@@ -0,0 +1,396 @@
+//! Additional [`Gizmos`] Functions -- Text
+//!
+//! Includes the implementation of [`Gizmos::text`],
+//! and assorted support items.
+
+use core::mem;
+
+use bevy_color::{Color, LinearRgba};
+use bevy_ecs::system::{Res, ResMut};
+use bevy_math::{Vec2, Vec3};
+
+use crate::{
+    config::{GizmoConfigGroup, GizmoConfigStore},
+    gizmos::{GizmoStorage, Gizmos},
+};
+
+#[cfg(feature = "bevy_render")]
+use {
+    bevy_ecs::system::Query,
+    bevy_render::{camera::Camera, view::RenderLayers},
+    bevy_transform::components::GlobalTransform,
+};
+
+/// The width of a glyph, in glyph units.
+const GLYPH_WIDTH: f32 = 4.;
+/// The height of a capital letter, in glyph units.
+const GLYPH_HEIGHT: f32 = 6.;
+/// The horizontal distance between the start of two glyphs, in glyph units.
+const GLYPH_ADVANCE: f32 = GLYPH_WIDTH + 1.;
+/// The vertical distance between the bottom of two lines, in glyph units.
+const LINE_ADVANCE: f32 = GLYPH_HEIGHT + 3.;
+
+/// The style of a text gizmo, drawn with [`Gizmos::text`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GizmoTextStyle {
+    /// The height of the capital letters, in world units.
+    ///
+    /// Defaults to `0.25`.
+    pub size: f32,
+    /// The color of the text.
+    ///
+    /// Defaults to white.
+    pub color: Color,
+    /// The point of the text that is at the position it's drawn at, from `(-0.5, -0.5)` for the
+    /// bottom left corner to `(0.5, 0.5)` for the top right corner.
+    ///
+    /// Defaults to the center of the text.
+    pub anchor: Vec2,
+}
+
+impl Default for GizmoTextStyle {
+    fn default() -> Self {
+        Self {
+            size: 0.25,
+            color: Color::WHITE,
+            anchor: Vec2::ZERO,
+        }
+    }
+}
+
+impl GizmoTextStyle {
+    /// A style with the given size and color, centered on the position of the text.
+    pub fn new(size: f32, color: impl Into<Color>) -> Self {
+        Self {
+            size,
+            color: color.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns this style with the given anchor.
+    pub fn with_anchor(mut self, anchor: Vec2) -> Self {
+        self.anchor = anchor;
+        self
+    }
+}
+
+/// A text gizmo waiting to be turned into lines facing the camera.
+#[derive(Debug, Clone)]
+pub(crate) struct GizmoText {
+    position: Vec3,
+    text: String,
+    style: GizmoTextStyle,
+}
+
+impl<Config, Clear> Gizmos<'_, '_, Config, Clear>
+where
+    Config: GizmoConfigGroup,
+    Clear: 'static + Send + Sync,
+{
+    /// Draw a text label at `position`, facing the camera.
+    ///
+    /// The text is drawn with lines in a simple built-in font, which has the digits, the letters
+    /// and the common punctuation of ASCII. Lowercase letters are drawn as uppercase, other
+    /// characters as `?`, and `\n` starts a new line.
+    ///
+    /// The text faces the active camera of highest order that renders the gizmos.
+    ///
+    /// This should be called for each frame the text needs to be rendered.
+    ///
+    /// # Example
+    /// ```
+    /// # use bevy_ecs::prelude::*;
+    /// # use bevy_gizmos::{prelude::*, text::GizmoTextStyle};
+    /// # use bevy_math::prelude::*;
+    /// # use bevy_color::palettes::basic::{GREEN, YELLOW};
+    /// # use bevy_transform::prelude::*;
+    /// #[derive(Component)]
+    /// struct Health(u32);
+    ///
+    /// fn system(mut gizmos: Gizmos, query: Query<(&Health, &GlobalTransform)>) {
+    ///     for (health, transform) in &query {
+    ///         let position = transform.translation();
+    ///         gizmos.sphere(position, 0.5, GREEN);
+    ///         gizmos.text(
+    ///             position + Vec3::Y,
+    ///             format!("HP {}", health.0),
+    ///             GizmoTextStyle::new(0.2, YELLOW),
+    ///         );
+    ///     }
+    /// }
+    /// # bevy_ecs::system::assert_is_system(system);
+    /// ```
+    pub fn text(&mut self, position: Vec3, text: impl Into<String>, style: GizmoTextStyle) {
+        if !self.enabled {
+            return;
+        }
+        self.texts.push(GizmoText {
+            position,
+            text: text.into(),
+            style,
+        });
+    }
+}
+
+/// Turns the text gizmos of `Config` into lines facing the camera.
+///
+/// This must run after the contextual gizmos are propagated, and before
+/// [`UpdateGizmoMeshes`](crate::UpdateGizmoMeshes).
+pub fn draw_gizmo_texts<Config: GizmoConfigGroup>(
+    mut storage: ResMut<GizmoStorage<Config, ()>>,
+    config_store: Res<GizmoConfigStore>,
+    #[cfg(feature = "bevy_render")] cameras: Query<(
+        &Camera,
+        &GlobalTransform,
+        Option<&RenderLayers>,
+    )>,
+) {
+    if storage.texts.is_empty() {
+        return;
+    }
+
+    let (right, up) = (Vec3::X, Vec3::Y);
+
+    #[cfg(feature = "bevy_render")]
+    let (right, up) = {
+        let (config, _) = config_store.config::<Config>();
+        cameras
+            .iter()
+            .filter(|(camera, _, render_layers)| {
+                camera.is_active
+                    && render_layers
+                        .cloned()
+                        .unwrap_or_default()
+                        .intersects(&config.render_layers)
+            })
+            .max_by_key(|(camera, ..)| camera.order)
+            .map_or((right, up), |(_, transform, _)| {
+                (*transform.right(), *transform.up())
+            })
+    };
+    #[cfg(not(feature = "bevy_render"))]
+    let _ = config_store;
+
+    for text in mem::take(&mut storage.texts) {
+        let scale = text.style.size / GLYPH_HEIGHT;
+        let offset = (text.style.anchor + 0.5) * text_size(&text.text);
+        let color = LinearRgba::from(text.style.color);
+        for stroke in text_strokes(&text.text) {
+            let start = storage.strip_positions.len();
+            storage.strip_positions.extend(stroke.map(|point| {
+                let point = (point - offset) * scale;
+                text.position + right * point.x + up * point.y
+            }));
+            storage.strip_positions.push(Vec3::NAN);
+            let len = storage.strip_positions.len() - start;
+            storage
+                .strip_colors
+                .extend(core::iter::repeat(color).take(len - 1));
+            storage.strip_colors.push(LinearRgba::NAN);
+        }
+    }
+}
+
+/// The size of a text, in glyph units.
+fn text_size(text: &str) -> Vec2 {
+    let lines = text.lines().count().max(1);
+    let columns = text.lines().map(|line| line.chars().count()).max();
+    let columns = columns.unwrap_or(0).max(1);
+    Vec2::new(
+        columns as f32 * GLYPH_ADVANCE - (GLYPH_ADVANCE - GLYPH_WIDTH),
+        lines as f32 * LINE_ADVANCE - (LINE_ADVANCE - GLYPH_HEIGHT),
+    )
+}
+
+/// The strokes of the glyphs of a text, in glyph units from the bottom left corner of the text.
+fn text_strokes(text: &str) -> impl Iterator<Item = impl Iterator<Item = Vec2> + '_> + '_ {
+    let top = text_size(text).y - GLYPH_HEIGHT;
+    text.lines().enumerate().flat_map(move |(row, line)| {
+        line.chars().enumerate().flat_map(move |(column, c)| {
+            let origin = Vec2::new(
+                column as f32 * GLYPH_ADVANCE,
+                top - row as f32 * LINE_ADVANCE,
+            );
+            glyph(c)
+                .split(' ')
+                .filter(|stroke| !stroke.is_empty())
+                .map(move |stroke| {
+                    stroke.as_bytes().chunks_exact(2).map(move |point| {
+                        origin + Vec2::new((point[0] - b'0') as f32, (point[1] - b'0') as f32)
+                    })
+                })
+        })
+    })
+}
+
+/// The strokes of a glyph, separated by spaces.
+///
+/// Each stroke is a line through points on a grid of 5 by 7 points, with each point written as
+/// its column and row digits from the bottom left corner.
+fn glyph(c: char) -> &'static str {
+    match c.to_ascii_uppercase() {
+        ' ' => "",
+        '0' => "0040460600 0046",
+        '1' => "152620 1030",
+        '2' => "05163645440040",
+        '3' => "05163645443313 334241301001",
+        '4' => "30360242",
+        '5' => "4606033342413000",
+        '6' => "46160501103041423303",
+        '7' => "064610",
+        '8' => "13040516364544331302011030414233",
+        '9' => "43130405163645413000",
+        'A' => "0004264440 0242",
+        'B' => "00063645443303 3342413000",
+        'C' => "4536160501103041",
+        'D' => "00062644422000",
+        'E' => "46060040 0333",
+        'F' => "460600 0333",
+        'G' => "45361605011030414323",
+        'H' => "0006 4640 0343",
+        'I' => "1636 2620 1030",
+        'J' => "4641301001",
+        'K' => "0006 4602 1340",
+        'L' => "060040",
+        'M' => "0006244640",
+        'N' => "00064046",
+        'O' => "100105163645413010",
+        'P' => "00063645443303",
+        'Q' => "100105163645413010 2240",
+        'R' => "00063645443303 2340",
+        'S' => "453616050413334241301001",
+        'T' => "0646 2620",
+        'U' => "060110304146",
+        'V' => "062046",
+        'W' => "0610233046",
+        'X' => "0046 0640",
+        'Y' => "0623 4623 2320",
+        'Z' => "06464000",
+        '.' => "2021",
+        ',' => "2110",
+        ':' => "2425 2122",
+        ';' => "2425 2210",
+        '-' => "1333",
+        '+' => "0343 2125",
+        '=' => "0444 0242",
+        '_' => "0040",
+        '/' => "0046",
+        '\\' => "0640",
+        '(' => "36252130",
+        ')' => "16252110",
+        '[' => "36161030",
+        ']' => "16363010",
+        '<' => "450341",
+        '>' => "054301",
+        '!' => "2622 2021",
+        '\'' => "2625",
+        '"' => "1615 3635",
+        '#' => "1115 3135 0444 0242",
+        '%' => "0046 0515 3141",
+        '*' => "1135 1531 0343",
+        '|' => "2620",
+        _ => "05163645442322 2021",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::{schedule::Schedule, world::World};
+
+    use super::*;
+    use crate::config::{DefaultGizmoConfigGroup, GizmoConfig};
+
+    #[test]
+    fn glyphs_are_on_the_grid() {
+        for c in (' '..='~').chain(['é', '\u{a0}']) {
+            for stroke in glyph(c).split(' ').filter(|stroke| !stroke.is_empty()) {
+                assert!(
+                    stroke.len() >= 4 && stroke.len() % 2 == 0,
+                    "{c:?} has an invalid stroke {stroke:?}"
+                );
+                for point in stroke.as_bytes().chunks_exact(2) {
+                    assert!(
+                        (b'0'..=b'4').contains(&point[0]) && (b'0'..=b'6').contains(&point[1]),
+                        "{c:?} has a point outside of the grid in {stroke:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn lowercase_and_unknown_characters() {
+        assert_eq!(glyph('a'), glyph('A'));
+        assert_eq!(glyph('z'), glyph('Z'));
+        assert_eq!(glyph('é'), glyph('?'));
+        assert_eq!(glyph('~'), glyph('?'));
+        assert!(glyph(' ').is_empty());
+    }
+
+    #[test]
+    fn text_sizes() {
+        assert_eq!(text_size(""), Vec2::new(GLYPH_WIDTH, GLYPH_HEIGHT));
+        assert_eq!(text_size("A"), Vec2::new(GLYPH_WIDTH, GLYPH_HEIGHT));
+        assert_eq!(
+            text_size("ABC"),
+            Vec2::new(2. * GLYPH_ADVANCE + GLYPH_WIDTH, GLYPH_HEIGHT)
+        );
+        assert_eq!(
+            text_size("AB\nC"),
+            Vec2::new(GLYPH_ADVANCE + GLYPH_WIDTH, LINE_ADVANCE + GLYPH_HEIGHT)
+        );
+    }
+
+    #[test]
+    fn strokes_are_placed_by_line_and_column() {
+        let strokes: Vec<Vec<Vec2>> = text_strokes("-\n -").map(Iterator::collect).collect();
+        assert_eq!(
+            strokes,
+            vec![
+                vec![
+                    Vec2::new(1., LINE_ADVANCE + 3.),
+                    Vec2::new(3., LINE_ADVANCE + 3.)
+                ],
+                vec![
+                    Vec2::new(GLYPH_ADVANCE + 1., 3.),
+                    Vec2::new(GLYPH_ADVANCE + 3., 3.)
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn texts_are_drawn_as_line_strips() {
+        let mut world = World::new();
+        let mut config_store = GizmoConfigStore::default();
+        config_store.insert(GizmoConfig::default(), DefaultGizmoConfigGroup);
+        world.insert_resource(config_store);
+        world.init_resource::<GizmoStorage<DefaultGizmoConfigGroup, ()>>();
+        #[cfg(feature = "bevy_render")]
+        world.spawn((Camera::default(), GlobalTransform::IDENTITY));
+
+        let style = GizmoTextStyle::new(GLYPH_HEIGHT, Color::WHITE).with_anchor(Vec2::splat(-0.5));
+        world
+            .resource_mut::<GizmoStorage<DefaultGizmoConfigGroup, ()>>()
+            .texts
+            .push(GizmoText {
+                position: Vec3::Z,
+                text: "T".into(),
+                style,
+            });
+        let mut schedule = Schedule::default();
+        schedule.add_systems(draw_gizmo_texts::<DefaultGizmoConfigGroup>);
+        schedule.run(&mut world);
+
+        // `T` has two strokes, each ended by a NaN that separates the strips.
+        let storage = world.resource::<GizmoStorage<DefaultGizmoConfigGroup, ()>>();
+        assert!(storage.texts.is_empty());
+        assert_eq!(storage.strip_positions.len(), 6);
+        assert_eq!(storage.strip_colors.len(), 6);
+        assert_eq!(storage.strip_positions[0], Vec3::new(0., 6., 1.));
+        assert_eq!(storage.strip_positions[1], Vec3::new(4., 6., 1.));
+        assert!(storage.strip_positions[2].is_nan());
+        assert!(storage.strip_colors[5].red.is_nan());
+    }
+}
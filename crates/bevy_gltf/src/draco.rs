@@ -0,0 +1,244 @@
+//! Support for primitives compressed with the
+//! [`KHR_draco_mesh_compression`](https://github.com/KhronosGroup/glTF/blob/main/extensions/2.0/Khronos/KHR_draco_mesh_compression/README.md)
+//! extension.
+
+use alloc::sync::Arc;
+use bevy_utils::HashMap;
+use gltf::{
+    accessor::{util::ItemIter, DataType, Dimensions, Iter},
+    json, Accessor, Document, Primitive,
+};
+use serde::Deserialize;
+
+/// The name of the `KHR_draco_mesh_compression` extension.
+pub const KHR_DRACO_MESH_COMPRESSION: &str = "KHR_draco_mesh_compression";
+
+/// The error returned by a [`DracoDecoder`].
+pub type DracoDecodeError = Box<dyn core::error::Error + Send + Sync + 'static>;
+
+/// Decompresses the Draco-compressed geometry of glTF primitives.
+///
+/// Bevy doesn't ship a Draco decoder itself. Register one with
+/// [`GltfPlugin::with_draco_decoder`](crate::GltfPlugin::with_draco_decoder),
+/// typically by wrapping a Draco decoding library, so that the [`GltfLoader`](crate::GltfLoader)
+/// can load primitives that use the `KHR_draco_mesh_compression` extension.
+///
+/// Without a decoder, such primitives fall back to their uncompressed data if
+/// the file provides any, and fail to load with
+/// [`GltfError::MissingDracoDecoder`](crate::GltfError::MissingDracoDecoder)
+/// otherwise.
+///
+/// The attributes of morph targets can be compressed too, by listing their
+/// unique IDs in the `targets` array of the extension, which mirrors the
+/// `targets` array of the primitive.
+pub trait DracoDecoder: Send + Sync + 'static {
+    /// Decodes a Draco-compressed mesh.
+    ///
+    /// `data` is the content of the buffer view referenced by the extension,
+    /// and `attributes` lists the attributes that the primitive reads from it.
+    fn decode(
+        &self,
+        data: &[u8],
+        attributes: &[DracoAttributeRequest],
+    ) -> Result<DracoMesh, DracoDecodeError>;
+}
+
+/// An attribute that the [`DracoDecoder`] needs to output.
+#[derive(Clone, Copy, Debug)]
+pub struct DracoAttributeRequest {
+    /// The unique ID of the attribute in the Draco-compressed data.
+    pub unique_id: u32,
+    /// The component type of the glTF accessor of the attribute.
+    pub data_type: DataType,
+    /// The number of components of the glTF accessor of the attribute.
+    pub dimensions: Dimensions,
+    /// Whether the glTF accessor of the attribute is normalized.
+    pub normalized: bool,
+    /// The number of vertices of the glTF accessor of the attribute.
+    pub count: usize,
+}
+
+impl DracoAttributeRequest {
+    fn new(unique_id: u32, accessor: &Accessor) -> Self {
+        Self {
+            unique_id,
+            data_type: accessor.data_type(),
+            dimensions: accessor.dimensions(),
+            normalized: accessor.normalized(),
+            count: accessor.count(),
+        }
+    }
+}
+
+/// The geometry of a primitive decoded by a [`DracoDecoder`].
+#[derive(Clone, Debug, Default)]
+pub struct DracoMesh {
+    /// The vertex indices of the primitive.
+    pub indices: Vec<u32>,
+    /// The values of each requested attribute, keyed by
+    /// [`DracoAttributeRequest::unique_id`].
+    ///
+    /// Each value is stored in the format of the attribute's glTF accessor, and
+    /// the values are tightly packed, as if they were read from a buffer view
+    /// without a stride.
+    pub attributes: HashMap<u32, Vec<u8>>,
+}
+
+/// The `KHR_draco_mesh_compression` extension object of a primitive.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DracoExtension {
+    buffer_view: usize,
+    attributes: HashMap<String, u32>,
+    #[serde(default)]
+    targets: Vec<HashMap<String, u32>>,
+}
+
+/// A primitive decoded by a [`DracoDecoder`], along with the attribute IDs
+/// needed to look up its values.
+pub(crate) struct DecodedPrimitive {
+    mesh: DracoMesh,
+    attribute_ids: HashMap<String, u32>,
+    target_ids: Vec<HashMap<String, u32>>,
+}
+
+/// The position, normal and tangent displacements of a morph target.
+pub(crate) type MorphTargetIters<'a> = (
+    Option<Iter<'a, [f32; 3]>>,
+    Option<Iter<'a, [f32; 3]>>,
+    Option<Iter<'a, [f32; 3]>>,
+);
+
+impl DecodedPrimitive {
+    /// Returns the decoded values of the attribute with the given glTF name,
+    /// e.g. `POSITION` or `TEXCOORD_0`.
+    pub(crate) fn attribute(&self, name: &str) -> Option<&[u8]> {
+        let unique_id = self.attribute_ids.get(name)?;
+        self.mesh.attributes.get(unique_id).map(Vec::as_slice)
+    }
+
+    /// Returns the decoded displacements of each morph target of the primitive.
+    ///
+    /// Fails if the values of an attribute don't match its accessor.
+    pub(crate) fn morph_targets(
+        &self,
+        primitive: &Primitive,
+    ) -> Result<Vec<MorphTargetIters<'_>>, DracoDecodeError> {
+        let stride = size_of::<[f32; 3]>();
+        primitive
+            .morph_targets()
+            .zip(&self.target_ids)
+            .enumerate()
+            .map(|(index, (target, ids))| {
+                let read = |accessor: Option<Accessor>, name: &str| {
+                    let Some(accessor) = accessor else {
+                        return Ok(None);
+                    };
+                    let data = ids
+                        .get(name)
+                        .and_then(|unique_id| self.mesh.attributes.get(unique_id))
+                        .filter(|data| data.len() == accessor.count() * stride)
+                        .ok_or_else(|| format!("invalid decoded {name} of morph target {index}"))?;
+                    Ok::<_, DracoDecodeError>(Some(Iter::Standard(ItemIter::new(data, stride))))
+                };
+                Ok((
+                    read(target.positions(), "POSITION")?,
+                    read(target.normals(), "NORMAL")?,
+                    read(target.tangents(), "TANGENT")?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Takes the decoded vertex indices.
+    pub(crate) fn take_indices(&mut self) -> Vec<u32> {
+        core::mem::take(&mut self.mesh.indices)
+    }
+}
+
+/// Returns true if the primitive is compressed with `KHR_draco_mesh_compression`.
+pub(crate) fn is_draco_compressed(primitive: &Primitive) -> bool {
+    primitive
+        .extensions()
+        .is_some_and(|extensions| extensions.contains_key(KHR_DRACO_MESH_COMPRESSION))
+}
+
+/// Decodes the Draco-compressed geometry of a primitive.
+pub(crate) fn decode_primitive(
+    decoder: &Arc<dyn DracoDecoder>,
+    primitive: &Primitive,
+    document: &Document,
+    buffer_data: &[Vec<u8>],
+) -> Result<DecodedPrimitive, DracoDecodeError> {
+    let extension = primitive
+        .extensions()
+        .and_then(|extensions| extensions.get(KHR_DRACO_MESH_COMPRESSION))
+        .ok_or("missing KHR_draco_mesh_compression extension")?;
+    let extension: DracoExtension = serde_json::from_value(extension.clone())?;
+
+    let view = document
+        .views()
+        .nth(extension.buffer_view)
+        .ok_or("invalid buffer view")?;
+    let data = buffer_data
+        .get(view.buffer().index())
+        .and_then(|buffer| buffer.get(view.offset()..view.offset() + view.length()))
+        .ok_or("buffer view out of bounds")?;
+
+    let mut attributes: Vec<_> = primitive
+        .attributes()
+        .filter_map(|(semantic, accessor)| {
+            let unique_id = extension.attributes.get(&semantic.to_string())?;
+            Some(DracoAttributeRequest::new(*unique_id, &accessor))
+        })
+        .collect();
+
+    // Draco reorders the vertices, so uncompressed morph targets wouldn't match
+    // the decoded attributes.
+    for (index, target) in primitive.morph_targets().enumerate() {
+        let ids = extension
+            .targets
+            .get(index)
+            .ok_or_else(|| format!("morph target {index} isn't compressed"))?;
+        for (accessor, name) in [
+            (target.positions(), "POSITION"),
+            (target.normals(), "NORMAL"),
+            (target.tangents(), "TANGENT"),
+        ] {
+            let Some(accessor) = accessor else {
+                continue;
+            };
+            let unique_id = ids
+                .get(name)
+                .ok_or_else(|| format!("{name} of morph target {index} isn't compressed"))?;
+            attributes.push(DracoAttributeRequest::new(*unique_id, &accessor));
+        }
+    }
+
+    Ok(DecodedPrimitive {
+        mesh: decoder.decode(data, &attributes)?,
+        attribute_ids: extension.attributes,
+        target_ids: extension.targets,
+    })
+}
+
+/// Returns true if all the validation errors of a glTF file are caused by it
+/// requiring `KHR_draco_mesh_compression`, which the `gltf` crate doesn't know
+/// about.
+pub(crate) fn only_requires_draco(
+    errors: &[(json::Path, json::validation::Error)],
+    root: &json::Root,
+) -> bool {
+    !errors.is_empty()
+        && errors.iter().all(|(path, error)| {
+            matches!(error, json::validation::Error::Unsupported)
+                && root
+                    .extensions_required
+                    .iter()
+                    .enumerate()
+                    .any(|(index, extension)| {
+                        extension == KHR_DRACO_MESH_COMPRESSION
+                            && path.as_str() == format!("extensionsRequired[{index}]")
+                    })
+        })
+}
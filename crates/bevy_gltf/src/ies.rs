@@ -0,0 +1,126 @@
+//! Support for lights with photometric profiles from the
+//! [`EXT_lights_ies`](https://github.com/KhronosGroup/glTF/blob/main/extensions/2.0/Vendor/EXT_lights_ies/README.md)
+//! extension.
+
+use bevy_asset::{Handle, LoadContext};
+use bevy_pbr::IesProfile;
+use gltf::{Document, Node};
+use serde::Deserialize;
+
+use crate::{
+    loader::{DataUri, GltfError},
+    GltfAssetLabel,
+};
+
+/// The name of the `EXT_lights_ies` extension.
+pub const EXT_LIGHTS_IES: &str = "EXT_lights_ies";
+
+/// The `EXT_lights_ies` extension object of the glTF root.
+#[derive(Deserialize)]
+struct IesLightsExtension {
+    lights: Vec<IesLightSource>,
+}
+
+/// A profile listed by the `EXT_lights_ies` extension, stored either at a URI
+/// or in a buffer view.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IesLightSource {
+    uri: Option<String>,
+    buffer_view: Option<usize>,
+}
+
+/// The `EXT_lights_ies` extension object of a node, which places a light with
+/// one of the listed profiles at the node.
+#[derive(Deserialize)]
+pub(crate) struct IesNodeLight {
+    /// The index of the profile in the root extension object.
+    pub(crate) light: usize,
+    /// The factor that the intensities of the profile are scaled by.
+    #[serde(default = "default_multiplier")]
+    pub(crate) multiplier: f32,
+    /// The linear RGB color of the light.
+    #[serde(default = "default_color")]
+    pub(crate) color: [f32; 3],
+    /// The distance beyond which the light has no effect, if the node sets one.
+    ///
+    /// Otherwise, [`GltfLoaderSettings::ies_light_range`](crate::GltfLoaderSettings::ies_light_range)
+    /// is used.
+    #[serde(default)]
+    pub(crate) range: Option<f32>,
+}
+
+fn default_multiplier() -> f32 {
+    1.0
+}
+
+fn default_color() -> [f32; 3] {
+    [1.0; 3]
+}
+
+/// Loads all the profiles listed by the `EXT_lights_ies` extension, in order.
+///
+/// Profiles that are embedded in the file become labeled assets, while
+/// profiles at external URIs are loaded by the [`IesProfileLoader`](bevy_pbr::IesProfileLoader).
+pub(crate) fn load_ies_profiles(
+    document: &Document,
+    buffer_data: &[Vec<u8>],
+    load_context: &mut LoadContext,
+) -> Result<Vec<Handle<IesProfile>>, GltfError> {
+    let Some(extension) = document
+        .extensions()
+        .and_then(|extensions| extensions.get(EXT_LIGHTS_IES))
+    else {
+        return Ok(vec![]);
+    };
+    let extension: IesLightsExtension = serde_json::from_value(extension.clone())
+        .map_err(|err| GltfError::InvalidExtension(EXT_LIGHTS_IES, err))?;
+
+    let mut profiles = Vec::with_capacity(extension.lights.len());
+    for (index, source) in extension.lights.into_iter().enumerate() {
+        let bytes = match (source.buffer_view, source.uri) {
+            (Some(buffer_view), _) => {
+                let view = document
+                    .views()
+                    .nth(buffer_view)
+                    .ok_or(GltfError::MissingIesProfile(index))?;
+                buffer_data
+                    .get(view.buffer().index())
+                    .and_then(|buffer| buffer.get(view.offset()..view.offset() + view.length()))
+                    .ok_or(GltfError::MissingIesProfile(index))?
+                    .to_vec()
+            }
+            (None, Some(uri)) => {
+                let uri = percent_encoding::percent_decode_str(&uri)
+                    .decode_utf8()
+                    .unwrap();
+                let uri = uri.as_ref();
+                match DataUri::parse(uri) {
+                    Ok(data_uri) => data_uri.decode()?,
+                    Err(()) => {
+                        let path = load_context.path().parent().unwrap().join(uri);
+                        profiles.push(load_context.load(path));
+                        continue;
+                    }
+                }
+            }
+            (None, None) => return Err(GltfError::MissingIesProfile(index)),
+        };
+
+        let profile = core::str::from_utf8(&bytes)
+            .map_err(bevy_pbr::IesProfileError::from)
+            .and_then(IesProfile::parse)
+            .map_err(|err| GltfError::IesProfile(index, err))?;
+        profiles.push(
+            load_context.add_labeled_asset(GltfAssetLabel::IesProfile(index).to_string(), profile),
+        );
+    }
+
+    Ok(profiles)
+}
+
+/// Returns the `EXT_lights_ies` extension object of a node, if it has one.
+pub(crate) fn node_ies_light(node: &Node) -> Option<IesNodeLight> {
+    let extension = node.extensions()?.get(EXT_LIGHTS_IES)?;
+    serde_json::from_value(extension.clone()).ok()
+}
@@ -92,19 +92,29 @@
 
 extern crate alloc;
 
+use alloc::sync::Arc;
 #[cfg(feature = "bevy_animation")]
 use bevy_animation::AnimationClip;
 use bevy_utils::HashMap;
 
+mod draco;
+mod ies;
 mod loader;
+pub mod lod;
+mod validation;
 mod vertex_attributes;
+pub use draco::{
+    DracoAttributeRequest, DracoDecodeError, DracoDecoder, DracoMesh, KHR_DRACO_MESH_COMPRESSION,
+};
+pub use ies::EXT_LIGHTS_IES;
 pub use loader::*;
+pub use validation::*;
 
 use bevy_app::prelude::*;
 use bevy_asset::{Asset, AssetApp, AssetPath, Handle};
-use bevy_ecs::{prelude::Component, reflect::ReflectComponent};
+use bevy_ecs::{prelude::Component, query::Changed, reflect::ReflectComponent, system::Query};
 use bevy_image::CompressedImageFormats;
-use bevy_pbr::StandardMaterial;
+use bevy_pbr::{MeshMaterial3d, StandardMaterial};
 use bevy_reflect::{std_traits::ReflectDefault, Reflect, TypePath};
 use bevy_render::{
     mesh::{skinning::SkinnedMeshInverseBindposes, Mesh, MeshVertexAttribute},
@@ -117,13 +127,14 @@ use bevy_scene::Scene;
 /// This includes the most common types in this crate, re-exported for your convenience.
 pub mod prelude {
     #[doc(hidden)]
-    pub use crate::{Gltf, GltfAssetLabel, GltfExtras};
+    pub use crate::{Gltf, GltfAssetLabel, GltfExtras, MaterialVariants};
 }
 
 /// Adds support for glTF file loading to the app.
 #[derive(Default)]
 pub struct GltfPlugin {
     custom_vertex_attributes: HashMap<Box<str>, MeshVertexAttribute>,
+    draco_decoder: Option<Arc<dyn DracoDecoder>>,
 }
 
 impl GltfPlugin {
@@ -140,6 +151,13 @@ impl GltfPlugin {
         self.custom_vertex_attributes.insert(name.into(), attribute);
         self
     }
+
+    /// Register a [`DracoDecoder`] so that primitives compressed with the
+    /// `KHR_draco_mesh_compression` extension can be loaded by the [`GltfLoader`].
+    pub fn with_draco_decoder(mut self, decoder: impl DracoDecoder) -> Self {
+        self.draco_decoder = Some(Arc::new(decoder));
+        self
+    }
 }
 
 impl Plugin for GltfPlugin {
@@ -149,12 +167,21 @@ impl Plugin for GltfPlugin {
             .register_type::<GltfMeshExtras>()
             .register_type::<GltfMaterialExtras>()
             .register_type::<GltfMaterialName>()
+            .register_type::<MaterialVariants>()
             .init_asset::<Gltf>()
             .init_asset::<GltfNode>()
             .init_asset::<GltfPrimitive>()
             .init_asset::<GltfMesh>()
             .init_asset::<GltfSkin>()
-            .preregister_asset_loader::<GltfLoader>(&["gltf", "glb"]);
+            .preregister_asset_loader::<GltfLoader>(&["gltf", "glb"])
+            .add_systems(PostUpdate, apply_material_variants);
+
+        if let Some(processor) = app
+            .world()
+            .get_resource::<bevy_asset::processor::AssetProcessor>()
+        {
+            processor.register_processor(lod::GltfLodProcessor);
+        }
     }
 
     fn finish(&self, app: &mut App) {
@@ -165,6 +192,7 @@ impl Plugin for GltfPlugin {
         app.register_asset_loader(GltfLoader {
             supported_compressed_formats,
             custom_vertex_attributes: self.custom_vertex_attributes.clone(),
+            draco_decoder: self.draco_decoder.clone(),
         });
     }
 }
@@ -192,6 +220,11 @@ pub struct Gltf {
     pub skins: Vec<Handle<GltfSkin>>,
     /// Named skins loaded from the glTF file.
     pub named_skins: HashMap<Box<str>, Handle<GltfSkin>>,
+    /// The names of the material variants defined by the `KHR_materials_variants`
+    /// extension, in the order they're declared in the glTF file.
+    ///
+    /// See [`MaterialVariants`] for how to switch between them.
+    pub material_variants: Vec<String>,
     /// Default scene to be displayed.
     pub default_scene: Option<Handle<Scene>>,
     /// All animations loaded from the glTF file.
@@ -202,6 +235,9 @@ pub struct Gltf {
     pub named_animations: HashMap<Box<str>, Handle<AnimationClip>>,
     /// The gltf root of the gltf asset, see <https://docs.rs/gltf/latest/gltf/struct.Gltf.html>. Only has a value when `GltfLoaderSettings::include_source` is true.
     pub source: Option<gltf::Gltf>,
+    /// The problems of the glTF file that affect how it renders or performs. Only has a value when
+    /// [`GltfLoaderSettings::validate`] is true.
+    pub validation_report: Option<GltfValidationReport>,
 }
 
 /// A glTF node with all of its child nodes, its [`GltfMesh`],
@@ -371,6 +407,9 @@ pub struct GltfPrimitive {
     pub name: String,
     /// Topology to be rendered.
     pub mesh: Handle<Mesh>,
+    /// Simplified levels of detail of the `mesh`, generated by the
+    /// [`GltfLodProcessor`](lod::GltfLodProcessor).
+    pub lods: Vec<Handle<Mesh>>,
     /// Material to apply to the `mesh`.
     pub material: Option<Handle<StandardMaterial>>,
     /// Additional data.
@@ -401,6 +440,7 @@ impl GltfPrimitive {
                 }
             },
             mesh,
+            lods: Vec::new(),
             material,
             extras,
             material_extras,
@@ -463,6 +503,80 @@ pub struct GltfMaterialExtras {
 #[reflect(Component)]
 pub struct GltfMaterialName(pub String);
 
+/// The alternative materials of a glTF primitive, as defined by the
+/// [`KHR_materials_variants`](https://github.com/KhronosGroup/glTF/blob/main/extensions/2.0/Khronos/KHR_materials_variants/README.md)
+/// extension.
+///
+/// The loader adds this component to the mesh entities of primitives that map
+/// at least one variant to a material. The variants themselves are defined for
+/// the whole glTF file (see [`Gltf::material_variants`]), so switching all the
+/// meshes of a spawned scene to a variant is a matter of calling
+/// [`MaterialVariants::select`] on each of them:
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_gltf::MaterialVariants;
+/// fn select_red_variant(mut query: Query<&mut MaterialVariants>) {
+///     for mut variants in &mut query {
+///         variants.select(Some("red"));
+///     }
+/// }
+/// ```
+///
+/// Whenever this component changes, the [`MeshMaterial3d`] of the entity is
+/// updated to the material of the [active](MaterialVariants::active) variant.
+/// Primitives that don't map the active variant fall back to their default
+/// material.
+#[derive(Clone, Debug, Reflect, Default, Component)]
+#[reflect(Component, Default, Debug)]
+pub struct MaterialVariants {
+    /// The material used when no variant is active, or when the active variant
+    /// isn't mapped for this primitive.
+    pub default_material: Handle<StandardMaterial>,
+    /// The material of each variant that this primitive maps, keyed by the
+    /// name of the variant.
+    pub materials: HashMap<String, Handle<StandardMaterial>>,
+    /// The name of the active variant, if any.
+    pub active: Option<String>,
+}
+
+impl MaterialVariants {
+    /// Makes the variant with the given name active, or goes back to the
+    /// default material if `variant` is `None`.
+    pub fn select(&mut self, variant: Option<&str>) {
+        self.active = variant.map(String::from);
+    }
+
+    /// Returns the material that the primitive should currently use.
+    pub fn active_material(&self) -> &Handle<StandardMaterial> {
+        self.active
+            .as_ref()
+            .and_then(|variant| self.materials.get(variant))
+            .unwrap_or(&self.default_material)
+    }
+
+    /// Returns the names of the variants that this primitive maps.
+    pub fn variants(&self) -> impl Iterator<Item = &str> {
+        self.materials.keys().map(String::as_str)
+    }
+}
+
+/// Updates the [`MeshMaterial3d`] of entities whose [`MaterialVariants`]
+/// changed to the material of the active variant.
+pub fn apply_material_variants(
+    mut query: Query<
+        (&MaterialVariants, &mut MeshMaterial3d<StandardMaterial>),
+        Changed<MaterialVariants>,
+    >,
+) {
+    for (variants, mut material) in &mut query {
+        let active_material = variants.active_material();
+        if material.0 != *active_material {
+            material.0 = active_material.clone();
+        }
+    }
+}
+
 /// Labels that can be used to load part of a glTF
 ///
 /// You can use [`GltfAssetLabel::from_asset`] to add it to an asset path
@@ -512,6 +626,16 @@ pub enum GltfAssetLabel {
         /// Index of this primitive in its parent mesh
         primitive: usize,
     },
+    /// `Mesh{}/Primitive{}/Lod{}`: Simplified level of detail of a glTF Primitive as a Bevy
+    /// `Mesh`, generated by the [`GltfLodProcessor`](lod::GltfLodProcessor)
+    PrimitiveLod {
+        /// Index of the mesh for this primitive
+        mesh: usize,
+        /// Index of this primitive in its parent mesh
+        primitive: usize,
+        /// Index of this level of detail, starting at 1
+        lod: usize,
+    },
     /// `Texture{}`: glTF Texture as a Bevy `Image`
     Texture(usize),
     /// `Material{}`: glTF Material as a Bevy `StandardMaterial`
@@ -529,6 +653,9 @@ pub enum GltfAssetLabel {
     Skin(usize),
     /// `Skin{}/InverseBindMatrices`: glTF mesh skin matrices as Bevy `SkinnedMeshInverseBindposes`
     InverseBindMatrices(usize),
+    /// `IesProfile{}`: photometric profile embedded with the `EXT_lights_ies`
+    /// extension as Bevy [`IesProfile`](bevy_pbr::IesProfile)
+    IesProfile(usize),
 }
 
 impl core::fmt::Display for GltfAssetLabel {
@@ -543,6 +670,11 @@ impl core::fmt::Display for GltfAssetLabel {
             GltfAssetLabel::MorphTarget { mesh, primitive } => {
                 f.write_str(&format!("Mesh{mesh}/Primitive{primitive}/MorphTargets"))
             }
+            GltfAssetLabel::PrimitiveLod {
+                mesh,
+                primitive,
+                lod,
+            } => f.write_str(&format!("Mesh{mesh}/Primitive{primitive}/Lod{lod}")),
             GltfAssetLabel::Texture(index) => f.write_str(&format!("Texture{index}")),
             GltfAssetLabel::Material {
                 index,
@@ -561,6 +693,7 @@ impl core::fmt::Display for GltfAssetLabel {
             GltfAssetLabel::InverseBindMatrices(index) => {
                 f.write_str(&format!("Skin{index}/InverseBindMatrices"))
             }
+            GltfAssetLabel::IesProfile(index) => f.write_str(&format!("IesProfile{index}")),
         }
     }
 }
@@ -582,3 +715,57 @@ impl GltfAssetLabel {
         path.into().with_label(self.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy_app::{App, Update};
+    use bevy_asset::Handle;
+    use bevy_pbr::{MeshMaterial3d, StandardMaterial};
+    use bevy_utils::HashMap;
+
+    use super::{apply_material_variants, MaterialVariants};
+
+    #[test]
+    fn material_variants() {
+        let default_material = Handle::<StandardMaterial>::weak_from_u128(1);
+        let red = Handle::<StandardMaterial>::weak_from_u128(2);
+        let blue = Handle::<StandardMaterial>::weak_from_u128(3);
+
+        let mut app = App::new();
+        app.add_systems(Update, apply_material_variants);
+        let entity = app
+            .world_mut()
+            .spawn((
+                MaterialVariants {
+                    default_material: default_material.clone(),
+                    materials: HashMap::from_iter([
+                        ("red".to_owned(), red.clone()),
+                        ("blue".to_owned(), blue.clone()),
+                    ]),
+                    active: None,
+                },
+                MeshMaterial3d(default_material.clone()),
+            ))
+            .id();
+        let select = |app: &mut App, variant: Option<&'static str>| {
+            app.world_mut()
+                .get_mut::<MaterialVariants>(entity)
+                .unwrap()
+                .select(variant);
+            app.update();
+            app.world()
+                .get::<MeshMaterial3d<StandardMaterial>>(entity)
+                .unwrap()
+                .0
+                .clone()
+        };
+
+        assert_eq!(select(&mut app, Some("red")), red);
+        assert_eq!(select(&mut app, Some("blue")), blue);
+        // Variants that this primitive doesn't map fall back to the default
+        // material.
+        assert_eq!(select(&mut app, Some("green")), default_material);
+        assert_eq!(select(&mut app, Some("red")), red);
+        assert_eq!(select(&mut app, None), default_material);
+    }
+}
@@ -1,9 +1,14 @@
 use crate::{
-    vertex_attributes::convert_attribute, Gltf, GltfAssetLabel, GltfExtras, GltfMaterialExtras,
-    GltfMaterialName, GltfMeshExtras, GltfNode, GltfSceneExtras, GltfSkin,
+    draco::{self, DracoDecodeError, DracoDecoder},
+    ies::{load_ies_profiles, node_ies_light},
+    lod::{primitive_lods, read_lod_indices},
+    vertex_attributes::convert_attribute,
+    Gltf, GltfAssetLabel, GltfExtras, GltfMaterialExtras, GltfMaterialName, GltfMeshExtras,
+    GltfNode, GltfSceneExtras, GltfSkin, GltfValidationIssueKind, GltfValidationReport,
+    GltfValidationSeverity, MaterialVariants,
 };
 
-use alloc::collections::VecDeque;
+use alloc::{collections::VecDeque, sync::Arc};
 use bevy_asset::{
     io::Reader, AssetLoadError, AssetLoader, Handle, LoadContext, ReadAssetBytesError,
 };
@@ -21,8 +26,8 @@ use bevy_image::{
 };
 use bevy_math::{Affine2, Mat4, Vec3};
 use bevy_pbr::{
-    DirectionalLight, MeshMaterial3d, PointLight, SpotLight, StandardMaterial, UvChannel,
-    MAX_JOINTS,
+    AutoLod, AutoLodLevel, DirectionalLight, IesLightProfile, IesProfile, IesProfileError,
+    MeshMaterial3d, PointLight, SpotLight, StandardMaterial, UvChannel, MAX_JOINTS,
 };
 use bevy_render::{
     alpha::AlphaMode,
@@ -30,7 +35,7 @@ use bevy_render::{
     mesh::{
         morph::{MeshMorphWeights, MorphAttributes, MorphTargetImage, MorphWeights},
         skinning::{SkinnedMesh, SkinnedMeshInverseBindposes},
-        Indices, Mesh, Mesh3d, MeshVertexAttribute, VertexAttributeValues,
+        Indices, Mesh, Mesh3d, MeshAabb, MeshVertexAttribute, VertexAttributeValues,
     },
     primitives::Aabb,
     render_asset::RenderAssetUsages,
@@ -115,6 +120,27 @@ pub enum GltfError {
     /// Failed to load a file.
     #[error("failed to load file: {0}")]
     Io(#[from] Error),
+    /// Failed to decode a primitive compressed with `KHR_draco_mesh_compression`.
+    #[error("failed to decode Draco-compressed primitive {0}: {1}")]
+    #[from(ignore)]
+    DracoDecode(String, DracoDecodeError),
+    /// A primitive, or the whole file, requires `KHR_draco_mesh_compression`,
+    /// but no [`DracoDecoder`] is registered.
+    #[error("{0} requires KHR_draco_mesh_compression, but no Draco decoder is registered (see `GltfPlugin::with_draco_decoder`)")]
+    #[from(ignore)]
+    MissingDracoDecoder(String),
+    /// The JSON of an extension is invalid.
+    #[error("invalid {0} extension: {1}")]
+    #[from(ignore)]
+    InvalidExtension(&'static str, serde_json::Error),
+    /// The data of a profile listed by the `EXT_lights_ies` extension is missing.
+    #[error("missing data for IES profile {0}")]
+    #[from(ignore)]
+    MissingIesProfile(usize),
+    /// Failed to parse a profile listed by the `EXT_lights_ies` extension.
+    #[error("failed to parse IES profile {0}: {1}")]
+    #[from(ignore)]
+    IesProfile(usize, IesProfileError),
 }
 
 /// Loads glTF files with all of their data as their corresponding bevy representations.
@@ -127,6 +153,9 @@ pub struct GltfLoader {
     /// See [this section of the glTF specification](https://registry.khronos.org/glTF/specs/2.0/glTF-2.0.html#meshes-overview)
     /// for additional details on custom attributes.
     pub custom_vertex_attributes: HashMap<Box<str>, MeshVertexAttribute>,
+    /// The decoder used for primitives compressed with the `KHR_draco_mesh_compression`
+    /// extension, if any.
+    pub draco_decoder: Option<Arc<dyn DracoDecoder>>,
 }
 
 /// Specifies optional settings for processing gltfs at load time. By default, all recognized contents of
@@ -162,6 +191,17 @@ pub struct GltfLoaderSettings {
     pub load_lights: bool,
     /// If true, the loader will include the root of the gltf root node.
     pub include_source: bool,
+    /// The range of the lights spawned for `EXT_lights_ies` nodes that don't set their own range.
+    #[serde(default = "default_ies_light_range")]
+    pub ies_light_range: f32,
+    /// If true, the loader checks the file for problems that affect how it renders or performs,
+    /// and reports them in [`Gltf::validation_report`].
+    #[serde(default)]
+    pub validate: bool,
+}
+
+fn default_ies_light_range() -> f32 {
+    PointLight::default().range
 }
 
 impl Default for GltfLoaderSettings {
@@ -172,6 +212,8 @@ impl Default for GltfLoaderSettings {
             load_cameras: true,
             load_lights: true,
             include_source: false,
+            ies_light_range: default_ies_light_range(),
+            validate: false,
         }
     }
 }
@@ -196,6 +238,27 @@ impl AssetLoader for GltfLoader {
     }
 }
 
+/// Parses and validates a glTF file.
+///
+/// The `gltf` crate rejects files that require extensions it doesn't know
+/// about, which includes `KHR_draco_mesh_compression`. Such files are accepted
+/// if a [`DracoDecoder`] is registered and that is the only problem.
+fn parse_gltf(loader: &GltfLoader, bytes: &[u8]) -> Result<gltf::Gltf, GltfError> {
+    match gltf::Gltf::from_slice(bytes) {
+        Err(gltf::Error::Validation(errors)) => {
+            let gltf = gltf::Gltf::from_slice_without_validation(bytes)?;
+            if !draco::only_requires_draco(&errors, gltf.document.as_json()) {
+                Err(gltf::Error::Validation(errors).into())
+            } else if loader.draco_decoder.is_none() {
+                Err(GltfError::MissingDracoDecoder("the file".to_string()))
+            } else {
+                Ok(gltf)
+            }
+        }
+        result => Ok(result?),
+    }
+}
+
 /// Loads an entire glTF file.
 async fn load_gltf<'a, 'b, 'c>(
     loader: &GltfLoader,
@@ -203,7 +266,7 @@ async fn load_gltf<'a, 'b, 'c>(
     load_context: &'b mut LoadContext<'c>,
     settings: &'b GltfLoaderSettings,
 ) -> Result<Gltf, GltfError> {
-    let gltf = gltf::Gltf::from_slice(bytes)?;
+    let gltf = parse_gltf(loader, bytes)?;
     let file_name = load_context
         .asset_path()
         .path()
@@ -213,7 +276,12 @@ async fn load_gltf<'a, 'b, 'c>(
             "Gltf file name invalid",
         ))))?
         .to_string();
+    let mut validation_report = settings.validate.then(GltfValidationReport::default);
+    if let Some(report) = &mut validation_report {
+        report.check_extensions(&gltf.document, loader.draco_decoder.is_some());
+    }
     let buffer_data = load_buffers(&gltf, load_context).await?;
+    let ies_profiles = load_ies_profiles(&gltf.document, &buffer_data, load_context)?;
 
     let mut linear_textures = <HashSet<_>>::default();
 
@@ -531,10 +599,16 @@ async fn load_gltf<'a, 'b, 'c>(
     fn process_loaded_texture(
         load_context: &mut LoadContext,
         handles: &mut Vec<Handle<Image>>,
+        validation_report: Option<&mut GltfValidationReport>,
         texture: ImageOrPath,
     ) {
         let handle = match texture {
             ImageOrPath::Image { label, image } => {
+                // The images in their own files are loaded later by the image loader, so only the
+                // embedded ones are checked.
+                if let Some(report) = validation_report {
+                    report.check_image(&label.to_string(), &image);
+                }
                 load_context.add_labeled_asset(label.to_string(), image)
             }
             ImageOrPath::Path {
@@ -571,7 +645,12 @@ async fn load_gltf<'a, 'b, 'c>(
                 settings.load_materials,
             )
             .await?;
-            process_loaded_texture(load_context, &mut _texture_handles, image);
+            process_loaded_texture(
+                load_context,
+                &mut _texture_handles,
+                validation_report.as_mut(),
+                image,
+            );
         }
     } else {
         #[cfg(not(target_arch = "wasm32"))]
@@ -597,7 +676,12 @@ async fn load_gltf<'a, 'b, 'c>(
             .into_iter()
             .for_each(|result| match result {
                 Ok(image) => {
-                    process_loaded_texture(load_context, &mut _texture_handles, image);
+                    process_loaded_texture(
+                        load_context,
+                        &mut _texture_handles,
+                        validation_report.as_mut(),
+                        image,
+                    );
                 }
                 Err(err) => {
                     warn!("Error loading glTF texture: {}", err);
@@ -642,6 +726,31 @@ async fn load_gltf<'a, 'b, 'c>(
 
             let mut mesh = Mesh::new(primitive_topology, settings.load_meshes);
 
+            let mut decoded_primitive = None;
+            if draco::is_draco_compressed(&primitive) {
+                if let Some(decoder) = &loader.draco_decoder {
+                    decoded_primitive = Some(
+                        draco::decode_primitive(decoder, &primitive, &gltf.document, &buffer_data)
+                            .map_err(|err| {
+                                GltfError::DracoDecode(primitive_label.to_string(), err)
+                            })?,
+                    );
+                } else if primitive
+                    .get(&Semantic::Positions)
+                    .is_none_or(|positions| positions.view().is_none())
+                {
+                    return Err(GltfError::MissingDracoDecoder(format!(
+                        "primitive {primitive_label}"
+                    )));
+                } else {
+                    warn!(
+                        "Primitive {} is compressed with {}, but no Draco decoder is registered. Falling back to its uncompressed data. See `GltfPlugin::with_draco_decoder`.",
+                        primitive_label,
+                        draco::KHR_DRACO_MESH_COMPRESSION
+                    );
+                }
+            }
+
             // Read vertex attributes
             for (semantic, accessor) in primitive.attributes() {
                 if [Semantic::Joints(0), Semantic::Weights(0)].contains(&semantic) {
@@ -656,10 +765,14 @@ async fn load_gltf<'a, 'b, 'c>(
                         error!("Skinned mesh {} used on both skinned and non skin nodes, this is likely to cause an error (NODE_SKINNED_MESH_WITHOUT_SKIN)", primitive_label);
                     }
                 }
+                let decoded_data = decoded_primitive
+                    .as_ref()
+                    .and_then(|decoded| decoded.attribute(&semantic.to_string()));
                 match convert_attribute(
                     semantic,
                     accessor,
                     &buffer_data,
+                    decoded_data,
                     &loader.custom_vertex_attributes,
                 ) {
                     Ok((attribute, values)) => mesh.insert_attribute(attribute, values),
@@ -669,7 +782,9 @@ async fn load_gltf<'a, 'b, 'c>(
 
             // Read vertex indices
             let reader = primitive.reader(|buffer| Some(buffer_data[buffer.index()].as_slice()));
-            if let Some(indices) = reader.read_indices() {
+            if let Some(decoded_primitive) = &mut decoded_primitive {
+                mesh.insert_indices(Indices::U32(decoded_primitive.take_indices()));
+            } else if let Some(indices) = reader.read_indices() {
                 mesh.insert_indices(match indices {
                     ReadIndices::U8(is) => Indices::U16(is.map(|x| x as u16).collect()),
                     ReadIndices::U16(is) => Indices::U16(is.collect()),
@@ -678,14 +793,19 @@ async fn load_gltf<'a, 'b, 'c>(
             };
 
             {
-                let morph_target_reader = reader.read_morph_targets();
-                if morph_target_reader.len() != 0 {
+                let morph_targets = match &decoded_primitive {
+                    Some(decoded_primitive) => decoded_primitive
+                        .morph_targets(&primitive)
+                        .map_err(|err| GltfError::DracoDecode(primitive_label.to_string(), err))?,
+                    None => reader.read_morph_targets().collect(),
+                };
+                if !morph_targets.is_empty() {
                     let morph_targets_label = GltfAssetLabel::MorphTarget {
                         mesh: gltf_mesh.index(),
                         primitive: primitive.index(),
                     };
                     let morph_target_image = MorphTargetImage::new(
-                        morph_target_reader.map(PrimitiveMorphAttributesIter),
+                        morph_targets.into_iter().map(PrimitiveMorphAttributesIter),
                         mesh.count_vertices(),
                         RenderAssetUsages::default(),
                     )?;
@@ -724,27 +844,91 @@ async fn load_gltf<'a, 'b, 'c>(
                 .map(|v| VertexAttributeValues::Float32x4(v.collect()))
             {
                 mesh.insert_attribute(Mesh::ATTRIBUTE_TANGENT, vertex_attribute);
-            } else if mesh.attribute(Mesh::ATTRIBUTE_NORMAL).is_some()
-                && material_needs_tangents(&primitive.material())
+            } else if material_needs_tangents(&primitive.material())
+                || primitive
+                    .mappings()
+                    .any(|mapping| material_needs_tangents(&mapping.material()))
             {
-                tracing::debug!(
-                    "Missing vertex tangents for {}, computing them using the mikktspace algorithm. Consider using a tool such as Blender to pre-compute the tangents.", file_name
-                );
+                let generated = if mesh.attribute(Mesh::ATTRIBUTE_NORMAL).is_some() {
+                    tracing::debug!(
+                        "Missing vertex tangents for {}, computing them using the mikktspace algorithm. Consider using a tool such as Blender to pre-compute the tangents.", file_name
+                    );
 
-                let generate_tangents_span = info_span!("generate_tangents", name = file_name);
+                    let generate_tangents_span = info_span!("generate_tangents", name = file_name);
+
+                    generate_tangents_span.in_scope(|| match mesh.generate_tangents() {
+                        Ok(()) => true,
+                        Err(err) => {
+                            warn!(
+                                "Failed to generate vertex tangents using the mikktspace algorithm: {}",
+                                err
+                            );
+                            false
+                        }
+                    })
+                } else {
+                    false
+                };
+                if let (false, Some(report)) = (generated, &mut validation_report) {
+                    report.push(
+                        GltfValidationSeverity::Error,
+                        GltfValidationIssueKind::MissingTangents,
+                        primitive_label.to_string(),
+                        "the material has a normal map, but the mesh has no tangents and they \
+                        couldn't be generated, so the normal map is ignored",
+                    );
+                }
+            }
+
+            if let Some(report) = &mut validation_report {
+                if mesh.compute_aabb().is_none() {
+                    report.push(
+                        GltfValidationSeverity::Warning,
+                        GltfValidationIssueKind::MissingAabb,
+                        primitive_label.to_string(),
+                        "the mesh has no positions to compute its bounds from, so it can't be \
+                        culled",
+                    );
+                }
+            }
 
-                generate_tangents_span.in_scope(|| {
-                    if let Err(err) = mesh.generate_tangents() {
+            // The levels of detail generated by the `GltfLodProcessor` index the vertices of the
+            // primitive as they're stored in the file.
+            let mut lods = Vec::new();
+            if decoded_primitive.is_none()
+                && mesh.primitive_topology() == PrimitiveTopology::TriangleList
+                && !mesh.has_morph_targets()
+            {
+                for (lod_index, lod) in primitive_lods(&primitive).iter().enumerate() {
+                    let Some(indices) =
+                        read_lod_indices(&gltf.document, lod, &buffer_data).filter(|indices| {
+                            indices.len() % 3 == 0
+                                && indices
+                                    .iter()
+                                    .all(|&index| (index as usize) < mesh.count_vertices())
+                        })
+                    else {
                         warn!(
-                            "Failed to generate vertex tangents using the mikktspace algorithm: {}",
-                            err
+                            "Invalid level of detail {} of {}, ignoring it and the following ones",
+                            lod_index + 1,
+                            primitive_label
                         );
-                    }
-                });
+                        break;
+                    };
+                    let lod_label = GltfAssetLabel::PrimitiveLod {
+                        mesh: gltf_mesh.index(),
+                        primitive: primitive.index(),
+                        lod: lod_index + 1,
+                    };
+                    lods.push(load_context.add_labeled_asset(
+                        lod_label.to_string(),
+                        mesh.with_compacted_indices(&indices),
+                    ));
+                }
             }
 
             let mesh_handle = load_context.add_labeled_asset(primitive_label.to_string(), mesh);
-            primitives.push(super::GltfPrimitive::new(
+            let mut gltf_primitive = super::GltfPrimitive::new(
                 &gltf_mesh,
                 &primitive,
                 mesh_handle,
@@ -754,7 +938,9 @@ async fn load_gltf<'a, 'b, 'c>(
                     .and_then(|i| materials.get(i).cloned()),
                 get_gltf_extras(primitive.extras()),
                 get_gltf_extras(primitive.material().extras()),
-            ));
+            );
+            gltf_primitive.lods = lods;
+            primitives.push(gltf_primitive);
         }
 
         let mesh =
@@ -802,7 +988,19 @@ async fn load_gltf<'a, 'b, 'c>(
                 get_gltf_extras(skin.extras()),
             );
 
+            let joint_count = gltf_skin.joints.len();
             let handle = load_context.add_labeled_asset(skin_label(&skin), gltf_skin);
+            if let (true, Some(report)) = (joint_count > MAX_JOINTS, &mut validation_report) {
+                report.push(
+                    GltfValidationSeverity::Warning,
+                    GltfValidationIssueKind::TooManyJoints,
+                    skin_label(&skin),
+                    format!(
+                        "the skin has {joint_count} joints, more than the {MAX_JOINTS} supported \
+                        on platforms without storage buffers, such as WebGL2"
+                    ),
+                );
+            }
 
             skins.push(handle.clone());
             if let Some(name) = skin.name() {
@@ -877,6 +1075,7 @@ async fn load_gltf<'a, 'b, 'c>(
                         #[cfg(feature = "bevy_animation")]
                         None,
                         &gltf.document,
+                        &ies_profiles,
                     );
                     if result.is_err() {
                         err = Some(result);
@@ -950,11 +1149,16 @@ async fn load_gltf<'a, 'b, 'c>(
         animations,
         #[cfg(feature = "bevy_animation")]
         named_animations,
+        material_variants: gltf
+            .variants()
+            .map(|variants| variants.map(|variant| variant.name().to_string()).collect())
+            .unwrap_or_default(),
         source: if settings.include_source {
             Some(gltf)
         } else {
             None
         },
+        validation_report,
     })
 }
 
@@ -1388,6 +1592,7 @@ fn load_node(
     #[cfg(feature = "bevy_animation")] animation_roots: &HashSet<usize>,
     #[cfg(feature = "bevy_animation")] mut animation_context: Option<AnimationContext>,
     document: &Document,
+    ies_profiles: &[Handle<IesProfile>],
 ) -> Result<(), GltfError> {
     let mut gltf_error = None;
     let transform = node_transform(gltf_node);
@@ -1487,17 +1692,13 @@ fn load_node(
                 // append primitives
                 for primitive in mesh.primitives() {
                     let material = primitive.material();
-                    let material_label = material_label(&material, is_scale_inverted);
-
-                    // This will make sure we load the default material now since it would not have been
-                    // added when iterating over all the gltf materials (since the default material is
-                    // not explicitly listed in the gltf).
-                    // It also ensures an inverted scale copy is instantiated if required.
-                    if !root_load_context.has_labeled_asset(&material_label)
-                        && !load_context.has_labeled_asset(&material_label)
-                    {
-                        load_material(&material, load_context, document, is_scale_inverted);
-                    }
+                    let material_handle = primitive_material_handle(
+                        &material,
+                        root_load_context,
+                        load_context,
+                        document,
+                        is_scale_inverted,
+                    );
 
                     let primitive_label = GltfAssetLabel::Primitive {
                         mesh: mesh.index(),
@@ -1508,11 +1709,36 @@ fn load_node(
                     let mut mesh_entity = parent.spawn((
                         // TODO: handle missing label handle errors here?
                         Mesh3d(load_context.get_label_handle(primitive_label.to_string())),
-                        MeshMaterial3d::<StandardMaterial>(
-                            load_context.get_label_handle(&material_label),
-                        ),
+                        MeshMaterial3d::<StandardMaterial>(material_handle.clone()),
                     ));
 
+                    let mut variant_materials = HashMap::default();
+                    for mapping in primitive.mappings() {
+                        let variant_material = primitive_material_handle(
+                            &mapping.material(),
+                            root_load_context,
+                            load_context,
+                            document,
+                            is_scale_inverted,
+                        );
+                        for &variant in mapping.variants() {
+                            if let Some(variant) = document
+                                .variants()
+                                .and_then(|mut variants| variants.nth(variant as usize))
+                            {
+                                variant_materials
+                                    .insert(variant.name().to_string(), variant_material.clone());
+                            }
+                        }
+                    }
+                    if !variant_materials.is_empty() {
+                        mesh_entity.insert(MaterialVariants {
+                            default_material: material_handle,
+                            materials: variant_materials,
+                            active: None,
+                        });
+                    }
+
                     let target_count = primitive.morph_targets().len();
                     if target_count != 0 {
                         let weights = match mesh.weights() {
@@ -1537,6 +1763,28 @@ fn load_node(
                         Vec3::from_slice(&bounds.max),
                     ));
 
+                    let lod_levels: Vec<_> = primitive_lods(&primitive)
+                        .iter()
+                        .enumerate()
+                        .map_while(|(lod_index, lod)| {
+                            let lod_label = GltfAssetLabel::PrimitiveLod {
+                                mesh: mesh.index(),
+                                primitive: primitive.index(),
+                                lod: lod_index + 1,
+                            }
+                            .to_string();
+                            root_load_context
+                                .has_labeled_asset(&lod_label)
+                                .then(|| AutoLodLevel {
+                                    mesh: load_context.get_label_handle(lod_label),
+                                    screen_coverage: lod.screen_coverage,
+                                })
+                        })
+                        .collect();
+                    if !lod_levels.is_empty() {
+                        mesh_entity.insert(AutoLod::new(lod_levels));
+                    }
+
                     if let Some(extras) = primitive.extras() {
                         mesh_entity.insert(GltfExtras {
                             value: extras.get().to_string(),
@@ -1635,6 +1883,35 @@ fn load_node(
                     }
                 }
             }
+
+            if let Some(ies_light) = node_ies_light(gltf_node) {
+                if let Some(profile) = ies_profiles.get(ies_light.light) {
+                    // The profile sets the intensity of the light, and its
+                    // photometric axis points down the node's -Z axis.
+                    parent.spawn((
+                        PointLight {
+                            color: Color::linear_rgb(
+                                ies_light.color[0],
+                                ies_light.color[1],
+                                ies_light.color[2],
+                            ),
+                            range: ies_light.range.unwrap_or(settings.ies_light_range),
+                            radius: 0.0,
+                            ..Default::default()
+                        },
+                        IesLightProfile {
+                            profile: profile.clone(),
+                            profile_intensity_multiplier: Some(ies_light.multiplier),
+                        },
+                    ));
+                } else {
+                    warn!(
+                        "Node {} uses IES profile {}, which doesn't exist",
+                        gltf_node.index(),
+                        ies_light.light
+                    );
+                }
+            }
         }
 
         // append other nodes
@@ -1654,6 +1931,7 @@ fn load_node(
                 #[cfg(feature = "bevy_animation")]
                 animation_context.clone(),
                 document,
+                ies_profiles,
             ) {
                 gltf_error = Some(err);
                 return;
@@ -1681,6 +1959,28 @@ fn load_node(
     }
 }
 
+/// Returns the handle to the given material as used by a primitive, loading it if necessary.
+///
+/// This will make sure we load the default material now since it would not have been
+/// added when iterating over all the gltf materials (since the default material is
+/// not explicitly listed in the gltf).
+/// It also ensures an inverted scale copy is instantiated if required.
+fn primitive_material_handle(
+    material: &Material,
+    root_load_context: &LoadContext,
+    load_context: &mut LoadContext,
+    document: &Document,
+    is_scale_inverted: bool,
+) -> Handle<StandardMaterial> {
+    let material_label = material_label(material, is_scale_inverted);
+    if !root_load_context.has_labeled_asset(&material_label)
+        && !load_context.has_labeled_asset(&material_label)
+    {
+        load_material(material, load_context, document, is_scale_inverted);
+    }
+    load_context.get_label_handle(&material_label)
+}
+
 fn primitive_name(mesh: &gltf::Mesh, primitive: &Primitive) -> String {
     let mesh_name = mesh.name().unwrap_or("Mesh");
     if mesh.primitives().len() > 1 {
@@ -1923,7 +2223,8 @@ impl<'a> GltfTreeIterator<'a> {
                 if skin.joints().len() > MAX_JOINTS && warned_about_max_joints.insert(skin.index())
                 {
                     warn!(
-                        "The glTF skin {} has {} joints, but the maximum supported is {}",
+                        "The glTF skin {} has {} joints, but the maximum supported on platforms \
+                        without storage buffers is {}",
                         skin.name()
                             .map(ToString::to_string)
                             .unwrap_or_else(|| skin.index().to_string()),
@@ -1999,7 +2300,7 @@ enum ImageOrPath {
     },
 }
 
-struct DataUri<'a> {
+pub(crate) struct DataUri<'a> {
     mime_type: &'a str,
     base64: bool,
     data: &'a str,
@@ -2011,7 +2312,7 @@ fn split_once(input: &str, delimiter: char) -> Option<(&str, &str)> {
 }
 
 impl<'a> DataUri<'a> {
-    fn parse(uri: &'a str) -> Result<DataUri<'a>, ()> {
+    pub(crate) fn parse(uri: &'a str) -> Result<DataUri<'a>, ()> {
         let uri = uri.strip_prefix("data:").ok_or(())?;
         let (mime_type, data) = split_once(uri, ',').ok_or(())?;
 
@@ -2027,7 +2328,7 @@ impl<'a> DataUri<'a> {
         })
     }
 
-    fn decode(&self) -> Result<Vec<u8>, base64::DecodeError> {
+    pub(crate) fn decode(&self) -> Result<Vec<u8>, base64::DecodeError> {
         if self.base64 {
             base64::Engine::decode(&base64::engine::general_purpose::STANDARD, self.data)
         } else {
@@ -2284,7 +2585,11 @@ fn material_needs_tangents(material: &Material) -> bool {
 mod test {
     use std::path::Path;
 
-    use crate::{Gltf, GltfAssetLabel, GltfNode, GltfSkin};
+    use crate::{
+        DracoAttributeRequest, DracoDecodeError, DracoDecoder, DracoMesh, Gltf, GltfAssetLabel,
+        GltfLoaderSettings, GltfMesh, GltfNode, GltfPrimitive, GltfSkin, GltfValidationIssueKind,
+        GltfValidationSeverity,
+    };
     use bevy_app::{App, TaskPoolPlugin};
     use bevy_asset::{
         io::{
@@ -2294,11 +2599,18 @@ mod test {
         AssetApp, AssetPlugin, AssetServer, Assets, Handle, LoadState,
     };
     use bevy_ecs::{system::Resource, world::World};
+    use bevy_image::Image;
     use bevy_log::LogPlugin;
-    use bevy_render::mesh::{skinning::SkinnedMeshInverseBindposes, MeshPlugin};
+    use bevy_render::mesh::{
+        skinning::SkinnedMeshInverseBindposes, Indices, Mesh, MeshPlugin, VertexAttributeValues,
+    };
     use bevy_scene::ScenePlugin;
 
     fn test_app(dir: Dir) -> App {
+        test_app_with_plugin(dir, crate::GltfPlugin::default())
+    }
+
+    fn test_app_with_plugin(dir: Dir, gltf_plugin: crate::GltfPlugin) -> App {
         let mut app = App::new();
         let reader = MemoryAssetReader { root: dir };
         app.register_asset_source(
@@ -2311,7 +2623,7 @@ mod test {
             AssetPlugin::default(),
             ScenePlugin,
             MeshPlugin,
-            crate::GltfPlugin::default(),
+            gltf_plugin,
         ));
 
         app.finish();
@@ -2334,6 +2646,14 @@ mod test {
     }
 
     fn load_gltf_into_app(gltf_path: &str, gltf: &str) -> App {
+        load_gltf_with_settings_into_app(gltf_path, gltf, |_| {})
+    }
+
+    fn load_gltf_with_settings_into_app(
+        gltf_path: &str,
+        gltf: &str,
+        settings: impl Fn(&mut GltfLoaderSettings) + Send + Sync + 'static,
+    ) -> App {
         #[expect(
             dead_code,
             reason = "This struct is used to keep the handle alive. As such, we have no need to handle the handle directly."
@@ -2346,7 +2666,7 @@ mod test {
         let mut app = test_app(dir);
         app.update();
         let asset_server = app.world().resource::<AssetServer>().clone();
-        let handle: Handle<Gltf> = asset_server.load(gltf_path.to_string());
+        let handle: Handle<Gltf> = asset_server.load_with_settings(gltf_path.to_string(), settings);
         let handle_id = handle.id();
         app.insert_resource(GltfHandle(handle));
         app.update();
@@ -2712,5 +3032,221 @@ mod test {
         assert_eq!(skinned_node.name, "skinned");
         assert_eq!(skinned_node.children.len(), 2);
         assert_eq!(skinned_node.skin.as_ref(), Some(&gltf_root.skins[0]));
+
+        // Files are only validated when asked to.
+        assert!(gltf_root.validation_report.is_none());
+    }
+
+    #[test]
+    fn validation_report() {
+        use base64::Engine;
+        use bevy_pbr::MAX_JOINTS;
+
+        let joint_count = MAX_JOINTS + 1;
+        let joints = (1..=joint_count)
+            .map(|joint| joint.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let joint_nodes = (1..=joint_count)
+            .map(|joint| format!(r#"{{ "name": "joint{joint}" }}"#))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let byte_length = joint_count * 64;
+        let inverse_bind_matrices =
+            base64::engine::general_purpose::STANDARD.encode(vec![0; byte_length]);
+        let gltf_path = "test.gltf";
+        let app = load_gltf_with_settings_into_app(
+            gltf_path,
+            &format!(
+                r#"
+{{
+    "asset": {{
+        "version": "2.0"
+    }},
+    "extensionsUsed": ["KHR_materials_sheen"],
+    "nodes": [
+        {{ "name": "skinned", "skin": 0, "children": [{joints}] }},
+        {joint_nodes}
+    ],
+    "skins": [{{ "inverseBindMatrices": 0, "joints": [{joints}] }}],
+    "buffers": [
+        {{
+            "uri": "data:application/gltf-buffer;base64,{inverse_bind_matrices}",
+            "byteLength": {byte_length}
+        }}
+    ],
+    "bufferViews": [{{ "buffer": 0, "byteLength": {byte_length} }}],
+    "accessors": [
+        {{ "bufferView": 0, "componentType": 5126, "count": {joint_count}, "type": "MAT4" }}
+    ],
+    "scene": 0,
+    "scenes": [{{ "nodes": [0] }}]
+}}
+"#
+            ),
+            |settings| settings.validate = true,
+        );
+        let asset_server = app.world().resource::<AssetServer>();
+        let handle = asset_server.load(gltf_path);
+        let gltf_root = app.world().resource::<Assets<Gltf>>().get(&handle).unwrap();
+        let report = gltf_root.validation_report.as_ref().unwrap();
+
+        let issues = report
+            .issues
+            .iter()
+            .map(|issue| (issue.severity, issue.kind, issue.subject.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            issues,
+            [
+                (
+                    GltfValidationSeverity::Warning,
+                    GltfValidationIssueKind::UnsupportedExtension,
+                    "KHR_materials_sheen"
+                ),
+                (
+                    GltfValidationSeverity::Warning,
+                    GltfValidationIssueKind::TooManyJoints,
+                    "Skin0"
+                ),
+            ]
+        );
+        assert!(!report.has_errors());
+    }
+
+    /// Decodes the fake `DRACO` bitstream of [`DRACO_GLTF`], filling each
+    /// requested attribute with values derived from its unique ID.
+    struct TestDracoDecoder;
+
+    impl DracoDecoder for TestDracoDecoder {
+        fn decode(
+            &self,
+            data: &[u8],
+            attributes: &[DracoAttributeRequest],
+        ) -> Result<DracoMesh, DracoDecodeError> {
+            if data != b"DRACO" {
+                return Err("invalid Draco data".into());
+            }
+            let mut mesh = DracoMesh {
+                indices: vec![2, 1, 0],
+                ..Default::default()
+            };
+            for attribute in attributes {
+                mesh.attributes
+                    .insert(attribute.unique_id, test_draco_values(attribute.unique_id));
+            }
+            Ok(mesh)
+        }
+    }
+
+    fn test_draco_values(unique_id: u32) -> Vec<u8> {
+        (0..9)
+            .flat_map(|index| (unique_id as f32 * 10.0 + index as f32).to_le_bytes())
+            .collect()
+    }
+
+    /// A triangle whose position, normal and morph target are only stored in
+    /// compressed form. Its buffer contains `DRACO`.
+    const DRACO_GLTF: &str = r#"
+{
+    "asset": {
+        "version": "2.0"
+    },
+    "extensionsUsed": ["KHR_draco_mesh_compression"],
+    "extensionsRequired": ["KHR_draco_mesh_compression"],
+    "buffers": [{ "uri": "data:application/gltf-buffer;base64,RFJBQ08=", "byteLength": 5 }],
+    "bufferViews": [{ "buffer": 0, "byteLength": 5 }],
+    "accessors": [
+        { "componentType": 5126, "count": 3, "type": "VEC3", "min": [0, 0, 0], "max": [1, 1, 1] },
+        { "componentType": 5126, "count": 3, "type": "VEC3" },
+        { "componentType": 5125, "count": 3, "type": "SCALAR" },
+        { "componentType": 5126, "count": 3, "type": "VEC3" }
+    ],
+    "meshes": [
+        {
+            "primitives": [
+                {
+                    "attributes": { "POSITION": 0, "NORMAL": 1 },
+                    "indices": 2,
+                    "targets": [{ "POSITION": 3 }],
+                    "extensions": {
+                        "KHR_draco_mesh_compression": {
+                            "bufferView": 0,
+                            "attributes": { "POSITION": 0, "NORMAL": 1 },
+                            "targets": [{ "POSITION": 2 }]
+                        }
+                    }
+                }
+            ],
+            "weights": [0]
+        }
+    ],
+    "nodes": [{ "mesh": 0 }],
+    "scene": 0,
+    "scenes": [{ "nodes": [0] }]
+}
+"#;
+
+    fn load_draco_gltf(gltf_plugin: crate::GltfPlugin) -> (App, Handle<Gltf>, LoadState) {
+        let gltf_path = "draco.gltf";
+        let dir = Dir::default();
+        dir.insert_asset_text(Path::new(gltf_path), DRACO_GLTF);
+        let mut app = test_app_with_plugin(dir, gltf_plugin);
+        app.init_asset::<Image>();
+        app.update();
+        let asset_server = app.world().resource::<AssetServer>().clone();
+        let handle: Handle<Gltf> = asset_server.load(gltf_path);
+        run_app_until(&mut app, |_world| {
+            let load_state = asset_server.get_load_state(handle.id()).unwrap();
+            (load_state.is_loaded() || load_state.is_failed()).then_some(())
+        });
+        let load_state = asset_server.get_load_state(handle.id()).unwrap();
+        (app, handle, load_state)
+    }
+
+    #[test]
+    fn draco_primitive() {
+        let (app, handle, load_state) =
+            load_draco_gltf(crate::GltfPlugin::default().with_draco_decoder(TestDracoDecoder));
+        assert!(load_state.is_loaded());
+
+        let gltf_root = app.world().resource::<Assets<Gltf>>().get(&handle).unwrap();
+        let gltf_mesh = app
+            .world()
+            .resource::<Assets<GltfMesh>>()
+            .get(&gltf_root.meshes[0])
+            .unwrap();
+        let GltfPrimitive { mesh, .. } = &gltf_mesh.primitives[0];
+        let mesh = app.world().resource::<Assets<Mesh>>().get(mesh).unwrap();
+
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            panic!("the mesh has no decoded positions");
+        };
+        assert_eq!(
+            positions,
+            &[[0.0, 1.0, 2.0], [3.0, 4.0, 5.0], [6.0, 7.0, 8.0]]
+        );
+        assert!(matches!(
+            mesh.attribute(Mesh::ATTRIBUTE_NORMAL),
+            Some(VertexAttributeValues::Float32x3(normals)) if normals[0] == [10.0, 11.0, 12.0]
+        ));
+        assert!(matches!(mesh.indices(), Some(Indices::U32(indices)) if indices == &[2, 1, 0]));
+
+        let morph_targets = mesh.morph_targets().unwrap();
+        assert!(app
+            .world()
+            .resource::<Assets<Image>>()
+            .contains(morph_targets));
+    }
+
+    #[test]
+    fn draco_primitive_without_decoder() {
+        let (_app, _handle, load_state) = load_draco_gltf(crate::GltfPlugin::default());
+        let LoadState::Failed(err) = load_state else {
+            panic!("the file loaded without a Draco decoder");
+        };
+        assert!(err.to_string().contains("no Draco decoder is registered"));
     }
 }
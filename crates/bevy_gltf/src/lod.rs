@@ -0,0 +1,531 @@
+//! Generation of simplified levels of detail for the mesh primitives of glTF
+//! files, while processing them.
+
+use bevy_asset::{
+    io::Writer,
+    meta::{AssetAction, AssetMeta},
+    processor::{Process, ProcessContext, ProcessError},
+    AsyncWriteExt,
+};
+use bevy_render::mesh::simplify_indices;
+use gltf::{
+    accessor::{DataType, Dimensions},
+    buffer,
+    mesh::Mode,
+    Document, Primitive, Semantic,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use thiserror::Error;
+use tracing::warn;
+
+use crate::{loader::DataUri, GltfLoader, GltfLoaderSettings};
+
+/// The name of the extension in which the [`GltfLodProcessor`] stores the
+/// levels of detail of a primitive.
+pub const BEVY_PRIMITIVE_LODS: &str = "BEVY_primitive_lods";
+
+/// The `UNSIGNED_INT` component type of glTF accessors.
+const UNSIGNED_INT: u32 = 5125;
+/// The `ELEMENT_ARRAY_BUFFER` target of glTF buffer views.
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+/// A simplified level of detail that the [`GltfLodProcessor`] generates for
+/// each mesh primitive.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct GltfLodSettings {
+    /// The fraction of the triangles of the primitive that this level keeps.
+    pub ratio: f32,
+    /// The screen coverage below which the previous level is replaced by this one.
+    ///
+    /// See [`AutoLodLevel::screen_coverage`](bevy_pbr::AutoLodLevel::screen_coverage).
+    pub screen_coverage: f32,
+}
+
+/// The settings of the [`GltfLodProcessor`].
+#[derive(Default, Serialize, Deserialize)]
+pub struct GltfLodProcessorSettings {
+    /// The simplified levels of detail to generate for each mesh primitive,
+    /// from the most to the least detailed.
+    #[serde(default)]
+    pub lods: Vec<GltfLodSettings>,
+    /// The settings the processed file is loaded with.
+    #[serde(default)]
+    pub loader_settings: GltfLoaderSettings,
+}
+
+/// An asset processor that generates simplified levels of detail for the mesh
+/// primitives of glTF files.
+///
+/// The indices of the levels are added to the file, which is otherwise
+/// unchanged, along with a `BEVY_primitive_lods` extension on each primitive
+/// that lists them. When loading the file, the [`GltfLoader`] turns them into
+/// meshes labeled with [`GltfAssetLabel::PrimitiveLod`](crate::GltfAssetLabel::PrimitiveLod),
+/// and gives the mesh entities of the scenes an [`AutoLod`](bevy_pbr::AutoLod)
+/// selecting between the primitive and its levels.
+///
+/// Only primitives that are triangle lists with normals, without morph
+/// targets, and that are stored in the file itself or in data URIs are
+/// simplified. Select this processor in the `.meta` file of a glTF file:
+///
+/// ```ron
+/// (
+///     meta_format_version: "1.0",
+///     asset: Process(
+///         processor: "bevy_gltf::lod::GltfLodProcessor",
+///         settings: (
+///             lods: [
+///                 (ratio: 0.5, screen_coverage: 0.25),
+///                 (ratio: 0.1, screen_coverage: 0.05),
+///             ],
+///         ),
+///     ),
+/// )
+/// ```
+#[derive(Default)]
+pub struct GltfLodProcessor;
+
+/// An error that occurred while generating the levels of detail of a glTF file.
+#[derive(Error, Debug)]
+pub enum GltfLodError {
+    /// The file isn't a valid glTF file.
+    #[error("invalid glTF: {0}")]
+    Gltf(#[from] gltf::Error),
+    /// The JSON of the file couldn't be read or written.
+    #[error("invalid glTF JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    /// Decoding the base64 data of a buffer failed.
+    #[error("failed to decode base64 buffer data")]
+    Base64Decode(#[from] base64::DecodeError),
+}
+
+impl Process for GltfLodProcessor {
+    type Settings = GltfLodProcessorSettings;
+    type OutputLoader = GltfLoader;
+
+    async fn process(
+        &self,
+        context: &mut ProcessContext<'_>,
+        meta: AssetMeta<(), Self>,
+        writer: &mut Writer,
+    ) -> Result<GltfLoaderSettings, ProcessError> {
+        let AssetAction::Process { settings, .. } = meta.asset else {
+            return Err(ProcessError::WrongMetaType);
+        };
+        let bytes = add_lods(context.asset_bytes(), &settings.lods)
+            .map_err(|err| ProcessError::AssetTransformError(err.into()))?;
+        writer
+            .write_all(&bytes)
+            .await
+            .map_err(|err| ProcessError::AssetSaveError(err.into()))?;
+        Ok(settings.loader_settings)
+    }
+}
+
+/// The levels of detail generated for a primitive.
+struct GeneratedLods {
+    mesh: usize,
+    primitive: usize,
+    levels: Vec<(Vec<u32>, f32)>,
+}
+
+/// Returns the glTF file `bytes` with the levels of detail of its primitives,
+/// in the same format.
+fn add_lods(bytes: &[u8], lods: &[GltfLodSettings]) -> Result<Vec<u8>, GltfLodError> {
+    let gltf = gltf::Gltf::from_slice(bytes)?;
+    let buffer_data = embedded_buffer_data(&gltf)?;
+
+    let mut generated = Vec::new();
+    for mesh in gltf.meshes() {
+        for primitive in mesh.primitives() {
+            let levels = simplify_primitive(mesh.index(), &primitive, &buffer_data, lods);
+            if let Some(levels) = levels.filter(|levels| !levels.is_empty()) {
+                generated.push(GeneratedLods {
+                    mesh: mesh.index(),
+                    primitive: primitive.index(),
+                    levels,
+                });
+            }
+        }
+    }
+    if generated.is_empty() {
+        return Ok(bytes.to_vec());
+    }
+
+    let is_glb = bytes.starts_with(b"glTF");
+    let (json, bin) = if is_glb {
+        let glb = gltf::binary::Glb::from_slice(bytes)?;
+        (glb.json.into_owned(), glb.bin.map(|bin| bin.into_owned()))
+    } else {
+        (bytes.to_vec(), None)
+    };
+    let mut root: Value = serde_json::from_slice(&json)?;
+
+    // The indices are appended to the binary chunk of GLB files, and stored in
+    // a new buffer with a data URI otherwise.
+    let bin_buffer = gltf
+        .buffers()
+        .find(|buffer| matches!(buffer.source(), buffer::Source::Bin))
+        .map(|buffer| buffer.index())
+        .filter(|_| bin.is_some());
+    let (buffer_index, mut data) = match bin_buffer {
+        Some(index) => (index, bin.unwrap_or_default()),
+        None => (gltf.buffers().len(), Vec::new()),
+    };
+
+    for lods in generated {
+        let mut levels = Vec::new();
+        for (indices, screen_coverage) in lods.levels {
+            data.resize(data.len().next_multiple_of(4), 0);
+            let view = push(
+                &mut root,
+                "bufferViews",
+                json!({
+                    "buffer": buffer_index,
+                    "byteOffset": data.len(),
+                    "byteLength": indices.len() * 4,
+                    "target": ELEMENT_ARRAY_BUFFER,
+                }),
+            );
+            let accessor = push(
+                &mut root,
+                "accessors",
+                json!({
+                    "bufferView": view,
+                    "componentType": UNSIGNED_INT,
+                    "count": indices.len(),
+                    "type": "SCALAR",
+                }),
+            );
+            data.extend(indices.iter().flat_map(|index| index.to_le_bytes()));
+            levels.push(json!({ "indices": accessor, "screenCoverage": screen_coverage }));
+        }
+        root["meshes"][lods.mesh]["primitives"][lods.primitive]["extensions"]
+            [BEVY_PRIMITIVE_LODS] = json!({ "levels": levels });
+    }
+
+    if bin_buffer.is_some() {
+        root["buffers"][buffer_index]["byteLength"] = json!(data.len());
+    } else {
+        let uri = format!(
+            "data:application/octet-stream;base64,{}",
+            base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &data)
+        );
+        push(
+            &mut root,
+            "buffers",
+            json!({ "byteLength": data.len(), "uri": uri }),
+        );
+    }
+    let extensions_used = &mut root["extensionsUsed"];
+    if !extensions_used.is_array() {
+        *extensions_used = json!([]);
+    }
+    if !extensions_used
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|extension| extension == BEVY_PRIMITIVE_LODS)
+    {
+        push(&mut root, "extensionsUsed", json!(BEVY_PRIMITIVE_LODS));
+    }
+
+    let json = serde_json::to_vec(&root)?;
+    Ok(if is_glb {
+        write_glb(&json, if bin_buffer.is_some() { &data } else { &[] })
+    } else {
+        json
+    })
+}
+
+/// Returns the data of the buffers of `gltf` that are stored in the file
+/// itself or in data URIs.
+fn embedded_buffer_data(gltf: &gltf::Gltf) -> Result<Vec<Option<Vec<u8>>>, GltfLodError> {
+    gltf.buffers()
+        .map(|buffer| match buffer.source() {
+            buffer::Source::Bin => Ok(gltf.blob.clone()),
+            buffer::Source::Uri(uri) => match DataUri::parse(uri) {
+                Ok(data_uri) => Ok(Some(data_uri.decode()?)),
+                Err(()) => Ok(None),
+            },
+        })
+        .collect()
+}
+
+/// Returns the indices and screen coverage of each level of detail of a
+/// primitive, or `None` if it can't be simplified.
+fn simplify_primitive(
+    mesh: usize,
+    primitive: &Primitive,
+    buffer_data: &[Option<Vec<u8>>],
+    lods: &[GltfLodSettings],
+) -> Option<Vec<(Vec<u32>, f32)>> {
+    // Flat normals are computed by duplicating the vertices of primitives
+    // without normals when loading them, which the indices wouldn't match.
+    if primitive.mode() != Mode::Triangles
+        || primitive.morph_targets().len() != 0
+        || primitive.get(&Semantic::Normals).is_none()
+    {
+        return None;
+    }
+    let reader = primitive.reader(|buffer| buffer_data.get(buffer.index())?.as_deref());
+    let positions: Vec<[f32; 3]> = match reader.read_positions() {
+        Some(positions) => positions.collect(),
+        None => {
+            warn!(
+                "Can't generate the levels of detail of primitive {} of mesh {}, as its positions aren't stored in the file",
+                primitive.index(),
+                mesh
+            );
+            return None;
+        }
+    };
+    let indices: Vec<u32> = match (primitive.indices(), reader.read_indices()) {
+        (None, _) => (0..positions.len() as u32).collect(),
+        (Some(_), Some(indices)) => indices.into_u32().collect(),
+        (Some(_), None) => {
+            warn!(
+                "Can't generate the levels of detail of primitive {} of mesh {}, as its indices aren't stored in the file",
+                primitive.index(),
+                mesh
+            );
+            return None;
+        }
+    };
+
+    let mut levels = Vec::new();
+    for lod in lods {
+        match simplify_indices(&positions, &indices, lod.ratio) {
+            // Empty levels can't be stored in buffer views, and wouldn't be
+            // visible anyway.
+            Ok(lod_indices) if lod_indices.is_empty() => break,
+            Ok(lod_indices) => levels.push((lod_indices, lod.screen_coverage)),
+            Err(err) => {
+                warn!(
+                    "Failed to generate the levels of detail of primitive {} of mesh {}: {}",
+                    primitive.index(),
+                    mesh,
+                    err
+                );
+                break;
+            }
+        }
+    }
+    Some(levels)
+}
+
+/// Pushes `value` to the array at `key` of the glTF JSON, creating it if
+/// needed, and returns its index.
+fn push(root: &mut Value, key: &str, value: Value) -> usize {
+    let array = &mut root[key];
+    if !array.is_array() {
+        *array = json!([]);
+    }
+    let array = array.as_array_mut().unwrap();
+    array.push(value);
+    array.len() - 1
+}
+
+/// Returns a GLB file with the given JSON and binary chunks.
+fn write_glb(json: &[u8], bin: &[u8]) -> Vec<u8> {
+    let json_length = json.len().next_multiple_of(4);
+    let bin_length = bin.len().next_multiple_of(4);
+    let mut length = 12 + 8 + json_length;
+    if !bin.is_empty() {
+        length += 8 + bin_length;
+    }
+
+    let mut glb = Vec::with_capacity(length);
+    glb.extend_from_slice(b"glTF");
+    glb.extend_from_slice(&2u32.to_le_bytes());
+    glb.extend_from_slice(&(length as u32).to_le_bytes());
+    glb.extend_from_slice(&(json_length as u32).to_le_bytes());
+    glb.extend_from_slice(b"JSON");
+    glb.extend_from_slice(json);
+    // The JSON chunk is padded with spaces and the binary chunk with zeros.
+    glb.resize(20 + json_length, b' ');
+    if !bin.is_empty() {
+        glb.extend_from_slice(&(bin_length as u32).to_le_bytes());
+        glb.extend_from_slice(b"BIN\0");
+        glb.extend_from_slice(bin);
+        glb.resize(length, 0);
+    }
+    glb
+}
+
+/// The `BEVY_primitive_lods` extension object of a primitive.
+#[derive(Deserialize)]
+struct PrimitiveLodsExtension {
+    levels: Vec<PrimitiveLod>,
+}
+
+/// A level of detail listed by the `BEVY_primitive_lods` extension.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PrimitiveLod {
+    /// The index of the accessor of the indices of the level.
+    pub(crate) indices: usize,
+    /// The screen coverage below which the previous level is replaced by this one.
+    pub(crate) screen_coverage: f32,
+}
+
+/// Returns the levels of detail listed by the `BEVY_primitive_lods` extension
+/// of a primitive, from the most to the least detailed.
+pub(crate) fn primitive_lods(primitive: &Primitive) -> Vec<PrimitiveLod> {
+    primitive
+        .extensions()
+        .and_then(|extensions| extensions.get(BEVY_PRIMITIVE_LODS))
+        .and_then(|extension| {
+            serde_json::from_value::<PrimitiveLodsExtension>(extension.clone()).ok()
+        })
+        .map(|extension| extension.levels)
+        .unwrap_or_default()
+}
+
+/// Reads the indices of a level of detail, if its accessor is valid.
+pub(crate) fn read_lod_indices(
+    document: &Document,
+    lod: &PrimitiveLod,
+    buffer_data: &[Vec<u8>],
+) -> Option<Vec<u32>> {
+    let accessor = document.accessors().nth(lod.indices)?;
+    if accessor.data_type() != DataType::U32 || accessor.dimensions() != Dimensions::Scalar {
+        return None;
+    }
+    let indices = gltf::accessor::Iter::<u32>::new(accessor, |buffer| {
+        buffer_data.get(buffer.index()).map(Vec::as_slice)
+    })?;
+    Some(indices.collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{add_lods, primitive_lods, read_lod_indices, GltfLodSettings};
+    use bevy_math::primitives::Plane3d;
+    use bevy_render::mesh::{Mesh, MeshBuilder, Meshable};
+    use serde_json::json;
+
+    /// Returns a glTF file with a subdivided plane, stored in a data URI or in
+    /// the binary chunk of a GLB file.
+    fn plane_gltf(glb: bool) -> Vec<u8> {
+        let mesh = Plane3d::default().mesh().subdivisions(8).build();
+        let positions = mesh
+            .attribute(Mesh::ATTRIBUTE_POSITION)
+            .unwrap()
+            .as_float3()
+            .unwrap();
+        let normals = mesh
+            .attribute(Mesh::ATTRIBUTE_NORMAL)
+            .unwrap()
+            .as_float3()
+            .unwrap();
+        let indices: Vec<u32> = mesh
+            .indices()
+            .unwrap()
+            .iter()
+            .map(|index| index as u32)
+            .collect();
+
+        let mut data = Vec::new();
+        data.extend(positions.iter().flatten().flat_map(|x| x.to_le_bytes()));
+        data.extend(normals.iter().flatten().flat_map(|x| x.to_le_bytes()));
+        data.extend(indices.iter().flat_map(|index| index.to_le_bytes()));
+        let vertex_bytes = positions.len() * 12;
+        let mut buffer = json!({ "byteLength": data.len() });
+        if !glb {
+            buffer["uri"] = json!(format!(
+                "data:application/octet-stream;base64,{}",
+                base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &data)
+            ));
+        }
+        let root = json!({
+            "asset": { "version": "2.0" },
+            "buffers": [buffer],
+            "bufferViews": [
+                { "buffer": 0, "byteOffset": 0, "byteLength": vertex_bytes },
+                { "buffer": 0, "byteOffset": vertex_bytes, "byteLength": vertex_bytes },
+                { "buffer": 0, "byteOffset": 2 * vertex_bytes, "byteLength": indices.len() * 4 },
+            ],
+            "accessors": [
+                {
+                    "bufferView": 0,
+                    "componentType": 5126,
+                    "count": positions.len(),
+                    "type": "VEC3",
+                    "min": [-0.5, 0.0, -0.5],
+                    "max": [0.5, 0.0, 0.5],
+                },
+                { "bufferView": 1, "componentType": 5126, "count": normals.len(), "type": "VEC3" },
+                { "bufferView": 2, "componentType": 5125, "count": indices.len(), "type": "SCALAR" },
+            ],
+            "meshes": [{
+                "primitives": [{ "attributes": { "POSITION": 0, "NORMAL": 1 }, "indices": 2 }],
+            }],
+        });
+        let json = serde_json::to_vec(&root).unwrap();
+        if glb {
+            super::write_glb(&json, &data)
+        } else {
+            json
+        }
+    }
+
+    fn assert_lods(bytes: &[u8]) {
+        let lods = [
+            GltfLodSettings {
+                ratio: 0.5,
+                screen_coverage: 0.25,
+            },
+            GltfLodSettings {
+                ratio: 0.1,
+                screen_coverage: 0.05,
+            },
+        ];
+        let processed = add_lods(bytes, &lods).unwrap();
+        assert_eq!(processed.starts_with(b"glTF"), bytes.starts_with(b"glTF"));
+
+        let gltf = gltf::Gltf::from_slice(&processed).unwrap();
+        assert!(gltf
+            .extensions_used()
+            .any(|extension| extension == super::BEVY_PRIMITIVE_LODS));
+        let buffer_data: Vec<Vec<u8>> = super::embedded_buffer_data(&gltf)
+            .unwrap()
+            .into_iter()
+            .map(Option::unwrap)
+            .collect();
+        let primitive = gltf.meshes().next().unwrap().primitives().next().unwrap();
+        let vertex_count = primitive.get(&gltf::Semantic::Positions).unwrap().count();
+        let triangle_count = primitive.indices().unwrap().count() / 3;
+
+        let levels = primitive_lods(&primitive);
+        assert_eq!(levels.len(), 2);
+        assert_eq!(levels[0].screen_coverage, 0.25);
+        assert_eq!(levels[1].screen_coverage, 0.05);
+        let mut previous_count = triangle_count;
+        for (level, lod) in levels.iter().zip(lods) {
+            let indices = read_lod_indices(&gltf.document, level, &buffer_data).unwrap();
+            assert_eq!(indices.len() % 3, 0);
+            assert!(indices.iter().all(|&index| (index as usize) < vertex_count));
+            let count = indices.len() / 3;
+            assert!(count <= previous_count);
+            assert!(count <= (triangle_count as f32 * lod.ratio).ceil() as usize);
+            previous_count = count;
+        }
+    }
+
+    #[test]
+    fn generate_gltf_lods() {
+        assert_lods(&plane_gltf(false));
+    }
+
+    #[test]
+    fn generate_glb_lods() {
+        assert_lods(&plane_gltf(true));
+    }
+
+    #[test]
+    fn skip_without_lods() {
+        let bytes = plane_gltf(false);
+        assert_eq!(add_lods(&bytes, &[]).unwrap(), bytes);
+    }
+}
@@ -0,0 +1,217 @@
+//! The report of the problems of a glTF file that affect how it renders or performs, which the
+//! [`GltfLoader`](crate::GltfLoader) produces when
+//! [`GltfLoaderSettings::validate`](crate::GltfLoaderSettings::validate) is set.
+
+use bevy_image::Image;
+use gltf::Document;
+use serde::{Deserialize, Serialize};
+
+use crate::{draco::KHR_DRACO_MESH_COMPRESSION, ies::EXT_LIGHTS_IES, lod::BEVY_PRIMITIVE_LODS};
+
+/// The glTF extensions that the [`GltfLoader`](crate::GltfLoader) fully reads, with the enabled
+/// cargo features.
+///
+/// The textures of `KHR_materials_anisotropy`, `KHR_materials_clearcoat`,
+/// `KHR_materials_transmission` and `KHR_materials_volume` are only read with the
+/// `pbr_anisotropy_texture`, `pbr_multi_layer_material_textures` and `pbr_transmission_textures`
+/// features, and [`KHR_DRACO_MESH_COMPRESSION`] is only read if a
+/// [`DracoDecoder`](crate::DracoDecoder) is registered, so they're only listed when they're fully
+/// read. The data added by other extensions is ignored.
+pub const SUPPORTED_EXTENSIONS: &[&str] = &[
+    "KHR_lights_punctual",
+    #[cfg(feature = "pbr_anisotropy_texture")]
+    "KHR_materials_anisotropy",
+    #[cfg(feature = "pbr_multi_layer_material_textures")]
+    "KHR_materials_clearcoat",
+    "KHR_materials_emissive_strength",
+    "KHR_materials_ior",
+    #[cfg(feature = "pbr_transmission_textures")]
+    "KHR_materials_transmission",
+    "KHR_materials_unlit",
+    "KHR_materials_variants",
+    #[cfg(feature = "pbr_transmission_textures")]
+    "KHR_materials_volume",
+    "KHR_texture_transform",
+    EXT_LIGHTS_IES,
+    BEVY_PRIMITIVE_LODS,
+];
+
+/// Returns why the data that `extension` adds to a glTF file is ignored, in full or in part, or
+/// `None` if it's fully read.
+fn unsupported_extension_reason(extension: &str, has_draco_decoder: bool) -> Option<&'static str> {
+    if SUPPORTED_EXTENSIONS.contains(&extension) {
+        return None;
+    }
+    match extension {
+        KHR_DRACO_MESH_COMPRESSION if has_draco_decoder => None,
+        KHR_DRACO_MESH_COMPRESSION => Some(
+            "no Draco decoder is registered, so the compressed primitives fall back to their \
+            uncompressed data, if any",
+        ),
+        "KHR_materials_anisotropy" => {
+            Some("its textures are ignored without the `pbr_anisotropy_texture` feature")
+        }
+        "KHR_materials_clearcoat" => {
+            Some("its textures are ignored without the `pbr_multi_layer_material_textures` feature")
+        }
+        "KHR_materials_transmission" | "KHR_materials_volume" => {
+            Some("its textures are ignored without the `pbr_transmission_textures` feature")
+        }
+        _ => Some("the extension isn't supported, so the data it adds is ignored"),
+    }
+}
+
+/// How bad a [`GltfValidationIssue`] is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GltfValidationSeverity {
+    /// The file renders as intended, but may perform worse than it could.
+    Warning,
+    /// Part of the file doesn't render as intended.
+    Error,
+}
+
+/// What a [`GltfValidationIssue`] is about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GltfValidationIssueKind {
+    /// A mesh has a material with a normal map, but no tangents, and they couldn't be generated.
+    MissingTangents,
+    /// A texture's size isn't a power of two.
+    NonPowerOfTwoTexture,
+    /// A texture isn't GPU compressed.
+    UncompressedTexture,
+    /// A mesh has no positions to compute its bounds from.
+    MissingAabb,
+    /// A skin has more joints than some platforms support.
+    TooManyJoints,
+    /// The file uses an extension that isn't fully read.
+    UnsupportedExtension,
+}
+
+/// A problem of a glTF file, in a [`GltfValidationReport`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GltfValidationIssue {
+    /// How bad the issue is.
+    pub severity: GltfValidationSeverity,
+    /// What the issue is about.
+    pub kind: GltfValidationIssueKind,
+    /// The asset label of the mesh, texture or skin that the issue is about, or the name of the
+    /// extension.
+    pub subject: String,
+    /// The explanation of the issue.
+    pub message: String,
+}
+
+/// The problems of a glTF file that affect how it renders or performs, in
+/// [`Gltf::validation_report`](crate::Gltf::validation_report).
+///
+/// The loader only checks what doesn't depend on the render device. Applications can
+/// [`push`](Self::push) the issues that do, such as skins with more joints than the device
+/// supports.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GltfValidationReport {
+    /// The issues, in the order they were found.
+    pub issues: Vec<GltfValidationIssue>,
+}
+
+impl GltfValidationReport {
+    /// Adds an issue to the report.
+    pub fn push(
+        &mut self,
+        severity: GltfValidationSeverity,
+        kind: GltfValidationIssueKind,
+        subject: impl Into<String>,
+        message: impl Into<String>,
+    ) {
+        self.issues.push(GltfValidationIssue {
+            severity,
+            kind,
+            subject: subject.into(),
+            message: message.into(),
+        });
+    }
+
+    /// Returns whether any issue is an [error](GltfValidationSeverity::Error).
+    pub fn has_errors(&self) -> bool {
+        self.issues
+            .iter()
+            .any(|issue| issue.severity == GltfValidationSeverity::Error)
+    }
+
+    /// Reports the extensions used by `document` that aren't fully read.
+    pub(crate) fn check_extensions(&mut self, document: &Document, has_draco_decoder: bool) {
+        for extension in document.extensions_used() {
+            let extension: &str = extension.as_ref();
+            if let Some(reason) = unsupported_extension_reason(extension, has_draco_decoder) {
+                self.push(
+                    GltfValidationSeverity::Warning,
+                    GltfValidationIssueKind::UnsupportedExtension,
+                    extension,
+                    reason,
+                );
+            }
+        }
+    }
+
+    /// Reports the issues of the texture loaded as `label`.
+    pub(crate) fn check_image(&mut self, label: &str, image: &Image) {
+        let size = image.size();
+        if !size.x.is_power_of_two() || !size.y.is_power_of_two() {
+            self.push(
+                GltfValidationSeverity::Warning,
+                GltfValidationIssueKind::NonPowerOfTwoTexture,
+                label,
+                format!(
+                    "the texture is {}x{}, which some GPU texture compression formats don't \
+                    support",
+                    size.x, size.y
+                ),
+            );
+        }
+        if !image.is_compressed() {
+            self.push(
+                GltfValidationSeverity::Warning,
+                GltfValidationIssueKind::UncompressedTexture,
+                label,
+                format!(
+                    "the texture isn't GPU compressed, and takes {:.1} MiB of GPU memory",
+                    image.data.len() as f32 / (1024.0 * 1024.0)
+                ),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{unsupported_extension_reason, SUPPORTED_EXTENSIONS};
+    use crate::KHR_DRACO_MESH_COMPRESSION;
+
+    #[test]
+    fn extensions_are_supported_with_their_features() {
+        for extension in SUPPORTED_EXTENSIONS {
+            assert_eq!(unsupported_extension_reason(extension, false), None);
+        }
+        assert!(unsupported_extension_reason("KHR_materials_sheen", true).is_some());
+
+        assert!(unsupported_extension_reason(KHR_DRACO_MESH_COMPRESSION, false).is_some());
+        assert_eq!(
+            unsupported_extension_reason(KHR_DRACO_MESH_COMPRESSION, true),
+            None
+        );
+
+        assert_eq!(
+            unsupported_extension_reason("KHR_materials_clearcoat", false).is_none(),
+            cfg!(feature = "pbr_multi_layer_material_textures")
+        );
+        assert_eq!(
+            unsupported_extension_reason("KHR_materials_anisotropy", false).is_none(),
+            cfg!(feature = "pbr_anisotropy_texture")
+        );
+        assert_eq!(
+            unsupported_extension_reason("KHR_materials_volume", false).is_none(),
+            cfg!(feature = "pbr_transmission_textures")
+        );
+    }
+}
@@ -5,7 +5,7 @@ use bevy_render::{
 };
 use bevy_utils::HashMap;
 use gltf::{
-    accessor::{DataType, Dimensions},
+    accessor::{util::ItemIter, DataType, Dimensions},
     mesh::util::{ReadColors, ReadJoints, ReadTexCoords, ReadWeights},
 };
 use thiserror::Error;
@@ -42,12 +42,25 @@ pub(crate) enum AccessFailed {
 struct BufferAccessor<'a> {
     accessor: gltf::Accessor<'a>,
     buffer_data: &'a Vec<Vec<u8>>,
+    /// Tightly packed elements to read instead of the accessor's buffer view,
+    /// e.g. decompressed Draco data.
+    decoded_data: Option<&'a [u8]>,
     normalization: Normalization,
 }
 
 impl<'a> BufferAccessor<'a> {
     /// Creates an iterator over the elements in this accessor
     fn iter<T: gltf::accessor::Item>(self) -> Result<gltf::accessor::Iter<'a, T>, AccessFailed> {
+        if let Some(decoded_data) = self.decoded_data {
+            let stride = size_of::<T>();
+            if decoded_data.len() != self.accessor.count() * stride {
+                return Err(AccessFailed::MalformedData);
+            }
+            return Ok(gltf::accessor::Iter::Standard(ItemIter::new(
+                decoded_data,
+                stride,
+            )));
+        }
         gltf::accessor::Iter::new(self.accessor, |buffer: gltf::Buffer| {
             self.buffer_data.get(buffer.index()).map(Vec::as_slice)
         })
@@ -104,12 +117,14 @@ impl<'a> VertexAttributeIter<'a> {
     fn from_accessor(
         accessor: gltf::Accessor<'a>,
         buffer_data: &'a Vec<Vec<u8>>,
+        decoded_data: Option<&'a [u8]>,
     ) -> Result<VertexAttributeIter<'a>, AccessFailed> {
         let normalization = Normalization(accessor.normalized());
         let format = (accessor.data_type(), accessor.dimensions());
         let acc = BufferAccessor {
             accessor,
             buffer_data,
+            decoded_data,
             normalization,
         };
         match format {
@@ -251,10 +266,17 @@ pub(crate) enum ConvertAttributeError {
     UnknownName(String),
 }
 
+/// Reads the values of a vertex attribute from its accessor, converting them
+/// to the format of the matching Bevy attribute.
+///
+/// If `decoded_data` is given, the values are read from it instead of the
+/// accessor's buffer view. It must contain the elements of the accessor,
+/// tightly packed.
 pub(crate) fn convert_attribute(
     semantic: gltf::Semantic,
     accessor: gltf::Accessor,
     buffer_data: &Vec<Vec<u8>>,
+    decoded_data: Option<&[u8]>,
     custom_vertex_attributes: &HashMap<Box<str>, MeshVertexAttribute>,
 ) -> Result<(MeshVertexAttribute, Values), ConvertAttributeError> {
     if let Some((attribute, conversion)) = match &semantic {
@@ -275,7 +297,8 @@ pub(crate) fn convert_attribute(
             .map(|attr| (*attr, ConversionMode::Any)),
         _ => None,
     } {
-        let raw_iter = VertexAttributeIter::from_accessor(accessor.clone(), buffer_data);
+        let raw_iter =
+            VertexAttributeIter::from_accessor(accessor.clone(), buffer_data, decoded_data);
         let converted_values = raw_iter.and_then(|iter| match conversion {
             ConversionMode::Any => iter.into_any_values(),
             ConversionMode::Rgba => iter.into_rgba_values(),
@@ -1,4 +1,4 @@
-use crate::{Children, HierarchyEvent, Parent};
+use crate::{disabling, Children, HierarchyEvent, Parent};
 use bevy_ecs::{
     bundle::Bundle,
     entity::Entity,
@@ -30,13 +30,15 @@ fn add_child_unchecked(world: &mut World, parent: Entity, child: Entity) {
 
 /// Sets [`Parent`] of the `child` to `new_parent`. Inserts [`Parent`] if `child` doesn't have one.
 fn update_parent(world: &mut World, child: Entity, new_parent: Entity) -> Option<Entity> {
-    let mut child = world.entity_mut(child);
-    if let Some(mut parent) = child.get_mut::<Parent>() {
+    let mut child_entity = world.entity_mut(child);
+    if let Some(mut parent) = child_entity.get_mut::<Parent>() {
         let previous = parent.0;
         *parent = Parent(new_parent);
+        // Observers don't see the in place mutation.
+        disabling::parent_mutated(world, child);
         Some(previous)
     } else {
-        child.insert(Parent(new_parent));
+        child_entity.insert(Parent(new_parent));
         None
     }
 }
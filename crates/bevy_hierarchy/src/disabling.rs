@@ -0,0 +1,199 @@
+use alloc::vec::Vec;
+
+use bevy_ecs::prelude::*;
+#[cfg(feature = "reflect")]
+use bevy_reflect::std_traits::ReflectDefault;
+
+use crate::{Children, Parent};
+
+/// Marks an entity with at least one [`Disabled`] ancestor.
+///
+/// Like [`Disabled`], this component hides the entity from the queries that don't explicitly ask
+/// for it, so that disabling an entity also disables all of its descendants.
+///
+/// This component is computed: it's only maintained after calling
+/// [`enable_disabled_propagation`], or adding the [`DisabledPropagationPlugin`], and is inserted
+/// and removed as [`Disabled`] is added to and removed from ancestors, and as entities are moved
+/// in the hierarchy. It shouldn't be added or removed manually.
+///
+/// Query for `Has<InheritedDisabled>` together with `Has<Disabled>` to find out whether an entity
+/// is disabled for either reason.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "reflect", derive(bevy_reflect::Reflect))]
+#[cfg_attr(feature = "reflect", reflect(Component, Default, Debug, PartialEq))]
+pub struct InheritedDisabled;
+
+/// Makes [`Disabled`] propagate to descendants through [`InheritedDisabled`].
+///
+/// Without this, [`Disabled`] only hides the entity it's on from queries. This must be called
+/// before any query that ought to skip the descendants of disabled entities is initialized,
+/// usually when building the app.
+///
+/// This is done by the [`DisabledPropagationPlugin`] when using `bevy_app`.
+pub fn enable_disabled_propagation(world: &mut World) {
+    world.init_resource::<DisabledPropagation>();
+    world.register_disabling_component::<InheritedDisabled>();
+    world.add_observer(on_disabled_changed::<OnInsert>);
+    world.add_observer(on_disabled_changed::<OnRemove>);
+    world.add_observer(on_parent_changed::<OnInsert>);
+    world.add_observer(on_parent_changed::<OnRemove>);
+}
+
+/// Makes [`Disabled`] propagate to the descendants of the entities it's on.
+///
+/// See [`enable_disabled_propagation`] for more information.
+#[cfg(feature = "bevy_app")]
+#[derive(Default)]
+pub struct DisabledPropagationPlugin;
+
+#[cfg(feature = "bevy_app")]
+impl bevy_app::Plugin for DisabledPropagationPlugin {
+    fn build(&self, app: &mut bevy_app::App) {
+        #[cfg(feature = "reflect")]
+        app.register_type::<InheritedDisabled>();
+
+        enable_disabled_propagation(app.world_mut());
+    }
+}
+
+/// Present once [`enable_disabled_propagation`] was called.
+#[derive(Resource, Default)]
+struct DisabledPropagation;
+
+/// Updates [`InheritedDisabled`] for an entity whose [`Parent`] was mutated in place, which the
+/// observers added by [`enable_disabled_propagation`] don't see.
+pub(crate) fn parent_mutated(world: &mut World, entity: Entity) {
+    if world.contains_resource::<DisabledPropagation>() {
+        update_subtree(world, entity);
+    }
+}
+
+fn on_disabled_changed<E: Event>(trigger: Trigger<E, Disabled>, mut commands: Commands) {
+    let entity = trigger.target();
+    // Removal observers run before the component is removed, so defer the update until the
+    // hierarchy is in its final state.
+    commands.queue(move |world: &mut World| update_children(world, entity));
+}
+
+fn on_parent_changed<E: Event>(trigger: Trigger<E, Parent>, mut commands: Commands) {
+    let entity = trigger.target();
+    commands.queue(move |world: &mut World| update_subtree(world, entity));
+}
+
+/// Returns `true` if the descendants of `entity` should be disabled.
+fn disables_descendants(world: &World, entity: Entity) -> bool {
+    world
+        .get_entity(entity)
+        .is_ok_and(|entity| entity.contains::<Disabled>() || entity.contains::<InheritedDisabled>())
+}
+
+/// Recomputes [`InheritedDisabled`] for `entity`, and for its descendants if it changed.
+fn update_subtree(world: &mut World, entity: Entity) {
+    let inherited = world
+        .get::<Parent>(entity)
+        .is_some_and(|parent| disables_descendants(world, parent.get()));
+    if set_inherited_disabled(world, entity, inherited) == Some(true) {
+        update_children(world, entity);
+    }
+}
+
+/// Recomputes [`InheritedDisabled`] for the descendants of `entity`.
+///
+/// Subtrees whose root keeps its state are skipped, since they are already up to date.
+fn update_children(world: &mut World, entity: Entity) {
+    let mut stack: Vec<(Entity, bool)> = Vec::new();
+    push_children(world, entity, &mut stack);
+    while let Some((entity, inherited)) = stack.pop() {
+        if set_inherited_disabled(world, entity, inherited) == Some(true) {
+            push_children(world, entity, &mut stack);
+        }
+    }
+}
+
+fn push_children(world: &World, entity: Entity, stack: &mut Vec<(Entity, bool)>) {
+    let inherited = disables_descendants(world, entity);
+    if let Some(children) = world.get::<Children>(entity) {
+        stack.extend(children.iter().map(|&child| (child, inherited)));
+    }
+}
+
+/// Inserts or removes [`InheritedDisabled`] on `entity`.
+///
+/// Returns whether the component was changed, or [`None`] if the entity doesn't exist.
+fn set_inherited_disabled(world: &mut World, entity: Entity, inherited: bool) -> Option<bool> {
+    let mut entity = world.get_entity_mut(entity).ok()?;
+    if entity.contains::<InheritedDisabled>() == inherited {
+        return Some(false);
+    }
+    if inherited {
+        entity.insert(InheritedDisabled);
+    } else {
+        entity.remove::<InheritedDisabled>();
+    }
+    Some(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::prelude::*;
+
+    use super::{enable_disabled_propagation, InheritedDisabled};
+    use crate::BuildChildren;
+
+    #[derive(Component)]
+    struct A;
+
+    fn inherits(world: &World, entity: Entity) -> bool {
+        world.entity(entity).contains::<InheritedDisabled>()
+    }
+
+    #[test]
+    fn disabling_propagates_to_descendants() {
+        let mut world = World::new();
+        enable_disabled_propagation(&mut world);
+
+        let root = world.spawn(A).id();
+        let child = world.spawn(A).set_parent(root).id();
+        let grandchild = world.spawn(A).set_parent(child).id();
+        world.flush();
+        assert_eq!(world.query::<&A>().iter(&world).count(), 3);
+
+        world.entity_mut(root).insert(Disabled);
+        world.flush();
+        assert!(!inherits(&world, root));
+        assert!(inherits(&world, child));
+        assert!(inherits(&world, grandchild));
+        assert_eq!(world.query::<&A>().iter(&world).count(), 0);
+
+        world.entity_mut(child).insert(Disabled);
+        world.entity_mut(root).remove::<Disabled>();
+        world.flush();
+        assert!(!inherits(&world, child));
+        assert!(inherits(&world, grandchild));
+        assert_eq!(world.query::<&A>().iter(&world).count(), 1);
+    }
+
+    #[test]
+    fn reparenting_updates_inherited_disabled() {
+        let mut world = World::new();
+        enable_disabled_propagation(&mut world);
+
+        let disabled = world.spawn(Disabled).id();
+        let enabled = world.spawn_empty().id();
+        let child = world.spawn(A).set_parent(disabled).id();
+        let grandchild = world.spawn(A).set_parent(child).id();
+        world.flush();
+        assert!(inherits(&world, grandchild));
+
+        world.entity_mut(child).set_parent(enabled);
+        world.flush();
+        assert!(!inherits(&world, child));
+        assert!(!inherits(&world, grandchild));
+
+        world.entity_mut(child).set_parent(disabled);
+        world.entity_mut(grandchild).remove_parent();
+        world.flush();
+        assert!(inherits(&world, child));
+        assert!(!inherits(&world, grandchild));
+    }
+}
@@ -23,7 +23,8 @@
 //! More advanced users may also appreciate
 //! [query extension methods] to traverse hierarchies,
 //! and [events] to notify hierarchical changes.
-//! There is also a [diagnostic plugin] to validate property propagation.
+//! There is also a [diagnostic plugin] to validate property propagation,
+//! and an opt-in [propagation] of disabled entities to their descendants.
 //!
 //! # Hierarchy management
 //!
@@ -50,6 +51,7 @@
 //! [events]: HierarchyEvent
 //! [hierarchical despawn extension methods]: DespawnRecursiveExt
 //! [plugin]: HierarchyPlugin
+//! [propagation]: enable_disabled_propagation
 //! [query extension methods]: HierarchyQueryExt
 
 #[cfg(feature = "std")]
@@ -75,16 +77,22 @@ pub use valid_parent_check_plugin::*;
 mod query_extension;
 pub use query_extension::*;
 
+mod disabling;
+pub use disabling::*;
+
 /// The hierarchy prelude.
 ///
 /// This includes the most common types in this crate, re-exported for your convenience.
 pub mod prelude {
     #[doc(hidden)]
-    pub use crate::{child_builder::*, components::*, hierarchy::*, query_extension::*};
+    pub use crate::{
+        child_builder::*, components::*, disabling::InheritedDisabled, hierarchy::*,
+        query_extension::*,
+    };
 
     #[doc(hidden)]
     #[cfg(feature = "bevy_app")]
-    pub use crate::{HierarchyPlugin, ValidParentCheckPlugin};
+    pub use crate::{DisabledPropagationPlugin, HierarchyPlugin, ValidParentCheckPlugin};
 }
 
 #[cfg(feature = "bevy_app")]
@@ -2,21 +2,122 @@ use crate::{Image, ImageFormat, ImageFormatSetting, ImageLoader, ImageLoaderSett
 
 use bevy_asset::saver::{AssetSaver, SavedAsset};
 use futures_lite::AsyncWriteExt;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use wgpu_types::TextureFormat;
 
-pub struct CompressedImageSaver;
+/// Compresses images with [Basis Universal] when processing assets.
+///
+/// Depending on the [`CompressionTarget`] of its [`CompressedImageSaverSettings`], the images
+/// are either saved as `.basis` files, which are transcoded to a format supported by the GPU
+/// when loaded, or as KTX2 files using a GPU compressed format directly.
+///
+/// [Basis Universal]: https://github.com/BinomialLLC/basis_universal
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CompressedImageSaver {
+    /// The platform the images are compressed for, when their target is
+    /// [`CompressionTarget::Platform`].
+    pub platform: TargetPlatform,
+}
+
+/// The kind of platform the processed assets are shipped to.
+///
+/// The asset processor usually runs on a different platform than the one the assets are
+/// processed for, so it's set explicitly instead of being detected.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TargetPlatform {
+    /// Desktop platforms, whose GPUs support BC formats.
+    #[default]
+    Desktop,
+    /// Android and iOS, whose GPUs support ASTC formats.
+    Mobile,
+    /// The web, where the supported formats depend on the browser and the GPU.
+    Web,
+}
+
+/// The kind of data stored in an image, which determines how it's compressed.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TextureKind {
+    /// Colors, stored in sRGB unless the source image is linear.
+    #[default]
+    Color,
+    /// Linear data, like roughness or occlusion maps.
+    Linear,
+    /// Tangent space normal maps. Only the red and green channels are kept when the target
+    /// supports two channel formats, and the blue channel is reconstructed in the shader.
+    NormalMap,
+}
+
+/// The format compressed images are saved in.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionTarget {
+    /// Save `.basis` files, which are transcoded when they're loaded to a format supported by the
+    /// GPU. This works on every platform, at the cost of transcoding at load time.
+    #[default]
+    Universal,
+    /// Pick the format for the [`TargetPlatform`] of the [`CompressedImageSaver`]:
+    /// [`Self::Bc`] on desktop, [`Self::Astc`] on mobile, and [`Self::Universal`] on the web.
+    Platform,
+    /// Save KTX2 files using BC7, or BC5 for normal maps. Supported by desktop GPUs.
+    Bc,
+    /// Save KTX2 files using ASTC 4x4. Supported by most mobile GPUs.
+    Astc,
+    /// Save KTX2 files using ETC2, or EAC RG11 for normal maps. Supported by mobile GPUs and
+    /// WebGL2.
+    Etc2,
+}
+
+impl CompressionTarget {
+    /// Returns the target [`Self::Platform`] stands for on the given platform, or `self` for
+    /// other targets.
+    pub fn resolve(self, platform: TargetPlatform) -> Self {
+        match (self, platform) {
+            (Self::Platform, TargetPlatform::Desktop) => Self::Bc,
+            (Self::Platform, TargetPlatform::Mobile) => Self::Astc,
+            (Self::Platform, TargetPlatform::Web) => Self::Universal,
+            (target, _) => target,
+        }
+    }
+}
+
+/// Settings for the [`CompressedImageSaver`], usually set in the `.meta` file of each image.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CompressedImageSaverSettings {
+    /// The kind of data stored in the image.
+    pub kind: TextureKind,
+    /// The format the image is saved in.
+    pub target: CompressionTarget,
+    /// Whether to generate mipmaps.
+    pub generate_mipmaps: bool,
+}
+
+impl Default for CompressedImageSaverSettings {
+    fn default() -> Self {
+        Self {
+            kind: TextureKind::Color,
+            target: CompressionTarget::Universal,
+            generate_mipmaps: true,
+        }
+    }
+}
 
 #[non_exhaustive]
 #[derive(Debug, Error)]
 pub enum CompressedImageSaverError {
     #[error(transparent)]
     Io(#[from] std::io::Error),
+    #[error("Unsupported image format {0:?}, images must be convertible to RGBA8")]
+    UnsupportedFormat(TextureFormat),
+    #[error("Failed to transcode mip level {0}")]
+    TranscodeError(u32),
+    #[error("Saving KTX2 images requires the `ktx2` feature")]
+    Ktx2Disabled,
 }
 
 impl AssetSaver for CompressedImageSaver {
     type Asset = Image;
 
-    type Settings = ();
+    type Settings = CompressedImageSaverSettings;
     type OutputLoader = ImageLoader;
     type Error = CompressedImageSaverError;
 
@@ -24,14 +125,49 @@ impl AssetSaver for CompressedImageSaver {
         &self,
         writer: &mut bevy_asset::io::Writer,
         image: SavedAsset<'_, Self::Asset>,
-        _settings: &Self::Settings,
+        settings: &Self::Settings,
     ) -> Result<ImageLoaderSettings, Self::Error> {
-        let is_srgb = image.texture_descriptor.format.is_srgb();
+        // Color images that were loaded as linear data stay linear. Formats without an sRGB
+        // variant, like the ones of grayscale images, are assumed to hold sRGB colors.
+        let source_format = image.texture_descriptor.format;
+        let source_is_linear =
+            !source_format.is_srgb() && source_format.add_srgb_suffix().is_srgb();
+        let is_srgb = settings.kind == TextureKind::Color && !source_is_linear;
+        let target = settings.target.resolve(self.platform);
+        let two_channels = settings.kind == TextureKind::NormalMap
+            && matches!(target, CompressionTarget::Bc | CompressionTarget::Etc2);
+
+        let format = if is_srgb {
+            TextureFormat::Rgba8UnormSrgb
+        } else {
+            TextureFormat::Rgba8Unorm
+        };
+        let converted;
+        let image: &Image = if image.texture_descriptor.format == format {
+            &image
+        } else {
+            converted =
+                image
+                    .convert(format)
+                    .ok_or(CompressedImageSaverError::UnsupportedFormat(
+                        image.texture_descriptor.format,
+                    ))?;
+            &converted
+        };
+        let size = image.size();
+
+        let mut data = image.data.clone();
+        if two_channels {
+            // Two channel formats are transcoded from the red and alpha channels of the source.
+            for pixel in data.chunks_exact_mut(4) {
+                pixel[3] = pixel[1];
+            }
+        }
 
         let compressed_basis_data = {
             let mut compressor_params = basis_universal::CompressorParams::new();
             compressor_params.set_basis_format(basis_universal::BasisTextureFormat::UASTC4x4);
-            compressor_params.set_generate_mipmaps(true);
+            compressor_params.set_generate_mipmaps(settings.generate_mipmaps);
             let color_space = if is_srgb {
                 basis_universal::ColorSpace::Srgb
             } else {
@@ -41,8 +177,7 @@ impl AssetSaver for CompressedImageSaver {
             compressor_params.set_uastc_quality_level(basis_universal::UASTC_QUALITY_DEFAULT);
 
             let mut source_image = compressor_params.source_image_mut(0);
-            let size = image.size();
-            source_image.init(&image.data, size.x, size.y, 4);
+            source_image.init(&data, size.x, size.y, 4);
 
             let mut compressor = basis_universal::Compressor::new(4);
             #[expect(
@@ -58,12 +193,309 @@ impl AssetSaver for CompressedImageSaver {
             compressor.basis_file().to_vec()
         };
 
-        writer.write_all(&compressed_basis_data).await?;
+        let format = match target {
+            CompressionTarget::Universal | CompressionTarget::Platform => {
+                writer.write_all(&compressed_basis_data).await?;
+                ImageFormat::Basis
+            }
+            #[cfg(feature = "ktx2")]
+            target => {
+                let ktx2 = ktx2_writer::transcode_to_ktx2(
+                    &compressed_basis_data,
+                    size.x,
+                    size.y,
+                    ktx2_writer::BlockFormat::new(target, two_channels, is_srgb),
+                )?;
+                writer.write_all(&ktx2).await?;
+                ImageFormat::Ktx2
+            }
+            #[cfg(not(feature = "ktx2"))]
+            _ => return Err(CompressedImageSaverError::Ktx2Disabled),
+        };
+
         Ok(ImageLoaderSettings {
-            format: ImageFormatSetting::Format(ImageFormat::Basis),
+            format: ImageFormatSetting::Format(format),
             is_srgb,
             sampler: image.sampler.clone(),
             asset_usage: image.asset_usage,
         })
     }
 }
+
+/// Writing of KTX2 files with a single 2D image and its mip levels.
+#[cfg(feature = "ktx2")]
+mod ktx2_writer {
+    use super::{CompressedImageSaverError, CompressionTarget};
+    use basis_universal::{DecodeFlags, TranscodeParameters, Transcoder, TranscoderTextureFormat};
+
+    const IDENTIFIER: [u8; 12] = [
+        0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+    ];
+    const HEADER_SIZE: usize = 80;
+    const LEVEL_INDEX_ENTRY_SIZE: usize = 24;
+
+    // Data format descriptor color models and channels, see the Khronos Data Format
+    // Specification.
+    const KHR_DF_MODEL_BC5: u8 = 132;
+    const KHR_DF_MODEL_BC7: u8 = 134;
+    const KHR_DF_MODEL_ETC2: u8 = 161;
+    const KHR_DF_MODEL_ASTC: u8 = 162;
+    const KHR_DF_CHANNEL_RED: u8 = 0;
+    const KHR_DF_CHANNEL_GREEN: u8 = 1;
+    const KHR_DF_CHANNEL_ETC2_COLOR: u8 = 2;
+    const KHR_DF_CHANNEL_ETC2_ALPHA: u8 = 15;
+
+    /// A GPU compressed block format, with 4x4 blocks of 16 bytes.
+    pub(super) struct BlockFormat {
+        transcode_format: TranscoderTextureFormat,
+        vk_format: u32,
+        color_model: u8,
+        is_srgb: bool,
+        /// The channel and bit offset of each sample.
+        samples: &'static [(u8, u16)],
+    }
+
+    impl BlockFormat {
+        pub(super) fn new(target: CompressionTarget, two_channels: bool, is_srgb: bool) -> Self {
+            let srgb = u32::from(is_srgb);
+            let (transcode_format, vk_format, color_model, samples): (_, _, _, &[_]) =
+                match (target, two_channels) {
+                    (CompressionTarget::Bc, false) => (
+                        TranscoderTextureFormat::BC7_RGBA,
+                        145 + srgb,
+                        KHR_DF_MODEL_BC7,
+                        &[(KHR_DF_CHANNEL_RED, 0)],
+                    ),
+                    (CompressionTarget::Bc, true) => (
+                        TranscoderTextureFormat::BC5_RG,
+                        141,
+                        KHR_DF_MODEL_BC5,
+                        &[(KHR_DF_CHANNEL_RED, 0), (KHR_DF_CHANNEL_GREEN, 64)],
+                    ),
+                    (CompressionTarget::Etc2, false) => (
+                        TranscoderTextureFormat::ETC2_RGBA,
+                        151 + srgb,
+                        KHR_DF_MODEL_ETC2,
+                        &[
+                            (KHR_DF_CHANNEL_ETC2_ALPHA, 0),
+                            (KHR_DF_CHANNEL_ETC2_COLOR, 64),
+                        ],
+                    ),
+                    (CompressionTarget::Etc2, true) => (
+                        TranscoderTextureFormat::ETC2_EAC_RG11,
+                        155,
+                        KHR_DF_MODEL_ETC2,
+                        &[(KHR_DF_CHANNEL_RED, 0), (KHR_DF_CHANNEL_GREEN, 64)],
+                    ),
+                    _ => (
+                        TranscoderTextureFormat::ASTC_4x4_RGBA,
+                        157 + srgb,
+                        KHR_DF_MODEL_ASTC,
+                        &[(KHR_DF_CHANNEL_RED, 0)],
+                    ),
+                };
+            Self {
+                transcode_format,
+                vk_format,
+                color_model,
+                is_srgb: is_srgb && !two_channels,
+                samples,
+            }
+        }
+
+        /// Returns the basic data format descriptor of this format, including its total size.
+        fn data_format_descriptor(&self) -> Vec<u8> {
+            let block_size = 24 + 16 * self.samples.len() as u16;
+            let mut dfd = Vec::new();
+            dfd.extend_from_slice(&(4 + u32::from(block_size)).to_le_bytes());
+            // Vendor and descriptor type, both 0 for the basic descriptor.
+            dfd.extend_from_slice(&0u32.to_le_bytes());
+            dfd.extend_from_slice(&2u16.to_le_bytes());
+            dfd.extend_from_slice(&block_size.to_le_bytes());
+            dfd.push(self.color_model);
+            // BT.709 primaries, and the sRGB or linear transfer function.
+            dfd.push(1);
+            dfd.push(if self.is_srgb { 2 } else { 1 });
+            // Straight alpha.
+            dfd.push(0);
+            // 4x4 blocks, stored as dimensions minus one.
+            dfd.extend_from_slice(&[3, 3, 0, 0]);
+            dfd.extend_from_slice(&[16, 0, 0, 0, 0, 0, 0, 0]);
+            let bit_length = (128 / self.samples.len() - 1) as u8;
+            for &(channel, bit_offset) in self.samples {
+                dfd.extend_from_slice(&bit_offset.to_le_bytes());
+                dfd.push(bit_length);
+                dfd.push(channel);
+                // Sample position.
+                dfd.extend_from_slice(&[0; 4]);
+                dfd.extend_from_slice(&0u32.to_le_bytes());
+                dfd.extend_from_slice(&u32::MAX.to_le_bytes());
+            }
+            dfd
+        }
+    }
+
+    /// Transcodes the first image of a `.basis` file and its mip levels to `format`, and returns
+    /// them as a KTX2 file.
+    pub(super) fn transcode_to_ktx2(
+        basis: &[u8],
+        width: u32,
+        height: u32,
+        format: BlockFormat,
+    ) -> Result<Vec<u8>, CompressedImageSaverError> {
+        let mut transcoder = Transcoder::new();
+        transcoder
+            .prepare_transcoding(basis)
+            .map_err(|_| CompressedImageSaverError::TranscodeError(0))?;
+        let level_count = transcoder.image_level_count(basis, 0);
+        let levels = (0..level_count)
+            .map(|level_index| {
+                transcoder
+                    .transcode_image_level(
+                        basis,
+                        format.transcode_format,
+                        TranscodeParameters {
+                            image_index: 0,
+                            level_index,
+                            decode_flags: Some(DecodeFlags::HIGH_QUALITY),
+                            ..Default::default()
+                        },
+                    )
+                    .map_err(|_| CompressedImageSaverError::TranscodeError(level_index))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let dfd = format.data_format_descriptor();
+        let dfd_offset = HEADER_SIZE + LEVEL_INDEX_ENTRY_SIZE * levels.len();
+
+        let mut ktx2 = Vec::new();
+        ktx2.extend_from_slice(&IDENTIFIER);
+        for value in [
+            format.vk_format,
+            // Type size, 1 for block compressed formats.
+            1,
+            width,
+            height,
+            // Depth, layer count and face count.
+            0,
+            0,
+            1,
+            level_count,
+            // No supercompression.
+            0,
+            dfd_offset as u32,
+            dfd.len() as u32,
+            // No key/value data.
+            0,
+            0,
+        ] {
+            ktx2.extend_from_slice(&value.to_le_bytes());
+        }
+        // No supercompression global data.
+        ktx2.extend_from_slice(&[0; 16]);
+
+        // Levels are aligned to the 16 bytes of a block.
+        let mut offset = (dfd_offset + dfd.len()).next_multiple_of(16);
+        for level in &levels {
+            ktx2.extend_from_slice(&(offset as u64).to_le_bytes());
+            ktx2.extend_from_slice(&(level.len() as u64).to_le_bytes());
+            ktx2.extend_from_slice(&(level.len() as u64).to_le_bytes());
+            offset = (offset + level.len()).next_multiple_of(16);
+        }
+        ktx2.extend_from_slice(&dfd);
+        for level in &levels {
+            ktx2.resize(ktx2.len().next_multiple_of(16), 0);
+            ktx2.extend_from_slice(level);
+        }
+        Ok(ktx2)
+    }
+}
+
+#[cfg(all(test, feature = "ktx2"))]
+mod tests {
+    use super::{
+        CompressedImageSaver, CompressedImageSaverSettings, CompressionTarget, TextureKind,
+    };
+    use crate::{CompressedImageFormats, Image, ImageSampler, ImageType};
+    use bevy_asset::{
+        saver::{AssetSaver, SavedAsset},
+        ErasedLoadedAsset, LoadedAsset, RenderAssetUsages,
+    };
+    use futures_lite::future::block_on;
+    use wgpu_types::{Extent3d, TextureDimension, TextureFormat};
+
+    fn save(image: Image, settings: &CompressedImageSaverSettings) -> (Vec<u8>, bool) {
+        let loaded: ErasedLoadedAsset = LoadedAsset::from(image).into();
+        let saved = SavedAsset::from_loaded(&loaded).unwrap();
+        let mut bytes = Vec::new();
+        let loader_settings =
+            block_on(CompressedImageSaver::default().save(&mut bytes, saved, settings)).unwrap();
+        (bytes, loader_settings.is_srgb)
+    }
+
+    #[test]
+    fn ktx2_round_trip() {
+        let image = |format| {
+            Image::new_fill(
+                Extent3d {
+                    width: 8,
+                    height: 8,
+                    depth_or_array_layers: 1,
+                },
+                TextureDimension::D2,
+                &[255, 128, 0, 255],
+                format,
+                RenderAssetUsages::default(),
+            )
+        };
+
+        for (format, kind, expected) in [
+            (
+                TextureFormat::Rgba8UnormSrgb,
+                TextureKind::Color,
+                TextureFormat::Bc7RgbaUnormSrgb,
+            ),
+            // Color images loaded as linear data stay linear.
+            (
+                TextureFormat::Rgba8Unorm,
+                TextureKind::Color,
+                TextureFormat::Bc7RgbaUnorm,
+            ),
+            (
+                TextureFormat::Rgba8Unorm,
+                TextureKind::Linear,
+                TextureFormat::Bc7RgbaUnorm,
+            ),
+            (
+                TextureFormat::Rgba8UnormSrgb,
+                TextureKind::NormalMap,
+                TextureFormat::Bc5RgUnorm,
+            ),
+        ] {
+            let settings = CompressedImageSaverSettings {
+                kind,
+                target: CompressionTarget::Bc,
+                generate_mipmaps: true,
+            };
+            let (bytes, is_srgb) = save(image(format), &settings);
+            let loaded = Image::from_buffer(
+                #[cfg(all(debug_assertions, feature = "dds"))]
+                "test.ktx2".into(),
+                &bytes,
+                ImageType::Extension("ktx2"),
+                CompressedImageFormats::BC,
+                is_srgb,
+                ImageSampler::Default,
+                RenderAssetUsages::default(),
+            )
+            .unwrap();
+            assert_eq!(
+                loaded.texture_descriptor.format, expected,
+                "{format:?} {kind:?}"
+            );
+            assert_eq!(loaded.width(), 8);
+            // 8x8, 4x4, 2x2 and 1x1.
+            assert_eq!(loaded.texture_descriptor.mip_level_count, 4);
+        }
+    }
+}
@@ -7,7 +7,7 @@ use super::ktx2::*;
 #[cfg(feature = "bevy_reflect")]
 use bevy_reflect::{std_traits::ReflectDefault, Reflect};
 
-use bevy_asset::{Asset, RenderAssetUsages};
+use bevy_asset::{Asset, AssetMemoryUsage, RenderAssetUsages};
 use bevy_color::{Color, ColorToComponents, Gray, LinearRgba, Srgba, Xyza};
 use bevy_math::{AspectRatio, UVec2, UVec3, Vec2};
 use core::hash::Hash;
@@ -716,6 +716,12 @@ impl Default for Image {
     }
 }
 
+impl AssetMemoryUsage for Image {
+    fn memory_usage(&self) -> usize {
+        self.data.len()
+    }
+}
+
 impl Image {
     /// Creates a new image from raw binary data and the corresponding metadata.
     ///
@@ -896,6 +902,7 @@ impl Image {
     /// supported as input and output:
     /// - `TextureFormat::R8Unorm`
     /// - `TextureFormat::Rg8Unorm`
+    /// - `TextureFormat::Rgba8Unorm`
     /// - `TextureFormat::Rgba8UnormSrgb`
     ///
     /// To get [`Image`] as a [`image::DynamicImage`] see:
@@ -912,6 +919,9 @@ impl Image {
                     image::DynamicImage::ImageLumaA8(img.into_luma_alpha8()),
                     false,
                 )),
+                TextureFormat::Rgba8Unorm => {
+                    Some((image::DynamicImage::ImageRgba8(img.into_rgba8()), false))
+                }
                 TextureFormat::Rgba8UnormSrgb => {
                     Some((image::DynamicImage::ImageRgba8(img.into_rgba8()), true))
                 }
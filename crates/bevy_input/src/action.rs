@@ -0,0 +1,818 @@
+//! Named input actions, bound to keyboard, mouse, gamepad and touch input.
+//!
+//! Instead of checking for specific keys and buttons, games can describe what the player can do
+//! with an action type, usually an enum, and bind each action to any number of physical inputs in
+//! an [`InputMap`]. Each frame, the [`InputActionPlugin`] updates the [`ActionState`] of the
+//! actions from the current input, so gameplay code can check
+//! `actions.just_pressed(Action::Jump)` whichever input the player has bound to jumping.
+//!
+//! Bindings are grouped in [`InputContext`]s, such as one for menus and one for gameplay, which
+//! can be enabled and disabled independently. Bindings can be changed at runtime, and with the
+//! `serialize` feature, the whole [`InputMap`] can be saved and loaded to persist the player's
+//! choices.
+//!
+//! ```
+//! # use bevy_app::prelude::*;
+//! # use bevy_ecs::prelude::*;
+//! # use bevy_input::{action::*, gamepad::{GamepadAxis, GamepadButton}, keyboard::KeyCode};
+//! #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+//! enum Action {
+//!     Jump,
+//!     MoveX,
+//!     Pause,
+//! }
+//!
+//! fn setup(app: &mut App) {
+//!     let gameplay = InputContext::new("gameplay")
+//!         .with_button(Action::Jump, ButtonBinding::Key(KeyCode::Space))
+//!         .with_button(Action::Jump, ButtonBinding::Gamepad(GamepadButton::South))
+//!         .with_axis(Action::MoveX, AxisBinding::Gamepad(GamepadAxis::LeftStickX))
+//!         .with_axis(
+//!             Action::MoveX,
+//!             AxisBinding::Buttons {
+//!                 negative: ButtonBinding::Key(KeyCode::KeyA),
+//!                 positive: ButtonBinding::Key(KeyCode::KeyD),
+//!             },
+//!         )
+//!         .with_button(Action::Pause, ButtonBinding::Key(KeyCode::Escape));
+//!
+//!     app.add_plugins(InputActionPlugin::<Action>::default())
+//!         .insert_resource(InputMap::default().with_context(gameplay))
+//!         .add_systems(Update, play);
+//! }
+//!
+//! fn play(actions: Res<ActionState<Action>>) {
+//!     if actions.just_pressed(Action::Jump) {
+//!         // Jump.
+//!     }
+//!     let speed = actions.value(Action::MoveX) * 5.0;
+//! }
+//! ```
+
+use crate::{
+    gamepad::{Gamepad, GamepadAxis, GamepadButton},
+    keyboard::KeyCode,
+    mouse::{AccumulatedMouseMotion, AccumulatedMouseScroll, MouseButton},
+    touch::Touches,
+    ButtonInput, InputSystem,
+};
+use alloc::{borrow::Cow, vec::Vec};
+use bevy_app::{App, Plugin, PreUpdate};
+use bevy_ecs::{
+    change_detection::DetectChangesMut,
+    entity::Entity,
+    schedule::IntoSystemConfigs,
+    system::{Local, Query, Res, ResMut, Resource, SystemParam},
+};
+use bevy_math::{ops, Vec2};
+use bevy_utils::{HashMap, HashSet};
+use core::{hash::Hash, marker::PhantomData};
+#[cfg(feature = "bevy_reflect")]
+use {
+    bevy_ecs::reflect::ReflectResource,
+    bevy_reflect::{std_traits::ReflectDefault, Reflect},
+};
+
+#[cfg(all(feature = "serialize", feature = "bevy_reflect"))]
+use bevy_reflect::{ReflectDeserialize, ReflectSerialize};
+
+/// How far a gamepad axis has to be pushed in a direction for a
+/// [`ButtonBinding::GamepadAxisPositive`] or [`ButtonBinding::GamepadAxisNegative`] to be pressed.
+pub const AXIS_PRESS_THRESHOLD: f32 = 0.5;
+
+/// A type whose values name the actions of a game, usually an enum.
+///
+/// This is implemented for every type with the same bounds as the inputs of a [`ButtonInput`].
+pub trait InputAction: Copy + Eq + Hash + Send + Sync + 'static {}
+
+impl<T: Copy + Eq + Hash + Send + Sync + 'static> InputAction for T {}
+
+/// Adds an [`InputMap`] and an [`ActionState`] for the actions of type `A`, and updates the
+/// [`ActionState`] from the input each frame.
+pub struct InputActionPlugin<A: InputAction>(PhantomData<A>);
+
+impl<A: InputAction> Default for InputActionPlugin<A> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<A: InputAction> Plugin for InputActionPlugin<A> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputMap<A>>()
+            .init_resource::<ActionState<A>>()
+            .add_systems(PreUpdate, update_action_state::<A>.after(InputSystem));
+    }
+}
+
+/// A physical input that is either pressed or released, which can be bound to an action.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(Reflect),
+    reflect(Debug, Hash, PartialEq)
+)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    all(feature = "serialize", feature = "bevy_reflect"),
+    reflect(Serialize, Deserialize)
+)]
+pub enum ButtonBinding {
+    /// A key of the keyboard.
+    Key(KeyCode),
+    /// A button of the mouse.
+    Mouse(MouseButton),
+    /// A button of a gamepad.
+    Gamepad(GamepadButton),
+    /// A gamepad axis, pushed past [`AXIS_PRESS_THRESHOLD`] in the positive direction.
+    GamepadAxisPositive(GamepadAxis),
+    /// A gamepad axis, pushed past [`AXIS_PRESS_THRESHOLD`] in the negative direction.
+    GamepadAxisNegative(GamepadAxis),
+    /// Any finger on a touch screen.
+    Touch,
+}
+
+/// A physical input with a value, which can be bound to an axis action.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(Reflect),
+    reflect(Debug, Hash, PartialEq)
+)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    all(feature = "serialize", feature = "bevy_reflect"),
+    reflect(Serialize, Deserialize)
+)]
+pub enum AxisBinding {
+    /// An axis of a gamepad, between `-1.0` and `1.0`.
+    Gamepad(GamepadAxis),
+    /// Two buttons, which give `-1.0` while `negative` is pressed and `1.0` while `positive` is
+    /// pressed, or `0.0` if both or neither are.
+    Buttons {
+        /// The button that pushes the axis in the negative direction.
+        negative: ButtonBinding,
+        /// The button that pushes the axis in the positive direction.
+        positive: ButtonBinding,
+    },
+    /// The horizontal motion of the mouse this frame, from [`AccumulatedMouseMotion`].
+    MouseMotionX,
+    /// The vertical motion of the mouse this frame, from [`AccumulatedMouseMotion`].
+    MouseMotionY,
+    /// The horizontal scrolling of the mouse this frame, from [`AccumulatedMouseScroll`].
+    MouseScrollX,
+    /// The vertical scrolling of the mouse this frame, from [`AccumulatedMouseScroll`].
+    MouseScrollY,
+}
+
+/// A named set of bindings of the actions of type `A`, which can be enabled and disabled
+/// together.
+///
+/// Games usually have a context for each mode of play with its own controls, such as menus,
+/// walking and driving.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "bevy_reflect", derive(Reflect), reflect(Debug))]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct InputContext<A: InputAction> {
+    name: Cow<'static, str>,
+    enabled: bool,
+    buttons: Vec<(A, ButtonBinding)>,
+    axes: Vec<(A, AxisBinding)>,
+}
+
+impl<A: InputAction> InputContext<A> {
+    /// Creates an enabled context without any bindings.
+    pub fn new(name: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            name: name.into(),
+            enabled: true,
+            buttons: Vec::new(),
+            axes: Vec::new(),
+        }
+    }
+
+    /// Returns this context with `binding` bound to `action`.
+    pub fn with_button(mut self, action: A, binding: ButtonBinding) -> Self {
+        self.bind_button(action, binding);
+        self
+    }
+
+    /// Returns this context with the axis `binding` bound to `action`.
+    pub fn with_axis(mut self, action: A, binding: AxisBinding) -> Self {
+        self.bind_axis(action, binding);
+        self
+    }
+
+    /// Returns this context, disabled.
+    pub fn disabled(mut self) -> Self {
+        self.enabled = false;
+        self
+    }
+
+    /// The name of this context.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the bindings of this context update the [`ActionState`].
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Enables or disables the bindings of this context.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Binds `binding` to `action`, in addition to its existing bindings.
+    pub fn bind_button(&mut self, action: A, binding: ButtonBinding) -> &mut Self {
+        if !self.buttons.contains(&(action, binding)) {
+            self.buttons.push((action, binding));
+        }
+        self
+    }
+
+    /// Binds the axis `binding` to `action`, in addition to its existing bindings.
+    pub fn bind_axis(&mut self, action: A, binding: AxisBinding) -> &mut Self {
+        if !self.axes.contains(&(action, binding)) {
+            self.axes.push((action, binding));
+        }
+        self
+    }
+
+    /// Replaces the `old` binding of `action` with `new`, keeping its other bindings.
+    ///
+    /// If `action` wasn't bound to `old`, `new` is added to its bindings.
+    pub fn rebind_button(&mut self, action: A, old: ButtonBinding, new: ButtonBinding) {
+        self.unbind_button(action, old);
+        self.bind_button(action, new);
+    }
+
+    /// Replaces the `old` axis binding of `action` with `new`, keeping its other bindings.
+    ///
+    /// If `action` wasn't bound to `old`, `new` is added to its bindings.
+    pub fn rebind_axis(&mut self, action: A, old: AxisBinding, new: AxisBinding) {
+        self.unbind_axis(action, old);
+        self.bind_axis(action, new);
+    }
+
+    /// Removes `binding` from the bindings of `action`, returning whether it was bound.
+    pub fn unbind_button(&mut self, action: A, binding: ButtonBinding) -> bool {
+        let len = self.buttons.len();
+        self.buttons.retain(|bound| *bound != (action, binding));
+        self.buttons.len() != len
+    }
+
+    /// Removes the axis `binding` from the bindings of `action`, returning whether it was bound.
+    pub fn unbind_axis(&mut self, action: A, binding: AxisBinding) -> bool {
+        let len = self.axes.len();
+        self.axes.retain(|bound| *bound != (action, binding));
+        self.axes.len() != len
+    }
+
+    /// Removes all the bindings of `action`.
+    pub fn unbind(&mut self, action: A) {
+        self.buttons.retain(|(bound, _)| *bound != action);
+        self.axes.retain(|(bound, _)| *bound != action);
+    }
+
+    /// The buttons bound to `action`.
+    pub fn button_bindings(&self, action: A) -> impl Iterator<Item = ButtonBinding> + '_ {
+        self.buttons
+            .iter()
+            .filter(move |(bound, _)| *bound == action)
+            .map(|(_, binding)| *binding)
+    }
+
+    /// The axes bound to `action`.
+    pub fn axis_bindings(&self, action: A) -> impl Iterator<Item = AxisBinding> + '_ {
+        self.axes
+            .iter()
+            .filter(move |(bound, _)| *bound == action)
+            .map(|(_, binding)| *binding)
+    }
+
+    /// The actions that `binding` is bound to, which can be used to warn about conflicts when
+    /// rebinding.
+    pub fn actions_bound_to(&self, binding: ButtonBinding) -> impl Iterator<Item = A> + '_ {
+        self.buttons
+            .iter()
+            .filter(move |(_, bound)| *bound == binding)
+            .map(|(action, _)| *action)
+    }
+}
+
+/// The bindings of the actions of type `A`, grouped in [`InputContext`]s.
+///
+/// The [`ActionState`] is updated from the bindings of all enabled contexts. With the `serialize`
+/// feature, this can be serialized to save the bindings chosen by the player, except for the
+/// [gamepad](InputMap::gamepad) that they're read from.
+#[derive(Resource, Debug, Clone)]
+#[cfg_attr(feature = "bevy_reflect", derive(Reflect), reflect(Default, Resource))]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct InputMap<A: InputAction> {
+    contexts: Vec<InputContext<A>>,
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    gamepad: Option<Entity>,
+}
+
+impl<A: InputAction> Default for InputMap<A> {
+    fn default() -> Self {
+        Self {
+            contexts: Vec::new(),
+            gamepad: None,
+        }
+    }
+}
+
+impl<A: InputAction> InputMap<A> {
+    /// Returns this map with `context` added to it.
+    pub fn with_context(mut self, context: InputContext<A>) -> Self {
+        self.add_context(context);
+        self
+    }
+
+    /// Adds `context` to this map, replacing the context with the same name if there is one.
+    pub fn add_context(&mut self, context: InputContext<A>) -> &mut Self {
+        match self.context_mut(context.name()) {
+            Some(existing) => *existing = context,
+            None => self.contexts.push(context),
+        }
+        self
+    }
+
+    /// Removes the context called `name` from this map, and returns it.
+    pub fn remove_context(&mut self, name: &str) -> Option<InputContext<A>> {
+        let index = self.contexts.iter().position(|c| c.name() == name)?;
+        Some(self.contexts.remove(index))
+    }
+
+    /// The context called `name`.
+    pub fn context(&self, name: &str) -> Option<&InputContext<A>> {
+        self.contexts.iter().find(|context| context.name() == name)
+    }
+
+    /// The context called `name`, mutably, for example to rebind its actions.
+    pub fn context_mut(&mut self, name: &str) -> Option<&mut InputContext<A>> {
+        self.contexts
+            .iter_mut()
+            .find(|context| context.name() == name)
+    }
+
+    /// The contexts of this map, in the order they were added.
+    pub fn contexts(&self) -> impl Iterator<Item = &InputContext<A>> {
+        self.contexts.iter()
+    }
+
+    /// Enables or disables the context called `name`, returning whether it exists.
+    pub fn set_context_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        let context = self.context_mut(name);
+        let exists = context.is_some();
+        if let Some(context) = context {
+            context.set_enabled(enabled);
+        }
+        exists
+    }
+
+    /// Enables the context called `name` and disables all the others, for example to switch from
+    /// gameplay to a menu.
+    pub fn enable_only(&mut self, name: &str) {
+        for context in &mut self.contexts {
+            context.enabled = context.name() == name;
+        }
+    }
+
+    /// The gamepad that the bindings read from, or `None` to read from every gamepad.
+    ///
+    /// Defaults to `None`.
+    pub fn gamepad(&self) -> Option<Entity> {
+        self.gamepad
+    }
+
+    /// Sets the gamepad that the bindings read from, or `None` to read from every gamepad.
+    pub fn set_gamepad(&mut self, gamepad: Option<Entity>) {
+        self.gamepad = gamepad;
+    }
+}
+
+/// The state of the actions of type `A`, updated from the [`InputMap`] each frame in
+/// [`PreUpdate`].
+///
+/// Button actions are pressed while any of their bindings are pressed. The value of an axis
+/// action is the value of its binding that is pushed the farthest, or `0.0`.
+#[derive(Resource, Debug, Clone)]
+#[cfg_attr(feature = "bevy_reflect", derive(Reflect), reflect(Default, Resource))]
+pub struct ActionState<A: InputAction> {
+    buttons: ButtonInput<A>,
+    axes: HashMap<A, f32>,
+}
+
+impl<A: InputAction> Default for ActionState<A> {
+    fn default() -> Self {
+        Self {
+            buttons: ButtonInput::default(),
+            axes: HashMap::default(),
+        }
+    }
+}
+
+impl<A: InputAction> ActionState<A> {
+    /// Returns `true` if `action` is pressed.
+    pub fn pressed(&self, action: A) -> bool {
+        self.buttons.pressed(action)
+    }
+
+    /// Returns `true` if `action` has been pressed during the current frame.
+    pub fn just_pressed(&self, action: A) -> bool {
+        self.buttons.just_pressed(action)
+    }
+
+    /// Returns `true` if `action` has been released during the current frame.
+    pub fn just_released(&self, action: A) -> bool {
+        self.buttons.just_released(action)
+    }
+
+    /// The value of the axis `action`, or `0.0` if none of its bindings have a value.
+    pub fn value(&self, action: A) -> f32 {
+        self.axes.get(&action).copied().unwrap_or(0.0)
+    }
+
+    /// The values of the axis actions `x` and `y`, as a vector.
+    pub fn axis_pair(&self, x: A, y: A) -> Vec2 {
+        Vec2::new(self.value(x), self.value(y))
+    }
+
+    /// The button state of the actions, for the other methods of [`ButtonInput`].
+    pub fn buttons(&self) -> &ButtonInput<A> {
+        &self.buttons
+    }
+
+    /// The button state of the actions, mutably, for example to press actions from an on-screen
+    /// control. Changes are overwritten when the state is next updated.
+    pub fn buttons_mut(&mut self) -> &mut ButtonInput<A> {
+        &mut self.buttons
+    }
+
+    /// Sets the value of the axis `action`, for example from an on-screen joystick. Changes are
+    /// overwritten when the state is next updated.
+    pub fn set_value(&mut self, action: A, value: f32) {
+        self.axes.insert(action, value);
+    }
+}
+
+/// The input resources that bindings are read from.
+///
+/// Besides being used to update the [`ActionState`], this can be used to implement rebinding, by
+/// waiting for the player to press the new input with [`BindingInputs::just_pressed_button`].
+#[derive(SystemParam)]
+pub struct BindingInputs<'w, 's> {
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    mouse_buttons: Res<'w, ButtonInput<MouseButton>>,
+    mouse_motion: Res<'w, AccumulatedMouseMotion>,
+    mouse_scroll: Res<'w, AccumulatedMouseScroll>,
+    touches: Res<'w, Touches>,
+    gamepads: Query<'w, 's, (Entity, &'static Gamepad)>,
+}
+
+impl BindingInputs<'_, '_> {
+    /// Returns `true` if `binding` is pressed, on `gamepad` or on any gamepad if it's `None`.
+    pub fn pressed(&self, binding: ButtonBinding, gamepad: Option<Entity>) -> bool {
+        let mut gamepads = self
+            .gamepads
+            .iter()
+            .filter(|(entity, _)| gamepad.is_none_or(|gamepad| gamepad == *entity))
+            .map(|(_, gamepad)| gamepad);
+        match binding {
+            ButtonBinding::Key(key) => self.keys.pressed(key),
+            ButtonBinding::Mouse(button) => self.mouse_buttons.pressed(button),
+            ButtonBinding::Gamepad(button) => gamepads.any(|gamepad| gamepad.pressed(button)),
+            ButtonBinding::GamepadAxisPositive(axis) => {
+                gamepads.any(|gamepad| gamepad.get(axis).unwrap_or(0.0) >= AXIS_PRESS_THRESHOLD)
+            }
+            ButtonBinding::GamepadAxisNegative(axis) => {
+                gamepads.any(|gamepad| gamepad.get(axis).unwrap_or(0.0) <= -AXIS_PRESS_THRESHOLD)
+            }
+            ButtonBinding::Touch => self.touches.iter().next().is_some(),
+        }
+    }
+
+    /// Returns `true` if `binding` has been pressed during the current frame, on `gamepad` or on
+    /// any gamepad if it's `None`.
+    ///
+    /// This is `true` even if `binding` was released again before the end of the frame. Gamepad
+    /// axes aren't tracked between frames, so this is always `false` for them.
+    pub fn just_pressed(&self, binding: ButtonBinding, gamepad: Option<Entity>) -> bool {
+        match binding {
+            ButtonBinding::Key(key) => self.keys.just_pressed(key),
+            ButtonBinding::Mouse(button) => self.mouse_buttons.just_pressed(button),
+            ButtonBinding::Gamepad(button) => self
+                .gamepads
+                .iter()
+                .filter(|(entity, _)| gamepad.is_none_or(|gamepad| gamepad == *entity))
+                .any(|(_, gamepad)| gamepad.just_pressed(button)),
+            ButtonBinding::GamepadAxisPositive(_) | ButtonBinding::GamepadAxisNegative(_) => false,
+            ButtonBinding::Touch => self.touches.any_just_pressed(),
+        }
+    }
+
+    /// The value of the axis `binding`, on `gamepad` or on the gamepad that pushes it the
+    /// farthest if it's `None`.
+    pub fn value(&self, binding: AxisBinding, gamepad: Option<Entity>) -> f32 {
+        match binding {
+            AxisBinding::Gamepad(axis) => self
+                .gamepads
+                .iter()
+                .filter(|(entity, _)| gamepad.is_none_or(|gamepad| gamepad == *entity))
+                .filter_map(|(_, gamepad)| gamepad.get(axis))
+                .fold(0.0, farthest),
+            AxisBinding::Buttons { negative, positive } => {
+                let value = |binding| {
+                    if self.pressed(binding, gamepad) {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                };
+                value(positive) - value(negative)
+            }
+            AxisBinding::MouseMotionX => self.mouse_motion.delta.x,
+            AxisBinding::MouseMotionY => self.mouse_motion.delta.y,
+            AxisBinding::MouseScrollX => self.mouse_scroll.delta.x,
+            AxisBinding::MouseScrollY => self.mouse_scroll.delta.y,
+        }
+    }
+
+    /// A button that has been pressed during the current frame, on `gamepad` or on any gamepad if
+    /// it's `None`.
+    ///
+    /// Keys are returned first, then mouse buttons, then gamepad buttons, then touches.
+    pub fn just_pressed_button(&self, gamepad: Option<Entity>) -> Option<ButtonBinding> {
+        self.keys
+            .get_just_pressed()
+            .next()
+            .map(|key| ButtonBinding::Key(*key))
+            .or_else(|| {
+                self.mouse_buttons
+                    .get_just_pressed()
+                    .next()
+                    .map(|button| ButtonBinding::Mouse(*button))
+            })
+            .or_else(|| {
+                self.gamepads
+                    .iter()
+                    .filter(|(entity, _)| gamepad.is_none_or(|gamepad| gamepad == *entity))
+                    .find_map(|(_, gamepad)| gamepad.get_just_pressed().next())
+                    .map(|button| ButtonBinding::Gamepad(*button))
+            })
+            .or_else(|| {
+                self.touches
+                    .any_just_pressed()
+                    .then_some(ButtonBinding::Touch)
+            })
+    }
+}
+
+/// Returns the value of `a` and `b` that is the farthest from zero.
+fn farthest(a: f32, b: f32) -> f32 {
+    if ops::abs(b) > ops::abs(a) {
+        b
+    } else {
+        a
+    }
+}
+
+/// Updates the [`ActionState`] of the actions of type `A` from the enabled contexts of their
+/// [`InputMap`].
+///
+/// Actions whose bindings were pressed and released within the same frame are both just pressed
+/// and just released, so that short taps aren't missed.
+pub fn update_action_state<A: InputAction>(
+    input_map: Res<InputMap<A>>,
+    mut action_state: ResMut<ActionState<A>>,
+    inputs: BindingInputs,
+    mut pressed: Local<HashSet<A>>,
+    mut tapped: Local<HashSet<A>>,
+) {
+    pressed.clear();
+    tapped.clear();
+    let gamepad = input_map.gamepad();
+
+    // Avoid clearing if it's not empty to ensure change detection is not triggered.
+    action_state.bypass_change_detection().buttons.clear();
+    if !action_state.axes.is_empty() {
+        action_state.axes.clear();
+    }
+
+    for context in input_map.contexts().filter(|context| context.is_enabled()) {
+        for (action, binding) in &context.buttons {
+            if inputs.pressed(*binding, gamepad) {
+                pressed.insert(*action);
+            } else if inputs.just_pressed(*binding, gamepad) {
+                tapped.insert(*action);
+            }
+        }
+        for (action, binding) in &context.axes {
+            let value = inputs.value(*binding, gamepad);
+            if value != 0.0 {
+                let current = action_state.axes.entry(*action).or_insert(0.0);
+                *current = farthest(*current, value);
+            }
+        }
+    }
+
+    let released: Vec<A> = action_state
+        .buttons
+        .get_pressed()
+        .filter(|action| !pressed.contains(*action))
+        .copied()
+        .collect();
+    for action in released {
+        action_state.buttons.release(action);
+    }
+    for action in pressed.iter() {
+        if !action_state.buttons.pressed(*action) {
+            action_state.buttons.press(*action);
+        }
+    }
+    for action in tapped.iter().filter(|action| !pressed.contains(*action)) {
+        action_state.buttons.press(*action);
+        action_state.buttons.release(*action);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ActionState, AxisBinding, ButtonBinding, InputActionPlugin, InputContext, InputMap,
+    };
+    use crate::{keyboard::KeyCode, ButtonInput, InputPlugin};
+    use bevy_app::App;
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+    enum Action {
+        Jump,
+        Move,
+        Confirm,
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((InputPlugin, InputActionPlugin::<Action>::default()))
+            .insert_resource(
+                InputMap::default()
+                    .with_context(
+                        InputContext::new("gameplay")
+                            .with_button(Action::Jump, ButtonBinding::Key(KeyCode::Space))
+                            .with_axis(
+                                Action::Move,
+                                AxisBinding::Buttons {
+                                    negative: ButtonBinding::Key(KeyCode::KeyA),
+                                    positive: ButtonBinding::Key(KeyCode::KeyD),
+                                },
+                            ),
+                    )
+                    .with_context(
+                        InputContext::new("menu")
+                            .with_button(Action::Confirm, ButtonBinding::Key(KeyCode::Space))
+                            .disabled(),
+                    ),
+            );
+        app
+    }
+
+    fn press(app: &mut App, key: KeyCode) {
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(key);
+    }
+
+    fn release(app: &mut App, key: KeyCode) {
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .release(key);
+    }
+
+    fn actions(app: &App) -> &ActionState<Action> {
+        app.world().resource::<ActionState<Action>>()
+    }
+
+    #[test]
+    fn button_actions_follow_their_bindings() {
+        let mut app = app();
+
+        press(&mut app, KeyCode::Space);
+        app.update();
+        assert!(actions(&app).just_pressed(Action::Jump));
+        assert!(!actions(&app).pressed(Action::Confirm));
+
+        app.update();
+        assert!(actions(&app).pressed(Action::Jump));
+        assert!(!actions(&app).just_pressed(Action::Jump));
+
+        release(&mut app, KeyCode::Space);
+        app.update();
+        assert!(actions(&app).just_released(Action::Jump));
+        assert!(!actions(&app).pressed(Action::Jump));
+    }
+
+    #[test]
+    fn axis_actions_combine_buttons() {
+        let mut app = app();
+
+        press(&mut app, KeyCode::KeyD);
+        app.update();
+        assert_eq!(actions(&app).value(Action::Move), 1.0);
+
+        press(&mut app, KeyCode::KeyA);
+        app.update();
+        assert_eq!(actions(&app).value(Action::Move), 0.0);
+
+        release(&mut app, KeyCode::KeyD);
+        app.update();
+        assert_eq!(actions(&app).value(Action::Move), -1.0);
+    }
+
+    #[test]
+    fn only_enabled_contexts_update_actions() {
+        let mut app = app();
+        app.world_mut()
+            .resource_mut::<InputMap<Action>>()
+            .enable_only("menu");
+
+        press(&mut app, KeyCode::Space);
+        app.update();
+        assert!(actions(&app).pressed(Action::Confirm));
+        assert!(!actions(&app).pressed(Action::Jump));
+    }
+
+    #[test]
+    fn rebinding_replaces_a_binding() {
+        let mut app = app();
+        app.world_mut()
+            .resource_mut::<InputMap<Action>>()
+            .context_mut("gameplay")
+            .unwrap()
+            .rebind_button(
+                Action::Jump,
+                ButtonBinding::Key(KeyCode::Space),
+                ButtonBinding::Key(KeyCode::KeyW),
+            );
+
+        press(&mut app, KeyCode::Space);
+        app.update();
+        assert!(!actions(&app).pressed(Action::Jump));
+
+        press(&mut app, KeyCode::KeyW);
+        app.update();
+        assert!(actions(&app).pressed(Action::Jump));
+    }
+
+    #[test]
+    fn presses_shorter_than_a_frame_are_not_missed() {
+        let mut app = app();
+
+        press(&mut app, KeyCode::Space);
+        release(&mut app, KeyCode::Space);
+        app.update();
+        assert!(actions(&app).just_pressed(Action::Jump));
+        assert!(actions(&app).just_released(Action::Jump));
+        assert!(!actions(&app).pressed(Action::Jump));
+
+        app.update();
+        assert!(!actions(&app).just_pressed(Action::Jump));
+        assert!(!actions(&app).just_released(Action::Jump));
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn input_maps_round_trip_through_serialization() {
+        use alloc::vec::Vec;
+
+        let app = app();
+        let input_map = app.world().resource::<InputMap<Action>>();
+
+        let serialized = ron::to_string(input_map).unwrap();
+        let deserialized: InputMap<Action> = ron::from_str(&serialized).unwrap();
+
+        assert_eq!(
+            deserialized
+                .contexts()
+                .map(InputContext::name)
+                .collect::<Vec<_>>(),
+            ["gameplay", "menu"]
+        );
+        for context in input_map.contexts() {
+            let other = deserialized.context(context.name()).unwrap();
+            assert_eq!(other.is_enabled(), context.is_enabled());
+            for action in [Action::Jump, Action::Move, Action::Confirm] {
+                assert!(other
+                    .button_bindings(action)
+                    .eq(context.button_bindings(action)));
+                assert!(other
+                    .axis_bindings(action)
+                    .eq(context.axis_bindings(action)));
+            }
+        }
+    }
+}
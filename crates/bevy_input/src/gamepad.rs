@@ -3,7 +3,7 @@
 use core::{ops::RangeInclusive, time::Duration};
 
 use crate::{Axis, ButtonInput, ButtonState};
-use alloc::string::String;
+use alloc::{string::String, vec::Vec};
 #[cfg(feature = "bevy_reflect")]
 use bevy_ecs::prelude::ReflectComponent;
 use bevy_ecs::{
@@ -23,7 +23,7 @@ use bevy_reflect::{std_traits::ReflectDefault, Reflect};
 use bevy_reflect::{ReflectDeserialize, ReflectSerialize};
 use bevy_utils::HashMap;
 use derive_more::derive::From;
-use log::{info, warn};
+use log::{debug, info, warn};
 use thiserror::Error;
 
 /// A gamepad event.
@@ -1643,6 +1643,12 @@ pub struct GamepadRumbleIntensity {
 }
 
 impl GamepadRumbleIntensity {
+    /// Don't rumble either gamepad motor.
+    pub const ZERO: Self = GamepadRumbleIntensity {
+        strong_motor: 0.0,
+        weak_motor: 0.0,
+    };
+
     /// Rumble both gamepad motors at maximum intensity.
     pub const MAX: Self = GamepadRumbleIntensity {
         strong_motor: 1.0,
@@ -1680,6 +1686,22 @@ impl GamepadRumbleIntensity {
             weak_motor: 0.0,
         }
     }
+
+    /// Returns this intensity with both motors scaled by `factor`.
+    pub fn scaled(self, factor: f32) -> Self {
+        Self {
+            strong_motor: self.strong_motor * factor,
+            weak_motor: self.weak_motor * factor,
+        }
+    }
+
+    /// Returns the sum of this intensity and `other`, saturated at `1.0` for each motor.
+    pub fn saturating_add(self, other: Self) -> Self {
+        Self {
+            strong_motor: (self.strong_motor + other.strong_motor).min(1.0),
+            weak_motor: (self.weak_motor + other.weak_motor).min(1.0),
+        }
+    }
 }
 
 /// An event that controls force-feedback rumbling of a [`Gamepad`] [`entity`](Entity).
@@ -1732,6 +1754,16 @@ pub enum GamepadRumbleRequest {
         /// The gamepad to rumble.
         gamepad: Entity,
     },
+    /// Play a [`RumbleEffect`] on the given gamepad.
+    ///
+    /// Like [`GamepadRumbleRequest::Add`], simultaneous effects add up to the sum of their
+    /// strengths.
+    Play {
+        /// The effect to play.
+        effect: RumbleEffect,
+        /// The gamepad to rumble.
+        gamepad: Entity,
+    },
     /// Stop all running rumbles on the given [`Entity`].
     Stop {
         /// The gamepad to stop rumble.
@@ -1743,21 +1775,471 @@ impl GamepadRumbleRequest {
     /// Get the [`Entity`] associated with this request.
     pub fn gamepad(&self) -> Entity {
         match self {
-            Self::Add { gamepad, .. } | Self::Stop { gamepad } => *gamepad,
+            Self::Add { gamepad, .. } | Self::Play { gamepad, .. } | Self::Stop { gamepad } => {
+                *gamepad
+            }
+        }
+    }
+}
+
+/// How the intensity of a [`RumblePulse`] ramps up at its start and down at its end.
+///
+/// Defaults to starting and ending the pulse abruptly.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(Reflect),
+    reflect(Debug, Default, PartialEq)
+)]
+pub struct RumbleEnvelope {
+    /// How long the intensity takes to ramp up from zero at the start of the pulse.
+    pub attack: Duration,
+    /// How long the intensity takes to ramp down to zero at the end of the pulse.
+    pub fade: Duration,
+}
+
+impl RumbleEnvelope {
+    /// Creates an envelope that ramps up for `attack` and down for `fade`.
+    pub const fn new(attack: Duration, fade: Duration) -> Self {
+        Self { attack, fade }
+    }
+
+    /// The factor that the intensity of a pulse of the given `duration` is scaled by at `elapsed`.
+    fn gain(&self, elapsed: Duration, duration: Duration) -> f32 {
+        let attack = if self.attack.is_zero() {
+            1.0
+        } else {
+            elapsed.as_secs_f32() / self.attack.as_secs_f32()
+        };
+        let fade = if self.fade.is_zero() {
+            1.0
+        } else {
+            duration.saturating_sub(elapsed).as_secs_f32() / self.fade.as_secs_f32()
+        };
+        attack.min(fade).clamp(0.0, 1.0)
+    }
+}
+
+/// A rumble of a given intensity, for a given duration.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "bevy_reflect", derive(Reflect), reflect(Debug, PartialEq))]
+pub struct RumblePulse {
+    /// The intensity of the pulse, which is reached after the attack of its envelope.
+    pub intensity: GamepadRumbleIntensity,
+    /// How long the pulse lasts, including the attack and fade of its envelope.
+    pub duration: Duration,
+    /// How the intensity ramps up and down.
+    pub envelope: RumbleEnvelope,
+}
+
+impl RumblePulse {
+    /// Creates a pulse at a constant `intensity` that lasts `duration`.
+    pub const fn new(intensity: GamepadRumbleIntensity, duration: Duration) -> Self {
+        Self {
+            intensity,
+            duration,
+            envelope: RumbleEnvelope::new(Duration::ZERO, Duration::ZERO),
+        }
+    }
+
+    /// Returns this pulse with the given envelope.
+    pub const fn with_envelope(mut self, envelope: RumbleEnvelope) -> Self {
+        self.envelope = envelope;
+        self
+    }
+
+    /// The intensity of the pulse at `elapsed` from its start.
+    pub fn intensity_at(&self, elapsed: Duration) -> GamepadRumbleIntensity {
+        self.intensity
+            .scaled(self.envelope.gain(elapsed, self.duration))
+    }
+}
+
+/// A step of a [`RumbleEffect`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "bevy_reflect", derive(Reflect), reflect(Debug, PartialEq))]
+pub enum RumbleStep {
+    /// Rumble with a pulse.
+    Pulse(RumblePulse),
+    /// Don't rumble for a duration.
+    Pause(Duration),
+}
+
+impl RumbleStep {
+    /// How long this step lasts.
+    pub fn duration(&self) -> Duration {
+        match self {
+            Self::Pulse(pulse) => pulse.duration,
+            Self::Pause(duration) => *duration,
         }
     }
 }
 
+/// How many times a [`RumbleEffect`] plays its steps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(Reflect),
+    reflect(Debug, Default, PartialEq)
+)]
+pub enum RumbleRepeat {
+    /// Play the steps the given number of times.
+    Times(u32),
+    /// Play the steps in a loop until the effect is stopped with [`GamepadRumbleRequest::Stop`].
+    Forever,
+}
+
+impl Default for RumbleRepeat {
+    fn default() -> Self {
+        Self::Times(1)
+    }
+}
+
+/// A timeline of rumble pulses and pauses, played on a gamepad with
+/// [`GamepadRumbleRequest::Play`].
+///
+/// # Example
+///
+/// A heartbeat, made of two short pulses, which repeats until it's stopped:
+///
+/// ```
+/// # use bevy_input::gamepad::{
+/// #     GamepadRumbleIntensity, RumbleEffect, RumbleEnvelope, RumblePulse, RumbleRepeat,
+/// # };
+/// # use core::time::Duration;
+/// let beat = RumblePulse::new(GamepadRumbleIntensity::strong_motor(0.8), Duration::from_millis(120))
+///     .with_envelope(RumbleEnvelope::new(Duration::ZERO, Duration::from_millis(80)));
+/// let heartbeat = RumbleEffect::new()
+///     .then_pulse(beat)
+///     .then_pause(Duration::from_millis(100))
+///     .then_pulse(beat)
+///     .then_pause(Duration::from_millis(600))
+///     .with_repeat(RumbleRepeat::Forever);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(Reflect),
+    reflect(Debug, Default, PartialEq)
+)]
+pub struct RumbleEffect {
+    steps: Vec<RumbleStep>,
+    repeat: RumbleRepeat,
+}
+
+impl RumbleEffect {
+    /// Creates an effect without any steps, which plays once.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an effect that rumbles at a constant `intensity` for `duration`.
+    pub fn constant(intensity: GamepadRumbleIntensity, duration: Duration) -> Self {
+        Self::new().then_pulse(RumblePulse::new(intensity, duration))
+    }
+
+    /// Returns this effect with `pulse` played after its current steps.
+    pub fn then_pulse(mut self, pulse: RumblePulse) -> Self {
+        self.steps.push(RumbleStep::Pulse(pulse));
+        self
+    }
+
+    /// Returns this effect with a pause of `duration` after its current steps.
+    pub fn then_pause(mut self, duration: Duration) -> Self {
+        self.steps.push(RumbleStep::Pause(duration));
+        self
+    }
+
+    /// Returns this effect, repeating its steps as given.
+    pub fn with_repeat(mut self, repeat: RumbleRepeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// The steps of this effect, in the order they're played.
+    pub fn steps(&self) -> &[RumbleStep] {
+        &self.steps
+    }
+
+    /// How many times the steps of this effect are played.
+    pub fn repeat(&self) -> RumbleRepeat {
+        self.repeat
+    }
+
+    /// How long it takes to play the steps of this effect once.
+    pub fn cycle_duration(&self) -> Duration {
+        self.steps.iter().map(RumbleStep::duration).sum()
+    }
+
+    /// How long this effect plays for, or `None` if it repeats forever.
+    pub fn duration(&self) -> Option<Duration> {
+        match self.repeat {
+            RumbleRepeat::Times(times) => Some(self.cycle_duration() * times),
+            RumbleRepeat::Forever => None,
+        }
+    }
+
+    /// The intensity of this effect at `elapsed` from its start, or `None` once it's finished.
+    pub fn intensity_at(&self, elapsed: Duration) -> Option<GamepadRumbleIntensity> {
+        let cycle = self.cycle_duration().as_nanos();
+        if cycle == 0 {
+            return None;
+        }
+        let elapsed = elapsed.as_nanos();
+        if let RumbleRepeat::Times(times) = self.repeat {
+            if elapsed / cycle >= u128::from(times) {
+                return None;
+            }
+        }
+
+        let mut time = Duration::from_nanos((elapsed % cycle) as u64);
+        for step in &self.steps {
+            if time < step.duration() {
+                return Some(match step {
+                    RumbleStep::Pulse(pulse) => pulse.intensity_at(time),
+                    RumbleStep::Pause(_) => GamepadRumbleIntensity::ZERO,
+                });
+            }
+            time -= step.duration();
+        }
+        Some(GamepadRumbleIntensity::ZERO)
+    }
+}
+
+/// Mixes the rumble effects that play at the same time on a gamepad into the intensity of its
+/// motors.
+///
+/// This is used by the input backends that support rumble to implement
+/// [`GamepadRumbleRequest`]s, with one mixer for each gamepad.
+#[derive(Clone, Debug, Default)]
+pub struct RumbleMixer {
+    /// The effects that are playing, and how long they've been playing for.
+    effects: Vec<(RumbleEffect, Duration)>,
+}
+
+impl RumbleMixer {
+    /// Starts playing `effect`, on top of the effects that are already playing.
+    pub fn play(&mut self, effect: RumbleEffect) {
+        self.effects.push((effect, Duration::ZERO));
+    }
+
+    /// Applies `request` to this mixer.
+    pub fn handle_request(&mut self, request: &GamepadRumbleRequest) {
+        match request {
+            GamepadRumbleRequest::Add {
+                duration,
+                intensity,
+                ..
+            } => self.play(RumbleEffect::constant(*intensity, *duration)),
+            GamepadRumbleRequest::Play { effect, .. } => self.play(effect.clone()),
+            GamepadRumbleRequest::Stop { .. } => self.stop(),
+        }
+    }
+
+    /// Stops all the effects.
+    pub fn stop(&mut self) {
+        self.effects.clear();
+    }
+
+    /// Returns `true` if no effects are playing.
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    /// Returns the sum of the intensities of the effects that are playing, saturated at `1.0`
+    /// for each motor, and then moves the effects forward by `delta`.
+    ///
+    /// Effects are removed once they're finished, so the intensity of the first call after an
+    /// effect is played is the intensity at its start.
+    pub fn advance(&mut self, delta: Duration) -> GamepadRumbleIntensity {
+        let mut intensity = GamepadRumbleIntensity::ZERO;
+        self.effects.retain_mut(|(effect, elapsed)| {
+            let Some(effect_intensity) = effect.intensity_at(*elapsed) else {
+                return false;
+            };
+            intensity = intensity.saturating_add(effect_intensity);
+            *elapsed += delta;
+            true
+        });
+        intensity
+    }
+}
+
+/// The optional features of a gamepad, as reported by the input backend.
+///
+/// Backends add this to the gamepads that they know the features of. Requests for features that
+/// a gamepad doesn't support are ignored.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(Reflect),
+    reflect(Debug, Default, PartialEq, Component)
+)]
+#[require(GamepadAdaptiveTriggers)]
+pub struct GamepadCapabilities {
+    /// Whether the gamepad has force-feedback motors, for [`GamepadRumbleRequest`]s.
+    pub rumble: bool,
+    /// Whether the triggers of the gamepad can resist being pulled, or vibrate, for
+    /// [`GamepadAdaptiveTriggerRequest`]s.
+    ///
+    /// This is always `false` for the gamepads of `bevy_gilrs`, as gilrs has no API for adaptive
+    /// triggers. Third-party backends can set it for the gamepads they drive.
+    pub adaptive_triggers: bool,
+}
+
+/// A trigger of a gamepad, for [`GamepadAdaptiveTriggerRequest`]s.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(Reflect),
+    reflect(Debug, Hash, PartialEq)
+)]
+pub enum GamepadTrigger {
+    /// The trigger of [`GamepadButton::LeftTrigger2`].
+    Left,
+    /// The trigger of [`GamepadButton::RightTrigger2`].
+    Right,
+}
+
+/// The force feedback of an adaptive trigger.
+///
+/// Positions along the trigger range from `0.0` when it's released to `1.0` when it's fully
+/// pulled, and strengths range from `0.0` to `1.0`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(Reflect),
+    reflect(Debug, Default, PartialEq)
+)]
+pub enum AdaptiveTriggerEffect {
+    /// The trigger moves freely.
+    #[default]
+    Off,
+    /// The trigger resists being pulled past `start`.
+    Resistance {
+        /// Where the resistance starts.
+        start: f32,
+        /// How hard the trigger resists.
+        strength: f32,
+    },
+    /// The trigger resists being pulled between `start` and `end`, and gives way past `end`, like
+    /// the trigger of a gun.
+    Weapon {
+        /// Where the resistance starts.
+        start: f32,
+        /// Where the trigger gives way.
+        end: f32,
+        /// How hard the trigger resists.
+        strength: f32,
+    },
+    /// The trigger vibrates once it's pulled past `start`.
+    Vibration {
+        /// Where the vibration starts.
+        start: f32,
+        /// How strongly the trigger vibrates.
+        amplitude: f32,
+        /// How fast the trigger vibrates, in hertz.
+        frequency: f32,
+    },
+}
+
+/// The effects of the adaptive triggers of a gamepad, which input backends apply to the gamepads
+/// that support them.
+///
+/// This is updated from [`GamepadAdaptiveTriggerRequest`]s by
+/// [`gamepad_adaptive_trigger_system`], and added to the gamepads with
+/// [`GamepadCapabilities`].
+///
+/// Bevy doesn't apply these effects itself: `bevy_gilrs` doesn't support adaptive triggers, so
+/// they only take effect with a third-party backend that reads this component.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(Reflect),
+    reflect(Debug, Default, PartialEq, Component)
+)]
+pub struct GamepadAdaptiveTriggers {
+    /// The effect of the left trigger.
+    pub left: AdaptiveTriggerEffect,
+    /// The effect of the right trigger.
+    pub right: AdaptiveTriggerEffect,
+}
+
+impl GamepadAdaptiveTriggers {
+    /// The effect of `trigger`.
+    pub fn get(&self, trigger: GamepadTrigger) -> AdaptiveTriggerEffect {
+        match trigger {
+            GamepadTrigger::Left => self.left,
+            GamepadTrigger::Right => self.right,
+        }
+    }
+
+    /// Sets the effect of `trigger`.
+    pub fn set(&mut self, trigger: GamepadTrigger, effect: AdaptiveTriggerEffect) {
+        match trigger {
+            GamepadTrigger::Left => self.left = effect,
+            GamepadTrigger::Right => self.right = effect,
+        }
+    }
+}
+
+/// An event that sets the force feedback of an adaptive trigger of a [`Gamepad`]
+/// [`entity`](Entity).
+///
+/// # Notes
+///
+/// Does nothing if the [`GamepadCapabilities`] of the gamepad don't include adaptive triggers,
+/// which is always the case with `bevy_gilrs`. Adaptive triggers are unsupported unless a
+/// third-party backend applies the [`GamepadAdaptiveTriggers`] of its gamepads.
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "bevy_reflect", derive(Reflect), reflect(Debug, PartialEq))]
+pub struct GamepadAdaptiveTriggerRequest {
+    /// The gamepad to set the trigger effect of.
+    pub gamepad: Entity,
+    /// The trigger to set the effect of.
+    pub trigger: GamepadTrigger,
+    /// The effect of the trigger.
+    pub effect: AdaptiveTriggerEffect,
+}
+
+/// Applies [`GamepadAdaptiveTriggerRequest`]s to the [`GamepadAdaptiveTriggers`] of the gamepads
+/// that support them.
+pub fn gamepad_adaptive_trigger_system(
+    mut requests: EventReader<GamepadAdaptiveTriggerRequest>,
+    mut gamepads: Query<(&GamepadCapabilities, &mut GamepadAdaptiveTriggers)>,
+) {
+    for request in requests.read() {
+        let Ok((capabilities, mut triggers)) = gamepads.get_mut(request.gamepad) else {
+            debug!(
+                "Tried to set an adaptive trigger of {}, but its capabilities are unknown",
+                request.gamepad
+            );
+            continue;
+        };
+        if !capabilities.adaptive_triggers {
+            debug!(
+                "Tried to set an adaptive trigger of {}, but it doesn't have adaptive triggers",
+                request.gamepad
+            );
+            continue;
+        }
+        triggers.set(request.trigger, request.effect);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
-        gamepad_connection_system, gamepad_event_processing_system, AxisSettings,
-        AxisSettingsError, ButtonAxisSettings, ButtonSettings, ButtonSettingsError, Gamepad,
-        GamepadAxis, GamepadAxisChangedEvent, GamepadButton, GamepadButtonChangedEvent,
-        GamepadButtonStateChangedEvent,
+        gamepad_adaptive_trigger_system, gamepad_connection_system,
+        gamepad_event_processing_system, AdaptiveTriggerEffect, AxisSettings, AxisSettingsError,
+        ButtonAxisSettings, ButtonSettings, ButtonSettingsError, Gamepad,
+        GamepadAdaptiveTriggerRequest, GamepadAdaptiveTriggers, GamepadAxis,
+        GamepadAxisChangedEvent, GamepadButton, GamepadButtonChangedEvent,
+        GamepadButtonStateChangedEvent, GamepadCapabilities,
         GamepadConnection::{Connected, Disconnected},
-        GamepadConnectionEvent, GamepadEvent, GamepadSettings, RawGamepadAxisChangedEvent,
-        RawGamepadButtonChangedEvent, RawGamepadEvent,
+        GamepadConnectionEvent, GamepadEvent, GamepadRumbleIntensity, GamepadSettings,
+        GamepadTrigger, RawGamepadAxisChangedEvent, RawGamepadButtonChangedEvent, RawGamepadEvent,
+        RumbleEffect, RumbleEnvelope, RumbleMixer, RumblePulse, RumbleRepeat,
     };
     use crate::ButtonState;
     use alloc::string::ToString;
@@ -1765,6 +2247,7 @@ mod tests {
     use bevy_ecs::entity::Entity;
     use bevy_ecs::event::Events;
     use bevy_ecs::schedule::IntoSystemConfigs;
+    use core::time::Duration;
 
     fn test_button_axis_settings_filter(
         settings: ButtonAxisSettings,
@@ -2912,4 +3395,105 @@ mod tests {
             4
         );
     }
+
+    #[test]
+    fn rumble_effects_follow_their_steps() {
+        let pulse = RumblePulse::new(GamepadRumbleIntensity::MAX, Duration::from_millis(100))
+            .with_envelope(RumbleEnvelope::new(
+                Duration::from_millis(50),
+                Duration::ZERO,
+            ));
+        let effect = RumbleEffect::new()
+            .then_pulse(pulse)
+            .then_pause(Duration::from_millis(100))
+            .with_repeat(RumbleRepeat::Times(2));
+
+        assert_eq!(effect.duration(), Some(Duration::from_millis(400)));
+        assert_eq!(
+            effect.intensity_at(Duration::ZERO),
+            Some(GamepadRumbleIntensity::ZERO)
+        );
+        assert_eq!(
+            effect.intensity_at(Duration::from_millis(25)),
+            Some(GamepadRumbleIntensity::MAX.scaled(0.5))
+        );
+        assert_eq!(
+            effect.intensity_at(Duration::from_millis(75)),
+            Some(GamepadRumbleIntensity::MAX)
+        );
+        assert_eq!(
+            effect.intensity_at(Duration::from_millis(150)),
+            Some(GamepadRumbleIntensity::ZERO)
+        );
+        assert_eq!(
+            effect.intensity_at(Duration::from_millis(275)),
+            Some(GamepadRumbleIntensity::MAX)
+        );
+        assert_eq!(effect.intensity_at(Duration::from_millis(400)), None);
+
+        let forever = effect.with_repeat(RumbleRepeat::Forever);
+        assert_eq!(forever.duration(), None);
+        assert_eq!(
+            forever.intensity_at(Duration::from_secs(60) + Duration::from_millis(75)),
+            Some(GamepadRumbleIntensity::MAX)
+        );
+    }
+
+    #[test]
+    fn rumble_mixer_sums_concurrent_effects() {
+        let mut mixer = RumbleMixer::default();
+        mixer.play(RumbleEffect::constant(
+            GamepadRumbleIntensity::strong_motor(0.75),
+            Duration::from_millis(100),
+        ));
+        mixer.play(RumbleEffect::constant(
+            GamepadRumbleIntensity::MAX.scaled(0.5),
+            Duration::from_millis(200),
+        ));
+
+        let step = Duration::from_millis(100);
+        assert_eq!(
+            mixer.advance(step),
+            GamepadRumbleIntensity {
+                strong_motor: 1.0,
+                weak_motor: 0.5,
+            }
+        );
+        assert_eq!(mixer.advance(step), GamepadRumbleIntensity::MAX.scaled(0.5));
+        assert_eq!(mixer.advance(step), GamepadRumbleIntensity::ZERO);
+        assert!(mixer.is_empty());
+    }
+
+    #[test]
+    fn adaptive_trigger_requests_need_the_capability() {
+        let mut app = App::new();
+        app.add_event::<GamepadAdaptiveTriggerRequest>()
+            .add_systems(PreUpdate, gamepad_adaptive_trigger_system);
+        let capable = app
+            .world_mut()
+            .spawn(GamepadCapabilities {
+                rumble: true,
+                adaptive_triggers: true,
+            })
+            .id();
+        let incapable = app.world_mut().spawn(GamepadCapabilities::default()).id();
+
+        let effect = AdaptiveTriggerEffect::Resistance {
+            start: 0.2,
+            strength: 0.8,
+        };
+        for gamepad in [capable, incapable] {
+            app.world_mut().send_event(GamepadAdaptiveTriggerRequest {
+                gamepad,
+                trigger: GamepadTrigger::Right,
+                effect,
+            });
+        }
+        app.update();
+
+        let triggers = |entity| *app.world().get::<GamepadAdaptiveTriggers>(entity).unwrap();
+        assert_eq!(triggers(capable).right, effect);
+        assert_eq!(triggers(capable).left, AdaptiveTriggerEffect::Off);
+        assert_eq!(triggers(incapable).right, AdaptiveTriggerEffect::Off);
+    }
 }
@@ -1,9 +1,30 @@
 //! Gestures functionality, from touchscreens and touchpads.
+//!
+//! The [`PinchGesture`], [`RotationGesture`], [`DoubleTapGesture`] and [`PanGesture`] events are
+//! sent by the platform, where it supports them. The [`TouchPinchGesture`],
+//! [`TouchRotationGesture`], [`SwipeGesture`] and [`LongPressGesture`] events are instead
+//! recognized from the [`Touches`] on every platform, with the thresholds of the
+//! [`TouchGestureSettings`].
 
-use bevy_ecs::event::Event;
-use bevy_math::Vec2;
+use core::{f32::consts::PI, time::Duration};
+
+use crate::touch::Touches;
+use bevy_ecs::{event::Event, system::Resource};
+use bevy_math::{ops, Vec2};
+use bevy_utils::{HashMap, HashSet};
 #[cfg(feature = "bevy_reflect")]
-use bevy_reflect::Reflect;
+use {
+    bevy_ecs::reflect::ReflectResource,
+    bevy_reflect::{std_traits::ReflectDefault, Reflect},
+};
+#[cfg(feature = "std")]
+use {
+    bevy_ecs::{
+        event::EventWriter,
+        system::{Local, Res},
+    },
+    bevy_time::{Real, Time},
+};
 
 #[cfg(all(feature = "serialize", feature = "bevy_reflect"))]
 use bevy_reflect::{ReflectDeserialize, ReflectSerialize};
@@ -72,3 +93,580 @@ pub struct DoubleTapGesture;
     reflect(Serialize, Deserialize)
 )]
 pub struct PanGesture(pub Vec2);
+
+/// Two-finger pinch gesture, recognized from the [`Touches`].
+///
+/// Sent each frame that the distance between the two fingers changes, once it has changed by
+/// more than [`TouchGestureSettings::pinch_threshold`] since they touched the screen.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "bevy_reflect", derive(Reflect), reflect(Debug, PartialEq))]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    all(feature = "serialize", feature = "bevy_reflect"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct TouchPinchGesture {
+    /// The ratio of the distance between the fingers to their distance in the previous frame, or
+    /// to their distance when they touched the screen for the first pinch.
+    ///
+    /// Greater than `1.0` when the fingers move apart, for zooming in, and less than `1.0` when
+    /// they move closer, for zooming out.
+    pub scale: f32,
+    /// The midpoint of the fingers, in the coordinates of [`TouchInput::position`](crate::touch::TouchInput::position).
+    pub center: Vec2,
+}
+
+/// Two-finger rotation gesture, recognized from the [`Touches`].
+///
+/// Sent each frame that the angle between the two fingers changes, once it has changed by more
+/// than [`TouchGestureSettings::rotation_threshold`] since they touched the screen.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "bevy_reflect", derive(Reflect), reflect(Debug, PartialEq))]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    all(feature = "serialize", feature = "bevy_reflect"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct TouchRotationGesture {
+    /// The change of the angle of the line between the fingers since the previous frame, or since
+    /// they touched the screen for the first rotation, in radians.
+    ///
+    /// Since touch positions have their Y axis pointing down, positive values are clockwise
+    /// rotations on the screen.
+    pub angle: f32,
+    /// The midpoint of the fingers, in the coordinates of [`TouchInput::position`](crate::touch::TouchInput::position).
+    pub center: Vec2,
+}
+
+/// The main direction of a [`SwipeGesture`] on the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(Reflect),
+    reflect(Debug, Hash, PartialEq)
+)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    all(feature = "serialize", feature = "bevy_reflect"),
+    reflect(Serialize, Deserialize)
+)]
+pub enum SwipeDirection {
+    /// Towards the left of the screen.
+    Left,
+    /// Towards the right of the screen.
+    Right,
+    /// Towards the top of the screen.
+    Up,
+    /// Towards the bottom of the screen.
+    Down,
+}
+
+impl SwipeDirection {
+    /// The direction along the largest component of `displacement`, in touch coordinates.
+    pub fn from_displacement(displacement: Vec2) -> Self {
+        if ops::abs(displacement.x) >= ops::abs(displacement.y) {
+            if displacement.x < 0.0 {
+                Self::Left
+            } else {
+                Self::Right
+            }
+        } else if displacement.y < 0.0 {
+            Self::Up
+        } else {
+            Self::Down
+        }
+    }
+}
+
+/// One-finger swipe gesture, recognized from the [`Touches`].
+///
+/// Sent when a finger is lifted after moving quickly enough, far enough, and for a short enough
+/// time, as configured in the [`TouchGestureSettings`].
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "bevy_reflect", derive(Reflect), reflect(Debug, PartialEq))]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    all(feature = "serialize", feature = "bevy_reflect"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct SwipeGesture {
+    /// The main direction of the swipe.
+    pub direction: SwipeDirection,
+    /// The average velocity of the finger during the swipe, in units of
+    /// [`TouchInput::position`](crate::touch::TouchInput::position) per second.
+    pub velocity: Vec2,
+    /// Where the finger touched the screen.
+    pub start: Vec2,
+    /// Where the finger was lifted.
+    pub end: Vec2,
+}
+
+/// One-finger long press gesture, recognized from the [`Touches`].
+///
+/// Sent once a finger has stayed on the screen for
+/// [`TouchGestureSettings::long_press_duration`] without moving further than
+/// [`TouchGestureSettings::long_press_max_distance`].
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "bevy_reflect", derive(Reflect), reflect(Debug, PartialEq))]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    all(feature = "serialize", feature = "bevy_reflect"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct LongPressGesture {
+    /// The id of the finger, as in [`TouchInput::id`](crate::touch::TouchInput::id).
+    pub id: u64,
+    /// The position of the finger.
+    pub position: Vec2,
+}
+
+/// The thresholds of the gestures recognized from the [`Touches`].
+///
+/// Distances are in the units of [`TouchInput::position`](crate::touch::TouchInput::position),
+/// which are logical pixels.
+#[derive(Resource, Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(Reflect),
+    reflect(Resource, Default, Debug, PartialEq)
+)]
+pub struct TouchGestureSettings {
+    /// How much the distance between two fingers has to change, as a fraction of their initial
+    /// distance, before [`TouchPinchGesture`]s are sent.
+    ///
+    /// Defaults to `0.05`.
+    pub pinch_threshold: f32,
+    /// How much the angle between two fingers has to change, in radians, before
+    /// [`TouchRotationGesture`]s are sent.
+    ///
+    /// Defaults to 10 degrees.
+    pub rotation_threshold: f32,
+    /// How far a finger has to move for a [`SwipeGesture`].
+    ///
+    /// Defaults to `50.0`.
+    pub swipe_min_distance: f32,
+    /// How fast a finger has to move on average for a [`SwipeGesture`].
+    ///
+    /// Defaults to `300.0` per second.
+    pub swipe_min_velocity: f32,
+    /// How long a finger can stay on the screen for a [`SwipeGesture`].
+    ///
+    /// Defaults to 500 milliseconds.
+    pub swipe_max_duration: Duration,
+    /// How long a finger has to stay on the screen for a [`LongPressGesture`].
+    ///
+    /// Defaults to 500 milliseconds.
+    pub long_press_duration: Duration,
+    /// How far a finger can move from where it touched the screen for a [`LongPressGesture`].
+    ///
+    /// Defaults to `10.0`.
+    pub long_press_max_distance: f32,
+}
+
+impl Default for TouchGestureSettings {
+    fn default() -> Self {
+        Self {
+            pinch_threshold: 0.05,
+            rotation_threshold: 10.0_f32.to_radians(),
+            swipe_min_distance: 50.0,
+            swipe_min_velocity: 300.0,
+            swipe_max_duration: Duration::from_millis(500),
+            long_press_duration: Duration::from_millis(500),
+            long_press_max_distance: 10.0,
+        }
+    }
+}
+
+/// A gesture recognized by a [`TouchGestureRecognizer`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TouchGesture {
+    /// A [`TouchPinchGesture`].
+    Pinch(TouchPinchGesture),
+    /// A [`TouchRotationGesture`].
+    Rotation(TouchRotationGesture),
+    /// A [`SwipeGesture`].
+    Swipe(SwipeGesture),
+    /// A [`LongPressGesture`].
+    LongPress(LongPressGesture),
+}
+
+/// The two fingers of a pinch or rotation gesture.
+#[derive(Debug, Clone, Copy)]
+struct TwoFingerGesture {
+    ids: (u64, u64),
+    /// The distance between the fingers when the last pinch was sent, or when they touched the
+    /// screen if they haven't pinched yet.
+    distance: f32,
+    /// The angle between the fingers when the last rotation was sent, or when they touched the
+    /// screen if they haven't rotated yet.
+    angle: f32,
+    pinching: bool,
+    rotating: bool,
+}
+
+/// Recognizes gestures from the state of the [`Touches`] in each frame.
+///
+/// [`touch_gesture_system`] uses one to send the gesture events, but this can also be used
+/// directly, for example to recognize gestures from recorded touches.
+#[derive(Debug, Clone, Default)]
+pub struct TouchGestureRecognizer {
+    /// When each finger that is on the screen touched it.
+    start_times: HashMap<u64, Duration>,
+    /// The fingers that were on the screen at the same time as another one, which can't swipe
+    /// or long press.
+    multi_touch: HashSet<u64>,
+    /// The fingers that have already long pressed, which can't swipe.
+    long_pressed: HashSet<u64>,
+    two_fingers: Option<TwoFingerGesture>,
+}
+
+impl TouchGestureRecognizer {
+    /// Updates the recognizer with the `touches` of a frame, at the time `now`, and calls `emit`
+    /// for each gesture that is recognized.
+    ///
+    /// `now` can be measured from any point, but must not decrease from one update to the next.
+    pub fn update(
+        &mut self,
+        touches: &Touches,
+        now: Duration,
+        settings: &TouchGestureSettings,
+        mut emit: impl FnMut(TouchGesture),
+    ) {
+        for touch in touches.iter_just_pressed() {
+            self.start_times.insert(touch.id(), now);
+            self.long_pressed.remove(&touch.id());
+        }
+
+        let pressed = touches.iter().count();
+        if pressed > 1 {
+            self.multi_touch
+                .extend(touches.iter().map(|touch| touch.id()));
+        }
+
+        self.update_two_fingers(touches, pressed, settings, &mut emit);
+
+        // Long presses.
+        if pressed == 1 {
+            for touch in touches.iter() {
+                let id = touch.id();
+                let Some(&start_time) = self.start_times.get(&id) else {
+                    continue;
+                };
+                if self.multi_touch.contains(&id)
+                    || self.long_pressed.contains(&id)
+                    || now.saturating_sub(start_time) < settings.long_press_duration
+                    || touch.distance().length() > settings.long_press_max_distance
+                {
+                    continue;
+                }
+                self.long_pressed.insert(id);
+                emit(TouchGesture::LongPress(LongPressGesture {
+                    id,
+                    position: touch.position(),
+                }));
+            }
+        }
+
+        // Swipes.
+        for touch in touches.iter_just_released() {
+            let id = touch.id();
+            let Some(&start_time) = self.start_times.get(&id) else {
+                continue;
+            };
+            if self.multi_touch.contains(&id) || self.long_pressed.contains(&id) {
+                continue;
+            }
+            let duration = now.saturating_sub(start_time);
+            let displacement = touch.distance();
+            if duration > settings.swipe_max_duration
+                || displacement.length() < settings.swipe_min_distance
+            {
+                continue;
+            }
+            let velocity = displacement / duration.as_secs_f32().max(f32::EPSILON);
+            if velocity.length() < settings.swipe_min_velocity {
+                continue;
+            }
+            emit(TouchGesture::Swipe(SwipeGesture {
+                direction: SwipeDirection::from_displacement(displacement),
+                velocity,
+                start: touch.start_position(),
+                end: touch.position(),
+            }));
+        }
+
+        // Forget about the fingers that left the screen.
+        for touch in touches
+            .iter_just_released()
+            .chain(touches.iter_just_canceled())
+        {
+            let id = touch.id();
+            self.start_times.remove(&id);
+            self.multi_touch.remove(&id);
+            self.long_pressed.remove(&id);
+        }
+    }
+
+    fn update_two_fingers(
+        &mut self,
+        touches: &Touches,
+        pressed: usize,
+        settings: &TouchGestureSettings,
+        emit: &mut impl FnMut(TouchGesture),
+    ) {
+        let mut fingers = touches.iter();
+        let (Some(a), Some(b), 2) = (fingers.next(), fingers.next(), pressed) else {
+            self.two_fingers = None;
+            return;
+        };
+        // The order of the touches isn't stable, so sort them by id.
+        let (a, b) = if a.id() < b.id() { (a, b) } else { (b, a) };
+        let offset = b.position() - a.position();
+        let distance = offset.length();
+        let angle = ops::atan2(offset.y, offset.x);
+        let center = (a.position() + b.position()) * 0.5;
+
+        let gesture = match &mut self.two_fingers {
+            Some(gesture) if gesture.ids == (a.id(), b.id()) => gesture,
+            _ => {
+                self.two_fingers = Some(TwoFingerGesture {
+                    ids: (a.id(), b.id()),
+                    distance,
+                    angle,
+                    pinching: false,
+                    rotating: false,
+                });
+                return;
+            }
+        };
+
+        // Until a gesture is recognized, its distance and angle stay as they were when the
+        // fingers touched the screen, so that its first event includes the change below the
+        // threshold.
+        if !gesture.pinching && gesture.distance > 0.0 {
+            let change = ops::abs(distance / gesture.distance - 1.0);
+            gesture.pinching = change > settings.pinch_threshold;
+        }
+        let rotation = wrap_angle(angle - gesture.angle);
+        if !gesture.rotating {
+            gesture.rotating = ops::abs(rotation) > settings.rotation_threshold;
+        }
+
+        if gesture.pinching {
+            if gesture.distance > 0.0 && distance != gesture.distance {
+                emit(TouchGesture::Pinch(TouchPinchGesture {
+                    scale: distance / gesture.distance,
+                    center,
+                }));
+            }
+            gesture.distance = distance;
+        }
+        if gesture.rotating {
+            if rotation != 0.0 {
+                emit(TouchGesture::Rotation(TouchRotationGesture {
+                    angle: rotation,
+                    center,
+                }));
+            }
+            gesture.angle = angle;
+        }
+    }
+}
+
+/// Wraps `angle` to the range from `-PI` to `PI`.
+fn wrap_angle(angle: f32) -> f32 {
+    if angle > PI {
+        angle - 2.0 * PI
+    } else if angle < -PI {
+        angle + 2.0 * PI
+    } else {
+        angle
+    }
+}
+
+/// Recognizes gestures from the [`Touches`], and sends [`TouchPinchGesture`],
+/// [`TouchRotationGesture`], [`SwipeGesture`] and [`LongPressGesture`] events.
+///
+/// Does nothing without a [`Time<Real>`] resource.
+#[cfg(feature = "std")]
+pub fn touch_gesture_system(
+    mut recognizer: Local<TouchGestureRecognizer>,
+    touches: Res<Touches>,
+    settings: Res<TouchGestureSettings>,
+    time: Option<Res<Time<Real>>>,
+    mut pinches: EventWriter<TouchPinchGesture>,
+    mut rotations: EventWriter<TouchRotationGesture>,
+    mut swipes: EventWriter<SwipeGesture>,
+    mut long_presses: EventWriter<LongPressGesture>,
+) {
+    let Some(time) = time else {
+        return;
+    };
+    recognizer.update(
+        &touches,
+        time.elapsed(),
+        &settings,
+        |gesture| match gesture {
+            TouchGesture::Pinch(pinch) => {
+                pinches.send(pinch);
+            }
+            TouchGesture::Rotation(rotation) => {
+                rotations.send(rotation);
+            }
+            TouchGesture::Swipe(swipe) => {
+                swipes.send(swipe);
+            }
+            TouchGesture::LongPress(long_press) => {
+                long_presses.send(long_press);
+            }
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        LongPressGesture, SwipeDirection, TouchGesture, TouchGestureRecognizer,
+        TouchGestureSettings,
+    };
+    use crate::touch::{TouchInput, TouchPhase, Touches};
+    use alloc::vec::Vec;
+    use bevy_ecs::entity::Entity;
+    use bevy_math::{ops, Vec2};
+    use core::{f32::consts::FRAC_PI_2, time::Duration};
+
+    struct Recording {
+        touches: Touches,
+        recognizer: TouchGestureRecognizer,
+        settings: TouchGestureSettings,
+        now: Duration,
+    }
+
+    impl Recording {
+        fn new() -> Self {
+            Self {
+                touches: Touches::default(),
+                recognizer: TouchGestureRecognizer::default(),
+                settings: TouchGestureSettings::default(),
+                now: Duration::ZERO,
+            }
+        }
+
+        /// Advances time by `millis`, applies the touch events, and returns the gestures.
+        fn frame(&mut self, millis: u64, events: &[(u64, TouchPhase, Vec2)]) -> Vec<TouchGesture> {
+            self.now += Duration::from_millis(millis);
+            self.touches.clear();
+            for &(id, phase, position) in events {
+                self.touches.process_touch_event(&TouchInput {
+                    phase,
+                    position,
+                    window: Entity::PLACEHOLDER,
+                    force: None,
+                    id,
+                });
+            }
+            let mut gestures = Vec::new();
+            self.recognizer
+                .update(&self.touches, self.now, &self.settings, |gesture| {
+                    gestures.push(gesture);
+                });
+            gestures
+        }
+    }
+
+    #[test]
+    fn quick_flick_is_a_swipe() {
+        let mut recording = Recording::new();
+        recording.frame(0, &[(0, TouchPhase::Started, Vec2::new(100.0, 100.0))]);
+        recording.frame(50, &[(0, TouchPhase::Moved, Vec2::new(200.0, 110.0))]);
+        let gestures = recording.frame(50, &[(0, TouchPhase::Ended, Vec2::new(200.0, 110.0))]);
+
+        let [TouchGesture::Swipe(swipe)] = gestures[..] else {
+            panic!("expected a swipe, got {gestures:?}");
+        };
+        assert_eq!(swipe.direction, SwipeDirection::Right);
+        assert!((swipe.velocity - Vec2::new(1000.0, 100.0)).length() < 1.0);
+    }
+
+    #[test]
+    fn holding_still_is_a_long_press() {
+        let mut recording = Recording::new();
+        let position = Vec2::new(10.0, 10.0);
+        recording.frame(0, &[(3, TouchPhase::Started, position)]);
+        assert!(recording.frame(400, &[]).is_empty());
+        assert_eq!(
+            recording.frame(200, &[]),
+            [TouchGesture::LongPress(LongPressGesture {
+                id: 3,
+                position
+            })]
+        );
+        assert!(recording.frame(200, &[]).is_empty());
+        // Lifting the finger after a long press isn't a swipe, even after moving.
+        let end = Vec2::new(200.0, 10.0);
+        assert!(recording
+            .frame(
+                10,
+                &[(3, TouchPhase::Moved, end), (3, TouchPhase::Ended, end)]
+            )
+            .is_empty());
+    }
+
+    #[test]
+    fn spreading_two_fingers_pinches() {
+        let mut recording = Recording::new();
+        recording.frame(
+            0,
+            &[
+                (0, TouchPhase::Started, Vec2::new(0.0, 0.0)),
+                (1, TouchPhase::Started, Vec2::new(100.0, 0.0)),
+            ],
+        );
+        // Below the threshold.
+        assert!(recording
+            .frame(16, &[(1, TouchPhase::Moved, Vec2::new(102.0, 0.0))])
+            .is_empty());
+
+        // The first pinch includes the change below the threshold.
+        let gestures = recording.frame(16, &[(1, TouchPhase::Moved, Vec2::new(153.0, 0.0))]);
+        let [TouchGesture::Pinch(pinch)] = gestures[..] else {
+            panic!("expected a pinch, got {gestures:?}");
+        };
+        assert!(ops::abs(pinch.scale - 1.53) < 1e-5);
+        assert_eq!(pinch.center, Vec2::new(76.5, 0.0));
+
+        let gestures = recording.frame(16, &[(1, TouchPhase::Moved, Vec2::new(306.0, 0.0))]);
+        let [TouchGesture::Pinch(pinch)] = gestures[..] else {
+            panic!("expected a pinch, got {gestures:?}");
+        };
+        assert!(ops::abs(pinch.scale - 2.0) < 1e-5);
+
+        // Neither finger swipes when lifted.
+        assert!(recording
+            .frame(
+                16,
+                &[
+                    (0, TouchPhase::Ended, Vec2::new(0.0, 0.0)),
+                    (1, TouchPhase::Ended, Vec2::new(306.0, 0.0)),
+                ],
+            )
+            .is_empty());
+    }
+
+    #[test]
+    fn turning_two_fingers_rotates() {
+        let mut recording = Recording::new();
+        recording.frame(
+            0,
+            &[
+                (0, TouchPhase::Started, Vec2::new(0.0, 0.0)),
+                (1, TouchPhase::Started, Vec2::new(100.0, 0.0)),
+            ],
+        );
+        let gestures = recording.frame(16, &[(1, TouchPhase::Moved, Vec2::new(0.0, 100.0))]);
+        let [TouchGesture::Rotation(rotation)] = gestures[..] else {
+            panic!("expected a rotation, got {gestures:?}");
+        };
+        assert!(ops::abs(rotation.angle - FRAC_PI_2) < 1e-5);
+    }
+}
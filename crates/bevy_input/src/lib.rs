@@ -11,12 +11,17 @@
 //! # Supported input devices
 //!
 //! `bevy` currently supports keyboard, mouse, gamepad, and touch inputs.
+//!
+//! # Actions
+//!
+//! The [`action`] module maps these inputs to named actions, which can be rebound at runtime.
 
 #[cfg(feature = "std")]
 extern crate std;
 
 extern crate alloc;
 
+pub mod action;
 mod axis;
 mod button_input;
 /// Common run conditions
@@ -57,14 +62,17 @@ use mouse::{
 };
 use touch::{touch_screen_input_system, TouchInput, Touches};
 
+use gamepad::{
+    gamepad_adaptive_trigger_system, gamepad_connection_system, gamepad_event_processing_system,
+    GamepadAdaptiveTriggerRequest, GamepadAxis, GamepadAxisChangedEvent, GamepadButton,
+    GamepadButtonChangedEvent, GamepadButtonStateChangedEvent, GamepadConnection,
+    GamepadConnectionEvent, GamepadEvent, GamepadInput, GamepadRumbleRequest, GamepadSettings,
+    RawGamepadAxisChangedEvent, RawGamepadButtonChangedEvent, RawGamepadEvent,
+};
 #[cfg(feature = "bevy_reflect")]
-use gamepad::Gamepad;
 use gamepad::{
-    gamepad_connection_system, gamepad_event_processing_system, GamepadAxis,
-    GamepadAxisChangedEvent, GamepadButton, GamepadButtonChangedEvent,
-    GamepadButtonStateChangedEvent, GamepadConnection, GamepadConnectionEvent, GamepadEvent,
-    GamepadInput, GamepadRumbleRequest, GamepadSettings, RawGamepadAxisChangedEvent,
-    RawGamepadButtonChangedEvent, RawGamepadEvent,
+    Gamepad, GamepadAdaptiveTriggers, GamepadCapabilities, RumbleEffect, RumbleEnvelope,
+    RumblePulse,
 };
 
 #[cfg(all(feature = "serialize", feature = "bevy_reflect"))]
@@ -104,6 +112,11 @@ impl Plugin for InputPlugin {
             .add_event::<RotationGesture>()
             .add_event::<DoubleTapGesture>()
             .add_event::<PanGesture>()
+            .add_event::<TouchPinchGesture>()
+            .add_event::<TouchRotationGesture>()
+            .add_event::<SwipeGesture>()
+            .add_event::<LongPressGesture>()
+            .init_resource::<TouchGestureSettings>()
             // gamepad
             .add_event::<GamepadEvent>()
             .add_event::<GamepadConnectionEvent>()
@@ -114,6 +127,7 @@ impl Plugin for InputPlugin {
             .add_event::<RawGamepadAxisChangedEvent>()
             .add_event::<RawGamepadButtonChangedEvent>()
             .add_event::<GamepadRumbleRequest>()
+            .add_event::<GamepadAdaptiveTriggerRequest>()
             .init_resource::<AccumulatedMouseMotion>()
             .init_resource::<AccumulatedMouseScroll>()
             .add_systems(
@@ -124,11 +138,20 @@ impl Plugin for InputPlugin {
                 )
                     .in_set(InputSystem),
             )
+            .add_systems(PostUpdate, gamepad_adaptive_trigger_system)
             // touch
             .add_event::<TouchInput>()
             .init_resource::<Touches>()
             .add_systems(PreUpdate, touch_screen_input_system.in_set(InputSystem));
 
+        #[cfg(feature = "std")]
+        app.add_systems(
+            PreUpdate,
+            touch_gesture_system
+                .after(touch_screen_input_system)
+                .in_set(InputSystem),
+        );
+
         #[cfg(feature = "bevy_reflect")]
         {
             // Register common types
@@ -139,6 +162,11 @@ impl Plugin for InputPlugin {
                 .register_type::<RotationGesture>()
                 .register_type::<DoubleTapGesture>()
                 .register_type::<PanGesture>()
+                .register_type::<TouchPinchGesture>()
+                .register_type::<TouchRotationGesture>()
+                .register_type::<SwipeGesture>()
+                .register_type::<LongPressGesture>()
+                .register_type::<TouchGestureSettings>()
                 .register_type::<TouchInput>()
                 .register_type::<RawGamepadEvent>()
                 .register_type::<RawGamepadAxisChangedEvent>()
@@ -153,6 +181,12 @@ impl Plugin for InputPlugin {
                 .register_type::<GamepadAxis>()
                 .register_type::<GamepadButton>()
                 .register_type::<GamepadInput>()
+                .register_type::<GamepadCapabilities>()
+                .register_type::<GamepadAdaptiveTriggers>()
+                .register_type::<GamepadAdaptiveTriggerRequest>()
+                .register_type::<RumbleEffect>()
+                .register_type::<RumblePulse>()
+                .register_type::<RumbleEnvelope>()
                 .register_type::<AccumulatedMouseMotion>()
                 .register_type::<AccumulatedMouseScroll>();
         }
@@ -13,7 +13,7 @@
 //!
 //! Under the hood, the [`DirectionalNavigationMap`] stores a directed graph of focusable entities.
 //! Each entity can have up to 8 neighbors, one for each [`CompassOctant`], balancing flexibility and required precision.
-//! For now, this graph must be built manually, but in the future, it could be generated automatically.
+//! The graph can be built manually, or generated from the layout of UI nodes by `bevy_ui`.
 
 use bevy_app::prelude::*;
 use bevy_ecs::{
@@ -20,6 +20,10 @@ plugin_group! {
         bevy_a11y:::AccessibilityPlugin,
         #[custom(cfg(any(unix, windows)))]
         bevy_app:::TerminalCtrlCHandlerPlugin,
+        // NOTE: Load this before the asset plugin, which builds the asset sources.
+        #[cfg(feature = "bevy_asset")]
+        #[custom(cfg(feature = "http"))]
+        bevy_asset::io::web:::WebAssetPlugin,
         #[cfg(feature = "bevy_asset")]
         bevy_asset:::AssetPlugin,
         #[cfg(feature = "bevy_scene")]
@@ -19,6 +19,23 @@
 //! The commonly used types are vectors like [`Vec2`] and [`Vec3`],
 //! matrices like [`Mat2`], [`Mat3`] and [`Mat4`] and orientation representations
 //! like [`Quat`].
+//!
+//! # Determinism
+//!
+//! By default, the results of floating-point operations may differ slightly between platforms:
+//! the functions of [`ops`] with unspecified precision, such as [`ops::sin`], use the
+//! implementations of the platform, and glam types use SIMD instructions where available, which
+//! may round differently than their scalar counterparts.
+//!
+//! Simulations that must produce identical results on all platforms, such as lockstep multiplayer
+//! games, should enable the `libm` feature, which replaces these functions with portable software
+//! implementations, and the `scalar_math` feature, which disables SIMD in glam. The
+//! `deterministic_math` feature of `bevy` enables both. Operations that are exactly rounded by
+//! IEEE 754, such as square roots, addition and multiplication, are already identical on all
+//! platforms.
+//!
+//! These are cargo features, so they're chosen at compile time for the whole build: there is no
+//! runtime switch, and they also apply to every other crate of the build that uses glam.
 
 #[cfg(feature = "std")]
 extern crate std;
@@ -9,6 +9,7 @@ mod mesh;
 mod mikktspace;
 pub mod morph;
 pub mod primitives;
+mod simplify;
 pub mod skinning;
 mod vertex;
 use bitflags::bitflags;
@@ -16,6 +17,7 @@ pub use index::*;
 pub use mesh::*;
 pub use mikktspace::*;
 pub use primitives::*;
+pub use simplify::*;
 pub use vertex::*;
 
 bitflags! {
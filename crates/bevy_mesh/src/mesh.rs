@@ -9,7 +9,7 @@ use super::{
     VertexFormatSize,
 };
 use alloc::collections::BTreeMap;
-use bevy_asset::{Asset, Handle, RenderAssetUsages};
+use bevy_asset::{Asset, AssetMemoryUsage, Handle, RenderAssetUsages};
 use bevy_image::Image;
 use bevy_math::{primitives::Triangle3d, *};
 use bevy_reflect::Reflect;
@@ -1205,6 +1205,17 @@ impl Mesh {
     }
 }
 
+impl AssetMemoryUsage for Mesh {
+    fn memory_usage(&self) -> usize {
+        let index_buffer_size = match &self.indices {
+            Some(Indices::U16(indices)) => indices.len() * size_of::<u16>(),
+            Some(Indices::U32(indices)) => indices.len() * size_of::<u32>(),
+            None => 0,
+        };
+        self.get_vertex_buffer_size() + index_buffer_size
+    }
+}
+
 impl core::ops::Mul<Mesh> for Transform {
     type Output = Mesh;
 
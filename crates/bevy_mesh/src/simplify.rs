@@ -0,0 +1,533 @@
+use super::{Indices, Mesh, PrimitiveTopology};
+use alloc::collections::BinaryHeap;
+use bevy_math::DVec3;
+use bevy_utils::HashMap;
+use core::cmp::Ordering;
+use thiserror::Error;
+
+/// How much more costly it is to move a vertex away from an open border of the
+/// mesh than away from the surface of the mesh.
+const BORDER_WEIGHT: f64 = 10.0;
+
+/// An error that occurred while trying to simplify a [`Mesh`].
+#[derive(Debug, Error)]
+pub enum MeshSimplificationError {
+    #[error("Mesh simplification only supports primitive topology `TriangleList`")]
+    WrongTopology,
+
+    #[error("Source mesh lacks position data")]
+    MissingPositions,
+
+    #[error("Source mesh position data is not Float32x3")]
+    PositionsFormat,
+
+    #[error("Source mesh has morph targets, which can't be simplified")]
+    MorphTargets,
+
+    #[error("Indices weren't in chunks of 3")]
+    AbruptIndicesEnd,
+
+    #[error("Face index data references vertices that do not exist")]
+    BadIndices,
+}
+
+impl Mesh {
+    /// Returns a simplified copy of this mesh with about `target_ratio` times
+    /// as many triangles, for use as a level of detail.
+    ///
+    /// Edges are collapsed one at a time, choosing the one that moves the
+    /// surface the least according to its *quadric error metric*, until the
+    /// target triangle count is reached or no edge can be collapsed without
+    /// flipping a triangle. Each edge collapses onto one of its vertices, so the
+    /// remaining vertices keep their attributes. Vertices on UV or normal seams,
+    /// where several vertices share a position, never move, so that the seams
+    /// don't open; the mesh should be indexed with shared vertices elsewhere for
+    /// this to simplify it well.
+    ///
+    /// Returns an error if any of the following conditions are met (see
+    /// [`MeshSimplificationError`]):
+    /// * The mesh's [primitive topology] is not `TriangleList`.
+    /// * The mesh is missing position data, or its position data has the wrong
+    ///   format (not `Float32x3`).
+    /// * The mesh has morph targets.
+    /// * The mesh's indices are invalid.
+    ///
+    /// [primitive topology]: PrimitiveTopology
+    pub fn simplified(&self, target_ratio: f32) -> Result<Mesh, MeshSimplificationError> {
+        if self.primitive_topology() != PrimitiveTopology::TriangleList {
+            return Err(MeshSimplificationError::WrongTopology);
+        }
+        if self.has_morph_targets() {
+            return Err(MeshSimplificationError::MorphTargets);
+        }
+        let Some(position_data) = self.attribute(Mesh::ATTRIBUTE_POSITION) else {
+            return Err(MeshSimplificationError::MissingPositions);
+        };
+        let Some(positions) = position_data.as_float3() else {
+            return Err(MeshSimplificationError::PositionsFormat);
+        };
+
+        let indices: Vec<u32> = match self.indices() {
+            Some(indices) => indices.iter().map(|index| index as u32).collect(),
+            None => (0..positions.len() as u32).collect(),
+        };
+        let triangles = simplify_indices(positions, &indices, target_ratio)?;
+        Ok(self.with_compacted_indices(&triangles))
+    }
+
+    /// Returns a copy of this mesh drawing the triangles of the given indices,
+    /// which only keeps the vertices they reference, in their original order.
+    ///
+    /// This turns the indices returned by [`simplify_indices`] into a level of
+    /// detail of the mesh they were computed from.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an index references a vertex that doesn't exist.
+    pub fn with_compacted_indices(&self, indices: &[u32]) -> Mesh {
+        let mut remap = vec![u32::MAX; self.count_vertices()];
+        for &index in indices {
+            remap[index as usize] = 0;
+        }
+        let mut kept = Vec::new();
+        for (index, new_index) in remap.iter_mut().enumerate() {
+            if *new_index == 0 {
+                *new_index = kept.len() as u32;
+                kept.push(index as u32);
+            }
+        }
+        let new_indices = indices.iter().map(|&index| remap[index as usize]);
+
+        let mut mesh = self.clone();
+        mesh.insert_indices(Indices::U32(kept));
+        mesh.duplicate_vertices();
+        mesh.insert_indices(match self.indices() {
+            Some(Indices::U16(_)) => Indices::U16(new_indices.map(|index| index as u16).collect()),
+            _ => Indices::U32(new_indices.collect()),
+        });
+        mesh
+    }
+}
+
+/// Simplifies the triangle list made of `positions` and `indices` to about
+/// `target_ratio` times as many triangles, returning the indices of the
+/// remaining triangles.
+///
+/// The returned indices reference the same vertices, which makes it possible
+/// to simplify a mesh once, while processing it, and to build the level of
+/// detail with [`Mesh::with_compacted_indices`] when loading it. See
+/// [`Mesh::simplified`] for how the triangles are simplified.
+///
+/// Returns an error if the indices aren't in chunks of 3 or reference vertices
+/// that don't exist.
+pub fn simplify_indices(
+    positions: &[[f32; 3]],
+    indices: &[u32],
+    target_ratio: f32,
+) -> Result<Vec<u32>, MeshSimplificationError> {
+    if indices.len() % 3 != 0 {
+        return Err(MeshSimplificationError::AbruptIndicesEnd);
+    }
+    if indices
+        .iter()
+        .any(|&index| index as usize >= positions.len())
+    {
+        return Err(MeshSimplificationError::BadIndices);
+    }
+
+    let triangle_count = indices.len() / 3;
+    let target_count = (triangle_count as f32 * target_ratio.clamp(0.0, 1.0)).ceil() as usize;
+    let triangles = Simplifier::new(positions, indices).simplify(target_count);
+    Ok(triangles.into_iter().flatten().collect())
+}
+
+/// A symmetric 4×4 matrix measuring the sum of the squared distances of a
+/// point to a set of planes.
+#[derive(Clone, Copy, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    /// The quadric of the plane with the given unit normal going through
+    /// `point`, scaled by `weight`.
+    fn from_plane(normal: DVec3, point: DVec3, weight: f64) -> Self {
+        let DVec3 { x: a, y: b, z: c } = normal;
+        let d = -normal.dot(point);
+        Self([
+            a * a,
+            a * b,
+            a * c,
+            a * d,
+            b * b,
+            b * c,
+            b * d,
+            c * c,
+            c * d,
+            d * d,
+        ])
+        .scaled(weight)
+    }
+
+    fn scaled(self, weight: f64) -> Self {
+        Self(self.0.map(|coefficient| coefficient * weight))
+    }
+
+    fn add(&mut self, other: &Quadric) {
+        for (coefficient, other) in self.0.iter_mut().zip(other.0) {
+            *coefficient += other;
+        }
+    }
+
+    fn error(&self, other: &Quadric, point: DVec3) -> f64 {
+        let mut q = *self;
+        q.add(other);
+        let [a2, ab, ac, ad, b2, bc, bd, c2, cd, d2] = q.0;
+        let DVec3 { x, y, z } = point;
+        let error = a2 * x * x
+            + 2.0 * ab * x * y
+            + 2.0 * ac * x * z
+            + 2.0 * ad * x
+            + b2 * y * y
+            + 2.0 * bc * y * z
+            + 2.0 * bd * y
+            + c2 * z * z
+            + 2.0 * cd * z
+            + d2;
+        error.max(0.0)
+    }
+}
+
+/// A candidate collapse of the vertex `from` onto the vertex `to`.
+struct Collapse {
+    error: f64,
+    from: u32,
+    to: u32,
+    /// The versions of `from` and `to` when the error was computed.
+    versions: (u32, u32),
+}
+
+impl PartialEq for Collapse {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Collapse {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed, so that the binary heap pops the smallest error first.
+        other.error.total_cmp(&self.error)
+    }
+}
+
+struct Simplifier<'a> {
+    positions: &'a [[f32; 3]],
+    triangles: Vec<[u32; 3]>,
+    removed: Vec<bool>,
+    live_triangle_count: usize,
+    /// The triangles using each vertex, including removed ones.
+    vertex_triangles: Vec<Vec<u32>>,
+    quadrics: Vec<Quadric>,
+    /// Whether each vertex is on a seam, and thus must not move.
+    locked: Vec<bool>,
+    /// A vertex with the same position as each vertex, used to detect
+    /// collapses across seams.
+    position_ids: Vec<u32>,
+    /// Incremented each time the quadric or the triangles of a vertex change,
+    /// to invalidate the queued collapses involving it.
+    versions: Vec<u32>,
+    queue: BinaryHeap<Collapse>,
+}
+
+impl<'a> Simplifier<'a> {
+    fn new(positions: &'a [[f32; 3]], indices: &[u32]) -> Self {
+        let triangles: Vec<[u32; 3]> = indices
+            .chunks_exact(3)
+            .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+            .collect();
+
+        let mut position_ids = Vec::with_capacity(positions.len());
+        let mut shared_positions = vec![0u32; positions.len()];
+        let mut first_vertices = HashMap::default();
+        for (index, position) in positions.iter().enumerate() {
+            let first = *first_vertices
+                .entry(position.map(f32::to_bits))
+                .or_insert(index as u32);
+            position_ids.push(first);
+            shared_positions[first as usize] += 1;
+        }
+        let locked = position_ids
+            .iter()
+            .map(|&first| shared_positions[first as usize] > 1)
+            .collect();
+
+        let mut vertex_triangles = vec![Vec::new(); positions.len()];
+        let mut quadrics = vec![Quadric::default(); positions.len()];
+        let mut edge_counts: HashMap<(u32, u32), (u32, u32)> = HashMap::default();
+        for (triangle_index, triangle) in triangles.iter().enumerate() {
+            let [a, b, c] =
+                triangle.map(|index| DVec3::from(positions[index as usize].map(f64::from)));
+            let cross = (b - a).cross(c - a);
+            let area = cross.length() * 0.5;
+            let quadric = Quadric::from_plane(cross.normalize_or_zero(), a, area);
+            for (corner, &index) in triangle.iter().enumerate() {
+                vertex_triangles[index as usize].push(triangle_index as u32);
+                quadrics[index as usize].add(&quadric);
+
+                let next = triangle[(corner + 1) % 3];
+                let edge = (index.min(next), index.max(next));
+                edge_counts
+                    .entry(edge)
+                    .or_insert((0, triangle_index as u32))
+                    .0 += 1;
+            }
+        }
+
+        // Keep the open borders of the mesh in place by adding planes
+        // perpendicular to the faces along them.
+        for ((from, to), (count, triangle_index)) in edge_counts {
+            if count != 1 {
+                continue;
+            }
+            let [a, b, c] = triangles[triangle_index as usize]
+                .map(|index| DVec3::from(positions[index as usize].map(f64::from)));
+            let normal = (b - a).cross(c - a).normalize_or_zero();
+            let start = DVec3::from(positions[from as usize].map(f64::from));
+            let edge = DVec3::from(positions[to as usize].map(f64::from)) - start;
+            let border_normal = edge.cross(normal).normalize_or_zero();
+            let quadric =
+                Quadric::from_plane(border_normal, start, BORDER_WEIGHT * edge.length_squared());
+            quadrics[from as usize].add(&quadric);
+            quadrics[to as usize].add(&quadric);
+        }
+
+        Self {
+            positions,
+            removed: vec![false; triangles.len()],
+            live_triangle_count: triangles.len(),
+            triangles,
+            vertex_triangles,
+            quadrics,
+            locked,
+            position_ids,
+            versions: vec![0; positions.len()],
+            queue: BinaryHeap::new(),
+        }
+    }
+
+    fn position(&self, index: u32) -> DVec3 {
+        DVec3::from(self.positions[index as usize].map(f64::from))
+    }
+
+    fn queue_collapse(&mut self, from: u32, to: u32) {
+        if self.locked[from as usize] {
+            return;
+        }
+        let error =
+            self.quadrics[from as usize].error(&self.quadrics[to as usize], self.position(to));
+        self.queue.push(Collapse {
+            error,
+            from,
+            to,
+            versions: (self.versions[from as usize], self.versions[to as usize]),
+        });
+    }
+
+    /// Returns the vertices sharing a live triangle with `vertex`.
+    fn neighbors(&self, vertex: u32) -> Vec<u32> {
+        let mut neighbors: Vec<u32> = self.vertex_triangles[vertex as usize]
+            .iter()
+            .filter(|&&triangle| !self.removed[triangle as usize])
+            .flat_map(|&triangle| self.triangles[triangle as usize])
+            .filter(|&other| other != vertex)
+            .collect();
+        neighbors.sort_unstable();
+        neighbors.dedup();
+        neighbors
+    }
+
+    /// Returns true if moving `from` onto `to` wouldn't flip any of the
+    /// triangles that remain, nor stretch the attributes of `to` across a seam.
+    fn can_collapse(&self, from: u32, to: u32) -> bool {
+        if self.neighbors(from).iter().any(|&other| {
+            other != to && self.position_ids[other as usize] == self.position_ids[to as usize]
+        }) {
+            return false;
+        }
+
+        let new_position = self.position(to);
+        self.vertex_triangles[from as usize]
+            .iter()
+            .filter(|&&triangle| !self.removed[triangle as usize])
+            .map(|&triangle| self.triangles[triangle as usize])
+            .filter(|triangle| !triangle.contains(&to))
+            .all(|triangle| {
+                let [a, b, c] = triangle.map(|index| self.position(index));
+                let old_normal = (b - a).cross(c - a);
+                let [a, b, c] = triangle.map(|index| {
+                    if index == from {
+                        new_position
+                    } else {
+                        self.position(index)
+                    }
+                });
+                let new_normal = (b - a).cross(c - a);
+                old_normal.dot(new_normal) > 0.0
+            })
+    }
+
+    fn collapse(&mut self, from: u32, to: u32) {
+        let from_triangles = core::mem::take(&mut self.vertex_triangles[from as usize]);
+        for &triangle_index in &from_triangles {
+            if self.removed[triangle_index as usize] {
+                continue;
+            }
+            let triangle = &mut self.triangles[triangle_index as usize];
+            if triangle.contains(&to) {
+                self.removed[triangle_index as usize] = true;
+                self.live_triangle_count -= 1;
+                continue;
+            }
+            for index in triangle.iter_mut() {
+                if *index == from {
+                    *index = to;
+                }
+            }
+        }
+        self.vertex_triangles[to as usize].extend(from_triangles);
+        self.vertex_triangles[to as usize].retain(|&triangle| !self.removed[triangle as usize]);
+
+        let from_quadric = self.quadrics[from as usize];
+        self.quadrics[to as usize].add(&from_quadric);
+        self.versions[from as usize] += 1;
+        self.versions[to as usize] += 1;
+
+        for neighbor in self.neighbors(to) {
+            self.queue_collapse(neighbor, to);
+            self.queue_collapse(to, neighbor);
+        }
+    }
+
+    fn simplify(mut self, target_count: usize) -> Vec<[u32; 3]> {
+        for triangle_index in 0..self.triangles.len() {
+            let triangle = self.triangles[triangle_index];
+            for corner in 0..3 {
+                let (a, b) = (triangle[corner], triangle[(corner + 1) % 3]);
+                self.queue_collapse(a, b);
+                self.queue_collapse(b, a);
+            }
+        }
+
+        while self.live_triangle_count > target_count {
+            let Some(collapse) = self.queue.pop() else {
+                break;
+            };
+            let (from, to) = (collapse.from, collapse.to);
+            if collapse.versions != (self.versions[from as usize], self.versions[to as usize])
+                || !self.can_collapse(from, to)
+            {
+                continue;
+            }
+            self.collapse(from, to);
+        }
+
+        self.triangles
+            .into_iter()
+            .zip(self.removed)
+            .filter(|(_, removed)| !removed)
+            .map(|(triangle, _)| triangle)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        simplify_indices, Indices, Mesh, MeshBuilder, MeshSimplificationError, Meshable,
+        PrimitiveTopology,
+    };
+    use bevy_asset::RenderAssetUsages;
+    use bevy_math::primitives::{Plane3d, Sphere};
+
+    fn triangle_count(mesh: &Mesh) -> usize {
+        mesh.indices().unwrap().len() / 3
+    }
+
+    #[test]
+    fn simplify_plane() {
+        let mesh = Plane3d::default().mesh().subdivisions(8).build();
+        let simplified = mesh.simplified(0.1).unwrap();
+
+        // A flat grid can be simplified without any error, so it should reach
+        // the target.
+        assert!(triangle_count(&simplified) <= triangle_count(&mesh).div_ceil(10));
+        let max_index = simplified.indices().unwrap().iter().max().unwrap();
+        assert_eq!(simplified.count_vertices(), max_index + 1);
+    }
+
+    #[test]
+    fn simplify_sphere() {
+        let mesh = Sphere::new(1.0).mesh().uv(32, 16);
+        let simplified = mesh.simplified(0.25).unwrap();
+
+        let count = triangle_count(&simplified);
+        assert!(count > 0 && count <= triangle_count(&mesh).div_ceil(4));
+        assert_eq!(
+            simplified.attribute(Mesh::ATTRIBUTE_UV_0).unwrap().len(),
+            simplified.count_vertices()
+        );
+    }
+
+    #[test]
+    fn simplify_wrong_topology() {
+        let mesh = Mesh::new(PrimitiveTopology::LineList, RenderAssetUsages::default())
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, vec![[0.0; 3]; 2])
+            .with_inserted_indices(Indices::U32(vec![0, 1]));
+        assert!(matches!(
+            mesh.simplified(0.5),
+            Err(MeshSimplificationError::WrongTopology)
+        ));
+    }
+
+    #[test]
+    fn simplify_indices_then_compact() {
+        let mesh = Sphere::new(1.0).mesh().uv(32, 16);
+        let positions = mesh
+            .attribute(Mesh::ATTRIBUTE_POSITION)
+            .unwrap()
+            .as_float3()
+            .unwrap();
+        let indices: Vec<u32> = mesh
+            .indices()
+            .unwrap()
+            .iter()
+            .map(|index| index as u32)
+            .collect();
+        let simplified_indices = simplify_indices(positions, &indices, 0.25).unwrap();
+
+        // Compacting the simplified indices gives the same mesh as simplifying
+        // the mesh directly.
+        let compacted = mesh.with_compacted_indices(&simplified_indices);
+        let simplified = mesh.simplified(0.25).unwrap();
+        assert_eq!(compacted.count_vertices(), simplified.count_vertices());
+        assert_eq!(
+            compacted.indices().unwrap().iter().collect::<Vec<_>>(),
+            simplified.indices().unwrap().iter().collect::<Vec<_>>()
+        );
+
+        assert!(matches!(
+            simplify_indices(positions, &[0, 1], 0.5),
+            Err(MeshSimplificationError::AbruptIndicesEnd)
+        ));
+        assert!(matches!(
+            simplify_indices(positions, &[0, 1, u32::MAX], 0.5),
+            Err(MeshSimplificationError::BadIndices)
+        ));
+    }
+}
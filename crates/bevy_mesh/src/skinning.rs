@@ -7,6 +7,7 @@ use bevy_ecs::{
 };
 use bevy_math::Mat4;
 use bevy_reflect::prelude::*;
+use bevy_transform::components::GlobalTransform;
 use core::ops::Deref;
 
 #[derive(Component, Debug, Default, Clone, Reflect, VisitEntities, VisitEntitiesMut)]
@@ -24,6 +25,131 @@ pub struct SkinnedMesh {
     pub joints: Vec<Entity>,
 }
 
+/// Selects the algorithm used to deform a [`SkinnedMesh`] by its joints.
+///
+/// Add this component to an entity with a [`SkinnedMesh`] to override the
+/// default. Entities without this component use
+/// [`SkinningMethod::LinearBlend`].
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq, Hash)]
+pub enum SkinningMethod {
+    /// Blends the joint matrices linearly.
+    ///
+    /// This is the fastest method, and the one that content tools usually
+    /// preview with, but volume is lost around joints that twist or bend
+    /// sharply (the "candy wrapper" artifact).
+    #[default]
+    LinearBlend,
+    /// Blends the joint transforms as dual quaternions.
+    ///
+    /// This preserves volume around twisting joints, at a small extra cost in
+    /// the vertex shader. Only the rotation, translation and scale of each
+    /// joint are taken into account; shear in joint transforms is discarded.
+    DualQuaternion,
+}
+
+/// Reduced joint palettes that a [`SkinnedMesh`] switches to as it gets
+/// farther away from the camera.
+///
+/// Each level maps every joint of [`SkinnedMesh::joints`] to a joint whose
+/// matrix is used in its place, so that, for example, finger joints can be
+/// collapsed into the hand joint for meshes in the distance. Joints that are
+/// mapped away don't need to be tracked for changes, so their animation no
+/// longer causes the joint matrices of the mesh to be recomputed and uploaded.
+///
+/// This only reduces the skinning work done for rendering. The mesh still
+/// uploads one matrix per joint of [`SkinnedMesh::joints`], with each joint that
+/// is mapped away getting a copy of its target's matrix, since its vertices
+/// keep referencing it. Joints that are mapped away are also still animated
+/// and have their transforms propagated like any other entity; to save that
+/// work too, stop animating them, for example by using a separate animation
+/// clip for meshes in the distance.
+///
+/// The distance is measured from the closest camera to the mesh, the same way
+/// as for `VisibilityRange`: if the mesh also has a `VisibilityRange` with
+/// `use_aabb` set, the center of its bounding box is used, otherwise its
+/// translation.
+#[derive(Component, Clone, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq)]
+pub struct SkinnedMeshLod {
+    /// The reduced palettes, sorted by increasing
+    /// [`SkinnedMeshLodLevel::start_distance`].
+    pub levels: Vec<SkinnedMeshLodLevel>,
+}
+
+/// A reduced joint palette used by a [`SkinnedMeshLod`].
+#[derive(Clone, Debug, Default, PartialEq, Reflect)]
+#[reflect(Default, Debug, PartialEq)]
+pub struct SkinnedMeshLodLevel {
+    /// The distance from the camera at which this palette starts being used.
+    pub start_distance: f32,
+    /// For each joint of [`SkinnedMesh::joints`], the index of the joint whose
+    /// matrix is used in its place.
+    ///
+    /// Joints without an entry, or with an out of range entry, keep their own
+    /// matrix.
+    pub joint_remap: Vec<u16>,
+}
+
+impl SkinnedMeshLodLevel {
+    /// Creates a palette for a mesh with `joint_count` joints, in which each
+    /// `(joint, target)` pair collapses `joint` into `target`.
+    pub fn collapsing(
+        start_distance: f32,
+        joint_count: usize,
+        collapsed_joints: impl IntoIterator<Item = (u16, u16)>,
+    ) -> Self {
+        let mut joint_remap: Vec<u16> = (0..joint_count).map(|joint| joint as u16).collect();
+        for (joint, target) in collapsed_joints {
+            if let Some(entry) = joint_remap.get_mut(joint as usize) {
+                *entry = target;
+            }
+        }
+        Self {
+            start_distance,
+            joint_remap,
+        }
+    }
+
+    /// Returns the index of the joint whose matrix is used for the joint at
+    /// `index`, out of `joint_count` joints.
+    pub fn remapped_joint(&self, index: usize, joint_count: usize) -> usize {
+        match self.joint_remap.get(index) {
+            Some(&target) if (target as usize) < joint_count => target as usize,
+            _ => index,
+        }
+    }
+}
+
+impl SkinnedMeshLod {
+    /// Returns the index of the level used at the given distance from the
+    /// camera, or `None` if the full palette should be used.
+    pub fn level_at(&self, distance: f32) -> Option<usize> {
+        self.levels
+            .iter()
+            .rposition(|level| distance >= level.start_distance)
+    }
+}
+
+/// Replaces the [`GlobalTransform`] of a joint of a [`SkinnedMesh`] with one
+/// computed elsewhere, for example by a physics engine simulating a ragdoll.
+///
+/// Add this component to a joint entity to take it over from animation and
+/// transform propagation. The override is copied to the joint's
+/// [`GlobalTransform`] in `SkinningSystems::ApplyJointOverrides`, after
+/// transform propagation and before the joints are extracted for rendering, so
+/// systems that update it should run in `SkinningSystems::WriteJointOverrides`.
+///
+/// The override only affects the joint itself: its children keep the
+/// transforms propagated from the joint's own [`Transform`]. Override every
+/// joint that the physics engine simulates. Once the component is removed, the
+/// joint goes back to its propagated transform on the next frame.
+///
+/// [`Transform`]: bevy_transform::components::Transform
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq)]
+pub struct JointOverride(pub GlobalTransform);
+
 #[derive(Asset, TypePath, Debug)]
 pub struct SkinnedMeshInverseBindposes(Box<[Mat4]>);
 
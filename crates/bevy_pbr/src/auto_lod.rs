@@ -0,0 +1,290 @@
+//! Automatic selection between simplified versions of a mesh, based on how much
+//! of the screen it covers.
+
+use core::ops::Range;
+
+use bevy_app::{App, Plugin, PostUpdate};
+use bevy_asset::Handle;
+use bevy_ecs::prelude::*;
+use bevy_hierarchy::{BuildChildren, DespawnRecursiveExt};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::{
+    camera::PerspectiveProjection,
+    mesh::{
+        skinning::{SkinnedMesh, SkinningMethod},
+        Mesh, Mesh3d,
+    },
+    primitives::Aabb,
+    view::{VisibilityRange, VisibilitySystems},
+};
+use bevy_transform::{components::GlobalTransform, TransformSystem};
+
+use crate::{NotShadowCaster, NotShadowReceiver, TransmittedShadowReceiver};
+
+/// Adds support for [`AutoLod`].
+pub struct AutoLodPlugin;
+
+impl Plugin for AutoLodPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<AutoLod>()
+            .register_type::<AutoLodChild>()
+            .add_systems(
+                PostUpdate,
+                (
+                    spawn_auto_lod_levels.before(TransformSystem::TransformPropagate),
+                    (
+                        sync_auto_lod_component::<SkinnedMesh>,
+                        sync_auto_lod_component::<SkinningMethod>,
+                        sync_auto_lod_component::<NotShadowCaster>,
+                        sync_auto_lod_component::<NotShadowReceiver>,
+                        sync_auto_lod_component::<TransmittedShadowReceiver>,
+                    )
+                        .after(spawn_auto_lod_levels),
+                    update_auto_lod_visibility_ranges
+                        .after(spawn_auto_lod_levels)
+                        .after(TransformSystem::TransformPropagate)
+                        .after(VisibilitySystems::CalculateBounds)
+                        .before(VisibilitySystems::CheckVisibility),
+                ),
+            );
+    }
+}
+
+/// Renders simplified versions of the [`Mesh3d`] of this entity as it covers
+/// less of the screen.
+///
+/// The simplified meshes, from the most to the least detailed, can be generated
+/// with [`Mesh::simplified`], or by the glTF loader, which adds this component
+/// to the mesh entities of the scenes it loads when its `lods` setting isn't
+/// empty.
+///
+/// A child entity is spawned for each level, with the same material as this
+/// entity, and [`VisibilityRange`]s are set on this entity and on its levels so
+/// that a single one is visible at a time, crossfading between them. Skinning
+/// and the shadow settings of this entity are copied to its levels, but not its
+/// other components.
+///
+/// The screen coverage of the entity is the fraction of the height of the
+/// screen that the bounding sphere of its [`Aabb`] covers. Since visibility
+/// ranges don't depend on the camera, screen coverages are converted to
+/// distances for a perspective camera with the vertical field of view
+/// [`AutoLod::reference_fov`].
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component, Default, Debug)]
+pub struct AutoLod {
+    /// The simplified levels of detail, from the most to the least detailed.
+    ///
+    /// Their screen coverages should decrease.
+    pub levels: Vec<AutoLodLevel>,
+
+    /// The screen coverage below which the least detailed level is hidden too.
+    ///
+    /// If this is zero, the default, the least detailed level is always
+    /// visible.
+    pub min_screen_coverage: f32,
+
+    /// The fraction of the distance at which a level replaces the previous one
+    /// over which they crossfade.
+    ///
+    /// Defaults to 0.1. If this is zero, levels switch abruptly.
+    pub crossfade: f32,
+
+    /// The vertical field of view, in radians, for which screen coverages are
+    /// converted to distances.
+    ///
+    /// Defaults to the field of view of [`PerspectiveProjection::default`].
+    pub reference_fov: f32,
+}
+
+/// A simplified level of detail of an entity with [`AutoLod`].
+#[derive(Clone, Debug, Reflect)]
+pub struct AutoLodLevel {
+    /// The simplified mesh.
+    pub mesh: Handle<Mesh>,
+
+    /// The screen coverage below which the previous level is replaced by this
+    /// one.
+    pub screen_coverage: f32,
+}
+
+impl Default for AutoLod {
+    fn default() -> Self {
+        Self {
+            levels: Vec::new(),
+            min_screen_coverage: 0.0,
+            crossfade: 0.1,
+            reference_fov: PerspectiveProjection::default().fov,
+        }
+    }
+}
+
+impl AutoLod {
+    /// Creates an [`AutoLod`] with the given levels, and the default settings
+    /// otherwise.
+    pub fn new(levels: impl IntoIterator<Item = AutoLodLevel>) -> Self {
+        Self {
+            levels: levels.into_iter().collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the distance from the camera at which an entity whose bounding
+    /// sphere has the given radius covers `screen_coverage` of the screen.
+    pub fn distance_for_screen_coverage(&self, radius: f32, screen_coverage: f32) -> f32 {
+        if screen_coverage <= 0.0 {
+            return f32::MAX;
+        }
+        radius / (screen_coverage * (self.reference_fov * 0.5).tan())
+    }
+
+    /// Returns the visibility ranges of the entity and of each of its levels,
+    /// for a bounding sphere with the given radius.
+    pub fn visibility_ranges(&self, radius: f32) -> Vec<VisibilityRange> {
+        let margin = |screen_coverage: f32| -> Range<f32> {
+            let distance = self.distance_for_screen_coverage(radius, screen_coverage);
+            distance..(distance * (1.0 + self.crossfade.max(0.0))).min(f32::MAX)
+        };
+
+        let mut ranges = Vec::with_capacity(self.levels.len() + 1);
+        let mut start_margin = 0.0..0.0;
+        for screen_coverage in self
+            .levels
+            .iter()
+            .map(|level| level.screen_coverage)
+            .chain([self.min_screen_coverage])
+        {
+            let mut end_margin = margin(screen_coverage);
+            end_margin.start = end_margin.start.max(start_margin.end);
+            end_margin.end = end_margin.end.max(end_margin.start);
+            ranges.push(VisibilityRange {
+                start_margin: start_margin.clone(),
+                end_margin: end_margin.clone(),
+                use_aabb: false,
+            });
+            start_margin = end_margin;
+        }
+        ranges
+    }
+}
+
+/// A level of detail spawned for an entity with [`AutoLod`], as a child of it.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component, Debug)]
+pub struct AutoLodChild {
+    /// The index of this level in [`AutoLod::levels`].
+    pub level: usize,
+}
+
+/// The levels of detail spawned for an entity with [`AutoLod`].
+#[derive(Component, Default)]
+pub struct AutoLodChildren(Vec<Entity>);
+
+/// Spawns the levels of the entities whose [`AutoLod`] changed, and despawns
+/// the levels of those that lost it.
+pub fn spawn_auto_lod_levels(
+    mut commands: Commands,
+    mut auto_lods: Query<(Entity, &AutoLod, Option<&mut AutoLodChildren>), Changed<AutoLod>>,
+    mut removed: RemovedComponents<AutoLod>,
+    children: Query<&AutoLodChildren, Without<AutoLod>>,
+) {
+    for entity in removed.read() {
+        let Ok(auto_lod_children) = children.get(entity) else {
+            continue;
+        };
+        for &child in &auto_lod_children.0 {
+            commands.entity(child).try_despawn_recursive();
+        }
+        if let Some(mut entity_commands) = commands.get_entity(entity) {
+            entity_commands.remove::<(AutoLodChildren, VisibilityRange)>();
+        }
+    }
+
+    for (entity, auto_lod, auto_lod_children) in &mut auto_lods {
+        let mut spawned = Vec::with_capacity(auto_lod.levels.len());
+        for (level_index, level) in auto_lod.levels.iter().enumerate() {
+            let child = commands
+                .spawn((
+                    AutoLodChild { level: level_index },
+                    Mesh3d(level.mesh.clone()),
+                ))
+                .id();
+            spawned.push(child);
+        }
+        commands.entity(entity).add_children(&spawned);
+
+        match auto_lod_children {
+            Some(mut auto_lod_children) => {
+                for child in core::mem::replace(&mut auto_lod_children.0, spawned) {
+                    commands.entity(child).try_despawn_recursive();
+                }
+            }
+            None => {
+                commands.entity(entity).insert(AutoLodChildren(spawned));
+            }
+        }
+    }
+}
+
+/// Copies the component `C` of the entities with [`AutoLod`] to their levels
+/// when it changes.
+pub fn sync_auto_lod_component<C: Component + Clone>(
+    mut commands: Commands,
+    changed: Query<(&C, &AutoLodChildren), Or<(Changed<C>, Changed<AutoLodChildren>)>>,
+    mut removed: RemovedComponents<C>,
+    children: Query<&AutoLodChildren, Without<C>>,
+) {
+    for (component, auto_lod_children) in &changed {
+        for &child in &auto_lod_children.0 {
+            commands.entity(child).insert(component.clone());
+        }
+    }
+    for entity in removed.read() {
+        let Ok(auto_lod_children) = children.get(entity) else {
+            continue;
+        };
+        for &child in &auto_lod_children.0 {
+            commands.entity(child).remove::<C>();
+        }
+    }
+}
+
+/// Sets the [`VisibilityRange`]s of the entities with [`AutoLod`] and of their
+/// levels from their bounds and scale.
+pub fn update_auto_lod_visibility_ranges(
+    mut commands: Commands,
+    auto_lods: Query<
+        (
+            Entity,
+            &AutoLod,
+            &AutoLodChildren,
+            &Aabb,
+            &GlobalTransform,
+            Option<&VisibilityRange>,
+        ),
+        Or<(
+            Changed<AutoLod>,
+            Changed<AutoLodChildren>,
+            Changed<Aabb>,
+            Changed<GlobalTransform>,
+        )>,
+    >,
+    visibility_ranges: Query<Option<&VisibilityRange>, With<AutoLodChild>>,
+) {
+    for (entity, auto_lod, auto_lod_children, aabb, transform, visibility_range) in &auto_lods {
+        let scale = transform.compute_transform().scale.abs().max_element();
+        let radius = aabb.half_extents.length() * scale;
+        let ranges = auto_lod.visibility_ranges(radius);
+
+        if visibility_range != Some(&ranges[0]) {
+            commands.entity(entity).insert(ranges[0].clone());
+        }
+        for (&child, range) in auto_lod_children.0.iter().zip(&ranges[1..]) {
+            let Ok(visibility_range) = visibility_ranges.get(child) else {
+                continue;
+            };
+            if visibility_range != Some(range) {
+                commands.entity(child).insert(range.clone());
+            }
+        }
+    }
+}
@@ -157,11 +157,14 @@ pub struct GpuClusterableObject {
     pub(crate) flags: u32,
     pub(crate) shadow_depth_bias: f32,
     pub(crate) shadow_normal_bias: f32,
+    // For point lights: the bits of the packed `PointLightShadowAtlasTile` of the shadow map
     pub(crate) spot_light_tan_angle: f32,
     pub(crate) soft_shadow_size: f32,
     pub(crate) shadow_map_near_z: f32,
-    pub(crate) pad_a: f32,
-    pub(crate) pad_b: f32,
+    // The index of the light's IES profile, or `u32::MAX` if it has none
+    pub(crate) ies_profile: u32,
+    // The octahedral-encoded photometric axis of the IES profile, as 2x16 snorm
+    pub(crate) ies_profile_axis: u32,
 }
 
 pub enum GpuClusterableObjects {
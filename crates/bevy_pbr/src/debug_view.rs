@@ -0,0 +1,125 @@
+use bevy_ecs::{component::Component, reflect::ReflectComponent};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::extract_component::ExtractComponent;
+
+use crate::MeshPipelineKey;
+
+/// Add this component to a [`Camera3d`](bevy_core_pipeline::core_3d::Camera3d) to render the
+/// meshes it sees with a debug view, instead of lighting them.
+///
+/// The views that replace the color of the meshes, such as [`DebugViewMode::WorldNormals`], are
+/// only applied by the [`StandardMaterial`](crate::StandardMaterial) shaders, in the forward
+/// renderer. They show their values as they are, without fog or the tonemapping of cameras
+/// without HDR. The views that change how the meshes are drawn, [`DebugViewMode::Wireframe`] and
+/// [`DebugViewMode::Overdraw`], apply to all the materials using the mesh pipeline.
+#[derive(
+    Debug, Component, ExtractComponent, Reflect, Clone, Copy, PartialEq, Eq, Hash, Default,
+)]
+#[reflect(Component, Default, Debug, PartialEq, Hash)]
+pub enum DebugViewMode {
+    /// The usual lit rendering.
+    #[default]
+    Lit,
+    /// The base color of the materials, without lighting.
+    Unlit,
+    /// The edges of the triangles of the meshes, lit as usual.
+    ///
+    /// This requires the [`WgpuFeatures::POLYGON_MODE_LINE`](bevy_render::settings::WgpuFeatures::POLYGON_MODE_LINE)
+    /// feature, like the [`WireframePlugin`](crate::wireframe::WireframePlugin). Without it, the
+    /// meshes are drawn filled, and a warning is logged.
+    Wireframe,
+    /// The world space normals of the surfaces, after normal mapping, with each component mapped
+    /// from `-1.0..1.0` to `0.0..1.0`.
+    WorldNormals,
+    /// A checkerboard of the first UV coordinates of the meshes, tinted by the coordinates, to
+    /// check their scale and seams.
+    ///
+    /// Meshes without UV coordinates are magenta.
+    UvChecker,
+    /// The light from the lightmaps of the meshes, or black without a lightmap.
+    Lightmap,
+    /// How many times each pixel is drawn, from black to bright red as more surfaces cover it.
+    ///
+    /// All the surfaces are drawn, including the ones behind others.
+    Overdraw,
+    /// The usual lit rendering, with the light of each directional light tinted by the shadow
+    /// cascade that the surface is in.
+    ShadowCascades,
+}
+
+impl DebugViewMode {
+    /// All the debug views, in the order they are usually cycled through.
+    pub const ALL: [Self; 8] = [
+        Self::Lit,
+        Self::Unlit,
+        Self::Wireframe,
+        Self::WorldNormals,
+        Self::UvChecker,
+        Self::Lightmap,
+        Self::Overdraw,
+        Self::ShadowCascades,
+    ];
+
+    /// The debug view after this one in [`DebugViewMode::ALL`], going back to the first after
+    /// the last one.
+    pub fn next(self) -> Self {
+        Self::ALL[(self as usize + 1) % Self::ALL.len()]
+    }
+
+    /// The debug view before this one in [`DebugViewMode::ALL`], going to the last before the
+    /// first one.
+    pub fn previous(self) -> Self {
+        Self::ALL[(self as usize + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
+pub const fn debug_view_pipeline_key(debug_view: DebugViewMode) -> MeshPipelineKey {
+    match debug_view {
+        DebugViewMode::Lit => MeshPipelineKey::DEBUG_VIEW_LIT,
+        DebugViewMode::Unlit => MeshPipelineKey::DEBUG_VIEW_UNLIT,
+        DebugViewMode::Wireframe => MeshPipelineKey::DEBUG_VIEW_WIREFRAME,
+        DebugViewMode::WorldNormals => MeshPipelineKey::DEBUG_VIEW_WORLD_NORMALS,
+        DebugViewMode::UvChecker => MeshPipelineKey::DEBUG_VIEW_UV_CHECKER,
+        DebugViewMode::Lightmap => MeshPipelineKey::DEBUG_VIEW_LIGHTMAP,
+        DebugViewMode::Overdraw => MeshPipelineKey::DEBUG_VIEW_OVERDRAW,
+        DebugViewMode::ShadowCascades => MeshPipelineKey::DEBUG_VIEW_SHADOW_CASCADES,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{debug_view_pipeline_key, DebugViewMode};
+    use crate::MeshPipelineKey;
+
+    #[test]
+    fn next_and_previous_cycle_through_all_the_views() {
+        let mut view = DebugViewMode::Lit;
+        for expected in DebugViewMode::ALL.iter().cycle().skip(1).take(9) {
+            view = view.next();
+            assert_eq!(view, *expected);
+            assert_eq!(view.next().previous(), view);
+        }
+        assert_eq!(DebugViewMode::Lit.previous(), DebugViewMode::ShadowCascades);
+        assert_eq!(DebugViewMode::ShadowCascades.next(), DebugViewMode::Lit);
+    }
+
+    #[test]
+    fn each_view_has_its_own_pipeline_key() {
+        for (i, a) in DebugViewMode::ALL.into_iter().enumerate() {
+            let key = debug_view_pipeline_key(a);
+            assert_eq!(
+                key,
+                key.intersection(MeshPipelineKey::DEBUG_VIEW_RESERVED_BITS),
+                "{a:?} sets bits outside of the debug view bits"
+            );
+            for b in DebugViewMode::ALL.into_iter().skip(i + 1) {
+                assert_ne!(key, debug_view_pipeline_key(b), "{a:?} and {b:?}");
+            }
+        }
+        // The default view keeps the usual pipelines.
+        assert_eq!(
+            debug_view_pipeline_key(DebugViewMode::Lit),
+            MeshPipelineKey::empty()
+        );
+    }
+}
@@ -6,13 +6,13 @@ use bevy_app::{App, Plugin};
 use bevy_asset::{load_internal_asset, Asset, Assets, Handle};
 use bevy_ecs::component::{require, Component};
 use bevy_math::{prelude::Rectangle, Quat, Vec2, Vec3};
-use bevy_reflect::{Reflect, TypePath};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect, TypePath};
 use bevy_render::{
     alpha::AlphaMode,
     mesh::{Mesh, Mesh3d, MeshBuilder, MeshVertexBufferLayoutRef, Meshable},
     render_resource::{
-        AsBindGroup, CompareFunction, RenderPipelineDescriptor, Shader,
-        SpecializedMeshPipelineError,
+        AsBindGroup, BlendComponent, BlendFactor, BlendOperation, BlendState, CompareFunction,
+        RenderPipelineDescriptor, Shader, ShaderDefVal, SpecializedMeshPipelineError,
     },
 };
 
@@ -31,7 +31,8 @@ impl Plugin for ForwardDecalPlugin {
             Shader::from_wgsl
         );
 
-        app.register_type::<ForwardDecal>();
+        app.register_type::<ForwardDecal>()
+            .register_type::<ForwardDecalBlendMode>();
 
         app.world_mut().resource_mut::<Assets<Mesh>>().insert(
             FORWARD_DECAL_MESH_HANDLE.id(),
@@ -55,6 +56,9 @@ impl Plugin for ForwardDecalPlugin {
 /// geometry towards the edges.
 ///
 /// Because forward decals are meshes, you can use arbitrary materials to control their appearance.
+/// With a [`StandardMaterial`], the decal is lit with all of its layers, so its normal map,
+/// metallic and roughness, and emissive apply on top of the underlying surface as well as its base
+/// color. See [`ForwardDecalBlendMode`] for the other ways a decal can combine with the surface.
 ///
 /// # Usage Notes
 ///
@@ -62,6 +66,8 @@ impl Plugin for ForwardDecalPlugin {
 /// * Any camera rendering a forward decal must have the [`bevy_core_pipeline::DepthPrepass`] component.
 /// * Looking at forward decals at a steep angle can cause distortion. This can be mitigated by padding your decal's
 ///   texture with extra transparent pixels on the edges.
+/// * Overlapping decals are rendered from back to front. Use [`ForwardDecalMaterialExt::sort_bias`] to keep their
+///   order stable when they lie on the same surface.
 #[derive(Component, Reflect)]
 #[require(Mesh3d(|| Mesh3d(FORWARD_DECAL_MESH_HANDLE)))]
 pub struct ForwardDecal;
@@ -82,6 +88,7 @@ pub type ForwardDecalMaterial<B: Material> = ExtendedMaterial<B, ForwardDecalMat
 /// The `FORWARD_DECAL` shader define will be made available to your shader so that you can gate
 /// the forward decal code behind an ifdef.
 #[derive(Asset, AsBindGroup, TypePath, Clone, Debug)]
+#[bind_group_data(ForwardDecalBlendMode)]
 pub struct ForwardDecalMaterialExt {
     /// Controls how far away a surface must be before the decal will stop blending with it, and instead render as opaque.
     ///
@@ -90,6 +97,46 @@ pub struct ForwardDecalMaterialExt {
     /// Units are in meters.
     #[uniform(200)]
     pub depth_fade_factor: f32,
+
+    /// How the decal combines with the surface underneath it.
+    pub blend_mode: ForwardDecalBlendMode,
+
+    /// A bias added to the view depth of the decal when sorting it against other transparent meshes.
+    ///
+    /// Decals with a higher bias render on top of overlapping decals with a lower one. This works
+    /// like [`StandardMaterial::depth_bias`], which is added to it.
+    pub sort_bias: f32,
+}
+
+/// How a [`ForwardDecal`] combines with the surface underneath it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Default, Debug)]
+pub enum ForwardDecalBlendMode {
+    /// The lit decal is blended over the surface, replacing all of its layers where the decal is
+    /// opaque.
+    ///
+    /// This is the mode to use for decals such as bullet holes and signs, which change the shape
+    /// and the material of the surface.
+    #[default]
+    Blend,
+
+    /// The color of the surface is multiplied by the base color of the decal, which isn't lit.
+    ///
+    /// Since the lighting of the surface is kept, this is the mode to use for decals such as
+    /// grime and stains, which only darken or tint the surface.
+    Multiply,
+
+    /// The lit decal is added to the surface.
+    ///
+    /// This is the mode to use for decals that only emit light, such as glowing runes, whose base
+    /// color is usually black.
+    Add,
+}
+
+impl From<&ForwardDecalMaterialExt> for ForwardDecalBlendMode {
+    fn from(extension: &ForwardDecalMaterialExt) -> Self {
+        extension.blend_mode
+    }
 }
 
 impl MaterialExtension for ForwardDecalMaterialExt {
@@ -97,11 +144,15 @@ impl MaterialExtension for ForwardDecalMaterialExt {
         Some(AlphaMode::Blend)
     }
 
+    fn depth_bias(&self) -> f32 {
+        self.sort_bias
+    }
+
     fn specialize(
         _pipeline: &MaterialExtensionPipeline,
         descriptor: &mut RenderPipelineDescriptor,
         _layout: &MeshVertexBufferLayoutRef,
-        _key: MaterialExtensionKey<Self>,
+        key: MaterialExtensionKey<Self>,
     ) -> Result<(), SpecializedMeshPipelineError> {
         descriptor.depth_stencil.as_mut().unwrap().depth_compare = CompareFunction::Always;
 
@@ -109,6 +160,38 @@ impl MaterialExtension for ForwardDecalMaterialExt {
 
         if let Some(fragment) = &mut descriptor.fragment {
             fragment.shader_defs.push("FORWARD_DECAL".into());
+
+            let (blend, shader_def) = match key.bind_group_data {
+                ForwardDecalBlendMode::Blend => (None, None),
+                ForwardDecalBlendMode::Multiply => (
+                    Some(BlendState {
+                        color: BlendComponent {
+                            src_factor: BlendFactor::Dst,
+                            dst_factor: BlendFactor::OneMinusSrcAlpha,
+                            operation: BlendOperation::Add,
+                        },
+                        alpha: BlendComponent::OVER,
+                    }),
+                    Some("FORWARD_DECAL_BLEND_MULTIPLY"),
+                ),
+                ForwardDecalBlendMode::Add => (
+                    Some(BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    Some("FORWARD_DECAL_BLEND_ADD"),
+                ),
+            };
+            if let Some(shader_def) = shader_def {
+                // Order independent transparency only supports alpha blending, so these decals are
+                // always blended in the transparent pass.
+                let oit_shader_defs: [ShaderDefVal; 2] =
+                    ["OIT_ENABLED".into(), "OIT_WEIGHTED_BLENDED".into()];
+                fragment
+                    .shader_defs
+                    .retain(|existing_shader_def| !oit_shader_defs.contains(existing_shader_def));
+                fragment.shader_defs.push(shader_def.into());
+                for target in fragment.targets.iter_mut().flatten() {
+                    target.blend = blend;
+                }
+            }
         }
 
         if let Some(label) = &mut descriptor.label {
@@ -123,6 +206,8 @@ impl Default for ForwardDecalMaterialExt {
     fn default() -> Self {
         Self {
             depth_fade_factor: 8.0,
+            blend_mode: ForwardDecalBlendMode::default(),
+            sort_bias: 0.0,
         }
     }
 }
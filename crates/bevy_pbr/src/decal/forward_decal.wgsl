@@ -50,3 +50,20 @@ fn get_forward_decal_info(in: VertexOutput) -> ForwardDecalInformation {
 
     return ForwardDecalInformation(world_position, uv, alpha);
 }
+
+// Applies the fade of the decal to the color it outputs, in the form that the
+// blend state of its blend mode expects.
+fn apply_forward_decal_alpha(color: vec4<f32>, decal_alpha: f32) -> vec4<f32> {
+    let alpha = min(decal_alpha, color.a);
+#ifdef FORWARD_DECAL_BLEND_MULTIPLY
+    // The blend function is `dst_color * src_color + (1 - src_alpha) * dst_color`,
+    // so premultiplying makes the decal fade towards leaving the surface as is.
+    return vec4(color.rgb * alpha, alpha);
+#else ifdef FORWARD_DECAL_BLEND_ADD
+    // With premultiplied alpha blending and a zero alpha, the faded color is
+    // added to the surface.
+    return vec4(color.rgb * alpha, 0.0);
+#else
+    return vec4(color.rgb, alpha);
+#endif
+}
@@ -21,6 +21,10 @@
     #import bevy_pbr::pbr_prepass_functions::calculate_motion_vector
 #endif
 
+#ifdef ENTITY_INDEX_PREPASS
+    #import bevy_pbr::mesh_bindings::mesh
+#endif
+
 // Creates the deferred gbuffer from a PbrInput.
 fn deferred_gbuffer_from_pbr_input(in: PbrInput) -> vec4<u32> {
      // Only monochrome occlusion supported. May not be worth including at all.
@@ -143,6 +147,17 @@ fn deferred_output(in: VertexOutput, pbr_input: PbrInput) -> FragmentOutput {
 #else
     out.motion_vector = calculate_motion_vector(in.world_position, in.previous_world_position);
 #endif
+#endif
+    // entity index if required
+#ifdef ENTITY_INDEX_PREPASS
+#ifdef MESHLET_MESH_MATERIAL_PASS
+    out.entity = in.entity;
+#else
+    out.entity = vec2(
+        mesh[in.instance_index].main_entity_index,
+        mesh[in.instance_index].main_entity_generation,
+    );
+#endif
 #endif
 
     return out;
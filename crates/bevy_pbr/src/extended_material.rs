@@ -47,6 +47,12 @@ pub trait MaterialExtension: Asset + AsBindGroup + Clone + Sized {
         None
     }
 
+    /// Returns a bias that's added to the [`Material::depth_bias`] of the base material, which
+    /// can be used to force a specific render order.
+    fn depth_bias(&self) -> f32 {
+        0.0
+    }
+
     /// Returns this material's prepass vertex shader. If [`ShaderRef::Default`] is returned, the base material prepass vertex shader
     /// will be used.
     fn prepass_vertex_shader() -> ShaderRef {
@@ -248,7 +254,7 @@ impl<B: Material, E: MaterialExtension> Material for ExtendedMaterial<B, E> {
     }
 
     fn depth_bias(&self) -> f32 {
-        B::depth_bias(&self.base)
+        B::depth_bias(&self.base) + E::depth_bias(&self.extension)
     }
 
     fn reads_view_transmission_texture(&self) -> bool {
@@ -0,0 +1,179 @@
+// Renders hair strands as ribbons facing the camera, shaded with the
+// Kajiya-Kay model [1] and the two specular lobes of Scheuermann [2].
+//
+// Both vertices of each point of a strand are at the same position. The
+// tangent of the vertices contains the direction of the strand, and half of
+// its width in `w`, with opposite signs for the two vertices, so that the
+// vertex shader moves them apart perpendicularly to the strand and the view.
+//
+// [1]: https://doi.org/10.1145/74334.74361
+//
+// [2]: https://web.engr.oregonstate.edu/~mjb/cs519/Projects/Papers/HairRendering.pdf
+
+#import bevy_pbr::{
+    forward_io::{Vertex, VertexOutput},
+    mesh_bindings::mesh,
+    mesh_functions,
+    mesh_types::MESH_FLAGS_SHADOW_RECEIVER_BIT,
+    mesh_view_bindings::{lights, view},
+    mesh_view_types::DIRECTIONAL_LIGHT_FLAGS_SHADOWS_ENABLED_BIT,
+    pbr_functions::calculate_view,
+    shadows,
+    skinning,
+    view_transformations::position_world_to_clip,
+}
+#import bevy_render::maths::PI
+
+#ifdef TONEMAP_IN_SHADER
+#import bevy_core_pipeline::tonemapping::tone_mapping
+#endif
+
+// The GPU version of `HairMaterial`. See the comments in `hair/mod.rs` for a
+// description of the fields.
+struct HairMaterial {
+    root_color: vec4<f32>,
+    tip_color: vec4<f32>,
+    specular_color: vec4<f32>,
+    primary_shift: f32,
+    secondary_shift: f32,
+    primary_exponent: f32,
+    secondary_exponent: f32,
+}
+
+@group(2) @binding(0) var<uniform> material: HairMaterial;
+
+fn is_orthographic() -> bool {
+    return view.clip_from_view[3].w == 1.0;
+}
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+
+#ifdef SKINNED
+#ifdef PRESKINNED
+    // The vertex was already skinned by the pre-skinning compute shader.
+    let preskinned = skinning::preskinned_vertex(vertex.index, vertex.instance_index);
+    let position = preskinned.position.xyz;
+    let tangent = preskinned.tangent;
+    let world_from_local = skinning::PRESKINNED_WORLD_FROM_LOCAL;
+#else   // PRESKINNED
+    let position = vertex.position;
+    let tangent = vertex.tangent;
+    let world_from_local = skinning::skin_model(
+        vertex.joint_indices,
+        vertex.joint_weights,
+        vertex.instance_index
+    );
+#endif  // PRESKINNED
+#else   // SKINNED
+    let position = vertex.position;
+    let tangent = vertex.tangent;
+    let world_from_local = mesh_functions::get_world_from_local(vertex.instance_index);
+#endif  // SKINNED
+
+    let world_position = mesh_functions::mesh_position_local_to_world(
+        world_from_local,
+        vec4(position, 1.0)
+    );
+
+    // Scale the width of the strand along with its length.
+    let world_direction = (world_from_local * vec4(tangent.xyz, 0.0)).xyz;
+    let T = normalize(world_direction);
+    let half_width = tangent.w * length(world_direction);
+
+    // Expand the ribbon perpendicularly to the strand and the view. If the
+    // strand points toward the camera, any direction perpendicular to it will
+    // do.
+    let V = calculate_view(world_position, is_orthographic());
+    var side = cross(T, V);
+    if (dot(side, side) < 1e-8) {
+        side = cross(T, select(vec3(0.0, 1.0, 0.0), vec3(1.0, 0.0, 0.0), abs(T.y) > 0.99));
+    }
+    side = normalize(side);
+
+    out.world_position = vec4(world_position.xyz + side * half_width, 1.0);
+    out.position = position_world_to_clip(out.world_position.xyz);
+    // The normal of the ribbon faces the camera. It's only used to offset the
+    // shadow lookups.
+    out.world_normal = normalize(cross(side, T));
+    out.world_tangent = vec4(T, 1.0);
+    out.uv = vertex.uv;
+
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+    out.instance_index = vertex.instance_index;
+#endif
+
+    return out;
+}
+
+// The specular term of Kajiya-Kay for the given tangent and half vector.
+fn strand_specular(T: vec3<f32>, H: vec3<f32>, exponent: f32) -> f32 {
+    let T_dot_H = dot(T, H);
+    let sin_T_H = sqrt(saturate(1.0 - T_dot_H * T_dot_H));
+    // Fade out the highlight when the light comes from behind the strand.
+    let direction_attenuation = smoothstep(-1.0, 0.0, T_dot_H);
+    // Normalize the lobe like a Blinn-Phong one, so that narrowing it doesn't
+    // make it brighter.
+    return direction_attenuation * pow(sin_T_H, exponent) * (exponent + 2.0) / (2.0 * PI);
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let N = normalize(in.world_normal);
+    let V = calculate_view(in.world_position, is_orthographic());
+    let base_color = mix(material.root_color, material.tip_color, in.uv.y);
+
+    // Shift the tangents along the normal to move the highlights along the
+    // strand, as the scales on its surface tilt the reflected light.
+    let T = normalize(in.world_tangent.xyz);
+    let T_primary = normalize(T + material.primary_shift * N);
+    let T_secondary = normalize(T + material.secondary_shift * N);
+
+    let view_z = dot(vec4<f32>(
+        view.view_from_world[0].z,
+        view.view_from_world[1].z,
+        view.view_from_world[2].z,
+        view.view_from_world[3].z
+    ), in.world_position);
+    let receives_shadows = (mesh[in.instance_index].flags & MESH_FLAGS_SHADOW_RECEIVER_BIT) != 0u;
+
+    var direct_light = vec3(0.0);
+    for (var i = 0u; i < lights.n_directional_lights; i += 1u) {
+        let light = &lights.directional_lights[i];
+        if ((*light).skip != 0u) {
+            continue;
+        }
+
+        var shadow = 1.0;
+        if (receives_shadows &&
+                ((*light).flags & DIRECTIONAL_LIGHT_FLAGS_SHADOWS_ENABLED_BIT) != 0u) {
+            shadow = shadows::fetch_directional_shadow(i, in.world_position, N, view_z);
+        }
+
+        let L = normalize((*light).direction_to_light);
+        let H = normalize(L + V);
+
+        // A thin cylinder receives light according to the sine of the angle
+        // between its axis and the light.
+        let T_dot_L = dot(T, L);
+        let sin_T_L = sqrt(saturate(1.0 - T_dot_L * T_dot_L));
+
+        let diffuse = base_color.rgb * sin_T_L / PI;
+        let specular = material.specular_color.rgb *
+            strand_specular(T_primary, H, material.primary_exponent) +
+            base_color.rgb * strand_specular(T_secondary, H, material.secondary_exponent);
+
+        direct_light += (diffuse + specular * sin_T_L) * (*light).color.rgb * shadow;
+    }
+
+    let ambient_light = base_color.rgb * lights.ambient_color.rgb;
+
+    var color = vec4((direct_light + ambient_light) * view.exposure, base_color.a);
+
+#ifdef TONEMAP_IN_SHADER
+    color = tone_mapping(color, view.color_grading);
+#endif
+
+    return color;
+}
@@ -0,0 +1,488 @@
+//! Rendering of hair and fur as strands.
+//!
+//! Each strand of a [`HairStrands`] asset is a curve that is expanded, in the
+//! vertex shader, into a thin ribbon facing the camera. The ribbons are shaded
+//! by a [`HairMaterial`] with the Kajiya-Kay model, which treats each strand as
+//! an infinitely thin cylinder and so only depends on its tangent.
+
+use bevy_app::{App, Plugin, PostUpdate};
+use bevy_asset::{load_internal_asset, Asset, AssetApp, AssetEvent, AssetId, Assets, Handle};
+use bevy_color::LinearRgba;
+use bevy_ecs::prelude::*;
+use bevy_math::Vec3;
+use bevy_reflect::{std_traits::ReflectDefault, Reflect, TypePath};
+use bevy_render::{
+    alpha::AlphaMode,
+    mesh::{
+        Indices, Mesh, Mesh3d, MeshVertexBufferLayoutRef, PrimitiveTopology, VertexAttributeValues,
+    },
+    render_asset::RenderAssetUsages,
+    render_resource::{
+        AsBindGroup, RenderPipelineDescriptor, Shader, ShaderRef, SpecializedMeshPipelineError,
+    },
+    view::VisibilitySystems,
+};
+use bevy_utils::HashMap;
+use thiserror::Error;
+
+use crate::{
+    Material, MaterialPipeline, MaterialPipelineKey, MaterialPlugin, OpaqueRendererMethod,
+};
+
+const HAIR_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(16144873940292001981);
+
+/// Adds support for rendering [`HairStrands`] with a [`HairMaterial`].
+///
+/// Hair doesn't cast shadows or write to the prepasses, as the ribbons are only
+/// expanded toward the main camera.
+pub struct HairPlugin;
+
+impl Plugin for HairPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(app, HAIR_SHADER_HANDLE, "hair.wgsl", Shader::from_wgsl);
+
+        app.init_asset::<HairStrands>()
+            .register_type::<HairStrands3d>()
+            .register_type::<HairMaterial>()
+            .init_resource::<HairMeshes>()
+            .add_plugins(MaterialPlugin::<HairMaterial> {
+                prepass_enabled: false,
+                shadows_enabled: false,
+                ..Default::default()
+            })
+            .add_systems(
+                PostUpdate,
+                update_hair_meshes.before(VisibilitySystems::CalculateBounds),
+            );
+    }
+}
+
+/// A set of hair strands, such as the hair of a character or the fur of an
+/// animal.
+///
+/// Add a [`HairStrands3d`] holding a handle to this asset to an entity to
+/// render it, along with a [`MeshMaterial3d`](crate::MeshMaterial3d) holding a
+/// [`HairMaterial`].
+#[derive(Asset, TypePath, Clone, Debug)]
+pub struct HairStrands {
+    /// The strands, each of which is rendered as a ribbon.
+    pub strands: Vec<HairStrand>,
+    /// The width of the strands at their roots, in the units of the mesh.
+    pub root_width: f32,
+    /// The width of the strands at their tips, in the units of the mesh.
+    pub tip_width: f32,
+}
+
+/// A single strand of a [`HairStrands`] asset.
+#[derive(Clone, Debug, Default)]
+pub struct HairStrand {
+    /// The points of the curve, from the root to the tip of the strand.
+    ///
+    /// Strands with less than two points are ignored.
+    pub points: Vec<Vec3>,
+    /// The joints that move this strand, if it's attached to a skinned scalp.
+    pub attachment: Option<HairStrandAttachment>,
+}
+
+/// The joints that a [`HairStrand`] is skinned to, which are usually those of
+/// the scalp vertex closest to its root.
+///
+/// See [`HairStrands::attach_to_scalp`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct HairStrandAttachment {
+    /// The indices of the joints, as in [`Mesh::ATTRIBUTE_JOINT_INDEX`].
+    pub joint_indices: [u16; 4],
+    /// The weights of the joints, as in [`Mesh::ATTRIBUTE_JOINT_WEIGHT`].
+    pub joint_weights: [f32; 4],
+}
+
+/// An error that occurs when attaching [`HairStrands`] to a scalp mesh.
+#[derive(Error, Debug)]
+pub enum HairAttachmentError {
+    #[error("the scalp mesh has no {0} attribute")]
+    MissingAttribute(&'static str),
+    #[error("the scalp mesh has no vertices")]
+    EmptyScalp,
+}
+
+impl Default for HairStrands {
+    fn default() -> Self {
+        Self {
+            strands: Vec::new(),
+            root_width: 0.002,
+            tip_width: 0.0005,
+        }
+    }
+}
+
+impl HairStrands {
+    /// Skins each strand to the joints of the vertex of the `scalp` mesh that
+    /// is the closest to its root.
+    ///
+    /// The strands must be in the same space as the vertices of the scalp. To
+    /// animate them, add the [`SkinnedMesh`](bevy_render::mesh::skinning::SkinnedMesh)
+    /// of the scalp to the entity of the hair as well.
+    pub fn attach_to_scalp(&mut self, scalp: &Mesh) -> Result<(), HairAttachmentError> {
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            scalp.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            return Err(HairAttachmentError::MissingAttribute(
+                Mesh::ATTRIBUTE_POSITION.name,
+            ));
+        };
+        let Some(VertexAttributeValues::Uint16x4(joint_indices)) =
+            scalp.attribute(Mesh::ATTRIBUTE_JOINT_INDEX)
+        else {
+            return Err(HairAttachmentError::MissingAttribute(
+                Mesh::ATTRIBUTE_JOINT_INDEX.name,
+            ));
+        };
+        let Some(VertexAttributeValues::Float32x4(joint_weights)) =
+            scalp.attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT)
+        else {
+            return Err(HairAttachmentError::MissingAttribute(
+                Mesh::ATTRIBUTE_JOINT_WEIGHT.name,
+            ));
+        };
+        if positions.is_empty() {
+            return Err(HairAttachmentError::EmptyScalp);
+        }
+
+        for strand in &mut self.strands {
+            let Some(root) = strand.points.first() else {
+                continue;
+            };
+            let nearest = positions
+                .iter()
+                .map(|position| root.distance_squared(Vec3::from(*position)))
+                .enumerate()
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(index, _)| index)
+                .unwrap_or_default();
+            strand.attachment = Some(HairStrandAttachment {
+                joint_indices: joint_indices[nearest],
+                joint_weights: joint_weights[nearest],
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Builds the mesh that the strands are rendered with.
+    ///
+    /// Each point of a strand becomes two vertices at the same position, which
+    /// the vertex shader of [`HairMaterial`] moves apart to face the camera.
+    /// The direction of the strand is stored in [`Mesh::ATTRIBUTE_TANGENT`],
+    /// along with half of its signed width in `w`, and [`Mesh::ATTRIBUTE_UV_0`]
+    /// goes from 0 to 1 across the strand in `x` and from the root to the tip
+    /// in `y`.
+    ///
+    /// The mesh is only skinned if all the strands are attached.
+    pub fn build_mesh(&self) -> Mesh {
+        let strands = || {
+            self.strands
+                .iter()
+                .filter(|strand| strand.points.len() >= 2)
+        };
+        let skinned =
+            strands().next().is_some() && strands().all(|strand| strand.attachment.is_some());
+
+        let vertex_count = strands().map(|strand| strand.points.len() * 2).sum();
+        let mut positions: Vec<[f32; 3]> = Vec::with_capacity(vertex_count);
+        let mut tangents: Vec<[f32; 4]> = Vec::with_capacity(vertex_count);
+        let mut uvs: Vec<[f32; 2]> = Vec::with_capacity(vertex_count);
+        let mut joint_indices: Vec<[u16; 4]> = Vec::new();
+        let mut joint_weights: Vec<[f32; 4]> = Vec::new();
+        let mut indices: Vec<u32> = Vec::new();
+
+        for strand in strands() {
+            let points = &strand.points;
+            let last = points.len() - 1;
+            for (i, point) in points.iter().enumerate() {
+                let t = i as f32 / last as f32;
+                let half_width = 0.5 * (self.root_width + (self.tip_width - self.root_width) * t);
+                let direction = (points[(i + 1).min(last)] - points[i.saturating_sub(1)])
+                    .try_normalize()
+                    .unwrap_or(Vec3::Y);
+
+                let first_vertex = positions.len() as u32;
+                for (side, u) in [(-1.0, 0.0), (1.0, 1.0)] {
+                    positions.push(point.to_array());
+                    tangents.push(direction.extend(side * half_width).to_array());
+                    uvs.push([u, t]);
+                }
+                if let Some(attachment) = strand.attachment.filter(|_| skinned) {
+                    joint_indices.extend([attachment.joint_indices; 2]);
+                    joint_weights.extend([attachment.joint_weights; 2]);
+                }
+
+                if i < last {
+                    indices.extend([
+                        first_vertex,
+                        first_vertex + 1,
+                        first_vertex + 2,
+                        first_vertex + 1,
+                        first_vertex + 3,
+                        first_vertex + 2,
+                    ]);
+                }
+            }
+        }
+
+        let mut mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_TANGENT, tangents)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+        .with_inserted_indices(Indices::U32(indices));
+        if skinned {
+            mesh.insert_attribute(
+                Mesh::ATTRIBUTE_JOINT_INDEX,
+                VertexAttributeValues::Uint16x4(joint_indices),
+            );
+            mesh.insert_attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT, joint_weights);
+        }
+        mesh
+    }
+}
+
+/// Renders the [`HairStrands`] with the given handle at this entity.
+///
+/// The [`Mesh3d`] of this entity is replaced with the mesh built from the
+/// strands once they're loaded.
+#[derive(Component, Clone, Debug, Default, Reflect, PartialEq, Eq)]
+#[reflect(Component, Default)]
+#[require(Mesh3d)]
+pub struct HairStrands3d(pub Handle<HairStrands>);
+
+/// The meshes built from each [`HairStrands`] asset.
+#[derive(Resource, Default)]
+struct HairMeshes(HashMap<AssetId<HairStrands>, Handle<Mesh>>);
+
+fn update_hair_meshes(
+    mut events: EventReader<AssetEvent<HairStrands>>,
+    hair_strands: Res<Assets<HairStrands>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut hair_meshes: ResMut<HairMeshes>,
+    mut hairs: Query<(&HairStrands3d, &mut Mesh3d)>,
+) {
+    for event in events.read() {
+        match *event {
+            AssetEvent::Added { id } | AssetEvent::Modified { id } => {
+                let Some(strands) = hair_strands.get(id) else {
+                    continue;
+                };
+                let mesh = strands.build_mesh();
+                match hair_meshes.0.get(&id) {
+                    Some(handle) => meshes.insert(handle, mesh),
+                    None => {
+                        hair_meshes.0.insert(id, meshes.add(mesh));
+                    }
+                }
+            }
+            AssetEvent::Removed { id } => {
+                hair_meshes.0.remove(&id);
+            }
+            AssetEvent::Unused { .. } | AssetEvent::LoadedWithDependencies { .. } => {}
+        }
+    }
+
+    for (hair, mut mesh) in &mut hairs {
+        if let Some(handle) = hair_meshes.0.get(&hair.0.id()) {
+            if mesh.0 != *handle {
+                mesh.0 = handle.clone();
+            }
+        }
+    }
+}
+
+/// A material that shades [`HairStrands`] with the Kajiya-Kay model.
+///
+/// Following Scheuermann's extension of the model, the highlight is split in
+/// two lobes: a white primary one, and a secondary one tinted by the color of
+/// the hair that is shifted toward the tip, as light that was refracted by the
+/// strand reflects off its back.
+///
+/// The strands are lit by the directional lights and the ambient light.
+#[derive(Asset, AsBindGroup, Reflect, Clone, Debug)]
+#[reflect(Default, Debug)]
+pub struct HairMaterial {
+    /// The color of the strands at their roots.
+    #[uniform(0)]
+    pub root_color: LinearRgba,
+    /// The color of the strands at their tips.
+    ///
+    /// With an [`AlphaMode`] that isn't opaque, the alpha can be lowered
+    /// toward the tips to soften them.
+    #[uniform(0)]
+    pub tip_color: LinearRgba,
+    /// The color of the primary highlight.
+    #[uniform(0)]
+    pub specular_color: LinearRgba,
+    /// How far the primary highlight is shifted along the strands.
+    ///
+    /// This models the tilt of the scales on the surface of the strands, and
+    /// is usually a small negative value, which shifts it toward the roots.
+    #[uniform(0)]
+    pub primary_shift: f32,
+    /// How far the secondary highlight is shifted along the strands.
+    #[uniform(0)]
+    pub secondary_shift: f32,
+    /// The specular exponent of the primary highlight. The higher it is, the
+    /// narrower the highlight.
+    #[uniform(0)]
+    pub primary_exponent: f32,
+    /// The specular exponent of the secondary highlight.
+    #[uniform(0)]
+    pub secondary_exponent: f32,
+    /// How the strands are blended with what's behind them.
+    pub alpha_mode: AlphaMode,
+}
+
+impl Default for HairMaterial {
+    fn default() -> Self {
+        Self {
+            root_color: LinearRgba::rgb(0.05, 0.03, 0.02),
+            tip_color: LinearRgba::rgb(0.2, 0.12, 0.07),
+            specular_color: LinearRgba::rgb(0.3, 0.3, 0.3),
+            primary_shift: -0.1,
+            secondary_shift: 0.1,
+            primary_exponent: 200.0,
+            secondary_exponent: 40.0,
+            alpha_mode: AlphaMode::Opaque,
+        }
+    }
+}
+
+impl Material for HairMaterial {
+    fn vertex_shader() -> ShaderRef {
+        HAIR_SHADER_HANDLE.into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        HAIR_SHADER_HANDLE.into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        self.alpha_mode
+    }
+
+    fn opaque_render_method(&self) -> OpaqueRendererMethod {
+        // The strands aren't written to the prepasses, so they can't be
+        // deferred.
+        OpaqueRendererMethod::Forward
+    }
+
+    fn specialize(
+        _pipeline: &MaterialPipeline<Self>,
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        // The ribbons face the camera, but may be seen from behind when their
+        // tangent is skinned or changes abruptly.
+        descriptor.primitive.cull_mode = None;
+
+        // The fragment shader reads the flags of the mesh to know whether it
+        // receives shadows.
+        descriptor
+            .vertex
+            .shader_defs
+            .push("VERTEX_OUTPUT_INSTANCE_INDEX".into());
+        if let Some(fragment) = descriptor.fragment.as_mut() {
+            fragment
+                .shader_defs
+                .push("VERTEX_OUTPUT_INSTANCE_INDEX".into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_math::Vec3;
+    use bevy_render::{
+        mesh::{Mesh, VertexAttributeValues},
+        render_asset::RenderAssetUsages,
+    };
+
+    use super::{HairStrand, HairStrandAttachment, HairStrands};
+
+    fn strand(points: &[Vec3]) -> HairStrand {
+        HairStrand {
+            points: points.to_vec(),
+            attachment: None,
+        }
+    }
+
+    #[test]
+    fn hair_mesh_has_two_vertices_per_point() {
+        let strands = HairStrands {
+            strands: vec![
+                strand(&[Vec3::ZERO, Vec3::Y, Vec3::Y * 2.0]),
+                strand(&[Vec3::X, Vec3::X + Vec3::Y]),
+                // Ignored, as it's not a curve.
+                strand(&[Vec3::Z]),
+            ],
+            root_width: 0.5,
+            tip_width: 0.25,
+        };
+        let mesh = strands.build_mesh();
+
+        assert_eq!(mesh.count_vertices(), 10);
+        assert_eq!(mesh.indices().unwrap().len(), (2 + 1) * 6);
+        assert!(mesh.attribute(Mesh::ATTRIBUTE_JOINT_INDEX).is_none());
+
+        let Some(VertexAttributeValues::Float32x4(tangents)) =
+            mesh.attribute(Mesh::ATTRIBUTE_TANGENT)
+        else {
+            panic!("the hair mesh has no tangents");
+        };
+        assert_eq!(tangents[0], [0.0, 1.0, 0.0, -0.25]);
+        assert_eq!(tangents[1], [0.0, 1.0, 0.0, 0.25]);
+        assert_eq!(tangents[5], [0.0, 1.0, 0.0, 0.125]);
+    }
+
+    #[test]
+    fn hair_attaches_to_nearest_scalp_vertex() {
+        let scalp = Mesh::new(
+            bevy_render::mesh::PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(
+            Mesh::ATTRIBUTE_POSITION,
+            vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0]],
+        )
+        .with_inserted_attribute(
+            Mesh::ATTRIBUTE_JOINT_INDEX,
+            VertexAttributeValues::Uint16x4(vec![[0, 0, 0, 0], [1, 2, 0, 0]]),
+        )
+        .with_inserted_attribute(
+            Mesh::ATTRIBUTE_JOINT_WEIGHT,
+            vec![[1.0, 0.0, 0.0, 0.0], [0.5, 0.5, 0.0, 0.0]],
+        );
+
+        let mut strands = HairStrands {
+            strands: vec![strand(&[
+                Vec3::new(0.9, 0.1, 0.0),
+                Vec3::new(0.9, 1.0, 0.0),
+            ])],
+            ..Default::default()
+        };
+        strands.attach_to_scalp(&scalp).unwrap();
+
+        assert_eq!(
+            strands.strands[0].attachment,
+            Some(HairStrandAttachment {
+                joint_indices: [1, 2, 0, 0],
+                joint_weights: [0.5, 0.5, 0.0, 0.0],
+            })
+        );
+        assert!(strands
+            .build_mesh()
+            .attribute(Mesh::ATTRIBUTE_JOINT_INDEX)
+            .is_some());
+    }
+}
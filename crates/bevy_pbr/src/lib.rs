@@ -24,34 +24,50 @@ pub mod experimental {
     }
 }
 
+mod auto_lod;
 mod cluster;
 mod components;
+mod debug_view;
 pub mod decal;
 pub mod deferred;
 mod extended_material;
 mod fog;
+mod hair;
 mod light;
+pub mod light_baking;
 mod light_probe;
 mod lightmap;
 mod material;
 mod material_bind_groups;
+pub mod material_graph;
 mod mesh_material;
 mod parallax;
+pub mod particles;
 mod pbr_material;
+#[cfg(feature = "pbr_planar_reflections")]
+mod planar_reflection;
 mod prepass;
 mod render;
+pub mod scatter;
 mod ssao;
+mod ssgi;
 mod ssr;
+pub mod terrain;
+pub mod virtual_texturing;
+mod volumetric_clouds;
 mod volumetric_fog;
 
 use crate::material_bind_groups::FallbackBindlessResources;
 
 use bevy_color::{Color, LinearRgba};
 
+pub use auto_lod::*;
 pub use cluster::*;
 pub use components::*;
+pub use debug_view::*;
 pub use extended_material::*;
 pub use fog::*;
+pub use hair::*;
 pub use light::*;
 pub use light_probe::*;
 pub use lightmap::*;
@@ -59,10 +75,17 @@ pub use material::*;
 pub use mesh_material::*;
 pub use parallax::*;
 pub use pbr_material::*;
+#[cfg(feature = "pbr_planar_reflections")]
+pub use planar_reflection::*;
 pub use prepass::*;
 pub use render::*;
 pub use ssao::*;
+pub use ssgi::*;
 pub use ssr::*;
+pub use volumetric_clouds::{
+    VolumetricClouds, VolumetricCloudsPlugin, DEFAULT_CLOUD_COVERAGE_TEXTURE,
+    DEFAULT_CLOUD_EROSION_TEXTURE,
+};
 pub use volumetric_fog::{FogVolume, VolumetricFog, VolumetricFogPlugin, VolumetricLight};
 
 /// The PBR prelude.
@@ -94,12 +117,31 @@ pub mod graph {
         DeferredLightingPass,
         /// Label for the volumetric lighting pass.
         VolumetricFog,
+        /// Label for the volumetric clouds pass.
+        VolumetricClouds,
         /// Label for the compute shader instance data building pass.
         GpuPreprocess,
         /// Label for the screen space reflections pass.
         ScreenSpaceReflections,
+        /// Label for the screen space global illumination pass.
+        ScreenSpaceGlobalIllumination,
+        /// Label for the pass that saves the lit color of the opaque surfaces,
+        /// from which screen space global illumination gathers light.
+        ScreenSpaceGlobalIlluminationHistory,
         /// Label for the indirect parameters building pass.
         BuildIndirectParameters,
+        /// Label for the depth pyramid building pass used by occlusion culling.
+        DownsampleDepth,
+        /// Label for the late compute shader instance data building pass used
+        /// by occlusion culling.
+        LateGpuPreprocess,
+        /// Label for the late indirect parameters building pass used by
+        /// occlusion culling.
+        LateBuildIndirectParameters,
+        /// Label for the pass that culls the instances of scatter layers.
+        Scatter,
+        /// Label for the pass that simulates and sorts particles.
+        Particles,
     }
 }
 
@@ -147,6 +189,7 @@ pub const PBR_PREPASS_FUNCTIONS_SHADER_HANDLE: Handle<Shader> =
 pub const PBR_DEFERRED_TYPES_HANDLE: Handle<Shader> = Handle::weak_from_u128(3221241127431430599);
 pub const PBR_DEFERRED_FUNCTIONS_HANDLE: Handle<Shader> = Handle::weak_from_u128(72019026415438599);
 pub const RGB9E5_FUNCTIONS_HANDLE: Handle<Shader> = Handle::weak_from_u128(2659010996143919192);
+pub const DEBUG_VIEW_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(8631918202179937795);
 const MESHLET_VISIBILITY_BUFFER_RESOLVE_SHADER_HANDLE: Handle<Shader> =
     Handle::weak_from_u128(2325134235233421);
 
@@ -259,6 +302,12 @@ impl Plugin for PbrPlugin {
             Shader::from_wgsl
         );
         load_internal_asset!(app, PBR_SHADER_HANDLE, "render/pbr.wgsl", Shader::from_wgsl);
+        load_internal_asset!(
+            app,
+            DEBUG_VIEW_SHADER_HANDLE,
+            "render/debug_view.wgsl",
+            Shader::from_wgsl
+        );
         load_internal_asset!(
             app,
             PBR_PREPASS_FUNCTIONS_SHADER_HANDLE,
@@ -305,8 +354,10 @@ impl Plugin for PbrPlugin {
             .register_type::<NotShadowReceiver>()
             .register_type::<PointLight>()
             .register_type::<PointLightShadowMap>()
+            .register_type::<ShadowPriority>()
             .register_type::<SpotLight>()
             .register_type::<ShadowFilteringMethod>()
+            .register_type::<DebugViewMode>()
             .init_resource::<AmbientLight>()
             .init_resource::<GlobalVisibleClusterableObjects>()
             .init_resource::<DirectionalLightShadowMap>()
@@ -334,14 +385,29 @@ impl Plugin for PbrPlugin {
                 },
                 VolumetricFogPlugin,
                 ScreenSpaceReflectionsPlugin,
+                RayTracedShadowsPlugin,
+                light_baking::LightBakingPlugin,
             ))
             .add_plugins((
                 decal::ForwardDecalPlugin,
+                IesProfilePlugin,
+                AutoLodPlugin,
+                ScreenSpaceGlobalIlluminationPlugin,
+                VolumetricCloudsPlugin,
+                HairPlugin,
+                terrain::TerrainPlugin,
+                scatter::ScatterPlugin,
+                particles::ParticlesPlugin,
+                material_graph::MaterialGraphPlugin,
                 SyncComponentPlugin::<DirectionalLight>::default(),
                 SyncComponentPlugin::<PointLight>::default(),
                 SyncComponentPlugin::<SpotLight>::default(),
                 ExtractComponentPlugin::<AmbientLight>::default(),
             ))
+            .add_plugins((
+                ShadowCachePlugin,
+                ExtractComponentPlugin::<DebugViewMode>::default(),
+            ))
             .configure_sets(
                 PostUpdate,
                 (
@@ -412,6 +478,9 @@ impl Plugin for PbrPlugin {
             app.add_plugins(DeferredPbrLightingPlugin);
         }
 
+        #[cfg(feature = "pbr_planar_reflections")]
+        app.add_plugins(PlanarReflectionPlugin);
+
         // Initialize the default material handle.
         app.world_mut()
             .resource_mut::<Assets<StandardMaterial>>()
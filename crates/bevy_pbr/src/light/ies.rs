@@ -0,0 +1,391 @@
+use bevy_app::{App, Plugin};
+use bevy_asset::{
+    io::Reader, Asset, AssetApp, AssetEvent, AssetId, AssetLoader, Assets, Handle, LoadContext,
+};
+use bevy_ecs::prelude::*;
+use bevy_math::{Vec2, Vec3};
+use bevy_reflect::prelude::*;
+use bevy_render::{
+    render_resource::{BufferUsages, RawBufferVec},
+    renderer::{RenderDevice, RenderQueue},
+    Extract, ExtractSchedule, Render, RenderApp, RenderSet,
+};
+use bevy_utils::HashMap;
+use thiserror::Error;
+
+/// The number of values that each [`IesProfile`] is resampled to on the GPU.
+///
+/// The samples are evenly spaced between a vertical angle of 0° and 180°.
+pub const IES_PROFILE_SAMPLE_COUNT: usize = 64;
+
+/// A photometric profile, as measured for a real-world light fixture and
+/// stored in an IES LM-63 file.
+///
+/// The profile describes the luminous intensity of the fixture in every
+/// direction. Add an [`IesLightProfile`] to a [`PointLight`](crate::PointLight)
+/// or a [`SpotLight`](crate::SpotLight) to shape its light with a profile.
+///
+/// Only type C photometry, which nearly all architectural fixtures use, is
+/// supported. Intensities are averaged over the horizontal angles, so
+/// profiles are treated as rotationally symmetric around their vertical axis.
+#[derive(Asset, Clone, Debug, TypePath)]
+pub struct IesProfile {
+    /// The vertical angles at which the intensity was measured, in degrees.
+    ///
+    /// 0° points along the photometric axis of the fixture, which is usually
+    /// straight down (the nadir), and 180° points away from it.
+    pub vertical_angles: Vec<f32>,
+    /// The horizontal angles at which the intensity was measured, in degrees.
+    pub horizontal_angles: Vec<f32>,
+    /// The measured luminous intensities, in candela.
+    ///
+    /// There is one row of [`IesProfile::vertical_angles`] values for each
+    /// horizontal angle.
+    pub candela: Vec<f32>,
+}
+
+/// An error that occurs when parsing an [`IesProfile`].
+#[derive(Error, Debug)]
+pub enum IesProfileError {
+    #[error("the file doesn't contain a TILT line")]
+    MissingTilt,
+    #[error("the file ends before all photometric data was read")]
+    UnexpectedEnd,
+    #[error("invalid number: {0}")]
+    InvalidNumber(String),
+    #[error("unsupported photometric type {0}, only type C (1) is supported")]
+    UnsupportedPhotometricType(u32),
+    #[error("the profile needs at least one vertical and one horizontal angle")]
+    MissingAngles,
+    #[error("failed to read the IES file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("the IES file is not valid UTF-8: {0}")]
+    Utf8(#[from] core::str::Utf8Error),
+}
+
+impl IesProfile {
+    /// Parses a profile from the text of an IES LM-63 file.
+    pub fn parse(text: &str) -> Result<Self, IesProfileError> {
+        let mut lines = text.lines();
+        let tilt = lines
+            .by_ref()
+            .find_map(|line| line.trim().strip_prefix("TILT="))
+            .ok_or(IesProfileError::MissingTilt)?;
+
+        let mut values = lines
+            .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == ','))
+            .filter(|token| !token.is_empty())
+            .map(|token| {
+                token
+                    .parse::<f32>()
+                    .map_err(|_| IesProfileError::InvalidNumber(token.to_owned()))
+            });
+        let mut next = || values.next().unwrap_or(Err(IesProfileError::UnexpectedEnd));
+
+        // Tilt data only matters for lamps that are mounted at an angle, so
+        // it's skipped.
+        if tilt.trim() == "INCLUDE" {
+            let _lamp_to_luminaire_geometry = next()?;
+            let tilt_angle_count = next()? as usize;
+            for _ in 0..tilt_angle_count * 2 {
+                next()?;
+            }
+        }
+
+        let _lamp_count = next()?;
+        let _lumens_per_lamp = next()?;
+        let candela_multiplier = next()?;
+        let vertical_angle_count = next()? as usize;
+        let horizontal_angle_count = next()? as usize;
+        let photometric_type = next()? as u32;
+        let _units_type = next()?;
+        let _width = next()?;
+        let _length = next()?;
+        let _height = next()?;
+        let ballast_factor = next()?;
+        let ballast_lamp_photometric_factor = next()?;
+        let _input_watts = next()?;
+
+        if photometric_type != 1 {
+            return Err(IesProfileError::UnsupportedPhotometricType(
+                photometric_type,
+            ));
+        }
+        if vertical_angle_count == 0 || horizontal_angle_count == 0 {
+            return Err(IesProfileError::MissingAngles);
+        }
+
+        let vertical_angles = (0..vertical_angle_count)
+            .map(|_| next())
+            .collect::<Result<Vec<_>, _>>()?;
+        let horizontal_angles = (0..horizontal_angle_count)
+            .map(|_| next())
+            .collect::<Result<Vec<_>, _>>()?;
+        let scale = candela_multiplier * ballast_factor * ballast_lamp_photometric_factor;
+        let candela = (0..vertical_angle_count * horizontal_angle_count)
+            .map(|_| next().map(|value| value * scale))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            vertical_angles,
+            horizontal_angles,
+            candela,
+        })
+    }
+
+    /// Returns the luminous intensity at the given vertical angle in degrees,
+    /// in candela, averaged over all horizontal angles.
+    ///
+    /// Angles that the profile doesn't cover have an intensity of zero.
+    pub fn average_intensity(&self, vertical_angle: f32) -> f32 {
+        let row_count = self.horizontal_angles.len().max(1);
+        let total: f32 = self
+            .candela
+            .chunks_exact(self.vertical_angles.len().max(1))
+            .map(|row| interpolate(&self.vertical_angles, row, vertical_angle))
+            .sum();
+        total / row_count as f32
+    }
+
+    /// Returns the highest luminous intensity of the averaged profile, in
+    /// candela.
+    pub fn peak_intensity(&self) -> f32 {
+        (0..IES_PROFILE_SAMPLE_COUNT)
+            .map(|index| self.average_intensity(sample_angle(index)))
+            .fold(0.0, f32::max)
+    }
+
+    /// Resamples the averaged profile to [`IES_PROFILE_SAMPLE_COUNT`] values,
+    /// normalized so that the brightest one is 1.
+    pub fn normalized_samples(&self) -> [f32; IES_PROFILE_SAMPLE_COUNT] {
+        let mut samples = [0.0; IES_PROFILE_SAMPLE_COUNT];
+        for (index, sample) in samples.iter_mut().enumerate() {
+            *sample = self.average_intensity(sample_angle(index));
+        }
+        let peak = samples.iter().copied().fold(0.0, f32::max);
+        if peak > 0.0 {
+            for sample in &mut samples {
+                *sample /= peak;
+            }
+        }
+        samples
+    }
+}
+
+/// Returns the vertical angle of the sample with the given index, in degrees.
+fn sample_angle(index: usize) -> f32 {
+    180.0 * index as f32 / (IES_PROFILE_SAMPLE_COUNT - 1) as f32
+}
+
+/// Linearly interpolates between the measured `values` at the given angle.
+fn interpolate(angles: &[f32], values: &[f32], angle: f32) -> f32 {
+    let (Some(&first), Some(&last)) = (angles.first(), angles.last()) else {
+        return 0.0;
+    };
+    if angle < first || angle > last {
+        return 0.0;
+    }
+    if angles.len() == 1 {
+        return values[0];
+    }
+    let upper = angles
+        .partition_point(|&measured| measured < angle)
+        .clamp(1, angles.len() - 1);
+    let lower = upper - 1;
+    let t = (angle - angles[lower]) / (angles[upper] - angles[lower]).max(f32::EPSILON);
+    values[lower] + (values[upper] - values[lower]) * t.clamp(0.0, 1.0)
+}
+
+/// Loads [`IesProfile`]s from `.ies` files.
+#[derive(Default)]
+pub struct IesProfileLoader;
+
+impl AssetLoader for IesProfileLoader {
+    type Asset = IesProfile;
+    type Settings = ();
+    type Error = IesProfileError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<IesProfile, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        IesProfile::parse(core::str::from_utf8(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ies"]
+    }
+}
+
+/// Shapes the light of a [`PointLight`](crate::PointLight) or a
+/// [`SpotLight`](crate::SpotLight) with a measured [`IesProfile`].
+///
+/// The photometric axis of the profile points along the light's forward
+/// direction (local -Z). Spot lights still apply their cone on top of the
+/// profile.
+///
+/// Profiles are only applied on platforms with at least 3 storage buffers
+/// available in the fragment shader, which excludes WebGL 2.
+#[derive(Component, Clone, Debug, Default, Reflect)]
+#[reflect(Component, Default, Debug)]
+pub struct IesLightProfile {
+    /// The profile to apply.
+    pub profile: Handle<IesProfile>,
+    /// How the profile affects the brightness of the light.
+    ///
+    /// If `None`, the light keeps its own intensity and the profile only
+    /// changes its shape. If `Some`, the intensity of the light is ignored,
+    /// and the brightest direction of the profile emits the measured
+    /// intensity of the fixture multiplied by this value.
+    pub profile_intensity_multiplier: Option<f32>,
+}
+
+/// Adds support for [`IesProfile`]s and [`IesLightProfile`]s.
+pub struct IesProfilePlugin;
+
+impl Plugin for IesProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<IesProfile>()
+            .register_asset_loader(IesProfileLoader)
+            .register_type::<IesLightProfile>();
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .init_resource::<IesProfileBuffer>()
+            .add_systems(ExtractSchedule, extract_ies_profiles)
+            .add_systems(
+                Render,
+                prepare_ies_profiles.in_set(RenderSet::PrepareResources),
+            );
+    }
+}
+
+/// The GPU buffer that holds the normalized samples of every loaded
+/// [`IesProfile`], one after another.
+#[derive(Resource)]
+pub struct IesProfileBuffer {
+    samples: RawBufferVec<f32>,
+    indices: HashMap<AssetId<IesProfile>, u32>,
+    dirty: bool,
+}
+
+impl Default for IesProfileBuffer {
+    fn default() -> Self {
+        let mut samples = RawBufferVec::new(BufferUsages::STORAGE);
+        samples.set_label(Some("ies_profile_buffer"));
+        Self {
+            samples,
+            indices: HashMap::default(),
+            dirty: true,
+        }
+    }
+}
+
+impl IesProfileBuffer {
+    /// Returns the index of the given profile in the buffer, if it's loaded.
+    pub fn index(&self, id: AssetId<IesProfile>) -> Option<u32> {
+        self.indices.get(&id).copied()
+    }
+
+    pub fn buffer(&self) -> &RawBufferVec<f32> {
+        &self.samples
+    }
+}
+
+/// Rebuilds the [`IesProfileBuffer`] whenever a profile is added, changed or
+/// removed.
+fn extract_ies_profiles(
+    mut buffer: ResMut<IesProfileBuffer>,
+    profiles: Extract<Res<Assets<IesProfile>>>,
+    mut events: Extract<EventReader<AssetEvent<IesProfile>>>,
+) {
+    if events.read().count() == 0 && !buffer.dirty {
+        return;
+    }
+
+    let buffer = &mut *buffer;
+    buffer.samples.clear();
+    buffer.indices.clear();
+    for (index, (id, profile)) in profiles.iter().enumerate() {
+        buffer.indices.insert(id, index as u32);
+        for sample in profile.normalized_samples() {
+            buffer.samples.push(sample);
+        }
+    }
+    buffer.dirty = true;
+}
+
+fn prepare_ies_profiles(
+    mut buffer: ResMut<IesProfileBuffer>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+) {
+    if !buffer.dirty {
+        return;
+    }
+
+    // Bindings can't be empty, so always upload at least one profile.
+    if buffer.samples.is_empty() {
+        for _ in 0..IES_PROFILE_SAMPLE_COUNT {
+            buffer.samples.push(0.0);
+        }
+    }
+    buffer.samples.write_buffer(&render_device, &render_queue);
+    buffer.dirty = false;
+}
+
+/// Packs the photometric axis of a light for the GPU, as an octahedral-encoded
+/// direction stored in two 16-bit signed normalized integers.
+pub(crate) fn pack_ies_profile_axis(axis: Vec3) -> u32 {
+    let n = axis / (axis.x.abs() + axis.y.abs() + axis.z.abs());
+    let encoded = if n.z >= 0.0 {
+        Vec2::new(n.x, n.y)
+    } else {
+        (1.0 - Vec2::new(n.y, n.x).abs())
+            * Vec2::new(
+                if n.x >= 0.0 { 1.0 } else { -1.0 },
+                if n.y >= 0.0 { 1.0 } else { -1.0 },
+            )
+    };
+    let snorm = |value: f32| (value.clamp(-1.0, 1.0) * 32767.0).round() as i16 as u16 as u32;
+    snorm(encoded.x) | (snorm(encoded.y) << 16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IesProfile;
+
+    const PROFILE: &str = "IESNA:LM-63-2002
+[TEST] downlight
+[MANUFAC] bevy
+TILT=NONE
+1 1000 2.0 3 2 1 2 0 0 0
+1.0 1.0 10
+0 45 90
+0 180
+100 50 0
+100 30 0
+";
+
+    #[test]
+    fn parse_ies_profile() {
+        let profile = IesProfile::parse(PROFILE).unwrap();
+        assert_eq!(profile.vertical_angles, [0.0, 45.0, 90.0]);
+        assert_eq!(profile.horizontal_angles, [0.0, 180.0]);
+        assert_eq!(profile.candela, [200.0, 100.0, 0.0, 200.0, 60.0, 0.0]);
+
+        assert_eq!(profile.average_intensity(0.0), 200.0);
+        assert_eq!(profile.average_intensity(45.0), 80.0);
+        assert_eq!(profile.average_intensity(22.5), 140.0);
+        assert_eq!(profile.average_intensity(120.0), 0.0);
+        assert_eq!(profile.peak_intensity(), 200.0);
+        assert_eq!(profile.normalized_samples()[0], 1.0);
+    }
+}
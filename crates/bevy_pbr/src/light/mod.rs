@@ -31,6 +31,21 @@ mod spot_light;
 pub use spot_light::SpotLight;
 mod directional_light;
 pub use directional_light::DirectionalLight;
+mod ies;
+pub(crate) use ies::pack_ies_profile_axis;
+pub use ies::{
+    IesLightProfile, IesProfile, IesProfileBuffer, IesProfileError, IesProfileLoader,
+    IesProfilePlugin, IES_PROFILE_SAMPLE_COUNT,
+};
+mod ray_traced_shadows;
+pub use ray_traced_shadows::{
+    ray_traced_shadows_are_usable, RayTracedShadows, RayTracedShadowsPlugin, RayTracingScene,
+};
+mod shadow_cache;
+pub use shadow_cache::{
+    prepare_cached_shadow_views, CachedShadowMap, CachedShadowView, RenderStaticShadowCasters,
+    ShadowCache, ShadowCachePlugin, StaticShadowCaster, StaticShadowView,
+};
 
 /// Constants for operating with the light units: lumens, and lux.
 pub mod light_consts {
@@ -89,15 +104,50 @@ pub mod light_consts {
     }
 }
 
+/// Controls the resolution of [`PointLight`] shadow maps.
+///
+/// The shadow maps of all point lights share an atlas. Each light gets a
+/// resolution between [`Self::min_size`] and [`Self::size`], depending on its
+/// [`ShadowPriority`] and how much of the screen its range covers, and the
+/// least important lights get smaller shadow maps, or none at all, to keep the
+/// atlas within [`Self::memory_budget`].
 #[derive(Resource, Clone, Debug, Reflect)]
 #[reflect(Resource, Debug, Default)]
 pub struct PointLightShadowMap {
+    /// The width and height of each face of the most detailed shadow maps, in
+    /// texels.
     pub size: usize,
+    /// The width and height of each face of the least detailed shadow maps, in
+    /// texels.
+    pub min_size: usize,
+    /// The largest amount of memory the atlas can take, in bytes.
+    pub memory_budget: usize,
 }
 
 impl Default for PointLightShadowMap {
     fn default() -> Self {
-        Self { size: 1024 }
+        Self {
+            size: 1024,
+            min_size: 128,
+            // Ten lights at the full size.
+            memory_budget: 256 * 1024 * 1024,
+        }
+    }
+}
+
+/// Scales the resolution that a [`PointLight`] shadow map gets in the atlas,
+/// see [`PointLightShadowMap`].
+///
+/// A light that covers the whole screen has a priority of 1 by default. Raise
+/// it for lights whose shadows matter more than their size suggests, and lower
+/// it for those whose shadows matter less.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component, Default, Debug)]
+pub struct ShadowPriority(pub f32);
+
+impl Default for ShadowPriority {
+    fn default() -> Self {
+        Self(1.0)
     }
 }
 
@@ -444,7 +494,7 @@ fn calculate_cascade(
     }
 }
 /// Add this component to make a [`Mesh3d`] not cast shadows.
-#[derive(Debug, Component, Reflect, Default)]
+#[derive(Debug, Clone, Copy, Component, Reflect, Default)]
 #[reflect(Component, Default, Debug)]
 pub struct NotShadowCaster;
 /// Add this component to make a [`Mesh3d`] not receive shadows.
@@ -452,7 +502,7 @@ pub struct NotShadowCaster;
 /// **Note:** If you're using diffuse transmission, setting [`NotShadowReceiver`] will
 /// cause both “regular” shadows as well as diffusely transmitted shadows to be disabled,
 /// even when [`TransmittedShadowReceiver`] is being used.
-#[derive(Debug, Component, Reflect, Default)]
+#[derive(Debug, Clone, Copy, Component, Reflect, Default)]
 #[reflect(Component, Default, Debug)]
 pub struct NotShadowReceiver;
 /// Add this component to make a [`Mesh3d`] using a PBR material with [`diffuse_transmission`](crate::pbr_material::StandardMaterial::diffuse_transmission)`> 0.0`
@@ -462,7 +512,7 @@ pub struct NotShadowReceiver;
 /// (and potentially even baking a thickness texture!) to match the geometry of the mesh, in order to avoid self-shadow artifacts.
 ///
 /// **Note:** Using [`NotShadowReceiver`] overrides this component.
-#[derive(Debug, Component, Reflect, Default)]
+#[derive(Debug, Clone, Copy, Component, Reflect, Default)]
 #[reflect(Component, Default, Debug)]
 pub struct TransmittedShadowReceiver;
 
@@ -0,0 +1,486 @@
+use core::iter;
+
+use bevy_app::{App, Plugin};
+use bevy_asset::{load_internal_asset, AssetId, Assets, Handle};
+use bevy_ecs::prelude::*;
+use bevy_math::Mat4;
+use bevy_reflect::prelude::*;
+use bevy_render::{
+    mesh::{
+        allocator::{MeshAllocator, MeshAllocatorSettings, MeshBufferSlice},
+        Mesh, Mesh3d, MeshVertexBufferLayoutRef, RenderMesh, RenderMeshBufferInfo,
+    },
+    render_asset::{prepare_assets, ExtractedAssets, RenderAssets},
+    render_resource::{
+        AccelerationStructureFlags, AccelerationStructureGeometryFlags,
+        AccelerationStructureUpdateMode, Blas, BlasBuildEntry, BlasGeometries,
+        BlasGeometrySizeDescriptors, BlasTriangleGeometry, BlasTriangleGeometrySizeDescriptor,
+        BufferAddress, BufferUsages, CommandEncoderDescriptor, CommandEncoderRayTracing,
+        CreateBlasDescriptor, CreateTlasDescriptor, DeviceRayTracing, IndexFormat,
+        PrimitiveTopology, Shader, TlasInstance, TlasPackage, VertexFormat,
+    },
+    renderer::{RenderDevice, RenderQueue},
+    settings::WgpuFeatures,
+    sync_world::MainEntity,
+    view::InheritedVisibility,
+    Extract, ExtractSchedule, Render, RenderApp, RenderSet,
+};
+use bevy_transform::components::GlobalTransform;
+use bevy_utils::{HashMap, HashSet};
+use tracing::warn;
+
+use crate::{AlphaMode, MeshMaterial3d, NotShadowCaster, StandardMaterial};
+
+pub(crate) const RAY_TRACED_SHADOWS_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(9371508862037150942);
+
+/// Casts the shadows of a [`DirectionalLight`](crate::DirectionalLight), a
+/// [`PointLight`](crate::PointLight) or a [`SpotLight`](crate::SpotLight) by
+/// tracing rays against the scene instead of sampling a shadow map.
+///
+/// The light must have `shadows_enabled` set. Its shadows are then resolved
+/// per pixel, without the resolution, bias and cascade artifacts of shadow
+/// maps, and no shadow map is rendered for it.
+///
+/// Ray tracing needs [`WgpuFeatures::RAY_QUERY`] and
+/// [`WgpuFeatures::RAY_TRACING_ACCELERATION_STRUCTURE`], which Bevy doesn't
+/// request by default: add them to the `features` of the
+/// [`WgpuSettings`](bevy_render::settings::WgpuSettings). Where they aren't
+/// available, lights with this component fall back to shadow maps.
+///
+/// The scene is made of every visible [`Mesh3d`] without a
+/// [`NotShadowCaster`] that uses a triangle list, except for the ones with a
+/// [`StandardMaterial`] that isn't [`AlphaMode::Opaque`]. The other casters,
+/// such as alpha-masked foliage, are still rendered into the shadow map of the
+/// light, which is combined with the traced shadows. Other materials are traced
+/// as opaque. Skinned and morphed meshes cast the shadow of their rest pose,
+/// and volumetric fog only uses the shadow maps, so only the casters that
+/// aren't traced shadow it.
+#[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+#[reflect(Component, Default, Debug)]
+pub struct RayTracedShadows;
+
+/// Adds support for [`RayTracedShadows`].
+pub struct RayTracedShadowsPlugin;
+
+impl Plugin for RayTracedShadowsPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            RAY_TRACED_SHADOWS_SHADER_HANDLE,
+            "ray_traced_shadows.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.register_type::<RayTracedShadows>();
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        let render_device = render_app.world().resource::<RenderDevice>();
+        if !ray_traced_shadows_are_usable(render_device) {
+            return;
+        }
+
+        // The acceleration structures are built straight from the mesh buffers.
+        let mut mesh_allocator_settings = render_app
+            .world_mut()
+            .resource_mut::<MeshAllocatorSettings>();
+        mesh_allocator_settings.extra_vertex_buffer_usages |= BufferUsages::BLAS_INPUT;
+        mesh_allocator_settings.extra_index_buffer_usages |= BufferUsages::BLAS_INPUT;
+
+        render_app
+            .init_resource::<RayTracingScene>()
+            .add_systems(ExtractSchedule, extract_ray_tracing_instances)
+            .add_systems(
+                Render,
+                (
+                    remove_stale_blas
+                        .in_set(RenderSet::PrepareAssets)
+                        .before(prepare_assets::<RenderMesh>),
+                    // Before the shadows are queued, which leaves the traced
+                    // casters out.
+                    prepare_ray_tracing_scene
+                        .in_set(RenderSet::PrepareAssets)
+                        .after(prepare_assets::<RenderMesh>),
+                ),
+            );
+    }
+}
+
+/// Returns true if [`RayTracedShadows`] are supported on the given device.
+pub fn ray_traced_shadows_are_usable(render_device: &RenderDevice) -> bool {
+    render_device
+        .features()
+        .contains(WgpuFeatures::RAY_QUERY | WgpuFeatures::RAY_TRACING_ACCELERATION_STRUCTURE)
+}
+
+/// The acceleration structures that shadow rays are traced against.
+///
+/// There's a bottom-level acceleration structure (BLAS) for each mesh, built
+/// once, and a top-level acceleration structure (TLAS) with an instance for
+/// each shadow-casting entity, rebuilt every frame.
+#[derive(Resource)]
+pub struct RayTracingScene {
+    blas: HashMap<AssetId<Mesh>, Blas>,
+    /// The meshes that can't be ray traced, until they change.
+    unsupported_meshes: HashSet<AssetId<Mesh>>,
+    tlas: TlasPackage,
+    tlas_capacity: u32,
+    instances: Vec<RayTracingInstance>,
+    instance_count: u32,
+    /// The entities that are in the TLAS this frame.
+    traced_entities: HashSet<MainEntity>,
+}
+
+struct RayTracingInstance {
+    entity: MainEntity,
+    mesh_id: AssetId<Mesh>,
+    transform: GlobalTransform,
+}
+
+/// The geometry of a mesh whose BLAS hasn't been built yet.
+struct PendingBlas<'a> {
+    mesh_id: AssetId<Mesh>,
+    size: BlasTriangleGeometrySizeDescriptor,
+    vertex_slice: MeshBufferSlice<'a>,
+    vertex_stride: BufferAddress,
+    index_slice: Option<(MeshBufferSlice<'a>, BufferAddress)>,
+}
+
+impl FromWorld for RayTracingScene {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        Self {
+            blas: HashMap::default(),
+            unsupported_meshes: HashSet::default(),
+            tlas: create_tlas(render_device, 1),
+            tlas_capacity: 1,
+            instances: Vec::new(),
+            instance_count: 0,
+            traced_entities: HashSet::default(),
+        }
+    }
+}
+
+impl RayTracingScene {
+    /// Returns the top-level acceleration structure of the scene.
+    pub fn tlas(&self) -> &TlasPackage {
+        &self.tlas
+    }
+
+    /// Returns true if the shadow rays are traced against the given entity
+    /// this frame.
+    pub fn is_traced(&self, entity: MainEntity) -> bool {
+        self.traced_entities.contains(&entity)
+    }
+}
+
+fn create_tlas(render_device: &RenderDevice, max_instances: u32) -> TlasPackage {
+    let tlas = render_device
+        .wgpu_device()
+        .create_tlas(&CreateTlasDescriptor {
+            label: Some("ray_tracing_tlas"),
+            max_instances,
+            flags: AccelerationStructureFlags::PREFER_FAST_TRACE,
+            update_mode: AccelerationStructureUpdateMode::Build,
+        });
+    TlasPackage::new(tlas, max_instances)
+}
+
+fn extract_ray_tracing_instances(
+    mut scene: ResMut<RayTracingScene>,
+    meshes: Extract<
+        Query<
+            (
+                Entity,
+                &Mesh3d,
+                &GlobalTransform,
+                &InheritedVisibility,
+                Option<&MeshMaterial3d<StandardMaterial>>,
+            ),
+            Without<NotShadowCaster>,
+        >,
+    >,
+    materials: Extract<Res<Assets<StandardMaterial>>>,
+) {
+    scene.instances.clear();
+    scene.instances.extend(
+        meshes
+            .iter()
+            .filter(|(_, _, _, visibility, material)| {
+                visibility.get()
+                    && material.is_none_or(|material| {
+                        materials
+                            .get(material)
+                            .is_none_or(|material| alpha_mode_is_traced(material.alpha_mode))
+                    })
+            })
+            .map(|(entity, mesh, transform, _, _)| RayTracingInstance {
+                entity: entity.into(),
+                mesh_id: mesh.id(),
+                transform: *transform,
+            }),
+    );
+}
+
+/// Returns true if the casters with a material of the given alpha mode are
+/// ray traced, as the rays don't run the material shaders to discard
+/// fragments.
+fn alpha_mode_is_traced(alpha_mode: AlphaMode) -> bool {
+    alpha_mode == AlphaMode::Opaque
+}
+
+/// Drops the BLAS of the meshes that changed or were removed, so that the
+/// changed ones are rebuilt from their new buffers.
+fn remove_stale_blas(
+    mut scene: ResMut<RayTracingScene>,
+    extracted_meshes: Res<ExtractedAssets<RenderMesh>>,
+) {
+    let scene = &mut *scene;
+    for mesh_id in extracted_meshes
+        .extracted
+        .iter()
+        .map(|(mesh_id, _)| mesh_id)
+        .chain(&extracted_meshes.removed)
+    {
+        scene.blas.remove(mesh_id);
+        scene.unsupported_meshes.remove(mesh_id);
+    }
+}
+
+/// Returns the vertex stride of the meshes with the given topology and vertex
+/// layout, if they can be ray traced.
+///
+/// Only triangle lists whose vertices start with a 3D position are supported.
+fn traceable_vertex_stride(
+    primitive_topology: PrimitiveTopology,
+    layout: &MeshVertexBufferLayoutRef,
+) -> Option<BufferAddress> {
+    if primitive_topology != PrimitiveTopology::TriangleList {
+        return None;
+    }
+
+    let position = layout.0.layout().attributes.first()?;
+    if layout.0.attribute_ids().first()? != &Mesh::ATTRIBUTE_POSITION.id
+        || position.offset != 0
+        || position.format != VertexFormat::Float32x3
+    {
+        return None;
+    }
+    Some(layout.0.layout().array_stride)
+}
+
+/// Returns the geometry to build the BLAS of a mesh from, if its buffers are
+/// allocated.
+fn pending_blas<'a>(
+    mesh_id: AssetId<Mesh>,
+    render_mesh: &RenderMesh,
+    vertex_stride: BufferAddress,
+    mesh_allocator: &'a MeshAllocator,
+) -> Option<PendingBlas<'a>> {
+    let vertex_slice = mesh_allocator.mesh_vertex_slice(&mesh_id)?;
+    let (index_format, index_count, index_slice) = match render_mesh.buffer_info {
+        RenderMeshBufferInfo::Indexed {
+            count,
+            index_format,
+        } => {
+            let index_slice = mesh_allocator.mesh_index_slice(&mesh_id)?;
+            let index_size = match index_format {
+                IndexFormat::Uint16 => 2,
+                IndexFormat::Uint32 => 4,
+            };
+            let offset = index_slice.range.start as BufferAddress * index_size;
+            (Some(index_format), Some(count), Some((index_slice, offset)))
+        }
+        RenderMeshBufferInfo::NonIndexed => (None, None, None),
+    };
+
+    Some(PendingBlas {
+        mesh_id,
+        size: BlasTriangleGeometrySizeDescriptor {
+            vertex_format: VertexFormat::Float32x3,
+            vertex_count: render_mesh.vertex_count,
+            index_format,
+            index_count,
+            flags: AccelerationStructureGeometryFlags::OPAQUE,
+        },
+        vertex_slice,
+        vertex_stride,
+        index_slice,
+    })
+}
+
+/// Builds the BLAS of the new meshes and rebuilds the TLAS.
+fn prepare_ray_tracing_scene(
+    mut scene: ResMut<RayTracingScene>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    render_meshes: Res<RenderAssets<RenderMesh>>,
+    mesh_allocator: Res<MeshAllocator>,
+) {
+    let scene = &mut *scene;
+
+    let mut seen_meshes = HashSet::new();
+    let mut pending = Vec::new();
+    for instance in &scene.instances {
+        let mesh_id = instance.mesh_id;
+        if scene.blas.contains_key(&mesh_id)
+            || scene.unsupported_meshes.contains(&mesh_id)
+            || !seen_meshes.insert(mesh_id)
+        {
+            continue;
+        }
+        let Some(render_mesh) = render_meshes.get(mesh_id) else {
+            continue;
+        };
+        // The mesh is only checked again once it changes.
+        let Some(vertex_stride) =
+            traceable_vertex_stride(render_mesh.primitive_topology(), &render_mesh.layout)
+        else {
+            warn!(
+                "The mesh {mesh_id} can't be ray traced, as it isn't a triangle list starting \
+                with `Float32x3` positions, so it only casts shadows into shadow maps"
+            );
+            scene.unsupported_meshes.insert(mesh_id);
+            continue;
+        };
+        pending.extend(pending_blas(
+            mesh_id,
+            render_mesh,
+            vertex_stride,
+            &mesh_allocator,
+        ));
+    }
+
+    for pending in &pending {
+        let blas = render_device.wgpu_device().create_blas(
+            &CreateBlasDescriptor {
+                label: Some("ray_tracing_blas"),
+                flags: AccelerationStructureFlags::PREFER_FAST_TRACE,
+                update_mode: AccelerationStructureUpdateMode::Build,
+            },
+            BlasGeometrySizeDescriptors::Triangles {
+                descriptors: vec![pending.size.clone()],
+            },
+        );
+        scene.blas.insert(pending.mesh_id, blas);
+    }
+
+    // Grow the TLAS in powers of two, so that it's rarely recreated.
+    let instance_count = scene.instances.len() as u32;
+    if instance_count > scene.tlas_capacity {
+        scene.tlas_capacity = instance_count.next_power_of_two();
+        scene.tlas = create_tlas(&render_device, scene.tlas_capacity);
+        scene.instance_count = 0;
+    }
+
+    for (index, instance) in scene.instances.iter().enumerate() {
+        // Rows of the 3x4 matrix from local to world space.
+        let rows = Mat4::from(instance.transform.affine())
+            .transpose()
+            .to_cols_array();
+        scene.tlas[index] = scene.blas.get(&instance.mesh_id).map(|blas| {
+            TlasInstance::new(blas, rows[..12].try_into().unwrap(), index as u32, 0xff)
+        });
+    }
+    for index in instance_count..scene.instance_count {
+        scene.tlas[index as usize] = None;
+    }
+    scene.instance_count = instance_count;
+
+    scene.traced_entities.clear();
+    scene.traced_entities.extend(
+        scene
+            .instances
+            .iter()
+            .filter(|instance| scene.blas.contains_key(&instance.mesh_id))
+            .map(|instance| instance.entity),
+    );
+
+    let blas_entries: Vec<_> = pending
+        .iter()
+        .map(|pending| BlasBuildEntry {
+            blas: &scene.blas[&pending.mesh_id],
+            geometry: BlasGeometries::TriangleGeometries(vec![BlasTriangleGeometry {
+                size: &pending.size,
+                vertex_buffer: pending.vertex_slice.buffer,
+                first_vertex: pending.vertex_slice.range.start,
+                vertex_stride: pending.vertex_stride,
+                index_buffer: pending
+                    .index_slice
+                    .as_ref()
+                    .map(|(slice, _)| &**slice.buffer),
+                index_buffer_offset: pending.index_slice.as_ref().map(|(_, offset)| *offset),
+                transform_buffer: None,
+                transform_buffer_offset: None,
+            }]),
+        })
+        .collect();
+
+    let mut command_encoder = render_device.create_command_encoder(&CommandEncoderDescriptor {
+        label: Some("ray_tracing_scene_build"),
+    });
+    command_encoder.build_acceleration_structures(blas_entries.iter(), iter::once(&scene.tlas));
+    render_queue.submit([command_encoder.finish()]);
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_math::primitives::Cuboid;
+    use bevy_render::{
+        mesh::{MeshVertexBufferLayouts, VertexAttributeValues},
+        render_asset::RenderAssetUsages,
+    };
+
+    use super::*;
+
+    #[test]
+    fn traceable_meshes() {
+        let mut layouts = MeshVertexBufferLayouts::default();
+
+        let cuboid = Mesh::from(Cuboid::default());
+        let layout = cuboid.get_mesh_vertex_buffer_layout(&mut layouts);
+        assert_eq!(
+            traceable_vertex_stride(cuboid.primitive_topology(), &layout),
+            Some(layout.0.layout().array_stride)
+        );
+
+        let lines = Mesh::new(PrimitiveTopology::LineList, RenderAssetUsages::default())
+            .with_inserted_attribute(
+                Mesh::ATTRIBUTE_POSITION,
+                vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0]],
+            );
+        let layout = lines.get_mesh_vertex_buffer_layout(&mut layouts);
+        assert_eq!(
+            traceable_vertex_stride(lines.primitive_topology(), &layout),
+            None
+        );
+
+        // 2D positions can't be traced.
+        let flat = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(
+            Mesh::ATTRIBUTE_POSITION,
+            VertexAttributeValues::Float32x2(vec![[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]]),
+        );
+        let layout = flat.get_mesh_vertex_buffer_layout(&mut layouts);
+        assert_eq!(
+            traceable_vertex_stride(flat.primitive_topology(), &layout),
+            None
+        );
+    }
+
+    #[test]
+    fn traced_alpha_modes() {
+        assert!(alpha_mode_is_traced(AlphaMode::Opaque));
+        assert!(!alpha_mode_is_traced(AlphaMode::Mask(0.5)));
+        assert!(!alpha_mode_is_traced(AlphaMode::AlphaToCoverage));
+        assert!(!alpha_mode_is_traced(AlphaMode::Blend));
+    }
+}
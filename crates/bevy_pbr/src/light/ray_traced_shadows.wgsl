@@ -0,0 +1,77 @@
+// Traces shadow rays against the `RayTracingScene`, for the lights with
+// `RayTracedShadows`.
+//
+// The rays start slightly off the surface, on the side that faces the light,
+// and stop at the first hit, as any hit occludes the light.
+
+#define_import_path bevy_pbr::ray_traced_shadows
+
+#import bevy_pbr::mesh_view_bindings as view_bindings
+
+// See `RAY_FLAG_TERMINATE_ON_FIRST_HIT` and `RAY_QUERY_INTERSECTION_NONE` in
+// the WGSL ray query extension.
+const SHADOW_RAY_FLAGS: u32 = 4u;
+const SHADOW_RAY_INTERSECTION_NONE: u32 = 0u;
+
+// How far the rays start from the surface, in world units.
+const SHADOW_RAY_OFFSET: f32 = 0.01;
+
+// Returns 1.0 if nothing lies between the surface and the light along
+// `direction`, within `max_distance`, and 0.0 otherwise.
+fn trace_shadow_ray(
+    surface_position: vec3<f32>,
+    surface_normal: vec3<f32>,
+    direction: vec3<f32>,
+    max_distance: f32,
+) -> f32 {
+    let normal = faceForward(surface_normal, -direction, surface_normal);
+    let origin = surface_position + normal * SHADOW_RAY_OFFSET;
+
+    var query: ray_query;
+    rayQueryInitialize(
+        &query,
+        view_bindings::ray_tracing_scene,
+        RayDesc(SHADOW_RAY_FLAGS, 0xffu, 0.0, max_distance, origin, direction),
+    );
+    rayQueryProceed(&query);
+
+    let intersection = rayQueryGetCommittedIntersection(&query);
+    return select(0.0, 1.0, intersection.kind == SHADOW_RAY_INTERSECTION_NONE);
+}
+
+// The shadow of a point or a spot light, traced towards the surface of the
+// light sphere.
+fn trace_point_shadow(
+    light_id: u32,
+    surface_position: vec3<f32>,
+    surface_normal: vec3<f32>,
+) -> f32 {
+    let light = &view_bindings::clusterable_objects.data[light_id];
+    let surface_to_light = (*light).position_radius.xyz - surface_position;
+    let distance_to_light = length(surface_to_light);
+    if (distance_to_light <= 0.0) {
+        return 1.0;
+    }
+
+    let max_distance = max(distance_to_light - (*light).position_radius.w, 0.0);
+    return trace_shadow_ray(
+        surface_position,
+        surface_normal,
+        surface_to_light / distance_to_light,
+        max_distance,
+    );
+}
+
+fn trace_directional_shadow(
+    light_id: u32,
+    surface_position: vec3<f32>,
+    surface_normal: vec3<f32>,
+) -> f32 {
+    let light = &view_bindings::lights.directional_lights[light_id];
+    return trace_shadow_ray(
+        surface_position,
+        surface_normal,
+        normalize((*light).direction_to_light),
+        1.0e20,
+    );
+}
@@ -0,0 +1,642 @@
+use bevy_app::{App, Plugin, PostUpdate};
+use bevy_asset::{AssetEvent, AssetId};
+use bevy_derive::{Deref, DerefMut};
+use bevy_ecs::{
+    entity::{EntityHashMap, EntityHashSet},
+    prelude::*,
+};
+use bevy_math::{Affine3A, UVec4, Vec3A};
+use bevy_reflect::prelude::*;
+use bevy_render::{
+    batching::gpu_preprocessing::{GpuPreprocessingMode, GpuPreprocessingSupport},
+    extract_component::{ExtractComponent, ExtractComponentPlugin},
+    mesh::{Mesh, Mesh3d},
+    primitives::{Aabb, Frustum, Sphere},
+    render_phase::ViewBinnedRenderPhases,
+    render_resource::{
+        CommandEncoder, Extent3d, ImageCopyTexture, Origin3d, Texture, TextureAspect,
+        TextureDescriptor, TextureDimension, TextureUsages, TextureViewDescriptor,
+        TextureViewDimension,
+    },
+    renderer::RenderDevice,
+    sync_world::MainEntity,
+    texture::DepthAttachment,
+    view::{
+        ExtractedView, InheritedVisibility, NoIndirectDrawing, RetainedViewEntity,
+        VisibilitySystems,
+    },
+    Extract, ExtractSchedule, Render, RenderApp, RenderSet,
+};
+use bevy_transform::{components::GlobalTransform, TransformSystem};
+use bevy_utils::{HashMap, HashSet};
+
+use bevy_core_pipeline::core_3d::CORE_3D_DEPTH_FORMAT;
+
+use crate::{
+    prepare_lights, ExtractedPointLight, LightEntity, PointLight, Shadow, ShadowView, SpotLight,
+    ViewLightEntities, ViewShadowBindings,
+};
+
+/// Marks a mesh that doesn't move, so that lights with a [`CachedShadowMap`]
+/// can render its shadow once and reuse it over the following frames.
+///
+/// The cached shadows are rendered again whenever a static caster in range of
+/// the light is added, removed, moved, shown, hidden, or gets a different or
+/// modified [`Mesh`]. Other changes, like a new material, aren't detected:
+/// bump the [`CachedShadowMap::generation`] of the affected lights by hand.
+#[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+#[reflect(Component, Default, Debug)]
+pub struct StaticShadowCaster;
+
+/// Caches the shadow map of a [`PointLight`] or a [`SpotLight`].
+///
+/// The shadows of [`StaticShadowCaster`]s are rendered into a cache when they
+/// change, and copied into the shadow map each frame, so that only the other
+/// shadow casters are rendered every frame.
+///
+/// The cache takes as much memory as the shadow map of the light. Directional
+/// lights can't be cached, as their cascades follow the camera.
+#[derive(Component, Clone, Copy, Debug, Default, Reflect, ExtractComponent)]
+#[reflect(Component, Default, Debug)]
+pub struct CachedShadowMap {
+    /// Incremented whenever the cached shadows need to be rendered again.
+    ///
+    /// This happens automatically when the light or the static casters in its
+    /// range change. Increment it to invalidate the cache for other reasons.
+    pub generation: u32,
+}
+
+/// Adds support for [`CachedShadowMap`]s.
+pub struct ShadowCachePlugin;
+
+impl Plugin for ShadowCachePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<StaticShadowCaster>()
+            .register_type::<CachedShadowMap>()
+            .add_plugins(ExtractComponentPlugin::<CachedShadowMap>::default())
+            .add_systems(
+                PostUpdate,
+                invalidate_cached_shadow_maps
+                    .after(TransformSystem::TransformPropagate)
+                    .after(VisibilitySystems::CalculateBounds)
+                    .after(VisibilitySystems::VisibilityPropagate),
+            );
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .init_resource::<ShadowCache>()
+            .init_resource::<RenderStaticShadowCasters>()
+            .add_systems(ExtractSchedule, extract_static_shadow_casters)
+            .add_systems(
+                Render,
+                prepare_cached_shadow_views
+                    .in_set(RenderSet::ManageViews)
+                    .after(prepare_lights),
+            );
+    }
+}
+
+/// The world space bounds of a [`StaticShadowCaster`], or `None` if it has no
+/// [`Aabb`] and could be anywhere.
+type CasterBounds = Option<(Aabb, Affine3A)>;
+
+/// The [`StaticShadowCaster`]s seen by [`invalidate_cached_shadow_maps`], as of
+/// the last time they changed.
+#[derive(Default)]
+struct TrackedShadowCasters {
+    casters: EntityHashMap<(Option<AssetId<Mesh>>, CasterBounds)>,
+    /// The casters using each mesh, to find them when the mesh is modified.
+    casters_by_mesh: HashMap<AssetId<Mesh>, EntityHashSet>,
+}
+
+impl TrackedShadowCasters {
+    /// Records the mesh and bounds of a caster, returning its previous bounds.
+    fn update(
+        &mut self,
+        entity: Entity,
+        mesh: Option<AssetId<Mesh>>,
+        bounds: CasterBounds,
+    ) -> Option<CasterBounds> {
+        let previous_bounds = self.remove(entity);
+        self.casters.insert(entity, (mesh, bounds));
+        if let Some(mesh) = mesh {
+            self.casters_by_mesh.entry(mesh).or_default().insert(entity);
+        }
+        previous_bounds
+    }
+
+    /// Forgets a caster, returning its bounds.
+    fn remove(&mut self, entity: Entity) -> Option<CasterBounds> {
+        let (mesh, bounds) = self.casters.remove(&entity)?;
+        if let Some(mesh) = mesh {
+            if let Some(casters) = self.casters_by_mesh.get_mut(&mesh) {
+                casters.remove(&entity);
+                if casters.is_empty() {
+                    self.casters_by_mesh.remove(&mesh);
+                }
+            }
+        }
+        Some(bounds)
+    }
+}
+
+/// Bumps the [`CachedShadowMap::generation`] of the lights whose light or
+/// static casters changed.
+///
+/// Only the casters that changed since the last run are visited, along with
+/// the casters using a modified mesh.
+fn invalidate_cached_shadow_maps(
+    mut lights: Query<(
+        Ref<GlobalTransform>,
+        AnyOf<(Ref<PointLight>, Ref<SpotLight>)>,
+        &mut CachedShadowMap,
+    )>,
+    changed_casters: Query<
+        (Entity, Option<&Mesh3d>, &GlobalTransform, Option<&Aabb>),
+        (
+            With<StaticShadowCaster>,
+            Or<(
+                Added<StaticShadowCaster>,
+                Changed<GlobalTransform>,
+                Changed<Mesh3d>,
+                Changed<Aabb>,
+                Changed<InheritedVisibility>,
+            )>,
+        ),
+    >,
+    mut removed_casters: RemovedComponents<StaticShadowCaster>,
+    mut mesh_events: EventReader<AssetEvent<Mesh>>,
+    mut tracked_casters: Local<TrackedShadowCasters>,
+) {
+    // The places where static shadows changed, or `None` for everywhere.
+    let mut changed_bounds = vec![];
+
+    for entity in removed_casters.read() {
+        if let Some(bounds) = tracked_casters.remove(entity) {
+            changed_bounds.push(bounds);
+        }
+    }
+
+    for (entity, mesh, transform, aabb) in &changed_casters {
+        let bounds = aabb.map(|aabb| (*aabb, transform.affine()));
+        if let Some(previous_bounds) =
+            tracked_casters.update(entity, mesh.map(|mesh| mesh.id()), bounds)
+        {
+            changed_bounds.push(previous_bounds);
+        }
+        changed_bounds.push(bounds);
+    }
+
+    // Modifying a mesh doesn't change the components of its casters.
+    for event in mesh_events.read() {
+        let AssetEvent::Modified { id } = event else {
+            continue;
+        };
+        let Some(casters) = tracked_casters.casters_by_mesh.get(id) else {
+            continue;
+        };
+        changed_bounds.extend(
+            casters
+                .iter()
+                .filter_map(|entity| tracked_casters.casters.get(entity))
+                .map(|(_, bounds)| *bounds),
+        );
+    }
+
+    for (transform, (point_light, spot_light), mut cached_shadow_map) in &mut lights {
+        let (range, light_changed) = match (point_light, spot_light) {
+            (Some(point_light), _) => (point_light.range, point_light.is_changed()),
+            (None, Some(spot_light)) => (spot_light.range, spot_light.is_changed()),
+            (None, None) => continue,
+        };
+
+        let light_sphere = Sphere {
+            center: Vec3A::from(transform.translation()),
+            radius: range,
+        };
+        let casters_changed = changed_bounds.iter().any(|bounds| match bounds {
+            Some((aabb, world_from_local)) => light_sphere.intersects_obb(aabb, world_from_local),
+            None => true,
+        });
+
+        if light_changed || transform.is_changed() || casters_changed {
+            cached_shadow_map.generation = cached_shadow_map.generation.wrapping_add(1);
+        }
+    }
+}
+
+/// The [`StaticShadowCaster`]s, extracted to the render world.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct RenderStaticShadowCasters(pub HashSet<MainEntity>);
+
+fn extract_static_shadow_casters(
+    mut render_static_shadow_casters: ResMut<RenderStaticShadowCasters>,
+    added_static_shadow_casters: Extract<Query<Entity, Added<StaticShadowCaster>>>,
+    mut removed_static_shadow_casters: Extract<RemovedComponents<StaticShadowCaster>>,
+) {
+    for entity in removed_static_shadow_casters.read() {
+        render_static_shadow_casters.remove(&MainEntity::from(entity));
+    }
+    render_static_shadow_casters.extend(added_static_shadow_casters.iter().map(MainEntity::from));
+}
+
+/// The cached shadow maps of the lights with a [`CachedShadowMap`], keyed by
+/// the render world light entity.
+#[derive(Resource, Default)]
+pub struct ShadowCache {
+    textures: EntityHashMap<CachedShadowMapTexture>,
+    /// The [`StaticShadowView`]s spawned this frame, to despawn next frame.
+    static_views: Vec<Entity>,
+    /// The views that got a [`CachedShadowView`] this frame.
+    cached_views: EntityHashSet,
+}
+
+struct CachedShadowMapTexture {
+    texture: Texture,
+    generation: u32,
+    /// Whether the static casters need to be rendered again.
+    dirty: bool,
+    /// Whether the static casters are rendered this frame.
+    rendering: bool,
+    /// Whether the light still has a shadow map this frame.
+    live: bool,
+}
+
+impl ShadowCache {
+    /// Renders the static casters of the light again next frame.
+    ///
+    /// Used when some of them couldn't be rendered this frame, because their
+    /// mesh, material or pipeline isn't ready yet.
+    pub fn invalidate(&mut self, light_entity: Entity) {
+        if let Some(cached_texture) = self.textures.get_mut(&light_entity) {
+            cached_texture.dirty = true;
+        }
+    }
+}
+
+/// Renders the [`StaticShadowCaster`]s of a light into its cached shadow map.
+///
+/// This is spawned next to the [`ShadowView`] of the light, for the frames in
+/// which the cache needs to be rendered again.
+#[derive(Component)]
+pub struct StaticShadowView {
+    /// The render world light entity.
+    pub light_entity: Entity,
+}
+
+/// Added to the [`ShadowView`] of a light with a [`CachedShadowMap`].
+///
+/// The view only renders the casters that aren't [`StaticShadowCaster`]s,
+/// over a copy of the cached shadow map.
+#[derive(Component)]
+pub struct CachedShadowView {
+    pub cache_texture: Texture,
+    pub cache_layer: u32,
+    pub shadow_map_texture: Texture,
+    /// Where the shadow map of the view starts in its texture.
+    pub shadow_map_origin: Origin3d,
+}
+
+impl CachedShadowView {
+    /// Copies the cached shadow map into the shadow map of the view.
+    pub fn copy_to_shadow_map(&self, command_encoder: &mut CommandEncoder) {
+        command_encoder.copy_texture_to_texture(
+            ImageCopyTexture {
+                texture: &self.cache_texture,
+                mip_level: 0,
+                origin: Origin3d {
+                    x: 0,
+                    y: 0,
+                    z: self.cache_layer,
+                },
+                aspect: TextureAspect::All,
+            },
+            ImageCopyTexture {
+                texture: &self.shadow_map_texture,
+                mip_level: 0,
+                origin: self.shadow_map_origin,
+                aspect: TextureAspect::All,
+            },
+            Extent3d {
+                width: self.cache_texture.width(),
+                height: self.cache_texture.height(),
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+}
+
+/// The bit set in the subview index of [`StaticShadowView`]s, to tell them
+/// apart from the shadow views of the same light.
+const STATIC_SHADOW_SUBVIEW: u32 = 1 << 31;
+
+/// Adds a [`CachedShadowView`] to the shadow views of the lights with a
+/// [`CachedShadowMap`], and a [`StaticShadowView`] before them when their
+/// cache needs to be rendered again.
+pub fn prepare_cached_shadow_views(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    mut shadow_cache: ResMut<ShadowCache>,
+    mut shadow_render_phases: ResMut<ViewBinnedRenderPhases<Shadow>>,
+    gpu_preprocessing_support: Res<GpuPreprocessingSupport>,
+    mut views: Query<(&ViewShadowBindings, &mut ViewLightEntities)>,
+    view_lights: Query<(
+        &ShadowView,
+        &ExtractedView,
+        &Frustum,
+        &LightEntity,
+        Has<NoIndirectDrawing>,
+    )>,
+    lights: Query<&CachedShadowMap, With<ExtractedPointLight>>,
+) {
+    let shadow_cache = &mut *shadow_cache;
+
+    for entity in shadow_cache.static_views.drain(..) {
+        commands.entity(entity).despawn();
+    }
+    let previous_cached_views = core::mem::take(&mut shadow_cache.cached_views);
+    for cached_texture in shadow_cache.textures.values_mut() {
+        cached_texture.live = false;
+    }
+
+    for (shadow_bindings, mut view_light_entities) in &mut views {
+        let mut lights_with_static_views = Vec::with_capacity(view_light_entities.lights.len());
+
+        for view_light_entity in view_light_entities.lights.iter().copied() {
+            lights_with_static_views.push(view_light_entity);
+
+            let Ok((shadow_view, extracted_view, frustum, light_entity, no_indirect_drawing)) =
+                view_lights.get(view_light_entity)
+            else {
+                continue;
+            };
+
+            let (light_entity, face_index, layer_count, shadow_map_texture) = match *light_entity {
+                LightEntity::Point {
+                    light_entity,
+                    face_index,
+                } => (
+                    light_entity,
+                    Some(face_index),
+                    6,
+                    &shadow_bindings.point_light_depth_texture,
+                ),
+                LightEntity::Spot { light_entity } => (
+                    light_entity,
+                    None,
+                    1,
+                    &shadow_bindings.directional_light_depth_texture,
+                ),
+                LightEntity::Directional { .. } => continue,
+            };
+
+            // Only the views that render the shadow map have a phase, the
+            // other cameras reuse it.
+            let Ok(cached_shadow_map) = lights.get(light_entity) else {
+                continue;
+            };
+            if !shadow_render_phases.contains_key(&extracted_view.retained_view_entity) {
+                continue;
+            }
+
+            // Point light shadow maps are tiles of the atlas.
+            let size = extracted_view.viewport.z;
+            let cached_texture = shadow_cache
+                .textures
+                .entry(light_entity)
+                .or_insert_with(|| CachedShadowMapTexture {
+                    texture: create_cache_texture(&render_device, size, layer_count),
+                    generation: cached_shadow_map.generation,
+                    dirty: true,
+                    rendering: false,
+                    live: false,
+                });
+
+            if !cached_texture.live {
+                cached_texture.live = true;
+                if cached_texture.texture.width() != size {
+                    cached_texture.texture =
+                        create_cache_texture(&render_device, size, layer_count);
+                    cached_texture.dirty = true;
+                }
+                if cached_texture.generation != cached_shadow_map.generation {
+                    cached_texture.generation = cached_shadow_map.generation;
+                    cached_texture.dirty = true;
+                }
+                cached_texture.rendering = cached_texture.dirty;
+                cached_texture.dirty = false;
+            }
+
+            let cache_layer = face_index.unwrap_or_default() as u32;
+
+            commands.entity(view_light_entity).insert(CachedShadowView {
+                cache_texture: cached_texture.texture.clone(),
+                cache_layer,
+                shadow_map_texture: shadow_map_texture.clone(),
+                shadow_map_origin: Origin3d {
+                    x: extracted_view.viewport.x,
+                    y: extracted_view.viewport.y,
+                    z: shadow_view.array_layer,
+                },
+            });
+            shadow_cache.cached_views.insert(view_light_entity);
+
+            if !cached_texture.rendering {
+                continue;
+            }
+
+            let depth_texture_view = cached_texture.texture.create_view(&TextureViewDescriptor {
+                label: Some("cached_shadow_map_texture_view"),
+                format: None,
+                dimension: Some(TextureViewDimension::D2),
+                aspect: TextureAspect::All,
+                base_mip_level: 0,
+                mip_level_count: None,
+                base_array_layer: cache_layer,
+                array_layer_count: Some(1u32),
+            });
+
+            let retained_view_entity = RetainedViewEntity {
+                subview_index: extracted_view.retained_view_entity.subview_index
+                    | STATIC_SHADOW_SUBVIEW,
+                ..extracted_view.retained_view_entity
+            };
+
+            let static_view_entity = commands
+                .spawn((
+                    ShadowView {
+                        depth_attachment: DepthAttachment::new(depth_texture_view, Some(0.0)),
+                        pass_name: format!("{} static casters", shadow_view.pass_name),
+                        array_layer: cache_layer,
+                    },
+                    ExtractedView {
+                        retained_view_entity,
+                        clip_from_view: extracted_view.clip_from_view,
+                        world_from_view: extracted_view.world_from_view,
+                        clip_from_world: extracted_view.clip_from_world,
+                        hdr: extracted_view.hdr,
+                        viewport: UVec4::new(0, 0, size, size),
+                        color_grading: extracted_view.color_grading.clone(),
+                    },
+                    *frustum,
+                    match face_index {
+                        Some(face_index) => LightEntity::Point {
+                            light_entity,
+                            face_index,
+                        },
+                        None => LightEntity::Spot { light_entity },
+                    },
+                    StaticShadowView { light_entity },
+                ))
+                .id();
+            if no_indirect_drawing {
+                commands
+                    .entity(static_view_entity)
+                    .insert(NoIndirectDrawing);
+            }
+
+            let gpu_preprocessing_mode = gpu_preprocessing_support.min(if !no_indirect_drawing {
+                GpuPreprocessingMode::Culling
+            } else {
+                GpuPreprocessingMode::PreprocessingOnly
+            });
+            shadow_render_phases.insert_or_clear(retained_view_entity, gpu_preprocessing_mode);
+
+            // The static casters have to be rendered into the cache before it's
+            // copied into the shadow map.
+            lights_with_static_views.insert(lights_with_static_views.len() - 1, static_view_entity);
+            shadow_cache.static_views.push(static_view_entity);
+        }
+
+        view_light_entities.lights = lights_with_static_views;
+    }
+
+    for entity in previous_cached_views.difference(&shadow_cache.cached_views) {
+        if let Some(mut entity_commands) = commands.get_entity(*entity) {
+            entity_commands.remove::<CachedShadowView>();
+        }
+    }
+
+    shadow_cache
+        .textures
+        .retain(|_, cached_texture| cached_texture.live);
+}
+
+fn create_cache_texture(render_device: &RenderDevice, size: u32, layer_count: u32) -> Texture {
+    render_device.create_texture(&TextureDescriptor {
+        label: Some("cached_shadow_map_texture"),
+        size: Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: layer_count,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: CORE_3D_DEPTH_FORMAT,
+        usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+        view_formats: &[],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_asset::{AssetEvent, Handle};
+    use bevy_ecs::{event::Events, prelude::*};
+    use bevy_math::Vec3;
+    use bevy_render::{
+        mesh::{Mesh, Mesh3d},
+        primitives::Aabb,
+    };
+    use bevy_transform::components::GlobalTransform;
+
+    use super::{invalidate_cached_shadow_maps, CachedShadowMap, StaticShadowCaster};
+    use crate::PointLight;
+
+    const MESH: Handle<Mesh> = Handle::weak_from_u128(0x5ad0_cac4e);
+
+    /// Spawns a light of range 10 at the origin, and a static caster at the
+    /// given position.
+    fn setup(caster_position: Vec3) -> (World, Schedule, Entity, Entity) {
+        let mut world = World::new();
+        world.init_resource::<Events<AssetEvent<Mesh>>>();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(invalidate_cached_shadow_maps);
+
+        let light = world
+            .spawn((
+                PointLight {
+                    range: 10.0,
+                    ..Default::default()
+                },
+                GlobalTransform::IDENTITY,
+                CachedShadowMap::default(),
+            ))
+            .id();
+        let caster = world
+            .spawn((
+                StaticShadowCaster,
+                Mesh3d(MESH),
+                GlobalTransform::from_translation(caster_position),
+                Aabb::from_min_max(Vec3::splat(-0.5), Vec3::splat(0.5)),
+            ))
+            .id();
+
+        // The new light renders its cache, then nothing changes.
+        schedule.run(&mut world);
+        schedule.run(&mut world);
+        assert_eq!(generation(&world, light), 1);
+        (world, schedule, light, caster)
+    }
+
+    fn generation(world: &World, light: Entity) -> u32 {
+        world.get::<CachedShadowMap>(light).unwrap().generation
+    }
+
+    #[test]
+    fn moved_casters_invalidate_lights_in_range() {
+        let (mut world, mut schedule, light, caster) = setup(Vec3::new(0.0, 0.0, 5.0));
+
+        // Leaving the range removes the shadow of the caster.
+        *world.get_mut::<GlobalTransform>(caster).unwrap() =
+            GlobalTransform::from_xyz(0.0, 0.0, 50.0);
+        schedule.run(&mut world);
+        assert_eq!(generation(&world, light), 2);
+
+        // Moving out of range doesn't change the shadows.
+        *world.get_mut::<GlobalTransform>(caster).unwrap() =
+            GlobalTransform::from_xyz(0.0, 0.0, 60.0);
+        schedule.run(&mut world);
+        assert_eq!(generation(&world, light), 2);
+        world.despawn(caster);
+        schedule.run(&mut world);
+        assert_eq!(generation(&world, light), 2);
+    }
+
+    #[test]
+    fn removed_casters_invalidate_lights_in_range() {
+        let (mut world, mut schedule, light, caster) = setup(Vec3::new(0.0, 0.0, 5.0));
+
+        world.entity_mut(caster).remove::<StaticShadowCaster>();
+        schedule.run(&mut world);
+        assert_eq!(generation(&world, light), 2);
+    }
+
+    #[test]
+    fn modified_meshes_invalidate_their_casters() {
+        let (mut world, mut schedule, light, _) = setup(Vec3::new(0.0, 0.0, 5.0));
+
+        world.send_event(AssetEvent::<Mesh>::Modified {
+            id: Handle::<Mesh>::weak_from_u128(1).id(),
+        });
+        schedule.run(&mut world);
+        assert_eq!(generation(&world, light), 1);
+
+        world.send_event(AssetEvent::Modified { id: MESH.id() });
+        schedule.run(&mut world);
+        assert_eq!(generation(&world, light), 2);
+    }
+}
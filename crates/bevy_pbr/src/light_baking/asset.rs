@@ -0,0 +1,386 @@
+//! The `.baked_lighting` files that the light baker writes, and the
+//! [`BakedLighting`] assets they're loaded as.
+
+use bevy_asset::{
+    io::{Reader, Writer},
+    saver::{AssetSaver, SavedAsset},
+    transformer::{AssetTransformer, TransformedAsset},
+    Asset, AssetLoader, AsyncReadExt, AsyncWriteExt, Handle, LoadContext, RenderAssetUsages,
+};
+use bevy_image::Image;
+use bevy_math::{UVec2, UVec3, Vec3};
+use bevy_reflect::TypePath;
+use bevy_render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use thiserror::Error;
+
+use super::{bake, LightBakeScene, LightBakeSettings};
+
+/// Unique identifier for the `.baked_lighting` format.
+const BAKED_LIGHTING_ASSET_MAGIC: u64 = 0x5447_494c_4b41_4221;
+
+/// The current version of the `.baked_lighting` format.
+pub const BAKED_LIGHTING_ASSET_VERSION: u64 = 1;
+
+/// The longest entity name that is read from a baked lighting asset, in
+/// bytes, so that a corrupt file doesn't allocate a huge string.
+const MAX_NAME_LENGTH: u64 = 64 * 1024;
+
+/// The largest image that is read from a baked lighting asset, in bytes.
+const MAX_IMAGE_SIZE: usize = 1 << 30;
+
+/// The lighting baked by [`bake`], before it's written to disk.
+#[derive(Asset, TypePath, Clone, Debug, Default)]
+pub struct LightBakeOutput {
+    pub lightmaps: Vec<BakedLightmap>,
+    pub irradiance_volumes: Vec<BakedIrradianceVolume>,
+}
+
+/// The lightmap of a mesh with [`BakeLightmap`](super::BakeLightmap).
+#[derive(Clone, Debug)]
+pub struct BakedLightmap {
+    /// The [`Name`](bevy_ecs::name::Name) of the baked entity.
+    pub name: String,
+    pub resolution: UVec2,
+    /// The texels, row by row, from the top of the lightmap. They hold the
+    /// irradiance divided by π, which is what the lightmap shader expects.
+    pub texels: Vec<Vec3>,
+}
+
+/// The voxels of an entity with
+/// [`BakeIrradianceVolume`](super::BakeIrradianceVolume).
+#[derive(Clone, Debug)]
+pub struct BakedIrradianceVolume {
+    /// The [`Name`](bevy_ecs::name::Name) of the baked entity.
+    pub name: String,
+    pub resolution: UVec3,
+    /// The ambient cube of each voxel, in X, then Y, then Z order. The sides
+    /// are in the order -X, +X, -Y, +Y, -Z, +Z.
+    pub ambient_cubes: Vec<[Vec3; 6]>,
+}
+
+/// The baked lighting of a scene, loaded from a `.baked_lighting` file.
+///
+/// The images are labeled sub-assets, `Lightmap{index}` and
+/// `IrradianceVolume{index}`. Add [`ApplyBakedLighting`](super::ApplyBakedLighting)
+/// to an entity to apply them to the entities they were baked for.
+#[derive(Asset, TypePath, Clone, Debug, Default)]
+pub struct BakedLighting {
+    /// The lightmaps, with the names of the entities they belong to.
+    pub lightmaps: Vec<(String, Handle<Image>)>,
+    /// The voxel images of the irradiance volumes, in the layout that
+    /// [`IrradianceVolume::voxels`](crate::irradiance_volume::IrradianceVolume::voxels)
+    /// expects, with the names of the entities they belong to.
+    pub irradiance_volumes: Vec<(String, Handle<Image>)>,
+}
+
+/// An [`AssetTransformer`] that bakes the lighting of a [`LightBakeScene`].
+#[derive(Default)]
+pub struct LightBakeTransformer;
+
+impl AssetTransformer for LightBakeTransformer {
+    type AssetInput = LightBakeScene;
+    type AssetOutput = LightBakeOutput;
+    type Settings = LightBakeSettings;
+    type Error = core::convert::Infallible;
+
+    async fn transform<'a>(
+        &'a self,
+        asset: TransformedAsset<LightBakeScene>,
+        settings: &'a LightBakeSettings,
+    ) -> Result<TransformedAsset<LightBakeOutput>, core::convert::Infallible> {
+        let output = bake(asset.get(), settings);
+        Ok(asset.replace_asset(output))
+    }
+}
+
+/// An [`AssetSaver`] for `.baked_lighting` files.
+#[derive(Default)]
+pub struct BakedLightingSaver;
+
+impl AssetSaver for BakedLightingSaver {
+    type Asset = LightBakeOutput;
+    type Settings = ();
+    type OutputLoader = BakedLightingLoader;
+    type Error = BakedLightingSaveOrLoadError;
+
+    async fn save(
+        &self,
+        writer: &mut Writer,
+        asset: SavedAsset<'_, LightBakeOutput>,
+        _settings: &(),
+    ) -> Result<(), BakedLightingSaveOrLoadError> {
+        writer
+            .write_all(&BAKED_LIGHTING_ASSET_MAGIC.to_le_bytes())
+            .await?;
+        writer
+            .write_all(&BAKED_LIGHTING_ASSET_VERSION.to_le_bytes())
+            .await?;
+
+        writer
+            .write_all(&(asset.lightmaps.len() as u64).to_le_bytes())
+            .await?;
+        for lightmap in &asset.lightmaps {
+            write_string(writer, &lightmap.name).await?;
+            let size = lightmap.resolution.extend(1);
+            writer
+                .write_all(bytemuck::bytes_of(&size.to_array()))
+                .await?;
+            let texels: Vec<u32> = lightmap
+                .texels
+                .iter()
+                .copied()
+                .map(vec3_to_rgb9e5)
+                .collect();
+            writer.write_all(bytemuck::cast_slice(&texels)).await?;
+        }
+
+        writer
+            .write_all(&(asset.irradiance_volumes.len() as u64).to_le_bytes())
+            .await?;
+        for volume in &asset.irradiance_volumes {
+            write_string(writer, &volume.name).await?;
+            let size = irradiance_volume_image_size(volume.resolution);
+            writer
+                .write_all(bytemuck::bytes_of(&size.to_array()))
+                .await?;
+            let texels = irradiance_volume_texels(volume);
+            writer.write_all(bytemuck::cast_slice(&texels)).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// An [`AssetLoader`] for `.baked_lighting` files.
+#[derive(Default)]
+pub struct BakedLightingLoader;
+
+impl AssetLoader for BakedLightingLoader {
+    type Asset = BakedLighting;
+    type Settings = ();
+    type Error = BakedLightingSaveOrLoadError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<BakedLighting, BakedLightingSaveOrLoadError> {
+        let magic = async_read_u64(reader).await?;
+        if magic != BAKED_LIGHTING_ASSET_MAGIC {
+            return Err(BakedLightingSaveOrLoadError::WrongFileType);
+        }
+        let version = async_read_u64(reader).await?;
+        if version != BAKED_LIGHTING_ASSET_VERSION {
+            return Err(BakedLightingSaveOrLoadError::WrongVersion { found: version });
+        }
+
+        let mut baked_lighting = BakedLighting::default();
+
+        let lightmap_count = async_read_u64(reader).await?;
+        for index in 0..lightmap_count {
+            let name = read_string(reader).await?;
+            let image = read_image(reader, TextureDimension::D2).await?;
+            let handle = load_context.add_labeled_asset(format!("Lightmap{index}"), image);
+            baked_lighting.lightmaps.push((name, handle));
+        }
+
+        let irradiance_volume_count = async_read_u64(reader).await?;
+        for index in 0..irradiance_volume_count {
+            let name = read_string(reader).await?;
+            let image = read_image(reader, TextureDimension::D3).await?;
+            let handle = load_context.add_labeled_asset(format!("IrradianceVolume{index}"), image);
+            baked_lighting.irradiance_volumes.push((name, handle));
+        }
+
+        Ok(baked_lighting)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["baked_lighting"]
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum BakedLightingSaveOrLoadError {
+    #[error("file was not a baked lighting asset")]
+    WrongFileType,
+    #[error("expected asset version {BAKED_LIGHTING_ASSET_VERSION} but found version {found}")]
+    WrongVersion { found: u64 },
+    #[error("an entity name was not valid UTF-8")]
+    InvalidName(#[from] alloc::string::FromUtf8Error),
+    #[error("an entity name is {length} bytes long, more than the maximum of {MAX_NAME_LENGTH}")]
+    NameTooLong { length: u64 },
+    #[error("an image has an invalid size of {size:?}")]
+    InvalidImageSize { size: [u32; 3] },
+    #[error("failed to read or write asset data")]
+    Io(#[from] std::io::Error),
+}
+
+/// The size of the voxel image of an irradiance volume: the positive and
+/// negative sides are stacked along Y, and the three axes along Z.
+fn irradiance_volume_image_size(resolution: UVec3) -> UVec3 {
+    resolution * UVec3::new(1, 2, 3)
+}
+
+/// Packs the ambient cubes of an irradiance volume in the layout that
+/// `irradiance_volume.wgsl` samples them with.
+fn irradiance_volume_texels(volume: &BakedIrradianceVolume) -> Vec<u32> {
+    let resolution = volume.resolution;
+    let size = irradiance_volume_image_size(resolution);
+    let mut texels = vec![0; (size.x * size.y * size.z) as usize];
+    for (voxel_index, ambient_cube) in volume.ambient_cubes.iter().enumerate() {
+        let voxel_index = voxel_index as u32;
+        let x = voxel_index % resolution.x;
+        let y = voxel_index / resolution.x % resolution.y;
+        let z = voxel_index / (resolution.x * resolution.y);
+        for (side, &irradiance) in ambient_cube.iter().enumerate() {
+            // The negative sides are in the upper half of the image.
+            let t = y + if side % 2 == 0 { resolution.y } else { 0 };
+            let p = z + (side as u32 / 2) * resolution.z;
+            texels[((p * size.y + t) * size.x + x) as usize] = vec3_to_rgb9e5(irradiance);
+        }
+    }
+    texels
+}
+
+/// Encodes a color in [`TextureFormat::Rgb9e5Ufloat`], as `rgb9e5.wgsl` does.
+fn vec3_to_rgb9e5(rgb: Vec3) -> u32 {
+    const MANTISSA_BITS: i32 = 9;
+    const EXPONENT_BIAS: i32 = 15;
+    const MAX_VALUE: f32 = 65408.0;
+
+    let rgb = rgb.clamp(Vec3::ZERO, Vec3::splat(MAX_VALUE));
+    let max = rgb.max_element();
+    let floor_log2 = ((max.to_bits() >> 23) & 0xff) as i32 - 127;
+    let mut exponent = floor_log2.max(-EXPONENT_BIAS - 1) + 1 + EXPONENT_BIAS;
+    // 2 to the power of `exponent - EXPONENT_BIAS - MANTISSA_BITS`.
+    let exp2 = |power: i32| f32::from_bits(((power + 127) as u32) << 23);
+    let mut denominator = exp2(exponent - EXPONENT_BIAS - MANTISSA_BITS);
+    if (max / denominator + 0.5).floor() as i32 == 1 << MANTISSA_BITS {
+        denominator *= 2.0;
+        exponent += 1;
+    }
+
+    let [r, g, b] = (rgb / denominator + 0.5)
+        .floor()
+        .to_array()
+        .map(|component| component as u32);
+    ((exponent as u32) << 27) | (b << 18) | (g << 9) | r
+}
+
+async fn read_image(
+    reader: &mut dyn Reader,
+    dimension: TextureDimension,
+) -> Result<Image, BakedLightingSaveOrLoadError> {
+    let mut size = [0u32; 3];
+    reader
+        .read_exact(bytemuck::cast_slice_mut(&mut size))
+        .await?;
+    let byte_count = size
+        .iter()
+        .try_fold(4usize, |count, &extent| count.checked_mul(extent as usize))
+        .filter(|&count| count > 0 && count <= MAX_IMAGE_SIZE)
+        .ok_or(BakedLightingSaveOrLoadError::InvalidImageSize { size })?;
+    let mut data = vec![0u8; byte_count];
+    reader.read_exact(&mut data).await?;
+
+    Ok(Image::new(
+        Extent3d {
+            width: size[0],
+            height: size[1],
+            depth_or_array_layers: size[2],
+        },
+        dimension,
+        data,
+        TextureFormat::Rgb9e5Ufloat,
+        RenderAssetUsages::RENDER_WORLD,
+    ))
+}
+
+async fn write_string(writer: &mut Writer, string: &str) -> Result<(), std::io::Error> {
+    writer
+        .write_all(&(string.len() as u64).to_le_bytes())
+        .await?;
+    writer.write_all(string.as_bytes()).await
+}
+
+async fn read_string(reader: &mut dyn Reader) -> Result<String, BakedLightingSaveOrLoadError> {
+    let length = async_read_u64(reader).await?;
+    if length > MAX_NAME_LENGTH {
+        return Err(BakedLightingSaveOrLoadError::NameTooLong { length });
+    }
+    let mut bytes = vec![0u8; length as usize];
+    reader.read_exact(&mut bytes).await?;
+    Ok(String::from_utf8(bytes)?)
+}
+
+async fn async_read_u64(reader: &mut dyn Reader) -> Result<u64, std::io::Error> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes).await?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_image, read_string, vec3_to_rgb9e5, BakedLightingSaveOrLoadError};
+    use bevy_asset::io::SliceReader;
+    use bevy_math::Vec3;
+    use bevy_render::render_resource::TextureDimension;
+
+    /// Decodes a color from [`TextureFormat::Rgb9e5Ufloat`](super::TextureFormat::Rgb9e5Ufloat).
+    fn rgb9e5_to_vec3(packed: u32) -> Vec3 {
+        let scale = f32::from_bits(((packed >> 27) + 127 - 15 - 9) << 23);
+        Vec3::new(
+            (packed & 0x1ff) as f32,
+            ((packed >> 9) & 0x1ff) as f32,
+            ((packed >> 18) & 0x1ff) as f32,
+        ) * scale
+    }
+
+    #[test]
+    fn rgb9e5_round_trip() {
+        for color in [
+            Vec3::ZERO,
+            Vec3::ONE,
+            Vec3::new(0.25, 0.5, 0.75),
+            Vec3::new(100.0, 3.0, 0.01),
+            Vec3::splat(65408.0),
+        ] {
+            let decoded = rgb9e5_to_vec3(vec3_to_rgb9e5(color));
+            let max = color.max_element();
+            assert!(
+                (decoded - color).abs().max_element() <= max / 256.0,
+                "{color} was decoded as {decoded}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_long_names() {
+        let bytes = u64::MAX.to_le_bytes();
+        let mut reader = SliceReader::new(&bytes);
+        assert!(matches!(
+            bevy_tasks::block_on(read_string(&mut reader)),
+            Err(BakedLightingSaveOrLoadError::NameTooLong { length: u64::MAX })
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_image_sizes() {
+        for size in [[u32::MAX; 3], [0, 4, 4], [1 << 16, 1 << 16, 1]] {
+            let bytes: Vec<u8> = size
+                .iter()
+                .flat_map(|extent| extent.to_le_bytes())
+                .collect();
+            let mut reader = SliceReader::new(&bytes);
+            assert!(
+                matches!(
+                    bevy_tasks::block_on(read_image(&mut reader, TextureDimension::D3)),
+                    Err(BakedLightingSaveOrLoadError::InvalidImageSize { size: found }) if found == size
+                ),
+                "{size:?} was read"
+            );
+        }
+    }
+}
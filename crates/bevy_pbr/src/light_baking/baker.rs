@@ -0,0 +1,588 @@
+use core::f32::consts::PI;
+
+use bevy_math::{ops, FloatPow, UVec2, UVec3, Vec2, Vec3, Vec3A};
+use bevy_tasks::{ComputeTaskPool, ParallelSlice, TaskPool};
+use serde::{Deserialize, Serialize};
+
+use tracing::warn;
+
+use super::{
+    bvh::Bvh, BakedIrradianceVolume, BakedLightmap, LightBakeLightKind, LightBakeOutput,
+    LightBakeScene, LightBakeVolume,
+};
+
+/// How far rays start from the surfaces they leave, to avoid hitting them.
+const SURFACE_OFFSET: f32 = 1e-3;
+
+/// The number of lightmap rows that are baked by each task.
+const ROWS_PER_TASK: usize = 4;
+
+/// Settings that control the quality of [`bake`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LightBakeSettings {
+    /// The number of rays traced from each lightmap texel, and from each side
+    /// of each irradiance volume voxel.
+    pub samples: u32,
+    /// The number of times that indirect light bounces off the scene.
+    ///
+    /// With 1 bounce, the indirect light is the direct light reflected once
+    /// by the surfaces around. With 0, no indirect light is baked.
+    pub bounces: u32,
+    /// The radiance of the rays that miss the scene, in the units of
+    /// [`StandardMaterial::emissive`](crate::StandardMaterial::emissive).
+    pub sky_color: Vec3,
+    /// The number of texels that lightmaps are extended by around their UV
+    /// islands, so that bilinear filtering doesn't blend in unbaked texels.
+    pub dilation: u32,
+}
+
+impl Default for LightBakeSettings {
+    fn default() -> Self {
+        Self {
+            samples: 128,
+            bounces: 2,
+            sky_color: Vec3::ZERO,
+            dilation: 2,
+        }
+    }
+}
+
+/// Bakes the lightmaps and the irradiance volumes of a scene on the CPU.
+///
+/// Lightmaps receive the direct light of the lights that are baked, see
+/// [`LightBakeLight::bake_direct_light`](super::LightBakeLight::bake_direct_light),
+/// and the indirect light of every light. Irradiance volumes only receive
+/// indirect light, as dynamic objects are lit directly at runtime.
+///
+/// This is slow, and meant to run ahead of time, usually in the asset
+/// processor.
+pub fn bake(scene: &LightBakeScene, settings: &LightBakeSettings) -> LightBakeOutput {
+    let baker = Baker {
+        scene,
+        settings,
+        bvh: Bvh::new(scene),
+    };
+
+    LightBakeOutput {
+        lightmaps: scene
+            .meshes
+            .iter()
+            .enumerate()
+            .filter_map(|(mesh_index, mesh)| {
+                let target = mesh.lightmap.as_ref()?;
+                if let Err(error) = mesh.validate() {
+                    warn!("Not baking the lightmap {:?}: {error}", target.name);
+                    return None;
+                }
+                let resolution = target.resolution.max(UVec2::ONE);
+                Some(BakedLightmap {
+                    name: target.name.clone(),
+                    resolution,
+                    texels: baker.bake_lightmap(mesh_index, resolution),
+                })
+            })
+            .collect(),
+        irradiance_volumes: scene
+            .irradiance_volumes
+            .iter()
+            .map(|volume| BakedIrradianceVolume {
+                name: volume.name.clone(),
+                resolution: volume.resolution.max(UVec3::ONE),
+                ambient_cubes: baker.bake_irradiance_volume(volume),
+            })
+            .collect(),
+    }
+}
+
+struct Baker<'a> {
+    scene: &'a LightBakeScene,
+    settings: &'a LightBakeSettings,
+    bvh: Bvh,
+}
+
+/// A point on a surface of the scene.
+struct SurfacePoint {
+    position: Vec3A,
+    normal: Vec3A,
+}
+
+impl Baker<'_> {
+    fn bake_lightmap(&self, mesh_index: usize, resolution: UVec2) -> Vec<Vec3> {
+        let mesh = &self.scene.meshes[mesh_index];
+        let width = resolution.x as usize;
+        let height = resolution.y as usize;
+
+        // Find the surface point at the center of each texel covered by the
+        // lightmap UVs.
+        let mut texels: Vec<Option<SurfacePoint>> = (0..width * height).map(|_| None).collect();
+        let size = resolution.as_vec2();
+        for triangle in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|index| index as usize);
+            let uvs = [a, b, c].map(|index| mesh.lightmap_uvs[index] * size);
+            let min = uvs[0].min(uvs[1]).min(uvs[2]).floor().max(Vec2::ZERO);
+            let max = uvs[0].max(uvs[1]).max(uvs[2]).ceil().min(size);
+            for y in min.y as usize..max.y as usize {
+                for x in min.x as usize..max.x as usize {
+                    let center = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+                    let Some(weights) = barycentrics(uvs, center) else {
+                        continue;
+                    };
+                    let position = weights[0] * mesh.positions[a]
+                        + weights[1] * mesh.positions[b]
+                        + weights[2] * mesh.positions[c];
+                    let normal = weights[0] * mesh.normals[a]
+                        + weights[1] * mesh.normals[b]
+                        + weights[2] * mesh.normals[c];
+                    texels[y * width + x] = Some(SurfacePoint {
+                        position: position.into(),
+                        normal: Vec3A::from(normal).normalize_or_zero(),
+                    });
+                }
+            }
+        }
+
+        let task_pool = ComputeTaskPool::get_or_init(TaskPool::default);
+        let mut lightmap: Vec<Option<Vec3>> = texels
+            .par_chunk_map(task_pool, width * ROWS_PER_TASK, |chunk_index, chunk| {
+                chunk
+                    .iter()
+                    .enumerate()
+                    .map(|(index, texel)| {
+                        let texel = texel.as_ref()?;
+                        let seed = (chunk_index * width * ROWS_PER_TASK + index) as u32;
+                        let mut rng = BakeRng::new(seed ^ (mesh_index as u32).wrapping_mul(7919));
+                        Some(self.texel_irradiance(texel, &mut rng))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .into_iter()
+            .flatten()
+            .collect();
+
+        for _ in 0..self.settings.dilation {
+            dilate(&mut lightmap, width, height);
+        }
+        lightmap
+            .into_iter()
+            .map(|texel| texel.unwrap_or_default())
+            .collect()
+    }
+
+    /// Returns the irradiance of a lightmap texel, divided by π, so that
+    /// multiplying it by the diffuse color gives the outgoing radiance.
+    fn texel_irradiance(&self, texel: &SurfacePoint, rng: &mut BakeRng) -> Vec3 {
+        if texel.normal == Vec3A::ZERO {
+            return Vec3::ZERO;
+        }
+        let mut irradiance = self.direct_irradiance(texel, true) / PI;
+        if self.settings.bounces > 0 {
+            irradiance += self.indirect_radiance(texel, texel.normal, 0, rng);
+        }
+        irradiance
+    }
+
+    fn bake_irradiance_volume(&self, volume: &LightBakeVolume) -> Vec<[Vec3; 6]> {
+        let resolution = volume.resolution.max(UVec3::ONE);
+        let voxel_count = (resolution.x * resolution.y * resolution.z) as usize;
+        let voxels: Vec<usize> = (0..voxel_count).collect();
+        let task_pool = ComputeTaskPool::get_or_init(TaskPool::default);
+        voxels
+            .par_chunk_map(task_pool, 64, |_, chunk| {
+                chunk
+                    .iter()
+                    .map(|&index| {
+                        let index = index as u32;
+                        let voxel = UVec3::new(
+                            index % resolution.x,
+                            index / resolution.x % resolution.y,
+                            index / (resolution.x * resolution.y),
+                        );
+                        let local = (voxel.as_vec3() + 0.5) / resolution.as_vec3() - 0.5;
+                        let position = volume.world_from_local.transform_point3(local);
+                        let mut rng = BakeRng::new(index.wrapping_mul(2654435761));
+
+                        // The sides are in the order -X, +X, -Y, +Y, -Z, +Z.
+                        let mut ambient_cube = [Vec3::ZERO; 6];
+                        for (side, value) in ambient_cube.iter_mut().enumerate() {
+                            let mut axis = Vec3A::ZERO;
+                            axis[side / 2] = if side % 2 == 0 { -1.0 } else { 1.0 };
+                            let point = SurfacePoint {
+                                position: position.into(),
+                                normal: axis,
+                            };
+                            *value = self.indirect_radiance(&point, axis, 0, &mut rng);
+                        }
+                        ambient_cube
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    /// Returns the average radiance that reaches a point from the hemisphere
+    /// around `normal`, weighted by the cosine of the angle to `normal`. This
+    /// is the indirect irradiance divided by π.
+    fn indirect_radiance(
+        &self,
+        point: &SurfacePoint,
+        normal: Vec3A,
+        depth: u32,
+        rng: &mut BakeRng,
+    ) -> Vec3 {
+        // Only the first bounce traces several rays, the next ones follow a
+        // single path.
+        let samples = if depth == 0 {
+            self.settings.samples.max(1)
+        } else {
+            1
+        };
+        let origin = point.position + normal * SURFACE_OFFSET;
+        let mut radiance = Vec3::ZERO;
+        for _ in 0..samples {
+            let direction = rng.cosine_direction(normal);
+            radiance += self.incoming_radiance(origin, direction, depth, rng);
+        }
+        radiance / samples as f32
+    }
+
+    /// Returns the radiance that arrives at `origin` from `direction`.
+    fn incoming_radiance(
+        &self,
+        origin: Vec3A,
+        direction: Vec3A,
+        depth: u32,
+        rng: &mut BakeRng,
+    ) -> Vec3 {
+        let Some(hit) = self.bvh.closest_hit(origin, direction, f32::MAX) else {
+            return self.settings.sky_color;
+        };
+
+        let triangle = &self.bvh.triangles[hit.triangle as usize];
+        let mesh = &self.scene.meshes[triangle.mesh as usize];
+        let indices = &mesh.indices[triangle.first_index as usize..][..3];
+        let [u, v] = hit.barycentrics;
+        let mut normal = Vec3A::from(
+            (1.0 - u - v) * mesh.normals[indices[0] as usize]
+                + u * mesh.normals[indices[1] as usize]
+                + v * mesh.normals[indices[2] as usize],
+        )
+        .normalize_or_zero();
+        if normal.dot(direction) > 0.0 {
+            normal = -normal;
+        }
+        let point = SurfacePoint {
+            position: origin + direction * hit.distance,
+            normal,
+        };
+
+        // Each light bounces off the surface, whether its direct light is
+        // baked or not.
+        let mut irradiance = self.direct_irradiance(&point, false) / PI;
+        if depth + 1 < self.settings.bounces {
+            irradiance += self.indirect_radiance(&point, normal, depth + 1, rng);
+        }
+        mesh.emissive + mesh.base_color * irradiance
+    }
+
+    /// Returns the direct irradiance at a point, from the lights whose direct
+    /// light is baked, or from every light if `baked_only` is false.
+    fn direct_irradiance(&self, point: &SurfacePoint, baked_only: bool) -> Vec3 {
+        let origin = point.position + point.normal * SURFACE_OFFSET;
+        let mut irradiance = Vec3::ZERO;
+        for light in &self.scene.lights {
+            if baked_only && !light.bake_direct_light {
+                continue;
+            }
+
+            let (direction, max_distance, attenuation) = match light.kind {
+                LightBakeLightKind::Directional { direction_to_light } => {
+                    (Vec3A::from(direction_to_light).normalize(), f32::MAX, 1.0)
+                }
+                LightBakeLightKind::Point {
+                    position,
+                    radius,
+                    range,
+                } => {
+                    let to_light = Vec3A::from(position) - point.position;
+                    let distance = to_light.length();
+                    (
+                        to_light / distance,
+                        distance - radius,
+                        distance_attenuation(distance, range),
+                    )
+                }
+                LightBakeLightKind::Spot {
+                    position,
+                    direction,
+                    radius,
+                    range,
+                    inner_angle,
+                    outer_angle,
+                } => {
+                    let to_light = Vec3A::from(position) - point.position;
+                    let distance = to_light.length();
+                    let to_light = to_light / distance;
+                    let spot =
+                        spot_attenuation(-to_light.dot(direction.into()), inner_angle, outer_angle);
+                    (
+                        to_light,
+                        distance - radius,
+                        distance_attenuation(distance, range) * spot,
+                    )
+                }
+            };
+
+            let n_dot_l = point.normal.dot(direction);
+            if n_dot_l <= 0.0 || attenuation <= 0.0 || !direction.is_finite() {
+                continue;
+            }
+            if self.bvh.is_occluded(origin, direction, max_distance) {
+                continue;
+            }
+            irradiance += light.color * n_dot_l * attenuation;
+        }
+        irradiance
+    }
+}
+
+/// The attenuation of point and spot lights, as in `getDistanceAttenuation`
+/// in `pbr_lighting.wgsl`.
+fn distance_attenuation(distance: f32, range: f32) -> f32 {
+    let distance_squared = distance.squared();
+    let factor = distance_squared / range.squared();
+    let smooth_factor = (1.0 - factor.squared()).clamp(0.0, 1.0);
+    smooth_factor.squared() / distance_squared.max(0.0001)
+}
+
+/// The angular attenuation of spot lights, as in `spot_light` in
+/// `pbr_lighting.wgsl`.
+fn spot_attenuation(cos_angle: f32, inner_angle: f32, outer_angle: f32) -> f32 {
+    let cos_outer = ops::cos(outer_angle);
+    let scale = 1.0 / (ops::cos(inner_angle) - cos_outer).max(1e-4);
+    (cos_angle * scale - cos_outer * scale)
+        .clamp(0.0, 1.0)
+        .squared()
+}
+
+/// Returns the barycentric coordinates of `point` in the triangle, if it's
+/// inside.
+fn barycentrics(triangle: [Vec2; 3], point: Vec2) -> Option<[f32; 3]> {
+    let [a, b, c] = triangle;
+    let area = (b - a).perp_dot(c - a);
+    if area.abs() < 1e-12 {
+        return None;
+    }
+    let u = (c - b).perp_dot(point - b) / area;
+    let v = (a - c).perp_dot(point - c) / area;
+    let w = 1.0 - u - v;
+    (u >= 0.0 && v >= 0.0 && w >= 0.0).then_some([u, v, w])
+}
+
+/// Fills the unbaked texels next to baked ones with the average of their
+/// baked neighbors.
+fn dilate(texels: &mut [Option<Vec3>], width: usize, height: usize) {
+    let source = texels.to_vec();
+    for y in 0..height {
+        for x in 0..width {
+            if source[y * width + x].is_some() {
+                continue;
+            }
+            let mut sum = Vec3::ZERO;
+            let mut count = 0;
+            for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                let (nx, ny) = (x as isize + dx, y as isize + dy);
+                if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
+                    continue;
+                }
+                if let Some(value) = source[ny as usize * width + nx as usize] {
+                    sum += value;
+                    count += 1;
+                }
+            }
+            if count > 0 {
+                texels[y * width + x] = Some(sum / count as f32);
+            }
+        }
+    }
+}
+
+/// The PCG hash, used as a small deterministic random number generator, so
+/// that bakes are reproducible.
+struct BakeRng(u32);
+
+impl BakeRng {
+    fn new(seed: u32) -> Self {
+        Self(seed)
+    }
+
+    /// Returns a random number in [0, 1).
+    fn next(&mut self) -> f32 {
+        let state = self.0.wrapping_mul(747796405).wrapping_add(2891336453);
+        let word = ((state >> ((state >> 28) + 4)) ^ state).wrapping_mul(277803737);
+        self.0 = (word >> 22) ^ word;
+        (self.0 >> 8) as f32 / 16777216.0
+    }
+
+    /// Returns a random direction in the hemisphere around `normal`, with a
+    /// cosine-weighted distribution.
+    fn cosine_direction(&mut self, normal: Vec3A) -> Vec3A {
+        let (sin_phi, cos_phi) = ops::sin_cos(2.0 * PI * self.next());
+        let r_squared = self.next();
+        let r = r_squared.sqrt();
+        let (tangent, bitangent) = normal.any_orthonormal_pair();
+        (tangent * (r * cos_phi) + bitangent * (r * sin_phi) + normal * (1.0 - r_squared).sqrt())
+            .normalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_math::{UVec2, Vec2, Vec3};
+
+    use super::{bake, LightBakeSettings};
+    use crate::light_baking::{
+        LightBakeLight, LightBakeLightKind, LightBakeMesh, LightBakeMeshError, LightBakeScene,
+        LightBakeTarget,
+    };
+
+    /// A 1×1 floor at y = 0, facing up, with lightmap UVs covering the
+    /// whole lightmap.
+    fn floor(y: f32, lightmap: Option<LightBakeTarget>) -> LightBakeMesh {
+        LightBakeMesh {
+            lightmap,
+            positions: vec![
+                Vec3::new(-0.5, y, -0.5),
+                Vec3::new(0.5, y, -0.5),
+                Vec3::new(0.5, y, 0.5),
+                Vec3::new(-0.5, y, 0.5),
+            ],
+            normals: vec![Vec3::Y; 4],
+            lightmap_uvs: vec![
+                Vec2::new(0.0, 0.0),
+                Vec2::new(1.0, 0.0),
+                Vec2::new(1.0, 1.0),
+                Vec2::new(0.0, 1.0),
+            ],
+            indices: vec![0, 2, 1, 0, 3, 2],
+            base_color: Vec3::ONE,
+            emissive: Vec3::ZERO,
+        }
+    }
+
+    fn sun(bake_direct_light: bool) -> LightBakeLight {
+        LightBakeLight {
+            kind: LightBakeLightKind::Directional {
+                direction_to_light: Vec3::Y,
+            },
+            color: Vec3::splat(core::f32::consts::PI),
+            bake_direct_light,
+        }
+    }
+
+    fn target() -> Option<LightBakeTarget> {
+        Some(LightBakeTarget {
+            name: "Floor".into(),
+            resolution: UVec2::splat(4),
+        })
+    }
+
+    #[test]
+    fn bakes_direct_light() {
+        let scene = LightBakeScene {
+            meshes: vec![floor(0.0, target())],
+            lights: vec![sun(true)],
+            irradiance_volumes: vec![],
+        };
+        let output = bake(&scene, &LightBakeSettings::default());
+        assert_eq!(output.lightmaps.len(), 1);
+        for texel in &output.lightmaps[0].texels {
+            // An irradiance of π, divided by π.
+            assert!((*texel - Vec3::ONE).abs().max_element() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn skips_lights_that_are_not_baked() {
+        let scene = LightBakeScene {
+            meshes: vec![floor(0.0, target())],
+            lights: vec![sun(false)],
+            irradiance_volumes: vec![],
+        };
+        let output = bake(&scene, &LightBakeSettings::default());
+        assert!(output.lightmaps[0]
+            .texels
+            .iter()
+            .all(|texel| *texel == Vec3::ZERO));
+    }
+
+    #[test]
+    fn occluders_cast_shadows() {
+        // A roof over the floor, without indirect light.
+        let scene = LightBakeScene {
+            meshes: vec![floor(0.0, target()), floor(1.0, None)],
+            lights: vec![sun(true)],
+            irradiance_volumes: vec![],
+        };
+        let settings = LightBakeSettings {
+            bounces: 0,
+            ..Default::default()
+        };
+        let output = bake(&scene, &settings);
+        assert!(output.lightmaps[0]
+            .texels
+            .iter()
+            .all(|texel| *texel == Vec3::ZERO));
+    }
+
+    #[test]
+    fn skips_invalid_meshes() {
+        let mut invalid = floor(
+            1.0,
+            Some(LightBakeTarget {
+                name: "Roof".into(),
+                resolution: UVec2::splat(4),
+            }),
+        );
+        invalid.indices[1] = 4;
+        assert_eq!(
+            invalid.validate(),
+            Err(LightBakeMeshError::InvalidIndex {
+                index: 4,
+                vertex_count: 4,
+            })
+        );
+
+        // The invalid roof neither gets a lightmap nor casts a shadow.
+        let scene = LightBakeScene {
+            meshes: vec![floor(0.0, target()), invalid],
+            lights: vec![sun(true)],
+            irradiance_volumes: vec![],
+        };
+        let output = bake(&scene, &LightBakeSettings::default());
+        assert_eq!(output.lightmaps.len(), 1);
+        assert_eq!(output.lightmaps[0].name, "Floor");
+        assert!(output.lightmaps[0]
+            .texels
+            .iter()
+            .all(|texel| (*texel - Vec3::ONE).abs().max_element() < 1e-4));
+    }
+
+    #[test]
+    fn validates_attribute_counts() {
+        let mut mesh = floor(0.0, target());
+        mesh.lightmap_uvs.pop();
+        assert_eq!(
+            mesh.validate(),
+            Err(LightBakeMeshError::AttributeCountMismatch {
+                attribute: "lightmap UVs",
+                count: 3,
+                vertex_count: 4,
+            })
+        );
+        // Lightmap UVs are only needed to bake a lightmap.
+        mesh.lightmap = None;
+        assert_eq!(mesh.validate(), Ok(()));
+    }
+}
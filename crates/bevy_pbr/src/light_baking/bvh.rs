@@ -0,0 +1,238 @@
+//! A bounding volume hierarchy over the triangles of a [`LightBakeScene`],
+//! used to trace rays on the CPU while baking.
+
+use bevy_math::{bounding::Aabb3d, Vec3A};
+
+use super::LightBakeScene;
+
+/// The largest number of triangles in a leaf of the hierarchy.
+const MAX_LEAF_TRIANGLES: usize = 4;
+
+/// A triangle of the scene, in world space.
+pub(super) struct BvhTriangle {
+    pub(super) vertices: [Vec3A; 3],
+    /// The index of the mesh in [`LightBakeScene::meshes`].
+    pub(super) mesh: u32,
+    /// The index of the first index of the triangle in the mesh.
+    pub(super) first_index: u32,
+}
+
+struct BvhNode {
+    bounds: Aabb3d,
+    /// For leaves, the first triangle. Otherwise, the index of the second
+    /// child; the first child always directly follows its parent.
+    start: u32,
+    /// The number of triangles in a leaf, or 0 for inner nodes.
+    count: u32,
+}
+
+/// The closest intersection of a ray with the scene.
+pub(super) struct BvhHit {
+    pub(super) distance: f32,
+    pub(super) triangle: u32,
+    /// The barycentric coordinates of the hit, for the second and third
+    /// vertex of the triangle.
+    pub(super) barycentrics: [f32; 2],
+}
+
+pub(super) struct Bvh {
+    nodes: Vec<BvhNode>,
+    pub(super) triangles: Vec<BvhTriangle>,
+}
+
+impl Bvh {
+    pub(super) fn new(scene: &LightBakeScene) -> Self {
+        let mut triangles = Vec::new();
+        for (mesh_index, mesh) in scene.meshes.iter().enumerate() {
+            // Invalid meshes are reported by the baker, and don't occlude.
+            if mesh.validate().is_err() {
+                continue;
+            }
+            for (triangle_index, triangle) in mesh.indices.chunks_exact(3).enumerate() {
+                let vertices = [triangle[0], triangle[1], triangle[2]]
+                    .map(|index| Vec3A::from(mesh.positions[index as usize]));
+                triangles.push(BvhTriangle {
+                    vertices,
+                    mesh: mesh_index as u32,
+                    first_index: triangle_index as u32 * 3,
+                });
+            }
+        }
+
+        let mut bvh = Self {
+            nodes: Vec::with_capacity(triangles.len() * 2),
+            triangles,
+        };
+        if !bvh.triangles.is_empty() {
+            bvh.build(0, bvh.triangles.len());
+        }
+        bvh
+    }
+
+    /// Builds the node over the given range of triangles, splitting them at
+    /// the median of their centroids along the longest axis.
+    fn build(&mut self, start: usize, end: usize) {
+        let bounds = triangle_bounds(&self.triangles[start..end]);
+        let node_index = self.nodes.len();
+        self.nodes.push(BvhNode {
+            bounds,
+            start: start as u32,
+            count: (end - start) as u32,
+        });
+        if end - start <= MAX_LEAF_TRIANGLES {
+            return;
+        }
+
+        let extents = bounds.max - bounds.min;
+        let axis = if extents.x >= extents.y && extents.x >= extents.z {
+            0
+        } else if extents.y >= extents.z {
+            1
+        } else {
+            2
+        };
+        let middle = (start + end) / 2;
+        self.triangles[start..end].select_nth_unstable_by(middle - start, |a, b| {
+            centroid(a)[axis].total_cmp(&centroid(b)[axis])
+        });
+
+        self.build(start, middle);
+        let second_child = self.nodes.len() as u32;
+        self.build(middle, end);
+        self.nodes[node_index].start = second_child;
+        self.nodes[node_index].count = 0;
+    }
+
+    /// Returns the closest intersection of the ray closer than `max_distance`.
+    pub(super) fn closest_hit(
+        &self,
+        origin: Vec3A,
+        direction: Vec3A,
+        max_distance: f32,
+    ) -> Option<BvhHit> {
+        let mut closest = None;
+        let mut max_distance = max_distance;
+        self.traverse(origin, direction, &mut max_distance, |triangle, hit| {
+            closest = Some(BvhHit {
+                triangle,
+                distance: hit.0,
+                barycentrics: hit.1,
+            });
+            false
+        });
+        closest
+    }
+
+    /// Returns true if anything intersects the ray closer than `max_distance`.
+    pub(super) fn is_occluded(&self, origin: Vec3A, direction: Vec3A, max_distance: f32) -> bool {
+        let mut occluded = false;
+        let mut max_distance = max_distance;
+        self.traverse(origin, direction, &mut max_distance, |_, _| {
+            occluded = true;
+            true
+        });
+        occluded
+    }
+
+    /// Calls `on_hit` for every triangle hit closer than the current
+    /// `max_distance`, which shrinks to the distance of each hit. Stops when
+    /// `on_hit` returns true.
+    fn traverse(
+        &self,
+        origin: Vec3A,
+        direction: Vec3A,
+        max_distance: &mut f32,
+        mut on_hit: impl FnMut(u32, (f32, [f32; 2])) -> bool,
+    ) {
+        if self.nodes.is_empty() {
+            return;
+        }
+
+        let inverse_direction = direction.recip();
+        let mut stack = Vec::with_capacity(64);
+        stack.push(0u32);
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index as usize];
+            if !intersects_bounds(&node.bounds, origin, inverse_direction, *max_distance) {
+                continue;
+            }
+
+            if node.count == 0 {
+                stack.push(node.start);
+                stack.push(node_index + 1);
+                continue;
+            }
+
+            for triangle_index in node.start..node.start + node.count {
+                let triangle = &self.triangles[triangle_index as usize];
+                if let Some(hit) = intersect_triangle(triangle, origin, direction, *max_distance) {
+                    *max_distance = hit.0;
+                    if on_hit(triangle_index, hit) {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn centroid(triangle: &BvhTriangle) -> Vec3A {
+    (triangle.vertices[0] + triangle.vertices[1] + triangle.vertices[2]) / 3.0
+}
+
+fn triangle_bounds(triangles: &[BvhTriangle]) -> Aabb3d {
+    let mut min = Vec3A::splat(f32::MAX);
+    let mut max = Vec3A::splat(f32::MIN);
+    for vertex in triangles.iter().flat_map(|triangle| triangle.vertices) {
+        min = min.min(vertex);
+        max = max.max(vertex);
+    }
+    Aabb3d { min, max }
+}
+
+/// The slab test, which also rejects boxes beyond `max_distance`.
+fn intersects_bounds(
+    bounds: &Aabb3d,
+    origin: Vec3A,
+    inverse_direction: Vec3A,
+    max_distance: f32,
+) -> bool {
+    let t1 = (bounds.min - origin) * inverse_direction;
+    let t2 = (bounds.max - origin) * inverse_direction;
+    let t_min = t1.min(t2).max_element().max(0.0);
+    let t_max = t1.max(t2).min_element().min(max_distance);
+    t_min <= t_max
+}
+
+/// The Möller–Trumbore intersection, returning the distance and the
+/// barycentric coordinates of the hit.
+fn intersect_triangle(
+    triangle: &BvhTriangle,
+    origin: Vec3A,
+    direction: Vec3A,
+    max_distance: f32,
+) -> Option<(f32, [f32; 2])> {
+    let [a, b, c] = triangle.vertices;
+    let edge_1 = b - a;
+    let edge_2 = c - a;
+    let p = direction.cross(edge_2);
+    let determinant = edge_1.dot(p);
+    if determinant.abs() < 1e-12 {
+        return None;
+    }
+
+    let inverse_determinant = 1.0 / determinant;
+    let s = origin - a;
+    let u = s.dot(p) * inverse_determinant;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(edge_1);
+    let v = direction.dot(q) * inverse_determinant;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let distance = edge_2.dot(q) * inverse_determinant;
+    (distance > 0.0 && distance < max_distance).then_some((distance, [u, v]))
+}
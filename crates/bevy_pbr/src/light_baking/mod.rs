@@ -0,0 +1,207 @@
+//! Baking of [lightmaps](crate::Lightmap) and
+//! [irradiance volumes](crate::irradiance_volume::IrradianceVolume) for static
+//! geometry.
+//!
+//! Baking happens ahead of time, in the asset processor:
+//!
+//! 1. Add [`BakeLightmap`] to the static meshes that should get a lightmap,
+//!    [`LightBakeOccluder`] to the other static meshes that should block and
+//!    reflect light, and [`BakeIrradianceVolume`] to the light probes that
+//!    should get an irradiance volume. Each baked entity needs a unique
+//!    [`Name`], which is how the results find their way back to it.
+//! 2. Capture the scene with [`LightBakeScene::from_world`], and write it with
+//!    [`LightBakeScene::to_ron`] to a `.lightbake.ron` file in the assets
+//!    folder.
+//! 3. The asset processor bakes the file with [`bake`] into a
+//!    `.baked_lighting` file, see [`LightBakeSettings`] for the settings in its
+//!    `.meta` file.
+//! 4. Add [`ApplyBakedLighting`] with the processed asset to any entity of the
+//!    scene to insert the [`Lightmap`]s and [`IrradianceVolume`]s on the baked
+//!    entities once it's loaded.
+//!
+//! The baker traces rays on the CPU, as the asset processor has no GPU to
+//! render with. A GPU baker is out of scope: it would need a render device
+//! outside of the render world, and would only run in apps that render.
+//! Materials are approximated by their base color and emissive.
+//!
+//! Captured meshes whose indices or attributes are inconsistent are skipped
+//! with a warning, and the loader rejects scenes containing them, see
+//! [`LightBakeMesh::validate`].
+
+mod asset;
+mod baker;
+mod bvh;
+mod scene;
+
+pub use asset::*;
+pub use baker::*;
+pub use scene::*;
+
+use bevy_app::{App, Plugin, Update};
+use bevy_asset::{AssetApp, AssetEvent, AssetId, Assets, Handle};
+use bevy_ecs::{name::Name, prelude::*};
+use bevy_math::{Rect, UVec2, UVec3};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::mesh::Mesh3d;
+use bevy_utils::HashSet;
+use tracing::warn;
+
+use crate::{irradiance_volume::IrradianceVolume, LightProbe, Lightmap};
+
+/// A plugin that bakes `.lightbake.ron` files in the asset processor, loads
+/// the baked lighting, and applies it with [`ApplyBakedLighting`].
+pub struct LightBakingPlugin;
+
+/// Marks a static mesh to bake a [`Lightmap`] for.
+///
+/// The mesh needs lightmap UVs, in
+/// [`ATTRIBUTE_UV_1`](bevy_render::mesh::Mesh::ATTRIBUTE_UV_1), and a unique
+/// [`Name`]. It also blocks and reflects light, like a [`LightBakeOccluder`].
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component, Default, Debug)]
+#[require(Mesh3d)]
+pub struct BakeLightmap {
+    /// The size of the lightmap, in texels.
+    pub resolution: UVec2,
+}
+
+impl Default for BakeLightmap {
+    fn default() -> Self {
+        Self {
+            resolution: UVec2::splat(64),
+        }
+    }
+}
+
+/// Marks a static mesh that blocks and reflects light while baking, without
+/// getting a lightmap of its own.
+#[derive(Component, Clone, Copy, Debug, Default, Reflect)]
+#[reflect(Component, Default, Debug)]
+#[require(Mesh3d)]
+pub struct LightBakeOccluder;
+
+/// Marks a light probe to bake an [`IrradianceVolume`] for, over the 1×1×1
+/// cube of its transform.
+///
+/// The light probe needs a unique [`Name`].
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component, Default, Debug)]
+pub struct BakeIrradianceVolume {
+    /// The number of voxels along each axis.
+    pub resolution: UVec3,
+}
+
+impl Default for BakeIrradianceVolume {
+    fn default() -> Self {
+        Self {
+            resolution: UVec3::splat(8),
+        }
+    }
+}
+
+/// Applies the [`BakedLighting`] to the entities it was baked for, matching
+/// them by [`Name`].
+///
+/// Each entity with a [`BakeLightmap`] gets the [`Lightmap`] baked for it, and
+/// each entity with a [`BakeIrradianceVolume`] gets a [`LightProbe`] and the
+/// [`IrradianceVolume`] baked for it. They're applied again whenever the
+/// asset is reloaded.
+#[derive(Component, Clone, Debug, Default, Reflect)]
+#[reflect(Component, Default, Debug)]
+pub struct ApplyBakedLighting(pub Handle<BakedLighting>);
+
+impl Plugin for LightBakingPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<BakeLightmap>()
+            .register_type::<LightBakeOccluder>()
+            .register_type::<BakeIrradianceVolume>()
+            .register_type::<ApplyBakedLighting>()
+            .init_asset::<LightBakeScene>()
+            .init_asset::<LightBakeOutput>()
+            .init_asset::<BakedLighting>()
+            .init_asset_loader::<LightBakeSceneLoader>()
+            .init_asset_loader::<BakedLightingLoader>()
+            .add_systems(Update, apply_baked_lighting);
+
+        if let Some(processor) = app
+            .world()
+            .get_resource::<bevy_asset::processor::AssetProcessor>()
+        {
+            processor.register_processor::<bevy_asset::processor::LoadTransformAndSave<
+                LightBakeSceneLoader,
+                LightBakeTransformer,
+                BakedLightingSaver,
+            >>(bevy_asset::processor::LoadTransformAndSave::new(
+                LightBakeTransformer,
+                BakedLightingSaver,
+            ));
+            processor.set_default_processor::<bevy_asset::processor::LoadTransformAndSave<
+                LightBakeSceneLoader,
+                LightBakeTransformer,
+                BakedLightingSaver,
+            >>("lightbake.ron");
+        }
+    }
+}
+
+/// Inserts the baked lighting on the baked entities, when an
+/// [`ApplyBakedLighting`] is added or its asset finishes loading.
+fn apply_baked_lighting(
+    mut commands: Commands,
+    mut asset_events: EventReader<AssetEvent<BakedLighting>>,
+    baked_lighting: Res<Assets<BakedLighting>>,
+    apply_query: Query<Ref<ApplyBakedLighting>>,
+    lightmaps_query: Query<(Entity, &Name), With<BakeLightmap>>,
+    irradiance_volumes_query: Query<(Entity, &Name), With<BakeIrradianceVolume>>,
+) {
+    let loaded: HashSet<AssetId<BakedLighting>> = asset_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    for apply in &apply_query {
+        if !apply.is_added() && !loaded.contains(&apply.0.id()) {
+            continue;
+        }
+        let Some(baked_lighting) = baked_lighting.get(&apply.0) else {
+            continue;
+        };
+
+        for (name, image) in &baked_lighting.lightmaps {
+            let Some((entity, _)) = lightmaps_query.iter().find(|(_, n)| n.as_str() == name) else {
+                warn!("No entity named {name:?} with `BakeLightmap` to apply its lightmap to");
+                continue;
+            };
+            commands.entity(entity).insert(Lightmap {
+                image: image.clone(),
+                uv_rect: Rect::new(0.0, 0.0, 1.0, 1.0),
+                bicubic_sampling: false,
+            });
+        }
+
+        for (name, voxels) in &baked_lighting.irradiance_volumes {
+            let Some((entity, _)) = irradiance_volumes_query
+                .iter()
+                .find(|(_, n)| n.as_str() == name)
+            else {
+                warn!(
+                    "No entity named {name:?} with `BakeIrradianceVolume` to apply its \
+                     irradiance volume to"
+                );
+                continue;
+            };
+            commands.entity(entity).insert((
+                LightProbe,
+                IrradianceVolume {
+                    voxels: voxels.clone(),
+                    intensity: 1.0,
+                    // The lightmaps already hold the indirect light.
+                    affects_lightmapped_meshes: false,
+                },
+            ));
+        }
+    }
+}
@@ -0,0 +1,414 @@
+use bevy_asset::{io::Reader, Asset, AssetLoader, Assets, LoadContext};
+use bevy_color::ColorToComponents;
+use bevy_ecs::{name::Name, prelude::*};
+use bevy_math::{Mat4, UVec2, UVec3, Vec2, Vec3};
+use bevy_reflect::TypePath;
+use bevy_render::{
+    mesh::{Mesh, Mesh3d, PrimitiveTopology, VertexAttributeValues},
+    view::InheritedVisibility,
+};
+use bevy_transform::components::GlobalTransform;
+use ron::de::SpannedError;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::warn;
+
+use super::{BakeIrradianceVolume, BakeLightmap, LightBakeOccluder};
+use crate::{DirectionalLight, MeshMaterial3d, PointLight, SpotLight, StandardMaterial};
+
+/// A snapshot of the static geometry and the lights of a scene, from which
+/// [`bake`](super::bake) computes lightmaps and irradiance volumes.
+///
+/// Use [`LightBakeScene::from_world`] to capture a scene, and save it with
+/// [`LightBakeScene::to_ron`] to a `.lightbake.ron` file to bake it with the
+/// asset processor. All positions are in world space.
+#[derive(Asset, TypePath, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LightBakeScene {
+    /// The meshes that block and reflect light.
+    pub meshes: Vec<LightBakeMesh>,
+    /// The lights of the scene.
+    pub lights: Vec<LightBakeLight>,
+    /// The regions to bake irradiance volumes for.
+    pub irradiance_volumes: Vec<LightBakeVolume>,
+}
+
+/// A static mesh of a [`LightBakeScene`].
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LightBakeMesh {
+    /// The lightmap to bake for the mesh, if any.
+    pub lightmap: Option<LightBakeTarget>,
+    /// The positions of the vertices.
+    pub positions: Vec<Vec3>,
+    /// The normals of the vertices.
+    pub normals: Vec<Vec3>,
+    /// The lightmap UVs of the vertices, from
+    /// [`Mesh::ATTRIBUTE_UV_1`]. Only needed if the mesh has a lightmap.
+    pub lightmap_uvs: Vec<Vec2>,
+    /// The vertex indices of the triangles.
+    pub indices: Vec<u32>,
+    /// The linear diffuse color of the surface.
+    pub base_color: Vec3,
+    /// The light emitted by the surface, in the units of
+    /// [`StandardMaterial::emissive`].
+    pub emissive: Vec3,
+}
+
+/// The lightmap to bake for a [`LightBakeMesh`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LightBakeTarget {
+    /// The [`Name`] of the entity that the lightmap is applied to.
+    pub name: String,
+    /// The size of the lightmap, in texels.
+    pub resolution: UVec2,
+}
+
+/// A light of a [`LightBakeScene`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LightBakeLight {
+    pub kind: LightBakeLightKind,
+    /// The linear color of the light, multiplied by its luminous intensity
+    /// for point and spot lights, or by its illuminance for directional
+    /// lights.
+    pub color: Vec3,
+    /// Whether the direct light is baked into the lightmaps.
+    ///
+    /// This is the case for lights that don't affect lightmapped meshes at
+    /// runtime, which avoids counting their light twice. The light of every
+    /// light bounces off the scene into the baked indirect light.
+    pub bake_direct_light: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum LightBakeLightKind {
+    Directional {
+        direction_to_light: Vec3,
+    },
+    Point {
+        position: Vec3,
+        radius: f32,
+        range: f32,
+    },
+    Spot {
+        position: Vec3,
+        direction: Vec3,
+        radius: f32,
+        range: f32,
+        inner_angle: f32,
+        outer_angle: f32,
+    },
+}
+
+/// A region of a [`LightBakeScene`] to bake an irradiance volume for.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LightBakeVolume {
+    /// The [`Name`] of the light probe entity that the volume is applied to.
+    pub name: String,
+    /// The transform of the 1×1×1 cube of the volume.
+    pub world_from_local: Mat4,
+    /// The number of voxels along each axis.
+    pub resolution: UVec3,
+}
+
+impl LightBakeScene {
+    /// Captures the meshes with a [`BakeLightmap`] or a [`LightBakeOccluder`],
+    /// the lights and the [`BakeIrradianceVolume`]s of the world.
+    ///
+    /// Only visible triangle-list meshes are captured, with the base color and
+    /// emissive of their [`StandardMaterial`], ignoring textures. Lightmaps
+    /// are only baked for named meshes with lightmap UVs.
+    pub fn from_world(world: &mut World) -> Self {
+        let mut scene = LightBakeScene::default();
+
+        let mut meshes_query = world.query_filtered::<(
+            &Mesh3d,
+            &GlobalTransform,
+            &InheritedVisibility,
+            Option<&MeshMaterial3d<StandardMaterial>>,
+            Option<&BakeLightmap>,
+            Option<&Name>,
+        ), Or<(With<BakeLightmap>, With<LightBakeOccluder>)>>();
+        let meshes = world.resource::<Assets<Mesh>>();
+        let materials = world.resource::<Assets<StandardMaterial>>();
+        for (mesh, transform, visibility, material, bake_lightmap, name) in meshes_query.iter(world)
+        {
+            if !visibility.get() {
+                continue;
+            }
+            let Some(mesh) = meshes.get(mesh) else {
+                continue;
+            };
+            let material = material.and_then(|material| materials.get(material));
+            let lightmap = bake_lightmap.and_then(|bake_lightmap| match name {
+                Some(name) => Some(LightBakeTarget {
+                    name: name.to_string(),
+                    resolution: bake_lightmap.resolution,
+                }),
+                None => {
+                    warn!("Not baking a lightmap for a mesh without a `Name`");
+                    None
+                }
+            });
+            if let Some(mesh) = LightBakeMesh::new(mesh, transform, material, lightmap) {
+                scene.meshes.push(mesh);
+            }
+        }
+
+        let mut directional_lights =
+            world.query::<(&DirectionalLight, &GlobalTransform, &InheritedVisibility)>();
+        for (light, transform, visibility) in directional_lights.iter(world) {
+            if visibility.get() {
+                scene.lights.push(LightBakeLight {
+                    kind: LightBakeLightKind::Directional {
+                        direction_to_light: transform.back().into(),
+                    },
+                    color: light.color.to_linear().to_vec3() * light.illuminance,
+                    bake_direct_light: !light.affects_lightmapped_mesh_diffuse,
+                });
+            }
+        }
+
+        // Point and spot light intensities are converted from lumens to
+        // candela like at runtime, see `extract_lights`.
+        let mut point_lights =
+            world.query::<(&PointLight, &GlobalTransform, &InheritedVisibility)>();
+        for (light, transform, visibility) in point_lights.iter(world) {
+            if visibility.get() {
+                scene.lights.push(LightBakeLight {
+                    kind: LightBakeLightKind::Point {
+                        position: transform.translation(),
+                        radius: light.radius,
+                        range: light.range,
+                    },
+                    color: light.color.to_linear().to_vec3() * light.intensity
+                        / (4.0 * core::f32::consts::PI),
+                    bake_direct_light: !light.affects_lightmapped_mesh_diffuse,
+                });
+            }
+        }
+
+        let mut spot_lights = world.query::<(&SpotLight, &GlobalTransform, &InheritedVisibility)>();
+        for (light, transform, visibility) in spot_lights.iter(world) {
+            if visibility.get() {
+                scene.lights.push(LightBakeLight {
+                    kind: LightBakeLightKind::Spot {
+                        position: transform.translation(),
+                        direction: transform.forward().into(),
+                        radius: light.radius,
+                        range: light.range,
+                        inner_angle: light.inner_angle,
+                        outer_angle: light.outer_angle,
+                    },
+                    color: light.color.to_linear().to_vec3() * light.intensity
+                        / (4.0 * core::f32::consts::PI),
+                    bake_direct_light: !light.affects_lightmapped_mesh_diffuse,
+                });
+            }
+        }
+
+        let mut volumes = world.query::<(&BakeIrradianceVolume, &GlobalTransform, Option<&Name>)>();
+        for (volume, transform, name) in volumes.iter(world) {
+            let Some(name) = name else {
+                warn!("Not baking an irradiance volume without a `Name`");
+                continue;
+            };
+            scene.irradiance_volumes.push(LightBakeVolume {
+                name: name.to_string(),
+                world_from_local: transform.compute_matrix(),
+                resolution: volume.resolution.max(UVec3::ONE),
+            });
+        }
+
+        scene
+    }
+
+    /// Serializes the scene to the `.lightbake.ron` format.
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string(self)
+    }
+}
+
+impl LightBakeMesh {
+    /// Checks that the indices refer to existing vertices, and that every
+    /// vertex has a normal, and lightmap UVs if the mesh has a lightmap.
+    pub fn validate(&self) -> Result<(), LightBakeMeshError> {
+        let vertex_count = self.positions.len();
+        if let Some(&index) = self
+            .indices
+            .iter()
+            .find(|&&index| index as usize >= vertex_count)
+        {
+            return Err(LightBakeMeshError::InvalidIndex {
+                index,
+                vertex_count,
+            });
+        }
+        if self.normals.len() != vertex_count {
+            return Err(LightBakeMeshError::AttributeCountMismatch {
+                attribute: "normals",
+                count: self.normals.len(),
+                vertex_count,
+            });
+        }
+        if self.lightmap.is_some() && self.lightmap_uvs.len() != vertex_count {
+            return Err(LightBakeMeshError::AttributeCountMismatch {
+                attribute: "lightmap UVs",
+                count: self.lightmap_uvs.len(),
+                vertex_count,
+            });
+        }
+        Ok(())
+    }
+
+    /// Transforms the triangles of a mesh to world space, or returns `None`
+    /// if it isn't a triangle list.
+    fn new(
+        mesh: &Mesh,
+        transform: &GlobalTransform,
+        material: Option<&StandardMaterial>,
+        mut lightmap: Option<LightBakeTarget>,
+    ) -> Option<Self> {
+        if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+            return None;
+        }
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            return None;
+        };
+
+        let indices: Vec<u32> = match mesh.indices() {
+            Some(indices) => indices.iter().map(|index| index as u32).collect(),
+            None => (0..positions.len() as u32).collect(),
+        };
+        if let Some(&index) = indices
+            .iter()
+            .find(|&&index| index as usize >= positions.len())
+        {
+            warn!(
+                "Not capturing a mesh for light baking, as its index {index} is out of \
+                 bounds for its {} vertices",
+                positions.len()
+            );
+            return None;
+        }
+
+        let affine = transform.affine();
+        let normal_matrix = affine.matrix3.inverse().transpose();
+        let positions: Vec<Vec3> = positions
+            .iter()
+            .map(|&position| affine.transform_point3(position.into()))
+            .collect();
+        let normals = match mesh.attribute(Mesh::ATTRIBUTE_NORMAL) {
+            Some(VertexAttributeValues::Float32x3(normals)) if normals.len() == positions.len() => {
+                normals
+                    .iter()
+                    .map(|&normal| (normal_matrix * Vec3::from(normal)).normalize_or_zero())
+                    .collect()
+            }
+            _ => flat_normals(&positions, &indices),
+        };
+
+        let lightmap_uvs = match mesh.attribute(Mesh::ATTRIBUTE_UV_1) {
+            Some(VertexAttributeValues::Float32x2(uvs)) if uvs.len() == positions.len() => {
+                uvs.iter().map(|&uv| Vec2::from(uv)).collect()
+            }
+            _ => {
+                if let Some(target) = lightmap.take() {
+                    warn!(
+                        "Not baking a lightmap for {}, as its mesh lacks lightmap UVs for some vertices",
+                        target.name
+                    );
+                }
+                Vec::new()
+            }
+        };
+
+        Some(Self {
+            lightmap,
+            positions,
+            normals,
+            lightmap_uvs,
+            indices,
+            base_color: material
+                .map(|material| material.base_color.to_linear().to_vec3())
+                .unwrap_or(Vec3::ONE),
+            emissive: material
+                .map(|material| material.emissive.to_vec3())
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/// Computes vertex normals from the faces, for meshes without normals.
+fn flat_normals(positions: &[Vec3], indices: &[u32]) -> Vec<Vec3> {
+    let mut normals = vec![Vec3::ZERO; positions.len()];
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|index| index as usize);
+        let normal = (positions[b] - positions[a]).cross(positions[c] - positions[a]);
+        for index in [a, b, c] {
+            normals[index] += normal;
+        }
+    }
+    normals
+        .iter()
+        .map(|normal| normal.normalize_or_zero())
+        .collect()
+}
+
+/// An error in the data of a [`LightBakeMesh`], see
+/// [`LightBakeMesh::validate`].
+#[derive(Error, Debug, PartialEq)]
+pub enum LightBakeMeshError {
+    #[error("index {index} is out of bounds for {vertex_count} vertices")]
+    InvalidIndex { index: u32, vertex_count: usize },
+    #[error("there are {count} {attribute} for {vertex_count} vertices")]
+    AttributeCountMismatch {
+        attribute: &'static str,
+        count: usize,
+        vertex_count: usize,
+    },
+}
+
+/// An error that occurs when loading a [`LightBakeScene`].
+#[derive(Error, Debug)]
+pub enum LightBakeSceneLoaderError {
+    #[error("failed to read the light bake scene: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("failed to parse the light bake scene: {0}")]
+    Ron(#[from] SpannedError),
+    #[error("mesh {index} of the light bake scene is invalid: {error}")]
+    InvalidMesh {
+        index: usize,
+        error: LightBakeMeshError,
+    },
+}
+
+/// Loads [`LightBakeScene`]s from `.lightbake.ron` files.
+#[derive(Default)]
+pub struct LightBakeSceneLoader;
+
+impl AssetLoader for LightBakeSceneLoader {
+    type Asset = LightBakeScene;
+    type Settings = ();
+    type Error = LightBakeSceneLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<LightBakeScene, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let scene: LightBakeScene = ron::de::from_bytes(&bytes)?;
+        for (index, mesh) in scene.meshes.iter().enumerate() {
+            mesh.validate()
+                .map_err(|error| LightBakeSceneLoaderError::InvalidMesh { index, error })?;
+        }
+        Ok(scene)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["lightbake.ron"]
+    }
+}
@@ -8,8 +8,8 @@
 //! geometry.
 //!
 //! To use irradiance volumes, you need to precompute, or *bake*, the indirect
-//! light in your scene. Bevy doesn't currently come with a way to do this.
-//! Fortunately, [Blender] provides a [baking tool] as part of the Eevee
+//! light in your scene. Bevy's asset processor can bake them for static
+//! geometry, see [`light_baking`](crate::light_baking). Alternatively, [Blender] provides a [baking tool] as part of the Eevee
 //! renderer, and its irradiance volumes are compatible with those used by Bevy.
 //! The [`bevy-baked-gi`] project provides a tool, `export-blender-gi`, that can
 //! extract the baked irradiance volumes from the Blender `.blend` file and
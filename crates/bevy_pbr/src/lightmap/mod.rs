@@ -1,10 +1,11 @@
 //! Lightmaps, baked lighting textures that can be applied at runtime to provide
 //! diffuse global illumination.
 //!
-//! Bevy doesn't currently have any way to actually bake lightmaps, but they can
-//! be baked in an external tool like [Blender](http://blender.org), for example
-//! with an addon like [The Lightmapper]. The tools in the [`bevy-baked-gi`]
-//! project support other lightmap baking methods.
+//! Lightmaps can be baked by Bevy's asset processor, see
+//! [`light_baking`](crate::light_baking), or in an external tool like
+//! [Blender](http://blender.org), for example with an addon like
+//! [The Lightmapper]. The tools in the [`bevy-baked-gi`] project support other
+//! lightmap baking methods.
 //!
 //! When a [`Lightmap`] component is added to an entity with a [`Mesh3d`] and a
 //! [`MeshMaterial3d<StandardMaterial>`], Bevy applies the lightmap when rendering. The brightness
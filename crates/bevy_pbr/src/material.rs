@@ -12,7 +12,7 @@ use bevy_core_pipeline::{
         AlphaMask3d, Camera3d, Opaque3d, Opaque3dBatchSetKey, Opaque3dBinKey,
         ScreenSpaceTransmissionQuality, Transmissive3d, Transparent3d,
     },
-    oit::OrderIndependentTransparencySettings,
+    oit::{OrderIndependentTransparencyMethod, OrderIndependentTransparencySettings},
     prepass::{
         DeferredPrepass, DepthPrepass, MotionVectorPrepass, NormalPrepass,
         OpaqueNoLightmap3dBatchSetKey, OpaqueNoLightmap3dBinKey,
@@ -270,7 +270,11 @@ where
     fn build(&self, app: &mut App) {
         app.init_asset::<M>()
             .register_type::<MeshMaterial3d<M>>()
-            .add_plugins(RenderAssetPlugin::<PreparedMaterial<M>>::default());
+            .add_plugins(RenderAssetPlugin::<PreparedMaterial<M>>::default())
+            .add_systems(
+                PostUpdate,
+                sync_auto_lod_component::<MeshMaterial3d<M>>.after(spawn_auto_lod_levels),
+            );
 
         if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
             render_app
@@ -644,7 +648,10 @@ pub fn queue_material_meshes<M: Material>(
         Option<&Tonemapping>,
         Option<&DebandDither>,
         Option<&ShadowFilteringMethod>,
-        Has<ScreenSpaceAmbientOcclusion>,
+        (
+            Has<ScreenSpaceAmbientOcclusion>,
+            Has<ScreenSpaceGlobalIllumination>,
+        ),
         (
             Has<NormalPrepass>,
             Has<DepthPrepass>,
@@ -658,7 +665,11 @@ pub fn queue_material_meshes<M: Material>(
             Has<RenderViewLightProbes<EnvironmentMapLight>>,
             Has<RenderViewLightProbes<IrradianceVolume>>,
         ),
-        Has<OrderIndependentTransparencySettings>,
+        (
+            Has<OrderIndependentTransparencySettings>,
+            Option<&OrderIndependentTransparencyMethod>,
+        ),
+        Option<&DebugViewMode>,
     )>,
 ) where
     M::Data: PartialEq + Eq + Hash + Clone,
@@ -670,13 +681,14 @@ pub fn queue_material_meshes<M: Material>(
         tonemapping,
         dither,
         shadow_filter_method,
-        ssao,
+        (ssao, ssgi),
         (normal_prepass, depth_prepass, motion_vector_prepass, deferred_prepass),
         camera_3d,
         temporal_jitter,
         projection,
         (has_environment_maps, has_irradiance_volumes),
-        has_oit,
+        (has_oit, oit_method),
+        debug_view,
     ) in &views
     {
         let (
@@ -732,6 +744,9 @@ pub fn queue_material_meshes<M: Material>(
 
         if has_oit {
             view_key |= MeshPipelineKey::OIT_ENABLED;
+            if oit_method == Some(&OrderIndependentTransparencyMethod::WeightedBlended) {
+                view_key |= MeshPipelineKey::OIT_WEIGHTED_BLENDED;
+            }
         }
 
         if let Some(projection) = projection {
@@ -766,11 +781,17 @@ pub fn queue_material_meshes<M: Material>(
         if ssao {
             view_key |= MeshPipelineKey::SCREEN_SPACE_AMBIENT_OCCLUSION;
         }
+        if ssgi {
+            view_key |= MeshPipelineKey::SCREEN_SPACE_GLOBAL_ILLUMINATION;
+        }
         if let Some(camera_3d) = camera_3d {
             view_key |= screen_space_specular_transmission_pipeline_key(
                 camera_3d.screen_space_specular_transmission_quality,
             );
         }
+        if let Some(debug_view) = debug_view {
+            view_key |= debug_view_pipeline_key(*debug_view);
+        }
 
         let rangefinder = view.rangefinder3d();
         for (render_entity, visible_entity) in visible_entities.iter::<Mesh3d>() {
@@ -816,6 +837,13 @@ pub fn queue_material_meshes<M: Material>(
                 mesh_key |= MeshPipelineKey::VISIBILITY_RANGE_DITHER;
             }
 
+            if mesh_instance
+                .flags
+                .contains(RenderMeshInstanceFlags::PRESKINNED)
+            {
+                mesh_key |= MeshPipelineKey::PRESKINNED;
+            }
+
             if motion_vector_prepass {
                 // If the previous frame have skins or morph targets, note that.
                 if mesh_instance
@@ -0,0 +1,357 @@
+use core::fmt::Write;
+
+use thiserror::Error;
+
+use super::{
+    MaterialGraph, MaterialGraphNode, MaterialGraphNodeId, MAX_MATERIAL_GRAPH_PARAMETERS,
+    MAX_MATERIAL_GRAPH_TEXTURES,
+};
+
+/// An error that prevents a [`MaterialGraph`] from being compiled.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum MaterialGraphError {
+    /// The graph refers to a node that isn't in [`MaterialGraph::nodes`].
+    #[error("The graph refers to node {}, which doesn't exist", .0.0)]
+    MissingNode(MaterialGraphNodeId),
+    /// A node depends on its own value.
+    #[error("Node {} depends on its own value", .0.0)]
+    Cycle(MaterialGraphNodeId),
+    /// A `Texture` node samples a texture that materials can't hold.
+    #[error(
+        "Node {} samples texture {slot}, but materials only have {max} textures",
+        .node.0,
+        max = MAX_MATERIAL_GRAPH_TEXTURES
+    )]
+    InvalidTextureSlot {
+        /// The node that samples the texture.
+        node: MaterialGraphNodeId,
+        /// The index of the texture.
+        slot: u32,
+    },
+    /// A `Parameter` node reads a parameter that materials can't hold.
+    #[error(
+        "Node {} reads parameter {index}, but materials only have {max} parameters",
+        .node.0,
+        max = MAX_MATERIAL_GRAPH_PARAMETERS
+    )]
+    InvalidParameter {
+        /// The node that reads the parameter.
+        node: MaterialGraphNodeId,
+        /// The index of the parameter.
+        index: u32,
+    },
+    /// A `Swizzle` node picks a component other than 0 to 3.
+    #[error("Node {} picks a component other than 0 to 3", .0.0)]
+    InvalidSwizzle(MaterialGraphNodeId),
+    /// A `Constant` node has an infinite or NaN component.
+    #[error("Node {} has an infinite or NaN component", .0.0)]
+    NonFiniteConstant(MaterialGraphNodeId),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum NodeState {
+    Unvisited,
+    Visiting,
+    Emitted,
+}
+
+struct Compiler<'a> {
+    graph: &'a MaterialGraph,
+    states: Vec<NodeState>,
+    body: String,
+    uses_time: bool,
+}
+
+impl Compiler<'_> {
+    /// Emits the nodes that `id` depends on, and then `id` itself, unless they were already
+    /// emitted.
+    fn visit(&mut self, id: MaterialGraphNodeId) -> Result<(), MaterialGraphError> {
+        let index = id.0 as usize;
+        match self.states.get(index) {
+            None => return Err(MaterialGraphError::MissingNode(id)),
+            Some(NodeState::Emitted) => return Ok(()),
+            Some(NodeState::Visiting) => return Err(MaterialGraphError::Cycle(id)),
+            Some(NodeState::Unvisited) => {}
+        }
+
+        self.states[index] = NodeState::Visiting;
+        let graph = self.graph;
+        let node = &graph.nodes[index];
+        for input in node.inputs() {
+            self.visit(input)?;
+        }
+        let expression = self.expression(id, node)?;
+        writeln!(&mut self.body, "    let node_{index} = {expression};").unwrap();
+        self.states[index] = NodeState::Emitted;
+        Ok(())
+    }
+
+    fn expression(
+        &mut self,
+        id: MaterialGraphNodeId,
+        node: &MaterialGraphNode,
+    ) -> Result<String, MaterialGraphError> {
+        use MaterialGraphNode::*;
+
+        let n = |id: &MaterialGraphNodeId| format!("node_{}", id.0);
+        Ok(match node {
+            Constant(value) => {
+                if !value.is_finite() {
+                    return Err(MaterialGraphError::NonFiniteConstant(id));
+                }
+                format!(
+                    "vec4<f32>({:?}, {:?}, {:?}, {:?})",
+                    value.x, value.y, value.z, value.w
+                )
+            }
+            Parameter(index) => {
+                if *index as usize >= MAX_MATERIAL_GRAPH_PARAMETERS {
+                    return Err(MaterialGraphError::InvalidParameter {
+                        node: id,
+                        index: *index,
+                    });
+                }
+                format!("material_graph_parameters[{index}]")
+            }
+            Uv => "vec4<f32>(uv, 0.0, 0.0)".to_owned(),
+            WorldPosition => "vec4<f32>(in.world_position.xyz, 1.0)".to_owned(),
+            WorldNormal => "vec4<f32>(pbr_input.world_normal, 0.0)".to_owned(),
+            Time => {
+                self.uses_time = true;
+                "vec4<f32>(globals.time)".to_owned()
+            }
+            Texture { slot, uv } => {
+                if *slot as usize >= MAX_MATERIAL_GRAPH_TEXTURES {
+                    return Err(MaterialGraphError::InvalidTextureSlot {
+                        node: id,
+                        slot: *slot,
+                    });
+                }
+                format!(
+                    "textureSample(material_graph_texture_{slot}, material_graph_sampler_{slot}, {}.xy)",
+                    n(uv)
+                )
+            }
+            Add(a, b) => format!("{} + {}", n(a), n(b)),
+            Subtract(a, b) => format!("{} - {}", n(a), n(b)),
+            Multiply(a, b) => format!("{} * {}", n(a), n(b)),
+            Divide(a, b) => format!("{} / {}", n(a), n(b)),
+            Min(a, b) => format!("min({}, {})", n(a), n(b)),
+            Max(a, b) => format!("max({}, {})", n(a), n(b)),
+            Power(a, b) => format!("pow({}, {})", n(a), n(b)),
+            Dot(a, b) => format!("vec4<f32>(dot({}.xyz, {}.xyz))", n(a), n(b)),
+            Mix { a, b, t } => format!("mix({}, {}, {})", n(a), n(b), n(t)),
+            Sin(a) => format!("sin({})", n(a)),
+            Cos(a) => format!("cos({})", n(a)),
+            Abs(a) => format!("abs({})", n(a)),
+            Fract(a) => format!("fract({})", n(a)),
+            Saturate(a) => format!("saturate({})", n(a)),
+            Swizzle { input, components } => {
+                let mut swizzle = String::with_capacity(4);
+                for &component in components {
+                    swizzle.push(match component {
+                        0 => 'x',
+                        1 => 'y',
+                        2 => 'z',
+                        3 => 'w',
+                        _ => return Err(MaterialGraphError::InvalidSwizzle(id)),
+                    });
+                }
+                format!("{}.{swizzle}", n(input))
+            }
+        })
+    }
+}
+
+impl MaterialGraphNode {
+    /// Returns the nodes that this node reads.
+    fn inputs(&self) -> Vec<MaterialGraphNodeId> {
+        use MaterialGraphNode::*;
+
+        match *self {
+            Constant(_) | Parameter(_) | Uv | WorldPosition | WorldNormal | Time => vec![],
+            Texture { uv, .. } => vec![uv],
+            Add(a, b)
+            | Subtract(a, b)
+            | Multiply(a, b)
+            | Divide(a, b)
+            | Min(a, b)
+            | Max(a, b)
+            | Power(a, b)
+            | Dot(a, b) => vec![a, b],
+            Mix { a, b, t } => vec![a, b, t],
+            Sin(a) | Cos(a) | Abs(a) | Fract(a) | Saturate(a) | Swizzle { input: a, .. } => vec![a],
+        }
+    }
+}
+
+pub(super) fn compile(graph: &MaterialGraph) -> Result<String, MaterialGraphError> {
+    let mut compiler = Compiler {
+        graph,
+        states: vec![NodeState::Unvisited; graph.nodes.len()],
+        body: String::new(),
+        uses_time: false,
+    };
+
+    let mut outputs = String::new();
+    let output_templates = [
+        (graph.base_color, "pbr_input.material.base_color = {};"),
+        (
+            graph.emissive,
+            "pbr_input.material.emissive = vec4<f32>({}.xyz, pbr_input.material.emissive.a);",
+        ),
+        (
+            graph.perceptual_roughness,
+            "pbr_input.material.perceptual_roughness = {}.x;",
+        ),
+        (graph.metallic, "pbr_input.material.metallic = {}.x;"),
+    ];
+    for (output, template) in output_templates {
+        if let Some(id) = output {
+            compiler.visit(id)?;
+            let line = template.replace("{}", &format!("node_{}", id.0));
+            writeln!(&mut outputs, "    {line}").unwrap();
+        }
+    }
+
+    let globals = if compiler.uses_time { GLOBALS } else { "" };
+    Ok(format!(
+        "{HEADER}{globals}{BINDINGS}{FRAGMENT_START}{}{}{FRAGMENT_END}",
+        compiler.body, outputs
+    ))
+}
+
+const HEADER: &str = r"// Generated from a `MaterialGraph`.
+
+#import bevy_pbr::{
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::alpha_discard,
+}
+
+#ifdef PREPASS_PIPELINE
+#import bevy_pbr::{
+    prepass_io::{VertexOutput, FragmentOutput},
+    pbr_deferred_functions::deferred_output,
+}
+#else
+#import bevy_pbr::{
+    forward_io::{VertexOutput, FragmentOutput},
+    pbr_functions::{apply_pbr_lighting, main_pass_post_lighting_processing},
+}
+#endif
+";
+
+// The globals are bound differently in the prepass and in the main pass.
+const GLOBALS: &str = r"
+#ifdef PREPASS_PIPELINE
+#import bevy_render::globals::Globals
+@group(0) @binding(1) var<uniform> globals: Globals;
+#else
+#import bevy_pbr::mesh_view_bindings::globals
+#endif
+";
+
+const BINDINGS: &str = r"
+@group(2) @binding(100) var material_graph_texture_0: texture_2d<f32>;
+@group(2) @binding(101) var material_graph_sampler_0: sampler;
+@group(2) @binding(102) var material_graph_texture_1: texture_2d<f32>;
+@group(2) @binding(103) var material_graph_sampler_1: sampler;
+@group(2) @binding(104) var material_graph_texture_2: texture_2d<f32>;
+@group(2) @binding(105) var material_graph_sampler_2: sampler;
+@group(2) @binding(106) var material_graph_texture_3: texture_2d<f32>;
+@group(2) @binding(107) var material_graph_sampler_3: sampler;
+@group(2) @binding(108) var<uniform> material_graph_parameters: array<vec4<f32>, 8>;
+";
+
+const FRAGMENT_START: &str = r"
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+
+#ifdef VERTEX_UVS_A
+    let uv = in.uv;
+#else
+    let uv = vec2<f32>(0.0);
+#endif
+
+";
+
+const FRAGMENT_END: &str = r"
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+
+#ifdef PREPASS_PIPELINE
+    let out = deferred_output(in, pbr_input);
+#else
+    var out: FragmentOutput;
+    out.color = apply_pbr_lighting(pbr_input);
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+#endif
+
+    return out;
+}
+";
+
+#[cfg(test)]
+mod tests {
+    use bevy_math::Vec4;
+
+    use super::MaterialGraphError;
+    use crate::material_graph::{MaterialGraph, MaterialGraphNode, MaterialGraphNodeId};
+
+    #[test]
+    fn compile_graph() {
+        let mut graph = MaterialGraph::default();
+        let uv = graph.add(MaterialGraphNode::Uv);
+        let texture = graph.add(MaterialGraphNode::Texture { slot: 1, uv });
+        let tint = graph.add(MaterialGraphNode::Parameter(0));
+        let color = graph.add(MaterialGraphNode::Multiply(texture, tint));
+        // Not used by any output.
+        graph.add(MaterialGraphNode::Time);
+        graph.base_color = Some(color);
+        graph.metallic = Some(tint);
+
+        let source = graph.compile().unwrap();
+        assert!(source.contains("let node_1 = textureSample(material_graph_texture_1, material_graph_sampler_1, node_0.xy);"));
+        assert!(source.contains("let node_3 = node_1 * node_2;"));
+        assert!(source.contains("pbr_input.material.base_color = node_3;"));
+        assert!(source.contains("pbr_input.material.metallic = node_2.x;"));
+        assert!(!source.contains("node_4"));
+        assert!(!source.contains("globals"));
+        // Each node is emitted once, after its inputs.
+        assert_eq!(source.matches("let node_2 =").count(), 1);
+        assert!(source.find("let node_2 =") < source.find("let node_3 ="));
+    }
+
+    #[test]
+    fn invalid_graphs() {
+        let mut graph = MaterialGraph {
+            nodes: vec![
+                MaterialGraphNode::Add(MaterialGraphNodeId(1), MaterialGraphNodeId(1)),
+                MaterialGraphNode::Sin(MaterialGraphNodeId(0)),
+            ],
+            base_color: Some(MaterialGraphNodeId(0)),
+            ..Default::default()
+        };
+        assert_eq!(
+            graph.compile(),
+            Err(MaterialGraphError::Cycle(MaterialGraphNodeId(0)))
+        );
+
+        graph.nodes[1] = MaterialGraphNode::Sin(MaterialGraphNodeId(2));
+        assert_eq!(
+            graph.compile(),
+            Err(MaterialGraphError::MissingNode(MaterialGraphNodeId(2)))
+        );
+
+        graph.nodes[1] = MaterialGraphNode::Constant(Vec4::splat(f32::NAN));
+        assert_eq!(
+            graph.compile(),
+            Err(MaterialGraphError::NonFiniteConstant(MaterialGraphNodeId(
+                1
+            )))
+        );
+    }
+}
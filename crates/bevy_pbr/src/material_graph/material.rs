@@ -0,0 +1,117 @@
+use bevy_asset::{Asset, AssetId, Handle};
+use bevy_image::Image;
+use bevy_math::Vec4;
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::{
+    mesh::MeshVertexBufferLayoutRef,
+    render_resource::{
+        AsBindGroup, RenderPipelineDescriptor, Shader, ShaderRef, SpecializedMeshPipelineError,
+    },
+};
+
+use super::{material_graph_shader, MaterialGraph};
+use crate::{
+    ExtendedMaterial, MaterialExtension, MaterialExtensionKey, MaterialExtensionPipeline,
+    StandardMaterial,
+};
+
+/// The number of textures that a [`MaterialGraphExtension`] can hold.
+pub const MAX_MATERIAL_GRAPH_TEXTURES: usize = 4;
+
+/// The number of parameters that a [`MaterialGraphExtension`] can hold.
+pub const MAX_MATERIAL_GRAPH_PARAMETERS: usize = 8;
+
+/// The fragment shader of [`MaterialGraphExtension`] until it's specialized, at which point it's
+/// replaced by the shader compiled from the graph of the material.
+const MATERIAL_GRAPH_PLACEHOLDER_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(16302569042388691763);
+
+/// A [`StandardMaterial`] whose inputs are computed by a [`MaterialGraph`].
+///
+/// The properties of the standard material that the graph doesn't output, such as its alpha
+/// mode, apply as usual.
+pub type MaterialGraphMaterial = ExtendedMaterial<StandardMaterial, MaterialGraphExtension>;
+
+/// The [`MaterialExtension`] of a [`MaterialGraphMaterial`].
+///
+/// Materials that use the same graph share their shader, and only differ by their textures and
+/// parameters. Meshes with this material aren't drawn until the graph is loaded and compiled.
+#[derive(Asset, AsBindGroup, Reflect, Clone, Debug, Default)]
+#[reflect(Default, Debug)]
+#[bind_group_data(MaterialGraphKey)]
+pub struct MaterialGraphExtension {
+    /// The graph that computes the inputs of the material.
+    pub graph: Handle<MaterialGraph>,
+
+    /// The texture sampled by the `Texture` nodes of slot 0.
+    #[texture(100)]
+    #[sampler(101)]
+    pub texture_0: Option<Handle<Image>>,
+
+    /// The texture sampled by the `Texture` nodes of slot 1.
+    #[texture(102)]
+    #[sampler(103)]
+    pub texture_1: Option<Handle<Image>>,
+
+    /// The texture sampled by the `Texture` nodes of slot 2.
+    #[texture(104)]
+    #[sampler(105)]
+    pub texture_2: Option<Handle<Image>>,
+
+    /// The texture sampled by the `Texture` nodes of slot 3.
+    #[texture(106)]
+    #[sampler(107)]
+    pub texture_3: Option<Handle<Image>>,
+
+    /// The values of the `Parameter` nodes of the graph, by index.
+    #[uniform(108)]
+    pub parameters: [Vec4; MAX_MATERIAL_GRAPH_PARAMETERS],
+}
+
+impl MaterialGraphExtension {
+    /// Creates an extension that uses `graph`, without textures, and with all parameters set to
+    /// zero.
+    pub fn new(graph: Handle<MaterialGraph>) -> Self {
+        Self {
+            graph,
+            ..Default::default()
+        }
+    }
+}
+
+/// The key used to specialize the pipelines of a [`MaterialGraphMaterial`], which selects the
+/// shader of its graph.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MaterialGraphKey(pub AssetId<MaterialGraph>);
+
+impl From<&MaterialGraphExtension> for MaterialGraphKey {
+    fn from(extension: &MaterialGraphExtension) -> Self {
+        Self(extension.graph.id())
+    }
+}
+
+impl MaterialExtension for MaterialGraphExtension {
+    fn fragment_shader() -> ShaderRef {
+        MATERIAL_GRAPH_PLACEHOLDER_SHADER_HANDLE.into()
+    }
+
+    fn deferred_fragment_shader() -> ShaderRef {
+        MATERIAL_GRAPH_PLACEHOLDER_SHADER_HANDLE.into()
+    }
+
+    fn specialize(
+        _pipeline: &MaterialExtensionPipeline,
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayoutRef,
+        key: MaterialExtensionKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        // Prepass pipelines that don't use the graph, such as the ones of shadows, keep their own
+        // fragment shader.
+        if let Some(fragment) = &mut descriptor.fragment {
+            if fragment.shader.id() == MATERIAL_GRAPH_PLACEHOLDER_SHADER_HANDLE.id() {
+                fragment.shader = material_graph_shader(key.bind_group_data.0);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,271 @@
+//! Materials described by graphs of nodes instead of hand-written WGSL.
+//!
+//! A [`MaterialGraph`] is a small expression graph that computes the base color, emissive,
+//! roughness and metallic inputs of a [`StandardMaterial`]. Graphs are compiled to the fragment
+//! shader of a [`MaterialGraphMaterial`] when they're loaded or modified, so tools can author
+//! materials as data, in `.matgraph.ron` files or at runtime, without a custom [`AsBindGroup`]
+//! implementation.
+//!
+//! All values in a graph are `vec4<f32>`s: scalars are splatted to all four components, and each
+//! output reads the components it needs, so nodes can be connected without a type system.
+//!
+//! [`StandardMaterial`]: crate::StandardMaterial
+//! [`AsBindGroup`]: bevy_render::render_resource::AsBindGroup
+
+mod compile;
+mod material;
+
+use std::io;
+
+use bevy_app::{App, Plugin, PostUpdate};
+use bevy_asset::{
+    io::Reader, Asset, AssetApp, AssetEvent, AssetId, AssetLoader, Assets, Handle, LoadContext,
+};
+use bevy_ecs::{
+    event::EventReader,
+    system::{Res, ResMut},
+};
+use bevy_math::Vec4;
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::render_resource::Shader;
+use bevy_utils::FixedHasher;
+use core::hash::BuildHasher;
+use ron::de::SpannedError;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::error;
+
+use crate::MaterialPlugin;
+
+pub use compile::MaterialGraphError;
+pub use material::{
+    MaterialGraphExtension, MaterialGraphKey, MaterialGraphMaterial, MAX_MATERIAL_GRAPH_PARAMETERS,
+    MAX_MATERIAL_GRAPH_TEXTURES,
+};
+
+/// Adds support for [`MaterialGraph`]s and [`MaterialGraphMaterial`]s.
+pub struct MaterialGraphPlugin;
+
+impl Plugin for MaterialGraphPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<MaterialGraph>()
+            .init_asset_loader::<MaterialGraphLoader>()
+            .register_asset_reflect::<MaterialGraph>()
+            .register_type::<MaterialGraphExtension>()
+            .add_plugins(MaterialPlugin::<MaterialGraphMaterial>::default())
+            .add_systems(PostUpdate, compile_material_graphs);
+    }
+}
+
+/// A graph of nodes that computes the inputs of a [`StandardMaterial`], compiled to the fragment
+/// shader of the [`MaterialGraphMaterial`]s that use it.
+///
+/// Nodes refer to each other by [`MaterialGraphNodeId`], and may be listed in any order, as long as
+/// the graph has no cycles. Nodes that no output depends on are ignored. Outputs that aren't set
+/// keep the value of the [`StandardMaterial`] of the material.
+///
+/// [`StandardMaterial`]: crate::StandardMaterial
+#[derive(Asset, Reflect, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[reflect(Default, Debug)]
+pub struct MaterialGraph {
+    /// The nodes of the graph.
+    pub nodes: Vec<MaterialGraphNode>,
+    /// The node whose value replaces the base color, including alpha.
+    #[serde(default)]
+    pub base_color: Option<MaterialGraphNodeId>,
+    /// The node whose `xyz` components replace the emissive color.
+    #[serde(default)]
+    pub emissive: Option<MaterialGraphNodeId>,
+    /// The node whose `x` component replaces the perceptual roughness.
+    #[serde(default)]
+    pub perceptual_roughness: Option<MaterialGraphNodeId>,
+    /// The node whose `x` component replaces the metallic factor.
+    #[serde(default)]
+    pub metallic: Option<MaterialGraphNodeId>,
+}
+
+impl MaterialGraph {
+    /// Adds a node to the graph, and returns its ID.
+    pub fn add(&mut self, node: MaterialGraphNode) -> MaterialGraphNodeId {
+        self.nodes.push(node);
+        MaterialGraphNodeId(self.nodes.len() as u32 - 1)
+    }
+
+    /// Compiles the graph to the WGSL source of the fragment shader of a
+    /// [`MaterialGraphMaterial`].
+    ///
+    /// [`MaterialGraphPlugin`] does this automatically for the graphs in [`Assets<MaterialGraph>`].
+    pub fn compile(&self) -> Result<String, MaterialGraphError> {
+        compile::compile(self)
+    }
+}
+
+/// The ID of a node of a [`MaterialGraph`], which is its index in [`MaterialGraph::nodes`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Reflect, Serialize, Deserialize)]
+#[reflect(Debug, PartialEq, Hash)]
+#[serde(transparent)]
+pub struct MaterialGraphNodeId(pub u32);
+
+/// A node of a [`MaterialGraph`].
+///
+/// Every node produces a `vec4<f32>`. Operations on two values apply to each component.
+#[derive(Clone, Debug, PartialEq, Reflect, Serialize, Deserialize)]
+#[reflect(Debug, PartialEq)]
+pub enum MaterialGraphNode {
+    /// A constant value.
+    Constant(Vec4),
+    /// A value set on each material, from [`MaterialGraphExtension::parameters`].
+    Parameter(u32),
+    /// The first UV channel of the mesh in `xy`, or zero if the mesh doesn't have one.
+    Uv,
+    /// The world space position of the fragment in `xyz`, and 1 in `w`.
+    WorldPosition,
+    /// The world space normal of the mesh in `xyz`, and 0 in `w`.
+    WorldNormal,
+    /// The time since the startup of the app, in seconds, in all components.
+    Time,
+    /// Samples one of the textures of the material, from [`MaterialGraphExtension::texture_0`] to
+    /// [`MaterialGraphExtension::texture_3`], at the coordinates in the `xy` components of the
+    /// input.
+    Texture {
+        /// The index of the texture, from 0 to 3.
+        slot: u32,
+        /// The node that computes the texture coordinates.
+        uv: MaterialGraphNodeId,
+    },
+    /// The sum of two values.
+    Add(MaterialGraphNodeId, MaterialGraphNodeId),
+    /// The first value minus the second.
+    Subtract(MaterialGraphNodeId, MaterialGraphNodeId),
+    /// The product of two values.
+    Multiply(MaterialGraphNodeId, MaterialGraphNodeId),
+    /// The first value divided by the second.
+    Divide(MaterialGraphNodeId, MaterialGraphNodeId),
+    /// The smaller of two values.
+    Min(MaterialGraphNodeId, MaterialGraphNodeId),
+    /// The larger of two values.
+    Max(MaterialGraphNodeId, MaterialGraphNodeId),
+    /// The first value raised to the power of the second.
+    Power(MaterialGraphNodeId, MaterialGraphNodeId),
+    /// The dot product of the `xyz` components of two values, in all components.
+    Dot(MaterialGraphNodeId, MaterialGraphNodeId),
+    /// The linear interpolation from `a` to `b` by `t`.
+    Mix {
+        /// The value when `t` is 0.
+        a: MaterialGraphNodeId,
+        /// The value when `t` is 1.
+        b: MaterialGraphNodeId,
+        /// The interpolation factor.
+        t: MaterialGraphNodeId,
+    },
+    /// The sine of a value, in radians.
+    Sin(MaterialGraphNodeId),
+    /// The cosine of a value, in radians.
+    Cos(MaterialGraphNodeId),
+    /// The absolute value of a value.
+    Abs(MaterialGraphNodeId),
+    /// The fractional part of a value.
+    Fract(MaterialGraphNodeId),
+    /// A value clamped between 0 and 1.
+    Saturate(MaterialGraphNodeId),
+    /// Reorders the components of a value: each entry is the index, from 0 to 3, of the
+    /// component of the input that goes in that component of the output.
+    Swizzle {
+        /// The node whose components are reordered.
+        input: MaterialGraphNodeId,
+        /// The index of the input component of each output component.
+        components: [u8; 4],
+    },
+}
+
+/// Loads [`MaterialGraph`]s from `.matgraph.ron` files.
+///
+/// Graphs are compiled as they're loaded, so loading fails if they're invalid.
+#[derive(Default)]
+pub struct MaterialGraphLoader;
+
+/// An error that can occur when loading a [`MaterialGraph`].
+#[derive(Error, Debug)]
+pub enum MaterialGraphLoadError {
+    /// An I/O error occurred.
+    #[error("I/O")]
+    Io(#[from] io::Error),
+    /// An error occurred in RON deserialization, and the location of the error is supplied.
+    #[error("RON deserialization")]
+    SpannedRon(#[from] SpannedError),
+    /// The graph couldn't be compiled.
+    #[error(transparent)]
+    Compile(#[from] MaterialGraphError),
+}
+
+impl AssetLoader for MaterialGraphLoader {
+    type Asset = MaterialGraph;
+    type Settings = ();
+    type Error = MaterialGraphLoadError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<MaterialGraph, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let graph: MaterialGraph = ron::de::from_bytes(&bytes)?;
+        graph.compile()?;
+        Ok(graph)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["matgraph.ron"]
+    }
+}
+
+/// Returns the handle of the shader compiled from the [`MaterialGraph`] with the given ID.
+///
+/// The shader is inserted into [`Assets<Shader>`] under an ID derived from the one of the graph,
+/// so that materials only need to know the graph to find their shader.
+pub fn material_graph_shader(graph: AssetId<MaterialGraph>) -> Handle<Shader> {
+    Handle::weak_from_u128(material_graph_shader_uuid(graph))
+}
+
+fn material_graph_shader_uuid(graph: AssetId<MaterialGraph>) -> u128 {
+    let high = FixedHasher.hash_one((graph, 0u8));
+    let low = FixedHasher.hash_one((graph, 1u8));
+    (u128::from(high) << 64) | u128::from(low)
+}
+
+/// Compiles the [`MaterialGraph`]s that were added or modified, and removes the shaders of the
+/// ones that were removed.
+fn compile_material_graphs(
+    mut events: EventReader<AssetEvent<MaterialGraph>>,
+    graphs: Res<Assets<MaterialGraph>>,
+    mut shaders: ResMut<Assets<Shader>>,
+) {
+    for event in events.read() {
+        match *event {
+            AssetEvent::Added { id } | AssetEvent::Modified { id } => {
+                let Some(graph) = graphs.get(id) else {
+                    continue;
+                };
+                match graph.compile() {
+                    Ok(source) => {
+                        let path = format!(
+                            "material_graph_{:032x}.wgsl",
+                            material_graph_shader_uuid(id)
+                        );
+                        shaders.insert(
+                            material_graph_shader(id).id(),
+                            Shader::from_wgsl(source, path),
+                        );
+                    }
+                    Err(err) => error!("Failed to compile material graph {id:?}: {err}"),
+                }
+            }
+            AssetEvent::Removed { id } => {
+                shaders.remove(material_graph_shader(id).id());
+            }
+            AssetEvent::Unused { .. } | AssetEvent::LoadedWithDependencies { .. } => {}
+        }
+    }
+}
@@ -126,7 +126,7 @@ fn cull_clusters(
     aabb_width_pixels = (aabb.z - aabb.x) * view.viewport.z;
     aabb_height_pixels = (aabb.w - aabb.y) * view.viewport.w;
 #endif
-    let cluster_is_small = all(vec2(aabb_width_pixels, aabb_height_pixels) < vec2(64.0));
+    let cluster_is_small = all(vec2(aabb_width_pixels, aabb_height_pixels) < vec2(constants.software_raster_max_cluster_size));
 
     // Let the hardware rasterizer handle near-plane clipping
     let not_intersects_near_plane = dot(view.frustum[4u], culling_bounding_sphere_center) > culling_bounding_sphere_radius;
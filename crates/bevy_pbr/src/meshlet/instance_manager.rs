@@ -121,6 +121,7 @@ impl InstanceManager {
 
         let mesh_uniform = MeshUniform::new(
             &transforms,
+            instance,
             0,
             mesh_material_binding_id.slot,
             None,
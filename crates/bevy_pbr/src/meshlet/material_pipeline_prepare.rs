@@ -9,7 +9,9 @@ use crate::{
 use bevy_asset::AssetServer;
 use bevy_core_pipeline::{
     core_3d::Camera3d,
-    prepass::{DeferredPrepass, DepthPrepass, MotionVectorPrepass, NormalPrepass},
+    prepass::{
+        DeferredPrepass, DepthPrepass, EntityIndexPrepass, MotionVectorPrepass, NormalPrepass,
+    },
     tonemapping::{DebandDither, Tonemapping},
 };
 use bevy_derive::{Deref, DerefMut};
@@ -48,7 +50,10 @@ pub fn prepare_material_meshlet_meshes_main_opaque_pass<M: Material>(
             Option<&Tonemapping>,
             Option<&DebandDither>,
             Option<&ShadowFilteringMethod>,
-            Has<ScreenSpaceAmbientOcclusion>,
+            (
+                Has<ScreenSpaceAmbientOcclusion>,
+                Has<ScreenSpaceGlobalIllumination>,
+            ),
             (
                 Has<NormalPrepass>,
                 Has<DepthPrepass>,
@@ -73,7 +78,7 @@ pub fn prepare_material_meshlet_meshes_main_opaque_pass<M: Material>(
         tonemapping,
         dither,
         shadow_filter_method,
-        ssao,
+        (ssao, ssgi),
         (normal_prepass, depth_prepass, motion_vector_prepass, deferred_prepass),
         temporal_jitter,
         projection,
@@ -143,6 +148,10 @@ pub fn prepare_material_meshlet_meshes_main_opaque_pass<M: Material>(
             view_key |= MeshPipelineKey::SCREEN_SPACE_AMBIENT_OCCLUSION;
         }
 
+        if ssgi {
+            view_key |= MeshPipelineKey::SCREEN_SPACE_GLOBAL_ILLUMINATION;
+        }
+
         view_key |= MeshPipelineKey::from_primitive_topology(PrimitiveTopology::TriangleList);
 
         for material_id in render_material_instances.values().collect::<HashSet<_>>() {
@@ -262,7 +271,12 @@ pub fn prepare_material_meshlet_meshes_prepass<M: Material>(
             &mut MeshletViewMaterialsPrepass,
             &mut MeshletViewMaterialsDeferredGBufferPrepass,
             &ExtractedView,
-            AnyOf<(&NormalPrepass, &MotionVectorPrepass, &DeferredPrepass)>,
+            AnyOf<(
+                &NormalPrepass,
+                &MotionVectorPrepass,
+                &DeferredPrepass,
+                &EntityIndexPrepass,
+            )>,
         ),
         With<Camera3d>,
     >,
@@ -275,7 +289,7 @@ pub fn prepare_material_meshlet_meshes_prepass<M: Material>(
         mut materials,
         mut deferred_materials,
         view,
-        (normal_prepass, motion_vector_prepass, deferred_prepass),
+        (normal_prepass, motion_vector_prepass, deferred_prepass, entity_index_prepass),
     ) in &mut views
     {
         let mut view_key =
@@ -287,6 +301,9 @@ pub fn prepare_material_meshlet_meshes_prepass<M: Material>(
         if motion_vector_prepass.is_some() {
             view_key |= MeshPipelineKey::MOTION_VECTOR_PREPASS;
         }
+        if entity_index_prepass.is_some() {
+            view_key |= MeshPipelineKey::ENTITY_INDEX_PREPASS;
+        }
 
         view_key |= MeshPipelineKey::from_primitive_topology(PrimitiveTopology::TriangleList);
 
@@ -312,7 +329,10 @@ pub fn prepare_material_meshlet_meshes_prepass<M: Material>(
             );
             if deferred_prepass.is_some() && material_wants_deferred {
                 view_key |= MeshPipelineKey::DEFERRED_PREPASS;
-            } else if normal_prepass.is_none() && motion_vector_prepass.is_none() {
+            } else if normal_prepass.is_none()
+                && motion_vector_prepass.is_none()
+                && entity_index_prepass.is_none()
+            {
                 continue;
             }
 
@@ -202,6 +202,10 @@ impl ViewNode for MeshletPrepassNode {
             // Use None in place of Deferred attachments
             None,
             None,
+            view_prepass_textures
+                .entity_index
+                .as_ref()
+                .map(|entity_index_texture| entity_index_texture.get_attachment()),
         ];
 
         let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
@@ -330,6 +334,10 @@ impl ViewNode for MeshletDeferredGBufferPrepassNode {
                 .deferred_lighting_pass_id
                 .as_ref()
                 .map(|deferred_lighting_pass_id| deferred_lighting_pass_id.get_attachment()),
+            view_prepass_textures
+                .entity_index
+                .as_ref()
+                .map(|entity_index_texture| entity_index_texture.get_attachment()),
         ];
 
         let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
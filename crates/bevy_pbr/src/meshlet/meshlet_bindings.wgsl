@@ -60,7 +60,7 @@ var<push_constant> scene_instance_count: u32;
 #endif
 
 #ifdef MESHLET_CULLING_PASS
-struct Constants { scene_cluster_count: u32, meshlet_raster_cluster_rightmost_slot: u32 }
+struct Constants { scene_cluster_count: u32, meshlet_raster_cluster_rightmost_slot: u32, software_raster_max_cluster_size: f32 }
 var<push_constant> constants: Constants;
 @group(0) @binding(0) var<storage, read> meshlet_cluster_meshlet_ids: array<u32>; // Per cluster
 @group(0) @binding(1) var<storage, read> meshlet_bounding_spheres: array<MeshletBoundingSpheres>; // Per meshlet
@@ -117,6 +117,10 @@ const MESHLET_MESH_MATERIAL_SHADER_HANDLE: Handle<Shader> =
 /// You must use one or the other by setting [`crate::DefaultOpaqueRendererMethod`].
 /// Do not override [`crate::Material::opaque_render_method`] for any material when using this plugin.
 ///
+/// With [`RenderDiagnosticsPlugin`](bevy_render::diagnostic::RenderDiagnosticsPlugin), the number of clusters
+/// rasterized in software and in hardware each frame, summed over all views, is recorded under the
+/// `render/meshlet/software_raster_clusters` and `render/meshlet/hardware_raster_clusters` diagnostic paths.
+///
 /// ![A render of the Stanford dragon as a `MeshletMesh`](https://raw.githubusercontent.com/bevyengine/bevy/main/crates/bevy_pbr/src/meshlet/meshlet_preview.png)
 pub struct MeshletPlugin {
     /// The maximum amount of clusters that can be processed at once,
@@ -128,9 +132,20 @@ pub struct MeshletPlugin {
     ///
     /// Must not be greater than 2^25.
     pub cluster_buffer_slots: u32,
+    /// Clusters smaller than this many pixels on screen, along both axes, are rasterized in a compute shader
+    /// instead of by the hardware rasterizer, which is inefficient for tiny triangles.
+    ///
+    /// Clusters intersecting the camera's near plane are always rasterized by the hardware rasterizer.
+    /// Set to `0.0` to disable software rasterization.
+    ///
+    /// Defaults to [`MeshletPlugin::DEFAULT_SOFTWARE_RASTER_MAX_CLUSTER_SIZE`].
+    pub software_raster_max_cluster_size: f32,
 }
 
 impl MeshletPlugin {
+    /// The default value of [`MeshletPlugin::software_raster_max_cluster_size`].
+    pub const DEFAULT_SOFTWARE_RASTER_MAX_CLUSTER_SIZE: f32 = 64.0;
+
     /// [`WgpuFeatures`] required for this plugin to function.
     pub fn required_wgpu_features() -> WgpuFeatures {
         WgpuFeatures::SHADER_INT64_ATOMIC_MIN_MAX
@@ -272,6 +287,7 @@ impl Plugin for MeshletPlugin {
             .insert_resource(InstanceManager::new())
             .insert_resource(ResourceManager::new(
                 self.cluster_buffer_slots,
+                self.software_raster_max_cluster_size,
                 &render_device,
             ))
             .init_resource::<MeshletPipelines>()
@@ -28,9 +28,11 @@ pub struct ResourceManager {
     /// Intermediate buffer of cluster IDs for use with rasterizing the visibility buffer
     visibility_buffer_raster_clusters: Buffer,
     /// Intermediate buffer of count of clusters to software rasterize
-    software_raster_cluster_count: Buffer,
+    pub software_raster_cluster_count: Buffer,
     /// Rightmost slot index of [`Self::visibility_buffer_raster_clusters`]
     raster_cluster_rightmost_slot: u32,
+    /// Screen-space size in pixels below which clusters are software rasterized
+    software_raster_max_cluster_size: f32,
 
     /// Per-cluster instance ID
     cluster_instance_ids: Option<Buffer>,
@@ -58,7 +60,11 @@ pub struct ResourceManager {
 }
 
 impl ResourceManager {
-    pub fn new(cluster_buffer_slots: u32, render_device: &RenderDevice) -> Self {
+    pub fn new(
+        cluster_buffer_slots: u32,
+        software_raster_max_cluster_size: f32,
+        render_device: &RenderDevice,
+    ) -> Self {
         let needs_dispatch_remap =
             cluster_buffer_slots > render_device.limits().max_compute_workgroups_per_dimension;
 
@@ -72,10 +78,11 @@ impl ResourceManager {
             software_raster_cluster_count: render_device.create_buffer(&BufferDescriptor {
                 label: Some("meshlet_software_raster_cluster_count"),
                 size: size_of::<u32>() as u64,
-                usage: BufferUsages::STORAGE,
+                usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC,
                 mapped_at_creation: false,
             }),
             raster_cluster_rightmost_slot: cluster_buffer_slots - 1,
+            software_raster_max_cluster_size,
 
             cluster_instance_ids: None,
             cluster_meshlet_ids: None,
@@ -264,6 +271,7 @@ pub struct MeshletViewResources {
     pub material_depth: Option<CachedTexture>,
     pub view_size: UVec2,
     pub raster_cluster_rightmost_slot: u32,
+    pub software_raster_max_cluster_size: f32,
 }
 
 #[derive(Component)]
@@ -456,13 +464,13 @@ pub fn prepare_meshlet_per_frame_resources(
             .create_buffer_with_data(&BufferInitDescriptor {
                 label: Some("meshlet_visibility_buffer_software_raster_indirect_args_first"),
                 contents: DispatchIndirectArgs { x: 0, y: 1, z: 1 }.as_bytes(),
-                usage: BufferUsages::STORAGE | BufferUsages::INDIRECT,
+                usage: BufferUsages::STORAGE | BufferUsages::INDIRECT | BufferUsages::COPY_SRC,
             });
         let visibility_buffer_software_raster_indirect_args_second = render_device
             .create_buffer_with_data(&BufferInitDescriptor {
                 label: Some("visibility_buffer_software_raster_indirect_args_second"),
                 contents: DispatchIndirectArgs { x: 0, y: 1, z: 1 }.as_bytes(),
-                usage: BufferUsages::STORAGE | BufferUsages::INDIRECT,
+                usage: BufferUsages::STORAGE | BufferUsages::INDIRECT | BufferUsages::COPY_SRC,
             });
 
         let visibility_buffer_hardware_raster_indirect_args_first = render_device
@@ -475,7 +483,7 @@ pub fn prepare_meshlet_per_frame_resources(
                     first_instance: 0,
                 }
                 .as_bytes(),
-                usage: BufferUsages::STORAGE | BufferUsages::INDIRECT,
+                usage: BufferUsages::STORAGE | BufferUsages::INDIRECT | BufferUsages::COPY_SRC,
             });
         let visibility_buffer_hardware_raster_indirect_args_second = render_device
             .create_buffer_with_data(&BufferInitDescriptor {
@@ -487,7 +495,7 @@ pub fn prepare_meshlet_per_frame_resources(
                     first_instance: 0,
                 }
                 .as_bytes(),
-                usage: BufferUsages::STORAGE | BufferUsages::INDIRECT,
+                usage: BufferUsages::STORAGE | BufferUsages::INDIRECT | BufferUsages::COPY_SRC,
             });
 
         let depth_pyramid_size = Extent3d {
@@ -570,6 +578,7 @@ pub fn prepare_meshlet_per_frame_resources(
                 .then(|| texture_cache.get(&render_device, material_depth)),
             view_size: view.viewport.zw(),
             raster_cluster_rightmost_slot: resource_manager.raster_cluster_rightmost_slot,
+            software_raster_max_cluster_size: resource_manager.software_raster_max_cluster_size,
         });
     }
 }
@@ -1,6 +1,6 @@
 use super::{
     pipelines::MeshletPipelines,
-    resource_manager::{MeshletViewBindGroups, MeshletViewResources},
+    resource_manager::{MeshletViewBindGroups, MeshletViewResources, ResourceManager},
 };
 use crate::{LightEntity, ShadowView, ViewLightEntities};
 use bevy_color::LinearRgba;
@@ -12,6 +12,7 @@ use bevy_ecs::{
 use bevy_math::ops;
 use bevy_render::{
     camera::ExtractedCamera,
+    diagnostic::RecordDiagnostics,
     render_graph::{Node, NodeRunError, RenderGraphContext},
     render_resource::*,
     renderer::RenderContext,
@@ -97,6 +98,10 @@ impl Node for MeshletVisibilityBufferRasterPassNode {
             return Ok(());
         };
 
+        let software_raster_cluster_count = &world
+            .resource::<ResourceManager>()
+            .software_raster_cluster_count;
+
         let first_node = meshlet_view_bind_groups
             .first_node
             .fetch_and(false, Ordering::SeqCst);
@@ -130,11 +135,15 @@ impl Node for MeshletVisibilityBufferRasterPassNode {
             thread_per_cluster_workgroups,
             meshlet_view_resources.scene_cluster_count,
             meshlet_view_resources.raster_cluster_rightmost_slot,
+            meshlet_view_resources.software_raster_max_cluster_size,
             meshlet_view_bind_groups
                 .remap_1d_to_2d_dispatch
                 .as_ref()
                 .map(|(bg1, _)| bg1),
             remap_1d_to_2d_dispatch_pipeline,
+            &meshlet_view_resources.visibility_buffer_software_raster_indirect_args_first,
+            &meshlet_view_resources.visibility_buffer_hardware_raster_indirect_args_first,
+            software_raster_cluster_count,
         );
         raster_pass(
             true,
@@ -166,11 +175,15 @@ impl Node for MeshletVisibilityBufferRasterPassNode {
             thread_per_cluster_workgroups,
             meshlet_view_resources.scene_cluster_count,
             meshlet_view_resources.raster_cluster_rightmost_slot,
+            meshlet_view_resources.software_raster_max_cluster_size,
             meshlet_view_bind_groups
                 .remap_1d_to_2d_dispatch
                 .as_ref()
                 .map(|(_, bg2)| bg2),
             remap_1d_to_2d_dispatch_pipeline,
+            &meshlet_view_resources.visibility_buffer_software_raster_indirect_args_second,
+            &meshlet_view_resources.visibility_buffer_hardware_raster_indirect_args_second,
+            software_raster_cluster_count,
         );
         raster_pass(
             false,
@@ -248,11 +261,15 @@ impl Node for MeshletVisibilityBufferRasterPassNode {
                 thread_per_cluster_workgroups,
                 meshlet_view_resources.scene_cluster_count,
                 meshlet_view_resources.raster_cluster_rightmost_slot,
+                meshlet_view_resources.software_raster_max_cluster_size,
                 meshlet_view_bind_groups
                     .remap_1d_to_2d_dispatch
                     .as_ref()
                     .map(|(bg1, _)| bg1),
                 remap_1d_to_2d_dispatch_pipeline,
+                &meshlet_view_resources.visibility_buffer_software_raster_indirect_args_first,
+                &meshlet_view_resources.visibility_buffer_hardware_raster_indirect_args_first,
+                software_raster_cluster_count,
             );
             raster_pass(
                 true,
@@ -284,11 +301,15 @@ impl Node for MeshletVisibilityBufferRasterPassNode {
                 thread_per_cluster_workgroups,
                 meshlet_view_resources.scene_cluster_count,
                 meshlet_view_resources.raster_cluster_rightmost_slot,
+                meshlet_view_resources.software_raster_max_cluster_size,
                 meshlet_view_bind_groups
                     .remap_1d_to_2d_dispatch
                     .as_ref()
                     .map(|(_, bg2)| bg2),
                 remap_1d_to_2d_dispatch_pipeline,
+                &meshlet_view_resources.visibility_buffer_software_raster_indirect_args_second,
+                &meshlet_view_resources.visibility_buffer_hardware_raster_indirect_args_second,
+                software_raster_cluster_count,
             );
             raster_pass(
                 false,
@@ -368,8 +389,12 @@ fn cull_pass(
     culling_workgroups: u32,
     scene_cluster_count: u32,
     raster_cluster_rightmost_slot: u32,
+    software_raster_max_cluster_size: f32,
     remap_1d_to_2d_dispatch_bind_group: Option<&BindGroup>,
     remap_1d_to_2d_dispatch_pipeline: Option<&ComputePipeline>,
+    visibility_buffer_software_raster_indirect_args: &Buffer,
+    visibility_buffer_hardware_raster_indirect_args: &Buffer,
+    software_raster_cluster_count: &Buffer,
 ) {
     let max_compute_workgroups_per_dimension = render_context
         .render_device()
@@ -384,7 +409,11 @@ fn cull_pass(
     cull_pass.set_pipeline(culling_pipeline);
     cull_pass.set_push_constants(
         0,
-        bytemuck::cast_slice(&[scene_cluster_count, raster_cluster_rightmost_slot]),
+        bytemuck::cast_slice(&[
+            scene_cluster_count,
+            raster_cluster_rightmost_slot,
+            software_raster_max_cluster_size.to_bits(),
+        ]),
     );
     cull_pass.set_bind_group(
         0,
@@ -393,6 +422,9 @@ fn cull_pass(
     );
     cull_pass.dispatch_workgroups(culling_workgroups, culling_workgroups, culling_workgroups);
 
+    // The remap pass replaces the 1d count of software raster workgroups in the indirect
+    // args with a 2d dispatch, after saving the count in a separate buffer
+    let mut software_raster_cluster_count_source = visibility_buffer_software_raster_indirect_args;
     if let (Some(remap_1d_to_2d_dispatch_pipeline), Some(remap_1d_to_2d_dispatch_bind_group)) = (
         remap_1d_to_2d_dispatch_pipeline,
         remap_1d_to_2d_dispatch_bind_group,
@@ -401,7 +433,28 @@ fn cull_pass(
         cull_pass.set_push_constants(0, &max_compute_workgroups_per_dimension.to_be_bytes());
         cull_pass.set_bind_group(0, remap_1d_to_2d_dispatch_bind_group, &[]);
         cull_pass.dispatch_workgroups(1, 1, 1);
+        software_raster_cluster_count_source = software_raster_cluster_count;
     }
+    drop(cull_pass);
+
+    // Each software raster workgroup rasterizes one cluster, and each hardware raster instance
+    // draws one cluster
+    let diagnostics = render_context.diagnostic_recorder();
+    let command_encoder = render_context.command_encoder();
+    diagnostics.record_gpu_u32(
+        command_encoder,
+        "meshlet/software_raster_clusters",
+        "",
+        software_raster_cluster_count_source,
+        0,
+    );
+    diagnostics.record_gpu_u32(
+        command_encoder,
+        "meshlet/hardware_raster_clusters",
+        "",
+        visibility_buffer_hardware_raster_indirect_args,
+        4,
+    );
 }
 
 fn raster_pass(
@@ -99,6 +99,11 @@ struct VertexOutput {
 #ifdef MOTION_VECTOR_PREPASS
     motion_vector: vec2<f32>,
 #endif
+#ifdef ENTITY_INDEX_PREPASS
+    // The index and the high 32 bits of the main world entity of the mesh, as written to the
+    // entity index prepass texture.
+    entity: vec2<u32>,
+#endif
 #endif
 }
 
@@ -179,6 +184,9 @@ fn resolve_vertex_output(frag_coord: vec4<f32>) -> VertexOutput {
 #ifdef MOTION_VECTOR_PREPASS
         motion_vector,
 #endif
+#ifdef ENTITY_INDEX_PREPASS
+        vec2(instance_uniform.main_entity_index, instance_uniform.main_entity_generation),
+#endif
 #endif
     );
 }
@@ -0,0 +1,476 @@
+//! GPU-simulated particle systems.
+//!
+//! A [`ParticleEmitter`] spawns the particles of a [`ParticleEffect`] at its
+//! position. The particles live on the GPU: a compute shader spawns them,
+//! applies the forces of the effect, and bounces them off the depth buffer of
+//! a camera, so that hundreds of thousands of particles can be simulated
+//! without involving the CPU or the ECS. They're drawn as camera-facing
+//! quads in the transparent pass, either sorted back to front and alpha
+//! blended, or added to the color behind them without sorting.
+//!
+//! The size and color of the particles follow [`ParticleCurve`]s over their
+//! lifetime. Effects can be loaded from `.particles.ron` files, or built at
+//! runtime. Particles relies on compute shaders, so it isn't supported on
+//! WebGL 2.
+
+mod render;
+
+use bevy_app::{App, Plugin};
+use bevy_asset::{
+    io::Reader, load_internal_asset, Asset, AssetApp, AssetLoader, Handle, LoadContext,
+};
+use bevy_color::LinearRgba;
+use bevy_core_pipeline::core_3d::{
+    graph::{Core3d, Node3d},
+    Transparent3d,
+};
+use bevy_ecs::prelude::*;
+use bevy_image::Image;
+use bevy_math::{Vec3, VectorSpace};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::{
+    render_graph::{RenderGraphApp, ViewNodeRunner},
+    render_phase::AddRenderCommand,
+    render_resource::{Shader, SpecializedRenderPipelines},
+    sync_component::SyncComponentPlugin,
+    view::Visibility,
+    ExtractSchedule, Render, RenderApp, RenderSet,
+};
+use bevy_transform::components::Transform;
+use ron::de::SpannedError;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::graph::NodePbr;
+
+use render::{
+    extract_particle_emitters, prepare_particle_emitters, prepare_particle_views,
+    queue_particle_emitters, DrawParticles, ParticleDrawPipeline, ParticleSimulationNode,
+    ParticleSimulationPipelines, ParticleViewBuffers, PARTICLES_SHADER_HANDLE,
+    PARTICLES_SIMULATE_SHADER_HANDLE, PARTICLES_TYPES_SHADER_HANDLE,
+};
+
+/// Adds support for [`ParticleEmitter`]s and [`ParticleEffect`]s.
+pub struct ParticlesPlugin;
+
+impl Plugin for ParticlesPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            PARTICLES_TYPES_SHADER_HANDLE,
+            "particles_types.wgsl",
+            Shader::from_wgsl
+        );
+        load_internal_asset!(
+            app,
+            PARTICLES_SIMULATE_SHADER_HANDLE,
+            "particles_simulate.wgsl",
+            Shader::from_wgsl
+        );
+        load_internal_asset!(
+            app,
+            PARTICLES_SHADER_HANDLE,
+            "particles.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.init_asset::<ParticleEffect>()
+            .init_asset_loader::<ParticleEffectLoader>()
+            .register_asset_reflect::<ParticleEffect>()
+            .register_type::<ParticleEmitter>()
+            .add_plugins(SyncComponentPlugin::<ParticleEmitter>::default());
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .init_resource::<ParticleViewBuffers>()
+            .init_resource::<SpecializedRenderPipelines<ParticleDrawPipeline>>()
+            .add_render_command::<Transparent3d, DrawParticles>()
+            .add_systems(ExtractSchedule, extract_particle_emitters)
+            .add_systems(
+                Render,
+                (
+                    queue_particle_emitters.in_set(RenderSet::Queue),
+                    prepare_particle_emitters.in_set(RenderSet::PrepareResources),
+                    prepare_particle_views.in_set(RenderSet::PrepareBindGroups),
+                ),
+            )
+            .add_render_graph_node::<ViewNodeRunner<ParticleSimulationNode>>(
+                Core3d,
+                NodePbr::Particles,
+            )
+            .add_render_graph_edges(
+                Core3d,
+                // The particles collide with the depth prepass, and are
+                // sorted before the transparent pass draws them.
+                (
+                    Node3d::EndPrepasses,
+                    NodePbr::Particles,
+                    Node3d::StartMainPass,
+                ),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .init_resource::<ParticleSimulationPipelines>()
+            .init_resource::<ParticleDrawPipeline>();
+    }
+}
+
+/// Spawns the particles of a [`ParticleEffect`].
+///
+/// Particles are spawned at the position of this entity, and then simulated
+/// in world space, so they stay behind when the emitter moves. Removing the
+/// emitter removes its particles.
+#[derive(Component, Clone, Debug, Default, Reflect)]
+#[reflect(Component, Default, Debug)]
+#[require(Transform, Visibility)]
+pub struct ParticleEmitter {
+    /// The effect that describes the particles.
+    pub effect: Handle<ParticleEffect>,
+
+    /// The texture of each particle, multiplied by its color.
+    ///
+    /// Defaults to `None`, which draws the particles as squares.
+    pub texture: Option<Handle<Image>>,
+
+    /// Set to true to stop spawning new particles, while the existing ones
+    /// keep living until the end of their lifetime.
+    ///
+    /// Defaults to false.
+    pub paused: bool,
+}
+
+/// Describes how a [`ParticleEmitter`] spawns, moves and draws its
+/// particles.
+///
+/// Effects can be loaded from `.particles.ron` files.
+#[derive(Asset, Clone, Debug, PartialEq, Reflect, Serialize, Deserialize)]
+#[reflect(Default, Debug)]
+#[serde(default)]
+pub struct ParticleEffect {
+    /// The maximum number of particles alive at once. When it's reached, new
+    /// particles replace the oldest ones.
+    ///
+    /// Defaults to 4096.
+    pub capacity: u32,
+
+    /// The number of particles spawned per second.
+    ///
+    /// Defaults to 100.
+    pub spawn_rate: f32,
+
+    /// The volume that particles are spawned in, in the local space of the
+    /// emitter.
+    pub shape: ParticleEmitterShape,
+
+    /// The number of seconds that each particle lives for.
+    ///
+    /// Defaults to 2.
+    pub lifetime: f32,
+
+    /// The fraction by which the lifetime of each particle is randomly
+    /// shortened, from 0 to 1.
+    ///
+    /// Defaults to 0.
+    pub lifetime_randomness: f32,
+
+    /// The velocity of the particles when they're spawned, in the local space
+    /// of the emitter.
+    ///
+    /// Defaults to 1 unit per second along +Y.
+    pub velocity: Vec3,
+
+    /// The largest angle, in radians, between the initial velocity of a
+    /// particle and [`ParticleEffect::velocity`].
+    ///
+    /// Defaults to 0.3.
+    pub velocity_spread: f32,
+
+    /// The fraction by which the initial speed of each particle is randomly
+    /// reduced, from 0 to 1.
+    ///
+    /// Defaults to 0.
+    pub speed_randomness: f32,
+
+    /// A constant acceleration applied to the particles in world space, like
+    /// gravity.
+    ///
+    /// Defaults to zero.
+    pub acceleration: Vec3,
+
+    /// How quickly the particles slow down, as the fraction of their velocity
+    /// lost per second.
+    ///
+    /// Defaults to 0.
+    pub drag: f32,
+
+    /// Makes the particles bounce off the depth prepass of a camera.
+    ///
+    /// The particles collide with the first 3D camera, by
+    /// [`Camera::order`](bevy_render::camera::Camera::order), that has a
+    /// [`DepthPrepass`](bevy_core_pipeline::prepass::DepthPrepass), so they
+    /// only bounce off the surfaces that it sees.
+    ///
+    /// Defaults to `None`.
+    pub collision: Option<ParticleCollision>,
+
+    /// The size of the particles, in world units, over their lifetime.
+    ///
+    /// Defaults to 0.1.
+    pub size: ParticleCurve<f32>,
+
+    /// The color of the particles over their lifetime, multiplied by the
+    /// texture of the emitter.
+    ///
+    /// Defaults to white.
+    pub color: ParticleCurve<LinearRgba>,
+
+    /// How the particles are blended with the color behind them.
+    pub blend_mode: ParticleBlendMode,
+}
+
+impl Default for ParticleEffect {
+    fn default() -> Self {
+        Self {
+            capacity: 4096,
+            spawn_rate: 100.0,
+            shape: ParticleEmitterShape::default(),
+            lifetime: 2.0,
+            lifetime_randomness: 0.0,
+            velocity: Vec3::Y,
+            velocity_spread: 0.3,
+            speed_randomness: 0.0,
+            acceleration: Vec3::ZERO,
+            drag: 0.0,
+            collision: None,
+            size: ParticleCurve::constant(0.1),
+            color: ParticleCurve::constant(LinearRgba::WHITE),
+            blend_mode: ParticleBlendMode::default(),
+        }
+    }
+}
+
+/// The volume that a [`ParticleEffect`] spawns its particles in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Reflect, Serialize, Deserialize)]
+#[reflect(Default, Debug, PartialEq)]
+pub enum ParticleEmitterShape {
+    /// All the particles are spawned at the origin of the emitter.
+    #[default]
+    Point,
+    /// The particles are spawned in a sphere centered on the origin of the
+    /// emitter.
+    Sphere {
+        /// The radius of the sphere.
+        radius: f32,
+    },
+    /// The particles are spawned in a box centered on the origin of the
+    /// emitter.
+    Box {
+        /// Half the size of the box along each axis.
+        half_size: Vec3,
+    },
+}
+
+/// How particles bounce off the depth buffer, see
+/// [`ParticleEffect::collision`].
+#[derive(Clone, Copy, Debug, PartialEq, Reflect, Serialize, Deserialize)]
+#[reflect(Default, Debug, PartialEq)]
+#[serde(default)]
+pub struct ParticleCollision {
+    /// The fraction of the velocity along the normal of the surface that's
+    /// kept after a bounce, from 0 to 1.
+    ///
+    /// Defaults to 0.5.
+    pub restitution: f32,
+
+    /// The fraction of the velocity along the surface that's lost in a
+    /// bounce, from 0 to 1.
+    ///
+    /// Defaults to 0.2.
+    pub friction: f32,
+
+    /// How far behind the depth buffer a particle can be and still collide
+    /// with it, in world units. Particles farther behind are considered to be
+    /// hidden by another object, and pass through.
+    ///
+    /// Defaults to 0.5.
+    pub thickness: f32,
+}
+
+impl Default for ParticleCollision {
+    fn default() -> Self {
+        Self {
+            restitution: 0.5,
+            friction: 0.2,
+            thickness: 0.5,
+        }
+    }
+}
+
+/// How particles are blended with the color behind them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect, Serialize, Deserialize)]
+#[reflect(Default, Debug, PartialEq, Hash)]
+pub enum ParticleBlendMode {
+    /// The particles are sorted back to front for each camera, and blended
+    /// by their alpha, like [`AlphaMode::Blend`](bevy_render::alpha::AlphaMode::Blend).
+    #[default]
+    Blend,
+    /// The color of the particles, multiplied by their alpha, is added to the
+    /// color behind them, which doesn't need sorting. Suits fire, sparks and
+    /// magic effects.
+    Additive,
+}
+
+/// A value that changes over the lifetime of the particles of a
+/// [`ParticleEffect`].
+///
+/// The curve is made of keys, each a time between 0, when a particle is
+/// spawned, and 1, when it dies, and the value at that time. The value is
+/// linearly interpolated between keys, and is constant before the first key
+/// and after the last one. The curve is sampled [`PARTICLE_CURVE_SAMPLES`]
+/// times for the GPU, so keys closer than that are smoothed out.
+#[derive(Clone, Debug, PartialEq, Reflect, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ParticleCurve<T> {
+    keys: Vec<(f32, T)>,
+}
+
+/// The number of samples of a [`ParticleCurve`] uploaded to the GPU.
+pub const PARTICLE_CURVE_SAMPLES: usize = 16;
+
+impl<T: VectorSpace> ParticleCurve<T> {
+    /// Creates a curve from its keys, which are sorted by time.
+    pub fn new(keys: impl IntoIterator<Item = (f32, T)>) -> Self {
+        let mut keys: Vec<_> = keys.into_iter().collect();
+        keys.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        Self { keys }
+    }
+
+    /// Creates a curve with the same value over the whole lifetime.
+    pub fn constant(value: T) -> Self {
+        Self {
+            keys: vec![(0.0, value)],
+        }
+    }
+
+    /// Creates a curve that goes from `start` when a particle is spawned to
+    /// `end` when it dies.
+    pub fn linear(start: T, end: T) -> Self {
+        Self {
+            keys: vec![(0.0, start), (1.0, end)],
+        }
+    }
+
+    /// Returns the keys of the curve, sorted by time.
+    pub fn keys(&self) -> &[(f32, T)] {
+        &self.keys
+    }
+
+    /// Returns the value of the curve at the given time, between 0 and 1.
+    ///
+    /// Curves without keys are zero everywhere.
+    pub fn sample(&self, time: f32) -> T {
+        let next = self.keys.partition_point(|&(key_time, _)| key_time <= time);
+        match (
+            next.checked_sub(1).map(|index| self.keys[index]),
+            self.keys.get(next),
+        ) {
+            (Some((start_time, start)), Some(&(end_time, end))) => {
+                start.lerp(end, (time - start_time) / (end_time - start_time))
+            }
+            (Some((_, value)), None) | (None, Some(&(_, value))) => value,
+            (None, None) => T::ZERO,
+        }
+    }
+
+    /// Samples the curve at [`PARTICLE_CURVE_SAMPLES`] evenly spaced times,
+    /// from 0 to 1.
+    pub(crate) fn bake(&self) -> [T; PARTICLE_CURVE_SAMPLES] {
+        core::array::from_fn(|index| {
+            self.sample(index as f32 / (PARTICLE_CURVE_SAMPLES - 1) as f32)
+        })
+    }
+}
+
+/// Loads [`ParticleEffect`]s from `.particles.ron` files.
+#[derive(Default)]
+pub struct ParticleEffectLoader;
+
+/// An error that can occur when loading a [`ParticleEffect`].
+#[derive(Error, Debug)]
+pub enum ParticleEffectLoadError {
+    /// An I/O error occurred.
+    #[error("I/O")]
+    Io(#[from] std::io::Error),
+    /// An error occurred in RON deserialization, and the location of the error is supplied.
+    #[error("RON deserialization")]
+    SpannedRon(#[from] SpannedError),
+}
+
+impl AssetLoader for ParticleEffectLoader {
+    type Asset = ParticleEffect;
+    type Settings = ();
+    type Error = ParticleEffectLoadError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<ParticleEffect, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["particles.ron"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_color::LinearRgba;
+
+    use super::{ParticleCurve, ParticleEffect, PARTICLE_CURVE_SAMPLES};
+
+    #[test]
+    fn sample_curve() {
+        let curve = ParticleCurve::new([(1.0, 4.0), (0.5, 2.0)]);
+        assert_eq!(curve.keys()[0], (0.5, 2.0));
+        assert_eq!(curve.sample(0.0), 2.0);
+        assert_eq!(curve.sample(0.75), 3.0);
+        assert_eq!(curve.sample(2.0), 4.0);
+
+        assert_eq!(ParticleCurve::constant(1.0).sample(0.5), 1.0);
+        assert_eq!(ParticleCurve::<f32>::new([]).sample(0.5), 0.0);
+
+        let baked = ParticleCurve::linear(LinearRgba::BLACK, LinearRgba::WHITE).bake();
+        assert_eq!(baked[0], LinearRgba::BLACK);
+        assert_eq!(baked[PARTICLE_CURVE_SAMPLES - 1], LinearRgba::WHITE);
+    }
+
+    #[test]
+    fn deserialize_effect() {
+        let effect: ParticleEffect = ron::de::from_str(
+            "(
+                spawn_rate: 10.0,
+                shape: Sphere(radius: 2.0),
+                size: [(0.0, 1.0), (1.0, 0.0)],
+                blend_mode: Additive,
+            )",
+        )
+        .unwrap();
+        assert_eq!(effect.spawn_rate, 10.0);
+        assert_eq!(effect.size.sample(0.5), 0.5);
+        assert_eq!(effect.capacity, ParticleEffect::default().capacity);
+    }
+}
@@ -0,0 +1,85 @@
+// Draws the particles of an emitter as quads facing the view.
+//
+// Each instance is a particle, in the order of the sort keys when the
+// particles are alpha blended. The size and the color come from the curves of
+// the effect, at the age of the particle. The quads of the dead particles are
+// moved outside of the view, so that they're clipped.
+
+#import bevy_pbr::particles_types::{Particle, ParticleEmitter, ParticleView}
+
+@group(0) @binding(0) var<uniform> view: ParticleView;
+@group(0) @binding(1) var<uniform> emitter: ParticleEmitter;
+@group(0) @binding(2) var<storage> particles: array<Particle>;
+@group(0) @binding(3) var<storage> sort_keys: array<vec2<u32>>;
+@group(0) @binding(4) var particle_texture: texture_2d<f32>;
+@group(0) @binding(5) var particle_sampler: sampler;
+
+// The number of samples of the curves, see `PARTICLE_CURVE_SAMPLES`.
+const CURVE_SAMPLES: u32 = 16u;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+// Returns the sample of the curves before the given time, and the fraction of
+// the way to the next one.
+fn curve_position(time: f32) -> vec2<f32> {
+    let position = clamp(time, 0.0, 1.0) * f32(CURVE_SAMPLES - 1u);
+    let first = min(floor(position), f32(CURVE_SAMPLES - 2u));
+    return vec2(first, position - first);
+}
+
+fn size_sample(index: u32) -> f32 {
+    return emitter.size[index / 4u][index % 4u];
+}
+
+@vertex
+fn vertex(
+    @builtin(vertex_index) vertex_index: u32,
+    @builtin(instance_index) instance_index: u32,
+) -> VertexOutput {
+#ifdef SORTED
+    let index = sort_keys[instance_index].y;
+#else
+    let index = instance_index;
+#endif
+
+    var out: VertexOutput;
+    var particle: Particle;
+    if index < emitter.capacity {
+        particle = particles[index];
+    }
+    if particle.age >= particle.lifetime {
+        out.position = vec4(2.0, 2.0, 2.0, 1.0);
+        return out;
+    }
+
+    let curve = curve_position(particle.age / particle.lifetime);
+    let first = u32(curve.x);
+    let size = mix(size_sample(first), size_sample(first + 1u), curve.y);
+    out.color = mix(emitter.color[first], emitter.color[first + 1u], curve.y);
+
+    // The two triangles of the quad.
+    var corners = array(
+        vec2(-1.0, -1.0),
+        vec2(1.0, -1.0),
+        vec2(1.0, 1.0),
+        vec2(-1.0, -1.0),
+        vec2(1.0, 1.0),
+        vec2(-1.0, 1.0),
+    );
+    let corner = corners[vertex_index];
+    out.uv = vec2(corner.x, -corner.y) * 0.5 + 0.5;
+
+    let world_position = particle.position
+        + (view.view_right * corner.x + view.view_up * corner.y) * size * 0.5;
+    out.position = view.clip_from_world * vec4(world_position, 1.0);
+    return out;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color * textureSample(particle_texture, particle_sampler, in.uv);
+}
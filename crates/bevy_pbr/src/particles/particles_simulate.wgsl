@@ -0,0 +1,284 @@
+// Simulates the particles of an emitter, and sorts them for a view.
+//
+// `simulate` runs one invocation per particle: it spawns the particles of
+// this frame in the slots that follow `spawn_start`, and moves the living
+// particles. With collisions, the particles that went behind the depth
+// prepass of the simulating view are pushed back onto the surface and bounce
+// off it.
+//
+// `sort_keys` and `sort_step` then sort the particles back to front for a
+// view with a bitonic sort, one dispatch per step.
+
+#import bevy_pbr::particles_types::{Particle, ParticleEmitter, ParticleView}
+
+// See `ParticleCollisionUniform` in `particles/render.rs`.
+struct ParticleCollisionView {
+    clip_from_world: mat4x4<f32>,
+    world_from_clip: mat4x4<f32>,
+    viewport: vec4<f32>,
+    view_position: vec3<f32>,
+}
+
+// See `ParticleSortStep` in `particles/render.rs`.
+struct SortStep {
+    block_size: u32,
+    compare_distance: u32,
+}
+
+const SHAPE_SPHERE: u32 = 1u;
+const SHAPE_BOX: u32 = 2u;
+const PI: f32 = 3.141592653589793;
+
+// The key of the particles that are dead, and of the padding after the last
+// particle, which sorts them last.
+const DEAD_KEY: u32 = 0xffffffffu;
+
+// Returns the index of an invocation dispatched over multiple rows of
+// workgroups of 64 invocations, as there can only be 65535 workgroups along
+// each axis.
+fn invocation_index(global_id: vec3<u32>, num_workgroups: vec3<u32>) -> u32 {
+    return global_id.y * num_workgroups.x * 64u + global_id.x;
+}
+
+#ifdef SORT
+
+@group(0) @binding(0) var<uniform> view: ParticleView;
+@group(0) @binding(1) var<storage> particles: array<Particle>;
+// The distance key and the index of each particle.
+@group(0) @binding(2) var<storage, read_write> sort_keys: array<vec2<u32>>;
+@group(1) @binding(0) var<uniform> sort_step: SortStep;
+
+// Writes the sort key of each particle. Positive floats sort like their bits,
+// so inverting the bits of the distance sorts the farthest particles first.
+@compute @workgroup_size(64)
+fn sort_keys(
+    @builtin(global_invocation_id) global_id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>,
+) {
+    let index = invocation_index(global_id, num_workgroups);
+    if index >= view.sort_size {
+        return;
+    }
+
+    var key = DEAD_KEY;
+    if index < view.capacity {
+        let particle = particles[index];
+        if particle.age < particle.lifetime {
+            let distance = dot(particle.position - view.view_position, view.view_forward);
+            // Keep the particles at the camera distinct from the dead ones.
+            key = ~bitcast<u32>(max(distance, 1.0e-6));
+        }
+    }
+    sort_keys[index] = vec2(key, index);
+}
+
+// Runs a step of the bitonic sort, in which each key is compared to the one
+// `compare_distance` away, in ascending or descending order depending on the
+// block it's in.
+@compute @workgroup_size(64)
+fn sort_step(
+    @builtin(global_invocation_id) global_id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>,
+) {
+    let index = invocation_index(global_id, num_workgroups);
+    let partner = index ^ sort_step.compare_distance;
+    if index >= view.sort_size || partner <= index {
+        return;
+    }
+
+    let ascending = (index & sort_step.block_size) == 0u;
+    let a = sort_keys[index];
+    let b = sort_keys[partner];
+    if (a.x > b.x) == ascending {
+        sort_keys[index] = b;
+        sort_keys[partner] = a;
+    }
+}
+
+#else // SORT
+
+@group(0) @binding(0) var<uniform> emitter: ParticleEmitter;
+@group(0) @binding(1) var<storage, read_write> particles: array<Particle>;
+
+#ifdef COLLISIONS
+@group(1) @binding(0) var<uniform> collision_view: ParticleCollisionView;
+#ifdef MULTISAMPLED
+@group(1) @binding(1) var depth_texture: texture_depth_multisampled_2d;
+#else
+@group(1) @binding(1) var depth_texture: texture_depth_2d;
+#endif
+#endif
+
+var<private> rng_state: u32;
+
+// The PCG hash, from "Hash Functions for GPU Rendering" by Jarzynski and
+// Olano.
+fn pcg_hash(input: u32) -> u32 {
+    let state = input * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+// Returns a random number in [0, 1).
+fn random() -> f32 {
+    rng_state = pcg_hash(rng_state);
+    return f32(rng_state >> 8u) / 16777216.0;
+}
+
+// Returns a random direction, uniformly distributed on the unit sphere.
+fn random_direction() -> vec3<f32> {
+    let z = random() * 2.0 - 1.0;
+    let phi = random() * 2.0 * PI;
+    let r = sqrt(max(1.0 - z * z, 0.0));
+    return vec3(r * cos(phi), r * sin(phi), z);
+}
+
+// Returns a random direction in the cone of the given half angle around
+// `axis`, which must be normalized.
+fn random_direction_in_cone(axis: vec3<f32>, half_angle: f32) -> vec3<f32> {
+    let cos_theta = mix(1.0, cos(half_angle), random());
+    let sin_theta = sqrt(max(1.0 - cos_theta * cos_theta, 0.0));
+    let phi = random() * 2.0 * PI;
+
+    // The orthonormal basis from "Building an Orthonormal Basis, Revisited" by
+    // Duff et al.
+    let s = select(-1.0, 1.0, axis.z >= 0.0);
+    let a = -1.0 / (s + axis.z);
+    let b = axis.x * axis.y * a;
+    let tangent = vec3(1.0 + s * axis.x * axis.x * a, s * b, -s * axis.x);
+    let bitangent = vec3(b, s + axis.y * axis.y * a, -axis.y);
+
+    return (tangent * cos(phi) + bitangent * sin(phi)) * sin_theta + axis * cos_theta;
+}
+
+// Spawns a particle in the shape of the emitter.
+fn spawn(index: u32) -> Particle {
+    rng_state = pcg_hash(index ^ pcg_hash(emitter.seed));
+
+    var local_position = vec3(0.0);
+    if emitter.shape == SHAPE_SPHERE {
+        local_position = random_direction() * pow(random(), 1.0 / 3.0) * emitter.shape_size.x;
+    } else if emitter.shape == SHAPE_BOX {
+        local_position = (vec3(random(), random(), random()) * 2.0 - 1.0) * emitter.shape_size;
+    }
+
+    var local_velocity = vec3(0.0);
+    let speed = length(emitter.velocity);
+    if speed > 0.0 {
+        let direction = random_direction_in_cone(emitter.velocity / speed, emitter.velocity_spread);
+        local_velocity = direction * speed * (1.0 - emitter.speed_randomness * random());
+    }
+
+    var particle: Particle;
+    particle.position = (emitter.world_from_local * vec4(local_position, 1.0)).xyz;
+    particle.velocity = (emitter.world_from_local * vec4(local_velocity, 0.0)).xyz;
+    particle.age = 0.0;
+    particle.lifetime = emitter.lifetime * (1.0 - emitter.lifetime_randomness * random());
+    return particle;
+}
+
+#ifdef COLLISIONS
+
+// Returns the texel of the depth prepass at the given normalized device
+// coordinates.
+fn depth_texel(ndc: vec2<f32>) -> vec2<i32> {
+    let uv = ndc * vec2(0.5, -0.5) + 0.5;
+    return vec2<i32>(collision_view.viewport.xy + uv * collision_view.viewport.zw);
+}
+
+fn load_depth(texel: vec2<i32>) -> f32 {
+    let size = vec2<i32>(textureDimensions(depth_texture));
+    return textureLoad(depth_texture, clamp(texel, vec2(0), size - 1), 0);
+}
+
+// Returns the world position of the surface at a texel of the depth prepass.
+fn surface_position(texel: vec2<i32>) -> vec3<f32> {
+    let uv = (vec2<f32>(texel) + 0.5 - collision_view.viewport.xy) / collision_view.viewport.zw;
+    let ndc = vec4((uv - 0.5) * vec2(2.0, -2.0), load_depth(texel), 1.0);
+    let world_position = collision_view.world_from_clip * ndc;
+    return world_position.xyz / world_position.w;
+}
+
+// Makes a particle that went behind the depth prepass bounce off the surface
+// in front of it.
+fn collide(particle: ptr<function, Particle>) {
+    let clip_position = collision_view.clip_from_world * vec4((*particle).position, 1.0);
+    if clip_position.w <= 0.0 {
+        return;
+    }
+    let ndc = clip_position.xyz / clip_position.w;
+    if any(abs(ndc.xy) > vec2(1.0)) {
+        return;
+    }
+
+    // The depth is reversed, so the particle is behind the surface when its
+    // depth is smaller. Particles too far behind it are hidden by the surface
+    // rather than colliding with it.
+    let texel = depth_texel(ndc.xy);
+    if ndc.z >= load_depth(texel) {
+        return;
+    }
+    let surface = surface_position(texel);
+    if distance(surface, (*particle).position) > emitter.thickness {
+        return;
+    }
+
+    // Reconstruct the normal of the surface from the neighboring texels, facing
+    // the view.
+    let to_view = collision_view.view_position - surface;
+    var normal = normalize(to_view);
+    let tangent = surface_position(texel + vec2(1, 0)) - surface_position(texel - vec2(1, 0));
+    let bitangent = surface_position(texel + vec2(0, 1)) - surface_position(texel - vec2(0, 1));
+    let cross_product = cross(tangent, bitangent);
+    if dot(cross_product, cross_product) > 1.0e-12 {
+        normal = normalize(cross_product) * select(-1.0, 1.0, dot(cross_product, to_view) >= 0.0);
+    }
+
+    (*particle).position = surface + normal * 1.0e-3;
+    let velocity = (*particle).velocity;
+    let normal_speed = dot(velocity, normal);
+    if normal_speed < 0.0 {
+        let tangent_velocity = velocity - normal_speed * normal;
+        (*particle).velocity = tangent_velocity * (1.0 - emitter.friction)
+            - normal * normal_speed * emitter.restitution;
+    }
+}
+
+#endif // COLLISIONS
+
+@compute @workgroup_size(64)
+fn simulate(
+    @builtin(global_invocation_id) global_id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>,
+) {
+    let index = invocation_index(global_id, num_workgroups);
+    if index >= emitter.capacity {
+        return;
+    }
+
+    // The particles of this frame replace the oldest ones, in the slots that
+    // follow `spawn_start`.
+    if (index + emitter.capacity - emitter.spawn_start) % emitter.capacity < emitter.spawn_count {
+        particles[index] = spawn(index);
+        return;
+    }
+
+    var particle = particles[index];
+    if particle.age >= particle.lifetime {
+        return;
+    }
+
+    let delta_time = emitter.delta_time;
+    particle.velocity += emitter.acceleration * delta_time;
+    particle.velocity /= 1.0 + emitter.drag * delta_time;
+    particle.position += particle.velocity * delta_time;
+    particle.age += delta_time;
+
+#ifdef COLLISIONS
+    collide(&particle);
+#endif
+
+    particles[index] = particle;
+}
+
+#endif // SORT
@@ -0,0 +1,44 @@
+#define_import_path bevy_pbr::particles_types
+
+// See `ParticleEmitterUniform` in `particles/render.rs`.
+struct ParticleEmitter {
+    world_from_local: mat4x4<f32>,
+    velocity: vec3<f32>,
+    velocity_spread: f32,
+    acceleration: vec3<f32>,
+    drag: f32,
+    shape_size: vec3<f32>,
+    shape: u32,
+    lifetime: f32,
+    lifetime_randomness: f32,
+    speed_randomness: f32,
+    delta_time: f32,
+    capacity: u32,
+    spawn_start: u32,
+    spawn_count: u32,
+    seed: u32,
+    restitution: f32,
+    friction: f32,
+    thickness: f32,
+    size: array<vec4<f32>, 4>,
+    color: array<vec4<f32>, 16>,
+}
+
+// A particle, in world space. It's dead once its age exceeds its lifetime.
+struct Particle {
+    position: vec3<f32>,
+    age: f32,
+    velocity: vec3<f32>,
+    lifetime: f32,
+}
+
+// See `ParticleViewUniform` in `particles/render.rs`.
+struct ParticleView {
+    clip_from_world: mat4x4<f32>,
+    view_position: vec3<f32>,
+    capacity: u32,
+    view_forward: vec3<f32>,
+    sort_size: u32,
+    view_right: vec3<f32>,
+    view_up: vec3<f32>,
+}
@@ -0,0 +1,1056 @@
+//! The render world side of [`ParticleEmitter`]s: simulating, sorting and
+//! drawing their particles.
+
+use bevy_asset::{AssetId, Assets, Handle};
+use bevy_color::ColorToComponents;
+use bevy_core_pipeline::{
+    core_3d::{Camera3d, Transparent3d, CORE_3D_DEPTH_FORMAT},
+    prepass::ViewPrepassTextures,
+};
+use bevy_ecs::{
+    entity::EntityHashMap,
+    prelude::*,
+    query::QueryItem,
+    system::{lifetimeless::SRes, SystemParamItem},
+};
+use bevy_image::{BevyDefault, Image};
+use bevy_math::{Mat4, Vec3, Vec4};
+use bevy_render::{
+    camera::ExtractedCamera,
+    render_asset::RenderAssets,
+    render_graph::{NodeRunError, RenderGraphContext, ViewNode},
+    render_phase::{
+        DrawFunctions, PhaseItem, PhaseItemExtraIndex, RenderCommand, RenderCommandResult,
+        SetItemPipeline, TrackedRenderPass, ViewSortedRenderPhases,
+    },
+    render_resource::{
+        binding_types::{
+            sampler, storage_buffer_read_only_sized, storage_buffer_sized, texture_2d,
+            texture_depth_2d, texture_depth_2d_multisampled, uniform_buffer,
+        },
+        BindGroup, BindGroupEntries, BindGroupLayout, BindGroupLayoutEntries, BlendComponent,
+        BlendFactor, BlendOperation, BlendState, Buffer, BufferDescriptor, BufferUsages,
+        CachedComputePipelineId, ColorTargetState, ColorWrites, CompareFunction,
+        ComputePassDescriptor, ComputePipelineDescriptor, DepthBiasState, DepthStencilState,
+        DynamicUniformBuffer, FragmentState, MultisampleState, PipelineCache, PrimitiveState,
+        RenderPipelineDescriptor, SamplerBindingType, Shader, ShaderStages, ShaderType,
+        SpecializedRenderPipeline, SpecializedRenderPipelines, StencilState, TextureFormat,
+        TextureSampleType, UniformBuffer, VertexState,
+    },
+    renderer::{RenderContext, RenderDevice, RenderQueue},
+    sync_world::{MainEntity, RenderEntity},
+    texture::{FallbackImage, GpuImage},
+    view::{ExtractedView, InheritedVisibility, Msaa, ViewTarget},
+    Extract,
+};
+use bevy_time::Time;
+use bevy_transform::components::GlobalTransform;
+use tracing::warn;
+
+use super::{
+    ParticleBlendMode, ParticleEffect, ParticleEmitter, ParticleEmitterShape,
+    PARTICLE_CURVE_SAMPLES,
+};
+
+/// The handle to the compute shader that simulates and sorts the particles.
+pub(super) const PARTICLES_SIMULATE_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(9217734806135781546);
+/// The handle to the shader that draws the particles.
+pub(super) const PARTICLES_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(2786719318643895417);
+/// The handle to the types shared by the particle shaders.
+pub(super) const PARTICLES_TYPES_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(12058275540918123118);
+
+/// The workgroup size of the simulation and sorting shaders.
+const WORKGROUP_SIZE: u32 = 64;
+
+/// The size of a `Particle` in `particles_simulate.wgsl`: a position, an age,
+/// a velocity and a lifetime.
+const PARTICLE_SIZE: u64 = 32;
+
+/// The size of a sort key: the distance of a particle to the view, and its
+/// index.
+const SORT_KEY_SIZE: u64 = 2 * size_of::<u32>() as u64;
+
+/// The base 2 logarithm of the largest number of particles that can be
+/// sorted, which also limits the capacity of the effects.
+const MAX_SORT_SIZE_LOG2: u32 = 24;
+
+/// A [`ParticleEmitter`] extracted to the render world, along with its
+/// effect.
+#[derive(Component)]
+pub(super) struct ExtractedParticleEmitter {
+    world_from_local: Mat4,
+    effect: ParticleEffect,
+    texture: Option<AssetId<Image>>,
+    paused: bool,
+}
+
+impl ExtractedParticleEmitter {
+    /// The number of particles that the buffers of the emitter hold, given the
+    /// largest storage buffer binding that the device supports, in bytes.
+    fn capacity(&self, max_storage_buffer_binding_size: u32) -> u32 {
+        let max_capacity = (max_storage_buffer_binding_size as u64 / PARTICLE_SIZE)
+            .min(1 << MAX_SORT_SIZE_LOG2) as u32;
+        self.effect.capacity.clamp(1, max_capacity.max(1))
+    }
+}
+
+/// The data that the simulation and drawing shaders read for an emitter,
+/// matching `ParticleEmitter` in `particles_simulate.wgsl` and
+/// `particles.wgsl`.
+#[derive(Clone, Copy, Default, ShaderType)]
+struct ParticleEmitterUniform {
+    world_from_local: Mat4,
+    velocity: Vec3,
+    velocity_spread: f32,
+    acceleration: Vec3,
+    drag: f32,
+    shape_size: Vec3,
+    shape: u32,
+    lifetime: f32,
+    lifetime_randomness: f32,
+    speed_randomness: f32,
+    delta_time: f32,
+    capacity: u32,
+    spawn_start: u32,
+    spawn_count: u32,
+    seed: u32,
+    restitution: f32,
+    friction: f32,
+    thickness: f32,
+    size: [Vec4; PARTICLE_CURVE_SAMPLES / 4],
+    color: [Vec4; PARTICLE_CURVE_SAMPLES],
+}
+
+/// The view that the simulation shader collides the particles with,
+/// matching `ParticleCollisionView` in `particles_simulate.wgsl`.
+#[derive(Clone, Copy, Default, ShaderType)]
+struct ParticleCollisionUniform {
+    clip_from_world: Mat4,
+    world_from_clip: Mat4,
+    viewport: Vec4,
+    view_position: Vec3,
+}
+
+/// The data that the sorting and drawing shaders read for an emitter and a
+/// view, matching `ParticleView` in `particles_simulate.wgsl` and
+/// `particles.wgsl`.
+#[derive(Clone, Copy, Default, ShaderType)]
+struct ParticleViewUniform {
+    clip_from_world: Mat4,
+    view_position: Vec3,
+    capacity: u32,
+    view_forward: Vec3,
+    sort_size: u32,
+    view_right: Vec3,
+    view_up: Vec3,
+}
+
+/// A step of the bitonic sort of the particles, matching `SortStep` in
+/// `particles_simulate.wgsl`.
+#[derive(Clone, Copy, Default, ShaderType)]
+struct ParticleSortStep {
+    /// The size of the blocks being merged, whose parity gives the direction
+    /// of the comparison.
+    block_size: u32,
+    /// The distance between the keys being compared.
+    compare_distance: u32,
+}
+
+/// The GPU buffers of a [`ParticleEmitter`], kept from one frame to the next.
+#[derive(Component)]
+pub(super) struct ParticleEmitterBuffers {
+    /// The particles, which are dead once their age exceeds their lifetime.
+    particles: Buffer,
+    /// The number of particles that [`Self::particles`] can hold.
+    capacity: u32,
+    /// The fraction of a particle that's left to spawn from the previous
+    /// frames.
+    spawn_remainder: f32,
+    /// The index at which the next particles are spawned, replacing the
+    /// oldest ones.
+    spawn_cursor: u32,
+    /// The number of frames that the emitter was simulated for, which seeds
+    /// the random numbers of the spawned particles.
+    frame: u32,
+    emitter_uniform: UniformBuffer<ParticleEmitterUniform>,
+    collision_uniform: UniformBuffer<ParticleCollisionUniform>,
+}
+
+/// The buffers and bind groups of the emitters, for each view.
+#[derive(Resource, Default)]
+pub(super) struct ParticleViewBuffers {
+    /// The emitters that each view simulates this frame. Each emitter is
+    /// simulated by a single view.
+    simulations: EntityHashMap<Vec<ParticleSimulation>>,
+    /// The sort keys and bind groups of each emitter, for each view.
+    views: EntityHashMap<EntityHashMap<ParticleViewEmitter>>,
+}
+
+/// The simulation shader dispatch of an emitter.
+struct ParticleSimulation {
+    bind_group: BindGroup,
+    /// The bind group of the depth prepass to collide with, and whether it's
+    /// multisampled.
+    collision: Option<(BindGroup, bool)>,
+    capacity: u32,
+}
+
+/// The buffers and bind groups of an emitter for a view.
+struct ParticleViewEmitter {
+    /// The distances and indices of the particles, sorted back to front, when
+    /// the effect is alpha blended.
+    sort_keys: Option<Buffer>,
+    /// The number of sort keys, which is the capacity rounded up to a power
+    /// of two, or zero when the particles aren't sorted.
+    sort_size: u32,
+    capacity: u32,
+    view_uniform: UniformBuffer<ParticleViewUniform>,
+    sort_bind_group: Option<BindGroup>,
+    draw_bind_group: BindGroup,
+}
+
+/// The bind group layouts and pipelines of the simulation and sorting
+/// shaders.
+#[derive(Resource)]
+pub(super) struct ParticleSimulationPipelines {
+    simulate_bind_group_layout: BindGroupLayout,
+    collision_bind_group_layout: BindGroupLayout,
+    collision_multisampled_bind_group_layout: BindGroupLayout,
+    sort_bind_group_layout: BindGroupLayout,
+    simulate: CachedComputePipelineId,
+    simulate_collisions: CachedComputePipelineId,
+    simulate_collisions_multisampled: CachedComputePipelineId,
+    sort_keys: CachedComputePipelineId,
+    sort_step: CachedComputePipelineId,
+    /// The offsets of the steps of the bitonic sort in the sort step bind
+    /// group. The steps that sort `2^n` keys are the first `n(n+1)/2` ones.
+    sort_step_offsets: Vec<u32>,
+    sort_step_bind_group: BindGroup,
+}
+
+impl FromWorld for ParticleSimulationPipelines {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let render_queue = world.resource::<RenderQueue>();
+
+        let simulate_bind_group_layout = render_device.create_bind_group_layout(
+            "particle simulate bind group layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    // `emitter`
+                    uniform_buffer::<ParticleEmitterUniform>(false),
+                    // `particles`
+                    storage_buffer_sized(false, None),
+                ),
+            ),
+        );
+        let collision_bind_group_layout = render_device.create_bind_group_layout(
+            "particle collision bind group layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    // `collision_view`
+                    uniform_buffer::<ParticleCollisionUniform>(false),
+                    // `depth_texture`
+                    texture_depth_2d(),
+                ),
+            ),
+        );
+        let collision_multisampled_bind_group_layout = render_device.create_bind_group_layout(
+            "particle collision multisampled bind group layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    uniform_buffer::<ParticleCollisionUniform>(false),
+                    texture_depth_2d_multisampled(),
+                ),
+            ),
+        );
+        let sort_bind_group_layout = render_device.create_bind_group_layout(
+            "particle sort bind group layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    // `view`
+                    uniform_buffer::<ParticleViewUniform>(false),
+                    // `particles`
+                    storage_buffer_read_only_sized(false, None),
+                    // `sort_keys`
+                    storage_buffer_sized(false, None),
+                ),
+            ),
+        );
+        let sort_step_bind_group_layout = render_device.create_bind_group_layout(
+            "particle sort step bind group layout",
+            &BindGroupLayoutEntries::single(
+                ShaderStages::COMPUTE,
+                // `sort_step`
+                uniform_buffer::<ParticleSortStep>(true),
+            ),
+        );
+
+        // The steps of a bitonic sort of `2^n` keys merge blocks of twice the
+        // size at each stage, so they start with the steps that sort fewer
+        // keys, and are shared by every emitter.
+        let mut sort_steps = DynamicUniformBuffer::default();
+        let mut sort_step_offsets = Vec::new();
+        for stage in 1..=MAX_SORT_SIZE_LOG2 {
+            for step in (0..stage).rev() {
+                sort_step_offsets.push(sort_steps.push(&ParticleSortStep {
+                    block_size: 1 << stage,
+                    compare_distance: 1 << step,
+                }));
+            }
+        }
+        sort_steps.write_buffer(render_device, render_queue);
+        let sort_step_bind_group = render_device.create_bind_group(
+            "particle_sort_step_bind_group",
+            &sort_step_bind_group_layout,
+            &BindGroupEntries::single(&sort_steps),
+        );
+
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let queue_pipeline = |label: &'static str,
+                              layout: Vec<BindGroupLayout>,
+                              entry_point: &'static str,
+                              shader_defs: &[&str]| {
+            pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+                label: Some(label.into()),
+                layout,
+                push_constant_ranges: vec![],
+                shader: PARTICLES_SIMULATE_SHADER_HANDLE,
+                shader_defs: shader_defs.iter().map(|&def| def.into()).collect(),
+                entry_point: entry_point.into(),
+                zero_initialize_workgroup_memory: false,
+            })
+        };
+
+        Self {
+            simulate: queue_pipeline(
+                "particle simulate",
+                vec![simulate_bind_group_layout.clone()],
+                "simulate",
+                &[],
+            ),
+            simulate_collisions: queue_pipeline(
+                "particle simulate collisions",
+                vec![
+                    simulate_bind_group_layout.clone(),
+                    collision_bind_group_layout.clone(),
+                ],
+                "simulate",
+                &["COLLISIONS"],
+            ),
+            simulate_collisions_multisampled: queue_pipeline(
+                "particle simulate collisions multisampled",
+                vec![
+                    simulate_bind_group_layout.clone(),
+                    collision_multisampled_bind_group_layout.clone(),
+                ],
+                "simulate",
+                &["COLLISIONS", "MULTISAMPLED"],
+            ),
+            sort_keys: queue_pipeline(
+                "particle sort keys",
+                vec![sort_bind_group_layout.clone()],
+                "sort_keys",
+                &["SORT"],
+            ),
+            sort_step: queue_pipeline(
+                "particle sort step",
+                vec![sort_bind_group_layout.clone(), sort_step_bind_group_layout],
+                "sort_step",
+                &["SORT"],
+            ),
+            simulate_bind_group_layout,
+            collision_bind_group_layout,
+            collision_multisampled_bind_group_layout,
+            sort_bind_group_layout,
+            sort_step_offsets,
+            sort_step_bind_group,
+        }
+    }
+}
+
+/// The pipeline that draws the particles of an emitter as camera-facing
+/// quads.
+#[derive(Resource)]
+pub(super) struct ParticleDrawPipeline {
+    bind_group_layout: BindGroupLayout,
+    /// Bound in place of the sort keys of the effects that aren't sorted.
+    empty_sort_keys: Buffer,
+}
+
+impl FromWorld for ParticleDrawPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let bind_group_layout = render_device.create_bind_group_layout(
+            "particle draw bind group layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::VERTEX_FRAGMENT,
+                (
+                    // `view`
+                    uniform_buffer::<ParticleViewUniform>(false),
+                    // `emitter`
+                    uniform_buffer::<ParticleEmitterUniform>(false),
+                    // `particles`
+                    storage_buffer_read_only_sized(false, None),
+                    // `sort_keys`
+                    storage_buffer_read_only_sized(false, None),
+                    // `particle_texture`
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    // `particle_sampler`
+                    sampler(SamplerBindingType::Filtering),
+                ),
+            ),
+        );
+
+        Self {
+            bind_group_layout,
+            empty_sort_keys: render_device.create_buffer(&BufferDescriptor {
+                label: Some("particle_empty_sort_keys"),
+                size: SORT_KEY_SIZE,
+                usage: BufferUsages::STORAGE,
+                mapped_at_creation: false,
+            }),
+        }
+    }
+}
+
+/// The key of a [`ParticleDrawPipeline`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) struct ParticleDrawPipelineKey {
+    hdr: bool,
+    msaa_samples: u32,
+    blend_mode: ParticleBlendMode,
+}
+
+impl SpecializedRenderPipeline for ParticleDrawPipeline {
+    type Key = ParticleDrawPipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mut shader_defs = vec![];
+        let blend = match key.blend_mode {
+            ParticleBlendMode::Blend => {
+                shader_defs.push("SORTED".into());
+                BlendState::ALPHA_BLENDING
+            }
+            ParticleBlendMode::Additive => BlendState {
+                color: BlendComponent {
+                    src_factor: BlendFactor::SrcAlpha,
+                    dst_factor: BlendFactor::One,
+                    operation: BlendOperation::Add,
+                },
+                alpha: BlendComponent {
+                    src_factor: BlendFactor::Zero,
+                    dst_factor: BlendFactor::One,
+                    operation: BlendOperation::Add,
+                },
+            },
+        };
+
+        let format = if key.hdr {
+            ViewTarget::TEXTURE_FORMAT_HDR
+        } else {
+            TextureFormat::bevy_default()
+        };
+
+        RenderPipelineDescriptor {
+            label: Some("particle pipeline".into()),
+            layout: vec![self.bind_group_layout.clone()],
+            push_constant_ranges: vec![],
+            vertex: VertexState {
+                shader: PARTICLES_SHADER_HANDLE,
+                shader_defs: shader_defs.clone(),
+                entry_point: "vertex".into(),
+                buffers: vec![],
+            },
+            fragment: Some(FragmentState {
+                shader: PARTICLES_SHADER_HANDLE,
+                shader_defs,
+                entry_point: "fragment".into(),
+                targets: vec![Some(ColorTargetState {
+                    format,
+                    blend: Some(blend),
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState::default(),
+            // The particles are hidden by the opaque meshes, but don't hide
+            // each other.
+            depth_stencil: Some(DepthStencilState {
+                format: CORE_3D_DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: CompareFunction::GreaterEqual,
+                stencil: StencilState::default(),
+                bias: DepthBiasState::default(),
+            }),
+            multisample: MultisampleState {
+                count: key.msaa_samples,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            zero_initialize_workgroup_memory: false,
+        }
+    }
+}
+
+/// Extracts the visible [`ParticleEmitter`]s whose effects are loaded.
+///
+/// The particles of hidden emitters are kept, but they aren't simulated or
+/// drawn until the emitter is visible again.
+pub(super) fn extract_particle_emitters(
+    mut commands: Commands,
+    emitters: Extract<
+        Query<(
+            RenderEntity,
+            &ParticleEmitter,
+            &GlobalTransform,
+            &InheritedVisibility,
+        )>,
+    >,
+    effects: Extract<Res<Assets<ParticleEffect>>>,
+) {
+    for (render_entity, emitter, transform, visibility) in &emitters {
+        let mut entity_commands = commands
+            .get_entity(render_entity)
+            .expect("Particle emitter entity wasn't synced.");
+
+        let (Some(effect), true) = (effects.get(&emitter.effect), visibility.get()) else {
+            entity_commands.remove::<ExtractedParticleEmitter>();
+            continue;
+        };
+
+        entity_commands.insert(ExtractedParticleEmitter {
+            world_from_local: transform.compute_matrix(),
+            effect: effect.clone(),
+            texture: emitter.texture.as_ref().map(Handle::id),
+            paused: emitter.paused,
+        });
+    }
+}
+
+/// Creates the buffers of the emitters, counts the particles they spawn this
+/// frame, and writes their uniforms.
+pub(super) fn prepare_particle_emitters(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    time: Res<Time>,
+    mut emitters: Query<(
+        Entity,
+        &ExtractedParticleEmitter,
+        Option<&mut ParticleEmitterBuffers>,
+    )>,
+) {
+    let delta_time = time.delta_secs();
+    let max_storage_buffer_binding_size = render_device.limits().max_storage_buffer_binding_size;
+
+    for (entity, emitter, buffers) in &mut emitters {
+        let capacity = emitter.capacity(max_storage_buffer_binding_size);
+        // Warn when the buffers are created rather than every frame.
+        if capacity < emitter.effect.capacity
+            && buffers
+                .as_ref()
+                .is_none_or(|buffers| buffers.capacity != capacity)
+        {
+            warn!(
+                "The capacity of the particle effect of {entity} was reduced from {} to {}, the \
+                most particles that fit in a storage buffer and can be sorted on this device",
+                emitter.effect.capacity, capacity
+            );
+        }
+        let mut new_buffers = None;
+        let buffers = match buffers {
+            Some(buffers) if buffers.capacity == capacity => buffers.into_inner(),
+            _ => new_buffers.insert(ParticleEmitterBuffers {
+                // The buffer is zeroed, and particles whose age and lifetime
+                // are both zero are dead.
+                particles: render_device.create_buffer(&BufferDescriptor {
+                    label: Some("particles"),
+                    size: capacity as u64 * PARTICLE_SIZE,
+                    usage: BufferUsages::STORAGE,
+                    mapped_at_creation: false,
+                }),
+                capacity,
+                spawn_remainder: 0.0,
+                spawn_cursor: 0,
+                frame: 0,
+                emitter_uniform: UniformBuffer::default(),
+                collision_uniform: UniformBuffer::default(),
+            }),
+        };
+
+        let effect = &emitter.effect;
+        let mut spawn_count = 0;
+        if emitter.paused {
+            buffers.spawn_remainder = 0.0;
+        } else {
+            buffers.spawn_remainder += effect.spawn_rate.max(0.0) * delta_time;
+            let whole = buffers.spawn_remainder.floor();
+            buffers.spawn_remainder -= whole;
+            spawn_count = (whole as u32).min(capacity);
+        }
+        let spawn_start = buffers.spawn_cursor;
+        buffers.spawn_cursor = (spawn_start + spawn_count) % capacity;
+        buffers.frame = buffers.frame.wrapping_add(1);
+
+        let (shape, shape_size) = match effect.shape {
+            ParticleEmitterShape::Point => (0, Vec3::ZERO),
+            ParticleEmitterShape::Sphere { radius } => (1, Vec3::splat(radius)),
+            ParticleEmitterShape::Box { half_size } => (2, half_size),
+        };
+        let collision = effect.collision.unwrap_or_default();
+        let size = effect.size.bake();
+        buffers.emitter_uniform.set(ParticleEmitterUniform {
+            world_from_local: emitter.world_from_local,
+            velocity: effect.velocity,
+            velocity_spread: effect.velocity_spread,
+            acceleration: effect.acceleration,
+            drag: effect.drag.max(0.0),
+            shape_size,
+            shape,
+            lifetime: effect.lifetime,
+            lifetime_randomness: effect.lifetime_randomness.clamp(0.0, 1.0),
+            speed_randomness: effect.speed_randomness.clamp(0.0, 1.0),
+            delta_time,
+            capacity,
+            spawn_start,
+            spawn_count,
+            // Emitters created on the same frame spawn different particles.
+            seed: buffers.frame ^ entity.index().wrapping_mul(0x9e37_79b9),
+            restitution: collision.restitution,
+            friction: collision.friction,
+            thickness: collision.thickness,
+            size: core::array::from_fn(|index| Vec4::from_slice(&size[index * 4..])),
+            color: effect.color.bake().map(ColorToComponents::to_vec4),
+        });
+        buffers
+            .emitter_uniform
+            .write_buffer(&render_device, &render_queue);
+
+        if let Some(new_buffers) = new_buffers {
+            commands.entity(entity).insert(new_buffers);
+        }
+    }
+}
+
+/// Chooses the view that simulates each emitter, and creates the sort keys
+/// and the bind groups of each emitter for each camera.
+pub(super) fn prepare_particle_views(
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    pipelines: Res<ParticleSimulationPipelines>,
+    draw_pipeline: Res<ParticleDrawPipeline>,
+    images: Res<RenderAssets<GpuImage>>,
+    fallback_image: Res<FallbackImage>,
+    mut view_buffers: ResMut<ParticleViewBuffers>,
+    views: Query<
+        (
+            Entity,
+            &ExtractedView,
+            &ExtractedCamera,
+            &Msaa,
+            Option<&ViewPrepassTextures>,
+        ),
+        With<Camera3d>,
+    >,
+    mut emitters: Query<(
+        Entity,
+        &ExtractedParticleEmitter,
+        &mut ParticleEmitterBuffers,
+    )>,
+) {
+    let view_buffers = &mut *view_buffers;
+    view_buffers.simulations.clear();
+
+    // Forget the views and emitters that are gone.
+    view_buffers
+        .views
+        .retain(|view_entity, _| views.contains(*view_entity));
+    for view_emitters in view_buffers.views.values_mut() {
+        view_emitters.retain(|emitter_entity, _| emitters.contains(*emitter_entity));
+    }
+
+    // The cameras that render first simulate the emitters, so that the
+    // cameras rendering after them draw the particles of this frame.
+    let mut sorted_views: Vec<_> = views.iter().collect();
+    sorted_views.sort_by_key(|(_, _, camera, _, _)| camera.order);
+
+    for (emitter_entity, emitter, mut buffers) in &mut emitters {
+        // Effects that collide are simulated by the first camera with a depth
+        // prepass, if any.
+        let collision_view = sorted_views
+            .iter()
+            .filter(|_| emitter.effect.collision.is_some())
+            .find_map(|&(view_entity, view, _, msaa, prepass_textures)| {
+                let depth = prepass_textures?.depth_view()?;
+                Some((view_entity, view, msaa, depth))
+            });
+        let Some(simulation_view) = collision_view
+            .map(|(view_entity, ..)| view_entity)
+            .or_else(|| sorted_views.first().map(|&(view_entity, ..)| view_entity))
+        else {
+            continue;
+        };
+
+        let collision = collision_view.map(|(_, view, msaa, depth)| {
+            let clip_from_world = view_clip_from_world(view);
+            buffers.collision_uniform.set(ParticleCollisionUniform {
+                clip_from_world,
+                world_from_clip: clip_from_world.inverse(),
+                viewport: view.viewport.as_vec4(),
+                view_position: view.world_from_view.translation(),
+            });
+            buffers
+                .collision_uniform
+                .write_buffer(&render_device, &render_queue);
+
+            let multisampled = msaa.samples() > 1;
+            let layout = if multisampled {
+                &pipelines.collision_multisampled_bind_group_layout
+            } else {
+                &pipelines.collision_bind_group_layout
+            };
+            let bind_group = render_device.create_bind_group(
+                "particle_collision_bind_group",
+                layout,
+                &BindGroupEntries::sequential((&buffers.collision_uniform, depth)),
+            );
+            (bind_group, multisampled)
+        });
+
+        let bind_group = render_device.create_bind_group(
+            "particle_simulate_bind_group",
+            &pipelines.simulate_bind_group_layout,
+            &BindGroupEntries::sequential((
+                &buffers.emitter_uniform,
+                buffers.particles.as_entire_binding(),
+            )),
+        );
+        view_buffers
+            .simulations
+            .entry(simulation_view)
+            .or_default()
+            .push(ParticleSimulation {
+                bind_group,
+                collision,
+                capacity: buffers.capacity,
+            });
+
+        let texture = emitter
+            .texture
+            .and_then(|texture| images.get(texture))
+            .unwrap_or(&fallback_image.d2);
+
+        for &(view_entity, view, ..) in &sorted_views {
+            let view_emitters = view_buffers.views.entry(view_entity).or_default();
+
+            // Reuse the buffers of the previous frame if the emitter still has
+            // the same capacity and blend mode.
+            let capacity = buffers.capacity;
+            let sort_size = match emitter.effect.blend_mode {
+                ParticleBlendMode::Blend => capacity.next_power_of_two(),
+                ParticleBlendMode::Additive => 0,
+            };
+            let previous = view_emitters
+                .remove(&emitter_entity)
+                .filter(|view_emitter| {
+                    view_emitter.capacity == capacity && view_emitter.sort_size == sort_size
+                });
+            let (sort_keys, mut view_uniform) = match previous {
+                Some(previous) => (previous.sort_keys, previous.view_uniform),
+                None => (
+                    (sort_size > 0).then(|| {
+                        render_device.create_buffer(&BufferDescriptor {
+                            label: Some("particle_sort_keys"),
+                            size: sort_size as u64 * SORT_KEY_SIZE,
+                            usage: BufferUsages::STORAGE,
+                            mapped_at_creation: false,
+                        })
+                    }),
+                    UniformBuffer::default(),
+                ),
+            };
+
+            let world_from_view = &view.world_from_view;
+            view_uniform.set(ParticleViewUniform {
+                clip_from_world: view_clip_from_world(view),
+                view_position: world_from_view.translation(),
+                capacity,
+                view_forward: world_from_view.forward().into(),
+                sort_size,
+                view_right: world_from_view.right().into(),
+                view_up: world_from_view.up().into(),
+            });
+            view_uniform.write_buffer(&render_device, &render_queue);
+
+            let sort_bind_group = sort_keys.as_ref().map(|sort_keys| {
+                render_device.create_bind_group(
+                    "particle_sort_bind_group",
+                    &pipelines.sort_bind_group_layout,
+                    &BindGroupEntries::sequential((
+                        &view_uniform,
+                        buffers.particles.as_entire_binding(),
+                        sort_keys.as_entire_binding(),
+                    )),
+                )
+            });
+            let draw_bind_group = render_device.create_bind_group(
+                "particle_draw_bind_group",
+                &draw_pipeline.bind_group_layout,
+                &BindGroupEntries::sequential((
+                    &view_uniform,
+                    &buffers.emitter_uniform,
+                    buffers.particles.as_entire_binding(),
+                    sort_keys
+                        .as_ref()
+                        .unwrap_or(&draw_pipeline.empty_sort_keys)
+                        .as_entire_binding(),
+                    &texture.texture_view,
+                    &texture.sampler,
+                )),
+            );
+
+            view_emitters.insert(
+                emitter_entity,
+                ParticleViewEmitter {
+                    sort_keys,
+                    sort_size,
+                    capacity,
+                    view_uniform,
+                    sort_bind_group,
+                    draw_bind_group,
+                },
+            );
+        }
+    }
+}
+
+fn view_clip_from_world(view: &ExtractedView) -> Mat4 {
+    view.clip_from_world
+        .unwrap_or_else(|| view.clip_from_view * view.world_from_view.compute_matrix().inverse())
+}
+
+/// Adds the emitters to the transparent phase of each camera.
+pub(super) fn queue_particle_emitters(
+    transparent_draw_functions: Res<DrawFunctions<Transparent3d>>,
+    draw_pipeline: Res<ParticleDrawPipeline>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<ParticleDrawPipeline>>,
+    pipeline_cache: Res<PipelineCache>,
+    emitters: Query<(Entity, &MainEntity, &ExtractedParticleEmitter)>,
+    mut transparent_render_phases: ResMut<ViewSortedRenderPhases<Transparent3d>>,
+    views: Query<(&ExtractedView, &Msaa), With<Camera3d>>,
+) {
+    if emitters.is_empty() {
+        return;
+    }
+
+    let draw_particles = transparent_draw_functions.read().id::<DrawParticles>();
+
+    for (view, msaa) in &views {
+        let Some(transparent_phase) = transparent_render_phases.get_mut(&view.retained_view_entity)
+        else {
+            continue;
+        };
+
+        let rangefinder = view.rangefinder3d();
+        for (entity, main_entity, emitter) in &emitters {
+            let pipeline = pipelines.specialize(
+                &pipeline_cache,
+                &draw_pipeline,
+                ParticleDrawPipelineKey {
+                    hdr: view.hdr,
+                    msaa_samples: msaa.samples(),
+                    blend_mode: emitter.effect.blend_mode,
+                },
+            );
+
+            transparent_phase.add(Transparent3d {
+                entity: (entity, *main_entity),
+                pipeline,
+                draw_function: draw_particles,
+                distance: rangefinder
+                    .distance_translation(&emitter.world_from_local.w_axis.truncate()),
+                batch_range: 0..1,
+                extra_index: PhaseItemExtraIndex::None,
+                indexed: false,
+            });
+        }
+    }
+}
+
+/// The render node that simulates the emitters assigned to a camera, and
+/// sorts the particles of every emitter for it.
+#[derive(Default)]
+pub(super) struct ParticleSimulationNode;
+
+impl ViewNode for ParticleSimulationNode {
+    type ViewQuery = Entity;
+
+    fn run<'w>(
+        &self,
+        _: &mut RenderGraphContext,
+        render_context: &mut RenderContext<'w>,
+        view_entity: QueryItem<'w, Self::ViewQuery>,
+        world: &'w World,
+    ) -> Result<(), NodeRunError> {
+        let view_buffers = world.resource::<ParticleViewBuffers>();
+        let simulations = view_buffers.simulations.get(&view_entity);
+        let view_emitters = view_buffers.views.get(&view_entity);
+        if simulations.is_none_or(Vec::is_empty)
+            && view_emitters.is_none_or(|view_emitters| {
+                view_emitters
+                    .values()
+                    .all(|view_emitter| view_emitter.sort_bind_group.is_none())
+            })
+        {
+            return Ok(());
+        }
+
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let pipelines = world.resource::<ParticleSimulationPipelines>();
+        let (
+            Some(simulate),
+            Some(simulate_collisions),
+            Some(simulate_collisions_multisampled),
+            Some(sort_keys),
+            Some(sort_step),
+        ) = (
+            pipeline_cache.get_compute_pipeline(pipelines.simulate),
+            pipeline_cache.get_compute_pipeline(pipelines.simulate_collisions),
+            pipeline_cache.get_compute_pipeline(pipelines.simulate_collisions_multisampled),
+            pipeline_cache.get_compute_pipeline(pipelines.sort_keys),
+            pipeline_cache.get_compute_pipeline(pipelines.sort_step),
+        )
+        else {
+            return Ok(());
+        };
+
+        let mut compute_pass =
+            render_context
+                .command_encoder()
+                .begin_compute_pass(&ComputePassDescriptor {
+                    label: Some("particles"),
+                    timestamp_writes: None,
+                });
+
+        for simulation in simulations.into_iter().flatten() {
+            match &simulation.collision {
+                Some((collision_bind_group, multisampled)) => {
+                    compute_pass.set_pipeline(if *multisampled {
+                        simulate_collisions_multisampled
+                    } else {
+                        simulate_collisions
+                    });
+                    compute_pass.set_bind_group(1, collision_bind_group, &[]);
+                }
+                None => compute_pass.set_pipeline(simulate),
+            }
+            compute_pass.set_bind_group(0, &simulation.bind_group, &[]);
+            let (x, y) = workgroup_count(simulation.capacity);
+            compute_pass.dispatch_workgroups(x, y, 1);
+        }
+
+        // Each dispatch sees the results of the previous ones, so the sort
+        // runs one dispatch per step.
+        for view_emitter in view_emitters
+            .into_iter()
+            .flat_map(|view_emitters| view_emitters.values())
+        {
+            let Some(sort_bind_group) = &view_emitter.sort_bind_group else {
+                continue;
+            };
+            let (x, y) = workgroup_count(view_emitter.sort_size);
+
+            compute_pass.set_pipeline(sort_keys);
+            compute_pass.set_bind_group(0, sort_bind_group, &[]);
+            compute_pass.dispatch_workgroups(x, y, 1);
+
+            compute_pass.set_pipeline(sort_step);
+            let stages = view_emitter.sort_size.trailing_zeros() as usize;
+            for &offset in &pipelines.sort_step_offsets[..stages * (stages + 1) / 2] {
+                compute_pass.set_bind_group(1, &pipelines.sort_step_bind_group, &[offset]);
+                compute_pass.dispatch_workgroups(x, y, 1);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the number of workgroups along X and Y that cover the given
+/// number of particles, as the number of workgroups along each axis is
+/// limited.
+fn workgroup_count(particle_count: u32) -> (u32, u32) {
+    const MAX_WORKGROUPS_PER_DIMENSION: u32 = 65535;
+    let workgroups = particle_count.div_ceil(WORKGROUP_SIZE);
+    let y = workgroups.div_ceil(MAX_WORKGROUPS_PER_DIMENSION);
+    (workgroups.div_ceil(y.max(1)), y.max(1))
+}
+
+/// The render commands that draw a [`ParticleEmitter`].
+pub(super) type DrawParticles = (SetItemPipeline, SetParticlesBindGroup<0>, DrawParticleQuads);
+
+/// Sets the bind group of the particles of an emitter for the current view.
+pub(super) struct SetParticlesBindGroup<const I: usize>;
+
+impl<P: PhaseItem, const I: usize> RenderCommand<P> for SetParticlesBindGroup<I> {
+    type Param = SRes<ParticleViewBuffers>;
+    type ViewQuery = Entity;
+    type ItemQuery = ();
+
+    fn render<'w>(
+        item: &P,
+        view_entity: Entity,
+        _: Option<()>,
+        view_buffers: SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let Some(view_emitter) = view_buffers
+            .into_inner()
+            .views
+            .get(&view_entity)
+            .and_then(|view_emitters| view_emitters.get(&item.entity()))
+        else {
+            return RenderCommandResult::Skip;
+        };
+
+        pass.set_bind_group(I, &view_emitter.draw_bind_group, &[]);
+        RenderCommandResult::Success
+    }
+}
+
+/// Draws a quad for each particle of an emitter. The vertex shader collapses
+/// the quads of the dead particles.
+pub(super) struct DrawParticleQuads;
+
+impl<P: PhaseItem> RenderCommand<P> for DrawParticleQuads {
+    type Param = SRes<ParticleViewBuffers>;
+    type ViewQuery = Entity;
+    type ItemQuery = ();
+
+    fn render<'w>(
+        item: &P,
+        view_entity: Entity,
+        _: Option<()>,
+        view_buffers: SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let Some(view_emitter) = view_buffers
+            .into_inner()
+            .views
+            .get(&view_entity)
+            .and_then(|view_emitters| view_emitters.get(&item.entity()))
+        else {
+            return RenderCommandResult::Skip;
+        };
+
+        pass.draw(0..6, 0..view_emitter.capacity);
+        RenderCommandResult::Success
+    }
+}
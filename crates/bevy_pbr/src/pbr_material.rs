@@ -680,6 +680,24 @@ pub struct StandardMaterial {
     /// The exposure (brightness) level of the lightmap, if present.
     pub lightmap_exposure: f32,
 
+    /// The texture that a [`PlanarReflectionProbe`](crate::PlanarReflectionProbe) renders its
+    /// reflection into, which replaces the specular light from environment maps.
+    ///
+    /// This is set automatically on the material of the mesh that the probe is on, so it usually
+    /// doesn't need to be set manually. The texture is sampled at the screen position of each
+    /// fragment, so it's only correct for meshes that lie on the plane of the probe.
+    ///
+    /// Planar reflections are only supported with forward rendering.
+    ///
+    /// **Important:** The [`PlanarReflectionProbe`](crate::PlanarReflectionProbe) and this texture
+    /// are only available with the `pbr_planar_reflections` feature, as the texture can exceed
+    /// the per-shader texture limit of some platforms, like WebGL 2.
+    #[cfg_attr(feature = "pbr_planar_reflections", texture(27))]
+    #[cfg_attr(feature = "pbr_planar_reflections", sampler(28))]
+    #[cfg_attr(feature = "pbr_planar_reflections", dependency)]
+    #[cfg(feature = "pbr_planar_reflections")]
+    pub planar_reflection_texture: Option<Handle<Image>>,
+
     /// Render method used for opaque materials. (Where `alpha_mode` is [`AlphaMode::Opaque`] or [`AlphaMode::Mask`])
     pub opaque_render_method: OpaqueRendererMethod,
 
@@ -832,6 +850,8 @@ impl Default for StandardMaterial {
             parallax_depth_scale: 0.1,
             max_parallax_layer_count: 16.0,
             lightmap_exposure: 1.0,
+            #[cfg(feature = "pbr_planar_reflections")]
+            planar_reflection_texture: None,
             parallax_mapping_method: ParallaxMappingMethod::Occlusion,
             opaque_render_method: OpaqueRendererMethod::Auto,
             deferred_lighting_pass_id: DEFAULT_PBR_DEFERRED_LIGHTING_PASS_ID,
@@ -887,6 +907,7 @@ bitflags::bitflags! {
         const CLEARCOAT_ROUGHNESS_TEXTURE = 1 << 15;
         const CLEARCOAT_NORMAL_TEXTURE   = 1 << 16;
         const ANISOTROPY_TEXTURE         = 1 << 17;
+        const PLANAR_REFLECTION_TEXTURE  = 1 << 18;
         const ALPHA_MODE_RESERVED_BITS   = Self::ALPHA_MODE_MASK_BITS << Self::ALPHA_MODE_SHIFT_BITS; // ← Bitmask reserving bits for the `AlphaMode`
         const ALPHA_MODE_OPAQUE          = 0 << Self::ALPHA_MODE_SHIFT_BITS;                          // ← Values are just sequential values bitshifted into
         const ALPHA_MODE_MASK            = 1 << Self::ALPHA_MODE_SHIFT_BITS;                          //   the bitmask, and can range from 0 to 7.
@@ -991,6 +1012,10 @@ impl AsBindGroupShaderType<StandardMaterialUniform> for StandardMaterial {
         if self.depth_map.is_some() {
             flags |= StandardMaterialFlags::DEPTH_MAP;
         }
+        #[cfg(feature = "pbr_planar_reflections")]
+        if self.planar_reflection_texture.is_some() {
+            flags |= StandardMaterialFlags::PLANAR_REFLECTION_TEXTURE;
+        }
         #[cfg(feature = "pbr_transmission_textures")]
         {
             if self.specular_transmission_texture.is_some() {
@@ -0,0 +1,476 @@
+//! Planar reflections, which render the scene mirrored across a plane.
+//!
+//! A [`PlanarReflectionProbe`] spawns a camera that renders the view of
+//! another camera reflected across the plane of the probe into an offscreen
+//! texture. A [`StandardMaterial`] with a
+//! [`StandardMaterial::planar_reflection_texture`] samples that texture at the
+//! screen position of each fragment, so the reflection lines up exactly on
+//! mirrors and water planes, without the artifacts of screen space reflections
+//! or the parallax errors of reflection probes.
+
+use bevy_app::{App, Plugin, PostUpdate};
+use bevy_asset::{load_internal_asset, Assets, Handle, RenderAssetUsages};
+use bevy_core_pipeline::{
+    core_3d::Camera3d,
+    tonemapping::{DebandDither, Tonemapping},
+};
+use bevy_ecs::prelude::*;
+use bevy_image::Image;
+use bevy_math::{Mat4, UVec2, Vec3, Vec4};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::{
+    camera::{Camera, CameraUpdateSystem, Exposure, Projection, RenderTarget},
+    extract_component::{ExtractComponent, ExtractComponentPlugin},
+    render_resource::{Extent3d, Shader, TextureDimension, TextureFormat, TextureUsages},
+    view::{ExtractedView, Msaa, RenderLayers, VisibilitySystems, VisibleEntities},
+    Render, RenderApp, RenderSet,
+};
+use bevy_transform::{
+    components::{GlobalTransform, Transform},
+    TransformSystem,
+};
+
+use crate::{MeshMaterial3d, StandardMaterial};
+
+pub const PLANAR_REFLECTION_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(16334012531797213578);
+
+/// Adds support for [`PlanarReflectionProbe`]s.
+pub struct PlanarReflectionPlugin;
+
+impl Plugin for PlanarReflectionPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            PLANAR_REFLECTION_SHADER_HANDLE,
+            "planar_reflection.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.register_type::<PlanarReflectionProbe>()
+            .add_plugins(ExtractComponentPlugin::<PlanarReflectionCamera>::default())
+            .add_systems(
+                PostUpdate,
+                (
+                    update_planar_reflection_cameras
+                        .after(TransformSystem::TransformPropagate)
+                        .after(CameraUpdateSystem)
+                        .before(VisibilitySystems::UpdateFrusta),
+                    apply_planar_reflections_to_materials.after(update_planar_reflection_cameras),
+                    hide_planar_reflection_probes_from_their_cameras
+                        .after(VisibilitySystems::CheckVisibility),
+                ),
+            );
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app.add_systems(
+            Render,
+            apply_planar_reflection_clip_planes.in_set(RenderSet::ManageViews),
+        );
+    }
+}
+
+/// Renders the scene mirrored across the plane of this entity, for mirrors
+/// and water planes.
+///
+/// The plane passes through the origin of the entity, and its normal is the
+/// local up (+Y) direction of the entity, like [`Plane3d::default`].
+///
+/// A camera is spawned that renders the view of [`PlanarReflectionProbe::camera`]
+/// reflected across the plane into an offscreen texture. If this entity has a
+/// [`MeshMaterial3d<StandardMaterial>`], the texture is assigned to the
+/// [`StandardMaterial::planar_reflection_texture`] of its material, which should
+/// therefore not be shared with other meshes. Otherwise, it can be retrieved
+/// from the [`PlanarReflectionTarget`] component, which is added to this
+/// entity.
+///
+/// The mesh of this entity is never rendered into its own reflection. Anything
+/// on the other side of the plane from the camera is clipped away.
+///
+/// Since the reflection is a single image of the scene, it's as sharp as the
+/// mirror is smooth, regardless of the roughness of the material. Planar
+/// reflections are only supported with forward rendering.
+///
+/// [`Plane3d::default`]: bevy_math::primitives::Plane3d
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component, Default, Debug)]
+#[require(Transform)]
+pub struct PlanarReflectionProbe {
+    /// The camera whose view is reflected.
+    ///
+    /// If this is `None`, the default, the active 3D camera with the highest
+    /// [`Camera::order`] is used.
+    pub camera: Option<Entity>,
+
+    /// The resolution of the reflection, as a fraction of the resolution of
+    /// the viewport of the camera.
+    ///
+    /// Defaults to 0.5.
+    pub resolution_scale: f32,
+
+    /// The render layers that aren't rendered into the reflection.
+    ///
+    /// This is useful to hide objects that shouldn't be reflected, such as the
+    /// meshes of other mirrors facing this one.
+    pub excluded_layers: RenderLayers,
+}
+
+impl Default for PlanarReflectionProbe {
+    fn default() -> Self {
+        Self {
+            camera: None,
+            resolution_scale: 0.5,
+            excluded_layers: RenderLayers::none(),
+        }
+    }
+}
+
+/// The camera and the texture that a [`PlanarReflectionProbe`] renders its
+/// reflection with.
+///
+/// This is added to the entity with the probe.
+#[derive(Component, Clone, Debug)]
+pub struct PlanarReflectionTarget {
+    camera: Entity,
+    image: Handle<Image>,
+    size: UVec2,
+}
+
+impl PlanarReflectionTarget {
+    /// Returns the camera that renders the reflection.
+    pub fn camera(&self) -> Entity {
+        self.camera
+    }
+
+    /// Returns the texture that the reflection is rendered into.
+    ///
+    /// The texture is replaced when the size of the reflection changes.
+    pub fn image(&self) -> &Handle<Image> {
+        &self.image
+    }
+}
+
+/// Marks a camera that renders the reflection of a [`PlanarReflectionProbe`].
+#[derive(Component, ExtractComponent, Clone, Copy, Debug)]
+pub struct PlanarReflectionCamera {
+    /// The entity with the [`PlanarReflectionProbe`].
+    pub probe: Entity,
+
+    /// The plane that clips away everything behind the mirror, in world
+    /// space, as the normal followed by the negated distance from the origin.
+    pub clip_plane: Vec4,
+}
+
+/// Spawns, moves and resizes the cameras of the [`PlanarReflectionProbe`]s
+/// to match the cameras whose view they reflect.
+pub fn update_planar_reflection_cameras(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut probes: Query<(
+        Entity,
+        &PlanarReflectionProbe,
+        &GlobalTransform,
+        Option<&mut PlanarReflectionTarget>,
+    )>,
+    removed_probes: Query<Entity, (With<PlanarReflectionTarget>, Without<PlanarReflectionProbe>)>,
+    source_cameras: Query<
+        (
+            &Camera,
+            Ref<Projection>,
+            &GlobalTransform,
+            Option<&RenderLayers>,
+            Option<&Exposure>,
+            Option<&Msaa>,
+        ),
+        (With<Camera3d>, Without<PlanarReflectionCamera>),
+    >,
+    mut reflection_cameras: Query<
+        (
+            Entity,
+            &mut Camera,
+            &mut Projection,
+            &mut Transform,
+            &mut GlobalTransform,
+            &mut RenderLayers,
+            &mut Exposure,
+            &mut Msaa,
+            &mut PlanarReflectionCamera,
+        ),
+        Without<PlanarReflectionProbe>,
+    >,
+) {
+    for entity in &removed_probes {
+        commands.entity(entity).remove::<PlanarReflectionTarget>();
+    }
+    for (camera_entity, .., reflection) in &reflection_cameras {
+        if !probes.contains(reflection.probe) {
+            commands.entity(camera_entity).despawn();
+        }
+    }
+
+    for (probe_entity, probe, probe_transform, target) in &mut probes {
+        let source_camera = match probe.camera {
+            Some(camera) => source_cameras.get(camera).ok(),
+            None => source_cameras
+                .iter()
+                .filter(|(camera, ..)| camera.is_active)
+                .max_by_key(|(camera, ..)| camera.order),
+        };
+        let reflection_camera = target
+            .as_ref()
+            .and_then(|target| reflection_cameras.get_mut(target.camera).ok());
+
+        let Some((
+            source_camera,
+            source_projection,
+            source_transform,
+            source_layers,
+            source_exposure,
+            source_msaa,
+        )) = source_camera
+        else {
+            if let Some((_, mut camera, ..)) = reflection_camera {
+                camera.is_active = false;
+            }
+            continue;
+        };
+        let Some(viewport_size) = source_camera.physical_viewport_size() else {
+            continue;
+        };
+        let size = (viewport_size.as_vec2() * probe.resolution_scale)
+            .as_uvec2()
+            .max(UVec2::ONE);
+
+        let normal = *probe_transform.up();
+        let origin = probe_transform.translation();
+        let reflect = |vector: Vec3| vector - 2.0 * vector.dot(normal) * normal;
+
+        // The reflected camera is rotated, rather than mirrored, so that
+        // triangles keep their winding order. This makes the texture a
+        // horizontally flipped image of the reflection, which the shader
+        // accounts for.
+        let camera_offset = source_transform.translation() - origin;
+        let transform = Transform::from_translation(origin + reflect(camera_offset)).looking_to(
+            reflect(*source_transform.forward()),
+            reflect(*source_transform.up()),
+        );
+
+        // Keep whatever is on the same side of the plane as the camera.
+        let side = camera_offset.dot(normal).signum();
+        let clip_plane = (normal * side).extend(-normal.dot(origin) * side);
+
+        let source_layers = source_layers.cloned().unwrap_or_default();
+        let layers =
+            source_layers.intersection(&source_layers.symmetric_difference(&probe.excluded_layers));
+        let exposure = source_exposure.copied().unwrap_or_default();
+        let msaa = source_msaa.copied().unwrap_or_default();
+
+        match (target, reflection_camera) {
+            (
+                Some(mut target),
+                Some((
+                    _,
+                    mut camera,
+                    mut projection,
+                    mut camera_transform,
+                    mut camera_global_transform,
+                    mut camera_layers,
+                    mut camera_exposure,
+                    mut camera_msaa,
+                    mut reflection,
+                )),
+            ) => {
+                if target.size != size {
+                    target.image = images.add(new_planar_reflection_image(size));
+                    target.size = size;
+                    camera.target = RenderTarget::Image(target.image.clone().into());
+                }
+                camera.is_active = source_camera.is_active;
+                camera.order = source_camera.order - 1;
+                if source_projection.is_changed() {
+                    *projection = source_projection.clone();
+                }
+                *camera_transform = transform;
+                *camera_global_transform = GlobalTransform::from(transform);
+                camera_layers.set_if_neq(layers);
+                if camera_exposure.ev100 != exposure.ev100 {
+                    *camera_exposure = exposure;
+                }
+                camera_msaa.set_if_neq(msaa);
+                reflection.clip_plane = clip_plane;
+            }
+            _ => {
+                let image = images.add(new_planar_reflection_image(size));
+                let camera = commands
+                    .spawn((
+                        Camera3d::default(),
+                        Camera {
+                            target: RenderTarget::Image(image.clone().into()),
+                            order: source_camera.order - 1,
+                            is_active: source_camera.is_active,
+                            hdr: true,
+                            clear_color: source_camera.clear_color.clone(),
+                            ..Default::default()
+                        },
+                        // The reflection is tonemapped along with the view
+                        // that it's reflected in.
+                        Tonemapping::None,
+                        DebandDither::Disabled,
+                        source_projection.clone(),
+                        transform,
+                        GlobalTransform::from(transform),
+                        layers,
+                        exposure,
+                        msaa,
+                        PlanarReflectionCamera {
+                            probe: probe_entity,
+                            clip_plane,
+                        },
+                    ))
+                    .id();
+                commands
+                    .entity(probe_entity)
+                    .insert(PlanarReflectionTarget {
+                        camera,
+                        image,
+                        size,
+                    });
+            }
+        }
+    }
+}
+
+/// Assigns the textures of the [`PlanarReflectionProbe`]s to the materials of
+/// their meshes.
+pub fn apply_planar_reflections_to_materials(
+    probes: Query<
+        (&PlanarReflectionTarget, &MeshMaterial3d<StandardMaterial>),
+        Or<(
+            Changed<PlanarReflectionTarget>,
+            Changed<MeshMaterial3d<StandardMaterial>>,
+        )>,
+    >,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (target, material) in &probes {
+        let needs_update = materials.get(material).is_some_and(|material| {
+            material.planar_reflection_texture.as_ref() != Some(&target.image)
+        });
+        if !needs_update {
+            continue;
+        }
+        if let Some(material) = materials.get_mut(material) {
+            material.planar_reflection_texture = Some(target.image.clone());
+        }
+    }
+}
+
+/// Removes each [`PlanarReflectionProbe`] from the visible entities of its own
+/// camera, as its material can't sample the texture being rendered to.
+pub fn hide_planar_reflection_probes_from_their_cameras(
+    mut cameras: Query<(&PlanarReflectionCamera, &mut VisibleEntities)>,
+) {
+    for (reflection, mut visible_entities) in &mut cameras {
+        for entities in visible_entities.entities.values_mut() {
+            entities.retain(|&entity| entity != reflection.probe);
+        }
+    }
+}
+
+/// Makes the near plane of the views of the [`PlanarReflectionCamera`]s
+/// coincide with their clip planes, so that nothing behind the mirror is
+/// reflected.
+pub fn apply_planar_reflection_clip_planes(
+    mut views: Query<(&mut ExtractedView, &PlanarReflectionCamera)>,
+) {
+    for (mut view, reflection) in &mut views {
+        // Planes transform by the inverse transpose of the matrix that
+        // transforms points.
+        let view_clip_plane =
+            view.world_from_view.compute_matrix().transpose() * reflection.clip_plane;
+        view.clip_from_view = oblique_clip_from_view(view.clip_from_view, view_clip_plane);
+    }
+}
+
+/// Returns a reversed-Z projection whose near plane is the given view space
+/// plane, which keeps the points on the side that its normal points to.
+///
+/// This is Lengyel's oblique near plane clipping, adapted to reversed Z: the
+/// far plane is tilted so that it still contains the farthest corner of the
+/// frustum, which minimizes the loss of depth precision.
+fn oblique_clip_from_view(clip_from_view: Mat4, view_clip_plane: Vec4) -> Mat4 {
+    let view_from_clip = clip_from_view.inverse();
+    let clip_space_plane = view_from_clip.transpose() * view_clip_plane;
+    let far_corner = view_from_clip
+        * Vec4::new(
+            clip_space_plane.x.signum(),
+            clip_space_plane.y.signum(),
+            0.0,
+            1.0,
+        );
+    let far_corner_distance = view_clip_plane.dot(far_corner);
+    if far_corner_distance <= 0.0 {
+        // The whole frustum is behind the plane.
+        return clip_from_view;
+    }
+
+    // Replace the row that computes the depth so that the near plane, `z <= w`,
+    // becomes the clip plane, and the far plane, `z >= 0`, passes through the
+    // far corner.
+    let mut rows = clip_from_view.transpose();
+    rows.z_axis = rows.w_axis - view_clip_plane / far_corner_distance;
+    rows.transpose()
+}
+
+fn new_planar_reflection_image(size: UVec2) -> Image {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0; 8],
+        TextureFormat::Rgba16Float,
+        RenderAssetUsages::default(),
+    );
+    image.texture_descriptor.usage =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_math::{Mat4, Vec3, Vec4};
+
+    use super::oblique_clip_from_view;
+
+    #[test]
+    fn oblique_near_plane() {
+        let clip_from_view = Mat4::perspective_infinite_reverse_rh(1.0, 1.5, 0.1);
+        // Keep what is above y = 1 in view space, which puts the camera on the
+        // clipped side, as for cameras reflected across a mirror.
+        let plane = Vec4::new(0.0, 1.0, 0.0, -1.0);
+        let oblique = oblique_clip_from_view(clip_from_view, plane);
+
+        let depth = |point: Vec3| {
+            let clip = oblique * point.extend(1.0);
+            clip.z / clip.w
+        };
+
+        // Points on the plane are on the near plane.
+        assert!((depth(Vec3::new(0.0, 1.0, -5.0)) - 1.0).abs() < 1e-4);
+        assert!((depth(Vec3::new(2.0, 1.0, -20.0)) - 1.0).abs() < 1e-4);
+        // Points above the plane are visible, and closer ones are in front.
+        let near = depth(Vec3::new(0.0, 2.0, -5.0));
+        let far = depth(Vec3::new(0.0, 20.0, -50.0));
+        assert!((0.0..1.0).contains(&near));
+        assert!((0.0..1.0).contains(&far));
+        assert!(near > far);
+        // Points below the plane are clipped.
+        assert!(depth(Vec3::new(0.0, 0.5, -5.0)) > 1.0);
+    }
+}
@@ -0,0 +1,24 @@
+#define_import_path bevy_pbr::planar_reflection
+
+#import bevy_pbr::lighting::{LightingInput, LAYER_BASE}
+
+// Returns the specular light reflected from the radiance of a planar
+// reflection, using the same split sum approximation of the BRDF as
+// environment maps do.
+fn planar_reflection_light(
+    input: ptr<function, LightingInput>,
+    radiance: vec3<f32>,
+) -> vec3<f32> {
+    // Unpack.
+    let roughness = (*input).layers[LAYER_BASE].roughness;
+    let NdotV = (*input).layers[LAYER_BASE].NdotV;
+    let F_ab = (*input).F_ab;
+    let F0 = (*input).F0_;
+
+    // See `environment_map_light` for the details of these terms.
+    let specular_occlusion = saturate(dot(F0, vec3(50.0 * 0.33)));
+    let Fr = max(vec3(1.0 - roughness), F0) - F0;
+    let kS = F0 + Fr * pow(1.0 - NdotV, 5.0);
+    let Ess = F_ab.x + F_ab.y;
+    return kS * Ess * specular_occlusion * radiance;
+}
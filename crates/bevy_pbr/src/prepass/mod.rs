@@ -485,6 +485,10 @@ where
             shader_defs.push("DEFERRED_PREPASS".into());
         }
 
+        if key.mesh_key.contains(MeshPipelineKey::ENTITY_INDEX_PREPASS) {
+            shader_defs.push("ENTITY_INDEX_PREPASS".into());
+        }
+
         if key.mesh_key.contains(MeshPipelineKey::LIGHTMAPPED) {
             shader_defs.push("LIGHTMAP".into());
         }
@@ -534,7 +538,8 @@ where
         if key.mesh_key.intersects(
             MeshPipelineKey::NORMAL_PREPASS
                 | MeshPipelineKey::MOTION_VECTOR_PREPASS
-                | MeshPipelineKey::DEFERRED_PREPASS,
+                | MeshPipelineKey::DEFERRED_PREPASS
+                | MeshPipelineKey::ENTITY_INDEX_PREPASS,
         ) {
             shader_defs.push("PREPASS_FRAGMENT".into());
         }
@@ -558,6 +563,7 @@ where
             key.mesh_key
                 .contains(MeshPipelineKey::MOTION_VECTOR_PREPASS),
             key.mesh_key.contains(MeshPipelineKey::DEFERRED_PREPASS),
+            key.mesh_key.contains(MeshPipelineKey::ENTITY_INDEX_PREPASS),
         );
 
         if targets.iter().all(Option::is_none) {
@@ -801,6 +807,7 @@ pub fn queue_prepass_material_meshes<M: Material>(
         Option<&NormalPrepass>,
         Option<&MotionVectorPrepass>,
         Option<&DeferredPrepass>,
+        Has<EntityIndexPrepass>,
     )>,
 ) where
     M::Data: PartialEq + Eq + Hash + Clone,
@@ -829,6 +836,7 @@ pub fn queue_prepass_material_meshes<M: Material>(
         normal_prepass,
         motion_vector_prepass,
         deferred_prepass,
+        entity_index_prepass,
     ) in &views
     {
         let (
@@ -862,6 +870,9 @@ pub fn queue_prepass_material_meshes<M: Material>(
         if motion_vector_prepass.is_some() {
             view_key |= MeshPipelineKey::MOTION_VECTOR_PREPASS;
         }
+        if entity_index_prepass {
+            view_key |= MeshPipelineKey::ENTITY_INDEX_PREPASS;
+        }
 
         for (render_entity, visible_entity) in visible_entities.iter::<Mesh3d>() {
             let Some(material_asset_id) = render_material_instances.get(visible_entity) else {
@@ -931,6 +942,13 @@ pub fn queue_prepass_material_meshes<M: Material>(
                 mesh_key |= MeshPipelineKey::VISIBILITY_RANGE_DITHER;
             }
 
+            if mesh_instance
+                .flags
+                .contains(RenderMeshInstanceFlags::PRESKINNED)
+            {
+                mesh_key |= MeshPipelineKey::PRESKINNED;
+            }
+
             // If the previous frame has skins or morph targets, note that.
             if motion_vector_prepass.is_some() {
                 if mesh_instance
@@ -42,13 +42,18 @@ fn morph_vertex(vertex_in: Vertex) -> Vertex {
 // bother morphing the normals and tangents.
 fn morph_prev_vertex(vertex_in: Vertex) -> Vertex {
     var vertex = vertex_in;
+    // The morph target texture is indexed relative to the mesh's first vertex,
+    // just like in `morph_vertex`.
+    let first_vertex = mesh[vertex.instance_index].first_vertex_index;
+    let vertex_index = vertex.index - first_vertex;
+
     let weight_count = morph::layer_count();
     for (var i: u32 = 0u; i < weight_count; i ++) {
         let weight = morph::prev_weight_at(i);
         if weight == 0.0 {
             continue;
         }
-        vertex.position += weight * morph::morph(vertex.index, morph::position_offset, i);
+        vertex.position += weight * morph::morph(vertex_index, morph::position_offset, i);
         // Don't bother morphing normals and tangents; we don't need them for
         // motion vector calculation.
     }
@@ -69,11 +74,24 @@ fn vertex(vertex_no_morph: Vertex) -> VertexOutput {
     let mesh_world_from_local = mesh_functions::get_world_from_local(vertex_no_morph.instance_index);
 
 #ifdef SKINNED
+#ifdef PRESKINNED
+    // The vertex was already skinned by the pre-skinning compute shader.
+    let preskinned = skinning::preskinned_vertex(vertex_no_morph.index, vertex_no_morph.instance_index);
+    vertex.position = preskinned.position.xyz;
+#ifdef NORMAL_PREPASS_OR_DEFERRED_PREPASS
+    vertex.normal = preskinned.normal.xyz;
+#ifdef VERTEX_TANGENTS
+    vertex.tangent = preskinned.tangent;
+#endif // VERTEX_TANGENTS
+#endif // NORMAL_PREPASS_OR_DEFERRED_PREPASS
+    var world_from_local = skinning::PRESKINNED_WORLD_FROM_LOCAL;
+#else // PRESKINNED
     var world_from_local = skinning::skin_model(
         vertex.joint_indices,
         vertex.joint_weights,
         vertex_no_morph.instance_index
     );
+#endif // PRESKINNED
 #else // SKINNED
     // Use vertex_no_morph.instance_index instead of vertex.instance_index to work around a wgpu dx12 bug.
     // See https://github.com/gfx-rs/naga/issues/2416
@@ -126,13 +144,22 @@ fn vertex(vertex_no_morph: Vertex) -> VertexOutput {
     // to know where the vertex was last frame.
 #ifdef MOTION_VECTOR_PREPASS
 
+#ifdef PRESKINNED
+    // The pre-skinning compute shader also skinned the vertex with the
+    // previous frame's joint matrices.
+    out.previous_world_position = vec4<f32>(preskinned.previous_position.xyz, 1.0);
+#else   // PRESKINNED
+
     // Take morph targets into account.
 #ifdef MORPH_TARGETS
 
 #ifdef HAS_PREVIOUS_MORPH
     let prev_vertex = morph_prev_vertex(vertex_no_morph);
 #else   // HAS_PREVIOUS_MORPH
-    let prev_vertex = vertex_no_morph;
+    // There are no weights from last frame (e.g. the mesh just became
+    // visible), so assume the weights didn't change rather than reporting
+    // motion from the unmorphed mesh to the morphed one.
+    let prev_vertex = vertex;
 #endif  // HAS_PREVIOUS_MORPH
 
 #else   // MORPH_TARGETS
@@ -149,7 +176,11 @@ fn vertex(vertex_no_morph: Vertex) -> VertexOutput {
         vertex_no_morph.instance_index
     );
 #else   // HAS_PREVIOUS_SKIN
-    let prev_model = mesh_functions::get_previous_world_from_local(prev_vertex.instance_index);
+    // There are no joint matrices from last frame (e.g. the mesh just became
+    // visible), so assume the joints didn't move. The mesh's own transform
+    // doesn't apply to skinned vertices, so falling back to it would report
+    // motion from the bind pose.
+    let prev_model = world_from_local;
 #endif  // HAS_PREVIOUS_SKIN
 
 #else   // SKINNED
@@ -160,6 +191,7 @@ fn vertex(vertex_no_morph: Vertex) -> VertexOutput {
         prev_model,
         vec4<f32>(prev_vertex.position, 1.0)
     );
+#endif  // PRESKINNED
 #endif // MOTION_VECTOR_PREPASS
 
 #ifdef VERTEX_OUTPUT_INSTANCE_INDEX
@@ -212,6 +244,13 @@ fn fragment(in: VertexOutput) -> FragmentOutput {
     out.deferred_lighting_pass_id = 1u;
 #endif
 
+#ifdef ENTITY_INDEX_PREPASS
+    out.entity = vec2(
+        mesh[in.instance_index].main_entity_index,
+        mesh[in.instance_index].main_entity_generation,
+    );
+#endif
+
     return out;
 }
 #endif // PREPASS_FRAGMENT
@@ -32,6 +32,8 @@ struct Vertex {
 
 #ifdef MORPH_TARGETS
     @builtin(vertex_index) index: u32,
+#else ifdef PRESKINNED
+    @builtin(vertex_index) index: u32,
 #endif // MORPH_TARGETS
 }
 
@@ -91,6 +93,11 @@ struct FragmentOutput {
     @location(3) deferred_lighting_pass_id: u32,
 #endif
 
+#ifdef ENTITY_INDEX_PREPASS
+    // The index and the high 32 bits of the main world entity of the mesh.
+    @location(4) entity: vec2<u32>,
+#endif
+
 #ifdef UNCLIPPED_DEPTH_ORTHO_EMULATION
     @builtin(frag_depth) frag_depth: f32,
 #endif // UNCLIPPED_DEPTH_ORTHO_EMULATION
@@ -45,15 +45,36 @@
     array<IndirectParametersNonIndexed>;
 #endif  // INDEXED
 
+#ifdef LATE_PHASE
+// The batches that the late mesh preprocessing pass found visible meshes in.
+//
+// With occlusion culling, the meshes of the main pass phases are only
+// processed after the prepass, so the indirect parameters of their batches are
+// built again afterwards.
+struct LateBatches {
+    count: u32,
+    indices: array<u32>,
+}
+
+@group(1) @binding(0) var<storage> late_batches: LateBatches;
+#endif  // LATE_PHASE
+
 @compute
 @workgroup_size(64)
 fn main(@builtin(global_invocation_id) global_invocation_id: vec3<u32>) {
     // Figure out our instance index (i.e. batch index). If this thread doesn't
     // correspond to any index, bail.
+#ifdef LATE_PHASE
+    if (global_invocation_id.x >= late_batches.count) {
+        return;
+    }
+    let instance_index = late_batches.indices[global_invocation_id.x];
+#else   // LATE_PHASE
     let instance_index = global_invocation_id.x;
     if (instance_index >= arrayLength(&indirect_parameters_metadata)) {
         return;
     }
+#endif  // LATE_PHASE
 
     // Unpack the metadata for this batch.
     let mesh_index = indirect_parameters_metadata[instance_index].mesh_index;
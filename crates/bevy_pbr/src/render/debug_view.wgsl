@@ -0,0 +1,39 @@
+#define_import_path bevy_pbr::debug_view
+
+#import bevy_pbr::{
+    forward_io::VertexOutput,
+    pbr_types::PbrInput,
+}
+
+// The number of checkerboard cells along each UV axis.
+const UV_CHECKER_CELLS: f32 = 8.0;
+
+// How much each surface adds to the overdraw heatmap.
+const OVERDRAW_COLOR: vec4<f32> = vec4<f32>(0.1, 0.03, 0.01, 1.0);
+
+// Returns the color of the fragment in the debug view of the camera, instead of its lit color.
+fn debug_view_color(in: VertexOutput, pbr_input: PbrInput, lit_color: vec4<f32>) -> vec4<f32> {
+#ifdef DEBUG_VIEW_UNLIT
+    return pbr_input.material.base_color;
+#else ifdef DEBUG_VIEW_WORLD_NORMALS
+    return vec4(pbr_input.N * 0.5 + 0.5, 1.0);
+#else ifdef DEBUG_VIEW_UV_CHECKER
+#ifdef VERTEX_UVS_A
+    let cell = vec2<i32>(floor(in.uv * UV_CHECKER_CELLS));
+    let shade = select(0.2, 0.8, ((cell.x + cell.y) & 1) == 0);
+    return vec4(mix(vec3(shade), vec3(fract(in.uv), 0.0), 0.3), 1.0);
+#else
+    return vec4(1.0, 0.0, 1.0, 1.0);
+#endif
+#else ifdef DEBUG_VIEW_LIGHTMAP
+#ifdef LIGHTMAP
+    return vec4(pbr_input.lightmap_light, 1.0);
+#else
+    return vec4(0.0, 0.0, 0.0, 1.0);
+#endif
+#else ifdef DEBUG_VIEW_OVERDRAW
+    return OVERDRAW_COLOR;
+#else
+    return lit_color;
+#endif
+}
@@ -0,0 +1,63 @@
+// Depth pyramid building.
+//
+// This is a compute shader that builds one mip level of the depth pyramid used
+// for occlusion culling from the level below it, or from the depth buffer for
+// the first level. Each texel holds the farthest depth (that is, the smallest,
+// since Bevy uses reversed Z) of all the texels below it that it overlaps, so
+// that testing a mesh against the pyramid never culls a visible mesh.
+
+#ifdef FIRST_MIP
+#ifdef MULTISAMPLED
+@group(0) @binding(0) var source: texture_depth_multisampled_2d;
+#else   // MULTISAMPLED
+@group(0) @binding(0) var source: texture_depth_2d;
+#endif  // MULTISAMPLED
+#else   // FIRST_MIP
+@group(0) @binding(0) var source: texture_2d<f32>;
+#endif  // FIRST_MIP
+
+@group(0) @binding(1) var destination: texture_storage_2d<r32float, write>;
+
+fn load_source(texel: vec2<u32>) -> f32 {
+#ifdef FIRST_MIP
+#ifdef MULTISAMPLED
+    var depth = 1.0;
+    for (var sample_index = 0u; sample_index < textureNumSamples(source); sample_index += 1u) {
+        depth = min(depth, textureLoad(source, texel, sample_index));
+    }
+    return depth;
+#else   // MULTISAMPLED
+    return textureLoad(source, texel, 0);
+#endif  // MULTISAMPLED
+#else   // FIRST_MIP
+    return textureLoad(source, texel, 0).r;
+#endif  // FIRST_MIP
+}
+
+@compute
+@workgroup_size(8, 8, 1)
+fn downsample_depth(@builtin(global_invocation_id) global_invocation_id: vec3<u32>) {
+    let destination_size = textureDimensions(destination);
+    if (any(global_invocation_id.xy >= destination_size)) {
+        return;
+    }
+
+    // Find the range of source texels that this texel overlaps. Since each
+    // level is half the size of the one below it rounded down, this is at most
+    // 3 texels wide.
+    let source_size = textureDimensions(source);
+    let start = global_invocation_id.xy * source_size / destination_size;
+    let end = min(
+        ((global_invocation_id.xy + 1u) * source_size + destination_size - 1u) / destination_size,
+        source_size
+    );
+
+    var depth = 1.0;
+    for (var y = start.y; y < end.y; y += 1u) {
+        for (var x = start.x; x < end.x; x += 1u) {
+            depth = min(depth, load_source(vec2(x, y)));
+        }
+    }
+
+    textureStore(destination, global_invocation_id.xy, vec4(depth));
+}
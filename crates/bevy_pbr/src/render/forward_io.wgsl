@@ -26,6 +26,8 @@ struct Vertex {
 #endif
 #ifdef MORPH_TARGETS
     @builtin(vertex_index) index: u32,
+#else ifdef PRESKINNED
+    @builtin(vertex_index) index: u32,
 #endif
 };
 
@@ -5,6 +5,9 @@
 //! instead of transferring [`MeshUniform`]s to the GPU, we transfer the smaller
 //! [`MeshInputUniform`]s instead and use the GPU to calculate the remaining
 //! derived fields in [`MeshUniform`].
+//!
+//! This pass also performs frustum culling and, for cameras with
+//! [`OcclusionCulling`](crate::OcclusionCulling), occlusion culling.
 
 use core::num::{NonZero, NonZeroU64};
 
@@ -26,7 +29,7 @@ use bevy_render::{
         IndirectParametersBuffers, IndirectParametersIndexed, IndirectParametersMetadata,
         IndirectParametersNonIndexed, PreprocessWorkItem, PreprocessWorkItemBuffers,
     },
-    render_graph::{Node, NodeRunError, RenderGraphApp, RenderGraphContext},
+    render_graph::{Node, NodeRunError, RenderGraphApp, RenderGraphContext, ViewNodeRunner},
     render_resource::{
         binding_types::{storage_buffer, storage_buffer_read_only, uniform_buffer},
         BindGroup, BindGroupEntries, BindGroupLayout, BindingResource, Buffer, BufferBinding,
@@ -48,7 +51,14 @@ use crate::{
     graph::NodePbr, MeshCullingData, MeshCullingDataBuffer, MeshInputUniform, MeshUniform,
 };
 
-use super::ViewLightEntities;
+use super::{
+    occlusion_culling::{
+        add_occlusion_culling_render_systems, is_early_occlusion_culling_phase,
+        is_late_occlusion_culling_phase, DownsampleDepthNode, OcclusionCullingPipelines,
+        OcclusionCullingPlugin, ViewOcclusionCullingBindGroups, ViewOcclusionCullingUniformOffset,
+    },
+    ViewLightEntities,
+};
 
 /// The handle to the `mesh_preprocess.wgsl` compute shader.
 pub const MESH_PREPROCESS_SHADER_HANDLE: Handle<Shader> =
@@ -81,6 +91,11 @@ pub struct GpuMeshPreprocessPlugin {
 /// done by the CPU), transforms them, and, if indirect drawing is on, populates
 /// indirect draw parameter metadata for the subsequent
 /// [`BuildIndirectParametersNode`].
+///
+/// For views with [`OcclusionCulling`](crate::OcclusionCulling), this only processes the meshes of the
+/// prepass phases, which it also tests against the depth pyramid of the
+/// previous frame. The meshes of the main pass phases are processed by the
+/// [`LateGpuPreprocessNode`] instead.
 pub struct GpuPreprocessNode {
     view_query: QueryState<
         (
@@ -88,12 +103,34 @@ pub struct GpuPreprocessNode {
             Read<PreprocessBindGroups>,
             Read<ViewUniformOffset>,
             Has<NoIndirectDrawing>,
+            Option<(
+                Read<ViewOcclusionCullingBindGroups>,
+                Read<ViewOcclusionCullingUniformOffset>,
+            )>,
         ),
         Without<SkipGpuPreprocess>,
     >,
     main_view_query: QueryState<Read<ViewLightEntities>>,
 }
 
+/// The render node for the late mesh preprocessing pass of views with
+/// [`OcclusionCulling`](crate::OcclusionCulling).
+///
+/// This runs after the prepass and the [`DownsampleDepthNode`], and processes
+/// the meshes of the main pass phases, testing them against the depth pyramid
+/// built from the prepass.
+pub struct LateGpuPreprocessNode {
+    view_query: QueryState<
+        (
+            Read<PreprocessBindGroups>,
+            Read<ViewUniformOffset>,
+            Read<ViewOcclusionCullingBindGroups>,
+            Read<ViewOcclusionCullingUniformOffset>,
+        ),
+        Without<SkipGpuPreprocess>,
+    >,
+}
+
 /// The render node for the indirect parameter building pass.
 ///
 /// This node runs a compute shader on the output of the [`GpuPreprocessNode`]
@@ -107,6 +144,15 @@ pub struct BuildIndirectParametersNode {
     >,
 }
 
+/// The render node for the late indirect parameter building pass of views with
+/// [`OcclusionCulling`](crate::OcclusionCulling).
+///
+/// This builds the indirect parameters of the batches in which the
+/// [`LateGpuPreprocessNode`] found visible meshes.
+pub struct LateBuildIndirectParametersNode {
+    view_query: QueryState<Read<ViewOcclusionCullingBindGroups>, Without<SkipGpuPreprocess>>,
+}
+
 /// The compute shader pipelines for the GPU mesh preprocessing and indirect
 /// parameter building passes.
 #[derive(Resource)]
@@ -117,6 +163,12 @@ pub struct PreprocessPipelines {
     /// The pipeline used for GPU culling. This pipeline populates indirect
     /// parameter metadata.
     pub gpu_culling_preprocess: PreprocessPipeline,
+    /// The pipeline used for GPU culling of the prepass phases of views with
+    /// [`OcclusionCulling`](crate::OcclusionCulling), before the prepass.
+    pub early_occlusion_culling_preprocess: PreprocessPipeline,
+    /// The pipeline used for GPU culling of the main pass phases of views with
+    /// [`OcclusionCulling`](crate::OcclusionCulling), after the prepass.
+    pub late_occlusion_culling_preprocess: PreprocessPipeline,
     /// The pipeline used for indexed indirect parameter building.
     ///
     /// This pipeline converts indirect parameter metadata into indexed indirect
@@ -127,12 +179,21 @@ pub struct PreprocessPipelines {
     /// This pipeline converts indirect parameter metadata into non-indexed
     /// indirect parameters.
     pub build_non_indexed_indirect_params: BuildIndirectParametersPipeline,
+    /// The pipeline used for indexed indirect parameter building after the
+    /// late mesh preprocessing pass.
+    pub late_build_indexed_indirect_params: BuildIndirectParametersPipeline,
+    /// The pipeline used for non-indexed indirect parameter building after the
+    /// late mesh preprocessing pass.
+    pub late_build_non_indexed_indirect_params: BuildIndirectParametersPipeline,
 }
 
 /// The pipeline for the GPU mesh preprocessing shader.
 pub struct PreprocessPipeline {
     /// The bind group layout for the compute shader.
     pub bind_group_layout: BindGroupLayout,
+    /// The layout of the second bind group, which holds the depth pyramid, if
+    /// this pipeline performs occlusion culling.
+    pub occlusion_culling_bind_group_layout: Option<BindGroupLayout>,
     /// The pipeline ID for the compute shader.
     ///
     /// This gets filled in `prepare_preprocess_pipelines`.
@@ -143,6 +204,9 @@ pub struct PreprocessPipeline {
 pub struct BuildIndirectParametersPipeline {
     /// The bind group layout for the compute shader.
     pub bind_group_layout: BindGroupLayout,
+    /// The layout of the second bind group, which holds the batches to
+    /// process, if this pipeline runs after the late mesh preprocessing pass.
+    pub late_batches_bind_group_layout: Option<BindGroupLayout>,
     /// The pipeline ID for the compute shader.
     ///
     /// This gets filled in `prepare_preprocess_pipelines`.
@@ -157,6 +221,18 @@ bitflags! {
         ///
         /// This `#define`'s `GPU_CULLING` in the shader.
         const GPU_CULLING = 1;
+        /// Whether meshes are tested against the depth pyramid of the previous
+        /// frame, before the prepass.
+        ///
+        /// This `#define`'s `OCCLUSION_CULLING` and `EARLY_PHASE` in the
+        /// shader.
+        const EARLY_OCCLUSION_CULLING = 2;
+        /// Whether meshes are tested against the depth pyramid of the current
+        /// frame, after the prepass.
+        ///
+        /// This `#define`'s `OCCLUSION_CULLING` and `LATE_PHASE` in the
+        /// shader.
+        const LATE_OCCLUSION_CULLING = 4;
     }
 
     /// Specifies variants of the indirect parameter building shader.
@@ -171,6 +247,11 @@ bitflags! {
         ///
         /// This defines `MULTI_DRAW_INDIRECT_COUNT_SUPPORTED` in the shader.
         const MULTI_DRAW_INDIRECT_COUNT_SUPPORTED = 2;
+        /// Whether the indirect parameter building shader only processes the
+        /// batches found visible by the late mesh preprocessing pass.
+        ///
+        /// This defines `LATE_PHASE` in the shader.
+        const LATE_PHASE = 4;
     }
 }
 
@@ -251,6 +332,8 @@ impl Plugin for GpuMeshPreprocessPlugin {
             "build_indirect_params.wgsl",
             Shader::from_wgsl
         );
+
+        app.add_plugins(OcclusionCullingPlugin);
     }
 
     fn finish(&self, app: &mut App) {
@@ -265,6 +348,8 @@ impl Plugin for GpuMeshPreprocessPlugin {
             return;
         }
 
+        add_occlusion_culling_render_systems(render_app);
+
         render_app
             .init_resource::<PreprocessPipelines>()
             .init_resource::<SpecializedComputePipelines<PreprocessPipeline>>()
@@ -290,9 +375,28 @@ impl Plugin for GpuMeshPreprocessPlugin {
                 Core3d,
                 (NodePbr::GpuPreprocess, NodePbr::BuildIndirectParameters, Node3d::Prepass)
             )
+            .add_render_graph_node::<ViewNodeRunner<DownsampleDepthNode>>(
+                Core3d,
+                NodePbr::DownsampleDepth
+            )
+            .add_render_graph_node::<LateGpuPreprocessNode>(Core3d, NodePbr::LateGpuPreprocess)
+            .add_render_graph_node::<LateBuildIndirectParametersNode>(
+                Core3d,
+                NodePbr::LateBuildIndirectParameters
+            )
             .add_render_graph_edges(
                 Core3d,
                 (NodePbr::GpuPreprocess, NodePbr::BuildIndirectParameters, NodePbr::ShadowPass)
+            )
+            .add_render_graph_edges(
+                Core3d,
+                (
+                    Node3d::EndPrepasses,
+                    NodePbr::DownsampleDepth,
+                    NodePbr::LateGpuPreprocess,
+                    NodePbr::LateBuildIndirectParameters,
+                    Node3d::StartMainPass,
+                )
             );
     }
 }
@@ -346,8 +450,13 @@ impl Node for GpuPreprocessNode {
         // Run the compute passes.
 
         for view_entity in all_views {
-            let Ok((view, bind_groups, view_uniform_offset, no_indirect_drawing)) =
-                self.view_query.get_manual(world, view_entity)
+            let Ok((
+                view,
+                bind_groups,
+                view_uniform_offset,
+                no_indirect_drawing,
+                occlusion_culling,
+            )) = self.view_query.get_manual(world, view_entity)
             else {
                 continue;
             };
@@ -358,36 +467,52 @@ impl Node for GpuPreprocessNode {
                 continue;
             };
 
-            // Select the right pipeline, depending on whether GPU culling is in
-            // use.
-            let maybe_pipeline_id = if !no_indirect_drawing {
-                preprocess_pipelines.gpu_culling_preprocess.pipeline_id
-            } else {
-                preprocess_pipelines.direct_preprocess.pipeline_id
-            };
-
-            // Fetch the pipeline.
-            let Some(preprocess_pipeline_id) = maybe_pipeline_id else {
-                warn!("The build mesh uniforms pipeline wasn't ready");
-                continue;
-            };
-
-            let Some(preprocess_pipeline) =
-                pipeline_cache.get_compute_pipeline(preprocess_pipeline_id)
-            else {
-                // This will happen while the pipeline is being compiled and is fine.
-                continue;
-            };
-
-            compute_pass.set_pipeline(preprocess_pipeline);
-
             // Loop over each render phase.
             for (phase_type_id, phase_work_item_buffers) in view_work_item_buffers {
+                // With occlusion culling, the meshes of the main pass phases
+                // are processed by the late mesh preprocessing pass instead,
+                // and the meshes of the prepass phases are tested against the
+                // depth pyramid of the previous frame.
+                let early_occlusion_culling = match occlusion_culling {
+                    Some(_) if is_late_occlusion_culling_phase(*phase_type_id) => continue,
+                    Some(occlusion_culling) if is_early_occlusion_culling_phase(*phase_type_id) => {
+                        Some(occlusion_culling)
+                    }
+                    _ => None,
+                };
+
                 // Fetch the bind group for the render phase.
                 let Some(phase_bind_groups) = bind_groups.get(phase_type_id) else {
                     continue;
                 };
 
+                // Select the right pipeline, depending on whether GPU culling
+                // and occlusion culling are in use.
+                let maybe_pipeline_id = if early_occlusion_culling.is_some() {
+                    preprocess_pipelines
+                        .early_occlusion_culling_preprocess
+                        .pipeline_id
+                } else if !no_indirect_drawing {
+                    preprocess_pipelines.gpu_culling_preprocess.pipeline_id
+                } else {
+                    preprocess_pipelines.direct_preprocess.pipeline_id
+                };
+
+                // Fetch the pipeline.
+                let Some(preprocess_pipeline_id) = maybe_pipeline_id else {
+                    warn!("The build mesh uniforms pipeline wasn't ready");
+                    continue;
+                };
+
+                let Some(preprocess_pipeline) =
+                    pipeline_cache.get_compute_pipeline(preprocess_pipeline_id)
+                else {
+                    // This will happen while the pipeline is being compiled and is fine.
+                    continue;
+                };
+
+                compute_pass.set_pipeline(preprocess_pipeline);
+
                 // If we're drawing indirectly, make sure the mesh preprocessing
                 // shader has access to the view info it needs to do culling.
                 let mut dynamic_offsets: SmallVec<[u32; 1]> = smallvec![];
@@ -430,6 +555,15 @@ impl Node for GpuPreprocessNode {
                         // Transform and cull indexed meshes if there are any.
                         if let Some(indexed_bind_group) = maybe_indexed_bind_group {
                             compute_pass.set_bind_group(0, indexed_bind_group, &dynamic_offsets);
+                            if let Some((occlusion_culling_bind_groups, uniform_offset)) =
+                                early_occlusion_culling
+                            {
+                                compute_pass.set_bind_group(
+                                    1,
+                                    &occlusion_culling_bind_groups.preprocess_indexed,
+                                    &[**uniform_offset],
+                                );
+                            }
                             let workgroup_count = indexed_buffer.len().div_ceil(WORKGROUP_SIZE);
                             if workgroup_count > 0 {
                                 compute_pass.dispatch_workgroups(workgroup_count as u32, 1, 1);
@@ -443,6 +577,15 @@ impl Node for GpuPreprocessNode {
                                 non_indexed_bind_group,
                                 &dynamic_offsets,
                             );
+                            if let Some((occlusion_culling_bind_groups, uniform_offset)) =
+                                early_occlusion_culling
+                            {
+                                compute_pass.set_bind_group(
+                                    1,
+                                    &occlusion_culling_bind_groups.preprocess_non_indexed,
+                                    &[**uniform_offset],
+                                );
+                            }
                             let workgroup_count = non_indexed_buffer.len().div_ceil(WORKGROUP_SIZE);
                             if workgroup_count > 0 {
                                 compute_pass.dispatch_workgroups(workgroup_count as u32, 1, 1);
@@ -457,6 +600,129 @@ impl Node for GpuPreprocessNode {
     }
 }
 
+impl FromWorld for LateGpuPreprocessNode {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            view_query: QueryState::new(world),
+        }
+    }
+}
+
+impl Node for LateGpuPreprocessNode {
+    fn update(&mut self, world: &mut World) {
+        self.view_query.update_archetypes(world);
+    }
+
+    fn run<'w>(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext<'w>,
+        world: &'w World,
+    ) -> Result<(), NodeRunError> {
+        let view_entity = graph.view_entity();
+        let Ok((
+            bind_groups,
+            view_uniform_offset,
+            occlusion_culling_bind_groups,
+            occlusion_culling_uniform_offset,
+        )) = self.view_query.get_manual(world, view_entity)
+        else {
+            return Ok(());
+        };
+
+        // Grab the work item buffers for this view.
+        let BatchedInstanceBuffers {
+            work_item_buffers: ref index_buffers,
+            ..
+        } = world.resource::<BatchedInstanceBuffers<MeshUniform, MeshInputUniform>>();
+        let Some(view_work_item_buffers) = index_buffers.get(&view_entity) else {
+            return Ok(());
+        };
+
+        // Fetch the pipeline.
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let preprocess_pipelines = world.resource::<PreprocessPipelines>();
+        let Some(preprocess_pipeline_id) = preprocess_pipelines
+            .late_occlusion_culling_preprocess
+            .pipeline_id
+        else {
+            warn!("The late build mesh uniforms pipeline wasn't ready");
+            return Ok(());
+        };
+        let Some(preprocess_pipeline) = pipeline_cache.get_compute_pipeline(preprocess_pipeline_id)
+        else {
+            // This will happen while the pipeline is being compiled and is fine.
+            return Ok(());
+        };
+
+        let mut compute_pass =
+            render_context
+                .command_encoder()
+                .begin_compute_pass(&ComputePassDescriptor {
+                    label: Some("late mesh preprocessing"),
+                    timestamp_writes: None,
+                });
+        compute_pass.set_pipeline(preprocess_pipeline);
+
+        // Loop over each main pass phase.
+        for (phase_type_id, phase_work_item_buffers) in view_work_item_buffers {
+            if !is_late_occlusion_culling_phase(*phase_type_id) {
+                continue;
+            }
+
+            let Some(PhasePreprocessBindGroups::Indirect {
+                indexed: maybe_indexed_bind_group,
+                non_indexed: maybe_non_indexed_bind_group,
+            }) = bind_groups.get(phase_type_id)
+            else {
+                continue;
+            };
+            let PreprocessWorkItemBuffers::Indirect {
+                indexed: indexed_buffer,
+                non_indexed: non_indexed_buffer,
+                ..
+            } = phase_work_item_buffers
+            else {
+                continue;
+            };
+
+            // Transform and cull indexed meshes if there are any.
+            if let Some(indexed_bind_group) = maybe_indexed_bind_group {
+                compute_pass.set_bind_group(0, indexed_bind_group, &[view_uniform_offset.offset]);
+                compute_pass.set_bind_group(
+                    1,
+                    &occlusion_culling_bind_groups.preprocess_indexed,
+                    &[**occlusion_culling_uniform_offset],
+                );
+                let workgroup_count = indexed_buffer.len().div_ceil(WORKGROUP_SIZE);
+                if workgroup_count > 0 {
+                    compute_pass.dispatch_workgroups(workgroup_count as u32, 1, 1);
+                }
+            }
+
+            // Transform and cull non-indexed meshes if there are any.
+            if let Some(non_indexed_bind_group) = maybe_non_indexed_bind_group {
+                compute_pass.set_bind_group(
+                    0,
+                    non_indexed_bind_group,
+                    &[view_uniform_offset.offset],
+                );
+                compute_pass.set_bind_group(
+                    1,
+                    &occlusion_culling_bind_groups.preprocess_non_indexed,
+                    &[**occlusion_culling_uniform_offset],
+                );
+                let workgroup_count = non_indexed_buffer.len().div_ceil(WORKGROUP_SIZE);
+                if workgroup_count > 0 {
+                    compute_pass.dispatch_workgroups(workgroup_count as u32, 1, 1);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl FromWorld for BuildIndirectParametersNode {
     fn from_world(world: &mut World) -> Self {
         Self {
@@ -565,16 +831,141 @@ impl Node for BuildIndirectParametersNode {
     }
 }
 
+impl FromWorld for LateBuildIndirectParametersNode {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            view_query: QueryState::new(world),
+        }
+    }
+}
+
+impl Node for LateBuildIndirectParametersNode {
+    fn update(&mut self, world: &mut World) {
+        self.view_query.update_archetypes(world);
+    }
+
+    fn run<'w>(
+        &self,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext<'w>,
+        world: &'w World,
+    ) -> Result<(), NodeRunError> {
+        // Fetch the bind groups.
+        let Ok(occlusion_culling_bind_groups) =
+            self.view_query.get_manual(world, graph.view_entity())
+        else {
+            return Ok(());
+        };
+        let Some(build_indirect_params_bind_groups) =
+            world.get_resource::<BuildIndirectParametersBindGroups>()
+        else {
+            return Ok(());
+        };
+
+        // Fetch the pipelines and the buffers we need.
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let preprocess_pipelines = world.resource::<PreprocessPipelines>();
+        let indirect_parameters_buffers = world.resource::<IndirectParametersBuffers>();
+
+        let (
+            Some(build_indexed_indirect_params_pipeline_id),
+            Some(build_non_indexed_indirect_params_pipeline_id),
+        ) = (
+            preprocess_pipelines
+                .late_build_indexed_indirect_params
+                .pipeline_id,
+            preprocess_pipelines
+                .late_build_non_indexed_indirect_params
+                .pipeline_id,
+        )
+        else {
+            warn!("The late build indirect parameters pipelines weren't ready");
+            return Ok(());
+        };
+
+        let (
+            Some(build_indexed_indirect_params_pipeline),
+            Some(build_non_indexed_indirect_params_pipeline),
+        ) = (
+            pipeline_cache.get_compute_pipeline(build_indexed_indirect_params_pipeline_id),
+            pipeline_cache.get_compute_pipeline(build_non_indexed_indirect_params_pipeline_id),
+        )
+        else {
+            // This will happen while the pipeline is being compiled and is fine.
+            return Ok(());
+        };
+
+        let mut compute_pass =
+            render_context
+                .command_encoder()
+                .begin_compute_pass(&ComputePassDescriptor {
+                    label: Some("late build indirect parameters"),
+                    timestamp_writes: None,
+                });
+
+        // Build the indirect parameters of the batches of indexed meshes that
+        // the late mesh preprocessing pass found visible meshes in. Since we
+        // don't know how many there are on the CPU, dispatch enough threads
+        // for all of them.
+        if let Some(ref build_indirect_indexed_params_bind_group) =
+            build_indirect_params_bind_groups.indexed
+        {
+            compute_pass.set_pipeline(build_indexed_indirect_params_pipeline);
+            compute_pass.set_bind_group(0, build_indirect_indexed_params_bind_group, &[]);
+            compute_pass.set_bind_group(1, &occlusion_culling_bind_groups.late_build_indexed, &[]);
+            let workgroup_count = indirect_parameters_buffers
+                .indexed_batch_count()
+                .div_ceil(WORKGROUP_SIZE);
+            if workgroup_count > 0 {
+                compute_pass.dispatch_workgroups(workgroup_count as u32, 1, 1);
+            }
+        }
+
+        // Do the same for the batches of non-indexed meshes.
+        if let Some(ref build_indirect_non_indexed_params_bind_group) =
+            build_indirect_params_bind_groups.non_indexed
+        {
+            compute_pass.set_pipeline(build_non_indexed_indirect_params_pipeline);
+            compute_pass.set_bind_group(0, build_indirect_non_indexed_params_bind_group, &[]);
+            compute_pass.set_bind_group(
+                1,
+                &occlusion_culling_bind_groups.late_build_non_indexed,
+                &[],
+            );
+            let workgroup_count = indirect_parameters_buffers
+                .non_indexed_batch_count()
+                .div_ceil(WORKGROUP_SIZE);
+            if workgroup_count > 0 {
+                compute_pass.dispatch_workgroups(workgroup_count as u32, 1, 1);
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl PreprocessPipelines {
     /// Returns true if the preprocessing and indirect parameters pipelines have
     /// been loaded or false otherwise.
     pub(crate) fn pipelines_are_loaded(&self, pipeline_cache: &PipelineCache) -> bool {
         self.direct_preprocess.is_loaded(pipeline_cache)
             && self.gpu_culling_preprocess.is_loaded(pipeline_cache)
+            && self
+                .early_occlusion_culling_preprocess
+                .is_loaded(pipeline_cache)
+            && self
+                .late_occlusion_culling_preprocess
+                .is_loaded(pipeline_cache)
             && self.build_indexed_indirect_params.is_loaded(pipeline_cache)
             && self
                 .build_non_indexed_indirect_params
                 .is_loaded(pipeline_cache)
+            && self
+                .late_build_indexed_indirect_params
+                .is_loaded(pipeline_cache)
+            && self
+                .late_build_non_indexed_indirect_params
+                .is_loaded(pipeline_cache)
     }
 }
 
@@ -603,12 +994,27 @@ impl SpecializedComputePipeline for PreprocessPipeline {
             shader_defs.push("INDIRECT".into());
             shader_defs.push("FRUSTUM_CULLING".into());
         }
+        if key.contains(PreprocessPipelineKey::EARLY_OCCLUSION_CULLING) {
+            shader_defs.push("OCCLUSION_CULLING".into());
+            shader_defs.push("EARLY_PHASE".into());
+        }
+        if key.contains(PreprocessPipelineKey::LATE_OCCLUSION_CULLING) {
+            shader_defs.push("OCCLUSION_CULLING".into());
+            shader_defs.push("LATE_PHASE".into());
+        }
+
+        let mut layout = vec![self.bind_group_layout.clone()];
+        layout.extend(self.occlusion_culling_bind_group_layout.clone());
 
         ComputePipelineDescriptor {
             label: Some(
                 format!(
                     "mesh preprocessing ({})",
-                    if key.contains(PreprocessPipelineKey::GPU_CULLING) {
+                    if key.contains(PreprocessPipelineKey::EARLY_OCCLUSION_CULLING) {
+                        "early occlusion culling"
+                    } else if key.contains(PreprocessPipelineKey::LATE_OCCLUSION_CULLING) {
+                        "late occlusion culling"
+                    } else if key.contains(PreprocessPipelineKey::GPU_CULLING) {
                         "GPU culling"
                     } else {
                         "direct"
@@ -616,7 +1022,7 @@ impl SpecializedComputePipeline for PreprocessPipeline {
                 )
                 .into(),
             ),
-            layout: vec![self.bind_group_layout.clone()],
+            layout,
             push_constant_ranges: vec![],
             shader: MESH_PREPROCESS_SHADER_HANDLE,
             shader_defs,
@@ -629,6 +1035,7 @@ impl SpecializedComputePipeline for PreprocessPipeline {
 impl FromWorld for PreprocessPipelines {
     fn from_world(world: &mut World) -> Self {
         let render_device = world.resource::<RenderDevice>();
+        let occlusion_culling_pipelines = world.resource::<OcclusionCullingPipelines>();
 
         // GPU culling bind group parameters are a superset of those in the CPU
         // culling (direct) shader.
@@ -675,18 +1082,58 @@ impl FromWorld for PreprocessPipelines {
         PreprocessPipelines {
             direct_preprocess: PreprocessPipeline {
                 bind_group_layout: direct_bind_group_layout,
+                occlusion_culling_bind_group_layout: None,
                 pipeline_id: None,
             },
             gpu_culling_preprocess: PreprocessPipeline {
+                bind_group_layout: gpu_culling_bind_group_layout.clone(),
+                occlusion_culling_bind_group_layout: None,
+                pipeline_id: None,
+            },
+            early_occlusion_culling_preprocess: PreprocessPipeline {
+                bind_group_layout: gpu_culling_bind_group_layout.clone(),
+                occlusion_culling_bind_group_layout: Some(
+                    occlusion_culling_pipelines
+                        .preprocess_bind_group_layout
+                        .clone(),
+                ),
+                pipeline_id: None,
+            },
+            late_occlusion_culling_preprocess: PreprocessPipeline {
                 bind_group_layout: gpu_culling_bind_group_layout,
+                occlusion_culling_bind_group_layout: Some(
+                    occlusion_culling_pipelines
+                        .preprocess_bind_group_layout
+                        .clone(),
+                ),
                 pipeline_id: None,
             },
             build_indexed_indirect_params: BuildIndirectParametersPipeline {
-                bind_group_layout: build_indexed_indirect_params_bind_group_layout,
+                bind_group_layout: build_indexed_indirect_params_bind_group_layout.clone(),
+                late_batches_bind_group_layout: None,
                 pipeline_id: None,
             },
             build_non_indexed_indirect_params: BuildIndirectParametersPipeline {
+                bind_group_layout: build_non_indexed_indirect_params_bind_group_layout.clone(),
+                late_batches_bind_group_layout: None,
+                pipeline_id: None,
+            },
+            late_build_indexed_indirect_params: BuildIndirectParametersPipeline {
+                bind_group_layout: build_indexed_indirect_params_bind_group_layout,
+                late_batches_bind_group_layout: Some(
+                    occlusion_culling_pipelines
+                        .late_build_bind_group_layout
+                        .clone(),
+                ),
+                pipeline_id: None,
+            },
+            late_build_non_indexed_indirect_params: BuildIndirectParametersPipeline {
                 bind_group_layout: build_non_indexed_indirect_params_bind_group_layout,
+                late_batches_bind_group_layout: Some(
+                    occlusion_culling_pipelines
+                        .late_build_bind_group_layout
+                        .clone(),
+                ),
                 pipeline_id: None,
             },
         }
@@ -743,6 +1190,20 @@ pub fn prepare_preprocess_pipelines(
         &mut specialized_preprocess_pipelines,
         PreprocessPipelineKey::GPU_CULLING,
     );
+    preprocess_pipelines
+        .early_occlusion_culling_preprocess
+        .prepare(
+            &pipeline_cache,
+            &mut specialized_preprocess_pipelines,
+            PreprocessPipelineKey::GPU_CULLING | PreprocessPipelineKey::EARLY_OCCLUSION_CULLING,
+        );
+    preprocess_pipelines
+        .late_occlusion_culling_preprocess
+        .prepare(
+            &pipeline_cache,
+            &mut specialized_preprocess_pipelines,
+            PreprocessPipelineKey::GPU_CULLING | PreprocessPipelineKey::LATE_OCCLUSION_CULLING,
+        );
 
     let mut build_indirect_parameters_pipeline_key = BuildIndirectParametersPipelineKey::empty();
 
@@ -769,6 +1230,22 @@ pub fn prepare_preprocess_pipelines(
             &mut specialized_build_indirect_parameters_pipelines,
             build_indirect_parameters_pipeline_key,
         );
+    preprocess_pipelines
+        .late_build_indexed_indirect_params
+        .prepare(
+            &pipeline_cache,
+            &mut specialized_build_indirect_parameters_pipelines,
+            build_indirect_parameters_pipeline_key
+                | BuildIndirectParametersPipelineKey::INDEXED
+                | BuildIndirectParametersPipelineKey::LATE_PHASE,
+        );
+    preprocess_pipelines
+        .late_build_non_indexed_indirect_params
+        .prepare(
+            &pipeline_cache,
+            &mut specialized_build_indirect_parameters_pipelines,
+            build_indirect_parameters_pipeline_key | BuildIndirectParametersPipelineKey::LATE_PHASE,
+        );
 }
 
 impl PreprocessPipeline {
@@ -798,14 +1275,24 @@ impl SpecializedComputePipeline for BuildIndirectParametersPipeline {
         if key.contains(BuildIndirectParametersPipelineKey::MULTI_DRAW_INDIRECT_COUNT_SUPPORTED) {
             shader_defs.push("MULTI_DRAW_INDIRECT_COUNT_SUPPORTED".into());
         }
+        if key.contains(BuildIndirectParametersPipelineKey::LATE_PHASE) {
+            shader_defs.push("LATE_PHASE".into());
+        }
+
+        let mut layout = vec![self.bind_group_layout.clone()];
+        layout.extend(self.late_batches_bind_group_layout.clone());
 
         ComputePipelineDescriptor {
-            label: if key.contains(BuildIndirectParametersPipelineKey::INDEXED) {
-                Some("build indexed indirect parameters".into())
-            } else {
-                Some("build non-indexed indirect parameters".into())
+            label: match (
+                key.contains(BuildIndirectParametersPipelineKey::INDEXED),
+                key.contains(BuildIndirectParametersPipelineKey::LATE_PHASE),
+            ) {
+                (true, false) => Some("build indexed indirect parameters".into()),
+                (false, false) => Some("build non-indexed indirect parameters".into()),
+                (true, true) => Some("late build indexed indirect parameters".into()),
+                (false, true) => Some("late build non-indexed indirect parameters".into()),
             },
-            layout: vec![self.bind_group_layout.clone()],
+            layout,
             push_constant_ranges: vec![],
             shader: BUILD_INDIRECT_PARAMS_SHADER_HANDLE,
             shader_defs,
@@ -1,7 +1,7 @@
 use self::assign::ClusterableObjectType;
 use crate::material_bind_groups::MaterialBindGroupAllocator;
 use crate::*;
-use bevy_asset::UntypedAssetId;
+use bevy_asset::{AssetId, Assets, UntypedAssetId};
 use bevy_color::ColorToComponents;
 use bevy_core_pipeline::core_3d::{Camera3d, CORE_3D_DEPTH_FORMAT};
 use bevy_derive::{Deref, DerefMut};
@@ -50,6 +50,9 @@ pub struct ExtractedPointLight {
     pub radius: f32,
     pub transform: GlobalTransform,
     pub shadows_enabled: bool,
+    /// whether the shadows of this light are ray traced, in which case its
+    /// shadow map only holds the casters that aren't ray traced
+    pub ray_traced_shadows: bool,
     pub shadow_depth_bias: f32,
     pub shadow_normal_bias: f32,
     pub shadow_map_near_z: f32,
@@ -58,6 +61,11 @@ pub struct ExtractedPointLight {
     pub soft_shadows_enabled: bool,
     /// whether this point light contributes diffuse light to lightmapped meshes
     pub affects_lightmapped_mesh_diffuse: bool,
+    /// the photometric profile that shapes the light, if any
+    pub ies_profile: Option<AssetId<IesProfile>>,
+    /// how much the point light deserves a detailed shadow map, see
+    /// [`ShadowPriority`]
+    pub shadow_priority: f32,
 }
 
 #[derive(Component, Debug)]
@@ -66,6 +74,9 @@ pub struct ExtractedDirectionalLight {
     pub illuminance: f32,
     pub transform: GlobalTransform,
     pub shadows_enabled: bool,
+    /// whether the shadows of this light are ray traced, in which case its
+    /// shadow cascades only hold the casters that aren't ray traced
+    pub ray_traced_shadows: bool,
     pub volumetric: bool,
     /// whether this directional light contributes diffuse light to lightmapped
     /// meshes
@@ -87,6 +98,7 @@ bitflags::bitflags! {
         const SPOT_LIGHT_Y_NEGATIVE             = 1 << 1;
         const VOLUMETRIC                        = 1 << 2;
         const AFFECTS_LIGHTMAPPED_MESH_DIFFUSE  = 1 << 3;
+        const RAY_TRACED_SHADOWS                = 1 << 4;
         const NONE                              = 0;
         const UNINITIALIZED                     = 0xFFFF;
     }
@@ -121,6 +133,7 @@ bitflags::bitflags! {
         const SHADOWS_ENABLED                   = 1 << 0;
         const VOLUMETRIC                        = 1 << 1;
         const AFFECTS_LIGHTMAPPED_MESH_DIFFUSE  = 1 << 2;
+        const RAY_TRACED_SHADOWS                = 1 << 3;
         const NONE                              = 0;
         const UNINITIALIZED                     = 0xFFFF;
     }
@@ -206,6 +219,17 @@ impl FromWorld for ShadowSamplers {
     }
 }
 
+/// Returns the luminous intensity, in candela, that an [`IesLightProfile`]
+/// overrides the intensity of its light with, if any.
+fn ies_profile_intensity(
+    ies_light_profile: &IesLightProfile,
+    ies_profiles: &Assets<IesProfile>,
+) -> Option<f32> {
+    let multiplier = ies_light_profile.profile_intensity_multiplier?;
+    let profile = ies_profiles.get(&ies_light_profile.profile)?;
+    Some(profile.peak_intensity() * multiplier)
+}
+
 pub fn extract_lights(
     mut commands: Commands,
     point_light_shadow_map: Extract<Res<PointLightShadowMap>>,
@@ -221,6 +245,9 @@ pub fn extract_lights(
             &ViewVisibility,
             &CubemapFrusta,
             Option<&VolumetricLight>,
+            Option<&IesLightProfile>,
+            Has<RayTracedShadows>,
+            Option<&ShadowPriority>,
         )>,
     >,
     spot_lights: Extract<
@@ -233,6 +260,8 @@ pub fn extract_lights(
             &ViewVisibility,
             &Frustum,
             Option<&VolumetricLight>,
+            Option<&IesLightProfile>,
+            Has<RayTracedShadows>,
         )>,
     >,
     directional_lights: Extract<
@@ -249,11 +278,14 @@ pub fn extract_lights(
                 &ViewVisibility,
                 Option<&RenderLayers>,
                 Option<&VolumetricLight>,
+                Has<RayTracedShadows>,
             ),
             Without<SpotLight>,
         >,
     >,
     mapper: Extract<Query<RenderEntity>>,
+    ies_profiles: Extract<Res<Assets<IesProfile>>>,
+    render_device: Res<RenderDevice>,
     mut previous_point_lights_len: Local<usize>,
     mut previous_spot_lights_len: Local<usize>,
 ) {
@@ -272,8 +304,14 @@ pub fn extract_lights(
     // point_light_texel_size = 2.0 / cube face width in texels
     // NOTE: When using various PCF kernel sizes, this will need to be adjusted, according to:
     // https://catlikecoding.com/unity/tutorials/custom-srp/point-and-spot-shadows/
+    // This is for the full size, `prepare_lights` scales it to the size that the light gets in the
+    // shadow atlas.
     let point_light_texel_size = 2.0 / point_light_shadow_map.size as f32;
 
+    // Lights with `RayTracedShadows` fall back to shadow maps where ray tracing
+    // isn't supported.
+    let ray_tracing_is_usable = ray_traced_shadows_are_usable(&render_device);
+
     let mut point_lights_values = Vec::with_capacity(*previous_point_lights_len);
     for entity in global_point_lights.iter().copied() {
         let Ok((
@@ -285,6 +323,9 @@ pub fn extract_lights(
             view_visibility,
             frusta,
             volumetric_light,
+            ies_light_profile,
+            has_ray_traced_shadows,
+            shadow_priority,
         )) = point_lights.get(entity)
         else {
             continue;
@@ -292,6 +333,8 @@ pub fn extract_lights(
         if !view_visibility.get() {
             continue;
         }
+        let ray_traced_shadows =
+            point_light.shadows_enabled && has_ray_traced_shadows && ray_tracing_is_usable;
         let render_cubemap_visible_entities = RenderCubemapVisibleEntities {
             data: cubemap_visible_entities
                 .iter()
@@ -306,11 +349,14 @@ pub fn extract_lights(
             // NOTE: Map from luminous power in lumens to luminous intensity in lumens per steradian
             // for a point light. See https://google.github.io/filament/Filament.html#mjx-eqn-pointLightLuminousPower
             // for details.
-            intensity: point_light.intensity / (4.0 * core::f32::consts::PI),
+            intensity: ies_light_profile
+                .and_then(|profile| ies_profile_intensity(profile, &ies_profiles))
+                .unwrap_or(point_light.intensity / (4.0 * core::f32::consts::PI)),
             range: point_light.range,
             radius: point_light.radius,
             transform: *transform,
             shadows_enabled: point_light.shadows_enabled,
+            ray_traced_shadows,
             shadow_depth_bias: point_light.shadow_depth_bias,
             // The factor of SQRT_2 is for the worst-case diagonal offset
             shadow_normal_bias: point_light.shadow_normal_bias
@@ -324,6 +370,8 @@ pub fn extract_lights(
             soft_shadows_enabled: point_light.soft_shadows_enabled,
             #[cfg(not(feature = "experimental_pbr_pcss"))]
             soft_shadows_enabled: false,
+            ies_profile: ies_light_profile.map(|profile| profile.profile.id()),
+            shadow_priority: shadow_priority.copied().unwrap_or_default().0,
         };
         point_lights_values.push((
             render_entity,
@@ -349,6 +397,8 @@ pub fn extract_lights(
             view_visibility,
             frustum,
             volumetric_light,
+            ies_light_profile,
+            has_ray_traced_shadows,
         )) = spot_lights.get(entity)
         {
             if !view_visibility.get() {
@@ -356,6 +406,8 @@ pub fn extract_lights(
             }
             let render_visible_entities =
                 create_render_visible_mesh_entities(&mapper, visible_entities);
+            let ray_traced_shadows =
+                spot_light.shadows_enabled && has_ray_traced_shadows && ray_tracing_is_usable;
 
             let texel_size =
                 2.0 * ops::tan(spot_light.outer_angle) / directional_light_shadow_map.size as f32;
@@ -371,11 +423,14 @@ pub fn extract_lights(
                         // Note: Filament uses a divisor of PI for spot lights. We choose to use the same 4*PI divisor
                         // in both cases so that toggling between point light and spot light keeps lit areas lit equally,
                         // which seems least surprising for users
-                        intensity: spot_light.intensity / (4.0 * core::f32::consts::PI),
+                        intensity: ies_light_profile
+                            .and_then(|profile| ies_profile_intensity(profile, &ies_profiles))
+                            .unwrap_or(spot_light.intensity / (4.0 * core::f32::consts::PI)),
                         range: spot_light.range,
                         radius: spot_light.radius,
                         transform: *transform,
                         shadows_enabled: spot_light.shadows_enabled,
+                        ray_traced_shadows,
                         shadow_depth_bias: spot_light.shadow_depth_bias,
                         // The factor of SQRT_2 is for the worst-case diagonal offset
                         shadow_normal_bias: spot_light.shadow_normal_bias
@@ -390,6 +445,9 @@ pub fn extract_lights(
                         soft_shadows_enabled: spot_light.soft_shadows_enabled,
                         #[cfg(not(feature = "experimental_pbr_pcss"))]
                         soft_shadows_enabled: false,
+                        ies_profile: ies_light_profile.map(|profile| profile.profile.id()),
+                        // Spot light shadow maps aren't in the atlas.
+                        shadow_priority: 0.0,
                     },
                     render_visible_entities,
                     *frustum,
@@ -413,6 +471,7 @@ pub fn extract_lights(
         view_visibility,
         maybe_layers,
         volumetric_light,
+        has_ray_traced_shadows,
     ) in &directional_lights
     {
         if !view_visibility.get() {
@@ -423,6 +482,9 @@ pub fn extract_lights(
             continue;
         }
 
+        let ray_traced_shadows =
+            directional_light.shadows_enabled && has_ray_traced_shadows && ray_tracing_is_usable;
+
         // TODO: update in place instead of reinserting.
         let mut extracted_cascades = EntityHashMap::default();
         let mut extracted_frusta = EntityHashMap::default();
@@ -470,6 +532,7 @@ pub fn extract_lights(
                     #[cfg(not(feature = "experimental_pbr_pcss"))]
                     soft_shadow_size: None,
                     shadows_enabled: directional_light.shadows_enabled,
+                    ray_traced_shadows,
                     shadow_depth_bias: directional_light.shadow_depth_bias,
                     // The factor of SQRT_2 is for the worst-case diagonal offset
                     shadow_normal_bias: directional_light.shadow_normal_bias
@@ -604,6 +667,8 @@ fn face_index_to_name(face_index: usize) -> &'static str {
 pub struct ShadowView {
     pub depth_attachment: DepthAttachment,
     pub pass_name: String,
+    /// The layer of the shadow map texture that the view renders to.
+    pub array_layer: u32,
 }
 
 #[derive(Component)]
@@ -737,7 +802,7 @@ pub fn prepare_lights(
     )>,
     directional_lights: Query<(Entity, &MainEntity, &ExtractedDirectionalLight)>,
     mut light_view_entities: Query<&mut LightViewEntities>,
-    sorted_cameras: Res<SortedCameras>,
+    (sorted_cameras, ies_profile_buffer): (Res<SortedCameras>, Res<IesProfileBuffer>),
     gpu_preprocessing_support: Res<GpuPreprocessingSupport>,
 ) {
     let views_iter = views.iter();
@@ -767,16 +832,8 @@ pub fn prepare_lights(
         feature = "webgpu"
     ))]
     let max_texture_array_layers = render_device.limits().max_texture_array_layers as usize;
-    #[cfg(any(
-        not(feature = "webgl"),
-        not(target_arch = "wasm32"),
-        feature = "webgpu"
-    ))]
-    let max_texture_cubes = max_texture_array_layers / 6;
     #[cfg(all(feature = "webgl", target_arch = "wasm32", not(feature = "webgpu")))]
     let max_texture_array_layers = 1;
-    #[cfg(all(feature = "webgl", target_arch = "wasm32", not(feature = "webgpu")))]
-    let max_texture_cubes = 1;
 
     if !*max_directional_lights_warning_emitted && directional_lights.len() > MAX_DIRECTIONAL_LIGHTS
     {
@@ -805,17 +862,10 @@ pub fn prepare_lights(
         .filter(|light| light.2.spot_light_angles.is_none())
         .count();
 
-    let point_light_volumetric_enabled_count = point_lights
-        .iter()
-        .filter(|(_, _, light, _)| light.volumetric && light.spot_light_angles.is_none())
-        .count()
-        .min(max_texture_cubes);
-
     let point_light_shadow_maps_count = point_lights
         .iter()
         .filter(|light| light.2.shadows_enabled && light.2.spot_light_angles.is_none())
-        .count()
-        .min(max_texture_cubes);
+        .count();
 
     let directional_volumetric_enabled_count = directional_lights
         .iter()
@@ -875,6 +925,48 @@ pub fn prepare_lights(
         (light.volumetric, light.shadows_enabled, *entity)
     });
 
+    // Point light shadow maps share an atlas, with more texels for the lights
+    // with a higher priority that cover more of the screen.
+    let view_coverage_scales: Vec<_> = views
+        .iter()
+        .map(|(_, _, extracted_view, ..)| {
+            (
+                extracted_view.world_from_view.translation(),
+                extracted_view.clip_from_view.y_axis.y,
+                extracted_view.clip_from_view.w_axis.w == 1.0,
+            )
+        })
+        .collect();
+    let point_light_shadow_requests: Vec<_> = point_lights
+        .iter()
+        // Lights are sorted, shadow enabled lights are first
+        .take(point_light_shadow_maps_count)
+        .map(|(_, _, light, _)| {
+            let position = light.transform.translation();
+            let coverage = view_coverage_scales
+                .iter()
+                .map(|&(view_position, scale, is_orthographic)| {
+                    let distance = view_position.distance(position);
+                    if is_orthographic {
+                        light.range * scale
+                    } else if distance <= light.range {
+                        1.0
+                    } else {
+                        light.range * scale / distance
+                    }
+                })
+                .fold(0.0, f32::max);
+            PointLightShadowRequest {
+                importance: light.shadow_priority * coverage,
+            }
+        })
+        .collect();
+    let point_light_shadow_atlas = allocate_point_light_shadow_atlas(
+        &point_light_shadow_requests,
+        &point_light_shadow_map,
+        render_device.limits().max_texture_dimension_2d,
+    );
+
     if global_light_meta.entity_to_index.capacity() < point_lights.len() {
         global_light_meta
             .entity_to_index
@@ -885,14 +977,19 @@ pub fn prepare_lights(
     for (index, &(entity, _, light, _)) in point_lights.iter().enumerate() {
         let mut flags = PointLightFlags::NONE;
 
+        let point_light_shadow_tile = point_light_shadow_atlas.tiles.get(index).copied().flatten();
+
         // Lights are sorted, shadow enabled lights are first
         if light.shadows_enabled
-            && (index < point_light_shadow_maps_count
+            && (point_light_shadow_tile.is_some()
                 || (light.spot_light_angles.is_some()
                     && index - point_light_count < spot_light_shadow_maps_count))
         {
             flags |= PointLightFlags::SHADOWS_ENABLED;
         }
+        if light.ray_traced_shadows {
+            flags |= PointLightFlags::RAY_TRACED_SHADOWS;
+        }
 
         let cube_face_projection = Mat4::perspective_infinite_reverse_rh(
             core::f32::consts::FRAC_PI_2,
@@ -901,7 +998,7 @@ pub fn prepare_lights(
         );
         if light.shadows_enabled
             && light.volumetric
-            && (index < point_light_volumetric_enabled_count
+            && (point_light_shadow_tile.is_some()
                 || (light.spot_light_angles.is_some()
                     && index - point_light_count < spot_light_volumetric_enabled_count))
         {
@@ -938,12 +1035,22 @@ pub fn prepare_lights(
                         cube_face_projection.w_axis.z,
                         cube_face_projection.w_axis.w,
                     ),
-                    // unused
-                    0.0,
+                    // For point lights: the tile of the shadow map in the atlas
+                    f32::from_bits(point_light_shadow_tile.map_or(0, |tile| tile.pack())),
                 )
             }
         };
 
+        // The normal bias is scaled for the full shadow map size, so it has to
+        // grow for the smaller ones.
+        let shadow_normal_bias = match point_light_shadow_tile {
+            Some(tile) => {
+                light.shadow_normal_bias * point_light_shadow_map.size.max(1) as f32
+                    / tile.size as f32
+            }
+            None => light.shadow_normal_bias,
+        };
+
         gpu_point_lights.push(GpuClusterableObject {
             light_custom_data,
             // premultiply color by intensity
@@ -955,11 +1062,14 @@ pub fn prepare_lights(
             position_radius: light.transform.translation().extend(light.radius),
             flags: flags.bits(),
             shadow_depth_bias: light.shadow_depth_bias,
-            shadow_normal_bias: light.shadow_normal_bias,
+            shadow_normal_bias,
             shadow_map_near_z: light.shadow_map_near_z,
             spot_light_tan_angle,
-            pad_a: 0.0,
-            pad_b: 0.0,
+            ies_profile: light
+                .ies_profile
+                .and_then(|id| ies_profile_buffer.index(id))
+                .unwrap_or(u32::MAX),
+            ies_profile_axis: pack_ies_profile_axis(light.transform.forward().into()),
             soft_shadow_size: if light.soft_shadows_enabled {
                 light.radius
             } else {
@@ -989,6 +1099,9 @@ pub fn prepare_lights(
         if light.shadows_enabled && (index < directional_shadow_enabled_count) {
             flags |= DirectionalLightFlags::SHADOWS_ENABLED;
         }
+        if light.ray_traced_shadows {
+            flags |= DirectionalLightFlags::RAY_TRACED_SHADOWS;
+        }
 
         if light.affects_lightmapped_mesh_diffuse {
             flags |= DirectionalLightFlags::AFFECTS_LIGHTMAPPED_MESH_DIFFUSE;
@@ -1031,23 +1144,26 @@ pub fn prepare_lights(
 
     live_shadow_mapping_lights.clear();
 
-    let mut point_light_depth_attachments = HashMap::<u32, DepthAttachment>::default();
+    let mut rendered_point_light_faces = HashSet::<u32>::default();
     let mut directional_light_depth_attachments = HashMap::<u32, DepthAttachment>::default();
 
     let point_light_depth_texture = texture_cache.get(
         &render_device,
         TextureDescriptor {
             size: Extent3d {
-                width: point_light_shadow_map.size as u32,
-                height: point_light_shadow_map.size as u32,
-                depth_or_array_layers: point_light_shadow_maps_count.max(1) as u32 * 6,
+                width: point_light_shadow_atlas.size,
+                height: point_light_shadow_atlas.size,
+                depth_or_array_layers: 1,
             },
             mip_level_count: 1,
             sample_count: 1,
             dimension: TextureDimension::D2,
             format: CORE_3D_DEPTH_FORMAT,
             label: Some("point_light_shadow_map_texture"),
-            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+            // Cached shadow maps get copied in.
+            usage: TextureUsages::RENDER_ATTACHMENT
+                | TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST,
             view_formats: &[],
         },
     );
@@ -1056,24 +1172,9 @@ pub fn prepare_lights(
         point_light_depth_texture
             .texture
             .create_view(&TextureViewDescriptor {
-                label: Some("point_light_shadow_map_array_texture_view"),
+                label: Some("point_light_shadow_map_atlas_texture_view"),
                 format: None,
-                // NOTE: iOS Simulator is missing CubeArray support so we use Cube instead.
-                // See https://github.com/bevyengine/bevy/pull/12052 - remove if support is added.
-                #[cfg(all(
-                    not(feature = "ios_simulator"),
-                    any(
-                        not(feature = "webgl"),
-                        not(target_arch = "wasm32"),
-                        feature = "webgpu"
-                    )
-                ))]
-                dimension: Some(TextureViewDimension::CubeArray),
-                #[cfg(any(
-                    feature = "ios_simulator",
-                    all(feature = "webgl", target_arch = "wasm32", not(feature = "webgpu"))
-                ))]
-                dimension: Some(TextureViewDimension::Cube),
+                dimension: Some(TextureViewDimension::D2),
                 aspect: TextureAspect::DepthOnly,
                 base_mip_level: 0,
                 mip_level_count: None,
@@ -1081,6 +1182,23 @@ pub fn prepare_lights(
                 array_layer_count: None,
             });
 
+    // All the faces render to the atlas, so only the first one clears it.
+    let point_light_depth_attachment = DepthAttachment::new(
+        point_light_depth_texture
+            .texture
+            .create_view(&TextureViewDescriptor {
+                label: Some("point_light_shadow_map_texture_view"),
+                format: None,
+                dimension: Some(TextureViewDimension::D2),
+                aspect: TextureAspect::All,
+                base_mip_level: 0,
+                mip_level_count: None,
+                base_array_layer: 0,
+                array_layer_count: None,
+            }),
+        Some(0.0),
+    );
+
     let directional_light_depth_texture = texture_cache.get(
         &render_device,
         TextureDescriptor {
@@ -1098,7 +1216,10 @@ pub fn prepare_lights(
             dimension: TextureDimension::D2,
             format: CORE_3D_DEPTH_FORMAT,
             label: Some("directional_light_shadow_map_texture"),
-            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+            // Cached shadow maps of spot lights get copied in.
+            usage: TextureUsages::RENDER_ATTACHMENT
+                | TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST,
             view_formats: &[],
         },
     );
@@ -1182,26 +1303,30 @@ pub fn prepare_lights(
         };
 
         // TODO: this should select lights based on relevance to the view instead of the first ones that show up in a query
-        for &(light_entity, light_main_entity, light, (point_light_frusta, _)) in point_lights
-            .iter()
-            // Lights are sorted, shadow enabled lights are first
-            .take(point_light_count.min(max_texture_cubes))
+        for &(light_entity, light_main_entity, light, (point_light_frusta, _)) in
+            point_lights.iter().take(point_light_count)
         {
             let Ok(mut light_view_entities) = light_view_entities.get_mut(light_entity) else {
                 continue;
             };
 
-            if !light.shadows_enabled {
-                if let Some(entities) = light_view_entities.remove(&entity) {
-                    despawn_entities(&mut commands, entities);
-                }
-                continue;
-            }
-
             let light_index = *global_light_meta
                 .entity_to_index
                 .get(&light_entity)
                 .unwrap();
+
+            // Lights without room in the atlas have no shadows
+            let shadow_tile = point_light_shadow_atlas
+                .tiles
+                .get(light_index)
+                .copied()
+                .flatten();
+            let Some(shadow_tile) = shadow_tile.filter(|_| light.shadows_enabled) else {
+                if let Some(entities) = light_view_entities.remove(&entity) {
+                    despawn_entities(&mut commands, entities);
+                }
+                continue;
+            };
             // ignore scale because we don't want to effectively scale light radius and range
             // by applying those as a view transform to shadow map rendering of objects
             // and ignore rotation because we want the shadow map projections to align with the axes
@@ -1224,31 +1349,9 @@ pub fn prepare_lights(
                 .zip(light_view_entities.iter().copied())
                 .enumerate()
             {
-                let mut first = false;
-                let base_array_layer = (light_index * 6 + face_index) as u32;
-
-                let depth_attachment = point_light_depth_attachments
-                    .entry(base_array_layer)
-                    .or_insert_with(|| {
-                        first = true;
-
-                        let depth_texture_view =
-                            point_light_depth_texture
-                                .texture
-                                .create_view(&TextureViewDescriptor {
-                                    label: Some("point_light_shadow_map_texture_view"),
-                                    format: None,
-                                    dimension: Some(TextureViewDimension::D2),
-                                    aspect: TextureAspect::All,
-                                    base_mip_level: 0,
-                                    mip_level_count: None,
-                                    base_array_layer,
-                                    array_layer_count: Some(1u32),
-                                });
-
-                        DepthAttachment::new(depth_texture_view, Some(0.0))
-                    })
-                    .clone();
+                let face_origin = shadow_tile.face_origin(face_index);
+                let first =
+                    rendered_point_light_faces.insert(light_index as u32 * 6 + face_index as u32);
 
                 let retained_view_entity = RetainedViewEntity::new(
                     *light_main_entity,
@@ -1258,20 +1361,21 @@ pub fn prepare_lights(
 
                 commands.entity(view_light_entity).insert((
                     ShadowView {
-                        depth_attachment,
+                        depth_attachment: point_light_depth_attachment.clone(),
                         pass_name: format!(
                             "shadow pass point light {} {}",
                             light_index,
                             face_index_to_name(face_index)
                         ),
+                        array_layer: 0,
                     },
                     ExtractedView {
                         retained_view_entity,
                         viewport: UVec4::new(
-                            0,
-                            0,
-                            point_light_shadow_map.size as u32,
-                            point_light_shadow_map.size as u32,
+                            face_origin.x,
+                            face_origin.y,
+                            shadow_tile.size,
+                            shadow_tile.size,
                         ),
                         world_from_view: view_translation * *view_rotation,
                         clip_from_world: None,
@@ -1365,6 +1469,7 @@ pub fn prepare_lights(
                 ShadowView {
                     depth_attachment,
                     pass_name: format!("shadow pass spot light {light_index}"),
+                    array_layer: base_array_layer,
                 },
                 ExtractedView {
                     retained_view_entity,
@@ -1484,6 +1589,7 @@ pub fn prepare_lights(
                 // However, for directional lights, we want a new depth attachment for each view,
                 // so that the view is cleared for each view.
                 let depth_attachment = DepthAttachment::new(depth_texture_view, Some(0.0));
+                let array_layer = directional_depth_texture_array_index;
 
                 directional_depth_texture_array_index += 1;
 
@@ -1504,6 +1610,7 @@ pub fn prepare_lights(
                         pass_name: format!(
                             "shadow pass directional light {light_index} cascade {cascade_index}"
                         ),
+                        array_layer,
                     },
                     ExtractedView {
                         retained_view_entity,
@@ -1599,20 +1706,27 @@ pub fn queue_shadows<M: Material>(
     gpu_preprocessing_support: Res<GpuPreprocessingSupport>,
     mesh_allocator: Res<MeshAllocator>,
     view_lights: Query<(Entity, &ViewLightEntities), With<ExtractedView>>,
-    view_light_entities: Query<(&LightEntity, &ExtractedView)>,
-    point_light_entities: Query<&RenderCubemapVisibleEntities, With<ExtractedPointLight>>,
-    directional_light_entities: Query<
-        &RenderCascadesVisibleEntities,
-        With<ExtractedDirectionalLight>,
-    >,
-    spot_light_entities: Query<&RenderVisibleMeshEntities, With<ExtractedPointLight>>,
+    view_light_entities: Query<(
+        &LightEntity,
+        &ExtractedView,
+        Option<&StaticShadowView>,
+        Has<CachedShadowView>,
+    )>,
+    (static_shadow_casters, mut shadow_cache, ray_tracing_scene): (
+        Res<RenderStaticShadowCasters>,
+        ResMut<ShadowCache>,
+        Option<Res<RayTracingScene>>,
+    ),
+    point_light_entities: Query<(&RenderCubemapVisibleEntities, &ExtractedPointLight)>,
+    directional_light_entities: Query<(&RenderCascadesVisibleEntities, &ExtractedDirectionalLight)>,
+    spot_light_entities: Query<(&RenderVisibleMeshEntities, &ExtractedPointLight)>,
 ) where
     M::Data: PartialEq + Eq + Hash + Clone,
 {
     for (entity, view_lights) in &view_lights {
         let draw_shadow_mesh = shadow_draw_functions.read().id::<DrawPrepass<M>>();
         for view_light_entity in view_lights.lights.iter().copied() {
-            let Ok((light_entity, extracted_view_light)) =
+            let Ok((light_entity, extracted_view_light, static_shadow_view, cached_shadow_view)) =
                 view_light_entities.get(view_light_entity)
             else {
                 continue;
@@ -1624,38 +1738,70 @@ pub fn queue_shadows<M: Material>(
             };
 
             let is_directional_light = matches!(light_entity, LightEntity::Directional { .. });
-            let visible_entities = match light_entity {
+            let (visible_entities, ray_traced_shadows) = match light_entity {
                 LightEntity::Directional {
                     light_entity,
                     cascade_index,
-                } => directional_light_entities
-                    .get(*light_entity)
-                    .expect("Failed to get directional light visible entities")
-                    .entities
-                    .get(&entity)
-                    .expect("Failed to get directional light visible entities for view")
-                    .get(*cascade_index)
-                    .expect("Failed to get directional light visible entities for cascade"),
+                } => {
+                    let (visible_entities, light) = directional_light_entities
+                        .get(*light_entity)
+                        .expect("Failed to get directional light visible entities");
+                    let visible_entities = visible_entities
+                        .entities
+                        .get(&entity)
+                        .expect("Failed to get directional light visible entities for view")
+                        .get(*cascade_index)
+                        .expect("Failed to get directional light visible entities for cascade");
+                    (visible_entities, light.ray_traced_shadows)
+                }
                 LightEntity::Point {
                     light_entity,
                     face_index,
-                } => point_light_entities
-                    .get(*light_entity)
-                    .expect("Failed to get point light visible entities")
-                    .get(*face_index),
-                LightEntity::Spot { light_entity } => spot_light_entities
-                    .get(*light_entity)
-                    .expect("Failed to get spot light visible entities"),
+                } => {
+                    let (visible_entities, light) = point_light_entities
+                        .get(*light_entity)
+                        .expect("Failed to get point light visible entities");
+                    (visible_entities.get(*face_index), light.ray_traced_shadows)
+                }
+                LightEntity::Spot { light_entity } => {
+                    let (visible_entities, light) = spot_light_entities
+                        .get(*light_entity)
+                        .expect("Failed to get spot light visible entities");
+                    (visible_entities, light.ray_traced_shadows)
+                }
             };
+            // The casters that the shadow rays are traced against are left out
+            // of the shadow maps of lights with ray-traced shadows.
+            let ray_traced_casters = ray_tracing_scene.as_deref().filter(|_| ray_traced_shadows);
             let mut light_key = MeshPipelineKey::DEPTH_PREPASS;
             light_key.set(MeshPipelineKey::UNCLIPPED_DEPTH_ORTHO, is_directional_light);
 
+            // With a cached shadow map, the static casters are only queued in
+            // the view that renders them into the cache.
+            let queue_static_casters = match (static_shadow_view, cached_shadow_view) {
+                (Some(_), _) => Some(true),
+                (None, true) => Some(false),
+                (None, false) => None,
+            };
+            // Whether some static casters couldn't be queued, so that the cache
+            // has to be rendered again next frame.
+            let mut static_casters_missing = false;
+
             // NOTE: Lights with shadow mapping disabled will have no visible entities
             // so no meshes will be queued
 
             for (entity, main_entity) in visible_entities.iter().copied() {
+                if queue_static_casters.is_some_and(|queue_static_casters| {
+                    static_shadow_casters.contains(&main_entity) != queue_static_casters
+                }) {
+                    continue;
+                }
+                if ray_traced_casters.is_some_and(|scene| scene.is_traced(main_entity)) {
+                    continue;
+                }
                 let Some(mesh_instance) = render_mesh_instances.render_mesh_queue_data(main_entity)
                 else {
+                    static_casters_missing = true;
                     continue;
                 };
                 if !mesh_instance
@@ -1668,14 +1814,17 @@ pub fn queue_shadows<M: Material>(
                     continue;
                 };
                 let Some(material) = render_materials.get(*material_asset_id) else {
+                    static_casters_missing = true;
                     continue;
                 };
                 let Some(material_bind_group) =
                     material_bind_group_allocator.get(material.binding.group)
                 else {
+                    static_casters_missing = true;
                     continue;
                 };
                 let Some(mesh) = render_meshes.get(mesh_instance.mesh_asset_id) else {
+                    static_casters_missing = true;
                     continue;
                 };
 
@@ -1691,6 +1840,13 @@ pub fn queue_shadows<M: Material>(
                     mesh_key |= MeshPipelineKey::LIGHTMAPPED;
                 }
 
+                if mesh_instance
+                    .flags
+                    .contains(RenderMeshInstanceFlags::PRESKINNED)
+                {
+                    mesh_key |= MeshPipelineKey::PRESKINNED;
+                }
+
                 mesh_key |= match material.properties.alpha_mode {
                     AlphaMode::Mask(_)
                     | AlphaMode::Blend
@@ -1715,9 +1871,13 @@ pub fn queue_shadows<M: Material>(
                     Ok(id) => id,
                     Err(err) => {
                         error!("{}", err);
+                        static_casters_missing = true;
                         continue;
                     }
                 };
+                if pipeline_cache.get_render_pipeline(pipeline_id).is_none() {
+                    static_casters_missing = true;
+                }
 
                 let (vertex_slab, index_slab) =
                     mesh_allocator.mesh_slabs(&mesh_instance.mesh_asset_id);
@@ -1741,6 +1901,10 @@ pub fn queue_shadows<M: Material>(
                     ),
                 );
             }
+
+            if let (Some(static_shadow_view), true) = (static_shadow_view, static_casters_missing) {
+                shadow_cache.invalidate(static_shadow_view.light_entity);
+            }
         }
     }
 }
@@ -1863,7 +2027,11 @@ impl CachedRenderPipelinePhaseItem for Shadow {
 
 pub struct ShadowPassNode {
     main_view_query: QueryState<Read<ViewLightEntities>>,
-    view_light_query: QueryState<(Read<ShadowView>, Read<ExtractedView>)>,
+    view_light_query: QueryState<(
+        Read<ShadowView>,
+        Read<ExtractedView>,
+        Option<Read<CachedShadowView>>,
+    )>,
 }
 
 impl ShadowPassNode {
@@ -1900,7 +2068,7 @@ impl Node for ShadowPassNode {
 
         if let Ok(view_lights) = self.main_view_query.get_manual(world, view_entity) {
             for view_light_entity in view_lights.lights.iter().copied() {
-                let Ok((view_light, extracted_light_view)) =
+                let Ok((view_light, extracted_light_view, cached_shadow_view)) =
                     self.view_light_query.get_manual(world, view_light_entity)
                 else {
                     continue;
@@ -1913,7 +2081,32 @@ impl Node for ShadowPassNode {
                 };
 
                 let depth_stencil_attachment =
-                    Some(view_light.depth_attachment.get_attachment(StoreOp::Store));
+                    view_light.depth_attachment.get_attachment(StoreOp::Store);
+                // The dynamic casters are rendered over the copy of the cached
+                // shadow map, so the shadow map is cleared before the copy if
+                // this is the first view to render to it.
+                let (clear_attachment, depth_stencil_attachment) = match cached_shadow_view {
+                    Some(_) => (
+                        matches!(
+                            depth_stencil_attachment.depth_ops,
+                            Some(Operations {
+                                load: LoadOp::Clear(_),
+                                ..
+                            })
+                        )
+                        .then(|| depth_stencil_attachment.clone()),
+                        RenderPassDepthStencilAttachment {
+                            depth_ops: Some(Operations {
+                                load: LoadOp::Load,
+                                store: StoreOp::Store,
+                            }),
+                            ..depth_stencil_attachment
+                        },
+                    ),
+                    None => (None, depth_stencil_attachment),
+                };
+                // Point light shadow maps are tiles of the atlas.
+                let viewport = extracted_light_view.viewport.as_vec4();
 
                 let diagnostics = render_context.diagnostic_recorder();
                 render_context.add_command_buffer_generation_task(move |render_device| {
@@ -1924,10 +2117,23 @@ impl Node for ShadowPassNode {
                             label: Some("shadow_pass_command_encoder"),
                         });
 
+                    if let Some(clear_attachment) = clear_attachment {
+                        command_encoder.begin_render_pass(&RenderPassDescriptor {
+                            label: Some("clear_shadow_map"),
+                            color_attachments: &[],
+                            depth_stencil_attachment: Some(clear_attachment),
+                            timestamp_writes: None,
+                            occlusion_query_set: None,
+                        });
+                    }
+                    if let Some(cached_shadow_view) = cached_shadow_view {
+                        cached_shadow_view.copy_to_shadow_map(&mut command_encoder);
+                    }
+
                     let render_pass = command_encoder.begin_render_pass(&RenderPassDescriptor {
                         label: Some(&view_light.pass_name),
                         color_attachments: &[],
-                        depth_stencil_attachment,
+                        depth_stencil_attachment: Some(depth_stencil_attachment),
                         timestamp_writes: None,
                         occlusion_query_set: None,
                     });
@@ -1935,6 +2141,8 @@ impl Node for ShadowPassNode {
                     let mut render_pass = TrackedRenderPass::new(&render_device, render_pass);
                     let pass_span =
                         diagnostics.pass_span(&mut render_pass, view_light.pass_name.clone());
+                    render_pass
+                        .set_viewport(viewport.x, viewport.y, viewport.z, viewport.w, 0.0, 1.0);
 
                     if let Err(err) =
                         shadow_phase.render(&mut render_pass, world, view_light_entity)
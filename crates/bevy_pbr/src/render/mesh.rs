@@ -2,6 +2,8 @@ use core::mem::size_of;
 
 use crate::material_bind_groups::{MaterialBindGroupIndex, MaterialBindGroupSlot};
 use allocator::MeshAllocator;
+#[cfg(feature = "gpu_preskinning")]
+use allocator::MeshAllocatorSettings;
 use bevy_asset::{load_internal_asset, AssetId, UntypedAssetId};
 use bevy_core_pipeline::{
     core_3d::{AlphaMask3d, Opaque3d, Transmissive3d, Transparent3d, CORE_3D_DEPTH_FORMAT},
@@ -17,6 +19,8 @@ use bevy_ecs::{
 };
 use bevy_image::{BevyDefault, ImageSampler, TextureFormatPixelInfo};
 use bevy_math::{Affine3, Rect, UVec2, Vec3, Vec4};
+#[cfg(feature = "gpu_preskinning")]
+use bevy_render::render_asset::prepare_assets;
 use bevy_render::{
     batching::{
         gpu_preprocessing::{
@@ -27,7 +31,7 @@ use bevy_render::{
         no_gpu_preprocessing, GetBatchData, GetFullBatchData, NoAutomaticBatching,
     },
     camera::Camera,
-    mesh::*,
+    mesh::{skinning::SkinningMethod, *},
     primitives::Aabb,
     render_asset::RenderAssets,
     render_phase::{
@@ -36,6 +40,7 @@ use bevy_render::{
     },
     render_resource::*,
     renderer::{RenderAdapter, RenderDevice, RenderQueue},
+    settings::WgpuFeatures,
     texture::DefaultImageSampler,
     view::{
         NoFrustumCulling, NoIndirectDrawing, RenderVisibilityRanges, ViewTarget, ViewUniformOffset,
@@ -44,7 +49,7 @@ use bevy_render::{
     Extract,
 };
 use bevy_transform::components::GlobalTransform;
-use bevy_utils::{default, hashbrown::hash_map::Entry, HashMap, Parallel};
+use bevy_utils::{default, hashbrown::hash_map::Entry, once, HashMap, Parallel};
 use material_bind_groups::MaterialBindingId;
 use render::skin::{self, SkinIndex};
 use tracing::{error, warn};
@@ -133,24 +138,40 @@ impl Plugin for MeshRenderPlugin {
         load_internal_asset!(app, MESH_SHADER_HANDLE, "mesh.wgsl", Shader::from_wgsl);
         load_internal_asset!(app, SKINNING_HANDLE, "skinning.wgsl", Shader::from_wgsl);
         load_internal_asset!(app, MORPH_HANDLE, "morph.wgsl", Shader::from_wgsl);
+        #[cfg(feature = "gpu_skinning_precompute")]
+        load_internal_asset!(
+            app,
+            SKIN_PRECOMPUTE_SHADER_HANDLE,
+            "skin_precompute.wgsl",
+            Shader::from_wgsl
+        );
+        #[cfg(feature = "gpu_preskinning")]
+        load_internal_asset!(
+            app,
+            PRESKINNING_SHADER_HANDLE,
+            "preskinning.wgsl",
+            Shader::from_wgsl
+        );
 
         if app.get_sub_app(RenderApp).is_none() {
             return;
         }
 
-        app.add_systems(
-            PostUpdate,
-            (no_automatic_skin_batching, no_automatic_morph_batching),
-        )
-        .add_plugins((
-            BinnedRenderPhasePlugin::<Opaque3d, MeshPipeline>::default(),
-            BinnedRenderPhasePlugin::<AlphaMask3d, MeshPipeline>::default(),
-            BinnedRenderPhasePlugin::<Shadow, MeshPipeline>::default(),
-            BinnedRenderPhasePlugin::<Opaque3dDeferred, MeshPipeline>::default(),
-            BinnedRenderPhasePlugin::<AlphaMask3dDeferred, MeshPipeline>::default(),
-            SortedRenderPhasePlugin::<Transmissive3d, MeshPipeline>::default(),
-            SortedRenderPhasePlugin::<Transparent3d, MeshPipeline>::default(),
-        ));
+        app.register_type::<SkinSettings>()
+            .init_resource::<SkinSettings>()
+            .add_systems(
+                PostUpdate,
+                (no_automatic_skin_batching, no_automatic_morph_batching),
+            )
+            .add_plugins((
+                BinnedRenderPhasePlugin::<Opaque3d, MeshPipeline>::default(),
+                BinnedRenderPhasePlugin::<AlphaMask3d, MeshPipeline>::default(),
+                BinnedRenderPhasePlugin::<Shadow, MeshPipeline>::default(),
+                BinnedRenderPhasePlugin::<Opaque3dDeferred, MeshPipeline>::default(),
+                BinnedRenderPhasePlugin::<AlphaMask3dDeferred, MeshPipeline>::default(),
+                SortedRenderPhasePlugin::<Transmissive3d, MeshPipeline>::default(),
+                SortedRenderPhasePlugin::<Transparent3d, MeshPipeline>::default(),
+            ));
 
         if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
             render_app
@@ -246,6 +267,51 @@ impl Plugin for MeshRenderPlugin {
                     );
             };
 
+            #[cfg(feature = "gpu_skinning_precompute")]
+            if skin::skins_use_gpu_precompute(render_app.world().resource::<RenderDevice>()) {
+                render_app
+                    .init_resource::<SkinPrecomputePipeline>()
+                    .add_systems(
+                        Render,
+                        precompute_skins
+                            .in_set(RenderSet::PrepareResources)
+                            .after(prepare_skins),
+                    );
+            }
+
+            #[cfg(feature = "gpu_preskinning")]
+            if skin::skins_use_preskinning(render_app.world().resource::<RenderDevice>()) {
+                // The pre-skinning compute shader reads the vertex data of
+                // skinned meshes straight from the mesh slabs.
+                render_app
+                    .world_mut()
+                    .resource_mut::<MeshAllocatorSettings>()
+                    .extra_vertex_buffer_usages |= BufferUsages::STORAGE;
+
+                let preskin_meshes_system = preskin_meshes
+                    .in_set(RenderSet::PrepareResources)
+                    .after(prepare_skins);
+                // The joint matrices have to be computed before they can be
+                // used to skin the vertices.
+                #[cfg(feature = "gpu_skinning_precompute")]
+                let preskin_meshes_system = preskin_meshes_system.after(precompute_skins);
+
+                render_app
+                    .init_resource::<PreskinningPipeline>()
+                    .init_resource::<PreskinnedMeshes>()
+                    .add_systems(ExtractSchedule, extract_preskinned_meshes)
+                    .add_systems(
+                        Render,
+                        (
+                            prepare_preskinned_meshes
+                                .in_set(RenderSet::PrepareAssets)
+                                .after(prepare_assets::<RenderMesh>)
+                                .after(set_mesh_motion_vector_flags),
+                            preskin_meshes_system,
+                        ),
+                    );
+            }
+
             let render_device = render_app.world().resource::<RenderDevice>();
             if let Some(per_object_buffer_batch_size) =
                 GpuArrayBuffer::<MeshUniform>::batch_size(render_device)
@@ -317,6 +383,14 @@ pub struct MeshUniform {
     /// Low 16 bits: index of the material inside the bind group data.
     /// High 16 bits: index of the lightmap in the binding array.
     pub material_and_lightmap_bind_group_slot: u32,
+    /// The index of the main world entity of this mesh.
+    ///
+    /// This is written to the entity index prepass texture, see
+    /// [`EntityIndexPrepass`](bevy_core_pipeline::prepass::EntityIndexPrepass).
+    pub main_entity_index: u32,
+    /// The high 32 bits of [`Entity::to_bits`](bevy_ecs::entity::Entity::to_bits) for the main
+    /// world entity of this mesh, which hold its generation. This is never zero.
+    pub main_entity_generation: u32,
 }
 
 /// Information that has to be transferred from CPU to GPU in order to produce
@@ -373,10 +447,11 @@ pub struct MeshInputUniform {
     /// Low 16 bits: index of the material inside the bind group data.
     /// High 16 bits: index of the lightmap in the binding array.
     pub material_and_lightmap_bind_group_slot: u32,
-    /// Padding.
-    pub pad_a: u32,
-    /// Padding.
-    pub pad_b: u32,
+    /// The index of the main world entity of this mesh.
+    pub main_entity_index: u32,
+    /// The high 32 bits of [`Entity::to_bits`](bevy_ecs::entity::Entity::to_bits) for the main
+    /// world entity of this mesh.
+    pub main_entity_generation: u32,
 }
 
 /// Information about each mesh instance needed to cull it on GPU.
@@ -405,6 +480,7 @@ pub struct MeshCullingDataBuffer(RawBufferVec<MeshCullingData>);
 impl MeshUniform {
     pub fn new(
         mesh_transforms: &MeshTransforms,
+        main_entity: MainEntity,
         first_vertex_index: u32,
         material_bind_group_slot: MaterialBindGroupSlot,
         maybe_lightmap: Option<(LightmapSlotIndex, Rect)>,
@@ -430,6 +506,8 @@ impl MeshUniform {
             previous_skin_index: previous_skin_index.unwrap_or(u32::MAX),
             material_and_lightmap_bind_group_slot: u32::from(material_bind_group_slot)
                 | ((lightmap_bind_group_slot as u32) << 16),
+            main_entity_index: main_entity.id().index(),
+            main_entity_generation: (main_entity.id().to_bits() >> 32) as u32,
         }
     }
 }
@@ -446,6 +524,11 @@ bitflags::bitflags! {
         ///
         /// This will be `u16::MAX` if this mesh has no LOD.
         const LOD_INDEX_MASK              = (1 << 16) - 1;
+        /// The mesh's joint data is packed as dual quaternions rather than
+        /// matrices.
+        ///
+        /// This corresponds to [`SkinningMethod::DualQuaternion`].
+        const DUAL_QUATERNION_SKINNING    = 1 << 27;
         /// Disables frustum culling for this mesh.
         ///
         /// This corresponds to the
@@ -468,6 +551,7 @@ impl MeshFlags {
         no_frustum_culling: bool,
         not_shadow_receiver: bool,
         transmitted_receiver: bool,
+        skinning_method: Option<&SkinningMethod>,
     ) -> MeshFlags {
         let mut mesh_flags = if not_shadow_receiver {
             MeshFlags::empty()
@@ -480,6 +564,9 @@ impl MeshFlags {
         if transmitted_receiver {
             mesh_flags |= MeshFlags::TRANSMITTED_SHADOW_RECEIVER;
         }
+        if skinning_method == Some(&SkinningMethod::DualQuaternion) {
+            mesh_flags |= MeshFlags::DUAL_QUATERNION_SKINNING;
+        }
         if transform.affine().matrix3.determinant().is_sign_positive() {
             mesh_flags |= MeshFlags::SIGN_DETERMINANT_MODEL_3X3;
         }
@@ -515,6 +602,10 @@ bitflags::bitflags! {
         /// The mesh had morph targets last frame and so they should be taken
         /// into account for motion vector computation.
         const HAS_PREVIOUS_MORPH      = 1 << 4;
+        /// The mesh's vertices were skinned by the pre-skinning compute shader
+        /// this frame, so the vertex shader should read them from the
+        /// pre-skinned vertex buffer instead of skinning them itself.
+        const PRESKINNED              = 1 << 5;
     }
 }
 
@@ -754,7 +845,11 @@ impl RenderMeshInstances {
 
     /// Inserts the given flags into the CPU or GPU render mesh instance data
     /// for the given mesh as appropriate.
-    fn insert_mesh_instance_flags(&mut self, entity: MainEntity, flags: RenderMeshInstanceFlags) {
+    pub(crate) fn insert_mesh_instance_flags(
+        &mut self,
+        entity: MainEntity,
+        flags: RenderMeshInstanceFlags,
+    ) {
         match *self {
             RenderMeshInstances::CpuBuilding(ref mut instances) => {
                 instances.insert_mesh_instance_flags(entity, flags);
@@ -764,6 +859,23 @@ impl RenderMeshInstances {
             }
         }
     }
+
+    /// Removes the given flags from the CPU or GPU render mesh instance data
+    /// for the given mesh as appropriate.
+    pub(crate) fn remove_mesh_instance_flags(
+        &mut self,
+        entity: MainEntity,
+        flags: RenderMeshInstanceFlags,
+    ) {
+        match *self {
+            RenderMeshInstances::CpuBuilding(ref mut instances) => {
+                instances.remove_mesh_instance_flags(entity, flags);
+            }
+            RenderMeshInstances::GpuBuilding(ref mut instances) => {
+                instances.remove_mesh_instance_flags(entity, flags);
+            }
+        }
+    }
 }
 
 impl RenderMeshInstancesCpu {
@@ -787,6 +899,14 @@ impl RenderMeshInstancesCpu {
             instance.flags.insert(flags);
         }
     }
+
+    /// Removes the given flags from the render mesh instance data for the
+    /// given mesh.
+    fn remove_mesh_instance_flags(&mut self, entity: MainEntity, flags: RenderMeshInstanceFlags) {
+        if let Some(instance) = self.get_mut(&entity) {
+            instance.flags.remove(flags);
+        }
+    }
 }
 
 impl RenderMeshInstancesGpu {
@@ -810,6 +930,14 @@ impl RenderMeshInstancesGpu {
             instance.flags.insert(flags);
         }
     }
+
+    /// Removes the given flags from the render mesh instance data for the
+    /// given mesh.
+    fn remove_mesh_instance_flags(&mut self, entity: MainEntity, flags: RenderMeshInstanceFlags) {
+        if let Some(instance) = self.get_mut(&entity) {
+            instance.flags.remove(flags);
+        }
+    }
 }
 
 impl RenderMeshInstanceGpuQueue {
@@ -977,8 +1105,8 @@ impl RenderMeshInstanceGpuBuilder {
             material_and_lightmap_bind_group_slot: u32::from(
                 self.shared.material_bindings_index.slot,
             ) | ((lightmap_slot as u32) << 16),
-            pad_a: 0,
-            pad_b: 0,
+            main_entity_index: entity.id().index(),
+            main_entity_generation: (entity.id().to_bits() >> 32) as u32,
         };
 
         // Did the last frame contain this entity as well?
@@ -1123,6 +1251,7 @@ pub fn extract_meshes_for_cpu_building(
             Has<NotShadowCaster>,
             Has<NoAutomaticBatching>,
             Has<VisibilityRange>,
+            Option<&SkinningMethod>,
         )>,
     >,
 ) {
@@ -1141,6 +1270,7 @@ pub fn extract_meshes_for_cpu_building(
             not_shadow_caster,
             no_automatic_batching,
             visibility_range,
+            skinning_method,
         )| {
             if !view_visibility.get() {
                 return;
@@ -1157,6 +1287,7 @@ pub fn extract_meshes_for_cpu_building(
                 no_frustum_culling,
                 not_shadow_receiver,
                 transmitted_receiver,
+                skinning_method,
             );
 
             let shared = RenderMeshInstanceShared::from_components(
@@ -1229,6 +1360,7 @@ pub fn extract_meshes_for_gpu_building(
                 Has<NotShadowCaster>,
                 Has<NoAutomaticBatching>,
                 Has<VisibilityRange>,
+                Option<&SkinningMethod>,
             ),
             Or<(
                 Changed<ViewVisibility>,
@@ -1243,6 +1375,7 @@ pub fn extract_meshes_for_gpu_building(
                 Changed<NotShadowCaster>,
                 Changed<NoAutomaticBatching>,
                 Changed<VisibilityRange>,
+                Changed<SkinningMethod>,
             )>,
         >,
     >,
@@ -1285,6 +1418,7 @@ pub fn extract_meshes_for_gpu_building(
             not_shadow_caster,
             no_automatic_batching,
             visibility_range,
+            skinning_method,
         )| {
             if !view_visibility.get() {
                 queue.remove(entity.into(), any_gpu_culling);
@@ -1302,6 +1436,7 @@ pub fn extract_meshes_for_gpu_building(
                 no_frustum_culling,
                 not_shadow_receiver,
                 transmitted_receiver,
+                skinning_method,
             );
 
             let shared = RenderMeshInstanceShared::from_components(
@@ -1372,7 +1507,7 @@ pub fn extract_meshes_for_gpu_building(
 /// [`crate::material::queue_material_meshes`] check the skin and morph target
 /// tables for each mesh, but that would be too slow in the hot mesh queuing
 /// loop.
-fn set_mesh_motion_vector_flags(
+pub(crate) fn set_mesh_motion_vector_flags(
     mut render_mesh_instances: ResMut<RenderMeshInstances>,
     skin_indices: Res<SkinIndices>,
     morph_indices: Res<MorphIndices>,
@@ -1515,6 +1650,14 @@ pub struct MeshPipeline {
     /// Whether skins will use uniform buffers on account of storage buffers
     /// being unavailable on this platform.
     pub skins_use_uniform_buffers: bool,
+
+    /// Whether [`RayTracedShadows`](crate::RayTracedShadows) are supported on
+    /// the current render device.
+    pub ray_traced_shadows_are_usable: bool,
+
+    /// Whether meshes can be drawn as lines on the current render device, for
+    /// [`DebugViewMode::Wireframe`](crate::DebugViewMode::Wireframe).
+    pub polygon_mode_line_is_usable: bool,
 }
 
 impl FromWorld for MeshPipeline {
@@ -1574,6 +1717,10 @@ impl FromWorld for MeshPipeline {
             per_object_buffer_batch_size: GpuArrayBuffer::<MeshUniform>::batch_size(&render_device),
             binding_arrays_are_usable: binding_arrays_are_usable(&render_device, &render_adapter),
             skins_use_uniform_buffers: skin::skins_use_uniform_buffers(&render_device),
+            ray_traced_shadows_are_usable: ray_traced_shadows_are_usable(&render_device),
+            polygon_mode_line_is_usable: render_device
+                .features()
+                .contains(WgpuFeatures::POLYGON_MODE_LINE),
         }
     }
 }
@@ -1645,6 +1792,7 @@ impl GetBatchData for MeshPipeline {
         Some((
             MeshUniform::new(
                 &mesh_instance.transforms,
+                main_entity,
                 first_vertex_index,
                 material_bind_group_index.slot,
                 maybe_lightmap.map(|lightmap| (lightmap.slot_index, lightmap.uv_rect)),
@@ -1712,6 +1860,7 @@ impl GetFullBatchData for MeshPipeline {
 
         Some(MeshUniform::new(
             &mesh_instance.transforms,
+            main_entity,
             first_vertex_index,
             mesh_instance.material_bindings_index.slot,
             maybe_lightmap.map(|lightmap| (lightmap.slot_index, lightmap.uv_rect)),
@@ -1802,7 +1951,11 @@ bitflags::bitflags! {
         const HAS_PREVIOUS_SKIN                 = 1 << 18;
         const HAS_PREVIOUS_MORPH                = 1 << 19;
         const OIT_ENABLED                       = 1 << 20;
-        const LAST_FLAG                         = Self::OIT_ENABLED.bits();
+        const PRESKINNED                        = 1 << 21;
+        const OIT_WEIGHTED_BLENDED              = 1 << 22;
+        const ENTITY_INDEX_PREPASS              = 1 << 23;
+        const SCREEN_SPACE_GLOBAL_ILLUMINATION  = 1 << 24;
+        const LAST_FLAG                         = Self::SCREEN_SPACE_GLOBAL_ILLUMINATION.bits();
 
         // Bitfields
         const MSAA_RESERVED_BITS                = Self::MSAA_MASK_BITS << Self::MSAA_SHIFT_BITS;
@@ -1835,13 +1988,23 @@ bitflags::bitflags! {
         const SCREEN_SPACE_SPECULAR_TRANSMISSION_MEDIUM = 1 << Self::SCREEN_SPACE_SPECULAR_TRANSMISSION_SHIFT_BITS;
         const SCREEN_SPACE_SPECULAR_TRANSMISSION_HIGH   = 2 << Self::SCREEN_SPACE_SPECULAR_TRANSMISSION_SHIFT_BITS;
         const SCREEN_SPACE_SPECULAR_TRANSMISSION_ULTRA  = 3 << Self::SCREEN_SPACE_SPECULAR_TRANSMISSION_SHIFT_BITS;
+        const DEBUG_VIEW_RESERVED_BITS          = Self::DEBUG_VIEW_MASK_BITS << Self::DEBUG_VIEW_SHIFT_BITS;
+        const DEBUG_VIEW_LIT                    = 0 << Self::DEBUG_VIEW_SHIFT_BITS;
+        const DEBUG_VIEW_UNLIT                  = 1 << Self::DEBUG_VIEW_SHIFT_BITS;
+        const DEBUG_VIEW_WIREFRAME              = 2 << Self::DEBUG_VIEW_SHIFT_BITS;
+        const DEBUG_VIEW_WORLD_NORMALS          = 3 << Self::DEBUG_VIEW_SHIFT_BITS;
+        const DEBUG_VIEW_UV_CHECKER             = 4 << Self::DEBUG_VIEW_SHIFT_BITS;
+        const DEBUG_VIEW_LIGHTMAP               = 5 << Self::DEBUG_VIEW_SHIFT_BITS;
+        const DEBUG_VIEW_OVERDRAW               = 6 << Self::DEBUG_VIEW_SHIFT_BITS;
+        const DEBUG_VIEW_SHADOW_CASCADES        = 7 << Self::DEBUG_VIEW_SHIFT_BITS;
         const ALL_RESERVED_BITS =
             Self::BLEND_RESERVED_BITS.bits() |
             Self::MSAA_RESERVED_BITS.bits() |
             Self::TONEMAP_METHOD_RESERVED_BITS.bits() |
             Self::SHADOW_FILTER_METHOD_RESERVED_BITS.bits() |
             Self::VIEW_PROJECTION_RESERVED_BITS.bits() |
-            Self::SCREEN_SPACE_SPECULAR_TRANSMISSION_RESERVED_BITS.bits();
+            Self::SCREEN_SPACE_SPECULAR_TRANSMISSION_RESERVED_BITS.bits() |
+            Self::DEBUG_VIEW_RESERVED_BITS.bits();
     }
 }
 
@@ -1869,6 +2032,11 @@ impl MeshPipelineKey {
     const SCREEN_SPACE_SPECULAR_TRANSMISSION_SHIFT_BITS: u64 =
         Self::VIEW_PROJECTION_MASK_BITS.count_ones() as u64 + Self::VIEW_PROJECTION_SHIFT_BITS;
 
+    const DEBUG_VIEW_MASK_BITS: u64 = 0b111;
+    const DEBUG_VIEW_SHIFT_BITS: u64 = Self::SCREEN_SPACE_SPECULAR_TRANSMISSION_MASK_BITS
+        .count_ones() as u64
+        + Self::SCREEN_SPACE_SPECULAR_TRANSMISSION_SHIFT_BITS;
+
     pub fn from_msaa_samples(msaa_samples: u32) -> Self {
         let msaa_bits =
             (msaa_samples.trailing_zeros() as u64 & Self::MSAA_MASK_BITS) << Self::MSAA_SHIFT_BITS;
@@ -1947,6 +2115,9 @@ pub fn setup_morph_and_skinning_defs(
 
     let mut add_skin_data = || {
         shader_defs.push("SKINNED".into());
+        if key.contains(MeshPipelineKey::PRESKINNED) {
+            shader_defs.push("PRESKINNED".into());
+        }
         vertex_attributes.push(Mesh::ATTRIBUTE_JOINT_INDEX.at_shader_location(offset));
         vertex_attributes.push(Mesh::ATTRIBUTE_JOINT_WEIGHT.at_shader_location(offset + 1));
     };
@@ -2045,6 +2216,9 @@ impl SpecializedMeshPipeline for MeshPipeline {
         if cfg!(feature = "pbr_anisotropy_texture") {
             shader_defs.push("PBR_ANISOTROPY_TEXTURE_SUPPORTED".into());
         }
+        if cfg!(feature = "pbr_planar_reflections") {
+            shader_defs.push("PBR_PLANAR_REFLECTIONS_SUPPORTED".into());
+        }
 
         let mut bind_group_layout = vec![self.get_view_layout(key.into()).clone()];
 
@@ -2066,9 +2240,13 @@ impl SpecializedMeshPipeline for MeshPipeline {
             shader_defs.push("SCREEN_SPACE_AMBIENT_OCCLUSION".into());
         }
 
+        if key.contains(MeshPipelineKey::SCREEN_SPACE_GLOBAL_ILLUMINATION) {
+            shader_defs.push("SCREEN_SPACE_GLOBAL_ILLUMINATION".into());
+        }
+
         let vertex_buffer_layout = layout.0.get_layout(&vertex_attributes)?;
 
-        let (label, blend, depth_write_enabled);
+        let (label, mut blend, mut depth_write_enabled);
         let pass = key.intersection(MeshPipelineKey::BLEND_RESERVED_BITS);
         let (mut is_opaque, mut alpha_to_coverage_enabled) = (false, false);
         if key.contains(MeshPipelineKey::OIT_ENABLED) && pass == MeshPipelineKey::BLEND_ALPHA {
@@ -2076,6 +2254,9 @@ impl SpecializedMeshPipeline for MeshPipeline {
             // TODO tail blending would need alpha blending
             blend = None;
             shader_defs.push("OIT_ENABLED".into());
+            if key.contains(MeshPipelineKey::OIT_WEIGHTED_BLENDED) {
+                shader_defs.push("OIT_WEIGHTED_BLENDED".into());
+            }
             // TODO it should be possible to use this to combine MSAA and OIT
             // alpha_to_coverage_enabled = true;
             depth_write_enabled = false;
@@ -2261,11 +2442,57 @@ impl SpecializedMeshPipeline for MeshPipeline {
             shader_defs.push("VISIBILITY_RANGE_DITHER".into());
         }
 
+        let (mut polygon_mode, mut depth_compare) =
+            (PolygonMode::Fill, CompareFunction::GreaterEqual);
+        let debug_view = key.intersection(MeshPipelineKey::DEBUG_VIEW_RESERVED_BITS);
+        if debug_view == MeshPipelineKey::DEBUG_VIEW_UNLIT {
+            shader_defs.push("DEBUG_VIEW".into());
+            shader_defs.push("DEBUG_VIEW_UNLIT".into());
+        } else if debug_view == MeshPipelineKey::DEBUG_VIEW_WIREFRAME {
+            if self.polygon_mode_line_is_usable {
+                polygon_mode = PolygonMode::Line;
+            } else {
+                once!(warn!(
+                    "The wireframe debug view needs the `POLYGON_MODE_LINE` feature, which the \
+                    render device doesn't support. Drawing the meshes filled instead."
+                ));
+            }
+        } else if debug_view == MeshPipelineKey::DEBUG_VIEW_WORLD_NORMALS {
+            shader_defs.push("DEBUG_VIEW".into());
+            shader_defs.push("DEBUG_VIEW_WORLD_NORMALS".into());
+        } else if debug_view == MeshPipelineKey::DEBUG_VIEW_UV_CHECKER {
+            shader_defs.push("DEBUG_VIEW".into());
+            shader_defs.push("DEBUG_VIEW_UV_CHECKER".into());
+        } else if debug_view == MeshPipelineKey::DEBUG_VIEW_LIGHTMAP {
+            shader_defs.push("DEBUG_VIEW".into());
+            shader_defs.push("DEBUG_VIEW_LIGHTMAP".into());
+        } else if debug_view == MeshPipelineKey::DEBUG_VIEW_OVERDRAW {
+            shader_defs.push("DEBUG_VIEW".into());
+            shader_defs.push("DEBUG_VIEW_OVERDRAW".into());
+            // Add up all the surfaces, including the hidden ones.
+            blend = Some(BlendState {
+                color: BlendComponent {
+                    src_factor: BlendFactor::One,
+                    dst_factor: BlendFactor::One,
+                    operation: BlendOperation::Add,
+                },
+                alpha: BlendComponent::OVER,
+            });
+            depth_write_enabled = false;
+            depth_compare = CompareFunction::Always;
+        } else if debug_view == MeshPipelineKey::DEBUG_VIEW_SHADOW_CASCADES {
+            shader_defs.push("DIRECTIONAL_LIGHT_SHADOW_MAP_DEBUG_CASCADES".into());
+        }
+
         if self.binding_arrays_are_usable {
             shader_defs.push("MULTIPLE_LIGHT_PROBES_IN_ARRAY".into());
             shader_defs.push("MULTIPLE_LIGHTMAPS_IN_ARRAY".into());
         }
 
+        if self.ray_traced_shadows_are_usable {
+            shader_defs.push("RAY_TRACED_SHADOWS".into());
+        }
+
         if IRRADIANCE_VOLUMES_ARE_USABLE {
             shader_defs.push("IRRADIANCE_VOLUMES_ARE_USABLE".into());
         }
@@ -2309,7 +2536,7 @@ impl SpecializedMeshPipeline for MeshPipeline {
                 front_face: FrontFace::Ccw,
                 cull_mode: Some(Face::Back),
                 unclipped_depth: false,
-                polygon_mode: PolygonMode::Fill,
+                polygon_mode,
                 conservative: false,
                 topology: key.primitive_topology(),
                 strip_index_format: None,
@@ -2317,7 +2544,7 @@ impl SpecializedMeshPipeline for MeshPipeline {
             depth_stencil: Some(DepthStencilState {
                 format: CORE_3D_DEPTH_FORMAT,
                 depth_write_enabled,
-                depth_compare: CompareFunction::GreaterEqual,
+                depth_compare,
                 stencil: StencilState {
                     front: StencilFaceState::IGNORE,
                     back: StencilFaceState::IGNORE,
@@ -2411,6 +2638,7 @@ pub fn prepare_mesh_bind_group(
     skins_uniform: Res<SkinUniforms>,
     weights_uniform: Res<MorphUniforms>,
     mut render_lightmaps: ResMut<RenderLightmaps>,
+    #[cfg(feature = "gpu_preskinning")] preskinned_meshes: Option<Res<PreskinnedMeshes>>,
 ) {
     groups.reset();
 
@@ -2431,15 +2659,32 @@ pub fn prepare_mesh_bind_group(
 
     groups.model_only = Some(layouts.model_only(&render_device, &model));
 
+    // If skinned meshes are pre-skinned, the skinned mesh bind groups also
+    // contain the pre-skinned vertices.
+    #[cfg(feature = "gpu_preskinning")]
+    let preskinned = preskinned_meshes
+        .as_deref()
+        .and_then(PreskinnedMeshes::buffers);
+    #[cfg(not(feature = "gpu_preskinning"))]
+    let preskinned = None;
+
     // Create the skinned mesh bind group with the current and previous buffers
     // (the latter being for motion vector computation). If there's no previous
     // buffer, just use the current one as the shader will ignore it.
     let skin = skins_uniform.current_buffer.buffer();
-    if let Some(skin) = skin {
+    if let Some(skin) =
+        skin.filter(|_| preskinned.is_some() || !skin::skins_use_preskinning(&render_device))
+    {
         let prev_skin = skins_uniform.prev_buffer.buffer().unwrap_or(skin);
         groups.skinned = Some(MeshBindGroupPair {
-            motion_vectors: layouts.skinned_motion(&render_device, &model, skin, prev_skin),
-            no_motion_vectors: layouts.skinned(&render_device, &model, skin),
+            motion_vectors: layouts.skinned_motion(
+                &render_device,
+                &model,
+                skin,
+                prev_skin,
+                preskinned,
+            ),
+            no_motion_vectors: layouts.skinned(&render_device, &model, skin, preskinned),
         });
     }
 
@@ -44,11 +44,26 @@ fn vertex(vertex_no_morph: Vertex) -> VertexOutput {
     let mesh_world_from_local = mesh_functions::get_world_from_local(vertex_no_morph.instance_index);
 
 #ifdef SKINNED
+#ifdef PRESKINNED
+    // The vertex was already skinned by the pre-skinning compute shader.
+    let preskinned = skinning::preskinned_vertex(vertex_no_morph.index, vertex_no_morph.instance_index);
+#ifdef VERTEX_POSITIONS
+    vertex.position = preskinned.position.xyz;
+#endif
+#ifdef VERTEX_NORMALS
+    vertex.normal = preskinned.normal.xyz;
+#endif
+#ifdef VERTEX_TANGENTS
+    vertex.tangent = preskinned.tangent;
+#endif
+    var world_from_local = skinning::PRESKINNED_WORLD_FROM_LOCAL;
+#else   // PRESKINNED
     var world_from_local = skinning::skin_model(
         vertex.joint_indices,
         vertex.joint_weights,
         vertex_no_morph.instance_index
     );
+#endif  // PRESKINNED
 #else
     // Use vertex_no_morph.instance_index instead of vertex.instance_index to work around a wgpu dx12 bug.
     // See https://github.com/gfx-rs/naga/issues/2416 .
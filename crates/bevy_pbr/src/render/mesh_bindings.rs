@@ -7,7 +7,11 @@ use bevy_render::{
     renderer::{RenderAdapter, RenderDevice},
 };
 
-use crate::{binding_arrays_are_usable, render::skin::MAX_JOINTS, LightmapSlab};
+use crate::{
+    binding_arrays_are_usable,
+    render::skin::{self, MAX_JOINTS},
+    LightmapSlab,
+};
 
 const MORPH_WEIGHT_SIZE: usize = size_of::<f32>();
 
@@ -53,6 +57,9 @@ mod layout_entry {
             storage_buffer_read_only_sized(false, size)
         }
     }
+    pub(super) fn preskinned() -> BindGroupLayoutEntryBuilder {
+        storage_buffer_read_only_sized(false, None)
+    }
     pub(super) fn weights() -> BindGroupLayoutEntryBuilder {
         uniform_buffer_sized(true, BufferSize::new(MORPH_BUFFER_SIZE as u64))
     }
@@ -116,6 +123,9 @@ mod entry {
         };
         entry(binding, size, buffer)
     }
+    pub(super) fn preskinned(binding: u32, buffer: &Buffer) -> BindGroupEntry {
+        entry(binding, None, buffer)
+    }
     pub(super) fn weights(binding: u32, buffer: &Buffer) -> BindGroupEntry {
         entry(binding, Some(MORPH_BUFFER_SIZE as u64), buffer)
     }
@@ -166,7 +176,13 @@ pub struct MeshLayouts {
     /// Includes the lightmap texture and uniform.
     pub lightmapped: BindGroupLayout,
 
-    /// Also includes the uniform for skinning
+    /// Also includes the uniform for skinning.
+    ///
+    /// If skinned meshes are pre-skinned in a compute shader (see
+    /// [`skins_use_preskinning`]), this also includes the pre-skinned vertex
+    /// buffers.
+    ///
+    /// [`skins_use_preskinning`]: crate::skins_use_preskinning
     pub skinned: BindGroupLayout,
 
     /// Like [`MeshLayouts::skinned`], but includes slots for the previous
@@ -225,6 +241,23 @@ impl MeshLayouts {
 
     /// Creates the layout for skinned meshes.
     fn skinned_layout(render_device: &RenderDevice) -> BindGroupLayout {
+        if skin::skins_use_preskinning(render_device) {
+            return render_device.create_bind_group_layout(
+                "skinned_mesh_layout",
+                &BindGroupLayoutEntries::with_indices(
+                    ShaderStages::VERTEX,
+                    (
+                        (0, layout_entry::model(render_device)),
+                        // The current frame's joint matrix buffer.
+                        (1, layout_entry::skinning(render_device)),
+                        // The index of each pre-skinned mesh's first vertex.
+                        (8, layout_entry::preskinned()),
+                        // The pre-skinned vertices.
+                        (9, layout_entry::preskinned()),
+                    ),
+                ),
+            );
+        }
         render_device.create_bind_group_layout(
             "skinned_mesh_layout",
             &BindGroupLayoutEntries::with_indices(
@@ -241,6 +274,25 @@ impl MeshLayouts {
     /// Creates the layout for skinned meshes with the infrastructure to compute
     /// motion vectors.
     fn skinned_motion_layout(render_device: &RenderDevice) -> BindGroupLayout {
+        if skin::skins_use_preskinning(render_device) {
+            return render_device.create_bind_group_layout(
+                "skinned_motion_mesh_layout",
+                &BindGroupLayoutEntries::with_indices(
+                    ShaderStages::VERTEX,
+                    (
+                        (0, layout_entry::model(render_device)),
+                        // The current frame's joint matrix buffer.
+                        (1, layout_entry::skinning(render_device)),
+                        // The previous frame's joint matrix buffer.
+                        (6, layout_entry::skinning(render_device)),
+                        // The index of each pre-skinned mesh's first vertex.
+                        (8, layout_entry::preskinned()),
+                        // The pre-skinned vertices.
+                        (9, layout_entry::preskinned()),
+                    ),
+                ),
+            );
+        }
         render_device.create_bind_group_layout(
             "skinned_motion_mesh_layout",
             &BindGroupLayoutEntries::with_indices(
@@ -407,20 +459,37 @@ impl MeshLayouts {
     }
 
     /// Creates the bind group for skinned meshes with no morph targets.
+    ///
+    /// `preskinned` holds the buffers of vertex offsets and vertices written
+    /// by the pre-skinning compute shader. It must be present if and only if
+    /// skinned meshes are pre-skinned on this platform.
     pub fn skinned(
         &self,
         render_device: &RenderDevice,
         model: &BindingResource,
         current_skin: &Buffer,
+        preskinned: Option<(&Buffer, &Buffer)>,
     ) -> BindGroup {
-        render_device.create_bind_group(
-            "skinned_mesh_bind_group",
-            &self.skinned,
-            &[
-                entry::model(0, model.clone()),
-                entry::skinning(render_device, 1, current_skin),
-            ],
-        )
+        match preskinned {
+            Some((vertex_offsets, vertices)) => render_device.create_bind_group(
+                "skinned_mesh_bind_group",
+                &self.skinned,
+                &[
+                    entry::model(0, model.clone()),
+                    entry::skinning(render_device, 1, current_skin),
+                    entry::preskinned(8, vertex_offsets),
+                    entry::preskinned(9, vertices),
+                ],
+            ),
+            None => render_device.create_bind_group(
+                "skinned_mesh_bind_group",
+                &self.skinned,
+                &[
+                    entry::model(0, model.clone()),
+                    entry::skinning(render_device, 1, current_skin),
+                ],
+            ),
+        }
     }
 
     /// Creates the bind group for skinned meshes with no morph targets, with
@@ -429,23 +498,38 @@ impl MeshLayouts {
     /// `current_skin` is the buffer of joint matrices for this frame;
     /// `prev_skin` is the buffer for the previous frame. The latter is used for
     /// motion vector computation. If there is no such applicable buffer,
-    /// `current_skin` and `prev_skin` will reference the same buffer.
+    /// `current_skin` and `prev_skin` will reference the same buffer. See
+    /// [`MeshLayouts::skinned`] for `preskinned`.
     pub fn skinned_motion(
         &self,
         render_device: &RenderDevice,
         model: &BindingResource,
         current_skin: &Buffer,
         prev_skin: &Buffer,
+        preskinned: Option<(&Buffer, &Buffer)>,
     ) -> BindGroup {
-        render_device.create_bind_group(
-            "skinned_motion_mesh_bind_group",
-            &self.skinned_motion,
-            &[
-                entry::model(0, model.clone()),
-                entry::skinning(render_device, 1, current_skin),
-                entry::skinning(render_device, 6, prev_skin),
-            ],
-        )
+        match preskinned {
+            Some((vertex_offsets, vertices)) => render_device.create_bind_group(
+                "skinned_motion_mesh_bind_group",
+                &self.skinned_motion,
+                &[
+                    entry::model(0, model.clone()),
+                    entry::skinning(render_device, 1, current_skin),
+                    entry::skinning(render_device, 6, prev_skin),
+                    entry::preskinned(8, vertex_offsets),
+                    entry::preskinned(9, vertices),
+                ],
+            ),
+            None => render_device.create_bind_group(
+                "skinned_motion_mesh_bind_group",
+                &self.skinned_motion,
+                &[
+                    entry::model(0, model.clone()),
+                    entry::skinning(render_device, 1, current_skin),
+                    entry::skinning(render_device, 6, prev_skin),
+                ],
+            ),
+        }
     }
 
     /// Creates the bind group for meshes with no skins but morph targets.
@@ -6,6 +6,12 @@
 // meshes for all views. As part of this process, the shader gathers each
 // mesh's transform on the previous frame and writes it into the `MeshUniform`
 // so that TAA works.
+//
+// With occlusion culling, this shader runs twice for each view: once before
+// the prepass for the meshes of the prepass phases, which are tested against
+// the depth pyramid of the previous frame (`EARLY_PHASE`), and once after it
+// for the meshes of the main pass phases, which are tested against the depth
+// pyramid of the current frame (`LATE_PHASE`).
 
 #import bevy_pbr::mesh_types::{Mesh, MESH_FLAGS_NO_FRUSTUM_CULLING_BIT}
 #import bevy_pbr::mesh_preprocess_types::{MeshInput, IndirectParametersMetadata}
@@ -96,6 +102,100 @@ fn view_frustum_intersects_obb(
 }
 #endif
 
+#ifdef OCCLUSION_CULLING
+// The data needed to test meshes against the depth pyramid.
+struct OcclusionCullingView {
+    // The view projection matrix of the previous frame.
+    previous_clip_from_world: mat4x4<f32>,
+    // The viewport of the previous frame, in pixels.
+    previous_viewport: vec4<f32>,
+    // The size of the depth buffer the depth pyramid is built from, in pixels.
+    depth_size: vec2<f32>,
+}
+
+// The batches that the late phase found visible meshes in, which the late
+// indirect parameters building pass processes.
+struct LateBatches {
+    count: atomic<u32>,
+    indices: array<u32>,
+}
+
+// The depth pyramid, in which each texel holds the farthest depth of the
+// depth buffer texels it covers.
+@group(1) @binding(0) var depth_pyramid: texture_2d<f32>;
+@group(1) @binding(1) var<uniform> occlusion_culling_view: OcclusionCullingView;
+#ifdef LATE_PHASE
+@group(1) @binding(2) var<storage, read_write> late_batches: LateBatches;
+#endif
+
+// Returns true if an axis-aligned bounding box (AABB) is entirely hidden
+// behind the depth pyramid.
+//
+// `viewport` is the viewport that `clip_from_local` projects to, in pixels.
+fn aabb_is_occluded(
+    clip_from_local: mat4x4<f32>,
+    viewport: vec4<f32>,
+    aabb_center: vec3<f32>,
+    aabb_half_extents: vec3<f32>,
+) -> bool {
+    // Find the bounds of the AABB in normalized device coordinates.
+    var ndc_min = vec3(1.0e9);
+    var ndc_max = vec3(-1.0e9);
+    for (var i = 0u; i < 8u; i += 1u) {
+        let corner_sign = vec3(
+            select(-1.0, 1.0, (i & 1u) != 0u),
+            select(-1.0, 1.0, (i & 2u) != 0u),
+            select(-1.0, 1.0, (i & 4u) != 0u),
+        );
+        let clip = clip_from_local * vec4(aabb_center + aabb_half_extents * corner_sign, 1.0);
+
+        // If the AABB crosses the camera plane, its projection isn't bounded,
+        // so treat it as visible.
+        if (clip.w <= 0.0) {
+            return false;
+        }
+
+        let ndc = clip.xyz / clip.w;
+        ndc_min = min(ndc_min, ndc);
+        ndc_max = max(ndc_max, ndc);
+    }
+
+    // Find the bounds of the AABB on the depth buffer, in UV coordinates.
+    let viewport_uv_min = saturate(vec2(ndc_min.x, -ndc_max.y) * 0.5 + 0.5);
+    let viewport_uv_max = saturate(vec2(ndc_max.x, -ndc_min.y) * 0.5 + 0.5);
+    let uv_min = (viewport.xy + viewport_uv_min * viewport.zw) / occlusion_culling_view.depth_size;
+    let uv_max = (viewport.xy + viewport_uv_max * viewport.zw) / occlusion_culling_view.depth_size;
+
+    // Pick the finest mip level at which the bounds span at most 2×2 texels.
+    let mip_level_count = textureNumLevels(depth_pyramid);
+    var mip_level = 0u;
+    var mip_size = vec2<f32>(textureDimensions(depth_pyramid, 0u));
+    while (mip_level + 1u < mip_level_count &&
+            any((uv_max - uv_min) * mip_size > vec2(1.0))) {
+        mip_level += 1u;
+        mip_size = vec2<f32>(textureDimensions(depth_pyramid, mip_level));
+    }
+
+    let max_texel = vec2<u32>(mip_size) - 1u;
+    let texel_min = min(vec2<u32>(uv_min * mip_size), max_texel);
+    let texel_max = min(vec2<u32>(uv_max * mip_size), max_texel);
+    let occluder_depth = min(
+        min(
+            textureLoad(depth_pyramid, texel_min, mip_level).r,
+            textureLoad(depth_pyramid, vec2(texel_max.x, texel_min.y), mip_level).r
+        ),
+        min(
+            textureLoad(depth_pyramid, vec2(texel_min.x, texel_max.y), mip_level).r,
+            textureLoad(depth_pyramid, texel_max, mip_level).r
+        ),
+    );
+
+    // With reversed Z, the AABB is hidden if its nearest point is farther than
+    // the farthest occluder.
+    return ndc_max.z < occluder_depth;
+}
+#endif  // OCCLUSION_CULLING
+
 @compute
 @workgroup_size(64)
 fn main(@builtin(global_invocation_id) global_invocation_id: vec3<u32>) {
@@ -126,6 +226,29 @@ fn main(@builtin(global_invocation_id) global_invocation_id: vec3<u32>) {
         if (!view_frustum_intersects_obb(world_from_local, model_center, aabb_half_extents)) {
             return;
         }
+
+#ifdef OCCLUSION_CULLING
+#ifdef EARLY_PHASE
+        // Test the mesh where it was on the previous frame against the depth
+        // pyramid of the previous frame.
+        var occlusion_world_from_local = world_from_local;
+        let occlusion_previous_input_index = current_input[input_index].previous_input_index;
+        if (occlusion_previous_input_index != 0xffffffff) {
+            occlusion_world_from_local = maths::affine3_to_square(
+                previous_input[occlusion_previous_input_index].world_from_local);
+        }
+        let clip_from_local =
+            occlusion_culling_view.previous_clip_from_world * occlusion_world_from_local;
+        let occlusion_viewport = occlusion_culling_view.previous_viewport;
+#else   // EARLY_PHASE
+        let clip_from_local = view.clip_from_world * world_from_local;
+        let occlusion_viewport = view.viewport;
+#endif  // EARLY_PHASE
+
+        if (aabb_is_occluded(clip_from_local, occlusion_viewport, aabb_center, aabb_half_extents)) {
+            return;
+        }
+#endif  // OCCLUSION_CULLING
     }
 #endif
 
@@ -158,6 +281,15 @@ fn main(@builtin(global_invocation_id) global_invocation_id: vec3<u32>) {
     let mesh_output_index =
         indirect_parameters_metadata[indirect_parameters_index].base_output_index +
         batch_output_index;
+
+#ifdef LATE_PHASE
+    // Record the batch the first time one of its meshes is found visible, so
+    // that the late indirect parameters building pass processes it.
+    if (batch_output_index == 0u) {
+        let late_batch_index = atomicAdd(&late_batches.count, 1u);
+        late_batches.indices[late_batch_index] = indirect_parameters_index;
+    }
+#endif  // LATE_PHASE
 #else   // INDIRECT
     let mesh_output_index = output_index;
 #endif  // INDIRECT
@@ -174,4 +306,7 @@ fn main(@builtin(global_invocation_id) global_invocation_id: vec3<u32>) {
     output[mesh_output_index].previous_skin_index = current_input[input_index].previous_skin_index;
     output[mesh_output_index].material_and_lightmap_bind_group_slot =
         current_input[input_index].material_and_lightmap_bind_group_slot;
+    output[mesh_output_index].main_entity_index = current_input[input_index].main_entity_index;
+    output[mesh_output_index].main_entity_generation =
+        current_input[input_index].main_entity_generation;
 }
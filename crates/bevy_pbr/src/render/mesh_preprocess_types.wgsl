@@ -28,6 +28,10 @@ struct MeshInput {
     // Low 16 bits: index of the material inside the bind group data.
     // High 16 bits: index of the lightmap in the binding array.
     material_and_lightmap_bind_group_slot: u32,
+    // The index of the main world entity of the mesh.
+    main_entity_index: u32,
+    // The high 32 bits of the main world entity of the mesh.
+    main_entity_generation: u32,
 }
 
 // The `wgpu` indirect parameters structure for indexed meshes.
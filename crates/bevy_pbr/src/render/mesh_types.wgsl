@@ -22,6 +22,10 @@ struct Mesh {
     // Low 16 bits: index of the material inside the bind group data.
     // High 16 bits: index of the lightmap in the binding array.
     material_and_lightmap_bind_group_slot: u32,
+    // The index of the main world entity of the mesh.
+    main_entity_index: u32,
+    // The high 32 bits of the main world entity of the mesh, which are never 0.
+    main_entity_generation: u32,
 };
 
 #ifdef SKINNED
@@ -38,6 +42,8 @@ struct MorphWeights {
 
 // [2^0, 2^16)
 const MESH_FLAGS_VISIBILITY_RANGE_INDEX_BITS: u32 = 65535u;
+// 2^27
+const MESH_FLAGS_DUAL_QUATERNION_SKINNING_BIT: u32 = 134217728u;
 // 2^28
 const MESH_FLAGS_NO_FRUSTUM_CULLING_BIT: u32 = 268435456u;
 // 2^29
@@ -37,16 +37,15 @@ use crate::{
         self, IrradianceVolume, RenderViewIrradianceVolumeBindGroupEntries,
         IRRADIANCE_VOLUMES_ARE_USABLE,
     },
-    prepass, EnvironmentMapUniformBuffer, FogMeta, GlobalClusterableObjectMeta,
-    GpuClusterableObjects, GpuFog, GpuLights, LightMeta, LightProbesBuffer, LightProbesUniform,
-    MeshPipeline, MeshPipelineKey, RenderViewLightProbes, ScreenSpaceAmbientOcclusionResources,
-    ScreenSpaceReflectionsBuffer, ScreenSpaceReflectionsUniform, ShadowSamplers,
-    ViewClusterBindings, ViewShadowBindings, CLUSTERED_FORWARD_STORAGE_BUFFER_COUNT,
+    prepass, ray_traced_shadows_are_usable, EnvironmentMapUniformBuffer, FogMeta,
+    GlobalClusterableObjectMeta, GpuClusterableObjects, GpuFog, GpuLights, IesProfileBuffer,
+    LightMeta, LightProbesBuffer, LightProbesUniform, MeshPipeline, MeshPipelineKey,
+    RayTracingScene, RenderViewLightProbes, ScreenSpaceAmbientOcclusionResources,
+    ScreenSpaceGlobalIlluminationTextures, ScreenSpaceReflectionsBuffer,
+    ScreenSpaceReflectionsUniform, ShadowSamplers, ViewClusterBindings, ViewShadowBindings,
+    CLUSTERED_FORWARD_STORAGE_BUFFER_COUNT,
 };
 
-#[cfg(all(feature = "webgl", target_arch = "wasm32", not(feature = "webgpu")))]
-use bevy_render::render_resource::binding_types::texture_cube;
-
 #[cfg(debug_assertions)]
 use {crate::MESH_PIPELINE_VIEW_LAYOUT_SAFE_MAX_TEXTURES, bevy_utils::once, tracing::warn};
 
@@ -205,24 +204,8 @@ fn layout_entries(
             ),
             // Lights
             (1, uniform_buffer::<GpuLights>(true)),
-            // Point Shadow Texture Cube Array
-            (
-                2,
-                #[cfg(all(
-                    not(feature = "ios_simulator"),
-                    any(
-                        not(feature = "webgl"),
-                        not(target_arch = "wasm32"),
-                        feature = "webgpu"
-                    )
-                ))]
-                texture_cube_array(TextureSampleType::Depth),
-                #[cfg(any(
-                    feature = "ios_simulator",
-                    all(feature = "webgl", target_arch = "wasm32", not(feature = "webgpu"))
-                ))]
-                texture_cube(TextureSampleType::Depth),
-            ),
+            // Point Shadow Texture Atlas
+            (2, texture_2d(TextureSampleType::Depth)),
             // Point Shadow Texture Array Comparison Sampler
             (3, sampler(SamplerBindingType::Comparison)),
             // Point Shadow Texture Array Linear Sampler
@@ -385,6 +368,25 @@ fn layout_entries(
         }
     }
 
+    // IES profiles
+    if matches!(
+        clustered_forward_buffer_binding_type,
+        BufferBindingType::Storage { .. }
+    ) {
+        entries = entries.extend_with_indices(((34, storage_buffer_read_only_sized(false, None)),));
+    }
+
+    // Ray traced shadows
+    if ray_traced_shadows_are_usable(render_device) {
+        entries = entries.extend_with_indices(((35, acceleration_structure()),));
+    }
+
+    // Screen space global illumination texture
+    entries = entries.extend_with_indices(((
+        36,
+        texture_2d(TextureSampleType::Float { filterable: false }),
+    ),));
+
     entries.to_vec()
 }
 
@@ -509,6 +511,7 @@ pub fn prepare_mesh_view_bind_groups(
         Option<&RenderViewLightProbes<EnvironmentMapLight>>,
         Option<&RenderViewLightProbes<IrradianceVolume>>,
         Has<OrderIndependentTransparencySettings>,
+        Option<&ScreenSpaceGlobalIlluminationTextures>,
     )>,
     (images, mut fallback_images, fallback_image, fallback_image_zero): (
         Res<RenderAssets<GpuImage>>,
@@ -521,7 +524,11 @@ pub fn prepare_mesh_view_bind_groups(
     light_probes_buffer: Res<LightProbesBuffer>,
     visibility_ranges: Res<RenderVisibilityRanges>,
     ssr_buffer: Res<ScreenSpaceReflectionsBuffer>,
-    oit_buffers: Res<OitBuffers>,
+    (oit_buffers, ies_profile_buffer, ray_tracing_scene): (
+        Res<OitBuffers>,
+        Res<IesProfileBuffer>,
+        Option<Res<RayTracingScene>>,
+    ),
 ) {
     if let (
         Some(view_binding),
@@ -556,6 +563,7 @@ pub fn prepare_mesh_view_bind_groups(
             render_view_environment_maps,
             render_view_irradiance_volumes,
             has_oit,
+            ssgi_textures,
         ) in &views
         {
             let fallback_ssao = fallback_images
@@ -695,6 +703,11 @@ pub fn prepare_mesh_view_bind_groups(
             entries =
                 entries.extend_with_indices(((29, transmission_view), (30, transmission_sampler)));
 
+            let ssgi_view = ssgi_textures
+                .map(|textures| &textures.irradiance.default_view)
+                .unwrap_or(&fallback_image_zero.texture_view);
+            entries = entries.extend_with_indices(((36, ssgi_view),));
+
             if has_oit {
                 if let (
                     Some(oit_layers_binding),
@@ -713,6 +726,18 @@ pub fn prepare_mesh_view_bind_groups(
                 }
             }
 
+            if let (GpuClusterableObjects::Storage(_), Some(ies_profiles_binding)) = (
+                &global_light_meta.gpu_clusterable_objects,
+                ies_profile_buffer.buffer().binding(),
+            ) {
+                entries = entries.extend_with_indices(((34, ies_profiles_binding),));
+            }
+
+            if let Some(ray_tracing_scene) = &ray_tracing_scene {
+                entries =
+                    entries.extend_with_indices(((35, ray_tracing_scene.tlas().as_binding()),));
+            }
+
             commands.entity(entity).insert(MeshViewBindGroup {
                 value: render_device.create_bind_group("mesh_view_bind_group", layout, &entries),
             });
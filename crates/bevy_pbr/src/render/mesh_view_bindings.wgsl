@@ -8,11 +8,8 @@
 
 @group(0) @binding(0) var<uniform> view: View;
 @group(0) @binding(1) var<uniform> lights: types::Lights;
-#ifdef NO_CUBE_ARRAY_TEXTURES_SUPPORT
-@group(0) @binding(2) var point_shadow_textures: texture_depth_cube;
-#else
-@group(0) @binding(2) var point_shadow_textures: texture_depth_cube_array;
-#endif
+// The shadow maps of all point lights, see `shadow_sampling::point_shadow_atlas_uv`.
+@group(0) @binding(2) var point_shadow_textures: texture_depth_2d;
 @group(0) @binding(3) var point_shadow_textures_comparison_sampler: sampler_comparison;
 #ifdef PCSS_SAMPLERS_AVAILABLE
 @group(0) @binding(4) var point_shadow_textures_linear_sampler: sampler;
@@ -107,7 +104,21 @@ const VISIBILITY_RANGE_UNIFORM_BUFFER_SIZE: u32 = 64u;
 @group(0) @binding(30) var view_transmission_sampler: sampler;
 
 #ifdef OIT_ENABLED
+#ifdef OIT_WEIGHTED_BLENDED
+@group(0) @binding(31) var<storage, read_write> oit_layers: array<atomic<u32>>;
+#else
 @group(0) @binding(31) var<storage, read_write> oit_layers: array<vec2<u32>>;
+#endif // OIT_WEIGHTED_BLENDED
 @group(0) @binding(32) var<storage, read_write> oit_layer_ids: array<atomic<i32>>;
 @group(0) @binding(33) var<uniform> oit_settings: types::OrderIndependentTransparencySettings;
 #endif // OIT_ENABLED
+
+#if AVAILABLE_STORAGE_BUFFER_BINDINGS >= 3
+@group(0) @binding(34) var<storage> ies_profiles: array<f32>;
+#endif
+
+#ifdef RAY_TRACED_SHADOWS
+@group(0) @binding(35) var ray_tracing_scene: acceleration_structure;
+#endif
+
+@group(0) @binding(36) var screen_space_global_illumination_texture: texture_2d<f32>;
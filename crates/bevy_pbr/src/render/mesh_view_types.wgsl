@@ -10,17 +10,25 @@ struct ClusterableObject {
     flags: u32,
     shadow_depth_bias: f32,
     shadow_normal_bias: f32,
+    // For point lights: the tile of the shadow map in the atlas, see
+    // `shadow_sampling::point_shadow_atlas_uv`.
     spot_light_tan_angle: f32,
     soft_shadow_size: f32,
     shadow_map_near_z: f32,
-    pad_a: f32,
-    pad_b: f32,
+    // The index of the light's IES profile, or `0xffffffffu` if it has none.
+    ies_profile: u32,
+    // The octahedral-encoded photometric axis of the IES profile.
+    ies_profile_axis: u32,
 };
 
 const POINT_LIGHT_FLAGS_SHADOWS_ENABLED_BIT: u32                    = 1u;
 const POINT_LIGHT_FLAGS_SPOT_LIGHT_Y_NEGATIVE: u32                  = 2u;
 const POINT_LIGHT_FLAGS_VOLUMETRIC_BIT: u32                         = 4u;
 const POINT_LIGHT_FLAGS_AFFECTS_LIGHTMAPPED_MESH_DIFFUSE_BIT: u32   = 8u;
+const POINT_LIGHT_FLAGS_RAY_TRACED_SHADOWS_BIT: u32                 = 16u;
+
+// Must match `IES_PROFILE_SAMPLE_COUNT` on the CPU.
+const IES_PROFILE_SAMPLE_COUNT: u32 = 64u;
 
 struct DirectionalCascade {
     clip_from_world: mat4x4<f32>,
@@ -46,6 +54,7 @@ struct DirectionalLight {
 const DIRECTIONAL_LIGHT_FLAGS_SHADOWS_ENABLED_BIT: u32                  = 1u;
 const DIRECTIONAL_LIGHT_FLAGS_VOLUMETRIC_BIT: u32                       = 2u;
 const DIRECTIONAL_LIGHT_FLAGS_AFFECTS_LIGHTMAPPED_MESH_DIFFUSE_BIT: u32 = 4u;
+const DIRECTIONAL_LIGHT_FLAGS_RAY_TRACED_SHADOWS_BIT: u32               = 8u;
 
 struct Lights {
     // NOTE: this array size must be kept in sync with the constants defined in bevy_pbr/src/render/light.rs
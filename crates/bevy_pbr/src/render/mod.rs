@@ -5,7 +5,13 @@ pub(crate) mod mesh;
 mod mesh_bindings;
 mod mesh_view_bindings;
 mod morph;
+mod occlusion_culling;
+#[cfg(feature = "gpu_preskinning")]
+mod preskinning;
+mod shadow_atlas;
 pub(crate) mod skin;
+#[cfg(feature = "gpu_skinning_precompute")]
+mod skin_precompute;
 
 pub use fog::*;
 pub use gpu_preprocess::*;
@@ -13,4 +19,19 @@ pub use light::*;
 pub use mesh::*;
 pub use mesh_bindings::MeshLayouts;
 pub use mesh_view_bindings::*;
-pub use skin::{extract_skins, prepare_skins, SkinIndices, SkinUniforms, MAX_JOINTS};
+pub use occlusion_culling::*;
+#[cfg(feature = "gpu_preskinning")]
+pub use preskinning::{
+    extract_preskinned_meshes, prepare_preskinned_meshes, preskin_meshes, PreskinnedMeshes,
+    PreskinningPipeline, PRESKINNED_VERTEX_SIZE, PRESKINNING_SHADER_HANDLE,
+};
+pub use shadow_atlas::*;
+pub use skin::{
+    extract_skins, max_joints_per_skin, prepare_skins, skins_use_gpu_precompute,
+    skins_use_preskinning, SkinIndices, SkinSettings, SkinUniforms, MAX_JOINTS,
+    MAX_STORAGE_BUFFER_JOINTS,
+};
+#[cfg(feature = "gpu_skinning_precompute")]
+pub use skin_precompute::{
+    precompute_skins, SkinPrecomputePipeline, SKIN_PRECOMPUTE_SHADER_HANDLE,
+};
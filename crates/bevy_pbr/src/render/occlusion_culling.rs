@@ -0,0 +1,707 @@
+//! Two-phase GPU occlusion culling.
+//!
+//! When a camera has the [`OcclusionCulling`] component, the GPU mesh
+//! preprocessing pass culls the meshes hidden behind other meshes, in addition
+//! to the meshes outside the view frustum. This happens in two phases:
+//!
+//! 1. Before the prepass, the meshes of the prepass phases are tested against
+//!    the *depth pyramid* built on the previous frame, at the position they had
+//!    on the previous frame. This approximates the set of meshes that were
+//!    visible on the previous frame, which the prepass then renders.
+//!
+//! 2. After the prepass, the depth pyramid is rebuilt from the prepass depth
+//!    buffer, and the meshes of the main pass phases are tested against it.
+//!    The meshes wrongly culled during the first phase, because they were
+//!    hidden on the previous frame but aren't anymore, are found visible again
+//!    at this point.
+//!
+//! The depth pyramid is a mipmapped texture in which each texel holds the
+//! farthest depth of the texels of the level below that it covers, so that a
+//! mesh can be conservatively tested against the depth buffer by reading at
+//! most four texels.
+
+use core::any::TypeId;
+
+use bevy_app::{App, Plugin, SubApp};
+use bevy_asset::{load_internal_asset, Handle};
+use bevy_core_pipeline::{
+    core_3d::{prepare_prepass_textures, AlphaMask3d, Opaque3d, Transmissive3d, Transparent3d},
+    prepass::{AlphaMask3dPrepass, DepthPrepass, Opaque3dPrepass, ViewPrepassTextures},
+};
+use bevy_derive::{Deref, DerefMut};
+use bevy_ecs::{prelude::*, query::QueryItem, system::lifetimeless::Read};
+use bevy_math::{Mat4, UVec2, UVec4, Vec2, Vec4};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::{
+    batching::gpu_preprocessing::IndirectParametersBuffers,
+    extract_component::{ExtractComponent, ExtractComponentPlugin},
+    render_graph::{NodeRunError, RenderGraphContext, ViewNode},
+    render_resource::{
+        binding_types::{
+            storage_buffer_read_only_sized, storage_buffer_sized, texture_2d, texture_depth_2d,
+            texture_depth_2d_multisampled, texture_storage_2d, uniform_buffer,
+        },
+        BindGroup, BindGroupEntries, BindGroupLayout, BindGroupLayoutEntries, Buffer,
+        BufferDescriptor, BufferUsages, CachedComputePipelineId, ComputePassDescriptor,
+        ComputePipelineDescriptor, DynamicUniformBuffer, Extent3d, PipelineCache, Shader,
+        ShaderStages, ShaderType, StorageTextureAccess, Texture, TextureDescriptor,
+        TextureDimension, TextureFormat, TextureSampleType, TextureUsages, TextureView,
+        TextureViewDescriptor,
+    },
+    renderer::{RenderContext, RenderDevice, RenderQueue},
+    view::{ExtractedView, NoIndirectDrawing},
+    Render, RenderSet,
+};
+
+/// The handle to the `downsample_depth.wgsl` compute shader.
+pub const DOWNSAMPLE_DEPTH_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(10923187623190831395);
+
+/// The workgroup size of the depth downsampling shader along each axis.
+const DOWNSAMPLE_DEPTH_WORKGROUP_SIZE: u32 = 8;
+
+/// Add this component to a 3D camera to cull the meshes hidden behind other
+/// meshes on the GPU.
+///
+/// Occlusion culling is most effective in scenes with a lot of meshes that
+/// occlude each other, such as interiors or cities. It requires GPU
+/// preprocessing and indirect drawing, so it has no effect on platforms that
+/// don't support compute shaders, or on cameras with [`NoIndirectDrawing`].
+///
+/// The meshes are culled in two phases: the meshes of the prepass are tested
+/// against the depth buffer of the previous frame, and the meshes of the main
+/// pass against the depth buffer of the prepass. Only the meshes that render to
+/// the depth prepass can occlude other meshes.
+///
+/// The meshes culled before the prepass because they were hidden on the
+/// previous frame are rendered by the main pass as soon as they become
+/// visible, but only show up in the prepass textures, such as the normals or
+/// the motion vectors, on the next frame.
+#[derive(Component, ExtractComponent, Reflect, Clone, Copy, Default, Debug)]
+#[reflect(Component, Default, Debug)]
+#[require(DepthPrepass)]
+pub struct OcclusionCulling;
+
+/// Adds support for [`OcclusionCulling`].
+///
+/// The render world resources and systems are only added by the
+/// [`super::GpuMeshPreprocessPlugin`], if GPU preprocessing is available.
+pub(crate) struct OcclusionCullingPlugin;
+
+impl Plugin for OcclusionCullingPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            DOWNSAMPLE_DEPTH_SHADER_HANDLE,
+            "downsample_depth.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.register_type::<OcclusionCulling>()
+            .add_plugins(ExtractComponentPlugin::<OcclusionCulling>::default());
+    }
+}
+
+/// Adds the render world resources and systems that occlusion culling needs.
+pub(crate) fn add_occlusion_culling_render_systems(render_app: &mut SubApp) {
+    render_app
+        .init_resource::<OcclusionCullingPipelines>()
+        .init_resource::<OcclusionCullingUniforms>()
+        .add_systems(
+            Render,
+            (
+                prepare_view_depth_pyramids
+                    .in_set(RenderSet::PrepareResources)
+                    .after(prepare_prepass_textures),
+                prepare_occlusion_culling_bind_groups.in_set(RenderSet::PrepareBindGroups),
+            ),
+        );
+}
+
+/// Returns true if the meshes of the render phase with the given type are
+/// tested against the depth pyramid of the previous frame, before the prepass.
+pub(crate) fn is_early_occlusion_culling_phase(phase_type_id: TypeId) -> bool {
+    phase_type_id == TypeId::of::<Opaque3dPrepass>()
+        || phase_type_id == TypeId::of::<AlphaMask3dPrepass>()
+}
+
+/// Returns true if the meshes of the render phase with the given type are
+/// tested against the depth pyramid of the current frame, after the prepass.
+pub(crate) fn is_late_occlusion_culling_phase(phase_type_id: TypeId) -> bool {
+    phase_type_id == TypeId::of::<Opaque3d>()
+        || phase_type_id == TypeId::of::<AlphaMask3d>()
+        || phase_type_id == TypeId::of::<Transmissive3d>()
+        || phase_type_id == TypeId::of::<Transparent3d>()
+}
+
+/// The depth pyramid of a view that uses [`OcclusionCulling`].
+///
+/// This is kept from one frame to the next, so that the meshes can be tested
+/// against the depth pyramid of the previous frame before the prepass.
+#[derive(Component)]
+pub struct ViewDepthPyramid {
+    /// The depth pyramid texture.
+    pub texture: Texture,
+    /// A view of all the mip levels of [`Self::texture`].
+    pub all_mips: TextureView,
+    /// A view of each mip level of [`Self::texture`].
+    pub mips: Vec<TextureView>,
+    /// The size of the depth buffer the pyramid was built from.
+    depth_size: UVec2,
+    /// The view projection matrix the pyramid was last built with.
+    clip_from_world: Mat4,
+    /// The viewport the pyramid was last built with.
+    viewport: UVec4,
+}
+
+/// The per-view data that the mesh preprocessing shader needs to test meshes
+/// against the depth pyramid.
+#[derive(Clone, Copy, ShaderType)]
+pub struct OcclusionCullingUniform {
+    /// The view projection matrix of the previous frame, which the depth
+    /// pyramid was built with when the first phase runs.
+    pub previous_clip_from_world: Mat4,
+    /// The viewport of the previous frame, in pixels.
+    pub previous_viewport: Vec4,
+    /// The size of the depth buffer, in pixels.
+    pub depth_size: Vec2,
+}
+
+/// The GPU buffer that stores the [`OcclusionCullingUniform`] of each view.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct OcclusionCullingUniforms(pub DynamicUniformBuffer<OcclusionCullingUniform>);
+
+/// The offset of the [`OcclusionCullingUniform`] of a view within the
+/// [`OcclusionCullingUniforms`].
+#[derive(Component, Clone, Copy, Deref, DerefMut)]
+pub struct ViewOcclusionCullingUniformOffset(pub u32);
+
+/// The bind groups that a view using [`OcclusionCulling`] needs this frame.
+#[derive(Component)]
+pub struct ViewOcclusionCullingBindGroups {
+    /// The bind groups that downsample each mip level of the depth pyramid,
+    /// starting with the one that reads the depth buffer.
+    pub downsample_depth: Vec<BindGroup>,
+    /// Whether the depth buffer is multisampled.
+    pub multisampled: bool,
+    /// The second bind group of the mesh preprocessing shader for indexed
+    /// meshes.
+    pub preprocess_indexed: BindGroup,
+    /// The second bind group of the mesh preprocessing shader for non-indexed
+    /// meshes.
+    pub preprocess_non_indexed: BindGroup,
+    /// The second bind group of the late indirect parameters building shader
+    /// for indexed meshes.
+    pub late_build_indexed: BindGroup,
+    /// The second bind group of the late indirect parameters building shader
+    /// for non-indexed meshes.
+    pub late_build_non_indexed: BindGroup,
+}
+
+/// The buffers in which the late mesh preprocessing pass of a view records the
+/// batches it found visible meshes in, kept from one frame to the next.
+#[derive(Component)]
+pub struct ViewLateBatchesBuffers {
+    /// The buffer for indexed meshes.
+    pub indexed: LateBatchesBuffer,
+    /// The buffer for non-indexed meshes.
+    pub non_indexed: LateBatchesBuffer,
+}
+
+/// A buffer in which the late mesh preprocessing pass records the batches it
+/// found visible meshes in: a count followed by the index of each batch.
+pub struct LateBatchesBuffer {
+    /// The buffer.
+    pub buffer: Buffer,
+    /// The number of batch indices the buffer can hold.
+    capacity: usize,
+}
+
+/// The bind group layouts and pipelines used to build the depth pyramid, and
+/// to test meshes against it.
+#[derive(Resource)]
+pub struct OcclusionCullingPipelines {
+    /// The layout of the second bind group of the mesh preprocessing shader
+    /// when occlusion culling is in use.
+    pub preprocess_bind_group_layout: BindGroupLayout,
+    /// The layout of the second bind group of the late indirect parameters
+    /// building shader.
+    pub late_build_bind_group_layout: BindGroupLayout,
+    /// The layout used to build the first mip level from a depth buffer.
+    pub downsample_first_bind_group_layout: BindGroupLayout,
+    /// The layout used to build the first mip level from a multisampled depth
+    /// buffer.
+    pub downsample_first_multisampled_bind_group_layout: BindGroupLayout,
+    /// The layout used to build each of the other mip levels.
+    pub downsample_bind_group_layout: BindGroupLayout,
+    /// The pipeline that builds the first mip level from a depth buffer.
+    pub downsample_first: CachedComputePipelineId,
+    /// The pipeline that builds the first mip level from a multisampled depth
+    /// buffer.
+    pub downsample_first_multisampled: CachedComputePipelineId,
+    /// The pipeline that builds each of the other mip levels.
+    pub downsample: CachedComputePipelineId,
+}
+
+impl FromWorld for OcclusionCullingPipelines {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let preprocess_bind_group_layout = render_device.create_bind_group_layout(
+            "occlusion culling preprocess bind group layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    // `depth_pyramid`
+                    texture_2d(TextureSampleType::Float { filterable: false }),
+                    // `occlusion_culling_view`
+                    uniform_buffer::<OcclusionCullingUniform>(/* has_dynamic_offset= */ true),
+                    // `late_batches`
+                    storage_buffer_sized(false, None),
+                ),
+            ),
+        );
+        let late_build_bind_group_layout = render_device.create_bind_group_layout(
+            "late build indirect parameters bind group layout",
+            &BindGroupLayoutEntries::single(
+                ShaderStages::COMPUTE,
+                // `late_batches`
+                storage_buffer_read_only_sized(false, None),
+            ),
+        );
+
+        let destination =
+            texture_storage_2d(TextureFormat::R32Float, StorageTextureAccess::WriteOnly);
+        let downsample_first_bind_group_layout = render_device.create_bind_group_layout(
+            "downsample depth first bind group layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (texture_depth_2d(), destination),
+            ),
+        );
+        let downsample_first_multisampled_bind_group_layout = render_device
+            .create_bind_group_layout(
+                "downsample depth first multisampled bind group layout",
+                &BindGroupLayoutEntries::sequential(
+                    ShaderStages::COMPUTE,
+                    (texture_depth_2d_multisampled(), destination),
+                ),
+            );
+        let downsample_bind_group_layout = render_device.create_bind_group_layout(
+            "downsample depth bind group layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    texture_2d(TextureSampleType::Float { filterable: false }),
+                    destination,
+                ),
+            ),
+        );
+
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let mut queue_downsample_pipeline =
+            |label: &'static str, layout: &BindGroupLayout, shader_defs: &[&str]| {
+                pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+                    label: Some(label.into()),
+                    layout: vec![layout.clone()],
+                    push_constant_ranges: vec![],
+                    shader: DOWNSAMPLE_DEPTH_SHADER_HANDLE,
+                    shader_defs: shader_defs.iter().map(|&def| def.into()).collect(),
+                    entry_point: "downsample_depth".into(),
+                    zero_initialize_workgroup_memory: false,
+                })
+            };
+        let downsample_first = queue_downsample_pipeline(
+            "downsample depth first",
+            &downsample_first_bind_group_layout,
+            &["FIRST_MIP"],
+        );
+        let downsample_first_multisampled = queue_downsample_pipeline(
+            "downsample depth first multisampled",
+            &downsample_first_multisampled_bind_group_layout,
+            &["FIRST_MIP", "MULTISAMPLED"],
+        );
+        let downsample =
+            queue_downsample_pipeline("downsample depth", &downsample_bind_group_layout, &[]);
+
+        Self {
+            preprocess_bind_group_layout,
+            late_build_bind_group_layout,
+            downsample_first_bind_group_layout,
+            downsample_first_multisampled_bind_group_layout,
+            downsample_bind_group_layout,
+            downsample_first,
+            downsample_first_multisampled,
+            downsample,
+        }
+    }
+}
+
+impl ViewDepthPyramid {
+    /// Creates the depth pyramid of a depth buffer of the given size.
+    ///
+    /// The first mip level is half the size of the depth buffer, rounded down.
+    /// Newly created textures are zeroed, which is the far plane with reversed
+    /// Z, so nothing is culled until the pyramid is first built.
+    fn new(render_device: &RenderDevice, depth_size: UVec2, view: &ExtractedView) -> Self {
+        let size = Extent3d {
+            width: (depth_size.x / 2).max(1),
+            height: (depth_size.y / 2).max(1),
+            depth_or_array_layers: 1,
+        };
+        let mip_level_count = size.max_mips(TextureDimension::D2);
+        let texture = render_device.create_texture(&TextureDescriptor {
+            label: Some("depth_pyramid"),
+            size,
+            mip_level_count,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::R32Float,
+            usage: TextureUsages::STORAGE_BINDING | TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let all_mips = texture.create_view(&TextureViewDescriptor {
+            label: Some("depth_pyramid_all_mips"),
+            ..Default::default()
+        });
+        let mips = (0..mip_level_count)
+            .map(|mip_level| {
+                texture.create_view(&TextureViewDescriptor {
+                    label: Some("depth_pyramid_mip"),
+                    base_mip_level: mip_level,
+                    mip_level_count: Some(1),
+                    ..Default::default()
+                })
+            })
+            .collect();
+
+        Self {
+            texture,
+            all_mips,
+            mips,
+            depth_size,
+            clip_from_world: clip_from_world(view),
+            viewport: view.viewport,
+        }
+    }
+}
+
+fn clip_from_world(view: &ExtractedView) -> Mat4 {
+    view.clip_from_world
+        .unwrap_or_else(|| view.clip_from_view * view.world_from_view.compute_matrix().inverse())
+}
+
+/// Creates the depth pyramids of the views that use [`OcclusionCulling`], and
+/// writes their [`OcclusionCullingUniform`]s.
+pub fn prepare_view_depth_pyramids(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    mut uniforms: ResMut<OcclusionCullingUniforms>,
+    mut views: Query<(
+        Entity,
+        &ExtractedView,
+        Option<&ViewPrepassTextures>,
+        Option<&mut ViewDepthPyramid>,
+        Has<OcclusionCulling>,
+        Has<NoIndirectDrawing>,
+    )>,
+) {
+    let Some(mut writer) = uniforms.get_writer(views.iter().len(), &render_device, &render_queue)
+    else {
+        return;
+    };
+
+    for (view_entity, view, prepass_textures, depth_pyramid, occlusion_culling, no_indirect) in
+        &mut views
+    {
+        let depth_size = prepass_textures
+            .filter(|prepass_textures| prepass_textures.depth.is_some())
+            .map(|prepass_textures| {
+                UVec2::new(prepass_textures.size.width, prepass_textures.size.height)
+            });
+        let (Some(depth_size), true, false) = (depth_size, occlusion_culling, no_indirect) else {
+            if depth_pyramid.is_some() {
+                commands.entity(view_entity).remove::<(
+                    ViewDepthPyramid,
+                    ViewOcclusionCullingUniformOffset,
+                    ViewOcclusionCullingBindGroups,
+                    ViewLateBatchesBuffers,
+                )>();
+            }
+            continue;
+        };
+
+        let uniform_offset = match depth_pyramid {
+            Some(mut depth_pyramid) if depth_pyramid.depth_size == depth_size => {
+                let offset = writer.write(&OcclusionCullingUniform {
+                    previous_clip_from_world: depth_pyramid.clip_from_world,
+                    previous_viewport: depth_pyramid.viewport.as_vec4(),
+                    depth_size: depth_size.as_vec2(),
+                });
+                depth_pyramid.clip_from_world = clip_from_world(view);
+                depth_pyramid.viewport = view.viewport;
+                offset
+            }
+            _ => {
+                let depth_pyramid = ViewDepthPyramid::new(&render_device, depth_size, view);
+                let offset = writer.write(&OcclusionCullingUniform {
+                    previous_clip_from_world: depth_pyramid.clip_from_world,
+                    previous_viewport: depth_pyramid.viewport.as_vec4(),
+                    depth_size: depth_size.as_vec2(),
+                });
+                commands.entity(view_entity).insert(depth_pyramid);
+                offset
+            }
+        };
+
+        commands
+            .entity(view_entity)
+            .insert(ViewOcclusionCullingUniformOffset(uniform_offset));
+    }
+}
+
+/// Creates the bind groups that the views using [`OcclusionCulling`] need
+/// this frame.
+pub fn prepare_occlusion_culling_bind_groups(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    pipelines: Res<OcclusionCullingPipelines>,
+    uniforms: Res<OcclusionCullingUniforms>,
+    render_queue: Res<RenderQueue>,
+    indirect_parameters_buffers: Res<IndirectParametersBuffers>,
+    mut views: Query<(
+        Entity,
+        &ViewDepthPyramid,
+        &ViewPrepassTextures,
+        Option<&mut ViewLateBatchesBuffers>,
+    )>,
+) {
+    let Some(uniforms_binding) = uniforms.binding() else {
+        return;
+    };
+
+    for (view_entity, depth_pyramid, prepass_textures, late_batches) in &mut views {
+        let Some(depth) = &prepass_textures.depth else {
+            continue;
+        };
+
+        let multisampled = depth.texture.texture.sample_count() > 1;
+        let first_layout = if multisampled {
+            &pipelines.downsample_first_multisampled_bind_group_layout
+        } else {
+            &pipelines.downsample_first_bind_group_layout
+        };
+        let mut downsample_depth = vec![render_device.create_bind_group(
+            "downsample_depth_first_bind_group",
+            first_layout,
+            &BindGroupEntries::sequential((&depth.texture.default_view, &depth_pyramid.mips[0])),
+        )];
+        downsample_depth.extend(depth_pyramid.mips.windows(2).map(|mips| {
+            render_device.create_bind_group(
+                "downsample_depth_bind_group",
+                &pipelines.downsample_bind_group_layout,
+                &BindGroupEntries::sequential((&mips[0], &mips[1])),
+            )
+        }));
+
+        let indexed_batch_count = indirect_parameters_buffers.indexed_batch_count();
+        let non_indexed_batch_count = indirect_parameters_buffers.non_indexed_batch_count();
+        let mut new_late_batches = None;
+        let late_batches = match late_batches {
+            Some(late_batches) => late_batches.into_inner(),
+            None => new_late_batches.insert(ViewLateBatchesBuffers {
+                indexed: LateBatchesBuffer::new(&render_device, indexed_batch_count),
+                non_indexed: LateBatchesBuffer::new(&render_device, non_indexed_batch_count),
+            }),
+        };
+        late_batches
+            .indexed
+            .prepare(&render_device, &render_queue, indexed_batch_count);
+        late_batches
+            .non_indexed
+            .prepare(&render_device, &render_queue, non_indexed_batch_count);
+        let late_indexed_batches = &late_batches.indexed.buffer;
+        let late_non_indexed_batches = &late_batches.non_indexed.buffer;
+        let create_preprocess_bind_group = |late_batches: &Buffer| {
+            render_device.create_bind_group(
+                "occlusion_culling_preprocess_bind_group",
+                &pipelines.preprocess_bind_group_layout,
+                &BindGroupEntries::sequential((
+                    &depth_pyramid.all_mips,
+                    uniforms_binding.clone(),
+                    late_batches.as_entire_binding(),
+                )),
+            )
+        };
+        let create_late_build_bind_group = |late_batches: &Buffer| {
+            render_device.create_bind_group(
+                "late_build_indirect_parameters_bind_group",
+                &pipelines.late_build_bind_group_layout,
+                &BindGroupEntries::single(late_batches.as_entire_binding()),
+            )
+        };
+
+        let bind_groups = ViewOcclusionCullingBindGroups {
+            downsample_depth,
+            multisampled,
+            preprocess_indexed: create_preprocess_bind_group(late_indexed_batches),
+            preprocess_non_indexed: create_preprocess_bind_group(late_non_indexed_batches),
+            late_build_indexed: create_late_build_bind_group(late_indexed_batches),
+            late_build_non_indexed: create_late_build_bind_group(late_non_indexed_batches),
+        };
+        let mut view_commands = commands.entity(view_entity);
+        view_commands.insert(bind_groups);
+        if let Some(late_batches) = new_late_batches {
+            view_commands.insert(late_batches);
+        }
+    }
+}
+
+impl LateBatchesBuffer {
+    fn new(render_device: &RenderDevice, batch_count: usize) -> Self {
+        let capacity = late_batches_capacity(batch_count);
+        Self {
+            buffer: render_device.create_buffer(&BufferDescriptor {
+                label: Some("late_batches"),
+                size: (1 + capacity as u64) * size_of::<u32>() as u64,
+                usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }),
+            capacity,
+        }
+    }
+
+    /// Grows the buffer if it can't hold `batch_count` batches, and resets its
+    /// count to zero for this frame.
+    fn prepare(
+        &mut self,
+        render_device: &RenderDevice,
+        render_queue: &RenderQueue,
+        batch_count: usize,
+    ) {
+        if batch_count > self.capacity {
+            // Newly created buffers are zeroed, so the count starts at zero.
+            *self = Self::new(render_device, batch_count);
+        } else {
+            render_queue.write_buffer(&self.buffer, 0, &0u32.to_le_bytes());
+        }
+    }
+}
+
+/// Returns the number of batch indices a late batches buffer holding
+/// `batch_count` batches is allocated for.
+///
+/// The capacity is rounded up to a power of two, so that the buffer isn't
+/// reallocated every time a batch is added.
+fn late_batches_capacity(batch_count: usize) -> usize {
+    batch_count.max(1).next_power_of_two()
+}
+
+/// The render node that builds the depth pyramid of a view from its prepass
+/// depth buffer, between the two occlusion culling phases.
+#[derive(Default)]
+pub struct DownsampleDepthNode;
+
+impl ViewNode for DownsampleDepthNode {
+    type ViewQuery = (Read<ViewDepthPyramid>, Read<ViewOcclusionCullingBindGroups>);
+
+    fn run<'w>(
+        &self,
+        _: &mut RenderGraphContext,
+        render_context: &mut RenderContext<'w>,
+        (depth_pyramid, bind_groups): QueryItem<'w, Self::ViewQuery>,
+        world: &'w World,
+    ) -> Result<(), NodeRunError> {
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let pipelines = world.resource::<OcclusionCullingPipelines>();
+
+        let first_pipeline_id = if bind_groups.multisampled {
+            pipelines.downsample_first_multisampled
+        } else {
+            pipelines.downsample_first
+        };
+        let (Some(first_pipeline), Some(pipeline)) = (
+            pipeline_cache.get_compute_pipeline(first_pipeline_id),
+            pipeline_cache.get_compute_pipeline(pipelines.downsample),
+        ) else {
+            return Ok(());
+        };
+
+        let mut compute_pass =
+            render_context
+                .command_encoder()
+                .begin_compute_pass(&ComputePassDescriptor {
+                    label: Some("downsample depth"),
+                    timestamp_writes: None,
+                });
+
+        let size = depth_pyramid.texture.size();
+        for (mip_level, bind_group) in bind_groups.downsample_depth.iter().enumerate() {
+            compute_pass.set_pipeline(if mip_level == 0 {
+                first_pipeline
+            } else {
+                pipeline
+            });
+            compute_pass.set_bind_group(0, bind_group, &[]);
+            let mip_size = size.mip_level_size(mip_level as u32, TextureDimension::D2);
+            compute_pass.dispatch_workgroups(
+                mip_size.width.div_ceil(DOWNSAMPLE_DEPTH_WORKGROUP_SIZE),
+                mip_size.height.div_ceil(DOWNSAMPLE_DEPTH_WORKGROUP_SIZE),
+                1,
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::any::TypeId;
+
+    use bevy_core_pipeline::{
+        core_3d::{AlphaMask3d, Opaque3d, Transmissive3d, Transparent3d},
+        prepass::{AlphaMask3dPrepass, Opaque3dPrepass},
+    };
+
+    use super::{
+        is_early_occlusion_culling_phase, is_late_occlusion_culling_phase, late_batches_capacity,
+    };
+
+    #[test]
+    fn occlusion_culling_phases() {
+        for phase in [
+            TypeId::of::<Opaque3dPrepass>(),
+            TypeId::of::<AlphaMask3dPrepass>(),
+        ] {
+            assert!(is_early_occlusion_culling_phase(phase));
+            assert!(!is_late_occlusion_culling_phase(phase));
+        }
+        for phase in [
+            TypeId::of::<Opaque3d>(),
+            TypeId::of::<AlphaMask3d>(),
+            TypeId::of::<Transmissive3d>(),
+            TypeId::of::<Transparent3d>(),
+        ] {
+            assert!(!is_early_occlusion_culling_phase(phase));
+            assert!(is_late_occlusion_culling_phase(phase));
+        }
+    }
+
+    #[test]
+    fn late_batches_buffer_growth() {
+        assert_eq!(late_batches_capacity(0), 1);
+        assert_eq!(late_batches_capacity(1), 1);
+        assert_eq!(late_batches_capacity(5), 8);
+        assert_eq!(late_batches_capacity(8), 8);
+        // The buffer only grows once in a while as batches are added.
+        let reallocations = (1..=1000)
+            .map(late_batches_capacity)
+            .collect::<Vec<_>>()
+            .windows(2)
+            .filter(|capacities| capacities[0] != capacities[1])
+            .count();
+        assert_eq!(reallocations, 10);
+    }
+}
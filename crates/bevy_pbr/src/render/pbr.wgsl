@@ -26,8 +26,12 @@
 #import bevy_core_pipeline::oit::oit_draw
 #endif // OIT_ENABLED
 
+#ifdef DEBUG_VIEW
+#import bevy_pbr::debug_view::debug_view_color
+#endif // DEBUG_VIEW
+
 #ifdef FORWARD_DECAL
-#import bevy_pbr::decal::forward::get_forward_decal_info
+#import bevy_pbr::decal::forward::{apply_forward_decal_alpha, get_forward_decal_info}
 #endif
 
 @fragment
@@ -64,6 +68,11 @@ fn fragment(
     // alpha discard
     pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
 
+#ifdef FORWARD_DECAL_BLEND_MULTIPLY
+    // Multiply decals tint the lit surface underneath them, so they aren't lit themselves.
+    pbr_input.material.flags |= pbr_types::STANDARD_MATERIAL_FLAGS_UNLIT_BIT;
+#endif
+
 #ifdef PREPASS_PIPELINE
     // write the gbuffer, lighting pass id, and optionally normal and motion_vector textures
     let out = deferred_output(in, pbr_input);
@@ -77,9 +86,16 @@ fn fragment(
         out.color = pbr_input.material.base_color;
     }
 
+#ifdef DEBUG_VIEW
+    // Show the debug view of the camera instead of the lit color. The post
+    // processing is skipped, so that fog and tonemapping don't change the
+    // values that the view shows.
+    out.color = debug_view_color(in, pbr_input, out.color);
+#else
     // apply in-shader post processing (fog, alpha-premultiply, and also tonemapping, debanding if the camera is non-hdr)
     // note this does not include fullscreen postprocessing effects like bloom.
     out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+#endif // DEBUG_VIEW
 #endif
 
 #ifdef OIT_ENABLED
@@ -92,7 +108,7 @@ fn fragment(
 #endif // OIT_ENABLED
 
 #ifdef FORWARD_DECAL
-        out.color.a = min(forward_decal_info.alpha, out.color.a);
+        out.color = apply_forward_decal_alpha(out.color, forward_decal_info.alpha);
 #endif
 
         return out;
@@ -77,3 +77,13 @@
 @group(2) @binding(26) var clearcoat_normal_sampler: sampler;
 #endif  // BINDLESS
 #endif  // PBR_MULTI_LAYER_MATERIAL_TEXTURES_SUPPORTED
+
+#ifdef PBR_PLANAR_REFLECTIONS_SUPPORTED
+#ifdef BINDLESS
+@group(2) @binding(27) var planar_reflection_texture: binding_array<texture_2d<f32>, 16>;
+@group(2) @binding(28) var planar_reflection_sampler: binding_array<sampler, 16>;
+#else   // BINDLESS
+@group(2) @binding(27) var planar_reflection_texture: texture_2d<f32>;
+@group(2) @binding(28) var planar_reflection_sampler: sampler;
+#endif  // BINDLESS
+#endif  // PBR_PLANAR_REFLECTIONS_SUPPORTED
@@ -744,6 +744,32 @@ fn pbr_input_from_standard_material(
 
         pbr_input.lightmap_light = lightmap(in.uv_b, lightmap_exposure, in.instance_index);
 #endif
+
+        // planar reflection
+#ifdef PBR_PLANAR_REFLECTIONS_SUPPORTED
+#ifndef PREPASS_PIPELINE
+        if ((flags & pbr_types::STANDARD_MATERIAL_FLAGS_PLANAR_REFLECTION_TEXTURE_BIT) != 0u) {
+            // The reflection is rendered as a horizontally flipped image of the
+            // view, so it's sampled at the flipped screen position.
+            var reflection_uv = (pbr_input.frag_coord.xy - view.viewport.xy) / view.viewport.zw;
+            reflection_uv.x = 1.0 - reflection_uv.x;
+            let reflection = textureSampleLevel(
+#ifdef BINDLESS
+                pbr_bindings::planar_reflection_texture[slot],
+                pbr_bindings::planar_reflection_sampler[slot],
+#else   // BINDLESS
+                pbr_bindings::planar_reflection_texture,
+                pbr_bindings::planar_reflection_sampler,
+#endif  // BINDLESS
+                reflection_uv,
+                0.0
+            ).rgb;
+            // The reflection was exposed by its camera, and the lighting is
+            // exposed again.
+            pbr_input.planar_reflection = vec4(reflection / view.exposure, 1.0);
+        }
+#endif  // PREPASS_PIPELINE
+#endif  // PBR_PLANAR_REFLECTIONS_SUPPORTED
     }
 
     return pbr_input;
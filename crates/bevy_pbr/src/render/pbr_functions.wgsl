@@ -16,6 +16,10 @@
 }
 #import bevy_render::maths::{E, powsafe}
 
+#ifdef PBR_PLANAR_REFLECTIONS_SUPPORTED
+#import bevy_pbr::planar_reflection
+#endif  // PBR_PLANAR_REFLECTIONS_SUPPORTED
+
 #ifdef MESHLET_MESH_MATERIAL_PASS
 #import bevy_pbr::meshlet_visibility_buffer_resolve::VertexOutput
 #else ifdef PREPASS_PIPELINE
@@ -418,7 +422,7 @@ fn apply_pbr_lighting(
 
         var shadow: f32 = 1.0;
         if ((in.flags & MESH_FLAGS_SHADOW_RECEIVER_BIT) != 0u
-                && (view_bindings::clusterable_objects.data[light_id].flags & mesh_view_types::POINT_LIGHT_FLAGS_SHADOWS_ENABLED_BIT) != 0u) {
+                && (view_bindings::clusterable_objects.data[light_id].flags & (mesh_view_types::POINT_LIGHT_FLAGS_SHADOWS_ENABLED_BIT | mesh_view_types::POINT_LIGHT_FLAGS_RAY_TRACED_SHADOWS_BIT)) != 0u) {
             shadow = shadows::fetch_point_shadow(light_id, in.world_position, in.world_normal);
         }
 
@@ -437,7 +441,7 @@ fn apply_pbr_lighting(
         // F0 = vec3<f32>(0.0)
         var transmitted_shadow: f32 = 1.0;
         if ((in.flags & (MESH_FLAGS_SHADOW_RECEIVER_BIT | MESH_FLAGS_TRANSMITTED_SHADOW_RECEIVER_BIT)) == (MESH_FLAGS_SHADOW_RECEIVER_BIT | MESH_FLAGS_TRANSMITTED_SHADOW_RECEIVER_BIT)
-                && (view_bindings::clusterable_objects.data[light_id].flags & mesh_view_types::POINT_LIGHT_FLAGS_SHADOWS_ENABLED_BIT) != 0u) {
+                && (view_bindings::clusterable_objects.data[light_id].flags & (mesh_view_types::POINT_LIGHT_FLAGS_SHADOWS_ENABLED_BIT | mesh_view_types::POINT_LIGHT_FLAGS_RAY_TRACED_SHADOWS_BIT)) != 0u) {
             transmitted_shadow = shadows::fetch_point_shadow(light_id, diffuse_transmissive_lobe_world_position, -in.world_normal);
         }
 
@@ -466,7 +470,7 @@ fn apply_pbr_lighting(
         var shadow: f32 = 1.0;
         if ((in.flags & MESH_FLAGS_SHADOW_RECEIVER_BIT) != 0u
                 && (view_bindings::clusterable_objects.data[light_id].flags &
-                    mesh_view_types::POINT_LIGHT_FLAGS_SHADOWS_ENABLED_BIT) != 0u) {
+                    (mesh_view_types::POINT_LIGHT_FLAGS_SHADOWS_ENABLED_BIT | mesh_view_types::POINT_LIGHT_FLAGS_RAY_TRACED_SHADOWS_BIT)) != 0u) {
             shadow = shadows::fetch_spot_shadow(
                 light_id,
                 in.world_position,
@@ -490,7 +494,7 @@ fn apply_pbr_lighting(
         // F0 = vec3<f32>(0.0)
         var transmitted_shadow: f32 = 1.0;
         if ((in.flags & (MESH_FLAGS_SHADOW_RECEIVER_BIT | MESH_FLAGS_TRANSMITTED_SHADOW_RECEIVER_BIT)) == (MESH_FLAGS_SHADOW_RECEIVER_BIT | MESH_FLAGS_TRANSMITTED_SHADOW_RECEIVER_BIT)
-                && (view_bindings::clusterable_objects.data[light_id].flags & mesh_view_types::POINT_LIGHT_FLAGS_SHADOWS_ENABLED_BIT) != 0u) {
+                && (view_bindings::clusterable_objects.data[light_id].flags & (mesh_view_types::POINT_LIGHT_FLAGS_SHADOWS_ENABLED_BIT | mesh_view_types::POINT_LIGHT_FLAGS_RAY_TRACED_SHADOWS_BIT)) != 0u) {
             transmitted_shadow = shadows::fetch_spot_shadow(
                 light_id,
                 diffuse_transmissive_lobe_world_position,
@@ -528,7 +532,7 @@ fn apply_pbr_lighting(
 
         var shadow: f32 = 1.0;
         if ((in.flags & MESH_FLAGS_SHADOW_RECEIVER_BIT) != 0u
-                && (view_bindings::lights.directional_lights[i].flags & mesh_view_types::DIRECTIONAL_LIGHT_FLAGS_SHADOWS_ENABLED_BIT) != 0u) {
+                && (view_bindings::lights.directional_lights[i].flags & (mesh_view_types::DIRECTIONAL_LIGHT_FLAGS_SHADOWS_ENABLED_BIT | mesh_view_types::DIRECTIONAL_LIGHT_FLAGS_RAY_TRACED_SHADOWS_BIT)) != 0u) {
             shadow = shadows::fetch_directional_shadow(i, in.world_position, in.world_normal, view_z);
         }
 
@@ -551,7 +555,7 @@ fn apply_pbr_lighting(
         // F0 = vec3<f32>(0.0)
         var transmitted_shadow: f32 = 1.0;
         if ((in.flags & (MESH_FLAGS_SHADOW_RECEIVER_BIT | MESH_FLAGS_TRANSMITTED_SHADOW_RECEIVER_BIT)) == (MESH_FLAGS_SHADOW_RECEIVER_BIT | MESH_FLAGS_TRANSMITTED_SHADOW_RECEIVER_BIT)
-                && (view_bindings::lights.directional_lights[i].flags & mesh_view_types::DIRECTIONAL_LIGHT_FLAGS_SHADOWS_ENABLED_BIT) != 0u) {
+                && (view_bindings::lights.directional_lights[i].flags & (mesh_view_types::DIRECTIONAL_LIGHT_FLAGS_SHADOWS_ENABLED_BIT | mesh_view_types::DIRECTIONAL_LIGHT_FLAGS_RAY_TRACED_SHADOWS_BIT)) != 0u) {
             transmitted_shadow = shadows::fetch_directional_shadow(i, diffuse_transmissive_lobe_world_position, -in.world_normal, view_z);
         }
 
@@ -638,12 +642,35 @@ fn apply_pbr_lighting(
             found_diffuse_indirect
         );
 
+        // Planar reflections replace the specular light from the environment
+        // map, which would otherwise be reflected twice.
         indirect_light += environment_light.diffuse * diffuse_occlusion +
-            environment_light.specular * specular_occlusion;
+            environment_light.specular * specular_occlusion * (1.0 - in.planar_reflection.a);
     }
 
 #endif  // ENVIRONMENT_MAP
 
+#ifdef PBR_PLANAR_REFLECTIONS_SUPPORTED
+    // Planar reflection light (indirect)
+    if (in.planar_reflection.a > 0.0) {
+        indirect_light += planar_reflection::planar_reflection_light(
+            &lighting_input,
+            in.planar_reflection.rgb,
+        ) * in.planar_reflection.a * specular_occlusion;
+    }
+#endif  // PBR_PLANAR_REFLECTIONS_SUPPORTED
+
+#ifdef SCREEN_SPACE_GLOBAL_ILLUMINATION
+    // Screen space global illumination (indirect), gathered before the main
+    // pass by the SSGI shader.
+    let screen_space_global_illumination = textureLoad(
+        view_bindings::screen_space_global_illumination_texture,
+        vec2<i32>(in.frag_coord.xy),
+        0
+    ).rgb;
+    indirect_light += screen_space_global_illumination * diffuse_color * diffuse_occlusion;
+#endif  // SCREEN_SPACE_GLOBAL_ILLUMINATION
+
     // Ambient light (indirect)
     indirect_light += ambient::ambient_light(in.world_position, in.N, in.V, NdotV, diffuse_color, F0, perceptual_roughness, diffuse_occlusion);
 
@@ -1,8 +1,9 @@
 #define_import_path bevy_pbr::lighting
 
 #import bevy_pbr::{
-    mesh_view_types::POINT_LIGHT_FLAGS_SPOT_LIGHT_Y_NEGATIVE,
+    mesh_view_types::{POINT_LIGHT_FLAGS_SPOT_LIGHT_Y_NEGATIVE, IES_PROFILE_SAMPLE_COUNT},
     mesh_view_bindings as view_bindings,
+    utils::octahedral_decode_signed,
 }
 #import bevy_render::maths::PI
 
@@ -440,6 +441,32 @@ fn perceptualRoughnessToRoughness(perceptualRoughness: f32) -> f32 {
     return clampedPerceptualRoughness * clampedPerceptualRoughness;
 }
 
+// Returns how much the IES profile of the given light, if any, scales the
+// light that it emits towards `light_to_frag_dir`.
+fn ies_profile_attenuation(light_id: u32, light_to_frag_dir: vec3<f32>) -> f32 {
+#if AVAILABLE_STORAGE_BUFFER_BINDINGS >= 3
+    let light = &view_bindings::clusterable_objects.data[light_id];
+    let profile = (*light).ies_profile;
+    if (profile == 0xffffffffu) {
+        return 1.0;
+    }
+
+    // The profile is sampled at evenly spaced angles from its axis.
+    let axis = octahedral_decode_signed(unpack2x16snorm((*light).ies_profile_axis));
+    let angle = acos(clamp(dot(axis, light_to_frag_dir), -1.0, 1.0));
+    let position = angle / PI * f32(IES_PROFILE_SAMPLE_COUNT - 1u);
+    let index = min(u32(position), IES_PROFILE_SAMPLE_COUNT - 2u);
+    let first_sample = profile * IES_PROFILE_SAMPLE_COUNT + index;
+    return mix(
+        view_bindings::ies_profiles[first_sample],
+        view_bindings::ies_profiles[first_sample + 1u],
+        saturate(position - f32(index)),
+    );
+#else   // AVAILABLE_STORAGE_BUFFER_BINDINGS >= 3
+    return 1.0;
+#endif  // AVAILABLE_STORAGE_BUFFER_BINDINGS >= 3
+}
+
 fn point_light(
     light_id: u32,
     input: ptr<function, LightingInput>,
@@ -540,7 +567,7 @@ fn point_light(
 #endif  // STANDARD_MATERIAL_CLEARCOAT
 
     return color * (*light).color_inverse_square_range.rgb *
-        (rangeAttenuation * derived_input.NdotL);
+        (rangeAttenuation * derived_input.NdotL * ies_profile_attenuation(light_id, -L));
 }
 
 fn spot_light(
@@ -133,6 +133,17 @@ fn fragment(
 #else
     out.motion_vector = pbr_prepass_functions::calculate_motion_vector(in.world_position, in.previous_world_position);
 #endif
+#endif
+
+#ifdef ENTITY_INDEX_PREPASS
+#ifdef MESHLET_MESH_MATERIAL_PASS
+    out.entity = in.entity;
+#else
+    out.entity = vec2(
+        mesh[in.instance_index].main_entity_index,
+        mesh[in.instance_index].main_entity_generation,
+    );
+#endif
 #endif
 
     return out;
@@ -52,6 +52,7 @@ const STANDARD_MATERIAL_FLAGS_CLEARCOAT_TEXTURE_BIT: u32          = 16384u;
 const STANDARD_MATERIAL_FLAGS_CLEARCOAT_ROUGHNESS_TEXTURE_BIT: u32 = 32768u;
 const STANDARD_MATERIAL_FLAGS_CLEARCOAT_NORMAL_TEXTURE_BIT: u32   = 65536u;
 const STANDARD_MATERIAL_FLAGS_ANISOTROPY_TEXTURE_BIT: u32         = 131072u;
+const STANDARD_MATERIAL_FLAGS_PLANAR_REFLECTION_TEXTURE_BIT: u32  = 262144u;
 const STANDARD_MATERIAL_FLAGS_ALPHA_MODE_RESERVED_BITS: u32       = 3758096384u; // (0b111u32 << 29)
 const STANDARD_MATERIAL_FLAGS_ALPHA_MODE_OPAQUE: u32              = 0u;          // (0u32 << 29)
 const STANDARD_MATERIAL_FLAGS_ALPHA_MODE_MASK: u32                = 536870912u;  // (1u32 << 29)
@@ -111,6 +112,9 @@ struct PbrInput {
     // view world position
     V: vec3<f32>,
     lightmap_light: vec3<f32>,
+    // The light reflected by a planar reflection probe in `rgb`, and how much
+    // it replaces the specular light from environment maps in `a`.
+    planar_reflection: vec4<f32>,
     clearcoat_N: vec3<f32>,
     anisotropy_strength: f32,
     // These two aren't specific to anisotropy, but we only fill them in if
@@ -144,6 +148,7 @@ fn pbr_input_new() -> PbrInput {
     pbr_input.anisotropy_B = vec3<f32>(0.0);
 
     pbr_input.lightmap_light = vec3<f32>(0.0);
+    pbr_input.planar_reflection = vec4<f32>(0.0);
 
     pbr_input.flags = 0u;
 
@@ -0,0 +1,552 @@
+//! GPU pre-skinning of skinned meshes.
+//!
+//! Normally, the vertices of a skinned mesh are skinned in the vertex shader of
+//! every pass that draws the mesh: each shadow view, the prepasses and the main
+//! pass. When the `gpu_preskinning` feature is enabled and storage buffers are
+//! available, the compute shader in this module instead skins each eligible
+//! mesh once per frame, writing the world-space positions, normals and tangents
+//! of its vertices to [`PreskinnedMeshes::vertices`]. The meshes are then
+//! specialized with [`MeshPipelineKey::PRESKINNED`], and their vertex shaders
+//! read the pre-skinned vertices instead of blending joint matrices.
+//!
+//! A skinned mesh is pre-skinned if it uses [`SkinningMethod::LinearBlend`],
+//! has no morph targets, and stores its vertex attributes in the standard
+//! formats. All other skinned meshes are skinned in the vertex shader as usual.
+//!
+//! [`MeshPipelineKey::PRESKINNED`]: crate::MeshPipelineKey::PRESKINNED
+
+use bevy_asset::{AssetId, Handle};
+use bevy_ecs::{
+    entity::Entity,
+    query::With,
+    system::{Query, Res, ResMut, Resource},
+    world::{FromWorld, World},
+};
+use bevy_render::{
+    mesh::{
+        allocator::{MeshAllocator, SlabId},
+        skinning::{SkinnedMesh, SkinningMethod},
+        Mesh, MeshVertexAttribute, MeshVertexBufferLayoutRef, RenderMesh,
+    },
+    render_asset::RenderAssets,
+    render_resource::{
+        binding_types::{storage_buffer_read_only_sized, storage_buffer_sized, uniform_buffer},
+        BindGroup, BindGroupEntries, BindGroupLayout, BindGroupLayoutEntries, Buffer,
+        BufferDescriptor, BufferUsages, CachedComputePipelineId, CommandEncoderDescriptor,
+        ComputePassDescriptor, ComputePipelineDescriptor, DynamicUniformBuffer, PipelineCache,
+        RawBufferVec, Shader, ShaderStages, ShaderType,
+    },
+    renderer::{RenderDevice, RenderQueue},
+    sync_world::MainEntity,
+    view::ViewVisibility,
+    Extract,
+};
+use bevy_utils::{hashbrown::hash_map::Entry, HashMap};
+
+use super::{
+    mesh::{RenderMeshInstanceFlags, RenderMeshInstances},
+    skin::{SkinIndex, SkinIndices, SkinUniforms},
+};
+
+/// The handle to the `preskinning.wgsl` compute shader.
+pub const PRESKINNING_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(5810473382405261175);
+
+/// The size of a single pre-skinned vertex in [`PreskinnedMeshes::vertices`],
+/// in bytes.
+///
+/// Each vertex consists of its position, its position as of the previous
+/// frame, its normal and its tangent, each stored as a `vec4<f32>`.
+pub const PRESKINNED_VERTEX_SIZE: u64 = 64;
+
+/// The GPU workgroup size.
+const WORKGROUP_SIZE: u32 = 64;
+
+/// The compute pipeline that skins the vertices of skinned meshes.
+#[derive(Resource)]
+pub struct PreskinningPipeline {
+    /// The bind group layout for the compute shader.
+    pub bind_group_layout: BindGroupLayout,
+    /// The pipeline ID for the compute shader.
+    pub pipeline_id: CachedComputePipelineId,
+}
+
+/// Everything the compute shader needs to know to skin a single mesh.
+///
+/// This must match the `PreskinningJob` structure in `preskinning.wgsl`.
+#[derive(ShaderType, Clone, Copy, Default)]
+struct PreskinningJob {
+    /// The number of vertices to skin.
+    vertex_count: u32,
+    /// The offset of the mesh's first vertex in its vertex slab, in 32-bit
+    /// words.
+    first_vertex_word: u32,
+    /// The size of a single vertex, in 32-bit words.
+    vertex_stride: u32,
+    /// The offset of the position within a vertex, in 32-bit words.
+    position_offset: u32,
+    /// The offset of the normal within a vertex, in 32-bit words, or
+    /// `u32::MAX` if the mesh has no normals.
+    normal_offset: u32,
+    /// The offset of the tangent within a vertex, in 32-bit words, or
+    /// `u32::MAX` if the mesh has no tangents.
+    tangent_offset: u32,
+    /// The offset of the joint indices within a vertex, in 32-bit words.
+    joint_index_offset: u32,
+    /// The offset of the joint weights within a vertex, in 32-bit words.
+    joint_weight_offset: u32,
+    /// The index of the mesh's first joint matrix in
+    /// [`SkinUniforms::current_buffer`].
+    current_skin_index: u32,
+    /// The index of the mesh's first joint matrix in
+    /// [`SkinUniforms::prev_buffer`], or the current one if the mesh wasn't
+    /// skinned last frame.
+    previous_skin_index: u32,
+    /// The index of the mesh's first vertex in [`PreskinnedMeshes::vertices`].
+    first_output_vertex: u32,
+}
+
+/// A single dispatch of the pre-skinning compute shader.
+struct PreskinningDispatch {
+    /// The mesh whose vertices are skinned.
+    mesh_id: AssetId<Mesh>,
+    /// The slab that the mesh's vertices live in.
+    vertex_slab: SlabId,
+    /// The offset of the [`PreskinningJob`] in [`PreskinnedMeshes::jobs`].
+    dynamic_offset: u32,
+    /// The number of vertices to skin.
+    vertex_count: u32,
+}
+
+/// The skinned meshes that are pre-skinned this frame, and the GPU buffers
+/// that the pre-skinned vertices are written to.
+#[derive(Resource)]
+pub struct PreskinnedMeshes {
+    /// Maps the current skin index of each pre-skinned mesh (see
+    /// [`SkinIndex::index`]) to the index of its first vertex in
+    /// [`PreskinnedMeshes::vertices`].
+    ///
+    /// Entries that don't correspond to a pre-skinned mesh are `u32::MAX`.
+    pub vertex_offsets: RawBufferVec<u32>,
+    /// The pre-skinned vertices, each [`PRESKINNED_VERTEX_SIZE`] bytes long.
+    ///
+    /// This buffer is only ever written to by the compute shader.
+    pub vertices: Option<Buffer>,
+    /// The number of vertices that [`PreskinnedMeshes::vertices`] can hold, or
+    /// 0 if it hasn't been created yet.
+    vertex_capacity: u32,
+    /// The number of vertices pre-skinned this frame.
+    vertex_count: u32,
+    /// The parameters of each dispatch this frame.
+    jobs: DynamicUniformBuffer<PreskinningJob>,
+    /// The dispatches to perform this frame.
+    dispatches: Vec<PreskinningDispatch>,
+    /// The visible skinned meshes that don't use dual quaternion skinning, as
+    /// of the last extraction.
+    candidates: Vec<MainEntity>,
+    /// The meshes that are pre-skinned this frame.
+    preskinned: Vec<MainEntity>,
+}
+
+/// The locations of the vertex attributes that the pre-skinning shader reads,
+/// in 32-bit words.
+struct PreskinnedAttributes {
+    stride: u32,
+    position: u32,
+    normal: u32,
+    tangent: u32,
+    joint_index: u32,
+    joint_weight: u32,
+}
+
+impl PreskinnedAttributes {
+    /// Finds the vertex attributes in the given layout, or returns `None` if
+    /// the mesh can't be pre-skinned.
+    fn new(layout: &MeshVertexBufferLayoutRef) -> Option<Self> {
+        let layout = &layout.0;
+        let vertex_buffer_layout = layout.layout();
+        if vertex_buffer_layout.array_stride % 4 != 0 {
+            return None;
+        }
+
+        // Returns `Some(u32::MAX)` if the attribute is missing, and `None` if
+        // it's present in a format that the shader can't read.
+        let find = |attribute: MeshVertexAttribute| {
+            let Some(index) = layout
+                .attribute_ids()
+                .iter()
+                .position(|id| *id == attribute.id)
+            else {
+                return Some(u32::MAX);
+            };
+            let vertex_attribute = &vertex_buffer_layout.attributes[index];
+            (vertex_attribute.format == attribute.format && vertex_attribute.offset % 4 == 0)
+                .then_some((vertex_attribute.offset / 4) as u32)
+        };
+        let required = |offset: u32| (offset != u32::MAX).then_some(offset);
+
+        Some(Self {
+            stride: (vertex_buffer_layout.array_stride / 4) as u32,
+            position: required(find(Mesh::ATTRIBUTE_POSITION)?)?,
+            normal: find(Mesh::ATTRIBUTE_NORMAL)?,
+            tangent: find(Mesh::ATTRIBUTE_TANGENT)?,
+            joint_index: required(find(Mesh::ATTRIBUTE_JOINT_INDEX)?)?,
+            joint_weight: required(find(Mesh::ATTRIBUTE_JOINT_WEIGHT)?)?,
+        })
+    }
+}
+
+impl FromWorld for PreskinningPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let bind_group_layout = render_device.create_bind_group_layout(
+            "preskinning bind group layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    // `job`
+                    uniform_buffer::<PreskinningJob>(true),
+                    // `vertex_data`
+                    storage_buffer_read_only_sized(false, None),
+                    // `joint_matrices`
+                    storage_buffer_read_only_sized(false, None),
+                    // `prev_joint_matrices`
+                    storage_buffer_read_only_sized(false, None),
+                    // `preskinned_vertices`
+                    storage_buffer_sized(false, None),
+                ),
+            ),
+        );
+
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let pipeline_id = pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+            label: Some("preskinning pipeline".into()),
+            layout: vec![bind_group_layout.clone()],
+            push_constant_ranges: vec![],
+            shader: PRESKINNING_SHADER_HANDLE,
+            shader_defs: vec![],
+            entry_point: "main".into(),
+            zero_initialize_workgroup_memory: false,
+        });
+
+        Self {
+            bind_group_layout,
+            pipeline_id,
+        }
+    }
+}
+
+impl FromWorld for PreskinnedMeshes {
+    fn from_world(_: &mut World) -> Self {
+        let mut vertex_offsets = RawBufferVec::new(BufferUsages::STORAGE);
+        vertex_offsets.set_label(Some("PreskinnedMeshes::vertex_offsets"));
+        let mut jobs = DynamicUniformBuffer::default();
+        jobs.set_label(Some("PreskinnedMeshes::jobs"));
+
+        Self {
+            vertex_offsets,
+            vertices: None,
+            vertex_capacity: 0,
+            vertex_count: 0,
+            jobs,
+            dispatches: vec![],
+            candidates: vec![],
+            preskinned: vec![],
+        }
+    }
+}
+
+impl PreskinnedMeshes {
+    /// Returns the buffers of vertex offsets and pre-skinned vertices, for
+    /// binding in the skinned mesh bind groups.
+    pub fn buffers(&self) -> Option<(&Buffer, &Buffer)> {
+        Some((self.vertex_offsets.buffer()?, self.vertices.as_ref()?))
+    }
+
+    /// Reserves room in [`PreskinnedMeshes::vertices`] for the vertices of the
+    /// mesh with the given current skin index, and returns the index of its
+    /// first vertex.
+    fn allocate_vertices(&mut self, skin_index: u32, vertex_count: u32) -> u32 {
+        let first_vertex = self.vertex_count;
+        let vertex_offsets = self.vertex_offsets.values_mut();
+        let index = skin_index as usize;
+        if vertex_offsets.len() <= index {
+            vertex_offsets.resize(index + 1, u32::MAX);
+        }
+        vertex_offsets[index] = first_vertex;
+        self.vertex_count += vertex_count;
+        first_vertex
+    }
+
+    /// Returns the number of vertices to create [`PreskinnedMeshes::vertices`]
+    /// with, if it's too small for the vertices pre-skinned this frame.
+    fn vertex_capacity_to_allocate(&self) -> Option<u32> {
+        let required_capacity = self.vertex_count.max(1);
+        (self.vertex_capacity < required_capacity).then(|| required_capacity.next_power_of_two())
+    }
+}
+
+/// Finds the skinned meshes that may be pre-skinned this frame.
+///
+/// Whether they actually are depends on their [`RenderMesh`], which is checked
+/// in [`prepare_preskinned_meshes`].
+pub fn extract_preskinned_meshes(
+    mut preskinned_meshes: ResMut<PreskinnedMeshes>,
+    query: Extract<Query<(Entity, &ViewVisibility, Option<&SkinningMethod>), With<SkinnedMesh>>>,
+) {
+    preskinned_meshes.candidates.clear();
+    preskinned_meshes.candidates.extend(
+        query
+            .iter()
+            .filter(|(_, view_visibility, skinning_method)| {
+                view_visibility.get() && *skinning_method != Some(&SkinningMethod::DualQuaternion)
+            })
+            .map(|(entity, _, _)| MainEntity::from(entity)),
+    );
+}
+
+/// Decides which skinned meshes are pre-skinned this frame, marks them with
+/// [`RenderMeshInstanceFlags::PRESKINNED`], and prepares the buffers that the
+/// compute shader reads from and writes to.
+///
+/// This has to run before the meshes are queued, so that they're specialized
+/// with the right pipeline key.
+pub fn prepare_preskinned_meshes(
+    preskinned_meshes: ResMut<PreskinnedMeshes>,
+    mut render_mesh_instances: ResMut<RenderMeshInstances>,
+    skin_indices: Res<SkinIndices>,
+    render_meshes: Res<RenderAssets<RenderMesh>>,
+    mesh_allocator: Res<MeshAllocator>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+) {
+    let preskinned_meshes = preskinned_meshes.into_inner();
+
+    // Mesh instance flags aren't necessarily reset every frame, so clear the
+    // ones we set last frame.
+    for entity in preskinned_meshes.preskinned.drain(..) {
+        render_mesh_instances
+            .remove_mesh_instance_flags(entity, RenderMeshInstanceFlags::PRESKINNED);
+    }
+    preskinned_meshes.vertex_offsets.clear();
+    preskinned_meshes.jobs.clear();
+    preskinned_meshes.dispatches.clear();
+    preskinned_meshes.vertex_count = 0;
+
+    for &entity in &preskinned_meshes.candidates {
+        let Some(skin_index) = skin_indices.current.get(&entity) else {
+            continue;
+        };
+        let Some(mesh_id) = render_mesh_instances.mesh_asset_id(entity) else {
+            continue;
+        };
+        let Some(render_mesh) = render_meshes.get(mesh_id) else {
+            continue;
+        };
+        if render_mesh.morph_targets.is_some() {
+            continue;
+        }
+        let Some(attributes) = PreskinnedAttributes::new(&render_mesh.layout) else {
+            continue;
+        };
+        let (Some(vertex_slab), _) = mesh_allocator.mesh_slabs(&mesh_id) else {
+            continue;
+        };
+        let Some(vertex_slice) = mesh_allocator.mesh_vertex_slice(&mesh_id) else {
+            continue;
+        };
+
+        let current_skin_index = skin_index.index();
+        let first_output_vertex =
+            preskinned_meshes.allocate_vertices(current_skin_index, render_mesh.vertex_count);
+        let dynamic_offset = preskinned_meshes.jobs.push(&PreskinningJob {
+            vertex_count: render_mesh.vertex_count,
+            first_vertex_word: vertex_slice.range.start * attributes.stride,
+            vertex_stride: attributes.stride,
+            position_offset: attributes.position,
+            normal_offset: attributes.normal,
+            tangent_offset: attributes.tangent,
+            joint_index_offset: attributes.joint_index,
+            joint_weight_offset: attributes.joint_weight,
+            current_skin_index,
+            previous_skin_index: skin_indices
+                .prev
+                .get(&entity)
+                .map_or(current_skin_index, SkinIndex::index),
+            first_output_vertex,
+        });
+        preskinned_meshes.dispatches.push(PreskinningDispatch {
+            mesh_id,
+            vertex_slab,
+            dynamic_offset,
+            vertex_count: render_mesh.vertex_count,
+        });
+
+        render_mesh_instances
+            .insert_mesh_instance_flags(entity, RenderMeshInstanceFlags::PRESKINNED);
+        preskinned_meshes.preskinned.push(entity);
+    }
+
+    // Make sure that there's always something to bind.
+    if preskinned_meshes.vertex_offsets.is_empty() {
+        preskinned_meshes.vertex_offsets.push(u32::MAX);
+    }
+    preskinned_meshes
+        .vertex_offsets
+        .write_buffer(&render_device, &render_queue);
+    preskinned_meshes
+        .jobs
+        .write_buffer(&render_device, &render_queue);
+
+    // The compute shader rewrites all the vertices every frame, so there's no
+    // need to preserve the old contents when growing the buffer.
+    if let Some(vertex_capacity) = preskinned_meshes.vertex_capacity_to_allocate() {
+        preskinned_meshes.vertices = Some(render_device.create_buffer(&BufferDescriptor {
+            label: Some("PreskinnedMeshes::vertices"),
+            size: vertex_capacity as u64 * PRESKINNED_VERTEX_SIZE,
+            usage: BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        }));
+        preskinned_meshes.vertex_capacity = vertex_capacity;
+    }
+}
+
+/// Dispatches the compute shader that fills in [`PreskinnedMeshes::vertices`].
+///
+/// Like `precompute_skins`, this is submitted on its own command buffer ahead
+/// of the render graph, so that the pre-skinned vertices are ready for every
+/// pass that draws skinned meshes.
+pub fn preskin_meshes(
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    pipeline_cache: Res<PipelineCache>,
+    preskinning_pipeline: Res<PreskinningPipeline>,
+    preskinned_meshes: Res<PreskinnedMeshes>,
+    skin_uniforms: Res<SkinUniforms>,
+    mesh_allocator: Res<MeshAllocator>,
+) {
+    if preskinned_meshes.dispatches.is_empty() {
+        return;
+    }
+
+    let (Some(jobs), Some(vertices), Some(joint_matrices)) = (
+        preskinned_meshes.jobs.binding(),
+        preskinned_meshes.vertices.as_ref(),
+        skin_uniforms.current_buffer.buffer(),
+    ) else {
+        return;
+    };
+    let prev_joint_matrices = skin_uniforms.prev_buffer.buffer().unwrap_or(joint_matrices);
+
+    let Some(pipeline) = pipeline_cache.get_compute_pipeline(preskinning_pipeline.pipeline_id)
+    else {
+        // This will happen while the pipeline is being compiled and is fine.
+        return;
+    };
+
+    let mut command_encoder = render_device.create_command_encoder(&CommandEncoderDescriptor {
+        label: Some("preskinning command encoder"),
+    });
+
+    {
+        let mut compute_pass = command_encoder.begin_compute_pass(&ComputePassDescriptor {
+            label: Some("preskinning"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(pipeline);
+
+        // Meshes whose vertices live in the same slab share a bind group.
+        let mut bind_groups: HashMap<SlabId, BindGroup> = HashMap::default();
+        for dispatch in &preskinned_meshes.dispatches {
+            let bind_group = match bind_groups.entry(dispatch.vertex_slab) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    let Some(vertex_slice) = mesh_allocator.mesh_vertex_slice(&dispatch.mesh_id)
+                    else {
+                        continue;
+                    };
+                    entry.insert(render_device.create_bind_group(
+                        "preskinning bind group",
+                        &preskinning_pipeline.bind_group_layout,
+                        &BindGroupEntries::sequential((
+                            jobs.clone(),
+                            vertex_slice.buffer.as_entire_binding(),
+                            joint_matrices.as_entire_binding(),
+                            prev_joint_matrices.as_entire_binding(),
+                            vertices.as_entire_binding(),
+                        )),
+                    ))
+                }
+            };
+            compute_pass.set_bind_group(0, &*bind_group, &[dispatch.dynamic_offset]);
+            compute_pass.dispatch_workgroups(dispatch.vertex_count.div_ceil(WORKGROUP_SIZE), 1, 1);
+        }
+    }
+
+    render_queue.submit([command_encoder.finish()]);
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::world::{FromWorld, World};
+    use bevy_render::{
+        mesh::{Mesh, MeshVertexBufferLayouts, PrimitiveTopology, VertexAttributeValues},
+        render_asset::RenderAssetUsages,
+    };
+
+    use super::{PreskinnedAttributes, PreskinnedMeshes};
+
+    #[test]
+    fn vertex_offsets() {
+        let mut preskinned_meshes = PreskinnedMeshes::from_world(&mut World::new());
+        assert_eq!(preskinned_meshes.vertex_capacity_to_allocate(), Some(1));
+
+        // Skin indices are the offsets of the joint matrices, so they're sparse.
+        assert_eq!(preskinned_meshes.allocate_vertices(4, 100), 0);
+        assert_eq!(preskinned_meshes.allocate_vertices(0, 30), 100);
+        assert_eq!(
+            preskinned_meshes.vertex_offsets.values(),
+            &[100, u32::MAX, u32::MAX, u32::MAX, 0]
+        );
+        assert_eq!(preskinned_meshes.vertex_count, 130);
+        assert_eq!(preskinned_meshes.vertex_capacity_to_allocate(), Some(256));
+
+        // The buffer is only recreated once it's too small.
+        preskinned_meshes.vertex_capacity = 256;
+        assert_eq!(preskinned_meshes.vertex_capacity_to_allocate(), None);
+        preskinned_meshes.allocate_vertices(8, 200);
+        assert_eq!(preskinned_meshes.vertex_capacity_to_allocate(), Some(512));
+    }
+
+    #[test]
+    fn preskinned_attributes() {
+        let mut mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        );
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vec![[0.0; 3]; 3]);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0; 3]; 3]);
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_JOINT_INDEX,
+            VertexAttributeValues::Uint16x4(vec![[0; 4]; 3]),
+        );
+        mesh.insert_attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT, vec![[0.25; 4]; 3]);
+        let mut layouts = MeshVertexBufferLayouts::default();
+        let attributes =
+            PreskinnedAttributes::new(&mesh.get_mesh_vertex_buffer_layout(&mut layouts)).unwrap();
+
+        // Attributes are sorted by ID: position, normal, joint weight, joint index.
+        assert_eq!(attributes.stride, 3 + 3 + 4 + 2);
+        assert_eq!(attributes.position, 0);
+        assert_eq!(attributes.normal, 3);
+        assert_eq!(attributes.tangent, u32::MAX);
+        assert_eq!(attributes.joint_weight, 6);
+        assert_eq!(attributes.joint_index, 10);
+
+        // Meshes without joints can't be pre-skinned.
+        mesh.remove_attribute(Mesh::ATTRIBUTE_JOINT_INDEX);
+        assert!(
+            PreskinnedAttributes::new(&mesh.get_mesh_vertex_buffer_layout(&mut layouts)).is_none()
+        );
+    }
+}
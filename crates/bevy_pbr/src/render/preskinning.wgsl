@@ -0,0 +1,136 @@
+// GPU pre-skinning of skinned meshes.
+//
+// This is a compute shader that skins every vertex of a skinned mesh once per
+// frame, writing the world-space position, normal and tangent of each vertex
+// to a buffer that the vertex shaders of all passes read from. Without this,
+// the shadow passes, the prepasses and the main pass would each blend the
+// joint matrices of every vertex again.
+//
+// Each dispatch handles a single mesh, described by `job`.
+
+struct PreskinningJob {
+    // The number of vertices to skin.
+    vertex_count: u32,
+    // The offset of the mesh's first vertex in `vertex_data`, in 32-bit words.
+    first_vertex_word: u32,
+    // The size of a single vertex, in 32-bit words.
+    vertex_stride: u32,
+    // The offsets of the attributes within a vertex, in 32-bit words. The
+    // normal and tangent offsets are `0xffffffffu` if the mesh lacks them.
+    position_offset: u32,
+    normal_offset: u32,
+    tangent_offset: u32,
+    joint_index_offset: u32,
+    joint_weight_offset: u32,
+    // The index of the mesh's first joint matrix in `joint_matrices`.
+    current_skin_index: u32,
+    // The index of the mesh's first joint matrix in `prev_joint_matrices`.
+    previous_skin_index: u32,
+    // The index of the mesh's first vertex in `preskinned_vertices`.
+    first_output_vertex: u32,
+}
+
+// Must match `PreskinnedVertex` in `skinning.wgsl`.
+struct PreskinnedVertex {
+    position: vec4<f32>,
+    previous_position: vec4<f32>,
+    normal: vec4<f32>,
+    tangent: vec4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> job: PreskinningJob;
+// The vertex slab that the mesh lives in.
+@group(0) @binding(1) var<storage> vertex_data: array<u32>;
+@group(0) @binding(2) var<storage> joint_matrices: array<mat4x4<f32>>;
+@group(0) @binding(3) var<storage> prev_joint_matrices: array<mat4x4<f32>>;
+@group(0) @binding(4) var<storage, read_write> preskinned_vertices: array<PreskinnedVertex>;
+
+fn read_vec3(word: u32) -> vec3<f32> {
+    return vec3<f32>(
+        bitcast<f32>(vertex_data[word]),
+        bitcast<f32>(vertex_data[word + 1u]),
+        bitcast<f32>(vertex_data[word + 2u]),
+    );
+}
+
+fn read_vec4(word: u32) -> vec4<f32> {
+    return vec4<f32>(read_vec3(word), bitcast<f32>(vertex_data[word + 3u]));
+}
+
+// Joint indices are stored as `Uint16x4`.
+fn read_joint_indices(word: u32) -> vec4<u32> {
+    let xy = vertex_data[word];
+    let zw = vertex_data[word + 1u];
+    return vec4<u32>(xy & 0xffffu, xy >> 16u, zw & 0xffffu, zw >> 16u);
+}
+
+// The same as `skinning::inverse_transpose_3x3m`.
+fn inverse_transpose_3x3m(in: mat3x3<f32>) -> mat3x3<f32> {
+    let x = cross(in[1], in[2]);
+    let y = cross(in[2], in[0]);
+    let z = cross(in[0], in[1]);
+    let det = dot(in[2], z);
+    return mat3x3<f32>(
+        x / det,
+        y / det,
+        z / det
+    );
+}
+
+fn blend_joints(skin_index: u32, indices: vec4<u32>, weights: vec4<f32>) -> mat4x4<f32> {
+    return weights.x * joint_matrices[skin_index + indices.x]
+        + weights.y * joint_matrices[skin_index + indices.y]
+        + weights.z * joint_matrices[skin_index + indices.z]
+        + weights.w * joint_matrices[skin_index + indices.w];
+}
+
+fn blend_prev_joints(skin_index: u32, indices: vec4<u32>, weights: vec4<f32>) -> mat4x4<f32> {
+    return weights.x * prev_joint_matrices[skin_index + indices.x]
+        + weights.y * prev_joint_matrices[skin_index + indices.y]
+        + weights.z * prev_joint_matrices[skin_index + indices.z]
+        + weights.w * prev_joint_matrices[skin_index + indices.w];
+}
+
+@compute
+@workgroup_size(64)
+fn main(@builtin(global_invocation_id) global_invocation_id: vec3<u32>) {
+    let vertex_index = global_invocation_id.x;
+    if (vertex_index >= job.vertex_count) {
+        return;
+    }
+
+    let base = job.first_vertex_word + vertex_index * job.vertex_stride;
+    let position = vec4<f32>(read_vec3(base + job.position_offset), 1.0);
+    let joint_indices = read_joint_indices(base + job.joint_index_offset);
+    let joint_weights = read_vec4(base + job.joint_weight_offset);
+
+    let world_from_local = blend_joints(job.current_skin_index, joint_indices, joint_weights);
+    let prev_world_from_local =
+        blend_prev_joints(job.previous_skin_index, joint_indices, joint_weights);
+    let model_3x3 = mat3x3<f32>(
+        world_from_local[0].xyz,
+        world_from_local[1].xyz,
+        world_from_local[2].xyz,
+    );
+
+    var out: PreskinnedVertex;
+    out.position = world_from_local * position;
+    out.previous_position = prev_world_from_local * position;
+
+    // This matches `skinning::skin_normals`.
+    if (job.normal_offset != 0xffffffffu) {
+        let normal = read_vec3(base + job.normal_offset);
+        out.normal = vec4<f32>(normalize(inverse_transpose_3x3m(model_3x3) * normal), 0.0);
+    }
+
+    // This matches `mesh_functions::mesh_tangent_local_to_world`, except for
+    // the sign of the determinant, which the vertex shader still applies.
+    if (job.tangent_offset != 0xffffffffu) {
+        let tangent = read_vec4(base + job.tangent_offset);
+        if (any(tangent != vec4<f32>(0.0))) {
+            out.tangent = vec4<f32>(normalize(model_3x3 * tangent.xyz), tangent.w);
+        }
+    }
+
+    preskinned_vertices[job.first_output_vertex + vertex_index] = out;
+}
@@ -0,0 +1,242 @@
+use bevy_math::UVec2;
+
+use crate::PointLightShadowMap;
+
+/// The granularity of the point light shadow atlas, in texels.
+///
+/// Tiles are placed along a Z-order curve of cells of this size, which must
+/// match `POINT_SHADOW_ATLAS_CELL_SIZE` in `shadow_sampling.wgsl`.
+pub const POINT_LIGHT_SHADOW_ATLAS_CELL_SIZE: u32 = 16;
+
+/// The bytes taken by a texel of the point light shadow atlas.
+const POINT_LIGHT_SHADOW_ATLAS_TEXEL_BYTES: usize = 4;
+
+/// The shadow map of a point light, for [`allocate_point_light_shadow_atlas`].
+#[derive(Clone, Copy, Debug)]
+pub struct PointLightShadowRequest {
+    /// How much the light deserves a detailed shadow map, the product of its
+    /// [`ShadowPriority`](crate::ShadowPriority) and its screen coverage.
+    pub importance: f32,
+}
+
+/// Where the six faces of a point light shadow map are in the atlas.
+///
+/// The faces are consecutive square tiles along the Z-order curve of the
+/// atlas.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PointLightShadowAtlasTile {
+    /// The index of the first cell of the first face along the Z-order curve.
+    pub cell: u32,
+    /// The width and height of each face, in texels.
+    pub size: u32,
+}
+
+impl PointLightShadowAtlasTile {
+    /// Returns the top-left texel of a face in the atlas.
+    pub fn face_origin(&self, face_index: usize) -> UVec2 {
+        let cells_per_face = (self.size / POINT_LIGHT_SHADOW_ATLAS_CELL_SIZE).pow(2);
+        let cell = self.cell + face_index as u32 * cells_per_face;
+        UVec2::new(compact_bits(cell), compact_bits(cell >> 1)) * POINT_LIGHT_SHADOW_ATLAS_CELL_SIZE
+    }
+
+    /// Packs the tile for the shader: the first cell in the low 24 bits and
+    /// the log2 of the face size in the high 8 bits.
+    pub fn pack(&self) -> u32 {
+        self.cell | (self.size.trailing_zeros() << 24)
+    }
+}
+
+/// The layout of the point light shadow atlas for a frame.
+#[derive(Clone, Debug, Default)]
+pub struct PointLightShadowAtlas {
+    /// The width and height of the atlas texture, in texels.
+    pub size: u32,
+    /// The tile of each requested shadow map, or `None` for the lights that
+    /// didn't fit in the budget.
+    pub tiles: Vec<Option<PointLightShadowAtlasTile>>,
+}
+
+/// Picks the resolution of each point light shadow map and packs them into an
+/// atlas.
+///
+/// Shadow maps start at [`PointLightShadowMap::size`] scaled by their
+/// importance, rounded to a power of two between
+/// [`PointLightShadowMap::min_size`] and [`PointLightShadowMap::size`]. While
+/// they take more than [`PointLightShadowMap::memory_budget`], or don't fit in
+/// `max_atlas_size`, the least important shadow map that can still shrink is
+/// halved, and once they're all at the minimum size the least important ones
+/// are dropped.
+pub fn allocate_point_light_shadow_atlas(
+    requests: &[PointLightShadowRequest],
+    settings: &PointLightShadowMap,
+    max_atlas_size: u32,
+) -> PointLightShadowAtlas {
+    let max_size = (settings.size as u32)
+        .max(POINT_LIGHT_SHADOW_ATLAS_CELL_SIZE)
+        .next_power_of_two();
+    let min_size = (settings.min_size as u32)
+        .max(POINT_LIGHT_SHADOW_ATLAS_CELL_SIZE)
+        .next_power_of_two()
+        .min(max_size);
+
+    // The largest power of two that fits both the budget and the device.
+    let budget_texels = (settings.memory_budget / POINT_LIGHT_SHADOW_ATLAS_TEXEL_BYTES) as u64;
+    let mut max_atlas_size = prev_power_of_two(max_atlas_size);
+    while max_atlas_size > 1 && u64::from(max_atlas_size).pow(2) > budget_texels {
+        max_atlas_size /= 2;
+    }
+    let capacity = u64::from(max_atlas_size).pow(2);
+    let max_size = max_size.min(max_atlas_size);
+    let min_size = min_size.min(max_size);
+
+    let mut sizes: Vec<u32> = requests
+        .iter()
+        .map(|request| {
+            let size = max_size as f32 * request.importance.clamp(0.0, 1.0);
+            (size as u32).next_power_of_two().clamp(min_size, max_size)
+        })
+        .collect();
+
+    // The requests from the least to the most important.
+    let mut by_importance: Vec<usize> = (0..requests.len()).collect();
+    by_importance.sort_by(|&a, &b| requests[a].importance.total_cmp(&requests[b].importance));
+
+    let area = |size: u32| 6 * u64::from(size).pow(2);
+    let mut total_area: u64 = sizes.iter().map(|&size| area(size)).sum();
+    while total_area > capacity {
+        if let Some(&index) = by_importance.iter().find(|&&index| sizes[index] > min_size) {
+            total_area -= area(sizes[index]) - area(sizes[index] / 2);
+            sizes[index] /= 2;
+        } else if let Some(&index) = by_importance.iter().find(|&&index| sizes[index] > 0) {
+            total_area -= area(sizes[index]);
+            sizes[index] = 0;
+        } else {
+            break;
+        }
+    }
+
+    // Power-of-two squares placed from the largest to the smallest along a
+    // Z-order curve never overlap and leave no gaps.
+    let mut by_size: Vec<usize> = (0..requests.len())
+        .filter(|&index| sizes[index] > 0)
+        .collect();
+    by_size.sort_by_key(|&index| core::cmp::Reverse(sizes[index]));
+
+    let mut tiles = vec![None; requests.len()];
+    let mut cell = 0;
+    for index in by_size {
+        let size = sizes[index];
+        tiles[index] = Some(PointLightShadowAtlasTile { cell, size });
+        cell += 6 * (size / POINT_LIGHT_SHADOW_ATLAS_CELL_SIZE).pow(2);
+    }
+
+    // The smallest power of two that holds all the tiles.
+    let mut size = POINT_LIGHT_SHADOW_ATLAS_CELL_SIZE;
+    while u64::from(size).pow(2) < total_area {
+        size *= 2;
+    }
+
+    PointLightShadowAtlas { size, tiles }
+}
+
+fn prev_power_of_two(value: u32) -> u32 {
+    if value == 0 {
+        0
+    } else {
+        1 << (31 - value.leading_zeros())
+    }
+}
+
+/// Keeps the even bits of `value`, compacted into the low 16 bits.
+fn compact_bits(value: u32) -> u32 {
+    let mut value = value & 0x5555_5555;
+    value = (value | (value >> 1)) & 0x3333_3333;
+    value = (value | (value >> 2)) & 0x0f0f_0f0f;
+    value = (value | (value >> 4)) & 0x00ff_00ff;
+    (value | (value >> 8)) & 0x0000_ffff
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_math::URect;
+
+    use super::*;
+
+    fn settings(size: usize, min_size: usize, memory_budget: usize) -> PointLightShadowMap {
+        PointLightShadowMap {
+            size,
+            min_size,
+            memory_budget,
+        }
+    }
+
+    fn requests(importances: &[f32]) -> Vec<PointLightShadowRequest> {
+        importances
+            .iter()
+            .map(|&importance| PointLightShadowRequest { importance })
+            .collect()
+    }
+
+    #[test]
+    fn faces_do_not_overlap() {
+        let atlas = allocate_point_light_shadow_atlas(
+            &requests(&[1.0, 0.3, 0.05, 0.6, 0.01]),
+            &settings(1024, 64, usize::MAX),
+            16384,
+        );
+
+        let mut faces = vec![];
+        for tile in atlas.tiles.iter().flatten() {
+            for face_index in 0..6 {
+                let origin = tile.face_origin(face_index);
+                let face = URect::from_corners(origin, origin + tile.size);
+                assert!(face.max.x <= atlas.size && face.max.y <= atlas.size);
+                for other in &faces {
+                    assert!(face.intersect(*other).is_empty());
+                }
+                faces.push(face);
+            }
+        }
+        assert_eq!(faces.len(), 30);
+    }
+
+    #[test]
+    fn sizes_follow_importance() {
+        let atlas = allocate_point_light_shadow_atlas(
+            &requests(&[1.0, 0.5, 0.0]),
+            &settings(1024, 64, usize::MAX),
+            16384,
+        );
+        let sizes: Vec<_> = atlas.tiles.iter().map(|tile| tile.unwrap().size).collect();
+        assert_eq!(sizes, [1024, 512, 64]);
+    }
+
+    #[test]
+    fn budget_shrinks_least_important_first() {
+        // 4096² texels of 4 bytes: room for ten 1024² faces and a bit more.
+        let budget = 4096 * 4096 * POINT_LIGHT_SHADOW_ATLAS_TEXEL_BYTES;
+        let atlas = allocate_point_light_shadow_atlas(
+            &requests(&[1.0, 1.0, 0.9]),
+            &settings(1024, 64, budget),
+            16384,
+        );
+        let sizes: Vec<_> = atlas.tiles.iter().map(|tile| tile.unwrap().size).collect();
+        assert_eq!(sizes, [1024, 1024, 512]);
+        assert_eq!(atlas.size, 4096);
+    }
+
+    #[test]
+    fn budget_drops_least_important_at_min_size() {
+        // Room for 2 point lights at the minimum size.
+        let budget = 128 * 128 * POINT_LIGHT_SHADOW_ATLAS_TEXEL_BYTES;
+        let atlas = allocate_point_light_shadow_atlas(
+            &requests(&[0.2, 1.0, 0.1, 0.5]),
+            &settings(1024, 32, budget),
+            16384,
+        );
+        assert!(atlas.tiles[0].is_none());
+        assert!(atlas.tiles[1].is_some());
+        assert!(atlas.tiles[2].is_none());
+        assert!(atlas.tiles[3].is_some());
+    }
+}
@@ -309,28 +309,69 @@ fn sample_shadow_map_pcss(
 #endif  // SHADOW_FILTER_METHOD_TEMPORAL
 }
 
+// Must match `POINT_LIGHT_SHADOW_ATLAS_CELL_SIZE` on the CPU.
+const POINT_SHADOW_ATLAS_CELL_SIZE: u32 = 16u;
+
+// Keeps the even bits of `value`, compacted into the low 16 bits.
+fn compact_bits(value: u32) -> u32 {
+    var bits = value & 0x55555555u;
+    bits = (bits | (bits >> 1u)) & 0x33333333u;
+    bits = (bits | (bits >> 2u)) & 0x0f0f0f0fu;
+    bits = (bits | (bits >> 4u)) & 0x00ff00ffu;
+    return (bits | (bits >> 8u)) & 0x0000ffffu;
+}
+
+// Returns where a cubemap lookup in the direction `light_local` lands in the
+// point light shadow atlas.
+//
+// The six faces of the shadow map of a point light are consecutive square
+// tiles along the Z-order curve of the atlas, in the order of the cubemap
+// faces. Point lights store the first cell of their tiles in the low 24 bits,
+// and the log2 of the tile size in the high 8 bits, of `spot_light_tan_angle`.
+fn point_shadow_atlas_uv(light_local: vec3<f32>, light_id: u32) -> vec2<f32> {
+    let tile = bitcast<u32>(view_bindings::clusterable_objects.data[light_id].spot_light_tan_angle);
+    let tile_size = 1u << (tile >> 24u);
+
+    // Pick the face and the UV on it like cubemap sampling does.
+    let abs_local = abs(light_local);
+    var face: u32;
+    var uv: vec2<f32>;
+    if (abs_local.x >= abs_local.y && abs_local.x >= abs_local.z) {
+        face = select(1u, 0u, light_local.x > 0.0);
+        uv = vec2(select(light_local.z, -light_local.z, light_local.x > 0.0), -light_local.y) /
+            abs_local.x;
+    } else if (abs_local.y >= abs_local.z) {
+        face = select(3u, 2u, light_local.y > 0.0);
+        uv = vec2(light_local.x, select(-light_local.z, light_local.z, light_local.y > 0.0)) /
+            abs_local.y;
+    } else {
+        face = select(5u, 4u, light_local.z > 0.0);
+        uv = vec2(select(-light_local.x, light_local.x, light_local.z > 0.0), -light_local.y) /
+            abs_local.z;
+    }
+
+    let cells_per_side = tile_size / POINT_SHADOW_ATLAS_CELL_SIZE;
+    let cell = (tile & 0x00ffffffu) + face * cells_per_side * cells_per_side;
+    let origin = vec2(compact_bits(cell), compact_bits(cell >> 1u)) * POINT_SHADOW_ATLAS_CELL_SIZE;
+
+    // Keep the filtering within the face.
+    let texel = clamp((uv * 0.5 + 0.5) * f32(tile_size), vec2(0.5), vec2(f32(tile_size) - 0.5));
+    return (vec2<f32>(origin) + texel) /
+        vec2<f32>(textureDimensions(view_bindings::point_shadow_textures));
+}
+
 // NOTE: Due to the non-uniform control flow in `shadows::fetch_point_shadow`,
 // we must use the Level variant of textureSampleCompare to avoid undefined
 // behavior due to some of the fragments in a quad (2x2 fragments) being
 // processed not being sampled, and this messing with mip-mapping functionality.
 // The shadow maps have no mipmaps so Level just samples from LOD 0.
 fn sample_shadow_cubemap_hardware(light_local: vec3<f32>, depth: f32, light_id: u32) -> f32 {
-#ifdef NO_CUBE_ARRAY_TEXTURES_SUPPORT
-    return textureSampleCompare(
-        view_bindings::point_shadow_textures,
-        view_bindings::point_shadow_textures_comparison_sampler,
-        light_local,
-        depth
-    );
-#else
     return textureSampleCompareLevel(
         view_bindings::point_shadow_textures,
         view_bindings::point_shadow_textures_comparison_sampler,
-        light_local,
-        i32(light_id),
+        point_shadow_atlas_uv(light_local, light_id),
         depth
     );
-#endif
 }
 
 // Performs one sample of the blocker search. This variation of the blocker
@@ -348,20 +389,12 @@ fn search_for_blockers_in_shadow_cubemap_hardware(
 
 #ifdef PCSS_SAMPLERS_AVAILABLE
 
-#ifdef NO_CUBE_ARRAY_TEXTURES_SUPPORT
-    let sampled_depth = textureSample(
-        view_bindings::point_shadow_textures,
-        view_bindings::point_shadow_textures_linear_sampler,
-        light_local,
-    );
-#else
-    let sampled_depth = textureSample(
+    let sampled_depth = textureSampleLevel(
         view_bindings::point_shadow_textures,
         view_bindings::point_shadow_textures_linear_sampler,
-        light_local,
-        i32(light_id),
+        point_shadow_atlas_uv(light_local, light_id),
+        0,
     );
-#endif
 
     return select(vec2(0.0), vec2(sampled_depth, 1.0), sampled_depth >= depth);
 
@@ -1,7 +1,11 @@
 #define_import_path bevy_pbr::shadows
 
 #import bevy_pbr::{
-    mesh_view_types::POINT_LIGHT_FLAGS_SPOT_LIGHT_Y_NEGATIVE,
+    mesh_view_types::{
+        POINT_LIGHT_FLAGS_SPOT_LIGHT_Y_NEGATIVE, POINT_LIGHT_FLAGS_SHADOWS_ENABLED_BIT,
+        POINT_LIGHT_FLAGS_RAY_TRACED_SHADOWS_BIT, DIRECTIONAL_LIGHT_FLAGS_SHADOWS_ENABLED_BIT,
+        DIRECTIONAL_LIGHT_FLAGS_RAY_TRACED_SHADOWS_BIT,
+    },
     mesh_view_bindings as view_bindings,
     shadow_sampling::{
         SPOT_SHADOW_TEXEL_SIZE, sample_shadow_cubemap, sample_shadow_cubemap_pcss,
@@ -14,9 +18,34 @@
     maths::PI_2
 }
 
+#ifdef RAY_TRACED_SHADOWS
+#import bevy_pbr::ray_traced_shadows::{trace_point_shadow, trace_directional_shadow}
+#endif
+
 const flip_z: vec3<f32> = vec3<f32>(1.0, 1.0, -1.0);
 
+// With ray-traced shadows, the shadow map of the light only holds the casters
+// that aren't in the ray tracing scene, like alpha-masked ones, so both are
+// combined.
+
 fn fetch_point_shadow(light_id: u32, frag_position: vec4<f32>, surface_normal: vec3<f32>) -> f32 {
+    let flags = view_bindings::clusterable_objects.data[light_id].flags;
+    var shadow = 1.0;
+#ifdef RAY_TRACED_SHADOWS
+    if ((flags & POINT_LIGHT_FLAGS_RAY_TRACED_SHADOWS_BIT) != 0u) {
+        shadow = trace_point_shadow(light_id, frag_position.xyz, surface_normal);
+        if (shadow == 0.0) {
+            return 0.0;
+        }
+    }
+#endif
+    if ((flags & POINT_LIGHT_FLAGS_SHADOWS_ENABLED_BIT) != 0u) {
+        shadow *= fetch_point_shadow_map(light_id, frag_position, surface_normal);
+    }
+    return shadow;
+}
+
+fn fetch_point_shadow_map(light_id: u32, frag_position: vec4<f32>, surface_normal: vec3<f32>) -> f32 {
     let light = &view_bindings::clusterable_objects.data[light_id];
 
     // because the shadow maps align with the axes and the frustum planes are at 45 degrees
@@ -67,6 +96,28 @@ fn fetch_spot_shadow(
     frag_position: vec4<f32>,
     surface_normal: vec3<f32>,
     near_z: f32,
+) -> f32 {
+    let flags = view_bindings::clusterable_objects.data[light_id].flags;
+    var shadow = 1.0;
+#ifdef RAY_TRACED_SHADOWS
+    if ((flags & POINT_LIGHT_FLAGS_RAY_TRACED_SHADOWS_BIT) != 0u) {
+        shadow = trace_point_shadow(light_id, frag_position.xyz, surface_normal);
+        if (shadow == 0.0) {
+            return 0.0;
+        }
+    }
+#endif
+    if ((flags & POINT_LIGHT_FLAGS_SHADOWS_ENABLED_BIT) != 0u) {
+        shadow *= fetch_spot_shadow_map(light_id, frag_position, surface_normal, near_z);
+    }
+    return shadow;
+}
+
+fn fetch_spot_shadow_map(
+    light_id: u32,
+    frag_position: vec4<f32>,
+    surface_normal: vec3<f32>,
+    near_z: f32,
 ) -> f32 {
     let light = &view_bindings::clusterable_objects.data[light_id];
 
@@ -200,7 +251,25 @@ fn sample_directional_cascade(
 }
 
 fn fetch_directional_shadow(light_id: u32, frag_position: vec4<f32>, surface_normal: vec3<f32>, view_z: f32) -> f32 {
+    let flags = view_bindings::lights.directional_lights[light_id].flags;
+    var shadow = 1.0;
+#ifdef RAY_TRACED_SHADOWS
+    if ((flags & DIRECTIONAL_LIGHT_FLAGS_RAY_TRACED_SHADOWS_BIT) != 0u) {
+        shadow = trace_directional_shadow(light_id, frag_position.xyz, surface_normal);
+        if (shadow == 0.0) {
+            return 0.0;
+        }
+    }
+#endif
+    if ((flags & DIRECTIONAL_LIGHT_FLAGS_SHADOWS_ENABLED_BIT) != 0u) {
+        shadow *= fetch_directional_shadow_map(light_id, frag_position, surface_normal, view_z);
+    }
+    return shadow;
+}
+
+fn fetch_directional_shadow_map(light_id: u32, frag_position: vec4<f32>, surface_normal: vec3<f32>, view_z: f32) -> f32 {
     let light = &view_bindings::lights.directional_lights[light_id];
+
     let cascade_index = get_cascade_index(light_id, view_z);
 
     if (cascade_index >= (*light).num_cascades) {
@@ -1,29 +1,107 @@
-use core::mem::{self, size_of};
+use core::{
+    mem::{self, size_of},
+    ops::Range,
+};
 use std::sync::OnceLock;
 
 use bevy_asset::Assets;
+#[cfg(feature = "gpu_skinning_precompute")]
+use bevy_asset::{AssetEvent, AssetId};
 use bevy_ecs::prelude::*;
-use bevy_math::Mat4;
-use bevy_render::sync_world::MainEntityHashMap;
+use bevy_math::{Mat4, Quat, Vec3A, Vec4};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::sync_world::{MainEntity, MainEntityHashMap};
 use bevy_render::{
     batching::NoAutomaticBatching,
-    mesh::skinning::{SkinnedMesh, SkinnedMeshInverseBindposes},
+    camera::Camera,
+    diagnostic::{DiagnosticsRecorder, RecordDiagnostics},
+    mesh::skinning::{
+        SkinnedMesh, SkinnedMeshInverseBindposes, SkinnedMeshLod, SkinnedMeshLodLevel,
+        SkinningMethod,
+    },
+    primitives::Aabb,
     render_resource::{BufferUsages, RawBufferVec},
     renderer::{RenderDevice, RenderQueue},
-    view::ViewVisibility,
+    view::{ViewVisibility, VisibilityRange},
     Extract,
 };
 use bevy_transform::prelude::GlobalTransform;
+#[cfg(feature = "gpu_skinning_precompute")]
+use bevy_utils::HashMap;
 
-/// Maximum number of joints supported for skinned meshes.
+/// Maximum number of joints supported for skinned meshes when skins are stored
+/// in uniform buffers.
 ///
 /// It is used to allocate buffers.
-/// The correctness of the value depends on the GPU/platform.
 /// The current value is chosen because it is guaranteed to work everywhere.
-/// To allow for bigger values, a check must be made for the limits
-/// of the GPU at runtime, which would mean not using consts anymore.
+/// When storage buffers are available, the limit is instead determined by
+/// [`SkinSettings::max_joints`]; see [`max_joints_per_skin`].
 pub const MAX_JOINTS: usize = 256;
 
+/// Maximum number of joints that a single skinned mesh can ever reference.
+///
+/// [`Mesh::ATTRIBUTE_JOINT_INDEX`] stores joint indices as 16-bit unsigned
+/// integers, so no vertex can refer to a joint beyond this index.
+///
+/// [`Mesh::ATTRIBUTE_JOINT_INDEX`]: bevy_render::mesh::Mesh::ATTRIBUTE_JOINT_INDEX
+pub const MAX_STORAGE_BUFFER_JOINTS: usize = u16::MAX as usize + 1;
+
+/// Controls how many joints each skinned mesh can use.
+///
+/// On platforms where skins are stored in uniform buffers (e.g. WebGL 2), the
+/// limit is always [`MAX_JOINTS`], regardless of this setting, because the
+/// shader has to declare a fixed-size array. When storage buffers are
+/// available, [`SkinSettings::max_joints`] is used instead, clamped to
+/// [`MAX_STORAGE_BUFFER_JOINTS`].
+///
+/// Joints beyond the limit are ignored, and vertices that are influenced by
+/// them will not be skinned correctly.
+///
+/// This also controls when the [`SkinUniforms`] buffers give memory back after
+/// the number of visible skinned meshes dropped.
+#[derive(Resource, Clone, Debug, Reflect)]
+#[reflect(Resource, Debug, Default)]
+pub struct SkinSettings {
+    /// The maximum number of joints per skinned mesh on platforms that support
+    /// storage buffers.
+    pub max_joints: usize,
+    /// The fraction of the skin buffers that has to be in use for them to keep
+    /// their size.
+    ///
+    /// When less than this fraction of the joint matrices in the skin buffers
+    /// belong to visible skins for [`SkinSettings::shrink_delay_frames`]
+    /// consecutive frames, the skins are packed together at the start of the
+    /// buffers, and the buffers are reallocated at the smaller size. Set this
+    /// to zero to never shrink the buffers.
+    pub shrink_utilization_threshold: f32,
+    /// The number of consecutive frames the skin buffers have to stay below
+    /// [`SkinSettings::shrink_utilization_threshold`] before they're shrunk.
+    ///
+    /// This keeps the buffers from being reallocated over and over when
+    /// skinned meshes are repeatedly spawned and despawned.
+    pub shrink_delay_frames: u32,
+}
+
+impl Default for SkinSettings {
+    fn default() -> Self {
+        Self {
+            max_joints: MAX_STORAGE_BUFFER_JOINTS,
+            shrink_utilization_threshold: 0.5,
+            shrink_delay_frames: 300,
+        }
+    }
+}
+
+/// Returns the maximum number of joints that each skinned mesh can use on the
+/// current platform, given the [`SkinSettings`].
+pub fn max_joints_per_skin(skin_settings: &SkinSettings, render_device: &RenderDevice) -> usize {
+    if skins_use_uniform_buffers(render_device) {
+        MAX_JOINTS
+    } else {
+        skin_settings.max_joints.clamp(1, MAX_STORAGE_BUFFER_JOINTS)
+    }
+}
+
 /// The location of the first joint matrix in the skin uniform buffer.
 #[derive(Component)]
 pub struct SkinIndex {
@@ -67,17 +145,340 @@ pub struct SkinIndices {
 ///
 /// This is double-buffered: we store the joint matrices of each mesh for the
 /// previous frame in addition to those of each mesh for the current frame. This
-/// is for motion vector calculation. Every frame, we swap buffers and overwrite
-/// the joint matrix buffer from two frames ago with the data for the current
-/// frame.
+/// is for motion vector calculation. Each skin occupies the same region in both
+/// buffers and keeps it across frames, so that only the skins that changed need
+/// to be rewritten and uploaded; see `extract_skins`.
+///
+/// Each joint occupies one [`Mat4`]. For skins using
+/// [`SkinningMethod::LinearBlend`], that's the joint matrix itself. For skins
+/// using [`SkinningMethod::DualQuaternion`], the joint matrix is decomposed and
+/// packed as described in [`pack_dual_quaternion_joint`]; the mesh shader
+/// tells the two layouts apart by means of a mesh flag.
+///
+/// The buffers only grow while skins are being added. When they have been
+/// mostly empty for a while, as configured by [`SkinSettings`], the skins are
+/// moved together and the buffers are reallocated at the smaller size.
+///
+/// If the [`RenderDiagnosticsPlugin`] is added, the following values are
+/// recorded every frame:
+///
+/// * `render/skins/joints`: the number of joint matrices in use.
+/// * `render/skins/buffer_joints`: the number of joint matrices that
+///   [`SkinUniforms::current_buffer`] can hold.
+/// * `render/skins/buffer_utilization`: the percentage of the buffer in use.
 ///
 /// Notes on implementation: see comment on top of the `extract_skins` system.
+///
+/// [`RenderDiagnosticsPlugin`]: bevy_render::diagnostic::RenderDiagnosticsPlugin
 #[derive(Resource)]
 pub struct SkinUniforms {
     /// Stores all the joint matrices for skinned meshes in the current frame.
     pub current_buffer: RawBufferVec<Mat4>,
     /// Stores all the joint matrices for skinned meshes in the previous frame.
     pub prev_buffer: RawBufferVec<Mat4>,
+    /// Stores the world-space transforms of every joint in the current frame.
+    ///
+    /// This is only used when the joint matrices are precomputed on the GPU
+    /// (see [`skins_use_gpu_precompute`]). In that case, nothing is written
+    /// into [`SkinUniforms::current_buffer`] on the CPU; instead, a compute
+    /// shader fills it in by multiplying each joint transform by the entry of
+    /// [`SkinUniforms::inverse_bindposes`] that
+    /// [`SkinUniforms::joint_bindpose_indices`] points to.
+    #[cfg(feature = "gpu_skinning_precompute")]
+    pub joint_transforms: RawBufferVec<Mat4>,
+    /// Stores the inverse bindposes of every [`SkinnedMeshInverseBindposes`]
+    /// asset used by a visible skin.
+    ///
+    /// Each asset is uploaded once, the first time a skin uses it, and again
+    /// only if it's modified. The first entry is the identity matrix, used by
+    /// joints that were already multiplied by their inverse bindpose on the
+    /// CPU.
+    #[cfg(feature = "gpu_skinning_precompute")]
+    pub inverse_bindposes: RawBufferVec<Mat4>,
+    /// Stores the index in [`SkinUniforms::inverse_bindposes`] of the inverse
+    /// bindpose of every joint in the current frame, laid out identically to
+    /// [`SkinUniforms::joint_transforms`].
+    #[cfg(feature = "gpu_skinning_precompute")]
+    pub joint_bindpose_indices: RawBufferVec<u32>,
+    /// Tracks where each asset is stored in
+    /// [`SkinUniforms::inverse_bindposes`].
+    #[cfg(feature = "gpu_skinning_precompute")]
+    bindpose_allocator: BindposeAllocator,
+    /// Tracks which region of the buffers each skin occupies, and which
+    /// regions need to be uploaded this frame.
+    allocator: SkinAllocator,
+}
+
+/// The region of [`SkinUniforms::current_buffer`] and
+/// [`SkinUniforms::prev_buffer`] that a single skin occupies.
+struct SkinAllocation {
+    /// The index of the first joint matrix.
+    offset: usize,
+    /// The number of joint matrices reserved for this skin, including any
+    /// padding.
+    len: usize,
+    /// Whether the joint data was packed as dual quaternions.
+    dual_quaternion: bool,
+    /// The [`SkinnedMeshLod`] level the joint data was written for, if any.
+    lod_level: Option<usize>,
+    /// Whether the joint matrices in the current buffer differ from those in
+    /// the previous buffer, because the skin was written last frame.
+    current_differs_from_prev: bool,
+    /// The value of [`SkinAllocator::frame`] the last time this skin was
+    /// extracted.
+    last_seen_frame: u32,
+}
+
+/// Hands out stable regions of the skin buffers to skins, so that they don't
+/// have to be rewritten every frame.
+///
+/// Allocation is first-fit from a list of free ranges; freed ranges are merged
+/// with their neighbors. When too much of the space below
+/// [`SkinAllocator::end`] is free for too long, the allocations are compacted.
+#[derive(Default)]
+pub(crate) struct SkinAllocator {
+    /// The region each visible skin occupies.
+    allocations: MainEntityHashMap<SkinAllocation>,
+    /// Unused ranges below [`SkinAllocator::end`], sorted by start.
+    free_ranges: Vec<Range<usize>>,
+    /// One past the last allocated joint matrix, as of the last compaction.
+    end: usize,
+    /// The total length of all allocations.
+    allocated: usize,
+    /// The number of consecutive frames for which the utilization was below
+    /// [`SkinSettings::shrink_utilization_threshold`].
+    underutilized_frames: u32,
+    /// A counter incremented every frame, used to find skins that weren't
+    /// extracted.
+    frame: u32,
+    /// Ranges of [`SkinUniforms::current_buffer`] that must be uploaded.
+    dirty_current: Vec<Range<usize>>,
+    /// Ranges of [`SkinUniforms::prev_buffer`] that must be uploaded.
+    dirty_prev: Vec<Range<usize>>,
+}
+
+impl SkinAllocator {
+    fn begin_frame(&mut self) {
+        self.frame = self.frame.wrapping_add(1);
+    }
+
+    /// Returns the offset of the region for the given skin, allocating one if
+    /// the skin doesn't have a region of the right size yet.
+    ///
+    /// The second return value is true if the region is new.
+    fn allocate(&mut self, entity: MainEntity, len: usize) -> (usize, bool) {
+        if let Some(allocation) = self.allocations.get_mut(&entity) {
+            if allocation.len == len {
+                allocation.last_seen_frame = self.frame;
+                return (allocation.offset, false);
+            }
+            self.free(entity);
+        }
+
+        let offset = match self
+            .free_ranges
+            .iter()
+            .position(|free_range| free_range.len() >= len)
+        {
+            Some(index) => {
+                let free_range = &mut self.free_ranges[index];
+                let offset = free_range.start;
+                free_range.start += len;
+                if free_range.is_empty() {
+                    self.free_ranges.remove(index);
+                }
+                offset
+            }
+            None => {
+                let offset = self.end;
+                self.end += len;
+                offset
+            }
+        };
+
+        self.allocated += len;
+        self.allocations.insert(
+            entity,
+            SkinAllocation {
+                offset,
+                len,
+                dual_quaternion: false,
+                lod_level: None,
+                current_differs_from_prev: false,
+                last_seen_frame: self.frame,
+            },
+        );
+        (offset, true)
+    }
+
+    fn get_mut(&mut self, entity: MainEntity) -> &mut SkinAllocation {
+        self.allocations
+            .get_mut(&entity)
+            .expect("skin should have been allocated")
+    }
+
+    /// Releases the region of the given skin, if it has one.
+    fn free(&mut self, entity: MainEntity) {
+        let Some(allocation) = self.allocations.remove(&entity) else {
+            return;
+        };
+        self.allocated -= allocation.len;
+        self.free_range(allocation.offset..(allocation.offset + allocation.len));
+    }
+
+    fn free_range(&mut self, range: Range<usize>) {
+        if range.is_empty() {
+            return;
+        }
+        let index = self
+            .free_ranges
+            .partition_point(|free_range| free_range.start < range.start);
+        self.free_ranges.insert(index, range);
+
+        // Merge with the next range, then with the previous one.
+        if index + 1 < self.free_ranges.len()
+            && self.free_ranges[index].end == self.free_ranges[index + 1].start
+        {
+            let next = self.free_ranges.remove(index + 1);
+            self.free_ranges[index].end = next.end;
+        }
+        if index > 0 && self.free_ranges[index - 1].end == self.free_ranges[index].start {
+            let this = self.free_ranges.remove(index);
+            self.free_ranges[index - 1].end = this.end;
+        }
+    }
+
+    /// Releases the regions of all skins that weren't seen this frame.
+    fn end_frame(&mut self) {
+        let frame = self.frame;
+        let mut freed = vec![];
+        self.allocations.retain(|_, allocation| {
+            let keep = allocation.last_seen_frame == frame;
+            if !keep {
+                freed.push(allocation.offset..(allocation.offset + allocation.len));
+            }
+            keep
+        });
+        for range in freed {
+            self.allocated -= range.len();
+            self.free_range(range);
+        }
+    }
+
+    /// One past the last allocated joint matrix, as of the last compaction.
+    fn end(&self) -> usize {
+        self.end
+    }
+
+    /// Updates how long the buffers have been underutilized, and returns true
+    /// if they should be shrunk.
+    fn track_utilization(&mut self, skin_settings: &SkinSettings) -> bool {
+        // Don't bother shrinking by less than a binding's worth of joints.
+        let underutilized = self.end - self.allocated >= MAX_JOINTS
+            && (self.allocated as f32)
+                < self.end as f32 * skin_settings.shrink_utilization_threshold;
+        if underutilized {
+            self.underutilized_frames = self.underutilized_frames.saturating_add(1);
+        } else {
+            self.underutilized_frames = 0;
+        }
+        underutilized && self.underutilized_frames >= skin_settings.shrink_delay_frames
+    }
+
+    /// Moves all allocations to the start of the buffers, in order, closing
+    /// the gaps between them, and truncates the buffers to fit.
+    fn compact(&mut self, mut buffers: [&mut Vec<Mat4>; 2]) {
+        let mut allocations: Vec<_> = self.allocations.values_mut().collect();
+        allocations.sort_unstable_by_key(|allocation| allocation.offset);
+
+        // Allocations only ever move down, so, going in order, we never
+        // overwrite an allocation that hasn't been moved yet.
+        let mut end = 0;
+        for allocation in allocations {
+            let range = allocation.offset..(allocation.offset + allocation.len);
+            for buffer in &mut buffers {
+                buffer.copy_within(range.clone(), end);
+            }
+            allocation.offset = end;
+            end += allocation.len;
+        }
+
+        // Keep a full binding of padding after the last allocation.
+        for buffer in buffers {
+            buffer.truncate(end + MAX_JOINTS);
+        }
+
+        self.free_ranges.clear();
+        self.end = end;
+        self.underutilized_frames = 0;
+    }
+}
+
+/// Stores each [`SkinnedMeshInverseBindposes`] asset once in
+/// [`SkinUniforms::inverse_bindposes`], for the GPU joint matrix precomputation.
+///
+/// Assets are appended when a skin first uses them. Modified and removed assets
+/// leave a stale region behind, and the buffer is rebuilt from the assets still
+/// in use once more of it is stale than live.
+#[cfg(feature = "gpu_skinning_precompute")]
+#[derive(Default)]
+struct BindposeAllocator {
+    /// The region of the buffer each asset occupies.
+    ranges: HashMap<AssetId<SkinnedMeshInverseBindposes>, Range<usize>>,
+    /// The number of inverse bindposes of assets that were modified or
+    /// removed since they were written.
+    stale: usize,
+    /// Ranges of [`SkinUniforms::inverse_bindposes`] that must be uploaded.
+    dirty: Vec<Range<usize>>,
+}
+
+#[cfg(feature = "gpu_skinning_precompute")]
+impl BindposeAllocator {
+    /// Forgets the assets that were modified or removed, so that they're
+    /// written again the next time a skin uses them.
+    fn handle_events<'a>(
+        &mut self,
+        events: impl Iterator<Item = &'a AssetEvent<SkinnedMeshInverseBindposes>>,
+        buffer: &mut RawBufferVec<Mat4>,
+    ) {
+        for event in events {
+            if let AssetEvent::Modified { id } | AssetEvent::Removed { id } = event {
+                if let Some(range) = self.ranges.remove(id) {
+                    self.stale += range.len();
+                }
+            }
+        }
+
+        if buffer.is_empty() || self.stale > buffer.len() - self.stale {
+            buffer.clear();
+            buffer.push(Mat4::IDENTITY);
+            self.ranges.clear();
+            self.stale = 0;
+            // The buffer shrank, so upload it in full.
+            buffer.discard_buffer();
+            self.dirty.clear();
+        }
+    }
+
+    /// Returns the offset of the inverse bindposes of the given asset in the
+    /// buffer, writing them if they aren't there yet.
+    fn offset(
+        &mut self,
+        id: AssetId<SkinnedMeshInverseBindposes>,
+        inverse_bindposes: &SkinnedMeshInverseBindposes,
+        buffer: &mut RawBufferVec<Mat4>,
+    ) -> usize {
+        self.ranges
+            .entry(id)
+            .or_insert_with(|| {
+                let start = buffer.len();
+                buffer.values_mut().extend_from_slice(inverse_bindposes);
+                let range = start..buffer.len();
+                self.dirty.push(range.clone());
+                range
+            })
+            .start
+    }
 }
 
 impl FromWorld for SkinUniforms {
@@ -100,6 +501,27 @@ impl FromWorld for SkinUniforms {
                 buffer.set_label(Some("SkinUniforms::prev_buffer"));
                 buffer
             },
+            #[cfg(feature = "gpu_skinning_precompute")]
+            joint_transforms: {
+                let mut buffer = RawBufferVec::new(BufferUsages::STORAGE);
+                buffer.set_label(Some("SkinUniforms::joint_transforms"));
+                buffer
+            },
+            #[cfg(feature = "gpu_skinning_precompute")]
+            inverse_bindposes: {
+                let mut buffer = RawBufferVec::new(BufferUsages::STORAGE);
+                buffer.set_label(Some("SkinUniforms::inverse_bindposes"));
+                buffer
+            },
+            #[cfg(feature = "gpu_skinning_precompute")]
+            joint_bindpose_indices: {
+                let mut buffer = RawBufferVec::new(BufferUsages::STORAGE);
+                buffer.set_label(Some("SkinUniforms::joint_bindpose_indices"));
+                buffer
+            },
+            #[cfg(feature = "gpu_skinning_precompute")]
+            bindpose_allocator: BindposeAllocator::default(),
+            allocator: SkinAllocator::default(),
         }
     }
 }
@@ -112,23 +534,234 @@ pub fn skins_use_uniform_buffers(render_device: &RenderDevice) -> bool {
         .get_or_init(|| render_device.limits().max_storage_buffers_per_shader_stage == 0)
 }
 
+/// Returns true if joint matrices are computed on the GPU from the raw joint
+/// transforms and inverse bindposes, instead of on the CPU during extraction.
+///
+/// This requires the `gpu_skinning_precompute` feature, as well as storage
+/// buffer support, since the compute shader writes the joint matrices directly
+/// into the storage buffer that the mesh shaders read from.
+pub fn skins_use_gpu_precompute(render_device: &RenderDevice) -> bool {
+    cfg!(feature = "gpu_skinning_precompute") && !skins_use_uniform_buffers(render_device)
+}
+
+/// Returns true if the vertices of skinned meshes are skinned once per frame in
+/// a compute shader, instead of in the vertex shader of every pass that draws
+/// them.
+///
+/// This requires the `gpu_preskinning` feature, as well as storage buffer
+/// support, since the vertex shaders read the pre-skinned vertices from a
+/// storage buffer. Even then, only some skinned meshes are eligible; see
+/// `preskinning.rs`.
+pub fn skins_use_preskinning(render_device: &RenderDevice) -> bool {
+    cfg!(feature = "gpu_preskinning") && !skins_use_uniform_buffers(render_device)
+}
+
+/// Packs a joint matrix into the dual quaternion layout used by
+/// [`SkinningMethod::DualQuaternion`].
+///
+/// The columns of the returned matrix are, in order:
+///
+/// 1. The real part of the dual quaternion (the joint rotation).
+/// 2. The dual part of the dual quaternion (which encodes the joint
+///    translation).
+/// 3. The joint scale, in `xyz`.
+/// 4. Unused, and set to zero.
+///
+/// The mesh shader blends the dual quaternions and the scales of all joints
+/// influencing each vertex, and then converts the result back to a matrix.
+pub fn pack_dual_quaternion_joint(joint_matrix: Mat4) -> Mat4 {
+    let (scale, rotation, translation) = joint_matrix.to_scale_rotation_translation();
+    let dual = Quat::from_xyzw(translation.x, translation.y, translation.z, 0.0) * rotation * 0.5;
+    Mat4::from_cols(
+        Vec4::from(rotation),
+        Vec4::from(dual),
+        scale.extend(0.0),
+        Vec4::ZERO,
+    )
+}
+
+/// The components needed to pick the [`SkinnedMeshLod`] level of a skinned
+/// mesh.
+type SkinLodQueryData = (
+    &'static SkinnedMeshLod,
+    Option<&'static Aabb>,
+    Option<&'static VisibilityRange>,
+);
+
+/// Returns the [`SkinnedMeshLod`] level that the given skinned mesh should use,
+/// based on its distance to the closest of the given camera positions.
+fn select_skin_lod<'a>(
+    entity: Entity,
+    lods: &'a Query<SkinLodQueryData>,
+    transforms: &Query<Ref<GlobalTransform>>,
+    view_positions: &[Vec3A],
+) -> Option<(usize, &'a SkinnedMeshLodLevel)> {
+    let (lod, aabb, visibility_range) = lods.get(entity).ok()?;
+    let transform = transforms.get(entity).ok()?;
+
+    // Measure the distance like `check_visibility_ranges` does.
+    let position = match (visibility_range.is_some_and(|range| range.use_aabb), aabb) {
+        (true, Some(aabb)) => transform.affine().transform_point3a(aabb.center),
+        _ => transform.translation_vec3a(),
+    };
+    let distance = view_positions
+        .iter()
+        .map(|view_position| view_position.distance(position))
+        .reduce(f32::min)?;
+
+    let level = lod.level_at(distance)?;
+    Some((level, &lod.levels[level]))
+}
+
+/// Returns the positions of all active cameras, or nothing if no skinned mesh
+/// has a [`SkinnedMeshLod`].
+fn skin_lod_view_positions(
+    lods: &Query<SkinLodQueryData>,
+    views: &Query<(&GlobalTransform, &Camera)>,
+) -> Vec<Vec3A> {
+    if lods.is_empty() {
+        return vec![];
+    }
+    views
+        .iter()
+        .filter(|(_, camera)| camera.is_active)
+        .map(|(transform, _)| transform.translation_vec3a())
+        .collect()
+}
+
 pub fn prepare_skins(
     render_device: Res<RenderDevice>,
     render_queue: Res<RenderQueue>,
     mut uniform: ResMut<SkinUniforms>,
+    diagnostics: Option<Res<DiagnosticsRecorder>>,
 ) {
+    #[cfg(feature = "gpu_skinning_precompute")]
+    if skins_use_gpu_precompute(&render_device) {
+        if uniform.joint_transforms.is_empty() {
+            return;
+        }
+
+        // Upload the inputs to the compute shader, and make sure that the
+        // output buffer is big enough. The compute shader will fill in the
+        // output buffer later; see `precompute_skins`.
+        let len = uniform.joint_transforms.len();
+        uniform.joint_transforms.reserve(len, &render_device);
+        uniform
+            .joint_transforms
+            .write_buffer(&render_device, &render_queue);
+        uniform
+            .joint_bindpose_indices
+            .write_buffer(&render_device, &render_queue);
+        let uniform = uniform.into_inner();
+        write_dirty_ranges(
+            &mut uniform.inverse_bindposes,
+            &mut uniform.bindpose_allocator.dirty,
+            &render_device,
+            &render_queue,
+        );
+        uniform.current_buffer.reserve(len, &render_device);
+        record_skin_diagnostics(
+            diagnostics.as_deref(),
+            len,
+            uniform.current_buffer.capacity(),
+        );
+        return;
+    }
+
     if uniform.current_buffer.is_empty() {
         return;
     }
 
-    let len = uniform.current_buffer.len();
-    uniform.current_buffer.reserve(len, &render_device);
-    uniform
-        .current_buffer
-        .write_buffer(&render_device, &render_queue);
+    // Only upload the regions that changed this frame. The rest of the data is
+    // still on the GPU from previous frames.
+    let SkinUniforms {
+        current_buffer,
+        prev_buffer,
+        allocator,
+        ..
+    } = uniform.into_inner();
+    write_dirty_ranges(
+        current_buffer,
+        &mut allocator.dirty_current,
+        &render_device,
+        &render_queue,
+    );
+    write_dirty_ranges(
+        prev_buffer,
+        &mut allocator.dirty_prev,
+        &render_device,
+        &render_queue,
+    );
+    record_skin_diagnostics(
+        diagnostics.as_deref(),
+        allocator.allocated,
+        current_buffer.capacity(),
+    );
+}
+
+fn record_skin_diagnostics(
+    diagnostics: Option<&DiagnosticsRecorder>,
+    joints: usize,
+    buffer_joints: usize,
+) {
+    let Some(diagnostics) = diagnostics else {
+        return;
+    };
+    diagnostics.record_value("skins/joints", "", joints as f64);
+    diagnostics.record_value("skins/buffer_joints", "", buffer_joints as f64);
+    if buffer_joints > 0 {
+        diagnostics.record_value(
+            "skins/buffer_utilization",
+            "%",
+            joints as f64 / buffer_joints as f64 * 100.0,
+        );
+    }
+}
+
+/// Uploads the given ranges of `buffer` to the GPU, or the whole buffer if it
+/// had to be reallocated.
+fn write_dirty_ranges(
+    buffer: &mut RawBufferVec<Mat4>,
+    dirty_ranges: &mut Vec<Range<usize>>,
+    render_device: &RenderDevice,
+    render_queue: &RenderQueue,
+) {
+    let old_capacity = buffer.capacity();
+    buffer.reserve(buffer.len(), render_device);
+    if buffer.capacity() != old_capacity {
+        buffer.write_buffer(render_device, render_queue);
+        dirty_ranges.clear();
+        return;
+    }
+
+    let Some(gpu_buffer) = buffer.buffer() else {
+        dirty_ranges.clear();
+        return;
+    };
 
-    // We don't need to write `uniform.prev_buffer` because we already wrote it
-    // last frame, and the data should still be on the GPU.
+    let write_range = |range: Range<usize>| {
+        render_queue.write_buffer(
+            gpu_buffer,
+            (range.start * size_of::<Mat4>()) as u64,
+            bytemuck::must_cast_slice(&buffer.values()[range]),
+        );
+    };
+
+    // Coalesce overlapping and adjacent ranges so that we issue as few writes
+    // as possible.
+    dirty_ranges.sort_unstable_by_key(|range| range.start);
+    let mut dirty_ranges = dirty_ranges.drain(..);
+    let Some(mut pending_range) = dirty_ranges.next() else {
+        return;
+    };
+    for range in dirty_ranges {
+        if range.start <= pending_range.end {
+            pending_range.end = pending_range.end.max(range.end);
+        } else {
+            write_range(mem::replace(&mut pending_range, range));
+        }
+    }
+    write_range(pending_range);
 }
 
 // Notes on implementation:
@@ -157,71 +790,348 @@ pub fn prepare_skins(
 // In this way, we can pack ‘variable sized arrays’ into uniform buffer bindings
 // which normally only support fixed size arrays. You just have to make sure
 // in the shader that you only read the values that are valid for that binding.
+//
+// Each skin keeps the region it was allocated for as long as it stays visible
+// and its joint count doesn't change, and the region is at the same offset in
+// both `current_buffer` and `prev_buffer`. This lets us skip recomputing and
+// reuploading the joint matrices of skins that haven't changed: only skins
+// with a changed joint `GlobalTransform` (or a changed `SkinnedMesh` or
+// `SkinningMethod`) are rewritten, and only their regions are uploaded.
+//
+// Regions freed by skins that disappear are reused by new skins, but the
+// buffers never shrink on their own. Instead, once the buffers have been
+// underutilized for long enough (see `SkinSettings`), all skins are moved to
+// the start of the buffers and the GPU buffers are reallocated at the smaller
+// size.
+//
+// Skins with a `SkinnedMeshLod` use the joint palette of the level matching
+// their distance to the closest camera: each joint matrix slot is filled with
+// the matrix of the joint it's remapped to, and only the joints that the
+// palette actually uses are checked for changes. Every slot is still uploaded,
+// because the vertices of the mesh index the full palette.
 pub fn extract_skins(
     skin_indices: ResMut<SkinIndices>,
     uniform: ResMut<SkinUniforms>,
-    query: Extract<Query<(Entity, &ViewVisibility, &SkinnedMesh)>>,
+    query: Extract<
+        Query<(
+            Entity,
+            &ViewVisibility,
+            Ref<SkinnedMesh>,
+            Option<&SkinningMethod>,
+        )>,
+    >,
     inverse_bindposes: Extract<Res<Assets<SkinnedMeshInverseBindposes>>>,
-    joints: Extract<Query<&GlobalTransform>>,
+    #[cfg(feature = "gpu_skinning_precompute")] mut inverse_bindpose_events: Extract<
+        EventReader<AssetEvent<SkinnedMeshInverseBindposes>>,
+    >,
+    joints: Extract<Query<Ref<GlobalTransform>>>,
+    lods: Extract<Query<SkinLodQueryData>>,
+    views: Extract<Query<(&GlobalTransform, &Camera)>>,
+    skin_settings: Extract<Res<SkinSettings>>,
     render_device: Res<RenderDevice>,
 ) {
     let skins_use_uniform_buffers = skins_use_uniform_buffers(&render_device);
+    let max_joints = max_joints_per_skin(&skin_settings, &render_device);
+    let view_positions = skin_lod_view_positions(&lods, &views);
 
     // Borrow check workaround.
     let (skin_indices, uniform) = (skin_indices.into_inner(), uniform.into_inner());
 
-    // Swap buffers. We need to keep the previous frame's buffer around for the
-    // purposes of motion vector computation.
-    mem::swap(&mut skin_indices.current, &mut skin_indices.prev);
-    mem::swap(&mut uniform.current_buffer, &mut uniform.prev_buffer);
+    #[cfg(feature = "gpu_skinning_precompute")]
+    if skins_use_gpu_precompute(&render_device) {
+        let SkinUniforms {
+            inverse_bindposes: inverse_bindpose_buffer,
+            bindpose_allocator,
+            ..
+        } = &mut *uniform;
+        bindpose_allocator.handle_events(inverse_bindpose_events.read(), inverse_bindpose_buffer);
+        extract_skins_for_gpu_precompute(
+            skin_indices,
+            uniform,
+            &query,
+            &inverse_bindposes,
+            &joints,
+            &lods,
+            &view_positions,
+            max_joints,
+        );
+        return;
+    }
+
     skin_indices.current.clear();
-    uniform.current_buffer.clear();
+    skin_indices.prev.clear();
 
-    let mut last_start = 0;
+    let SkinUniforms {
+        current_buffer,
+        prev_buffer,
+        allocator,
+        ..
+    } = uniform;
+    allocator.begin_frame();
 
     // PERF: This can be expensive, can we move this to prepare?
-    for (entity, view_visibility, skin) in &query {
+    for (entity, view_visibility, skin, skinning_method) in &query {
         if !view_visibility.get() {
             continue;
         }
-        let buffer = &mut uniform.current_buffer;
+        let main_entity = MainEntity::from(entity);
+        let dual_quaternion = skinning_method == Some(&SkinningMethod::DualQuaternion);
         let Some(inverse_bindposes) = inverse_bindposes.get(&skin.inverse_bindposes) else {
+            allocator.free(main_entity);
             continue;
         };
-        let start = buffer.len();
-
-        let target = start + skin.joints.len().min(MAX_JOINTS);
-        buffer.extend(
-            joints
-                .iter_many(&skin.joints)
-                .zip(inverse_bindposes.iter())
-                .take(MAX_JOINTS)
-                .map(|(joint, bindpose)| joint.affine() * *bindpose),
-        );
-        // iter_many will skip any failed fetches. This will cause it to assign the wrong bones,
-        // so just bail by truncating to the start.
-        if buffer.len() != target {
-            buffer.truncate(start);
+        let lod = select_skin_lod(entity, &lods, &joints, &view_positions);
+        let lod_level = lod.map(|(level, _)| level);
+        let palette = lod.map(|(_, palette)| palette);
+
+        // Check that all the joints exist, and whether any of them moved.
+        // iter_many will skip any failed fetches. This would cause it to assign
+        // the wrong bones, so just bail if that happens.
+        let joint_count = skin.joints.len().min(max_joints);
+        let mut fetched_joint_count = 0;
+        let mut joints_changed = false;
+        for joint in joints.iter_many(&skin.joints).take(max_joints) {
+            fetched_joint_count += 1;
+            joints_changed |= joint.is_changed();
+        }
+        if fetched_joint_count != joint_count || inverse_bindposes.len() < joint_count {
+            allocator.free(main_entity);
             continue;
         }
-        last_start = last_start.max(start);
+
+        // Joints that the palette maps away don't affect the joint matrices.
+        if let (true, Some(palette)) = (joints_changed, palette) {
+            joints_changed = (0..joint_count).any(|index| {
+                joints
+                    .get(skin.joints[palette.remapped_joint(index, joint_count)])
+                    .is_ok_and(|joint| joint.is_changed())
+            });
+        }
 
         // Pad to 256 byte alignment if we're using a uniform buffer.
         // There's no need to do this if we're using storage buffers, though.
-        if skins_use_uniform_buffers {
-            while buffer.len() % 4 != 0 {
-                buffer.push(Mat4::ZERO);
+        let allocation_len = if skins_use_uniform_buffers {
+            joint_count.next_multiple_of(4)
+        } else {
+            joint_count
+        };
+
+        let (start, is_new) = allocator.allocate(main_entity, allocation_len);
+        let range = start..(start + joint_count);
+
+        let allocation = allocator.get_mut(main_entity);
+        let dirty = is_new
+            || joints_changed
+            || skin.is_changed()
+            || allocation.dual_quaternion != dual_quaternion
+            || allocation.lod_level != lod_level;
+        let prev_needs_sync = !dirty && allocation.current_differs_from_prev;
+        allocation.dual_quaternion = dual_quaternion;
+        allocation.lod_level = lod_level;
+        allocation.current_differs_from_prev = dirty;
+
+        // Make sure the buffers are big enough, keeping a full binding of
+        // padding after the last allocation.
+        let required_len = allocator.end() + MAX_JOINTS;
+        if current_buffer.len() < required_len {
+            current_buffer.values_mut().resize(required_len, Mat4::ZERO);
+            prev_buffer.values_mut().resize(required_len, Mat4::ZERO);
+        }
+
+        if dirty {
+            // Save the joint matrices from the last frame for motion vectors
+            // before overwriting them.
+            if !is_new {
+                prev_buffer.values_mut()[range.clone()]
+                    .copy_from_slice(&current_buffer.values()[range.clone()]);
+                allocator.dirty_prev.push(range.clone());
+            }
+
+            let pack = |matrix: Mat4| {
+                if dual_quaternion {
+                    pack_dual_quaternion_joint(matrix)
+                } else {
+                    matrix
+                }
+            };
+            let joint_matrices = &mut current_buffer.values_mut()[range.clone()];
+            match palette {
+                None => {
+                    for (joint_matrix, (joint, bindpose)) in joint_matrices
+                        .iter_mut()
+                        .zip(joints.iter_many(&skin.joints).zip(inverse_bindposes.iter()))
+                    {
+                        *joint_matrix = pack(joint.affine() * *bindpose);
+                    }
+                }
+                Some(palette) => {
+                    for (index, joint_matrix) in joint_matrices.iter_mut().enumerate() {
+                        let source = palette.remapped_joint(index, joint_count);
+                        if let Ok(joint) = joints.get(skin.joints[source]) {
+                            *joint_matrix = pack(joint.affine() * inverse_bindposes[source]);
+                        }
+                    }
+                }
+            }
+            if is_new {
+                // There are no joint matrices from last frame, so reuse this frame's ones for
+                // the skins that are pre-skinned, which read the previous joints at the same
+                // index. Otherwise they'd report motion from stale matrices.
+                prev_buffer.values_mut()[range.clone()]
+                    .copy_from_slice(&current_buffer.values()[range.clone()]);
+                allocator.dirty_prev.push(range.clone());
+            }
+            allocator.dirty_current.push(range);
+        } else if prev_needs_sync {
+            // The skin stopped moving, so last frame's joint matrices, which
+            // are still in the current buffer, are now the previous ones too.
+            prev_buffer.values_mut()[range.clone()]
+                .copy_from_slice(&current_buffer.values()[range.clone()]);
+            allocator.dirty_prev.push(range);
+        }
+
+        skin_indices
+            .current
+            .insert(main_entity, SkinIndex::new(start));
+        if !is_new {
+            skin_indices.prev.insert(main_entity, SkinIndex::new(start));
+        }
+    }
+
+    // Release the regions of skins that are no longer visible.
+    allocator.end_frame();
+
+    // Give memory back if the buffers have been mostly empty for a while.
+    if allocator.track_utilization(&skin_settings) {
+        allocator.compact([current_buffer.values_mut(), prev_buffer.values_mut()]);
+        for (entity, allocation) in &allocator.allocations {
+            if let Some(skin_index) = skin_indices.current.get_mut(entity) {
+                *skin_index = SkinIndex::new(allocation.offset);
+            }
+            if let Some(skin_index) = skin_indices.prev.get_mut(entity) {
+                *skin_index = SkinIndex::new(allocation.offset);
+            }
+        }
+
+        // The new buffers will be uploaded in full.
+        current_buffer.discard_buffer();
+        prev_buffer.discard_buffer();
+        allocator.dirty_current.clear();
+        allocator.dirty_prev.clear();
+    }
+
+    // Pad out the buffer to ensure that there's enough space for bindings,
+    // even when there are no skins.
+    if current_buffer.len() < MAX_JOINTS {
+        current_buffer.values_mut().resize(MAX_JOINTS, Mat4::ZERO);
+        prev_buffer.values_mut().resize(MAX_JOINTS, Mat4::ZERO);
+        allocator.dirty_current.push(0..MAX_JOINTS);
+        allocator.dirty_prev.push(0..MAX_JOINTS);
+    }
+}
+
+/// Like [`extract_skins`], but writes the raw joint transforms, and the index of
+/// the inverse bindpose of each joint, instead of the final joint matrices.
+///
+/// The multiplication is deferred to the `skin_precompute.wgsl` compute shader,
+/// which writes its output to [`SkinUniforms::current_buffer`] at the same
+/// offsets. This path is only used with storage buffers, so no padding between
+/// skins is necessary.
+#[cfg(feature = "gpu_skinning_precompute")]
+fn extract_skins_for_gpu_precompute(
+    skin_indices: &mut SkinIndices,
+    uniform: &mut SkinUniforms,
+    query: &Query<(
+        Entity,
+        &ViewVisibility,
+        Ref<SkinnedMesh>,
+        Option<&SkinningMethod>,
+    )>,
+    inverse_bindposes: &Assets<SkinnedMeshInverseBindposes>,
+    joints: &Query<Ref<GlobalTransform>>,
+    lods: &Query<SkinLodQueryData>,
+    view_positions: &[Vec3A],
+    max_joints: usize,
+) {
+    // The compute shader rewrites every joint matrix each frame anyway, so
+    // there's no point in tracking which skins changed. Instead, swap buffers,
+    // keeping the previous frame's buffer around for the purposes of motion
+    // vector computation.
+    mem::swap(&mut skin_indices.current, &mut skin_indices.prev);
+    mem::swap(&mut uniform.current_buffer, &mut uniform.prev_buffer);
+    skin_indices.current.clear();
+    uniform.joint_transforms.clear();
+    uniform.joint_bindpose_indices.clear();
+
+    let mut last_start = 0;
+
+    for (entity, view_visibility, skin, skinning_method) in query {
+        if !view_visibility.get() {
+            continue;
+        }
+        let dual_quaternion = skinning_method == Some(&SkinningMethod::DualQuaternion);
+        let Some(skin_inverse_bindposes) = inverse_bindposes.get(&skin.inverse_bindposes) else {
+            continue;
+        };
+        let start = uniform.joint_transforms.len();
+        let palette =
+            select_skin_lod(entity, lods, joints, view_positions).map(|(_, palette)| palette);
+        let bindpose_offset = uniform.bindpose_allocator.offset(
+            skin.inverse_bindposes.id(),
+            skin_inverse_bindposes,
+            &mut uniform.inverse_bindposes,
+        );
+
+        let joint_count = skin.joints.len().min(max_joints);
+        let target = start + joint_count;
+        for (index, joint) in joints
+            .iter_many(&skin.joints)
+            .take(max_joints.min(skin_inverse_bindposes.len()))
+            .enumerate()
+        {
+            // Substitute the joint that the LOD palette maps this one to.
+            let (joint, bindpose_index) = match palette {
+                Some(palette) => {
+                    let source = palette.remapped_joint(index, joint_count);
+                    match joints.get(skin.joints[source]) {
+                        Ok(joint) if source < skin_inverse_bindposes.len() => (joint, source),
+                        _ => (joint, index),
+                    }
+                }
+                None => (joint, index),
+            };
+            if dual_quaternion {
+                // The dual quaternion decomposition can't be done on the GPU
+                // by a simple matrix multiplication, so pack the joint on the
+                // CPU and have the compute shader multiply it by the identity.
+                uniform.joint_transforms.push(pack_dual_quaternion_joint(
+                    joint.affine() * skin_inverse_bindposes[bindpose_index],
+                ));
+                uniform.joint_bindpose_indices.push(0);
+            } else {
+                uniform.joint_transforms.push(Mat4::from(joint.affine()));
+                uniform
+                    .joint_bindpose_indices
+                    .push((bindpose_offset + bindpose_index) as u32);
             }
         }
+        // As above, bail if any of the joints failed to be fetched.
+        if uniform.joint_transforms.len() != target {
+            uniform.joint_transforms.truncate(start);
+            uniform.joint_bindpose_indices.truncate(start);
+            continue;
+        }
+        last_start = last_start.max(start);
 
         skin_indices
             .current
             .insert(entity.into(), SkinIndex::new(start));
     }
 
-    // Pad out the buffer to ensure that there's enough space for bindings
-    while uniform.current_buffer.len() - last_start < MAX_JOINTS {
-        uniform.current_buffer.push(Mat4::ZERO);
+    // Pad out the buffers to ensure that there's enough space for bindings.
+    while uniform.joint_transforms.len() - last_start < MAX_JOINTS {
+        uniform.joint_transforms.push(Mat4::ZERO);
+        uniform.joint_bindpose_indices.push(0);
     }
 }
 
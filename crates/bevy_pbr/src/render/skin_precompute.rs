@@ -0,0 +1,173 @@
+//! GPU precomputation of joint matrices.
+//!
+//! When the `gpu_skinning_precompute` feature is enabled and storage buffers
+//! are available, [`extract_skins`] uploads the raw joint transforms, and the
+//! index of the inverse bindpose of each joint, instead of the final joint
+//! matrices. The inverse bindposes themselves are only uploaded once per
+//! asset. The compute shader in this module multiplies them together on the
+//! GPU and writes the result to
+//! [`SkinUniforms::current_buffer`], which the mesh shaders then read from as
+//! usual.
+//!
+//! [`extract_skins`]: super::skin::extract_skins
+
+use core::num::NonZeroU64;
+
+use bevy_asset::Handle;
+use bevy_ecs::{
+    system::{Res, Resource},
+    world::{FromWorld, World},
+};
+use bevy_math::Mat4;
+use bevy_render::{
+    render_resource::{
+        binding_types::{storage_buffer_read_only_sized, storage_buffer_sized},
+        BindGroupEntries, BindGroupLayout, BindGroupLayoutEntries, BufferBinding,
+        CachedComputePipelineId, CommandEncoderDescriptor, ComputePassDescriptor,
+        ComputePipelineDescriptor, PipelineCache, Shader, ShaderStages,
+    },
+    renderer::{RenderDevice, RenderQueue},
+};
+
+use super::skin::SkinUniforms;
+
+/// The handle to the `skin_precompute.wgsl` compute shader.
+pub const SKIN_PRECOMPUTE_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(8395977386814177967);
+
+/// The GPU workgroup size.
+const WORKGROUP_SIZE: usize = 64;
+
+/// The compute pipeline that multiplies joint transforms by inverse bindposes.
+#[derive(Resource)]
+pub struct SkinPrecomputePipeline {
+    /// The bind group layout for the compute shader.
+    pub bind_group_layout: BindGroupLayout,
+    /// The pipeline ID for the compute shader.
+    pub pipeline_id: CachedComputePipelineId,
+}
+
+impl FromWorld for SkinPrecomputePipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let bind_group_layout = render_device.create_bind_group_layout(
+            "skin precompute bind group layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    // `joint_transforms`
+                    storage_buffer_read_only_sized(false, None),
+                    // `inverse_bindposes`
+                    storage_buffer_read_only_sized(false, None),
+                    // `joint_matrices`
+                    storage_buffer_sized(false, None),
+                    // `joint_bindpose_indices`
+                    storage_buffer_read_only_sized(false, None),
+                ),
+            ),
+        );
+
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let pipeline_id = pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+            label: Some("skin precompute pipeline".into()),
+            layout: vec![bind_group_layout.clone()],
+            push_constant_ranges: vec![],
+            shader: SKIN_PRECOMPUTE_SHADER_HANDLE,
+            shader_defs: vec![],
+            entry_point: "main".into(),
+            zero_initialize_workgroup_memory: false,
+        });
+
+        Self {
+            bind_group_layout,
+            pipeline_id,
+        }
+    }
+}
+
+/// Dispatches the compute shader that fills in
+/// [`SkinUniforms::current_buffer`] from the joint transforms and inverse
+/// bindposes uploaded in [`super::skin::prepare_skins`].
+///
+/// This is submitted on its own command buffer, ahead of the render graph, so
+/// that the joint matrices are ready for every pass that draws skinned meshes
+/// (shadows, prepasses and the main pass alike) without tying the compute work
+/// to any particular view.
+pub fn precompute_skins(
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    pipeline_cache: Res<PipelineCache>,
+    skin_precompute_pipeline: Res<SkinPrecomputePipeline>,
+    uniform: Res<SkinUniforms>,
+) {
+    let joint_count = uniform.joint_transforms.len();
+    if joint_count == 0 {
+        return;
+    }
+
+    let (
+        Some(joint_transforms),
+        Some(inverse_bindposes),
+        Some(joint_matrices),
+        Some(joint_bindpose_indices),
+    ) = (
+        uniform.joint_transforms.buffer(),
+        uniform.inverse_bindposes.buffer(),
+        uniform.current_buffer.buffer(),
+        uniform.joint_bindpose_indices.buffer(),
+    )
+    else {
+        return;
+    };
+
+    let Some(pipeline) = pipeline_cache.get_compute_pipeline(skin_precompute_pipeline.pipeline_id)
+    else {
+        // This will happen while the pipeline is being compiled and is fine.
+        return;
+    };
+
+    // Bind only the joints that were written this frame, so that the shader
+    // doesn't process stale data at the end of the buffers.
+    let size = NonZeroU64::new((joint_count * size_of::<Mat4>()) as u64);
+    let index_size = NonZeroU64::new((joint_count * size_of::<u32>()) as u64);
+    let bind_group = render_device.create_bind_group(
+        "skin precompute bind group",
+        &skin_precompute_pipeline.bind_group_layout,
+        &BindGroupEntries::sequential((
+            BufferBinding {
+                buffer: joint_transforms,
+                offset: 0,
+                size,
+            },
+            inverse_bindposes.as_entire_buffer_binding(),
+            BufferBinding {
+                buffer: joint_matrices,
+                offset: 0,
+                size,
+            },
+            BufferBinding {
+                buffer: joint_bindpose_indices,
+                offset: 0,
+                size: index_size,
+            },
+        )),
+    );
+
+    let mut command_encoder = render_device.create_command_encoder(&CommandEncoderDescriptor {
+        label: Some("skin precompute command encoder"),
+    });
+
+    {
+        let mut compute_pass = command_encoder.begin_compute_pass(&ComputePassDescriptor {
+            label: Some("skin precompute"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(pipeline);
+        compute_pass.set_bind_group(0, &bind_group, &[]);
+        let workgroup_count = joint_count.div_ceil(WORKGROUP_SIZE);
+        compute_pass.dispatch_workgroups(workgroup_count as u32, 1, 1);
+    }
+
+    render_queue.submit([command_encoder.finish()]);
+}
@@ -0,0 +1,29 @@
+// GPU joint matrix precomputation.
+//
+// This is a compute shader that multiplies the world-space transform of every
+// joint by the inverse bindpose of that joint, producing the joint matrices
+// that `skinning.wgsl` reads. Running this on the GPU avoids having to perform
+// the multiplication on the CPU for every visible skinned mesh every frame
+// during extraction.
+
+// The world-space transform of each joint.
+@group(0) @binding(0) var<storage> joint_transforms: array<mat4x4<f32>>;
+// The inverse bindposes of every skin asset, each stored once.
+@group(0) @binding(1) var<storage> inverse_bindposes: array<mat4x4<f32>>;
+// The output joint matrices, laid out identically to `joint_transforms`.
+@group(0) @binding(2) var<storage, read_write> joint_matrices: array<mat4x4<f32>>;
+// The index in `inverse_bindposes` of the inverse bindpose of each joint, laid
+// out identically to `joint_transforms`.
+@group(0) @binding(3) var<storage> joint_bindpose_indices: array<u32>;
+
+@compute
+@workgroup_size(64)
+fn main(@builtin(global_invocation_id) global_invocation_id: vec3<u32>) {
+    let joint_index = global_invocation_id.x;
+    if (joint_index >= arrayLength(&joint_transforms)) {
+        return;
+    }
+
+    let inverse_bindpose = inverse_bindposes[joint_bindpose_indices[joint_index]];
+    joint_matrices[joint_index] = joint_transforms[joint_index] * inverse_bindpose;
+}
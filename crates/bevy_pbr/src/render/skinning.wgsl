@@ -1,6 +1,6 @@
 #define_import_path bevy_pbr::skinning
 
-#import bevy_pbr::mesh_types::SkinnedMesh
+#import bevy_pbr::mesh_types::{SkinnedMesh, MESH_FLAGS_DUAL_QUATERNION_SKINNING_BIT}
 #import bevy_pbr::mesh_bindings::mesh
 
 #ifdef SKINNED
@@ -23,22 +23,64 @@
 @group(1) @binding(6) var<storage> prev_joint_matrices: array<mat4x4<f32>>;
 #endif  // SKINS_USE_UNIFORM_BUFFERS
 
+#ifdef PRESKINNED
+// A vertex skinned by the `preskinning.wgsl` compute shader. Everything is in
+// world space.
+struct PreskinnedVertex {
+    position: vec4<f32>,
+    // The position of the vertex skinned with the previous frame's joint
+    // matrices, for motion vectors.
+    previous_position: vec4<f32>,
+    normal: vec4<f32>,
+    tangent: vec4<f32>,
+}
+
+// The index of the first pre-skinned vertex of each mesh, indexed by the
+// mesh's `current_skin_index`.
+@group(1) @binding(8) var<storage> preskinned_vertex_offsets: array<u32>;
+@group(1) @binding(9) var<storage> preskinned_vertices: array<PreskinnedVertex>;
+
+// Returns the pre-skinned version of the vertex with the given index.
+fn preskinned_vertex(vertex_index: u32, instance_index: u32) -> PreskinnedVertex {
+    let skin_index = mesh[instance_index].current_skin_index;
+    let first_vertex = mesh[instance_index].first_vertex_index;
+    return preskinned_vertices[preskinned_vertex_offsets[skin_index] + vertex_index - first_vertex];
+}
+
+// The pre-skinned vertices are already in world space, so this stands in for
+// the model matrix.
+const PRESKINNED_WORLD_FROM_LOCAL: mat4x4<f32> = mat4x4<f32>(
+    vec4<f32>(1.0, 0.0, 0.0, 0.0),
+    vec4<f32>(0.0, 1.0, 0.0, 0.0),
+    vec4<f32>(0.0, 0.0, 1.0, 0.0),
+    vec4<f32>(0.0, 0.0, 0.0, 1.0),
+);
+#endif  // PRESKINNED
+
 fn skin_model(
     indexes: vec4<u32>,
     weights: vec4<f32>,
     instance_index: u32,
 ) -> mat4x4<f32> {
 #ifdef SKINS_USE_UNIFORM_BUFFERS
-    return weights.x * joint_matrices.data[indexes.x]
-        + weights.y * joint_matrices.data[indexes.y]
-        + weights.z * joint_matrices.data[indexes.z]
-        + weights.w * joint_matrices.data[indexes.w];
+    return blend_joints(
+        joint_matrices.data[indexes.x],
+        joint_matrices.data[indexes.y],
+        joint_matrices.data[indexes.z],
+        joint_matrices.data[indexes.w],
+        weights,
+        instance_index,
+    );
 #else   // SKINS_USE_UNIFORM_BUFFERS
     let skin_index = mesh[instance_index].current_skin_index;
-    return weights.x * joint_matrices[skin_index + indexes.x]
-        + weights.y * joint_matrices[skin_index + indexes.y]
-        + weights.z * joint_matrices[skin_index + indexes.z]
-        + weights.w * joint_matrices[skin_index + indexes.w];
+    return blend_joints(
+        joint_matrices[skin_index + indexes.x],
+        joint_matrices[skin_index + indexes.y],
+        joint_matrices[skin_index + indexes.z],
+        joint_matrices[skin_index + indexes.w],
+        weights,
+        instance_index,
+    );
 #endif  // SKINS_USE_UNIFORM_BUFFERS
 }
 
@@ -52,19 +94,104 @@ fn skin_prev_model(
     instance_index: u32,
 ) -> mat4x4<f32> {
 #ifdef SKINS_USE_UNIFORM_BUFFERS
-    return weights.x * prev_joint_matrices.data[indexes.x]
-        + weights.y * prev_joint_matrices.data[indexes.y]
-        + weights.z * prev_joint_matrices.data[indexes.z]
-        + weights.w * prev_joint_matrices.data[indexes.w];
+    return blend_joints(
+        prev_joint_matrices.data[indexes.x],
+        prev_joint_matrices.data[indexes.y],
+        prev_joint_matrices.data[indexes.z],
+        prev_joint_matrices.data[indexes.w],
+        weights,
+        instance_index,
+    );
 #else   // SKINS_USE_UNIFORM_BUFFERS
     let skin_index = mesh[instance_index].previous_skin_index;
-    return weights.x * prev_joint_matrices[skin_index + indexes.x]
-        + weights.y * prev_joint_matrices[skin_index + indexes.y]
-        + weights.z * prev_joint_matrices[skin_index + indexes.z]
-        + weights.w * prev_joint_matrices[skin_index + indexes.w];
+    return blend_joints(
+        prev_joint_matrices[skin_index + indexes.x],
+        prev_joint_matrices[skin_index + indexes.y],
+        prev_joint_matrices[skin_index + indexes.z],
+        prev_joint_matrices[skin_index + indexes.w],
+        weights,
+        instance_index,
+    );
 #endif  // SKINS_USE_UNIFORM_BUFFERS
 }
 
+// Blends the four joints influencing a vertex, using either linear blend
+// skinning or dual quaternion skinning depending on the mesh flags.
+fn blend_joints(
+    joint_x: mat4x4<f32>,
+    joint_y: mat4x4<f32>,
+    joint_z: mat4x4<f32>,
+    joint_w: mat4x4<f32>,
+    weights: vec4<f32>,
+    instance_index: u32,
+) -> mat4x4<f32> {
+    if ((mesh[instance_index].flags & MESH_FLAGS_DUAL_QUATERNION_SKINNING_BIT) != 0u) {
+        return blend_dual_quaternion_joints(joint_x, joint_y, joint_z, joint_w, weights);
+    }
+    return weights.x * joint_x
+        + weights.y * joint_y
+        + weights.z * joint_z
+        + weights.w * joint_w;
+}
+
+// Blends four joints packed as dual quaternions and returns the resulting
+// matrix.
+//
+// See `pack_dual_quaternion_joint` in `skin.rs` for the packing: the first
+// column is the real part, the second column is the dual part, and the third
+// column holds the scale.
+fn blend_dual_quaternion_joints(
+    joint_x: mat4x4<f32>,
+    joint_y: mat4x4<f32>,
+    joint_z: mat4x4<f32>,
+    joint_w: mat4x4<f32>,
+    weights: vec4<f32>,
+) -> mat4x4<f32> {
+    // `q` and `-q` represent the same rotation, so flip the sign of any dual
+    // quaternion that lies in the opposite hemisphere from the first one.
+    // Otherwise blending takes the long way around.
+    let pivot = joint_x[0];
+    let weight_x = weights.x;
+    let weight_y = select(weights.y, -weights.y, dot(pivot, joint_y[0]) < 0.0);
+    let weight_z = select(weights.z, -weights.z, dot(pivot, joint_z[0]) < 0.0);
+    let weight_w = select(weights.w, -weights.w, dot(pivot, joint_w[0]) < 0.0);
+
+    var real = weight_x * joint_x[0] + weight_y * joint_y[0] + weight_z * joint_z[0] +
+        weight_w * joint_w[0];
+    var dual = weight_x * joint_x[1] + weight_y * joint_y[1] + weight_z * joint_z[1] +
+        weight_w * joint_w[1];
+    let scale = weights.x * joint_x[2].xyz + weights.y * joint_y[2].xyz +
+        weights.z * joint_z[2].xyz + weights.w * joint_w[2].xyz;
+
+    let inverse_length = inverseSqrt(dot(real, real));
+    real *= inverse_length;
+    dual *= inverse_length;
+
+    return dual_quaternion_to_matrix(real, dual, scale);
+}
+
+// Converts a unit dual quaternion, plus a scale that's applied first, to an
+// affine matrix.
+fn dual_quaternion_to_matrix(real: vec4<f32>, dual: vec4<f32>, scale: vec3<f32>) -> mat4x4<f32> {
+    let x = real.x;
+    let y = real.y;
+    let z = real.z;
+    let w = real.w;
+
+    // translation = 2 * dual * conjugate(real)
+    let translation = 2.0 * (real.w * dual.xyz - dual.w * real.xyz + cross(real.xyz, dual.xyz));
+
+    return mat4x4<f32>(
+        vec4<f32>(1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y + w * z), 2.0 * (x * z - w * y), 0.0)
+            * scale.x,
+        vec4<f32>(2.0 * (x * y - w * z), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z + w * x), 0.0)
+            * scale.y,
+        vec4<f32>(2.0 * (x * z + w * y), 2.0 * (y * z - w * x), 1.0 - 2.0 * (x * x + y * y), 0.0)
+            * scale.z,
+        vec4<f32>(translation, 1.0),
+    );
+}
+
 fn inverse_transpose_3x3m(in: mat3x3<f32>) -> mat3x3<f32> {
     let x = cross(in[1], in[2]);
     let y = cross(in[2], in[0]);
@@ -0,0 +1,601 @@
+//! GPU-driven scattering of instances over surfaces.
+//!
+//! A [`ScatterLayer`] covers a mesh or a heightmap with instances of another
+//! mesh, such as blades of grass, flowers or rocks. The instances are placed
+//! by a compute shader, at random points of the surface whose density follows
+//! an optional density map, so that millions of instances can be placed
+//! without involving the CPU or the ECS. Each frame, another compute shader
+//! culls the instances outside the view frustum, too far from the camera, or,
+//! for cameras with [`OcclusionCulling`](crate::OcclusionCulling), hidden
+//! behind the depth prepass. The remaining instances are drawn with a single
+//! indirect draw call per layer and view.
+//!
+//! The instances are placed again whenever the layer, its surface or its
+//! instance mesh change. Scattered instances don't cast shadows, and don't
+//! render to the prepasses. Scattering relies on compute shaders, so it isn't
+//! supported on WebGL 2.
+
+mod render;
+
+use alloc::sync::Arc;
+
+use bevy_app::{App, Plugin, PostUpdate};
+use bevy_asset::{load_internal_asset, AssetEvent, AssetId, Assets, Handle};
+use bevy_core_pipeline::core_3d::{
+    graph::{Core3d, Node3d},
+    AlphaMask3d, Opaque3d, Transparent3d,
+};
+use bevy_ecs::prelude::*;
+use bevy_image::Image;
+use bevy_math::{UVec2, Vec2, Vec3};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::{
+    mesh::{Mesh, MeshAabb, PrimitiveTopology, VertexAttributeValues},
+    primitives::Aabb,
+    render_graph::{RenderGraph, RenderGraphApp, ViewNodeRunner},
+    render_phase::AddRenderCommand,
+    render_resource::{Shader, SpecializedMeshPipelines},
+    sync_component::SyncComponentPlugin,
+    view::Visibility,
+    ExtractSchedule, Render, RenderApp, RenderSet,
+};
+use bevy_transform::components::Transform;
+use bevy_utils::HashSet;
+use bytemuck::{Pod, Zeroable};
+use thiserror::Error;
+
+use crate::{
+    graph::NodePbr,
+    terrain::{Heightmap, Terrain},
+    StandardMaterial,
+};
+
+use render::{
+    extract_scatter_layers, prepare_scatter_layers, prepare_scatter_views, queue_scatter_layers,
+    DrawScatterLayer, ScatterComputePipelines, ScatterCullNode, ScatterDrawPipeline,
+    ScatterGenerateLabel, ScatterGenerateNode, ScatterGenerations, ScatterMaterials,
+    ScatterViewBuffers, SCATTER_CULL_SHADER_HANDLE, SCATTER_GENERATE_SHADER_HANDLE,
+    SCATTER_SHADER_HANDLE, SCATTER_TYPES_SHADER_HANDLE,
+};
+
+/// Adds support for [`ScatterLayer`]s.
+pub struct ScatterPlugin;
+
+impl Plugin for ScatterPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            SCATTER_GENERATE_SHADER_HANDLE,
+            "scatter_generate.wgsl",
+            Shader::from_wgsl
+        );
+        load_internal_asset!(
+            app,
+            SCATTER_CULL_SHADER_HANDLE,
+            "scatter_cull.wgsl",
+            Shader::from_wgsl
+        );
+        load_internal_asset!(
+            app,
+            SCATTER_SHADER_HANDLE,
+            "scatter.wgsl",
+            Shader::from_wgsl
+        );
+        load_internal_asset!(
+            app,
+            SCATTER_TYPES_SHADER_HANDLE,
+            "scatter_types.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.register_type::<ScatterLayer>()
+            .add_plugins(SyncComponentPlugin::<ScatterLayer>::default())
+            .add_systems(PostUpdate, update_scatter_surfaces);
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .init_resource::<ScatterGenerations>()
+            .init_resource::<ScatterViewBuffers>()
+            .init_resource::<ScatterMaterials>()
+            .init_resource::<SpecializedMeshPipelines<ScatterDrawPipeline>>()
+            .add_render_command::<Opaque3d, DrawScatterLayer>()
+            .add_render_command::<AlphaMask3d, DrawScatterLayer>()
+            .add_render_command::<Transparent3d, DrawScatterLayer>()
+            .add_systems(ExtractSchedule, extract_scatter_layers)
+            .add_systems(
+                Render,
+                (
+                    queue_scatter_layers.in_set(RenderSet::Queue),
+                    prepare_scatter_layers.in_set(RenderSet::PrepareResources),
+                    prepare_scatter_views.in_set(RenderSet::PrepareBindGroups),
+                ),
+            )
+            .add_render_graph_node::<ViewNodeRunner<ScatterCullNode>>(Core3d, NodePbr::Scatter)
+            .add_render_graph_edges(
+                Core3d,
+                // The instances are culled against the depth prepass, before
+                // any of the main passes draws them.
+                (
+                    Node3d::EndPrepasses,
+                    NodePbr::Scatter,
+                    Node3d::StartMainPass,
+                ),
+            );
+
+        let mut render_graph = render_app.world_mut().resource_mut::<RenderGraph>();
+        render_graph.add_node(ScatterGenerateLabel, ScatterGenerateNode);
+        render_graph.add_node_edge(ScatterGenerateLabel, bevy_render::graph::CameraDriverLabel);
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .init_resource::<ScatterComputePipelines>()
+            .init_resource::<ScatterDrawPipeline>();
+
+        // With GPU preprocessing, the depth pyramid is only complete once the
+        // depth of the late prepass is downsampled.
+        let mut render_graph = render_app.world_mut().resource_mut::<RenderGraph>();
+        if let Some(core_3d) = render_graph.get_sub_graph_mut(Core3d) {
+            if core_3d.get_node_state(NodePbr::DownsampleDepth).is_ok() {
+                core_3d.add_node_edge(NodePbr::DownsampleDepth, NodePbr::Scatter);
+            }
+        }
+    }
+}
+
+/// Scatters instances of a mesh over a surface.
+///
+/// The instances are placed at random over the [`ScatterLayer::surface`],
+/// which is in the local space of this entity, so the layer is usually added to
+/// the entity of the mesh or [`Terrain`] it covers. They are rotated to stand
+/// on the surface, spun around their up axis at random, and scaled by a random
+/// factor.
+///
+/// The meshes of the surface and of the instances must be kept in the main
+/// world, with [`RenderAssetUsages::MAIN_WORLD`](bevy_render::render_asset::RenderAssetUsages::MAIN_WORLD),
+/// as the instances are placed relative to their vertices.
+///
+/// The instances are lit like meshes with the [`ScatterLayer::material`], from
+/// which only the uniform properties and the base color texture are used. Any
+/// alpha mode other than [`AlphaMode::Opaque`](bevy_render::alpha::AlphaMode::Opaque)
+/// is treated as [`AlphaMode::Mask`](bevy_render::alpha::AlphaMode::Mask),
+/// which suits foliage.
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component, Default, Debug)]
+#[require(Transform, Visibility)]
+pub struct ScatterLayer {
+    /// The mesh of each instance.
+    ///
+    /// The instance's origin is placed on the surface, with its +Y axis
+    /// pointing away from it.
+    pub mesh: Handle<Mesh>,
+
+    /// The material of the instances.
+    pub material: Handle<StandardMaterial>,
+
+    /// The surface that the instances are scattered over.
+    pub surface: ScatterSurface,
+
+    /// The average number of instances per square unit of the surface, where
+    /// the [`ScatterLayer::density_map`] is white.
+    ///
+    /// Defaults to 1.
+    pub density: f32,
+
+    /// A texture stretched over the UVs of the surface, whose red channel is
+    /// the fraction of the [`ScatterLayer::density`] at each point.
+    ///
+    /// Heightmap surfaces have UVs from (0, 0) at their -X -Z corner to (1, 1)
+    /// at their +X +Z corner. Defaults to `None`, which scatters the
+    /// instances evenly.
+    pub density_map: Option<Handle<Image>>,
+
+    /// The smallest random scale of the instances.
+    ///
+    /// Defaults to 0.8.
+    pub min_scale: f32,
+
+    /// The largest random scale of the instances.
+    ///
+    /// Defaults to 1.2.
+    pub max_scale: f32,
+
+    /// How much the instances lean along the normal of the surface, from 0,
+    /// where they stand upright along the +Y axis of this entity, to 1, where
+    /// they stand perpendicular to the surface.
+    ///
+    /// Defaults to 1.
+    pub align_to_normal: f32,
+
+    /// The maximum number of instances of this layer.
+    ///
+    /// Defaults to 1048576.
+    pub max_instances: u32,
+
+    /// The distance from the camera beyond which the instances aren't drawn.
+    ///
+    /// Defaults to 200.
+    pub max_distance: f32,
+
+    /// The seed of the random placement of the instances. Layers with the same
+    /// settings and seed place their instances at the same points.
+    ///
+    /// Defaults to 0.
+    pub seed: u32,
+}
+
+impl Default for ScatterLayer {
+    fn default() -> Self {
+        Self {
+            mesh: Handle::default(),
+            material: Handle::default(),
+            surface: ScatterSurface::default(),
+            density: 1.0,
+            density_map: None,
+            min_scale: 0.8,
+            max_scale: 1.2,
+            align_to_normal: 1.0,
+            max_instances: 1 << 20,
+            max_distance: 200.0,
+            seed: 0,
+        }
+    }
+}
+
+/// The surface that a [`ScatterLayer`] places its instances on.
+#[derive(Clone, Debug, Reflect)]
+#[reflect(Default, Debug)]
+pub enum ScatterSurface {
+    /// The triangles of a mesh with a [`PrimitiveTopology::TriangleList`]
+    /// topology.
+    ///
+    /// The normals and the first UVs of the mesh are used if it has them.
+    Mesh(Handle<Mesh>),
+
+    /// A heightmap centered on the origin, laid out like a [`Terrain`].
+    Heightmap {
+        /// The heights of the surface.
+        heightmap: Handle<Heightmap>,
+        /// The extent of the surface along the X and Z axes.
+        size: Vec2,
+        /// The height of the surface where the heightmap is 1.
+        height: f32,
+    },
+}
+
+impl Default for ScatterSurface {
+    fn default() -> Self {
+        Self::Mesh(Handle::default())
+    }
+}
+
+impl From<&Terrain> for ScatterSurface {
+    fn from(terrain: &Terrain) -> Self {
+        Self::Heightmap {
+            heightmap: terrain.heightmap.clone(),
+            size: terrain.size,
+            height: terrain.height,
+        }
+    }
+}
+
+/// The data of a [`ScatterLayer`] that its compute shaders need, gathered
+/// from its assets whenever they change.
+#[derive(Component, Clone)]
+pub(crate) struct ScatterSurfaceData {
+    /// The geometry of the surface.
+    pub(crate) geometry: Arc<ScatterSurfaceGeometry>,
+    /// The area of the surface, in square units.
+    pub(crate) area: f32,
+    /// The bounding box of the instance mesh.
+    pub(crate) instance_aabb: Aabb,
+    /// Increased every time the instances need to be placed again.
+    pub(crate) revision: u32,
+}
+
+/// The geometry of a [`ScatterSurface`], as uploaded to the GPU.
+pub(crate) enum ScatterSurfaceGeometry {
+    Triangles {
+        vertices: Vec<ScatterSurfaceVertex>,
+        indices: Vec<u32>,
+        /// The sum of the areas of each triangle and the ones before it,
+        /// divided by the area of the surface.
+        cumulative_areas: Vec<f32>,
+    },
+    Heightmap {
+        /// The number of columns and rows of the heightmap.
+        grid_size: UVec2,
+        heights: Vec<f32>,
+        size: Vec2,
+        height: f32,
+    },
+}
+
+/// The reasons why a mesh can't be used as a [`ScatterSurface`].
+#[derive(Error, Debug, PartialEq)]
+pub(crate) enum ScatterSurfaceError {
+    #[error("the surface mesh isn't a triangle list")]
+    UnsupportedTopology,
+    #[error("the surface mesh has no positions")]
+    MissingPositions,
+    #[error("the surface mesh has {count} {attribute}, but {vertex_count} positions")]
+    AttributeCountMismatch {
+        attribute: &'static str,
+        count: usize,
+        vertex_count: usize,
+    },
+    #[error("the surface mesh has an index out of bounds")]
+    InvalidIndex,
+    #[error("the surface mesh has no area")]
+    NoArea,
+}
+
+/// A vertex of a mesh surface, as uploaded to the GPU.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub(crate) struct ScatterSurfaceVertex {
+    position: Vec3,
+    u: f32,
+    normal: Vec3,
+    v: f32,
+}
+
+impl ScatterSurfaceGeometry {
+    /// Gathers the triangles of a mesh, and returns them along with the area
+    /// of the mesh.
+    fn from_mesh(mesh: &Mesh) -> Result<(Self, f32), ScatterSurfaceError> {
+        if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+            return Err(ScatterSurfaceError::UnsupportedTopology);
+        }
+        let positions = mesh
+            .attribute(Mesh::ATTRIBUTE_POSITION)
+            .and_then(VertexAttributeValues::as_float3)
+            .ok_or(ScatterSurfaceError::MissingPositions)?;
+        let normals = match mesh.attribute(Mesh::ATTRIBUTE_NORMAL) {
+            Some(VertexAttributeValues::Float32x3(normals)) => Some(normals),
+            _ => None,
+        };
+        let uvs = match mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
+            Some(VertexAttributeValues::Float32x2(uvs)) => Some(uvs),
+            _ => None,
+        };
+        for (attribute, count) in [
+            ("normals", normals.map(Vec::len)),
+            ("UVs", uvs.map(Vec::len)),
+        ] {
+            if let Some(count) = count.filter(|&count| count != positions.len()) {
+                return Err(ScatterSurfaceError::AttributeCountMismatch {
+                    attribute,
+                    count,
+                    vertex_count: positions.len(),
+                });
+            }
+        }
+        let indices: Vec<u32> = match mesh.indices() {
+            Some(indices) => indices.iter().map(|index| index as u32).collect(),
+            None => (0..positions.len() as u32).collect(),
+        };
+        if indices
+            .iter()
+            .any(|&index| index as usize >= positions.len())
+        {
+            return Err(ScatterSurfaceError::InvalidIndex);
+        }
+
+        let mut vertices: Vec<_> = positions
+            .iter()
+            .enumerate()
+            .map(|(index, &position)| {
+                let uv = uvs.map_or([0.0; 2], |uvs| uvs[index]);
+                ScatterSurfaceVertex {
+                    position: position.into(),
+                    u: uv[0],
+                    normal: normals.map_or(Vec3::ZERO, |normals| normals[index].into()),
+                    v: uv[1],
+                }
+            })
+            .collect();
+
+        let mut area = 0.0;
+        let mut cumulative_areas = Vec::with_capacity(indices.len() / 3);
+        for triangle in indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|corner| vertices[triangle[corner] as usize].position);
+            let cross = (b - a).cross(c - a);
+            area += 0.5 * cross.length();
+            cumulative_areas.push(area);
+
+            // Meshes without normals get the normals of their faces.
+            if normals.is_none() {
+                for &index in triangle {
+                    vertices[index as usize].normal = cross.normalize_or_zero();
+                }
+            }
+        }
+        if area <= 0.0 {
+            return Err(ScatterSurfaceError::NoArea);
+        }
+        for cumulative_area in &mut cumulative_areas {
+            *cumulative_area /= area;
+        }
+
+        Ok((
+            Self::Triangles {
+                vertices,
+                indices,
+                cumulative_areas,
+            },
+            area,
+        ))
+    }
+
+    /// Gathers the heights of a heightmap surface, and returns them along
+    /// with the area of the surface, ignoring its slopes.
+    fn from_heightmap(heightmap: &Heightmap, size: Vec2, height: f32) -> (Self, f32) {
+        (
+            Self::Heightmap {
+                grid_size: heightmap.size(),
+                heights: heightmap.heights().to_vec(),
+                size,
+                height,
+            },
+            size.x * size.y,
+        )
+    }
+}
+
+/// Gathers the data of the [`ScatterLayer`]s whose settings or assets changed.
+///
+/// Layers whose surface mesh can't be used are reported once, and skipped
+/// until the layer or its meshes change.
+fn update_scatter_surfaces(
+    mut commands: Commands,
+    mut invalid_surfaces: Local<HashSet<Entity>>,
+    layers: Query<(Entity, Ref<ScatterLayer>, Option<&ScatterSurfaceData>)>,
+    mut mesh_events: EventReader<AssetEvent<Mesh>>,
+    mut heightmap_events: EventReader<AssetEvent<Heightmap>>,
+    meshes: Res<Assets<Mesh>>,
+    heightmaps: Res<Assets<Heightmap>>,
+) {
+    let modified_meshes: HashSet<AssetId<Mesh>> = mesh_events
+        .read()
+        .filter_map(|event| match *event {
+            AssetEvent::Modified { id } | AssetEvent::Removed { id } => Some(id),
+            _ => None,
+        })
+        .collect();
+    let modified_heightmaps: HashSet<AssetId<Heightmap>> = heightmap_events
+        .read()
+        .filter_map(|event| match *event {
+            AssetEvent::Modified { id } | AssetEvent::Removed { id } => Some(id),
+            _ => None,
+        })
+        .collect();
+    invalid_surfaces.retain(|&entity| layers.contains(entity));
+
+    for (entity, layer, data) in &layers {
+        let surface_modified = match &layer.surface {
+            ScatterSurface::Mesh(mesh) => modified_meshes.contains(&mesh.id()),
+            ScatterSurface::Heightmap { heightmap, .. } => {
+                modified_heightmaps.contains(&heightmap.id())
+            }
+        };
+        let changed =
+            layer.is_changed() || surface_modified || modified_meshes.contains(&layer.mesh.id());
+        if changed {
+            invalid_surfaces.remove(&entity);
+        } else if data.is_some() || invalid_surfaces.contains(&entity) {
+            continue;
+        }
+
+        // Try again on the next frame if the assets aren't loaded yet.
+        let geometry = match &layer.surface {
+            ScatterSurface::Mesh(mesh) => {
+                match meshes.get(mesh).map(ScatterSurfaceGeometry::from_mesh) {
+                    Some(Ok(geometry)) => Some(geometry),
+                    Some(Err(err)) => {
+                        tracing::warn!("Can't scatter instances over the mesh of {entity}: {err}");
+                        invalid_surfaces.insert(entity);
+                        None
+                    }
+                    None => None,
+                }
+            }
+            ScatterSurface::Heightmap {
+                heightmap,
+                size,
+                height,
+            } => heightmaps
+                .get(heightmap)
+                .map(|heightmap| ScatterSurfaceGeometry::from_heightmap(heightmap, *size, *height)),
+        };
+        let instance_aabb = meshes.get(&layer.mesh).and_then(MeshAabb::compute_aabb);
+        let (Some((geometry, area)), Some(instance_aabb)) = (geometry, instance_aabb) else {
+            if data.is_some() {
+                commands.entity(entity).remove::<ScatterSurfaceData>();
+            }
+            continue;
+        };
+
+        commands.entity(entity).insert(ScatterSurfaceData {
+            geometry: Arc::new(geometry),
+            area,
+            instance_aabb,
+            revision: data.map_or(0, |data| data.revision.wrapping_add(1)),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_math::{primitives::Plane3d, UVec2, Vec2, Vec3};
+    use bevy_render::mesh::Mesh;
+
+    use super::{ScatterSurface, ScatterSurfaceError, ScatterSurfaceGeometry};
+    use crate::terrain::{Heightmap, Terrain};
+
+    #[test]
+    fn mesh_surface_areas() {
+        let mesh = Mesh::from(Plane3d::new(Vec3::Y, Vec2::splat(2.0)));
+        let (geometry, area) = ScatterSurfaceGeometry::from_mesh(&mesh).unwrap();
+        assert!((area - 16.0).abs() < 1e-4);
+
+        let ScatterSurfaceGeometry::Triangles {
+            cumulative_areas, ..
+        } = geometry
+        else {
+            panic!("a mesh surface should have triangles");
+        };
+        assert_eq!(cumulative_areas.len(), 2);
+        assert!((cumulative_areas[0] - 0.5).abs() < 1e-6);
+        assert!((cumulative_areas[1] - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn mesh_surface_attribute_counts() {
+        let mut mesh = Mesh::from(Plane3d::new(Vec3::Y, Vec2::splat(2.0)));
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0, 0.0]; 3]);
+        assert_eq!(
+            ScatterSurfaceGeometry::from_mesh(&mesh).err(),
+            Some(ScatterSurfaceError::AttributeCountMismatch {
+                attribute: "UVs",
+                count: 3,
+                vertex_count: 4,
+            })
+        );
+
+        mesh.remove_attribute(Mesh::ATTRIBUTE_UV_0);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 1.0, 0.0]; 5]);
+        assert_eq!(
+            ScatterSurfaceGeometry::from_mesh(&mesh).err(),
+            Some(ScatterSurfaceError::AttributeCountMismatch {
+                attribute: "normals",
+                count: 5,
+                vertex_count: 4,
+            })
+        );
+    }
+
+    #[test]
+    fn terrain_surface() {
+        let heightmap = Heightmap::new(UVec2::splat(2), vec![0.0; 4]).unwrap();
+        let terrain = Terrain {
+            size: Vec2::new(100.0, 50.0),
+            height: 10.0,
+            ..Terrain::default()
+        };
+        let ScatterSurface::Heightmap { size, height, .. } = ScatterSurface::from(&terrain) else {
+            panic!("a terrain should be a heightmap surface");
+        };
+        assert_eq!((size, height), (terrain.size, terrain.height));
+
+        let (_, area) = ScatterSurfaceGeometry::from_heightmap(&heightmap, size, height);
+        assert_eq!(area, 5000.0);
+    }
+}
@@ -0,0 +1,1265 @@
+//! The render world side of [`ScatterLayer`]s: placing, culling and drawing
+//! their instances.
+
+use bevy_asset::{AssetEvent, AssetId, Assets, Handle};
+use bevy_core_pipeline::{
+    core_3d::{
+        AlphaMask3d, Camera3d, Opaque3d, Opaque3dBatchSetKey, Opaque3dBinKey, Transparent3d,
+    },
+    oit::{OrderIndependentTransparencyMethod, OrderIndependentTransparencySettings},
+    prepass::{
+        DeferredPrepass, DepthPrepass, MotionVectorPrepass, NormalPrepass,
+        OpaqueNoLightmap3dBatchSetKey, OpaqueNoLightmap3dBinKey, ViewPrepassTextures,
+    },
+    tonemapping::{DebandDither, Tonemapping},
+};
+use bevy_ecs::{
+    entity::EntityHashMap,
+    prelude::*,
+    query::QueryItem,
+    system::{
+        lifetimeless::{Read, SRes},
+        SystemParamItem,
+    },
+};
+use bevy_image::Image;
+use bevy_math::{Mat3, Mat4, UVec2, Vec2, Vec3, Vec4};
+use bevy_render::{
+    alpha::AlphaMode,
+    camera::Projection,
+    mesh::{
+        allocator::MeshAllocator, Mesh, MeshVertexBufferLayoutRef, RenderMesh, RenderMeshBufferInfo,
+    },
+    primitives::Frustum,
+    render_asset::RenderAssets,
+    render_graph::{Node, NodeRunError, RenderGraphContext, RenderLabel, ViewNode},
+    render_phase::{
+        BinnedRenderPhaseType, DrawFunctions, PhaseItem, PhaseItemExtraIndex, RenderCommand,
+        RenderCommandResult, SetItemPipeline, TrackedRenderPass, ViewBinnedRenderPhases,
+        ViewSortedRenderPhases,
+    },
+    render_resource::{
+        binding_types::{
+            sampler, storage_buffer_read_only_sized, storage_buffer_sized, texture_2d,
+            uniform_buffer,
+        },
+        AsBindGroupShaderType, BindGroup, BindGroupEntries, BindGroupLayout,
+        BindGroupLayoutEntries, Buffer, BufferDescriptor, BufferInitDescriptor, BufferUsages,
+        CachedComputePipelineId, ComputePassDescriptor, ComputePipelineDescriptor, Face,
+        PipelineCache, RenderPipelineDescriptor, SamplerBindingType, Shader, ShaderStages,
+        ShaderType, SpecializedMeshPipeline, SpecializedMeshPipelineError,
+        SpecializedMeshPipelines, TextureSampleType, UniformBuffer,
+    },
+    renderer::{RenderContext, RenderDevice, RenderQueue},
+    sync_world::{MainEntity, RenderEntity},
+    texture::{FallbackImage, GpuImage},
+    view::{ExtractedView, InheritedVisibility, Msaa},
+    Extract,
+};
+use bevy_transform::components::GlobalTransform;
+use bevy_utils::{default, HashMap, HashSet};
+use bytemuck::cast_slice;
+
+use super::{ScatterLayer, ScatterSurfaceData, ScatterSurfaceGeometry};
+use crate::{
+    alpha_mode_pipeline_key, irradiance_volume::IrradianceVolume, tonemapping_pipeline_key,
+    EnvironmentMapLight, MeshPipeline, MeshPipelineKey, RenderViewLightProbes,
+    ScreenSpaceAmbientOcclusion, ScreenSpaceGlobalIllumination, SetMeshViewBindGroup,
+    ShadowFilteringMethod, StandardMaterial, StandardMaterialUniform, ViewDepthPyramid,
+};
+
+/// The handle to the compute shader that places the instances.
+pub(super) const SCATTER_GENERATE_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(13184902933251627130);
+/// The handle to the compute shader that culls the instances.
+pub(super) const SCATTER_CULL_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(6447603213724015863);
+/// The handle to the shader that draws the instances.
+pub(super) const SCATTER_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(1533942810076322749);
+/// The handle to the types shared by the scattering shaders.
+pub(super) const SCATTER_TYPES_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(15818631009266551441);
+
+/// The workgroup size of the placement and culling shaders.
+const WORKGROUP_SIZE: u32 = 64;
+
+/// The size of a `ScatterInstance` in `scatter_types.wgsl`: a position, a
+/// scale and a rotation quaternion.
+const INSTANCE_SIZE: u64 = 32;
+
+/// The size of the indirect draw parameters of a layer, which is the size of
+/// the indexed ones. The non-indexed ones only use the first four words.
+const INDIRECT_PARAMETERS_SIZE: u64 = 5 * size_of::<u32>() as u64;
+
+/// The render graph label of the node that places the instances of the layers
+/// that changed, before any camera renders.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, RenderLabel)]
+pub(super) struct ScatterGenerateLabel;
+
+/// A [`ScatterLayer`] extracted to the render world.
+#[derive(Component)]
+pub(super) struct ExtractedScatterLayer {
+    world_from_local: Mat4,
+    mesh: AssetId<Mesh>,
+    material: AssetId<StandardMaterial>,
+    density_map: Option<AssetId<Image>>,
+    surface: ScatterSurfaceData,
+    density: f32,
+    min_scale: f32,
+    max_scale: f32,
+    align_to_normal: f32,
+    max_instances: u32,
+    max_distance: f32,
+    seed: u32,
+}
+
+impl ExtractedScatterLayer {
+    /// The number of points of the surface that the placement shader tries,
+    /// each of which becomes an instance unless the density map rejects it.
+    fn candidate_count(&self) -> u32 {
+        let count = (self.surface.area * self.density).ceil();
+        (count.min(self.max_instances as f32) as u32).max(1)
+    }
+}
+
+/// The data that the placement shader reads, matching `ScatterGeneration` in
+/// `scatter_generate.wgsl`.
+#[derive(Clone, Copy, ShaderType)]
+struct ScatterGenerationUniform {
+    candidate_count: u32,
+    seed: u32,
+    min_scale: f32,
+    max_scale: f32,
+    align_to_normal: f32,
+    triangle_count: u32,
+    grid_size: UVec2,
+    size: Vec2,
+    height: f32,
+}
+
+/// The data that the culling shader reads for a layer and a view, matching
+/// `ScatterCullView` in `scatter_cull.wgsl`.
+#[derive(Clone, Copy, Default, ShaderType)]
+struct ScatterCullUniform {
+    world_from_local: Mat4,
+    clip_from_world: Mat4,
+    frustum: [Vec4; 6],
+    viewport: Vec4,
+    view_position: Vec3,
+    max_distance: f32,
+    bounding_sphere_center: Vec3,
+    bounding_sphere_radius: f32,
+    depth_size: Vec2,
+}
+
+/// The transform of a layer, matching `ScatterLayer` in `scatter.wgsl`.
+#[derive(Clone, Copy, Default, ShaderType)]
+struct ScatterLayerUniform {
+    world_from_local: Mat4,
+    normal_from_local: Mat3,
+}
+
+/// The GPU buffers of a [`ScatterLayer`], kept from one frame to the next.
+#[derive(Component)]
+pub(super) struct ScatterLayerBuffers {
+    /// The instances placed on the surface.
+    instances: Buffer,
+    /// The number of instances in [`Self::instances`].
+    instance_count: Buffer,
+    /// The number of instances that [`Self::instances`] can hold.
+    capacity: u32,
+    /// The revision of the surface data that the instances were placed with,
+    /// and whether the density map was loaded at that time.
+    generated: Option<(u32, bool)>,
+    layer_uniform: UniformBuffer<ScatterLayerUniform>,
+    material_uniform: UniformBuffer<StandardMaterialUniform>,
+}
+
+/// The materials of the extracted layers, which are only cloned from the main
+/// world when they're first used or when they change.
+#[derive(Resource, Default)]
+pub(super) struct ScatterMaterials(HashMap<AssetId<StandardMaterial>, StandardMaterial>);
+
+/// The placement shader dispatches that [`ScatterGenerateNode`] runs this
+/// frame.
+#[derive(Resource, Default)]
+pub(super) struct ScatterGenerations(Vec<ScatterGeneration>);
+
+struct ScatterGeneration {
+    pipeline: CachedComputePipelineId,
+    bind_group: BindGroup,
+    candidate_count: u32,
+}
+
+/// The buffers and bind groups of each layer, for each view.
+#[derive(Resource, Default)]
+pub(super) struct ScatterViewBuffers(EntityHashMap<EntityHashMap<ScatterViewLayer>>);
+
+/// The buffers and bind groups of a layer for a view.
+struct ScatterViewLayer {
+    /// The indices of the instances that passed culling.
+    visible_instances: Buffer,
+    /// The indirect draw parameters, whose instance count the culling shader
+    /// fills in.
+    indirect_parameters: Buffer,
+    capacity: u32,
+    cull_uniform: UniformBuffer<ScatterCullUniform>,
+    cull_bind_group: BindGroup,
+    occlusion_culling: bool,
+    draw_bind_group: BindGroup,
+    indexed: bool,
+}
+
+/// The bind group layouts and pipelines of the placement and culling shaders.
+#[derive(Resource)]
+pub(super) struct ScatterComputePipelines {
+    generate_bind_group_layout: BindGroupLayout,
+    cull_bind_group_layout: BindGroupLayout,
+    cull_occlusion_bind_group_layout: BindGroupLayout,
+    generate_triangles: CachedComputePipelineId,
+    generate_heightmap: CachedComputePipelineId,
+    cull: CachedComputePipelineId,
+    cull_occlusion: CachedComputePipelineId,
+}
+
+impl FromWorld for ScatterComputePipelines {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let generate_bind_group_layout = render_device.create_bind_group_layout(
+            "scatter generate bind group layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    // `generation`
+                    uniform_buffer::<ScatterGenerationUniform>(false),
+                    // `vertices` or `heights`
+                    storage_buffer_read_only_sized(false, None),
+                    // `indices`
+                    storage_buffer_read_only_sized(false, None),
+                    // `cumulative_areas`
+                    storage_buffer_read_only_sized(false, None),
+                    // `density_map`
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    // `density_map_sampler`
+                    sampler(SamplerBindingType::Filtering),
+                    // `instances`
+                    storage_buffer_sized(false, None),
+                    // `instance_count`
+                    storage_buffer_sized(false, None),
+                ),
+            ),
+        );
+
+        let cull_entries = (
+            // `cull_view`
+            uniform_buffer::<ScatterCullUniform>(false),
+            // `instances`
+            storage_buffer_read_only_sized(false, None),
+            // `instance_count`
+            storage_buffer_read_only_sized(false, None),
+            // `visible_instances`
+            storage_buffer_sized(false, None),
+            // `indirect_parameters`
+            storage_buffer_sized(false, None),
+        );
+        let cull_bind_group_layout = render_device.create_bind_group_layout(
+            "scatter cull bind group layout",
+            &BindGroupLayoutEntries::sequential(ShaderStages::COMPUTE, cull_entries),
+        );
+        let cull_occlusion_bind_group_layout = render_device.create_bind_group_layout(
+            "scatter cull occlusion bind group layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    cull_entries.0,
+                    cull_entries.1,
+                    cull_entries.2,
+                    cull_entries.3,
+                    cull_entries.4,
+                    // `depth_pyramid`
+                    texture_2d(TextureSampleType::Float { filterable: false }),
+                ),
+            ),
+        );
+
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let queue_pipeline = |label: &'static str,
+                              layout: &BindGroupLayout,
+                              shader: Handle<Shader>,
+                              entry_point: &'static str,
+                              shader_defs: &[&str]| {
+            pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+                label: Some(label.into()),
+                layout: vec![layout.clone()],
+                push_constant_ranges: vec![],
+                shader,
+                shader_defs: shader_defs.iter().map(|&def| def.into()).collect(),
+                entry_point: entry_point.into(),
+                zero_initialize_workgroup_memory: false,
+            })
+        };
+
+        Self {
+            generate_triangles: queue_pipeline(
+                "scatter generate triangles",
+                &generate_bind_group_layout,
+                SCATTER_GENERATE_SHADER_HANDLE,
+                "generate",
+                &[],
+            ),
+            generate_heightmap: queue_pipeline(
+                "scatter generate heightmap",
+                &generate_bind_group_layout,
+                SCATTER_GENERATE_SHADER_HANDLE,
+                "generate",
+                &["HEIGHTMAP"],
+            ),
+            cull: queue_pipeline(
+                "scatter cull",
+                &cull_bind_group_layout,
+                SCATTER_CULL_SHADER_HANDLE,
+                "cull",
+                &[],
+            ),
+            cull_occlusion: queue_pipeline(
+                "scatter cull occlusion",
+                &cull_occlusion_bind_group_layout,
+                SCATTER_CULL_SHADER_HANDLE,
+                "cull",
+                &["OCCLUSION_CULLING"],
+            ),
+            generate_bind_group_layout,
+            cull_bind_group_layout,
+            cull_occlusion_bind_group_layout,
+        }
+    }
+}
+
+/// The pipeline that draws the instances of a layer, which is the mesh
+/// pipeline with the instance transforms read from the culling results.
+#[derive(Resource)]
+pub(super) struct ScatterDrawPipeline {
+    mesh_pipeline: MeshPipeline,
+    bind_group_layout: BindGroupLayout,
+}
+
+impl FromWorld for ScatterDrawPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let bind_group_layout = render_device.create_bind_group_layout(
+            "scatter draw bind group layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::VERTEX_FRAGMENT,
+                (
+                    // `layer`
+                    uniform_buffer::<ScatterLayerUniform>(false),
+                    // `instances`
+                    storage_buffer_read_only_sized(false, None),
+                    // `visible_instances`
+                    storage_buffer_read_only_sized(false, None),
+                    // `material`
+                    uniform_buffer::<StandardMaterialUniform>(false),
+                    // `base_color_texture`
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    // `base_color_sampler`
+                    sampler(SamplerBindingType::Filtering),
+                ),
+            ),
+        );
+
+        Self {
+            mesh_pipeline: world.resource::<MeshPipeline>().clone(),
+            bind_group_layout,
+        }
+    }
+}
+
+/// The key of a [`ScatterDrawPipeline`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) struct ScatterDrawPipelineKey {
+    mesh_key: MeshPipelineKey,
+    double_sided: bool,
+}
+
+impl SpecializedMeshPipeline for ScatterDrawPipeline {
+    type Key = ScatterDrawPipelineKey;
+
+    fn specialize(
+        &self,
+        key: Self::Key,
+        layout: &MeshVertexBufferLayoutRef,
+    ) -> Result<RenderPipelineDescriptor, SpecializedMeshPipelineError> {
+        let mut descriptor = self.mesh_pipeline.specialize(key.mesh_key, layout)?;
+        descriptor.label = Some("scatter pipeline".into());
+
+        // Replace the mesh bind group with the instances of the layer.
+        descriptor.layout.truncate(1);
+        descriptor.layout.push(self.bind_group_layout.clone());
+
+        let mut shader_defs = descriptor.vertex.shader_defs.clone();
+        if key.mesh_key.contains(MeshPipelineKey::MAY_DISCARD) {
+            shader_defs.push("SCATTER_ALPHA_MASK".into());
+        }
+        // Only the blended layers and the ones using alpha to coverage need
+        // their alpha.
+        if key
+            .mesh_key
+            .intersection(MeshPipelineKey::BLEND_RESERVED_BITS)
+            == MeshPipelineKey::BLEND_OPAQUE
+        {
+            shader_defs.push("SCATTER_OPAQUE".into());
+        }
+        if key.double_sided {
+            shader_defs.push("SCATTER_DOUBLE_SIDED".into());
+        }
+        descriptor.vertex.shader = SCATTER_SHADER_HANDLE;
+        descriptor.vertex.shader_defs = shader_defs.clone();
+        let fragment = descriptor.fragment.as_mut().unwrap();
+        fragment.shader = SCATTER_SHADER_HANDLE;
+        fragment.shader_defs = shader_defs;
+
+        descriptor.primitive.cull_mode = if key.double_sided {
+            None
+        } else {
+            Some(Face::Back)
+        };
+
+        Ok(descriptor)
+    }
+}
+
+/// Extracts the visible [`ScatterLayer`]s whose assets are loaded.
+pub(super) fn extract_scatter_layers(
+    mut commands: Commands,
+    layers: Extract<
+        Query<(
+            RenderEntity,
+            &ScatterLayer,
+            Option<&ScatterSurfaceData>,
+            &GlobalTransform,
+            &InheritedVisibility,
+        )>,
+    >,
+    materials: Extract<Res<Assets<StandardMaterial>>>,
+    mut material_events: Extract<EventReader<AssetEvent<StandardMaterial>>>,
+    mut scatter_materials: ResMut<ScatterMaterials>,
+) {
+    for event in material_events.read() {
+        if let AssetEvent::Modified { id }
+        | AssetEvent::Removed { id }
+        | AssetEvent::Unused { id } = *event
+        {
+            scatter_materials.0.remove(&id);
+        }
+    }
+
+    let mut used_materials = HashSet::new();
+    for (render_entity, layer, surface, transform, visibility) in &layers {
+        let mut entity_commands = commands
+            .get_entity(render_entity)
+            .expect("Scatter layer entity wasn't synced.");
+
+        let (Some(surface), Some(material), true) =
+            (surface, materials.get(&layer.material), visibility.get())
+        else {
+            entity_commands.remove::<ExtractedScatterLayer>();
+            continue;
+        };
+
+        let material_id = layer.material.id();
+        scatter_materials
+            .0
+            .entry(material_id)
+            .or_insert_with(|| material.clone());
+        used_materials.insert(material_id);
+
+        entity_commands.insert(ExtractedScatterLayer {
+            world_from_local: transform.compute_matrix(),
+            mesh: layer.mesh.id(),
+            material: material_id,
+            density_map: layer.density_map.as_ref().map(Handle::id),
+            surface: surface.clone(),
+            density: layer.density,
+            min_scale: layer.min_scale,
+            max_scale: layer.max_scale,
+            align_to_normal: layer.align_to_normal,
+            max_instances: layer.max_instances,
+            max_distance: layer.max_distance,
+            seed: layer.seed,
+        });
+    }
+
+    scatter_materials
+        .0
+        .retain(|id, _| used_materials.contains(id));
+}
+
+/// Creates the buffers of the layers, writes their uniforms, and records the
+/// layers whose instances need to be placed again.
+pub(super) fn prepare_scatter_layers(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    pipeline_cache: Res<PipelineCache>,
+    pipelines: Res<ScatterComputePipelines>,
+    images: Res<RenderAssets<GpuImage>>,
+    fallback_image: Res<FallbackImage>,
+    materials: Res<ScatterMaterials>,
+    mut generations: ResMut<ScatterGenerations>,
+    mut layers: Query<(
+        Entity,
+        &ExtractedScatterLayer,
+        Option<&mut ScatterLayerBuffers>,
+    )>,
+) {
+    generations.0.clear();
+
+    for (entity, layer, buffers) in &mut layers {
+        let Some(material) = materials.0.get(&layer.material) else {
+            continue;
+        };
+        let capacity = layer.candidate_count();
+        let mut new_buffers = None;
+        let buffers = match buffers {
+            Some(buffers) if buffers.capacity == capacity => buffers.into_inner(),
+            _ => new_buffers.insert(ScatterLayerBuffers {
+                instances: render_device.create_buffer(&BufferDescriptor {
+                    label: Some("scatter_instances"),
+                    size: capacity as u64 * INSTANCE_SIZE,
+                    usage: BufferUsages::STORAGE,
+                    mapped_at_creation: false,
+                }),
+                instance_count: render_device.create_buffer(&BufferDescriptor {
+                    label: Some("scatter_instance_count"),
+                    size: size_of::<u32>() as u64,
+                    usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                }),
+                capacity,
+                generated: None,
+                layer_uniform: UniformBuffer::default(),
+                material_uniform: UniformBuffer::default(),
+            }),
+        };
+
+        let world_from_local = layer.world_from_local;
+        buffers.layer_uniform.set(ScatterLayerUniform {
+            world_from_local,
+            normal_from_local: Mat3::from_mat4(world_from_local).inverse().transpose(),
+        });
+        buffers
+            .layer_uniform
+            .write_buffer(&render_device, &render_queue);
+        buffers
+            .material_uniform
+            .set(material.as_bind_group_shader_type(&images));
+        buffers
+            .material_uniform
+            .write_buffer(&render_device, &render_queue);
+
+        // Place the instances again when the surface changes, and once the
+        // density map is loaded.
+        let density_map = layer.density_map.and_then(|id| images.get(id));
+        let generation_key = (
+            layer.surface.revision,
+            layer.density_map.is_none() || density_map.is_some(),
+        );
+        let pipeline_id = match *layer.surface.geometry {
+            ScatterSurfaceGeometry::Triangles { .. } => pipelines.generate_triangles,
+            ScatterSurfaceGeometry::Heightmap { .. } => pipelines.generate_heightmap,
+        };
+        if buffers.generated != Some(generation_key)
+            && pipeline_cache.get_compute_pipeline(pipeline_id).is_some()
+        {
+            let density_map = density_map.unwrap_or(&fallback_image.d2);
+            generations.0.push(create_generation(
+                &render_device,
+                &render_queue,
+                &pipelines,
+                pipeline_id,
+                layer,
+                buffers,
+                density_map,
+            ));
+            buffers.generated = Some(generation_key);
+        }
+
+        if let Some(new_buffers) = new_buffers {
+            commands.entity(entity).insert(new_buffers);
+        }
+    }
+}
+
+/// Uploads the surface of a layer, and creates the bind group that places its
+/// instances.
+fn create_generation(
+    render_device: &RenderDevice,
+    render_queue: &RenderQueue,
+    pipelines: &ScatterComputePipelines,
+    pipeline: CachedComputePipelineId,
+    layer: &ExtractedScatterLayer,
+    buffers: &ScatterLayerBuffers,
+    density_map: &GpuImage,
+) -> ScatterGeneration {
+    let create_storage_buffer = |label: &'static str, contents: &[u8]| {
+        render_device.create_buffer_with_data(&BufferInitDescriptor {
+            label: Some(label),
+            contents,
+            usage: BufferUsages::STORAGE,
+        })
+    };
+
+    let candidate_count = layer.candidate_count();
+    let mut uniform = ScatterGenerationUniform {
+        candidate_count,
+        seed: layer.seed,
+        min_scale: layer.min_scale,
+        max_scale: layer.max_scale,
+        align_to_normal: layer.align_to_normal,
+        triangle_count: 0,
+        grid_size: UVec2::ZERO,
+        size: Vec2::ZERO,
+        height: 0.0,
+    };
+    let (vertices, indices, cumulative_areas) = match &*layer.surface.geometry {
+        ScatterSurfaceGeometry::Triangles {
+            vertices,
+            indices,
+            cumulative_areas,
+        } => {
+            uniform.triangle_count = cumulative_areas.len() as u32;
+            (
+                create_storage_buffer("scatter_surface_vertices", cast_slice(vertices)),
+                create_storage_buffer("scatter_surface_indices", cast_slice(indices)),
+                create_storage_buffer(
+                    "scatter_surface_cumulative_areas",
+                    cast_slice(cumulative_areas),
+                ),
+            )
+        }
+        ScatterSurfaceGeometry::Heightmap {
+            grid_size,
+            heights,
+            size,
+            height,
+        } => {
+            uniform.grid_size = *grid_size;
+            uniform.size = *size;
+            uniform.height = *height;
+            // The heightmap shader only reads the first buffer.
+            let heights = create_storage_buffer("scatter_surface_heights", cast_slice(heights));
+            (heights.clone(), heights.clone(), heights)
+        }
+    };
+
+    let mut uniform_buffer = UniformBuffer::from(uniform);
+    uniform_buffer.write_buffer(render_device, render_queue);
+    render_queue.write_buffer(&buffers.instance_count, 0, &0u32.to_le_bytes());
+
+    let bind_group = render_device.create_bind_group(
+        "scatter_generate_bind_group",
+        &pipelines.generate_bind_group_layout,
+        &BindGroupEntries::sequential((
+            &uniform_buffer,
+            vertices.as_entire_binding(),
+            indices.as_entire_binding(),
+            cumulative_areas.as_entire_binding(),
+            &density_map.texture_view,
+            &density_map.sampler,
+            buffers.instances.as_entire_binding(),
+            buffers.instance_count.as_entire_binding(),
+        )),
+    );
+
+    ScatterGeneration {
+        pipeline,
+        bind_group,
+        candidate_count,
+    }
+}
+
+/// Creates the culling results and the bind groups of each layer for each
+/// camera, and resets their indirect draw parameters.
+pub(super) fn prepare_scatter_views(
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    pipelines: Res<ScatterComputePipelines>,
+    draw_pipeline: Res<ScatterDrawPipeline>,
+    images: Res<RenderAssets<GpuImage>>,
+    fallback_image: Res<FallbackImage>,
+    render_meshes: Res<RenderAssets<RenderMesh>>,
+    mesh_allocator: Res<MeshAllocator>,
+    materials: Res<ScatterMaterials>,
+    mut view_buffers: ResMut<ScatterViewBuffers>,
+    views: Query<
+        (
+            Entity,
+            &ExtractedView,
+            &Frustum,
+            Option<&ViewDepthPyramid>,
+            Option<&ViewPrepassTextures>,
+        ),
+        With<Camera3d>,
+    >,
+    layers: Query<(Entity, &ExtractedScatterLayer, &ScatterLayerBuffers)>,
+) {
+    // Forget the views and layers that are gone.
+    view_buffers
+        .0
+        .retain(|view_entity, _| views.contains(*view_entity));
+    for view_layers in view_buffers.0.values_mut() {
+        view_layers.retain(|layer_entity, _| layers.contains(*layer_entity));
+    }
+
+    for (view_entity, view, frustum, depth_pyramid, prepass_textures) in &views {
+        let view_layers = view_buffers.0.entry(view_entity).or_default();
+        let clip_from_world = view.clip_from_world.unwrap_or_else(|| {
+            view.clip_from_view * view.world_from_view.compute_matrix().inverse()
+        });
+        let depth_size = prepass_textures.map_or(Vec2::ZERO, |prepass_textures| {
+            Vec2::new(
+                prepass_textures.size.width as f32,
+                prepass_textures.size.height as f32,
+            )
+        });
+        let depth_pyramid = depth_pyramid.filter(|_| depth_size != Vec2::ZERO);
+
+        for (layer_entity, layer, layer_buffers) in &layers {
+            let (Some(mesh), Some(vertex_slice), Some(material)) = (
+                render_meshes.get(layer.mesh),
+                mesh_allocator.mesh_vertex_slice(&layer.mesh),
+                materials.0.get(&layer.material),
+            ) else {
+                view_layers.remove(&layer_entity);
+                continue;
+            };
+
+            // Reset the instance count of the indirect draw parameters, which
+            // the culling shader then increments.
+            let (indirect_parameters, indexed) = match mesh.buffer_info {
+                RenderMeshBufferInfo::Indexed { count, .. } => {
+                    let Some(index_slice) = mesh_allocator.mesh_index_slice(&layer.mesh) else {
+                        view_layers.remove(&layer_entity);
+                        continue;
+                    };
+                    (
+                        [
+                            count,
+                            0,
+                            index_slice.range.start,
+                            vertex_slice.range.start,
+                            0,
+                        ],
+                        true,
+                    )
+                }
+                RenderMeshBufferInfo::NonIndexed => (
+                    [mesh.vertex_count, 0, vertex_slice.range.start, 0, 0],
+                    false,
+                ),
+            };
+
+            // Reuse the buffers of the previous frame if the layer still has
+            // the same capacity. The bind groups are recreated every frame
+            // since the depth pyramid and the base color texture may change.
+            let capacity = layer_buffers.capacity;
+            let previous = view_layers
+                .remove(&layer_entity)
+                .filter(|view_layer| view_layer.capacity == capacity);
+            let (visible_instances, indirect_parameters_buffer, mut cull_uniform) = match previous {
+                Some(previous) => (
+                    previous.visible_instances,
+                    previous.indirect_parameters,
+                    previous.cull_uniform,
+                ),
+                None => (
+                    render_device.create_buffer(&BufferDescriptor {
+                        label: Some("scatter_visible_instances"),
+                        size: capacity as u64 * size_of::<u32>() as u64,
+                        usage: BufferUsages::STORAGE,
+                        mapped_at_creation: false,
+                    }),
+                    render_device.create_buffer(&BufferDescriptor {
+                        label: Some("scatter_indirect_parameters"),
+                        size: INDIRECT_PARAMETERS_SIZE,
+                        usage: BufferUsages::STORAGE
+                            | BufferUsages::INDIRECT
+                            | BufferUsages::COPY_DST,
+                        mapped_at_creation: false,
+                    }),
+                    UniformBuffer::default(),
+                ),
+            };
+
+            let aabb = &layer.surface.instance_aabb;
+            let (layer_scale, _, _) = layer.world_from_local.to_scale_rotation_translation();
+            cull_uniform.set(ScatterCullUniform {
+                world_from_local: layer.world_from_local,
+                clip_from_world,
+                frustum: frustum.half_spaces.map(|half_space| half_space.normal_d()),
+                viewport: view.viewport.as_vec4(),
+                view_position: view.world_from_view.translation(),
+                max_distance: layer.max_distance,
+                bounding_sphere_center: aabb.center.into(),
+                bounding_sphere_radius: Vec3::from(aabb.half_extents).length()
+                    * layer_scale.abs().max_element(),
+                depth_size,
+            });
+            cull_uniform.write_buffer(&render_device, &render_queue);
+
+            render_queue.write_buffer(
+                &indirect_parameters_buffer,
+                0,
+                cast_slice(&indirect_parameters),
+            );
+
+            let cull_bind_group = match depth_pyramid {
+                Some(depth_pyramid) => render_device.create_bind_group(
+                    "scatter_cull_occlusion_bind_group",
+                    &pipelines.cull_occlusion_bind_group_layout,
+                    &BindGroupEntries::sequential((
+                        &cull_uniform,
+                        layer_buffers.instances.as_entire_binding(),
+                        layer_buffers.instance_count.as_entire_binding(),
+                        visible_instances.as_entire_binding(),
+                        indirect_parameters_buffer.as_entire_binding(),
+                        &depth_pyramid.all_mips,
+                    )),
+                ),
+                None => render_device.create_bind_group(
+                    "scatter_cull_bind_group",
+                    &pipelines.cull_bind_group_layout,
+                    &BindGroupEntries::sequential((
+                        &cull_uniform,
+                        layer_buffers.instances.as_entire_binding(),
+                        layer_buffers.instance_count.as_entire_binding(),
+                        visible_instances.as_entire_binding(),
+                        indirect_parameters_buffer.as_entire_binding(),
+                    )),
+                ),
+            };
+
+            let base_color_texture = material
+                .base_color_texture
+                .as_ref()
+                .and_then(|texture| images.get(texture))
+                .unwrap_or(&fallback_image.d2);
+            let draw_bind_group = render_device.create_bind_group(
+                "scatter_draw_bind_group",
+                &draw_pipeline.bind_group_layout,
+                &BindGroupEntries::sequential((
+                    &layer_buffers.layer_uniform,
+                    layer_buffers.instances.as_entire_binding(),
+                    visible_instances.as_entire_binding(),
+                    &layer_buffers.material_uniform,
+                    &base_color_texture.texture_view,
+                    &base_color_texture.sampler,
+                )),
+            );
+
+            view_layers.insert(
+                layer_entity,
+                ScatterViewLayer {
+                    visible_instances,
+                    indirect_parameters: indirect_parameters_buffer,
+                    capacity,
+                    cull_uniform,
+                    cull_bind_group,
+                    occlusion_culling: depth_pyramid.is_some(),
+                    draw_bind_group,
+                    indexed,
+                },
+            );
+        }
+    }
+}
+
+/// Adds the layers to the phase of each camera that matches the alpha mode of
+/// their material.
+pub(super) fn queue_scatter_layers(
+    opaque_draw_functions: Res<DrawFunctions<Opaque3d>>,
+    alpha_mask_draw_functions: Res<DrawFunctions<AlphaMask3d>>,
+    transparent_draw_functions: Res<DrawFunctions<Transparent3d>>,
+    draw_pipeline: Res<ScatterDrawPipeline>,
+    mut pipelines: ResMut<SpecializedMeshPipelines<ScatterDrawPipeline>>,
+    pipeline_cache: Res<PipelineCache>,
+    render_meshes: Res<RenderAssets<RenderMesh>>,
+    materials: Res<ScatterMaterials>,
+    layers: Query<(Entity, &MainEntity, &ExtractedScatterLayer)>,
+    mut opaque_render_phases: ResMut<ViewBinnedRenderPhases<Opaque3d>>,
+    mut alpha_mask_render_phases: ResMut<ViewBinnedRenderPhases<AlphaMask3d>>,
+    mut transparent_render_phases: ResMut<ViewSortedRenderPhases<Transparent3d>>,
+    views: Query<(
+        &ExtractedView,
+        &Msaa,
+        Option<&Tonemapping>,
+        Option<&DebandDither>,
+        Option<&ShadowFilteringMethod>,
+        Option<&Projection>,
+        (
+            Has<ScreenSpaceAmbientOcclusion>,
+            Has<ScreenSpaceGlobalIllumination>,
+        ),
+        (
+            Has<NormalPrepass>,
+            Has<DepthPrepass>,
+            Has<MotionVectorPrepass>,
+            Has<DeferredPrepass>,
+        ),
+        (
+            Has<RenderViewLightProbes<EnvironmentMapLight>>,
+            Has<RenderViewLightProbes<IrradianceVolume>>,
+        ),
+        (
+            Has<OrderIndependentTransparencySettings>,
+            Option<&OrderIndependentTransparencyMethod>,
+        ),
+    )>,
+) {
+    if layers.is_empty() {
+        return;
+    }
+
+    let draw_opaque_scatter_layer = opaque_draw_functions.read().id::<DrawScatterLayer>();
+    let draw_alpha_mask_scatter_layer = alpha_mask_draw_functions.read().id::<DrawScatterLayer>();
+    let draw_transparent_scatter_layer = transparent_draw_functions.read().id::<DrawScatterLayer>();
+
+    for (
+        view,
+        msaa,
+        tonemapping,
+        dither,
+        shadow_filter_method,
+        projection,
+        (ssao, ssgi),
+        (normal_prepass, depth_prepass, motion_vector_prepass, deferred_prepass),
+        (has_environment_maps, has_irradiance_volumes),
+        (has_oit, oit_method),
+    ) in &views
+    {
+        let (Some(opaque_phase), Some(alpha_mask_phase), Some(transparent_phase)) = (
+            opaque_render_phases.get_mut(&view.retained_view_entity),
+            alpha_mask_render_phases.get_mut(&view.retained_view_entity),
+            transparent_render_phases.get_mut(&view.retained_view_entity),
+        ) else {
+            continue;
+        };
+
+        // The view bind group of the pipeline must match the one of the view,
+        // so the key is built like the one of the materials.
+        let mut view_key = MeshPipelineKey::from_msaa_samples(msaa.samples())
+            | MeshPipelineKey::from_hdr(view.hdr);
+        for (enabled, flag) in [
+            (normal_prepass, MeshPipelineKey::NORMAL_PREPASS),
+            (depth_prepass, MeshPipelineKey::DEPTH_PREPASS),
+            (
+                motion_vector_prepass,
+                MeshPipelineKey::MOTION_VECTOR_PREPASS,
+            ),
+            (deferred_prepass, MeshPipelineKey::DEFERRED_PREPASS),
+            (has_environment_maps, MeshPipelineKey::ENVIRONMENT_MAP),
+            (has_irradiance_volumes, MeshPipelineKey::IRRADIANCE_VOLUME),
+            (ssao, MeshPipelineKey::SCREEN_SPACE_AMBIENT_OCCLUSION),
+            (ssgi, MeshPipelineKey::SCREEN_SPACE_GLOBAL_ILLUMINATION),
+        ] {
+            if enabled {
+                view_key |= flag;
+            }
+        }
+        if has_oit {
+            view_key |= MeshPipelineKey::OIT_ENABLED;
+            if oit_method == Some(&OrderIndependentTransparencyMethod::WeightedBlended) {
+                view_key |= MeshPipelineKey::OIT_WEIGHTED_BLENDED;
+            }
+        }
+        if let Some(projection) = projection {
+            view_key |= match projection {
+                Projection::Perspective(_) => MeshPipelineKey::VIEW_PROJECTION_PERSPECTIVE,
+                Projection::Orthographic(_) => MeshPipelineKey::VIEW_PROJECTION_ORTHOGRAPHIC,
+                Projection::Custom(_) => MeshPipelineKey::VIEW_PROJECTION_NONSTANDARD,
+            };
+        }
+        view_key |= match shadow_filter_method.unwrap_or(&ShadowFilteringMethod::default()) {
+            ShadowFilteringMethod::Hardware2x2 => {
+                MeshPipelineKey::SHADOW_FILTER_METHOD_HARDWARE_2X2
+            }
+            ShadowFilteringMethod::Gaussian => MeshPipelineKey::SHADOW_FILTER_METHOD_GAUSSIAN,
+            ShadowFilteringMethod::Temporal => MeshPipelineKey::SHADOW_FILTER_METHOD_TEMPORAL,
+        };
+        if !view.hdr {
+            if let Some(tonemapping) = tonemapping {
+                view_key |= MeshPipelineKey::TONEMAP_IN_SHADER;
+                view_key |= tonemapping_pipeline_key(*tonemapping);
+            }
+            if let Some(DebandDither::Enabled) = dither {
+                view_key |= MeshPipelineKey::DEBAND_DITHER;
+            }
+        }
+
+        let rangefinder = view.rangefinder3d();
+        for (entity, main_entity, layer) in &layers {
+            let (Some(mesh), Some(material)) = (
+                render_meshes.get(layer.mesh),
+                materials.0.get(&layer.material),
+            ) else {
+                continue;
+            };
+            let key = ScatterDrawPipelineKey {
+                mesh_key: view_key
+                    | MeshPipelineKey::from_primitive_topology(mesh.primitive_topology())
+                    | alpha_mode_pipeline_key(material.alpha_mode, msaa),
+                double_sided: material.double_sided,
+            };
+            let pipeline =
+                match pipelines.specialize(&pipeline_cache, &draw_pipeline, key, &mesh.layout) {
+                    Ok(pipeline) => pipeline,
+                    Err(err) => {
+                        tracing::error!("{}", err);
+                        continue;
+                    }
+                };
+
+            // The layers draw their instances themselves, so they're added
+            // as non-mesh items to the binned phases.
+            match material.alpha_mode {
+                AlphaMode::Opaque => opaque_phase.add(
+                    Opaque3dBatchSetKey {
+                        pipeline,
+                        draw_function: draw_opaque_scatter_layer,
+                        material_bind_group_index: None,
+                        vertex_slab: default(),
+                        index_slab: None,
+                        lightmap_slab: None,
+                    },
+                    Opaque3dBinKey {
+                        asset_id: AssetId::<Mesh>::invalid().untyped(),
+                    },
+                    (entity, *main_entity),
+                    BinnedRenderPhaseType::NonMesh,
+                ),
+                AlphaMode::Mask(_) | AlphaMode::AlphaToCoverage => alpha_mask_phase.add(
+                    OpaqueNoLightmap3dBatchSetKey {
+                        pipeline,
+                        draw_function: draw_alpha_mask_scatter_layer,
+                        material_bind_group_index: None,
+                        vertex_slab: default(),
+                        index_slab: None,
+                    },
+                    OpaqueNoLightmap3dBinKey {
+                        asset_id: AssetId::<Mesh>::invalid().untyped(),
+                    },
+                    (entity, *main_entity),
+                    BinnedRenderPhaseType::NonMesh,
+                ),
+                _ => transparent_phase.add(Transparent3d {
+                    entity: (entity, *main_entity),
+                    pipeline,
+                    draw_function: draw_transparent_scatter_layer,
+                    distance: rangefinder
+                        .distance_translation(&layer.world_from_local.w_axis.truncate()),
+                    batch_range: 0..1,
+                    extra_index: PhaseItemExtraIndex::None,
+                    indexed: mesh.indexed(),
+                }),
+            }
+        }
+    }
+}
+
+/// The render node that places the instances of the layers that changed.
+pub(super) struct ScatterGenerateNode;
+
+impl Node for ScatterGenerateNode {
+    fn run<'w>(
+        &self,
+        _: &mut RenderGraphContext,
+        render_context: &mut RenderContext<'w>,
+        world: &'w World,
+    ) -> Result<(), NodeRunError> {
+        let generations = world.resource::<ScatterGenerations>();
+        if generations.0.is_empty() {
+            return Ok(());
+        }
+        let pipeline_cache = world.resource::<PipelineCache>();
+
+        let mut compute_pass =
+            render_context
+                .command_encoder()
+                .begin_compute_pass(&ComputePassDescriptor {
+                    label: Some("scatter generate"),
+                    timestamp_writes: None,
+                });
+
+        for generation in &generations.0 {
+            let Some(pipeline) = pipeline_cache.get_compute_pipeline(generation.pipeline) else {
+                continue;
+            };
+            compute_pass.set_pipeline(pipeline);
+            compute_pass.set_bind_group(0, &generation.bind_group, &[]);
+            let (x, y) = workgroup_count(generation.candidate_count);
+            compute_pass.dispatch_workgroups(x, y, 1);
+        }
+
+        Ok(())
+    }
+}
+
+/// The render node that culls the instances of every layer for a camera.
+#[derive(Default)]
+pub(super) struct ScatterCullNode;
+
+impl ViewNode for ScatterCullNode {
+    type ViewQuery = Entity;
+
+    fn run<'w>(
+        &self,
+        _: &mut RenderGraphContext,
+        render_context: &mut RenderContext<'w>,
+        view_entity: QueryItem<'w, Self::ViewQuery>,
+        world: &'w World,
+    ) -> Result<(), NodeRunError> {
+        let Some(view_layers) = world.resource::<ScatterViewBuffers>().0.get(&view_entity) else {
+            return Ok(());
+        };
+        if view_layers.is_empty() {
+            return Ok(());
+        }
+
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let pipelines = world.resource::<ScatterComputePipelines>();
+        let (Some(cull), Some(cull_occlusion)) = (
+            pipeline_cache.get_compute_pipeline(pipelines.cull),
+            pipeline_cache.get_compute_pipeline(pipelines.cull_occlusion),
+        ) else {
+            return Ok(());
+        };
+
+        let mut compute_pass =
+            render_context
+                .command_encoder()
+                .begin_compute_pass(&ComputePassDescriptor {
+                    label: Some("scatter cull"),
+                    timestamp_writes: None,
+                });
+
+        for view_layer in view_layers.values() {
+            compute_pass.set_pipeline(if view_layer.occlusion_culling {
+                cull_occlusion
+            } else {
+                cull
+            });
+            compute_pass.set_bind_group(0, &view_layer.cull_bind_group, &[]);
+            let (x, y) = workgroup_count(view_layer.capacity);
+            compute_pass.dispatch_workgroups(x, y, 1);
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the number of workgroups along X and Y that cover the given
+/// number of instances, as the number of workgroups along each axis is
+/// limited.
+fn workgroup_count(instance_count: u32) -> (u32, u32) {
+    const MAX_WORKGROUPS_PER_DIMENSION: u32 = 65535;
+    let workgroups = instance_count.div_ceil(WORKGROUP_SIZE);
+    let y = workgroups.div_ceil(MAX_WORKGROUPS_PER_DIMENSION);
+    (workgroups.div_ceil(y.max(1)), y.max(1))
+}
+
+/// The render commands that draw a [`ScatterLayer`].
+pub(super) type DrawScatterLayer = (
+    SetItemPipeline,
+    SetMeshViewBindGroup<0>,
+    SetScatterLayerBindGroup<1>,
+    DrawScatterInstances,
+);
+
+/// Sets the bind group of the instances of a layer for the current view.
+pub(super) struct SetScatterLayerBindGroup<const I: usize>;
+
+impl<P: PhaseItem, const I: usize> RenderCommand<P> for SetScatterLayerBindGroup<I> {
+    type Param = SRes<ScatterViewBuffers>;
+    type ViewQuery = Entity;
+    type ItemQuery = ();
+
+    fn render<'w>(
+        item: &P,
+        view_entity: Entity,
+        _: Option<()>,
+        view_buffers: SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        let Some(view_layer) = view_buffers
+            .into_inner()
+            .0
+            .get(&view_entity)
+            .and_then(|view_layers| view_layers.get(&item.entity()))
+        else {
+            return RenderCommandResult::Skip;
+        };
+
+        pass.set_bind_group(I, &view_layer.draw_bind_group, &[]);
+        RenderCommandResult::Success
+    }
+}
+
+/// Draws the instances of a layer that passed culling for the current view.
+pub(super) struct DrawScatterInstances;
+
+impl<P: PhaseItem> RenderCommand<P> for DrawScatterInstances {
+    type Param = (
+        SRes<ScatterViewBuffers>,
+        SRes<RenderAssets<RenderMesh>>,
+        SRes<MeshAllocator>,
+    );
+    type ViewQuery = Entity;
+    type ItemQuery = Read<ExtractedScatterLayer>;
+
+    fn render<'w>(
+        item: &P,
+        view_entity: Entity,
+        layer: Option<&'w ExtractedScatterLayer>,
+        (view_buffers, render_meshes, mesh_allocator): SystemParamItem<'w, '_, Self::Param>,
+        pass: &mut TrackedRenderPass<'w>,
+    ) -> RenderCommandResult {
+        // A borrow check workaround.
+        let mesh_allocator = mesh_allocator.into_inner();
+
+        let (Some(layer), Some(view_layer)) = (
+            layer,
+            view_buffers
+                .into_inner()
+                .0
+                .get(&view_entity)
+                .and_then(|view_layers| view_layers.get(&item.entity())),
+        ) else {
+            return RenderCommandResult::Skip;
+        };
+        let (Some(mesh), Some(vertex_slice)) = (
+            render_meshes.into_inner().get(layer.mesh),
+            mesh_allocator.mesh_vertex_slice(&layer.mesh),
+        ) else {
+            return RenderCommandResult::Skip;
+        };
+
+        pass.set_vertex_buffer(0, vertex_slice.buffer.slice(..));
+        match &mesh.buffer_info {
+            RenderMeshBufferInfo::Indexed { index_format, .. } if view_layer.indexed => {
+                let Some(index_slice) = mesh_allocator.mesh_index_slice(&layer.mesh) else {
+                    return RenderCommandResult::Skip;
+                };
+                pass.set_index_buffer(index_slice.buffer.slice(..), 0, *index_format);
+                pass.draw_indexed_indirect(&view_layer.indirect_parameters, 0);
+            }
+            RenderMeshBufferInfo::NonIndexed if !view_layer.indexed => {
+                pass.draw_indirect(&view_layer.indirect_parameters, 0);
+            }
+            _ => return RenderCommandResult::Skip,
+        }
+
+        RenderCommandResult::Success
+    }
+}
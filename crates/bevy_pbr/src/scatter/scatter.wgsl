@@ -0,0 +1,129 @@
+// Draws the instances of a scatter layer that passed culling, lit like
+// meshes with a `StandardMaterial`.
+//
+// Each instance of the draw reads its transform from the instances of the
+// layer, through the list of visible instances written by the culling shader.
+// Only the uniform properties and the base color texture of the material are
+// used.
+
+#import bevy_pbr::{
+    forward_io::{Vertex, VertexOutput, FragmentOutput},
+    mesh_types::MESH_FLAGS_SHADOW_RECEIVER_BIT,
+    mesh_view_bindings::view,
+    pbr_functions::{apply_pbr_lighting, calculate_view, main_pass_post_lighting_processing},
+    pbr_types::{
+        pbr_input_new, StandardMaterial, STANDARD_MATERIAL_FLAGS_BASE_COLOR_TEXTURE_BIT,
+        STANDARD_MATERIAL_FLAGS_UNLIT_BIT,
+    },
+    scatter_types::{ScatterInstance, quat_rotate},
+    view_transformations::position_world_to_clip,
+}
+
+// See `ScatterLayerUniform` in `scatter/render.rs`.
+struct ScatterLayer {
+    world_from_local: mat4x4<f32>,
+    normal_from_local: mat3x3<f32>,
+}
+
+@group(1) @binding(0) var<uniform> layer: ScatterLayer;
+@group(1) @binding(1) var<storage> instances: array<ScatterInstance>;
+@group(1) @binding(2) var<storage> visible_instances: array<u32>;
+@group(1) @binding(3) var<uniform> material: StandardMaterial;
+@group(1) @binding(4) var base_color_texture: texture_2d<f32>;
+@group(1) @binding(5) var base_color_sampler: sampler;
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    let instance = instances[visible_instances[vertex.instance_index]];
+    let world_from_local = layer.world_from_local;
+
+    var out: VertexOutput;
+    let local_position =
+        instance.position + quat_rotate(instance.rotation, vertex.position * instance.scale);
+    out.world_position = world_from_local * vec4(local_position, 1.0);
+    out.position = position_world_to_clip(out.world_position.xyz);
+
+#ifdef VERTEX_NORMALS
+    let normal = vertex.normal;
+#else
+    let normal = vec3(0.0, 1.0, 0.0);
+#endif
+    out.world_normal = normalize(layer.normal_from_local * quat_rotate(instance.rotation, normal));
+
+#ifdef VERTEX_UVS_A
+    out.uv = vertex.uv;
+#endif
+#ifdef VERTEX_UVS_B
+    out.uv_b = vertex.uv_b;
+#endif
+#ifdef VERTEX_TANGENTS
+    let tangent = quat_rotate(instance.rotation, vertex.tangent.xyz);
+    out.world_tangent = vec4(
+        normalize((world_from_local * vec4(tangent, 0.0)).xyz),
+        vertex.tangent.w
+    );
+#endif
+#ifdef VERTEX_COLORS
+    out.color = vertex.color;
+#endif
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+    out.instance_index = vertex.instance_index;
+#endif
+#ifdef VISIBILITY_RANGE_DITHER
+    out.visibility_range_dither = 0;
+#endif
+
+    return out;
+}
+
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    var pbr_input = pbr_input_new();
+    pbr_input.material = material;
+
+#ifdef VERTEX_UVS_A
+    if ((material.flags & STANDARD_MATERIAL_FLAGS_BASE_COLOR_TEXTURE_BIT) != 0u) {
+        let uv = (material.uv_transform * vec3(in.uv, 1.0)).xy;
+        pbr_input.material.base_color *= textureSample(base_color_texture, base_color_sampler, uv);
+    }
+#endif
+#ifdef VERTEX_COLORS
+    pbr_input.material.base_color *= in.color;
+#endif
+
+#ifdef SCATTER_ALPHA_MASK
+    if (pbr_input.material.base_color.a < material.alpha_cutoff) {
+        discard;
+    }
+#endif
+#ifdef SCATTER_OPAQUE
+    pbr_input.material.base_color.a = 1.0;
+#endif
+
+    var N = normalize(in.world_normal);
+#ifdef SCATTER_DOUBLE_SIDED
+    if (!is_front) {
+        N = -N;
+    }
+#endif
+
+    pbr_input.frag_coord = in.position;
+    pbr_input.world_position = in.world_position;
+    pbr_input.world_normal = N;
+    pbr_input.N = N;
+    pbr_input.is_orthographic = view.clip_from_view[3].w == 1.0;
+    pbr_input.V = calculate_view(in.world_position, pbr_input.is_orthographic);
+    pbr_input.flags = MESH_FLAGS_SHADOW_RECEIVER_BIT;
+
+    var out: FragmentOutput;
+    if ((material.flags & STANDARD_MATERIAL_FLAGS_UNLIT_BIT) == 0u) {
+        out.color = apply_pbr_lighting(pbr_input);
+    } else {
+        out.color = pbr_input.material.base_color;
+    }
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+    return out;
+}
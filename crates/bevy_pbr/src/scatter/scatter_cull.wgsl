@@ -0,0 +1,143 @@
+// Culls the instances of a scatter layer for a view.
+//
+// The instances whose bounding spheres are outside the view frustum, farther
+// than the maximum distance of the layer, or, with occlusion culling, hidden
+// behind the depth pyramid of the view, are culled. The indices of the others
+// are appended to `visible_instances`, and counted in the indirect draw
+// parameters.
+
+#import bevy_pbr::scatter_types::{ScatterInstance, invocation_index, quat_rotate}
+
+// See `ScatterCullUniform` in `scatter/render.rs`.
+struct ScatterCullView {
+    world_from_local: mat4x4<f32>,
+    clip_from_world: mat4x4<f32>,
+    frustum: array<vec4<f32>, 6>,
+    viewport: vec4<f32>,
+    view_position: vec3<f32>,
+    max_distance: f32,
+    // The bounding sphere of the instance mesh, in the mesh's local space.
+    // The radius is scaled by the largest scale of the layer.
+    bounding_sphere_center: vec3<f32>,
+    bounding_sphere_radius: f32,
+    // The size of the depth buffer the depth pyramid was built from.
+    depth_size: vec2<f32>,
+}
+
+// The indirect draw parameters of indexed meshes, of which non-indexed ones
+// only use the first four words. Either way, the instance count is second.
+struct IndirectParameters {
+    index_or_vertex_count: u32,
+    instance_count: atomic<u32>,
+    first_index_or_vertex: u32,
+    base_vertex_or_first_instance: u32,
+    first_instance: u32,
+}
+
+@group(0) @binding(0) var<uniform> cull_view: ScatterCullView;
+@group(0) @binding(1) var<storage> instances: array<ScatterInstance>;
+@group(0) @binding(2) var<storage> instance_count: u32;
+@group(0) @binding(3) var<storage, read_write> visible_instances: array<u32>;
+@group(0) @binding(4) var<storage, read_write> indirect_parameters: IndirectParameters;
+
+#ifdef OCCLUSION_CULLING
+// The depth pyramid, in which each texel holds the farthest depth of the
+// depth buffer texels it covers.
+@group(0) @binding(5) var depth_pyramid: texture_2d<f32>;
+
+// Returns true if a sphere is entirely hidden behind the depth pyramid. This
+// is the same test as `aabb_is_occluded` in `mesh_preprocess.wgsl`, applied
+// to the bounding box of the sphere.
+fn sphere_is_occluded(center: vec3<f32>, radius: f32) -> bool {
+    // Find the bounds of the sphere in normalized device coordinates.
+    var ndc_min = vec3(1.0e9);
+    var ndc_max = vec3(-1.0e9);
+    for (var i = 0u; i < 8u; i += 1u) {
+        let corner_sign = vec3(
+            select(-1.0, 1.0, (i & 1u) != 0u),
+            select(-1.0, 1.0, (i & 2u) != 0u),
+            select(-1.0, 1.0, (i & 4u) != 0u),
+        );
+        let clip = cull_view.clip_from_world * vec4(center + radius * corner_sign, 1.0);
+
+        // If the sphere crosses the camera plane, its projection isn't
+        // bounded, so treat it as visible.
+        if (clip.w <= 0.0) {
+            return false;
+        }
+
+        let ndc = clip.xyz / clip.w;
+        ndc_min = min(ndc_min, ndc);
+        ndc_max = max(ndc_max, ndc);
+    }
+
+    // Find the bounds of the sphere on the depth buffer, in UV coordinates.
+    let viewport = cull_view.viewport;
+    let viewport_uv_min = saturate(vec2(ndc_min.x, -ndc_max.y) * 0.5 + 0.5);
+    let viewport_uv_max = saturate(vec2(ndc_max.x, -ndc_min.y) * 0.5 + 0.5);
+    let uv_min = (viewport.xy + viewport_uv_min * viewport.zw) / cull_view.depth_size;
+    let uv_max = (viewport.xy + viewport_uv_max * viewport.zw) / cull_view.depth_size;
+
+    // Pick the finest mip level at which the bounds span at most 2×2 texels.
+    let mip_level_count = textureNumLevels(depth_pyramid);
+    var mip_level = 0u;
+    var mip_size = vec2<f32>(textureDimensions(depth_pyramid, 0u));
+    while (mip_level + 1u < mip_level_count &&
+            any((uv_max - uv_min) * mip_size > vec2(1.0))) {
+        mip_level += 1u;
+        mip_size = vec2<f32>(textureDimensions(depth_pyramid, mip_level));
+    }
+
+    let max_texel = vec2<u32>(mip_size) - 1u;
+    let texel_min = min(vec2<u32>(uv_min * mip_size), max_texel);
+    let texel_max = min(vec2<u32>(uv_max * mip_size), max_texel);
+    let occluder_depth = min(
+        min(
+            textureLoad(depth_pyramid, texel_min, mip_level).r,
+            textureLoad(depth_pyramid, vec2(texel_max.x, texel_min.y), mip_level).r
+        ),
+        min(
+            textureLoad(depth_pyramid, vec2(texel_min.x, texel_max.y), mip_level).r,
+            textureLoad(depth_pyramid, texel_max, mip_level).r
+        ),
+    );
+
+    // With reversed Z, the sphere is hidden if its nearest point is farther
+    // than the farthest occluder.
+    return ndc_max.z < occluder_depth;
+}
+#endif  // OCCLUSION_CULLING
+
+@compute
+@workgroup_size(64)
+fn cull(
+    @builtin(global_invocation_id) global_id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>,
+) {
+    let index = invocation_index(global_id, num_workgroups);
+    if (index >= instance_count) {
+        return;
+    }
+
+    let instance = instances[index];
+    let local_center = instance.position +
+        quat_rotate(instance.rotation, cull_view.bounding_sphere_center * instance.scale);
+    let center = (cull_view.world_from_local * vec4(local_center, 1.0)).xyz;
+    let radius = cull_view.bounding_sphere_radius * instance.scale;
+
+    if (distance(center, cull_view.view_position) - radius > cull_view.max_distance) {
+        return;
+    }
+    for (var i = 0u; i < 6u; i += 1u) {
+        if (dot(cull_view.frustum[i], vec4(center, 1.0)) + radius <= 0.0) {
+            return;
+        }
+    }
+#ifdef OCCLUSION_CULLING
+    if (sphere_is_occluded(center, radius)) {
+        return;
+    }
+#endif  // OCCLUSION_CULLING
+
+    visible_instances[atomicAdd(&indirect_parameters.instance_count, 1u)] = index;
+}
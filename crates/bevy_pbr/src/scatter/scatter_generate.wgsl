@@ -0,0 +1,190 @@
+// Places the instances of a scatter layer at random points of its surface.
+//
+// Each invocation tries one point of the surface, picked uniformly by area,
+// and keeps it with the probability read from the density map. The kept
+// points are appended to `instances`.
+
+#import bevy_pbr::scatter_types::{ScatterInstance, invocation_index}
+#import bevy_render::maths::PI
+
+// See `ScatterGenerationUniform` in `scatter/render.rs`.
+struct ScatterGeneration {
+    candidate_count: u32,
+    seed: u32,
+    min_scale: f32,
+    max_scale: f32,
+    align_to_normal: f32,
+    triangle_count: u32,
+    // The number of columns and rows of the heightmap.
+    grid_size: vec2<u32>,
+    // The extent of the heightmap along the X and Z axes.
+    size: vec2<f32>,
+    // The height of the heightmap where it's 1.
+    height: f32,
+}
+
+// A vertex of a mesh surface. See `ScatterSurfaceVertex` in `scatter/mod.rs`.
+struct SurfaceVertex {
+    position: vec3<f32>,
+    u: f32,
+    normal: vec3<f32>,
+    v: f32,
+}
+
+struct SurfacePoint {
+    position: vec3<f32>,
+    normal: vec3<f32>,
+    uv: vec2<f32>,
+}
+
+@group(0) @binding(0) var<uniform> generation: ScatterGeneration;
+#ifdef HEIGHTMAP
+@group(0) @binding(1) var<storage> heights: array<f32>;
+#else   // HEIGHTMAP
+@group(0) @binding(1) var<storage> vertices: array<SurfaceVertex>;
+@group(0) @binding(2) var<storage> indices: array<u32>;
+// The sum of the areas of each triangle and the ones before it, normalized so
+// that the last one is 1.
+@group(0) @binding(3) var<storage> cumulative_areas: array<f32>;
+#endif  // HEIGHTMAP
+@group(0) @binding(4) var density_map: texture_2d<f32>;
+@group(0) @binding(5) var density_map_sampler: sampler;
+@group(0) @binding(6) var<storage, read_write> instances: array<ScatterInstance>;
+@group(0) @binding(7) var<storage, read_write> instance_count: atomic<u32>;
+
+var<private> rng_state: u32;
+
+// The PCG hash, from "Hash Functions for GPU Rendering" by Jarzynski and
+// Olano.
+fn pcg_hash(input: u32) -> u32 {
+    let state = input * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+// Returns a random number in [0, 1).
+fn random() -> f32 {
+    rng_state = pcg_hash(rng_state);
+    return f32(rng_state >> 8u) / 16777216.0;
+}
+
+#ifdef HEIGHTMAP
+
+fn height_at(texel: vec2<i32>) -> f32 {
+    let clamped = vec2<u32>(clamp(texel, vec2(0), vec2<i32>(generation.grid_size) - 1));
+    return heights[clamped.y * generation.grid_size.x + clamped.x];
+}
+
+// Bilinearly samples the heightmap, like `Heightmap::sample`.
+fn sample_height(uv: vec2<f32>) -> f32 {
+    let position = saturate(uv) * vec2<f32>(generation.grid_size - 1u);
+    let cell = floor(position);
+    let t = position - cell;
+    let texel = vec2<i32>(cell);
+    let top = mix(height_at(texel), height_at(texel + vec2(1, 0)), t.x);
+    let bottom = mix(height_at(texel + vec2(0, 1)), height_at(texel + vec2(1, 1)), t.x);
+    return mix(top, bottom, t.y) * generation.height;
+}
+
+fn sample_surface() -> SurfacePoint {
+    var point: SurfacePoint;
+    point.uv = vec2(random(), random());
+    let xz = (point.uv - 0.5) * generation.size;
+    point.position = vec3(xz.x, sample_height(point.uv), xz.y);
+
+    // Find the normal from the slopes one texel away on each side.
+    let texel_size = 1.0 / vec2<f32>(generation.grid_size - 1u);
+    let dx = sample_height(point.uv + vec2(texel_size.x, 0.0)) -
+        sample_height(point.uv - vec2(texel_size.x, 0.0));
+    let dz = sample_height(point.uv + vec2(0.0, texel_size.y)) -
+        sample_height(point.uv - vec2(0.0, texel_size.y));
+    let step = 2.0 * texel_size * generation.size;
+    point.normal = normalize(vec3(-dx / step.x, 1.0, -dz / step.y));
+    return point;
+}
+
+#else   // HEIGHTMAP
+
+fn sample_surface() -> SurfacePoint {
+    // Pick a triangle with a probability proportional to its area.
+    let target_area = random();
+    var low = 0u;
+    var high = generation.triangle_count - 1u;
+    while (low < high) {
+        let middle = (low + high) / 2u;
+        if (cumulative_areas[middle] < target_area) {
+            low = middle + 1u;
+        } else {
+            high = middle;
+        }
+    }
+
+    let a = vertices[indices[low * 3u]];
+    let b = vertices[indices[low * 3u + 1u]];
+    let c = vertices[indices[low * 3u + 2u]];
+
+    // Pick a point uniformly in the triangle.
+    let r = sqrt(random());
+    let s = random();
+    let weights = vec3(1.0 - r, r * (1.0 - s), r * s);
+
+    var point: SurfacePoint;
+    point.position = a.position * weights.x + b.position * weights.y + c.position * weights.z;
+    let normal = a.normal * weights.x + b.normal * weights.y + c.normal * weights.z;
+    point.normal = select(vec3(0.0, 1.0, 0.0), normalize(normal), dot(normal, normal) > 1e-8);
+    point.uv = vec2(a.u, a.v) * weights.x + vec2(b.u, b.v) * weights.y + vec2(c.u, c.v) * weights.z;
+    return point;
+}
+
+#endif  // HEIGHTMAP
+
+// Returns the quaternion that rotates the +Y axis onto `to`.
+fn rotation_from_up(to: vec3<f32>) -> vec4<f32> {
+    let up = vec3(0.0, 1.0, 0.0);
+    let w = 1.0 + dot(up, to);
+    if (w < 1e-6) {
+        // `to` points downward, so turn around the X axis.
+        return vec4(1.0, 0.0, 0.0, 0.0);
+    }
+    return normalize(vec4(cross(up, to), w));
+}
+
+fn quat_mul(a: vec4<f32>, b: vec4<f32>) -> vec4<f32> {
+    return vec4(
+        a.w * b.xyz + b.w * a.xyz + cross(a.xyz, b.xyz),
+        a.w * b.w - dot(a.xyz, b.xyz)
+    );
+}
+
+@compute
+@workgroup_size(64)
+fn generate(
+    @builtin(global_invocation_id) global_id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>,
+) {
+    let index = invocation_index(global_id, num_workgroups);
+    if (index >= generation.candidate_count) {
+        return;
+    }
+
+    rng_state = pcg_hash(index ^ pcg_hash(generation.seed));
+    let point = sample_surface();
+
+    let density = textureSampleLevel(density_map, density_map_sampler, point.uv, 0.0).r;
+    if (random() >= density) {
+        return;
+    }
+
+    // Stand the instance on the surface, then spin it around its up axis.
+    let up = mix(vec3(0.0, 1.0, 0.0), point.normal, generation.align_to_normal);
+    let tilt = rotation_from_up(select(vec3(0.0, 1.0, 0.0), normalize(up), dot(up, up) > 1e-8));
+    let half_yaw = random() * PI;
+    let yaw = vec4(0.0, sin(half_yaw), 0.0, cos(half_yaw));
+
+    var instance: ScatterInstance;
+    instance.position = point.position;
+    instance.scale = mix(generation.min_scale, generation.max_scale, random());
+    instance.rotation = quat_mul(tilt, yaw);
+
+    instances[atomicAdd(&instance_count, 1u)] = instance;
+}
@@ -0,0 +1,22 @@
+#define_import_path bevy_pbr::scatter_types
+
+// An instance placed by a scatter layer, in the local space of the layer.
+struct ScatterInstance {
+    position: vec3<f32>,
+    scale: f32,
+    // A quaternion.
+    rotation: vec4<f32>,
+}
+
+// Rotates a vector by a quaternion.
+fn quat_rotate(q: vec4<f32>, v: vec3<f32>) -> vec3<f32> {
+    let t = 2.0 * cross(q.xyz, v);
+    return v + q.w * t + cross(q.xyz, t);
+}
+
+// Returns the index of an invocation of a compute shader dispatched over
+// multiple rows of workgroups of 64 invocations, as there can only be 65535
+// workgroups along each axis.
+fn invocation_index(global_id: vec3<u32>, num_workgroups: vec3<u32>) -> u32 {
+    return global_id.y * num_workgroups.x * 64u + global_id.x;
+}
@@ -0,0 +1,617 @@
+//! Screen space global illumination implemented via raymarching.
+
+use bevy_app::{App, Plugin};
+use bevy_asset::{load_internal_asset, Handle};
+use bevy_core_pipeline::{
+    core_3d::{
+        graph::{Core3d, Node3d},
+        Camera3d, DEPTH_TEXTURE_SAMPLING_SUPPORTED,
+    },
+    fullscreen_vertex_shader,
+    prepass::{DepthPrepass, NormalPrepass, ViewPrepassTextures},
+};
+use bevy_derive::{Deref, DerefMut};
+use bevy_ecs::{
+    component::{require, Component},
+    entity::Entity,
+    query::{QueryItem, With},
+    reflect::ReflectComponent,
+    schedule::IntoSystemConfigs as _,
+    system::{lifetimeless::Read, Commands, Query, Res, ResMut, Resource},
+    world::{FromWorld, World},
+};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::{
+    camera::Camera,
+    globals::{GlobalsBuffer, GlobalsUniform},
+    render_graph::{NodeRunError, RenderGraphApp, RenderGraphContext, ViewNode, ViewNodeRunner},
+    render_resource::{
+        binding_types, AddressMode, BindGroup, BindGroupEntries, BindGroupLayout,
+        BindGroupLayoutEntries, CachedRenderPipelineId, ColorTargetState, ColorWrites,
+        DynamicUniformBuffer, FilterMode, FragmentState, Operations, PipelineCache,
+        RenderPassColorAttachment, RenderPassDescriptor, RenderPipelineDescriptor, Sampler,
+        SamplerBindingType, SamplerDescriptor, Shader, ShaderStages, ShaderType, TextureDescriptor,
+        TextureDimension, TextureFormat, TextureSampleType, TextureUsages,
+    },
+    renderer::{RenderContext, RenderDevice, RenderQueue},
+    sync_component::SyncComponentPlugin,
+    sync_world::RenderEntity,
+    texture::{CachedTexture, FallbackImageZero, TextureCache},
+    view::{Msaa, ViewTarget, ViewUniform, ViewUniformOffset, ViewUniforms},
+    Extract, ExtractSchedule, Render, RenderApp, RenderSet,
+};
+use bevy_utils::{once, prelude::default};
+use tracing::{error, info};
+
+use crate::graph::NodePbr;
+
+const SSGI_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(5901534207548723412);
+
+/// The format of the texture that the gathered light is written to.
+const SSGI_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+
+/// Enables screen-space global illumination for a camera.
+pub struct ScreenSpaceGlobalIlluminationPlugin;
+
+/// Add this component to a camera to enable *screen-space global illumination*
+/// (SSGI).
+///
+/// SSGI adds one bounce of diffuse light to the ambient lighting of each
+/// pixel, by marching short rays in random directions of the hemisphere around
+/// its normal through the depth buffer, and gathering the lit color of the
+/// surfaces that they hit.
+///
+/// The light is gathered before the main pass, so SSGI works with both the
+/// forward and the deferred renderers. It needs the [`DepthPrepass`] and
+/// [`NormalPrepass`] components, which are inserted automatically, and
+/// requires [`Msaa::Off`]. Adding a
+/// [`MotionVectorPrepass`](bevy_core_pipeline::prepass::MotionVectorPrepass)
+/// lets moving surfaces keep their light.
+///
+/// The lit color of the surfaces is read from the previous frame, so the
+/// gathered light lags one frame behind the lighting of the scene.
+///
+/// Only a few rays are traced per pixel, so the result is noisy. The random
+/// directions change every frame, so temporal anti-aliasing can be used to
+/// smooth it out.
+///
+/// As with all screen-space techniques, SSGI can only gather light from
+/// objects on screen. Light that misses all of them is left to the other
+/// ambient lighting, such as [`EnvironmentMapLight`](crate::EnvironmentMapLight)
+/// and [`AmbientLight`](crate::AmbientLight).
+///
+/// Screen-space global illumination is presently unsupported on WebGL 2,
+/// because depth textures can't be sampled there.
+#[derive(Clone, Copy, Component, Reflect)]
+#[reflect(Component, Default)]
+#[require(DepthPrepass, NormalPrepass)]
+#[doc(alias = "Ssgi")]
+pub struct ScreenSpaceGlobalIllumination {
+    /// A multiplier for the gathered light.
+    pub intensity: f32,
+
+    /// The number of rays traced for each pixel. Must not be zero.
+    ///
+    /// Higher values result in less noise, but take more GPU time.
+    pub sample_count: u32,
+
+    /// The maximum distance in world units that the rays travel.
+    ///
+    /// Longer rays gather light from farther surfaces, but are more likely to
+    /// miss thin objects.
+    pub max_distance: f32,
+
+    /// When marching the depth buffer, we only have 2.5D information and don't
+    /// know how thick surfaces are. We shall assume that the depth buffer
+    /// fragments are cuboids with a constant thickness defined by this
+    /// parameter.
+    pub thickness: f32,
+
+    /// The number of steps to be taken at regular intervals along each ray to
+    /// find an intersection. Must not be zero.
+    pub linear_steps: u32,
+
+    /// Number of steps in a bisection (binary search) to perform once the
+    /// linear search has found an intersection.
+    pub bisection_steps: u32,
+}
+
+/// A version of [`ScreenSpaceGlobalIllumination`] for upload to the GPU.
+///
+/// For more information on these fields, see the corresponding documentation in
+/// [`ScreenSpaceGlobalIllumination`].
+#[derive(Clone, Copy, PartialEq, Debug, ShaderType)]
+pub struct ScreenSpaceGlobalIlluminationUniform {
+    intensity: f32,
+    sample_count: u32,
+    max_distance: f32,
+    thickness: f32,
+    linear_steps: u32,
+    bisection_steps: u32,
+}
+
+/// The node in the render graph that gathers screen space global illumination
+/// before the main pass.
+#[derive(Default)]
+pub struct ScreenSpaceGlobalIlluminationNode;
+
+/// The node in the render graph that saves the lit color of the opaque
+/// surfaces, from which the next frame gathers its light.
+#[derive(Default)]
+pub struct ScreenSpaceGlobalIlluminationHistoryNode;
+
+/// Information relating to the render pipeline for the screen space global
+/// illumination shader.
+#[derive(Resource)]
+pub struct ScreenSpaceGlobalIlluminationPipeline {
+    bind_group_layout: BindGroupLayout,
+    history_sampler: Sampler,
+    pipeline_id: CachedRenderPipelineId,
+}
+
+/// A GPU buffer that stores the screen space global illumination settings for
+/// each view.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct ScreenSpaceGlobalIlluminationBuffer(
+    pub DynamicUniformBuffer<ScreenSpaceGlobalIlluminationUniform>,
+);
+
+/// A component that stores the offset within the
+/// [`ScreenSpaceGlobalIlluminationBuffer`] for each view.
+#[derive(Component, Default, Deref, DerefMut)]
+pub struct ViewScreenSpaceGlobalIlluminationUniformOffset(u32);
+
+/// The textures of a view with screen space global illumination.
+#[derive(Component)]
+pub struct ScreenSpaceGlobalIlluminationTextures {
+    /// The light gathered for each pixel, which the PBR shaders add to their
+    /// indirect light.
+    pub irradiance: CachedTexture,
+    /// The lit color of the opaque surfaces of the previous frame.
+    history: CachedTexture,
+}
+
+/// The bind group of the screen space global illumination pass of a view.
+#[derive(Component, Deref, DerefMut)]
+pub struct ScreenSpaceGlobalIlluminationBindGroup(BindGroup);
+
+impl Plugin for ScreenSpaceGlobalIlluminationPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(app, SSGI_SHADER_HANDLE, "ssgi.wgsl", Shader::from_wgsl);
+
+        app.register_type::<ScreenSpaceGlobalIllumination>()
+            .add_plugins(SyncComponentPlugin::<ScreenSpaceGlobalIllumination>::default());
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .init_resource::<ScreenSpaceGlobalIlluminationBuffer>()
+            .add_systems(ExtractSchedule, extract_ssgi_settings)
+            .add_systems(
+                Render,
+                (
+                    (prepare_ssgi_settings, prepare_ssgi_textures)
+                        .in_set(RenderSet::PrepareResources),
+                    prepare_ssgi_bind_groups.in_set(RenderSet::PrepareBindGroups),
+                ),
+            )
+            .add_render_graph_node::<ViewNodeRunner<ScreenSpaceGlobalIlluminationNode>>(
+                Core3d,
+                NodePbr::ScreenSpaceGlobalIllumination,
+            )
+            .add_render_graph_node::<ViewNodeRunner<ScreenSpaceGlobalIlluminationHistoryNode>>(
+                Core3d,
+                NodePbr::ScreenSpaceGlobalIlluminationHistory,
+            )
+            .add_render_graph_edges(
+                Core3d,
+                (
+                    Node3d::EndPrepasses,
+                    NodePbr::ScreenSpaceGlobalIllumination,
+                    Node3d::StartMainPass,
+                ),
+            )
+            .add_render_graph_edges(
+                Core3d,
+                (
+                    Node3d::MainOpaquePass,
+                    NodePbr::ScreenSpaceGlobalIlluminationHistory,
+                    Node3d::MainTransmissivePass,
+                ),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app.init_resource::<ScreenSpaceGlobalIlluminationPipeline>();
+    }
+}
+
+impl Default for ScreenSpaceGlobalIllumination {
+    fn default() -> Self {
+        Self {
+            intensity: 1.0,
+            sample_count: 4,
+            max_distance: 2.0,
+            thickness: 0.25,
+            linear_steps: 8,
+            bisection_steps: 2,
+        }
+    }
+}
+
+impl ViewNode for ScreenSpaceGlobalIlluminationNode {
+    type ViewQuery = (
+        Read<ScreenSpaceGlobalIlluminationTextures>,
+        Read<ScreenSpaceGlobalIlluminationBindGroup>,
+        Read<ViewUniformOffset>,
+        Read<ViewScreenSpaceGlobalIlluminationUniformOffset>,
+    );
+
+    fn run<'w>(
+        &self,
+        _: &mut RenderGraphContext,
+        render_context: &mut RenderContext<'w>,
+        (ssgi_textures, ssgi_bind_group, view_uniform_offset, view_ssgi_offset): QueryItem<
+            'w,
+            Self::ViewQuery,
+        >,
+        world: &'w World,
+    ) -> Result<(), NodeRunError> {
+        // Grab the render pipeline.
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let ssgi_pipeline = world.resource::<ScreenSpaceGlobalIlluminationPipeline>();
+        let Some(render_pipeline) = pipeline_cache.get_render_pipeline(ssgi_pipeline.pipeline_id)
+        else {
+            return Ok(());
+        };
+
+        // Build the SSGI render pass.
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("ssgi_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: &ssgi_textures.irradiance.default_view,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        // Perform the SSGI render pass.
+        render_pass.set_render_pipeline(render_pipeline);
+        render_pass.set_bind_group(
+            0,
+            ssgi_bind_group,
+            &[view_uniform_offset.offset, **view_ssgi_offset],
+        );
+        render_pass.draw(0..3, 0..1);
+
+        Ok(())
+    }
+}
+
+impl ViewNode for ScreenSpaceGlobalIlluminationHistoryNode {
+    type ViewQuery = (
+        Read<ViewTarget>,
+        Read<ScreenSpaceGlobalIlluminationTextures>,
+    );
+
+    fn run<'w>(
+        &self,
+        _: &mut RenderGraphContext,
+        render_context: &mut RenderContext<'w>,
+        (view_target, ssgi_textures): QueryItem<'w, Self::ViewQuery>,
+        _: &'w World,
+    ) -> Result<(), NodeRunError> {
+        let history = &ssgi_textures.history.texture;
+        render_context.command_encoder().copy_texture_to_texture(
+            view_target.main_texture().as_image_copy(),
+            history.as_image_copy(),
+            history.size(),
+        );
+
+        Ok(())
+    }
+}
+
+impl FromWorld for ScreenSpaceGlobalIlluminationPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+
+        let bind_group_layout = render_device.create_bind_group_layout(
+            "ssgi_bind_group_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::FRAGMENT,
+                (
+                    // Depth prepass
+                    binding_types::texture_depth_2d(),
+                    // Normal prepass
+                    binding_types::texture_2d(TextureSampleType::Float { filterable: false }),
+                    // Motion vector prepass
+                    binding_types::texture_2d(TextureSampleType::Float { filterable: false }),
+                    // History
+                    binding_types::texture_2d(TextureSampleType::Float { filterable: true }),
+                    binding_types::sampler(SamplerBindingType::Filtering),
+                    binding_types::uniform_buffer::<ViewUniform>(true),
+                    binding_types::uniform_buffer::<GlobalsUniform>(false),
+                    binding_types::uniform_buffer::<ScreenSpaceGlobalIlluminationUniform>(true),
+                ),
+            ),
+        );
+
+        let history_sampler = render_device.create_sampler(&SamplerDescriptor {
+            label: Some("ssgi_history_sampler"),
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..default()
+        });
+
+        let pipeline_id =
+            world
+                .resource_mut::<PipelineCache>()
+                .queue_render_pipeline(RenderPipelineDescriptor {
+                    label: Some("ssgi_pipeline".into()),
+                    layout: vec![bind_group_layout.clone()],
+                    vertex: fullscreen_vertex_shader::fullscreen_shader_vertex_state(),
+                    fragment: Some(FragmentState {
+                        shader: SSGI_SHADER_HANDLE,
+                        shader_defs: vec![],
+                        entry_point: "fragment".into(),
+                        targets: vec![Some(ColorTargetState {
+                            format: SSGI_TEXTURE_FORMAT,
+                            blend: None,
+                            write_mask: ColorWrites::ALL,
+                        })],
+                    }),
+                    push_constant_ranges: vec![],
+                    primitive: default(),
+                    depth_stencil: None,
+                    multisample: default(),
+                    zero_initialize_workgroup_memory: false,
+                });
+
+        Self {
+            bind_group_layout,
+            history_sampler,
+            pipeline_id,
+        }
+    }
+}
+
+fn extract_ssgi_settings(
+    mut commands: Commands,
+    cameras: Extract<
+        Query<
+            (RenderEntity, &Camera, &ScreenSpaceGlobalIllumination, &Msaa),
+            (With<Camera3d>, With<DepthPrepass>, With<NormalPrepass>),
+        >,
+    >,
+) {
+    if !DEPTH_TEXTURE_SAMPLING_SUPPORTED {
+        once!(info!(
+            "Disabling screen-space global illumination on this platform because depth \
+            textures aren't supported correctly"
+        ));
+        return;
+    }
+
+    for (entity, camera, ssgi_settings, msaa) in &cameras {
+        if *msaa != Msaa::Off {
+            error!(
+                "SSGI is being used which requires Msaa::Off, but Msaa is currently set to Msaa::{:?}",
+                *msaa
+            );
+            continue;
+        }
+        let mut entity_commands = commands
+            .get_entity(entity)
+            .expect("SSGI entity wasn't synced.");
+        if camera.is_active {
+            entity_commands.insert(*ssgi_settings);
+        } else {
+            entity_commands.remove::<ScreenSpaceGlobalIllumination>();
+        }
+    }
+}
+
+/// Gathers up screen space global illumination settings for each applicable
+/// view and writes them into a GPU buffer.
+pub fn prepare_ssgi_settings(
+    mut commands: Commands,
+    views: Query<(Entity, &ScreenSpaceGlobalIllumination)>,
+    mut ssgi_settings_buffer: ResMut<ScreenSpaceGlobalIlluminationBuffer>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+) {
+    let Some(mut writer) =
+        ssgi_settings_buffer.get_writer(views.iter().len(), &render_device, &render_queue)
+    else {
+        return;
+    };
+
+    for (view, ssgi_settings) in views.iter() {
+        commands
+            .entity(view)
+            .insert(ViewScreenSpaceGlobalIlluminationUniformOffset(
+                writer.write(&ScreenSpaceGlobalIlluminationUniform::from(*ssgi_settings)),
+            ));
+    }
+}
+
+fn prepare_ssgi_textures(
+    mut commands: Commands,
+    mut texture_cache: ResMut<TextureCache>,
+    render_device: Res<RenderDevice>,
+    views: Query<(Entity, &ViewTarget), With<ScreenSpaceGlobalIllumination>>,
+) {
+    for (entity, view_target) in &views {
+        // The history is copied from the main texture, so it must have the
+        // same size and format. The gathered light is read with the fragment
+        // coordinates of the main pass, so it has the same size too.
+        let size = view_target.main_texture().size();
+
+        let irradiance = texture_cache.get(
+            &render_device,
+            TextureDescriptor {
+                label: Some("ssgi_irradiance_texture"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: SSGI_TEXTURE_FORMAT,
+                usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+        );
+
+        let history = texture_cache.get(
+            &render_device,
+            TextureDescriptor {
+                label: Some("ssgi_history_texture"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: view_target.main_texture_format(),
+                usage: TextureUsages::COPY_DST | TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+        );
+
+        commands
+            .entity(entity)
+            .insert(ScreenSpaceGlobalIlluminationTextures {
+                irradiance,
+                history,
+            });
+    }
+}
+
+fn prepare_ssgi_bind_groups(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    ssgi_pipeline: Res<ScreenSpaceGlobalIlluminationPipeline>,
+    view_uniforms: Res<ViewUniforms>,
+    globals_buffer: Res<GlobalsBuffer>,
+    ssgi_settings_buffer: Res<ScreenSpaceGlobalIlluminationBuffer>,
+    fallback_image_zero: Res<FallbackImageZero>,
+    views: Query<(
+        Entity,
+        &ScreenSpaceGlobalIlluminationTextures,
+        &ViewPrepassTextures,
+    )>,
+) {
+    let (Some(view_binding), Some(globals_binding), Some(ssgi_settings_binding)) = (
+        view_uniforms.uniforms.binding(),
+        globals_buffer.buffer.binding(),
+        ssgi_settings_buffer.binding(),
+    ) else {
+        return;
+    };
+
+    for (entity, ssgi_textures, prepass_textures) in &views {
+        let (Some(depth_view), Some(normal_view)) = (
+            prepass_textures.depth_view(),
+            prepass_textures.normal_view(),
+        ) else {
+            continue;
+        };
+        // Without motion vectors, the surfaces are assumed not to move. The
+        // shader clamps the coordinates it loads, so any texture works.
+        let motion_vectors_view = prepass_textures
+            .motion_vectors_view()
+            .unwrap_or(&fallback_image_zero.texture_view);
+
+        let bind_group = render_device.create_bind_group(
+            "ssgi_bind_group",
+            &ssgi_pipeline.bind_group_layout,
+            &BindGroupEntries::sequential((
+                depth_view,
+                normal_view,
+                motion_vectors_view,
+                &ssgi_textures.history.default_view,
+                &ssgi_pipeline.history_sampler,
+                view_binding.clone(),
+                globals_binding.clone(),
+                ssgi_settings_binding.clone(),
+            )),
+        );
+
+        commands
+            .entity(entity)
+            .insert(ScreenSpaceGlobalIlluminationBindGroup(bind_group));
+    }
+}
+
+impl From<ScreenSpaceGlobalIllumination> for ScreenSpaceGlobalIlluminationUniform {
+    fn from(settings: ScreenSpaceGlobalIllumination) -> Self {
+        Self {
+            intensity: settings.intensity,
+            sample_count: settings.sample_count.max(1),
+            max_distance: settings.max_distance,
+            thickness: settings.thickness,
+            linear_steps: settings.linear_steps.max(1),
+            bisection_steps: settings.bisection_steps,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MeshPipelineKey;
+    use bevy_core_pipeline::prepass::DeferredPrepass;
+
+    #[test]
+    fn ssgi_requires_forward_prepasses() {
+        let mut world = World::new();
+        let entity = world.spawn(ScreenSpaceGlobalIllumination::default()).id();
+        let entity = world.entity(entity);
+
+        assert!(entity.contains::<DepthPrepass>());
+        assert!(entity.contains::<NormalPrepass>());
+        // The forward renderer is supported, so the deferred prepass isn't
+        // forced on the camera.
+        assert!(!entity.contains::<DeferredPrepass>());
+    }
+
+    #[test]
+    fn ssgi_uniform_clamps_step_counts() {
+        let uniform = ScreenSpaceGlobalIlluminationUniform::from(ScreenSpaceGlobalIllumination {
+            sample_count: 0,
+            linear_steps: 0,
+            ..default()
+        });
+        assert_eq!(uniform.sample_count, 1);
+        assert_eq!(uniform.linear_steps, 1);
+
+        let settings = ScreenSpaceGlobalIllumination::default();
+        let uniform = ScreenSpaceGlobalIlluminationUniform::from(settings);
+        assert_eq!(uniform.intensity, settings.intensity);
+        assert_eq!(uniform.sample_count, settings.sample_count);
+        assert_eq!(uniform.max_distance, settings.max_distance);
+        assert_eq!(uniform.thickness, settings.thickness);
+        assert_eq!(uniform.linear_steps, settings.linear_steps);
+        assert_eq!(uniform.bisection_steps, settings.bisection_steps);
+    }
+
+    #[test]
+    fn ssgi_pipeline_key_keeps_other_bits() {
+        for samples in [1, 2, 4, 8] {
+            let key = MeshPipelineKey::from_msaa_samples(samples)
+                | MeshPipelineKey::SCREEN_SPACE_GLOBAL_ILLUMINATION;
+            assert_eq!(key.msaa_samples(), samples);
+            assert!(!MeshPipelineKey::from_msaa_samples(samples)
+                .contains(MeshPipelineKey::SCREEN_SPACE_GLOBAL_ILLUMINATION));
+        }
+    }
+}
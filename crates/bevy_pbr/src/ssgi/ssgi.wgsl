@@ -0,0 +1,175 @@
+// Gathers screen-space global illumination before the main pass, into a
+// texture that the PBR shaders add to their indirect light.
+
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_pbr::utils::{rand_f, rand_vec2f}
+#import bevy_render::{
+    globals::Globals,
+    maths::{orthonormalize, PI},
+    view::View,
+}
+
+// The settings of the SSGI pass. See `ScreenSpaceGlobalIllumination` for the
+// meaning of each field.
+struct ScreenSpaceGlobalIlluminationSettings {
+    intensity: f32,
+    sample_count: u32,
+    max_distance: f32,
+    thickness: f32,
+    linear_steps: u32,
+    bisection_steps: u32,
+}
+
+// A point along a ray, projected on the screen.
+struct RaySample {
+    // The position of the sample on the render target, in pixels.
+    pixel: vec2<f32>,
+    // How far behind the surface in the depth buffer the sample is, in view
+    // space. Negative in front of the surface.
+    depth_behind: f32,
+    on_screen: bool,
+}
+
+@group(0) @binding(0) var depth_prepass_texture: texture_depth_2d;
+@group(0) @binding(1) var normal_prepass_texture: texture_2d<f32>;
+@group(0) @binding(2) var motion_vector_prepass_texture: texture_2d<f32>;
+// The lit color of the opaque surfaces of the previous frame.
+@group(0) @binding(3) var history_texture: texture_2d<f32>;
+@group(0) @binding(4) var history_sampler: sampler;
+@group(0) @binding(5) var<uniform> view: View;
+@group(0) @binding(6) var<uniform> globals: Globals;
+@group(0) @binding(7) var<uniform> ssgi_settings: ScreenSpaceGlobalIlluminationSettings;
+
+fn ndc_to_pixel(ndc: vec2<f32>) -> vec2<f32> {
+    return view.viewport.xy + (ndc * vec2(0.5, -0.5) + 0.5) * view.viewport.zw;
+}
+
+fn pixel_to_ndc(pixel: vec2<f32>) -> vec2<f32> {
+    return ((pixel - view.viewport.xy) / view.viewport.zw - 0.5) * vec2(2.0, -2.0);
+}
+
+fn view_z(ndc: vec3<f32>) -> f32 {
+    let view_position = view.view_from_clip * vec4(ndc, 1.0);
+    return view_position.z / view_position.w;
+}
+
+fn sample_ray(world_position: vec3<f32>) -> RaySample {
+    var ray_sample: RaySample;
+    let clip_position = view.clip_from_world * vec4(world_position, 1.0);
+    let ndc = clip_position.xyz / clip_position.w;
+    ray_sample.on_screen = clip_position.w > 0.0 && all(abs(ndc.xy) <= vec2(1.0));
+    if (!ray_sample.on_screen) {
+        return ray_sample;
+    }
+    ray_sample.pixel = ndc_to_pixel(ndc.xy);
+
+    let surface_depth = textureLoad(depth_prepass_texture, vec2<i32>(ray_sample.pixel), 0);
+    if (surface_depth == 0.0) {
+        // Nothing is behind the background.
+        ray_sample.depth_behind = -1.0;
+    } else {
+        ray_sample.depth_behind = view_z(vec3(ndc.xy, surface_depth)) - view_z(ndc);
+    }
+    return ray_sample;
+}
+
+// Returns the light of the surface at the given pixel, read from the lit color
+// of the previous frame.
+fn surface_light(pixel: vec2<f32>) -> vec3<f32> {
+    // The fallback texture used without a motion vector prepass is smaller
+    // than the screen, so the coordinates are clamped.
+    let motion_vector_coords = min(
+        vec2<u32>(pixel),
+        textureDimensions(motion_vector_prepass_texture) - 1u
+    );
+    let motion_vector = textureLoad(motion_vector_prepass_texture, motion_vector_coords, 0).xy;
+    let history_uv = (pixel - motion_vector * view.viewport.zw) /
+        vec2<f32>(textureDimensions(history_texture));
+
+    // The color of the previous frame is exposed, unlike the indirect light that
+    // the PBR shaders add up.
+    return textureSampleLevel(history_texture, history_sampler, history_uv, 0.0).rgb /
+        view.exposure;
+}
+
+// Returns the light arriving at `origin` from the surface hit by a ray marched
+// in the direction `direction`, or zero if the ray missed.
+fn trace_ray(origin: vec3<f32>, direction: vec3<f32>, jitter: f32) -> vec3<f32> {
+    let step_length = ssgi_settings.max_distance / f32(ssgi_settings.linear_steps);
+
+    var previous_distance = 0.0;
+    for (var step_index = 0u; step_index < ssgi_settings.linear_steps; step_index += 1u) {
+        let ray_distance = (f32(step_index) + jitter) * step_length;
+        let ray_sample = sample_ray(origin + direction * ray_distance);
+        if (!ray_sample.on_screen) {
+            break;
+        }
+
+        if (ray_sample.depth_behind > 0.0) {
+            // Refine the intersection between the last two samples.
+            var hit = ray_sample;
+            var start = previous_distance;
+            var end = ray_distance;
+            for (var bisection = 0u; bisection < ssgi_settings.bisection_steps; bisection += 1u) {
+                let middle = 0.5 * (start + end);
+                let middle_sample = sample_ray(origin + direction * middle);
+                if (middle_sample.depth_behind > 0.0) {
+                    hit = middle_sample;
+                    end = middle;
+                } else {
+                    start = middle;
+                }
+            }
+
+            // Otherwise, the ray went behind the surface, and keeps marching.
+            if (hit.depth_behind <= ssgi_settings.thickness) {
+                return surface_light(hit.pixel);
+            }
+        }
+
+        previous_distance = ray_distance;
+    }
+
+    return vec3(0.0);
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let pixel = vec2<i32>(in.position.xy);
+    let depth = textureLoad(depth_prepass_texture, pixel, 0);
+
+    // Don't do anything for the background.
+    if (depth == 0.0) {
+        return vec4(0.0);
+    }
+
+    // Reconstruct the surface from the prepasses.
+    let world_position = view.world_from_clip * vec4(pixel_to_ndc(in.position.xy), depth, 1.0);
+    let P_world = world_position.xyz / world_position.w;
+    let N = normalize(textureLoad(normal_prepass_texture, pixel, 0).xyz * 2.0 - 1.0);
+
+    // Build a basis around the normal to orient the rays in.
+    let up = select(vec3(0.0, 1.0, 0.0), vec3(1.0, 0.0, 0.0), abs(N.y) > 0.99);
+    let tangent_to_world = orthonormalize(N, up);
+
+    // Seed the random number generator differently for each pixel and frame,
+    // so that the noise can be resolved by temporal anti-aliasing.
+    var rng = u32(in.position.x) * 1973u + u32(in.position.y) * 9277u + globals.frame_count * 26699u;
+
+    // Trace cosine-weighted rays over the hemisphere. With this distribution,
+    // the irradiance is the average of the light that the rays gather, without
+    // any further weighting.
+    var gathered_light = vec3(0.0);
+    for (var sample_index = 0u; sample_index < ssgi_settings.sample_count; sample_index += 1u) {
+        let xi = rand_vec2f(&rng);
+        let radius = sqrt(xi.x);
+        let phi = 2.0 * PI * xi.y;
+        let L_tangent = vec3(radius * cos(phi), radius * sin(phi), sqrt(1.0 - xi.x));
+        // Keep the first step away from the surface the rays start from.
+        let jitter = 0.5 + 0.5 * rand_f(&rng);
+        gathered_light += trace_ray(P_world, tangent_to_world * L_tangent, jitter);
+    }
+    gathered_light /= f32(ssgi_settings.sample_count);
+
+    return vec4(gathered_light * ssgi_settings.intensity, 1.0);
+}
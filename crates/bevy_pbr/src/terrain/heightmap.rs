@@ -0,0 +1,145 @@
+use bevy_asset::{io::Reader, Asset, AssetLoader, LoadContext};
+use bevy_image::Image;
+use bevy_math::{UVec2, Vec2};
+use bevy_reflect::TypePath;
+use bevy_render::render_resource::TextureFormat;
+use thiserror::Error;
+
+/// A grid of heights that a [`Terrain`](super::Terrain) is built from.
+///
+/// The heights are normalized: 0 is the bottom of the terrain, and 1 is at
+/// [`Terrain::height`](super::Terrain::height) above it. The first row of the
+/// grid is at the -Z edge of the terrain, and the first column at its -X edge.
+#[derive(Asset, TypePath, Clone, Debug)]
+pub struct Heightmap {
+    size: UVec2,
+    heights: Vec<f32>,
+}
+
+/// An error that occurs when creating or loading a [`Heightmap`].
+#[derive(Error, Debug)]
+pub enum HeightmapError {
+    #[error("the heightmap is {width}x{height}, but it contains {len} heights")]
+    WrongLength { width: u32, height: u32, len: usize },
+    #[error("the heightmap needs at least 2x2 heights")]
+    TooSmall,
+    #[error("`.r16` heightmaps must be square, but the file contains {0} heights")]
+    NotSquare(usize),
+    #[error("images with the format {0:?} can't be used as heightmaps")]
+    UnsupportedFormat(TextureFormat),
+    #[error("failed to read the heightmap: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl Heightmap {
+    /// Creates a heightmap with the given number of columns and rows, from
+    /// heights between 0 and 1 listed row by row.
+    pub fn new(size: UVec2, heights: Vec<f32>) -> Result<Self, HeightmapError> {
+        if heights.len() != (size.x as usize) * (size.y as usize) {
+            return Err(HeightmapError::WrongLength {
+                width: size.x,
+                height: size.y,
+                len: heights.len(),
+            });
+        }
+        if size.x < 2 || size.y < 2 {
+            return Err(HeightmapError::TooSmall);
+        }
+        Ok(Self { size, heights })
+    }
+
+    /// Creates a heightmap from the first channel of an [`Image`].
+    ///
+    /// The image must have an 8 or 16 bit normalized, or a 32 bit float
+    /// format.
+    pub fn from_image(image: &Image) -> Result<Self, HeightmapError> {
+        let size = image.size();
+        let data = &image.data;
+        let heights = match image.texture_descriptor.format {
+            TextureFormat::R8Unorm => data.iter().map(|&value| value as f32 / 255.0).collect(),
+            TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => data
+                .chunks_exact(4)
+                .map(|texel| texel[0] as f32 / 255.0)
+                .collect(),
+            TextureFormat::R16Unorm => data
+                .chunks_exact(2)
+                .map(|texel| u16::from_le_bytes([texel[0], texel[1]]) as f32 / 65535.0)
+                .collect(),
+            TextureFormat::R32Float => data
+                .chunks_exact(4)
+                .map(|texel| f32::from_le_bytes([texel[0], texel[1], texel[2], texel[3]]))
+                .collect(),
+            format => return Err(HeightmapError::UnsupportedFormat(format)),
+        };
+        Self::new(size, heights)
+    }
+
+    /// Parses a square heightmap from raw little-endian 16 bit heights, as
+    /// exported by most terrain editors.
+    pub fn from_r16(bytes: &[u8]) -> Result<Self, HeightmapError> {
+        let heights: Vec<f32> = bytes
+            .chunks_exact(2)
+            .map(|texel| u16::from_le_bytes([texel[0], texel[1]]) as f32 / 65535.0)
+            .collect();
+        let side = (heights.len() as f64).sqrt() as u32;
+        if (side as usize) * (side as usize) != heights.len() {
+            return Err(HeightmapError::NotSquare(heights.len()));
+        }
+        Self::new(UVec2::splat(side), heights)
+    }
+
+    /// The number of columns and rows of heights.
+    pub fn size(&self) -> UVec2 {
+        self.size
+    }
+
+    /// The heights, row by row.
+    pub fn heights(&self) -> &[f32] {
+        &self.heights
+    }
+
+    /// Returns the height at the given column and row, clamped to the grid.
+    pub fn get(&self, x: i32, y: i32) -> f32 {
+        let x = x.clamp(0, self.size.x as i32 - 1) as usize;
+        let y = y.clamp(0, self.size.y as i32 - 1) as usize;
+        self.heights[y * self.size.x as usize + x]
+    }
+
+    /// Bilinearly samples the height at the given UV, where (0, 0) is the
+    /// first height and (1, 1) is the last one.
+    pub fn sample(&self, uv: Vec2) -> f32 {
+        let position = uv.clamp(Vec2::ZERO, Vec2::ONE) * (self.size - 1).as_vec2();
+        let cell = position.floor();
+        let t = position - cell;
+        let (x, y) = (cell.x as i32, cell.y as i32);
+        let top = self.get(x, y) + (self.get(x + 1, y) - self.get(x, y)) * t.x;
+        let bottom = self.get(x, y + 1) + (self.get(x + 1, y + 1) - self.get(x, y + 1)) * t.x;
+        top + (bottom - top) * t.y
+    }
+}
+
+/// Loads [`Heightmap`]s from `.r16` files, which contain a square grid of raw
+/// little-endian 16 bit heights.
+#[derive(Default)]
+pub struct HeightmapLoader;
+
+impl AssetLoader for HeightmapLoader {
+    type Asset = Heightmap;
+    type Settings = ();
+    type Error = HeightmapError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Heightmap, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Heightmap::from_r16(&bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["r16"]
+    }
+}
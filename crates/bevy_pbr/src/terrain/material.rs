@@ -0,0 +1,64 @@
+use bevy_asset::{Asset, Handle};
+use bevy_image::Image;
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::render_resource::{AsBindGroup, Shader, ShaderRef};
+
+use crate::{ExtendedMaterial, MaterialExtension, StandardMaterial};
+
+pub(super) const TERRAIN_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(11775174496539757469);
+
+/// A [`StandardMaterial`] whose base color is blended from four layers
+/// according to a splat map, which is the usual way to texture a
+/// [`Terrain`](super::Terrain).
+///
+/// The other properties of the standard material apply to all the layers.
+pub type TerrainMaterial = ExtendedMaterial<StandardMaterial, TerrainMaterialExtension>;
+
+/// The [`MaterialExtension`] of a [`TerrainMaterial`].
+#[derive(Asset, AsBindGroup, Reflect, Clone, Debug)]
+#[reflect(Default, Debug)]
+pub struct TerrainMaterialExtension {
+    /// The weights of the layers, stretched over the whole terrain.
+    ///
+    /// The red, green, blue and alpha channels are the weights of the first,
+    /// second, third and fourth layers, and are normalized before blending.
+    #[texture(100)]
+    #[sampler(101)]
+    pub splat_map: Option<Handle<Image>>,
+
+    /// A 2D array texture with four layers, the colors of which are multiplied
+    /// with the base color of the material.
+    ///
+    /// The layers are tiled over the terrain in world space, so they keep the
+    /// same density on every level of detail.
+    #[texture(102, dimension = "2d_array")]
+    #[sampler(103)]
+    pub layers: Option<Handle<Image>>,
+
+    /// The size of one tile of the layers, in world units.
+    ///
+    /// Defaults to 10.
+    #[uniform(104)]
+    pub layer_tile_size: f32,
+}
+
+impl Default for TerrainMaterialExtension {
+    fn default() -> Self {
+        Self {
+            splat_map: None,
+            layers: None,
+            layer_tile_size: 10.0,
+        }
+    }
+}
+
+impl MaterialExtension for TerrainMaterialExtension {
+    fn fragment_shader() -> ShaderRef {
+        TERRAIN_SHADER_HANDLE.into()
+    }
+
+    fn deferred_fragment_shader() -> ShaderRef {
+        TERRAIN_SHADER_HANDLE.into()
+    }
+}
@@ -0,0 +1,613 @@
+//! Rendering of large terrains from heightmaps.
+//!
+//! A [`Terrain`] is split into a quadtree of square chunks, in the manner of
+//! CDLOD: chunks close to the cameras are subdivided into four finer ones, so
+//! that the density of the triangles on the screen stays roughly the same.
+//! Each chunk is a child entity with its own mesh, built from the
+//! [`Heightmap`] when the chunk is first needed. Chunks in the view frustums
+//! are built before the others, and a limited number of chunks are built each
+//! frame, so that moving across the terrain doesn't cause hitches.
+//!
+//! Chunks have skirts along their edges to hide the cracks between chunks of
+//! different levels of detail.
+
+mod heightmap;
+mod material;
+
+use bevy_app::{App, Plugin, PostUpdate};
+use bevy_asset::{load_internal_asset, AssetApp, AssetEvent, AssetId, Assets, Handle};
+use bevy_core_pipeline::core_3d::Camera3d;
+use bevy_ecs::prelude::*;
+use bevy_hierarchy::{BuildChildren, DespawnRecursiveExt};
+use bevy_math::{Rect, UVec2, Vec2, Vec3, Vec3A};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::{
+    camera::Camera,
+    mesh::{Indices, Mesh, Mesh3d, MeshAabb, PrimitiveTopology},
+    primitives::{Aabb, Frustum},
+    render_asset::RenderAssetUsages,
+    render_resource::Shader,
+    view::{Visibility, VisibilitySystems},
+};
+use bevy_transform::{
+    components::{GlobalTransform, Transform},
+    TransformSystem,
+};
+use bevy_utils::{HashMap, HashSet};
+
+use crate::{MaterialPlugin, MeshMaterial3d, NotShadowCaster, NotShadowReceiver, StandardMaterial};
+
+pub use heightmap::{Heightmap, HeightmapError, HeightmapLoader};
+pub use material::{TerrainMaterial, TerrainMaterialExtension};
+
+use material::TERRAIN_SHADER_HANDLE;
+
+/// Adds support for [`Terrain`]s, [`Heightmap`]s and [`TerrainMaterial`]s.
+pub struct TerrainPlugin;
+
+impl Plugin for TerrainPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            TERRAIN_SHADER_HANDLE,
+            "terrain.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.init_asset::<Heightmap>()
+            .init_asset_loader::<HeightmapLoader>()
+            .register_type::<Terrain>()
+            .register_type::<TerrainChunk>()
+            .add_plugins(MaterialPlugin::<TerrainMaterial>::default())
+            .add_systems(
+                PostUpdate,
+                (
+                    update_terrain_chunks
+                        .after(TransformSystem::TransformPropagate)
+                        .before(VisibilitySystems::CheckVisibility),
+                    (
+                        sync_terrain_component::<MeshMaterial3d<StandardMaterial>>,
+                        sync_terrain_component::<MeshMaterial3d<TerrainMaterial>>,
+                        sync_terrain_component::<NotShadowCaster>,
+                        sync_terrain_component::<NotShadowReceiver>,
+                    )
+                        .after(update_terrain_chunks),
+                ),
+            );
+    }
+}
+
+/// A terrain whose shape comes from a [`Heightmap`].
+///
+/// The terrain is centered on the origin of this entity, and spans
+/// [`Terrain::size`] along the X and Z axes, and [`Terrain::height`] upward.
+/// Its chunks are spawned as children of this entity, with the same material
+/// and shadow settings. Add a [`MeshMaterial3d`] of a [`StandardMaterial`] or a
+/// [`TerrainMaterial`] to this entity to choose how the terrain looks.
+///
+/// All distances are in the local space of this entity.
+#[derive(Component, Clone, Debug, Reflect)]
+#[reflect(Component, Default, Debug)]
+#[require(Transform, Visibility, TerrainChunks)]
+pub struct Terrain {
+    /// The heightmap that the terrain is built from.
+    pub heightmap: Handle<Heightmap>,
+
+    /// The extent of the terrain along the X and Z axes.
+    ///
+    /// Defaults to 1024×1024.
+    pub size: Vec2,
+
+    /// The height of the terrain where the heightmap is 1.
+    ///
+    /// Defaults to 100.
+    pub height: f32,
+
+    /// The number of quads along each side of a chunk.
+    ///
+    /// Defaults to 32.
+    pub chunk_resolution: u32,
+
+    /// The number of levels of the quadtree, from a single chunk covering
+    /// the whole terrain to the finest chunks.
+    ///
+    /// Defaults to 6, which splits the terrain in up to 32×32 chunks.
+    pub lod_count: u32,
+
+    /// How close, relative to its size, a camera must be to a chunk for the
+    /// chunk to be subdivided.
+    ///
+    /// Higher values keep more detail in the distance. Defaults to 2.
+    pub subdivision_distance: f32,
+
+    /// The distance beyond which chunks aren't rendered.
+    ///
+    /// Defaults to 4096.
+    pub view_distance: f32,
+
+    /// The maximum number of chunks whose meshes are built each frame.
+    ///
+    /// Defaults to 4.
+    pub max_chunk_builds_per_frame: usize,
+}
+
+impl Default for Terrain {
+    fn default() -> Self {
+        Self {
+            heightmap: Handle::default(),
+            size: Vec2::splat(1024.0),
+            height: 100.0,
+            chunk_resolution: 32,
+            lod_count: 6,
+            subdivision_distance: 2.0,
+            view_distance: 4096.0,
+            max_chunk_builds_per_frame: 4,
+        }
+    }
+}
+
+impl Terrain {
+    /// Returns the height of the terrain above the given point of its local
+    /// XZ plane, as it would be with the finest level of detail.
+    pub fn height_at(&self, heightmap: &Heightmap, position: Vec2) -> f32 {
+        heightmap.sample(position / self.size + 0.5) * self.height
+    }
+
+    /// Returns the extent of the given chunk in the local XZ plane.
+    pub fn chunk_rect(&self, chunk: TerrainChunkId) -> Rect {
+        let chunk_size = self.size / (1u32 << chunk.lod) as f32;
+        let min = -0.5 * self.size + chunk_size * UVec2::new(chunk.x, chunk.z).as_vec2();
+        Rect::from_corners(min, min + chunk_size)
+    }
+
+    fn chunk_aabb(&self, chunk: TerrainChunkId) -> Aabb {
+        let rect = self.chunk_rect(chunk);
+        Aabb::from_min_max(
+            Vec3::new(rect.min.x, 0.0, rect.min.y),
+            Vec3::new(rect.max.x, self.height, rect.max.y),
+        )
+    }
+
+    fn chunk_distance(&self, chunk: TerrainChunkId, position: Vec3) -> f32 {
+        let aabb = self.chunk_aabb(chunk);
+        let position = Vec3A::from(position);
+        let offset = (aabb.min() - position).max(position - aabb.max());
+        offset.max(Vec3A::ZERO).length()
+    }
+
+    /// Builds the mesh of the given chunk, relative to the corner of the chunk
+    /// at its minimum X and Z.
+    ///
+    /// The UVs of the mesh go from 0 to 1 across the whole terrain.
+    pub fn build_chunk_mesh(&self, heightmap: &Heightmap, chunk: TerrainChunkId) -> Mesh {
+        let resolution = self.chunk_resolution.max(1);
+        let rect = self.chunk_rect(chunk);
+        let cell_size = rect.size() / resolution as f32;
+        let row = resolution + 1;
+
+        let uv_at = |x: u32, z: u32| {
+            (rect.min + cell_size * Vec2::new(x as f32, z as f32)) / self.size + 0.5
+        };
+        let height_at = |uv: Vec2| heightmap.sample(uv) * self.height;
+
+        let mut positions = Vec::with_capacity((row * row + 4 * row) as usize);
+        let mut normals = Vec::with_capacity(positions.capacity());
+        let mut uvs = Vec::with_capacity(positions.capacity());
+        let mut min_height = f32::MAX;
+        for z in 0..row {
+            for x in 0..row {
+                let uv = uv_at(x, z);
+                let height = height_at(uv);
+                min_height = min_height.min(height);
+                positions.push([x as f32 * cell_size.x, height, z as f32 * cell_size.y]);
+
+                // Take the normal from the slope across the neighboring
+                // vertices, so that it matches the level of detail.
+                let uv_step = cell_size / self.size;
+                let slope_x = (height_at(uv + Vec2::new(uv_step.x, 0.0))
+                    - height_at(uv - Vec2::new(uv_step.x, 0.0)))
+                    / (2.0 * cell_size.x);
+                let slope_z = (height_at(uv + Vec2::new(0.0, uv_step.y))
+                    - height_at(uv - Vec2::new(0.0, uv_step.y)))
+                    / (2.0 * cell_size.y);
+                normals.push(Vec3::new(-slope_x, 1.0, -slope_z).normalize().to_array());
+                uvs.push(uv.to_array());
+            }
+        }
+
+        let mut indices =
+            Vec::with_capacity((resolution * resolution * 6 + 4 * resolution * 12) as usize);
+        for z in 0..resolution {
+            for x in 0..resolution {
+                let index = z * row + x;
+                indices.extend([index, index + row, index + 1]);
+                indices.extend([index + 1, index + row, index + row + 1]);
+            }
+        }
+
+        // The skirts hang from the edges down to below the lowest point of the
+        // chunk, which hides any crack with a neighbor of another level of
+        // detail, as the cracks are never deeper than the chunk is high.
+        let skirt_bottom = min_height - cell_size.max_element();
+        let edges = [
+            (0..row).collect::<Vec<_>>(),
+            (0..row).map(|x| resolution * row + x).collect(),
+            (0..row).map(|z| z * row).collect(),
+            (0..row).map(|z| z * row + resolution).collect(),
+        ];
+        for edge in edges {
+            let first_skirt_vertex = positions.len() as u32;
+            for &vertex in &edge {
+                let [x, _, z] = positions[vertex as usize];
+                positions.push([x, skirt_bottom, z]);
+                normals.push(normals[vertex as usize]);
+                uvs.push(uvs[vertex as usize]);
+            }
+            for (i, pair) in edge.windows(2).enumerate() {
+                let (top_0, top_1) = (pair[0], pair[1]);
+                let bottom_0 = first_skirt_vertex + i as u32;
+                let bottom_1 = bottom_0 + 1;
+                // Skirts are seen from both sides depending on the edge, so
+                // they're made of triangles of both windings.
+                indices.extend([top_0, bottom_0, top_1, top_1, bottom_0, bottom_1]);
+                indices.extend([top_0, top_1, bottom_0, top_1, bottom_1, bottom_0]);
+            }
+        }
+
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::RENDER_WORLD,
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+        .with_inserted_indices(Indices::U32(indices))
+    }
+}
+
+/// Identifies a chunk in the quadtree of a [`Terrain`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Default, Debug, PartialEq, Hash)]
+pub struct TerrainChunkId {
+    /// The level of the chunk in the quadtree, 0 being the chunk that covers
+    /// the whole terrain.
+    pub lod: u32,
+    /// The column of the chunk among the chunks of its level, from -X to +X.
+    pub x: u32,
+    /// The row of the chunk among the chunks of its level, from -Z to +Z.
+    pub z: u32,
+}
+
+impl TerrainChunkId {
+    /// The chunk that covers the whole terrain.
+    pub const ROOT: Self = Self { lod: 0, x: 0, z: 0 };
+
+    /// Returns the chunk that this one was subdivided from.
+    pub fn parent(self) -> Option<Self> {
+        (self.lod > 0).then(|| Self {
+            lod: self.lod - 1,
+            x: self.x / 2,
+            z: self.z / 2,
+        })
+    }
+
+    /// Returns the four chunks that this one is subdivided into.
+    pub fn children(self) -> [Self; 4] {
+        let (x, z, lod) = (self.x * 2, self.z * 2, self.lod + 1);
+        [
+            Self { lod, x, z },
+            Self { lod, x: x + 1, z },
+            Self { lod, x, z: z + 1 },
+            Self {
+                lod,
+                x: x + 1,
+                z: z + 1,
+            },
+        ]
+    }
+}
+
+/// A chunk of a [`Terrain`], spawned as a child of it.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component, Debug)]
+pub struct TerrainChunk(pub TerrainChunkId);
+
+/// The chunks spawned for an entity with [`Terrain`].
+#[derive(Component, Default)]
+pub struct TerrainChunks {
+    chunks: HashMap<TerrainChunkId, Entity>,
+    heightmap: AssetId<Heightmap>,
+}
+
+/// Selects the chunks of each [`Terrain`] to render, builds the missing ones
+/// and despawns those that aren't needed anymore.
+pub fn update_terrain_chunks(
+    mut commands: Commands,
+    mut terrains: Query<(Entity, Ref<Terrain>, &GlobalTransform, &mut TerrainChunks)>,
+    cameras: Query<(&Camera, &GlobalTransform, &Frustum), With<Camera3d>>,
+    mut chunk_visibilities: Query<&mut Visibility, (With<TerrainChunk>, Without<Terrain>)>,
+    mut heightmap_events: EventReader<AssetEvent<Heightmap>>,
+    heightmaps: Res<Assets<Heightmap>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let modified_heightmaps: HashSet<_> = heightmap_events
+        .read()
+        .filter_map(|event| match *event {
+            AssetEvent::Modified { id } | AssetEvent::Removed { id } => Some(id),
+            _ => None,
+        })
+        .collect();
+
+    for (entity, terrain, transform, mut chunks) in &mut terrains {
+        // Rebuild everything when the terrain or its heightmap changes.
+        let heightmap_id = terrain.heightmap.id();
+        if terrain.is_changed()
+            || chunks.heightmap != heightmap_id
+            || modified_heightmaps.contains(&heightmap_id)
+        {
+            for (_, chunk) in chunks.chunks.drain() {
+                commands.entity(chunk).try_despawn_recursive();
+            }
+            chunks.heightmap = heightmap_id;
+        }
+
+        let Some(heightmap) = heightmaps.get(heightmap_id) else {
+            continue;
+        };
+
+        let world_from_terrain = transform.affine();
+        let terrain_from_world = world_from_terrain.inverse();
+        let views: Vec<_> = cameras
+            .iter()
+            .filter(|(camera, ..)| camera.is_active)
+            .map(|(_, camera_transform, frustum)| {
+                (
+                    terrain_from_world.transform_point3(camera_transform.translation()),
+                    frustum,
+                )
+            })
+            .collect();
+
+        let mut selection = TerrainChunkSelection {
+            terrain: &*terrain,
+            view_positions: views.iter().map(|(position, _)| *position).collect(),
+            built: &chunks.chunks,
+            selected: HashSet::default(),
+            requested: Vec::new(),
+        };
+        if !selection.view_positions.is_empty() {
+            selection.select(TerrainChunkId::ROOT);
+        }
+        let TerrainChunkSelection {
+            selected,
+            mut requested,
+            view_positions,
+            ..
+        } = selection;
+
+        // Build the chunks that are in view first, then the closest ones.
+        let in_view = |chunk: TerrainChunkId| {
+            let aabb = terrain.chunk_aabb(chunk);
+            views
+                .iter()
+                .any(|(_, frustum)| frustum.intersects_obb(&aabb, &world_from_terrain, true, false))
+        };
+        let distance = |chunk: TerrainChunkId| {
+            view_positions
+                .iter()
+                .map(|&position| terrain.chunk_distance(chunk, position))
+                .fold(f32::MAX, f32::min)
+        };
+        requested.sort_unstable_by_key(|&chunk| (chunk.lod, chunk.x, chunk.z));
+        requested.dedup();
+        let mut requested: Vec<_> = requested
+            .into_iter()
+            .map(|chunk| (!in_view(chunk), distance(chunk), chunk))
+            .collect();
+        requested.sort_unstable_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+
+        let mut spawned = Vec::new();
+        for (_, _, chunk) in requested
+            .into_iter()
+            .take(terrain.max_chunk_builds_per_frame)
+        {
+            let mesh = terrain.build_chunk_mesh(heightmap, chunk);
+            let rect = terrain.chunk_rect(chunk);
+            // The mesh is only kept in the render world, so compute its bounds
+            // now.
+            let aabb = mesh.compute_aabb().unwrap_or_default();
+            let chunk_entity = commands
+                .spawn((
+                    TerrainChunk(chunk),
+                    Mesh3d(meshes.add(mesh)),
+                    aabb,
+                    Transform::from_xyz(rect.min.x, 0.0, rect.min.y),
+                    // The chunk is shown once it's selected.
+                    Visibility::Hidden,
+                ))
+                .id();
+            spawned.push((chunk, chunk_entity));
+        }
+        if !spawned.is_empty() {
+            commands.entity(entity).add_children(
+                &spawned
+                    .iter()
+                    .map(|(_, entity)| *entity)
+                    .collect::<Vec<_>>(),
+            );
+            chunks.chunks.extend(spawned);
+        }
+
+        // Show the selected chunks, and keep the children of the selected
+        // chunks around, hidden, as they are likely to be selected again
+        // soon. Despawn the other ones.
+        let mut despawned = Vec::new();
+        for (&chunk, &chunk_entity) in chunks.bypass_change_detection().chunks.iter() {
+            let visibility = if selected.contains(&chunk) {
+                Visibility::Inherited
+            } else if chunk
+                .parent()
+                .is_some_and(|parent| selected.contains(&parent))
+            {
+                Visibility::Hidden
+            } else {
+                despawned.push(chunk);
+                continue;
+            };
+            if let Ok(mut chunk_visibility) = chunk_visibilities.get_mut(chunk_entity) {
+                chunk_visibility.set_if_neq(visibility);
+            }
+        }
+        for chunk in despawned {
+            if let Some(chunk_entity) = chunks.chunks.remove(&chunk) {
+                commands.entity(chunk_entity).try_despawn_recursive();
+            }
+        }
+    }
+}
+
+/// The state of the traversal of the quadtree of a [`Terrain`] that selects
+/// its chunks to render.
+struct TerrainChunkSelection<'a> {
+    terrain: &'a Terrain,
+    view_positions: Vec<Vec3>,
+    built: &'a HashMap<TerrainChunkId, Entity>,
+    selected: HashSet<TerrainChunkId>,
+    requested: Vec<TerrainChunkId>,
+}
+
+impl TerrainChunkSelection<'_> {
+    fn distance(&self, chunk: TerrainChunkId) -> f32 {
+        self.view_positions
+            .iter()
+            .map(|&position| self.terrain.chunk_distance(chunk, position))
+            .fold(f32::MAX, f32::min)
+    }
+
+    fn is_in_range(&self, chunk: TerrainChunkId) -> bool {
+        self.distance(chunk) < self.terrain.view_distance
+    }
+
+    /// Selects the given chunk or its descendants, falling back to the chunks
+    /// that are already built while the ones that should replace them are
+    /// being built.
+    fn select(&mut self, chunk: TerrainChunkId) {
+        if !self.is_in_range(chunk) {
+            return;
+        }
+
+        let is_leaf = chunk.lod + 1 >= self.terrain.lod_count;
+        let chunk_size = self.terrain.chunk_rect(chunk).size().max_element();
+        let subdivide =
+            !is_leaf && self.distance(chunk) < chunk_size * self.terrain.subdivision_distance;
+        let children_ready = !is_leaf
+            && chunk
+                .children()
+                .iter()
+                .all(|&child| self.built.contains_key(&child) || !self.is_in_range(child));
+
+        if subdivide && children_ready {
+            for child in chunk.children() {
+                self.select(child);
+            }
+            return;
+        }
+
+        if subdivide {
+            for child in chunk.children() {
+                if !self.built.contains_key(&child) && self.is_in_range(child) {
+                    self.requested.push(child);
+                }
+            }
+        }
+
+        if self.built.contains_key(&chunk) {
+            self.selected.insert(chunk);
+        } else {
+            self.requested.push(chunk);
+            // Keep rendering the finer chunks until this one is built.
+            if children_ready {
+                for child in chunk.children() {
+                    self.select(child);
+                }
+            }
+        }
+    }
+}
+
+/// Copies the component `C` of the entities with [`Terrain`] to their chunks
+/// when it changes.
+pub fn sync_terrain_component<C: Component + Clone>(
+    mut commands: Commands,
+    changed: Query<(&C, &TerrainChunks), Or<(Changed<C>, Changed<TerrainChunks>)>>,
+    mut removed: RemovedComponents<C>,
+    chunks: Query<&TerrainChunks, Without<C>>,
+) {
+    for (component, terrain_chunks) in &changed {
+        for &chunk in terrain_chunks.chunks.values() {
+            commands.entity(chunk).insert(component.clone());
+        }
+    }
+    for entity in removed.read() {
+        let Ok(terrain_chunks) = chunks.get(entity) else {
+            continue;
+        };
+        for &chunk in terrain_chunks.chunks.values() {
+            commands.entity(chunk).remove::<C>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_math::{UVec2, Vec2};
+    use bevy_render::mesh::VertexAttributeValues;
+
+    use super::{Heightmap, Terrain, TerrainChunkId};
+
+    #[test]
+    fn terrain_chunk_quadtree() {
+        let chunk = TerrainChunkId { lod: 2, x: 3, z: 1 };
+        for child in chunk.children() {
+            assert_eq!(child.parent(), Some(chunk));
+        }
+        assert_eq!(TerrainChunkId::ROOT.parent(), None);
+
+        let terrain = Terrain {
+            size: Vec2::splat(100.0),
+            ..Default::default()
+        };
+        let rect = terrain.chunk_rect(chunk);
+        assert_eq!(rect.min, Vec2::new(25.0, -25.0));
+        assert_eq!(rect.max, Vec2::new(50.0, 0.0));
+    }
+
+    #[test]
+    fn terrain_chunk_mesh_follows_heightmap() {
+        // A slope rising along X.
+        let heightmap =
+            Heightmap::new(UVec2::new(3, 2), vec![0.0, 0.5, 1.0, 0.0, 0.5, 1.0]).unwrap();
+        let terrain = Terrain {
+            size: Vec2::splat(2.0),
+            height: 10.0,
+            chunk_resolution: 2,
+            ..Default::default()
+        };
+        assert_eq!(terrain.height_at(&heightmap, Vec2::new(0.5, 0.0)), 7.5);
+
+        let mesh = terrain.build_chunk_mesh(&heightmap, TerrainChunkId::ROOT);
+        // 3×3 vertices on the surface, and 3 for the skirt of each edge.
+        assert_eq!(mesh.count_vertices(), 9 + 4 * 3);
+
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(bevy_render::mesh::Mesh::ATTRIBUTE_POSITION)
+        else {
+            panic!("the chunk mesh has no positions");
+        };
+        assert_eq!(positions[0], [0.0, 0.0, 0.0]);
+        assert_eq!(positions[2], [2.0, 10.0, 0.0]);
+        assert_eq!(positions[4], [1.0, 5.0, 1.0]);
+        // The skirts hang below the lowest vertex.
+        assert!(positions[9][1] < 0.0);
+    }
+}
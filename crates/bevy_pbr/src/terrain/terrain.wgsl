@@ -0,0 +1,56 @@
+// The fragment shader of `TerrainMaterial`, which blends the base color of the
+// terrain from four layers according to a splat map.
+
+#import bevy_pbr::{
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::alpha_discard,
+}
+
+#ifdef PREPASS_PIPELINE
+#import bevy_pbr::{
+    prepass_io::{VertexOutput, FragmentOutput},
+    pbr_deferred_functions::deferred_output,
+}
+#else
+#import bevy_pbr::{
+    forward_io::{VertexOutput, FragmentOutput},
+    pbr_functions::{apply_pbr_lighting, main_pass_post_lighting_processing},
+}
+#endif
+
+@group(2) @binding(100) var splat_map: texture_2d<f32>;
+@group(2) @binding(101) var splat_map_sampler: sampler;
+@group(2) @binding(102) var layers: texture_2d_array<f32>;
+@group(2) @binding(103) var layers_sampler: sampler;
+@group(2) @binding(104) var<uniform> layer_tile_size: f32;
+
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+
+    // The UVs of the terrain cover it entirely, while the layers are tiled in
+    // world space.
+    var weights = textureSample(splat_map, splat_map_sampler, in.uv);
+    weights /= max(dot(weights, vec4(1.0)), 1e-4);
+    let layer_uv = in.world_position.xz / layer_tile_size;
+    var layer_color = vec4(0.0);
+    for (var layer = 0; layer < 4; layer += 1) {
+        layer_color += weights[layer] * textureSample(layers, layers_sampler, layer_uv, layer);
+    }
+    pbr_input.material.base_color *= layer_color;
+
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+
+#ifdef PREPASS_PIPELINE
+    let out = deferred_output(in, pbr_input);
+#else
+    var out: FragmentOutput;
+    out.color = apply_pbr_lighting(pbr_input);
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+#endif
+
+    return out;
+}
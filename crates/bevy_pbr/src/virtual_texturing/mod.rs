@@ -0,0 +1,325 @@
+//! Virtual texturing, which renders textures too large to fit in GPU memory by only keeping the
+//! parts of them that are in view in a fixed size cache.
+//!
+//! A [`TiledTexture`] is a texture split into square pages at each of its mip levels, stored in a
+//! `.vtex` file. Only the header of the file is read when the asset is loaded: its pages are read
+//! on demand.
+//!
+//! To render a tiled texture, spawn a [`VirtualTexture`] for it and use a
+//! [`VirtualTextureMaterial`] pointing to the virtual texture. The material multiplies the base
+//! color of the [`StandardMaterial`] by the virtual texture, sampled with the first UV channel of
+//! the mesh. It works as follows:
+//!
+//! * A page table texture, with one texel per page at each mip level, maps the pages to the slots of
+//!   the cache texture where they're resident.
+//! * Each frame, the material records the pages it samples in a feedback buffer, which is read back
+//!   on the CPU.
+//! * The missing pages are read from the `.vtex` file in the background, and uploaded to a free
+//!   slot of the cache, evicting the least recently used pages if it's full.
+//! * While a page isn't resident, the material falls back to the closest coarser mip level that is.
+//!   The pages of the coarsest mip level are always resident.
+//!
+//! Virtual texturing requires storage buffers, so it isn't supported on WebGL 2. The material
+//! only records the pages it samples in the forward pass, so its [`StandardMaterial`] must use
+//! [`OpaqueRendererMethod::Forward`](crate::OpaqueRendererMethod::Forward).
+
+mod streaming;
+mod tiled_texture;
+
+pub use tiled_texture::{
+    encode_tiled_texture, PageId, TiledTexture, TiledTextureError, TiledTextureFormat,
+    TiledTextureLoader,
+};
+
+use bevy_app::{App, Plugin, Update};
+use bevy_asset::{load_internal_asset, Asset, AssetApp, AssetId, Assets, Handle};
+use bevy_ecs::prelude::*;
+use bevy_image::Image;
+use bevy_math::UVec2;
+use bevy_reflect::TypePath;
+use bevy_render::{
+    render_asset::{prepare_assets, RenderAssets},
+    render_resource::{
+        AsBindGroup, Extent3d, ImageCopyTexture, ImageDataLayout, Origin3d, Shader, ShaderRef,
+        ShaderType, TextureAspect,
+    },
+    renderer::RenderQueue,
+    storage::ShaderStorageBuffer,
+    texture::GpuImage,
+    ExtractSchedule, MainWorld, Render, RenderApp, RenderSet,
+};
+
+use crate::{ExtendedMaterial, MaterialExtension, MaterialPlugin, StandardMaterial};
+use streaming::{read_feedback, update_virtual_textures, PageCache};
+
+const VIRTUAL_TEXTURING_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(140376620402995522466);
+
+/// The maximum number of slots of a cache along each axis, since the page table stores the slot
+/// coordinates in 8 bits each.
+pub const MAX_VIRTUAL_TEXTURE_CACHE_SLOTS: u32 = 256;
+
+/// Adds support for [`VirtualTexture`]s and [`VirtualTextureMaterial`]s.
+pub struct VirtualTexturingPlugin;
+
+impl Plugin for VirtualTexturingPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            VIRTUAL_TEXTURING_SHADER_HANDLE,
+            "virtual_texturing.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.init_asset::<TiledTexture>()
+            .init_asset_loader::<TiledTextureLoader>()
+            .add_plugins(MaterialPlugin::<VirtualTextureMaterial>::default())
+            .add_systems(Update, update_virtual_textures)
+            .add_observer(read_feedback);
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .init_resource::<PendingTextureWrites>()
+            .add_systems(ExtractSchedule, extract_texture_writes)
+            .add_systems(
+                Render,
+                write_textures
+                    .in_set(RenderSet::PrepareResources)
+                    .after(prepare_assets::<GpuImage>),
+            );
+    }
+}
+
+/// Streams the pages of a [`TiledTexture`] into a cache texture of a fixed size.
+///
+/// The cache, the page table mapping the pages to their slot in it and the feedback buffer
+/// recording the pages in view are created when the tiled texture is loaded. Their handles are
+/// reserved beforehand, so that the [`VirtualTextureMaterial`]s using this virtual texture can be
+/// created right away.
+#[derive(Component)]
+pub struct VirtualTexture {
+    source: Handle<TiledTexture>,
+    cache_size: UVec2,
+    max_page_loads: usize,
+    params: Handle<ShaderStorageBuffer>,
+    page_table: Handle<Image>,
+    cache: Handle<Image>,
+    feedback: Handle<ShaderStorageBuffer>,
+    page_cache: Option<PageCache>,
+    /// The texture writes to apply in the render world.
+    writes: Vec<TextureWrite>,
+}
+
+impl VirtualTexture {
+    /// Creates a virtual texture streaming the pages of `source` into a cache holding
+    /// `cache_size.x` × `cache_size.y` pages.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cache is empty, or has more than [`MAX_VIRTUAL_TEXTURE_CACHE_SLOTS`] slots
+    /// along an axis.
+    pub fn new(
+        source: Handle<TiledTexture>,
+        cache_size: UVec2,
+        images: &Assets<Image>,
+        buffers: &Assets<ShaderStorageBuffer>,
+    ) -> Self {
+        assert!(
+            cache_size.cmpge(UVec2::ONE).all()
+                && cache_size
+                    .cmple(UVec2::splat(MAX_VIRTUAL_TEXTURE_CACHE_SLOTS))
+                    .all(),
+            "the cache of a virtual texture must have between 1 and {MAX_VIRTUAL_TEXTURE_CACHE_SLOTS} slots along each axis"
+        );
+        Self {
+            source,
+            cache_size,
+            max_page_loads: 16,
+            params: buffers.reserve_handle(),
+            page_table: images.reserve_handle(),
+            cache: images.reserve_handle(),
+            feedback: buffers.reserve_handle(),
+            page_cache: None,
+            writes: Vec::new(),
+        }
+    }
+
+    /// Sets the maximum number of pages read at the same time.
+    ///
+    /// Defaults to 16.
+    pub fn with_max_page_loads(mut self, max_page_loads: usize) -> Self {
+        self.max_page_loads = max_page_loads;
+        self
+    }
+
+    /// Returns the tiled texture this virtual texture streams pages from.
+    pub fn source(&self) -> &Handle<TiledTexture> {
+        &self.source
+    }
+
+    /// Returns the number of slots of the cache along each axis.
+    pub fn cache_size(&self) -> UVec2 {
+        self.cache_size
+    }
+
+    /// Returns the texture the resident pages are stored in.
+    pub fn cache(&self) -> &Handle<Image> {
+        &self.cache
+    }
+
+    /// Returns the texture mapping each page to its slot in the cache.
+    pub fn page_table(&self) -> &Handle<Image> {
+        &self.page_table
+    }
+
+    /// Returns the number of pages currently in the cache.
+    pub fn resident_page_count(&self) -> usize {
+        self.page_cache.as_ref().map_or(0, PageCache::resident_len)
+    }
+}
+
+/// A [`StandardMaterial`] whose base color is multiplied by a [`VirtualTexture`].
+///
+/// See the [module docs](self) for more information.
+pub type VirtualTextureMaterial = ExtendedMaterial<StandardMaterial, VirtualTextureExtension>;
+
+/// The [`MaterialExtension`] sampling a [`VirtualTexture`] in a [`VirtualTextureMaterial`].
+#[derive(Asset, AsBindGroup, TypePath, Clone, Debug)]
+pub struct VirtualTextureExtension {
+    #[storage(100, read_only, visibility(fragment))]
+    params: Handle<ShaderStorageBuffer>,
+    #[texture(101, sample_type = "u_int")]
+    page_table: Handle<Image>,
+    #[texture(102)]
+    #[sampler(103)]
+    cache: Handle<Image>,
+    #[storage(104, visibility(fragment))]
+    feedback: Handle<ShaderStorageBuffer>,
+}
+
+impl From<&VirtualTexture> for VirtualTextureExtension {
+    fn from(virtual_texture: &VirtualTexture) -> Self {
+        Self {
+            params: virtual_texture.params.clone(),
+            page_table: virtual_texture.page_table.clone(),
+            cache: virtual_texture.cache.clone(),
+            feedback: virtual_texture.feedback.clone(),
+        }
+    }
+}
+
+impl MaterialExtension for VirtualTextureExtension {
+    fn fragment_shader() -> ShaderRef {
+        VIRTUAL_TEXTURING_SHADER_HANDLE.into()
+    }
+}
+
+/// The layout of a [`TiledTexture`] and its cache, as seen by the shader.
+#[derive(ShaderType)]
+struct VirtualTextureParams {
+    page_count: UVec2,
+    cache_slots: UVec2,
+    page_size: u32,
+    border: u32,
+    mip_level_count: u32,
+}
+
+/// A write to the cache or to the page table of a [`VirtualTexture`], applied in the render world.
+pub(crate) struct TextureWrite {
+    to_page_table: bool,
+    mip_level: u32,
+    origin: UVec2,
+    size: UVec2,
+    data: Vec<u8>,
+}
+
+impl TextureWrite {
+    /// Writes the texels of a page to its slot in the cache.
+    fn page(slot: UVec2, slot_size: u32, data: Vec<u8>) -> Self {
+        Self {
+            to_page_table: false,
+            mip_level: 0,
+            origin: slot * slot_size,
+            size: UVec2::splat(slot_size),
+            data,
+        }
+    }
+
+    /// Marks `page` as resident in `slot`, or as not resident.
+    fn page_table_entry(page: PageId, slot: Option<UVec2>) -> Self {
+        let data = match slot {
+            Some(slot) => vec![slot.x as u8, slot.y as u8, 0, 1],
+            None => vec![0; 4],
+        };
+        Self {
+            to_page_table: true,
+            mip_level: page.mip,
+            origin: page.position,
+            size: UVec2::ONE,
+            data,
+        }
+    }
+}
+
+/// The texture writes waiting for their texture to be prepared.
+#[derive(Resource, Default)]
+struct PendingTextureWrites(Vec<(AssetId<Image>, TextureWrite)>);
+
+fn extract_texture_writes(
+    mut main_world: ResMut<MainWorld>,
+    mut pending: ResMut<PendingTextureWrites>,
+) {
+    let mut virtual_textures = main_world.query::<&mut VirtualTexture>();
+    for mut virtual_texture in virtual_textures.iter_mut(&mut main_world) {
+        let virtual_texture = virtual_texture.bypass_change_detection();
+        let (cache, page_table) = (virtual_texture.cache.id(), virtual_texture.page_table.id());
+        pending
+            .0
+            .extend(virtual_texture.writes.drain(..).map(|write| {
+                let image = if write.to_page_table {
+                    page_table
+                } else {
+                    cache
+                };
+                (image, write)
+            }));
+    }
+}
+
+fn write_textures(
+    mut pending: ResMut<PendingTextureWrites>,
+    images: Res<RenderAssets<GpuImage>>,
+    render_queue: Res<RenderQueue>,
+) {
+    pending.0.retain(|(image, write)| {
+        let Some(gpu_image) = images.get(*image) else {
+            return true;
+        };
+        render_queue.write_texture(
+            ImageCopyTexture {
+                texture: &gpu_image.texture,
+                mip_level: write.mip_level,
+                origin: Origin3d {
+                    x: write.origin.x,
+                    y: write.origin.y,
+                    z: 0,
+                },
+                aspect: TextureAspect::All,
+            },
+            &write.data,
+            ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(write.size.x * 4),
+                rows_per_image: None,
+            },
+            Extent3d {
+                width: write.size.x,
+                height: write.size.y,
+                depth_or_array_layers: 1,
+            },
+        );
+        false
+    });
+}
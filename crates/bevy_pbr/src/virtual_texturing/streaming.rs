@@ -0,0 +1,322 @@
+//! Decides which pages of a [`VirtualTexture`] are resident, and reads them in the background.
+
+use bevy_asset::{
+    io::{AssetReaderError, AsyncSeekForwardExt, MissingAssetSourceError},
+    AssetPath, AssetServer, Assets, AsyncReadExt,
+};
+use bevy_ecs::prelude::*;
+use bevy_image::Image;
+use bevy_math::UVec2;
+use bevy_render::{
+    gpu_readback::{Readback, ReadbackComplete},
+    render_asset::RenderAssetUsages,
+    render_resource::{BufferUsages, Extent3d, TextureDimension, TextureFormat},
+    storage::ShaderStorageBuffer,
+};
+use bevy_tasks::{block_on, futures_lite::future, IoTaskPool, Task};
+use bevy_utils::{HashMap, HashSet};
+use thiserror::Error;
+use tracing::{error, warn};
+
+use super::{PageId, TextureWrite, TiledTexture, VirtualTexture, VirtualTextureParams};
+
+/// The number of frames a page stays needed after it was last sampled.
+///
+/// The material only records the pages it samples for one fragment out of four each frame, so
+/// this must be large enough for all of them to be recorded in time.
+const NEEDED_FRAMES: u32 = 8;
+
+/// The streaming state of a [`VirtualTexture`] whose [`TiledTexture`] is loaded.
+pub(crate) struct PageCache {
+    texture: TiledTexture,
+    free_slots: Vec<UVec2>,
+    resident: HashMap<PageId, ResidentPage>,
+    loading: HashMap<PageId, Task<Result<Vec<u8>, PageReadError>>>,
+    failed: HashSet<PageId>,
+    /// The last frame each page was sampled, plus one, as read back from the feedback buffer.
+    last_used: Vec<u32>,
+    /// The latest frame found in the feedback buffer.
+    latest_frame: u32,
+    /// The pages that are needed but not resident, coarsest first.
+    wanted: Vec<PageId>,
+    warned_full: bool,
+}
+
+struct ResidentPage {
+    slot: UVec2,
+    pinned: bool,
+}
+
+#[derive(Error, Debug)]
+enum PageReadError {
+    #[error(transparent)]
+    MissingSource(#[from] MissingAssetSourceError),
+    #[error(transparent)]
+    Reader(#[from] AssetReaderError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+impl PageCache {
+    fn new(texture: TiledTexture, cache_size: UVec2) -> Self {
+        let free_slots = (0..cache_size.y)
+            .rev()
+            .flat_map(|y| (0..cache_size.x).rev().map(move |x| UVec2::new(x, y)))
+            .collect();
+        let coarsest = texture.mip_level_count() - 1;
+        let count = texture.page_count(coarsest);
+        // The coarsest mip level is always resident, so that there's always a page to fall back to.
+        let wanted = (0..count.y)
+            .flat_map(|y| (0..count.x).map(move |x| UVec2::new(x, y)))
+            .map(|position| PageId {
+                mip: coarsest,
+                position,
+            })
+            .collect();
+        Self {
+            last_used: vec![0; texture.total_page_count()],
+            texture,
+            free_slots,
+            resident: HashMap::default(),
+            loading: HashMap::default(),
+            failed: HashSet::default(),
+            latest_frame: 0,
+            wanted,
+            warned_full: false,
+        }
+    }
+
+    /// Returns the number of pages in the cache.
+    pub(crate) fn resident_len(&self) -> usize {
+        self.resident.len()
+    }
+
+    fn is_needed(&self, page: PageId) -> bool {
+        let last_used = self.last_used[self.texture.page_index(page)];
+        last_used != 0 && last_used + NEEDED_FRAMES > self.latest_frame
+    }
+
+    /// Records the pages sampled by the material, as read back from the feedback buffer.
+    fn apply_feedback(&mut self, feedback: &[u8]) {
+        for (last_used, frame) in self.last_used.iter_mut().zip(feedback.chunks_exact(4)) {
+            *last_used = u32::from_le_bytes(frame.try_into().unwrap());
+        }
+        self.latest_frame = self.last_used.iter().copied().max().unwrap_or(0);
+
+        let mut wanted: Vec<_> = (0..self.last_used.len())
+            .filter_map(|index| self.texture.page_at(index))
+            .filter(|page| {
+                self.is_needed(*page)
+                    && !self.resident.contains_key(page)
+                    && !self.loading.contains_key(page)
+                    && !self.failed.contains(page)
+            })
+            .collect();
+        wanted.sort_by_key(|page| core::cmp::Reverse(page.mip));
+        // Keep waiting for the coarsest pages until they're loaded.
+        let coarsest = self.texture.mip_level_count() - 1;
+        let pinned = self
+            .wanted
+            .iter()
+            .copied()
+            .filter(|page| page.mip == coarsest && !wanted.contains(page));
+        self.wanted = pinned.chain(wanted).collect();
+    }
+
+    /// Starts reading the wanted pages, up to `max_page_loads` at once.
+    fn start_loads(&mut self, asset_server: &AssetServer, max_page_loads: usize) {
+        let task_pool = IoTaskPool::get();
+        while self.loading.len() < max_page_loads && !self.wanted.is_empty() {
+            let page = self.wanted.remove(0);
+            if self.resident.contains_key(&page) || self.loading.contains_key(&page) {
+                continue;
+            }
+            let asset_server = asset_server.clone();
+            let path = self.texture.path().clone();
+            let offset = self.texture.page_offset(page);
+            let len = self.texture.page_byte_len();
+            let task = task_pool.spawn(read_page(asset_server, path, offset, len));
+            self.loading.insert(page, task);
+        }
+    }
+
+    /// Places the pages that finished loading in the cache, and queues their upload.
+    fn finish_loads(&mut self, writes: &mut Vec<TextureWrite>) {
+        let mut loaded = Vec::new();
+        self.loading
+            .retain(|&page, task| match block_on(future::poll_once(task)) {
+                Some(result) => {
+                    loaded.push((page, result));
+                    false
+                }
+                None => true,
+            });
+
+        for (page, result) in loaded {
+            let data = match result {
+                Ok(data) => data,
+                Err(err) => {
+                    error!(
+                        "Failed to read page {:?} of {}: {err}",
+                        page,
+                        self.texture.path()
+                    );
+                    self.failed.insert(page);
+                    continue;
+                }
+            };
+            let Some(slot) = self.allocate_slot(writes) else {
+                if !self.warned_full {
+                    warn!(
+                        "The cache of the virtual texture {} is too small to hold all the pages in view",
+                        self.texture.path()
+                    );
+                    self.warned_full = true;
+                }
+                continue;
+            };
+            let pinned = page.mip == self.texture.mip_level_count() - 1;
+            self.resident.insert(page, ResidentPage { slot, pinned });
+            writes.push(TextureWrite::page(slot, self.texture.slot_size(), data));
+            writes.push(TextureWrite::page_table_entry(page, Some(slot)));
+        }
+    }
+
+    /// Returns a free slot of the cache, evicting the least recently used page that isn't needed
+    /// anymore if there's none.
+    fn allocate_slot(&mut self, writes: &mut Vec<TextureWrite>) -> Option<UVec2> {
+        if let Some(slot) = self.free_slots.pop() {
+            return Some(slot);
+        }
+        let (&evicted, _) = self
+            .resident
+            .iter()
+            .filter(|(&page, resident)| !resident.pinned && !self.is_needed(page))
+            .min_by_key(|(&page, _)| self.last_used[self.texture.page_index(page)])?;
+        let resident = self.resident.remove(&evicted)?;
+        writes.push(TextureWrite::page_table_entry(evicted, None));
+        Some(resident.slot)
+    }
+}
+
+async fn read_page(
+    asset_server: AssetServer,
+    path: AssetPath<'static>,
+    offset: u64,
+    len: usize,
+) -> Result<Vec<u8>, PageReadError> {
+    let source = asset_server.get_source(path.source())?;
+    let mut reader = source.reader().read(path.path()).await?;
+    reader.seek_forward(offset).await?;
+    let mut data = vec![0; len];
+    reader.read_exact(&mut data).await?;
+    Ok(data)
+}
+
+/// Creates the GPU resources of the [`VirtualTexture`]s whose [`TiledTexture`] finished loading,
+/// and streams the pages of the others.
+pub(crate) fn update_virtual_textures(
+    mut commands: Commands,
+    mut virtual_textures: Query<(Entity, &mut VirtualTexture)>,
+    tiled_textures: Res<Assets<TiledTexture>>,
+    mut images: ResMut<Assets<Image>>,
+    mut buffers: ResMut<Assets<ShaderStorageBuffer>>,
+    asset_server: Res<AssetServer>,
+) {
+    for (entity, mut virtual_texture) in &mut virtual_textures {
+        let virtual_texture = &mut *virtual_texture;
+        let Some(cache) = &mut virtual_texture.page_cache else {
+            let Some(texture) = tiled_textures.get(&virtual_texture.source) else {
+                continue;
+            };
+            create_resources(virtual_texture, texture, &mut images, &mut buffers);
+            commands
+                .entity(entity)
+                .insert(Readback::buffer(virtual_texture.feedback.clone()));
+            virtual_texture.page_cache =
+                Some(PageCache::new(texture.clone(), virtual_texture.cache_size));
+            continue;
+        };
+        cache.finish_loads(&mut virtual_texture.writes);
+        cache.start_loads(&asset_server, virtual_texture.max_page_loads);
+    }
+}
+
+fn create_resources(
+    virtual_texture: &VirtualTexture,
+    texture: &TiledTexture,
+    images: &mut Assets<Image>,
+    buffers: &mut Assets<ShaderStorageBuffer>,
+) {
+    let cache_size = virtual_texture.cache_size;
+    let coarsest = texture.page_count(texture.mip_level_count() - 1);
+    if cache_size.element_product() <= coarsest.element_product() {
+        warn!(
+            "The cache of the virtual texture {} only has {} slots, which isn't enough to hold its coarsest mip level and any other page",
+            texture.path(),
+            cache_size.element_product()
+        );
+    }
+
+    // Each texel of the page table is the slot of the page in the cache, and whether it's
+    // resident in its alpha channel.
+    let page_count = texture.page_count(0);
+    let mut page_table = Image::new(
+        Extent3d {
+            width: page_count.x,
+            height: page_count.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        vec![0; (page_count.element_product() * 4) as usize],
+        TextureFormat::Rgba8Uint,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    page_table.texture_descriptor.mip_level_count = texture.mip_level_count();
+    page_table.data = vec![0; texture.total_page_count() * 4];
+    images.insert(&virtual_texture.page_table, page_table);
+
+    let slot_size = texture.slot_size();
+    let mut cache = Image::new(
+        Extent3d {
+            width: cache_size.x * slot_size,
+            height: cache_size.y * slot_size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        vec![0; (cache_size.element_product() * slot_size * slot_size * 4) as usize],
+        texture.format().texture_format(),
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    cache.sampler = bevy_image::ImageSampler::linear();
+    images.insert(&virtual_texture.cache, cache);
+
+    let mut params = ShaderStorageBuffer::from(VirtualTextureParams {
+        page_count,
+        cache_slots: cache_size,
+        page_size: texture.page_size(),
+        border: texture.border(),
+        mip_level_count: texture.mip_level_count(),
+    });
+    params.asset_usage = RenderAssetUsages::RENDER_WORLD;
+    buffers.insert(&virtual_texture.params, params);
+
+    let mut feedback = ShaderStorageBuffer::from(vec![0u32; texture.total_page_count()]);
+    feedback.buffer_description.usage |= BufferUsages::COPY_SRC;
+    feedback.asset_usage = RenderAssetUsages::RENDER_WORLD;
+    buffers.insert(&virtual_texture.feedback, feedback);
+}
+
+/// Records the pages sampled by the [`VirtualTextureMaterial`](super::VirtualTextureMaterial)s
+/// of a [`VirtualTexture`] when its feedback buffer is read back.
+pub(crate) fn read_feedback(
+    trigger: Trigger<ReadbackComplete>,
+    mut virtual_textures: Query<&mut VirtualTexture>,
+) {
+    let Ok(mut virtual_texture) = virtual_textures.get_mut(trigger.target()) else {
+        return;
+    };
+    if let Some(cache) = &mut virtual_texture.page_cache {
+        cache.apply_feedback(&trigger.event().0);
+    }
+}
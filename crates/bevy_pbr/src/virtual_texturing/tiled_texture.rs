@@ -0,0 +1,524 @@
+//! The [`TiledTexture`] asset, and the `.vtex` files it's loaded from.
+
+use alloc::sync::Arc;
+use bevy_asset::{
+    io::{AsyncSeekForwardExt, Reader},
+    Asset, AssetLoader, AssetPath, AsyncReadExt, LoadContext,
+};
+use bevy_image::Image;
+use bevy_math::UVec2;
+use bevy_reflect::TypePath;
+use bevy_render::render_resource::{TextureDimension, TextureFormat};
+use thiserror::Error;
+
+const MAGIC: [u8; 4] = *b"BVTX";
+const VERSION: u32 = 1;
+const HEADER_SIZE: usize = 32;
+
+/// A texture split into square pages at each of its mip levels, whose pages are read from its
+/// `.vtex` file on demand by a [`VirtualTexture`](super::VirtualTexture).
+///
+/// Only the layout of the texture is kept in memory: loading a tiled texture reads the header of
+/// its file, not its pages.
+///
+/// Tiled textures are created from [`Image`]s with [`encode_tiled_texture`].
+#[derive(Asset, TypePath, Clone, Debug)]
+pub struct TiledTexture {
+    size: UVec2,
+    page_size: u32,
+    border: u32,
+    mip_level_count: u32,
+    format: TiledTextureFormat,
+    path: AssetPath<'static>,
+    page_offsets: Arc<[u64]>,
+}
+
+/// The format of the texels of a [`TiledTexture`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TiledTextureFormat {
+    /// Linear RGBA, 8 bits per channel.
+    Rgba8Unorm,
+    /// sRGB RGBA, 8 bits per channel.
+    Rgba8UnormSrgb,
+}
+
+impl TiledTextureFormat {
+    /// Returns the format of the cache texture storing pages of this format.
+    pub fn texture_format(self) -> TextureFormat {
+        match self {
+            Self::Rgba8Unorm => TextureFormat::Rgba8Unorm,
+            Self::Rgba8UnormSrgb => TextureFormat::Rgba8UnormSrgb,
+        }
+    }
+
+    fn from_texture_format(format: TextureFormat) -> Option<Self> {
+        match format {
+            TextureFormat::Rgba8Unorm => Some(Self::Rgba8Unorm),
+            TextureFormat::Rgba8UnormSrgb => Some(Self::Rgba8UnormSrgb),
+            _ => None,
+        }
+    }
+}
+
+/// A page of a [`TiledTexture`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct PageId {
+    /// The mip level of the page.
+    pub mip: u32,
+    /// The position of the page in the grid of pages of its mip level.
+    pub position: UVec2,
+}
+
+impl TiledTexture {
+    /// Returns the size of the texture at mip level 0, in texels.
+    pub fn size(&self) -> UVec2 {
+        self.size
+    }
+
+    /// Returns the size of the side of a page, in texels, not counting its border.
+    pub fn page_size(&self) -> u32 {
+        self.page_size
+    }
+
+    /// Returns the number of texels copied from the neighboring pages around each page, so that
+    /// filtering doesn't bleed across pages.
+    pub fn border(&self) -> u32 {
+        self.border
+    }
+
+    /// Returns the number of mip levels of the texture.
+    pub fn mip_level_count(&self) -> u32 {
+        self.mip_level_count
+    }
+
+    /// Returns the format of the texels of the texture.
+    pub fn format(&self) -> TiledTextureFormat {
+        self.format
+    }
+
+    /// Returns the path of the `.vtex` file the pages are read from.
+    pub fn path(&self) -> &AssetPath<'static> {
+        &self.path
+    }
+
+    /// Returns the number of pages of the given mip level, along each axis.
+    pub fn page_count(&self, mip: u32) -> UVec2 {
+        ((self.size / self.page_size) >> mip).max(UVec2::ONE)
+    }
+
+    /// Returns the total number of pages, across all mip levels.
+    pub fn total_page_count(&self) -> usize {
+        (0..self.mip_level_count)
+            .map(|mip| self.page_count(mip).element_product() as usize)
+            .sum()
+    }
+
+    /// Returns the size of the side of a page stored in the cache, in texels, including its
+    /// border.
+    pub fn slot_size(&self) -> u32 {
+        self.page_size + 2 * self.border
+    }
+
+    /// Returns the number of bytes of the texels of a page, including its border.
+    pub fn page_byte_len(&self) -> usize {
+        (self.slot_size() * self.slot_size()) as usize * 4
+    }
+
+    /// Returns the index of `page` among all the pages of the texture.
+    ///
+    /// Pages are ordered by mip level, then row-major.
+    pub fn page_index(&self, page: PageId) -> usize {
+        let offset: u32 = (0..page.mip)
+            .map(|mip| self.page_count(mip).element_product())
+            .sum();
+        let count = self.page_count(page.mip);
+        (offset + page.position.y * count.x + page.position.x) as usize
+    }
+
+    /// Returns the page with the given index, as returned by [`Self::page_index`], or `None` if
+    /// the index is out of bounds.
+    pub fn page_at(&self, mut index: usize) -> Option<PageId> {
+        for mip in 0..self.mip_level_count {
+            let count = self.page_count(mip);
+            let len = count.element_product() as usize;
+            if index < len {
+                let index = index as u32;
+                return Some(PageId {
+                    mip,
+                    position: UVec2::new(index % count.x, index / count.x),
+                });
+            }
+            index -= len;
+        }
+        None
+    }
+
+    /// Returns the offset of the texels of `page` in the `.vtex` file.
+    pub(crate) fn page_offset(&self, page: PageId) -> u64 {
+        self.page_offsets[self.page_index(page)]
+    }
+}
+
+/// An error produced when encoding a [`TiledTexture`] or loading its `.vtex` file.
+#[derive(Error, Debug)]
+pub enum TiledTextureError {
+    /// An IO error occurred.
+    #[error("An IO error occurred while reading the tiled texture: {0}")]
+    Io(#[from] std::io::Error),
+    /// The file isn't a `.vtex` file of a supported version.
+    #[error("The file is not a tiled texture of version {VERSION}")]
+    InvalidHeader,
+    /// The page table is cut off, or lists a page that overlaps the page table or extends past
+    /// the end of the file.
+    #[error("The page table of the tiled texture doesn't match the size of its file")]
+    InvalidPageTable,
+    /// The texture format isn't supported.
+    #[error(
+        "Tiled textures can't use the {0:?} texture format, only Rgba8Unorm and Rgba8UnormSrgb"
+    )]
+    UnsupportedFormat(TextureFormat),
+    /// The image isn't a 2D image whose size is a multiple of the page size.
+    #[error("An image of size {0} can't be split into pages of size {1}: it must be 2D, and its width and height must be multiples of the page size")]
+    InvalidSize(UVec2, u32),
+}
+
+/// Loads [`TiledTexture`]s from `.vtex` files.
+///
+/// A `.vtex` file, written by [`encode_tiled_texture`], starts with a header of eight
+/// little-endian `u32`s: the magic bytes `BVTX`, the version, the width and height of the
+/// texture, the page size, the border size, the number of mip levels, and the format (`0` for
+/// [`TiledTextureFormat::Rgba8Unorm`], `1` for [`TiledTextureFormat::Rgba8UnormSrgb`]). It's
+/// followed by the `u64` offset in the file of each page, in the order of
+/// [`TiledTexture::page_index`], and then by the RGBA8 texels of the pages, border included.
+#[derive(Default)]
+pub struct TiledTextureLoader;
+
+impl AssetLoader for TiledTextureLoader {
+    type Asset = TiledTexture;
+    type Settings = ();
+    type Error = TiledTextureError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<TiledTexture, Self::Error> {
+        read_tiled_texture(reader, load_context.asset_path().clone()).await
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["vtex"]
+    }
+}
+
+async fn read_tiled_texture(
+    reader: &mut (dyn Reader + '_),
+    path: AssetPath<'static>,
+) -> Result<TiledTexture, TiledTextureError> {
+    let mut header = [0; HEADER_SIZE];
+    reader.read_exact(&mut header).await?;
+    let field =
+        |index: usize| u32::from_le_bytes(header[index * 4..index * 4 + 4].try_into().unwrap());
+    if header[..4] != MAGIC || field(1) != VERSION {
+        return Err(TiledTextureError::InvalidHeader);
+    }
+    let format = match field(7) {
+        0 => TiledTextureFormat::Rgba8Unorm,
+        1 => TiledTextureFormat::Rgba8UnormSrgb,
+        _ => return Err(TiledTextureError::InvalidHeader),
+    };
+    let mut texture = TiledTexture {
+        size: UVec2::new(field(2), field(3)),
+        page_size: field(4),
+        border: field(5),
+        mip_level_count: field(6),
+        format,
+        path,
+        page_offsets: Arc::new([]),
+    };
+    if texture.page_size == 0
+        || texture.size.x % texture.page_size != 0
+        || texture.size.y % texture.page_size != 0
+        || texture.mip_level_count == 0
+        || texture.mip_level_count > max_mip_level_count(texture.size / texture.page_size)
+    {
+        return Err(TiledTextureError::InvalidHeader);
+    }
+
+    // Make sure that the page counts and sizes computed from the header fit in a `u32`.
+    let page_count = (0..texture.mip_level_count).try_fold(0u32, |total, mip| {
+        let count = texture.page_count(mip);
+        count.x.checked_mul(count.y)?.checked_add(total)
+    });
+    let page_byte_len = texture
+        .border
+        .checked_mul(2)
+        .and_then(|border| border.checked_add(texture.page_size))
+        .and_then(|slot_size| slot_size.checked_mul(slot_size)?.checked_mul(4));
+    let (Some(page_count), Some(page_byte_len)) = (page_count, page_byte_len) else {
+        return Err(TiledTextureError::InvalidHeader);
+    };
+
+    // Read the page table incrementally, so that a header claiming a huge number of pages can't
+    // allocate more memory than the file actually holds.
+    let table_len = u64::from(page_count) * 8;
+    let mut offsets = Vec::new();
+    (&mut *reader)
+        .take(table_len)
+        .read_to_end(&mut offsets)
+        .await?;
+    if offsets.len() as u64 != table_len {
+        return Err(TiledTextureError::InvalidPageTable);
+    }
+    texture.page_offsets = offsets
+        .chunks_exact(8)
+        .map(|offset| u64::from_le_bytes(offset.try_into().unwrap()))
+        .collect();
+
+    // Every page must be stored after the page table, and within the file. There's at least one
+    // page, so the end of the last one is past the current position.
+    let data_offset = HEADER_SIZE as u64 + table_len;
+    let mut end = data_offset;
+    for &offset in texture.page_offsets.iter() {
+        let page_end = offset
+            .checked_add(u64::from(page_byte_len))
+            .filter(|_| offset >= data_offset)
+            .ok_or(TiledTextureError::InvalidPageTable)?;
+        end = end.max(page_end);
+    }
+    reader.seek_forward(end - data_offset - 1).await?;
+    let mut last_byte = [0];
+    reader
+        .read_exact(&mut last_byte)
+        .await
+        .map_err(|err| match err.kind() {
+            std::io::ErrorKind::UnexpectedEof => TiledTextureError::InvalidPageTable,
+            _ => err.into(),
+        })?;
+    Ok(texture)
+}
+
+/// Returns the number of mip levels of a texture with the given number of pages at mip level 0,
+/// such that each mip level has half as many pages along each axis as the previous one.
+fn max_mip_level_count(page_count: UVec2) -> u32 {
+    1 + page_count
+        .x
+        .trailing_zeros()
+        .min(page_count.y.trailing_zeros())
+}
+
+/// Splits `image` into `page_size`×`page_size` pages surrounded by `border` texels, at as many
+/// mip levels as its size allows, and returns the contents of the resulting `.vtex` file.
+///
+/// The mip levels are generated by averaging 2×2 blocks of texels. The image must be a 2D image
+/// in the [`TextureFormat::Rgba8Unorm`] or [`TextureFormat::Rgba8UnormSrgb`] format, whose
+/// width and height are multiples of `page_size`.
+pub fn encode_tiled_texture(
+    image: &Image,
+    page_size: u32,
+    border: u32,
+) -> Result<Vec<u8>, TiledTextureError> {
+    let descriptor = &image.texture_descriptor;
+    let format = TiledTextureFormat::from_texture_format(descriptor.format)
+        .ok_or(TiledTextureError::UnsupportedFormat(descriptor.format))?;
+    let size = UVec2::new(descriptor.size.width, descriptor.size.height);
+    if descriptor.dimension != TextureDimension::D2
+        || descriptor.size.depth_or_array_layers != 1
+        || page_size == 0
+        || size.x % page_size != 0
+        || size.y % page_size != 0
+    {
+        return Err(TiledTextureError::InvalidSize(size, page_size));
+    }
+    let mip_level_count = max_mip_level_count(size / page_size);
+
+    let mut levels = vec![(
+        size,
+        image.data[..(size.element_product() * 4) as usize].to_vec(),
+    )];
+    for _ in 1..mip_level_count {
+        let (size, texels) = levels.last().unwrap();
+        levels.push(downsample(*size, texels));
+    }
+
+    let texture = TiledTexture {
+        size,
+        page_size,
+        border,
+        mip_level_count,
+        format,
+        path: AssetPath::default(),
+        page_offsets: Arc::new([]),
+    };
+    let page_count = texture.total_page_count();
+    let data_offset = (HEADER_SIZE + page_count * 8) as u64;
+
+    let mut bytes = Vec::with_capacity(data_offset as usize + page_count * texture.page_byte_len());
+    bytes.extend_from_slice(&MAGIC);
+    let format_index: u32 = match format {
+        TiledTextureFormat::Rgba8Unorm => 0,
+        TiledTextureFormat::Rgba8UnormSrgb => 1,
+    };
+    for field in [
+        VERSION,
+        size.x,
+        size.y,
+        page_size,
+        border,
+        mip_level_count,
+        format_index,
+    ] {
+        bytes.extend_from_slice(&field.to_le_bytes());
+    }
+    for index in 0..page_count as u64 {
+        let offset = data_offset + index * texture.page_byte_len() as u64;
+        bytes.extend_from_slice(&offset.to_le_bytes());
+    }
+
+    let slot_size = texture.slot_size() as i32;
+    for (mip, (level_size, texels)) in levels.iter().enumerate() {
+        let count = texture.page_count(mip as u32);
+        for page_y in 0..count.y {
+            for page_x in 0..count.x {
+                let origin_x = (page_x * page_size) as i32 - border as i32;
+                let origin_y = (page_y * page_size) as i32 - border as i32;
+                for y in 0..slot_size {
+                    let y = (origin_y + y).clamp(0, level_size.y as i32 - 1) as u32;
+                    for x in 0..slot_size {
+                        let x = (origin_x + x).clamp(0, level_size.x as i32 - 1) as u32;
+                        let texel = ((y * level_size.x + x) * 4) as usize;
+                        bytes.extend_from_slice(&texels[texel..texel + 4]);
+                    }
+                }
+            }
+        }
+    }
+    Ok(bytes)
+}
+
+/// Halves the size of an RGBA8 image by averaging 2×2 blocks of texels.
+fn downsample(size: UVec2, texels: &[u8]) -> (UVec2, Vec<u8>) {
+    let half = size / 2;
+    let mut result = Vec::with_capacity((half.element_product() * 4) as usize);
+    for y in 0..half.y {
+        for x in 0..half.x {
+            for channel in 0..4 {
+                let texel =
+                    |x: u32, y: u32| texels[((y * size.x + x) * 4 + channel) as usize] as u32;
+                let sum = texel(2 * x, 2 * y)
+                    + texel(2 * x + 1, 2 * y)
+                    + texel(2 * x, 2 * y + 1)
+                    + texel(2 * x + 1, 2 * y + 1);
+                result.push(((sum + 2) / 4) as u8);
+            }
+        }
+    }
+    (half, result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        encode_tiled_texture, read_tiled_texture, PageId, TiledTexture, TiledTextureError,
+        TiledTextureFormat,
+    };
+    use bevy_asset::{io::SliceReader, AssetPath, RenderAssetUsages};
+    use bevy_image::Image;
+    use bevy_math::UVec2;
+    use bevy_render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+    fn test_image(size: UVec2) -> Image {
+        let data = (0..size.element_product())
+            .flat_map(|texel| [(texel % size.x) as u8, (texel / size.x) as u8, 0, 255])
+            .collect();
+        Image::new(
+            Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8Unorm,
+            RenderAssetUsages::default(),
+        )
+    }
+
+    fn read(bytes: &[u8]) -> Result<TiledTexture, TiledTextureError> {
+        let mut reader = SliceReader::new(bytes);
+        bevy_tasks::block_on(read_tiled_texture(
+            &mut reader,
+            AssetPath::from("terrain.vtex"),
+        ))
+    }
+
+    #[test]
+    fn encode_and_read() {
+        let size = UVec2::new(32, 16);
+        let bytes = encode_tiled_texture(&test_image(size), 8, 1).unwrap();
+        let texture = read(&bytes).unwrap();
+        assert_eq!(texture.size(), size);
+        assert_eq!(texture.format(), TiledTextureFormat::Rgba8Unorm);
+        // 4x2 pages, then 2x1 pages.
+        assert_eq!(texture.mip_level_count(), 2);
+        assert_eq!(texture.total_page_count(), 10);
+
+        let page = PageId {
+            mip: 0,
+            position: UVec2::new(1, 1),
+        };
+        assert_eq!(texture.page_at(texture.page_index(page)), Some(page));
+        assert_eq!(texture.page_at(texture.total_page_count()), None);
+        let offset = texture.page_offset(page) as usize;
+        let page_bytes = &bytes[offset..offset + texture.page_byte_len()];
+        // The first texel is in the border, one texel up and to the left of the page.
+        assert_eq!(page_bytes[..2], [7, 7]);
+        // The texels past the bottom edge of the image are clamped.
+        let last_row = (texture.slot_size() - 1) * texture.slot_size() * 4;
+        assert_eq!(
+            page_bytes[last_row as usize + 4..last_row as usize + 6],
+            [8, 15]
+        );
+
+        let coarse = PageId {
+            mip: 1,
+            position: UVec2::new(1, 0),
+        };
+        assert_eq!(texture.page_index(coarse), 9);
+        let offset = texture.page_offset(coarse) as usize;
+        assert_eq!(offset + texture.page_byte_len(), bytes.len());
+    }
+
+    #[test]
+    fn reject_invalid_files() {
+        let bytes = encode_tiled_texture(&test_image(UVec2::new(16, 16)), 8, 1).unwrap();
+        assert!(read(&bytes).is_ok());
+
+        // The last page is cut off.
+        assert!(matches!(
+            read(&bytes[..bytes.len() - 1]),
+            Err(TiledTextureError::InvalidPageTable)
+        ));
+        // The page table is cut off.
+        assert!(matches!(
+            read(&bytes[..40]),
+            Err(TiledTextureError::InvalidPageTable)
+        ));
+
+        // A page overlaps the page table.
+        let mut overlapping = bytes.clone();
+        overlapping[32..40].copy_from_slice(&0u64.to_le_bytes());
+        assert!(matches!(
+            read(&overlapping),
+            Err(TiledTextureError::InvalidPageTable)
+        ));
+
+        // A 2^31×2^31 texture with 1×1 pages has more pages than a `u32` can count.
+        let mut huge = bytes;
+        for (index, field) in [(2, 1u32 << 31), (3, 1 << 31), (4, 1)] {
+            huge[index * 4..index * 4 + 4].copy_from_slice(&field.to_le_bytes());
+        }
+        assert!(matches!(read(&huge), Err(TiledTextureError::InvalidHeader)));
+    }
+}
@@ -0,0 +1,98 @@
+#import bevy_pbr::{
+    forward_io::{VertexOutput, FragmentOutput},
+    mesh_view_bindings::globals,
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::{alpha_discard, apply_pbr_lighting, main_pass_post_lighting_processing},
+}
+
+struct VirtualTextureParams {
+    // The number of pages at mip level 0.
+    page_count: vec2<u32>,
+    // The number of slots of the cache.
+    cache_slots: vec2<u32>,
+    page_size: u32,
+    border: u32,
+    mip_level_count: u32,
+}
+
+@group(2) @binding(100) var<storage, read> virtual_texture: VirtualTextureParams;
+@group(2) @binding(101) var page_table: texture_2d<u32>;
+@group(2) @binding(102) var page_cache: texture_2d<f32>;
+@group(2) @binding(103) var page_cache_sampler: sampler;
+// The last frame each page was sampled, plus one.
+@group(2) @binding(104) var<storage, read_write> feedback: array<atomic<u32>>;
+
+fn page_count(mip: u32) -> vec2<u32> {
+    return max(virtual_texture.page_count >> vec2(mip), vec2(1u));
+}
+
+// Must match `TiledTexture::page_index`.
+fn page_index(mip: u32, page: vec2<u32>) -> u32 {
+    var offset = 0u;
+    for (var level = 0u; level < mip; level += 1u) {
+        let count = page_count(level);
+        offset += count.x * count.y;
+    }
+    return offset + page.y * page_count(mip).x + page.x;
+}
+
+fn page_at(uv: vec2<f32>, mip: u32) -> vec2<u32> {
+    let count = page_count(mip);
+    return min(vec2<u32>(uv * vec2<f32>(count)), count - 1u);
+}
+
+fn sample_page(uv: vec2<f32>, mip: u32, slot: vec2<u32>) -> vec4<f32> {
+    let slot_size = f32(virtual_texture.page_size + 2u * virtual_texture.border);
+    let in_page = fract(uv * vec2<f32>(page_count(mip))) * f32(virtual_texture.page_size);
+    let texel = vec2<f32>(slot) * slot_size + f32(virtual_texture.border) + in_page;
+    let cache_size = vec2<f32>(virtual_texture.cache_slots) * slot_size;
+    return textureSampleLevel(page_cache, page_cache_sampler, texel / cache_size, 0.0);
+}
+
+fn sample_virtual_texture(uv: vec2<f32>, frag_coord: vec2<f32>) -> vec4<f32> {
+    let texel_uv = uv * vec2<f32>(virtual_texture.page_count * virtual_texture.page_size);
+    let dx = dpdx(texel_uv);
+    let dy = dpdy(texel_uv);
+    let lod = 0.5 * log2(max(dot(dx, dx), dot(dy, dy)));
+    let coarsest = virtual_texture.mip_level_count - 1u;
+    let wanted_mip = u32(clamp(lod, 0.0, f32(coarsest)));
+    let wrapped_uv = fract(uv);
+
+    // Only record the page of one fragment out of four each frame, to limit the contention on the
+    // feedback buffer.
+    let pixel = vec2<u32>(frag_coord);
+    if (pixel.x + pixel.y * 2u + globals.frame_count) % 4u == 0u {
+        let page = page_at(wrapped_uv, wanted_mip);
+        atomicMax(&feedback[page_index(wanted_mip, page)], globals.frame_count + 1u);
+    }
+
+    // Fall back to coarser mip levels until a resident page is found. The coarsest mip level is
+    // always resident.
+    for (var mip = wanted_mip; mip < coarsest; mip += 1u) {
+        let entry = textureLoad(page_table, page_at(wrapped_uv, mip), i32(mip));
+        if entry.a != 0u {
+            return sample_page(wrapped_uv, mip, entry.xy);
+        }
+    }
+    let entry = textureLoad(page_table, page_at(wrapped_uv, coarsest), i32(coarsest));
+    return sample_page(wrapped_uv, coarsest, entry.xy);
+}
+
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+
+#ifdef VERTEX_UVS_A
+    pbr_input.material.base_color *= sample_virtual_texture(in.uv, in.position.xy);
+#endif
+
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+
+    var out: FragmentOutput;
+    out.color = apply_pbr_lighting(pbr_input);
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+    return out;
+}
@@ -0,0 +1,445 @@
+//! Volumetric clouds.
+//!
+//! This module renders a layer of clouds between two altitudes, the *cloud
+//! slab*, by raymarching through it in a postprocessing pass, in the same
+//! way as [`crate::volumetric_fog`] raymarches through fog volumes.
+//!
+//! To add clouds to a scene, add [`VolumetricClouds`] to the camera. The shape
+//! of the clouds comes from two tiling noise textures: a 2D *coverage* texture
+//! that determines where clouds appear when seen from above, and a 3D *erosion*
+//! texture that carves detail out of their edges. Both textures scroll with the
+//! wind over time, which animates the clouds. Bevy provides default textures,
+//! which can be replaced with your own.
+//!
+//! The clouds are lit by every [`DirectionalLight`](crate::DirectionalLight),
+//! such as the sun, with self-shadowing computed by raymarching toward the
+//! light, and by a constant ambient light. Clouds don't currently cast shadows
+//! onto the scene.
+
+use bevy_app::{App, Plugin};
+use bevy_asset::{load_internal_asset, Assets, Handle, RenderAssetUsages};
+use bevy_color::Color;
+use bevy_core_pipeline::core_3d::{
+    graph::{Core3d, Node3d},
+    prepare_core_3d_depth_textures,
+};
+use bevy_ecs::{component::Component, reflect::ReflectComponent, schedule::IntoSystemConfigs as _};
+use bevy_image::Image;
+use bevy_math::Vec3;
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::{
+    extract_component::{ExtractComponent, ExtractComponentPlugin},
+    render_graph::{RenderGraph, RenderGraphApp, ViewNodeRunner},
+    render_resource::{
+        Extent3d, Shader, SpecializedRenderPipelines, TextureDimension, TextureFormat,
+    },
+    Render, RenderApp, RenderSet,
+};
+use render::{
+    VolumetricCloudsNode, VolumetricCloudsPipeline, VolumetricCloudsUniformBuffer,
+    VOLUMETRIC_CLOUDS_HANDLE,
+};
+
+use crate::graph::NodePbr;
+
+pub mod render;
+
+/// The default coverage texture of [`VolumetricClouds`].
+pub const DEFAULT_CLOUD_COVERAGE_TEXTURE: Handle<Image> =
+    Handle::weak_from_u128(9321950777809173326);
+
+/// The default erosion texture of [`VolumetricClouds`].
+pub const DEFAULT_CLOUD_EROSION_TEXTURE: Handle<Image> =
+    Handle::weak_from_u128(1641248280830624127);
+
+/// The size in texels of each side of [`DEFAULT_CLOUD_COVERAGE_TEXTURE`].
+const COVERAGE_TEXTURE_SIZE: u32 = 128;
+
+/// The size in texels of each side of [`DEFAULT_CLOUD_EROSION_TEXTURE`].
+const EROSION_TEXTURE_SIZE: u32 = 32;
+
+/// A plugin that implements volumetric clouds.
+pub struct VolumetricCloudsPlugin;
+
+/// When placed on a [`bevy_core_pipeline::core_3d::Camera3d`], renders a layer
+/// of volumetric clouds between two altitudes.
+///
+/// The clouds are rendered on top of the main pass, and behind any
+/// [`FogVolume`](crate::FogVolume).
+#[derive(Clone, Component, Debug, Reflect, ExtractComponent)]
+#[reflect(Component, Default, Debug)]
+pub struct VolumetricClouds {
+    /// The altitude of the bottom of the cloud layer, in meters.
+    ///
+    /// The default value is 1500.
+    pub bottom_height: f32,
+
+    /// The altitude of the top of the cloud layer, in meters.
+    ///
+    /// The default value is 3000.
+    pub top_height: f32,
+
+    /// The fraction of the sky that's covered by clouds, from 0 to 1.
+    ///
+    /// The default value is 0.5.
+    pub coverage: f32,
+
+    /// The extinction coefficient of the clouds, which measures which fraction
+    /// of light is absorbed or scattered out by each meter of cloud.
+    ///
+    /// Increasing this value makes the clouds more opaque.
+    ///
+    /// The default value is 0.01.
+    pub density: f32,
+
+    /// The color of the clouds.
+    ///
+    /// Note that the clouds must be lit by a directional light or ambient
+    /// light in order for this color to appear.
+    ///
+    /// Defaults to white.
+    pub color: Color,
+
+    /// A 2D tiling texture whose red channel determines the shape of the
+    /// clouds when seen from above.
+    ///
+    /// The texture is stretched horizontally over [`Self::coverage_scale`]
+    /// meters. Defaults to [`DEFAULT_CLOUD_COVERAGE_TEXTURE`].
+    pub coverage_texture: Handle<Image>,
+
+    /// The size, in meters, that the coverage texture covers before repeating.
+    ///
+    /// The default value is 20000.
+    pub coverage_scale: f32,
+
+    /// A 3D tiling texture whose red channel is subtracted from the edges of
+    /// the clouds to give them detail.
+    ///
+    /// Defaults to [`DEFAULT_CLOUD_EROSION_TEXTURE`].
+    pub erosion_texture: Handle<Image>,
+
+    /// The size, in meters, that the erosion texture covers before repeating.
+    ///
+    /// The default value is 2000.
+    pub erosion_scale: f32,
+
+    /// How much of the erosion texture is subtracted from the clouds, from 0
+    /// to 1.
+    ///
+    /// The default value is 0.3.
+    pub erosion_strength: f32,
+
+    /// The velocity at which the clouds move, in meters per second.
+    ///
+    /// The coverage and erosion textures are scrolled by this velocity over
+    /// time.
+    ///
+    /// The default value is (10, 0, 5).
+    pub wind: Vec3,
+
+    /// Measures the fraction of light that's scattered *toward* the camera, as
+    /// opposed to *away* from the camera.
+    ///
+    /// Higher values make the clouds glow more when the camera looks at them
+    /// with the sun behind them, as they do in reality.
+    ///
+    /// The default value is 0.6.
+    pub scattering_asymmetry: f32,
+
+    /// Color of the ambient light that lights the clouds.
+    ///
+    /// Defaults to a light blue, approximating the color of the sky.
+    pub ambient_color: Color,
+
+    /// The brightness of the ambient light.
+    ///
+    /// The default value is 0.3.
+    pub ambient_intensity: f32,
+
+    /// The number of raymarching steps to perform through the cloud layer.
+    ///
+    /// Higher values produce higher-quality results with less banding, but
+    /// reduce performance.
+    ///
+    /// The default value is 64.
+    pub step_count: u32,
+
+    /// The number of raymarching steps to perform toward each directional
+    /// light to compute the self-shadowing of the clouds.
+    ///
+    /// The default value is 6.
+    pub light_step_count: u32,
+
+    /// The maximum distance from the camera, in meters, at which clouds are
+    /// rendered.
+    ///
+    /// The default value is 50000.
+    pub max_distance: f32,
+}
+
+impl Plugin for VolumetricCloudsPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            VOLUMETRIC_CLOUDS_HANDLE,
+            "volumetric_clouds.wgsl",
+            Shader::from_wgsl
+        );
+
+        let mut images = app.world_mut().resource_mut::<Assets<Image>>();
+        images.insert(
+            &DEFAULT_CLOUD_COVERAGE_TEXTURE,
+            new_noise_image(COVERAGE_TEXTURE_SIZE, TextureDimension::D2, coverage_noise),
+        );
+        images.insert(
+            &DEFAULT_CLOUD_EROSION_TEXTURE,
+            new_noise_image(EROSION_TEXTURE_SIZE, TextureDimension::D3, erosion_noise),
+        );
+
+        app.register_type::<VolumetricClouds>()
+            .add_plugins(ExtractComponentPlugin::<VolumetricClouds>::default());
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .init_resource::<SpecializedRenderPipelines<VolumetricCloudsPipeline>>()
+            .init_resource::<VolumetricCloudsUniformBuffer>()
+            .add_systems(
+                Render,
+                (
+                    render::prepare_volumetric_clouds_pipelines.in_set(RenderSet::Prepare),
+                    render::prepare_volumetric_clouds_uniforms.in_set(RenderSet::Prepare),
+                    render::prepare_view_depth_textures_for_volumetric_clouds
+                        .in_set(RenderSet::Prepare)
+                        .before(prepare_core_3d_depth_textures),
+                ),
+            )
+            .add_render_graph_node::<ViewNodeRunner<VolumetricCloudsNode>>(
+                Core3d,
+                NodePbr::VolumetricClouds,
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app.init_resource::<VolumetricCloudsPipeline>();
+
+        // Clouds are far away, so they go behind the volumetric fog if it has
+        // been added.
+        let has_volumetric_fog_pass = render_app
+            .world()
+            .resource::<RenderGraph>()
+            .sub_graph(Core3d)
+            .get_node_state(NodePbr::VolumetricFog)
+            .is_ok();
+
+        if has_volumetric_fog_pass {
+            render_app.add_render_graph_edges(
+                Core3d,
+                (
+                    Node3d::EndMainPass,
+                    NodePbr::VolumetricClouds,
+                    NodePbr::VolumetricFog,
+                ),
+            );
+        } else {
+            render_app.add_render_graph_edges(
+                Core3d,
+                (
+                    Node3d::EndMainPass,
+                    NodePbr::VolumetricClouds,
+                    Node3d::Bloom,
+                ),
+            );
+        }
+    }
+}
+
+impl Default for VolumetricClouds {
+    fn default() -> Self {
+        Self {
+            bottom_height: 1500.0,
+            top_height: 3000.0,
+            coverage: 0.5,
+            density: 0.01,
+            color: Color::WHITE,
+            coverage_texture: DEFAULT_CLOUD_COVERAGE_TEXTURE,
+            coverage_scale: 20000.0,
+            erosion_texture: DEFAULT_CLOUD_EROSION_TEXTURE,
+            erosion_scale: 2000.0,
+            erosion_strength: 0.3,
+            wind: Vec3::new(10.0, 0.0, 5.0),
+            scattering_asymmetry: 0.6,
+            ambient_color: Color::srgb(0.6, 0.75, 1.0),
+            ambient_intensity: 0.3,
+            step_count: 64,
+            light_step_count: 6,
+            max_distance: 50000.0,
+        }
+    }
+}
+
+/// Creates a single-channel image with `size` texels on each side, filled by
+/// calling `noise` with the coordinates of each texel.
+fn new_noise_image(size: u32, dimension: TextureDimension, noise: fn(Vec3) -> f32) -> Image {
+    let depth = if dimension == TextureDimension::D3 {
+        size
+    } else {
+        1
+    };
+
+    let mut data = Vec::with_capacity((size * size * depth) as usize);
+    for z in 0..depth {
+        for y in 0..size {
+            for x in 0..size {
+                let position = Vec3::new(x as f32, y as f32, z as f32) / size as f32;
+                data.push((noise(position).clamp(0.0, 1.0) * 255.0).round() as u8);
+            }
+        }
+    }
+
+    Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: depth,
+        },
+        dimension,
+        data,
+        TextureFormat::R8Unorm,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+/// The noise of [`DEFAULT_CLOUD_COVERAGE_TEXTURE`]: a few octaves of value
+/// noise that tile over the unit square.
+fn coverage_noise(position: Vec3) -> f32 {
+    let mut value = 0.0;
+    let mut amplitude = 0.5;
+    let mut total_amplitude = 0.0;
+    for period in [4, 8, 16, 32] {
+        value += value_noise_2d(position.x, position.y, period) * amplitude;
+        total_amplitude += amplitude;
+        amplitude *= 0.5;
+    }
+    value / total_amplitude
+}
+
+/// The noise of [`DEFAULT_CLOUD_EROSION_TEXTURE`]: a few octaves of Worley
+/// noise that tile over the unit cube.
+fn erosion_noise(position: Vec3) -> f32 {
+    worley_noise_3d(position, 4) * 0.625
+        + worley_noise_3d(position, 8) * 0.25
+        + worley_noise_3d(position, 16) * 0.125
+}
+
+/// Returns value noise at `(x, y)` that repeats `period` times over the unit
+/// square.
+fn value_noise_2d(x: f32, y: f32, period: u32) -> f32 {
+    let (x, y) = (x * period as f32, y * period as f32);
+    let (cell_x, cell_y) = (x.floor(), y.floor());
+    let (fraction_x, fraction_y) = (smoothstep(x - cell_x), smoothstep(y - cell_y));
+
+    let corner = |offset_x: u32, offset_y: u32| {
+        let corner_x = (cell_x as u32 + offset_x) % period;
+        let corner_y = (cell_y as u32 + offset_y) % period;
+        hash_to_unit(hash([corner_x, corner_y, 0, period]))
+    };
+
+    let bottom = lerp(corner(0, 0), corner(1, 0), fraction_x);
+    let top = lerp(corner(0, 1), corner(1, 1), fraction_x);
+    lerp(bottom, top, fraction_y)
+}
+
+/// Returns one minus the distance from `position` to the nearest of one random
+/// point in each of `period`³ cells, so that the noise repeats `period` times
+/// over the unit cube.
+fn worley_noise_3d(position: Vec3, period: u32) -> f32 {
+    let position = position * period as f32;
+    let cell = position.floor();
+
+    let mut min_distance_squared = f32::MAX;
+    for offset_z in -1..=1 {
+        for offset_y in -1..=1 {
+            for offset_x in -1..=1 {
+                let neighbor = cell + Vec3::new(offset_x as f32, offset_y as f32, offset_z as f32);
+                let wrapped = neighbor.rem_euclid(Vec3::splat(period as f32));
+                let seed = [wrapped.x as u32, wrapped.y as u32, wrapped.z as u32, period];
+                let point = neighbor
+                    + Vec3::new(
+                        hash_to_unit(hash(seed)),
+                        hash_to_unit(hash(seed) ^ 0x68e3_1da4),
+                        hash_to_unit(hash(seed) ^ 0xb529_7a4d),
+                    );
+                min_distance_squared = min_distance_squared.min(position.distance_squared(point));
+            }
+        }
+    }
+
+    1.0 - min_distance_squared.sqrt().min(1.0)
+}
+
+/// Hashes the given integers into a pseudorandom `u32`.
+fn hash(values: [u32; 4]) -> u32 {
+    values.iter().fold(0x9e37_79b9_u32, |state, &value| {
+        let mut state = (state ^ value).wrapping_mul(0x85eb_ca6b);
+        state ^= state >> 13;
+        state = state.wrapping_mul(0xc2b2_ae35);
+        state ^ (state >> 16)
+    })
+}
+
+/// Converts a hash into a float in the range [0, 1).
+fn hash_to_unit(hash: u32) -> f32 {
+    (hash >> 8) as f32 / (1 << 24) as f32
+}
+
+fn smoothstep(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_math::Vec3;
+
+    use super::{coverage_noise, erosion_noise};
+
+    #[test]
+    fn cloud_noise_tiles() {
+        for &(x, y, z) in &[(0.0, 0.0, 0.0), (0.3, 0.7, 0.1), (0.99, 0.01, 0.5)] {
+            let position = Vec3::new(x, y, z);
+            for offset in [Vec3::X, Vec3::Y, Vec3::Z] {
+                let (a, b) = (erosion_noise(position), erosion_noise(position + offset));
+                assert!(
+                    (a - b).abs() < 1e-4,
+                    "erosion noise doesn't tile: {a} != {b}"
+                );
+            }
+            for offset in [Vec3::X, Vec3::Y] {
+                let (a, b) = (coverage_noise(position), coverage_noise(position + offset));
+                assert!(
+                    (a - b).abs() < 1e-4,
+                    "coverage noise doesn't tile: {a} != {b}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn cloud_noise_is_normalized() {
+        for i in 0..64 {
+            let position = Vec3::new(i as f32 * 0.013, i as f32 * 0.029, i as f32 * 0.041);
+            assert!((0.0..=1.0).contains(&coverage_noise(position)));
+            assert!((0.0..=1.0).contains(&erosion_noise(position)));
+        }
+    }
+}
@@ -0,0 +1,459 @@
+//! Rendering of volumetric clouds.
+
+use bevy_asset::Handle;
+use bevy_color::ColorToComponents as _;
+use bevy_core_pipeline::{
+    core_3d::Camera3d,
+    fullscreen_vertex_shader,
+    prepass::{DeferredPrepass, DepthPrepass, MotionVectorPrepass, NormalPrepass},
+};
+use bevy_derive::{Deref, DerefMut};
+use bevy_ecs::{
+    component::Component,
+    entity::Entity,
+    query::{Has, QueryItem, With},
+    system::{lifetimeless::Read, Commands, Query, Res, ResMut, Resource},
+    world::{FromWorld, World},
+};
+use bevy_image::BevyDefault as _;
+use bevy_math::Vec3;
+use bevy_render::{
+    render_asset::RenderAssets,
+    render_graph::{NodeRunError, RenderGraphContext, ViewNode},
+    render_resource::{
+        binding_types::{
+            sampler, texture_2d, texture_3d, texture_depth_2d, texture_depth_2d_multisampled,
+            uniform_buffer,
+        },
+        AddressMode, BindGroupEntries, BindGroupLayout, BindGroupLayoutEntries, BlendComponent,
+        BlendFactor, BlendOperation, BlendState, CachedRenderPipelineId, ColorTargetState,
+        ColorWrites, DynamicUniformBuffer, FilterMode, FragmentState, LoadOp, Operations,
+        PipelineCache, RenderPassColorAttachment, RenderPassDescriptor, RenderPipelineDescriptor,
+        Sampler, SamplerBindingType, SamplerDescriptor, Shader, ShaderStages, ShaderType,
+        SpecializedRenderPipeline, SpecializedRenderPipelines, StoreOp, TextureFormat,
+        TextureSampleType, TextureUsages,
+    },
+    renderer::{RenderContext, RenderDevice, RenderQueue},
+    texture::GpuImage,
+    view::{ExtractedView, Msaa, ViewDepthTexture, ViewTarget, ViewUniformOffset},
+};
+use bevy_utils::prelude::default;
+
+use crate::{
+    MeshPipelineViewLayoutKey, MeshPipelineViewLayouts, MeshViewBindGroup,
+    ViewEnvironmentMapUniformOffset, ViewFogUniformOffset, ViewLightProbesUniformOffset,
+    ViewLightsUniformOffset, ViewScreenSpaceReflectionsUniformOffset, VolumetricClouds,
+};
+
+/// The volumetric clouds shader.
+pub const VOLUMETRIC_CLOUDS_HANDLE: Handle<Shader> = Handle::weak_from_u128(2760889712848192275);
+
+/// The GPU pipeline for the volumetric clouds postprocessing effect.
+#[derive(Resource)]
+pub struct VolumetricCloudsPipeline {
+    /// A reference to the shared set of mesh pipeline view layouts.
+    mesh_view_layouts: MeshPipelineViewLayouts,
+
+    /// The bind group layouts for views without and with multisampling
+    /// respectively.
+    bind_group_layouts: [BindGroupLayout; 2],
+
+    /// The sampler that repeats the coverage and erosion textures.
+    noise_sampler: Sampler,
+}
+
+/// The render pipeline that a view uses to render volumetric clouds.
+#[derive(Component, Deref, DerefMut)]
+pub struct ViewVolumetricCloudsPipeline(pub CachedRenderPipelineId);
+
+/// The node in the render graph, part of the postprocessing stack, that
+/// implements volumetric clouds.
+#[derive(Default)]
+pub struct VolumetricCloudsNode;
+
+/// Identifies a single specialization of the volumetric clouds shader.
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+pub struct VolumetricCloudsPipelineKey {
+    /// The layout of the view, which is needed for the raymarching.
+    mesh_pipeline_view_key: MeshPipelineViewLayoutKey,
+
+    /// Whether the view's color format has high dynamic range.
+    hdr: bool,
+}
+
+/// The same as [`VolumetricClouds`], but formatted for the GPU.
+///
+/// See the documentation of that structure for more information on these
+/// fields.
+#[derive(ShaderType)]
+pub struct VolumetricCloudsUniform {
+    color: Vec3,
+    bottom_height: f32,
+    ambient_color: Vec3,
+    top_height: f32,
+    wind: Vec3,
+    coverage: f32,
+    density: f32,
+    coverage_scale: f32,
+    erosion_scale: f32,
+    erosion_strength: f32,
+    scattering_asymmetry: f32,
+    step_count: u32,
+    light_step_count: u32,
+    max_distance: f32,
+}
+
+/// Specifies the offset within the [`VolumetricCloudsUniformBuffer`] of the
+/// [`VolumetricCloudsUniform`] for a specific view.
+#[derive(Component, Deref, DerefMut)]
+pub struct ViewVolumetricCloudsUniformOffset(u32);
+
+/// The GPU buffer that stores the [`VolumetricCloudsUniform`] data.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct VolumetricCloudsUniformBuffer(pub DynamicUniformBuffer<VolumetricCloudsUniform>);
+
+impl FromWorld for VolumetricCloudsPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let mesh_view_layouts = world.resource::<MeshPipelineViewLayouts>();
+
+        let bind_group_layouts = [false, true].map(|multisampled| {
+            render_device.create_bind_group_layout(
+                if multisampled {
+                    "volumetric clouds view bind group layout (multisampled)"
+                } else {
+                    "volumetric clouds view bind group layout"
+                },
+                &BindGroupLayoutEntries::sequential(
+                    ShaderStages::FRAGMENT,
+                    (
+                        // `volumetric_clouds`
+                        uniform_buffer::<VolumetricCloudsUniform>(true),
+                        // `depth_texture`
+                        if multisampled {
+                            texture_depth_2d_multisampled()
+                        } else {
+                            texture_depth_2d()
+                        },
+                        // `coverage_texture`
+                        texture_2d(TextureSampleType::Float { filterable: true }),
+                        // `erosion_texture`
+                        texture_3d(TextureSampleType::Float { filterable: true }),
+                        // `noise_sampler`
+                        sampler(SamplerBindingType::Filtering),
+                    ),
+                ),
+            )
+        });
+
+        let noise_sampler = render_device.create_sampler(&SamplerDescriptor {
+            label: Some("volumetric clouds noise sampler"),
+            address_mode_u: AddressMode::Repeat,
+            address_mode_v: AddressMode::Repeat,
+            address_mode_w: AddressMode::Repeat,
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..default()
+        });
+
+        VolumetricCloudsPipeline {
+            mesh_view_layouts: mesh_view_layouts.clone(),
+            bind_group_layouts,
+            noise_sampler,
+        }
+    }
+}
+
+impl ViewNode for VolumetricCloudsNode {
+    type ViewQuery = (
+        Read<ViewTarget>,
+        Read<ViewDepthTexture>,
+        Read<VolumetricClouds>,
+        Read<ViewVolumetricCloudsPipeline>,
+        Read<ViewVolumetricCloudsUniformOffset>,
+        Read<ViewUniformOffset>,
+        Read<ViewLightsUniformOffset>,
+        Read<ViewFogUniformOffset>,
+        Read<ViewLightProbesUniformOffset>,
+        Read<MeshViewBindGroup>,
+        Read<ViewScreenSpaceReflectionsUniformOffset>,
+        Read<Msaa>,
+        Read<ViewEnvironmentMapUniformOffset>,
+    );
+
+    fn run<'w>(
+        &self,
+        _: &mut RenderGraphContext,
+        render_context: &mut RenderContext<'w>,
+        (
+            view_target,
+            view_depth_texture,
+            volumetric_clouds,
+            view_volumetric_clouds_pipeline,
+            view_volumetric_clouds_offset,
+            view_uniform_offset,
+            view_lights_offset,
+            view_fog_offset,
+            view_light_probes_offset,
+            view_bind_group,
+            view_ssr_offset,
+            msaa,
+            view_environment_map_offset,
+        ): QueryItem<'w, Self::ViewQuery>,
+        world: &'w World,
+    ) -> Result<(), NodeRunError> {
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let volumetric_clouds_pipeline = world.resource::<VolumetricCloudsPipeline>();
+        let volumetric_clouds_uniform_buffer = world.resource::<VolumetricCloudsUniformBuffer>();
+        let image_assets = world.resource::<RenderAssets<GpuImage>>();
+
+        // Fetch the pipeline, the uniform buffer binding, and the noise
+        // textures, which may not have been loaded yet.
+        let (
+            Some(pipeline),
+            Some(volumetric_clouds_uniform_buffer_binding),
+            Some(coverage_image),
+            Some(erosion_image),
+        ) = (
+            pipeline_cache.get_render_pipeline(**view_volumetric_clouds_pipeline),
+            volumetric_clouds_uniform_buffer.binding(),
+            image_assets.get(&volumetric_clouds.coverage_texture),
+            image_assets.get(&volumetric_clouds.erosion_texture),
+        )
+        else {
+            return Ok(());
+        };
+
+        // Create the bind group for the view.
+        //
+        // TODO: Cache this.
+        let multisampled = !matches!(*msaa, Msaa::Off);
+        let volumetric_clouds_bind_group = render_context.render_device().create_bind_group(
+            None,
+            &volumetric_clouds_pipeline.bind_group_layouts[multisampled as usize],
+            &BindGroupEntries::sequential((
+                volumetric_clouds_uniform_buffer_binding,
+                view_depth_texture.view(),
+                &coverage_image.texture_view,
+                &erosion_image.texture_view,
+                &volumetric_clouds_pipeline.noise_sampler,
+            )),
+        );
+
+        let render_pass_descriptor = RenderPassDescriptor {
+            label: Some("volumetric clouds pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: view_target.main_texture_view(),
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Load,
+                    store: StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        };
+
+        let mut render_pass = render_context
+            .command_encoder()
+            .begin_render_pass(&render_pass_descriptor);
+
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(
+            0,
+            &view_bind_group.value,
+            &[
+                view_uniform_offset.offset,
+                view_lights_offset.offset,
+                view_fog_offset.offset,
+                **view_light_probes_offset,
+                **view_ssr_offset,
+                **view_environment_map_offset,
+            ],
+        );
+        render_pass.set_bind_group(
+            1,
+            &volumetric_clouds_bind_group,
+            &[**view_volumetric_clouds_offset],
+        );
+        render_pass.draw(0..3, 0..1);
+
+        Ok(())
+    }
+}
+
+impl SpecializedRenderPipeline for VolumetricCloudsPipeline {
+    type Key = VolumetricCloudsPipelineKey;
+
+    fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let mesh_view_layout = self
+            .mesh_view_layouts
+            .get_view_layout(key.mesh_pipeline_view_key);
+
+        let multisampled = key
+            .mesh_pipeline_view_key
+            .contains(MeshPipelineViewLayoutKey::MULTISAMPLED);
+
+        let mut shader_defs = vec![];
+        if multisampled {
+            shader_defs.push("MULTISAMPLED".into());
+        }
+
+        RenderPipelineDescriptor {
+            label: Some("volumetric clouds pipeline".into()),
+            layout: vec![
+                mesh_view_layout.clone(),
+                self.bind_group_layouts[multisampled as usize].clone(),
+            ],
+            push_constant_ranges: vec![],
+            vertex: fullscreen_vertex_shader::fullscreen_shader_vertex_state(),
+            primitive: default(),
+            depth_stencil: None,
+            multisample: default(),
+            fragment: Some(FragmentState {
+                shader: VOLUMETRIC_CLOUDS_HANDLE,
+                shader_defs,
+                entry_point: "fragment".into(),
+                targets: vec![Some(ColorTargetState {
+                    format: if key.hdr {
+                        ViewTarget::TEXTURE_FORMAT_HDR
+                    } else {
+                        TextureFormat::bevy_default()
+                    },
+                    // Blend on top of what's already in the framebuffer, like
+                    // volumetric fog does.
+                    blend: Some(BlendState {
+                        color: BlendComponent {
+                            src_factor: BlendFactor::One,
+                            dst_factor: BlendFactor::OneMinusSrcAlpha,
+                            operation: BlendOperation::Add,
+                        },
+                        alpha: BlendComponent {
+                            src_factor: BlendFactor::Zero,
+                            dst_factor: BlendFactor::One,
+                            operation: BlendOperation::Add,
+                        },
+                    }),
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            zero_initialize_workgroup_memory: false,
+        }
+    }
+}
+
+/// Specializes volumetric clouds pipelines for all views with that effect
+/// enabled.
+pub fn prepare_volumetric_clouds_pipelines(
+    mut commands: Commands,
+    pipeline_cache: Res<PipelineCache>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<VolumetricCloudsPipeline>>,
+    volumetric_clouds_pipeline: Res<VolumetricCloudsPipeline>,
+    view_targets: Query<
+        (
+            Entity,
+            &ExtractedView,
+            &Msaa,
+            Has<NormalPrepass>,
+            Has<DepthPrepass>,
+            Has<MotionVectorPrepass>,
+            Has<DeferredPrepass>,
+        ),
+        With<VolumetricClouds>,
+    >,
+) {
+    for (
+        entity,
+        view,
+        msaa,
+        normal_prepass,
+        depth_prepass,
+        motion_vector_prepass,
+        deferred_prepass,
+    ) in view_targets.iter()
+    {
+        // Create a mesh pipeline view layout key corresponding to the view.
+        let mut mesh_pipeline_view_key = MeshPipelineViewLayoutKey::from(*msaa);
+        mesh_pipeline_view_key.set(MeshPipelineViewLayoutKey::NORMAL_PREPASS, normal_prepass);
+        mesh_pipeline_view_key.set(MeshPipelineViewLayoutKey::DEPTH_PREPASS, depth_prepass);
+        mesh_pipeline_view_key.set(
+            MeshPipelineViewLayoutKey::MOTION_VECTOR_PREPASS,
+            motion_vector_prepass,
+        );
+        mesh_pipeline_view_key.set(
+            MeshPipelineViewLayoutKey::DEFERRED_PREPASS,
+            deferred_prepass,
+        );
+
+        let pipeline_id = pipelines.specialize(
+            &pipeline_cache,
+            &volumetric_clouds_pipeline,
+            VolumetricCloudsPipelineKey {
+                mesh_pipeline_view_key,
+                hdr: view.hdr,
+            },
+        );
+
+        commands
+            .entity(entity)
+            .insert(ViewVolumetricCloudsPipeline(pipeline_id));
+    }
+}
+
+/// A system that converts [`VolumetricClouds`] into
+/// [`VolumetricCloudsUniform`]s.
+pub fn prepare_volumetric_clouds_uniforms(
+    mut commands: Commands,
+    mut volumetric_clouds_uniform_buffer: ResMut<VolumetricCloudsUniformBuffer>,
+    view_targets: Query<(Entity, &VolumetricClouds), With<ExtractedView>>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+) {
+    let Some(mut writer) = volumetric_clouds_uniform_buffer.get_writer(
+        view_targets.iter().len(),
+        &render_device,
+        &render_queue,
+    ) else {
+        return;
+    };
+
+    for (view_entity, volumetric_clouds) in view_targets.iter() {
+        let uniform_buffer_offset = writer.write(&VolumetricCloudsUniform {
+            color: volumetric_clouds.color.to_linear().to_vec3(),
+            bottom_height: volumetric_clouds.bottom_height,
+            ambient_color: volumetric_clouds.ambient_color.to_linear().to_vec3()
+                * volumetric_clouds.ambient_intensity,
+            top_height: volumetric_clouds
+                .top_height
+                .max(volumetric_clouds.bottom_height),
+            wind: volumetric_clouds.wind,
+            coverage: volumetric_clouds.coverage,
+            density: volumetric_clouds.density,
+            coverage_scale: volumetric_clouds.coverage_scale,
+            erosion_scale: volumetric_clouds.erosion_scale,
+            erosion_strength: volumetric_clouds.erosion_strength,
+            scattering_asymmetry: volumetric_clouds.scattering_asymmetry,
+            step_count: volumetric_clouds.step_count.max(1),
+            light_step_count: volumetric_clouds.light_step_count,
+            max_distance: volumetric_clouds.max_distance,
+        });
+
+        commands
+            .entity(view_entity)
+            .insert(ViewVolumetricCloudsUniformOffset(uniform_buffer_offset));
+    }
+}
+
+/// A system that marks the depth textures of views with volumetric clouds as
+/// readable in shaders.
+///
+/// The volumetric clouds pass needs to do this, and it doesn't happen by
+/// default.
+pub fn prepare_view_depth_textures_for_volumetric_clouds(
+    mut view_targets: Query<&mut Camera3d, With<VolumetricClouds>>,
+) {
+    for mut camera in view_targets.iter_mut() {
+        camera.depth_texture_usages.0 |= TextureUsages::TEXTURE_BINDING.bits();
+    }
+}
@@ -0,0 +1,209 @@
+// A postprocessing shader that renders a layer of volumetric clouds via
+// raymarching.
+//
+// For each pixel, we intersect the view ray with the slab between the bottom
+// and the top of the cloud layer, and march through the part of it in front of
+// the scene. At each step, the density of the clouds comes from the coverage
+// texture, shaped by a height gradient, minus the erosion texture. Light from
+// each directional light is attenuated by a second, shorter march toward the
+// light, and scattered toward the camera according to the Henyey-Greenstein
+// phase function, as in volumetric fog. The scattering is integrated over each
+// step with the energy-conserving formula of [1].
+//
+// [1]: https://www.ea.com/frostbite/news/physically-based-unified-volumetric-rendering-in-frostbite
+
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+#import bevy_pbr::mesh_view_bindings::{globals, lights, view}
+#import bevy_pbr::utils::interleaved_gradient_noise
+#import bevy_pbr::view_transformations::{frag_coord_to_ndc, position_ndc_to_world}
+
+// The GPU version of [`VolumetricClouds`]. See the comments in
+// `volumetric_clouds/mod.rs` for descriptions of the fields here.
+struct VolumetricClouds {
+    color: vec3<f32>,
+    bottom_height: f32,
+    ambient_color: vec3<f32>,
+    top_height: f32,
+    wind: vec3<f32>,
+    coverage: f32,
+    density: f32,
+    coverage_scale: f32,
+    erosion_scale: f32,
+    erosion_strength: f32,
+    scattering_asymmetry: f32,
+    step_count: u32,
+    light_step_count: u32,
+    max_distance: f32,
+}
+
+@group(1) @binding(0) var<uniform> volumetric_clouds: VolumetricClouds;
+
+#ifdef MULTISAMPLED
+@group(1) @binding(1) var depth_texture: texture_depth_multisampled_2d;
+#else
+@group(1) @binding(1) var depth_texture: texture_depth_2d;
+#endif
+
+@group(1) @binding(2) var coverage_texture: texture_2d<f32>;
+@group(1) @binding(3) var erosion_texture: texture_3d<f32>;
+@group(1) @binding(4) var noise_sampler: sampler;
+
+// 1 / (4π)
+const FRAC_4_PI: f32 = 0.07957747154594767;
+
+// Below this transmittance, the rest of the clouds are considered invisible.
+const MIN_TRANSMITTANCE: f32 = 0.01;
+
+// See the comments in `volumetric_fog.wgsl`.
+fn henyey_greenstein(neg_LdotV: f32) -> f32 {
+    let g = volumetric_clouds.scattering_asymmetry;
+    let denom = 1.0 + g * g - 2.0 * g * neg_LdotV;
+    return FRAC_4_PI * (1.0 - g * g) / (denom * sqrt(denom));
+}
+
+// Returns the extinction coefficient of the clouds at the given world-space
+// position.
+fn cloud_density(P_world: vec3<f32>) -> f32 {
+    let bottom = volumetric_clouds.bottom_height;
+    let top = volumetric_clouds.top_height;
+    let height_fraction = (P_world.y - bottom) / max(top - bottom, 1e-4);
+    if (height_fraction < 0.0 || height_fraction > 1.0) {
+        return 0.0;
+    }
+
+    // Round off the bottoms and, more gently, the tops of the clouds.
+    let height_gradient = saturate(height_fraction * 4.0) * saturate((1.0 - height_fraction) * 2.0);
+
+    // Scroll the textures with the wind.
+    let P_wind = P_world - volumetric_clouds.wind * globals.time;
+
+    // Determine the base shape of the clouds from the coverage. With a
+    // coverage of 0, no texel is dense enough to become a cloud; with a
+    // coverage of 1, they all are.
+    let coverage_uv = P_wind.xz / volumetric_clouds.coverage_scale;
+    let coverage_noise = textureSampleLevel(coverage_texture, noise_sampler, coverage_uv, 0.0).r;
+    let coverage = volumetric_clouds.coverage;
+    let base_shape = saturate(
+        (coverage_noise * height_gradient - (1.0 - coverage)) / max(coverage, 1e-4));
+    if (base_shape <= 0.0) {
+        return 0.0;
+    }
+
+    // Carve the detail out of the base shape.
+    let erosion_uvw = P_wind / volumetric_clouds.erosion_scale;
+    let erosion_noise = textureSampleLevel(erosion_texture, noise_sampler, erosion_uvw, 0.0).r;
+    let erosion = erosion_noise * volumetric_clouds.erosion_strength;
+    let shape = saturate((base_shape - erosion) / max(1.0 - erosion, 1e-4));
+
+    return shape * volumetric_clouds.density;
+}
+
+// Returns the fraction of the light coming from the direction `L_world` that
+// reaches the given world-space position through the clouds.
+fn light_transmittance(P_world: vec3<f32>, L_world: vec3<f32>) -> f32 {
+    let light_step_count = volumetric_clouds.light_step_count;
+    if (light_step_count == 0u) {
+        return 1.0;
+    }
+
+    // March across half of the thickness of the cloud layer, or less if the
+    // light is low on the horizon, as the clouds are mostly shadowed by
+    // their immediate surroundings.
+    let thickness = volumetric_clouds.top_height - volumetric_clouds.bottom_height;
+    let step_size = 0.5 * thickness / (f32(light_step_count) * max(abs(L_world.y), 0.25));
+
+    var optical_depth = 0.0;
+    for (var step_index = 0u; step_index < light_step_count; step_index += 1u) {
+        let P_step = P_world + L_world * step_size * (f32(step_index) + 0.5);
+        optical_depth += cloud_density(P_step) * step_size;
+    }
+    return exp(-optical_depth);
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let frag_coord = in.position;
+
+    // Calculate the ray origin (`Ro`) and the ray direction (`Rd`) in world
+    // coordinates.
+    let Ro_world = view.world_position;
+    let Rd_world = normalize(
+        position_ndc_to_world(vec3(frag_coord_to_ndc(frag_coord).xy, 1.0)) - Ro_world);
+
+    // Sample the depth to put an upper bound on the length of the ray, as we
+    // shouldn't trace through solid objects. If this is multisample, just use
+    // sample 0; this is approximate but good enough.
+    let ndc_depth = textureLoad(depth_texture, vec2<i32>(frag_coord.xy), 0);
+    var max_distance = volumetric_clouds.max_distance;
+    if (ndc_depth > 0.0) {
+        let P_scene = position_ndc_to_world(
+            frag_coord_to_ndc(vec4(frag_coord.xy, ndc_depth, 1.0)));
+        max_distance = min(max_distance, distance(P_scene, Ro_world));
+    }
+
+    // Intersect the ray with the cloud slab.
+    let bottom = volumetric_clouds.bottom_height;
+    let top = volumetric_clouds.top_height;
+    var t_enter = 0.0;
+    var t_exit = max_distance;
+    if (abs(Rd_world.y) > 1e-6) {
+        let t_bottom = (bottom - Ro_world.y) / Rd_world.y;
+        let t_top = (top - Ro_world.y) / Rd_world.y;
+        t_enter = max(min(t_bottom, t_top), 0.0);
+        t_exit = min(max(t_bottom, t_top), max_distance);
+    } else if (Ro_world.y < bottom || Ro_world.y > top) {
+        // The ray is parallel to the slab and outside it.
+        return vec4(0.0);
+    }
+    if (t_exit <= t_enter) {
+        return vec4(0.0);
+    }
+
+    let step_count = volumetric_clouds.step_count;
+    let step_size = (t_exit - t_enter) / f32(step_count);
+
+    // Offset the start of the ray within a step, to trade banding for noise
+    // that temporal antialiasing can resolve.
+    let jitter = interleaved_gradient_noise(frag_coord.xy, globals.frame_count);
+
+    var accumulated_color = vec3(0.0);
+    var transmittance = 1.0;
+
+    for (var step_index = 0u; step_index < step_count; step_index += 1u) {
+        let t = t_enter + step_size * (f32(step_index) + jitter);
+        let P_world = Ro_world + Rd_world * t;
+
+        let extinction = cloud_density(P_world);
+        if (extinction <= 0.0) {
+            continue;
+        }
+
+        // Gather the light that reaches this point from all directional
+        // lights, and from the ambient light.
+        var light = volumetric_clouds.ambient_color;
+        for (var light_index = 0u; light_index < lights.n_directional_lights;
+                light_index += 1u) {
+            let directional_light = &lights.directional_lights[light_index];
+            let L_world = normalize((*directional_light).direction_to_light.xyz);
+            let phase = henyey_greenstein(dot(L_world, Rd_world));
+            light += (*directional_light).color.rgb * view.exposure * phase *
+                light_transmittance(P_world, L_world);
+        }
+
+        // Integrate the scattered light over the step. Clouds scatter almost
+        // all the light that they don't let through, so the scattering
+        // coefficient is the extinction coefficient modulated by their color.
+        let step_transmittance = exp(-extinction * step_size);
+        let scattered_light = volumetric_clouds.color * light * extinction;
+        accumulated_color += transmittance * (scattered_light - scattered_light * step_transmittance) /
+            extinction;
+        transmittance *= step_transmittance;
+
+        if (transmittance < MIN_TRANSMITTANCE) {
+            break;
+        }
+    }
+
+    // Return the color with alpha so it can be blended onto the render target.
+    return vec4(accumulated_color, 1.0 - transmittance);
+}
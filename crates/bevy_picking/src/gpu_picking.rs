@@ -0,0 +1,491 @@
+//! A GPU picking backend for [`bevy_picking`](crate), which reads the entities under the pointers
+//! back from an ID buffer.
+//!
+//! Cameras with a [`GpuPickingCamera`] write the entity drawn at each pixel to a
+//! texture in their prepass (see [`EntityIndexPrepass`]). Each frame, the pixels under the
+//! pointers are copied from that texture and from the depth texture to the CPU, and the entities
+//! found there are sent as [`PointerHits`].
+//!
+//! Since the entity indices are written by the same shaders that draw the meshes, picking is
+//! pixel-accurate for skinned and morphed meshes, and for meshes with alpha masks, where the CPU
+//! mesh data used by the mesh picking backend is wrong or unavailable.
+//!
+//! ## Limitations
+//!
+//! - Reading data back from the GPU takes a few frames, so hits lag slightly behind the pointers.
+//! - Only the closest entity under each pointer is hit. Entities that aren't
+//!   [hoverable](Pickable::is_hoverable) block the entities behind them without being hit.
+//! - Only meshes drawn in the prepass can be picked, so transparent meshes, and meshes with
+//!   materials that disable the prepass, can't be picked.
+//! - The cameras must have [`Msaa::Off`], as multisampled textures can't be copied.
+
+use crate::{
+    backend::{HitData, PointerHits},
+    pointer::{PointerId, PointerLocation},
+    PickSet, Pickable,
+};
+use bevy_app::prelude::*;
+use bevy_core_pipeline::{
+    core_3d::graph::{Core3d, Node3d},
+    prepass::{DepthPrepass, EntityIndexPrepass, ViewPrepassTextures},
+};
+use bevy_ecs::{entity::EntityHashMap, prelude::*, query::QueryItem};
+use bevy_math::{UVec2, Vec2};
+use bevy_reflect::prelude::*;
+use bevy_render::{
+    camera::Camera,
+    render_graph::{
+        NodeRunError, RenderGraphApp, RenderGraphContext, RenderLabel, ViewNode, ViewNodeRunner,
+    },
+    render_resource::{
+        Buffer, BufferDescriptor, BufferUsages, Extent3d, ImageCopyBuffer, ImageCopyTexture,
+        ImageDataLayout, MapMode, Origin3d, TextureAspect,
+    },
+    renderer::{render_system, RenderContext, RenderDevice},
+    sync_world::{MainEntity, RenderEntity},
+    view::Msaa,
+    Extract, ExtractSchedule, Render, RenderApp, RenderSet,
+};
+use bevy_transform::components::GlobalTransform;
+use bevy_utils::once;
+use bevy_window::PrimaryWindow;
+use crossbeam_channel::{Receiver, Sender};
+use tracing::warn;
+
+/// The distance between two copies in a readback buffer.
+///
+/// This is the strictest alignment of the offset of a texture copy in a buffer among the
+/// backends.
+const COPY_STRIDE: u64 = 512;
+
+/// Marks a camera whose pointers are picked with the [`GpuPickingPlugin`].
+///
+/// The camera must have [`Msaa::Off`].
+#[derive(Debug, Clone, Default, Component, Reflect)]
+#[reflect(Component, Default, Debug)]
+#[require(EntityIndexPrepass, DepthPrepass)]
+pub struct GpuPickingCamera;
+
+/// Adds the GPU picking backend to your app.
+#[derive(Clone, Default)]
+pub struct GpuPickingPlugin;
+
+impl Plugin for GpuPickingPlugin {
+    fn build(&self, app: &mut App) {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+
+        app.register_type::<GpuPickingCamera>()
+            .insert_resource(GpuPickingResults {
+                receiver,
+                latest: EntityHashMap::default(),
+            })
+            .add_systems(PreUpdate, update_hits.in_set(PickSet::Backend));
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app
+            .insert_resource(GpuPickingSender(sender))
+            .add_systems(ExtractSchedule, extract_picking_requests)
+            .add_systems(
+                Render,
+                (
+                    prepare_picking_buffers.in_set(RenderSet::PrepareResources),
+                    map_picking_buffers
+                        .after(render_system)
+                        .in_set(RenderSet::Render),
+                ),
+            )
+            .add_render_graph_node::<ViewNodeRunner<GpuPickingNode>>(Core3d, GpuPickingLabel)
+            .add_render_graph_edges(
+                Core3d,
+                (Node3d::EndPrepasses, GpuPickingLabel, Node3d::StartMainPass),
+            );
+    }
+}
+
+/// The render graph node that copies the pixels under the pointers to the readback buffer.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct GpuPickingLabel;
+
+/// The pixel of the entity index and depth textures under a pointer, read back from the GPU.
+#[derive(Debug, Clone, Copy)]
+struct GpuPickingPixel {
+    pointer: PointerId,
+    /// The position of the pixel in the render target, in physical pixels.
+    position: UVec2,
+    /// The entity drawn at the pixel, if any.
+    ///
+    /// It may have been despawned since, or its index reused by a new entity of another
+    /// generation, which is a different entity.
+    entity: Option<Entity>,
+    /// The depth of the pixel, in normalized device coordinates.
+    depth: f32,
+}
+
+/// The pixels read back for a camera in a frame.
+struct GpuPickingReadback {
+    camera: Entity,
+    /// The render frame the pixels were copied in, as readbacks may complete out of order.
+    frame: u64,
+    pixels: Vec<GpuPickingPixel>,
+}
+
+/// The latest pixels read back for each [`GpuPickingCamera`].
+#[derive(Resource)]
+struct GpuPickingResults {
+    receiver: Receiver<GpuPickingReadback>,
+    latest: EntityHashMap<(u64, Vec<GpuPickingPixel>)>,
+}
+
+/// Sends completed readbacks from the render world to the main world.
+#[derive(Resource)]
+struct GpuPickingSender(Sender<GpuPickingReadback>);
+
+/// The pixels to read back for a camera, in the render world.
+#[derive(Component)]
+struct GpuPickingRequests {
+    pointers: Vec<(PointerId, UVec2)>,
+}
+
+/// The buffer the pixels of a camera are copied to, in the render world.
+#[derive(Component)]
+struct GpuPickingBuffer(Buffer);
+
+impl GpuPickingResults {
+    /// Keeps the latest readback of each camera among the received ones.
+    fn receive(&mut self) {
+        for readback in self.receiver.try_iter() {
+            match self.latest.get(&readback.camera) {
+                Some((frame, _)) if *frame > readback.frame => {}
+                _ => {
+                    self.latest
+                        .insert(readback.camera, (readback.frame, readback.pixels));
+                }
+            }
+        }
+    }
+}
+
+/// Returns the entity written to a texel of the entity index texture, from the low and high 32
+/// bits of [`Entity::to_bits`], or `None` if no entity was drawn there.
+fn decode_entity(texel: [u32; 2]) -> Option<Entity> {
+    let [low, high] = texel;
+    // The high bits of an entity are never 0, as its generation isn't.
+    Entity::try_from_bits((u64::from(high) << 32) | u64::from(low)).ok()
+}
+
+/// Sends [`PointerHits`] for the entities under the pointers of each [`GpuPickingCamera`], as of
+/// the latest readback.
+fn update_hits(
+    mut results: ResMut<GpuPickingResults>,
+    cameras: Query<(Entity, &Camera, &GlobalTransform), With<GpuPickingCamera>>,
+    entities: &Entities,
+    pickables: Query<&Pickable>,
+    mut output: EventWriter<PointerHits>,
+) {
+    results.receive();
+    results.latest.retain(|camera, _| cameras.contains(*camera));
+
+    for (camera_entity, camera, camera_transform) in &cameras {
+        let (Some((_, pixels)), Some(scale)) = (
+            results.latest.get(&camera_entity),
+            camera.target_scaling_factor(),
+        ) else {
+            continue;
+        };
+        let Some(viewport) = camera.logical_viewport_rect() else {
+            continue;
+        };
+
+        for pixel in pixels {
+            let Some(entity) = pixel.entity.filter(|&entity| entities.contains(entity)) else {
+                continue;
+            };
+            if pickables
+                .get(entity)
+                .is_ok_and(|pickable| !pickable.is_hoverable)
+            {
+                continue;
+            }
+
+            // Unproject the center of the pixel at its depth.
+            let viewport_position = (pixel.position.as_vec2() + 0.5) / scale - viewport.min;
+            let ndc = (viewport_position / viewport.size() * 2. - 1.) * Vec2::new(1., -1.);
+            let (Some(position), Ok(ray)) = (
+                camera.ndc_to_world(camera_transform, ndc.extend(pixel.depth)),
+                camera.viewport_to_world(camera_transform, viewport_position),
+            ) else {
+                continue;
+            };
+            let depth = (position - ray.origin).dot(*ray.direction);
+
+            let hit = HitData::new(camera_entity, depth, Some(position), None);
+            output.send(PointerHits::new(
+                pixel.pointer,
+                vec![(entity, hit)],
+                camera.order as f32,
+            ));
+        }
+    }
+}
+
+/// Extracts the pixels under the pointers of each [`GpuPickingCamera`].
+fn extract_picking_requests(
+    mut commands: Commands,
+    cameras: Extract<Query<(RenderEntity, &Camera, &Msaa), With<GpuPickingCamera>>>,
+    pointers: Extract<Query<(&PointerId, &PointerLocation)>>,
+    primary_window: Extract<Query<Entity, With<PrimaryWindow>>>,
+) {
+    for (render_entity, camera, msaa) in cameras.iter() {
+        if *msaa != Msaa::Off {
+            once!(warn!(
+                "GPU picking cameras must have `Msaa::Off`: multisampled textures can't be read back."
+            ));
+            continue;
+        }
+        let (Some(scale), Some(size)) = (
+            camera.target_scaling_factor(),
+            camera.physical_target_size(),
+        ) else {
+            continue;
+        };
+
+        let pointers = pointers
+            .iter()
+            .filter_map(|(pointer, pointer_location)| {
+                let location = pointer_location.location()?;
+                if !camera.is_active || !location.is_in_viewport(camera, &primary_window) {
+                    return None;
+                }
+                let pixel = (location.position * scale).as_uvec2();
+                Some((*pointer, pixel.min(size.saturating_sub(UVec2::ONE))))
+            })
+            .collect();
+
+        commands
+            .entity(render_entity)
+            .insert(GpuPickingRequests { pointers });
+    }
+}
+
+/// Creates a buffer for the pixels of each camera with pointers to read back.
+fn prepare_picking_buffers(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    views: Query<(Entity, &GpuPickingRequests)>,
+) {
+    for (entity, requests) in &views {
+        if requests.pointers.is_empty() {
+            commands.entity(entity).remove::<GpuPickingBuffer>();
+            continue;
+        }
+
+        // Each pointer has a copy of its entity texel, followed by a copy of its depth.
+        let buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("gpu_picking_readback_buffer"),
+            size: requests.pointers.len() as u64 * 2 * COPY_STRIDE,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        commands.entity(entity).insert(GpuPickingBuffer(buffer));
+    }
+}
+
+/// Copies the pixels under the pointers from the prepass textures to the readback buffer.
+#[derive(Default)]
+struct GpuPickingNode;
+
+impl ViewNode for GpuPickingNode {
+    type ViewQuery = (
+        &'static ViewPrepassTextures,
+        &'static GpuPickingRequests,
+        &'static GpuPickingBuffer,
+    );
+
+    fn run<'w>(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext<'w>,
+        (prepass_textures, requests, buffer): QueryItem<'w, Self::ViewQuery>,
+        _world: &'w World,
+    ) -> Result<(), NodeRunError> {
+        let (Some(entity_index_texture), Some(depth_texture)) =
+            (&prepass_textures.entity_index, &prepass_textures.depth)
+        else {
+            return Ok(());
+        };
+
+        let command_encoder = render_context.command_encoder();
+        for (i, &(_, pixel)) in requests.pointers.iter().enumerate() {
+            let offset = i as u64 * 2 * COPY_STRIDE;
+            for (texture, aspect, offset) in [
+                (entity_index_texture, TextureAspect::All, offset),
+                (
+                    depth_texture,
+                    TextureAspect::DepthOnly,
+                    offset + COPY_STRIDE,
+                ),
+            ] {
+                command_encoder.copy_texture_to_buffer(
+                    ImageCopyTexture {
+                        texture: &texture.texture.texture,
+                        mip_level: 0,
+                        origin: Origin3d {
+                            x: pixel.x,
+                            y: pixel.y,
+                            z: 0,
+                        },
+                        aspect,
+                    },
+                    ImageCopyBuffer {
+                        buffer: &buffer.0,
+                        layout: ImageDataLayout {
+                            offset,
+                            bytes_per_row: None,
+                            rows_per_image: None,
+                        },
+                    },
+                    Extent3d {
+                        width: 1,
+                        height: 1,
+                        depth_or_array_layers: 1,
+                    },
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Maps the readback buffers after the commands copying to them have been submitted, and sends
+/// their pixels to the main world once they are mapped.
+fn map_picking_buffers(
+    sender: Res<GpuPickingSender>,
+    views: Query<(&MainEntity, &GpuPickingRequests, Option<&GpuPickingBuffer>)>,
+    mut frame: Local<u64>,
+) {
+    *frame += 1;
+    for (main_entity, requests, buffer) in &views {
+        let camera = main_entity.id();
+        let frame = *frame;
+
+        let Some(GpuPickingBuffer(buffer)) = buffer else {
+            // There are no pointers over the camera, so it doesn't hit anything anymore.
+            let _ = sender.0.send(GpuPickingReadback {
+                camera,
+                frame,
+                pixels: Vec::new(),
+            });
+            continue;
+        };
+
+        let buffer = buffer.clone();
+        let pointers = requests.pointers.clone();
+        let sender = sender.0.clone();
+        buffer
+            .clone()
+            .slice(..)
+            .map_async(MapMode::Read, move |result| {
+                if let Err(error) = result {
+                    warn!("Failed to map GPU picking buffer: {error}");
+                    return;
+                }
+                let data = buffer.slice(..).get_mapped_range();
+                let read = |offset: u64| {
+                    let offset = offset as usize;
+                    u32::from_le_bytes([
+                        data[offset],
+                        data[offset + 1],
+                        data[offset + 2],
+                        data[offset + 3],
+                    ])
+                };
+                let pixels = pointers
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &(pointer, position))| {
+                        let offset = i as u64 * 2 * COPY_STRIDE;
+                        GpuPickingPixel {
+                            pointer,
+                            position,
+                            entity: decode_entity([read(offset), read(offset + 4)]),
+                            depth: f32::from_bits(read(offset + COPY_STRIDE)),
+                        }
+                    })
+                    .collect();
+                drop(data);
+                buffer.unmap();
+                let _ = sender.send(GpuPickingReadback {
+                    camera,
+                    frame,
+                    pixels,
+                });
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_entity(entity: Entity) -> [u32; 2] {
+        let bits = entity.to_bits();
+        [bits as u32, (bits >> 32) as u32]
+    }
+
+    #[test]
+    fn entities_are_decoded_with_their_generation() {
+        let mut world = World::new();
+        let despawned = world.spawn_empty().id();
+        world.despawn(despawned);
+        let entity = world.spawn_empty().id();
+        // The index of the despawned entity is reused by the new entity.
+        assert_eq!(entity.index(), despawned.index());
+
+        assert_eq!(decode_entity(encode_entity(entity)), Some(entity));
+        assert_eq!(decode_entity(encode_entity(despawned)), Some(despawned));
+        assert!(!world.entities().contains(despawned));
+
+        // The texture is cleared to 0 where no entity was drawn.
+        assert_eq!(decode_entity([0, 0]), None);
+        assert_eq!(decode_entity([entity.index(), 0]), None);
+    }
+
+    #[test]
+    fn results_keep_the_latest_readback() {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let mut results = GpuPickingResults {
+            receiver,
+            latest: EntityHashMap::default(),
+        };
+        let camera = Entity::from_raw(1);
+        let pixel = GpuPickingPixel {
+            pointer: PointerId::Mouse,
+            position: UVec2::ZERO,
+            entity: Some(Entity::from_raw(2)),
+            depth: 0.5,
+        };
+        let readback = |frame, pixels| GpuPickingReadback {
+            camera,
+            frame,
+            pixels,
+        };
+
+        // Readbacks can complete out of order.
+        sender.send(readback(2, vec![pixel])).unwrap();
+        sender.send(readback(1, Vec::new())).unwrap();
+        results.receive();
+        let (frame, pixels) = &results.latest[&camera];
+        assert_eq!(*frame, 2);
+        assert_eq!(pixels.len(), 1);
+
+        sender.send(readback(3, Vec::new())).unwrap();
+        results.receive();
+        let (frame, pixels) = &results.latest[&camera];
+        assert_eq!(*frame, 3);
+        assert!(pixels.is_empty());
+    }
+}
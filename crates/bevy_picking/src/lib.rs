@@ -160,6 +160,8 @@ extern crate alloc;
 
 pub mod backend;
 pub mod events;
+#[cfg(feature = "bevy_gpu_picking_backend")]
+pub mod gpu_picking;
 pub mod hover;
 pub mod input;
 #[cfg(feature = "bevy_mesh_picking_backend")]
@@ -175,10 +177,16 @@ use bevy_reflect::prelude::*;
 ///
 /// This includes the most common types in this crate, re-exported for your convenience.
 pub mod prelude {
+    #[cfg(feature = "bevy_gpu_picking_backend")]
+    #[doc(hidden)]
+    pub use crate::gpu_picking::{GpuPickingCamera, GpuPickingPlugin};
     #[cfg(feature = "bevy_mesh_picking_backend")]
     #[doc(hidden)]
     pub use crate::mesh_picking::{
-        ray_cast::{MeshRayCast, MeshRayCastSettings, RayCastBackfaces, RayCastVisibility},
+        ray_cast::{
+            MeshRayCast, MeshRayCastSettings, RayCastBackfaces, RayCastDeformation,
+            RayCastVisibility,
+        },
         MeshPickingPlugin, MeshPickingSettings, RayCastPickable,
     };
     #[doc(hidden)]
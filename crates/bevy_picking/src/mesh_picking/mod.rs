@@ -19,7 +19,9 @@ use bevy_app::prelude::*;
 use bevy_ecs::prelude::*;
 use bevy_reflect::prelude::*;
 use bevy_render::{prelude::*, view::RenderLayers};
-use ray_cast::{MeshRayCast, MeshRayCastSettings, RayCastVisibility, SimplifiedMesh};
+use ray_cast::{
+    MeshRayCast, MeshRayCastSettings, RayCastDeformation, RayCastVisibility, SimplifiedMesh,
+};
 
 /// Runtime settings for the [`MeshPickingPlugin`].
 #[derive(Resource, Reflect)]
@@ -38,6 +40,13 @@ pub struct MeshPickingSettings {
     /// Defaults to [`RayCastVisibility::VisibleInView`], only performing picking against visible entities
     /// that are in the view of a camera.
     pub ray_cast_visibility: RayCastVisibility,
+
+    /// Determines how mesh picking should consider skinned and morphed meshes. When set to
+    /// [`RayCastDeformation::Deformed`], animated characters can be picked in the pose they're
+    /// rendered in, at the cost of deforming their vertices on the CPU.
+    ///
+    /// Defaults to [`RayCastDeformation::BindPose`].
+    pub ray_cast_deformation: RayCastDeformation,
 }
 
 impl Default for MeshPickingSettings {
@@ -45,6 +54,7 @@ impl Default for MeshPickingSettings {
         Self {
             require_markers: false,
             ray_cast_visibility: RayCastVisibility::VisibleInView,
+            ray_cast_deformation: RayCastDeformation::BindPose,
         }
     }
 }
@@ -107,6 +117,7 @@ pub fn update_hits(
                     .get(entity_hit)
                     .is_ok_and(|pickable| pickable.should_block_lower)
             },
+            deformation: backend_settings.ray_cast_deformation,
         };
         let picks = ray_cast
             .cast_ray(ray, &settings)
@@ -0,0 +1,327 @@
+//! Deformation of skinned and morphed meshes on the CPU, so that rays can be cast against the
+//! vertices in the pose they're rendered in.
+
+use bevy_asset::Assets;
+use bevy_ecs::system::Query;
+use bevy_image::Image;
+use bevy_math::{
+    bounding::{Aabb3d, BoundingVolume},
+    Mat4, Vec3, Vec3A,
+};
+use bevy_render::mesh::{
+    morph::MorphAttributes,
+    skinning::{SkinnedMesh, SkinnedMeshInverseBindposes},
+    Mesh, VertexAttributeValues,
+};
+use bevy_transform::components::GlobalTransform;
+
+/// Computes the joint matrices of `skinned_mesh`, which move its vertices from their bind pose
+/// to their posed position in world space, the same way they're computed for rendering.
+///
+/// Returns `false` if the inverse bindposes aren't loaded, or if a joint doesn't have a
+/// [`GlobalTransform`].
+pub(super) fn compute_joint_matrices(
+    skinned_mesh: &SkinnedMesh,
+    inverse_bindposes: &Assets<SkinnedMeshInverseBindposes>,
+    joints: &Query<&GlobalTransform>,
+    output: &mut Vec<Mat4>,
+) -> bool {
+    output.clear();
+    let Some(inverse_bindposes) = inverse_bindposes.get(&skinned_mesh.inverse_bindposes) else {
+        return false;
+    };
+    for (joint, inverse_bindpose) in skinned_mesh.joints.iter().zip(inverse_bindposes.iter()) {
+        let Ok(joint) = joints.get(*joint) else {
+            return false;
+        };
+        output.push(joint.affine() * *inverse_bindpose);
+    }
+    true
+}
+
+/// Returns the bounds in world space of the vertices of a skinned mesh, given their bounds in
+/// their bind pose in the space of the mesh, and the joint matrices of the mesh.
+///
+/// Each skinned vertex is a blend of its position moved by each of its joints, so the union of
+/// the bounds moved by each joint encloses it, as for
+/// [`SkinnedMeshBounds`](bevy_render::mesh::SkinnedMeshBounds).
+pub(super) fn skinned_aabb(bind_pose: &Aabb3d, joint_matrices: &[Mat4]) -> Option<Aabb3d> {
+    joint_matrices
+        .iter()
+        .map(|joint_matrix| transform_aabb(bind_pose, joint_matrix))
+        .reduce(|a, b| a.merge(&b))
+}
+
+/// Returns the bounds of `aabb` moved by `transform`.
+pub(super) fn transform_aabb(aabb: &Aabb3d, transform: &Mat4) -> Aabb3d {
+    let center = transform.transform_point3a(aabb.center());
+    let half_size = aabb.half_size();
+    let half_size = Vec3A::from(transform.x_axis.truncate().abs()) * half_size.x
+        + Vec3A::from(transform.y_axis.truncate().abs()) * half_size.y
+        + Vec3A::from(transform.z_axis.truncate().abs()) * half_size.z;
+    Aabb3d::new(center, half_size)
+}
+
+/// Returns the largest distance that the morph targets of a mesh with `vertex_count` vertices
+/// move any of its vertices with the given weights.
+pub(super) fn morph_displacement_bound(
+    targets: &Image,
+    weights: &[f32],
+    vertex_count: usize,
+) -> f32 {
+    let size = targets.texture_descriptor.size;
+    let target_len = (size.width * size.height) as usize;
+    weights
+        .iter()
+        .enumerate()
+        .filter(|(_, weight)| **weight != 0.0)
+        .map(|(target, weight)| {
+            let max_displacement = (0..vertex_count)
+                .filter_map(|vertex| {
+                    let attributes =
+                        target * target_len + vertex * MorphAttributes::COMPONENT_COUNT;
+                    read_vec3(&targets.data, attributes)
+                })
+                .map(Vec3::length)
+                .fold(0.0, f32::max);
+            weight.abs() * max_displacement
+        })
+        .sum()
+}
+
+/// Writes the positions and normals of `mesh` deformed by its morph targets and its skin to
+/// `positions` and `normals`.
+///
+/// The morph targets are applied first, with the given weights, and then the vertices are
+/// skinned with the given joint matrices, which moves them into world space. Without joint
+/// matrices, the vertices stay in the space of the mesh.
+///
+/// Returns `false` if the mesh doesn't have positions, or if it's skinned and doesn't have joint
+/// indices and weights. `normals` is left empty if the mesh doesn't have normals.
+pub(super) fn deform_mesh(
+    mesh: &Mesh,
+    morph: Option<(&Image, &[f32])>,
+    joint_matrices: Option<&[Mat4]>,
+    positions: &mut Vec<[f32; 3]>,
+    normals: &mut Vec<[f32; 3]>,
+) -> bool {
+    let Some(mesh_positions) = mesh
+        .attribute(Mesh::ATTRIBUTE_POSITION)
+        .and_then(VertexAttributeValues::as_float3)
+    else {
+        return false;
+    };
+    positions.clear();
+    positions.extend_from_slice(mesh_positions);
+    normals.clear();
+    if let Some(mesh_normals) = mesh
+        .attribute(Mesh::ATTRIBUTE_NORMAL)
+        .and_then(VertexAttributeValues::as_float3)
+    {
+        normals.extend_from_slice(mesh_normals);
+    }
+
+    if let Some((targets, weights)) = morph {
+        apply_morph_targets(targets, weights, positions, normals);
+    }
+    match joint_matrices {
+        Some(joint_matrices) => apply_skin(mesh, joint_matrices, positions, normals),
+        None => true,
+    }
+}
+
+/// Adds the position and normal displacements of each morph target, scaled by its weight.
+///
+/// `targets` is the image returned by [`Mesh::morph_targets`], which is only readable here if
+/// it's kept in the main world.
+fn apply_morph_targets(
+    targets: &Image,
+    weights: &[f32],
+    positions: &mut [[f32; 3]],
+    normals: &mut [[f32; 3]],
+) {
+    let size = targets.texture_descriptor.size;
+    let target_len = (size.width * size.height) as usize;
+    for (target, &weight) in weights.iter().enumerate() {
+        if weight == 0.0 {
+            continue;
+        }
+        for (vertex, position) in positions.iter_mut().enumerate() {
+            let attributes = target * target_len + vertex * MorphAttributes::COMPONENT_COUNT;
+            if let Some(displacement) = read_vec3(&targets.data, attributes) {
+                *position = (Vec3::from(*position) + displacement * weight).into();
+            }
+            if let Some(normal) = normals.get_mut(vertex) {
+                if let Some(displacement) = read_vec3(&targets.data, attributes + 3) {
+                    *normal = (Vec3::from(*normal) + displacement * weight).into();
+                }
+            }
+        }
+    }
+}
+
+/// Reads three consecutive `f32` components starting at component `index` of `data`.
+fn read_vec3(data: &[u8], index: usize) -> Option<Vec3> {
+    let bytes = data.get(index * 4..(index + 3) * 4)?;
+    let mut components = bytes
+        .chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]));
+    Some(Vec3::new(
+        components.next()?,
+        components.next()?,
+        components.next()?,
+    ))
+}
+
+/// Moves each vertex by the blend of its joint matrices, weighted by its joint weights.
+///
+/// This is linear blend skinning. Meshes rendered with dual quaternion skinning are
+/// approximated by it, which only differs noticeably around joints that twist a lot.
+fn apply_skin(
+    mesh: &Mesh,
+    joint_matrices: &[Mat4],
+    positions: &mut [[f32; 3]],
+    normals: &mut [[f32; 3]],
+) -> bool {
+    let (
+        Some(VertexAttributeValues::Uint16x4(joint_indices)),
+        Some(VertexAttributeValues::Float32x4(joint_weights)),
+    ) = (
+        mesh.attribute(Mesh::ATTRIBUTE_JOINT_INDEX),
+        mesh.attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT),
+    )
+    else {
+        return false;
+    };
+    for (vertex, (indices, weights)) in joint_indices.iter().zip(joint_weights).enumerate() {
+        let mut model = Mat4::ZERO;
+        for (&index, &weight) in indices.iter().zip(weights) {
+            if let Some(joint_matrix) = joint_matrices.get(index as usize) {
+                model += *joint_matrix * weight;
+            }
+        }
+        if let Some(position) = positions.get_mut(vertex) {
+            *position = model.transform_point3(Vec3::from(*position)).into();
+        }
+        if let Some(normal) = normals.get_mut(vertex) {
+            *normal = model
+                .transform_vector3(Vec3::from(*normal))
+                .normalize_or_zero()
+                .into();
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_asset::RenderAssetUsages;
+    use bevy_render::render_resource::{
+        Extent3d, PrimitiveTopology, TextureDimension, TextureFormat,
+    };
+
+    use super::*;
+
+    fn triangle() -> Mesh {
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(
+            Mesh::ATTRIBUTE_POSITION,
+            vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]],
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0., 0., 1.]; 3])
+    }
+
+    /// A morph target image with one target, that moves the first vertex of three by
+    /// `displacement`.
+    fn morph_targets(displacement: Vec3) -> Image {
+        let mut components = vec![0.0f32; 3 * MorphAttributes::COMPONENT_COUNT];
+        components[..3].copy_from_slice(&displacement.to_array());
+        Image::new(
+            Extent3d {
+                width: components.len() as u32,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D3,
+            components.iter().flat_map(|c| c.to_le_bytes()).collect(),
+            TextureFormat::R32Float,
+            RenderAssetUsages::all(),
+        )
+    }
+
+    #[test]
+    fn morph_targets_move_vertices_by_their_weight() {
+        let targets = morph_targets(Vec3::Z);
+        let (mut positions, mut normals) = (Vec::new(), Vec::new());
+        assert!(deform_mesh(
+            &triangle(),
+            Some((&targets, &[0.5])),
+            None,
+            &mut positions,
+            &mut normals,
+        ));
+        assert_eq!(positions, vec![[0., 0., 0.5], [1., 0., 0.], [0., 1., 0.]]);
+        assert_eq!(normals, vec![[0., 0., 1.]; 3]);
+
+        assert_eq!(morph_displacement_bound(&targets, &[0.5], 3), 0.5);
+        assert_eq!(morph_displacement_bound(&targets, &[-2.], 3), 2.);
+        assert_eq!(morph_displacement_bound(&targets, &[0.], 3), 0.);
+    }
+
+    #[test]
+    fn skinning_blends_joint_matrices() {
+        let mesh = triangle()
+            .with_inserted_attribute(
+                Mesh::ATTRIBUTE_JOINT_INDEX,
+                VertexAttributeValues::Uint16x4(vec![[0, 1, 0, 0]; 3]),
+            )
+            .with_inserted_attribute(
+                Mesh::ATTRIBUTE_JOINT_WEIGHT,
+                vec![[1., 0., 0., 0.], [0.5, 0.5, 0., 0.], [0., 1., 0., 0.]],
+            );
+        let joint_matrices = [
+            Mat4::from_translation(Vec3::Y),
+            Mat4::from_translation(Vec3::NEG_Y),
+        ];
+        let (mut positions, mut normals) = (Vec::new(), Vec::new());
+        assert!(deform_mesh(
+            &mesh,
+            None,
+            Some(&joint_matrices),
+            &mut positions,
+            &mut normals,
+        ));
+        assert_eq!(positions, vec![[0., 1., 0.], [1., 0., 0.], [0., 0., 0.]]);
+
+        // Skinned meshes need joint indices and weights.
+        assert!(!deform_mesh(
+            &triangle(),
+            None,
+            Some(&joint_matrices),
+            &mut positions,
+            &mut normals,
+        ));
+    }
+
+    #[test]
+    fn skinned_aabbs_enclose_every_joint() {
+        let bind_pose = Aabb3d::new(Vec3::ZERO, Vec3::ONE);
+        let joint_matrices = [
+            Mat4::from_translation(Vec3::X * 2.),
+            Mat4::from_scale(Vec3::splat(2.)),
+        ];
+        let aabb = skinned_aabb(&bind_pose, &joint_matrices).unwrap();
+        assert_eq!(aabb.min, Vec3A::new(-2., -2., -2.));
+        assert_eq!(aabb.max, Vec3A::new(3., 2., 2.));
+        assert!(skinned_aabb(&bind_pose, &[]).is_none());
+
+        let rotated = transform_aabb(
+            &Aabb3d::new(Vec3::ZERO, Vec3::new(2., 1., 1.)),
+            &Mat4::from_rotation_z(core::f32::consts::FRAC_PI_2),
+        );
+        assert!((rotated.half_size() - Vec3A::new(1., 2., 1.)).length() < 1e-5);
+    }
+}
@@ -35,9 +35,6 @@ pub(super) fn ray_intersection_over_mesh(
     ray: Ray3d,
     culling: Backfaces,
 ) -> Option<RayMeshHit> {
-    if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
-        return None; // ray_mesh_intersection assumes vertices are laid out in a triangle list
-    }
     // Vertex positions are required
     let positions = mesh.attribute(Mesh::ATTRIBUTE_POSITION)?.as_float3()?;
 
@@ -46,6 +43,23 @@ pub(super) fn ray_intersection_over_mesh(
         .attribute(Mesh::ATTRIBUTE_NORMAL)
         .and_then(|normal_values| normal_values.as_float3());
 
+    ray_intersection_over_vertices(mesh, positions, normals, transform, ray, culling)
+}
+
+/// Casts a ray on the triangles of a mesh with the given vertex positions and normals instead of
+/// its own, and returns the intersection.
+pub(super) fn ray_intersection_over_vertices(
+    mesh: &Mesh,
+    positions: &[[f32; 3]],
+    normals: Option<&[[f32; 3]]>,
+    transform: &Mat4,
+    ray: Ray3d,
+    culling: Backfaces,
+) -> Option<RayMeshHit> {
+    if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+        return None; // ray_mesh_intersection assumes vertices are laid out in a triangle list
+    }
+
     match mesh.indices() {
         Some(Indices::U16(indices)) => {
             ray_mesh_intersection(ray, transform, positions, normals, Some(indices), culling)
@@ -2,19 +2,26 @@
 //!
 //! See the [`MeshRayCast`] system parameter for more information.
 
+mod deformation;
 mod intersections;
 
 use bevy_derive::{Deref, DerefMut};
 
-use bevy_math::{bounding::Aabb3d, Ray3d};
+use bevy_math::{bounding::Aabb3d, Mat4, Ray3d, Vec3A};
 use bevy_reflect::{std_traits::ReflectDefault, Reflect};
-use bevy_render::mesh::Mesh;
+use bevy_render::mesh::{
+    morph::MeshMorphWeights,
+    skinning::{SkinnedMesh, SkinnedMeshInverseBindposes},
+    Mesh, MeshAabb, SkinnedMeshBounds,
+};
 
+use deformation::*;
 use intersections::*;
 pub use intersections::{ray_aabb_intersection_3d, ray_mesh_intersection, RayMeshHit};
 
 use bevy_asset::{Assets, Handle};
 use bevy_ecs::{prelude::*, system::lifetimeless::Read, system::SystemParam};
+use bevy_image::Image;
 use bevy_math::FloatOrd;
 use bevy_render::{prelude::*, primitives::Aabb};
 use bevy_transform::components::GlobalTransform;
@@ -32,6 +39,33 @@ pub enum RayCastVisibility {
     VisibleInView,
 }
 
+/// How a ray cast should handle meshes that are deformed by a [`SkinnedMesh`] or by
+/// [morph targets](MeshMorphWeights).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect(Default, Debug, PartialEq)]
+pub enum RayCastDeformation {
+    /// Cast rays against the vertices of meshes in their bind pose, ignoring skinning and morph
+    /// targets.
+    ///
+    /// This is the fastest option, but rays can miss animated meshes, or hit them where they
+    /// aren't drawn.
+    #[default]
+    BindPose,
+    /// Deform the vertices of skinned and morphed meshes on the CPU before casting rays against
+    /// them, so that the rays hit them in the pose they're rendered in.
+    ///
+    /// Skinned meshes are deformed with the same joint matrices as for rendering. Meshes that use
+    /// [`SkinningMethod::DualQuaternion`](bevy_render::mesh::skinning::SkinningMethod) are
+    /// approximated with linear blend skinning. Morph targets are only applied if the image of the
+    /// targets is still in the main world, which requires its
+    /// [`RenderAssetUsages`](bevy_asset::RenderAssetUsages) to include `MAIN_WORLD`.
+    ///
+    /// Every vertex of each deformed mesh along the ray is deformed for each ray cast, so consider
+    /// using a [`SimplifiedMesh`] for detailed characters, or only enabling this for ray casts
+    /// that need it.
+    Deformed,
+}
+
 /// Settings for a ray cast.
 #[derive(Clone)]
 pub struct MeshRayCastSettings<'a> {
@@ -43,6 +77,8 @@ pub struct MeshRayCastSettings<'a> {
     /// A function that is run every time a hit is found. Ray casting will continue to check for hits
     /// along the ray as long as this returns `false`.
     pub early_exit_test: &'a dyn Fn(Entity) -> bool,
+    /// Determines how ray casting should consider skinned and morphed meshes.
+    pub deformation: RayCastDeformation,
 }
 
 impl<'a> MeshRayCastSettings<'a> {
@@ -64,6 +100,12 @@ impl<'a> MeshRayCastSettings<'a> {
         self
     }
 
+    /// Set the [`RayCastDeformation`] setting to apply to the ray cast.
+    pub fn with_deformation(mut self, deformation: RayCastDeformation) -> Self {
+        self.deformation = deformation;
+        self
+    }
+
     /// This ray cast should exit as soon as the nearest hit is found.
     pub fn always_early_exit(self) -> Self {
         self.with_early_exit_test(&|_| true)
@@ -81,6 +123,7 @@ impl<'a> Default for MeshRayCastSettings<'a> {
             visibility: RayCastVisibility::VisibleInView,
             filter: &|_| true,
             early_exit_test: &|_| true,
+            deformation: RayCastDeformation::BindPose,
         }
     }
 }
@@ -185,6 +228,9 @@ pub struct MeshRayCast<'w, 's> {
             Read<Aabb>,
             Read<GlobalTransform>,
             Entity,
+            Has<SkinnedMesh>,
+            Has<SkinnedMeshBounds>,
+            Has<MeshMorphWeights>,
         ),
         MeshFilter,
     >,
@@ -198,9 +244,23 @@ pub struct MeshRayCast<'w, 's> {
             Option<Read<SimplifiedMesh>>,
             Has<RayCastBackfaces>,
             Read<GlobalTransform>,
+            Option<Read<SkinnedMesh>>,
+            Option<Read<MeshMorphWeights>>,
         ),
         MeshFilter,
     >,
+    #[doc(hidden)]
+    pub joint_query: Query<'w, 's, Read<GlobalTransform>>,
+    #[doc(hidden)]
+    pub inverse_bindposes: Option<Res<'w, Assets<SkinnedMeshInverseBindposes>>>,
+    #[doc(hidden)]
+    pub images: Option<Res<'w, Assets<Image>>>,
+    #[doc(hidden)]
+    pub joint_matrices: Local<'s, Vec<Mat4>>,
+    #[doc(hidden)]
+    pub deformed_positions: Local<'s, Vec<[f32; 3]>>,
+    #[doc(hidden)]
+    pub deformed_normals: Local<'s, Vec<[f32; 3]>>,
 }
 
 impl<'w, 's> MeshRayCast<'w, 's> {
@@ -221,21 +281,44 @@ impl<'w, 's> MeshRayCast<'w, 's> {
         // of entities that are in the path of the ray.
         let (aabb_hits_tx, aabb_hits_rx) = crossbeam_channel::unbounded::<(FloatOrd, Entity)>();
         let visibility_setting = settings.visibility;
+        let deform = settings.deformation == RayCastDeformation::Deformed;
         self.culling_query.par_iter().for_each(
-            |(inherited_visibility, view_visibility, aabb, transform, entity)| {
+            |(
+                inherited_visibility,
+                view_visibility,
+                aabb,
+                transform,
+                entity,
+                is_skinned,
+                has_skinned_bounds,
+                is_morphed,
+            )| {
                 let should_ray_cast = match visibility_setting {
                     RayCastVisibility::Any => true,
                     RayCastVisibility::Visible => inherited_visibility.get(),
                     RayCastVisibility::VisibleInView => view_visibility.get(),
                 };
-                if should_ray_cast {
-                    if let Some(distance) = ray_aabb_intersection_3d(
+                if !should_ray_cast {
+                    return;
+                }
+                // The AABB of a deformed mesh only encloses its vertices in their bind pose,
+                // unless it's a skinned mesh whose AABB follows its joints.
+                let aabb_is_deformed = (is_skinned && !has_skinned_bounds) || is_morphed;
+                let distance = match (deform && aabb_is_deformed)
+                    .then(|| self.deformed_aabb(entity, aabb, has_skinned_bounds, transform))
+                    .flatten()
+                {
+                    Some(deformed_aabb) => {
+                        ray_aabb_intersection_3d(ray, &deformed_aabb, &Mat4::IDENTITY)
+                    }
+                    None => ray_aabb_intersection_3d(
                         ray,
                         &Aabb3d::new(aabb.center, aabb.half_extents),
                         &transform.compute_matrix(),
-                    ) {
-                        aabb_hits_tx.send((FloatOrd(distance), entity)).ok();
-                    }
+                    ),
+                };
+                if let Some(distance) = distance {
+                    aabb_hits_tx.send((FloatOrd(distance), entity)).ok();
                 }
             },
         );
@@ -254,8 +337,15 @@ impl<'w, 's> MeshRayCast<'w, 's> {
             .filter(|(_, entity)| (settings.filter)(*entity))
             .for_each(|(aabb_near, entity)| {
                 // Get the mesh components and transform.
-                let Ok((mesh2d, mesh3d, simplified_mesh, has_backfaces, transform)) =
-                    self.mesh_query.get(*entity)
+                let Ok((
+                    mesh2d,
+                    mesh3d,
+                    simplified_mesh,
+                    has_backfaces,
+                    transform,
+                    skinned_mesh,
+                    morph_weights,
+                )) = self.mesh_query.get(*entity)
                 else {
                     return;
                 };
@@ -287,7 +377,54 @@ impl<'w, 's> MeshRayCast<'w, 's> {
                 // Perform the actual ray cast.
                 let _ray_cast_guard = ray_cast_guard.enter();
                 let transform = transform.compute_matrix();
-                let intersection = ray_intersection_over_mesh(mesh, &transform, ray, backfaces);
+                let intersection = if deform && (skinned_mesh.is_some() || morph_weights.is_some())
+                {
+                    // Skinned vertices are moved into world space by their joints, so they ignore
+                    // the transform of the mesh.
+                    let joint_matrices = skinned_mesh
+                        .zip(self.inverse_bindposes.as_deref())
+                        .filter(|(skinned_mesh, inverse_bindposes)| {
+                            compute_joint_matrices(
+                                skinned_mesh,
+                                inverse_bindposes,
+                                &self.joint_query,
+                                &mut self.joint_matrices,
+                            )
+                        })
+                        .map(|_| self.joint_matrices.as_slice());
+                    let morph = morph_weights
+                        .zip(mesh.morph_targets())
+                        .zip(self.images.as_deref())
+                        .and_then(|((weights, targets), images)| {
+                            Some((images.get(targets)?, weights.weights()))
+                        });
+                    let vertex_transform = match joint_matrices {
+                        Some(_) => Mat4::IDENTITY,
+                        None => transform,
+                    };
+                    if deform_mesh(
+                        mesh,
+                        morph,
+                        joint_matrices,
+                        &mut self.deformed_positions,
+                        &mut self.deformed_normals,
+                    ) {
+                        let normals = (!self.deformed_normals.is_empty())
+                            .then_some(self.deformed_normals.as_slice());
+                        ray_intersection_over_vertices(
+                            mesh,
+                            &self.deformed_positions,
+                            normals,
+                            &vertex_transform,
+                            ray,
+                            backfaces,
+                        )
+                    } else {
+                        ray_intersection_over_mesh(mesh, &transform, ray, backfaces)
+                    }
+                } else {
+                    ray_intersection_over_mesh(mesh, &transform, ray, backfaces)
+                };
 
                 if let Some(intersection) = intersection {
                     let distance = FloatOrd(intersection.distance);
@@ -309,4 +446,62 @@ impl<'w, 's> MeshRayCast<'w, 's> {
         self.output.extend(hits);
         self.output.as_ref()
     }
+
+    /// Returns the bounds in world space of a skinned or morphed mesh in the pose it's rendered
+    /// in, or `None` if its [`Aabb`] can be used as is, or if its mesh isn't loaded.
+    ///
+    /// These bounds are computed from the bind pose with the same joint matrices and morph weights
+    /// as the vertices that the ray is cast against, so culling never skips a deformed mesh that
+    /// the ray would hit.
+    fn deformed_aabb(
+        &self,
+        entity: Entity,
+        aabb: &Aabb,
+        has_skinned_bounds: bool,
+        transform: &GlobalTransform,
+    ) -> Option<Aabb3d> {
+        let (mesh2d, mesh3d, simplified_mesh, _, _, skinned_mesh, morph_weights) =
+            self.mesh_query.get(entity).ok()?;
+        let mesh_handle = simplified_mesh
+            .map(|m| &m.0)
+            .or(mesh3d.map(|m| &m.0).or(mesh2d.map(|m| &m.0)))?;
+        let mesh = self.meshes.get(mesh_handle)?;
+        let morph = morph_weights
+            .zip(mesh.morph_targets())
+            .zip(self.images.as_deref())
+            .and_then(|((weights, targets), images)| {
+                Some((images.get(targets)?, weights.weights()))
+            });
+
+        // The bounds that follow the joints don't include the morph targets.
+        let mut bind_pose = match (has_skinned_bounds, morph) {
+            (true, None) => return None,
+            (true, Some(_)) => mesh.compute_aabb()?,
+            (false, _) => *aabb,
+        };
+        if let Some((targets, weights)) = morph {
+            bind_pose.half_extents += Vec3A::splat(morph_displacement_bound(
+                targets,
+                weights,
+                mesh.count_vertices(),
+            ));
+        }
+        let bind_pose = Aabb3d::new(bind_pose.center, bind_pose.half_extents);
+
+        let mut joint_matrices = Vec::new();
+        let skinned = skinned_mesh
+            .zip(self.inverse_bindposes.as_deref())
+            .filter(|(skinned_mesh, inverse_bindposes)| {
+                compute_joint_matrices(
+                    skinned_mesh,
+                    inverse_bindposes,
+                    &self.joint_query,
+                    &mut joint_matrices,
+                )
+            })
+            .and_then(|_| skinned_aabb(&bind_pose, &joint_matrices));
+        // Meshes that can't be skinned are cast against in the space of the mesh, like their
+        // vertices.
+        Some(skinned.unwrap_or_else(|| transform_aabb(&bind_pose, &transform.compute_matrix())))
+    }
 }
@@ -0,0 +1,530 @@
+//! Built-in verbs for inspecting, reloading and replacing assets over the Bevy Remote Protocol.
+//!
+//! These are enabled by the `bevy_asset` feature, and need the [`AssetServer`] of the
+//! `AssetPlugin`. Assets are addressed by the path they were loaded from.
+
+use core::any::TypeId;
+
+use bevy_asset::{
+    AssetPath, AssetServer, DependencyLoadState, LoadState, RecursiveDependencyLoadState,
+    ReflectAsset, UntypedHandle,
+};
+use bevy_ecs::{reflect::AppTypeRegistry, system::In, world::World};
+use bevy_reflect::{
+    serde::TypedReflectDeserializer, PartialReflect, ReflectFromReflect, TypeRegistration,
+};
+use serde::{de::DeserializeSeed as _, Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{builtin_methods::parse_some, error_codes, BrpError, BrpResult};
+
+/// The method path for a `bevy/asset/list` request.
+pub const BRP_ASSET_LIST_METHOD: &str = "bevy/asset/list";
+
+/// The method path for a `bevy/asset/load_state` request.
+pub const BRP_ASSET_LOAD_STATE_METHOD: &str = "bevy/asset/load_state";
+
+/// The method path for a `bevy/asset/reload` request.
+pub const BRP_ASSET_RELOAD_METHOD: &str = "bevy/asset/reload";
+
+/// The method path for a `bevy/asset/insert` request.
+pub const BRP_ASSET_INSERT_METHOD: &str = "bevy/asset/insert";
+
+/// The method path for a `bevy/asset/insert_image` request.
+#[cfg(feature = "bevy_image")]
+pub const BRP_ASSET_INSERT_IMAGE_METHOD: &str = "bevy/asset/insert_image";
+
+/// `bevy/asset/list`: Lists the assets of a given type.
+///
+/// The server responds with a [`BrpAssetListResponse`].
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BrpAssetListParams {
+    /// The [full path] of the asset type, which must be registered with
+    /// `App::register_asset_reflect`.
+    ///
+    /// [full path]: bevy_reflect::TypePath::type_path
+    pub asset_type: String,
+}
+
+/// `bevy/asset/load_state` and `bevy/asset/reload`: Targets the asset loaded from a path.
+///
+/// The server responds to `bevy/asset/load_state` with a [`BrpAssetLoadStateResponse`], and to
+/// `bevy/asset/reload` with null.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BrpAssetPathParams {
+    /// The path the asset was loaded from, e.g. `textures/wall.png`.
+    pub path: String,
+}
+
+/// `bevy/asset/insert`: Replaces the asset loaded from a path with the given value.
+///
+/// The server responds with null.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BrpAssetInsertParams {
+    /// The [full path] of the asset type, which must be registered with
+    /// `App::register_asset_reflect`.
+    ///
+    /// [full path]: bevy_reflect::TypePath::type_path
+    pub asset_type: String,
+
+    /// The path the asset was loaded from.
+    pub path: String,
+
+    /// The serialized value of the asset.
+    pub value: Value,
+}
+
+/// `bevy/asset/insert_image`: Replaces the image loaded from a path with the given pixels.
+///
+/// The server responds with null.
+#[cfg(feature = "bevy_image")]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BrpAssetInsertImageParams {
+    /// The path the image was loaded from.
+    pub path: String,
+
+    /// The width of the image, in pixels.
+    pub width: u32,
+
+    /// The height of the image, in pixels.
+    pub height: u32,
+
+    /// The pixels of the image, row by row, as 8-bit sRGB red, green, blue and alpha values.
+    pub data: Vec<u8>,
+}
+
+/// An asset listed in response to a `bevy/asset/list` request.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BrpAssetInfo {
+    /// The ID of the asset, formatted for display.
+    pub id: String,
+
+    /// The path the asset was loaded from, if any.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub path: Option<String>,
+
+    /// The load state of the asset.
+    pub load_state: BrpLoadState,
+}
+
+/// A response from the world to the client that specifies the assets of the requested type.
+///
+/// This is sent in response to `bevy/asset/list`.
+pub type BrpAssetListResponse = Vec<BrpAssetInfo>;
+
+/// A response from the world to the client that specifies the load states of an asset.
+///
+/// This is sent in response to `bevy/asset/load_state`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BrpAssetLoadStateResponse {
+    /// The load state of the asset itself.
+    pub load_state: BrpLoadState,
+
+    /// The load state of the direct dependencies of the asset.
+    pub dependency_load_state: BrpLoadState,
+
+    /// The load state of all the dependencies of the asset, recursively.
+    pub recursive_dependency_load_state: BrpLoadState,
+
+    /// The error that made the asset or one of its dependencies fail to load, if any.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub error: Option<String>,
+}
+
+/// The serialized form of a [`LoadState`], [`DependencyLoadState`] or
+/// [`RecursiveDependencyLoadState`].
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BrpLoadState {
+    /// The asset, or its dependencies, haven't started loading yet.
+    NotLoaded,
+    /// The asset, or some of its dependencies, are loading.
+    Loading,
+    /// The asset, or all of its dependencies, are loaded.
+    Loaded,
+    /// The asset, or one of its dependencies, failed to load.
+    Failed,
+}
+
+impl From<&LoadState> for BrpLoadState {
+    fn from(state: &LoadState) -> Self {
+        match state {
+            LoadState::NotLoaded => Self::NotLoaded,
+            LoadState::Loading => Self::Loading,
+            LoadState::Loaded => Self::Loaded,
+            LoadState::Failed(_) => Self::Failed,
+        }
+    }
+}
+
+impl From<&DependencyLoadState> for BrpLoadState {
+    fn from(state: &DependencyLoadState) -> Self {
+        match state {
+            DependencyLoadState::NotLoaded => Self::NotLoaded,
+            DependencyLoadState::Loading => Self::Loading,
+            DependencyLoadState::Loaded => Self::Loaded,
+            DependencyLoadState::Failed(_) => Self::Failed,
+        }
+    }
+}
+
+impl From<&RecursiveDependencyLoadState> for BrpLoadState {
+    fn from(state: &RecursiveDependencyLoadState) -> Self {
+        match state {
+            RecursiveDependencyLoadState::NotLoaded => Self::NotLoaded,
+            RecursiveDependencyLoadState::Loading => Self::Loading,
+            RecursiveDependencyLoadState::Loaded => Self::Loaded,
+            RecursiveDependencyLoadState::Failed(_) => Self::Failed,
+        }
+    }
+}
+
+/// Handles a `bevy/asset/list` request coming from a client.
+pub fn process_remote_asset_list_request(
+    In(params): In<Option<Value>>,
+    world: &World,
+) -> BrpResult {
+    let BrpAssetListParams { asset_type } = parse_some(params)?;
+
+    let asset_server = get_asset_server(world)?;
+    let app_type_registry = world.resource::<AppTypeRegistry>();
+    let type_registry = app_type_registry.read();
+    let registration = type_registry.get_with_type_path(&asset_type);
+    let (_, reflect_asset) = get_asset_type_registration(registration, &asset_type)?;
+
+    let response: BrpAssetListResponse = reflect_asset
+        .ids(world)
+        .map(|id| BrpAssetInfo {
+            id: id.to_string(),
+            path: asset_server.get_path(id).map(|path| path.to_string()),
+            // Assets that were added rather than loaded aren't tracked by the asset server.
+            load_state: asset_server
+                .get_load_state(id)
+                .as_ref()
+                .map_or(BrpLoadState::Loaded, BrpLoadState::from),
+        })
+        .collect();
+    serde_json::to_value(response).map_err(BrpError::internal)
+}
+
+/// Handles a `bevy/asset/load_state` request coming from a client.
+///
+/// Paths that no asset was loaded from are reported as not loaded.
+pub fn process_remote_asset_load_state_request(
+    In(params): In<Option<Value>>,
+    world: &World,
+) -> BrpResult {
+    let BrpAssetPathParams { path } = parse_some(params)?;
+
+    let asset_server = get_asset_server(world)?;
+    let path = parse_asset_path(&path)?;
+    let response = match asset_server
+        .get_path_id(path)
+        .and_then(|id| asset_server.get_load_states(id))
+    {
+        Some((load_state, dependency_load_state, recursive_dependency_load_state)) => {
+            let error = match &recursive_dependency_load_state {
+                RecursiveDependencyLoadState::Failed(error) => Some(error.to_string()),
+                _ => None,
+            };
+            BrpAssetLoadStateResponse {
+                load_state: (&load_state).into(),
+                dependency_load_state: (&dependency_load_state).into(),
+                recursive_dependency_load_state: (&recursive_dependency_load_state).into(),
+                error,
+            }
+        }
+        None => BrpAssetLoadStateResponse {
+            load_state: BrpLoadState::NotLoaded,
+            dependency_load_state: BrpLoadState::NotLoaded,
+            recursive_dependency_load_state: BrpLoadState::NotLoaded,
+            error: None,
+        },
+    };
+    serde_json::to_value(response).map_err(BrpError::internal)
+}
+
+/// Handles a `bevy/asset/reload` request coming from a client.
+///
+/// The asset is reloaded in the background, so clients can follow its progress with
+/// `bevy/asset/load_state`.
+pub fn process_remote_asset_reload_request(
+    In(params): In<Option<Value>>,
+    world: &World,
+) -> BrpResult {
+    let BrpAssetPathParams { path } = parse_some(params)?;
+
+    let asset_server = get_asset_server(world)?;
+    asset_server.reload(parse_asset_path(&path)?);
+
+    Ok(Value::Null)
+}
+
+/// Handles a `bevy/asset/insert` request coming from a client.
+pub fn process_remote_asset_insert_request(
+    In(params): In<Option<Value>>,
+    world: &mut World,
+) -> BrpResult {
+    let BrpAssetInsertParams {
+        asset_type,
+        path,
+        value,
+    } = parse_some(params)?;
+
+    let app_type_registry = world.resource::<AppTypeRegistry>().clone();
+    let type_registry = app_type_registry.read();
+    let registration = type_registry.get_with_type_path(&asset_type);
+    let (registration, reflect_asset) = get_asset_type_registration(registration, &asset_type)?;
+
+    let handle = get_asset_handle(world, registration.type_id(), &path)?;
+    let reflected: Box<dyn PartialReflect> =
+        TypedReflectDeserializer::new(registration, &type_registry)
+            .deserialize(&value)
+            .map_err(|err| BrpError::asset_error(format!("{asset_type} is invalid: {err}")))?;
+    // Convert the value to the asset type here, as `ReflectAsset::insert` panics if it can't.
+    let asset = registration
+        .data::<ReflectFromReflect>()
+        .ok_or_else(|| {
+            BrpError::asset_error(format!(
+                "Asset type `{asset_type}` doesn't reflect `FromReflect`"
+            ))
+        })?
+        .from_reflect(&*reflected)
+        .ok_or_else(|| {
+            BrpError::asset_error(format!("{asset_type} can't be built from the given value"))
+        })?;
+    reflect_asset.insert(world, handle, asset.as_partial_reflect());
+
+    Ok(Value::Null)
+}
+
+/// Handles a `bevy/asset/insert_image` request coming from a client.
+///
+/// This keeps the sampler of the replaced image.
+#[cfg(feature = "bevy_image")]
+pub fn process_remote_asset_insert_image_request(
+    In(params): In<Option<Value>>,
+    world: &mut World,
+) -> BrpResult {
+    use core::any::TypeId;
+
+    use bevy_asset::{Assets, RenderAssetUsages};
+    use bevy_image::Image;
+    use wgpu_types::{Extent3d, TextureDimension, TextureFormat};
+
+    let BrpAssetInsertImageParams {
+        path,
+        width,
+        height,
+        data,
+    } = parse_some(params)?;
+
+    if data.len() as u64 != u64::from(width) * u64::from(height) * 4 {
+        return Err(BrpError {
+            code: error_codes::INVALID_PARAMS,
+            message: format!(
+                "Expected {} bytes of data for a {width}x{height} image, got {}",
+                u64::from(width) * u64::from(height) * 4,
+                data.len()
+            ),
+            data: None,
+        });
+    }
+
+    let handle = get_asset_handle(world, TypeId::of::<Image>(), &path)?.typed::<Image>();
+
+    let mut image = Image::new(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    let mut images = world
+        .get_resource_mut::<Assets<Image>>()
+        .ok_or_else(|| BrpError::asset_error("Images aren't supported by this app"))?;
+    if let Some(replaced) = images.get(&handle) {
+        image.sampler = replaced.sampler.clone();
+    }
+    images.insert(&handle, image);
+
+    Ok(Value::Null)
+}
+
+/// Returns the [`AssetServer`] of the `world`, or an error if the app doesn't have one.
+fn get_asset_server(world: &World) -> Result<&AssetServer, BrpError> {
+    world
+        .get_resource::<AssetServer>()
+        .ok_or_else(|| BrpError::asset_error("The app doesn't have an asset server"))
+}
+
+/// Parses the path of an asset.
+fn parse_asset_path(path: &str) -> Result<AssetPath<'_>, BrpError> {
+    AssetPath::try_parse(path).map_err(|err| BrpError {
+        code: error_codes::INVALID_PARAMS,
+        message: format!("Invalid asset path `{path}`: {err}"),
+        data: None,
+    })
+}
+
+/// Given the registration of an asset's type, return it along with its [`ReflectAsset`].
+fn get_asset_type_registration<'r>(
+    registration: Option<&'r TypeRegistration>,
+    asset_type: &str,
+) -> Result<(&'r TypeRegistration, &'r ReflectAsset), BrpError> {
+    let registration = registration
+        .ok_or_else(|| BrpError::asset_error(format!("Unknown asset type: `{asset_type}`")))?;
+    let reflect_asset = registration.data::<ReflectAsset>().ok_or_else(|| {
+        BrpError::asset_error(format!("Asset type `{asset_type}` isn't reflectable"))
+    })?;
+    Ok((registration, reflect_asset))
+}
+
+/// Returns the handle of the asset of the given type that was loaded from `path`.
+fn get_asset_handle(
+    world: &World,
+    asset_type_id: TypeId,
+    path: &str,
+) -> Result<UntypedHandle, BrpError> {
+    get_asset_server(world)?
+        .get_path_and_type_id_handle(&parse_asset_path(path)?, asset_type_id)
+        .ok_or_else(|| BrpError::asset_not_found(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_app::{App, TaskPoolPlugin};
+    use bevy_asset::{Asset, AssetApp, AssetPlugin, Assets};
+    use bevy_reflect::{Reflect, TypePath};
+    use serde_json::json;
+
+    #[derive(Asset, Reflect, Debug, PartialEq)]
+    struct Note {
+        text: String,
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((TaskPoolPlugin::default(), AssetPlugin::default()))
+            .init_asset::<Note>()
+            .register_asset_reflect::<Note>();
+        app
+    }
+
+    /// Tests serialization and deserialization of any type implementing Serialize and
+    /// Deserialize traits.
+    fn test_serialize_deserialize<T>(value: T)
+    where
+        T: Serialize + for<'a> Deserialize<'a> + PartialEq + core::fmt::Debug,
+    {
+        let serialized = serde_json::to_string(&value).expect("Failed to serialize");
+        let deserialized: T = serde_json::from_str(&serialized).expect("Failed to deserialize");
+        assert_eq!(
+            &value, &deserialized,
+            "Deserialized value does not match original"
+        );
+    }
+
+    #[test]
+    fn serialization_tests() {
+        test_serialize_deserialize(BrpAssetInfo {
+            id: "UntypedAssetId { index: 0, generation: 0 }".to_owned(),
+            path: Some("textures/wall.png".to_owned()),
+            load_state: BrpLoadState::Loaded,
+        });
+        test_serialize_deserialize(BrpAssetLoadStateResponse {
+            load_state: BrpLoadState::Loaded,
+            dependency_load_state: BrpLoadState::Failed,
+            recursive_dependency_load_state: BrpLoadState::Failed,
+            error: Some("Path not found: textures/wall.png".to_owned()),
+        });
+        test_serialize_deserialize(BrpAssetInsertParams {
+            asset_type: "bevy_pbr::pbr_material::StandardMaterial".to_owned(),
+            path: "materials/wall.ron".to_owned(),
+            value: Value::Null,
+        });
+        assert_eq!(
+            serde_json::to_value(BrpLoadState::NotLoaded).unwrap(),
+            Value::from("not_loaded")
+        );
+    }
+
+    #[test]
+    fn list_assets() {
+        let mut app = app();
+        let id = app
+            .world_mut()
+            .resource_mut::<Assets<Note>>()
+            .add(Note {
+                text: "hello".to_owned(),
+            })
+            .id();
+
+        let response = app
+            .world_mut()
+            .run_system_cached_with(
+                process_remote_asset_list_request,
+                Some(json!({ "asset_type": Note::type_path() })),
+            )
+            .unwrap()
+            .unwrap();
+        let response: BrpAssetListResponse = serde_json::from_value(response).unwrap();
+        assert_eq!(
+            response,
+            [BrpAssetInfo {
+                id: id.untyped().to_string(),
+                path: None,
+                load_state: BrpLoadState::Loaded,
+            }]
+        );
+    }
+
+    #[test]
+    fn insert_assets() {
+        let mut app = app();
+        let handle = app
+            .world()
+            .resource::<AssetServer>()
+            .load::<Note>("notes/first.note");
+        let mut insert = |asset_type: &str, path: &str, value: Value| {
+            app.world_mut()
+                .run_system_cached_with(
+                    process_remote_asset_insert_request,
+                    Some(json!({ "asset_type": asset_type, "path": path, "value": value })),
+                )
+                .unwrap()
+        };
+
+        insert(
+            Note::type_path(),
+            "notes/first.note",
+            json!({ "text": "hello" }),
+        )
+        .unwrap();
+
+        let error = insert("Unknown", "notes/first.note", json!({})).unwrap_err();
+        assert_eq!(error.code, error_codes::ASSET_ERROR);
+        let error =
+            insert(Note::type_path(), "notes/first.note", json!({ "text": 1 })).unwrap_err();
+        assert_eq!(error.code, error_codes::ASSET_ERROR);
+        let error = insert(
+            Note::type_path(),
+            "notes/second.note",
+            json!({ "text": "hello" }),
+        )
+        .unwrap_err();
+        assert_eq!(error.code, error_codes::ASSET_NOT_FOUND);
+
+        assert_eq!(
+            app.world().resource::<Assets<Note>>().get(&handle),
+            Some(&Note {
+                text: "hello".to_owned()
+            })
+        );
+    }
+}
@@ -4,10 +4,10 @@ use core::any::TypeId;
 
 use anyhow::{anyhow, Result as AnyhowResult};
 use bevy_ecs::{
-    component::ComponentId,
-    entity::Entity,
-    event::EventCursor,
-    query::QueryBuilder,
+    component::{ComponentId, Tick},
+    entity::{Entity, EntityHashSet},
+    event::{EventCursor, Events},
+    query::{QueryBuilder, QueryState},
     reflect::{AppTypeRegistry, ReflectComponent, ReflectResource},
     removal_detection::RemovedComponentEntity,
     system::{In, Local},
@@ -24,7 +24,7 @@ use bevy_utils::HashMap;
 use serde::{de::DeserializeSeed as _, Deserialize, Serialize};
 use serde_json::{json, Map, Value};
 
-use crate::{error_codes, BrpError, BrpResult};
+use crate::{error_codes, BrpError, BrpResult, RemoteWatchingState};
 
 /// The method path for a `bevy/get` request.
 pub const BRP_GET_METHOD: &str = "bevy/get";
@@ -59,6 +59,9 @@ pub const BRP_GET_AND_WATCH_METHOD: &str = "bevy/get+watch";
 /// The method path for a `bevy/list+watch` request.
 pub const BRP_LIST_AND_WATCH_METHOD: &str = "bevy/list+watch";
 
+/// The method path for a `bevy/query+watch` request.
+pub const BRP_QUERY_AND_WATCH_METHOD: &str = "bevy/query+watch";
+
 /// The method path for a `bevy/registry/schema` request.
 pub const BRP_REGISTRY_SCHEMA_METHOD: &str = "bevy/registry/schema";
 
@@ -358,6 +361,40 @@ pub struct BrpListWatchingResponse {
     removed: Vec<String>,
 }
 
+/// A single response from a `bevy/query+watch` request.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BrpQueryWatchingResponse {
+    /// The entities that match the query and started matching it, or had some of the requested
+    /// components added, changed or removed, in the last tick.
+    pub changed: Vec<BrpQueryWatchingRow>,
+
+    /// The entities that stopped matching the query, or were despawned, in the last tick.
+    ///
+    /// This may include entities that didn't match the query before either.
+    pub removed: Vec<Entity>,
+}
+
+/// The changes made to an entity that matches the query of a `bevy/query+watch` request.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BrpQueryWatchingRow {
+    /// The ID of the entity that matched.
+    pub entity: Entity,
+
+    /// The serialized values of the requested components that were added or changed, or of all
+    /// the requested components if the entity started matching the query.
+    pub components: HashMap<String, Value>,
+
+    /// The [full paths] of the optional components that were removed.
+    ///
+    /// [full paths]: bevy_reflect::TypePath::type_path
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub removed: Vec<String>,
+
+    /// The boolean-only containment query results, if any of them changed.
+    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
+    pub has: HashMap<String, Value>,
+}
+
 /// The response to a `bevy/query` request.
 pub type BrpQueryResponse = Vec<BrpQueryRow>;
 
@@ -385,7 +422,9 @@ fn parse<T: for<'de> Deserialize<'de>>(value: Value) -> Result<T, BrpError> {
 }
 
 /// A helper function used to parse a `serde_json::Value` wrapped in an `Option`.
-fn parse_some<T: for<'de> Deserialize<'de>>(value: Option<Value>) -> Result<T, BrpError> {
+pub(crate) fn parse_some<T: for<'de> Deserialize<'de>>(
+    value: Option<Value>,
+) -> Result<T, BrpError> {
     match value {
         Some(value) => parse(value),
         None => Err(BrpError {
@@ -588,49 +627,23 @@ fn reflect_component(
 /// Handles a `bevy/query` request coming from a client.
 pub fn process_remote_query_request(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
     let BrpQueryParams {
-        data: BrpQuery {
-            components,
-            option,
-            has,
-        },
-        filter: BrpQueryFilter { without, with },
+        data,
+        filter,
         strict,
     } = parse_some(params)?;
 
     let app_type_registry = world.resource::<AppTypeRegistry>().clone();
     let type_registry = app_type_registry.read();
 
-    let components = get_component_ids(&type_registry, world, components, strict)
-        .map_err(BrpError::component_error)?;
-    let option = get_component_ids(&type_registry, world, option, strict)
-        .map_err(BrpError::component_error)?;
-    let has =
-        get_component_ids(&type_registry, world, has, strict).map_err(BrpError::component_error)?;
-    let without = get_component_ids(&type_registry, world, without, strict)
-        .map_err(BrpError::component_error)?;
-    let with = get_component_ids(&type_registry, world, with, strict)
+    let QueryComponentIds {
+        components,
+        option,
+        has,
+        without,
+        with,
+    } = QueryComponentIds::new(&type_registry, world, data, filter, strict)
         .map_err(BrpError::component_error)?;
-
-    let mut query = QueryBuilder::<FilteredEntityRef>::new(world);
-    for (_, component) in &components {
-        query.ref_id(*component);
-    }
-    for (_, option) in &option {
-        query.optional(|query| {
-            query.ref_id(*option);
-        });
-    }
-    for (_, has) in &has {
-        query.optional(|query| {
-            query.ref_id(*has);
-        });
-    }
-    for (_, without) in without {
-        query.without_id(without);
-    }
-    for (_, with) in with {
-        query.with_id(with);
-    }
+    let mut query = build_query(world, &components, &option, &has, &without, &with);
 
     // At this point, we can safely unify `components` and `option`, since we only retrieved
     // entities that actually have all the `components` already.
@@ -652,7 +665,6 @@ pub fn process_remote_query_request(In(params): In<Option<Value>>, world: &mut W
         .map_err(BrpError::component_error)?;
 
     let mut response = BrpQueryResponse::default();
-    let mut query = query.build();
     for row in query.iter(world) {
         // The map of component values:
         let components_map = build_components_map(
@@ -677,6 +689,213 @@ pub fn process_remote_query_request(In(params): In<Option<Value>>, world: &mut W
     serde_json::to_value(response).map_err(BrpError::internal)
 }
 
+/// Handles a `bevy/query+watch` request coming from a client.
+///
+/// The first run for a request sends all the entities that match the query, and the later ones
+/// send what changed since the previous run for the same request, which is tracked with its
+/// [`RemoteWatchingState`].
+pub fn process_remote_query_watching_request(
+    In(params): In<Option<Value>>,
+    world: &mut World,
+) -> BrpResult<Option<Value>> {
+    let watcher = world
+        .get_resource_mut::<RemoteWatchingState>()
+        .and_then(|mut state| state.take::<QueryWatcher>());
+    let mut watcher = match watcher {
+        Some(watcher) => watcher,
+        None => QueryWatcher::new(parse_some(params)?, world)?,
+    };
+    let response = watcher.changes(world);
+    if let Some(mut state) = world.get_resource_mut::<RemoteWatchingState>() {
+        state.set(watcher);
+    }
+    response
+}
+
+/// The state of a `bevy/query+watch` request, kept between the runs of its handler.
+struct QueryWatcher {
+    component_ids: QueryComponentIds,
+    query: QueryState<FilteredEntityRef<'static>>,
+    without_queries: Vec<(ComponentId, QueryState<Entity>)>,
+    /// The tick of the previous run, or [`None`] before the first one.
+    last_run: Option<Tick>,
+}
+
+impl QueryWatcher {
+    fn new(
+        BrpQueryParams {
+            data,
+            filter,
+            strict,
+        }: BrpQueryParams,
+        world: &mut World,
+    ) -> BrpResult<Self> {
+        let app_type_registry = world.resource::<AppTypeRegistry>().clone();
+        let type_registry = app_type_registry.read();
+        let component_ids = QueryComponentIds::new(&type_registry, world, data, filter, strict)
+            .map_err(BrpError::component_error)?;
+        let QueryComponentIds {
+            components,
+            option,
+            has,
+            without,
+            with,
+        } = &component_ids;
+        let query = build_query(world, components, option, has, without, with);
+        let without_queries = without
+            .iter()
+            .map(|(_, component_id)| {
+                let query = QueryBuilder::<Entity>::new(world)
+                    .with_id(*component_id)
+                    .build();
+                (*component_id, query)
+            })
+            .collect();
+        Ok(Self {
+            component_ids,
+            query,
+            without_queries,
+            last_run: None,
+        })
+    }
+
+    /// Returns what changed since the previous run, or all the entities that match the query on
+    /// the first run, or [`None`] if nothing changed.
+    fn changes(&mut self, world: &World) -> BrpResult<Option<Value>> {
+        let app_type_registry = world.resource::<AppTypeRegistry>().clone();
+        let type_registry = app_type_registry.read();
+        let QueryComponentIds {
+            components,
+            option,
+            has,
+            without,
+            with,
+        } = &self.component_ids;
+
+        let ids_and_reflect_components = components
+            .iter()
+            .chain(option)
+            .map(|(type_id, component_id)| {
+                let (type_path, reflect_component) =
+                    reflect_component_from_id(*type_id, &type_registry)?;
+                Ok::<_, anyhow::Error>((*component_id, type_path, reflect_component))
+            })
+            .collect::<AnyhowResult<Vec<(ComponentId, &str, &ReflectComponent)>>>()
+            .map_err(BrpError::component_error)?;
+        let has_paths_and_reflect_components: Vec<(&str, &ReflectComponent)> = has
+            .iter()
+            .map(|(type_id, _)| reflect_component_from_id(*type_id, &type_registry))
+            .collect::<AnyhowResult<Vec<(&str, &ReflectComponent)>>>()
+            .map_err(BrpError::component_error)?;
+
+        let this_run = world.read_change_tick();
+        let last_run = self.last_run.replace(this_run);
+        // On the first run, every entity that matches the query is sent as if it just started
+        // matching it.
+        let first_run = last_run.is_none();
+        let last_run = last_run.unwrap_or(this_run);
+        let is_added = |entity: Entity, component_ids: &[(TypeId, ComponentId)]| {
+            component_ids.iter().any(|(_, component_id)| {
+                world
+                    .entity(entity)
+                    .get_change_ticks_by_id(*component_id)
+                    .is_some_and(|ticks| ticks.is_added(last_run, this_run))
+            })
+        };
+
+        // Entities that may have started matching the query, and whose requested components must
+        // all be sent, are the ones that had a required component added or an excluded one
+        // removed.
+        let now_included = removed_entities(world, without);
+        // Entities that may have stopped matching the query are the ones that had a required
+        // component removed or an excluded one added, or that were despawned.
+        let mut maybe_excluded = removed_entities(world, components);
+        maybe_excluded.extend(removed_entities(world, with));
+        let has_removed = removed_entities(world, has);
+
+        let mut response = BrpQueryWatchingResponse::default();
+        for row in self.query.iter(world) {
+            let entity = row.id();
+            let is_new = first_run
+                || now_included.contains(&entity)
+                || is_added(entity, components)
+                || is_added(entity, with);
+
+            let changed = ids_and_reflect_components
+                .iter()
+                .filter(|(component_id, ..)| {
+                    is_new
+                        || row
+                            .get_change_ticks_by_id(*component_id)
+                            .is_some_and(|ticks| ticks.is_changed(last_run, this_run))
+                })
+                .map(|(_, type_path, reflect_component)| (*type_path, *reflect_component));
+            let components_map = build_components_map(row.clone(), changed, &type_registry)
+                .map_err(BrpError::component_error)?;
+
+            let removed: Vec<String> = option
+                .iter()
+                .filter(|(_, component_id)| {
+                    !first_run
+                        && !row.contains_id(*component_id)
+                        && world
+                            .removed_components()
+                            .get(*component_id)
+                            .is_some_and(|events| {
+                                events
+                                    .iter_current_update_events()
+                                    .any(|event| Entity::from(event.clone()) == entity)
+                            })
+                })
+                .filter_map(|(type_id, _)| reflect_component_from_id(*type_id, &type_registry).ok())
+                .map(|(type_path, _)| type_path.to_owned())
+                .collect();
+
+            let has_changed = is_new || has_removed.contains(&entity) || is_added(entity, has);
+            if components_map.is_empty() && removed.is_empty() && !has_changed {
+                continue;
+            }
+            let has_map = if has_changed {
+                build_has_map(
+                    row.clone(),
+                    has_paths_and_reflect_components.iter().copied(),
+                )
+            } else {
+                Default::default()
+            };
+            response.changed.push(BrpQueryWatchingRow {
+                entity,
+                components: components_map,
+                removed,
+                has: has_map,
+            });
+        }
+
+        if !first_run {
+            for (component_id, without_query) in &mut self.without_queries {
+                maybe_excluded.extend(without_query.iter(world).filter(|entity| {
+                    world
+                        .entity(*entity)
+                        .get_change_ticks_by_id(*component_id)
+                        .is_some_and(|ticks| ticks.is_added(last_run, this_run))
+                }));
+            }
+            response.removed = maybe_excluded
+                .into_iter()
+                .filter(|entity| self.query.get(world, *entity).is_err())
+                .collect();
+            response.removed.sort_unstable();
+        }
+
+        if response.changed.is_empty() && response.removed.is_empty() && !first_run {
+            return Ok(None);
+        }
+        Ok(Some(
+            serde_json::to_value(response).map_err(BrpError::internal)?,
+        ))
+    }
+}
+
 /// Handles a `bevy/spawn` request coming from a client.
 pub fn process_remote_spawn_request(In(params): In<Option<Value>>, world: &mut World) -> BrpResult {
     let BrpSpawnParams { components } = parse_some(params)?;
@@ -1357,6 +1576,80 @@ fn get_component_ids(
     Ok(component_ids)
 }
 
+/// The [`TypeId`] and [`ComponentId`] of each component of a [`BrpQuery`] and a [`BrpQueryFilter`].
+struct QueryComponentIds {
+    components: Vec<(TypeId, ComponentId)>,
+    option: Vec<(TypeId, ComponentId)>,
+    has: Vec<(TypeId, ComponentId)>,
+    without: Vec<(TypeId, ComponentId)>,
+    with: Vec<(TypeId, ComponentId)>,
+}
+
+impl QueryComponentIds {
+    fn new(
+        type_registry: &TypeRegistry,
+        world: &World,
+        BrpQuery {
+            components,
+            option,
+            has,
+        }: BrpQuery,
+        BrpQueryFilter { without, with }: BrpQueryFilter,
+        strict: bool,
+    ) -> AnyhowResult<Self> {
+        Ok(Self {
+            components: get_component_ids(type_registry, world, components, strict)?,
+            option: get_component_ids(type_registry, world, option, strict)?,
+            has: get_component_ids(type_registry, world, has, strict)?,
+            without: get_component_ids(type_registry, world, without, strict)?,
+            with: get_component_ids(type_registry, world, with, strict)?,
+        })
+    }
+}
+
+/// Builds the query of a `bevy/query` or `bevy/query+watch` request.
+fn build_query(
+    world: &mut World,
+    components: &[(TypeId, ComponentId)],
+    option: &[(TypeId, ComponentId)],
+    has: &[(TypeId, ComponentId)],
+    without: &[(TypeId, ComponentId)],
+    with: &[(TypeId, ComponentId)],
+) -> QueryState<FilteredEntityRef<'static>> {
+    let mut query = QueryBuilder::<FilteredEntityRef>::new(world);
+    for (_, component) in components {
+        query.ref_id(*component);
+    }
+    for (_, option) in option {
+        query.optional(|query| {
+            query.ref_id(*option);
+        });
+    }
+    for (_, has) in has {
+        query.optional(|query| {
+            query.ref_id(*has);
+        });
+    }
+    for (_, without) in without {
+        query.without_id(*without);
+    }
+    for (_, with) in with {
+        query.with_id(*with);
+    }
+    query.build()
+}
+
+/// Returns the entities that had any of the given components removed, or were despawned, in the
+/// last tick.
+fn removed_entities(world: &World, component_ids: &[(TypeId, ComponentId)]) -> EntityHashSet {
+    component_ids
+        .iter()
+        .filter_map(|(_, component_id)| world.removed_components().get(*component_id))
+        .flat_map(Events::iter_current_update_events)
+        .map(|event| Entity::from(event.clone()))
+        .collect()
+}
+
 /// Given an entity (`entity_ref`) and a list of reflected component information
 /// (`paths_and_reflect_components`), return a map which associates each component to
 /// its serialized value from the entity.
@@ -1513,7 +1806,7 @@ mod tests {
     }
     use super::*;
     use bevy_ecs::{component::Component, system::Resource};
-    use bevy_reflect::Reflect;
+    use bevy_reflect::{Reflect, TypePath};
 
     #[test]
     fn serialization_tests() {
@@ -1523,6 +1816,15 @@ mod tests {
             has: Default::default(),
         });
         test_serialize_deserialize(BrpListWatchingResponse::default());
+        test_serialize_deserialize(BrpQueryWatchingResponse {
+            changed: vec![BrpQueryWatchingRow {
+                entity: Entity::from_raw(0),
+                components: Default::default(),
+                removed: vec!["bevy_transform::components::transform::Transform".to_owned()],
+                has: Default::default(),
+            }],
+            removed: vec![Entity::from_raw(1)],
+        });
         test_serialize_deserialize(BrpQuery::default());
         test_serialize_deserialize(BrpJsonSchemaQueryFilter::default());
         test_serialize_deserialize(BrpJsonSchemaQueryFilter {
@@ -1725,4 +2027,58 @@ mod tests {
         });
         assert_eq!(schema_as_value, value);
     }
+
+    #[test]
+    fn query_watching_sends_all_rows_then_changes() {
+        #[derive(Component, Reflect)]
+        #[reflect(Component)]
+        struct Health(u32);
+
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        world
+            .resource::<AppTypeRegistry>()
+            .write()
+            .register::<Health>();
+        world.register_component::<Health>();
+        let entity = world.spawn(Health(100)).id();
+
+        let params = Some(json!({ "data": { "components": [Health::type_path()] } }));
+        let system = world.register_system(process_remote_query_watching_request);
+        let run = |world: &mut World, state: &mut RemoteWatchingState| {
+            world.insert_resource(core::mem::take(state));
+            let response = world.run_system_with(system, params.clone()).unwrap();
+            *state = world.remove_resource::<RemoteWatchingState>().unwrap();
+            // Removed components are only reported for one frame.
+            world.clear_trackers();
+            response.unwrap().map(|response| {
+                serde_json::from_value::<BrpQueryWatchingResponse>(response).unwrap()
+            })
+        };
+        let mut first_watcher = RemoteWatchingState::default();
+
+        // The first response contains every matching entity.
+        let response = run(&mut world, &mut first_watcher).unwrap();
+        assert_eq!(response.changed.len(), 1);
+        assert_eq!(response.changed[0].entity, entity);
+        assert!(response.changed[0]
+            .components
+            .contains_key(Health::type_path()));
+        assert!(run(&mut world, &mut first_watcher).is_none());
+
+        world.get_mut::<Health>(entity).unwrap().0 = 90;
+        let response = run(&mut world, &mut first_watcher).unwrap();
+        assert_eq!(response.changed.len(), 1);
+        assert!(run(&mut world, &mut first_watcher).is_none());
+
+        // Another request for the same method starts over.
+        let mut second_watcher = RemoteWatchingState::default();
+        let response = run(&mut world, &mut second_watcher).unwrap();
+        assert_eq!(response.changed.len(), 1);
+
+        world.despawn(entity);
+        let response = run(&mut world, &mut first_watcher).unwrap();
+        assert!(response.changed.is_empty());
+        assert_eq!(response.removed, [entity]);
+    }
 }
@@ -274,6 +274,90 @@
 //! - `removed`: An array of fully-qualified type names of components removed from the entity
 //!   in the last tick.
 //!
+//! ### bevy/query+watch
+//!
+//! Watch the entities that match a query, and the values of their components.
+//!
+//! The first response contains all the entities that match the query, with all the requested
+//! components, as if they had all just started matching it. Each later response only contains
+//! what changed since the previous one.
+//!
+//! `params`: The same as the ones of `bevy/query`.
+//!
+//! `result`:
+//! - `changed`: An array of objects, one for each entity that matches the query and started
+//!   matching it, or had some of the requested components added, changed or removed, since the
+//!   previous response. Each object contains:
+//!   - `entity`: The ID of the entity.
+//!   - `components`: A map associating each type name to its value, for the components that were
+//!     added or changed, or for all the requested components if the entity started matching.
+//!   - `removed` (optional): An array of the type names of the optional components that were
+//!     removed.
+//!   - `has` (optional): The same as the `has` of `bevy/query`, if any of them changed.
+//! - `removed`: An array of the IDs of the entities that stopped matching the query, or were
+//!   despawned, since the previous response. This may include entities that didn't match it
+//!   before either.
+//!
+//! ### bevy/asset/list
+//!
+//! List the assets of a type. This and the following asset methods need the `bevy_asset` feature.
+//!
+//! `params`:
+//! - `asset_type`: The [fully-qualified type name] of the asset type, which must be registered
+//!   with `App::register_asset_reflect`.
+//!
+//! `result`: An array, each of which is an object containing:
+//! - `id`: The ID of the asset, formatted for display.
+//! - `path` (optional): The path the asset was loaded from.
+//! - `load_state`: One of `not_loaded`, `loading`, `loaded` or `failed`.
+//!
+//! ### bevy/asset/load_state
+//!
+//! Get the load states of the asset loaded from a path.
+//!
+//! `params`:
+//! - `path`: The path of the asset, e.g. `textures/wall.png`.
+//!
+//! `result`:
+//! - `load_state`: The load state of the asset itself, as in `bevy/asset/list`.
+//! - `dependency_load_state`: The load state of its direct dependencies.
+//! - `recursive_dependency_load_state`: The load state of all its dependencies, recursively.
+//! - `error` (optional): The error that made the asset or one of its dependencies fail to load.
+//!
+//! ### bevy/asset/reload
+//!
+//! Reload the asset at a path in the background.
+//!
+//! `params`:
+//! - `path`: The path of the asset.
+//!
+//! `result`: null.
+//!
+//! ### bevy/asset/insert
+//!
+//! Replace the asset loaded from a path with a new value, which is used wherever the asset is.
+//!
+//! `params`:
+//! - `asset_type`: The fully-qualified type name of the asset type.
+//! - `path`: The path the asset was loaded from.
+//! - `value`: The new value of the asset.
+//!
+//! `result`: null.
+//!
+//! ### bevy/asset/insert_image
+//!
+//! Replace the image loaded from a path with the given pixels, for example to change the base
+//! color texture of a material. This needs the `bevy_image` feature.
+//!
+//! `params`:
+//! - `path`: The path the image was loaded from.
+//! - `width`: The width of the new image, in pixels.
+//! - `height`: The height of the new image, in pixels.
+//! - `data`: An array of the 8-bit sRGB red, green, blue and alpha values of each pixel, row by
+//!   row.
+//!
+//! `result`: null.
+//!
 //!
 //! ## Custom methods
 //!
@@ -328,10 +412,13 @@ use bevy_ecs::{
     world::World,
 };
 use bevy_utils::{prelude::default, HashMap};
+use core::any::Any;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::sync::RwLock;
 
+#[cfg(feature = "bevy_asset")]
+pub mod builtin_asset_methods;
 pub mod builtin_methods;
 #[cfg(feature = "http")]
 pub mod http;
@@ -389,7 +476,7 @@ impl RemotePlugin {
 
 impl Default for RemotePlugin {
     fn default() -> Self {
-        Self::empty()
+        let plugin = Self::empty()
             .with_method(
                 builtin_methods::BRP_GET_METHOD,
                 builtin_methods::process_remote_get_request,
@@ -438,6 +525,34 @@ impl Default for RemotePlugin {
                 builtin_methods::BRP_LIST_AND_WATCH_METHOD,
                 builtin_methods::process_remote_list_watching_request,
             )
+            .with_watching_method(
+                builtin_methods::BRP_QUERY_AND_WATCH_METHOD,
+                builtin_methods::process_remote_query_watching_request,
+            );
+        #[cfg(feature = "bevy_asset")]
+        let plugin = plugin
+            .with_method(
+                builtin_asset_methods::BRP_ASSET_LIST_METHOD,
+                builtin_asset_methods::process_remote_asset_list_request,
+            )
+            .with_method(
+                builtin_asset_methods::BRP_ASSET_LOAD_STATE_METHOD,
+                builtin_asset_methods::process_remote_asset_load_state_request,
+            )
+            .with_method(
+                builtin_asset_methods::BRP_ASSET_RELOAD_METHOD,
+                builtin_asset_methods::process_remote_asset_reload_request,
+            )
+            .with_method(
+                builtin_asset_methods::BRP_ASSET_INSERT_METHOD,
+                builtin_asset_methods::process_remote_asset_insert_request,
+            );
+        #[cfg(feature = "bevy_image")]
+        let plugin = plugin.with_method(
+            builtin_asset_methods::BRP_ASSET_INSERT_IMAGE_METHOD,
+            builtin_asset_methods::process_remote_asset_insert_image_request,
+        );
+        plugin
     }
 }
 
@@ -565,9 +680,49 @@ impl RemoteMethods {
     }
 }
 
-/// Holds the [`BrpMessage`]'s of all ongoing watching requests along with their handlers.
+/// Holds the [`BrpMessage`]'s of all ongoing watching requests along with their handlers and
+/// their [`RemoteWatchingState`]s.
 #[derive(Debug, Resource, Default)]
-pub struct RemoteWatchingRequests(Vec<(BrpMessage, RemoteWatchingMethodSystemId)>);
+pub struct RemoteWatchingRequests(
+    Vec<(
+        BrpMessage,
+        RemoteWatchingMethodSystemId,
+        RemoteWatchingState,
+    )>,
+);
+
+/// The state that the handler of a watching method keeps for one ongoing request, such as what
+/// it already sent.
+///
+/// The handlers of watching methods are shared by all the requests for the method, so their
+/// [`Local`](bevy_ecs::system::Local)s are too. Instead, each request has its own state, which
+/// is inserted as a resource while its handler runs, and dropped when the request is closed.
+#[derive(Resource, Default)]
+pub struct RemoteWatchingState(Option<Box<dyn Any + Send + Sync>>);
+
+impl RemoteWatchingState {
+    /// Takes the state of type `T` out, or returns [`None`] if the handler didn't
+    /// [`set`](Self::set) one for this request yet.
+    pub fn take<T: Any + Send + Sync>(&mut self) -> Option<T> {
+        self.0
+            .take()
+            .and_then(|state| state.downcast().ok())
+            .map(|state| *state)
+    }
+
+    /// Sets the state of the request, which the next run of the handler can
+    /// [`take`](Self::take).
+    pub fn set<T: Any + Send + Sync>(&mut self, state: T) {
+        self.0 = Some(Box::new(state));
+    }
+}
+
+impl core::fmt::Debug for RemoteWatchingState {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RemoteWatchingState")
+            .finish_non_exhaustive()
+    }
+}
 
 /// A single request from a Bevy Remote Protocol client to the server,
 /// serialized in JSON.
@@ -735,6 +890,26 @@ impl BrpError {
             data: None,
         }
     }
+
+    /// No asset of the requested type was loaded from a path.
+    #[must_use]
+    pub fn asset_not_found(path: &str) -> Self {
+        Self {
+            code: error_codes::ASSET_NOT_FOUND,
+            message: format!("No asset of the requested type was loaded from `{path}`"),
+            data: None,
+        }
+    }
+
+    /// An arbitrary asset error. Possibly related to reflection.
+    #[must_use]
+    pub fn asset_error<E: ToString>(error: E) -> Self {
+        Self {
+            code: error_codes::ASSET_ERROR,
+            message: error.to_string(),
+            data: None,
+        }
+    }
 }
 
 /// Error codes used by BRP.
@@ -770,6 +945,12 @@ pub mod error_codes {
 
     /// Cannot reparent an entity to itself.
     pub const SELF_REPARENT: i16 = -23404;
+
+    /// Could not find asset loaded from a path.
+    pub const ASSET_NOT_FOUND: i16 = -23405;
+
+    /// Could not reflect or find asset type, or the asset server.
+    pub const ASSET_ERROR: i16 = -23406;
 }
 
 /// The result of a request.
@@ -861,10 +1042,11 @@ fn process_remote_requests(world: &mut World) {
                 let _ = message.sender.force_send(result);
             }
             RemoteMethodSystemId::Watching(id) => {
-                world
-                    .resource_mut::<RemoteWatchingRequests>()
-                    .0
-                    .push((message, id));
+                world.resource_mut::<RemoteWatchingRequests>().0.push((
+                    message,
+                    id,
+                    RemoteWatchingState::default(),
+                ));
             }
         }
     }
@@ -873,9 +1055,13 @@ fn process_remote_requests(world: &mut World) {
 /// A system that checks all ongoing watching requests for changes that should be sent
 /// and handles it if so.
 fn process_ongoing_watching_requests(world: &mut World) {
-    world.resource_scope::<RemoteWatchingRequests, ()>(|world, requests| {
-        for (message, system_id) in requests.0.iter() {
+    world.resource_scope::<RemoteWatchingRequests, ()>(|world, mut requests| {
+        for (message, system_id, state) in requests.0.iter_mut() {
+            world.insert_resource(core::mem::take(state));
             let handler_result = process_single_ongoing_watching_request(world, message, system_id);
+            if let Some(handler_state) = world.remove_resource::<RemoteWatchingState>() {
+                *state = handler_state;
+            }
             let sender_result = match handler_result {
                 Ok(Some(value)) => message.sender.try_send(Ok(value)),
                 Err(err) => message.sender.try_send(Err(err)),
@@ -906,7 +1092,7 @@ fn process_single_ongoing_watching_request(
 
 fn remove_closed_watching_requests(mut requests: ResMut<RemoteWatchingRequests>) {
     for i in (0..requests.0.len()).rev() {
-        let Some((message, _)) = requests.0.get(i) else {
+        let Some((message, ..)) = requests.0.get(i) else {
             unreachable!()
         };
 
@@ -132,9 +132,10 @@ pub fn derive_as_bind_group(ast: syn::DeriveInput) -> Result<TokenStream> {
         }
     };
 
-    // Count the number of sampler fields needed. We might have to disable
-    // bindless if bindless arrays take the GPU over the maximum number of
-    // samplers.
+    // Count the number of texture and sampler fields needed. We might have to
+    // disable bindless if bindless arrays take the GPU over the maximum number
+    // of textures or samplers.
+    let mut texture_binding_count = 0;
     let mut sampler_binding_count = 0;
 
     // Read field-level attributes
@@ -346,7 +347,7 @@ pub fn derive_as_bind_group(ast: syn::DeriveInput) -> Result<TokenStream> {
                         )
                     });
 
-                    sampler_binding_count += 1;
+                    texture_binding_count += 1;
 
                     binding_layouts.push(quote! {
                         #render_path::render_resource::BindGroupLayoutEntry {
@@ -571,12 +572,15 @@ pub fn derive_as_bind_group(ast: syn::DeriveInput) -> Result<TokenStream> {
     // Calculate the number of samplers that we need, so that we don't go over
     // the limit on certain platforms. See
     // https://github.com/bevyengine/bevy/issues/16988.
-    let samplers_needed = match attr_bindless_count {
+    let (textures_needed, samplers_needed) = match attr_bindless_count {
         Some(Lit::Int(ref bindless_count)) => match bindless_count.base10_parse::<u32>() {
-            Ok(bindless_count) => sampler_binding_count * bindless_count,
-            Err(_) => 0,
+            Ok(bindless_count) => (
+                texture_binding_count * bindless_count,
+                sampler_binding_count * bindless_count,
+            ),
+            Err(_) => (0, 0),
         },
-        _ => 0,
+        _ => (0, 0),
     };
 
     // Calculate the actual number of bindless slots, taking hardware
@@ -589,11 +593,16 @@ pub fn derive_as_bind_group(ast: syn::DeriveInput) -> Result<TokenStream> {
                 }
 
                 fn bindless_supported(render_device: &#render_path::renderer::RenderDevice) -> bool {
+                    // The shaders index the binding arrays with the slot of the
+                    // material, which varies between the instances of a batch,
+                    // so non-uniform indexing is required.
                     render_device.features().contains(
                         #render_path::settings::WgpuFeatures::BUFFER_BINDING_ARRAY |
-                        #render_path::settings::WgpuFeatures::TEXTURE_BINDING_ARRAY
+                        #render_path::settings::WgpuFeatures::TEXTURE_BINDING_ARRAY |
+                        #render_path::settings::WgpuFeatures::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING
                     ) &&
                     render_device.limits().max_storage_buffers_per_shader_stage > 0 &&
+                        render_device.limits().max_sampled_textures_per_shader_stage >= #textures_needed &&
                         render_device.limits().max_samplers_per_shader_stage >= #samplers_needed
                 }
             },
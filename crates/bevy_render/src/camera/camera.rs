@@ -74,6 +74,33 @@ impl Default for Viewport {
     }
 }
 
+impl Viewport {
+    /// Returns the viewport that the main passes of a camera should render to: its `viewport`,
+    /// with the size replaced by the `main_pass_resolution_override` if there is one.
+    ///
+    /// A view without a viewport renders to the whole target, so the returned viewport starts in
+    /// its top-left corner when it's overridden.
+    pub fn from_viewport_and_override(
+        viewport: Option<&Self>,
+        main_pass_resolution_override: Option<&MainPassResolutionOverride>,
+    ) -> Option<Self> {
+        let mut viewport = viewport.cloned();
+        if let Some(override_size) = main_pass_resolution_override {
+            viewport.get_or_insert_with(Default::default).physical_size = override_size.0;
+        }
+        viewport
+    }
+}
+
+/// Overrides the resolution of the main passes of a view, in physical pixels.
+///
+/// The main passes render to the top-left corner of their viewport, at this size, and effects
+/// that run after them, like upscalers, are responsible for filling the whole viewport. This is a
+/// component of the render world, inserted by the effects that need it.
+#[derive(Component, Reflect, Deref, Debug, Clone, Copy, PartialEq, Eq)]
+#[reflect(Component, Debug, PartialEq)]
+pub struct MainPassResolutionOverride(pub UVec2);
+
 /// Settings to define a camera sub view.
 ///
 /// When [`Camera::sub_camera_view`] is `Some`, only the sub-section of the
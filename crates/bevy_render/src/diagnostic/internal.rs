@@ -7,23 +7,31 @@ use std::thread::{self, ThreadId};
 
 use bevy_diagnostic::{Diagnostic, DiagnosticMeasurement, DiagnosticPath, DiagnosticsStore};
 use bevy_ecs::system::{Res, ResMut, Resource};
-use bevy_utils::Instant;
+use bevy_utils::{HashMap, Instant};
 use std::sync::Mutex;
 use wgpu::{
     Buffer, BufferDescriptor, BufferUsages, CommandEncoder, ComputePass, Features, MapMode,
     PipelineStatisticsTypes, QuerySet, QuerySetDescriptor, QueryType, Queue, RenderPass,
 };
 
-use crate::renderer::{RenderDevice, WgpuWrapper};
+use crate::{
+    render_graph::{InternedRenderLabel, InternedRenderSubGraph},
+    renderer::{RenderDevice, WgpuWrapper},
+};
 
-use super::RecordDiagnostics;
+use super::{
+    render_node_diagnostic_path, RecordDiagnostics, RENDER_GRAPH_ELAPSED_CPU,
+    RENDER_GRAPH_ELAPSED_GPU,
+};
 
 // buffer offset must be divisible by 256, so this constant must be divisible by 32 (=256/8)
 const MAX_TIMESTAMP_QUERIES: u32 = 256;
 const MAX_PIPELINE_STATISTICS: u32 = 128;
+const MAX_GPU_VALUES: u32 = 64;
 
 const TIMESTAMP_SIZE: u64 = 8;
 const PIPELINE_STATISTICS_SIZE: u64 = 40;
+const GPU_VALUE_SIZE: u64 = 4;
 
 struct DiagnosticsRecorderInternal {
     timestamp_period_ns: f32,
@@ -139,6 +147,43 @@ impl RecordDiagnostics for DiagnosticsRecorder {
     fn end_pass_span<P: Pass>(&self, pass: &mut P) {
         self.current_frame_lock().end_pass(pass);
     }
+
+    fn record_cpu_value(&self, name: Cow<'static, str>, suffix: &'static str, value: f64) {
+        self.current_frame_lock().record_value(name, suffix, value);
+    }
+
+    fn record_gpu_value(
+        &self,
+        encoder: &mut CommandEncoder,
+        name: Cow<'static, str>,
+        suffix: &'static str,
+        buffer: &Buffer,
+        offset: u64,
+    ) {
+        self.current_frame_lock()
+            .record_gpu_value(encoder, name, suffix, buffer, offset);
+    }
+}
+
+impl DiagnosticsRecorder {
+    /// Begins the span of a render graph node, recorded by the graph runner when
+    /// [`RenderNodeDiagnosticsPlugin`](super::RenderNodeDiagnosticsPlugin) is enabled.
+    ///
+    /// Unlike other spans, node spans aren't the parents of the spans that the node records.
+    pub(crate) fn begin_node_span(
+        &self,
+        encoder: &mut CommandEncoder,
+        sub_graph: Option<InternedRenderSubGraph>,
+        label: InternedRenderLabel,
+    ) {
+        self.current_frame_lock()
+            .begin_node_span(encoder, sub_graph, label);
+    }
+
+    /// Ends the span started by [`DiagnosticsRecorder::begin_node_span`].
+    pub(crate) fn end_node_span(&self, encoder: &mut CommandEncoder) {
+        self.current_frame_lock().end_node_span(encoder);
+    }
 }
 
 struct SpanRecord {
@@ -152,6 +197,20 @@ struct SpanRecord {
     pipeline_statistics_index: Option<u32>,
 }
 
+struct NodeSpanRecord {
+    sub_graph: Option<InternedRenderSubGraph>,
+    label: InternedRenderLabel,
+    begin_timestamp_index: Option<u32>,
+    end_timestamp_index: Option<u32>,
+    begin_instant: Instant,
+    end_instant: Option<Instant>,
+}
+
+struct GpuValueRecord {
+    path: DiagnosticPath,
+    suffix: &'static str,
+}
+
 struct FrameData {
     timestamps_query_set: Option<QuerySet>,
     num_timestamps: u32,
@@ -161,11 +220,20 @@ struct FrameData {
     num_pipeline_statistics: u32,
     buffer_size: u64,
     pipeline_statistics_buffer_offset: u64,
-    resolve_buffer: Option<Buffer>,
-    read_buffer: Option<Buffer>,
+    gpu_values_buffer_offset: u64,
+    gpu_values: Vec<GpuValueRecord>,
+    resolve_buffer: Buffer,
+    read_buffer: Buffer,
     path_components: Vec<Cow<'static, str>>,
     open_spans: Vec<SpanRecord>,
     closed_spans: Vec<SpanRecord>,
+    open_node_span: Option<NodeSpanRecord>,
+    node_spans: Vec<NodeSpanRecord>,
+    /// Values recorded with [`RecordDiagnostics::record_value`].
+    ///
+    /// Unlike spans, these aren't reset in `FrameData::begin`, since they're
+    /// typically recorded by systems that run before rendering begins.
+    values: Vec<RenderDiagnostic>,
     is_mapped: Arc<AtomicBool>,
     callback: Option<Box<dyn FnOnce(RenderDiagnostics) + Send + Sync + 'static>>,
 }
@@ -200,23 +268,23 @@ impl FrameData {
                 None
             };
 
-        let (resolve_buffer, read_buffer) = if buffer_size > 0 {
-            let resolve_buffer = wgpu_device.create_buffer(&BufferDescriptor {
-                label: Some("render_statistics_resolve_buffer"),
-                size: buffer_size,
-                usage: BufferUsages::QUERY_RESOLVE | BufferUsages::COPY_SRC,
-                mapped_at_creation: false,
-            });
-            let read_buffer = wgpu_device.create_buffer(&BufferDescriptor {
-                label: Some("render_statistics_read_buffer"),
-                size: buffer_size,
-                usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
-                mapped_at_creation: false,
-            });
-            (Some(resolve_buffer), Some(read_buffer))
-        } else {
-            (None, None)
-        };
+        // values recorded from GPU buffers are copied after the query results,
+        // which are 256 byte aligned
+        let gpu_values_buffer_offset = buffer_size;
+        buffer_size += u64::from(MAX_GPU_VALUES) * GPU_VALUE_SIZE;
+
+        let resolve_buffer = wgpu_device.create_buffer(&BufferDescriptor {
+            label: Some("render_statistics_resolve_buffer"),
+            size: buffer_size,
+            usage: BufferUsages::QUERY_RESOLVE | BufferUsages::COPY_SRC | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let read_buffer = wgpu_device.create_buffer(&BufferDescriptor {
+            label: Some("render_statistics_read_buffer"),
+            size: buffer_size,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
 
         FrameData {
             timestamps_query_set,
@@ -229,11 +297,16 @@ impl FrameData {
             num_pipeline_statistics: 0,
             buffer_size,
             pipeline_statistics_buffer_offset,
+            gpu_values_buffer_offset,
+            gpu_values: Vec::new(),
             resolve_buffer,
             read_buffer,
             path_components: Vec::new(),
             open_spans: Vec::new(),
             closed_spans: Vec::new(),
+            open_node_span: None,
+            node_spans: Vec::new(),
+            values: Vec::new(),
             is_mapped: Arc::new(AtomicBool::new(false)),
             callback: None,
         }
@@ -242,9 +315,47 @@ impl FrameData {
     fn begin(&mut self) {
         self.num_timestamps = 0;
         self.num_pipeline_statistics = 0;
+        self.gpu_values.clear();
         self.path_components.clear();
         self.open_spans.clear();
         self.closed_spans.clear();
+        self.open_node_span = None;
+        self.node_spans.clear();
+    }
+
+    fn record_value(&mut self, name: Cow<'static, str>, suffix: &'static str, value: f64) {
+        self.values.push(RenderDiagnostic {
+            path: DiagnosticPath::from_components(["render", &*name]),
+            suffix,
+            value,
+        });
+    }
+
+    fn record_gpu_value(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        name: Cow<'static, str>,
+        suffix: &'static str,
+        buffer: &Buffer,
+        offset: u64,
+    ) {
+        if self.gpu_values.len() >= MAX_GPU_VALUES as usize {
+            return;
+        }
+
+        let destination =
+            self.gpu_values_buffer_offset + self.gpu_values.len() as u64 * GPU_VALUE_SIZE;
+        encoder.copy_buffer_to_buffer(
+            buffer,
+            offset,
+            &self.resolve_buffer,
+            destination,
+            GPU_VALUE_SIZE,
+        );
+        self.gpu_values.push(GpuValueRecord {
+            path: DiagnosticPath::from_components(["render", &*name]),
+            suffix,
+        });
     }
 
     fn write_timestamp(
@@ -385,10 +496,39 @@ impl FrameData {
         span.end_instant = Some(Instant::now());
     }
 
-    fn resolve(&mut self, encoder: &mut CommandEncoder) {
-        let Some(resolve_buffer) = &self.resolve_buffer else {
+    fn begin_node_span(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        sub_graph: Option<InternedRenderSubGraph>,
+        label: InternedRenderLabel,
+    ) {
+        let begin_instant = Instant::now();
+        let begin_timestamp_index = self.write_timestamp(encoder, false);
+
+        self.open_node_span = Some(NodeSpanRecord {
+            sub_graph,
+            label,
+            begin_timestamp_index,
+            end_timestamp_index: None,
+            begin_instant,
+            end_instant: None,
+        });
+    }
+
+    fn end_node_span(&mut self, encoder: &mut CommandEncoder) {
+        let end_timestamp_index = self.write_timestamp(encoder, false);
+
+        // The span is missing if the node failed, in which case rendering stops anyway.
+        let Some(mut span) = self.open_node_span.take() else {
             return;
         };
+        span.end_timestamp_index = end_timestamp_index;
+        span.end_instant = Some(Instant::now());
+        self.node_spans.push(span);
+    }
+
+    fn resolve(&mut self, encoder: &mut CommandEncoder) {
+        let resolve_buffer = &self.resolve_buffer;
 
         match &self.timestamps_query_set {
             Some(set) if self.num_timestamps > 0 => {
@@ -409,11 +549,7 @@ impl FrameData {
             _ => {}
         }
 
-        let Some(read_buffer) = &self.read_buffer else {
-            return;
-        };
-
-        encoder.copy_buffer_to_buffer(resolve_buffer, 0, read_buffer, 0, self.buffer_size);
+        encoder.copy_buffer_to_buffer(resolve_buffer, 0, &self.read_buffer, 0, self.buffer_size);
     }
 
     fn diagnostic_path(&self, range: &Range<usize>, field: &str) -> DiagnosticPath {
@@ -425,10 +561,14 @@ impl FrameData {
     }
 
     fn finish(&mut self, callback: impl FnOnce(RenderDiagnostics) + Send + Sync + 'static) {
-        let Some(read_buffer) = &self.read_buffer else {
+        let recorded_on_gpu = self.num_timestamps > 0
+            || self.num_pipeline_statistics > 0
+            || !self.gpu_values.is_empty();
+
+        if !recorded_on_gpu {
             // we still have cpu timings, so let's use them
 
-            let mut diagnostics = Vec::new();
+            let mut diagnostics = core::mem::take(&mut self.values);
 
             for span in &self.closed_spans {
                 if let (Some(begin), Some(end)) = (span.begin_instant, span.end_instant) {
@@ -440,28 +580,90 @@ impl FrameData {
                 }
             }
 
+            self.push_node_diagnostics(&mut diagnostics, &[], 0.0);
+
             callback(RenderDiagnostics(diagnostics));
             return;
-        };
+        }
 
         self.callback = Some(Box::new(callback));
 
         let is_mapped = self.is_mapped.clone();
-        read_buffer.slice(..).map_async(MapMode::Read, move |res| {
-            if let Err(e) = res {
-                tracing::warn!("Failed to download render statistics buffer: {e}");
-                return;
+        self.read_buffer
+            .slice(..)
+            .map_async(MapMode::Read, move |res| {
+                if let Err(e) = res {
+                    tracing::warn!("Failed to download render statistics buffer: {e}");
+                    return;
+                }
+
+                is_mapped.store(true, Ordering::Release);
+            });
+    }
+
+    fn push_node_diagnostics(
+        &self,
+        diagnostics: &mut Vec<RenderDiagnostic>,
+        timestamps: &[u64],
+        timestamp_period_ns: f32,
+    ) {
+        if self.node_spans.is_empty() {
+            return;
+        }
+
+        // Nodes of sub graphs that run once per view have one span per view, which are summed
+        // so that a diagnostic gets a single measurement per frame.
+        let mut values = HashMap::<DiagnosticPath, f64>::default();
+        let mut total_cpu = 0.0;
+        let mut total_gpu = None;
+
+        for span in &self.node_spans {
+            if let Some(end) = span.end_instant {
+                let value = (end - span.begin_instant).as_secs_f64() * 1000.0;
+                let path = render_node_diagnostic_path(span.sub_graph, span.label, "elapsed_cpu");
+                *values.entry(path).or_default() += value;
+                total_cpu += value;
             }
 
-            is_mapped.store(true, Ordering::Release);
+            if let (Some(begin), Some(end)) = (span.begin_timestamp_index, span.end_timestamp_index)
+            {
+                let begin = timestamps[begin as usize] as f64;
+                let end = timestamps[end as usize] as f64;
+                let value = (end - begin) * (timestamp_period_ns as f64) / 1e6;
+
+                let path = render_node_diagnostic_path(span.sub_graph, span.label, "elapsed_gpu");
+                *values.entry(path).or_default() += value;
+                *total_gpu.get_or_insert(0.0) += value;
+            }
+        }
+
+        diagnostics.extend(values.into_iter().map(|(path, value)| RenderDiagnostic {
+            path,
+            suffix: "ms",
+            value,
+        }));
+
+        diagnostics.push(RenderDiagnostic {
+            path: RENDER_GRAPH_ELAPSED_CPU,
+            suffix: "ms",
+            value: total_cpu,
         });
+
+        if let Some(total_gpu) = total_gpu {
+            diagnostics.push(RenderDiagnostic {
+                path: RENDER_GRAPH_ELAPSED_GPU,
+                suffix: "ms",
+                value: total_gpu,
+            });
+        }
     }
 
     // returns true if the frame is considered finished, false otherwise
     fn run_mapped_callback(&mut self, timestamp_period_ns: f32) -> bool {
-        let Some(read_buffer) = &self.read_buffer else {
+        // the callback already ran in `finish` if nothing was recorded on the GPU
+        if self.callback.is_none() {
             return true;
-        };
+        }
         if !self.is_mapped.load(Ordering::Acquire) {
             // need to wait more
             return false;
@@ -470,6 +672,7 @@ impl FrameData {
             return true;
         };
 
+        let read_buffer = &self.read_buffer;
         let data = read_buffer.slice(..).get_mapped_range();
 
         let timestamps = data[..(self.num_timestamps * 8) as usize]
@@ -484,7 +687,30 @@ impl FrameData {
             .map(|v| u64::from_le_bytes(v.try_into().unwrap()))
             .collect::<Vec<u64>>();
 
-        let mut diagnostics = Vec::new();
+        let start = self.gpu_values_buffer_offset as usize;
+        let gpu_values = data[start..start + self.gpu_values.len() * 4]
+            .chunks(4)
+            .map(|v| u32::from_le_bytes(v.try_into().unwrap()))
+            .collect::<Vec<u32>>();
+
+        let mut diagnostics = core::mem::take(&mut self.values);
+
+        let mut summed_gpu_values = HashMap::<DiagnosticPath, (&'static str, f64)>::default();
+        for (record, value) in self.gpu_values.iter().zip(gpu_values) {
+            let (_, sum) = summed_gpu_values
+                .entry(record.path.clone())
+                .or_insert((record.suffix, 0.0));
+            *sum += value as f64;
+        }
+        diagnostics.extend(
+            summed_gpu_values
+                .into_iter()
+                .map(|(path, (suffix, value))| RenderDiagnostic {
+                    path,
+                    suffix,
+                    value,
+                }),
+        );
 
         for span in &self.closed_spans {
             if let (Some(begin), Some(end)) = (span.begin_instant, span.end_instant) {
@@ -547,6 +773,8 @@ impl FrameData {
             }
         }
 
+        self.push_node_diagnostics(&mut diagnostics, &timestamps, timestamp_period_ns);
+
         callback(RenderDiagnostics(diagnostics));
 
         drop(data);
@@ -8,13 +8,23 @@ use alloc::{borrow::Cow, sync::Arc};
 use core::marker::PhantomData;
 
 use bevy_app::{App, Plugin, PreUpdate};
+use bevy_diagnostic::{DiagnosticPath, DiagnosticsStore};
+use bevy_ecs::{
+    event::{Event, EventWriter},
+    schedule::IntoSystemConfigs,
+    system::{Local, Res, Resource},
+};
+use bevy_utils::{HashMap, Instant};
+use wgpu::{Buffer, CommandEncoder};
 
-use crate::RenderApp;
-
-use self::internal::{
-    sync_diagnostics, DiagnosticsRecorder, Pass, RenderDiagnosticsMutex, WriteTimestamp,
+use crate::{
+    render_graph::{InternedRenderLabel, InternedRenderSubGraph},
+    RenderApp,
 };
 
+pub use self::internal::DiagnosticsRecorder;
+use self::internal::{sync_diagnostics, Pass, RenderDiagnosticsMutex, WriteTimestamp};
+
 use super::{RenderDevice, RenderQueue};
 
 /// Enables collecting render diagnostics, such as CPU/GPU elapsed time per render pass,
@@ -40,6 +50,12 @@ use super::{RenderDevice, RenderQueue};
 ///     time_span.end(render_context.command_encoder());
 ///     ```
 ///
+/// Values that are known on the CPU, such as the size of a buffer, can be recorded with
+/// [`RecordDiagnostics::record_value`]. Outside of render graph nodes, the recorder is available
+/// as the [`DiagnosticsRecorder`] resource in the render world, until rendering starts.
+/// Counters written by shaders, such as the number of culled objects, can be read back with
+/// [`RecordDiagnostics::record_gpu_u32`].
+///
 /// # Supported platforms
 /// Timestamp queries and pipeline statistics are currently supported only on Vulkan and DX12.
 /// On other platforms (Metal, WebGPU, WebGL2) only CPU time will be recorded.
@@ -101,6 +117,35 @@ pub trait RecordDiagnostics: Send + Sync {
         }
     }
 
+    /// Record a value measured on the CPU for the current frame, such as the size of a buffer.
+    ///
+    /// The value is stored under the `render/{name}` diagnostic path.
+    fn record_value<N>(&self, name: N, suffix: &'static str, value: f64)
+    where
+        N: Into<Cow<'static, str>>,
+    {
+        self.record_cpu_value(name.into(), suffix, value);
+    }
+
+    /// Record a `u32` written by the GPU at `offset` in `buffer`, once the commands recorded in
+    /// `encoder` so far have run. The buffer needs the [`COPY_SRC`](wgpu::BufferUsages::COPY_SRC)
+    /// usage, and the offset must be a multiple of 4.
+    ///
+    /// The value is stored under the `render/{name}` diagnostic path, a few frames later once it
+    /// has been downloaded. The values recorded under the same name in a frame are summed.
+    fn record_gpu_u32<N>(
+        &self,
+        encoder: &mut CommandEncoder,
+        name: N,
+        suffix: &'static str,
+        buffer: &Buffer,
+        offset: u64,
+    ) where
+        N: Into<Cow<'static, str>>,
+    {
+        self.record_gpu_value(encoder, name.into(), suffix, buffer, offset);
+    }
+
     #[doc(hidden)]
     fn begin_time_span<E: WriteTimestamp>(&self, encoder: &mut E, name: Cow<'static, str>);
 
@@ -112,6 +157,19 @@ pub trait RecordDiagnostics: Send + Sync {
 
     #[doc(hidden)]
     fn end_pass_span<P: Pass>(&self, pass: &mut P);
+
+    #[doc(hidden)]
+    fn record_cpu_value(&self, name: Cow<'static, str>, suffix: &'static str, value: f64);
+
+    #[doc(hidden)]
+    fn record_gpu_value(
+        &self,
+        encoder: &mut CommandEncoder,
+        name: Cow<'static, str>,
+        suffix: &'static str,
+        buffer: &Buffer,
+        offset: u64,
+    );
 }
 
 /// Guard returned by [`RecordDiagnostics::time_span`].
@@ -158,6 +216,159 @@ impl<R: ?Sized, P> Drop for PassSpanGuard<'_, R, P> {
     }
 }
 
+/// The total CPU time spent running the nodes of the render graph, recorded by
+/// [`RenderNodeDiagnosticsPlugin`].
+pub const RENDER_GRAPH_ELAPSED_CPU: DiagnosticPath =
+    DiagnosticPath::const_new("render/graph/elapsed_cpu");
+
+/// The total GPU time spent on the commands of the nodes of the render graph, recorded by
+/// [`RenderNodeDiagnosticsPlugin`].
+pub const RENDER_GRAPH_ELAPSED_GPU: DiagnosticPath =
+    DiagnosticPath::const_new("render/graph/elapsed_gpu");
+
+/// Records the CPU and GPU time spent in every node of the render graph, without changes to the
+/// nodes, and checks them against the [`RenderBudgets`].
+///
+/// The time of each node is stored in the [`DiagnosticsStore`] under the path returned by
+/// [`render_node_diagnostic_path`], with the `elapsed_cpu` and `elapsed_gpu` measurements, and
+/// the total of all nodes under [`RENDER_GRAPH_ELAPSED_CPU`] and [`RENDER_GRAPH_ELAPSED_GPU`].
+/// The times of a node that runs for several views are summed.
+///
+/// GPU times are measured with timestamps written around each node, so they include the command
+/// buffers encoded in parallel by the node. They're only available where
+/// [`RenderDiagnosticsPlugin`] supports timestamps, and at most 128 nodes are measured per frame,
+/// including the spans recorded by the nodes themselves.
+///
+/// Adds [`RenderDiagnosticsPlugin`] if it isn't added yet.
+#[derive(Default)]
+pub struct RenderNodeDiagnosticsPlugin;
+
+impl Plugin for RenderNodeDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<RenderDiagnosticsPlugin>() {
+            app.add_plugins(RenderDiagnosticsPlugin);
+        }
+
+        app.init_resource::<RenderBudgets>()
+            .add_event::<RenderBudgetExceeded>()
+            .add_systems(PreUpdate, check_render_budgets.after(sync_diagnostics));
+
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app.insert_resource(RecordRenderNodeDiagnostics);
+        }
+    }
+}
+
+/// Makes the graph runner record the spans of render graph nodes.
+#[derive(Resource)]
+pub(crate) struct RecordRenderNodeDiagnostics;
+
+/// Returns the path of the diagnostic that [`RenderNodeDiagnosticsPlugin`] records for a node,
+/// where `measurement` is `elapsed_cpu` or `elapsed_gpu`.
+///
+/// The path is `render/graph/{sub_graph}/{label}/{measurement}` for the nodes of sub graphs, and
+/// `render/graph/{label}/{measurement}` for the nodes of the main graph, using the [`Debug`]
+/// representations of the labels.
+pub fn render_node_diagnostic_path(
+    sub_graph: Option<InternedRenderSubGraph>,
+    label: InternedRenderLabel,
+    measurement: &str,
+) -> DiagnosticPath {
+    let label = format!("{label:?}");
+    match sub_graph {
+        Some(sub_graph) => DiagnosticPath::from_components([
+            "render",
+            "graph",
+            &format!("{sub_graph:?}"),
+            &label,
+            measurement,
+        ]),
+        None => DiagnosticPath::from_components(["render", "graph", &label, measurement]),
+    }
+}
+
+/// Time budgets, in milliseconds, for render diagnostics.
+///
+/// When the smoothed value of a diagnostic exceeds its budget, [`RenderNodeDiagnosticsPlugin`]
+/// sends a [`RenderBudgetExceeded`] event each time new render diagnostics are received, so that
+/// the app can lower its quality settings.
+///
+/// ```ignore
+/// budgets.set(RENDER_GRAPH_ELAPSED_GPU, 12.0);
+/// budgets.set(
+///     render_node_diagnostic_path(Some(Core3d.intern()), Node3d::Bloom.intern(), "elapsed_gpu"),
+///     1.0,
+/// );
+/// ```
+#[derive(Resource, Debug, Clone, Default)]
+pub struct RenderBudgets(HashMap<DiagnosticPath, f64>);
+
+impl RenderBudgets {
+    /// Sets the budget of the diagnostic at `path`, in milliseconds.
+    pub fn set(&mut self, path: DiagnosticPath, budget: f64) {
+        self.0.insert(path, budget);
+    }
+
+    /// Returns the budget of the diagnostic at `path`, in milliseconds.
+    pub fn get(&self, path: &DiagnosticPath) -> Option<f64> {
+        self.0.get(path).copied()
+    }
+
+    /// Removes the budget of the diagnostic at `path`.
+    pub fn remove(&mut self, path: &DiagnosticPath) -> Option<f64> {
+        self.0.remove(path)
+    }
+
+    /// Iterates over the paths of the diagnostics and their budgets.
+    pub fn iter(&self) -> impl Iterator<Item = (&DiagnosticPath, f64)> {
+        self.0.iter().map(|(path, budget)| (path, *budget))
+    }
+}
+
+/// Sent when the smoothed value of a render diagnostic exceeds its budget in [`RenderBudgets`].
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct RenderBudgetExceeded {
+    /// The path of the diagnostic.
+    pub path: DiagnosticPath,
+    /// The smoothed value of the diagnostic, in milliseconds.
+    pub value: f64,
+    /// The budget of the diagnostic, in milliseconds.
+    pub budget: f64,
+}
+
+/// Sends a [`RenderBudgetExceeded`] event for each diagnostic that got a new measurement since
+/// the last run and whose smoothed value exceeds its budget.
+pub fn check_render_budgets(
+    budgets: Res<RenderBudgets>,
+    store: Res<DiagnosticsStore>,
+    mut last_run: Local<Option<Instant>>,
+    mut events: EventWriter<RenderBudgetExceeded>,
+) {
+    for (path, budget) in budgets.iter() {
+        let Some(diagnostic) = store.get(path).filter(|diagnostic| diagnostic.is_enabled) else {
+            continue;
+        };
+        let Some(measurement) = diagnostic.measurement() else {
+            continue;
+        };
+        if last_run.is_some_and(|last_run| measurement.time <= last_run) {
+            continue;
+        }
+        let Some(value) = diagnostic.smoothed() else {
+            continue;
+        };
+        if value > budget {
+            events.send(RenderBudgetExceeded {
+                path: path.clone(),
+                value,
+                budget,
+            });
+        }
+    }
+
+    *last_run = Some(Instant::now());
+}
+
 impl<T: RecordDiagnostics> RecordDiagnostics for Option<Arc<T>> {
     fn begin_time_span<E: WriteTimestamp>(&self, encoder: &mut E, name: Cow<'static, str>) {
         if let Some(recorder) = &self {
@@ -182,4 +393,88 @@ impl<T: RecordDiagnostics> RecordDiagnostics for Option<Arc<T>> {
             recorder.end_pass_span(pass);
         }
     }
+
+    fn record_cpu_value(&self, name: Cow<'static, str>, suffix: &'static str, value: f64) {
+        if let Some(recorder) = &self {
+            recorder.record_cpu_value(name, suffix, value);
+        }
+    }
+
+    fn record_gpu_value(
+        &self,
+        encoder: &mut CommandEncoder,
+        name: Cow<'static, str>,
+        suffix: &'static str,
+        buffer: &Buffer,
+        offset: u64,
+    ) {
+        if let Some(recorder) = &self {
+            recorder.record_gpu_value(encoder, name, suffix, buffer, offset);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_app::{App, Update};
+    use bevy_diagnostic::{Diagnostic, DiagnosticMeasurement, DiagnosticsStore};
+    use bevy_ecs::event::Events;
+    use bevy_utils::Instant;
+
+    use super::{
+        check_render_budgets, RenderBudgetExceeded, RenderBudgets, RENDER_GRAPH_ELAPSED_GPU,
+    };
+
+    fn measure(app: &mut App, value: f64) {
+        let mut store = app.world_mut().resource_mut::<DiagnosticsStore>();
+        let diagnostic = store.get_mut(&RENDER_GRAPH_ELAPSED_GPU).unwrap();
+        // Resets the smoothed value to the new measurement.
+        diagnostic.clear_history();
+        diagnostic.add_measurement(DiagnosticMeasurement {
+            time: Instant::now(),
+            value,
+        });
+    }
+
+    fn events(app: &mut App) -> Vec<RenderBudgetExceeded> {
+        app.world_mut()
+            .resource_mut::<Events<RenderBudgetExceeded>>()
+            .drain()
+            .collect()
+    }
+
+    #[test]
+    fn budget_exceeded() {
+        let mut app = App::new();
+        app.init_resource::<DiagnosticsStore>()
+            .init_resource::<RenderBudgets>()
+            .add_event::<RenderBudgetExceeded>()
+            .add_systems(Update, check_render_budgets);
+
+        app.world_mut()
+            .resource_mut::<RenderBudgets>()
+            .set(RENDER_GRAPH_ELAPSED_GPU, 10.0);
+        app.world_mut()
+            .resource_mut::<DiagnosticsStore>()
+            .add(Diagnostic::new(RENDER_GRAPH_ELAPSED_GPU));
+
+        measure(&mut app, 5.0);
+        app.update();
+        assert!(events(&mut app).is_empty());
+
+        measure(&mut app, 20.0);
+        app.update();
+        assert_eq!(
+            events(&mut app),
+            vec![RenderBudgetExceeded {
+                path: RENDER_GRAPH_ELAPSED_GPU,
+                value: 20.0,
+                budget: 10.0,
+            }]
+        );
+
+        // No event is sent until a new measurement is received.
+        app.update();
+        assert!(events(&mut app).is_empty());
+    }
 }
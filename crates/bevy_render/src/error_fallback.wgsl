@@ -0,0 +1,8 @@
+// The fragment shader that replaces the one of a render pipeline whose shaders
+// failed to compile, when `PipelineCache::set_error_fallback` is enabled, so
+// that the affected meshes stand out until the shader is fixed.
+
+@fragment
+fn fragment() -> @location(0) vec4<f32> {
+    return vec4(1.0, 0.0, 1.0, 1.0);
+}
@@ -38,6 +38,7 @@ pub mod render_asset;
 pub mod render_graph;
 pub mod render_phase;
 pub mod render_resource;
+pub mod render_to_image;
 pub mod renderer;
 pub mod settings;
 pub mod storage;
@@ -253,6 +254,8 @@ pub const INSTANCE_INDEX_SHADER_HANDLE: Handle<Shader> =
 pub const MATHS_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(10665356303104593376);
 pub const COLOR_OPERATIONS_SHADER_HANDLE: Handle<Shader> =
     Handle::weak_from_u128(1844674407370955161);
+pub const ERROR_FALLBACK_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(12370386465349909814);
 
 impl Plugin for RenderPlugin {
     /// Initializes the renderer, sets up the [`RenderSet`] and creates the rendering sub-app.
@@ -390,6 +393,12 @@ impl Plugin for RenderPlugin {
             "color_operations.wgsl",
             Shader::from_wgsl
         );
+        load_internal_asset!(
+            app,
+            ERROR_FALLBACK_SHADER_HANDLE,
+            "error_fallback.wgsl",
+            Shader::from_wgsl
+        );
         if let Some(future_render_resources) =
             app.world_mut().remove_resource::<FutureRenderResources>()
         {
@@ -115,6 +115,20 @@ pub struct MeshAllocatorSettings {
     ///
     /// The default value is 1.5.
     pub growth_factor: f64,
+
+    /// Additional usages that buffers holding vertex data are created with.
+    ///
+    /// For example, [`BufferUsages::STORAGE`] allows compute shaders to read
+    /// mesh vertex data directly. Note that storage buffers aren't available
+    /// on every platform.
+    ///
+    /// The default value is [`BufferUsages::empty`].
+    pub extra_vertex_buffer_usages: BufferUsages,
+
+    /// Additional usages that buffers holding index data are created with.
+    ///
+    /// The default value is [`BufferUsages::empty`].
+    pub extra_index_buffer_usages: BufferUsages,
 }
 
 impl Default for MeshAllocatorSettings {
@@ -128,6 +142,8 @@ impl Default for MeshAllocatorSettings {
             large_threshold: 1024 * 1024 * 256,
             // 1.5× growth
             growth_factor: 1.5,
+            extra_vertex_buffer_usages: BufferUsages::empty(),
+            extra_index_buffer_usages: BufferUsages::empty(),
         }
     }
 }
@@ -462,13 +478,31 @@ impl MeshAllocator {
 
         // Perform growth.
         for (slab_id, slab_to_grow) in slabs_to_grow.0 {
-            self.reallocate_slab(render_device, render_queue, slab_id, slab_to_grow);
+            self.reallocate_slab(
+                render_device,
+                render_queue,
+                slab_id,
+                slab_to_grow,
+                mesh_allocator_settings,
+            );
         }
 
         // Copy new mesh data in.
         for (mesh_id, mesh) in &extracted_meshes.extracted {
-            self.copy_mesh_vertex_data(mesh_id, mesh, render_device, render_queue);
-            self.copy_mesh_index_data(mesh_id, mesh, render_device, render_queue);
+            self.copy_mesh_vertex_data(
+                mesh_id,
+                mesh,
+                mesh_allocator_settings,
+                render_device,
+                render_queue,
+            );
+            self.copy_mesh_index_data(
+                mesh_id,
+                mesh,
+                mesh_allocator_settings,
+                render_device,
+                render_queue,
+            );
         }
     }
 
@@ -478,6 +512,7 @@ impl MeshAllocator {
         &mut self,
         mesh_id: &AssetId<Mesh>,
         mesh: &Mesh,
+        settings: &MeshAllocatorSettings,
         render_device: &RenderDevice,
         render_queue: &RenderQueue,
     ) {
@@ -490,7 +525,7 @@ impl MeshAllocator {
             mesh_id,
             mesh.get_vertex_buffer_size(),
             |slice| mesh.write_packed_vertex_buffer_data(slice),
-            BufferUsages::VERTEX,
+            ElementClass::Vertex.buffer_usages(settings),
             slab_id,
             render_device,
             render_queue,
@@ -503,6 +538,7 @@ impl MeshAllocator {
         &mut self,
         mesh_id: &AssetId<Mesh>,
         mesh: &Mesh,
+        settings: &MeshAllocatorSettings,
         render_device: &RenderDevice,
         render_queue: &RenderQueue,
     ) {
@@ -518,7 +554,7 @@ impl MeshAllocator {
             mesh_id,
             index_data.len(),
             |slice| slice.copy_from_slice(index_data),
-            BufferUsages::INDEX,
+            ElementClass::Index.buffer_usages(settings),
             slab_id,
             render_device,
             render_queue,
@@ -782,6 +818,7 @@ impl MeshAllocator {
         render_queue: &RenderQueue,
         slab_id: SlabId,
         slab_to_grow: SlabToReallocate,
+        settings: &MeshAllocatorSettings,
     ) {
         let Some(Slab::General(slab)) = self.slabs.get_mut(&slab_id) else {
             error!("Couldn't find slab {} to grow", slab_id);
@@ -790,11 +827,9 @@ impl MeshAllocator {
 
         let old_buffer = slab.buffer.take();
 
-        let mut buffer_usages = BufferUsages::COPY_SRC | BufferUsages::COPY_DST;
-        match slab.element_layout.class {
-            ElementClass::Vertex => buffer_usages |= BufferUsages::VERTEX,
-            ElementClass::Index => buffer_usages |= BufferUsages::INDEX,
-        };
+        let buffer_usages = BufferUsages::COPY_SRC
+            | BufferUsages::COPY_DST
+            | slab.element_layout.class.buffer_usages(settings);
 
         // Create the buffer.
         let new_buffer = render_device.create_buffer(&BufferDescriptor {
@@ -961,6 +996,17 @@ impl GeneralSlab {
     }
 }
 
+impl ElementClass {
+    /// Returns the usages of the buffers holding data of this class, not
+    /// counting the copy usages.
+    fn buffer_usages(self, settings: &MeshAllocatorSettings) -> BufferUsages {
+        match self {
+            ElementClass::Vertex => BufferUsages::VERTEX | settings.extra_vertex_buffer_usages,
+            ElementClass::Index => BufferUsages::INDEX | settings.extra_index_buffer_usages,
+        }
+    }
+}
+
 impl ElementLayout {
     /// Creates an [`ElementLayout`] for mesh data of the given class (vertex or
     /// index) with the given byte size.
@@ -1026,3 +1072,64 @@ fn buffer_usages_to_str(buffer_usages: BufferUsages) -> &'static str {
         ""
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy_asset::AssetId;
+    use wgpu::BufferUsages;
+
+    use super::{ElementClass, ElementLayout, GeneralSlab, MeshAllocatorSettings, SlabId};
+
+    #[test]
+    fn extra_buffer_usages() {
+        let settings = MeshAllocatorSettings {
+            extra_vertex_buffer_usages: BufferUsages::STORAGE,
+            ..Default::default()
+        };
+        assert_eq!(
+            ElementClass::Vertex.buffer_usages(&settings),
+            BufferUsages::VERTEX | BufferUsages::STORAGE
+        );
+        assert_eq!(
+            ElementClass::Index.buffer_usages(&settings),
+            BufferUsages::INDEX
+        );
+    }
+
+    #[test]
+    fn grow_slab() {
+        let settings = MeshAllocatorSettings {
+            min_slab_size: 1024,
+            max_slab_size: 2048,
+            extra_vertex_buffer_usages: BufferUsages::STORAGE,
+            ..Default::default()
+        };
+        // 16-byte vertices, so 64 of them fit in the initial slab.
+        let layout = ElementLayout::new(ElementClass::Vertex, 16);
+        let mut mesh_allocation = None;
+        let mut slab = GeneralSlab::new(
+            SlabId::default(),
+            &mut mesh_allocation,
+            &settings,
+            layout,
+            8,
+        );
+        assert_eq!(slab.slot_capacity, 64);
+        let mesh_allocation = mesh_allocation.unwrap();
+        slab.resident_allocations
+            .insert(AssetId::default(), mesh_allocation.slab_allocation);
+
+        // Growing keeps the resident mesh, which has to be copied to the new buffer.
+        let slab_to_grow = slab.try_grow(&settings).unwrap();
+        assert_eq!(slab.slot_capacity, 96);
+        assert_eq!(
+            slab_to_grow.allocations_to_copy[&AssetId::default()].slot_count,
+            8
+        );
+
+        // The slab can't grow past the maximum slab size.
+        slab.try_grow(&settings).unwrap();
+        assert_eq!(slab.slot_capacity, 128);
+        assert!(slab.try_grow(&settings).is_err());
+    }
+}
@@ -4,21 +4,24 @@ pub use bevy_mesh::*;
 use morph::{MeshMorphWeights, MorphWeights};
 pub mod allocator;
 mod components;
+mod skinned_bounds;
 use crate::{
     primitives::Aabb,
     render_asset::{PrepareAssetError, RenderAsset, RenderAssetPlugin, RenderAssets},
     render_resource::TextureView,
     texture::GpuImage,
-    view::VisibilitySystems,
+    view::{self, VisibilitySystems},
     RenderApp,
 };
 use allocator::MeshAllocatorPlugin;
 use bevy_app::{App, Plugin, PostUpdate};
 use bevy_asset::{AssetApp, AssetId, RenderAssetUsages};
 use bevy_ecs::{
+    change_detection::DetectChangesMut,
     entity::Entity,
     query::{Changed, With},
-    schedule::IntoSystemConfigs,
+    removal_detection::RemovedComponents,
+    schedule::{IntoSystemConfigs, IntoSystemSetConfigs, SystemSet},
     system::Query,
 };
 use bevy_ecs::{
@@ -28,7 +31,13 @@ use bevy_ecs::{
         SystemParamItem,
     },
 };
+use bevy_transform::{
+    components::{GlobalTransform, Transform},
+    TransformSystem,
+};
 pub use components::{Mesh2d, Mesh3d};
+pub use skinned_bounds::*;
+use skinning::JointOverride;
 use wgpu::IndexFormat;
 
 /// Adds the [`Mesh`] as an asset and makes sure that they are extracted and prepared for the GPU.
@@ -41,14 +50,36 @@ impl Plugin for MeshPlugin {
             .register_asset_reflect::<Mesh>()
             .register_type::<Mesh3d>()
             .register_type::<skinning::SkinnedMesh>()
+            .register_type::<skinning::SkinningMethod>()
+            .register_type::<skinning::SkinnedMeshLod>()
+            .register_type::<JointOverride>()
             .register_type::<Vec<Entity>>()
             // 'Mesh' must be prepared after 'Image' as meshes rely on the morph target image being ready
             .add_plugins(RenderAssetPlugin::<RenderMesh, GpuImage>::default())
             .add_plugins(MeshAllocatorPlugin)
+            .configure_sets(
+                PostUpdate,
+                (
+                    SkinningSystems::WriteJointOverrides,
+                    SkinningSystems::ApplyJointOverrides,
+                )
+                    .chain()
+                    .after(TransformSystem::TransformPropagate),
+            )
             .add_systems(
                 PostUpdate,
-                components::mark_3d_meshes_as_changed_if_their_assets_changed
-                    .ambiguous_with(VisibilitySystems::CalculateBounds),
+                (
+                    components::mark_3d_meshes_as_changed_if_their_assets_changed
+                        .ambiguous_with(VisibilitySystems::CalculateBounds),
+                    apply_joint_overrides.in_set(SkinningSystems::ApplyJointOverrides),
+                    (compute_skinned_mesh_bounds, update_skinned_mesh_aabbs)
+                        .chain()
+                        .in_set(VisibilitySystems::CalculateBounds)
+                        .after(SkinningSystems::ApplyJointOverrides)
+                        // `calculate_bounds` inserts the static bounds of new
+                        // meshes, which these systems then replace.
+                        .after(view::calculate_bounds),
+                ),
             );
 
         let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
@@ -59,6 +90,48 @@ impl Plugin for MeshPlugin {
     }
 }
 
+/// System sets for the joints of [`SkinnedMesh`](skinning::SkinnedMesh)es,
+/// in [`PostUpdate`].
+///
+/// Both sets run after [`TransformSystem::TransformPropagate`], so animation
+/// and transform propagation have already updated the joints'
+/// [`GlobalTransform`]s, and before the end of [`PostUpdate`], so the joints
+/// are extracted for rendering with their final transforms.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
+pub enum SkinningSystems {
+    /// Systems that update [`JointOverride`]s, such as the ones that copy the
+    /// transforms of ragdoll bodies from a physics engine, should run in this
+    /// set.
+    WriteJointOverrides,
+    /// Label for the [`apply_joint_overrides`] system, which runs after
+    /// [`SkinningSystems::WriteJointOverrides`].
+    ///
+    /// Systems that read the final [`GlobalTransform`]s of joints should run
+    /// after this set, like [`update_skinned_mesh_aabbs`].
+    ApplyJointOverrides,
+}
+
+/// Copies each [`JointOverride`] to the [`GlobalTransform`] of its joint.
+///
+/// When a [`JointOverride`] is removed, the joint's [`Transform`] is marked as
+/// changed, so that transform propagation restores its [`GlobalTransform`] on
+/// the next frame.
+pub fn apply_joint_overrides(
+    mut overridden_joints: Query<(&JointOverride, &mut GlobalTransform)>,
+    mut removed_overrides: RemovedComponents<JointOverride>,
+    mut transforms: Query<&mut Transform>,
+) {
+    for (joint_override, mut global_transform) in &mut overridden_joints {
+        global_transform.set_if_neq(joint_override.0);
+    }
+
+    for entity in removed_overrides.read() {
+        if let Ok(mut transform) = transforms.get_mut(entity) {
+            transform.set_changed();
+        }
+    }
+}
+
 /// [Inherit weights](inherit_weights) from glTF mesh parent entity to direct
 /// bevy mesh child entities (ie: glTF primitive).
 pub struct MorphPlugin;
@@ -221,3 +294,70 @@ impl RenderAsset for RenderMesh {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy_app::{App, PostUpdate};
+    use bevy_ecs::schedule::{IntoSystemConfigs, IntoSystemSetConfigs};
+    use bevy_hierarchy::BuildChildren;
+    use bevy_math::Vec3;
+    use bevy_transform::{
+        components::{GlobalTransform, Transform},
+        TransformPlugin, TransformSystem,
+    };
+
+    use super::{apply_joint_overrides, skinning::JointOverride, SkinningSystems};
+
+    #[test]
+    fn joint_overrides() {
+        let mut app = App::new();
+        app.add_plugins(TransformPlugin)
+            .configure_sets(
+                PostUpdate,
+                SkinningSystems::ApplyJointOverrides.after(TransformSystem::TransformPropagate),
+            )
+            .add_systems(
+                PostUpdate,
+                apply_joint_overrides.in_set(SkinningSystems::ApplyJointOverrides),
+            );
+
+        let overridden = GlobalTransform::from_translation(Vec3::splat(5.0));
+        let mut joint = None;
+        let mut child_joint = None;
+        app.world_mut()
+            .spawn(Transform::from_xyz(1.0, 0.0, 0.0))
+            .with_children(|parent| {
+                joint = Some(
+                    parent
+                        .spawn((
+                            Transform::from_xyz(0.0, 1.0, 0.0),
+                            JointOverride(overridden),
+                        ))
+                        .with_children(|parent| {
+                            child_joint =
+                                Some(parent.spawn(Transform::from_xyz(0.0, 0.0, 1.0)).id());
+                        })
+                        .id(),
+                );
+            });
+        let (joint, child_joint) = (joint.unwrap(), child_joint.unwrap());
+        let global_transform =
+            |app: &App, entity| *app.world().get::<GlobalTransform>(entity).unwrap();
+
+        app.update();
+        assert_eq!(global_transform(&app, joint), overridden);
+        // Children of the joint keep the transform propagated from its own
+        // `Transform`.
+        assert_eq!(
+            global_transform(&app, child_joint),
+            GlobalTransform::from_xyz(1.0, 1.0, 1.0)
+        );
+
+        app.world_mut().entity_mut(joint).remove::<JointOverride>();
+        app.update();
+        assert_eq!(
+            global_transform(&app, joint),
+            GlobalTransform::from_xyz(1.0, 1.0, 0.0)
+        );
+    }
+}
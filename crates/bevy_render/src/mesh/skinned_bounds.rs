@@ -0,0 +1,261 @@
+use bevy_asset::Assets;
+use bevy_ecs::{
+    component::Component,
+    entity::Entity,
+    query::{Changed, Or, Without},
+    system::{Commands, Query, Res},
+};
+use bevy_math::{Affine3A, Vec3, Vec3A};
+use bevy_transform::components::GlobalTransform;
+
+use super::{
+    skinning::{SkinnedMesh, SkinnedMeshInverseBindposes},
+    Mesh, Mesh3d, VertexAttributeValues,
+};
+use crate::{primitives::Aabb, view::NoFrustumCulling};
+
+/// The bounds of the vertices that each joint of a [`SkinnedMesh`] influences,
+/// in the space of the joint.
+///
+/// These are computed once from the mesh and the inverse bindposes by
+/// [`compute_skinned_mesh_bounds`], and then moved by the joints every frame
+/// by [`update_skinned_mesh_aabbs`], so that the [`Aabb`] of the mesh follows
+/// the animation instead of staying at the bind pose. Entities with a
+/// [`NoFrustumCulling`] component don't get one.
+///
+/// Since each vertex is a blend of its position moved by each of its joints,
+/// the union of the moved bounds always encloses the animated mesh, though it
+/// can be somewhat larger than the tightest box.
+#[derive(Component, Clone, Debug, Default)]
+pub struct SkinnedMeshBounds {
+    joint_aabbs: Vec<Option<Aabb>>,
+}
+
+impl SkinnedMeshBounds {
+    /// Computes the bounds of each joint from the positions, joint indices and
+    /// joint weights of `mesh`.
+    ///
+    /// Returns `None` if the mesh doesn't have these attributes in the formats
+    /// that Bevy uses for skinning.
+    pub fn from_mesh(mesh: &Mesh, inverse_bindposes: &SkinnedMeshInverseBindposes) -> Option<Self> {
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            return None;
+        };
+        let Some(VertexAttributeValues::Uint16x4(joint_indices)) =
+            mesh.attribute(Mesh::ATTRIBUTE_JOINT_INDEX)
+        else {
+            return None;
+        };
+        let Some(VertexAttributeValues::Float32x4(joint_weights)) =
+            mesh.attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT)
+        else {
+            return None;
+        };
+
+        let mut joint_extents: Vec<Option<(Vec3, Vec3)>> = vec![None; inverse_bindposes.len()];
+        for ((position, indices), weights) in positions.iter().zip(joint_indices).zip(joint_weights)
+        {
+            let position = Vec3::from_slice(position);
+            for (&joint, &weight) in indices.iter().zip(weights) {
+                let joint = joint as usize;
+                if weight <= 0.0 || joint >= inverse_bindposes.len() {
+                    continue;
+                }
+                let joint_position = inverse_bindposes[joint].transform_point3(position);
+                let extents = joint_extents[joint].get_or_insert((joint_position, joint_position));
+                extents.0 = extents.0.min(joint_position);
+                extents.1 = extents.1.max(joint_position);
+            }
+        }
+
+        Some(Self {
+            joint_aabbs: joint_extents
+                .into_iter()
+                .map(|extents| extents.map(|(min, max)| Aabb::from_min_max(min, max)))
+                .collect(),
+        })
+    }
+
+    /// Returns the bounds of the vertices influenced by the joint at `index`,
+    /// in the space of the joint, or `None` if no vertex is weighted to it.
+    pub fn joint_aabb(&self, index: usize) -> Option<&Aabb> {
+        self.joint_aabbs.get(index)?.as_ref()
+    }
+
+    /// Returns the bounds of the mesh in its own space, given the transform from
+    /// the space of each joint to the space of the mesh.
+    ///
+    /// Joints without a transform are skipped, so `None` is returned if none of
+    /// the joints that influence vertices have one.
+    pub fn compute_aabb(
+        &self,
+        mut mesh_from_joint: impl FnMut(usize) -> Option<Affine3A>,
+    ) -> Option<Aabb> {
+        let mut extents: Option<(Vec3A, Vec3A)> = None;
+        for (index, joint_aabb) in self.joint_aabbs.iter().enumerate() {
+            let Some(joint_aabb) = joint_aabb else {
+                continue;
+            };
+            let Some(transform) = mesh_from_joint(index) else {
+                continue;
+            };
+            let center = transform.transform_point3a(joint_aabb.center);
+            let matrix = transform.matrix3;
+            let half_extents = matrix.x_axis.abs() * joint_aabb.half_extents.x
+                + matrix.y_axis.abs() * joint_aabb.half_extents.y
+                + matrix.z_axis.abs() * joint_aabb.half_extents.z;
+            let (min, max) = extents.get_or_insert((center, center));
+            *min = min.min(center - half_extents);
+            *max = max.max(center + half_extents);
+        }
+        extents.map(|(min, max)| Aabb::from_min_max(min.into(), max.into()))
+    }
+}
+
+/// Adds a [`SkinnedMeshBounds`] component to the entities with a
+/// [`SkinnedMesh`] and a [`Mesh3d`], and recomputes it when either of them
+/// changes, including when the mesh asset is modified.
+///
+/// If the assets aren't loaded yet, the component is removed so that the
+/// bounds are computed once they are.
+pub fn compute_skinned_mesh_bounds(
+    mut commands: Commands,
+    meshes: Res<Assets<Mesh>>,
+    inverse_bindposes: Res<Assets<SkinnedMeshInverseBindposes>>,
+    skinned_meshes: Query<
+        (Entity, &Mesh3d, &SkinnedMesh, Option<&SkinnedMeshBounds>),
+        (
+            Without<NoFrustumCulling>,
+            Or<(
+                Changed<Mesh3d>,
+                Changed<SkinnedMesh>,
+                Without<SkinnedMeshBounds>,
+            )>,
+        ),
+    >,
+) {
+    for (entity, mesh_handle, skinned_mesh, bounds) in &skinned_meshes {
+        let new_bounds = meshes
+            .get(mesh_handle)
+            .zip(inverse_bindposes.get(&skinned_mesh.inverse_bindposes))
+            .and_then(|(mesh, inverse_bindposes)| {
+                SkinnedMeshBounds::from_mesh(mesh, inverse_bindposes)
+            });
+        match new_bounds {
+            Some(new_bounds) => {
+                commands.entity(entity).try_insert(new_bounds);
+            }
+            None if bounds.is_some() => {
+                commands.entity(entity).remove::<SkinnedMeshBounds>();
+            }
+            None => {}
+        }
+    }
+}
+
+/// Updates the [`Aabb`] of the entities with a [`SkinnedMeshBounds`] from the
+/// current [`GlobalTransform`]s of their joints.
+///
+/// This system runs in [`VisibilitySystems::CalculateBounds`], after
+/// [`SkinningSystems::ApplyJointOverrides`], so frustum culling sees the mesh
+/// in the pose that it's rendered in.
+///
+/// [`VisibilitySystems::CalculateBounds`]: crate::view::VisibilitySystems::CalculateBounds
+/// [`SkinningSystems::ApplyJointOverrides`]: super::SkinningSystems::ApplyJointOverrides
+pub fn update_skinned_mesh_aabbs(
+    mut commands: Commands,
+    mut skinned_meshes: Query<
+        (
+            Entity,
+            &SkinnedMesh,
+            &SkinnedMeshBounds,
+            &GlobalTransform,
+            Option<&mut Aabb>,
+        ),
+        Without<NoFrustumCulling>,
+    >,
+    joints: Query<&GlobalTransform>,
+) {
+    for (entity, skinned_mesh, bounds, mesh_transform, aabb) in &mut skinned_meshes {
+        let mesh_from_world = mesh_transform.affine().inverse();
+        let Some(new_aabb) = bounds.compute_aabb(|index| {
+            let joint = *skinned_mesh.joints.get(index)?;
+            Some(mesh_from_world * joints.get(joint).ok()?.affine())
+        }) else {
+            continue;
+        };
+
+        match aabb {
+            Some(mut aabb) => {
+                if *aabb != new_aabb {
+                    *aabb = new_aabb;
+                }
+            }
+            None => {
+                commands.entity(entity).try_insert(new_aabb);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_asset::RenderAssetUsages;
+    use bevy_math::{Affine3A, Mat4, Vec3, Vec3A};
+
+    use super::SkinnedMeshBounds;
+    use crate::mesh::{
+        skinning::SkinnedMeshInverseBindposes, Mesh, PrimitiveTopology, VertexAttributeValues,
+    };
+
+    #[test]
+    fn joint_bounds_follow_joints() {
+        // Two vertices on each of two joints, the second of which is bound one
+        // unit up.
+        let mesh = Mesh::new(PrimitiveTopology::PointList, RenderAssetUsages::default())
+            .with_inserted_attribute(
+                Mesh::ATTRIBUTE_POSITION,
+                vec![
+                    [-1.0, 0.0, 0.0],
+                    [1.0, 0.0, 0.0],
+                    [-1.0, 1.0, 0.0],
+                    [1.0, 1.0, 0.0],
+                ],
+            )
+            .with_inserted_attribute(
+                Mesh::ATTRIBUTE_JOINT_INDEX,
+                VertexAttributeValues::Uint16x4(vec![
+                    [0, 0, 0, 0],
+                    [0, 0, 0, 0],
+                    [1, 0, 0, 0],
+                    [1, 0, 0, 0],
+                ]),
+            )
+            .with_inserted_attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT, vec![[1.0, 0.0, 0.0, 0.0]; 4]);
+        let inverse_bindposes = SkinnedMeshInverseBindposes::from(vec![
+            Mat4::IDENTITY,
+            Mat4::from_translation(-Vec3::Y),
+        ]);
+
+        let bounds = SkinnedMeshBounds::from_mesh(&mesh, &inverse_bindposes).unwrap();
+        let second_joint = bounds.joint_aabb(1).unwrap();
+        assert_eq!(second_joint.min(), Vec3A::new(-1.0, 0.0, 0.0));
+        assert_eq!(second_joint.max(), Vec3A::new(1.0, 0.0, 0.0));
+
+        // Bind pose.
+        let aabb = bounds
+            .compute_aabb(|index| Some(Affine3A::from_translation(Vec3::Y * index as f32)))
+            .unwrap();
+        assert_eq!(aabb.min(), Vec3A::new(-1.0, 0.0, 0.0));
+        assert_eq!(aabb.max(), Vec3A::new(1.0, 1.0, 0.0));
+
+        // The second joint moved far away from the first one.
+        let aabb = bounds
+            .compute_aabb(|index| Some(Affine3A::from_translation(Vec3::Y * 10.0 * index as f32)))
+            .unwrap();
+        assert_eq!(aabb.min(), Vec3A::new(-1.0, 0.0, 0.0));
+        assert_eq!(aabb.max(), Vec3A::new(1.0, 10.0, 0.0));
+    }
+}
@@ -16,7 +16,7 @@ use super::InternedRenderLabel;
 ///
 /// [`RenderGraph::add_node_edge`]: crate::render_graph::RenderGraph::add_node_edge
 /// [`RenderGraph::add_slot_edge`]: crate::render_graph::RenderGraph::add_slot_edge
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub enum Edge {
     /// An edge describing to ordering of both nodes (`output_node` before `input_node`)
     /// and connecting the output slot at the `output_index` of the `output_node`
@@ -671,8 +671,9 @@ impl Node for GraphInputNode {
 mod tests {
     use crate::{
         render_graph::{
-            node::IntoRenderNodeArray, Edge, InternedRenderLabel, Node, NodeRunError, RenderGraph,
-            RenderGraphContext, RenderGraphError, RenderLabel, SlotInfo, SlotType,
+            node::IntoRenderNodeArray, Edge, InternedRenderLabel, Node, NodeRunError, NodeState,
+            RenderGraph, RenderGraphContext, RenderGraphError, RenderLabel,
+            SkippedRenderGraphNodes, SlotInfo, SlotType,
         },
         renderer::RenderContext,
     };
@@ -914,4 +915,18 @@ mod tests {
             "B -> C"
         );
     }
+
+    #[test]
+    fn skip_nodes_without_outputs() {
+        let skipped = SkippedRenderGraphNodes(
+            [TestLabel::A, TestLabel::B]
+                .map(RenderLabel::intern)
+                .into_iter()
+                .collect(),
+        );
+        assert!(skipped.skips(&NodeState::new(TestLabel::A.intern(), TestNode::new(1, 0))));
+        // Nodes with outputs run anyway.
+        assert!(!skipped.skips(&NodeState::new(TestLabel::B.intern(), TestNode::new(0, 1))));
+        assert!(!skipped.skips(&NodeState::new(TestLabel::C.intern(), TestNode::new(0, 0))));
+    }
 }
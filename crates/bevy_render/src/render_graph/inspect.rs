@@ -0,0 +1,414 @@
+use alloc::borrow::Cow;
+use core::{
+    fmt::Write,
+    hash::{BuildHasher, Hash, Hasher},
+    time::Duration,
+};
+use std::sync::Mutex;
+
+use bevy_ecs::{entity::Entity, system::Resource};
+use bevy_math::UVec2;
+use bevy_utils::FixedHasher;
+use wgpu::TextureFormat;
+
+use super::{Edge, InternedRenderLabel, InternedRenderSubGraph, NodeState, RenderGraph, SlotInfo};
+use crate::{
+    sync_world::MainEntity,
+    view::{ViewDepthTexture, ViewTarget},
+};
+
+/// A snapshot of the structure of a [`RenderGraph`] and its sub graphs, returned by
+/// [`RenderGraph::describe`].
+///
+/// Unlike the graph itself, the snapshot can be sent to other threads and worlds, compared, and
+/// exported to the DOT format of Graphviz with [`RenderGraphDescription::to_dot`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RenderGraphDescription {
+    /// The nodes of the graph, sorted by label.
+    pub nodes: Vec<RenderNodeDescription>,
+    /// The edges between the nodes of the graph.
+    pub edges: Vec<RenderEdgeDescription>,
+    /// The sub graphs of the graph, sorted by label.
+    pub sub_graphs: Vec<(InternedRenderSubGraph, RenderGraphDescription)>,
+}
+
+/// A node of a [`RenderGraphDescription`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderNodeDescription {
+    /// The label of the node.
+    pub label: InternedRenderLabel,
+    /// The name of the type that implements [`Node`](super::Node).
+    pub type_name: &'static str,
+    /// The input slots of the node.
+    pub input_slots: Vec<SlotInfo>,
+    /// The output slots of the node.
+    pub output_slots: Vec<SlotInfo>,
+}
+
+/// An edge of a [`RenderGraphDescription`], which makes `output_node` run before `input_node`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RenderEdgeDescription {
+    /// The node that runs first.
+    pub output_node: InternedRenderLabel,
+    /// The node that runs second.
+    pub input_node: InternedRenderLabel,
+    /// For slot edges, the indices of the output slot of `output_node` and of the input slot of
+    /// `input_node` that are connected.
+    pub slots: Option<(usize, usize)>,
+}
+
+impl RenderGraph {
+    /// Returns a hash of the nodes, edges and sub graphs of the graph, which changes whenever
+    /// [`RenderGraph::describe`] would return a different description.
+    ///
+    /// This is much cheaper to compute than the description, so it can be used to only describe
+    /// the graph again when it changed.
+    pub fn structure_hash(&self) -> u64 {
+        // The nodes and sub graphs are stored in hash maps, so their hashes are combined in a way
+        // that doesn't depend on the order they're iterated in.
+        let node_hashes = self.iter_nodes().map(|node| {
+            let mut hasher = FixedHasher.build_hasher();
+            node.label.hash(&mut hasher);
+            node.type_name.hash(&mut hasher);
+            node.input_slots
+                .iter()
+                .for_each(|slot| slot.hash(&mut hasher));
+            node.output_slots
+                .iter()
+                .for_each(|slot| slot.hash(&mut hasher));
+            node.edges.output_edges().hash(&mut hasher);
+            hasher.finish()
+        });
+        let sub_graph_hashes = self.iter_sub_graphs().map(|(label, sub_graph)| {
+            let mut hasher = FixedHasher.build_hasher();
+            label.hash(&mut hasher);
+            sub_graph.structure_hash().hash(&mut hasher);
+            hasher.finish()
+        });
+
+        let mut hasher = FixedHasher.build_hasher();
+        node_hashes.fold(0u64, u64::wrapping_add).hash(&mut hasher);
+        sub_graph_hashes
+            .fold(0u64, u64::wrapping_add)
+            .hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Returns a snapshot of the nodes, edges and sub graphs of the graph.
+    ///
+    /// Describing the graph allocates and sorts all of its nodes, so it shouldn't be done every
+    /// frame: see [`RenderGraph::structure_hash`] to find out when the graph changed.
+    pub fn describe(&self) -> RenderGraphDescription {
+        let mut nodes: Vec<&NodeState> = self.iter_nodes().collect();
+        nodes.sort_by_cached_key(|node| format!("{:?}", node.label));
+
+        let edges = nodes
+            .iter()
+            .flat_map(|node| node.edges.output_edges())
+            .map(|edge| match *edge {
+                Edge::SlotEdge {
+                    input_node,
+                    input_index,
+                    output_node,
+                    output_index,
+                } => RenderEdgeDescription {
+                    output_node,
+                    input_node,
+                    slots: Some((output_index, input_index)),
+                },
+                Edge::NodeEdge {
+                    input_node,
+                    output_node,
+                } => RenderEdgeDescription {
+                    output_node,
+                    input_node,
+                    slots: None,
+                },
+            })
+            .collect();
+
+        let mut sub_graphs: Vec<_> = self
+            .iter_sub_graphs()
+            .map(|(label, sub_graph)| (label, sub_graph.describe()))
+            .collect();
+        sub_graphs.sort_by_cached_key(|(label, _)| format!("{label:?}"));
+
+        RenderGraphDescription {
+            nodes: nodes
+                .into_iter()
+                .map(|node| RenderNodeDescription {
+                    label: node.label,
+                    type_name: node.type_name,
+                    input_slots: node.input_slots.iter().cloned().collect(),
+                    output_slots: node.output_slots.iter().cloned().collect(),
+                })
+                .collect(),
+            edges,
+            sub_graphs,
+        }
+    }
+}
+
+impl RenderGraphDescription {
+    /// Returns the node with the given label, if it's in this graph.
+    ///
+    /// Sub graphs aren't searched.
+    pub fn node(&self, label: InternedRenderLabel) -> Option<&RenderNodeDescription> {
+        self.nodes.iter().find(|node| node.label == label)
+    }
+
+    /// Returns the sub graph with the given label.
+    pub fn sub_graph(&self, label: InternedRenderSubGraph) -> Option<&RenderGraphDescription> {
+        self.sub_graphs
+            .iter()
+            .find(|(sub_graph_label, _)| *sub_graph_label == label)
+            .map(|(_, sub_graph)| sub_graph)
+    }
+
+    /// Returns the graph in the DOT format of Graphviz, with each sub graph in a cluster.
+    ///
+    /// Slot edges are labeled with the names of the slots they connect, and node edges are
+    /// dashed.
+    pub fn to_dot(&self) -> String {
+        let mut dot =
+            String::from("digraph render_graph {\n    rankdir=LR;\n    node [shape=box];\n");
+        self.write_dot(&mut dot, "", 1);
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn write_dot(&self, dot: &mut String, prefix: &str, depth: usize) {
+        let indent = "    ".repeat(depth);
+        let id = |label: InternedRenderLabel| escape(&format!("{prefix}{label:?}"));
+
+        for node in &self.nodes {
+            writeln!(
+                dot,
+                "{indent}\"{}\" [label=\"{:?}\\n{}\"];",
+                id(node.label),
+                node.label,
+                escape(node.type_name)
+            )
+            .unwrap();
+        }
+
+        for edge in &self.edges {
+            let attributes = match edge.slots {
+                Some((output_index, input_index)) => {
+                    let slot_name = |node: InternedRenderLabel, index: usize, output: bool| {
+                        self.node(node)
+                            .and_then(|node| {
+                                let slots = if output {
+                                    &node.output_slots
+                                } else {
+                                    &node.input_slots
+                                };
+                                slots.get(index)
+                            })
+                            .map(|slot| slot.name.clone())
+                            .unwrap_or(Cow::Owned(index.to_string()))
+                    };
+                    format!(
+                        "label=\"{} -> {}\"",
+                        escape(&slot_name(edge.output_node, output_index, true)),
+                        escape(&slot_name(edge.input_node, input_index, false))
+                    )
+                }
+                None => "style=dashed".to_owned(),
+            };
+            writeln!(
+                dot,
+                "{indent}\"{}\" -> \"{}\" [{attributes}];",
+                id(edge.output_node),
+                id(edge.input_node)
+            )
+            .unwrap();
+        }
+
+        for (label, sub_graph) in &self.sub_graphs {
+            let sub_graph_prefix = format!("{prefix}{label:?}/");
+            writeln!(
+                dot,
+                "{indent}subgraph \"cluster_{}\" {{",
+                escape(&sub_graph_prefix)
+            )
+            .unwrap();
+            writeln!(
+                dot,
+                "{indent}    label=\"{}\";",
+                escape(&format!("{label:?}"))
+            )
+            .unwrap();
+            sub_graph.write_dot(dot, &sub_graph_prefix, depth + 1);
+            writeln!(dot, "{indent}}}").unwrap();
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// The textures that the nodes of the sub graph of a view render to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderViewAttachments {
+    /// The render world entity of the view, which is the one that the sub graph is run for.
+    pub view_entity: Entity,
+    /// The main world entity of the camera.
+    pub main_entity: Entity,
+    /// The size of the textures, in pixels.
+    pub size: UVec2,
+    /// The format of the main textures, which most nodes render to.
+    pub main_texture_format: TextureFormat,
+    /// The number of samples of the main textures.
+    pub sample_count: u32,
+    /// The format of the texture that the view is finally written to, such as a window surface.
+    pub out_texture_format: TextureFormat,
+    /// The format of the depth texture, if the view has one.
+    pub depth_texture_format: Option<TextureFormat>,
+}
+
+impl RenderViewAttachments {
+    /// Describes the [`ViewTarget`] and [`ViewDepthTexture`] of a view.
+    pub fn new(
+        view_entity: Entity,
+        main_entity: &MainEntity,
+        target: &ViewTarget,
+        depth_texture: Option<&ViewDepthTexture>,
+    ) -> Self {
+        let size = target.main_texture().size();
+        Self {
+            view_entity,
+            main_entity: main_entity.id(),
+            size: UVec2::new(size.width, size.height),
+            main_texture_format: target.main_texture_format(),
+            sample_count: target
+                .sampled_main_texture()
+                .map_or(1, |texture| texture.sample_count()),
+            out_texture_format: target.out_texture_format(),
+            depth_texture_format: depth_texture.map(|depth_texture| depth_texture.texture.format()),
+        }
+    }
+}
+
+/// The CPU time spent running each node of the [`RenderGraph`] on the last frame.
+///
+/// Insert this resource into the render world to record the timings. Nodes that didn't run on
+/// the last frame, for example because a sub graph wasn't run for any view, have no timing.
+///
+/// The timings only include the time spent in [`Node::run`](super::Node::run): the time spent
+/// by the tasks that some nodes spawn to encode commands in parallel isn't included, nor is the
+/// time spent by the GPU.
+#[derive(Resource, Default)]
+pub struct RenderGraphTimings(pub(crate) Mutex<Vec<RenderNodeTiming>>);
+
+impl RenderGraphTimings {
+    /// Returns the timings of the last frame, in the order that the nodes ran.
+    pub fn last_frame(&self) -> Vec<RenderNodeTiming> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+/// The time spent running a node of the [`RenderGraph`], see [`RenderGraphTimings`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderNodeTiming {
+    /// The sub graph that the node is in, or `None` for the main graph.
+    pub sub_graph: Option<InternedRenderSubGraph>,
+    /// The view that the sub graph was run for.
+    pub view_entity: Option<Entity>,
+    /// The label of the node.
+    pub label: InternedRenderLabel,
+    /// The name of the type that implements [`Node`](super::Node).
+    pub type_name: &'static str,
+    /// The CPU time spent running the node.
+    pub duration: Duration,
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::world::World;
+
+    use crate::{
+        render_graph::{
+            Node, NodeRunError, RenderGraph, RenderGraphContext, RenderLabel, RenderSubGraph,
+        },
+        renderer::RenderContext,
+    };
+
+    #[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+    enum TestLabel {
+        A,
+        B,
+    }
+
+    #[derive(Debug, Hash, PartialEq, Eq, Clone, RenderSubGraph)]
+    struct TestSubGraph;
+
+    struct TestNode;
+
+    impl Node for TestNode {
+        fn run(
+            &self,
+            _: &mut RenderGraphContext,
+            _: &mut RenderContext,
+            _: &World,
+        ) -> Result<(), NodeRunError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn describe_graph() {
+        let mut sub_graph = RenderGraph::default();
+        sub_graph.add_node(TestLabel::A, TestNode);
+
+        let mut graph = RenderGraph::default();
+        graph.add_node(TestLabel::B, TestNode);
+        graph.add_node(TestLabel::A, TestNode);
+        graph.add_node_edge(TestLabel::A, TestLabel::B);
+        graph.add_sub_graph(TestSubGraph, sub_graph);
+
+        let description = graph.describe();
+        assert_eq!(description.nodes.len(), 2);
+        assert_eq!(description.nodes[0].label, TestLabel::A.intern());
+        assert_eq!(description.edges.len(), 1);
+        assert_eq!(description.edges[0].output_node, TestLabel::A.intern());
+        assert_eq!(description.edges[0].input_node, TestLabel::B.intern());
+        assert!(description
+            .sub_graph(TestSubGraph.intern())
+            .unwrap()
+            .node(TestLabel::A.intern())
+            .is_some());
+
+        let dot = description.to_dot();
+        assert!(dot.contains("\"A\" -> \"B\" [style=dashed];"));
+        assert!(dot.contains("subgraph \"cluster_TestSubGraph/\""));
+        assert!(dot.contains("\"TestSubGraph/A\""));
+    }
+
+    #[test]
+    fn structure_hash() {
+        let mut graph = RenderGraph::default();
+        graph.add_node(TestLabel::A, TestNode);
+        graph.add_node(TestLabel::B, TestNode);
+        let unconnected = graph.structure_hash();
+        assert_eq!(graph.structure_hash(), unconnected);
+
+        graph.add_node_edge(TestLabel::A, TestLabel::B);
+        let connected = graph.structure_hash();
+        assert_ne!(connected, unconnected);
+
+        graph.add_sub_graph(TestSubGraph, RenderGraph::default());
+        let with_sub_graph = graph.structure_hash();
+        assert_ne!(with_sub_graph, connected);
+
+        // Changes in the sub graphs change the hash too.
+        graph
+            .sub_graph_mut(TestSubGraph)
+            .add_node(TestLabel::A, TestNode);
+        assert_ne!(graph.structure_hash(), with_sub_graph);
+
+        graph.remove_sub_graph(TestSubGraph);
+        assert_eq!(graph.structure_hash(), connected);
+    }
+}
@@ -6,13 +6,16 @@ use crate::{
     render_phase::DrawError,
     renderer::RenderContext,
 };
+use bevy_derive::{Deref, DerefMut};
 pub use bevy_ecs::label::DynEq;
 use bevy_ecs::{
+    component::Component,
     define_label,
     intern::Interned,
     query::{QueryItem, QueryState, ReadOnlyQueryData},
     world::{FromWorld, World},
 };
+use bevy_utils::{once, HashSet};
 use core::fmt::Debug;
 use downcast_rs::{impl_downcast, Downcast};
 use thiserror::Error;
@@ -415,3 +418,30 @@ where
         Ok(())
     }
 }
+
+/// The nodes of the render graph that don't run for a view.
+///
+/// When this is on a view entity of the render world, the nodes it lists are
+/// skipped whenever a sub graph runs for that view, and the nodes that depend
+/// on them run as if they did nothing. Nodes with output slots can't be
+/// skipped, since their outputs would be missing: they run anyway, with a
+/// warning.
+#[derive(Component, Clone, Debug, Default, Deref, DerefMut)]
+pub struct SkippedRenderGraphNodes(pub HashSet<InternedRenderLabel>);
+
+impl SkippedRenderGraphNodes {
+    /// Returns true if the node is listed and can be skipped.
+    pub fn skips(&self, node_state: &NodeState) -> bool {
+        if !self.contains(&node_state.label) {
+            return false;
+        }
+        if !node_state.output_slots.is_empty() {
+            once!(tracing::warn!(
+                "The render graph node {:?} can't be skipped, as it has output slots",
+                node_state.label
+            ));
+            return false;
+        }
+        true
+    }
+}
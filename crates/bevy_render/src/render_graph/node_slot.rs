@@ -40,7 +40,7 @@ impl SlotValue {
 /// the render [`Nodes`](super::Node).
 ///
 /// This should not be confused with [`SlotValue`], which actually contains the passed data.
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum SlotType {
     /// A GPU-accessible [`Buffer`].
     Buffer,
@@ -92,7 +92,7 @@ impl From<&'static str> for SlotLabel {
 }
 
 /// The internal representation of a slot, which specifies its [`SlotType`] and name.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct SlotInfo {
     pub name: Cow<'static, str>,
     pub slot_type: SlotType,
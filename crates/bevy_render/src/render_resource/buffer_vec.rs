@@ -161,6 +161,17 @@ impl<T: NoUninit> RawBufferVec<T> {
         }
     }
 
+    /// Drops the [`Buffer`] on the [`RenderDevice`], so that the next
+    /// [`reserve`](RawBufferVec::reserve) creates one with exactly the requested capacity.
+    ///
+    /// Since [`reserve`](RawBufferVec::reserve) never shrinks the buffer, this can be used to
+    /// give memory back after the number of elements decreased. The values in system RAM are
+    /// kept, but have to be written to the new buffer again.
+    pub fn discard_buffer(&mut self) {
+        self.buffer = None;
+        self.capacity = 0;
+    }
+
     /// Queues writing of data from system RAM to VRAM using the [`RenderDevice`]
     /// and the provided [`RenderQueue`].
     ///
@@ -58,6 +58,14 @@ pub use wgpu::{
     VertexState as RawVertexState, VertexStepMode, COPY_BUFFER_ALIGNMENT,
 };
 
+pub use wgpu::ray_tracing::{
+    AccelerationStructureFlags, AccelerationStructureGeometryFlags,
+    AccelerationStructureUpdateMode, Blas, BlasBuildEntry, BlasGeometries,
+    BlasGeometrySizeDescriptors, BlasTriangleGeometry, BlasTriangleGeometrySizeDescriptor,
+    CommandEncoderRayTracing, CreateBlasDescriptor, CreateTlasDescriptor, DeviceRayTracing, Tlas,
+    TlasInstance, TlasPackage,
+};
+
 pub use crate::mesh::VertexBufferLayout;
 
 pub mod encase {
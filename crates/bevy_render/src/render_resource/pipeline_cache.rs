@@ -2,7 +2,7 @@ use crate::renderer::WgpuWrapper;
 use crate::{
     render_resource::*,
     renderer::{RenderAdapter, RenderDevice},
-    Extract,
+    Extract, ERROR_FALLBACK_SHADER_HANDLE,
 };
 use alloc::{borrow::Cow, sync::Arc};
 use bevy_asset::{AssetEvent, AssetId, Assets};
@@ -454,6 +454,19 @@ pub struct PipelineCache {
     /// If `true`, disables asynchronous pipeline compilation.
     /// This has no effect on macOS, wasm, or without the `multi_threaded` feature.
     synchronous_pipeline_compilation: bool,
+    errors: HashMap<CachedPipelineId, PipelineError>,
+    error_fallback: bool,
+}
+
+/// The error of a pipeline whose shaders failed to compile.
+///
+/// See [`PipelineCache::pipeline_errors()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PipelineError {
+    /// The label of the pipeline descriptor.
+    pub label: Option<Cow<'static, str>>,
+    /// The error message, which points into the shader source when possible.
+    pub message: String,
 }
 
 impl PipelineCache {
@@ -481,9 +494,29 @@ impl PipelineCache {
             new_pipelines: default(),
             pipelines: default(),
             synchronous_pipeline_compilation,
+            errors: default(),
+            error_fallback: false,
         }
     }
 
+    /// Returns the errors of the pipelines whose shaders failed to compile.
+    ///
+    /// An error is kept until one of the shaders of the pipeline is modified,
+    /// for example by hot reloading, and the pipeline is created again.
+    pub fn pipeline_errors(&self) -> impl Iterator<Item = &PipelineError> {
+        self.errors.values()
+    }
+
+    /// Sets whether render pipelines whose shaders fail to compile are replaced
+    /// by pipelines that draw in magenta, instead of not being available.
+    ///
+    /// The replacement keeps the vertex stage and the targets of the original
+    /// pipeline, so it only works when the error is in a fragment shader.
+    /// Defaults to `false`.
+    pub fn set_error_fallback(&mut self, enabled: bool) {
+        self.error_fallback = enabled;
+    }
+
     /// Get the state of a cached render pipeline.
     ///
     /// See [`PipelineCache::queue_render_pipeline()`].
@@ -858,6 +891,7 @@ impl PipelineCache {
     fn process_pipeline(&mut self, cached_pipeline: &mut CachedPipeline, id: usize) {
         match &mut cached_pipeline.state {
             CachedPipelineState::Queued => {
+                self.errors.remove(&id);
                 cached_pipeline.state = match &cached_pipeline.descriptor {
                     PipelineDescriptor::RenderPipelineDescriptor(descriptor) => {
                         self.start_create_render_pipeline(id, *descriptor.clone())
@@ -879,25 +913,58 @@ impl PipelineCache {
                 }
             }
 
-            CachedPipelineState::Err(err) => match err {
-                // Retry
-                PipelineCacheError::ShaderNotLoaded(_)
-                | PipelineCacheError::ShaderImportNotYetAvailable => {
-                    cached_pipeline.state = CachedPipelineState::Queued;
-                }
-
-                // Shader could not be processed ... retrying won't help
-                PipelineCacheError::ProcessShaderError(err) => {
-                    let error_detail =
-                        err.emit_to_string(&self.shader_cache.lock().unwrap().composer);
-                    error!("failed to process shader:\n{}", error_detail);
+            CachedPipelineState::Err(err) => {
+                // An error was already reported, and the fallback failed too
+                if self.errors.contains_key(&id) {
                     return;
                 }
-                PipelineCacheError::CreateShaderModule(description) => {
-                    error!("failed to create shader module: {}", description);
+
+                let message = match err {
+                    // Retry
+                    PipelineCacheError::ShaderNotLoaded(_)
+                    | PipelineCacheError::ShaderImportNotYetAvailable => {
+                        cached_pipeline.state = CachedPipelineState::Queued;
+                        self.waiting_pipelines.insert(id);
+                        return;
+                    }
+
+                    // Shader could not be processed ... retrying won't help
+                    PipelineCacheError::ProcessShaderError(err) => {
+                        let error_detail =
+                            err.emit_to_string(&self.shader_cache.lock().unwrap().composer);
+                        error!("failed to process shader:\n{}", error_detail);
+                        error_detail
+                    }
+                    PipelineCacheError::CreateShaderModule(description) => {
+                        error!("failed to create shader module: {}", description);
+                        description.clone()
+                    }
+                };
+
+                let label = match &cached_pipeline.descriptor {
+                    PipelineDescriptor::RenderPipelineDescriptor(descriptor) => {
+                        descriptor.label.clone()
+                    }
+                    PipelineDescriptor::ComputePipelineDescriptor(descriptor) => {
+                        descriptor.label.clone()
+                    }
+                };
+                self.errors.insert(id, PipelineError { label, message });
+
+                let fallback_descriptor = match &cached_pipeline.descriptor {
+                    PipelineDescriptor::RenderPipelineDescriptor(descriptor)
+                        if self.error_fallback =>
+                    {
+                        error_fallback_descriptor(descriptor)
+                    }
+                    _ => None,
+                };
+                // Keep the original error if there's no fallback.
+                let Some(fallback_descriptor) = fallback_descriptor else {
                     return;
-                }
-            },
+                };
+                cached_pipeline.state = self.start_create_render_pipeline(id, fallback_descriptor);
+            }
 
             CachedPipelineState::Ok(_) => return,
         }
@@ -937,6 +1004,49 @@ impl PipelineCache {
     }
 }
 
+/// Returns a copy of `descriptor` whose fragment shader draws in magenta to the
+/// first color target, and leaves the others untouched.
+///
+/// Returns `None` if the pipeline has no fragment shader, or if any of its
+/// color targets doesn't have a float format, which the fallback shader can't
+/// write to.
+fn error_fallback_descriptor(
+    descriptor: &RenderPipelineDescriptor,
+) -> Option<RenderPipelineDescriptor> {
+    let all_float_targets = descriptor
+        .fragment
+        .as_ref()?
+        .targets
+        .iter()
+        .flatten()
+        .all(|target| {
+            matches!(
+                target.format.sample_type(None, None),
+                Some(TextureSampleType::Float { .. })
+            )
+        });
+    if !all_float_targets {
+        return None;
+    }
+
+    let mut descriptor = descriptor.clone();
+    let fragment = descriptor.fragment.as_mut()?;
+    fragment.shader = ERROR_FALLBACK_SHADER_HANDLE;
+    fragment.shader_defs = Vec::new();
+    fragment.entry_point = "fragment".into();
+    for (index, target) in fragment.targets.iter_mut().enumerate() {
+        if let Some(target) = target {
+            target.blend = None;
+            target.write_mask = if index == 0 {
+                ColorWrites::ALL
+            } else {
+                ColorWrites::empty()
+            };
+        }
+    }
+    Some(descriptor)
+}
+
 #[cfg(all(
     not(target_arch = "wasm32"),
     not(target_os = "macos"),
@@ -1067,6 +1177,91 @@ fn get_capabilities(features: Features, downlevel: DownlevelFlags) -> Capabiliti
         Capabilities::SUBGROUP_VERTEX_STAGE,
         features.contains(Features::SUBGROUP_VERTEX),
     );
+    capabilities.set(
+        Capabilities::RAY_QUERY,
+        features.contains(Features::RAY_QUERY),
+    );
 
     capabilities
 }
+
+#[cfg(test)]
+mod tests {
+    use super::error_fallback_descriptor;
+    use crate::render_resource::{
+        BlendState, ColorTargetState, ColorWrites, FragmentState, RenderPipelineDescriptor, Shader,
+        TextureFormat, VertexState,
+    };
+    use bevy_asset::Handle;
+    use bevy_utils::default;
+
+    fn descriptor(formats: &[TextureFormat]) -> RenderPipelineDescriptor {
+        RenderPipelineDescriptor {
+            label: None,
+            layout: vec![],
+            push_constant_ranges: vec![],
+            vertex: VertexState {
+                shader: Handle::<Shader>::default(),
+                shader_defs: vec![],
+                entry_point: "vertex".into(),
+                buffers: vec![],
+            },
+            primitive: default(),
+            depth_stencil: None,
+            multisample: default(),
+            fragment: Some(FragmentState {
+                shader: Handle::<Shader>::default(),
+                shader_defs: vec!["SOME_DEF".into()],
+                entry_point: "main".into(),
+                targets: formats
+                    .iter()
+                    .map(|&format| {
+                        Some(ColorTargetState {
+                            format,
+                            blend: Some(BlendState::ALPHA_BLENDING),
+                            write_mask: ColorWrites::ALL,
+                        })
+                    })
+                    .collect(),
+            }),
+            zero_initialize_workgroup_memory: false,
+        }
+    }
+
+    #[test]
+    fn error_fallback_float_targets() {
+        let fallback = error_fallback_descriptor(&descriptor(&[
+            TextureFormat::Rgba16Float,
+            TextureFormat::Rgba8Unorm,
+        ]))
+        .unwrap();
+        let fragment = fallback.fragment.unwrap();
+        assert!(fragment.shader_defs.is_empty());
+        assert_eq!(fragment.entry_point, "fragment");
+        let write_masks: Vec<_> = fragment
+            .targets
+            .iter()
+            .map(|target| target.as_ref().unwrap().write_mask)
+            .collect();
+        assert_eq!(write_masks, [ColorWrites::ALL, ColorWrites::empty()]);
+        assert!(fragment
+            .targets
+            .iter()
+            .all(|target| target.as_ref().unwrap().blend.is_none()));
+    }
+
+    #[test]
+    fn error_fallback_integer_targets() {
+        // The fallback shader can't write to integer targets, so the original
+        // error is kept.
+        assert!(error_fallback_descriptor(&descriptor(&[
+            TextureFormat::Rgba16Float,
+            TextureFormat::R32Uint,
+        ]))
+        .is_none());
+
+        let mut without_fragment = descriptor(&[]);
+        without_fragment.fragment = None;
+        assert!(error_fallback_descriptor(&without_fragment).is_none());
+    }
+}
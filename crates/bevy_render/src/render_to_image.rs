@@ -0,0 +1,246 @@
+//! Rendering cameras into images, and reading each rendered frame back to the CPU.
+//!
+//! This is how an app renders without a window, for example to generate thumbnails on a server,
+//! or to compare frames against reference images in automated visual tests.
+
+use crate::{
+    camera::{Camera, CameraUpdateSystem, RenderTarget},
+    gpu_readback::{align_byte_size, Readback, ReadbackComplete},
+    render_asset::RenderAssetUsages,
+    render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
+};
+use bevy_app::{App, Plugin, PostUpdate};
+use bevy_asset::{Assets, Handle};
+use bevy_ecs::{
+    component::Component,
+    entity::Entity,
+    event::{Event, EventWriter},
+    observer::Trigger,
+    removal_detection::RemovedComponents,
+    schedule::IntoSystemConfigs,
+    system::{Commands, Query, Res},
+};
+use bevy_image::{BevyDefault, Image, TextureFormatPixelInfo};
+use bevy_math::UVec2;
+use tracing::warn;
+
+/// Adds support for the [`RenderToImage`] component, which renders a camera into an image and
+/// reads each rendered frame back to the CPU as an [`ImageRendered`] event.
+///
+/// To render without a window, disable the `WinitPlugin`, set the primary window of the
+/// `WindowPlugin` to [`None`] and its exit condition to `ExitCondition::DontExit`, and run the app
+/// with the `ScheduleRunnerPlugin`. Rendering doesn't need a display then, but it still needs a
+/// GPU, or a software renderer such as the ones provided by Mesa or WARP.
+///
+/// The first frames usually don't show the whole scene, because the meshes, textures and shaders
+/// are loaded and compiled in the background.
+pub struct RenderToImagePlugin;
+
+impl Plugin for RenderToImagePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ImageRendered>()
+            .add_systems(PostUpdate, target_render_images.before(CameraUpdateSystem))
+            .add_observer(receive_rendered_images);
+    }
+}
+
+/// Renders the [`Camera`] of this entity into [`RenderToImage::image`], and sends each rendered
+/// frame as an [`ImageRendered`] event, with the [`RenderToImagePlugin`].
+///
+/// This replaces the [`Camera::target`]. The image must be usable as a render target that can be
+/// copied from, and must be kept in the main world, as the one created by
+/// [`RenderToImage::create_image`] is.
+///
+/// The frames are read back asynchronously, so each one is received a few frames after it was
+/// rendered.
+#[derive(Component, Clone, Debug)]
+pub struct RenderToImage {
+    /// The image that the camera renders into.
+    pub image: Handle<Image>,
+    frames: u32,
+}
+
+impl RenderToImage {
+    /// Renders into the given image.
+    pub fn new(image: Handle<Image>) -> Self {
+        Self { image, frames: 0 }
+    }
+
+    /// Renders into a new image of the given size, in the default texture format.
+    pub fn create_image(size: UVec2, images: &mut Assets<Image>) -> Self {
+        let mut image = Image::new_fill(
+            Extent3d {
+                width: size.x,
+                height: size.y,
+                ..Default::default()
+            },
+            TextureDimension::D2,
+            &[0; 4],
+            TextureFormat::bevy_default(),
+            RenderAssetUsages::default(),
+        );
+        image.texture_descriptor.usage |= TextureUsages::RENDER_ATTACHMENT
+            | TextureUsages::COPY_SRC
+            | TextureUsages::TEXTURE_BINDING;
+        Self::new(images.add(image))
+    }
+
+    /// The number of frames received so far.
+    pub fn frames(&self) -> u32 {
+        self.frames
+    }
+}
+
+/// A frame rendered by a camera with a [`RenderToImage`] component.
+#[derive(Event, Clone, Debug)]
+pub struct ImageRendered {
+    /// The entity of the camera.
+    pub camera: Entity,
+    /// The index of the frame among the frames received from this camera, starting at `0`.
+    pub frame: u32,
+    /// The rendered frame, with the size and texture format of [`RenderToImage::image`].
+    pub image: Image,
+}
+
+/// Targets the [`RenderToImage::image`] of each camera, and starts reading it back.
+fn target_render_images(
+    mut commands: Commands,
+    mut cameras: Query<(Entity, &RenderToImage, &mut Camera)>,
+    mut removed: RemovedComponents<RenderToImage>,
+) {
+    for entity in removed.read() {
+        if let Some(mut entity) = commands.get_entity(entity) {
+            entity.remove::<Readback>();
+        }
+    }
+    for (entity, render_to_image, mut camera) in &mut cameras {
+        let is_targeted = matches!(
+            &camera.target,
+            RenderTarget::Image(target) if target.handle == render_to_image.image
+        );
+        if !is_targeted {
+            camera.target = render_to_image.image.clone().into();
+            commands
+                .entity(entity)
+                .insert(Readback::texture(render_to_image.image.clone()));
+        }
+    }
+}
+
+/// Turns the bytes read back from the images of the cameras into [`ImageRendered`] events.
+fn receive_rendered_images(
+    trigger: Trigger<ReadbackComplete>,
+    mut cameras: Query<&mut RenderToImage>,
+    images: Res<Assets<Image>>,
+    mut rendered: EventWriter<ImageRendered>,
+) {
+    let camera = trigger.target();
+    let Ok(mut render_to_image) = cameras.get_mut(camera) else {
+        return;
+    };
+    let Some(target) = images.get(&render_to_image.image) else {
+        warn!(
+            "The image that {camera} renders into isn't in the main world, so its frames can't \
+             be received"
+        );
+        return;
+    };
+
+    let size = target.texture_descriptor.size;
+    let format = target.texture_descriptor.format;
+    let Some(data) = unpad_rows(trigger.event(), size, format) else {
+        // The image was resized or replaced while this frame was being read back.
+        warn!(
+            "Skipping a frame of {camera} that was read back from an image of another size than \
+             its current image"
+        );
+        return;
+    };
+
+    rendered.send(ImageRendered {
+        camera,
+        frame: render_to_image.frames,
+        image: Image::new(
+            size,
+            TextureDimension::D2,
+            data,
+            format,
+            RenderAssetUsages::MAIN_WORLD,
+        ),
+    });
+    render_to_image.frames += 1;
+}
+
+/// Removes the padding of the rows of `data`, which were read back from a texture of the given
+/// `size` and `format`, or returns [`None`] if `data` wasn't read back from such a texture.
+fn unpad_rows(data: &[u8], size: Extent3d, format: TextureFormat) -> Option<Vec<u8>> {
+    // The rows of the read back texture are padded to the alignment required for copies.
+    let row_bytes = size.width as usize * format.pixel_size();
+    let padded_row_bytes = align_byte_size(row_bytes as u32) as usize;
+    if data.len() != padded_row_bytes * size.height as usize {
+        return None;
+    }
+    Some(
+        data.chunks(padded_row_bytes)
+            .flat_map(|row| &row[..row_bytes])
+            .copied()
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{receive_rendered_images, unpad_rows, ImageRendered, RenderToImage};
+    use crate::{
+        gpu_readback::ReadbackComplete,
+        render_resource::{Extent3d, TextureFormat},
+    };
+    use bevy_asset::Assets;
+    use bevy_ecs::{event::Events, world::World};
+    use bevy_image::Image;
+    use bevy_math::UVec2;
+
+    #[test]
+    fn rows_are_unpadded() {
+        let size = Extent3d {
+            width: 2,
+            height: 2,
+            depth_or_array_layers: 1,
+        };
+        // Rows of 2 pixels of 4 bytes are padded to 256 bytes.
+        let mut data = vec![0; 512];
+        data[..8].fill(1);
+        data[256..264].fill(2);
+        let unpadded = unpad_rows(&data, size, TextureFormat::Rgba8UnormSrgb).unwrap();
+        assert_eq!(unpadded, [[1; 8], [2; 8]].concat());
+
+        assert!(unpad_rows(&data[..256], size, TextureFormat::Rgba8UnormSrgb).is_none());
+    }
+
+    #[test]
+    fn stale_frames_are_skipped() {
+        let mut world = World::new();
+        world.init_resource::<Assets<Image>>();
+        world.init_resource::<Events<ImageRendered>>();
+        world.add_observer(receive_rendered_images);
+        let render_to_image = RenderToImage::create_image(
+            UVec2::new(2, 2),
+            &mut world.resource_mut::<Assets<Image>>(),
+        );
+        let camera = world.spawn(render_to_image).id();
+
+        // A frame read back before the image was resized to 2x2.
+        world.trigger_targets(ReadbackComplete(vec![0; 256]), camera);
+        world.flush();
+        assert!(world.resource::<Events<ImageRendered>>().is_empty());
+        assert_eq!(world.get::<RenderToImage>(camera).unwrap().frames(), 0);
+
+        world.trigger_targets(ReadbackComplete(vec![0; 512]), camera);
+        world.flush();
+        let events = world.resource::<Events<ImageRendered>>();
+        let rendered = events.iter_current_update_events().next().unwrap();
+        assert_eq!(rendered.frame, 0);
+        assert_eq!(rendered.image.size(), UVec2::new(2, 2));
+        assert_eq!(world.get::<RenderToImage>(camera).unwrap().frames(), 1);
+    }
+}
@@ -1,5 +1,5 @@
 use bevy_ecs::{prelude::Entity, world::World};
-use bevy_utils::HashMap;
+use bevy_utils::{HashMap, Instant};
 #[cfg(feature = "trace")]
 use tracing::info_span;
 
@@ -8,10 +8,14 @@ use smallvec::{smallvec, SmallVec};
 use thiserror::Error;
 
 use crate::{
-    diagnostic::internal::{DiagnosticsRecorder, RenderDiagnosticsMutex},
+    diagnostic::{
+        internal::{DiagnosticsRecorder, RenderDiagnosticsMutex},
+        RecordRenderNodeDiagnostics,
+    },
     render_graph::{
         Edge, InternedRenderLabel, InternedRenderSubGraph, NodeRunError, NodeState, RenderGraph,
-        RenderGraphContext, SlotLabel, SlotType, SlotValue,
+        RenderGraphContext, RenderGraphTimings, RenderNodeTiming, SkippedRenderGraphNodes,
+        SlotLabel, SlotType, SlotValue,
     },
     renderer::{RenderContext, RenderDevice},
 };
@@ -83,7 +87,20 @@ impl RenderGraphRunner {
             adapter.get_info(),
             diagnostics_recorder,
         );
-        Self::run_graph(graph, None, &mut render_context, world, &[], None)?;
+        let timings = world.get_resource::<RenderGraphTimings>();
+        let mut node_timings = timings.map(|_| Vec::new());
+        Self::run_graph(
+            graph,
+            None,
+            &mut render_context,
+            world,
+            &[],
+            None,
+            &mut node_timings,
+        )?;
+        if let (Some(timings), Some(node_timings)) = (timings, node_timings) {
+            *timings.0.lock().unwrap() = node_timings;
+        }
         finalizer(render_context.command_encoder());
 
         let (render_device, mut diagnostics_recorder) = {
@@ -115,6 +132,7 @@ impl RenderGraphRunner {
         world: &'w World,
         inputs: &[SlotValue],
         view_entity: Option<Entity>,
+        node_timings: &mut Option<Vec<RenderNodeTiming>>,
     ) -> Result<(), RenderGraphRunnerError> {
         let mut node_outputs: HashMap<InternedRenderLabel, SmallVec<[SlotValue; 4]>> =
             HashMap::default();
@@ -127,6 +145,11 @@ impl RenderGraphRunner {
         #[cfg(feature = "trace")]
         let _guard = span.enter();
 
+        let node_diagnostics = render_context
+            .diagnostics_recorder
+            .clone()
+            .filter(|_| world.contains_resource::<RecordRenderNodeDiagnostics>());
+
         // Queue up nodes without inputs, which can be run immediately
         let mut node_queue: VecDeque<&NodeState> = graph
             .iter_nodes()
@@ -218,7 +241,10 @@ impl RenderGraphRunner {
 
             let mut outputs: SmallVec<[Option<SlotValue>; 4]> =
                 smallvec![None; node_state.output_slots.len()];
-            {
+            let skipped = view_entity
+                .and_then(|view_entity| world.get::<SkippedRenderGraphNodes>(view_entity))
+                .is_some_and(|skipped_nodes| skipped_nodes.skips(node_state));
+            if !skipped {
                 let mut context = RenderGraphContext::new(graph, node_state, &inputs, &mut outputs);
                 if let Some(view_entity) = view_entity {
                     context.set_view_entity(view_entity);
@@ -228,7 +254,27 @@ impl RenderGraphRunner {
                     #[cfg(feature = "trace")]
                     let _span = info_span!("node", name = node_state.type_name).entered();
 
+                    if let Some(recorder) = &node_diagnostics {
+                        recorder.begin_node_span(
+                            render_context.command_encoder(),
+                            sub_graph,
+                            node_state.label,
+                        );
+                    }
+                    let start = node_timings.is_some().then(Instant::now);
                     node_state.node.run(&mut context, render_context, world)?;
+                    if let Some(recorder) = &node_diagnostics {
+                        recorder.end_node_span(render_context.command_encoder());
+                    }
+                    if let (Some(node_timings), Some(start)) = (node_timings.as_mut(), start) {
+                        node_timings.push(RenderNodeTiming {
+                            sub_graph,
+                            view_entity,
+                            label: node_state.label,
+                            type_name: node_state.type_name,
+                            duration: start.elapsed(),
+                        });
+                    }
                 }
 
                 for run_sub_graph in context.finish() {
@@ -242,6 +288,7 @@ impl RenderGraphRunner {
                         world,
                         &run_sub_graph.inputs,
                         run_sub_graph.view_entity,
+                        node_timings,
                     )?;
                 }
             }
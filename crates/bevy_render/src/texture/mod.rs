@@ -4,11 +4,11 @@ mod texture_attachment;
 mod texture_cache;
 
 pub use crate::render_resource::DefaultImageSampler;
-#[cfg(feature = "basis-universal")]
-use bevy_image::CompressedImageSaver;
 #[cfg(feature = "hdr")]
 use bevy_image::HdrTextureLoader;
 use bevy_image::{CompressedImageFormats, Image, ImageLoader, ImageSamplerDescriptor};
+#[cfg(feature = "basis-universal")]
+use bevy_image::{CompressedImageSaver, TargetPlatform};
 pub use fallback_image::*;
 pub use gpu_image::*;
 pub use texture_attachment::*;
@@ -34,6 +34,12 @@ pub const TRANSPARENT_IMAGE_HANDLE: Handle<Image> =
 pub struct ImagePlugin {
     /// The default image sampler to use when [`bevy_image::ImageSampler`] is set to `Default`.
     pub default_sampler: ImageSamplerDescriptor,
+    /// The platform images using [`CompressionTarget::Platform`] are compressed for, when
+    /// assets are processed.
+    ///
+    /// [`CompressionTarget::Platform`]: bevy_image::CompressionTarget::Platform
+    #[cfg(feature = "basis-universal")]
+    pub compression_platform: TargetPlatform,
 }
 
 impl Default for ImagePlugin {
@@ -47,6 +53,8 @@ impl ImagePlugin {
     pub fn default_linear() -> ImagePlugin {
         ImagePlugin {
             default_sampler: ImageSamplerDescriptor::linear(),
+            #[cfg(feature = "basis-universal")]
+            compression_platform: TargetPlatform::default(),
         }
     }
 
@@ -54,6 +62,8 @@ impl ImagePlugin {
     pub fn default_nearest() -> ImagePlugin {
         ImagePlugin {
             default_sampler: ImageSamplerDescriptor::nearest(),
+            #[cfg(feature = "basis-universal")]
+            compression_platform: TargetPlatform::default(),
         }
     }
 }
@@ -89,12 +99,19 @@ impl Plugin for ImagePlugin {
                 ImageLoader,
                 bevy_asset::transformer::IdentityAssetTransformer<Image>,
                 CompressedImageSaver,
-            >>(CompressedImageSaver.into());
-            processor.set_default_processor::<bevy_asset::processor::LoadTransformAndSave<
-                ImageLoader,
-                bevy_asset::transformer::IdentityAssetTransformer<Image>,
-                CompressedImageSaver,
-            >>("png");
+            >>(
+                CompressedImageSaver {
+                    platform: self.compression_platform,
+                }
+                .into(),
+            );
+            for extension in ["png", "jpg", "jpeg"] {
+                processor.set_default_processor::<bevy_asset::processor::LoadTransformAndSave<
+                    ImageLoader,
+                    bevy_asset::transformer::IdentityAssetTransformer<Image>,
+                    CompressedImageSaver,
+                >>(extension);
+            }
         }
 
         if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
@@ -8,7 +8,8 @@ pub use window::*;
 use crate::{
     camera::{
         CameraMainTextureUsages, ClearColor, ClearColorConfig, Exposure, ExtractedCamera,
-        ManualTextureViews, MipBias, NormalizedRenderTarget, TemporalJitter,
+        MainPassResolutionOverride, ManualTextureViews, MipBias, NormalizedRenderTarget,
+        TemporalJitter,
     },
     extract_component::ExtractComponentPlugin,
     prelude::Shader,
@@ -35,6 +36,7 @@ use bevy_reflect::{std_traits::ReflectDefault, Reflect};
 use bevy_render_macros::ExtractComponent;
 use bevy_transform::components::GlobalTransform;
 use bevy_utils::{hashbrown::hash_map::Entry, HashMap};
+use bevy_window::HdrOutput;
 use core::{
     ops::Range,
     sync::atomic::{AtomicUsize, Ordering},
@@ -589,6 +591,7 @@ pub struct ViewTarget {
     /// This is shared across view targets with the same render target
     main_texture: Arc<AtomicUsize>,
     out_texture: OutputColorAttachment,
+    hdr_output: Option<HdrOutput>,
 }
 
 /// Contains [`OutputColorAttachment`] used for each target present on any view in the current
@@ -814,6 +817,16 @@ impl ViewTarget {
         self.out_texture.format
     }
 
+    /// The HDR output settings of the window this view renders to, if the window is presented in
+    /// HDR.
+    ///
+    /// In that case, the final texture is in [`HdrOutput::format`], so the output of the view must
+    /// be scaled from SDR white to [`HdrOutput::paper_white`] and encoded in that format.
+    #[inline]
+    pub fn hdr_output(&self) -> Option<HdrOutput> {
+        self.hdr_output
+    }
+
     /// This will start a new "post process write", which assumes that the caller
     /// will write the [`PostProcessWrite`]'s `source` to the `destination`.
     ///
@@ -875,6 +888,7 @@ pub fn prepare_view_uniforms(
         Option<&Frustum>,
         Option<&TemporalJitter>,
         Option<&MipBias>,
+        Option<&MainPassResolutionOverride>,
     )>,
 ) {
     let view_iter = views.iter();
@@ -886,8 +900,21 @@ pub fn prepare_view_uniforms(
     else {
         return;
     };
-    for (entity, extracted_camera, extracted_view, frustum, temporal_jitter, mip_bias) in &views {
-        let viewport = extracted_view.viewport.as_vec4();
+    for (
+        entity,
+        extracted_camera,
+        extracted_view,
+        frustum,
+        temporal_jitter,
+        mip_bias,
+        resolution_override,
+    ) in &views
+    {
+        let mut viewport = extracted_view.viewport.as_vec4();
+        if let Some(resolution_override) = resolution_override {
+            viewport.z = resolution_override.0.x as f32;
+            viewport.w = resolution_override.0.y as f32;
+        }
         let unjittered_projection = extracted_view.clip_from_view;
         let mut clip_from_view = unjittered_projection;
 
@@ -984,6 +1011,7 @@ pub fn clear_view_attachments(mut view_target_attachments: ResMut<ViewTargetAtta
 
 pub fn prepare_view_targets(
     mut commands: Commands,
+    windows: Res<ExtractedWindows>,
     clear_color_global: Res<ClearColor>,
     render_device: Res<RenderDevice>,
     mut texture_cache: ResMut<TextureCache>,
@@ -1080,6 +1108,13 @@ pub fn prepare_view_targets(
 
         let converted_clear_color = clear_color.map(Into::into);
 
+        let hdr_output = match target {
+            NormalizedRenderTarget::Window(window_ref) => windows
+                .get(&window_ref.entity())
+                .and_then(ExtractedWindow::hdr_output),
+            _ => None,
+        };
+
         let main_textures = MainTargetTextures {
             a: ColorAttachment::new(a.clone(), sampled.clone(), converted_clear_color),
             b: ColorAttachment::new(b.clone(), sampled.clone(), converted_clear_color),
@@ -1091,6 +1126,7 @@ pub fn prepare_view_targets(
             main_textures,
             main_texture_format,
             out_texture: out_attachment.clone(),
+            hdr_output,
         });
     }
 }
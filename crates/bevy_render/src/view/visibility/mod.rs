@@ -356,6 +356,9 @@ impl Plugin for VisibilityPlugin {
 /// Computes and adds an [`Aabb`] component to entities with a
 /// [`Mesh3d`] component and without a [`NoFrustumCulling`] component.
 ///
+/// The bounds of skinned meshes are then kept up to date with their joints by
+/// [`update_skinned_mesh_aabbs`](crate::mesh::update_skinned_mesh_aabbs).
+///
 /// This system is used in system set [`VisibilitySystems::CalculateBounds`].
 pub fn calculate_bounds(
     mut commands: Commands,
@@ -7,12 +7,14 @@ use bevy_app::{App, Plugin};
 use bevy_ecs::{entity::EntityHashMap, prelude::*};
 use bevy_utils::{default, HashSet};
 use bevy_window::{
-    CompositeAlphaMode, PresentMode, PrimaryWindow, RawHandleWrapper, Window, WindowClosing,
+    CompositeAlphaMode, HdrOutput, HdrOutputFormat, PresentMode, PrimaryWindow, RawHandleWrapper,
+    Window, WindowClosing,
 };
 use core::{
     num::NonZero,
     ops::{Deref, DerefMut},
 };
+use std::sync::{Arc, RwLock};
 use tracing::{debug, warn};
 use wgpu::{
     SurfaceConfiguration, SurfaceTargetUnsafe, TextureFormat, TextureUsages, TextureViewDescriptor,
@@ -28,8 +30,12 @@ impl Plugin for WindowRenderPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(ScreenshotPlugin);
 
+        let hdr_output_support = HdrOutputSupport::default();
+        app.insert_resource(hdr_output_support.clone());
+
         if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
             render_app
+                .insert_resource(hdr_output_support)
                 .init_resource::<ExtractedWindows>()
                 .init_resource::<WindowSurfaces>()
                 .add_systems(ExtractSchedule, extract_windows)
@@ -67,9 +73,47 @@ pub struct ExtractedWindow {
     pub size_changed: bool,
     pub present_mode_changed: bool,
     pub alpha_mode: CompositeAlphaMode,
+    /// The requested HDR output settings, see [`ExtractedWindow::hdr_output`] for whether
+    /// they're in use.
+    pub requested_hdr_output: HdrOutput,
+    pub hdr_output_changed: bool,
+}
+
+/// The format of window surfaces configured for HDR output in extended linear sRGB.
+pub const HDR_SURFACE_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+
+/// The format of window surfaces configured for HDR10 output.
+pub const HDR10_SURFACE_FORMAT: TextureFormat = TextureFormat::Rgb10a2Unorm;
+
+/// Returns the format of the window surfaces presented in the given HDR output format.
+pub fn hdr_output_surface_format(format: HdrOutputFormat) -> TextureFormat {
+    match format {
+        HdrOutputFormat::ExtendedLinearSrgb => HDR_SURFACE_FORMAT,
+        HdrOutputFormat::Hdr10 => HDR10_SURFACE_FORMAT,
+    }
 }
 
 impl ExtractedWindow {
+    /// Returns the HDR output settings of the window if it's presented in HDR, which requires
+    /// HDR output to be enabled and supported by the surface of the window.
+    ///
+    /// [`HdrOutput::format`] is the format the surface is configured with, which differs from the
+    /// requested one if the surface only supports the other HDR format.
+    pub fn hdr_output(&self) -> Option<HdrOutput> {
+        if !self.requested_hdr_output.enabled {
+            return None;
+        }
+        let format = [HdrOutputFormat::ExtendedLinearSrgb, HdrOutputFormat::Hdr10]
+            .into_iter()
+            .find(|&format| {
+                self.swap_chain_texture_format == Some(hdr_output_surface_format(format))
+            })?;
+        Some(HdrOutput {
+            format,
+            ..self.requested_hdr_output
+        })
+    }
+
     fn set_swapchain_texture(&mut self, frame: wgpu::SurfaceTexture) {
         let texture_view_descriptor = TextureViewDescriptor {
             format: Some(frame.texture.format().add_srgb_suffix()),
@@ -108,6 +152,7 @@ fn extract_windows(
     windows: Extract<Query<(Entity, &Window, &RawHandleWrapper, Option<&PrimaryWindow>)>>,
     mut removed: Extract<RemovedComponents<RawHandleWrapper>>,
     mut window_surfaces: ResMut<WindowSurfaces>,
+    hdr_output_support: Res<HdrOutputSupport>,
 ) {
     for (entity, window, handle, primary) in windows.iter() {
         if primary.is_some() {
@@ -132,6 +177,8 @@ fn extract_windows(
             swap_chain_texture_format: None,
             present_mode_changed: false,
             alpha_mode: window.composite_alpha_mode,
+            requested_hdr_output: window.hdr_output,
+            hdr_output_changed: false,
         });
 
         // NOTE: Drop the swap chain frame here
@@ -140,6 +187,11 @@ fn extract_windows(
             || new_height != extracted_window.physical_height;
         extracted_window.present_mode_changed =
             window.present_mode != extracted_window.present_mode;
+        extracted_window.hdr_output_changed = window.hdr_output.enabled
+            != extracted_window.requested_hdr_output.enabled
+            || window.hdr_output.format != extracted_window.requested_hdr_output.format;
+        // The luminances don't need the surface to be reconfigured.
+        extracted_window.requested_hdr_output = window.hdr_output;
 
         if extracted_window.size_changed {
             debug!(
@@ -165,10 +217,12 @@ fn extract_windows(
     for closing_window in closing.read() {
         extracted_windows.remove(&closing_window.window);
         window_surfaces.remove(&closing_window.window);
+        hdr_output_support.remove(closing_window.window);
     }
     for removed_window in removed.read() {
         extracted_windows.remove(&removed_window);
         window_surfaces.remove(&removed_window);
+        hdr_output_support.remove(removed_window);
     }
 }
 
@@ -192,6 +246,38 @@ impl WindowSurfaces {
     }
 }
 
+/// The HDR output formats supported by the surface of each window.
+///
+/// This resource is shared between the main world and the render world, and a window is added to
+/// it once its surface is created.
+#[derive(Resource, Clone, Default)]
+pub struct HdrOutputSupport(Arc<RwLock<EntityHashMap<Vec<HdrOutputFormat>>>>);
+
+impl HdrOutputSupport {
+    /// Returns the HDR output formats supported by the surface of `window`, or `None` if its
+    /// surface isn't created yet.
+    pub fn formats(&self, window: Entity) -> Option<Vec<HdrOutputFormat>> {
+        self.0.read().unwrap().get(&window).cloned()
+    }
+
+    /// Returns whether the surface of `window` supports the given HDR output format.
+    pub fn supports(&self, window: Entity, format: HdrOutputFormat) -> bool {
+        self.0
+            .read()
+            .unwrap()
+            .get(&window)
+            .is_some_and(|formats| formats.contains(&format))
+    }
+
+    fn insert(&self, window: Entity, formats: Vec<HdrOutputFormat>) {
+        self.0.write().unwrap().insert(window, formats);
+    }
+
+    fn remove(&self, window: Entity) {
+        self.0.write().unwrap().remove(&window);
+    }
+}
+
 /// (re)configures window surfaces, and obtains a swapchain texture for rendering.
 ///
 /// NOTE: `get_current_texture` in `prepare_windows` can take a long time if the GPU workload is
@@ -286,6 +372,7 @@ pub fn need_surface_configuration(
         if !window_surfaces.configured_windows.contains(&window.entity)
             || window.size_changed
             || window.present_mode_changed
+            || window.hdr_output_changed
         {
             return true;
         }
@@ -311,6 +398,7 @@ pub fn create_surfaces(
     render_instance: Res<RenderInstance>,
     render_adapter: Res<RenderAdapter>,
     render_device: Res<RenderDevice>,
+    hdr_output_support: Res<HdrOutputSupport>,
 ) {
     for window in windows.windows.values() {
         let data = window_surfaces
@@ -330,20 +418,8 @@ pub fn create_surfaces(
                         .expect("Failed to create wgpu surface")
                 };
                 let caps = surface.get_capabilities(&render_adapter);
-                let formats = caps.formats;
-                // For future HDR output support, we'll need to request a format that supports HDR,
-                // but as of wgpu 0.15 that is not yet supported.
-                // Prefer sRGB formats for surfaces, but fall back to first available format if no sRGB formats are available.
-                let mut format = *formats.first().expect("No supported formats for surface");
-                for available_format in formats {
-                    // Rgba8UnormSrgb and Bgra8UnormSrgb and the only sRGB formats wgpu exposes that we can use for surfaces.
-                    if available_format == TextureFormat::Rgba8UnormSrgb
-                        || available_format == TextureFormat::Bgra8UnormSrgb
-                    {
-                        format = available_format;
-                        break;
-                    }
-                }
+                hdr_output_support.insert(window.entity, supported_hdr_formats(&caps.formats));
+                let format = select_surface_format(&caps.formats, requested_hdr_format(window));
 
                 let configuration = SurfaceConfiguration {
                     format,
@@ -373,11 +449,7 @@ pub fn create_surfaces(
                         }
                         CompositeAlphaMode::Inherit => wgpu::CompositeAlphaMode::Inherit,
                     },
-                    view_formats: if !format.is_srgb() {
-                        vec![format.add_srgb_suffix()]
-                    } else {
-                        vec![]
-                    },
+                    view_formats: surface_view_formats(format),
                 };
 
                 render_device.configure_surface(&surface, &configuration);
@@ -388,6 +460,21 @@ pub fn create_surfaces(
                 }
             });
 
+        if window.hdr_output_changed {
+            let caps = data.surface.get_capabilities(&render_adapter);
+            hdr_output_support.insert(window.entity, supported_hdr_formats(&caps.formats));
+            let format = select_surface_format(&caps.formats, requested_hdr_format(window));
+            if format != data.configuration.format {
+                debug!(
+                    "Window surface format changed from {:?} to {:?}",
+                    data.configuration.format, format
+                );
+                data.configuration.format = format;
+                data.configuration.view_formats = surface_view_formats(format);
+                render_device.configure_surface(&data.surface, &data.configuration);
+            }
+        }
+
         if window.size_changed || window.present_mode_changed {
             data.configuration.width = window.physical_width;
             data.configuration.height = window.physical_height;
@@ -405,3 +492,127 @@ pub fn create_surfaces(
         window_surfaces.configured_windows.insert(window.entity);
     }
 }
+
+fn requested_hdr_format(window: &ExtractedWindow) -> Option<HdrOutputFormat> {
+    window
+        .requested_hdr_output
+        .enabled
+        .then_some(window.requested_hdr_output.format)
+}
+
+/// Returns the HDR output formats that a window surface supporting `formats` can be presented in.
+fn supported_hdr_formats(formats: &[TextureFormat]) -> Vec<HdrOutputFormat> {
+    [HdrOutputFormat::ExtendedLinearSrgb, HdrOutputFormat::Hdr10]
+        .into_iter()
+        .filter(|&format| formats.contains(&hdr_output_surface_format(format)))
+        .collect()
+}
+
+/// Selects the format of a window surface among the formats it supports.
+///
+/// Prefers the surface format of `hdr_output` if it's requested, then the one of the other HDR
+/// format, then sRGB formats, but falls back to the first available format if no sRGB formats are
+/// available.
+fn select_surface_format(
+    formats: &[TextureFormat],
+    hdr_output: Option<HdrOutputFormat>,
+) -> TextureFormat {
+    if let Some(requested_format) = hdr_output {
+        let supported_formats = supported_hdr_formats(formats);
+        if supported_formats.contains(&requested_format) {
+            return hdr_output_surface_format(requested_format);
+        }
+        if let Some(&fallback_format) = supported_formats.first() {
+            warn!(
+                "HDR output in {:?} was requested, but the window surface only supports {:?}",
+                requested_format, fallback_format
+            );
+            return hdr_output_surface_format(fallback_format);
+        }
+        warn!("HDR output was requested, but the window surface doesn't support it");
+    }
+
+    let mut format = *formats.first().expect("No supported formats for surface");
+    for &available_format in formats {
+        // Rgba8UnormSrgb and Bgra8UnormSrgb and the only sRGB formats wgpu exposes that we can use for surfaces.
+        if available_format == TextureFormat::Rgba8UnormSrgb
+            || available_format == TextureFormat::Bgra8UnormSrgb
+        {
+            format = available_format;
+            break;
+        }
+    }
+    format
+}
+
+fn surface_view_formats(format: TextureFormat) -> Vec<TextureFormat> {
+    if format.add_srgb_suffix() != format {
+        vec![format.add_srgb_suffix()]
+    } else {
+        vec![]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn select_sdr_surface_format() {
+        let formats = [TextureFormat::Bgra8Unorm, TextureFormat::Bgra8UnormSrgb];
+        assert_eq!(
+            select_surface_format(&formats, None),
+            TextureFormat::Bgra8UnormSrgb
+        );
+
+        // Without any sRGB format, the first format is used.
+        let formats = [TextureFormat::Rgb10a2Unorm, TextureFormat::Rgba16Float];
+        assert_eq!(
+            select_surface_format(&formats, None),
+            TextureFormat::Rgb10a2Unorm
+        );
+    }
+
+    #[test]
+    fn select_hdr_surface_format() {
+        let formats = [
+            TextureFormat::Bgra8UnormSrgb,
+            TextureFormat::Rgb10a2Unorm,
+            TextureFormat::Rgba16Float,
+        ];
+        assert_eq!(
+            select_surface_format(&formats, Some(HdrOutputFormat::ExtendedLinearSrgb)),
+            HDR_SURFACE_FORMAT
+        );
+        assert_eq!(
+            select_surface_format(&formats, Some(HdrOutputFormat::Hdr10)),
+            HDR10_SURFACE_FORMAT
+        );
+
+        // The other HDR format is used if the requested one isn't supported.
+        let formats = [TextureFormat::Bgra8UnormSrgb, TextureFormat::Rgba16Float];
+        assert_eq!(
+            select_surface_format(&formats, Some(HdrOutputFormat::Hdr10)),
+            HDR_SURFACE_FORMAT
+        );
+
+        // Then SDR formats.
+        let formats = [TextureFormat::Bgra8Unorm, TextureFormat::Bgra8UnormSrgb];
+        assert_eq!(
+            select_surface_format(&formats, Some(HdrOutputFormat::ExtendedLinearSrgb)),
+            TextureFormat::Bgra8UnormSrgb
+        );
+    }
+
+    #[test]
+    fn detect_hdr_formats() {
+        assert_eq!(
+            supported_hdr_formats(&[TextureFormat::Bgra8UnormSrgb]),
+            Vec::new()
+        );
+        assert_eq!(
+            supported_hdr_formats(&[TextureFormat::Rgb10a2Unorm, TextureFormat::Rgba16Float]),
+            vec![HdrOutputFormat::ExtendedLinearSrgb, HdrOutputFormat::Hdr10]
+        );
+    }
+}
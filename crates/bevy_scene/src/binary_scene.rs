@@ -0,0 +1,207 @@
+use crate::{
+    serde::{SceneAssetLoader, SceneDeserializer, SceneSerializer},
+    DynamicScene,
+};
+use bevy_asset::{
+    io::{Reader, Writer},
+    saver::{AssetSaver, SavedAsset},
+    AssetLoader, AsyncWriteExt, LoadContext,
+};
+use bevy_ecs::{
+    reflect::AppTypeRegistry,
+    world::{FromWorld, World},
+};
+use bevy_reflect::{TypeRegistry, TypeRegistryArc};
+use serde::de::DeserializeSeed;
+use thiserror::Error;
+
+/// Unique identifier for the binary scene format (`.bscn`).
+const BINARY_SCENE_MAGIC: [u8; 4] = *b"BSCN";
+
+/// The current version of the binary scene format (`.bscn`).
+pub const BINARY_SCENE_VERSION: u32 = 1;
+
+/// Possible errors that can be produced when serializing or deserializing a binary scene.
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum BinarySceneError {
+    /// An [IO Error](std::io::Error)
+    #[error("Error while trying to read or write the scene file: {0}")]
+    Io(#[from] std::io::Error),
+    /// A [postcard Error](postcard::Error)
+    #[error("Could not serialize or deserialize the binary scene: {0}")]
+    Postcard(#[from] postcard::Error),
+    /// The file doesn't start with the binary scene header.
+    #[error("Not a binary scene")]
+    InvalidMagic,
+    /// The file was written with a version of the binary scene format that isn't supported.
+    #[error("Unsupported binary scene version: {0}")]
+    UnsupportedVersion(u32),
+}
+
+impl DynamicScene {
+    /// Serialize this dynamic scene into the binary Bevy scene format (`.bscn`).
+    ///
+    /// The binary scene format stores the same data as the [RON format](Self::serialize) in the
+    /// compact [postcard] format, which is much faster to load for large scenes. To deserialize
+    /// the scene, use the [`BinarySceneLoader`] or [`deserialize_binary_scene`].
+    ///
+    /// [postcard]: https://crates.io/crates/postcard
+    pub fn serialize_binary(&self, registry: &TypeRegistry) -> Result<Vec<u8>, BinarySceneError> {
+        let mut bytes = Vec::from(BINARY_SCENE_MAGIC);
+        bytes.extend_from_slice(&BINARY_SCENE_VERSION.to_le_bytes());
+        bytes.extend(postcard::to_allocvec(&SceneSerializer::new(
+            self, registry,
+        ))?);
+        Ok(bytes)
+    }
+}
+
+/// Deserialize a dynamic scene from the binary Bevy scene format (`.bscn`), as serialized with
+/// [`DynamicScene::serialize_binary`].
+///
+/// The handles of the scene that refer to assets by path are loaded with the `asset_loader`, as in
+/// [`SceneDeserializer`].
+pub fn deserialize_binary_scene(
+    bytes: &[u8],
+    type_registry: &TypeRegistry,
+    asset_loader: Option<&mut dyn SceneAssetLoader>,
+) -> Result<DynamicScene, BinarySceneError> {
+    let bytes = bytes
+        .strip_prefix(&BINARY_SCENE_MAGIC)
+        .ok_or(BinarySceneError::InvalidMagic)?;
+    let (version, bytes) = bytes
+        .split_first_chunk::<4>()
+        .ok_or(BinarySceneError::InvalidMagic)?;
+    let version = u32::from_le_bytes(*version);
+    if version != BINARY_SCENE_VERSION {
+        return Err(BinarySceneError::UnsupportedVersion(version));
+    }
+
+    let scene_deserializer = SceneDeserializer {
+        type_registry,
+        asset_loader,
+    };
+    Ok(scene_deserializer.deserialize(&mut postcard::Deserializer::from_bytes(bytes))?)
+}
+
+/// Asset loader for a Bevy dynamic scene in the binary format (`.bscn`).
+///
+/// The loader handles assets serialized with [`DynamicScene::serialize_binary`].
+#[derive(Debug)]
+pub struct BinarySceneLoader {
+    type_registry: TypeRegistryArc,
+}
+
+impl FromWorld for BinarySceneLoader {
+    fn from_world(world: &mut World) -> Self {
+        let type_registry = world.resource::<AppTypeRegistry>();
+        BinarySceneLoader {
+            type_registry: type_registry.0.clone(),
+        }
+    }
+}
+
+impl AssetLoader for BinarySceneLoader {
+    type Asset = DynamicScene;
+    type Settings = ();
+    type Error = BinarySceneError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        deserialize_binary_scene(&bytes, &self.type_registry.read(), Some(load_context))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["bscn"]
+    }
+}
+
+/// An [`AssetSaver`] that saves dynamic scenes in the binary format (`.bscn`).
+///
+/// The [`ScenePlugin`](crate::ScenePlugin) uses it to convert the `.scn` / `.scn.ron` scenes to
+/// the binary format when assets are processed.
+#[derive(Debug)]
+pub struct BinarySceneSaver {
+    type_registry: TypeRegistryArc,
+}
+
+impl FromWorld for BinarySceneSaver {
+    fn from_world(world: &mut World) -> Self {
+        let type_registry = world.resource::<AppTypeRegistry>();
+        BinarySceneSaver {
+            type_registry: type_registry.0.clone(),
+        }
+    }
+}
+
+impl AssetSaver for BinarySceneSaver {
+    type Asset = DynamicScene;
+    type Settings = ();
+    type OutputLoader = BinarySceneLoader;
+    type Error = BinarySceneError;
+
+    async fn save(
+        &self,
+        writer: &mut Writer,
+        asset: SavedAsset<'_, DynamicScene>,
+        _settings: &(),
+    ) -> Result<(), BinarySceneError> {
+        let bytes = asset.serialize_binary(&self.type_registry.read())?;
+        writer.write_all(&bytes).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{deserialize_binary_scene, BinarySceneError, DynamicScene};
+    use bevy_ecs::{
+        prelude::{Component, ReflectComponent, World},
+        reflect::AppTypeRegistry,
+    };
+    use bevy_reflect::Reflect;
+
+    #[derive(Component, Reflect, Debug, PartialEq)]
+    #[reflect(Component, PartialEq)]
+    struct Foo(String);
+
+    #[test]
+    fn should_roundtrip_binary_scene() {
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        world
+            .resource::<AppTypeRegistry>()
+            .write()
+            .register::<Foo>();
+        world.spawn(Foo("foo".to_string()));
+
+        let scene = DynamicScene::from_world(&world);
+        let registry = world.resource::<AppTypeRegistry>().read();
+        let bytes = scene.serialize_binary(&registry).unwrap();
+        assert!(bytes.starts_with(b"BSCN"));
+
+        let deserialized = deserialize_binary_scene(&bytes, &registry, None).unwrap();
+        assert_eq!(deserialized.entities.len(), 1);
+        assert!(deserialized.entities[0].components[0]
+            .reflect_partial_eq(&Foo("foo".to_string()))
+            .unwrap());
+
+        let mut bytes = bytes;
+        bytes[4] = 2;
+        assert!(matches!(
+            deserialize_binary_scene(&bytes, &registry, None),
+            Err(BinarySceneError::UnsupportedVersion(2))
+        ));
+        assert!(matches!(
+            deserialize_binary_scene(b"(resources: {})", &registry, None),
+            Err(BinarySceneError::InvalidMagic)
+        ));
+    }
+}
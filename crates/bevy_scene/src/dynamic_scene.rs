@@ -39,6 +39,32 @@ pub struct DynamicEntity {
     pub components: Vec<Box<dyn PartialReflect>>,
 }
 
+impl Clone for DynamicScene {
+    fn clone(&self) -> Self {
+        Self {
+            resources: self
+                .resources
+                .iter()
+                .map(|resource| resource.clone_value())
+                .collect(),
+            entities: self.entities.clone(),
+        }
+    }
+}
+
+impl Clone for DynamicEntity {
+    fn clone(&self) -> Self {
+        Self {
+            entity: self.entity,
+            components: self
+                .components
+                .iter()
+                .map(|component| component.clone_value())
+                .collect(),
+        }
+    }
+}
+
 impl DynamicScene {
     /// Create a new dynamic scene from a given scene.
     pub fn from_scene(scene: &Scene) -> Self {
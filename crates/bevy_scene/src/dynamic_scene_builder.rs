@@ -1,14 +1,17 @@
-use crate::{DynamicEntity, DynamicScene, SceneFilter};
+use crate::{DynamicEntity, DynamicScene, SceneFilter, SceneInstance, SceneSpawner};
 use alloc::collections::BTreeMap;
 use bevy_ecs::{
     component::{Component, ComponentId},
+    entity::EntityHashSet,
     prelude::Entity,
     reflect::{AppTypeRegistry, ReflectComponent, ReflectResource},
     system::Resource,
     world::World,
 };
-use bevy_reflect::{PartialReflect, ReflectFromReflect};
+use bevy_hierarchy::Children;
+use bevy_reflect::{PartialReflect, ReflectFromReflect, ReflectMut};
 use bevy_utils::default;
+use core::any::TypeId;
 
 /// A [`DynamicScene`] builder, used to build a scene from a [`World`] by extracting some entities and resources.
 ///
@@ -231,6 +234,74 @@ impl<'w> DynamicSceneBuilder<'w> {
         self
     }
 
+    /// Removes the entities spawned by the scene instances of the extracted entities, including
+    /// the ones of nested instances, and removes them from the [`Children`] of the remaining
+    /// entities.
+    ///
+    /// The [`SceneRoot`](crate::SceneRoot)s and [`DynamicSceneRoot`](crate::DynamicSceneRoot)s of
+    /// the extracted entities will spawn these instances again when the scene is spawned, along
+    /// with their [`SceneOverrides`](crate::SceneOverrides).
+    #[must_use]
+    pub fn remove_scene_instance_entities(mut self) -> Self {
+        let Some(scene_spawner) = self.original_world.get_resource::<SceneSpawner>() else {
+            return self;
+        };
+
+        let mut instance_entities = EntityHashSet::default();
+        let mut entities: Vec<Entity> = self.extracted_scene.keys().copied().collect();
+        while let Some(entity) = entities.pop() {
+            let Some(instance) = self.original_world.get::<SceneInstance>(entity) else {
+                continue;
+            };
+            for instance_entity in scene_spawner.iter_instance_entities(**instance) {
+                if instance_entities.insert(instance_entity) {
+                    entities.push(instance_entity);
+                }
+            }
+        }
+        if instance_entities.is_empty() {
+            return self;
+        }
+
+        self.extracted_scene
+            .retain(|entity, _| !instance_entities.contains(entity));
+
+        for entity in self.extracted_scene.values_mut() {
+            entity.components.retain_mut(|component| {
+                if !component
+                    .get_represented_type_info()
+                    .is_some_and(|info| info.type_id() == TypeId::of::<Children>())
+                {
+                    return true;
+                }
+                let ReflectMut::TupleStruct(children) = component.reflect_mut() else {
+                    return true;
+                };
+                let Some(ReflectMut::List(children)) =
+                    children.field_mut(0).map(PartialReflect::reflect_mut)
+                else {
+                    return true;
+                };
+
+                let mut index = 0;
+                while index < children.len() {
+                    let is_instance_entity = children
+                        .get(index)
+                        .and_then(|child| child.try_downcast_ref::<Entity>())
+                        .is_some_and(|child| instance_entities.contains(child));
+                    if is_instance_entity {
+                        children.remove(index);
+                    } else {
+                        index += 1;
+                    }
+                }
+                !children.is_empty()
+            });
+        }
+
+        self
+    }
+
     /// Extract entities from the builder's [`World`].
     ///
     /// Re-extracting an entity that was already extracted will have no effect.
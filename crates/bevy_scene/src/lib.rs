@@ -13,12 +13,18 @@
 
 extern crate alloc;
 
+#[cfg(feature = "serialize")]
+mod binary_scene;
 mod components;
 mod dynamic_scene;
 mod dynamic_scene_builder;
+#[cfg(feature = "serialize")]
+mod save;
 mod scene;
+mod scene_diff;
 mod scene_filter;
 mod scene_loader;
+mod scene_overrides;
 mod scene_spawner;
 
 #[cfg(feature = "serialize")]
@@ -28,12 +34,18 @@ pub mod serde;
 pub use bevy_asset::ron;
 
 use bevy_ecs::schedule::IntoSystemConfigs;
+#[cfg(feature = "serialize")]
+pub use binary_scene::*;
 pub use components::*;
 pub use dynamic_scene::*;
 pub use dynamic_scene_builder::*;
+#[cfg(feature = "serialize")]
+pub use save::*;
 pub use scene::*;
+pub use scene_diff::*;
 pub use scene_filter::*;
 pub use scene_loader::*;
+pub use scene_overrides::*;
 pub use scene_spawner::*;
 
 /// The scene prelude.
@@ -42,13 +54,15 @@ pub use scene_spawner::*;
 pub mod prelude {
     #[doc(hidden)]
     pub use crate::{
-        DynamicScene, DynamicSceneBuilder, DynamicSceneRoot, Scene, SceneFilter, SceneRoot,
-        SceneSpawner,
+        DynamicScene, DynamicSceneBuilder, DynamicSceneRoot, Scene, SceneFilter, SceneOverrides,
+        SceneRoot, SceneSpawner,
     };
 }
 
 use bevy_app::prelude::*;
 use bevy_asset::AssetApp;
+#[cfg(feature = "serialize")]
+use bevy_ecs::world::FromWorld;
 
 /// Plugin that provides scene functionality to an [`App`].
 #[derive(Default)]
@@ -60,11 +74,35 @@ impl Plugin for ScenePlugin {
         app.init_asset::<DynamicScene>()
             .init_asset::<Scene>()
             .init_asset_loader::<SceneLoader>()
+            .init_asset_loader::<BinarySceneLoader>()
             .init_resource::<SceneSpawner>()
             .register_type::<SceneRoot>()
             .register_type::<DynamicSceneRoot>()
+            .register_type::<SceneOverrides>()
+            .register_type::<Saveable>()
+            .init_resource::<SaveConfig>()
             .add_systems(SpawnScene, (scene_spawner, scene_spawner_system).chain());
 
+        // Convert the RON scenes to the binary format when assets are processed.
+        let saver = BinarySceneSaver::from_world(app.world_mut());
+        if let Some(processor) = app
+            .world()
+            .get_resource::<bevy_asset::processor::AssetProcessor>()
+        {
+            processor.register_processor::<bevy_asset::processor::LoadTransformAndSave<
+                SceneLoader,
+                bevy_asset::transformer::IdentityAssetTransformer<DynamicScene>,
+                BinarySceneSaver,
+            >>(saver.into());
+            for extension in ["scn", "scn.ron"] {
+                processor.set_default_processor::<bevy_asset::processor::LoadTransformAndSave<
+                    SceneLoader,
+                    bevy_asset::transformer::IdentityAssetTransformer<DynamicScene>,
+                    BinarySceneSaver,
+                >>(extension);
+            }
+        }
+
         // Register component hooks for DynamicSceneRoot
         app.world_mut()
             .register_component_hooks::<DynamicSceneRoot>()
@@ -77,9 +115,7 @@ impl Plugin for ScenePlugin {
                     let Some(mut scene_spawner) = world.get_resource_mut::<SceneSpawner>() else {
                         return;
                     };
-                    if let Some(instance_ids) = scene_spawner.spawned_dynamic_scenes.get_mut(&id) {
-                        instance_ids.remove(&scene_instance);
-                    }
+                    scene_spawner.remove_dynamic_instance(id, &scene_instance);
                     scene_spawner.despawn_instance(scene_instance);
                 }
             });
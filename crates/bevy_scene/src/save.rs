@@ -0,0 +1,642 @@
+use crate::{
+    ron,
+    serde::{SceneAssetLoader, SceneDeserializer, SceneSerializer},
+    serialize_ron, DynamicScene, DynamicSceneBuilder, SceneFilter, SceneSpawnError,
+};
+use alloc::collections::VecDeque;
+use bevy_asset::AssetServer;
+use bevy_ecs::{
+    component::Component,
+    entity::{Entity, EntityHashMap},
+    reflect::{AppTypeRegistry, ReflectComponent},
+    system::Resource,
+    world::World,
+};
+use bevy_hierarchy::DespawnRecursiveExt;
+use bevy_reflect::{
+    prelude::ReflectDefault, FromReflect, PartialReflect, Reflect, TypeInfo, TypeRegistry,
+};
+use bevy_utils::HashMap;
+use core::{any::TypeId, fmt::Formatter};
+use serde::{
+    de::{DeserializeSeed, Error, MapAccess, SeqAccess, Visitor},
+    ser::SerializeStruct,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use thiserror::Error;
+
+/// Marks an entity whose state is saved by [`SaveWorldExt`].
+#[derive(Component, Reflect, Default, Debug, Clone, Copy)]
+#[reflect(Component, Default, Debug)]
+pub struct Saveable;
+
+type SaveMigration = Box<dyn Fn(&mut DynamicScene) + Send + Sync>;
+
+/// Converts a component or resource when it's saved, or loaded.
+struct SaveHook {
+    on_save: Box<dyn Fn(Box<dyn PartialReflect>) -> Option<Box<dyn PartialReflect>> + Send + Sync>,
+    on_load: Box<dyn Fn(Box<dyn PartialReflect>) -> Box<dyn PartialReflect> + Send + Sync>,
+}
+
+/// Configures how [`SaveWorldExt`] saves and loads the state of the game.
+///
+/// The components of the entities with [`Saveable`] and the resources that pass the filters are
+/// saved, as long as they're registered with [`ReflectComponent`] or
+/// [`ReflectResource`](bevy_ecs::reflect::ReflectResource) in the [`AppTypeRegistry`].
+#[derive(Resource)]
+pub struct SaveConfig {
+    /// The version of the saves, written in each save.
+    ///
+    /// Increase it when the saved components or resources change in a way that breaks the older
+    /// saves, and add a migration for the previous version with [`SaveConfig::add_migration`].
+    pub version: u32,
+    /// Filters the components of the [`Saveable`] entities that are saved. Allows all of them by
+    /// default.
+    ///
+    /// [`Saveable`] itself is always saved, so that the loaded entities are saved again.
+    pub component_filter: SceneFilter,
+    /// Filters the resources that are saved. Denies all of them by default.
+    pub resource_filter: SceneFilter,
+    /// The number of checkpoints kept for [`SaveWorldExt::rollback`].
+    pub max_checkpoints: usize,
+    migrations: HashMap<u32, Vec<SaveMigration>>,
+    hooks: HashMap<TypeId, SaveHook>,
+}
+
+impl Default for SaveConfig {
+    fn default() -> Self {
+        Self {
+            version: 0,
+            component_filter: SceneFilter::allow_all(),
+            resource_filter: SceneFilter::deny_all(),
+            max_checkpoints: 16,
+            migrations: HashMap::default(),
+            hooks: HashMap::default(),
+        }
+    }
+}
+
+impl SaveConfig {
+    /// Adds a migration that upgrades the saves of `from_version` to the next version.
+    ///
+    /// Loading a save of an older version runs the migrations of all the versions between it and
+    /// the current [`version`](SaveConfig::version), in order. The types of the save still have to
+    /// be registered to deserialize it, so keep the types that were removed since registered until
+    /// the saves that contain them are no longer supported.
+    pub fn add_migration(
+        &mut self,
+        from_version: u32,
+        migration: impl Fn(&mut DynamicScene) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.migrations
+            .entry(from_version)
+            .or_default()
+            .push(Box::new(migration));
+        self
+    }
+
+    /// Adds hooks that convert the `T` components or resources when they're saved, and loaded.
+    ///
+    /// This can be used to reset runtime state, or to save only part of a value. Returning
+    /// [`None`] from `on_save` doesn't save the value at all.
+    pub fn add_hook<T: FromReflect>(
+        &mut self,
+        on_save: impl Fn(T) -> Option<T> + Send + Sync + 'static,
+        on_load: impl Fn(T) -> T + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.hooks.insert(
+            TypeId::of::<T>(),
+            SaveHook {
+                on_save: Box::new(move |value: Box<dyn PartialReflect>| {
+                    match T::from_reflect(&*value) {
+                        Some(value) => {
+                            on_save(value).map(|value| Box::new(value) as Box<dyn PartialReflect>)
+                        }
+                        None => Some(value),
+                    }
+                }),
+                on_load: Box::new(move |value: Box<dyn PartialReflect>| {
+                    match T::from_reflect(&*value) {
+                        Some(value) => Box::new(on_load(value)) as Box<dyn PartialReflect>,
+                        None => value,
+                    }
+                }),
+            },
+        );
+        self
+    }
+
+    fn on_save(&self, values: Vec<Box<dyn PartialReflect>>) -> Vec<Box<dyn PartialReflect>> {
+        values
+            .into_iter()
+            .filter_map(|value| match self.hook(value.as_ref()) {
+                Some(hook) => (hook.on_save)(value),
+                None => Some(value),
+            })
+            .collect()
+    }
+
+    fn on_load(&self, values: Vec<Box<dyn PartialReflect>>) -> Vec<Box<dyn PartialReflect>> {
+        values
+            .into_iter()
+            .map(|value| match self.hook(value.as_ref()) {
+                Some(hook) => (hook.on_load)(value),
+                None => value,
+            })
+            .collect()
+    }
+
+    fn hook(&self, value: &dyn PartialReflect) -> Option<&SaveHook> {
+        let type_id = value.get_represented_type_info().map(TypeInfo::type_id)?;
+        self.hooks.get(&type_id)
+    }
+
+    /// Converts the values of a snapshot back from their saved form.
+    fn prepare_load(&self, mut snapshot: DynamicScene) -> DynamicScene {
+        snapshot.resources = self.on_load(snapshot.resources);
+        for entity in &mut snapshot.entities {
+            entity.components = self.on_load(core::mem::take(&mut entity.components));
+        }
+        snapshot
+    }
+}
+
+/// The snapshots saved by [`SaveWorldExt::checkpoint`], to restore with
+/// [`SaveWorldExt::rollback`].
+#[derive(Resource, Default)]
+pub struct SaveCheckpoints(VecDeque<DynamicScene>);
+
+impl SaveCheckpoints {
+    /// Returns the number of checkpoints.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if there are no checkpoints.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Removes all the checkpoints.
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+/// Possible errors that can be produced when saving or loading the state of the game.
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum SaveError {
+    /// A [RON Error](ron::Error)
+    #[error("Could not serialize the save: {0}")]
+    Ron(#[from] ron::Error),
+    /// A [RON Error](ron::error::SpannedError)
+    #[error("Could not parse the save: {0}")]
+    RonSpannedError(#[from] ron::error::SpannedError),
+    /// The save couldn't be written to the world.
+    #[error("Could not load the save: {0}")]
+    Spawn(#[from] SceneSpawnError),
+    /// The save was written by a newer version of the game.
+    #[error("The save has version {version}, which is newer than the current version {current}")]
+    UnsupportedVersion {
+        /// The version of the save.
+        version: u32,
+        /// The current [`SaveConfig::version`].
+        current: u32,
+    },
+}
+
+/// Saves and loads the state of the game from a [`World`], as configured by its [`SaveConfig`].
+///
+/// The methods of this trait panic if the world doesn't contain the [`SaveConfig`] and
+/// [`AppTypeRegistry`] resources, which the [`ScenePlugin`](crate::ScenePlugin) adds.
+pub trait SaveWorldExt {
+    /// Takes a snapshot of the [`Saveable`] entities and of the saved resources, in their saved
+    /// form.
+    fn save_snapshot(&self) -> DynamicScene;
+
+    /// Saves the state of the game in the RON format.
+    fn save(&self) -> Result<String, SaveError>;
+
+    /// Reads a save, migrated to the current [`SaveConfig::version`].
+    fn read_save(&self, input: &str) -> Result<DynamicScene, SaveError>;
+
+    /// Loads a save, replacing all the [`Saveable`] entities, along with their descendants, with
+    /// the ones of the save.
+    fn load_save(&mut self, input: &str) -> Result<(), SaveError>;
+
+    /// Restores the state of the game from a snapshot taken with
+    /// [`save_snapshot`](SaveWorldExt::save_snapshot) in the same world.
+    ///
+    /// The [`Saveable`] entities that still exist are restored in place, so the entities that
+    /// reference them stay valid. The ones that were spawned since the snapshot are despawned,
+    /// along with their descendants, and the ones that were despawned are spawned again.
+    fn apply_snapshot(&mut self, snapshot: &DynamicScene) -> Result<(), SaveError>;
+
+    /// Takes a snapshot and keeps it in the [`SaveCheckpoints`], dropping the oldest ones beyond
+    /// the [`SaveConfig::max_checkpoints`].
+    fn checkpoint(&mut self);
+
+    /// Restores the most recent checkpoint and removes it, see
+    /// [`apply_snapshot`](SaveWorldExt::apply_snapshot).
+    ///
+    /// Returns `false` if there was no checkpoint to restore.
+    fn rollback(&mut self) -> Result<bool, SaveError>;
+}
+
+impl SaveWorldExt for World {
+    fn save_snapshot(&self) -> DynamicScene {
+        let config = self.resource::<SaveConfig>();
+        let mut snapshot = DynamicSceneBuilder::from_world(self)
+            .with_component_filter(config.component_filter.clone())
+            .with_resource_filter(config.resource_filter.clone())
+            .extract_entities(
+                self.iter_entities()
+                    .filter(|entity| entity.contains::<Saveable>())
+                    .map(|entity| entity.id()),
+            )
+            .extract_resources()
+            .build();
+
+        snapshot.resources = config.on_save(snapshot.resources);
+        for entity in &mut snapshot.entities {
+            entity.components = config.on_save(core::mem::take(&mut entity.components));
+            let has_marker = entity.components.iter().any(|component| {
+                component
+                    .get_represented_type_info()
+                    .is_some_and(|info| info.type_id() == TypeId::of::<Saveable>())
+            });
+            if !has_marker {
+                entity.components.push(Box::new(Saveable));
+            }
+        }
+        snapshot
+    }
+
+    fn save(&self) -> Result<String, SaveError> {
+        let snapshot = self.save_snapshot();
+        let registry = self.resource::<AppTypeRegistry>().read();
+        Ok(serialize_ron(SaveSerializer {
+            version: self.resource::<SaveConfig>().version,
+            scene: &snapshot,
+            registry: &registry,
+        })?)
+    }
+
+    fn read_save(&self, input: &str) -> Result<DynamicScene, SaveError> {
+        let mut asset_server = self.get_resource::<AssetServer>().cloned();
+        let mut deserializer = ron::de::Deserializer::from_str(input)?;
+        let save_deserializer = SaveDeserializer {
+            type_registry: &self.resource::<AppTypeRegistry>().read(),
+            asset_loader: asset_server
+                .as_mut()
+                .map(|asset_server| asset_server as &mut dyn SceneAssetLoader),
+        };
+        let (version, mut snapshot) = save_deserializer
+            .deserialize(&mut deserializer)
+            .map_err(|e| deserializer.span_error(e))?;
+
+        let config = self.resource::<SaveConfig>();
+        if version > config.version {
+            return Err(SaveError::UnsupportedVersion {
+                version,
+                current: config.version,
+            });
+        }
+        for version in version..config.version {
+            for migration in config.migrations.get(&version).into_iter().flatten() {
+                migration(&mut snapshot);
+            }
+        }
+        Ok(snapshot)
+    }
+
+    fn load_save(&mut self, input: &str) -> Result<(), SaveError> {
+        let snapshot = self.read_save(input)?;
+        let snapshot = self.resource::<SaveConfig>().prepare_load(snapshot);
+
+        despawn_saveable_entities(self, |_| true);
+        snapshot.write_to_world(self, &mut EntityHashMap::default())?;
+        Ok(())
+    }
+
+    fn apply_snapshot(&mut self, snapshot: &DynamicScene) -> Result<(), SaveError> {
+        let config = self.resource::<SaveConfig>();
+        let component_filter = config.component_filter.clone();
+        let snapshot = config.prepare_load(snapshot.clone());
+
+        let snapshot_entities: EntityHashMap<&[Box<dyn PartialReflect>]> = snapshot
+            .entities
+            .iter()
+            .map(|entity| (entity.entity, entity.components.as_slice()))
+            .collect();
+        despawn_saveable_entities(self, |entity| !snapshot_entities.contains_key(&entity));
+
+        // Remove the saved components that were added since the snapshot.
+        let type_registry = self.resource::<AppTypeRegistry>().clone();
+        let type_registry = type_registry.read();
+        let mut entity_map = EntityHashMap::default();
+        for (&entity, components) in &snapshot_entities {
+            let Ok(entity_ref) = self.get_entity(entity) else {
+                continue;
+            };
+            entity_map.insert(entity, entity);
+
+            let added: Vec<&ReflectComponent> = entity_ref
+                .archetype()
+                .components()
+                .filter_map(|id| self.components().get_info(id)?.type_id())
+                .filter(|&type_id| {
+                    component_filter.is_allowed_by_id(type_id)
+                        && !components.iter().any(|component| {
+                            component
+                                .get_represented_type_info()
+                                .is_some_and(|info| info.type_id() == type_id)
+                        })
+                })
+                .filter_map(|type_id| type_registry.get_type_data::<ReflectComponent>(type_id))
+                .collect();
+            let mut entity_mut = self.entity_mut(entity);
+            for reflect_component in added {
+                reflect_component.remove(&mut entity_mut);
+            }
+        }
+        drop(type_registry);
+
+        snapshot.write_to_world(self, &mut entity_map)?;
+        Ok(())
+    }
+
+    fn checkpoint(&mut self) {
+        let snapshot = self.save_snapshot();
+        let max_checkpoints = self.resource::<SaveConfig>().max_checkpoints;
+        let mut checkpoints = self.get_resource_or_insert_with(SaveCheckpoints::default);
+        checkpoints.0.push_back(snapshot);
+        while checkpoints.0.len() > max_checkpoints {
+            checkpoints.0.pop_front();
+        }
+    }
+
+    fn rollback(&mut self) -> Result<bool, SaveError> {
+        let Some(snapshot) = self
+            .get_resource_mut::<SaveCheckpoints>()
+            .and_then(|mut checkpoints| checkpoints.0.pop_back())
+        else {
+            return Ok(false);
+        };
+        self.apply_snapshot(&snapshot)?;
+        Ok(true)
+    }
+}
+
+/// Despawns the [`Saveable`] entities that match the `filter`, along with their descendants.
+fn despawn_saveable_entities(world: &mut World, filter: impl Fn(Entity) -> bool) {
+    let entities: Vec<Entity> = world
+        .iter_entities()
+        .filter(|entity| entity.contains::<Saveable>() && filter(entity.id()))
+        .map(|entity| entity.id())
+        .collect();
+    for entity in entities {
+        if let Ok(mut entity_mut) = world.get_entity_mut(entity) {
+            entity_mut.remove_parent();
+            entity_mut.despawn_recursive();
+        }
+    }
+}
+
+const SAVE_STRUCT: &str = "Save";
+const SAVE_VERSION: &str = "version";
+const SAVE_SCENE: &str = "scene";
+
+/// Serializer for a save: its version, and the snapshot of the game state.
+pub struct SaveSerializer<'a> {
+    /// The version of the save.
+    pub version: u32,
+    /// The snapshot to serialize.
+    pub scene: &'a DynamicScene,
+    /// The type registry containing the types present in the snapshot.
+    pub registry: &'a TypeRegistry,
+}
+
+impl<'a> Serialize for SaveSerializer<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct(SAVE_STRUCT, 2)?;
+        state.serialize_field(SAVE_VERSION, &self.version)?;
+        state.serialize_field(SAVE_SCENE, &SceneSerializer::new(self.scene, self.registry))?;
+        state.end()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum SaveField {
+    Version,
+    Scene,
+}
+
+/// Handles save deserialization, into the version of the save and its snapshot.
+pub struct SaveDeserializer<'a> {
+    /// Type registry in which the components and resources types used in the save are registered.
+    pub type_registry: &'a TypeRegistry,
+    /// Loads the assets that the handles in the save refer to by path, as in
+    /// [`SceneDeserializer`].
+    pub asset_loader: Option<&'a mut dyn SceneAssetLoader>,
+}
+
+impl<'a, 'de> DeserializeSeed<'de> for SaveDeserializer<'a> {
+    type Value = (u32, DynamicScene);
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_struct(SAVE_STRUCT, &[SAVE_VERSION, SAVE_SCENE], self)
+    }
+}
+
+impl<'a, 'de> Visitor<'de> for SaveDeserializer<'a> {
+    type Value = (u32, DynamicScene);
+
+    fn expecting(&self, formatter: &mut Formatter) -> core::fmt::Result {
+        formatter.write_str("save struct")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let version = seq
+            .next_element()?
+            .ok_or_else(|| Error::missing_field(SAVE_VERSION))?;
+        let scene = seq
+            .next_element_seed(SceneDeserializer {
+                type_registry: self.type_registry,
+                asset_loader: self.asset_loader,
+            })?
+            .ok_or_else(|| Error::missing_field(SAVE_SCENE))?;
+        Ok((version, scene))
+    }
+
+    fn visit_map<A>(mut self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut version = None;
+        let mut scene = None;
+        while let Some(key) = map.next_key()? {
+            match key {
+                SaveField::Version => {
+                    if version.is_some() {
+                        return Err(Error::duplicate_field(SAVE_VERSION));
+                    }
+                    version = Some(map.next_value()?);
+                }
+                SaveField::Scene => {
+                    if scene.is_some() {
+                        return Err(Error::duplicate_field(SAVE_SCENE));
+                    }
+                    scene = Some(map.next_value_seed(SceneDeserializer {
+                        type_registry: self.type_registry,
+                        asset_loader: self.asset_loader.take(),
+                    })?);
+                }
+            }
+        }
+
+        let version = version.ok_or_else(|| Error::missing_field(SAVE_VERSION))?;
+        let scene = scene.ok_or_else(|| Error::missing_field(SAVE_SCENE))?;
+        Ok((version, scene))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{SaveConfig, SaveError, SaveWorldExt, Saveable, SceneFilter};
+    use bevy_ecs::{
+        component::Component,
+        reflect::{AppTypeRegistry, ReflectComponent},
+        world::World,
+    };
+    use bevy_reflect::{prelude::ReflectDefault, Reflect};
+
+    #[derive(Component, Reflect, Default, Debug, PartialEq)]
+    #[reflect(Component, Default, PartialEq)]
+    struct Health(u32);
+
+    #[derive(Component, Reflect, Default, Debug, PartialEq)]
+    #[reflect(Component, Default, PartialEq)]
+    struct Poisoned;
+
+    fn create_world() -> World {
+        let mut world = World::new();
+        let type_registry = AppTypeRegistry::default();
+        {
+            let mut type_registry = type_registry.write();
+            type_registry.register::<Saveable>();
+            type_registry.register::<Health>();
+            type_registry.register::<Poisoned>();
+        }
+        world.insert_resource(type_registry);
+        world.init_resource::<SaveConfig>();
+        world
+    }
+
+    fn health(world: &mut World) -> Vec<u32> {
+        let mut health: Vec<u32> = world
+            .query::<&Health>()
+            .iter(world)
+            .map(|health| health.0)
+            .collect();
+        health.sort_unstable();
+        health
+    }
+
+    #[test]
+    fn save_and_load() {
+        let mut world = create_world();
+        world.resource_mut::<SaveConfig>().add_hook::<Health>(
+            |health| Some(Health(health.0 * 10)),
+            |health| Health(health.0 / 10),
+        );
+        world.spawn((Saveable, Health(3)));
+        world.spawn((Saveable, Health(7)));
+        world.spawn(Health(100));
+
+        let save = world.save().unwrap();
+        assert!(save.contains("version: 0"));
+        assert!(save.contains("(30)"));
+
+        world.spawn((Saveable, Health(1)));
+        world.load_save(&save).unwrap();
+        assert_eq!(health(&mut world), vec![3, 7, 100]);
+    }
+
+    #[test]
+    fn save_allowed_components_again() {
+        let mut world = create_world();
+        world.resource_mut::<SaveConfig>().component_filter =
+            SceneFilter::deny_all().allow::<Health>();
+        world.spawn((Saveable, Health(3), Poisoned));
+        world.spawn((Saveable, Health(7)));
+
+        let save = world.save().unwrap();
+        world.load_save(&save).unwrap();
+        assert_eq!(health(&mut world), vec![3, 7]);
+        assert_eq!(world.query::<&Poisoned>().iter(&world).len(), 0);
+
+        // The loaded entities are still saveable.
+        assert_eq!(world.query::<&Saveable>().iter(&world).len(), 2);
+        let save = world.save().unwrap();
+        world.load_save(&save).unwrap();
+        assert_eq!(health(&mut world), vec![3, 7]);
+    }
+
+    #[test]
+    fn migrate_save() {
+        let mut world = create_world();
+        world.spawn((Saveable, Health(3)));
+        let save = world.save().unwrap();
+
+        let mut config = world.resource_mut::<SaveConfig>();
+        config.version = 1;
+        config.add_migration(0, |snapshot| {
+            for entity in &mut snapshot.entities {
+                entity.components.push(Box::new(Poisoned));
+            }
+        });
+        world.load_save(&save).unwrap();
+        assert_eq!(world.query::<&Poisoned>().iter(&world).len(), 1);
+
+        world.resource_mut::<SaveConfig>().version = 0;
+        let newer_save = save.replace("version: 0", "version: 1");
+        assert!(matches!(
+            world.load_save(&newer_save),
+            Err(SaveError::UnsupportedVersion {
+                version: 1,
+                current: 0
+            })
+        ));
+    }
+
+    #[test]
+    fn rollback_in_place() {
+        let mut world = create_world();
+        let kept = world.spawn((Saveable, Health(3))).id();
+        let despawned = world.spawn((Saveable, Health(5))).id();
+        world.checkpoint();
+
+        world.get_mut::<Health>(kept).unwrap().0 = 1;
+        world.entity_mut(kept).insert(Poisoned);
+        world.despawn(despawned);
+        world.spawn((Saveable, Health(9)));
+
+        assert!(world.rollback().unwrap());
+        assert_eq!(world.get::<Health>(kept), Some(&Health(3)));
+        assert!(world.get::<Poisoned>(kept).is_none());
+        assert_eq!(health(&mut world), vec![3, 5]);
+        assert!(!world.rollback().unwrap());
+    }
+}
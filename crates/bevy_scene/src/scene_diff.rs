@@ -0,0 +1,282 @@
+use crate::{DynamicEntity, DynamicScene, SceneSpawnError};
+use bevy_ecs::{
+    entity::{Entity, EntityHashMap},
+    reflect::{AppTypeRegistry, ReflectComponent, ReflectResource},
+    world::World,
+};
+use bevy_hierarchy::DespawnRecursiveExt;
+use bevy_reflect::{PartialReflect, TypeInfo};
+use core::any::TypeId;
+
+/// The changes between two versions of a [`DynamicScene`], down to its components and resources.
+///
+/// Applying it to an instance of the old version with [`SceneDiff::apply`] updates the instance in
+/// place: the components that are the same in both versions keep their current value, and the
+/// entities and components added to the instance at runtime are left alone.
+///
+/// The [`SceneSpawner`](crate::SceneSpawner) uses it to update the instances of the dynamic scenes
+/// that are reloaded.
+#[derive(Default)]
+pub struct SceneDiff {
+    /// The resources that were added or changed, and the entities that were added or whose
+    /// components were added or changed, with only these components.
+    pub changes: DynamicScene,
+    /// The entities that were removed.
+    pub removed_entities: Vec<Entity>,
+    /// The entities whose components were removed, with the types of these components.
+    pub removed_components: Vec<(Entity, Vec<TypeId>)>,
+    /// The types of the resources that were removed.
+    pub removed_resources: Vec<TypeId>,
+}
+
+impl SceneDiff {
+    /// Computes the changes from the `old` version of a scene to the `new` one.
+    ///
+    /// Entities are matched by their identifier within the scenes, and components and resources
+    /// by their type. They're compared with [`PartialReflect::reflect_partial_eq`], and are
+    /// considered changed if they can't be compared.
+    pub fn new(old: &DynamicScene, new: &DynamicScene) -> Self {
+        let (resources, removed_resources) = diff_values(&old.resources, &new.resources);
+        let mut diff = Self {
+            changes: DynamicScene {
+                resources,
+                entities: Vec::new(),
+            },
+            removed_resources,
+            ..Self::default()
+        };
+
+        let old_entities: EntityHashMap<&DynamicEntity> = old
+            .entities
+            .iter()
+            .map(|entity| (entity.entity, entity))
+            .collect();
+        for new_entity in &new.entities {
+            let Some(old_entity) = old_entities.get(&new_entity.entity) else {
+                diff.changes.entities.push(new_entity.clone());
+                continue;
+            };
+
+            let (components, removed_components) =
+                diff_values(&old_entity.components, &new_entity.components);
+            if !components.is_empty() {
+                diff.changes.entities.push(DynamicEntity {
+                    entity: new_entity.entity,
+                    components,
+                });
+            }
+            if !removed_components.is_empty() {
+                diff.removed_components
+                    .push((new_entity.entity, removed_components));
+            }
+        }
+
+        let new_entities: EntityHashMap<&DynamicEntity> = new
+            .entities
+            .iter()
+            .map(|entity| (entity.entity, entity))
+            .collect();
+        diff.removed_entities = old
+            .entities
+            .iter()
+            .map(|entity| entity.entity)
+            .filter(|entity| !new_entities.contains_key(entity))
+            .collect();
+
+        diff
+    }
+
+    /// Returns `true` if the two versions of the scene are the same.
+    pub fn is_empty(&self) -> bool {
+        self.changes.resources.is_empty()
+            && self.changes.entities.is_empty()
+            && self.removed_entities.is_empty()
+            && self.removed_components.is_empty()
+            && self.removed_resources.is_empty()
+    }
+
+    /// Applies the changes to the instance of the old version of the scene that was spawned with
+    /// the given `entity_map`.
+    ///
+    /// Removed entities are despawned along with their descendants, and added entities are
+    /// spawned and added to the `entity_map`. The entities of the instance that were despawned
+    /// since it was spawned aren't spawned again.
+    pub fn apply(
+        &self,
+        world: &mut World,
+        entity_map: &mut EntityHashMap<Entity>,
+    ) -> Result<(), SceneSpawnError> {
+        let type_registry = world.resource::<AppTypeRegistry>().clone();
+
+        for scene_entity in &self.removed_entities {
+            let Some(entity) = entity_map.remove(scene_entity) else {
+                continue;
+            };
+            if let Ok(mut entity_mut) = world.get_entity_mut(entity) {
+                entity_mut.remove_parent();
+                entity_mut.despawn_recursive();
+            }
+        }
+
+        {
+            let type_registry = type_registry.read();
+            for (scene_entity, type_ids) in &self.removed_components {
+                let Some(mut entity_mut) = entity_map
+                    .get(scene_entity)
+                    .and_then(|&entity| world.get_entity_mut(entity).ok())
+                else {
+                    continue;
+                };
+                for &type_id in type_ids {
+                    if let Some(reflect_component) =
+                        type_registry.get_type_data::<ReflectComponent>(type_id)
+                    {
+                        reflect_component.remove(&mut entity_mut);
+                    }
+                }
+            }
+
+            for &type_id in &self.removed_resources {
+                if let Some(reflect_resource) =
+                    type_registry.get_type_data::<ReflectResource>(type_id)
+                {
+                    reflect_resource.remove(world);
+                }
+            }
+        }
+
+        let changes = DynamicScene {
+            resources: self
+                .changes
+                .resources
+                .iter()
+                .map(|resource| resource.clone_value())
+                .collect(),
+            entities: self
+                .changes
+                .entities
+                .iter()
+                .filter(|scene_entity| {
+                    entity_map
+                        .get(&scene_entity.entity)
+                        .is_none_or(|&entity| world.get_entity(entity).is_ok())
+                })
+                .cloned()
+                .collect(),
+        };
+        changes.write_to_world_with(world, entity_map, &type_registry)
+    }
+}
+
+/// Returns the `new` values that were added or changed since the `old` ones, and the types of the
+/// `old` values that were removed.
+fn diff_values(
+    old: &[Box<dyn PartialReflect>],
+    new: &[Box<dyn PartialReflect>],
+) -> (Vec<Box<dyn PartialReflect>>, Vec<TypeId>) {
+    let changed = new
+        .iter()
+        .filter(|new_value| {
+            let type_id = represented_type_id(new_value.as_ref());
+            !old.iter().any(|old_value| {
+                type_id.is_some()
+                    && represented_type_id(old_value.as_ref()) == type_id
+                    && old_value
+                        .reflect_partial_eq(new_value.as_ref())
+                        .unwrap_or_default()
+            })
+        })
+        .map(|new_value| new_value.clone_value())
+        .collect();
+
+    let removed = old
+        .iter()
+        .filter_map(|old_value| represented_type_id(old_value.as_ref()))
+        .filter(|&type_id| {
+            !new.iter()
+                .any(|new_value| represented_type_id(new_value.as_ref()) == Some(type_id))
+        })
+        .collect();
+
+    (changed, removed)
+}
+
+fn represented_type_id(value: &dyn PartialReflect) -> Option<TypeId> {
+    value.get_represented_type_info().map(TypeInfo::type_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DynamicEntity, DynamicScene, SceneDiff};
+    use bevy_ecs::{
+        component::Component,
+        entity::{Entity, EntityHashMap},
+        reflect::{AppTypeRegistry, ReflectComponent},
+        world::World,
+    };
+    use bevy_reflect::{PartialReflect, Reflect};
+    use core::any::TypeId;
+
+    #[derive(Component, Reflect, Debug, PartialEq)]
+    #[reflect(Component, PartialEq)]
+    struct A(u32);
+
+    #[derive(Component, Reflect, Debug, PartialEq)]
+    #[reflect(Component, PartialEq)]
+    struct B(u32);
+
+    fn scene(entities: Vec<DynamicEntity>) -> DynamicScene {
+        DynamicScene {
+            resources: Vec::new(),
+            entities,
+        }
+    }
+
+    fn entity(index: u32, components: Vec<Box<dyn PartialReflect>>) -> DynamicEntity {
+        DynamicEntity {
+            entity: Entity::from_raw(index),
+            components,
+        }
+    }
+
+    #[test]
+    fn diff_and_apply() {
+        let old = scene(vec![
+            entity(0, vec![Box::new(A(1)), Box::new(B(1))]),
+            entity(1, vec![Box::new(A(1))]),
+        ]);
+        let new = scene(vec![
+            entity(0, vec![Box::new(A(2))]),
+            entity(2, vec![Box::new(B(3))]),
+        ]);
+
+        let diff = SceneDiff::new(&old, &new);
+        assert_eq!(diff.changes.entities.len(), 2);
+        assert_eq!(diff.removed_entities, vec![Entity::from_raw(1)]);
+        assert_eq!(
+            diff.removed_components,
+            vec![(Entity::from_raw(0), vec![TypeId::of::<B>()])]
+        );
+        assert!(SceneDiff::new(&new, &new).is_empty());
+
+        let mut world = World::new();
+        let type_registry = AppTypeRegistry::default();
+        type_registry.write().register::<A>();
+        type_registry.write().register::<B>();
+        world.insert_resource(type_registry);
+
+        let mut entity_map = EntityHashMap::default();
+        old.write_to_world(&mut world, &mut entity_map).unwrap();
+        let kept = entity_map[&Entity::from_raw(0)];
+        let removed = entity_map[&Entity::from_raw(1)];
+
+        diff.apply(&mut world, &mut entity_map).unwrap();
+        assert_eq!(world.get::<A>(kept), Some(&A(2)));
+        assert!(world.get::<B>(kept).is_none());
+        assert!(world.get_entity(removed).is_err());
+        assert_eq!(
+            world.get::<B>(entity_map[&Entity::from_raw(2)]),
+            Some(&B(3))
+        );
+    }
+}
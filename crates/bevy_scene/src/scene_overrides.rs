@@ -0,0 +1,140 @@
+use crate::{DynamicEntity, DynamicScene, InstanceId, SceneSpawnError, SceneSpawner};
+use bevy_ecs::{
+    component::Component,
+    entity::{Entity, EntityHashMap, EntityMapper},
+    prelude::ReflectComponent,
+    reflect::{AppTypeRegistry, ReflectMapEntities},
+    world::World,
+};
+use bevy_reflect::{prelude::ReflectDefault, PartialReflect, Reflect};
+
+/// Component overrides for the entities of the scene instance spawned from the
+/// [`SceneRoot`](crate::SceneRoot) or [`DynamicSceneRoot`](crate::DynamicSceneRoot) of this
+/// entity.
+///
+/// This makes the scene a prefab: each instance of it can change some of the components of its
+/// entities, while the rest still follows the scene, even when the scene is reloaded.
+///
+/// Each [`DynamicEntity`] of the overrides is identified by the entity it overrides within the
+/// scene, and its components are applied to that entity every time the scene is spawned for this
+/// entity, including when the scene is reloaded. Overriding an entity that isn't in the scene
+/// adds a new entity to the instance. The entities that the components reference are mapped like
+/// the ones of the scene.
+///
+/// Use [`SceneOverrides::record`] to record the changes made to an instance, and
+/// [`DynamicSceneBuilder::remove_scene_instance_entities`](crate::DynamicSceneBuilder::remove_scene_instance_entities)
+/// to serialize a scene containing instances as references to their scenes and their overrides.
+#[derive(Component, Reflect, Clone, Default)]
+#[reflect(opaque)]
+#[reflect(Component, Default)]
+pub struct SceneOverrides {
+    /// The overridden entities, and their overridden components.
+    pub entities: Vec<DynamicEntity>,
+}
+
+impl SceneOverrides {
+    /// Records the components of the entities of a scene instance that differ from the ones in
+    /// the `scene` it was spawned from.
+    ///
+    /// Only the components of the scene are compared, so components added to the instance, like
+    /// the [`Parent`](bevy_hierarchy::Parent) of its root entity, aren't recorded.
+    pub fn record(
+        world: &World,
+        instance_id: InstanceId,
+        scene: &DynamicScene,
+    ) -> Result<Self, SceneSpawnError> {
+        let Some(instance) = world
+            .get_resource::<SceneSpawner>()
+            .and_then(|scene_spawner| scene_spawner.spawned_instances.get(&instance_id))
+        else {
+            return Ok(Self::default());
+        };
+        let mut scene_entities = EntityHashMap::default();
+        for (&scene_entity, &entity) in &instance.entity_map {
+            scene_entities.insert(entity, scene_entity);
+        }
+
+        let type_registry = world.resource::<AppTypeRegistry>().read();
+        let mut entities = Vec::new();
+        for scene_entity in &scene.entities {
+            let Some(entity) = instance
+                .entity_map
+                .get(&scene_entity.entity)
+                .and_then(|&entity| world.get_entity(entity).ok())
+            else {
+                continue;
+            };
+
+            let mut components = Vec::new();
+            for component in &scene_entity.components {
+                let type_info = component.get_represented_type_info().ok_or_else(|| {
+                    SceneSpawnError::NoRepresentedType {
+                        type_path: component.reflect_type_path().to_string(),
+                    }
+                })?;
+                let registration = type_registry.get(type_info.type_id()).ok_or_else(|| {
+                    SceneSpawnError::UnregisteredButReflectedType {
+                        type_path: type_info.type_path().to_string(),
+                    }
+                })?;
+                let reflect_component =
+                    registration.data::<ReflectComponent>().ok_or_else(|| {
+                        SceneSpawnError::UnregisteredComponent {
+                            type_path: type_info.type_path().to_string(),
+                        }
+                    })?;
+                let Some(value) = reflect_component.reflect(entity) else {
+                    continue;
+                };
+
+                // Map the entities that the component references back to the ones of the scene,
+                // so that it can be compared to the scene, and applied to other instances.
+                let mut value = value.clone_value();
+                if let Some(map_entities) = registration.data::<ReflectMapEntities>() {
+                    map_entities.map_entities(
+                        value.as_partial_reflect_mut(),
+                        &mut InstanceEntityMapper(&scene_entities),
+                    );
+                }
+                if !value
+                    .reflect_partial_eq(component.as_partial_reflect())
+                    .unwrap_or_default()
+                {
+                    components.push(value);
+                }
+            }
+
+            if !components.is_empty() {
+                entities.push(DynamicEntity {
+                    entity: scene_entity.entity,
+                    components,
+                });
+            }
+        }
+
+        Ok(Self { entities })
+    }
+
+    /// Applies the overrides to the entities of a scene instance that was spawned with the given
+    /// `entity_map`.
+    pub(crate) fn apply(
+        &self,
+        world: &mut World,
+        entity_map: &mut EntityHashMap<Entity>,
+    ) -> Result<(), SceneSpawnError> {
+        let overrides = DynamicScene {
+            resources: Vec::new(),
+            entities: self.clone().entities,
+        };
+        overrides.write_to_world(world, entity_map)
+    }
+}
+
+/// Maps the entities of a scene instance back to the ones of its scene.
+struct InstanceEntityMapper<'a>(&'a EntityHashMap<Entity>);
+
+impl EntityMapper for InstanceEntityMapper<'_> {
+    fn map_entity(&mut self, entity: Entity) -> Entity {
+        self.0.get(&entity).copied().unwrap_or(entity)
+    }
+}
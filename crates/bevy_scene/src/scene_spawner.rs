@@ -1,4 +1,4 @@
-use crate::{DynamicScene, Scene};
+use crate::{DynamicScene, Scene, SceneDiff, SceneOverrides};
 use bevy_asset::{AssetEvent, AssetId, Assets, Handle};
 use bevy_ecs::{
     entity::{Entity, EntityHashMap},
@@ -79,6 +79,9 @@ pub struct SceneSpawner {
     scenes_to_despawn: Vec<AssetId<DynamicScene>>,
     instances_to_despawn: Vec<InstanceId>,
     scenes_with_parent: Vec<(InstanceId, Entity)>,
+    instance_parents: HashMap<InstanceId, Entity>,
+    /// The versions of the spawned dynamic scenes that their instances were last updated to.
+    dynamic_scene_snapshots: HashMap<AssetId<DynamicScene>, DynamicScene>,
 }
 
 /// Errors that can occur when spawning a scene.
@@ -189,7 +192,9 @@ impl SceneSpawner {
         world: &mut World,
         id: impl Into<AssetId<DynamicScene>>,
     ) -> Result<(), SceneSpawnError> {
-        if let Some(instance_ids) = self.spawned_dynamic_scenes.remove(&id.into()) {
+        let id = id.into();
+        self.dynamic_scene_snapshots.remove(&id);
+        if let Some(instance_ids) = self.spawned_dynamic_scenes.remove(&id) {
             for instance_id in instance_ids {
                 self.despawn_instance_sync(world, &instance_id);
             }
@@ -199,6 +204,16 @@ impl SceneSpawner {
 
     /// Immediately despawns a scene instance, removing all its entities from the world.
     pub fn despawn_instance_sync(&mut self, world: &mut World, instance_id: &InstanceId) {
+        self.instance_parents.remove(instance_id);
+        let ids = self
+            .spawned_dynamic_scenes
+            .iter()
+            .filter(|(_, instance_ids)| instance_ids.contains(instance_id))
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        for id in ids {
+            self.remove_dynamic_instance(id, instance_id);
+        }
         if let Some(instance) = self.spawned_instances.remove(instance_id) {
             for &entity in instance.entity_map.values() {
                 if let Ok(mut entity_mut) = world.get_entity_mut(entity) {
@@ -218,6 +233,7 @@ impl SceneSpawner {
         let mut entity_map = EntityHashMap::default();
         let id = id.into();
         Self::spawn_dynamic_internal(world, id, &mut entity_map)?;
+        self.snapshot_dynamic_scene(world, id);
         let instance_id = InstanceId::new();
         self.spawned_instances
             .insert(instance_id, InstanceInfo { entity_map });
@@ -240,6 +256,31 @@ impl SceneSpawner {
         })
     }
 
+    /// Forgets an instance of a dynamic scene, along with the copy of the scene once it has no
+    /// instances left.
+    pub(crate) fn remove_dynamic_instance(
+        &mut self,
+        id: AssetId<DynamicScene>,
+        instance_id: &InstanceId,
+    ) {
+        if let Some(instance_ids) = self.spawned_dynamic_scenes.get_mut(&id) {
+            instance_ids.remove(instance_id);
+            if instance_ids.is_empty() {
+                self.dynamic_scene_snapshots.remove(&id);
+            }
+        }
+    }
+
+    /// Keeps a copy of a spawned dynamic scene, to update its instances in place once it's modified.
+    fn snapshot_dynamic_scene(&mut self, world: &World, id: AssetId<DynamicScene>) {
+        if self.dynamic_scene_snapshots.contains_key(&id) {
+            return;
+        }
+        if let Some(scene) = world.resource::<Assets<DynamicScene>>().get(id) {
+            self.dynamic_scene_snapshots.insert(id, scene.clone());
+        }
+    }
+
     /// Immediately spawns a new instance of the provided scene.
     pub fn spawn_sync(
         &mut self,
@@ -273,20 +314,64 @@ impl SceneSpawner {
         })
     }
 
+    /// Applies the [`SceneOverrides`] of the parent of a scene instance to its entities.
+    fn apply_overrides_internal(
+        world: &mut World,
+        parent: Option<Entity>,
+        entity_map: &mut EntityHashMap<Entity>,
+    ) -> Result<(), SceneSpawnError> {
+        let Some(overrides) =
+            parent.and_then(|parent| world.get::<SceneOverrides>(parent).cloned())
+        else {
+            return Ok(());
+        };
+        overrides.apply(world, entity_map)
+    }
+
     /// Iterate through all instances of the provided scenes and update those immediately.
     ///
     /// Useful for updating already spawned scene instances after their corresponding scene has been modified.
+    /// Only the changes since the version of the scene the instances were last updated to are
+    /// applied, as a [`SceneDiff`], so components that didn't change keep their runtime state.
+    /// The [`SceneOverrides`] of the instances spawned as children are then applied again.
     pub fn update_spawned_scenes(
         &mut self,
         world: &mut World,
         scene_ids: &[AssetId<DynamicScene>],
     ) -> Result<(), SceneSpawnError> {
         for id in scene_ids {
-            if let Some(spawned_instances) = self.spawned_dynamic_scenes.get(id) {
-                for instance_id in spawned_instances {
-                    if let Some(instance_info) = self.spawned_instances.get_mut(instance_id) {
+            let Some(spawned_instances) = self
+                .spawned_dynamic_scenes
+                .get(id)
+                .filter(|instance_ids| !instance_ids.is_empty())
+            else {
+                continue;
+            };
+            let scene = world
+                .resource::<Assets<DynamicScene>>()
+                .get(*id)
+                .ok_or(SceneSpawnError::NonExistentScene { id: *id })?
+                .clone();
+            let diff = self
+                .dynamic_scene_snapshots
+                .insert(*id, scene)
+                .map(|snapshot| SceneDiff::new(&snapshot, &self.dynamic_scene_snapshots[id]));
+            if diff.as_ref().is_some_and(SceneDiff::is_empty) {
+                continue;
+            }
+
+            for instance_id in spawned_instances {
+                if let Some(instance_info) = self.spawned_instances.get_mut(instance_id) {
+                    if let Some(diff) = &diff {
+                        diff.apply(world, &mut instance_info.entity_map)?;
+                    } else {
                         Self::spawn_dynamic_internal(world, *id, &mut instance_info.entity_map)?;
                     }
+                    Self::apply_overrides_internal(
+                        world,
+                        self.instance_parents.get(instance_id).copied(),
+                        &mut instance_info.entity_map,
+                    )?;
                 }
             }
         }
@@ -313,6 +398,9 @@ impl SceneSpawner {
     }
 
     /// Immediately spawns all scenes scheduled for spawn.
+    ///
+    /// The [`SceneOverrides`] of the parents of the scenes spawned as children are applied to
+    /// their instances.
     pub fn spawn_queued_scenes(&mut self, world: &mut World) -> Result<(), SceneSpawnError> {
         let scenes_to_spawn = core::mem::take(&mut self.dynamic_scenes_to_spawn);
 
@@ -321,6 +409,11 @@ impl SceneSpawner {
 
             match Self::spawn_dynamic_internal(world, handle.id(), &mut entity_map) {
                 Ok(_) => {
+                    Self::apply_overrides_internal(world, parent, &mut entity_map)?;
+                    self.snapshot_dynamic_scene(world, handle.id());
+                    if let Some(parent) = parent {
+                        self.instance_parents.insert(instance_id, parent);
+                    }
                     self.spawned_instances
                         .insert(instance_id, InstanceInfo { entity_map });
                     let spawned = self
@@ -351,6 +444,7 @@ impl SceneSpawner {
 
             match Self::spawn_sync_internal(world, scene_handle.id(), &mut entity_map) {
                 Ok(_) => {
+                    Self::apply_overrides_internal(world, parent, &mut entity_map)?;
                     self.spawned_instances
                         .insert(instance_id, InstanceInfo { entity_map });
 
@@ -526,7 +620,9 @@ mod tests {
     };
     use bevy_reflect::Reflect;
 
-    use crate::{DynamicSceneBuilder, DynamicSceneRoot, ScenePlugin};
+    use crate::{
+        DynamicEntity, DynamicSceneBuilder, DynamicSceneRoot, SceneOverrides, ScenePlugin,
+    };
 
     use super::*;
     use crate::{DynamicScene, SceneSpawner};
@@ -654,6 +750,50 @@ mod tests {
         assert_eq!(old_a, new_a);
     }
 
+    #[test]
+    fn update_dynamic_entities_in_place() {
+        let mut world = World::default();
+
+        // setup
+        let atr = AppTypeRegistry::default();
+        atr.write().register::<A>();
+        atr.write().register::<ComponentA>();
+        world.insert_resource(atr);
+        world.insert_resource(Assets::<DynamicScene>::default());
+
+        let scene_entity = Entity::from_raw(0);
+        let scene = |a: usize| DynamicScene {
+            resources: Vec::new(),
+            entities: vec![DynamicEntity {
+                entity: scene_entity,
+                components: vec![Box::new(A(a)), Box::new(ComponentA { x: 1.0, y: 1.0 })],
+            }],
+        };
+        let scene_id = world.resource_mut::<Assets<DynamicScene>>().add(scene(1));
+
+        let mut scene_spawner = SceneSpawner::default();
+        let instance_id = scene_spawner
+            .spawn_dynamic_sync(&mut world, &scene_id)
+            .unwrap();
+        let entity = scene_spawner
+            .iter_instance_entities(instance_id)
+            .next()
+            .unwrap();
+
+        // change the runtime state of the instance, then modify the scene
+        world.get_mut::<ComponentA>(entity).unwrap().x = 9.0;
+        world
+            .resource_mut::<Assets<DynamicScene>>()
+            .insert(&scene_id, scene(2));
+        scene_spawner
+            .update_spawned_scenes(&mut world, &[scene_id.id()])
+            .unwrap();
+
+        // only the component that changed in the scene was updated
+        assert_eq!(world.get::<A>(entity), Some(&A(2)));
+        assert_eq!(world.get::<ComponentA>(entity).unwrap().x, 9.0);
+    }
+
     #[derive(Component, Reflect, Default)]
     #[reflect(Component)]
     struct ComponentF;
@@ -838,6 +978,13 @@ mod tests {
                 expected_count
             );
             assert_eq!(scene_spawner.spawned_instances.len(), expected_count);
+            // The copy of the scene is only kept while it has instances.
+            assert_eq!(
+                scene_spawner
+                    .dynamic_scene_snapshots
+                    .contains_key(&scene.id()),
+                expected_count > 0
+            );
         };
 
         // Spawn scene.
@@ -863,4 +1010,70 @@ mod tests {
         app.update();
         check(app.world_mut(), 0);
     }
+
+    #[test]
+    fn scene_overrides() {
+        let mut app = App::new();
+        app.add_plugins((HierarchyPlugin, AssetPlugin::default(), ScenePlugin))
+            .register_type::<ComponentA>();
+
+        let mut scene_world = World::new();
+        scene_world.insert_resource(app.world().resource::<AppTypeRegistry>().clone());
+        let scene_entity = scene_world.spawn(ComponentA { x: 3.0, y: 4.0 }).id();
+        let scene = DynamicScene::from_world(&scene_world);
+        let scene_handle = app
+            .world_mut()
+            .resource_mut::<Assets<DynamicScene>>()
+            .add(DynamicScene::from_world(&scene_world));
+
+        // Spawn the scene with an override of `ComponentA`.
+        let root = app
+            .world_mut()
+            .spawn((
+                DynamicSceneRoot(scene_handle),
+                SceneOverrides {
+                    entities: vec![DynamicEntity {
+                        entity: scene_entity,
+                        components: vec![Box::new(ComponentA { x: 5.0, y: 4.0 })],
+                    }],
+                },
+            ))
+            .id();
+        app.update();
+
+        let (instance_entity, component_a) = app
+            .world_mut()
+            .query::<(Entity, &ComponentA)>()
+            .single(app.world());
+        assert_eq!(component_a.x, 5.0);
+        assert_eq!(component_a.y, 4.0);
+
+        // Record the overrides back from the instance.
+        app.world_mut()
+            .get_mut::<ComponentA>(instance_entity)
+            .unwrap()
+            .y = 6.0;
+        let instance_id = **app.world().get::<SceneInstance>(root).unwrap();
+        let overrides = SceneOverrides::record(app.world(), instance_id, &scene).unwrap();
+        assert_eq!(overrides.entities.len(), 1);
+        assert_eq!(overrides.entities[0].entity, scene_entity);
+        let component_a = overrides.entities[0].components[0]
+            .try_downcast_ref::<ComponentA>()
+            .unwrap();
+        assert_eq!(component_a.x, 5.0);
+        assert_eq!(component_a.y, 6.0);
+
+        // Only the root is extracted, without the instance it's the parent of.
+        let extracted = DynamicSceneBuilder::from_world(app.world())
+            .extract_entities(app.world().iter_entities().map(|entity| entity.id()))
+            .remove_scene_instance_entities()
+            .remove_empty_entities()
+            .build();
+        assert_eq!(extracted.entities.len(), 1);
+        assert_eq!(extracted.entities[0].entity, root);
+        assert!(extracted.entities[0]
+            .components
+            .iter()
+            .all(|component| !component.represents::<Children>()));
+    }
 }
@@ -1,21 +1,25 @@
 //! `serde` serialization and deserialization implementation for Bevy scenes.
 
-use crate::{DynamicEntity, DynamicScene};
+use crate::{DynamicEntity, DynamicScene, SceneOverrides};
+use bevy_asset::{
+    AssetPath, AssetServer, LoadContext, ReflectHandle, UntypedAssetId, UntypedHandle,
+};
 use bevy_ecs::entity::Entity;
 use bevy_reflect::{
     serde::{
-        ReflectDeserializer, TypeRegistrationDeserializer, TypedReflectDeserializer,
-        TypedReflectSerializer,
+        ReflectDeserializer, ReflectDeserializerProcessor, ReflectSerializerProcessor,
+        TypeRegistrationDeserializer, TypedReflectDeserializer, TypedReflectSerializer,
     },
-    PartialReflect, ReflectFromReflect, TypeRegistry,
+    PartialReflect, ReflectFromReflect, TypeRegistration, TypeRegistry,
 };
 use bevy_utils::HashSet;
-use core::fmt::Formatter;
+use core::{any::TypeId, fmt::Formatter};
 use serde::{
     de::{DeserializeSeed, Error, MapAccess, SeqAccess, Visitor},
     ser::{SerializeMap, SerializeStruct},
     Deserialize, Deserializer, Serialize, Serializer,
 };
+use uuid::Uuid;
 
 /// Name of the serialized scene struct type.
 pub const SCENE_STRUCT: &str = "Scene";
@@ -34,6 +38,12 @@ pub const ENTITY_FIELD_COMPONENTS: &str = "components";
 /// Helper object defining Bevy's serialize format for a [`DynamicScene`] and implementing
 /// the [`Serialize`] trait for use with Serde.
 ///
+/// [`Handle`]s to assets loaded from a path are serialized as that path, so that deserializing the
+/// scene with a [`SceneAssetLoader`] loads the assets again. Handles with a constant [`Uuid`] are
+/// serialized as that id, while handles to other assets can't be serialized.
+///
+/// [`Handle`]: bevy_asset::Handle
+///
 /// # Example
 ///
 /// ```
@@ -156,6 +166,10 @@ impl<'a> Serialize for EntitySerializer<'a> {
 /// deserializing through [`SceneMapDeserializer`].
 ///
 /// Note: The entries are sorted by type path before they're serialized.
+///
+/// The [`Handle`]s in the entries are serialized as described in [`SceneSerializer`].
+///
+/// [`Handle`]: bevy_asset::Handle
 pub struct SceneMapSerializer<'a> {
     /// List of boxed values of unique type to serialize.
     pub entries: &'a [Box<dyn PartialReflect>],
@@ -187,7 +201,11 @@ impl<'a> Serialize for SceneMapSerializer<'a> {
         for (type_path, partial_reflect) in sorted_entries {
             state.serialize_entry(
                 type_path,
-                &TypedReflectSerializer::new(partial_reflect, self.registry),
+                &TypedReflectSerializer::with_processor(
+                    partial_reflect,
+                    self.registry,
+                    &SceneSerializerProcessor,
+                ),
             )?;
         }
         state.end()
@@ -211,6 +229,35 @@ enum EntityField {
 pub struct SceneDeserializer<'a> {
     /// Type registry in which the components and resources types used in the scene to deserialize are registered.
     pub type_registry: &'a TypeRegistry,
+    /// Loads the assets that the [`Handle`]s in the scene refer to by path.
+    ///
+    /// Deserializing a scene that contains such handles fails without one.
+    ///
+    /// [`Handle`]: bevy_asset::Handle
+    pub asset_loader: Option<&'a mut dyn SceneAssetLoader>,
+}
+
+impl<'a> SceneDeserializer<'a> {
+    /// Creates a deserializer for scenes whose types are registered in `type_registry`.
+    ///
+    /// Without an [asset loader](Self::with_asset_loader), the scene can't contain [`Handle`]s to
+    /// assets loaded from a path.
+    ///
+    /// [`Handle`]: bevy_asset::Handle
+    pub fn new(type_registry: &'a TypeRegistry) -> Self {
+        Self {
+            type_registry,
+            asset_loader: None,
+        }
+    }
+
+    /// Loads the assets that the [`Handle`]s in the scene refer to by path with `asset_loader`.
+    ///
+    /// [`Handle`]: bevy_asset::Handle
+    pub fn with_asset_loader(mut self, asset_loader: &'a mut dyn SceneAssetLoader) -> Self {
+        self.asset_loader = Some(asset_loader);
+        self
+    }
 }
 
 impl<'a, 'de> DeserializeSeed<'de> for SceneDeserializer<'a> {
@@ -225,6 +272,7 @@ impl<'a, 'de> DeserializeSeed<'de> for SceneDeserializer<'a> {
             &[SCENE_RESOURCES, SCENE_ENTITIES],
             SceneVisitor {
                 type_registry: self.type_registry,
+                asset_loader: self.asset_loader,
             },
         )
     }
@@ -232,6 +280,7 @@ impl<'a, 'de> DeserializeSeed<'de> for SceneDeserializer<'a> {
 
 struct SceneVisitor<'a> {
     pub type_registry: &'a TypeRegistry,
+    pub asset_loader: Option<&'a mut dyn SceneAssetLoader>,
 }
 
 impl<'a, 'de> Visitor<'de> for SceneVisitor<'a> {
@@ -241,19 +290,21 @@ impl<'a, 'de> Visitor<'de> for SceneVisitor<'a> {
         formatter.write_str("scene struct")
     }
 
-    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    fn visit_seq<A>(mut self, mut seq: A) -> Result<Self::Value, A::Error>
     where
         A: SeqAccess<'de>,
     {
         let resources = seq
             .next_element_seed(SceneMapDeserializer {
                 registry: self.type_registry,
+                asset_loader: reborrow(&mut self.asset_loader),
             })?
             .ok_or_else(|| Error::missing_field(SCENE_RESOURCES))?;
 
         let entities = seq
             .next_element_seed(SceneEntitiesDeserializer {
                 type_registry: self.type_registry,
+                asset_loader: reborrow(&mut self.asset_loader),
             })?
             .ok_or_else(|| Error::missing_field(SCENE_ENTITIES))?;
 
@@ -263,7 +314,7 @@ impl<'a, 'de> Visitor<'de> for SceneVisitor<'a> {
         })
     }
 
-    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    fn visit_map<A>(mut self, mut map: A) -> Result<Self::Value, A::Error>
     where
         A: MapAccess<'de>,
     {
@@ -277,6 +328,7 @@ impl<'a, 'de> Visitor<'de> for SceneVisitor<'a> {
                     }
                     resources = Some(map.next_value_seed(SceneMapDeserializer {
                         registry: self.type_registry,
+                        asset_loader: reborrow(&mut self.asset_loader),
                     })?);
                 }
                 SceneField::Entities => {
@@ -285,6 +337,7 @@ impl<'a, 'de> Visitor<'de> for SceneVisitor<'a> {
                     }
                     entities = Some(map.next_value_seed(SceneEntitiesDeserializer {
                         type_registry: self.type_registry,
+                        asset_loader: reborrow(&mut self.asset_loader),
                     })?);
                 }
             }
@@ -304,6 +357,26 @@ impl<'a, 'de> Visitor<'de> for SceneVisitor<'a> {
 pub struct SceneEntitiesDeserializer<'a> {
     /// Type registry in which the component types used by the entities to deserialize are registered.
     pub type_registry: &'a TypeRegistry,
+    /// Loads the assets that the handles in the components refer to, as in [`SceneDeserializer`].
+    pub asset_loader: Option<&'a mut dyn SceneAssetLoader>,
+}
+
+impl<'a> SceneEntitiesDeserializer<'a> {
+    /// Creates a deserializer for entities whose component types are registered in
+    /// `type_registry`, as [`SceneDeserializer::new`] does.
+    pub fn new(type_registry: &'a TypeRegistry) -> Self {
+        Self {
+            type_registry,
+            asset_loader: None,
+        }
+    }
+
+    /// Loads the assets that the handles in the components refer to with `asset_loader`, as
+    /// [`SceneDeserializer::with_asset_loader`] does.
+    pub fn with_asset_loader(mut self, asset_loader: &'a mut dyn SceneAssetLoader) -> Self {
+        self.asset_loader = Some(asset_loader);
+        self
+    }
 }
 
 impl<'a, 'de> DeserializeSeed<'de> for SceneEntitiesDeserializer<'a> {
@@ -315,12 +388,14 @@ impl<'a, 'de> DeserializeSeed<'de> for SceneEntitiesDeserializer<'a> {
     {
         deserializer.deserialize_map(SceneEntitiesVisitor {
             type_registry: self.type_registry,
+            asset_loader: self.asset_loader,
         })
     }
 }
 
 struct SceneEntitiesVisitor<'a> {
     pub type_registry: &'a TypeRegistry,
+    pub asset_loader: Option<&'a mut dyn SceneAssetLoader>,
 }
 
 impl<'a, 'de> Visitor<'de> for SceneEntitiesVisitor<'a> {
@@ -330,7 +405,7 @@ impl<'a, 'de> Visitor<'de> for SceneEntitiesVisitor<'a> {
         formatter.write_str("map of entities")
     }
 
-    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    fn visit_map<A>(mut self, mut map: A) -> Result<Self::Value, A::Error>
     where
         A: MapAccess<'de>,
     {
@@ -339,6 +414,7 @@ impl<'a, 'de> Visitor<'de> for SceneEntitiesVisitor<'a> {
             let entity = map.next_value_seed(SceneEntityDeserializer {
                 entity,
                 type_registry: self.type_registry,
+                asset_loader: reborrow(&mut self.asset_loader),
             })?;
             entities.push(entity);
         }
@@ -353,6 +429,27 @@ pub struct SceneEntityDeserializer<'a> {
     pub entity: Entity,
     /// Type registry in which the component types used by the entity to deserialize are registered.
     pub type_registry: &'a TypeRegistry,
+    /// Loads the assets that the handles in the components refer to, as in [`SceneDeserializer`].
+    pub asset_loader: Option<&'a mut dyn SceneAssetLoader>,
+}
+
+impl<'a> SceneEntityDeserializer<'a> {
+    /// Creates a deserializer for the given `entity`, whose component types are registered in
+    /// `type_registry`, as [`SceneDeserializer::new`] does.
+    pub fn new(entity: Entity, type_registry: &'a TypeRegistry) -> Self {
+        Self {
+            entity,
+            type_registry,
+            asset_loader: None,
+        }
+    }
+
+    /// Loads the assets that the handles in the components refer to with `asset_loader`, as
+    /// [`SceneDeserializer::with_asset_loader`] does.
+    pub fn with_asset_loader(mut self, asset_loader: &'a mut dyn SceneAssetLoader) -> Self {
+        self.asset_loader = Some(asset_loader);
+        self
+    }
 }
 
 impl<'a, 'de> DeserializeSeed<'de> for SceneEntityDeserializer<'a> {
@@ -368,6 +465,7 @@ impl<'a, 'de> DeserializeSeed<'de> for SceneEntityDeserializer<'a> {
             SceneEntityVisitor {
                 entity: self.entity,
                 registry: self.type_registry,
+                asset_loader: self.asset_loader,
             },
         )
     }
@@ -376,6 +474,7 @@ impl<'a, 'de> DeserializeSeed<'de> for SceneEntityDeserializer<'a> {
 struct SceneEntityVisitor<'a> {
     pub entity: Entity,
     pub registry: &'a TypeRegistry,
+    pub asset_loader: Option<&'a mut dyn SceneAssetLoader>,
 }
 
 impl<'a, 'de> Visitor<'de> for SceneEntityVisitor<'a> {
@@ -392,6 +491,7 @@ impl<'a, 'de> Visitor<'de> for SceneEntityVisitor<'a> {
         let components = seq
             .next_element_seed(SceneMapDeserializer {
                 registry: self.registry,
+                asset_loader: self.asset_loader,
             })?
             .ok_or_else(|| Error::missing_field(ENTITY_FIELD_COMPONENTS))?;
 
@@ -401,7 +501,7 @@ impl<'a, 'de> Visitor<'de> for SceneEntityVisitor<'a> {
         })
     }
 
-    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    fn visit_map<A>(mut self, mut map: A) -> Result<Self::Value, A::Error>
     where
         A: MapAccess<'de>,
     {
@@ -415,6 +515,7 @@ impl<'a, 'de> Visitor<'de> for SceneEntityVisitor<'a> {
 
                     components = Some(map.next_value_seed(SceneMapDeserializer {
                         registry: self.registry,
+                        asset_loader: reborrow(&mut self.asset_loader),
                     })?);
                 }
             }
@@ -434,6 +535,8 @@ impl<'a, 'de> Visitor<'de> for SceneEntityVisitor<'a> {
 pub struct SceneMapDeserializer<'a> {
     /// Type registry in which the types of the values to deserialize are registered.
     pub registry: &'a TypeRegistry,
+    /// Loads the assets that the handles in the values refer to, as in [`SceneDeserializer`].
+    pub asset_loader: Option<&'a mut dyn SceneAssetLoader>,
 }
 
 impl<'a, 'de> DeserializeSeed<'de> for SceneMapDeserializer<'a> {
@@ -445,12 +548,14 @@ impl<'a, 'de> DeserializeSeed<'de> for SceneMapDeserializer<'a> {
     {
         deserializer.deserialize_map(SceneMapVisitor {
             registry: self.registry,
+            asset_loader: self.asset_loader,
         })
     }
 }
 
 struct SceneMapVisitor<'a> {
     pub registry: &'a TypeRegistry,
+    pub asset_loader: Option<&'a mut dyn SceneAssetLoader>,
 }
 
 impl<'a, 'de> Visitor<'de> for SceneMapVisitor<'a> {
@@ -464,8 +569,14 @@ impl<'a, 'de> Visitor<'de> for SceneMapVisitor<'a> {
     where
         A: SeqAccess<'de>,
     {
+        let mut processor = SceneDeserializerProcessor {
+            asset_loader: self.asset_loader,
+        };
         let mut dynamic_properties = Vec::new();
-        while let Some(entity) = seq.next_element_seed(ReflectDeserializer::new(self.registry))? {
+        while let Some(entity) = seq.next_element_seed(ReflectDeserializer::with_processor(
+            self.registry,
+            &mut processor,
+        ))? {
             dynamic_properties.push(entity);
         }
 
@@ -476,6 +587,9 @@ impl<'a, 'de> Visitor<'de> for SceneMapVisitor<'a> {
     where
         A: MapAccess<'de>,
     {
+        let mut processor = SceneDeserializerProcessor {
+            asset_loader: self.asset_loader,
+        };
         let mut added = <HashSet<_>>::default();
         let mut entries = Vec::new();
         while let Some(registration) =
@@ -488,8 +602,11 @@ impl<'a, 'de> Visitor<'de> for SceneMapVisitor<'a> {
                 )));
             }
 
-            let value =
-                map.next_value_seed(TypedReflectDeserializer::new(registration, self.registry))?;
+            let value = map.next_value_seed(TypedReflectDeserializer::with_processor(
+                registration,
+                self.registry,
+                &mut processor,
+            ))?;
 
             // Attempt to convert using FromReflect.
             let value = self
@@ -507,13 +624,176 @@ impl<'a, 'de> Visitor<'de> for SceneMapVisitor<'a> {
     }
 }
 
+/// Loads the assets that the [`Handle`]s of a scene refer to by path, while the scene is being
+/// deserialized.
+///
+/// This is implemented for the [`LoadContext`] of an [`AssetLoader`], which loads the assets as
+/// dependencies of the scene, and for the [`AssetServer`], which is cheap to clone to get a mutable
+/// reference to.
+///
+/// [`Handle`]: bevy_asset::Handle
+/// [`AssetLoader`]: bevy_asset::AssetLoader
+pub trait SceneAssetLoader {
+    /// Begins loading the asset of the type with the given [`TypeId`] stored at `path`.
+    fn load_erased(&mut self, type_id: TypeId, path: AssetPath<'static>) -> UntypedHandle;
+}
+
+impl SceneAssetLoader for LoadContext<'_> {
+    fn load_erased(&mut self, type_id: TypeId, path: AssetPath<'static>) -> UntypedHandle {
+        self.loader().with_dynamic_type(type_id).load(path)
+    }
+}
+
+impl SceneAssetLoader for AssetServer {
+    fn load_erased(&mut self, type_id: TypeId, path: AssetPath<'static>) -> UntypedHandle {
+        AssetServer::load_erased(self, type_id, path)
+    }
+}
+
+/// Reborrows the [`SceneAssetLoader`] of a deserializer for a nested deserializer.
+fn reborrow<'a>(
+    asset_loader: &'a mut Option<&mut dyn SceneAssetLoader>,
+) -> Option<&'a mut dyn SceneAssetLoader> {
+    match asset_loader {
+        Some(asset_loader) => {
+            let asset_loader: &mut dyn SceneAssetLoader = &mut **asset_loader;
+            Some(asset_loader)
+        }
+        None => None,
+    }
+}
+
+/// The serialized form of a [`Handle`].
+///
+/// [`Handle`]: bevy_asset::Handle
+#[derive(Serialize, Deserialize)]
+enum SerializedHandle {
+    /// A handle to the asset loaded from this path.
+    Path(String),
+    /// A handle to the asset with this constant id.
+    Uuid(Uuid),
+}
+
+/// Serializes [`Handle`]s as a [`SerializedHandle`], and the entities of [`SceneOverrides`] like
+/// the ones of a scene.
+///
+/// [`Handle`]: bevy_asset::Handle
+struct SceneSerializerProcessor;
+
+impl ReflectSerializerProcessor for SceneSerializerProcessor {
+    fn try_serialize<S>(
+        &self,
+        value: &dyn PartialReflect,
+        registry: &TypeRegistry,
+        serializer: S,
+    ) -> Result<Result<S::Ok, S>, S::Error>
+    where
+        S: Serializer,
+    {
+        if let Some(overrides) = value.try_downcast_ref::<SceneOverrides>() {
+            return EntitiesSerializer {
+                entities: &overrides.entities,
+                registry,
+            }
+            .serialize(serializer)
+            .map(Ok);
+        }
+
+        let Some((registration, reflect_handle)) = value
+            .get_represented_type_info()
+            .and_then(|info| registry.get(info.type_id()))
+            .and_then(|registration| Some((registration, registration.data::<ReflectHandle>()?)))
+        else {
+            return Ok(Err(serializer));
+        };
+
+        // Dynamic representations of handles are converted back to handles first.
+        let handle = value
+            .try_as_reflect()
+            .and_then(|value| reflect_handle.downcast_handle_untyped(value.as_any()))
+            .or_else(|| {
+                let value = registration
+                    .data::<ReflectFromReflect>()?
+                    .from_reflect(value)?;
+                reflect_handle.downcast_handle_untyped((*value).as_any())
+            });
+        let Some(handle) = handle else {
+            return Ok(Err(serializer));
+        };
+
+        let serialized = match (handle.path(), handle.id()) {
+            (Some(path), _) => SerializedHandle::Path(path.to_string()),
+            (None, UntypedAssetId::Uuid { uuid, .. }) => SerializedHandle::Uuid(uuid),
+            (None, id @ UntypedAssetId::Index { .. }) => {
+                return Err(<S::Error as serde::ser::Error>::custom(format_args!(
+                    "cannot serialize a handle to an asset that wasn't loaded from a path: {id}"
+                )));
+            }
+        };
+        serialized.serialize(serializer).map(Ok)
+    }
+}
+
+/// Deserializes [`Handle`]s from a [`SerializedHandle`], loading the assets they refer to by path
+/// with a [`SceneAssetLoader`], and the entities of [`SceneOverrides`] like the ones of a scene.
+///
+/// [`Handle`]: bevy_asset::Handle
+struct SceneDeserializerProcessor<'a> {
+    asset_loader: Option<&'a mut dyn SceneAssetLoader>,
+}
+
+impl ReflectDeserializerProcessor for SceneDeserializerProcessor<'_> {
+    fn try_deserialize<'de, D>(
+        &mut self,
+        registration: &TypeRegistration,
+        registry: &TypeRegistry,
+        deserializer: D,
+    ) -> Result<Result<Box<dyn PartialReflect>, D>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if registration.type_id() == TypeId::of::<SceneOverrides>() {
+            let entities = SceneEntitiesDeserializer {
+                type_registry: registry,
+                asset_loader: reborrow(&mut self.asset_loader),
+            }
+            .deserialize(deserializer)?;
+            return Ok(Ok(Box::new(SceneOverrides { entities })));
+        }
+
+        let Some(reflect_handle) = registration.data::<ReflectHandle>() else {
+            return Ok(Err(deserializer));
+        };
+
+        let type_id = reflect_handle.asset_type_id();
+        let handle = match SerializedHandle::deserialize(deserializer)? {
+            SerializedHandle::Path(path) => {
+                let Some(asset_loader) = self.asset_loader.as_deref_mut() else {
+                    return Err(Error::custom(format_args!(
+                        "cannot load the asset at `{path}` without a scene asset loader"
+                    )));
+                };
+                let path = AssetPath::try_parse(&path)
+                    .map_err(Error::custom)?
+                    .into_owned();
+                asset_loader.load_erased(type_id, path)
+            }
+            SerializedHandle::Uuid(uuid) => {
+                UntypedHandle::Weak(UntypedAssetId::Uuid { type_id, uuid })
+            }
+        };
+        Ok(Ok(reflect_handle.typed(handle).into_partial_reflect()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
         ron,
-        serde::{SceneDeserializer, SceneSerializer},
-        DynamicScene, DynamicSceneBuilder,
+        serde::{SceneAssetLoader, SceneDeserializer, SceneSerializer},
+        DynamicEntity, DynamicScene, DynamicSceneBuilder, SceneOverrides,
     };
+    use bevy_asset::{Asset, AssetPath, Handle, ReflectHandle, UntypedAssetId, UntypedHandle};
     use bevy_ecs::{
         entity::{Entity, EntityHashMap, VisitEntities, VisitEntitiesMut},
         prelude::{Component, ReflectComponent, ReflectResource, Resource, World},
@@ -523,8 +803,10 @@ mod tests {
     };
     use bevy_reflect::{Reflect, ReflectDeserialize, ReflectSerialize};
     use bincode::Options;
+    use core::any::TypeId;
     use serde::{de::DeserializeSeed, Deserialize, Serialize};
     use std::io::BufReader;
+    use uuid::Uuid;
 
     #[derive(Component, Reflect, Default)]
     #[reflect(Component)]
@@ -594,6 +876,13 @@ mod tests {
         }
     }
 
+    #[derive(Asset, Reflect)]
+    struct MyAsset;
+
+    #[derive(Component, Reflect, PartialEq)]
+    #[reflect(Component, PartialEq)]
+    struct MyAssetRef(Handle<MyAsset>);
+
     fn create_world() -> World {
         let mut world = World::new();
         let registry = AppTypeRegistry::default();
@@ -612,6 +901,9 @@ mod tests {
             registry.register::<MyEntityRef>();
             registry.register::<Entity>();
             registry.register::<MyResource>();
+            registry.register::<MyAssetRef>();
+            registry.register::<Handle<MyAsset>>();
+            registry.register_type_data::<Handle<MyAsset>, ReflectHandle>();
         }
         world.insert_resource(registry);
         world
@@ -697,9 +989,8 @@ mod tests {
   },
 )"#;
         let mut deserializer = ron::de::Deserializer::from_str(input).unwrap();
-        let scene_deserializer = SceneDeserializer {
-            type_registry: &world.resource::<AppTypeRegistry>().read(),
-        };
+        let type_registry = world.resource::<AppTypeRegistry>().read();
+        let scene_deserializer = SceneDeserializer::new(&type_registry);
         let scene = scene_deserializer.deserialize(&mut deserializer).unwrap();
 
         assert_eq!(
@@ -732,9 +1023,7 @@ mod tests {
         let registry = world.resource::<AppTypeRegistry>().read();
         let serialized = scene.serialize(&registry).unwrap();
         let mut deserializer = ron::de::Deserializer::from_str(&serialized).unwrap();
-        let scene_deserializer = SceneDeserializer {
-            type_registry: &registry,
-        };
+        let scene_deserializer = SceneDeserializer::new(&registry);
         let deserialized_scene = scene_deserializer.deserialize(&mut deserializer).unwrap();
         (scene, deserialized_scene)
     }
@@ -778,6 +1067,111 @@ mod tests {
             .all(|r| world.get_entity(r.0).is_err()));
     }
 
+    #[test]
+    fn should_roundtrip_handles_with_uuids() {
+        let mut world = create_world();
+        world.spawn(MyAssetRef(Handle::weak_from_u128(42)));
+
+        let serialized = DynamicScene::from_world(&world)
+            .serialize(&world.resource::<AppTypeRegistry>().read())
+            .unwrap();
+        assert!(serialized.contains(
+            r#""bevy_scene::serde::tests::MyAssetRef": (Uuid("00000000-0000-0000-0000-00000000002a"))"#
+        ));
+
+        let (scene, deserialized_scene) = roundtrip_ron(&world);
+        assert_eq!(1, deserialized_scene.entities.len());
+        assert_scene_eq(&scene, &deserialized_scene);
+    }
+
+    #[test]
+    fn should_roundtrip_scene_overrides() {
+        let mut world = create_world();
+        world
+            .resource::<AppTypeRegistry>()
+            .write()
+            .register::<SceneOverrides>();
+        let overridden = Entity::from_raw(3);
+        world.spawn(SceneOverrides {
+            entities: vec![DynamicEntity {
+                entity: overridden,
+                components: vec![
+                    Box::new(Foo(5)),
+                    Box::new(MyAssetRef(Handle::weak_from_u128(42))),
+                ],
+            }],
+        });
+
+        let (scene, deserialized_scene) = roundtrip_ron(&world);
+        assert_eq!(1, deserialized_scene.entities.len());
+
+        let overrides = |scene: &DynamicScene| DynamicScene {
+            resources: Vec::new(),
+            entities: scene.entities[0].components[0]
+                .try_downcast_ref::<SceneOverrides>()
+                .unwrap()
+                .clone()
+                .entities,
+        };
+        let deserialized_overrides = overrides(&deserialized_scene);
+        assert_eq!(overridden, deserialized_overrides.entities[0].entity);
+        assert_scene_eq(&overrides(&scene), &deserialized_overrides);
+    }
+
+    #[test]
+    fn should_load_handle_paths() {
+        #[derive(Default)]
+        struct PathRecorder(Vec<(TypeId, AssetPath<'static>)>);
+
+        impl SceneAssetLoader for PathRecorder {
+            fn load_erased(&mut self, type_id: TypeId, path: AssetPath<'static>) -> UntypedHandle {
+                self.0.push((type_id, path));
+                UntypedHandle::Weak(UntypedAssetId::Uuid {
+                    type_id,
+                    uuid: Uuid::from_u128(7),
+                })
+            }
+        }
+
+        let world = create_world();
+        let registry = world.resource::<AppTypeRegistry>().read();
+        let input = r#"(
+  resources: {},
+  entities: {
+    4294967296: (
+      components: {
+        "bevy_scene::serde::tests::MyAssetRef": (Path("models/fox.glb#Mesh0")),
+      },
+    ),
+  },
+)"#;
+
+        let mut deserializer = ron::de::Deserializer::from_str(input).unwrap();
+        let error = SceneDeserializer::new(&registry)
+            .deserialize(&mut deserializer)
+            .unwrap_err();
+        assert!(
+            error.to_string().contains("without a scene asset loader"),
+            "{error}"
+        );
+
+        let mut recorder = PathRecorder::default();
+        let mut deserializer = ron::de::Deserializer::from_str(input).unwrap();
+        let scene = SceneDeserializer::new(&registry)
+            .with_asset_loader(&mut recorder)
+            .deserialize(&mut deserializer)
+            .unwrap();
+        assert_eq!(
+            recorder.0,
+            vec![(TypeId::of::<MyAsset>(), "models/fox.glb#Mesh0".into())]
+        );
+
+        let component = scene.entities[0].components[0]
+            .try_downcast_ref::<MyAssetRef>()
+            .unwrap();
+        assert_eq!(component.0, Handle::weak_from_u128(7));
+    }
+
     #[test]
     fn should_roundtrip_with_custom_serialization() {
         let mut world = create_world();
@@ -824,9 +1218,7 @@ mod tests {
             serialized_scene
         );
 
-        let scene_deserializer = SceneDeserializer {
-            type_registry: registry,
-        };
+        let scene_deserializer = SceneDeserializer::new(registry);
         let deserialized_scene = scene_deserializer
             .deserialize(&mut postcard::Deserializer::from_bytes(&serialized_scene))
             .unwrap();
@@ -866,9 +1258,7 @@ mod tests {
             buf
         );
 
-        let scene_deserializer = SceneDeserializer {
-            type_registry: registry,
-        };
+        let scene_deserializer = SceneDeserializer::new(registry);
         let mut reader = BufReader::new(buf.as_slice());
 
         let deserialized_scene = scene_deserializer
@@ -909,9 +1299,7 @@ mod tests {
             serialized_scene
         );
 
-        let scene_deserializer = SceneDeserializer {
-            type_registry: registry,
-        };
+        let scene_deserializer = SceneDeserializer::new(registry);
 
         let deserialized_scene = bincode::DefaultOptions::new()
             .with_fixint_encoding()
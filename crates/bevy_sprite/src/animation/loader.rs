@@ -0,0 +1,376 @@
+use core::{fmt, time::Duration};
+
+use bevy_asset::{io::Reader, AssetLoader, LoadContext, ParseAssetPathError};
+use bevy_image::TextureAtlasLayout;
+use bevy_math::{URect, UVec2};
+use bevy_utils::HashMap;
+use serde::{
+    de::{MapAccess, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+use thiserror::Error;
+
+use super::{SpriteAnimation, SpriteAnimationFrame, SpriteAnimationLoopMode, SpriteAnimationRange};
+
+/// Loads the JSON that Aseprite and TexturePacker export along with a sprite
+/// sheet as a [`SpriteAnimation`].
+///
+/// Both the "hash" and "array" layouts of the frames are supported. Every frame
+/// of the file is a frame of the animation, in order, and the tags of an
+/// Aseprite file become [`SpriteAnimation::ranges`]. The sprite sheet image is
+/// loaded from the path in the `meta` section, relative to the JSON file, and
+/// the layout of its frames is added as the `Layout` labeled asset.
+///
+/// The loader handles the `.aseprite.json` and `.atlas.json` extensions, so
+/// that it doesn't take over every JSON file.
+#[derive(Default)]
+pub struct SpriteAnimationLoader;
+
+/// The settings of a [`SpriteAnimationLoader`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SpriteAnimationLoaderSettings {
+    /// The duration of frames that don't have one in the file, which is the
+    /// case of every frame exported by TexturePacker.
+    pub default_frame_duration: Duration,
+    /// How the whole animation plays, when no range is selected.
+    pub loop_mode: SpriteAnimationLoopMode,
+}
+
+impl Default for SpriteAnimationLoaderSettings {
+    fn default() -> Self {
+        Self {
+            default_frame_duration: Duration::from_millis(100),
+            loop_mode: SpriteAnimationLoopMode::Loop,
+        }
+    }
+}
+
+/// An error when loading a [`SpriteAnimation`] with [`SpriteAnimationLoader`].
+#[derive(Error, Debug)]
+pub enum SpriteAnimationLoaderError {
+    /// An error occurred while reading the file.
+    #[error("Could not read the file: {0}")]
+    Io(#[from] std::io::Error),
+    /// The file isn't valid sprite sheet JSON.
+    #[error("Could not parse the sprite sheet JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    /// The path to the sprite sheet image isn't valid.
+    #[error("Invalid sprite sheet image path: {0}")]
+    ImagePath(#[from] ParseAssetPathError),
+    /// The frame is rotated in the sprite sheet, which sprites can't display.
+    #[error("Frame {0:?} is rotated in the sprite sheet, which isn't supported")]
+    RotatedFrame(String),
+    /// The rectangle of the frame overflows the coordinates of the sprite sheet.
+    #[error("Frame {0:?} has a rectangle that overflows")]
+    InvalidFrameRect(String),
+    /// The tag doesn't cover a valid range of frames.
+    #[error("Tag {name:?} covers frames {from} to {to}, but there are {frame_count} frames")]
+    InvalidTag {
+        name: String,
+        from: usize,
+        to: usize,
+        frame_count: usize,
+    },
+    /// The direction of the tag is unknown.
+    #[error("Tag {name:?} has an unknown direction {direction:?}")]
+    UnknownDirection { name: String, direction: String },
+}
+
+impl AssetLoader for SpriteAnimationLoader {
+    type Asset = SpriteAnimation;
+    type Settings = SpriteAnimationLoaderSettings;
+    type Error = SpriteAnimationLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<SpriteAnimation, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let sheet: SpriteSheetJson = serde_json::from_slice(&bytes)?;
+
+        let mut layout = TextureAtlasLayout::new_empty(sheet.meta.size.into());
+        let mut frames = Vec::with_capacity(sheet.frames.0.len());
+        for (name, frame) in sheet.frames.0 {
+            if frame.rotated {
+                return Err(SpriteAnimationLoaderError::RotatedFrame(name));
+            }
+            let Ok(rect) = URect::try_from(frame.frame) else {
+                return Err(SpriteAnimationLoaderError::InvalidFrameRect(name));
+            };
+            let index = layout.add_texture(rect);
+            frames.push(SpriteAnimationFrame {
+                index,
+                duration: frame
+                    .duration
+                    .map(Duration::from_millis)
+                    .unwrap_or(settings.default_frame_duration),
+            });
+        }
+
+        let mut ranges = HashMap::default();
+        for tag in sheet.meta.frame_tags {
+            if tag.from > tag.to || tag.to >= frames.len() {
+                return Err(SpriteAnimationLoaderError::InvalidTag {
+                    name: tag.name,
+                    from: tag.from,
+                    to: tag.to,
+                    frame_count: frames.len(),
+                });
+            }
+            let (reverse, ping_pong) = match tag.direction.as_str() {
+                "forward" => (false, false),
+                "reverse" => (true, false),
+                "pingpong" => (false, true),
+                "pingpong_reverse" => (true, true),
+                _ => {
+                    return Err(SpriteAnimationLoaderError::UnknownDirection {
+                        name: tag.name,
+                        direction: tag.direction,
+                    })
+                }
+            };
+            let loop_mode = tag_loop_mode(tag.repeat.as_deref(), ping_pong);
+            ranges.insert(
+                tag.name,
+                SpriteAnimationRange {
+                    frames: tag.from..tag.to + 1,
+                    reverse,
+                    loop_mode,
+                },
+            );
+        }
+
+        let image_path = load_context.asset_path().resolve_embed(&sheet.meta.image)?;
+        let image = load_context.load(image_path);
+        let layout = load_context.add_labeled_asset("Layout".into(), layout);
+
+        Ok(SpriteAnimation {
+            frames,
+            ranges,
+            loop_mode: settings.loop_mode,
+            image: Some(image),
+            layout: Some(layout),
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["aseprite.json", "atlas.json"]
+    }
+}
+
+#[derive(Deserialize)]
+struct SpriteSheetJson {
+    frames: FramesJson,
+    meta: MetaJson,
+}
+
+/// The frames of a sprite sheet, in the order of the file, from either an
+/// object of frames by name or an array of frames with a `filename`.
+struct FramesJson(Vec<(String, FrameJson)>);
+
+impl<'de> Deserialize<'de> for FramesJson {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(FramesVisitor)
+    }
+}
+
+struct FramesVisitor;
+
+impl<'de> Visitor<'de> for FramesVisitor {
+    type Value = FramesJson;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an object or an array of frames")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<FramesJson, A::Error> {
+        let mut frames = Vec::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some(entry) = map.next_entry()? {
+            frames.push(entry);
+        }
+        Ok(FramesJson(frames))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<FramesJson, A::Error> {
+        let mut frames = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(NamedFrameJson { filename, frame }) = seq.next_element()? {
+            frames.push((filename, frame));
+        }
+        Ok(FramesJson(frames))
+    }
+}
+
+#[derive(Deserialize)]
+struct NamedFrameJson {
+    #[serde(default)]
+    filename: String,
+    #[serde(flatten)]
+    frame: FrameJson,
+}
+
+#[derive(Deserialize)]
+struct FrameJson {
+    frame: RectJson,
+    #[serde(default)]
+    rotated: bool,
+    /// In milliseconds.
+    duration: Option<u64>,
+}
+
+#[derive(Deserialize, Clone)]
+struct RectJson {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+}
+
+/// The rectangle of a frame ends past `u32::MAX`.
+struct RectOverflowError;
+
+impl TryFrom<RectJson> for URect {
+    type Error = RectOverflowError;
+
+    fn try_from(rect: RectJson) -> Result<Self, Self::Error> {
+        let (Some(max_x), Some(max_y)) = (rect.x.checked_add(rect.w), rect.y.checked_add(rect.h))
+        else {
+            return Err(RectOverflowError);
+        };
+        Ok(URect::new(rect.x, rect.y, max_x, max_y))
+    }
+}
+
+#[derive(Deserialize)]
+struct SizeJson {
+    w: u32,
+    h: u32,
+}
+
+impl From<SizeJson> for UVec2 {
+    fn from(size: SizeJson) -> Self {
+        UVec2::new(size.w, size.h)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MetaJson {
+    image: String,
+    size: SizeJson,
+    #[serde(default)]
+    frame_tags: Vec<FrameTagJson>,
+}
+
+#[derive(Deserialize)]
+struct FrameTagJson {
+    name: String,
+    from: usize,
+    to: usize,
+    #[serde(default = "default_direction")]
+    direction: String,
+    repeat: Option<String>,
+}
+
+fn default_direction() -> String {
+    "forward".into()
+}
+
+/// The loop mode of a tag with the given repeat count.
+///
+/// Aseprite only exports a repeat count for tags that don't repeat forever.
+/// Tags that repeat a few times play once per loop here. A ping-pong tag that
+/// plays once goes to its last frame and back.
+fn tag_loop_mode(repeat: Option<&str>, ping_pong: bool) -> SpriteAnimationLoopMode {
+    match (repeat, ping_pong) {
+        (Some("1"), true) => SpriteAnimationLoopMode::PingPongOnce,
+        (Some("1"), false) => SpriteAnimationLoopMode::Once,
+        (_, true) => SpriteAnimationLoopMode::PingPong,
+        (_, false) => SpriteAnimationLoopMode::Loop,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_keep_the_order_of_the_file() {
+        let hash: SpriteSheetJson = serde_json::from_str(
+            r#"{
+                "frames": {
+                    "walk 10": { "frame": { "x": 32, "y": 0, "w": 16, "h": 16 }, "duration": 50 },
+                    "walk 2": { "frame": { "x": 0, "y": 0, "w": 16, "h": 16 } }
+                },
+                "meta": { "image": "walk.png", "size": { "w": 64, "h": 16 } }
+            }"#,
+        )
+        .unwrap();
+        let names: Vec<_> = hash
+            .frames
+            .0
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(names, ["walk 10", "walk 2"]);
+        assert_eq!(hash.frames.0[0].1.duration, Some(50));
+
+        let array: SpriteSheetJson = serde_json::from_str(
+            r#"{
+                "frames": [
+                    { "filename": "b", "frame": { "x": 16, "y": 0, "w": 16, "h": 16 }, "rotated": false },
+                    { "filename": "a", "frame": { "x": 0, "y": 0, "w": 16, "h": 16 } }
+                ],
+                "meta": {
+                    "image": "walk.png",
+                    "size": { "w": 32, "h": 16 },
+                    "frameTags": [{ "name": "idle", "from": 0, "to": 1, "direction": "pingpong" }]
+                }
+            }"#,
+        )
+        .unwrap();
+        let names: Vec<_> = array
+            .frames
+            .0
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(names, ["b", "a"]);
+        assert_eq!(
+            URect::try_from(array.frames.0[0].1.frame.clone()).ok(),
+            Some(URect::new(16, 0, 32, 16))
+        );
+        assert_eq!(array.meta.frame_tags[0].direction, "pingpong");
+    }
+
+    #[test]
+    fn overflowing_rects_are_rejected() {
+        let rect = RectJson {
+            x: u32::MAX - 8,
+            y: 0,
+            w: 16,
+            h: 16,
+        };
+        assert!(URect::try_from(rect).is_err());
+    }
+
+    #[test]
+    fn tag_loop_modes() {
+        assert_eq!(tag_loop_mode(None, false), SpriteAnimationLoopMode::Loop);
+        assert_eq!(tag_loop_mode(None, true), SpriteAnimationLoopMode::PingPong);
+        assert_eq!(
+            tag_loop_mode(Some("1"), false),
+            SpriteAnimationLoopMode::Once
+        );
+        assert_eq!(
+            tag_loop_mode(Some("1"), true),
+            SpriteAnimationLoopMode::PingPongOnce
+        );
+        assert_eq!(
+            tag_loop_mode(Some("3"), true),
+            SpriteAnimationLoopMode::PingPong
+        );
+    }
+}
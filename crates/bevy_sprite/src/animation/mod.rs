@@ -0,0 +1,488 @@
+//! Flipbook animation of sprites through the frames of a texture atlas.
+
+#[cfg(feature = "sprite_animation_json")]
+mod loader;
+
+#[cfg(feature = "sprite_animation_json")]
+pub use loader::*;
+
+use core::{ops::Range, time::Duration};
+
+use bevy_app::{App, Plugin, PostUpdate};
+use bevy_asset::{Asset, AssetApp, Assets, Handle};
+use bevy_ecs::prelude::*;
+use bevy_image::{Image, TextureAtlas, TextureAtlasLayout};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::view::VisibilitySystems;
+use bevy_time::Time;
+use bevy_utils::HashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::{Sprite, SpriteSystem};
+
+/// A flipbook animation, as a sequence of frames of a texture atlas.
+///
+/// The animation can be loaded from the JSON that Aseprite or TexturePacker
+/// export with a sprite sheet, with the `SpriteAnimationLoader` of the
+/// `sprite_animation_json` feature, and played by a [`SpriteAnimationPlayer`].
+#[derive(Asset, Reflect, Clone, Debug, Default)]
+#[reflect(Default, Debug)]
+pub struct SpriteAnimation {
+    /// The frames of the animation, in the order they play when no range is
+    /// selected.
+    pub frames: Vec<SpriteAnimationFrame>,
+    /// Named ranges of [`SpriteAnimation::frames`], each of which plays as an
+    /// animation of its own, such as the tags of an Aseprite file.
+    pub ranges: HashMap<String, SpriteAnimationRange>,
+    /// How the whole animation plays when no range is selected.
+    pub loop_mode: SpriteAnimationLoopMode,
+    /// The image the frames are in, which players set on their [`Sprite`].
+    ///
+    /// When this or [`SpriteAnimation::layout`] is `None`, players only change
+    /// the index of the texture atlas of their sprite.
+    #[dependency]
+    pub image: Option<Handle<Image>>,
+    /// The layout of the frames in [`SpriteAnimation::image`].
+    #[dependency]
+    pub layout: Option<Handle<TextureAtlasLayout>>,
+}
+
+impl SpriteAnimation {
+    /// An animation through the given atlas indices, each shown for the same
+    /// duration.
+    pub fn from_indices(
+        indices: impl IntoIterator<Item = usize>,
+        frame_duration: Duration,
+    ) -> Self {
+        Self {
+            frames: indices
+                .into_iter()
+                .map(|index| SpriteAnimationFrame {
+                    index,
+                    duration: frame_duration,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Adds a named range of frames.
+    pub fn with_range(mut self, name: impl Into<String>, range: SpriteAnimationRange) -> Self {
+        self.ranges.insert(name.into(), range);
+        self
+    }
+
+    /// Returns the range that plays for `range_name`, or all the frames for
+    /// `None`.
+    ///
+    /// Returns `None` if there's no range with that name.
+    pub fn range(&self, range_name: Option<&str>) -> Option<SpriteAnimationRange> {
+        match range_name {
+            Some(range_name) => self.ranges.get(range_name).cloned(),
+            None => Some(SpriteAnimationRange {
+                frames: 0..self.frames.len(),
+                reverse: false,
+                loop_mode: self.loop_mode,
+            }),
+        }
+    }
+}
+
+/// A frame of a [`SpriteAnimation`].
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[reflect(Default, Debug, PartialEq)]
+pub struct SpriteAnimationFrame {
+    /// The index of the frame in the texture atlas.
+    pub index: usize,
+    /// How long the frame shows.
+    pub duration: Duration,
+}
+
+/// A range of the frames of a [`SpriteAnimation`] that plays as an animation
+/// of its own.
+#[derive(Reflect, Clone, Debug, Default, PartialEq, Eq)]
+#[reflect(Default, Debug, PartialEq)]
+pub struct SpriteAnimationRange {
+    /// The indices of the frames in [`SpriteAnimation::frames`].
+    pub frames: Range<usize>,
+    /// Whether the range plays from its last frame to its first.
+    pub reverse: bool,
+    /// What happens when the range reaches its end.
+    pub loop_mode: SpriteAnimationLoopMode,
+}
+
+/// What happens when a sprite animation reaches its end.
+#[derive(Reflect, Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[reflect(Default, Debug, PartialEq, Hash)]
+pub enum SpriteAnimationLoopMode {
+    /// The animation stops on its last frame.
+    Once,
+    /// The animation starts over from its first frame.
+    #[default]
+    Loop,
+    /// The animation plays backwards to its first frame, then forwards again.
+    PingPong,
+    /// The animation plays backwards to its first frame once, and stops there.
+    PingPongOnce,
+}
+
+/// Plays a [`SpriteAnimation`] on the [`Sprite`] of the entity.
+///
+/// The player sets the index of the texture atlas of the sprite to that of the
+/// current frame, as well as its image and layout if the animation has them.
+/// It sends [`SpriteAnimationFrameChanged`], [`SpriteAnimationLoopCompleted`]
+/// and [`SpriteAnimationFinished`] events as it plays.
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component, Default, Debug)]
+#[require(Sprite)]
+pub struct SpriteAnimationPlayer {
+    /// The animation to play.
+    pub animation: Handle<SpriteAnimation>,
+    /// How fast the animation plays, as a multiple of the durations of its
+    /// frames.
+    pub speed: f32,
+    /// Whether the animation is paused on its current frame.
+    pub paused: bool,
+    range: Option<String>,
+    /// The index of the current frame among those of the range, in the order
+    /// they play.
+    position: usize,
+    /// How long the current frame has shown for.
+    elapsed: Duration,
+    /// Whether a ping-pong animation is on its way back.
+    backwards: bool,
+    finished: bool,
+}
+
+impl Default for SpriteAnimationPlayer {
+    fn default() -> Self {
+        Self {
+            animation: Handle::default(),
+            speed: 1.0,
+            paused: false,
+            range: None,
+            position: 0,
+            elapsed: Duration::ZERO,
+            backwards: false,
+            finished: false,
+        }
+    }
+}
+
+impl SpriteAnimationPlayer {
+    /// Plays all the frames of an animation.
+    pub fn new(animation: Handle<SpriteAnimation>) -> Self {
+        Self {
+            animation,
+            ..Default::default()
+        }
+    }
+
+    /// Plays the named range of the animation instead of all its frames.
+    pub fn with_range(mut self, range: impl Into<String>) -> Self {
+        self.play(range);
+        self
+    }
+
+    /// Starts playing the named range of the animation from its first frame.
+    ///
+    /// Does nothing if the range is already playing, so this can be called
+    /// every frame with the range that matches the state of a character.
+    pub fn play(&mut self, range: impl Into<String>) -> &mut Self {
+        let range = range.into();
+        if self.range.as_ref() != Some(&range) {
+            self.range = Some(range);
+            self.restart();
+        }
+        self
+    }
+
+    /// Starts playing all the frames of the animation from the first one.
+    pub fn play_all(&mut self) -> &mut Self {
+        if self.range.is_some() {
+            self.range = None;
+            self.restart();
+        }
+        self
+    }
+
+    /// Goes back to the first frame of the current range.
+    pub fn restart(&mut self) -> &mut Self {
+        self.position = 0;
+        self.elapsed = Duration::ZERO;
+        self.backwards = false;
+        self.finished = false;
+        self
+    }
+
+    /// The name of the range that plays, or `None` if all the frames play.
+    pub fn range(&self) -> Option<&str> {
+        self.range.as_deref()
+    }
+
+    /// Returns the index of the current frame in [`SpriteAnimation::frames`].
+    pub fn frame(&self, animation: &SpriteAnimation) -> Option<usize> {
+        let range = animation.range(self.range())?;
+        let len = range.frames.len();
+        if len == 0 {
+            return None;
+        }
+        let position = self.position.min(len - 1);
+        Some(if range.reverse {
+            range.frames.end - 1 - position
+        } else {
+            range.frames.start + position
+        })
+    }
+
+    /// Whether a [`SpriteAnimationLoopMode::Once`] animation reached its last
+    /// frame, or a [`SpriteAnimationLoopMode::PingPongOnce`] animation came
+    /// back to its first frame.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Moves to the next frame of a range of `len` frames, and returns whether
+    /// the range completed a loop.
+    fn step(&mut self, len: usize, loop_mode: SpriteAnimationLoopMode) -> bool {
+        match loop_mode {
+            SpriteAnimationLoopMode::Once => {
+                if self.position + 1 >= len {
+                    self.finished = true;
+                    true
+                } else {
+                    self.position += 1;
+                    false
+                }
+            }
+            SpriteAnimationLoopMode::Loop => {
+                self.position = (self.position + 1) % len;
+                self.position == 0
+            }
+            SpriteAnimationLoopMode::PingPong | SpriteAnimationLoopMode::PingPongOnce => {
+                let completed = if len == 1 {
+                    true
+                } else if self.backwards {
+                    self.position -= 1;
+                    self.backwards = self.position != 0;
+                    !self.backwards
+                } else {
+                    self.position += 1;
+                    self.backwards = self.position == len - 1;
+                    false
+                };
+                if completed && loop_mode == SpriteAnimationLoopMode::PingPongOnce {
+                    self.finished = true;
+                }
+                completed
+            }
+        }
+    }
+}
+
+/// Sent when a [`SpriteAnimationPlayer`] moves to another frame.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpriteAnimationFrameChanged {
+    /// The entity of the player.
+    pub entity: Entity,
+    /// The index of the new frame in [`SpriteAnimation::frames`].
+    pub frame: usize,
+}
+
+/// Sent when a [`SpriteAnimationPlayer`] reaches the end of its animation,
+/// before it loops, or back to its first frame in ping-pong mode.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpriteAnimationLoopCompleted {
+    /// The entity of the player.
+    pub entity: Entity,
+}
+
+/// Sent when a [`SpriteAnimationPlayer`] in [`SpriteAnimationLoopMode::Once`]
+/// or [`SpriteAnimationLoopMode::PingPongOnce`] reaches the end of its
+/// animation.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpriteAnimationFinished {
+    /// The entity of the player.
+    pub entity: Entity,
+}
+
+/// Adds support for [`SpriteAnimation`]s.
+pub struct SpriteAnimationPlugin;
+
+impl Plugin for SpriteAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<SpriteAnimation>()
+            .register_asset_reflect::<SpriteAnimation>()
+            .register_type::<SpriteAnimationPlayer>()
+            .register_type::<SpriteAnimationLoopMode>()
+            .add_event::<SpriteAnimationFrameChanged>()
+            .add_event::<SpriteAnimationLoopCompleted>()
+            .add_event::<SpriteAnimationFinished>()
+            .add_systems(
+                PostUpdate,
+                animate_sprites
+                    .in_set(SpriteSystem::Animate)
+                    .before(SpriteSystem::ComputeSlices)
+                    .before(VisibilitySystems::CalculateBounds),
+            );
+
+        #[cfg(feature = "sprite_animation_json")]
+        app.init_asset_loader::<SpriteAnimationLoader>();
+    }
+}
+
+/// Advances the [`SpriteAnimationPlayer`]s and updates their sprites.
+pub fn animate_sprites(
+    time: Res<Time>,
+    animations: Res<Assets<SpriteAnimation>>,
+    mut players: Query<(Entity, &mut SpriteAnimationPlayer, &mut Sprite)>,
+    mut frame_changed_events: EventWriter<SpriteAnimationFrameChanged>,
+    mut loop_completed_events: EventWriter<SpriteAnimationLoopCompleted>,
+    mut finished_events: EventWriter<SpriteAnimationFinished>,
+) {
+    for (entity, mut player, mut sprite) in &mut players {
+        let Some(animation) = animations.get(&player.animation) else {
+            continue;
+        };
+        let Some(range) = animation.range(player.range()) else {
+            continue;
+        };
+        let len = range.frames.len();
+        if len == 0 || range.frames.end > animation.frames.len() {
+            continue;
+        }
+
+        let previous_frame = player.frame(animation);
+        if !player.paused && !player.finished {
+            let delta = time.delta().mul_f32(player.speed.max(0.0));
+            player.elapsed += delta;
+            // Frames without a duration still show for a whole update, and an
+            // update never goes through the range more than once.
+            for _ in 0..len {
+                let Some(frame) = player.frame(animation) else {
+                    break;
+                };
+                let duration = animation.frames[frame].duration;
+                if player.elapsed < duration || (duration.is_zero() && delta.is_zero()) {
+                    break;
+                }
+                player.elapsed -= duration;
+                if player.step(len, range.loop_mode) {
+                    if player.finished {
+                        player.elapsed = Duration::ZERO;
+                        finished_events.send(SpriteAnimationFinished { entity });
+                        break;
+                    }
+                    loop_completed_events.send(SpriteAnimationLoopCompleted { entity });
+                }
+            }
+        }
+
+        let Some(frame) = player.frame(animation) else {
+            continue;
+        };
+        if previous_frame != Some(frame) {
+            frame_changed_events.send(SpriteAnimationFrameChanged { entity, frame });
+        }
+
+        let index = animation.frames[frame].index;
+        if let (Some(image), Some(layout)) = (&animation.image, &animation.layout) {
+            if sprite.image != *image {
+                sprite.image = image.clone();
+            }
+            match &mut sprite.texture_atlas {
+                Some(atlas) if atlas.layout == *layout => {
+                    if atlas.index != index {
+                        atlas.index = index;
+                    }
+                }
+                texture_atlas => {
+                    *texture_atlas = Some(TextureAtlas {
+                        layout: layout.clone(),
+                        index,
+                    });
+                }
+            }
+        } else if let Some(atlas) = sprite
+            .texture_atlas
+            .as_mut()
+            .filter(|atlas| atlas.index != index)
+        {
+            atlas.index = index;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player_positions(
+        loop_mode: SpriteAnimationLoopMode,
+        len: usize,
+        steps: usize,
+    ) -> Vec<(usize, bool)> {
+        let mut player = SpriteAnimationPlayer::default();
+        (0..steps)
+            .map(|_| {
+                let completed = player.step(len, loop_mode);
+                (player.position, completed)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn once_stops_on_last_frame() {
+        assert_eq!(
+            player_positions(SpriteAnimationLoopMode::Once, 3, 4),
+            [(1, false), (2, false), (2, true), (2, true)]
+        );
+    }
+
+    #[test]
+    fn loop_wraps_around() {
+        assert_eq!(
+            player_positions(SpriteAnimationLoopMode::Loop, 3, 4),
+            [(1, false), (2, false), (0, true), (1, false)]
+        );
+    }
+
+    #[test]
+    fn ping_pong_goes_back_and_forth() {
+        assert_eq!(
+            player_positions(SpriteAnimationLoopMode::PingPong, 3, 5),
+            [(1, false), (2, false), (1, false), (0, true), (1, false)]
+        );
+    }
+
+    #[test]
+    fn ping_pong_once_stops_on_first_frame() {
+        assert_eq!(
+            player_positions(SpriteAnimationLoopMode::PingPongOnce, 3, 4),
+            [(1, false), (2, false), (1, false), (0, true)]
+        );
+        let mut player = SpriteAnimationPlayer::default();
+        for _ in 0..4 {
+            player.step(3, SpriteAnimationLoopMode::PingPongOnce);
+        }
+        assert!(player.is_finished());
+    }
+
+    #[test]
+    fn reversed_range_frames() {
+        let animation = SpriteAnimation::from_indices(0..6, Duration::from_millis(100)).with_range(
+            "back",
+            SpriteAnimationRange {
+                frames: 2..5,
+                reverse: true,
+                loop_mode: SpriteAnimationLoopMode::Loop,
+            },
+        );
+        let mut player = SpriteAnimationPlayer::default().with_range("back");
+        assert_eq!(player.frame(&animation), Some(4));
+        player.step(3, SpriteAnimationLoopMode::Loop);
+        assert_eq!(player.frame(&animation), Some(3));
+        player.play_all();
+        assert_eq!(player.frame(&animation), Some(0));
+    }
+}
@@ -10,6 +10,8 @@
 
 extern crate alloc;
 
+mod animation;
+mod light2d;
 mod mesh2d;
 #[cfg(feature = "bevy_sprite_picking_backend")]
 mod picking_backend;
@@ -25,10 +27,13 @@ pub mod prelude {
     pub use crate::{
         sprite::{Sprite, SpriteImageMode},
         texture_slice::{BorderRect, SliceScaleMode, TextureSlice, TextureSlicer},
-        ColorMaterial, MeshMaterial2d,
+        ColorMaterial, DirectionalLight2d, LightOccluder2d, Lighting2d, MeshMaterial2d,
+        PointLight2d, SpriteAnimation, SpriteAnimationPlayer, SpriteNormalMap,
     };
 }
 
+pub use animation::*;
+pub use light2d::*;
 pub use mesh2d::*;
 #[cfg(feature = "bevy_sprite_picking_backend")]
 pub use picking_backend::*;
@@ -83,6 +88,7 @@ pub const SPRITE_VIEW_BINDINGS_SHADER_HANDLE: Handle<Shader> =
 pub enum SpriteSystem {
     ExtractSprites,
     ComputeSlices,
+    Animate,
 }
 
 impl Plugin for SpritePlugin {
@@ -109,7 +115,12 @@ impl Plugin for SpritePlugin {
             .register_type::<TextureSlicer>()
             .register_type::<Anchor>()
             .register_type::<Mesh2d>()
-            .add_plugins((Mesh2dRenderPlugin, ColorMaterialPlugin))
+            .add_plugins((
+                Mesh2dRenderPlugin,
+                ColorMaterialPlugin,
+                Light2dPlugin,
+                SpriteAnimationPlugin,
+            ))
             .add_systems(
                 PostUpdate,
                 (
@@ -0,0 +1,91 @@
+#define_import_path bevy_sprite::light2d
+
+#import bevy_sprite::{
+    light2d_types::POINT_LIGHT_2D_FLAGS_SHADOWS_ENABLED_BIT,
+    sprite_view_bindings::{lights, occluders},
+}
+
+// A smooth window that goes from 1 at the light to 0 at its range.
+fn point_light_2d_attenuation(distance: f32, range: f32) -> f32 {
+    let factor = saturate(1.0 - (distance * distance) / (range * range));
+    return factor * factor;
+}
+
+// Returns true if the segments from `a` to `b` and from `c` to `d` cross, not counting the
+// end of the first one.
+fn segments_intersect(a: vec2<f32>, b: vec2<f32>, c: vec2<f32>, d: vec2<f32>) -> bool {
+    let r = b - a;
+    let s = d - c;
+    let denominator = r.x * s.y - r.y * s.x;
+    if abs(denominator) < 1e-6 {
+        // Parallel segments don't block each other.
+        return false;
+    }
+    let ac = c - a;
+    let t = (ac.x * s.y - ac.y * s.x) / denominator;
+    let u = (ac.x * r.y - ac.y * r.x) / denominator;
+    return t >= 0.0 && t < 1.0 && u >= 0.0 && u <= 1.0;
+}
+
+// Returns true if an occluder edge is between `position` and `light_position`, skipping the
+// `own_occluder.y` edges from `own_occluder.x`.
+fn is_shadowed_2d(
+    position: vec2<f32>,
+    light_position: vec2<f32>,
+    own_occluder: vec2<u32>,
+) -> bool {
+    for (var i = 0u; i < lights.n_occluder_segments; i += 1u) {
+        if i >= own_occluder.x && i < own_occluder.x + own_occluder.y {
+            continue;
+        }
+        let segment = occluders.segments[i];
+        if segments_intersect(position, light_position, segment.xy, segment.zw) {
+            return true;
+        }
+    }
+    return false;
+}
+
+// Returns the light that reaches a sprite fragment at `position`, with the given world space
+// `normal`. The normal is only used with the `NORMAL_MAP_2D` shader def.
+fn light_sprite(position: vec2<f32>, normal: vec3<f32>, own_occluder: vec2<u32>) -> vec3<f32> {
+    var light = lights.ambient_color;
+
+    for (var i = 0u; i < lights.n_point_lights; i += 1u) {
+        let point_light = lights.point_lights[i];
+        let to_light = point_light.position.xy - position;
+        let distance = length(to_light);
+        if distance >= point_light.range {
+            continue;
+        }
+
+        var attenuation = point_light_2d_attenuation(distance, point_light.range);
+#ifdef NORMAL_MAP_2D
+        // The small offset keeps the direction defined right under the light.
+        let direction_to_light = normalize(vec3(to_light, point_light.position.z + 1e-4));
+        attenuation *= saturate(dot(normal, direction_to_light));
+#endif
+        if attenuation <= 0.0 {
+            continue;
+        }
+
+        if (point_light.flags & POINT_LIGHT_2D_FLAGS_SHADOWS_ENABLED_BIT) != 0u &&
+                is_shadowed_2d(position, point_light.position.xy, own_occluder) {
+            continue;
+        }
+
+        light += point_light.color * attenuation;
+    }
+
+    for (var i = 0u; i < lights.n_directional_lights; i += 1u) {
+        let directional_light = lights.directional_lights[i];
+#ifdef NORMAL_MAP_2D
+        light += directional_light.color *
+            saturate(dot(normal, directional_light.direction_to_light));
+#else
+        light += directional_light.color;
+#endif
+    }
+
+    return light;
+}
@@ -0,0 +1,31 @@
+#define_import_path bevy_sprite::light2d_types
+
+struct PointLight2d {
+    // The position of the light, with its height in `z`.
+    position: vec3<f32>,
+    range: f32,
+    color: vec3<f32>,
+    // 'flags' is a bit field indicating various options. u32 is 32 bits so we have up to 32 options.
+    flags: u32,
+};
+
+const POINT_LIGHT_2D_FLAGS_SHADOWS_ENABLED_BIT: u32 = 1u;
+
+struct DirectionalLight2d {
+    direction_to_light: vec3<f32>,
+    color: vec3<f32>,
+};
+
+struct Lights2d {
+    point_lights: array<PointLight2d, #{MAX_POINT_LIGHTS_2D}u>,
+    directional_lights: array<DirectionalLight2d, #{MAX_DIRECTIONAL_LIGHTS_2D}u>,
+    ambient_color: vec3<f32>,
+    n_point_lights: u32,
+    n_directional_lights: u32,
+    n_occluder_segments: u32,
+};
+
+// The edges of all the light occluders, each with its start in `xy` and its end in `zw`.
+struct Occluders2d {
+    segments: array<vec4<f32>, #{MAX_OCCLUDER_SEGMENTS_2D}u>,
+};
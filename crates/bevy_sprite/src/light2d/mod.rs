@@ -0,0 +1,449 @@
+//! Lighting and shadows for sprites.
+//!
+//! Sprites are lit in the views of cameras with a [`Lighting2d`] component, by
+//! every [`PointLight2d`] and [`DirectionalLight2d`] of the world. A
+//! [`SpriteNormalMap`] gives a sprite bumps that react to the direction of the
+//! lights, and point lights with shadows enabled are blocked by the edges of
+//! [`LightOccluder2d`]s.
+//!
+//! Cameras without [`Lighting2d`] render sprites unlit, as if this module
+//! didn't exist.
+
+use bevy_app::{App, Plugin};
+use bevy_asset::{load_internal_asset, Handle};
+use bevy_color::{Color, ColorToComponents, LinearRgba};
+use bevy_ecs::prelude::*;
+use bevy_image::Image;
+use bevy_math::{UVec2, Vec2, Vec3, Vec3Swizzles, Vec4};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::{
+    extract_component::{ExtractComponent, ExtractComponentPlugin},
+    render_resource::{DynamicUniformBuffer, Shader, ShaderType, UniformBuffer},
+    renderer::{RenderDevice, RenderQueue},
+    sync_world::MainEntity,
+    view::{ExtractedView, InheritedVisibility, Visibility},
+    Extract, ExtractSchedule, Render, RenderApp, RenderSet,
+};
+use bevy_transform::components::{GlobalTransform, Transform};
+use bevy_utils::{once, HashMap};
+use tracing::warn;
+
+pub const LIGHT_2D_TYPES_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(10761455504405878639);
+pub const LIGHT_2D_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(3517621104972165583);
+
+/// The most point lights that can light sprites at once.
+pub const MAX_POINT_LIGHTS_2D: usize = 64;
+/// The most directional lights that can light sprites at once.
+pub const MAX_DIRECTIONAL_LIGHTS_2D: usize = 8;
+/// The most edges of [`LightOccluder2d`]s that can cast shadows at once.
+pub const MAX_OCCLUDER_SEGMENTS_2D: usize = 512;
+
+/// Lights the sprites in the view of a 2D camera.
+///
+/// Without this component, a camera renders sprites unlit.
+#[derive(Component, Clone, Debug, Reflect, ExtractComponent)]
+#[reflect(Component, Default, Debug)]
+pub struct Lighting2d {
+    /// The color of the light that reaches every sprite, even outside of the
+    /// range of every light.
+    pub ambient_color: Color,
+    /// A multiplier of [`Lighting2d::ambient_color`].
+    pub ambient_brightness: f32,
+}
+
+impl Default for Lighting2d {
+    fn default() -> Self {
+        Self {
+            ambient_color: Color::WHITE,
+            ambient_brightness: 0.1,
+        }
+    }
+}
+
+/// A light that shines in every direction from a point, up to a range.
+///
+/// The light is at the translation of the entity, [`PointLight2d::height`]
+/// above the sprites, which only matters to sprites with a
+/// [`SpriteNormalMap`].
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component, Default, Debug)]
+#[require(Transform, Visibility)]
+pub struct PointLight2d {
+    pub color: Color,
+    /// A multiplier of [`PointLight2d::color`].
+    pub intensity: f32,
+    /// How far the light reaches, in world units. It fades out smoothly
+    /// towards this distance.
+    pub range: f32,
+    /// How far above the sprites the light is, in world units.
+    ///
+    /// A light at height `0.0` only lights the sides of the bumps of normal
+    /// maps that face it, while a high light lights them from above.
+    pub height: f32,
+    /// Whether the edges of [`LightOccluder2d`]s block this light.
+    pub shadows_enabled: bool,
+}
+
+impl Default for PointLight2d {
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            intensity: 1.0,
+            range: 200.0,
+            height: 0.0,
+            shadows_enabled: false,
+        }
+    }
+}
+
+/// A light that shines evenly on every sprite from a direction, like the sun.
+///
+/// The light shines along the forward direction of the entity, straight down
+/// onto the sprites with the default rotation. The direction only matters to
+/// sprites with a [`SpriteNormalMap`].
+///
+/// Directional lights don't cast shadows.
+#[derive(Component, Clone, Copy, Debug, Reflect)]
+#[reflect(Component, Default, Debug)]
+#[require(Transform, Visibility)]
+pub struct DirectionalLight2d {
+    pub color: Color,
+    /// A multiplier of [`DirectionalLight2d::color`].
+    pub intensity: f32,
+}
+
+impl Default for DirectionalLight2d {
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            intensity: 1.0,
+        }
+    }
+}
+
+/// A normal map for the image of the sprite on the same entity.
+///
+/// The normal map must line up with the image of the sprite, including its
+/// texture atlas or rect, and be loaded as linear data rather than sRGB. Its
+/// red and green channels point towards the right and the top of the image.
+#[derive(Component, Clone, Debug, Default, Reflect)]
+#[reflect(Component, Default, Debug)]
+pub struct SpriteNormalMap(pub Handle<Image>);
+
+/// The outline of an object that blocks [`PointLight2d`]s with shadows
+/// enabled.
+///
+/// The outline is a closed polygon in the local space of the entity. A sprite
+/// on the same entity isn't shadowed by its own occluder.
+#[derive(Component, Clone, Debug, Default, Reflect)]
+#[reflect(Component, Default, Debug)]
+#[require(Transform, Visibility)]
+pub struct LightOccluder2d {
+    /// The corners of the polygon, in order.
+    pub vertices: Vec<Vec2>,
+}
+
+impl LightOccluder2d {
+    /// An occluder in the shape of a polygon.
+    pub fn polygon(vertices: impl IntoIterator<Item = Vec2>) -> Self {
+        Self {
+            vertices: vertices.into_iter().collect(),
+        }
+    }
+
+    /// An occluder in the shape of a rectangle centered on the entity.
+    pub fn rectangle(size: Vec2) -> Self {
+        let half_size = size / 2.0;
+        Self::polygon([
+            Vec2::new(-half_size.x, -half_size.y),
+            Vec2::new(half_size.x, -half_size.y),
+            Vec2::new(half_size.x, half_size.y),
+            Vec2::new(-half_size.x, half_size.y),
+        ])
+    }
+
+    /// Iterates over the edges of the polygon.
+    pub fn segments(&self) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+        // With two vertices, the polygon is a single line.
+        let count = match self.vertices.len() {
+            0 | 1 => 0,
+            2 => 1,
+            len => len,
+        };
+        (0..count).map(|index| {
+            (
+                self.vertices[index],
+                self.vertices[(index + 1) % self.vertices.len()],
+            )
+        })
+    }
+}
+
+/// Lights sprites with 2D lights.
+pub struct Light2dPlugin;
+
+impl Plugin for Light2dPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            LIGHT_2D_TYPES_SHADER_HANDLE,
+            "light2d_types.wgsl",
+            Shader::from_wgsl
+        );
+        load_internal_asset!(
+            app,
+            LIGHT_2D_SHADER_HANDLE,
+            "light2d.wgsl",
+            Shader::from_wgsl
+        );
+
+        app.register_type::<Lighting2d>()
+            .register_type::<PointLight2d>()
+            .register_type::<DirectionalLight2d>()
+            .register_type::<SpriteNormalMap>()
+            .register_type::<LightOccluder2d>()
+            .add_plugins(ExtractComponentPlugin::<Lighting2d>::default());
+
+        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            render_app
+                .init_resource::<ExtractedLights2d>()
+                .init_resource::<Light2dMeta>()
+                .add_systems(ExtractSchedule, extract_lights_2d)
+                .add_systems(
+                    Render,
+                    prepare_lights_2d.in_set(RenderSet::PrepareResources),
+                );
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, ShaderType)]
+pub struct GpuPointLight2d {
+    /// The position of the light, with its height in `z`.
+    position: Vec3,
+    range: f32,
+    color: Vec3,
+    flags: u32,
+}
+
+#[derive(Clone, Copy, Default, ShaderType)]
+pub struct GpuDirectionalLight2d {
+    direction_to_light: Vec3,
+    color: Vec3,
+}
+
+pub const POINT_LIGHT_2D_FLAGS_SHADOWS_ENABLED_BIT: u32 = 1 << 0;
+
+#[derive(Clone, ShaderType)]
+pub struct GpuLights2d {
+    point_lights: [GpuPointLight2d; MAX_POINT_LIGHTS_2D],
+    directional_lights: [GpuDirectionalLight2d; MAX_DIRECTIONAL_LIGHTS_2D],
+    ambient_color: Vec3,
+    n_point_lights: u32,
+    n_directional_lights: u32,
+    n_occluder_segments: u32,
+}
+
+impl Default for GpuLights2d {
+    fn default() -> Self {
+        Self {
+            point_lights: [GpuPointLight2d::default(); MAX_POINT_LIGHTS_2D],
+            directional_lights: [GpuDirectionalLight2d::default(); MAX_DIRECTIONAL_LIGHTS_2D],
+            ambient_color: Vec3::ZERO,
+            n_point_lights: 0,
+            n_directional_lights: 0,
+            n_occluder_segments: 0,
+        }
+    }
+}
+
+/// The edges of all the [`LightOccluder2d`]s, each with its start in `xy` and
+/// its end in `zw`.
+#[derive(Clone, ShaderType)]
+pub struct GpuOccluders2d {
+    segments: [Vec4; MAX_OCCLUDER_SEGMENTS_2D],
+}
+
+impl Default for GpuOccluders2d {
+    fn default() -> Self {
+        Self {
+            segments: [Vec4::ZERO; MAX_OCCLUDER_SEGMENTS_2D],
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ExtractedLights2d {
+    pub point_lights: Vec<GpuPointLight2d>,
+    pub directional_lights: Vec<GpuDirectionalLight2d>,
+    pub occluder_segments: Vec<Vec4>,
+    /// The first segment and the segment count of the occluder of each entity,
+    /// so that sprites aren't shadowed by their own occluder.
+    pub occluder_segment_ranges: HashMap<MainEntity, UVec2>,
+}
+
+pub fn extract_lights_2d(
+    mut extracted_lights: ResMut<ExtractedLights2d>,
+    point_lights: Extract<Query<(&PointLight2d, &GlobalTransform, &InheritedVisibility)>>,
+    directional_lights: Extract<
+        Query<(&DirectionalLight2d, &GlobalTransform, &InheritedVisibility)>,
+    >,
+    occluders: Extract<
+        Query<(
+            Entity,
+            &LightOccluder2d,
+            &GlobalTransform,
+            &InheritedVisibility,
+        )>,
+    >,
+) {
+    let ExtractedLights2d {
+        point_lights: extracted_point_lights,
+        directional_lights: extracted_directional_lights,
+        occluder_segments,
+        occluder_segment_ranges,
+    } = &mut *extracted_lights;
+    extracted_point_lights.clear();
+    extracted_directional_lights.clear();
+    occluder_segments.clear();
+    occluder_segment_ranges.clear();
+
+    for (light, transform, visibility) in &point_lights {
+        if !visibility.get() {
+            continue;
+        }
+        if extracted_point_lights.len() == MAX_POINT_LIGHTS_2D {
+            once!(warn!(
+                "More than {MAX_POINT_LIGHTS_2D} point lights 2D are visible, the extra ones are ignored"
+            ));
+            break;
+        }
+        let mut flags = 0;
+        if light.shadows_enabled {
+            flags |= POINT_LIGHT_2D_FLAGS_SHADOWS_ENABLED_BIT;
+        }
+        extracted_point_lights.push(GpuPointLight2d {
+            position: transform.translation().truncate().extend(light.height),
+            range: light.range,
+            color: LinearRgba::from(light.color).to_vec3() * light.intensity,
+            flags,
+        });
+    }
+
+    for (light, transform, visibility) in &directional_lights {
+        if !visibility.get() {
+            continue;
+        }
+        if extracted_directional_lights.len() == MAX_DIRECTIONAL_LIGHTS_2D {
+            once!(warn!(
+                "More than {MAX_DIRECTIONAL_LIGHTS_2D} directional lights 2D are visible, the extra ones are ignored"
+            ));
+            break;
+        }
+        extracted_directional_lights.push(GpuDirectionalLight2d {
+            direction_to_light: transform.back().as_vec3(),
+            color: LinearRgba::from(light.color).to_vec3() * light.intensity,
+        });
+    }
+
+    for (entity, occluder, transform, visibility) in &occluders {
+        if !visibility.get() {
+            continue;
+        }
+        let first = occluder_segments.len();
+        for (start, end) in occluder.segments() {
+            if occluder_segments.len() == MAX_OCCLUDER_SEGMENTS_2D {
+                once!(warn!(
+                    "Light occluders 2D have more than {MAX_OCCLUDER_SEGMENTS_2D} edges, the extra ones don't cast shadows"
+                ));
+                break;
+            }
+            let start = transform.transform_point(start.extend(0.0)).xy();
+            let end = transform.transform_point(end.extend(0.0)).xy();
+            occluder_segments.push(start.extend(end.x).extend(end.y));
+        }
+        occluder_segment_ranges.insert(
+            entity.into(),
+            UVec2::new(first as u32, (occluder_segments.len() - first) as u32),
+        );
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Light2dMeta {
+    pub lights: DynamicUniformBuffer<GpuLights2d>,
+    pub occluders: UniformBuffer<GpuOccluders2d>,
+}
+
+/// The offset of the [`GpuLights2d`] of a view in [`Light2dMeta::lights`].
+#[derive(Component)]
+pub struct ViewLights2dUniformOffset {
+    pub offset: u32,
+}
+
+pub fn prepare_lights_2d(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    extracted_lights: Res<ExtractedLights2d>,
+    mut light_meta: ResMut<Light2dMeta>,
+    views: Query<(Entity, Option<&Lighting2d>), With<ExtractedView>>,
+) {
+    let occluders = light_meta.occluders.get_mut();
+    occluders.segments[..extracted_lights.occluder_segments.len()]
+        .copy_from_slice(&extracted_lights.occluder_segments);
+    light_meta
+        .occluders
+        .write_buffer(&render_device, &render_queue);
+
+    let mut gpu_lights = GpuLights2d {
+        n_point_lights: extracted_lights.point_lights.len() as u32,
+        n_directional_lights: extracted_lights.directional_lights.len() as u32,
+        n_occluder_segments: extracted_lights.occluder_segments.len() as u32,
+        ..Default::default()
+    };
+    gpu_lights.point_lights[..extracted_lights.point_lights.len()]
+        .copy_from_slice(&extracted_lights.point_lights);
+    gpu_lights.directional_lights[..extracted_lights.directional_lights.len()]
+        .copy_from_slice(&extracted_lights.directional_lights);
+
+    let views_iter = views.iter();
+    let view_count = views_iter.len();
+    let Some(mut writer) = light_meta
+        .lights
+        .get_writer(view_count, &render_device, &render_queue)
+    else {
+        return;
+    };
+    for (entity, lighting) in views_iter {
+        gpu_lights.ambient_color = lighting
+            .map(|lighting| {
+                LinearRgba::from(lighting.ambient_color).to_vec3() * lighting.ambient_brightness
+            })
+            .unwrap_or(Vec3::ZERO);
+        commands.entity(entity).insert(ViewLights2dUniformOffset {
+            offset: writer.write(&gpu_lights),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_math::Vec2;
+
+    use super::LightOccluder2d;
+
+    #[test]
+    fn occluder_segments() {
+        let rectangle = LightOccluder2d::rectangle(Vec2::new(2.0, 4.0));
+        let segments: Vec<_> = rectangle.segments().collect();
+        assert_eq!(segments.len(), 4);
+        assert_eq!(segments[3], (Vec2::new(-1.0, 2.0), Vec2::new(-1.0, -2.0)));
+
+        let line = LightOccluder2d::polygon([Vec2::ZERO, Vec2::X]);
+        assert_eq!(line.segments().collect::<Vec<_>>(), [(Vec2::ZERO, Vec2::X)]);
+
+        assert_eq!(LightOccluder2d::polygon([Vec2::ZERO]).segments().count(), 0);
+    }
+}
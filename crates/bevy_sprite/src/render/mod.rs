@@ -1,6 +1,10 @@
 use core::ops::Range;
 
-use crate::{ComputedTextureSlices, Sprite, SPRITE_SHADER_HANDLE};
+use crate::{
+    ComputedTextureSlices, ExtractedLights2d, GpuLights2d, GpuOccluders2d, Light2dMeta, Lighting2d,
+    Sprite, SpriteNormalMap, ViewLights2dUniformOffset, MAX_DIRECTIONAL_LIGHTS_2D,
+    MAX_OCCLUDER_SEGMENTS_2D, MAX_POINT_LIGHTS_2D, SPRITE_SHADER_HANDLE,
+};
 use bevy_asset::{AssetEvent, AssetId, Assets};
 use bevy_color::{ColorToComponents, LinearRgba};
 use bevy_core_pipeline::{
@@ -16,7 +20,7 @@ use bevy_ecs::{
     system::{lifetimeless::*, SystemParamItem, SystemState},
 };
 use bevy_image::{BevyDefault, Image, ImageSampler, TextureAtlasLayout, TextureFormatPixelInfo};
-use bevy_math::{Affine3A, FloatOrd, Quat, Rect, Vec2, Vec4};
+use bevy_math::{Affine3A, FloatOrd, Quat, Rect, UVec2, Vec2, Vec4};
 use bevy_render::sync_world::MainEntity;
 use bevy_render::view::RenderVisibleEntities;
 use bevy_render::{
@@ -74,6 +78,14 @@ impl FromWorld for SpritePipeline {
                         2,
                         tonemapping_lut_entries[1].visibility(ShaderStages::FRAGMENT),
                     ),
+                    (
+                        3,
+                        uniform_buffer::<GpuLights2d>(true).visibility(ShaderStages::FRAGMENT),
+                    ),
+                    (
+                        4,
+                        uniform_buffer::<GpuOccluders2d>(false).visibility(ShaderStages::FRAGMENT),
+                    ),
                 ),
             ),
         );
@@ -85,6 +97,8 @@ impl FromWorld for SpritePipeline {
                 (
                     texture_2d(TextureSampleType::Float { filterable: true }),
                     sampler(SamplerBindingType::Filtering),
+                    // The normal map, for `SpritePipelineKey::NORMAL_MAP`.
+                    texture_2d(TextureSampleType::Float { filterable: true }),
                 ),
             ),
         );
@@ -138,6 +152,8 @@ bitflags::bitflags! {
         const HDR                               = 1 << 0;
         const TONEMAP_IN_SHADER                 = 1 << 1;
         const DEBAND_DITHER                     = 1 << 2;
+        const LIGHTING                          = 1 << 3;
+        const NORMAL_MAP                        = 1 << 4;
         const MSAA_RESERVED_BITS                = Self::MSAA_MASK_BITS << Self::MSAA_SHIFT_BITS;
         const TONEMAP_METHOD_RESERVED_BITS      = Self::TONEMAP_METHOD_MASK_BITS << Self::TONEMAP_METHOD_SHIFT_BITS;
         const TONEMAP_METHOD_NONE               = 0 << Self::TONEMAP_METHOD_SHIFT_BITS;
@@ -184,7 +200,23 @@ impl SpecializedRenderPipeline for SpritePipeline {
     type Key = SpritePipelineKey;
 
     fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
-        let mut shader_defs = Vec::new();
+        let mut shader_defs = vec![
+            ShaderDefVal::UInt("MAX_POINT_LIGHTS_2D".into(), MAX_POINT_LIGHTS_2D as u32),
+            ShaderDefVal::UInt(
+                "MAX_DIRECTIONAL_LIGHTS_2D".into(),
+                MAX_DIRECTIONAL_LIGHTS_2D as u32,
+            ),
+            ShaderDefVal::UInt(
+                "MAX_OCCLUDER_SEGMENTS_2D".into(),
+                MAX_OCCLUDER_SEGMENTS_2D as u32,
+            ),
+        ];
+        if key.contains(SpritePipelineKey::LIGHTING) {
+            shader_defs.push("LIGHTING_2D".into());
+            if key.contains(SpritePipelineKey::NORMAL_MAP) {
+                shader_defs.push("NORMAL_MAP_2D".into());
+            }
+        }
         if key.contains(SpritePipelineKey::TONEMAP_IN_SHADER) {
             shader_defs.push("TONEMAP_IN_SHADER".into());
             shader_defs.push(ShaderDefVal::UInt(
@@ -229,7 +261,7 @@ impl SpecializedRenderPipeline for SpritePipeline {
         };
 
         let instance_rate_vertex_buffer_layout = VertexBufferLayout {
-            array_stride: 80,
+            array_stride: 96,
             step_mode: VertexStepMode::Instance,
             attributes: vec![
                 // @location(0) i_model_transpose_col0: vec4<f32>,
@@ -262,6 +294,12 @@ impl SpecializedRenderPipeline for SpritePipeline {
                     offset: 64,
                     shader_location: 4,
                 },
+                // @location(5) i_occluder: vec2<u32>,
+                VertexAttribute {
+                    format: VertexFormat::Uint32x2,
+                    offset: 80,
+                    shader_location: 5,
+                },
             ],
         };
 
@@ -333,6 +371,8 @@ pub struct ExtractedSprite {
     /// Asset ID of the [`Image`] of this sprite
     /// PERF: storing an `AssetId` instead of `Handle<Image>` enables some optimizations (`ExtractedSprite` becomes `Copy` and doesn't need to be dropped)
     pub image_handle_id: AssetId<Image>,
+    /// Asset ID of the [`SpriteNormalMap`] of this sprite, if any
+    pub normal_map_id: Option<AssetId<Image>>,
     pub flip_x: bool,
     pub flip_y: bool,
     pub anchor: Vec2,
@@ -375,12 +415,16 @@ pub fn extract_sprites(
             &Sprite,
             &GlobalTransform,
             Option<&ComputedTextureSlices>,
+            Option<&SpriteNormalMap>,
         )>,
     >,
 ) {
     extracted_sprites.sprites.clear();
-    for (original_entity, entity, view_visibility, sprite, transform, slices) in sprite_query.iter()
+    for (original_entity, entity, view_visibility, sprite, transform, slices, normal_map) in
+        sprite_query.iter()
     {
+        let normal_map_id = normal_map.map(|normal_map| normal_map.0.id());
+
         if !view_visibility.get() {
             continue;
         }
@@ -388,7 +432,7 @@ pub fn extract_sprites(
         if let Some(slices) = slices {
             extracted_sprites.sprites.extend(
                 slices
-                    .extract_sprites(transform, original_entity, sprite)
+                    .extract_sprites(transform, original_entity, sprite, normal_map_id)
                     .map(|e| {
                         (
                             (
@@ -428,6 +472,7 @@ pub fn extract_sprites(
                     flip_x: sprite.flip_x,
                     flip_y: sprite.flip_y,
                     image_handle_id: sprite.image.id(),
+                    normal_map_id,
                     anchor: sprite.anchor.as_vec(),
                     original_entity: Some(original_entity),
                 },
@@ -443,11 +488,18 @@ struct SpriteInstance {
     pub i_model_transpose: [Vec4; 3],
     pub i_color: [f32; 4],
     pub i_uv_offset_scale: [f32; 4],
+    pub i_occluder: [u32; 2],
+    pub _padding: [u32; 2],
 }
 
 impl SpriteInstance {
     #[inline]
-    fn from(transform: &Affine3A, color: &LinearRgba, uv_offset_scale: &Vec4) -> Self {
+    fn from(
+        transform: &Affine3A,
+        color: &LinearRgba,
+        uv_offset_scale: &Vec4,
+        occluder: UVec2,
+    ) -> Self {
         let transpose_model_3x3 = transform.matrix3.transpose();
         Self {
             i_model_transpose: [
@@ -457,6 +509,8 @@ impl SpriteInstance {
             ],
             i_color: color.to_f32_array(),
             i_uv_offset_scale: uv_offset_scale.to_array(),
+            i_occluder: occluder.to_array(),
+            _padding: [0; 2],
         }
     }
 }
@@ -484,12 +538,14 @@ pub struct SpriteViewBindGroup {
 #[derive(Component, PartialEq, Eq, Clone)]
 pub struct SpriteBatch {
     image_handle_id: AssetId<Image>,
+    normal_map_id: Option<AssetId<Image>>,
     range: Range<u32>,
 }
 
+/// The bind groups of the images of sprites, with their normal maps if any.
 #[derive(Resource, Default)]
 pub struct ImageBindGroups {
-    values: HashMap<AssetId<Image>, BindGroup>,
+    values: HashMap<(AssetId<Image>, Option<AssetId<Image>>), BindGroup>,
 }
 
 pub fn queue_sprites(
@@ -506,11 +562,12 @@ pub fn queue_sprites(
         &Msaa,
         Option<&Tonemapping>,
         Option<&DebandDither>,
+        Has<Lighting2d>,
     )>,
 ) {
     let draw_sprite_function = draw_functions.read().id::<DrawSprite>();
 
-    for (visible_entities, view, msaa, tonemapping, dither) in &mut views {
+    for (visible_entities, view, msaa, tonemapping, dither, lighting) in &mut views {
         let Some(transparent_phase) = transparent_render_phases.get_mut(&view.retained_view_entity)
         else {
             continue;
@@ -542,7 +599,18 @@ pub fn queue_sprites(
             }
         }
 
+        if lighting {
+            view_key |= SpritePipelineKey::LIGHTING;
+        }
+
         let pipeline = pipelines.specialize(&pipeline_cache, &sprite_pipeline, view_key);
+        let normal_mapped_pipeline = lighting.then(|| {
+            pipelines.specialize(
+                &pipeline_cache,
+                &sprite_pipeline,
+                view_key | SpritePipelineKey::NORMAL_MAP,
+            )
+        });
 
         view_entities.clear();
         view_entities.extend(
@@ -565,6 +633,13 @@ pub fn queue_sprites(
             // These items will be sorted by depth with other phase items
             let sort_key = FloatOrd(extracted_sprite.transform.translation().z);
 
+            let pipeline = match normal_mapped_pipeline {
+                Some(normal_mapped_pipeline) if extracted_sprite.normal_map_id.is_some() => {
+                    normal_mapped_pipeline
+                }
+                _ => pipeline,
+            };
+
             // Add the item to the render phase
             transparent_phase.add(Transparent2d {
                 draw_function: draw_sprite_function,
@@ -589,8 +664,13 @@ pub fn prepare_sprite_view_bind_groups(
     tonemapping_luts: Res<TonemappingLuts>,
     images: Res<RenderAssets<GpuImage>>,
     fallback_image: Res<FallbackImage>,
+    light_meta: Res<Light2dMeta>,
 ) {
-    let Some(view_binding) = view_uniforms.uniforms.binding() else {
+    let (Some(view_binding), Some(lights_binding), Some(occluders_binding)) = (
+        view_uniforms.uniforms.binding(),
+        light_meta.lights.binding(),
+        light_meta.occluders.binding(),
+    ) else {
         return;
     };
 
@@ -604,6 +684,8 @@ pub fn prepare_sprite_view_bind_groups(
                 (0, view_binding.clone()),
                 (1, lut_bindings.0),
                 (2, lut_bindings.1),
+                (3, lights_binding.clone()),
+                (4, occluders_binding.clone()),
             )),
         );
 
@@ -623,6 +705,7 @@ pub fn prepare_sprite_image_bind_groups(
     mut image_bind_groups: ResMut<ImageBindGroups>,
     gpu_images: Res<RenderAssets<GpuImage>>,
     extracted_sprites: Res<ExtractedSprites>,
+    extracted_lights: Res<ExtractedLights2d>,
     mut phases: ResMut<ViewSortedRenderPhases<Transparent2d>>,
     events: Res<SpriteAssetEvents>,
 ) {
@@ -633,7 +716,9 @@ pub fn prepare_sprite_image_bind_groups(
             // Images don't have dependencies
             AssetEvent::LoadedWithDependencies { .. } => {}
             AssetEvent::Unused { id } | AssetEvent::Modified { id } | AssetEvent::Removed { id } => {
-                image_bind_groups.values.remove(id);
+                image_bind_groups
+                    .values
+                    .retain(|(image, normal_map), _| image != id && *normal_map != Some(*id));
             }
         };
     }
@@ -652,6 +737,7 @@ pub fn prepare_sprite_image_bind_groups(
         let mut batch_item_index = 0;
         let mut batch_image_size = Vec2::ZERO;
         let mut batch_image_handle = AssetId::invalid();
+        let mut batch_normal_map_handle = None;
 
         // Iterate through the phase items and detect when successive sprites that can be batched.
         // Spawn an entity with a `SpriteBatch` component for each possible batch.
@@ -666,16 +752,29 @@ pub fn prepare_sprite_image_bind_groups(
                 continue;
             };
 
-            if batch_image_handle != extracted_sprite.image_handle_id {
+            if batch_image_handle != extracted_sprite.image_handle_id
+                || batch_normal_map_handle != extracted_sprite.normal_map_id
+            {
                 let Some(gpu_image) = gpu_images.get(extracted_sprite.image_handle_id) else {
                     continue;
                 };
+                let normal_map = match extracted_sprite.normal_map_id {
+                    Some(normal_map_id) => {
+                        let Some(gpu_normal_map) = gpu_images.get(normal_map_id) else {
+                            continue;
+                        };
+                        &gpu_normal_map.texture_view
+                    }
+                    // Only `SpritePipelineKey::NORMAL_MAP` pipelines read the normal map.
+                    None => &sprite_pipeline.dummy_white_gpu_image.texture_view,
+                };
 
                 batch_image_size = gpu_image.size_2d().as_vec2();
                 batch_image_handle = extracted_sprite.image_handle_id;
+                batch_normal_map_handle = extracted_sprite.normal_map_id;
                 image_bind_groups
                     .values
-                    .entry(batch_image_handle)
+                    .entry((batch_image_handle, batch_normal_map_handle))
                     .or_insert_with(|| {
                         render_device.create_bind_group(
                             "sprite_material_bind_group",
@@ -683,6 +782,7 @@ pub fn prepare_sprite_image_bind_groups(
                             &BindGroupEntries::sequential((
                                 &gpu_image.texture_view,
                                 &gpu_image.sampler,
+                                normal_map,
                             )),
                         )
                     });
@@ -692,6 +792,7 @@ pub fn prepare_sprite_image_bind_groups(
                     item.entity(),
                     SpriteBatch {
                         image_handle_id: batch_image_handle,
+                        normal_map_id: batch_normal_map_handle,
                         range: index..index,
                     },
                 ));
@@ -737,6 +838,13 @@ pub fn prepare_sprite_image_bind_groups(
                     (quad_size * (-extracted_sprite.anchor - Vec2::splat(0.5))).extend(0.0),
                 );
 
+            // Sprites aren't shadowed by the light occluder on the same entity.
+            let occluder = extracted_lights
+                .occluder_segment_ranges
+                .get(&item.entity.1)
+                .copied()
+                .unwrap_or_default();
+
             // Store the vertex data and add the item to the render phase
             sprite_meta
                 .sprite_instance_buffer
@@ -744,6 +852,7 @@ pub fn prepare_sprite_image_bind_groups(
                     &transform,
                     &extracted_sprite.color,
                     &uv_offset_scale,
+                    occluder,
                 ));
 
             transparent_phase.items[batch_item_index]
@@ -796,17 +905,25 @@ pub type DrawSprite = (
 pub struct SetSpriteViewBindGroup<const I: usize>;
 impl<P: PhaseItem, const I: usize> RenderCommand<P> for SetSpriteViewBindGroup<I> {
     type Param = ();
-    type ViewQuery = (Read<ViewUniformOffset>, Read<SpriteViewBindGroup>);
+    type ViewQuery = (
+        Read<ViewUniformOffset>,
+        Read<ViewLights2dUniformOffset>,
+        Read<SpriteViewBindGroup>,
+    );
     type ItemQuery = ();
 
     fn render<'w>(
         _item: &P,
-        (view_uniform, sprite_view_bind_group): ROQueryItem<'w, Self::ViewQuery>,
+        (view_uniform, view_lights, sprite_view_bind_group): ROQueryItem<'w, Self::ViewQuery>,
         _entity: Option<()>,
         _param: SystemParamItem<'w, '_, Self::Param>,
         pass: &mut TrackedRenderPass<'w>,
     ) -> RenderCommandResult {
-        pass.set_bind_group(I, &sprite_view_bind_group.value, &[view_uniform.offset]);
+        pass.set_bind_group(
+            I,
+            &sprite_view_bind_group.value,
+            &[view_uniform.offset, view_lights.offset],
+        );
         RenderCommandResult::Success
     }
 }
@@ -832,7 +949,7 @@ impl<P: PhaseItem, const I: usize> RenderCommand<P> for SetSpriteTextureBindGrou
             I,
             image_bind_groups
                 .values
-                .get(&batch.image_handle_id)
+                .get(&(batch.image_handle_id, batch.normal_map_id))
                 .unwrap(),
             &[],
         );
@@ -9,6 +9,10 @@
 
 #import bevy_sprite::sprite_view_bindings::view
 
+#ifdef LIGHTING_2D
+#import bevy_sprite::light2d
+#endif
+
 struct VertexInput {
     @builtin(vertex_index) index: u32,
     // NOTE: Instance-rate vertex buffer members prefixed with i_
@@ -19,12 +23,20 @@ struct VertexInput {
     @location(2) i_model_transpose_col2: vec4<f32>,
     @location(3) i_color: vec4<f32>,
     @location(4) i_uv_offset_scale: vec4<f32>,
+    // The first edge and the edge count of the light occluder of the sprite.
+    @location(5) i_occluder: vec2<u32>,
 }
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) uv: vec2<f32>,
     @location(1) @interpolate(flat) color: vec4<f32>,
+#ifdef LIGHTING_2D
+    @location(2) world_position: vec2<f32>,
+    // The world space directions of the right and the top of the image, in `xy` and `zw`.
+    @location(3) @interpolate(flat) tangents: vec4<f32>,
+    @location(4) @interpolate(flat) occluder: vec2<u32>,
+#endif
 };
 
 @vertex
@@ -37,24 +49,54 @@ fn vertex(in: VertexInput) -> VertexOutput {
         0.0
     );
 
-    out.clip_position = view.clip_from_world * affine3_to_square(mat3x4<f32>(
+    let world_from_local = affine3_to_square(mat3x4<f32>(
         in.i_model_transpose_col0,
         in.i_model_transpose_col1,
         in.i_model_transpose_col2,
-    )) * vec4<f32>(vertex_position, 1.0);
+    ));
+    let world_position = world_from_local * vec4<f32>(vertex_position, 1.0);
+    out.clip_position = view.clip_from_world * world_position;
     out.uv = vec2<f32>(vertex_position.xy) * in.i_uv_offset_scale.zw + in.i_uv_offset_scale.xy;
     out.color = in.i_color;
 
+#ifdef LIGHTING_2D
+    out.world_position = world_position.xy;
+    // The UVs of unflipped sprites grow towards the right and the bottom, so flipped sprites are
+    // the ones whose UV scale has the opposite signs.
+    let right = normalize(world_from_local[0].xy) * sign(in.i_uv_offset_scale.z);
+    let top = normalize(world_from_local[1].xy) * -sign(in.i_uv_offset_scale.w);
+    out.tangents = vec4(right, top);
+    out.occluder = in.i_occluder;
+#endif
+
     return out;
 }
 
 @group(1) @binding(0) var sprite_texture: texture_2d<f32>;
 @group(1) @binding(1) var sprite_sampler: sampler;
+#ifdef NORMAL_MAP_2D
+@group(1) @binding(2) var sprite_normal_map: texture_2d<f32>;
+#endif
 
 @fragment
 fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
     var color = in.color * textureSample(sprite_texture, sprite_sampler, in.uv);
 
+#ifdef LIGHTING_2D
+#ifdef NORMAL_MAP_2D
+    let tangent_normal =
+        textureSample(sprite_normal_map, sprite_sampler, in.uv).xyz * 2.0 - 1.0;
+    let normal = normalize(vec3(
+        tangent_normal.x * in.tangents.xy + tangent_normal.y * in.tangents.zw,
+        tangent_normal.z,
+    ));
+#else
+    let normal = vec3(0.0, 0.0, 1.0);
+#endif
+    let light = light2d::light_sprite(in.world_position, normal, in.occluder);
+    color = vec4(color.rgb * light, color.a);
+#endif
+
 #ifdef TONEMAP_IN_SHADER
     color = tonemapping::tone_mapping(color, view.color_grading);
 #endif
@@ -13,7 +13,7 @@ use bevy_render::{
 };
 use bevy_transform::components::Transform;
 
-use crate::TextureSlicer;
+use crate::{TextureSlice, TextureSlicer};
 
 /// Describes a sprite to be rendered to a 2D camera
 #[derive(Component, Debug, Default, Clone, Reflect)]
@@ -85,6 +85,9 @@ impl Sprite {
     /// from in this sprite. `point_relative_to_sprite` must be in the sprite's
     /// local frame. Returns an Ok if the point is inside the bounds of the
     /// sprite (not just the image), and returns an Err otherwise.
+    ///
+    /// Sliced and tiled sprites are sampled from the slice of the texture that is drawn at
+    /// the point, according to their [`SpriteImageMode`].
     pub fn compute_pixel_space_point(
         &self,
         point_relative_to_sprite: Vec2,
@@ -139,7 +142,38 @@ impl Sprite {
         let point_relative_to_texture =
             point_relative_to_sprite_center * sprite_to_texture_ratio + texture_rect.center();
 
-        // TODO: Support `SpriteImageMode`.
+        // Sliced and tiled sprites sample the texture from the slice under the point.
+        let slices = match &self.image_mode {
+            SpriteImageMode::Auto => Vec::new(),
+            SpriteImageMode::Sliced(slicer) => {
+                slicer.compute_slices(texture_rect, Some(sprite_size))
+            }
+            SpriteImageMode::Tiled {
+                tile_x,
+                tile_y,
+                stretch_value,
+            } => TextureSlice {
+                texture_rect,
+                draw_size: sprite_size,
+                offset: Vec2::ZERO,
+            }
+            .tiled(*stretch_value, (*tile_x, *tile_y)),
+        };
+        for slice in slices
+            .into_iter()
+            .filter(|slice| slice.draw_size.cmpgt(Vec2::ZERO).all())
+        {
+            // Slice offsets point up, like the sprite's local frame.
+            let slice_center = slice.offset * Vec2::new(1.0, -1.0);
+            let point_relative_to_slice = point_relative_to_sprite_center - slice_center;
+            if Rect::from_center_size(Vec2::ZERO, slice.draw_size).contains(point_relative_to_slice)
+            {
+                let slice_to_texture_ratio = slice.texture_rect.size() / slice.draw_size;
+                return Ok(
+                    point_relative_to_slice * slice_to_texture_ratio + slice.texture_rect.center()
+                );
+            }
+        }
 
         if texture_rect.contains(point_relative_to_texture) {
             Ok(point_relative_to_texture)
@@ -162,7 +196,9 @@ pub enum SpriteImageMode {
     /// The sprite will take on the size of the image by default, and will be stretched or shrunk if [`Sprite::custom_size`] is set.
     #[default]
     Auto,
-    /// The texture will be cut in 9 slices, keeping the texture in proportions on resize
+    /// The texture will be cut in 9 slices, keeping the texture in proportions on resize.
+    ///
+    /// The center and sides can be stretched or tiled along each axis, see [`SliceScaleMode`](crate::SliceScaleMode).
     Sliced(TextureSlicer),
     /// The texture will be repeated if stretched beyond `stretched_value`
     Tiled {
@@ -234,7 +270,7 @@ mod tests {
     use bevy_math::{Rect, URect, UVec2, Vec2};
     use bevy_render::render_resource::{Extent3d, TextureDimension, TextureFormat};
 
-    use crate::Anchor;
+    use crate::{Anchor, BorderRect, SpriteImageMode, TextureSlicer};
 
     use super::Sprite;
 
@@ -486,4 +522,32 @@ mod tests {
         // The pixel is outside the texture atlas, but is still a valid pixel in the image.
         assert_eq!(compute(Vec2::new(0.0, 35.0)), Err(Vec2::new(2.5, -1.0)));
     }
+
+    #[test]
+    fn compute_pixel_space_point_for_sliced_sprite() {
+        let mut image_assets = Assets::<Image>::default();
+        let texture_atlas_assets = Assets::<TextureAtlasLayout>::default();
+
+        let image = image_assets.add(make_image(UVec2::new(10, 10)));
+
+        let sprite = Sprite {
+            image,
+            custom_size: Some(Vec2::new(20.0, 20.0)),
+            image_mode: SpriteImageMode::Sliced(TextureSlicer {
+                border: BorderRect::all(2.0),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let compute =
+            |point| sprite.compute_pixel_space_point(point, &image_assets, &texture_atlas_assets);
+        // The corners keep their size.
+        assert_eq!(compute(Vec2::new(-9.5, 9.5)), Ok(Vec2::new(0.5, 0.5)));
+        assert_eq!(compute(Vec2::new(9.5, -9.5)), Ok(Vec2::new(9.5, 9.5)));
+        // The center is stretched.
+        assert_eq!(compute(Vec2::new(0.0, 0.0)), Ok(Vec2::new(5.0, 5.0)));
+        assert_eq!(compute(Vec2::new(4.0, 0.0)), Ok(Vec2::new(6.5, 5.0)));
+        assert_eq!(compute(Vec2::new(11.0, 0.0)), Err(Vec2::new(10.5, 5.0)));
+    }
 }
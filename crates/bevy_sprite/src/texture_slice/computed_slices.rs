@@ -1,7 +1,7 @@
 use crate::{ExtractedSprite, Sprite, SpriteImageMode, TextureAtlasLayout};
 
 use super::TextureSlice;
-use bevy_asset::{AssetEvent, Assets};
+use bevy_asset::{AssetEvent, AssetId, Assets};
 use bevy_ecs::prelude::*;
 use bevy_image::Image;
 use bevy_math::{Rect, Vec2};
@@ -29,6 +29,7 @@ impl ComputedTextureSlices {
         transform: &'a GlobalTransform,
         original_entity: Entity,
         sprite: &'a Sprite,
+        normal_map_id: Option<AssetId<Image>>,
     ) -> impl ExactSizeIterator<Item = ExtractedSprite> + 'a {
         let mut flip = Vec2::ONE;
         let [mut flip_x, mut flip_y] = [false; 2];
@@ -52,6 +53,7 @@ impl ComputedTextureSlices {
                 flip_x,
                 flip_y,
                 image_handle_id: sprite.image.id(),
+                normal_map_id,
                 anchor: Self::redepend_anchor_from_sprite_to_slice(sprite, slice),
             }
         })
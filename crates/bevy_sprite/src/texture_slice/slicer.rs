@@ -41,9 +41,54 @@ pub enum SliceScaleMode {
         /// Note: the value will be clamped to `0.001` if lower
         stretch_value: f32,
     },
+    /// The slice will be tiled horizontally and stretched vertically to fit the area
+    TileX {
+        /// The slice will repeat horizontally when the ratio between the *drawing width* of texture and the
+        /// *original texture width* is above `stretch_value`.
+        ///
+        /// See [`SliceScaleMode::Tile`].
+        stretch_value: f32,
+    },
+    /// The slice will be tiled vertically and stretched horizontally to fit the area
+    TileY {
+        /// The slice will repeat vertically when the ratio between the *drawing height* of texture and the
+        /// *original texture height* is above `stretch_value`.
+        ///
+        /// See [`SliceScaleMode::Tile`].
+        stretch_value: f32,
+    },
+}
+
+impl SliceScaleMode {
+    /// Returns the stretch value of the mode and whether it tiles horizontally and vertically,
+    /// or `None` if the slice is stretched along both axes.
+    #[inline]
+    pub fn tiling(&self) -> Option<(f32, (bool, bool))> {
+        match *self {
+            SliceScaleMode::Stretch => None,
+            SliceScaleMode::Tile { stretch_value } => Some((stretch_value, (true, true))),
+            SliceScaleMode::TileX { stretch_value } => Some((stretch_value, (true, false))),
+            SliceScaleMode::TileY { stretch_value } => Some((stretch_value, (false, true))),
+        }
+    }
 }
 
 impl TextureSlicer {
+    /// Returns the slicing lines of [`TextureSlicer::border`] as fractions of `size`, the size of
+    /// the sliced section of the texture.
+    ///
+    /// These are the insets of the slices in UV space relative to the sliced section, which
+    /// custom shaders and materials can use to slice a texture the same way.
+    #[must_use]
+    pub fn uv_insets(&self, size: Vec2) -> BorderRect {
+        BorderRect {
+            left: self.border.left / size.x,
+            right: self.border.right / size.x,
+            top: self.border.top / size.y,
+            bottom: self.border.bottom / size.y,
+        }
+    }
+
     /// Computes the 4 corner slices: top left, top right, bottom left, bottom right.
     #[must_use]
     fn corner_slices(&self, base_rect: Rect, render_size: Vec2) -> [TextureSlice; 4] {
@@ -247,29 +292,31 @@ impl TextureSlicer {
         };
 
         slices.extend(corners);
-        match self.center_scale_mode {
-            SliceScaleMode::Stretch => {
+        match self.center_scale_mode.tiling() {
+            None => {
                 slices.push(center);
             }
-            SliceScaleMode::Tile { stretch_value } => {
-                slices.extend(center.tiled(stretch_value, (true, true)));
+            Some((stretch_value, tile_axes)) => {
+                slices.extend(center.tiled(stretch_value, tile_axes));
             }
         }
-        match self.sides_scale_mode {
-            SliceScaleMode::Stretch => {
+        match self.sides_scale_mode.tiling() {
+            None => {
                 slices.extend(horizontal_sides);
                 slices.extend(vertical_sides);
             }
-            SliceScaleMode::Tile { stretch_value } => {
+            // The left and right sides can only tile vertically, and the top and bottom sides
+            // horizontally
+            Some((stretch_value, (tile_x, tile_y))) => {
                 slices.extend(
                     horizontal_sides
                         .into_iter()
-                        .flat_map(|s| s.tiled(stretch_value, (false, true))),
+                        .flat_map(|s| s.tiled(stretch_value, (false, tile_y))),
                 );
                 slices.extend(
                     vertical_sides
                         .into_iter()
-                        .flat_map(|s| s.tiled(stretch_value, (true, false))),
+                        .flat_map(|s| s.tiled(stretch_value, (tile_x, false))),
                 );
             }
         }
@@ -431,4 +478,57 @@ mod test {
             }
         );
     }
+
+    #[test]
+    fn test_per_axis_tiling() {
+        let slicer = TextureSlicer {
+            border: BorderRect::all(10.),
+            center_scale_mode: SliceScaleMode::TileX { stretch_value: 1.0 },
+            sides_scale_mode: SliceScaleMode::TileY { stretch_value: 1.0 },
+            max_corner_scale: 1.0,
+        };
+        let rect = Rect {
+            min: Vec2::ZERO,
+            max: Vec2::splat(50.),
+        };
+        let slices = slicer.compute_slices(rect, Some(Vec2::splat(100.)));
+        // 4 corners, 3 center columns, 3 tiles for each of the left and right sides,
+        // and the stretched top and bottom sides
+        assert_eq!(slices.len(), 15);
+        assert_eq!(
+            slices[4],
+            TextureSlice {
+                texture_rect: Rect {
+                    min: Vec2::splat(10.0),
+                    max: Vec2::splat(40.0)
+                },
+                draw_size: Vec2::new(30.0, 80.0),
+                offset: Vec2::new(-25.0, 0.0),
+            }
+        );
+        assert_eq!(slices[7].draw_size, Vec2::new(10.0, 30.0));
+        assert_eq!(slices[13].draw_size, Vec2::new(80.0, 10.0));
+    }
+
+    #[test]
+    fn test_uv_insets() {
+        let slicer = TextureSlicer {
+            border: BorderRect {
+                left: 5.,
+                right: 10.,
+                top: 20.,
+                bottom: 0.,
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            slicer.uv_insets(Vec2::new(50., 40.)),
+            BorderRect {
+                left: 0.1,
+                right: 0.2,
+                top: 0.5,
+                bottom: 0.0,
+            }
+        );
+    }
 }
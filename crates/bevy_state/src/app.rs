@@ -5,8 +5,9 @@ use log::warn;
 
 use crate::{
     state::{
-        setup_state_transitions_in_world, ComputedStates, FreelyMutableState, NextState, State,
-        StateTransition, StateTransitionEvent, StateTransitionSteps, States, SubStates,
+        setup_state_transitions_in_world, ComputedStates, FreelyMutableState, NextState,
+        PausedSubStates, State, StateStack, StateTransition, StateTransitionEvent,
+        StateTransitionSteps, States, SubStates,
     },
     state_scoped::clear_state_scoped_entities,
 };
@@ -54,9 +55,22 @@ pub trait AppExtStates {
 
     /// Sets up a type implementing [`SubStates`].
     ///
+    /// Adds the [`PausedSubStates<S>`] resource, to pause and resume the sub state along with its
+    /// source states when they're pushed over and popped back with a
+    /// [`StateStack`](crate::state::StateStack).
+    ///
     /// This method is idempotent: it has no effect when called again using the same generic type.
     fn add_sub_state<S: SubStates>(&mut self) -> &mut Self;
 
+    /// Enables the [`StateStack<S>`] of a state initialized with [`init_state`](Self::init_state)
+    /// or [`insert_state`](Self::insert_state), to push states over the current one and pop them.
+    ///
+    /// Adds the [`StateStack<S>`] resource, and enables use of the [`OnPause`](crate::state::OnPause)
+    /// and [`OnResume`](crate::state::OnResume) schedules.
+    ///
+    /// This method is idempotent: it has no effect when called again using the same generic type.
+    fn init_state_stack<S: FreelyMutableState>(&mut self) -> &mut Self;
+
     /// Enable state-scoped entity clearing for state `S`.
     ///
     /// If the [`States`] trait was derived with the `#[states(scoped_entities)]` attribute, it
@@ -190,6 +204,7 @@ impl AppExtStates for SubApp {
             .contains_resource::<Events<StateTransitionEvent<S>>>()
         {
             self.init_resource::<NextState<S>>();
+            self.init_resource::<PausedSubStates<S>>();
             self.add_event::<StateTransitionEvent<S>>();
             let schedule = self.get_schedule_mut(StateTransition).expect(
                 "The `StateTransition` schedule is missing. Did you forget to add StatesPlugin or DefaultPlugins before calling add_sub_state?"
@@ -214,6 +229,29 @@ impl AppExtStates for SubApp {
         self
     }
 
+    fn init_state_stack<S: FreelyMutableState>(&mut self) -> &mut Self {
+        warn_if_no_states_plugin_installed(self);
+        if !self
+            .world()
+            .contains_resource::<Events<StateTransitionEvent<S>>>()
+        {
+            let name = core::any::type_name::<S>();
+            warn!("A state stack is enabled for state `{}`, but the state isn't installed in the app!", name);
+        }
+        if !self.world().contains_resource::<StateStack<S>>() {
+            self.init_resource::<StateStack<S>>();
+            let schedule = self.get_schedule_mut(StateTransition).expect(
+                "The `StateTransition` schedule is missing. Did you forget to add StatesPlugin or DefaultPlugins before calling init_state_stack?"
+            );
+            StateStack::<S>::register_state_stack_systems(schedule);
+        } else {
+            let name = core::any::type_name::<S>();
+            warn!("State stack {} is already initialized.", name);
+        }
+
+        self
+    }
+
     fn enable_state_scoped_entities<S: States>(&mut self) -> &mut Self {
         if !self
             .world()
@@ -276,6 +314,11 @@ impl AppExtStates for App {
         self
     }
 
+    fn init_state_stack<S: FreelyMutableState>(&mut self) -> &mut Self {
+        self.main_mut().init_state_stack::<S>();
+        self
+    }
+
     fn enable_state_scoped_entities<S: States>(&mut self) -> &mut Self {
         self.main_mut().enable_state_scoped_entities::<S>();
         self
@@ -1,7 +1,7 @@
 use bevy_ecs::{system::Commands, world::World};
 use log::debug;
 
-use crate::state::{FreelyMutableState, NextState};
+use crate::state::{FreelyMutableState, NextState, StateStack};
 
 /// Extension trait for [`Commands`] adding `bevy_state` helpers.
 pub trait CommandsStatesExt {
@@ -13,6 +13,18 @@ pub trait CommandsStatesExt {
     /// Note that commands introduce sync points to the ECS schedule, so modifying `NextState`
     /// directly may be more efficient depending on your use-case.
     fn set_state<S: FreelyMutableState>(&mut self, state: S);
+
+    /// Pauses the current state and enters `state` over it.
+    ///
+    /// Internally this schedules a command that calls [`StateStack::push`] on the
+    /// [`StateStack<S>`](crate::prelude::StateStack) resource.
+    fn push_state<S: FreelyMutableState>(&mut self, state: S);
+
+    /// Exits the current state and resumes the last paused one.
+    ///
+    /// Internally this schedules a command that calls [`StateStack::pop`] on the
+    /// [`StateStack<S>`](crate::prelude::StateStack) resource.
+    fn pop_state<S: FreelyMutableState>(&mut self);
 }
 
 impl CommandsStatesExt for Commands<'_, '_> {
@@ -27,4 +39,16 @@ impl CommandsStatesExt for Commands<'_, '_> {
             next.set(state);
         });
     }
+
+    fn push_state<S: FreelyMutableState>(&mut self, state: S) {
+        self.queue(move |w: &mut World| {
+            w.resource_mut::<StateStack<S>>().push(state);
+        });
+    }
+
+    fn pop_state<S: FreelyMutableState>(&mut self) {
+        self.queue(|w: &mut World| {
+            w.resource_mut::<StateStack<S>>().pop();
+        });
+    }
 }
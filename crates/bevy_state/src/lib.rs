@@ -83,8 +83,8 @@ pub mod prelude {
         condition::*,
         state::{
             last_transition, ComputedStates, EnterSchedules, ExitSchedules, NextState, OnEnter,
-            OnExit, OnTransition, State, StateSet, StateTransition, StateTransitionEvent, States,
-            SubStates, TransitionSchedules,
+            OnExit, OnPause, OnResume, OnTransition, State, StateSet, StateStack, StateTransition,
+            StateTransitionEvent, States, SubStates, TransitionSchedules,
         },
         state_scoped::StateScoped,
     };
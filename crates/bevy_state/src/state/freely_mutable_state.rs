@@ -46,7 +46,7 @@ pub trait FreelyMutableState: States {
     }
 }
 
-fn apply_state_transition<S: FreelyMutableState>(
+pub(super) fn apply_state_transition<S: FreelyMutableState>(
     event: EventWriter<StateTransitionEvent<S>>,
     commands: Commands,
     current_state: Option<ResMut<State<S>>>,
@@ -2,6 +2,7 @@ mod computed_states;
 mod freely_mutable_state;
 mod resources;
 mod state_set;
+mod state_stack;
 mod states;
 mod sub_states;
 mod transitions;
@@ -11,6 +12,7 @@ pub use computed_states::*;
 pub use freely_mutable_state::*;
 pub use resources::*;
 pub use state_set::*;
+pub use state_stack::*;
 pub use states::*;
 pub use sub_states::*;
 pub use transitions::*;
@@ -725,4 +727,175 @@ mod tests {
         assert_eq!(transitions[7], "sub enter");
         assert_eq!(transitions[8], "computed enter");
     }
+
+    #[derive(Resource, Default, PartialEq, Debug)]
+    struct StackCounter {
+        pause: u8,
+        resume: u8,
+        exit: u8,
+        enter: u8,
+    }
+
+    #[test]
+    fn state_stack_pauses_and_resumes_states() {
+        let mut world = World::new();
+        setup_state_transitions_in_world(&mut world);
+        EventRegistry::register_event::<StateTransitionEvent<SimpleState>>(&mut world);
+        EventRegistry::register_event::<StateTransitionEvent<TestComputedState>>(&mut world);
+        world.init_resource::<State<SimpleState>>();
+        world.init_resource::<StateStack<SimpleState>>();
+        let mut schedules = world.resource_mut::<Schedules>();
+        let apply_changes = schedules.get_mut(StateTransition).unwrap();
+        SimpleState::register_state(apply_changes);
+        StateStack::<SimpleState>::register_state_stack_systems(apply_changes);
+        TestComputedState::register_computed_state_systems(apply_changes);
+
+        let mut on_pause = Schedule::new(OnPause(SimpleState::A));
+        on_pause.add_systems(|mut c: ResMut<StackCounter>| c.pause += 1);
+        schedules.insert(on_pause);
+        let mut on_resume = Schedule::new(OnResume(SimpleState::A));
+        on_resume.add_systems(|mut c: ResMut<StackCounter>| c.resume += 1);
+        schedules.insert(on_resume);
+        let mut on_exit = Schedule::new(OnExit(SimpleState::A));
+        on_exit.add_systems(|mut c: ResMut<StackCounter>| c.exit += 1);
+        schedules.insert(on_exit);
+        let mut on_enter = Schedule::new(OnEnter(SimpleState::A));
+        on_enter.add_systems(|mut c: ResMut<StackCounter>| c.enter += 1);
+        schedules.insert(on_enter);
+        world.init_resource::<StackCounter>();
+
+        world.run_schedule(StateTransition);
+        world
+            .resource_mut::<StateStack<SimpleState>>()
+            .push(SimpleState::B(true));
+        world.insert_resource(NextState::Pending(SimpleState::B(false)));
+        world.run_schedule(StateTransition);
+        assert_eq!(
+            world.resource::<State<SimpleState>>().0,
+            SimpleState::B(true)
+        );
+        assert_eq!(
+            world.resource::<State<TestComputedState>>().0,
+            TestComputedState::BisTrue
+        );
+        assert_eq!(
+            world.resource::<StateStack<SimpleState>>().paused(),
+            &[SimpleState::A]
+        );
+        assert!(matches!(
+            world.resource::<NextState<SimpleState>>(),
+            NextState::Unchanged
+        ));
+
+        world.resource_mut::<StateStack<SimpleState>>().pop();
+        world.run_schedule(StateTransition);
+        assert_eq!(world.resource::<State<SimpleState>>().0, SimpleState::A);
+        assert!(!world.contains_resource::<State<TestComputedState>>());
+        assert!(world.resource::<StateStack<SimpleState>>().is_empty());
+        assert_eq!(
+            *world.resource::<StackCounter>(),
+            StackCounter {
+                pause: 1,
+                resume: 1,
+                exit: 0,
+                enter: 0,
+            }
+        );
+
+        // Popping an empty stack does nothing, and plain transitions still run the usual schedules.
+        world.resource_mut::<StateStack<SimpleState>>().pop();
+        world.run_schedule(StateTransition);
+        assert_eq!(world.resource::<State<SimpleState>>().0, SimpleState::A);
+        world.insert_resource(NextState::Pending(SimpleState::B(false)));
+        world.run_schedule(StateTransition);
+        world.insert_resource(NextState::Pending(SimpleState::A));
+        world.run_schedule(StateTransition);
+        assert_eq!(
+            *world.resource::<StackCounter>(),
+            StackCounter {
+                pause: 1,
+                resume: 1,
+                exit: 1,
+                enter: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn state_stack_pauses_and_resumes_sub_states() {
+        let mut world = World::new();
+        setup_state_transitions_in_world(&mut world);
+        EventRegistry::register_event::<StateTransitionEvent<SimpleState>>(&mut world);
+        EventRegistry::register_event::<StateTransitionEvent<SubState>>(&mut world);
+        world.insert_resource(State(SimpleState::B(true)));
+        world.init_resource::<StateStack<SimpleState>>();
+        world.init_resource::<PausedSubStates<SubState>>();
+        let mut schedules = world.resource_mut::<Schedules>();
+        let apply_changes = schedules.get_mut(StateTransition).unwrap();
+        SimpleState::register_state(apply_changes);
+        StateStack::<SimpleState>::register_state_stack_systems(apply_changes);
+        SubState::register_sub_state_systems(apply_changes);
+
+        let mut on_pause = Schedule::new(OnPause(SubState::Two));
+        on_pause.add_systems(|mut c: ResMut<StackCounter>| c.pause += 1);
+        schedules.insert(on_pause);
+        let mut on_resume = Schedule::new(OnResume(SubState::Two));
+        on_resume.add_systems(|mut c: ResMut<StackCounter>| c.resume += 1);
+        schedules.insert(on_resume);
+        let mut on_exit = Schedule::new(OnExit(SubState::Two));
+        on_exit.add_systems(|mut c: ResMut<StackCounter>| c.exit += 1);
+        schedules.insert(on_exit);
+        let mut on_enter = Schedule::new(OnEnter(SubState::Two));
+        on_enter.add_systems(|mut c: ResMut<StackCounter>| c.enter += 1);
+        schedules.insert(on_enter);
+        world.init_resource::<StackCounter>();
+
+        world.send_event(StateTransitionEvent {
+            exited: None,
+            entered: Some(SimpleState::B(true)),
+        });
+        world.insert_resource(NextState::Pending(SubState::Two));
+        world.run_schedule(StateTransition);
+        assert_eq!(world.resource::<State<SubState>>().0, SubState::Two);
+
+        world
+            .resource_mut::<StateStack<SimpleState>>()
+            .push(SimpleState::A);
+        world.run_schedule(StateTransition);
+        assert!(!world.contains_resource::<State<SubState>>());
+        assert!(world
+            .resource::<PausedSubStates<SubState>>()
+            .is_paused(&SubState::Two));
+
+        // Pushing the current state does nothing.
+        world
+            .resource_mut::<StateStack<SimpleState>>()
+            .push(SimpleState::A);
+        world.run_schedule(StateTransition);
+        assert_eq!(world.resource::<StateStack<SimpleState>>().len(), 1);
+
+        world.resource_mut::<StateStack<SimpleState>>().pop();
+        world.run_schedule(StateTransition);
+        assert_eq!(
+            world.resource::<State<SimpleState>>().0,
+            SimpleState::B(true)
+        );
+        assert_eq!(world.resource::<State<SubState>>().0, SubState::Two);
+        assert_eq!(
+            world
+                .resource::<PausedSubStates<SubState>>()
+                .paused()
+                .count(),
+            0
+        );
+        assert_eq!(
+            *world.resource::<StackCounter>(),
+            StackCounter {
+                pause: 1,
+                resume: 1,
+                exit: 0,
+                enter: 1,
+            }
+        );
+    }
 }
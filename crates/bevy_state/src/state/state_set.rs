@@ -1,4 +1,5 @@
 use bevy_ecs::{
+    change_detection::DetectChangesMut,
     event::{EventReader, EventWriter},
     schedule::{IntoSystemConfigs, IntoSystemSetConfigs, Schedule},
     system::{Commands, IntoSystem, Res, ResMut},
@@ -9,9 +10,9 @@ use self::sealed::StateSetSealed;
 
 use super::{
     computed_states::ComputedStates, internal_apply_state_transition, last_transition, run_enter,
-    run_exit, run_transition, sub_states::SubStates, take_next_state, ApplyStateTransition,
-    EnterSchedules, ExitSchedules, NextState, State, StateTransitionEvent, StateTransitionSteps,
-    States, TransitionSchedules,
+    run_exit, run_transition, stack_transition, sub_states::SubStates, take_next_state,
+    ApplyStateTransition, EnterSchedules, ExitSchedules, NextState, PausedSubStates, State,
+    StateStack, StateTransitionEvent, StateTransitionSteps, States, TransitionSchedules,
 };
 
 mod sealed {
@@ -171,11 +172,21 @@ impl<S: InnerStateSet> StateSet for S {
              commands: Commands,
              current_state_res: Option<ResMut<State<T>>>,
              next_state_res: Option<ResMut<NextState<T>>>,
-             state_set: Option<Res<State<S::RawState>>>| {
+             state_set: Option<Res<State<S::RawState>>>,
+             (parent_stack, parent_paused): (
+                Option<Res<StateStack<S::RawState>>>,
+                Option<Res<PausedSubStates<S::RawState>>>,
+            ),
+             mut paused: Option<ResMut<PausedSubStates<T>>>| {
+                if let Some(paused) = paused.as_mut() {
+                    paused.bypass_change_detection().start_transition();
+                }
                 let parent_changed = parent_changed.read().last().is_some();
                 let next_state = take_next_state(next_state_res);
+                let parent_transition =
+                    stack_transition(parent_stack.as_deref(), parent_paused.as_deref());
 
-                if !parent_changed && next_state.is_none() {
+                if !parent_changed && next_state.is_none() && parent_transition.is_none() {
                     return;
                 }
 
@@ -190,7 +201,15 @@ impl<S: InnerStateSet> StateSet for S {
                 } else {
                     current_state.clone()
                 };
-                let new_state = initial_state.map(|x| next_state.or(current_state).unwrap_or(x));
+                let mut new_state =
+                    initial_state.map(|x| next_state.or(current_state.clone()).unwrap_or(x));
+                if let Some(paused) = paused.as_mut() {
+                    new_state = paused.pause_or_resume(
+                        parent_transition,
+                        current_state.as_ref(),
+                        new_state,
+                    );
+                }
 
                 internal_apply_state_transition(event, commands, current_state_res, new_state);
             };
@@ -229,7 +248,7 @@ impl<S: InnerStateSet> StateSet for S {
 }
 
 macro_rules! impl_state_set_sealed_tuples {
-    ($(#[$meta:meta])* $(($param: ident, $val: ident, $evt: ident)), *) => {
+    ($(#[$meta:meta])* $(($param: ident, $val: ident, $evt: ident, $stack: ident, $stack_paused: ident)), *) => {
         $(#[$meta])*
         impl<$($param: InnerStateSet),*> StateSetSealed for ($($param,)*) {}
 
@@ -292,11 +311,18 @@ macro_rules! impl_state_set_sealed_tuples {
                      commands: Commands,
                      current_state_res: Option<ResMut<State<T>>>,
                      next_state_res: Option<ResMut<NextState<T>>>,
-                     ($($val),*,): ($(Option<Res<State<$param::RawState>>>),*,)| {
+                     ($($val),*,): ($(Option<Res<State<$param::RawState>>>),*,),
+                     ($(($stack, $stack_paused)),*,): ($((Option<Res<StateStack<$param::RawState>>>, Option<Res<PausedSubStates<$param::RawState>>>)),*,),
+                     mut paused: Option<ResMut<PausedSubStates<T>>>| {
+                        if let Some(paused) = paused.as_mut() {
+                            paused.bypass_change_detection().start_transition();
+                        }
                         let parent_changed = ($($evt.read().last().is_some())&&*);
                         let next_state = take_next_state(next_state_res);
+                        let parent_transition = None
+                            $(.or_else(|| stack_transition($stack.as_deref(), $stack_paused.as_deref())))*;
 
-                        if !parent_changed && next_state.is_none() {
+                        if !parent_changed && next_state.is_none() && parent_transition.is_none() {
                             return;
                         }
 
@@ -311,7 +337,10 @@ macro_rules! impl_state_set_sealed_tuples {
                         } else {
                             current_state.clone()
                         };
-                        let new_state = initial_state.map(|x| next_state.or(current_state).unwrap_or(x));
+                        let mut new_state = initial_state.map(|x| next_state.or(current_state.clone()).unwrap_or(x));
+                        if let Some(paused) = paused.as_mut() {
+                            new_state = paused.pause_or_resume(parent_transition, current_state.as_ref(), new_state);
+                        }
 
                         internal_apply_state_transition(event, commands, current_state_res, new_state);
                     };
@@ -347,5 +376,7 @@ all_tuples!(
     15,
     S,
     s,
-    ereader
+    ereader,
+    stack,
+    stack_paused
 );
@@ -0,0 +1,300 @@
+use alloc::vec::Vec;
+
+use bevy_ecs::{
+    change_detection::DetectChangesMut,
+    event::EventWriter,
+    schedule::{IntoSystemConfigs, Schedule, ScheduleLabel},
+    system::{Commands, ResMut, Resource},
+    world::World,
+};
+use log::{debug, warn};
+
+use super::{
+    freely_mutable_state::{apply_state_transition, FreelyMutableState},
+    internal_apply_state_transition, take_next_state, ApplyStateTransition, NextState, State,
+    StateTransitionEvent, States,
+};
+
+/// The label of a [`Schedule`] that **only** runs whenever [`State<S>`] is paused in the provided
+/// state, because another state was pushed over it with [`StateStack::push`].
+///
+/// It runs instead of [`OnExit`](super::OnExit) for the paused state.
+#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct OnPause<S: States>(pub S);
+
+/// The label of a [`Schedule`] that **only** runs whenever [`State<S>`] resumes the provided
+/// state, because the state pushed over it was popped with [`StateStack::pop`].
+///
+/// It runs instead of [`OnEnter`](super::OnEnter) for the resumed state.
+#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct OnResume<S: States>(pub S);
+
+/// A pending operation on a [`StateStack`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateStackOperation<S: States> {
+    /// Pause the current state and enter the given one over it.
+    Push(S),
+    /// Exit the current state and resume the last paused one.
+    Pop,
+}
+
+/// The kind of the last transition applied by a [`StateStack`], used to run [`OnPause`] and
+/// [`OnResume`] instead of [`OnExit`](super::OnExit) and [`OnEnter`](super::OnEnter).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StateStackTransition {
+    Push,
+    Pop,
+}
+
+/// The states paused under the current [`State<S>`], for overlay states like pause menus that are
+/// pushed over the gameplay and popped back off it.
+///
+/// Pushing a state pauses the current one and enters the new one: [`OnPause`] runs for the paused
+/// state instead of [`OnExit`](super::OnExit), and its [`StateScoped`](crate::state_scoped::StateScoped)
+/// entities are kept. Popping it exits it and resumes the paused state: [`OnResume`] runs for the
+/// resumed state instead of [`OnEnter`](super::OnEnter).
+///
+/// Pushes and pops change [`State<S>`] and send a [`StateTransitionEvent`] like any other
+/// transition, so computed states and sub states follow the current state. The sub states of a
+/// paused state are paused along with it, and resumed with the value they had: see
+/// [`PausedSubStates`]. Pushing the current state has no effect.
+///
+/// Add it with [`AppExtStates::init_state_stack`](crate::app::AppExtStates::init_state_stack).
+/// Pending operations take precedence over the [`NextState<S>`], which is discarded. Setting the
+/// [`NextState<S>`] replaces the current state and keeps the paused states.
+///
+/// ```
+/// use bevy_state::prelude::*;
+/// use bevy_ecs::prelude::*;
+///
+/// #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, States)]
+/// enum GameState {
+///     #[default]
+///     InGame,
+///     PauseMenu,
+/// }
+///
+/// fn toggle_pause_menu(state: Res<State<GameState>>, mut stack: ResMut<StateStack<GameState>>) {
+///     match state.get() {
+///         GameState::InGame => stack.push(GameState::PauseMenu),
+///         GameState::PauseMenu => stack.pop(),
+///     }
+/// }
+/// ```
+#[derive(Resource, Debug)]
+pub struct StateStack<S: States> {
+    paused: Vec<S>,
+    pending: Option<StateStackOperation<S>>,
+    transition: Option<StateStackTransition>,
+}
+
+impl<S: States> Default for StateStack<S> {
+    fn default() -> Self {
+        Self {
+            paused: Vec::new(),
+            pending: None,
+            transition: None,
+        }
+    }
+}
+
+impl<S: States> StateStack<S> {
+    /// Returns the paused states, from the bottom of the stack to the top.
+    pub fn paused(&self) -> &[S] {
+        &self.paused
+    }
+
+    /// Returns the number of paused states.
+    pub fn len(&self) -> usize {
+        self.paused.len()
+    }
+
+    /// Returns `true` if no state is paused.
+    pub fn is_empty(&self) -> bool {
+        self.paused.is_empty()
+    }
+
+    /// Returns `true` if the given state is paused.
+    pub fn is_paused(&self, state: &S) -> bool {
+        self.paused.contains(state)
+    }
+
+    /// Returns the pending operation, if any.
+    pub fn pending(&self) -> Option<&StateStackOperation<S>> {
+        self.pending.as_ref()
+    }
+
+    /// Tentatively pause the current state and enter `state` over it.
+    ///
+    /// This has no effect if `state` is the current state when the operation is applied.
+    pub fn push(&mut self, state: S) {
+        self.pending = Some(StateStackOperation::Push(state));
+    }
+
+    /// Tentatively exit the current state and resume the last paused one.
+    ///
+    /// This has no effect if no state is paused when the operation is applied.
+    pub fn pop(&mut self) {
+        self.pending = Some(StateStackOperation::Pop);
+    }
+
+    /// Remove any pending operation.
+    pub fn reset(&mut self) {
+        self.pending = None;
+    }
+}
+
+impl<S: FreelyMutableState> StateStack<S> {
+    /// Registers the systems that apply the pending operations of the [`StateStack<S>`].
+    ///
+    /// The systems of the state itself must be registered with
+    /// [`FreelyMutableState::register_state`].
+    pub fn register_state_stack_systems(schedule: &mut Schedule) {
+        schedule.add_systems(
+            apply_state_stack_operation::<S>
+                .in_set(ApplyStateTransition::<S>::default())
+                .before(apply_state_transition::<S>),
+        );
+    }
+}
+
+/// The values of the sub state `S` that were paused along with their source state, because
+/// another state was pushed over it with [`StateStack::push`].
+///
+/// Pausing a sub state runs [`OnPause`] for it instead of [`OnExit`](super::OnExit), and
+/// resuming it runs [`OnResume`] instead of [`OnEnter`](super::OnEnter), with the value it had
+/// when it was paused.
+///
+/// Added by [`AppExtStates::add_sub_state`](crate::app::AppExtStates::add_sub_state).
+#[derive(Resource, Debug)]
+pub struct PausedSubStates<S: States> {
+    /// The value of the sub state, if it existed, for each state paused under the current one.
+    paused: Vec<Option<S>>,
+    transition: Option<StateStackTransition>,
+}
+
+impl<S: States> Default for PausedSubStates<S> {
+    fn default() -> Self {
+        Self {
+            paused: Vec::new(),
+            transition: None,
+        }
+    }
+}
+
+impl<S: States> PausedSubStates<S> {
+    /// Returns the paused values of the sub state, from the bottom of the stack to the top.
+    pub fn paused(&self) -> impl Iterator<Item = &S> {
+        self.paused.iter().flatten()
+    }
+
+    /// Returns `true` if the given value of the sub state is paused.
+    pub fn is_paused(&self, state: &S) -> bool {
+        self.paused
+            .iter()
+            .any(|paused| paused.as_ref() == Some(state))
+    }
+
+    /// Clears the transition applied during the last run of the
+    /// [`StateTransition`](super::StateTransition) schedule.
+    pub(crate) fn start_transition(&mut self) {
+        self.transition = None;
+    }
+
+    /// Pauses or resumes the sub state when its source state is paused or resumed, and returns
+    /// the value it should have.
+    pub(crate) fn pause_or_resume(
+        &mut self,
+        source_transition: Option<StateStackTransition>,
+        current_state: Option<&S>,
+        new_state: Option<S>,
+    ) -> Option<S> {
+        match source_transition {
+            Some(StateStackTransition::Push) => {
+                self.paused.push(current_state.cloned());
+                if current_state.is_some() && new_state.is_none() {
+                    self.transition = Some(StateStackTransition::Push);
+                }
+                new_state
+            }
+            Some(StateStackTransition::Pop) => match self.paused.pop().flatten() {
+                Some(resumed) if current_state.is_none() && new_state.is_some() => {
+                    self.transition = Some(StateStackTransition::Pop);
+                    Some(resumed)
+                }
+                _ => new_state,
+            },
+            None => new_state,
+        }
+    }
+}
+
+/// Returns the kind of the transition applied to `S` by its [`StateStack<S>`], or by the stack
+/// of its source states if it's a sub state, during the current run of the
+/// [`StateTransition`](super::StateTransition) schedule, if any.
+pub(crate) fn stack_transition<S: States>(
+    stack: Option<&StateStack<S>>,
+    paused_sub_states: Option<&PausedSubStates<S>>,
+) -> Option<StateStackTransition> {
+    stack
+        .and_then(|stack| stack.transition)
+        .or_else(|| paused_sub_states.and_then(|paused| paused.transition))
+}
+
+/// Returns the [`stack_transition`] of `S` in `world`.
+pub(crate) fn last_stack_transition<S: States>(world: &World) -> Option<StateStackTransition> {
+    stack_transition(
+        world.get_resource::<StateStack<S>>(),
+        world.get_resource::<PausedSubStates<S>>(),
+    )
+}
+
+fn apply_state_stack_operation<S: FreelyMutableState>(
+    event: EventWriter<StateTransitionEvent<S>>,
+    commands: Commands,
+    mut stack: ResMut<StateStack<S>>,
+    current_state: Option<ResMut<State<S>>>,
+    next_state: Option<ResMut<NextState<S>>>,
+) {
+    stack.bypass_change_detection().transition = None;
+    let Some(operation) = stack.bypass_change_detection().pending.take() else {
+        return;
+    };
+    let Some(current_state) = current_state else {
+        return;
+    };
+
+    let entered = match operation {
+        StateStackOperation::Push(state) if state == *current_state.get() => {
+            warn!(
+                "Tried to push state {:?}, but it's already the current state.",
+                state
+            );
+            return;
+        }
+        StateStackOperation::Push(state) => {
+            stack.paused.push(current_state.get().clone());
+            stack.transition = Some(StateStackTransition::Push);
+            state
+        }
+        StateStackOperation::Pop => {
+            let Some(state) = stack.paused.pop() else {
+                warn!(
+                    "Tried to pop state {:?}, but no state is paused under it.",
+                    current_state.get()
+                );
+                return;
+            };
+            stack.transition = Some(StateStackTransition::Pop);
+            state
+        }
+    };
+    if let Some(discarded) = take_next_state(next_state) {
+        debug!(
+            "discarding next state {:?} for the state stack operation",
+            discarded
+        );
+    }
+
+    internal_apply_state_transition(event, commands, Some(current_state), Some(entered));
+}
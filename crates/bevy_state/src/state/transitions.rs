@@ -7,7 +7,11 @@ use bevy_ecs::{
     world::World,
 };
 
-use super::{resources::State, states::States};
+use super::{
+    resources::State,
+    state_stack::{last_stack_transition, OnPause, OnResume, StateStackTransition},
+    states::States,
+};
 
 /// The label of a [`Schedule`] that **only** runs whenever [`State<S>`] enters the provided state.
 ///
@@ -219,6 +223,10 @@ pub(crate) fn run_enter<S: States>(
         return;
     };
 
+    if last_stack_transition::<S>(world) == Some(StateStackTransition::Pop) {
+        let _ = world.try_run_schedule(OnResume(entered));
+        return;
+    }
     let _ = world.try_run_schedule(OnEnter(entered));
 }
 
@@ -236,6 +244,10 @@ pub(crate) fn run_exit<S: States>(
         return;
     };
 
+    if last_stack_transition::<S>(world) == Some(StateStackTransition::Push) {
+        let _ = world.try_run_schedule(OnPause(exited));
+        return;
+    }
     let _ = world.try_run_schedule(OnExit(exited));
 }
 
@@ -4,14 +4,14 @@ use bevy_ecs::{
     component::Component,
     entity::Entity,
     event::EventReader,
-    system::{Commands, Query},
+    system::{Commands, Query, Res},
 };
 #[cfg(feature = "bevy_hierarchy")]
 use bevy_hierarchy::DespawnRecursiveExt;
 #[cfg(feature = "bevy_reflect")]
 use bevy_reflect::prelude::*;
 
-use crate::state::{StateTransitionEvent, States};
+use crate::state::{PausedSubStates, StateStack, StateTransitionEvent, States};
 
 /// Entities marked with this component will be removed
 /// when the world's state of the matching type no longer matches the supplied value.
@@ -77,6 +77,8 @@ pub fn clear_state_scoped_entities<S: States>(
     mut commands: Commands,
     mut transitions: EventReader<StateTransitionEvent<S>>,
     query: Query<(Entity, &StateScoped<S>)>,
+    state_stack: Option<Res<StateStack<S>>>,
+    paused_sub_states: Option<Res<PausedSubStates<S>>>,
 ) {
     // We use the latest event, because state machine internals generate at most 1
     // transition event (per type) each frame. No event means no change happened
@@ -90,6 +92,12 @@ pub fn clear_state_scoped_entities<S: States>(
     let Some(exited) = &transition.exited else {
         return;
     };
+    // The entities of a state paused by a `StateStack` are kept until it is exited.
+    if state_stack.is_some_and(|stack| stack.is_paused(exited))
+        || paused_sub_states.is_some_and(|paused| paused.is_paused(exited))
+    {
+        return;
+    }
     for (entity, binding) in &query {
         if binding.0 == *exited {
             #[cfg(feature = "bevy_hierarchy")]
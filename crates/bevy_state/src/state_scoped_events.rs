@@ -4,12 +4,12 @@ use core::marker::PhantomData;
 use bevy_app::{App, SubApp};
 use bevy_ecs::{
     event::{Event, EventReader, Events},
-    system::{Commands, Resource},
+    system::{Commands, Res, Resource},
     world::World,
 };
 use bevy_utils::HashMap;
 
-use crate::state::{FreelyMutableState, OnExit, StateTransitionEvent};
+use crate::state::{FreelyMutableState, OnExit, StateStack, StateTransitionEvent};
 
 fn clear_event_queue<E: Event>(w: &mut World) {
     if let Some(mut queue) = w.get_resource_mut::<Events<E>>() {
@@ -51,6 +51,7 @@ impl<S: FreelyMutableState> Default for StateScopedEvents<S> {
 fn cleanup_state_scoped_event<S: FreelyMutableState>(
     mut c: Commands,
     mut transitions: EventReader<StateTransitionEvent<S>>,
+    state_stack: Option<Res<StateStack<S>>>,
 ) {
     let Some(transition) = transitions.read().last() else {
         return;
@@ -61,6 +62,9 @@ fn cleanup_state_scoped_event<S: FreelyMutableState>(
     let Some(exited) = transition.exited.clone() else {
         return;
     };
+    if state_stack.is_some_and(|stack| stack.is_paused(&exited)) {
+        return;
+    }
 
     c.queue(move |w: &mut World| {
         w.resource_scope::<StateScopedEvents<S>, ()>(|w, events| {
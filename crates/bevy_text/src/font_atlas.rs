@@ -79,7 +79,8 @@ impl FontAtlas {
     ///
     /// The glyph is represented by `glyph`, and its image content is `glyph_texture`.
     /// This content is copied into the atlas texture, and the atlas layout is updated
-    /// to store the location of that glyph into the atlas.
+    /// to store the location of that glyph into the atlas. `is_color` is true for glyphs
+    /// that have their own colors, see [`GlyphAtlasLocation::is_color`].
     ///
     /// # Returns
     ///
@@ -93,6 +94,7 @@ impl FontAtlas {
         cache_key: cosmic_text::CacheKey,
         texture: &Image,
         offset: IVec2,
+        is_color: bool,
     ) -> Result<(), TextError> {
         let atlas_layout = atlas_layouts.get_mut(&self.texture_atlas).unwrap();
         let atlas_texture = textures.get_mut(&self.texture).unwrap();
@@ -106,6 +108,7 @@ impl FontAtlas {
                 GlyphAtlasLocation {
                     glyph_index,
                     offset,
+                    is_color,
                 },
             );
             Ok(())
@@ -121,7 +121,7 @@ impl FontAtlasSet {
                 )]
             });
 
-        let (glyph_texture, offset) = Self::get_outlined_glyph_texture(
+        let (glyph_texture, offset, is_color) = Self::get_outlined_glyph_texture(
             font_system,
             swash_cache,
             &physical_glyph,
@@ -134,6 +134,7 @@ impl FontAtlasSet {
                 physical_glyph.cache_key,
                 &glyph_texture,
                 offset,
+                is_color,
             )
         };
         if !font_atlases
@@ -161,6 +162,7 @@ impl FontAtlasSet {
                 physical_glyph.cache_key,
                 &glyph_texture,
                 offset,
+                is_color,
             )?;
         }
 
@@ -200,13 +202,17 @@ impl FontAtlasSet {
         self.font_atlases.len() == 0
     }
 
-    /// Get the texture of the glyph as a rendered image, and its offset
+    /// Get the texture of the glyph as a rendered image, its offset, and whether it has its own
+    /// colors.
+    ///
+    /// Glyphs of color fonts, like emoji from `COLR` outlines or embedded bitmaps, keep their
+    /// colors. Other glyphs are white masks that take the color of their text span.
     pub fn get_outlined_glyph_texture(
         font_system: &mut cosmic_text::FontSystem,
         swash_cache: &mut cosmic_text::SwashCache,
         physical_glyph: &cosmic_text::PhysicalGlyph,
         font_smoothing: FontSmoothing,
-    ) -> Result<(Image, IVec2), TextError> {
+    ) -> Result<(Image, IVec2, bool), TextError> {
         // NOTE: Ideally, we'd ask COSMIC Text to honor the font smoothing setting directly.
         // However, since it currently doesn't support that, we render the glyph with antialiasing
         // and apply a threshold to the alpha channel to simulate the effect.
@@ -226,6 +232,7 @@ impl FontAtlasSet {
             height,
         } = image.placement;
 
+        let is_color = matches!(image.content, cosmic_text::SwashContent::Color);
         let data = match image.content {
             cosmic_text::SwashContent::Mask => {
                 if font_smoothing == FontSmoothing::None {
@@ -263,6 +270,7 @@ impl FontAtlasSet {
                 RenderAssetUsages::MAIN_WORLD,
             ),
             IVec2::new(left, top),
+            is_color,
         ))
     }
 }
@@ -70,4 +70,7 @@ pub struct GlyphAtlasLocation {
     pub glyph_index: usize,
     /// The required offset (relative positioning) when placed
     pub offset: IVec2,
+    /// Whether the glyph has its own colors, like a color emoji, instead of being a mask
+    /// that takes the color of its text span.
+    pub is_color: bool,
 }
@@ -39,8 +39,11 @@ mod font_atlas_set;
 mod font_loader;
 mod glyph;
 mod pipeline;
+mod rich_text;
 mod text;
 mod text2d;
+#[cfg(feature = "bevy_text_picking_backend")]
+mod text2d_picking;
 mod text_access;
 
 pub use bounds::*;
@@ -51,8 +54,11 @@ pub use font_atlas_set::*;
 pub use font_loader::*;
 pub use glyph::*;
 pub use pipeline::*;
+pub use rich_text::*;
 pub use text::*;
 pub use text2d::*;
+#[cfg(feature = "bevy_text_picking_backend")]
+pub use text2d_picking::*;
 pub use text_access::*;
 
 /// The text prelude.
@@ -61,8 +67,8 @@ pub use text_access::*;
 pub mod prelude {
     #[doc(hidden)]
     pub use crate::{
-        Font, JustifyText, LineBreak, Text2d, Text2dReader, Text2dWriter, TextColor, TextError,
-        TextFont, TextLayout, TextSpan,
+        Font, InlineTextImage, JustifyText, LineBreak, Text2d, Text2dReader, Text2dWriter,
+        TextColor, TextError, TextFont, TextLayout, TextLink, TextSpan,
     };
 }
 
@@ -115,6 +121,8 @@ impl Plugin for TextPlugin {
             .register_type::<TextLayout>()
             .register_type::<ComputedTextBlock>()
             .register_type::<TextEntity>()
+            .register_type::<InlineTextImage>()
+            .register_type::<TextLink>()
             .init_asset_loader::<FontLoader>()
             .init_resource::<FontAtlasSets>()
             .init_resource::<TextPipeline>()
@@ -125,6 +133,7 @@ impl Plugin for TextPlugin {
                 PostUpdate,
                 (
                     remove_dropped_font_atlas_sets,
+                    update_inline_text_images,
                     detect_text_needs_rerender::<Text2d>,
                     update_text2d_layout
                         // Potential conflict: `Assets<Image>`
@@ -140,6 +149,9 @@ impl Plugin for TextPlugin {
             )
             .add_systems(Last, trim_cosmic_cache);
 
+        #[cfg(feature = "bevy_text_picking_backend")]
+        app.add_plugins(Text2dPickingPlugin);
+
         if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
             render_app.add_systems(
                 ExtractSchedule,
@@ -10,7 +10,7 @@ use bevy_ecs::{
     system::{ResMut, Resource},
 };
 use bevy_image::prelude::*;
-use bevy_math::{UVec2, Vec2};
+use bevy_math::{Rect, UVec2, Vec2};
 use bevy_reflect::{std_traits::ReflectDefault, Reflect};
 use bevy_utils::HashMap;
 
@@ -109,7 +109,8 @@ impl TextPipeline {
             // Save this span entity in the computed text block.
             computed.entities.push(TextEntity { entity, depth });
 
-            if span.is_empty() {
+            let inline_image_aspect_ratio = computed.inline_images.get(&entity).copied();
+            if span.is_empty() && inline_image_aspect_ratio.is_none() {
                 continue;
             }
             // Return early if a font is not loaded yet.
@@ -143,6 +144,15 @@ impl TextPipeline {
             if scale_factor <= 0.0 || text_font.font_size <= 0.0 {
                 continue;
             }
+
+            // Inline images take the place of their span's text.
+            let span = match inline_image_aspect_ratio {
+                Some(aspect_ratio) => {
+                    let (face_id, _) = self.map_handle_to_font_id[&text_font.font.id()];
+                    inline_image_placeholder(font_system, face_id, aspect_ratio)
+                }
+                None => span,
+            };
             spans.push((span_index, span, text_font, face_info, color));
         }
 
@@ -230,6 +240,8 @@ impl TextPipeline {
         swash_cache: &mut SwashCache,
     ) -> Result<(), TextError> {
         layout_info.glyphs.clear();
+        layout_info.section_rects.clear();
+        layout_info.inline_images.clear();
         layout_info.size = Default::default();
 
         // Clear this here at the focal point of text rendering to ensure the field's lifecycle has strong boundaries.
@@ -259,6 +271,42 @@ impl TextPipeline {
 
         let buffer = &mut computed.buffer;
         let box_size = buffer_dimensions(buffer);
+        let flip_y = |y: f32| match y_axis_orientation {
+            YAxisOrientation::TopToBottom => y,
+            YAxisOrientation::BottomToTop => box_size.y - y,
+        };
+
+        // Bound the glyphs of each span on each line.
+        for run in buffer.layout_runs() {
+            let mut current_section: Option<(usize, Rect)> = None;
+            for layout_glyph in run.glyphs.iter() {
+                let rect = Rect::new(
+                    layout_glyph.x,
+                    flip_y(run.line_top),
+                    layout_glyph.x + layout_glyph.w,
+                    flip_y(run.line_top + run.line_height),
+                );
+                match &mut current_section {
+                    Some((span_index, section_rect)) if *span_index == layout_glyph.metadata => {
+                        *section_rect = section_rect.union(rect);
+                    }
+                    _ => {
+                        if let Some(section) =
+                            current_section.replace((layout_glyph.metadata, rect))
+                        {
+                            layout_info.push_section(
+                                section,
+                                &computed.entities,
+                                &computed.inline_images,
+                            );
+                        }
+                    }
+                }
+            }
+            if let Some(section) = current_section {
+                layout_info.push_section(section, &computed.entities, &computed.inline_images);
+            }
+        }
 
         let result = buffer
             .layout_runs()
@@ -270,6 +318,13 @@ impl TextPipeline {
             .try_for_each(|(layout_glyph, line_y)| {
                 let mut temp_glyph;
                 let span_index = layout_glyph.metadata;
+                if computed
+                    .inline_images
+                    .contains_key(&computed.entities[span_index].entity)
+                {
+                    // The glyphs of inline images are only placeholders.
+                    return Ok(());
+                }
                 let font_id = glyph_info[span_index].0;
                 let font_smoothing = glyph_info[span_index].1;
 
@@ -317,10 +372,7 @@ impl TextPipeline {
                 // offset by half the size because the origin is center
                 let x = glyph_size.x as f32 / 2.0 + left + physical_glyph.x as f32;
                 let y = line_y.round() + physical_glyph.y as f32 - top + glyph_size.y as f32 / 2.0;
-                let y = match y_axis_orientation {
-                    YAxisOrientation::TopToBottom => y,
-                    YAxisOrientation::BottomToTop => box_size.y - y,
-                };
+                let y = flip_y(y);
 
                 let position = Vec2::new(x, y);
 
@@ -407,10 +459,37 @@ impl TextPipeline {
 pub struct TextLayoutInfo {
     /// Scaled and positioned glyphs in screenspace
     pub glyphs: Vec<PositionedGlyph>,
+    /// Rects bounding the glyphs of each text span on each line, with the span entity.
+    ///
+    /// They're in the same coordinates as the positions of [`TextLayoutInfo::glyphs`], and can be
+    /// used to find the span under a point of the text block, like a [`TextLink`](crate::TextLink).
+    pub section_rects: Vec<(Entity, Rect)>,
+    /// Where the images of [`InlineTextImage`](crate::InlineTextImage) spans are drawn, with the span
+    /// entity, in the same coordinates as [`TextLayoutInfo::section_rects`].
+    pub inline_images: Vec<(Entity, Rect)>,
     /// The glyphs resulting size
     pub size: Vec2,
 }
 
+impl TextLayoutInfo {
+    /// Adds the rect of a span on a line, and the rect of its image for inline images.
+    fn push_section(
+        &mut self,
+        (span_index, rect): (usize, Rect),
+        entities: &[TextEntity],
+        inline_images: &HashMap<Entity, f32>,
+    ) {
+        let entity = entities[span_index].entity;
+        self.section_rects.push((entity, rect));
+        if let Some(aspect_ratio) = inline_images.get(&entity) {
+            // The placeholder is about as wide as the image, center the image on it.
+            let size = Vec2::new(rect.width(), rect.width() / aspect_ratio);
+            self.inline_images
+                .push((entity, Rect::from_center_size(rect.center(), size)));
+        }
+    }
+}
+
 /// Size information for a corresponding [`ComputedTextBlock`] component.
 ///
 /// Generated via [`TextPipeline::create_text_measure`].
@@ -501,6 +580,37 @@ fn get_attrs<'a>(
     attrs
 }
 
+/// No-break spaces laid out in place of inline images, so that lines never wrap inside an image.
+const INLINE_IMAGE_PLACEHOLDER: &str = "\u{a0}\u{a0}\u{a0}\u{a0}\u{a0}\u{a0}\u{a0}\u{a0}\
+    \u{a0}\u{a0}\u{a0}\u{a0}\u{a0}\u{a0}\u{a0}\u{a0}\
+    \u{a0}\u{a0}\u{a0}\u{a0}\u{a0}\u{a0}\u{a0}\u{a0}\
+    \u{a0}\u{a0}\u{a0}\u{a0}\u{a0}\u{a0}\u{a0}\u{a0}";
+
+/// The character of [`INLINE_IMAGE_PLACEHOLDER`].
+const NO_BREAK_SPACE: char = '\u{a0}';
+
+/// Returns the no-break spaces that take the place of an inline image with the given aspect ratio, in the
+/// font of its span.
+fn inline_image_placeholder(
+    font_system: &mut cosmic_text::FontSystem,
+    face_id: cosmic_text::fontdb::ID,
+    aspect_ratio: f32,
+) -> &'static str {
+    // The width of a space, in ems.
+    let space_width = font_system
+        .get_font(face_id)
+        .map(|font| {
+            let font = font.as_swash();
+            let space = font.charmap().map(NO_BREAK_SPACE);
+            font.glyph_metrics(&[]).advance_width(space) / f32::from(font.metrics(&[]).units_per_em)
+        })
+        .filter(|width| *width > 0.0)
+        .unwrap_or(0.5);
+    let max_spaces = INLINE_IMAGE_PLACEHOLDER.len() / NO_BREAK_SPACE.len_utf8();
+    let spaces = ((aspect_ratio / space_width).round() as usize).clamp(1, max_spaces);
+    &INLINE_IMAGE_PLACEHOLDER[..spaces * NO_BREAK_SPACE.len_utf8()]
+}
+
 /// Calculate the size of the text area for the given buffer.
 fn buffer_dimensions(buffer: &Buffer) -> Vec2 {
     let (width, height) = buffer
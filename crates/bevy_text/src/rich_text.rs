@@ -0,0 +1,108 @@
+use bevy_asset::{AssetEvent, AssetId, Assets, Handle};
+use bevy_derive::{Deref, DerefMut};
+use bevy_ecs::prelude::*;
+use bevy_hierarchy::{HierarchyQueryExt, Parent};
+use bevy_image::Image;
+use bevy_reflect::prelude::*;
+use bevy_utils::HashSet;
+
+use crate::{ComputedTextBlock, TextSpan};
+
+/// An image laid out inline with the text of a block, such as an icon in a chat message.
+///
+/// Add this component to a [`TextSpan`] entity to draw the image in place of the span's text.
+/// The image is as high as the font size of the span's [`TextFont`](crate::TextFont), and as wide
+/// as its aspect ratio requires. It isn't tinted by the span's [`TextColor`](crate::TextColor).
+///
+/// ```
+/// # use bevy_asset::Handle;
+/// # use bevy_ecs::world::World;
+/// # use bevy_hierarchy::BuildChildren;
+/// # use bevy_image::Image;
+/// # use bevy_text::{InlineTextImage, Text2d, TextSpan};
+/// # let coin: Handle<Image> = Default::default();
+/// # let mut world = World::default();
+/// world
+///     .spawn(Text2d::new("You found 3 "))
+///     .with_child(InlineTextImage(coin))
+///     .with_child(TextSpan::new("!"));
+/// ```
+#[derive(Component, Debug, Clone, Default, Deref, DerefMut, Reflect)]
+#[reflect(Component, Default, Debug)]
+#[require(TextSpan)]
+pub struct InlineTextImage(pub Handle<Image>);
+
+/// Marks a text span as a link.
+///
+/// The regions of the text covered by link spans are reported to picking as hits on the span
+/// entity, so that observers of pointer events such as `Pointer<Click>` on the span can react to
+/// the link. The string identifies the link, for instance with a URL or the id of a dialogue
+/// choice.
+///
+/// Links are picked in UI text with the UI picking backend, and in [`Text2d`](crate::Text2d) with
+/// the sprite picking backend, which also enables the `bevy_text_picking_backend` feature.
+#[derive(Component, Debug, Clone, Default, Deref, DerefMut, Reflect)]
+#[reflect(Component, Default, Debug)]
+#[require(TextSpan)]
+pub struct TextLink(pub String);
+
+impl TextLink {
+    /// Makes a new text link component.
+    pub fn new(link: impl Into<String>) -> Self {
+        Self(link.into())
+    }
+}
+
+/// Updates the aspect ratio of [`InlineTextImage`]s in their [`ComputedTextBlock`], and makes the
+/// block rerender when an image is added, changed, loaded or removed.
+pub fn update_inline_text_images(
+    images: Res<Assets<Image>>,
+    mut image_events: EventReader<AssetEvent<Image>>,
+    inline_images: Query<(Entity, Ref<InlineTextImage>)>,
+    mut removed_inline_images: RemovedComponents<InlineTextImage>,
+    parents: Query<&Parent>,
+    mut computed: Query<&mut ComputedTextBlock>,
+) {
+    let modified_images: HashSet<AssetId<Image>> = image_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Added { id } | AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    for (entity, inline_image) in &inline_images {
+        if !inline_image.is_changed() && !modified_images.contains(&inline_image.id()) {
+            continue;
+        }
+        let aspect_ratio = images
+            .get(inline_image.id())
+            .map(Image::size_f32)
+            .filter(|size| size.y > 0.0)
+            .map_or(1.0, |size| size.x / size.y);
+        if let Some(mut computed) = find_text_block(entity, &parents, &mut computed) {
+            computed.inline_images.insert(entity, aspect_ratio);
+            computed.needs_rerender = true;
+        }
+    }
+
+    for entity in removed_inline_images.read() {
+        if let Some(mut computed) = find_text_block(entity, &parents, &mut computed) {
+            if computed.inline_images.remove(&entity).is_some() {
+                computed.needs_rerender = true;
+            }
+        }
+    }
+}
+
+/// Finds the [`ComputedTextBlock`] of the nearest ancestor of a span.
+fn find_text_block<'a>(
+    span: Entity,
+    parents: &Query<&Parent>,
+    computed: &'a mut Query<&mut ComputedTextBlock>,
+) -> Option<Mut<'a, ComputedTextBlock>> {
+    let root = parents
+        .iter_ancestors(span)
+        .find(|ancestor| computed.contains(*ancestor))?;
+    computed.get_mut(root).ok()
+}
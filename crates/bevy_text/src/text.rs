@@ -10,7 +10,7 @@ use bevy_derive::{Deref, DerefMut};
 use bevy_ecs::{prelude::*, reflect::ReflectComponent};
 use bevy_hierarchy::{Children, Parent};
 use bevy_reflect::prelude::*;
-use bevy_utils::once;
+use bevy_utils::{once, HashMap};
 use cosmic_text::{Buffer, Metrics};
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
@@ -69,6 +69,11 @@ pub struct ComputedTextBlock {
     // solution would probably require splitting TextLayout and TextFont into structural/non-structural
     // components for more granular change detection. A cost/benefit analysis is needed.
     pub(crate) needs_rerender: bool,
+    /// Aspect ratios of the [`InlineTextImage`](crate::InlineTextImage) spans in the block.
+    ///
+    /// Updated by [`update_inline_text_images`](crate::update_inline_text_images).
+    #[reflect(ignore)]
+    pub(crate) inline_images: HashMap<Entity, f32>,
 }
 
 impl ComputedTextBlock {
@@ -95,6 +100,7 @@ impl Default for ComputedTextBlock {
             buffer: CosmicBuffer::default(),
             entities: SmallVec::default(),
             needs_rerender: true,
+            inline_images: HashMap::default(),
         }
     }
 }
@@ -1,11 +1,11 @@
 use crate::pipeline::CosmicFontSystem;
 use crate::{
-    ComputedTextBlock, Font, FontAtlasSets, LineBreak, PositionedGlyph, SwashCache, TextBounds,
-    TextColor, TextError, TextFont, TextLayout, TextLayoutInfo, TextPipeline, TextReader, TextRoot,
-    TextSpanAccess, TextWriter, YAxisOrientation,
+    ComputedTextBlock, Font, FontAtlasSets, InlineTextImage, LineBreak, PositionedGlyph,
+    SwashCache, TextBounds, TextColor, TextError, TextFont, TextLayout, TextLayoutInfo,
+    TextPipeline, TextReader, TextRoot, TextSpanAccess, TextWriter, YAxisOrientation,
 };
 use bevy_asset::Assets;
-use bevy_color::LinearRgba;
+use bevy_color::{Alpha, LinearRgba};
 use bevy_derive::{Deref, DerefMut};
 use bevy_ecs::entity::EntityHashSet;
 use bevy_ecs::{
@@ -143,6 +143,7 @@ pub fn extract_text2d_sprite(
         )>,
     >,
     text_styles: Extract<Query<(&TextFont, &TextColor)>>,
+    inline_images: Extract<Query<&InlineTextImage>>,
 ) {
     // TODO: Support window-independent scaling: https://github.com/bevyengine/bevy/issues/5621
     let scale_factor = windows
@@ -200,10 +201,41 @@ pub fn extract_text2d_sprite(
                 ),
                 ExtractedSprite {
                     transform: transform * GlobalTransform::from_translation(position.extend(0.)),
-                    color,
+                    // Color glyphs, like emoji, keep their own colors.
+                    color: if atlas_info.location.is_color {
+                        LinearRgba::WHITE.with_alpha(color.alpha)
+                    } else {
+                        color
+                    },
                     rect: Some(atlas.textures[atlas_info.location.glyph_index].as_rect()),
                     custom_size: None,
                     image_handle_id: atlas_info.texture.id(),
+                    normal_map_id: None,
+                    flip_x: false,
+                    flip_y: false,
+                    anchor: Anchor::Center.as_vec(),
+                    original_entity: Some(original_entity),
+                },
+            );
+        }
+
+        for (span_entity, rect) in &text_layout_info.inline_images {
+            let Ok(inline_image) = inline_images.get(*span_entity) else {
+                continue;
+            };
+            extracted_sprites.sprites.insert(
+                (
+                    commands.spawn(TemporaryRenderEntity).id(),
+                    original_entity.into(),
+                ),
+                ExtractedSprite {
+                    transform: transform
+                        * GlobalTransform::from_translation(rect.center().extend(0.)),
+                    color: LinearRgba::WHITE,
+                    rect: None,
+                    custom_size: Some(rect.size()),
+                    image_handle_id: inline_image.id(),
+                    normal_map_id: None,
                     flip_x: false,
                     flip_y: false,
                     anchor: Anchor::Center.as_vec(),
@@ -347,10 +379,15 @@ pub fn calculate_bounds_text2d(
 mod tests {
 
     use bevy_app::{App, Update};
-    use bevy_asset::{load_internal_binary_asset, Handle};
+    use bevy_asset::{load_internal_binary_asset, AssetEvent, Handle};
     use bevy_ecs::schedule::IntoSystemConfigs;
+    use bevy_hierarchy::BuildChildren;
+    use bevy_render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    };
 
-    use crate::{detect_text_needs_rerender, TextIterScratch};
+    use crate::{detect_text_needs_rerender, update_inline_text_images, TextIterScratch};
 
     use super::*;
 
@@ -367,9 +404,11 @@ mod tests {
             .init_resource::<CosmicFontSystem>()
             .init_resource::<SwashCache>()
             .init_resource::<TextIterScratch>()
+            .add_event::<AssetEvent<Image>>()
             .add_systems(
                 Update,
                 (
+                    update_inline_text_images,
                     detect_text_needs_rerender::<Text2d>,
                     update_text2d_layout,
                     calculate_bounds_text2d,
@@ -455,4 +494,45 @@ mod tests {
         assert!(FIRST_TEXT.len() < SECOND_TEXT.len());
         assert!(first_aabb.half_extents.x < second_aabb.half_extents.x);
     }
+
+    #[test]
+    fn inline_text_image_replaces_span_glyphs() {
+        let (mut app, entity) = setup();
+
+        let image = app
+            .world_mut()
+            .resource_mut::<Assets<Image>>()
+            .add(Image::new_fill(
+                Extent3d {
+                    width: 40,
+                    height: 20,
+                    depth_or_array_layers: 1,
+                },
+                TextureDimension::D2,
+                &[255; 4],
+                TextureFormat::Rgba8UnormSrgb,
+                RenderAssetUsages::default(),
+            ));
+        let image_span = app.world_mut().spawn(InlineTextImage(image)).id();
+        app.world_mut().entity_mut(entity).add_child(image_span);
+
+        app.update();
+
+        let layout = app
+            .world()
+            .get::<TextLayoutInfo>(entity)
+            .expect("Could not find TextLayoutInfo");
+        let (span, rect) = layout.inline_images[0];
+        assert_eq!(span, image_span);
+        approx::assert_relative_eq!(rect.width() / rect.height(), 2.0);
+        // The image takes space after the text, but only the text has glyphs.
+        let text_end = layout
+            .section_rects
+            .iter()
+            .filter(|(span, _)| *span == entity)
+            .map(|(_, section)| section.max.x)
+            .fold(0.0, f32::max);
+        assert!(text_end > 0.0 && rect.min.x + 0.001 >= text_end);
+        assert!(layout.glyphs.iter().all(|glyph| glyph.span_index == 0));
+    }
 }
@@ -0,0 +1,170 @@
+//! A [`bevy_picking`] backend for the [`TextLink`]s of [`Text2d`] blocks.
+//!
+//! A pointer over the glyphs of a link span hits the span entity, at the depth of its text block,
+//! so that pointer events target the span and then bubble up to the [`Text2d`] entity. Only
+//! cameras with an orthographic projection pick links, like the sprite backend.
+
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_math::{Vec2, Vec3Swizzles};
+use bevy_picking::backend::prelude::*;
+use bevy_render::prelude::*;
+use bevy_sprite::Anchor;
+use bevy_transform::prelude::*;
+use bevy_window::{PrimaryWindow, Window};
+
+use crate::{Text2d, TextLayoutInfo, TextLink};
+
+/// Adds the picking backend for the [`TextLink`]s of [`Text2d`] blocks.
+#[derive(Clone)]
+pub struct Text2dPickingPlugin;
+
+impl Plugin for Text2dPickingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreUpdate, text2d_link_picking.in_set(PickSet::Backend));
+    }
+}
+
+fn text2d_link_picking(
+    pointers: Query<(&PointerId, &PointerLocation)>,
+    cameras: Query<(Entity, &Camera, &GlobalTransform, &Projection)>,
+    primary_window: Query<(Entity, &Window), With<PrimaryWindow>>,
+    texts: Query<(&TextLayoutInfo, &Anchor, &GlobalTransform, &ViewVisibility), With<Text2d>>,
+    text_links: Query<(), With<TextLink>>,
+    mut output: EventWriter<PointerHits>,
+) {
+    // The layout of 2D text is in physical pixels of the primary window, see
+    // `update_text2d_layout`.
+    let (primary_window, scale_factor) = primary_window
+        .get_single()
+        .map_or((None, 1.0), |(entity, window)| {
+            (Some(entity), window.resolution.scale_factor())
+        });
+
+    for (pointer, location) in pointers.iter().filter_map(|(pointer, pointer_location)| {
+        pointer_location.location().map(|loc| (pointer, loc))
+    }) {
+        let Some((camera_entity, camera, camera_transform, Projection::Orthographic(projection))) =
+            cameras
+                .iter()
+                .filter(|(_, camera, _, _)| camera.is_active)
+                .find(|(_, camera, _, _)| {
+                    camera
+                        .target
+                        .normalize(primary_window)
+                        .is_some_and(|target| target == location.target)
+                })
+        else {
+            continue;
+        };
+
+        let viewport_pos = camera
+            .logical_viewport_rect()
+            .map(|viewport| viewport.min)
+            .unwrap_or_default();
+        let Ok(ray) = camera.viewport_to_world(camera_transform, location.position - viewport_pos)
+        else {
+            continue;
+        };
+
+        let mut picks = Vec::new();
+        for (layout_info, anchor, text_transform, view_visibility) in &texts {
+            if !view_visibility.get() || layout_info.section_rects.is_empty() {
+                continue;
+            }
+
+            // The text block, with its origin at its bottom left corner, as in
+            // `extract_text2d_sprite`.
+            let alignment_translation = layout_info.size * -(anchor.as_vec() + 0.5);
+            let text_from_world = (*text_transform
+                * GlobalTransform::from_translation(alignment_translation.extend(0.0)))
+            .affine()
+            .inverse();
+            let origin = text_from_world.transform_point3(ray.origin);
+            let direction = text_from_world.transform_vector3(*ray.direction);
+            if direction.z == 0.0 {
+                // The ray is parallel to the text.
+                continue;
+            }
+            let distance = -origin.z / direction.z;
+            if distance < 0.0 {
+                continue;
+            }
+            let position = (origin + direction * distance).xy() * scale_factor;
+            let Some(link) = link_at(layout_info, position, |span| text_links.contains(span))
+            else {
+                continue;
+            };
+
+            let hit_position = ray.origin + *ray.direction * distance;
+            // HitData requires a depth as calculated from the camera's near clipping plane
+            let depth = -projection.near
+                - camera_transform
+                    .affine()
+                    .inverse()
+                    .transform_point3(hit_position)
+                    .z;
+            picks.push((
+                link,
+                HitData::new(
+                    camera_entity,
+                    depth,
+                    Some(hit_position),
+                    Some(*text_transform.back()),
+                ),
+            ));
+        }
+
+        output.send(PointerHits::new(*pointer, picks, camera.order as f32));
+    }
+}
+
+/// Returns the link span whose glyphs are at the given position of a text block, in the
+/// coordinates of [`TextLayoutInfo::section_rects`].
+fn link_at(
+    layout_info: &TextLayoutInfo,
+    position: Vec2,
+    is_link: impl Fn(Entity) -> bool,
+) -> Option<Entity> {
+    layout_info
+        .section_rects
+        .iter()
+        .find(|(span, rect)| rect.contains(position) && is_link(*span))
+        .map(|(span, _)| *span)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::entity::Entity;
+    use bevy_math::{Rect, Vec2};
+
+    use super::link_at;
+    use crate::TextLayoutInfo;
+
+    #[test]
+    fn links_are_found_under_their_glyphs() {
+        let text = Entity::from_raw(1);
+        let link = Entity::from_raw(2);
+        let layout_info = TextLayoutInfo {
+            section_rects: vec![
+                (text, Rect::new(0.0, 0.0, 50.0, 20.0)),
+                (link, Rect::new(50.0, 0.0, 80.0, 20.0)),
+                // The link wraps to the next line.
+                (link, Rect::new(0.0, 20.0, 30.0, 40.0)),
+            ],
+            ..Default::default()
+        };
+        let is_link = |span| span == link;
+
+        assert_eq!(
+            link_at(&layout_info, Vec2::new(60.0, 10.0), is_link),
+            Some(link)
+        );
+        assert_eq!(
+            link_at(&layout_info, Vec2::new(10.0, 30.0), is_link),
+            Some(link)
+        );
+        assert_eq!(link_at(&layout_info, Vec2::new(10.0, 10.0), is_link), None);
+        assert_eq!(link_at(&layout_info, Vec2::new(60.0, 30.0), is_link), None);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{Real, Time, Timer, TimerMode, Virtual};
+use crate::{Real, Time, TimeContext, Timer, TimerMode, Virtual};
 use bevy_ecs::system::Res;
 use core::time::Duration;
 
@@ -235,6 +235,13 @@ pub fn paused(time: Res<Time<Virtual>>) -> bool {
     time.is_paused()
 }
 
+/// Run condition that is active when the [`Time<C>`] clock of the [`TimeContext`] `C` is paused.
+/// Use [`bevy_ecs::schedule::common_conditions::not`] to make it active when
+/// it's not paused.
+pub fn context_paused<C: TimeContext>(time: Res<Time<C>>) -> bool {
+    time.is_paused()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,368 @@
+use bevy_app::{App, First};
+use bevy_ecs::{
+    schedule::{IntoSystemConfigs, ScheduleLabel},
+    system::{Res, ResMut},
+    world::World,
+};
+#[cfg(feature = "bevy_reflect")]
+use bevy_reflect::Reflect;
+
+use crate::{real::Real, time::Time, time_system, virt::Virtual, TimeSystem};
+
+/// The clock a [`TimeContext`] advances from.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "bevy_reflect", derive(Reflect))]
+pub enum TimeContextSource {
+    /// The context advances with [`Time<Real>`](Real), and keeps running when the game is paused.
+    ///
+    /// Its delta is limited to the [`max_delta()`](Time::max_delta) of [`Time<Virtual>`](Virtual).
+    #[default]
+    Real,
+    /// The context advances with [`Time<Virtual>`](Virtual), and is paused and scaled along with
+    /// the game.
+    Virtual,
+}
+
+/// The speed and pause controls of a [`TimeContext`].
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "bevy_reflect", derive(Reflect))]
+pub struct TimeContextClock {
+    source: TimeContextSource,
+    paused: bool,
+    relative_speed: f64,
+    effective_speed: f64,
+}
+
+impl TimeContextClock {
+    /// Creates the controls of a clock advancing from the given `source`.
+    pub fn new(source: TimeContextSource) -> Self {
+        Self {
+            source,
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for TimeContextClock {
+    fn default() -> Self {
+        Self {
+            source: TimeContextSource::default(),
+            paused: false,
+            relative_speed: 1.0,
+            effective_speed: 1.0,
+        }
+    }
+}
+
+/// The context of a named clock, like a `Time<UiTime>` for UI animations or a `Time<WorldTime>`
+/// for a part of the game, which can be paused and sped up independently of
+/// [`Time<Virtual>`](Virtual).
+///
+/// Add the clock with [`TimeContextAppExt::add_time_context`]. It's updated in the
+/// [`TimeSystem`] set from its [`TimeContextSource`], and systems opt into it by using
+/// `Res<Time<C>>`, or by running a schedule with [`run_schedule_in_time_context`].
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_time::{prelude::*, TimeContext, TimeContextClock};
+/// #
+/// #[derive(Default)]
+/// struct UiTime(TimeContextClock);
+///
+/// impl TimeContext for UiTime {
+///     fn clock(&self) -> &TimeContextClock {
+///         &self.0
+///     }
+///
+///     fn clock_mut(&mut self) -> &mut TimeContextClock {
+///         &mut self.0
+///     }
+/// }
+///
+/// fn animate_ui(time: Res<Time<UiTime>>) {
+///     println!("this keeps running when the game is paused: delta {:?}", time.delta());
+/// }
+/// ```
+pub trait TimeContext: Default + Send + Sync + 'static {
+    /// Returns the speed and pause controls of the clock.
+    fn clock(&self) -> &TimeContextClock;
+
+    /// Returns a mutable reference to the speed and pause controls of the clock.
+    fn clock_mut(&mut self) -> &mut TimeContextClock;
+}
+
+impl<C: TimeContext> Time<C> {
+    /// Returns the clock this context advances from.
+    #[inline]
+    pub fn source(&self) -> TimeContextSource {
+        self.context().clock().source
+    }
+
+    /// Sets the clock this context advances from.
+    #[inline]
+    pub fn set_source(&mut self, source: TimeContextSource) {
+        self.context_mut().clock_mut().source = source;
+    }
+
+    /// Returns the speed the clock advances relative to its source, as [`f32`].
+    #[inline]
+    pub fn relative_speed(&self) -> f32 {
+        self.relative_speed_f64() as f32
+    }
+
+    /// Returns the speed the clock advances relative to its source, as [`f64`].
+    #[inline]
+    pub fn relative_speed_f64(&self) -> f64 {
+        self.context().clock().relative_speed
+    }
+
+    /// Returns the speed the clock advanced relative to its source in this update, as [`f32`].
+    ///
+    /// Returns `0.0` if the clock was paused or what the `relative_speed` value was at the start
+    /// of this update.
+    #[inline]
+    pub fn effective_speed(&self) -> f32 {
+        self.context().clock().effective_speed as f32
+    }
+
+    /// Returns the speed the clock advanced relative to its source in this update, as [`f64`].
+    ///
+    /// Returns `0.0` if the clock was paused or what the `relative_speed` value was at the start
+    /// of this update.
+    #[inline]
+    pub fn effective_speed_f64(&self) -> f64 {
+        self.context().clock().effective_speed
+    }
+
+    /// Sets the speed the clock advances relative to its source, given as an [`f32`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ratio` is negative or not finite.
+    #[inline]
+    pub fn set_relative_speed(&mut self, ratio: f32) {
+        self.set_relative_speed_f64(ratio as f64);
+    }
+
+    /// Sets the speed the clock advances relative to its source, given as an [`f64`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ratio` is negative or not finite.
+    #[inline]
+    pub fn set_relative_speed_f64(&mut self, ratio: f64) {
+        assert!(ratio.is_finite(), "tried to go infinitely fast");
+        assert!(ratio >= 0.0, "tried to go back in time");
+        self.context_mut().clock_mut().relative_speed = ratio;
+    }
+
+    /// Stops the clock, preventing it from advancing until resumed.
+    #[inline]
+    pub fn pause(&mut self) {
+        self.context_mut().clock_mut().paused = true;
+    }
+
+    /// Resumes the clock if paused.
+    #[inline]
+    pub fn unpause(&mut self) {
+        self.context_mut().clock_mut().paused = false;
+    }
+
+    /// Returns `true` if the clock is currently paused.
+    #[inline]
+    pub fn is_paused(&self) -> bool {
+        self.context().clock().paused
+    }
+
+    /// Returns `true` if the clock was paused at the start of this update.
+    #[inline]
+    pub fn was_paused(&self) -> bool {
+        self.context().clock().effective_speed == 0.0
+    }
+
+    /// Updates the elapsed duration of `self` by the delta of its source.
+    fn advance_from_source(&mut self, real: &Time<Real>, virt: &Time<Virtual>) {
+        let source_delta = match self.source() {
+            TimeContextSource::Real => real.delta().min(virt.max_delta()),
+            TimeContextSource::Virtual => virt.delta(),
+        };
+        let effective_speed = if self.is_paused() {
+            0.0
+        } else {
+            self.relative_speed_f64()
+        };
+        let delta = if effective_speed != 1.0 {
+            source_delta.mul_f64(effective_speed)
+        } else {
+            // avoid rounding when at normal speed
+            source_delta
+        };
+        self.context_mut().clock_mut().effective_speed = effective_speed;
+        self.advance_by(delta);
+    }
+}
+
+/// Advances [`Time<C>`] based on the elapsed time of its [`TimeContextSource`].
+pub fn update_time_context<C: TimeContext>(
+    mut time: ResMut<Time<C>>,
+    real: Res<Time<Real>>,
+    virt: Res<Time<Virtual>>,
+) {
+    time.advance_from_source(&real, &virt);
+}
+
+/// Runs the `schedule` with the generic [`Time`] resource set to the time context `C`, so that its
+/// systems using `Res<Time>` follow the context, then sets it back.
+///
+/// ```
+/// # use bevy_app::{App, Update};
+/// # use bevy_ecs::{prelude::*, schedule::ScheduleLabel};
+/// # use bevy_time::{prelude::*, run_schedule_in_time_context, TimeContext, TimeContextClock};
+/// #
+/// # #[derive(Default)]
+/// # struct UiTime(TimeContextClock);
+/// #
+/// # impl TimeContext for UiTime {
+/// #     fn clock(&self) -> &TimeContextClock {
+/// #         &self.0
+/// #     }
+/// #
+/// #     fn clock_mut(&mut self) -> &mut TimeContextClock {
+/// #         &mut self.0
+/// #     }
+/// # }
+/// #
+/// #[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+/// struct UiAnimations;
+///
+/// # let mut app = App::new();
+/// app.add_systems(Update, |world: &mut World| {
+///     run_schedule_in_time_context::<UiTime>(world, UiAnimations);
+/// });
+/// ```
+pub fn run_schedule_in_time_context<C: TimeContext>(
+    world: &mut World,
+    schedule: impl ScheduleLabel,
+) {
+    let previous = *world.resource::<Time>();
+    let context = world.resource::<Time<C>>().as_generic();
+    *world.resource_mut::<Time>() = context;
+    let _ = world.try_run_schedule(schedule);
+    *world.resource_mut::<Time>() = previous;
+}
+
+/// Extension trait for [`App`] adding [`TimeContext`] clocks.
+pub trait TimeContextAppExt {
+    /// Adds the [`Time<C>`] clock of a [`TimeContext`], and updates it every frame in the
+    /// [`TimeSystem`] set.
+    ///
+    /// This method is idempotent: it has no effect when called again using the same generic type.
+    fn add_time_context<C: TimeContext>(&mut self) -> &mut Self;
+}
+
+impl TimeContextAppExt for App {
+    fn add_time_context<C: TimeContext>(&mut self) -> &mut Self {
+        if self.world().contains_resource::<Time<C>>() {
+            return self;
+        }
+        self.init_resource::<Time<C>>().add_systems(
+            First,
+            update_time_context::<C>
+                .in_set(TimeSystem)
+                .after(time_system),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Time, TimeContext, TimeContextAppExt, TimeContextClock, TimeContextSource, TimePlugin,
+        TimeUpdateStrategy, Virtual,
+    };
+    use bevy_app::App;
+    use core::time::Duration;
+
+    #[derive(Default)]
+    struct UiTime(TimeContextClock);
+
+    impl TimeContext for UiTime {
+        fn clock(&self) -> &TimeContextClock {
+            &self.0
+        }
+
+        fn clock_mut(&mut self) -> &mut TimeContextClock {
+            &mut self.0
+        }
+    }
+
+    struct WorldTime(TimeContextClock);
+
+    impl Default for WorldTime {
+        fn default() -> Self {
+            Self(TimeContextClock::new(TimeContextSource::Virtual))
+        }
+    }
+
+    impl TimeContext for WorldTime {
+        fn clock(&self) -> &TimeContextClock {
+            &self.0
+        }
+
+        fn clock_mut(&mut self) -> &mut TimeContextClock {
+            &mut self.0
+        }
+    }
+
+    #[test]
+    fn time_contexts_advance_independently() {
+        let mut app = App::new();
+        app.add_plugins(TimePlugin)
+            .add_time_context::<UiTime>()
+            .add_time_context::<WorldTime>()
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                100,
+            )));
+
+        // The first update doesn't advance the real clock.
+        app.update();
+        app.world_mut()
+            .resource_mut::<Time<WorldTime>>()
+            .set_relative_speed(0.5);
+        app.update();
+        assert_eq!(
+            app.world().resource::<Time<UiTime>>().delta(),
+            Duration::from_millis(100)
+        );
+        assert_eq!(
+            app.world().resource::<Time<WorldTime>>().delta(),
+            Duration::from_millis(50)
+        );
+
+        app.world_mut().resource_mut::<Time<Virtual>>().pause();
+        app.world_mut()
+            .resource_mut::<Time<UiTime>>()
+            .set_relative_speed(2.0);
+        app.update();
+        assert_eq!(
+            app.world().resource::<Time<UiTime>>().delta(),
+            Duration::from_millis(200)
+        );
+        assert_eq!(
+            app.world().resource::<Time<UiTime>>().elapsed(),
+            Duration::from_millis(300)
+        );
+        assert_eq!(
+            app.world().resource::<Time<WorldTime>>().delta(),
+            Duration::ZERO
+        );
+
+        app.world_mut().resource_mut::<Time<UiTime>>().pause();
+        app.update();
+        assert!(app.world().resource::<Time<UiTime>>().was_paused());
+        assert_eq!(
+            app.world().resource::<Time<UiTime>>().delta(),
+            Duration::ZERO
+        );
+    }
+}
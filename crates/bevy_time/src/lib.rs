@@ -8,6 +8,7 @@
 
 /// Common run conditions
 pub mod common_conditions;
+mod context;
 mod fixed;
 mod real;
 mod stopwatch;
@@ -15,6 +16,7 @@ mod time;
 mod timer;
 mod virt;
 
+pub use context::*;
 pub use fixed::*;
 pub use real::*;
 pub use stopwatch::*;
@@ -1,11 +1,12 @@
 //! System parameter for computing up-to-date [`GlobalTransform`]s.
 
+use alloc::vec;
 use bevy_ecs::{
     prelude::Entity,
     query::QueryEntityError,
     system::{Query, SystemParam},
 };
-use bevy_hierarchy::{HierarchyQueryExt, Parent};
+use bevy_hierarchy::Parent;
 use thiserror::Error;
 
 use crate::components::{GlobalTransform, Transform};
@@ -24,6 +25,9 @@ pub struct TransformHelper<'w, 's> {
 
 impl<'w, 's> TransformHelper<'w, 's> {
     /// Computes the [`GlobalTransform`] of the given entity from the [`Transform`] component on it and its ancestors.
+    ///
+    /// The transforms are combined from the root of the hierarchy down, in the same order as the
+    /// transform propagation systems, so the result is identical to the propagated [`GlobalTransform`].
     pub fn compute_global_transform(
         &self,
         entity: Entity,
@@ -33,18 +37,24 @@ impl<'w, 's> TransformHelper<'w, 's> {
             .get(entity)
             .map_err(|err| map_error(err, false))?;
 
-        let mut global_transform = GlobalTransform::from(*transform);
-
-        for entity in self.parent_query.iter_ancestors(entity) {
+        // Collect the transforms up to the root first, instead of recursing, so that deep
+        // hierarchies don't overflow the stack.
+        let mut transforms = vec![*transform];
+        let mut current = entity;
+        while let Ok(parent) = self.parent_query.get(current) {
+            current = parent.get();
             let transform = self
                 .transform_query
-                .get(entity)
+                .get(current)
                 .map_err(|err| map_error(err, true))?;
-
-            global_transform = *transform * global_transform;
+            transforms.push(*transform);
         }
 
-        Ok(global_transform)
+        let mut transforms = transforms.into_iter().rev();
+        let root = GlobalTransform::from(transforms.next().unwrap());
+        Ok(transforms.fold(root, |global_transform, transform| {
+            global_transform.mul_transform(transform)
+        }))
     }
 }
 
@@ -115,6 +125,25 @@ mod tests {
         ]);
     }
 
+    #[test]
+    fn deep_hierarchy() {
+        let mut app = App::new();
+        let mut entity = app.world_mut().spawn(Transform::from_xyz(1., 0., 0.)).id();
+        for _ in 1..10_000 {
+            entity = app
+                .world_mut()
+                .spawn(Transform::from_xyz(1., 0., 0.))
+                .set_parent(entity)
+                .id();
+        }
+
+        let mut state = SystemState::<TransformHelper>::new(app.world_mut());
+        let helper = state.get(app.world());
+
+        let computed_transform = helper.compute_global_transform(entity).unwrap();
+        assert_eq!(computed_transform.translation(), Vec3::new(10_000., 0., 0.));
+    }
+
     fn match_transform_propagation_systems_inner(transforms: Vec<Transform>) {
         let mut app = App::new();
         app.add_plugins(TransformPlugin);
@@ -143,5 +172,6 @@ mod tests {
         let computed_transform = helper.compute_global_transform(leaf_entity).unwrap();
 
         approx::assert_abs_diff_eq!(transform.affine(), computed_transform.affine());
+        assert_eq!(transform, computed_transform);
     }
 }
@@ -0,0 +1,192 @@
+use bevy_app::{App, FixedLast, Plugin, PostUpdate, RunFixedMainLoop, RunFixedMainLoopSystem};
+use bevy_ecs::{
+    component::Component,
+    prelude::require,
+    schedule::IntoSystemConfigs,
+    system::{Query, Res},
+};
+use bevy_time::{Fixed, Time};
+
+#[cfg(feature = "bevy_reflect")]
+use {bevy_ecs::reflect::ReflectComponent, bevy_reflect::prelude::*};
+
+use crate::{components::Transform, plugins::TransformSystem};
+
+/// Smooths the rendered [`Transform`] of an entity that is moved in the
+/// [`FixedMain`](bevy_app::FixedMain) schedules, by interpolating between its last two fixed
+/// steps.
+///
+/// After each fixed step, the [`Transform`] of the entity is recorded. During
+/// [`PostUpdate`], before [`TransformPropagate`](TransformSystem::TransformPropagate), the
+/// [`Transform`] is set to the interpolation between the previous and the current fixed step by
+/// the [`overstep_fraction()`](Time::overstep_fraction) of [`Time<Fixed>`], and it is set back to
+/// the current fixed step before the fixed steps of the next frame run.
+///
+/// Changing the [`Transform`] outside of the fixed steps, for example to teleport the entity,
+/// resets the interpolation to the new value.
+///
+/// The interpolation is applied by the systems of the [`TransformInterpolationPlugin`].
+#[derive(Component, Debug, PartialEq, Clone, Copy, Default)]
+#[require(Transform)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(Reflect),
+    reflect(Component, Default, PartialEq, Debug)
+)]
+pub struct TransformInterpolation {
+    previous: Option<Transform>,
+    current: Option<Transform>,
+    interpolated: Option<Transform>,
+}
+
+impl TransformInterpolation {
+    /// Returns the [`Transform`] of the entity at the start of the last fixed step.
+    pub fn previous(&self) -> Option<Transform> {
+        self.previous
+    }
+
+    /// Returns the [`Transform`] of the entity at the end of the last fixed step.
+    pub fn current(&self) -> Option<Transform> {
+        self.current
+    }
+
+    /// Discards the recorded fixed steps, so that the next one isn't interpolated from them.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Interpolates the [`Transform`] of [`TransformInterpolation`] entities between the fixed steps.
+///
+/// This requires the [`TimePlugin`](bevy_time::TimePlugin).
+#[derive(Default)]
+pub struct TransformInterpolationPlugin;
+
+impl Plugin for TransformInterpolationPlugin {
+    fn build(&self, app: &mut App) {
+        #[cfg(feature = "bevy_reflect")]
+        app.register_type::<TransformInterpolation>();
+
+        app.add_systems(
+            RunFixedMainLoop,
+            restore_fixed_transforms.in_set(RunFixedMainLoopSystem::BeforeFixedMainLoop),
+        )
+        .add_systems(FixedLast, record_fixed_transforms)
+        .add_systems(
+            PostUpdate,
+            interpolate_transforms.before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+/// Sets the [`Transform`] of [`TransformInterpolation`] entities back to their current fixed step
+/// before the fixed steps run, or resets the interpolation if it was changed since it was
+/// interpolated.
+pub fn restore_fixed_transforms(mut query: Query<(&mut Transform, &mut TransformInterpolation)>) {
+    for (mut transform, mut interpolation) in &mut query {
+        match (interpolation.current, interpolation.interpolated) {
+            (Some(current), Some(interpolated)) if *transform == interpolated => {
+                *transform = current;
+            }
+            _ => {
+                interpolation.previous = Some(*transform);
+                interpolation.current = Some(*transform);
+            }
+        }
+        interpolation.interpolated = None;
+    }
+}
+
+/// Records the [`Transform`] of [`TransformInterpolation`] entities after each fixed step.
+pub fn record_fixed_transforms(mut query: Query<(&Transform, &mut TransformInterpolation)>) {
+    for (transform, mut interpolation) in &mut query {
+        interpolation.previous = interpolation.current.or(Some(*transform));
+        interpolation.current = Some(*transform);
+    }
+}
+
+/// Sets the [`Transform`] of [`TransformInterpolation`] entities to the interpolation between
+/// their last two fixed steps, by the [`overstep_fraction()`](Time::overstep_fraction) of
+/// [`Time<Fixed>`].
+pub fn interpolate_transforms(
+    fixed_time: Res<Time<Fixed>>,
+    mut query: Query<(&mut Transform, &mut TransformInterpolation)>,
+) {
+    let fraction = fixed_time.overstep_fraction();
+    for (mut transform, mut interpolation) in &mut query {
+        let (Some(previous), Some(current)) = (interpolation.previous, interpolation.current)
+        else {
+            continue;
+        };
+        let interpolated = Transform {
+            translation: previous.translation.lerp(current.translation, fraction),
+            rotation: previous.rotation.slerp(current.rotation, fraction),
+            scale: previous.scale.lerp(current.scale, fraction),
+        };
+        *transform = interpolated;
+        interpolation.interpolated = Some(interpolated);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_app::{App, FixedUpdate};
+    use bevy_ecs::system::Query;
+    use bevy_math::Vec3;
+    use bevy_time::{Fixed, Time, TimePlugin, TimeUpdateStrategy};
+    use core::time::Duration;
+
+    use crate::{
+        components::Transform,
+        interpolation::{TransformInterpolation, TransformInterpolationPlugin},
+    };
+
+    fn move_right(mut query: Query<&mut Transform>) {
+        for mut transform in &mut query {
+            transform.translation.x += 10.0;
+        }
+    }
+
+    fn translation_x(app: &mut App) -> f32 {
+        app.world_mut()
+            .query::<&Transform>()
+            .single(app.world())
+            .translation
+            .x
+    }
+
+    #[test]
+    fn interpolates_between_fixed_steps() {
+        let mut app = App::new();
+        app.add_plugins((TimePlugin, TransformInterpolationPlugin))
+            .insert_resource(Time::<Fixed>::from_duration(Duration::from_millis(100)))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                150,
+            )))
+            .add_systems(FixedUpdate, move_right);
+        let entity = app
+            .world_mut()
+            .spawn(TransformInterpolation::default())
+            .id();
+
+        // The first update doesn't advance the time.
+        app.update();
+        assert_eq!(translation_x(&mut app), 0.0);
+
+        // One fixed step, and half of the next one.
+        app.update();
+        assert!((translation_x(&mut app) - 5.0).abs() < 1e-4);
+
+        // Two fixed steps, and none of the next one.
+        app.update();
+        assert!((translation_x(&mut app) - 20.0).abs() < 1e-4);
+
+        // Teleporting the entity resets the interpolation.
+        app.world_mut()
+            .get_mut::<Transform>(entity)
+            .unwrap()
+            .translation = Vec3::X * 100.0;
+        app.update();
+        assert!((translation_x(&mut app) - 105.0).abs() < 1e-4);
+    }
+}
@@ -24,6 +24,10 @@ pub mod traits;
 #[cfg(feature = "bevy-support")]
 pub mod plugins;
 
+/// Interpolation of transforms between fixed timesteps
+#[cfg(feature = "bevy_time")]
+pub mod interpolation;
+
 /// [`GlobalTransform`]: components::GlobalTransform
 /// Helpers related to computing global transforms
 #[cfg(feature = "bevy-support")]
@@ -48,6 +52,10 @@ pub mod prelude {
         plugins::{TransformPlugin, TransformSystem},
         traits::TransformPoint,
     };
+
+    #[cfg(feature = "bevy_time")]
+    #[doc(hidden)]
+    pub use crate::interpolation::{TransformInterpolation, TransformInterpolationPlugin};
 }
 
 #[cfg(feature = "bevy-support")]
@@ -47,6 +47,11 @@ pub fn sync_simple_transforms(
 /// [`Transform`] component.
 ///
 /// Third party plugins should ensure that this is used in concert with [`sync_simple_transforms`].
+///
+/// The hierarchies are propagated in parallel, but each [`GlobalTransform`] is computed from the
+/// [`GlobalTransform`] of its parent only, so the results don't depend on the order the entities
+/// are visited in. Together with the `deterministic_math` feature, they're identical on all
+/// platforms.
 pub fn propagate_transforms(
     mut root_query: Query<
         (Entity, &Children, Ref<Transform>, &mut GlobalTransform),
@@ -0,0 +1,470 @@
+//! Directional navigation between UI nodes with the arrow keys and the gamepad d-pad.
+//!
+//! Add the [`UiDirectionalNavigationPlugin`] to an app, and an [`AutoDirectionalNavigation`]
+//! component to the nodes that can be focused. The plugin then:
+//! - builds the [`DirectionalNavigationMap`] of these nodes from the layout, picking the closest
+//!   node in each [`CompassOctant`] as the neighbor of a node, unless the neighbor is overridden
+//!   with [`DirectionalNavigationOverrides`].
+//! - moves the [`InputFocus`] between the nodes when the inputs bound in
+//!   [`UiNavigationBindings`] are pressed, and triggers a [`UiActivate`] event on the focused node
+//!   when it is activated.
+//! - shows the [`FocusOutline`] of the focused node while [`InputFocusVisible`] is true.
+
+use crate::{ComputedNode, Node, Outline, TargetCamera};
+use bevy_app::{App, Plugin, PostUpdate, PreUpdate};
+use bevy_color::Color;
+use bevy_ecs::{entity::EntityHashSet, prelude::*};
+use bevy_hierarchy::Parent;
+use bevy_input::{
+    gamepad::{Gamepad, GamepadButton},
+    keyboard::KeyCode,
+    ButtonInput, InputSystem,
+};
+use bevy_input_focus::{
+    directional_navigation::{
+        DirectionalNavigation, DirectionalNavigationMap, DirectionalNavigationPlugin, NavNeighbors,
+    },
+    InputFocus, InputFocusVisible,
+};
+use bevy_math::{CompassOctant, Dir2, FloatOrd, Rect, Vec2};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_render::view::InheritedVisibility;
+use bevy_transform::{components::GlobalTransform, TransformSystem};
+use bevy_utils::{HashMap, HashSet};
+
+/// Builds the directional navigation graph of UI nodes from their layout, and navigates it with
+/// the keyboard and gamepads.
+///
+/// Also adds the [`DirectionalNavigationPlugin`] if it hasn't been added yet.
+pub struct UiDirectionalNavigationPlugin;
+
+impl Plugin for UiDirectionalNavigationPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<DirectionalNavigationPlugin>() {
+            app.add_plugins(DirectionalNavigationPlugin);
+        }
+
+        app.init_resource::<InputFocus>()
+            .init_resource::<InputFocusVisible>()
+            .init_resource::<UiNavigationBindings>()
+            .register_type::<AutoDirectionalNavigation>()
+            .register_type::<DirectionalNavigationOverrides>()
+            .register_type::<FocusOutline>()
+            .register_type::<UiNavigationBindings>()
+            .add_systems(PreUpdate, navigate_ui_with_input.after(InputSystem))
+            .add_systems(
+                PostUpdate,
+                (
+                    update_ui_navigation_map.after(TransformSystem::TransformPropagate),
+                    update_focus_outlines,
+                ),
+            );
+    }
+}
+
+/// Marks a UI node that can be focused with directional navigation.
+///
+/// The neighbors of the node in the [`DirectionalNavigationMap`] are found from the layout: the
+/// neighbor in each direction is the closest visible node with this component in that
+/// direction, that is rendered by the same camera. Edges of the node that were added to the map
+/// by hand are replaced whenever the layout changes, use [`DirectionalNavigationOverrides`]
+/// instead.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq)]
+#[require(Node)]
+pub struct AutoDirectionalNavigation;
+
+/// An explicit neighbor of a node in [`DirectionalNavigationOverrides`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Debug, PartialEq)]
+pub enum NavigationOverride {
+    /// Navigating in this direction focuses the given entity.
+    Neighbor(Entity),
+    /// Navigating in this direction does nothing.
+    Blocked,
+}
+
+/// Overrides the neighbors that are found from the layout for a node with
+/// [`AutoDirectionalNavigation`].
+///
+/// This is useful to wrap around the edges of a menu, or to stop the focus from leaving it.
+#[derive(Component, Debug, Clone, Default, PartialEq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq)]
+pub struct DirectionalNavigationOverrides {
+    /// The overrides, indexed by [`CompassOctant::to_index`].
+    pub overrides: [Option<NavigationOverride>; 8],
+}
+
+impl DirectionalNavigationOverrides {
+    /// Returns these overrides with navigation in the `octant` direction focusing `entity`.
+    pub fn with_neighbor(mut self, octant: CompassOctant, entity: Entity) -> Self {
+        self.overrides[octant.to_index()] = Some(NavigationOverride::Neighbor(entity));
+        self
+    }
+
+    /// Returns these overrides with navigation in the `octant` direction blocked.
+    pub fn with_blocked(mut self, octant: CompassOctant) -> Self {
+        self.overrides[octant.to_index()] = Some(NavigationOverride::Blocked);
+        self
+    }
+
+    /// Returns the override in the `octant` direction, if any.
+    pub fn get(&self, octant: CompassOctant) -> Option<NavigationOverride> {
+        self.overrides[octant.to_index()]
+    }
+}
+
+/// The outline of a UI node while it has the [`InputFocus`] and [`InputFocusVisible`] is true.
+///
+/// The [`Outline`] of the node is managed by this component: it is set to this outline while the
+/// node is focused, and to a transparent outline otherwise.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq)]
+#[require(Node)]
+pub struct FocusOutline(pub Outline);
+
+/// An action of the UI navigation that an input can be bound to in [`UiNavigationBindings`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Debug, PartialEq, Hash)]
+pub enum UiNavigationAction {
+    /// Moves the focus up.
+    Up,
+    /// Moves the focus down.
+    Down,
+    /// Moves the focus left.
+    Left,
+    /// Moves the focus right.
+    Right,
+    /// Triggers a [`UiActivate`] event on the focused node.
+    Activate,
+}
+
+/// The keyboard keys and gamepad buttons bound to each [`UiNavigationAction`].
+///
+/// By default, the arrow keys and the d-pad move the focus, and `Enter` and the south gamepad
+/// button activate the focused node. Pressing two opposite directions at once cancels them out,
+/// and pressing two perpendicular directions at once moves the focus diagonally.
+#[derive(Resource, Debug, Clone, Reflect)]
+#[reflect(Resource, Default, Debug)]
+pub struct UiNavigationBindings {
+    /// The actions of keyboard keys.
+    pub keyboard: HashMap<KeyCode, UiNavigationAction>,
+    /// The actions of gamepad buttons, on any gamepad.
+    pub gamepad: HashMap<GamepadButton, UiNavigationAction>,
+}
+
+impl Default for UiNavigationBindings {
+    fn default() -> Self {
+        Self {
+            keyboard: HashMap::from_iter([
+                (KeyCode::ArrowUp, UiNavigationAction::Up),
+                (KeyCode::ArrowDown, UiNavigationAction::Down),
+                (KeyCode::ArrowLeft, UiNavigationAction::Left),
+                (KeyCode::ArrowRight, UiNavigationAction::Right),
+                (KeyCode::Enter, UiNavigationAction::Activate),
+                (KeyCode::NumpadEnter, UiNavigationAction::Activate),
+            ]),
+            gamepad: HashMap::from_iter([
+                (GamepadButton::DPadUp, UiNavigationAction::Up),
+                (GamepadButton::DPadDown, UiNavigationAction::Down),
+                (GamepadButton::DPadLeft, UiNavigationAction::Left),
+                (GamepadButton::DPadRight, UiNavigationAction::Right),
+                (GamepadButton::South, UiNavigationAction::Activate),
+            ]),
+        }
+    }
+}
+
+/// Triggered on the focused node when the [`UiNavigationAction::Activate`] input is pressed, and
+/// propagated up the hierarchy.
+///
+/// Observe this event to press buttons and other widgets with the keyboard or a gamepad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UiActivate {
+    /// The focused node that was activated.
+    pub entity: Entity,
+}
+
+impl Event for UiActivate {
+    type Traversal = &'static Parent;
+
+    const AUTO_PROPAGATE: bool = true;
+}
+
+/// Returns the neighbor of the node with the rect `origin` in the `octant` direction among the
+/// `candidates`, in physical pixels, if any.
+///
+/// The candidates are measured from the center of the origin to their closest point, so that a
+/// wide node is the neighbor of all the nodes that are right above or below it. A candidate is in
+/// the direction if it's within 45 degrees of it, and candidates that are off to the side count
+/// as farther away.
+pub fn find_navigation_neighbor(
+    origin: Rect,
+    octant: CompassOctant,
+    candidates: impl IntoIterator<Item = (Entity, Rect)>,
+) -> Option<Entity> {
+    // UI coordinates point down, while the compass points up.
+    let direction = Vec2::from(Dir2::from(octant)) * Vec2::new(1., -1.);
+    let center = origin.center();
+    candidates
+        .into_iter()
+        .filter_map(|(entity, rect)| {
+            let offset = center.clamp(rect.min, rect.max) - center;
+            let along = offset.dot(direction);
+            let across = offset.perp_dot(direction).abs();
+            (along > 0. && across <= along).then_some((entity, along + 2. * across))
+        })
+        .min_by_key(|(_, distance)| FloatOrd(*distance))
+        .map(|(entity, _)| entity)
+}
+
+/// Updates the [`DirectionalNavigationMap`] from the layout of the nodes with
+/// [`AutoDirectionalNavigation`], whenever any of them moves, resizes, or is shown or hidden.
+///
+/// Hidden nodes are removed from the map, so that they can't be navigated to.
+pub fn update_ui_navigation_map(
+    mut map: ResMut<DirectionalNavigationMap>,
+    mut mapped: Local<EntityHashSet>,
+    nodes: Query<
+        (
+            Entity,
+            &ComputedNode,
+            &GlobalTransform,
+            &InheritedVisibility,
+            Option<&TargetCamera>,
+            Option<&DirectionalNavigationOverrides>,
+        ),
+        With<AutoDirectionalNavigation>,
+    >,
+    changed: Query<
+        (),
+        (
+            With<AutoDirectionalNavigation>,
+            Or<(
+                Changed<AutoDirectionalNavigation>,
+                Changed<ComputedNode>,
+                Changed<GlobalTransform>,
+                Changed<InheritedVisibility>,
+                Changed<TargetCamera>,
+                Changed<DirectionalNavigationOverrides>,
+            )>,
+        ),
+    >,
+    mut removed: RemovedComponents<AutoDirectionalNavigation>,
+    mut removed_overrides: RemovedComponents<DirectionalNavigationOverrides>,
+) {
+    let removed = removed.read().count() + removed_overrides.read().count();
+    if removed == 0 && changed.is_empty() {
+        return;
+    }
+
+    let visible: Vec<_> = nodes
+        .iter()
+        .filter(|(_, node, _, visibility, ..)| visibility.get() && !node.is_empty())
+        .map(|(entity, node, transform, _, camera, overrides)| {
+            let rect = Rect::from_center_size(transform.translation().truncate(), node.size());
+            (entity, rect, camera.map(TargetCamera::entity), overrides)
+        })
+        .collect();
+
+    let visible_entities: EntityHashSet = visible.iter().map(|(entity, ..)| *entity).collect();
+    for entity in mapped
+        .iter()
+        .filter(|entity| !visible_entities.contains(entity))
+    {
+        map.remove(*entity);
+    }
+
+    for &(entity, rect, camera, overrides) in &visible {
+        let mut neighbors = NavNeighbors::default();
+        for (index, neighbor) in neighbors.neighbors.iter_mut().enumerate() {
+            let octant = CompassOctant::from_index(index).unwrap();
+            *neighbor = match overrides.and_then(|overrides| overrides.get(octant)) {
+                Some(NavigationOverride::Neighbor(neighbor)) => Some(neighbor),
+                Some(NavigationOverride::Blocked) => None,
+                None => find_navigation_neighbor(
+                    rect,
+                    octant,
+                    visible
+                        .iter()
+                        .filter(|(other, _, other_camera, _)| {
+                            *other != entity && *other_camera == camera
+                        })
+                        .map(|(other, other_rect, ..)| (*other, *other_rect)),
+                ),
+            };
+        }
+        map.neighbors.insert(entity, neighbors);
+    }
+
+    *mapped = visible_entities;
+}
+
+/// Moves the [`InputFocus`] between nodes and activates the focused node, from the inputs bound in
+/// [`UiNavigationBindings`].
+///
+/// If no node in the [`DirectionalNavigationMap`] has the focus when a direction is pressed, the
+/// top-left node with [`AutoDirectionalNavigation`] is focused. Navigating makes the focus
+/// visible.
+pub fn navigate_ui_with_input(
+    mut commands: Commands,
+    bindings: Res<UiNavigationBindings>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut navigation: DirectionalNavigation,
+    mut focus_visible: ResMut<InputFocusVisible>,
+    nodes: Query<
+        (
+            Entity,
+            &ComputedNode,
+            &GlobalTransform,
+            &InheritedVisibility,
+        ),
+        With<AutoDirectionalNavigation>,
+    >,
+) {
+    let actions: HashSet<UiNavigationAction> = keyboard
+        .get_just_pressed()
+        .filter_map(|key| bindings.keyboard.get(key))
+        .chain(gamepads.iter().flat_map(|gamepad| {
+            gamepad
+                .get_just_pressed()
+                .filter_map(|button| bindings.gamepad.get(button))
+        }))
+        .copied()
+        .collect();
+    if actions.is_empty() {
+        return;
+    }
+
+    let axis =
+        |positive, negative| actions.contains(&positive) as i8 - actions.contains(&negative) as i8;
+    let octant = match (
+        axis(UiNavigationAction::Right, UiNavigationAction::Left),
+        axis(UiNavigationAction::Up, UiNavigationAction::Down),
+    ) {
+        (0, 1) => Some(CompassOctant::North),
+        (1, 1) => Some(CompassOctant::NorthEast),
+        (1, 0) => Some(CompassOctant::East),
+        (1, -1) => Some(CompassOctant::SouthEast),
+        (0, -1) => Some(CompassOctant::South),
+        (-1, -1) => Some(CompassOctant::SouthWest),
+        (-1, 0) => Some(CompassOctant::West),
+        (-1, 1) => Some(CompassOctant::NorthWest),
+        _ => None,
+    };
+
+    if let Some(octant) = octant {
+        let focus = navigation.focus.get();
+        if focus.is_some_and(|focus| navigation.map.get_neighbors(focus).is_some()) {
+            // The focus stays put when there is no neighbor in this direction.
+            let _ = navigation.navigate(octant);
+        } else if let Some((first, ..)) = nodes
+            .iter()
+            .filter(|(_, node, _, visibility)| visibility.get() && !node.is_empty())
+            .min_by_key(|(_, node, transform, _)| {
+                let top_left = transform.translation().truncate() - 0.5 * node.size();
+                (FloatOrd(top_left.y), FloatOrd(top_left.x))
+            })
+        {
+            navigation.focus.set(first);
+        }
+        if !focus_visible.0 {
+            focus_visible.0 = true;
+        }
+    }
+
+    if actions.contains(&UiNavigationAction::Activate) {
+        if let Some(entity) = navigation.focus.get() {
+            commands.trigger_targets(UiActivate { entity }, entity);
+        }
+    }
+}
+
+/// Shows the [`FocusOutline`] of the focused node, and hides the outlines of the other nodes.
+pub fn update_focus_outlines(
+    mut commands: Commands,
+    focus: Res<InputFocus>,
+    focus_visible: Res<InputFocusVisible>,
+    mut nodes: Query<(Entity, Ref<FocusOutline>, Option<&mut Outline>)>,
+) {
+    let focus_changed = focus.is_changed() || focus_visible.is_changed();
+    for (entity, focus_outline, outline) in &mut nodes {
+        if !focus_changed && !focus_outline.is_changed() {
+            continue;
+        }
+        let mut target = focus_outline.0;
+        if !(focus_visible.0 && focus.get() == Some(entity)) {
+            target.color = Color::NONE;
+        }
+        match outline {
+            Some(mut outline) => {
+                outline.set_if_neq(target);
+            }
+            None => {
+                commands.entity(entity).insert(target);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::entity::Entity;
+    use bevy_math::{CompassOctant, Rect, Vec2};
+
+    use super::find_navigation_neighbor;
+
+    fn cell(x: f32, y: f32) -> Rect {
+        Rect::from_center_size(Vec2::new(x, y), Vec2::splat(10.))
+    }
+
+    #[test]
+    fn neighbors_in_a_grid() {
+        let entities: Vec<_> = (0..9).map(Entity::from_raw).collect();
+        let grid: Vec<_> = (0..9)
+            .map(|i| {
+                let rect = cell((i % 3) as f32 * 20., (i / 3) as f32 * 20.);
+                (entities[i], rect)
+            })
+            .collect();
+        let neighbor = |i: usize, octant| {
+            find_navigation_neighbor(
+                grid[i].1,
+                octant,
+                grid.iter().copied().filter(|(e, _)| *e != entities[i]),
+            )
+        };
+
+        assert_eq!(neighbor(4, CompassOctant::North), Some(entities[1]));
+        assert_eq!(neighbor(4, CompassOctant::East), Some(entities[5]));
+        assert_eq!(neighbor(4, CompassOctant::South), Some(entities[7]));
+        assert_eq!(neighbor(4, CompassOctant::West), Some(entities[3]));
+        assert_eq!(neighbor(4, CompassOctant::NorthEast), Some(entities[2]));
+        assert_eq!(neighbor(4, CompassOctant::SouthWest), Some(entities[6]));
+        assert_eq!(neighbor(0, CompassOctant::North), None);
+        assert_eq!(neighbor(0, CompassOctant::West), None);
+        assert_eq!(neighbor(0, CompassOctant::East), Some(entities[1]));
+    }
+
+    #[test]
+    fn wide_nodes_neighbor_the_nodes_beside_them() {
+        let wide = Entity::from_raw(0);
+        let right = Entity::from_raw(1);
+        let wide_rect = Rect::new(0., 0., 100., 10.);
+        let right_rect = cell(95., 30.);
+
+        assert_eq!(
+            find_navigation_neighbor(right_rect, CompassOctant::North, [(wide, wide_rect)]),
+            Some(wide)
+        );
+        assert_eq!(
+            find_navigation_neighbor(wide_rect, CompassOctant::South, [(right, right_rect)]),
+            None
+        );
+        assert_eq!(
+            find_navigation_neighbor(wide_rect, CompassOctant::SouthEast, [(right, right_rect)]),
+            Some(right)
+        );
+    }
+}
@@ -0,0 +1,732 @@
+//! Drag-and-drop of UI nodes, built on the drag events of `bevy_picking`.
+//!
+//! Add [`Draggable`] to the nodes that can be dragged and [`DropTarget`] to the nodes they can be
+//! dropped on. Every drag carries a payload entity, such as the inventory item of a slot, and
+//! triggers these events, which can be observed on the nodes:
+//!
+//! - [`UiDragStart`] on the dragged node when the drag starts.
+//! - [`UiDragOver`] on the drop target under the pointer, every frame of the drag.
+//! - [`UiDragLeave`] on a drop target when the pointer leaves it, or the drag is canceled over it.
+//! - [`UiDrop`] on the drop target under the pointer when the drag ends.
+//! - [`UiDragEnd`] on the dragged node when the drag ends, after any [`UiDrop`].
+//!
+//! While a node is dragged, a ghost of it follows the pointer, unless [`Draggable::ghost`] is
+//! `false`.
+
+#![deny(missing_docs)]
+
+use core::iter;
+
+use crate::{
+    widget::ImageNode, BackgroundColor, BorderColor, BorderRadius, ComputedNode, GlobalZIndex,
+    Node, PositionType, TargetCamera, UiOpacity, UiScale, Val,
+};
+use bevy_app::prelude::*;
+use bevy_ecs::prelude::*;
+use bevy_hierarchy::{DespawnRecursiveExt, HierarchyQueryExt, Parent};
+use bevy_math::Vec2;
+use bevy_picking::{
+    events::{Cancel, Drag, DragEnd, DragStart, Pointer},
+    hover::HoverMap,
+    pointer::{PointerButton, PointerId},
+    PickSet, Pickable,
+};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_transform::prelude::GlobalTransform;
+use bevy_utils::HashMap;
+
+/// A plugin that adds drag-and-drop of UI nodes.
+#[derive(Clone)]
+pub struct UiDragDropPlugin;
+
+impl Plugin for UiDragDropPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UiDrags>()
+            .register_type::<Draggable>()
+            .register_type::<DropTarget>()
+            .add_systems(
+                PreUpdate,
+                (
+                    start_ui_drags,
+                    move_ui_drags,
+                    end_ui_drags,
+                    update_ui_drop_targets,
+                )
+                    .chain()
+                    .in_set(PickSet::Last),
+            );
+    }
+}
+
+/// Makes a UI node draggable with drag-and-drop.
+///
+/// Dragging the node or any of its descendants drags the node.
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component, Default, Debug)]
+#[require(Node)]
+pub struct Draggable {
+    /// The entity that the drag carries to drop targets, or `None` to carry the node itself.
+    pub payload: Option<Entity>,
+    /// The button that drags the node.
+    pub button: PointerButton,
+    /// Whether a ghost of the node follows the pointer during the drag.
+    ///
+    /// The ghost is a root node with the size, background, border and image of the dragged node at
+    /// a lower [`UiOpacity`], on top of all other nodes. It doesn't copy the children of the node,
+    /// but children can be added to it from an observer of [`UiDragStart`].
+    pub ghost: bool,
+}
+
+impl Default for Draggable {
+    fn default() -> Self {
+        Self {
+            payload: None,
+            button: PointerButton::Primary,
+            ghost: true,
+        }
+    }
+}
+
+impl Draggable {
+    /// Makes a draggable node that carries the given payload.
+    pub fn with_payload(payload: Entity) -> Self {
+        Self {
+            payload: Some(payload),
+            ..Default::default()
+        }
+    }
+}
+
+/// Marks a UI node that draggable nodes can be dropped on.
+///
+/// Drags over any of its descendants are over the nearest drop target.
+#[derive(Component, Debug, Clone, Copy, Default, Reflect)]
+#[reflect(Component, Default, Debug)]
+#[require(Node)]
+pub struct DropTarget;
+
+/// Marks the ghost of a dragged node.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct DragGhost;
+
+/// A drag of a UI node in progress.
+#[derive(Debug, Clone)]
+pub struct UiDrag {
+    /// The dragged node.
+    pub dragged: Entity,
+    /// The entity that the drag carries.
+    pub payload: Entity,
+    /// The button that drags the node.
+    pub button: PointerButton,
+    /// The ghost of the node that follows the pointer, if any.
+    pub ghost: Option<Entity>,
+    /// The drop target under the pointer, if any.
+    pub target: Option<Entity>,
+    /// The latest position of the pointer, in logical pixels of its render target.
+    pub position: Vec2,
+    /// The position of the pointer relative to the top left corner of the node, in the units of
+    /// [`Val::Px`].
+    grab_offset: Vec2,
+}
+
+/// The drags of UI nodes in progress, by pointer.
+#[derive(Resource, Debug, Default)]
+pub struct UiDrags(HashMap<PointerId, UiDrag>);
+
+impl UiDrags {
+    /// Returns the drag of the pointer, if it's dragging a node.
+    pub fn get(&self, pointer_id: PointerId) -> Option<&UiDrag> {
+        self.0.get(&pointer_id)
+    }
+
+    /// Iterates over the drags in progress and their pointers.
+    pub fn iter(&self) -> impl Iterator<Item = (PointerId, &UiDrag)> {
+        self.0.iter().map(|(pointer_id, drag)| (*pointer_id, drag))
+    }
+
+    /// Returns true if the node is being dragged.
+    pub fn is_dragged(&self, node: Entity) -> bool {
+        self.0.values().any(|drag| drag.dragged == node)
+    }
+}
+
+/// Triggered on a [`Draggable`] node when a pointer starts dragging it.
+#[derive(Event, Debug, Clone, Reflect)]
+pub struct UiDragStart {
+    /// The pointer that drags the node.
+    pub pointer_id: PointerId,
+    /// The entity that the drag carries.
+    pub payload: Entity,
+    /// The ghost of the node that follows the pointer, if any.
+    pub ghost: Option<Entity>,
+}
+
+/// Triggered on a [`DropTarget`] every frame that a pointer drags a node over it.
+#[derive(Event, Debug, Clone, Reflect)]
+pub struct UiDragOver {
+    /// The pointer that drags the node.
+    pub pointer_id: PointerId,
+    /// The dragged node.
+    pub dragged: Entity,
+    /// The entity that the drag carries.
+    pub payload: Entity,
+    /// The position of the pointer, in logical pixels of its render target.
+    pub position: Vec2,
+}
+
+/// Triggered on a [`DropTarget`] when a pointer that drags a node leaves it, or when the drag is
+/// canceled over it.
+#[derive(Event, Debug, Clone, Reflect)]
+pub struct UiDragLeave {
+    /// The pointer that drags the node.
+    pub pointer_id: PointerId,
+    /// The dragged node.
+    pub dragged: Entity,
+    /// The entity that the drag carries.
+    pub payload: Entity,
+}
+
+/// Triggered on a [`DropTarget`] when a pointer drops a node on it.
+#[derive(Event, Debug, Clone, Reflect)]
+pub struct UiDrop {
+    /// The pointer that dragged the node.
+    pub pointer_id: PointerId,
+    /// The dropped node.
+    pub dragged: Entity,
+    /// The entity that the drag carried.
+    pub payload: Entity,
+    /// The position of the pointer, in logical pixels of its render target.
+    pub position: Vec2,
+}
+
+/// Triggered on a [`Draggable`] node when its drag ends, whether it was dropped or not.
+#[derive(Event, Debug, Clone, Reflect)]
+pub struct UiDragEnd {
+    /// The pointer that dragged the node.
+    pub pointer_id: PointerId,
+    /// The entity that the drag carried.
+    pub payload: Entity,
+    /// The drop target that the node was dropped on, if any.
+    pub target: Option<Entity>,
+}
+
+/// Starts the drags of [`Draggable`] nodes, and spawns their ghosts.
+fn start_ui_drags(
+    mut commands: Commands,
+    mut drag_starts: EventReader<Pointer<DragStart>>,
+    mut drags: ResMut<UiDrags>,
+    draggables: Query<&Draggable>,
+    parents: Query<&Parent>,
+    nodes: Query<(
+        &Node,
+        &ComputedNode,
+        &GlobalTransform,
+        Option<&TargetCamera>,
+        Option<&BackgroundColor>,
+        Option<&BorderColor>,
+        Option<&BorderRadius>,
+        Option<&ImageNode>,
+    )>,
+    ui_scale: Res<UiScale>,
+) {
+    for event in drag_starts.read() {
+        if drags.0.contains_key(&event.pointer_id) {
+            continue;
+        }
+        let Some((dragged, draggable)) = iter::once(event.target)
+            .chain(parents.iter_ancestors(event.target))
+            .find_map(|entity| {
+                draggables
+                    .get(entity)
+                    .ok()
+                    .map(|draggable| (entity, draggable))
+            })
+        else {
+            continue;
+        };
+        if draggable.button != event.event.button {
+            continue;
+        }
+
+        let position = event.pointer_location.position;
+        let mut grab_offset = Vec2::ZERO;
+        let mut ghost = None;
+        if let Ok((
+            node,
+            computed_node,
+            transform,
+            target_camera,
+            background_color,
+            border_color,
+            border_radius,
+            image,
+        )) = nodes.get(dragged)
+        {
+            let size = computed_node.size() * computed_node.inverse_scale_factor();
+            let min = transform.translation().truncate() * computed_node.inverse_scale_factor()
+                - 0.5 * size;
+            grab_offset = position / ui_scale.0 - min;
+
+            if draggable.ghost {
+                let mut ghost_commands = commands.spawn((
+                    DragGhost,
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(min.x),
+                        top: Val::Px(min.y),
+                        width: Val::Px(size.x),
+                        height: Val::Px(size.y),
+                        border: node.border,
+                        padding: node.padding,
+                        ..Default::default()
+                    },
+                    UiOpacity(0.6),
+                    GlobalZIndex(i32::MAX),
+                    Pickable::IGNORE,
+                ));
+                if let Some(target_camera) = target_camera {
+                    ghost_commands.insert(target_camera.clone());
+                }
+                if let Some(background_color) = background_color {
+                    ghost_commands.insert(*background_color);
+                }
+                if let Some(border_color) = border_color {
+                    ghost_commands.insert(*border_color);
+                }
+                if let Some(border_radius) = border_radius {
+                    ghost_commands.insert(*border_radius);
+                }
+                if let Some(image) = image {
+                    ghost_commands.insert(image.clone());
+                }
+                ghost = Some(ghost_commands.id());
+            }
+        }
+
+        let payload = draggable.payload.unwrap_or(dragged);
+        drags.0.insert(
+            event.pointer_id,
+            UiDrag {
+                dragged,
+                payload,
+                button: draggable.button,
+                ghost,
+                target: None,
+                position,
+                grab_offset,
+            },
+        );
+        commands.trigger_targets(
+            UiDragStart {
+                pointer_id: event.pointer_id,
+                payload,
+                ghost,
+            },
+            dragged,
+        );
+    }
+}
+
+/// Moves the ghosts of dragged nodes with their pointers.
+fn move_ui_drags(
+    mut drag_events: EventReader<Pointer<Drag>>,
+    mut drags: ResMut<UiDrags>,
+    mut ghosts: Query<&mut Node, With<DragGhost>>,
+    ui_scale: Res<UiScale>,
+) {
+    for event in drag_events.read() {
+        let Some(drag) = drags.0.get_mut(&event.pointer_id) else {
+            continue;
+        };
+        drag.position = event.pointer_location.position;
+        let Some(mut ghost) = drag.ghost.and_then(|ghost| ghosts.get_mut(ghost).ok()) else {
+            continue;
+        };
+        let min = drag.position / ui_scale.0 - drag.grab_offset;
+        ghost.left = Val::Px(min.x);
+        ghost.top = Val::Px(min.y);
+    }
+}
+
+/// Ends the drags whose button was released or whose pointer was canceled, and drops their
+/// payloads on the drop target under the pointer.
+fn end_ui_drags(
+    mut commands: Commands,
+    mut drag_ends: EventReader<Pointer<DragEnd>>,
+    mut cancels: EventReader<Pointer<Cancel>>,
+    mut drags: ResMut<UiDrags>,
+) {
+    let drag_ends = drag_ends
+        .read()
+        .map(|event| (event.pointer_id, Some(event.event.button)));
+    let cancels = cancels.read().map(|event| (event.pointer_id, None));
+    for (pointer_id, released_button) in drag_ends.chain(cancels) {
+        let dropped = match (drags.0.get(&pointer_id), released_button) {
+            (None, _) => continue,
+            (Some(drag), Some(button)) if drag.button != button => continue,
+            (Some(_), button) => button.is_some(),
+        };
+        let Some(drag) = drags.0.remove(&pointer_id) else {
+            continue;
+        };
+
+        if let Some(mut ghost) = drag.ghost.and_then(|ghost| commands.get_entity(ghost)) {
+            ghost.despawn_recursive();
+        }
+
+        let target = drag.target.filter(|_| dropped);
+        match (drag.target, target) {
+            (_, Some(target)) => {
+                commands.trigger_targets(
+                    UiDrop {
+                        pointer_id,
+                        dragged: drag.dragged,
+                        payload: drag.payload,
+                        position: drag.position,
+                    },
+                    target,
+                );
+            }
+            (Some(hovered), None) => {
+                commands.trigger_targets(
+                    UiDragLeave {
+                        pointer_id,
+                        dragged: drag.dragged,
+                        payload: drag.payload,
+                    },
+                    hovered,
+                );
+            }
+            (None, None) => {}
+        }
+        commands.trigger_targets(
+            UiDragEnd {
+                pointer_id,
+                payload: drag.payload,
+                target,
+            },
+            drag.dragged,
+        );
+    }
+}
+
+/// Finds the drop targets under the pointers of drags, and triggers [`UiDragOver`] and
+/// [`UiDragLeave`] on them.
+fn update_ui_drop_targets(
+    mut commands: Commands,
+    mut drags: ResMut<UiDrags>,
+    hover_map: Res<HoverMap>,
+    drop_targets: Query<(), With<DropTarget>>,
+    parents: Query<&Parent>,
+) {
+    for (pointer_id, drag) in drags.0.iter_mut() {
+        let target = hover_map.get(pointer_id).and_then(|hovered| {
+            hovered
+                .iter()
+                .filter_map(|(entity, hit)| {
+                    iter::once(*entity)
+                        .chain(parents.iter_ancestors(*entity))
+                        .find(|entity| drop_targets.contains(*entity))
+                        .map(|target| (target, hit.depth))
+                })
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(target, _)| target)
+        });
+
+        if target != drag.target {
+            if let Some(previous) = drag.target {
+                commands.trigger_targets(
+                    UiDragLeave {
+                        pointer_id: *pointer_id,
+                        dragged: drag.dragged,
+                        payload: drag.payload,
+                    },
+                    previous,
+                );
+            }
+            drag.target = target;
+        }
+
+        if let Some(target) = target {
+            commands.trigger_targets(
+                UiDragOver {
+                    pointer_id: *pointer_id,
+                    dragged: drag.dragged,
+                    payload: drag.payload,
+                    position: drag.position,
+                },
+                target,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::fmt::Debug;
+
+    use bevy_ecs::schedule::Schedule;
+    use bevy_hierarchy::BuildChildren;
+    use bevy_picking::{backend::HitData, pointer::Location};
+    use bevy_render::camera::{ManualTextureViewHandle, NormalizedRenderTarget};
+
+    use super::*;
+
+    /// The events triggered on the nodes, in order.
+    #[derive(Resource, Default)]
+    struct Triggered(Vec<(&'static str, Entity)>);
+
+    struct TestWorld {
+        world: World,
+        schedule: Schedule,
+        camera: Entity,
+    }
+
+    impl TestWorld {
+        fn new() -> Self {
+            let mut world = World::new();
+            world.init_resource::<Events<Pointer<DragStart>>>();
+            world.init_resource::<Events<Pointer<Drag>>>();
+            world.init_resource::<Events<Pointer<DragEnd>>>();
+            world.init_resource::<Events<Pointer<Cancel>>>();
+            world.init_resource::<HoverMap>();
+            world.init_resource::<UiScale>();
+            world.init_resource::<UiDrags>();
+            world.init_resource::<Triggered>();
+            world.add_observer(
+                |trigger: Trigger<UiDragStart>, mut triggered: ResMut<Triggered>| {
+                    triggered.0.push(("start", trigger.target()));
+                },
+            );
+            world.add_observer(
+                |trigger: Trigger<UiDragOver>, mut triggered: ResMut<Triggered>| {
+                    triggered.0.push(("over", trigger.target()));
+                },
+            );
+            world.add_observer(
+                |trigger: Trigger<UiDragLeave>, mut triggered: ResMut<Triggered>| {
+                    triggered.0.push(("leave", trigger.target()));
+                },
+            );
+            world.add_observer(
+                |trigger: Trigger<UiDrop>, mut triggered: ResMut<Triggered>| {
+                    triggered.0.push(("drop", trigger.target()));
+                },
+            );
+            world.add_observer(
+                |trigger: Trigger<UiDragEnd>, mut triggered: ResMut<Triggered>| {
+                    triggered.0.push(("end", trigger.target()));
+                },
+            );
+
+            let mut schedule = Schedule::default();
+            schedule.add_systems(
+                (
+                    start_ui_drags,
+                    move_ui_drags,
+                    end_ui_drags,
+                    update_ui_drop_targets,
+                )
+                    .chain(),
+            );
+            let camera = world.spawn_empty().id();
+            Self {
+                world,
+                schedule,
+                camera,
+            }
+        }
+
+        fn hit(&self) -> HitData {
+            HitData::new(self.camera, 0., None, None)
+        }
+
+        fn send<E: Debug + Clone + Reflect>(&mut self, target: Entity, event: E) {
+            let location = Location {
+                target: NormalizedRenderTarget::TextureView(ManualTextureViewHandle(0)),
+                position: Vec2::new(10., 20.),
+            };
+            self.world
+                .send_event(Pointer::new(PointerId::Mouse, location, target, event));
+        }
+
+        fn hover(&mut self, entity: Option<Entity>) {
+            let hit = self.hit();
+            let mut hover_map = self.world.resource_mut::<HoverMap>();
+            hover_map.clear();
+            if let Some(entity) = entity {
+                let mut hovered = HashMap::default();
+                hovered.insert(entity, hit);
+                hover_map.insert(PointerId::Mouse, hovered);
+            }
+        }
+
+        fn update(&mut self) -> Vec<(&'static str, Entity)> {
+            self.schedule.run(&mut self.world);
+            self.world
+                .resource_mut::<Events<Pointer<DragStart>>>()
+                .update();
+            self.world.resource_mut::<Events<Pointer<Drag>>>().update();
+            self.world
+                .resource_mut::<Events<Pointer<DragEnd>>>()
+                .update();
+            self.world
+                .resource_mut::<Events<Pointer<Cancel>>>()
+                .update();
+            core::mem::take(&mut self.world.resource_mut::<Triggered>().0)
+        }
+    }
+
+    #[test]
+    fn drag_and_drop_on_a_target() {
+        let mut test = TestWorld::new();
+        let payload = test.world.spawn_empty().id();
+        let mut handle = None;
+        let draggable = test
+            .world
+            .spawn(Draggable::with_payload(payload))
+            .with_children(|builder| {
+                handle = Some(builder.spawn(Node::default()).id());
+            })
+            .id();
+        let mut slot = None;
+        let target = test
+            .world
+            .spawn(DropTarget)
+            .with_children(|builder| {
+                slot = Some(builder.spawn(Node::default()).id());
+            })
+            .id();
+
+        // Dragging a descendant drags the draggable node, with a ghost.
+        let hit = test.hit();
+        test.send(
+            handle.unwrap(),
+            DragStart {
+                button: PointerButton::Primary,
+                hit,
+            },
+        );
+        assert_eq!(test.update(), vec![("start", draggable)]);
+        let drag = test
+            .world
+            .resource::<UiDrags>()
+            .get(PointerId::Mouse)
+            .unwrap();
+        assert_eq!(drag.dragged, draggable);
+        assert_eq!(drag.payload, payload);
+        let ghost = drag.ghost.unwrap();
+        assert!(test.world.get::<DragGhost>(ghost).is_some());
+
+        // Hovering a descendant of the drop target is over the drop target.
+        test.hover(slot);
+        assert_eq!(test.update(), vec![("over", target)]);
+        assert_eq!(
+            test.world
+                .resource::<UiDrags>()
+                .get(PointerId::Mouse)
+                .unwrap()
+                .target,
+            Some(target)
+        );
+
+        test.send(
+            handle.unwrap(),
+            DragEnd {
+                button: PointerButton::Primary,
+                distance: Vec2::ZERO,
+            },
+        );
+        assert_eq!(test.update(), vec![("drop", target), ("end", draggable)]);
+        assert!(test
+            .world
+            .resource::<UiDrags>()
+            .get(PointerId::Mouse)
+            .is_none());
+        assert!(test.world.get_entity(ghost).is_err());
+    }
+
+    #[test]
+    fn canceled_drags_leave_their_target() {
+        let mut test = TestWorld::new();
+        let draggable = test
+            .world
+            .spawn(Draggable {
+                ghost: false,
+                ..Default::default()
+            })
+            .id();
+        let target = test.world.spawn(DropTarget).id();
+
+        let hit = test.hit();
+        test.send(
+            draggable,
+            DragStart {
+                button: PointerButton::Primary,
+                hit: hit.clone(),
+            },
+        );
+        test.hover(Some(target));
+        assert_eq!(test.update(), vec![("start", draggable), ("over", target)]);
+        assert!(test
+            .world
+            .resource::<UiDrags>()
+            .get(PointerId::Mouse)
+            .unwrap()
+            .ghost
+            .is_none());
+
+        test.send(draggable, Cancel { hit });
+        assert_eq!(test.update(), vec![("leave", target), ("end", draggable)]);
+        assert!(test
+            .world
+            .resource::<UiDrags>()
+            .get(PointerId::Mouse)
+            .is_none());
+    }
+
+    #[test]
+    fn only_the_drag_button_drags() {
+        let mut test = TestWorld::new();
+        let draggable = test.world.spawn(Draggable::default()).id();
+
+        let hit = test.hit();
+        test.send(
+            draggable,
+            DragStart {
+                button: PointerButton::Secondary,
+                hit: hit.clone(),
+            },
+        );
+        assert!(test.update().is_empty());
+        assert!(test
+            .world
+            .resource::<UiDrags>()
+            .get(PointerId::Mouse)
+            .is_none());
+
+        test.send(
+            draggable,
+            DragStart {
+                button: PointerButton::Primary,
+                hit,
+            },
+        );
+        assert_eq!(test.update(), vec![("start", draggable)]);
+
+        // Releasing another button doesn't end the drag.
+        test.send(
+            draggable,
+            DragEnd {
+                button: PointerButton::Secondary,
+                distance: Vec2::ZERO,
+            },
+        );
+        assert!(test.update().is_empty());
+        assert!(test
+            .world
+            .resource::<UiDrags>()
+            .get(PointerId::Mouse)
+            .is_some());
+    }
+}
@@ -10,13 +10,18 @@
 //! Spawn UI elements with [`widget::Button`], [`ImageNode`], [`Text`](prelude::Text) and [`Node`]
 //! This UI is laid out with the Flexbox and CSS Grid layout models (see <https://cssreference.io/flexbox/>)
 
+pub mod directional_navigation;
 pub mod measurement;
 pub mod ui_material;
 pub mod update;
 pub mod widget;
 
+#[cfg(feature = "bevy_ui_picking_backend")]
+pub mod drag_drop;
 #[cfg(feature = "bevy_ui_picking_backend")]
 pub mod picking_backend;
+#[cfg(feature = "bevy_ui_world_space")]
+pub mod world_space;
 
 use bevy_derive::{Deref, DerefMut};
 use bevy_reflect::{std_traits::ReflectDefault, Reflect};
@@ -29,6 +34,7 @@ mod geometry;
 mod layout;
 mod render;
 mod stack;
+mod transition;
 mod ui_node;
 
 pub use focus::*;
@@ -36,6 +42,7 @@ pub use geometry::*;
 pub use layout::*;
 pub use measurement::*;
 pub use render::*;
+pub use transition::*;
 pub use ui_material::*;
 pub use ui_node::*;
 
@@ -56,8 +63,8 @@ pub mod prelude {
             geometry::*,
             ui_material::*,
             ui_node::*,
-            widget::{Button, ImageNode, Label},
-            Interaction, MaterialNode, UiMaterialPlugin, UiScale,
+            widget::{Button, ImageNode, Label, VirtualList, VirtualListItem},
+            Interaction, MaterialNode, UiMaterialPlugin, UiScale, UiTransition,
         },
         // `bevy_sprite` re-exports for texture slicing
         bevy_sprite::{BorderRect, SliceScaleMode, SpriteImageMode, TextureSlicer},
@@ -72,7 +79,7 @@ use bevy_transform::TransformSystem;
 use layout::ui_surface::UiSurface;
 use stack::ui_stack_system;
 pub use stack::UiStack;
-use update::{update_clipping_system, update_target_camera_system};
+use update::{update_clipping_system, update_target_camera_system, update_ui_opacity_system};
 
 /// The basic plugin for Bevy UI
 pub struct UiPlugin {
@@ -158,11 +165,16 @@ impl Plugin for UiPlugin {
             .register_type::<ImageNodeSize>()
             .register_type::<UiRect>()
             .register_type::<UiScale>()
+            .register_type::<UiOpacity>()
+            .register_type::<ComputedUiOpacity>()
+            .register_type::<UiTransition>()
             .register_type::<BorderColor>()
             .register_type::<BorderRadius>()
             .register_type::<BoxShadow>()
             .register_type::<widget::Button>()
             .register_type::<widget::Label>()
+            .register_type::<widget::VirtualList>()
+            .register_type::<widget::VirtualListItem>()
             .register_type::<ZIndex>()
             .register_type::<Outline>()
             .register_type::<BoxShadowSamples>()
@@ -195,6 +207,11 @@ impl Plugin for UiPlugin {
             PostUpdate,
             (
                 update_target_camera_system.in_set(UiSystem::Prepare),
+                widget::update_virtual_lists.in_set(UiSystem::Prepare),
+                update_ui_transitions.in_set(UiSystem::Prepare),
+                update_ui_opacity_system
+                    .in_set(UiSystem::Prepare)
+                    .after(update_ui_transitions),
                 ui_layout_system_config,
                 ui_stack_system
                     .in_set(UiSystem::Stack)
@@ -217,9 +234,15 @@ impl Plugin for UiPlugin {
 
         #[cfg(feature = "bevy_ui_picking_backend")]
         if self.add_picking {
-            app.add_plugins(picking_backend::UiPickingPlugin);
+            app.add_plugins((
+                picking_backend::UiPickingPlugin,
+                drag_drop::UiDragDropPlugin,
+            ));
         }
 
+        #[cfg(feature = "bevy_ui_world_space")]
+        app.add_plugins(world_space::WorldSpaceUiPlugin);
+
         if !self.enable_rendering {
             return;
         }
@@ -261,6 +284,7 @@ fn build_text_interop(app: &mut App) {
             )
                 .chain()
                 .in_set(UiSystem::Prepare)
+                .after(bevy_text::update_inline_text_images)
                 // Text and Text2d are independent.
                 .ambiguous_with(bevy_text::detect_text_needs_rerender::<bevy_text::Text2d>)
                 // Potential conflict: `Assets<Image>`
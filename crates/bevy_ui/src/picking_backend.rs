@@ -18,6 +18,10 @@
 //! - `bevy_ui` can render on any camera with a flag, it is special, and is not tied to a particular
 //!   camera.
 //! - To correctly sort picks, the order of `bevy_ui` is set to be the camera order plus 0.5.
+//! - Text spans with a [`TextLink`] are hit above their text node when the pointer is over their
+//!   glyphs, so that pointer events target the span and then bubble up to the node.
+//! - Pointers can be forwarded to UI cameras that render to textures with [`ForwardedUiPointers`],
+//!   which is how world-space UI is picked.
 
 #![deny(missing_docs)]
 
@@ -26,6 +30,7 @@ use bevy_app::prelude::*;
 use bevy_ecs::{prelude::*, query::QueryData};
 use bevy_math::{Rect, Vec2};
 use bevy_render::prelude::*;
+use bevy_text::{TextLayoutInfo, TextLink};
 use bevy_transform::prelude::*;
 use bevy_utils::HashMap;
 use bevy_window::PrimaryWindow;
@@ -37,10 +42,31 @@ use bevy_picking::backend::prelude::*;
 pub struct UiPickingPlugin;
 impl Plugin for UiPickingPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(PreUpdate, ui_picking.in_set(PickSet::Backend));
+        app.init_resource::<ForwardedUiPointers>()
+            .add_systems(PreUpdate, ui_picking.in_set(PickSet::Backend));
     }
 }
 
+/// A pointer forwarded to a UI camera that doesn't render to the render target of the pointer,
+/// such as the camera of a [`WorldSpaceUi`](crate::world_space::WorldSpaceUi).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ForwardedUiPointer {
+    /// The position of the pointer in the viewport of the UI camera, in physical pixels.
+    pub position: Vec2,
+    /// The depth of the hits on the UI, as in [`HitData::depth`].
+    pub depth: f32,
+    /// The order of the hits on the UI, as in [`PointerHits::order`].
+    pub order: f32,
+}
+
+/// Pointers forwarded to UI cameras by UI camera and pointer, for the next run of [`ui_picking`].
+///
+/// Systems that forward pointers, for instance from a 3D scene to UI rendered to a texture in the
+/// scene, should insert them in [`PickSet::Backend`] before [`ui_picking`]. The pointers are
+/// cleared after each run.
+#[derive(Resource, Debug, Default)]
+pub struct ForwardedUiPointers(pub HashMap<(Entity, PointerId), ForwardedUiPointer>);
+
 /// Main query from bevy's `ui_focus_system`
 #[derive(QueryData)]
 #[query_data(mutable)]
@@ -52,6 +78,7 @@ pub struct NodeQuery {
     calculated_clip: Option<&'static CalculatedClip>,
     view_visibility: Option<&'static ViewVisibility>,
     target_camera: Option<&'static TargetCamera>,
+    text_layout_info: Option<&'static TextLayoutInfo>,
 }
 
 /// Computes the UI node entities under each pointer.
@@ -65,6 +92,8 @@ pub fn ui_picking(
     primary_window: Query<Entity, With<PrimaryWindow>>,
     ui_stack: Res<UiStack>,
     node_query: Query<NodeQuery>,
+    text_links: Query<(), With<TextLink>>,
+    mut forwarded_pointers: ResMut<ForwardedUiPointers>,
     mut output: EventWriter<PointerHits>,
 ) {
     // For each camera, the pointer and its position
@@ -106,6 +135,13 @@ pub fn ui_picking(
         }
     }
 
+    for ((camera, pointer_id), forwarded) in forwarded_pointers.0.iter() {
+        pointer_pos_by_camera
+            .entry(*camera)
+            .or_default()
+            .insert(*pointer_id, forwarded.position);
+    }
+
     // The list of node entities hovered for each (camera, pointer) combo
     let mut hit_nodes = HashMap::<(Entity, PointerId), Vec<Entity>>::default();
 
@@ -171,10 +207,22 @@ pub fn ui_picking(
                     node.node.border_radius,
                 )
             {
-                hit_nodes
-                    .entry((camera_entity, *pointer_id))
-                    .or_default()
-                    .push(*node_entity);
+                let hits = hit_nodes.entry((camera_entity, *pointer_id)).or_default();
+                // Links in text are hit above the text node.
+                if let Some(text_layout_info) = node.text_layout_info {
+                    let cursor_position_in_text = *cursor_position - node_rect.min;
+                    hits.extend(
+                        text_layout_info
+                            .section_rects
+                            .iter()
+                            .filter(|(span, rect)| {
+                                text_links.contains(*span) && rect.contains(cursor_position_in_text)
+                            })
+                            .map(|(span, _)| *span)
+                            .take(1),
+                    );
+                }
+                hits.push(*node_entity);
             }
         }
     }
@@ -183,9 +231,16 @@ pub fn ui_picking(
         // As soon as a node with a `Block` focus policy is detected, the iteration will stop on it
         // because it "captures" the interaction.
         let mut picks = Vec::new();
-        let mut depth = 0.0;
+        let forwarded = forwarded_pointers.0.get(&(*camera, *pointer));
+        let mut depth = forwarded.map_or(0.0, |forwarded| forwarded.depth);
 
-        for node in node_query.iter_many(hovered_nodes) {
+        for entity in hovered_nodes {
+            let Ok(node) = node_query.get(*entity) else {
+                // A text link, which lets the hit go through to its text node.
+                picks.push((*entity, HitData::new(*camera, depth, None, None)));
+                depth += 0.00001;
+                continue;
+            };
             let Some(camera_entity) = node
                 .target_camera
                 .map(TargetCamera::entity)
@@ -209,12 +264,19 @@ pub fn ui_picking(
             depth += 0.00001; // keep depth near 0 for precision
         }
 
-        let order = camera_query
-            .get(*camera)
-            .map(|(_, cam, _)| cam.order)
-            .unwrap_or_default() as f32
-            + 0.5; // bevy ui can run on any camera, it's a special case
+        let order = forwarded.map_or_else(
+            || {
+                camera_query
+                    .get(*camera)
+                    .map(|(_, cam, _)| cam.order)
+                    .unwrap_or_default() as f32
+                    + 0.5 // bevy ui can run on any camera, it's a special case
+            },
+            |forwarded| forwarded.order,
+        );
 
         output.send(PointerHits::new(*pointer, picks, order));
     }
+
+    forwarded_pointers.0.clear();
 }
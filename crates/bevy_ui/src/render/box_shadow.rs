@@ -3,8 +3,8 @@
 use core::{hash::Hash, ops::Range};
 
 use crate::{
-    BoxShadow, BoxShadowSamples, CalculatedClip, ComputedNode, DefaultUiCamera, RenderUiSystem,
-    ResolvedBorderRadius, TargetCamera, TransparentUi, Val,
+    BoxShadow, BoxShadowSamples, CalculatedClip, ComputedNode, ComputedUiOpacity, DefaultUiCamera,
+    RenderUiSystem, ResolvedBorderRadius, TargetCamera, TransparentUi, Val,
 };
 use bevy_app::prelude::*;
 use bevy_asset::*;
@@ -247,13 +247,15 @@ pub fn extract_shadows(
             &BoxShadow,
             Option<&CalculatedClip>,
             Option<&TargetCamera>,
+            Option<&ComputedUiOpacity>,
         )>,
     >,
     mapping: Extract<Query<RenderEntity>>,
 ) {
     let default_camera_entity = default_ui_camera.get();
 
-    for (entity, uinode, transform, view_visibility, box_shadow, clip, camera) in &box_shadow_query
+    for (entity, uinode, transform, view_visibility, box_shadow, clip, camera, opacity) in
+        &box_shadow_query
     {
         let Some(camera_entity) = camera.map(TargetCamera::entity).or(default_camera_entity) else {
             continue;
@@ -279,8 +281,11 @@ pub fn extract_shadows(
 
         let scale_factor = uinode.inverse_scale_factor.recip();
 
+        let opacity = opacity.copied().unwrap_or_default();
+
         for drop_shadow in box_shadow.iter() {
-            if drop_shadow.color.is_fully_transparent() {
+            let color = opacity.apply(drop_shadow.color);
+            if color.is_fully_transparent() {
                 continue;
             }
 
@@ -323,7 +328,7 @@ pub fn extract_shadows(
                     stack_index: uinode.stack_index,
                     transform: transform.compute_matrix()
                         * Mat4::from_translation(offset.extend(0.)),
-                    color: drop_shadow.color.into(),
+                    color: color.into(),
                     bounds: shadow_size + 6. * blur_radius,
                     clip: clip.map(|clip| clip.clip),
                     extracted_camera_entity,
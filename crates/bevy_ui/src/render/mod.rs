@@ -9,12 +9,12 @@ mod debug_overlay;
 
 use crate::widget::ImageNode;
 use crate::{
-    BackgroundColor, BorderColor, BoxShadowSamples, CalculatedClip, ComputedNode, DefaultUiCamera,
-    Outline, ResolvedBorderRadius, TargetCamera, UiAntiAlias,
+    BackgroundColor, BorderColor, BoxShadowSamples, CalculatedClip, ComputedNode,
+    ComputedUiOpacity, DefaultUiCamera, Outline, ResolvedBorderRadius, TargetCamera, UiAntiAlias,
 };
 use bevy_app::prelude::*;
 use bevy_asset::{load_internal_asset, AssetEvent, AssetId, Assets, Handle};
-use bevy_color::{Alpha, ColorToComponents, LinearRgba};
+use bevy_color::{Alpha, Color, ColorToComponents, LinearRgba};
 use bevy_core_pipeline::core_2d::graph::{Core2d, Node2d};
 use bevy_core_pipeline::core_3d::graph::{Core3d, Node3d};
 use bevy_core_pipeline::{core_2d::Camera2d, core_3d::Camera3d};
@@ -50,7 +50,7 @@ use bevy_sprite::{BorderRect, SpriteAssetEvents};
 pub use debug_overlay::UiDebugOptions;
 
 use crate::{Display, Node};
-use bevy_text::{ComputedTextBlock, PositionedGlyph, TextColor, TextLayoutInfo};
+use bevy_text::{ComputedTextBlock, InlineTextImage, PositionedGlyph, TextColor, TextLayoutInfo};
 use bevy_transform::components::GlobalTransform;
 use bevy_utils::{HashMap, HashSet};
 use box_shadow::BoxShadowPlugin;
@@ -289,12 +289,13 @@ pub fn extract_uinode_background_colors(
             Option<&CalculatedClip>,
             Option<&TargetCamera>,
             &BackgroundColor,
+            Option<&ComputedUiOpacity>,
         )>,
     >,
     mapping: Extract<Query<RenderEntity>>,
 ) {
     let default_camera_entity = default_ui_camera.get();
-    for (entity, uinode, transform, view_visibility, clip, camera, background_color) in
+    for (entity, uinode, transform, view_visibility, clip, camera, background_color, opacity) in
         &uinode_query
     {
         let Some(camera_entity) = camera.map(TargetCamera::entity).or(default_camera_entity) else {
@@ -305,8 +306,13 @@ pub fn extract_uinode_background_colors(
             continue;
         };
 
+        let color = opacity
+            .copied()
+            .unwrap_or_default()
+            .apply(background_color.0);
+
         // Skip invisible backgrounds
-        if !view_visibility.get() || background_color.0.is_fully_transparent() {
+        if !view_visibility.get() || color.is_fully_transparent() {
             continue;
         }
 
@@ -314,7 +320,7 @@ pub fn extract_uinode_background_colors(
             commands.spawn(TemporaryRenderEntity).id(),
             ExtractedUiNode {
                 stack_index: uinode.stack_index,
-                color: background_color.0.into(),
+                color: color.into(),
                 rect: Rect {
                     min: Vec2::ZERO,
                     max: uinode.size,
@@ -351,12 +357,14 @@ pub fn extract_uinode_images(
             Option<&CalculatedClip>,
             Option<&TargetCamera>,
             &ImageNode,
+            Option<&ComputedUiOpacity>,
         )>,
     >,
     mapping: Extract<Query<RenderEntity>>,
 ) {
     let default_camera_entity = default_ui_camera.get();
-    for (entity, uinode, transform, view_visibility, clip, camera, image) in &uinode_query {
+    for (entity, uinode, transform, view_visibility, clip, camera, image, opacity) in &uinode_query
+    {
         let Some(camera_entity) = camera.map(TargetCamera::entity).or(default_camera_entity) else {
             continue;
         };
@@ -365,9 +373,11 @@ pub fn extract_uinode_images(
             continue;
         };
 
+        let color = opacity.copied().unwrap_or_default().apply(image.color);
+
         // Skip invisible images
         if !view_visibility.get()
-            || image.color.is_fully_transparent()
+            || color.is_fully_transparent()
             || image.image.id() == TRANSPARENT_IMAGE_HANDLE.id()
             || image.image_mode.uses_slices()
         {
@@ -407,7 +417,7 @@ pub fn extract_uinode_images(
             commands.spawn(TemporaryRenderEntity).id(),
             ExtractedUiNode {
                 stack_index: uinode.stack_index,
-                color: image.color.into(),
+                color: color.into(),
                 rect,
                 clip: clip.map(|clip| clip.clip),
                 image: image.image.id(),
@@ -441,6 +451,7 @@ pub fn extract_uinode_borders(
             Option<&CalculatedClip>,
             Option<&TargetCamera>,
             AnyOf<(&BorderColor, &Outline)>,
+            Option<&ComputedUiOpacity>,
         )>,
     >,
     mapping: Extract<Query<RenderEntity>>,
@@ -456,8 +467,11 @@ pub fn extract_uinode_borders(
         maybe_clip,
         maybe_camera,
         (maybe_border_color, maybe_outline),
+        maybe_opacity,
     ) in &uinode_query
     {
+        let opacity = maybe_opacity.copied().unwrap_or_default();
+
         let Some(camera_entity) = maybe_camera
             .map(TargetCamera::entity)
             .or(default_camera_entity)
@@ -476,13 +490,15 @@ pub fn extract_uinode_borders(
 
         // Don't extract borders with zero width along all edges
         if computed_node.border() != BorderRect::ZERO {
-            if let Some(border_color) = maybe_border_color.filter(|bc| !bc.0.is_fully_transparent())
+            if let Some(border_color) = maybe_border_color
+                .map(|border_color| opacity.apply(border_color.0))
+                .filter(|color| !color.is_fully_transparent())
             {
                 extracted_uinodes.uinodes.insert(
                     commands.spawn(TemporaryRenderEntity).id(),
                     ExtractedUiNode {
                         stack_index: computed_node.stack_index,
-                        color: border_color.0.into(),
+                        color: border_color.into(),
                         rect: Rect {
                             max: computed_node.size(),
                             ..Default::default()
@@ -509,14 +525,16 @@ pub fn extract_uinode_borders(
             continue;
         }
 
-        if let Some(outline) = maybe_outline.filter(|outline| !outline.color.is_fully_transparent())
+        if let Some(outline_color) = maybe_outline
+            .map(|outline| opacity.apply(outline.color))
+            .filter(|color| !color.is_fully_transparent())
         {
             let outline_size = computed_node.outlined_node_size();
             extracted_uinodes.uinodes.insert(
                 commands.spawn(TemporaryRenderEntity).id(),
                 ExtractedUiNode {
                     stack_index: computed_node.stack_index,
-                    color: outline.color.into(),
+                    color: outline_color.into(),
                     rect: Rect {
                         max: outline_size,
                         ..Default::default()
@@ -681,9 +699,11 @@ pub fn extract_text_sections(
             Option<&TargetCamera>,
             &ComputedTextBlock,
             &TextLayoutInfo,
+            Option<&ComputedUiOpacity>,
         )>,
     >,
     text_styles: Extract<Query<&TextColor>>,
+    inline_images: Extract<Query<&InlineTextImage>>,
     mapping: Extract<Query<RenderEntity>>,
 ) {
     let mut start = 0;
@@ -699,8 +719,11 @@ pub fn extract_text_sections(
         camera,
         computed_block,
         text_layout_info,
+        opacity,
     ) in &uinode_query
     {
+        let opacity = opacity.copied().unwrap_or_default();
+
         let Some(camera_entity) = camera.map(TargetCamera::entity).or(default_ui_camera) else {
             continue;
         };
@@ -738,7 +761,7 @@ pub fn extract_text_sections(
                             .map(|t| t.entity)
                             .unwrap_or(Entity::PLACEHOLDER),
                     )
-                    .map(|text_color| LinearRgba::from(text_color.0))
+                    .map(|text_color| LinearRgba::from(opacity.apply(text_color.0)))
                     .unwrap_or_default();
                 current_span = *span_index;
             }
@@ -754,7 +777,9 @@ pub fn extract_text_sections(
             });
 
             if text_layout_info.glyphs.get(i + 1).is_none_or(|info| {
-                info.span_index != current_span || info.atlas_info.texture != atlas_info.texture
+                info.span_index != current_span
+                    || info.atlas_info.texture != atlas_info.texture
+                    || info.atlas_info.location.is_color != atlas_info.location.is_color
             }) {
                 let id = commands.spawn(TemporaryRenderEntity).id();
 
@@ -762,7 +787,12 @@ pub fn extract_text_sections(
                     id,
                     ExtractedUiNode {
                         stack_index: uinode.stack_index,
-                        color,
+                        // Color glyphs, like emoji, keep their own colors.
+                        color: if atlas_info.location.is_color {
+                            LinearRgba::WHITE.with_alpha(color.alpha)
+                        } else {
+                            color
+                        },
                         image: atlas_info.texture.id(),
                         clip: clip.map(|clip| clip.clip),
                         extracted_camera_entity,
@@ -776,6 +806,39 @@ pub fn extract_text_sections(
 
             end += 1;
         }
+
+        for (span_entity, rect) in &text_layout_info.inline_images {
+            let Ok(inline_image) = inline_images.get(*span_entity) else {
+                continue;
+            };
+            extracted_uinodes.uinodes.insert(
+                commands.spawn(TemporaryRenderEntity).id(),
+                ExtractedUiNode {
+                    stack_index: uinode.stack_index,
+                    color: opacity.apply(Color::WHITE).into(),
+                    rect: Rect {
+                        min: Vec2::ZERO,
+                        max: rect.size(),
+                    },
+                    clip: clip.map(|clip| clip.clip),
+                    image: inline_image.id(),
+                    extracted_camera_entity,
+                    item: ExtractedUiItem::Node {
+                        atlas_scaling: None,
+                        transform: Mat4::from(
+                            transform
+                                * bevy_math::Affine3A::from_translation(rect.center().extend(0.)),
+                        ),
+                        flip_x: false,
+                        flip_y: false,
+                        border: BorderRect::ZERO,
+                        border_radius: ResolvedBorderRadius::ZERO,
+                        node_type: NodeType::Rect,
+                    },
+                    main_entity: entity.into(),
+                },
+            );
+        }
     }
 }
 
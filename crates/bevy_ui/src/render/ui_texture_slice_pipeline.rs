@@ -258,13 +258,15 @@ pub fn extract_ui_texture_slices(
             Option<&CalculatedClip>,
             Option<&TargetCamera>,
             &ImageNode,
+            Option<&ComputedUiOpacity>,
         )>,
     >,
     mapping: Extract<Query<RenderEntity>>,
 ) {
     let default_camera_entity = default_ui_camera.get();
 
-    for (entity, uinode, transform, view_visibility, clip, camera, image) in &slicers_query {
+    for (entity, uinode, transform, view_visibility, clip, camera, image, opacity) in &slicers_query
+    {
         let Some(camera_entity) = camera.map(TargetCamera::entity).or(default_camera_entity) else {
             continue;
         };
@@ -289,9 +291,11 @@ pub fn extract_ui_texture_slices(
             _ => continue,
         };
 
+        let color = opacity.copied().unwrap_or_default().apply(image.color);
+
         // Skip invisible images
         if !view_visibility.get()
-            || image.color.is_fully_transparent()
+            || color.is_fully_transparent()
             || image.image.id() == TRANSPARENT_IMAGE_HANDLE.id()
         {
             continue;
@@ -319,7 +323,7 @@ pub fn extract_ui_texture_slices(
             ExtractedUiTextureSlice {
                 stack_index: uinode.stack_index,
                 transform: transform.compute_matrix(),
-                color: image.color.into(),
+                color: color.into(),
                 rect: Rect {
                     min: Vec2::ZERO,
                     max: uinode.size,
@@ -750,22 +754,25 @@ fn compute_texture_slices(
     image_scale_mode: &SpriteImageMode,
 ) -> [[f32; 4]; 3] {
     match image_scale_mode {
-        SpriteImageMode::Sliced(TextureSlicer {
-            border: border_rect,
-            center_scale_mode,
-            sides_scale_mode,
-            max_corner_scale,
-        }) => {
+        SpriteImageMode::Sliced(
+            slicer @ TextureSlicer {
+                border: border_rect,
+                center_scale_mode,
+                sides_scale_mode,
+                max_corner_scale,
+            },
+        ) => {
             let min_coeff = (target_size / image_size)
                 .min_element()
                 .min(*max_corner_scale);
 
             // calculate the normalized extents of the nine-patched image slices
+            let uv_insets = slicer.uv_insets(image_size);
             let slices = [
-                border_rect.left / image_size.x,
-                border_rect.top / image_size.y,
-                1. - border_rect.right / image_size.x,
-                1. - border_rect.bottom / image_size.y,
+                uv_insets.left,
+                uv_insets.top,
+                1. - uv_insets.right,
+                1. - uv_insets.bottom,
             ];
 
             // calculate the normalized extents of the target slices
@@ -784,13 +791,21 @@ fn compute_texture_slices(
             // compute the number of times to repeat the side and center slices when tiling along each axis
             // if the returned value is `1.` the slice will be stretched to fill the axis.
             let repeat_side_x =
-                compute_tiled_subaxis(image_side_width, target_side_width, sides_scale_mode);
-            let repeat_side_y =
-                compute_tiled_subaxis(image_side_height, target_side_height, sides_scale_mode);
+                compute_tiled_subaxis(image_side_width, target_side_width, sides_scale_mode, true);
+            let repeat_side_y = compute_tiled_subaxis(
+                image_side_height,
+                target_side_height,
+                sides_scale_mode,
+                false,
+            );
             let repeat_center_x =
-                compute_tiled_subaxis(image_side_width, target_side_width, center_scale_mode);
-            let repeat_center_y =
-                compute_tiled_subaxis(image_side_height, target_side_height, center_scale_mode);
+                compute_tiled_subaxis(image_side_width, target_side_width, center_scale_mode, true);
+            let repeat_center_y = compute_tiled_subaxis(
+                image_side_height,
+                target_side_height,
+                center_scale_mode,
+                false,
+            );
 
             [
                 slices,
@@ -827,12 +842,17 @@ fn compute_tiled_axis(tile: bool, image_extent: f32, target_extent: f32, stretch
     }
 }
 
-fn compute_tiled_subaxis(image_extent: f32, target_extent: f32, mode: &SliceScaleMode) -> f32 {
-    match mode {
-        SliceScaleMode::Stretch => 1.,
-        SliceScaleMode::Tile { stretch_value } => {
-            let s = image_extent * *stretch_value;
-            target_extent / s
+fn compute_tiled_subaxis(
+    image_extent: f32,
+    target_extent: f32,
+    mode: &SliceScaleMode,
+    horizontal: bool,
+) -> f32 {
+    match mode.tiling() {
+        None => 1.,
+        Some((stretch_value, (tile_x, tile_y))) => {
+            let tile = if horizontal { tile_x } else { tile_y };
+            compute_tiled_axis(tile, image_extent, target_extent, stretch_value)
         }
     }
 }
@@ -0,0 +1,385 @@
+//! Transitions that animate the style of UI nodes when it changes.
+
+use core::time::Duration;
+
+use crate::{BackgroundColor, Node, UiOpacity, Val};
+use bevy_color::{Color, Mix};
+use bevy_ecs::prelude::*;
+use bevy_math::{
+    curve::{Curve, EaseFunction, EasingCurve},
+    FloatExt,
+};
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_time::Time;
+
+/// How long a [`UiTransition`] of a property lasts, and how it eases between the old and the new
+/// value.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Debug, PartialEq)]
+pub struct TransitionTiming {
+    /// The duration of the transition.
+    pub duration: Duration,
+    /// The easing curve of the transition.
+    pub ease: EaseFunction,
+}
+
+impl TransitionTiming {
+    /// Makes a new transition timing.
+    pub const fn new(duration: Duration, ease: EaseFunction) -> Self {
+        Self { duration, ease }
+    }
+}
+
+/// Animates style properties of a UI node when they are set to new values.
+///
+/// When a transitioned property of the node changes, the property is set back to the value on
+/// screen, then animated towards the new value over the [`TransitionTiming`] of the property. If
+/// the property changes again during the transition, the transition restarts towards the newest
+/// value from wherever it got to. This makes hover and focus effects smooth without per-widget
+/// animation systems: a system can simply set the style of the node from its [`Interaction`].
+///
+/// The transitioned properties are:
+/// - `size`: the `width` and `height` of the [`Node`].
+/// - `position`: the `left`, `right`, `top` and `bottom` of the [`Node`].
+/// - `background_color`: the [`BackgroundColor`] of the node.
+/// - `opacity`: the [`UiOpacity`] of the node.
+///
+/// [`Val`]s are only interpolated between values of the same unit. A transition between different
+/// units, like [`Val::Auto`] and [`Val::Px`], jumps to the new value.
+///
+/// ```
+/// # use core::time::Duration;
+/// # use bevy_color::palettes::basic::{BLUE, NAVY};
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_math::curve::EaseFunction;
+/// # use bevy_ui::prelude::*;
+/// fn setup(mut commands: Commands) {
+///     commands.spawn((
+///         Button,
+///         BackgroundColor(NAVY.into()),
+///         UiTransition::all(Duration::from_millis(150), EaseFunction::CubicOut),
+///     ));
+/// }
+///
+/// fn highlight_buttons(
+///     mut buttons: Query<(&Interaction, &mut BackgroundColor), Changed<Interaction>>,
+/// ) {
+///     for (interaction, mut background_color) in &mut buttons {
+///         background_color.0 = match interaction {
+///             Interaction::None => NAVY.into(),
+///             _ => BLUE.into(),
+///         };
+///     }
+/// }
+/// ```
+///
+/// [`Interaction`]: crate::Interaction
+#[derive(Component, Debug, Clone, Default, Reflect)]
+#[reflect(Component, Default, Debug)]
+#[require(Node)]
+pub struct UiTransition {
+    /// The transition of the `width` and `height` of the node, if any.
+    pub size: Option<TransitionTiming>,
+    /// The transition of the `left`, `right`, `top` and `bottom` of the node, if any.
+    pub position: Option<TransitionTiming>,
+    /// The transition of the [`BackgroundColor`] of the node, if any.
+    pub background_color: Option<TransitionTiming>,
+    /// The transition of the [`UiOpacity`] of the node, if any.
+    pub opacity: Option<TransitionTiming>,
+    #[reflect(ignore)]
+    state: UiTransitionState,
+}
+
+impl UiTransition {
+    /// Transitions all the properties with the same timing.
+    pub fn all(duration: Duration, ease: EaseFunction) -> Self {
+        let timing = Some(TransitionTiming::new(duration, ease));
+        Self {
+            size: timing,
+            position: timing,
+            background_color: timing,
+            opacity: timing,
+            ..Default::default()
+        }
+    }
+
+    /// Returns this transition with the `width` and `height` of the node transitioned.
+    pub fn with_size(mut self, duration: Duration, ease: EaseFunction) -> Self {
+        self.size = Some(TransitionTiming::new(duration, ease));
+        self
+    }
+
+    /// Returns this transition with the `left`, `right`, `top` and `bottom` of the node
+    /// transitioned.
+    pub fn with_position(mut self, duration: Duration, ease: EaseFunction) -> Self {
+        self.position = Some(TransitionTiming::new(duration, ease));
+        self
+    }
+
+    /// Returns this transition with the [`BackgroundColor`] of the node transitioned.
+    pub fn with_background_color(mut self, duration: Duration, ease: EaseFunction) -> Self {
+        self.background_color = Some(TransitionTiming::new(duration, ease));
+        self
+    }
+
+    /// Returns this transition with the [`UiOpacity`] of the node transitioned.
+    pub fn with_opacity(mut self, duration: Duration, ease: EaseFunction) -> Self {
+        self.opacity = Some(TransitionTiming::new(duration, ease));
+        self
+    }
+
+    /// Returns true while any property of the node is transitioning.
+    pub fn is_active(&self) -> bool {
+        self.state.size.active.is_some()
+            || self.state.position.active.is_some()
+            || self.state.background_color.active.is_some()
+            || self.state.opacity.active.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct UiTransitionState {
+    size: PropertyTransition<[Val; 2]>,
+    position: PropertyTransition<[Val; 4]>,
+    background_color: PropertyTransition<Color>,
+    opacity: PropertyTransition<f32>,
+}
+
+/// The transition of one property.
+#[derive(Debug, Clone)]
+struct PropertyTransition<T> {
+    /// The value on screen, as last seen or written by the transition.
+    current: Option<T>,
+    /// The start and target values, and the time elapsed since the start.
+    active: Option<(T, T, Duration)>,
+}
+
+impl<T> Default for PropertyTransition<T> {
+    fn default() -> Self {
+        Self {
+            current: None,
+            active: None,
+        }
+    }
+}
+
+impl<T: Copy + PartialEq> PropertyTransition<T> {
+    /// Advances the transition by `delta` given the `value` of the property, and returns the value
+    /// to set the property to, if it has to change.
+    fn update(
+        &mut self,
+        value: T,
+        timing: Option<TransitionTiming>,
+        delta: Duration,
+        interpolate: impl Fn(T, T, f32) -> T,
+    ) -> Option<T> {
+        let (Some(timing), Some(current)) = (timing, self.current) else {
+            self.current = Some(value);
+            self.active = None;
+            return None;
+        };
+        let is_active_target = self.active.is_some_and(|(_, target, _)| target == value);
+        if value != current && !is_active_target {
+            // The property was set to a new value, which becomes the target.
+            self.active = Some((current, value, Duration::ZERO));
+        }
+        let (start, target, elapsed) = self.active.as_mut()?;
+        *elapsed += delta;
+        let t = if timing.duration.is_zero() {
+            1.
+        } else {
+            elapsed.as_secs_f32() / timing.duration.as_secs_f32()
+        };
+        let next = if t >= 1. {
+            let target = *target;
+            self.active = None;
+            target
+        } else {
+            let t = EasingCurve::new(0., 1., timing.ease).sample_clamped(t);
+            interpolate(*start, *target, t)
+        };
+        self.current = Some(next);
+        (next != value).then_some(next)
+    }
+}
+
+fn interpolate_val(start: Val, end: Val, t: f32) -> Val {
+    match (start, end) {
+        (Val::Px(a), Val::Px(b)) => Val::Px(a.lerp(b, t)),
+        (Val::Percent(a), Val::Percent(b)) => Val::Percent(a.lerp(b, t)),
+        (Val::Vw(a), Val::Vw(b)) => Val::Vw(a.lerp(b, t)),
+        (Val::Vh(a), Val::Vh(b)) => Val::Vh(a.lerp(b, t)),
+        (Val::VMin(a), Val::VMin(b)) => Val::VMin(a.lerp(b, t)),
+        (Val::VMax(a), Val::VMax(b)) => Val::VMax(a.lerp(b, t)),
+        _ => end,
+    }
+}
+
+fn interpolate_vals<const N: usize>(start: [Val; N], end: [Val; N], t: f32) -> [Val; N] {
+    core::array::from_fn(|i| interpolate_val(start[i], end[i], t))
+}
+
+/// Advances the [`UiTransition`]s of UI nodes, and starts transitions of the properties that
+/// changed.
+pub fn update_ui_transitions(
+    time: Res<Time>,
+    mut query: Query<(
+        &mut UiTransition,
+        &mut Node,
+        Option<&mut BackgroundColor>,
+        Option<&mut UiOpacity>,
+    )>,
+) {
+    let delta = time.delta();
+    for (mut transition, mut node, background_color, opacity) in &mut query {
+        let transition = transition.bypass_change_detection();
+        let state = &mut transition.state;
+
+        let size = [node.width, node.height];
+        if let Some([width, height]) =
+            state
+                .size
+                .update(size, transition.size, delta, interpolate_vals)
+        {
+            node.width = width;
+            node.height = height;
+        }
+
+        let position = [node.left, node.right, node.top, node.bottom];
+        if let Some([left, right, top, bottom]) =
+            state
+                .position
+                .update(position, transition.position, delta, interpolate_vals)
+        {
+            node.left = left;
+            node.right = right;
+            node.top = top;
+            node.bottom = bottom;
+        }
+
+        if let Some(mut background_color) = background_color {
+            if let Some(color) = state.background_color.update(
+                background_color.0,
+                transition.background_color,
+                delta,
+                |start, end, t| start.mix(&end, t),
+            ) {
+                background_color.0 = color;
+            }
+        }
+
+        if let Some(mut opacity) = opacity {
+            if let Some(value) =
+                state
+                    .opacity
+                    .update(opacity.0, transition.opacity, delta, FloatExt::lerp)
+            {
+                opacity.0 = value;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use bevy_math::curve::EaseFunction;
+    use bevy_math::FloatExt;
+
+    use super::{interpolate_val, PropertyTransition, TransitionTiming};
+    use crate::Val;
+
+    #[test]
+    fn property_transitions_to_new_values() {
+        let timing = Some(TransitionTiming::new(
+            Duration::from_secs(1),
+            EaseFunction::Linear,
+        ));
+        let step = Duration::from_millis(250);
+        let mut transition = PropertyTransition::<f32>::default();
+
+        // The first value is taken as is.
+        assert_eq!(transition.update(0., timing, step, FloatExt::lerp), None);
+
+        // A new value is reset to the value on screen, then animated towards.
+        assert_eq!(
+            transition.update(1., timing, step, FloatExt::lerp),
+            Some(0.25)
+        );
+        assert_eq!(
+            transition.update(0.25, timing, step, FloatExt::lerp),
+            Some(0.5)
+        );
+
+        // A change during the transition restarts it from the value on screen.
+        assert_eq!(
+            transition.update(0., timing, step, FloatExt::lerp),
+            Some(0.375)
+        );
+        assert_eq!(
+            transition.update(0.375, timing, step, FloatExt::lerp),
+            Some(0.25)
+        );
+        assert_eq!(
+            transition.update(0.25, timing, step, FloatExt::lerp),
+            Some(0.125)
+        );
+        assert_eq!(
+            transition.update(0.125, timing, step, FloatExt::lerp),
+            Some(0.)
+        );
+        assert!(transition.active.is_none());
+        assert_eq!(transition.update(0., timing, step, FloatExt::lerp), None);
+
+        // Properties without a transition change immediately.
+        assert_eq!(transition.update(1., None, step, FloatExt::lerp), None);
+    }
+
+    #[test]
+    fn writing_the_target_again_continues_the_transition() {
+        let timing = Some(TransitionTiming::new(
+            Duration::from_secs(1),
+            EaseFunction::Linear,
+        ));
+        let step = Duration::from_millis(250);
+        let mut transition = PropertyTransition::<f32>::default();
+        transition.update(0., timing, step, FloatExt::lerp);
+        assert_eq!(
+            transition.update(1., timing, step, FloatExt::lerp),
+            Some(0.25)
+        );
+
+        // Systems that write the same target every frame don't restart the transition.
+        assert_eq!(
+            transition.update(1., timing, step, FloatExt::lerp),
+            Some(0.5)
+        );
+        assert_eq!(
+            transition.update(1., timing, step, FloatExt::lerp),
+            Some(0.75)
+        );
+        assert_eq!(
+            transition.update(1., timing, step, FloatExt::lerp),
+            Some(1.)
+        );
+        assert!(transition.active.is_none());
+    }
+
+    #[test]
+    fn vals_only_interpolate_between_the_same_units() {
+        assert_eq!(
+            interpolate_val(Val::Px(10.), Val::Px(20.), 0.5),
+            Val::Px(15.)
+        );
+        assert_eq!(
+            interpolate_val(Val::Percent(0.), Val::Percent(100.), 0.25),
+            Val::Percent(25.)
+        );
+        assert_eq!(interpolate_val(Val::Auto, Val::Px(20.), 0.5), Val::Px(20.));
+        assert_eq!(
+            interpolate_val(Val::Px(10.), Val::Vw(20.), 0.5),
+            Val::Vw(20.)
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{FocusPolicy, UiRect, Val};
-use bevy_color::Color;
+use bevy_color::{Alpha, Color};
 use bevy_derive::{Deref, DerefMut};
 use bevy_ecs::{prelude::*, system::SystemParam};
 use bevy_math::{vec4, Rect, Vec2, Vec4Swizzles};
@@ -2030,6 +2030,61 @@ impl Default for BorderColor {
     }
 }
 
+/// The opacity of the UI node, from `0.0` for fully transparent to `1.0` for fully opaque.
+///
+/// Scales the alpha of the background, border, outline, shadow, image and text colors of the node
+/// and of its descendants, multiplying with the opacities of their ancestors. The result is kept in
+/// the [`ComputedUiOpacity`] of each node.
+#[derive(Component, Copy, Clone, Debug, PartialEq, Deref, DerefMut, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub struct UiOpacity(pub f32);
+
+impl UiOpacity {
+    /// Nodes are fully opaque by default.
+    pub const DEFAULT: Self = Self(1.);
+
+    /// Returns the color with its alpha scaled by this opacity.
+    pub fn apply(&self, color: Color) -> Color {
+        color.with_alpha(color.alpha() * self.0.clamp(0., 1.))
+    }
+}
+
+impl Default for UiOpacity {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// The opacity a UI node is drawn with: its [`UiOpacity`] multiplied by the [`UiOpacity`] of its
+/// ancestors.
+///
+/// This is inserted and updated automatically on the nodes that aren't fully opaque, and removed
+/// when they become fully opaque again.
+#[derive(Component, Copy, Clone, Debug, PartialEq, Deref, Reflect)]
+#[reflect(Component, Default, Debug, PartialEq)]
+pub struct ComputedUiOpacity(pub(crate) f32);
+
+impl ComputedUiOpacity {
+    /// Fully opaque, as nodes without this component are drawn.
+    pub const OPAQUE: Self = Self(1.);
+
+    /// Returns the color with its alpha scaled by this opacity.
+    pub fn apply(&self, color: Color) -> Color {
+        color.with_alpha(color.alpha() * self.0)
+    }
+}
+
+impl Default for ComputedUiOpacity {
+    fn default() -> Self {
+        Self::OPAQUE
+    }
+}
+
 #[derive(Component, Copy, Clone, Default, Debug, PartialEq, Reflect)]
 #[reflect(Component, Default, Debug, PartialEq)]
 #[cfg_attr(
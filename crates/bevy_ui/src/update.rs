@@ -2,7 +2,7 @@
 
 use crate::{
     experimental::{UiChildren, UiRootNodes},
-    CalculatedClip, Display, Node, OverflowAxis, TargetCamera,
+    CalculatedClip, ComputedUiOpacity, Display, Node, OverflowAxis, TargetCamera, UiOpacity,
 };
 
 use super::ComputedNode;
@@ -16,6 +16,54 @@ use bevy_sprite::BorderRect;
 use bevy_transform::components::GlobalTransform;
 use bevy_utils::HashSet;
 
+/// Updates the [`ComputedUiOpacity`] of all nodes from the [`UiOpacity`] of their ancestors
+pub fn update_ui_opacity_system(
+    mut commands: Commands,
+    root_nodes: UiRootNodes,
+    mut node_query: Query<(Option<&UiOpacity>, Option<&mut ComputedUiOpacity>), With<Node>>,
+    ui_children: UiChildren,
+) {
+    for root_node in root_nodes.iter() {
+        update_ui_opacity(&mut commands, &ui_children, &mut node_query, root_node, 1.);
+    }
+}
+
+fn update_ui_opacity(
+    commands: &mut Commands,
+    ui_children: &UiChildren,
+    node_query: &mut Query<(Option<&UiOpacity>, Option<&mut ComputedUiOpacity>), With<Node>>,
+    entity: Entity,
+    inherited_opacity: f32,
+) {
+    let Ok((maybe_opacity, maybe_computed_opacity)) = node_query.get_mut(entity) else {
+        return;
+    };
+
+    let opacity = inherited_opacity * maybe_opacity.map_or(1., |opacity| opacity.0.clamp(0., 1.));
+
+    // Only the nodes that aren't fully opaque keep a ComputedUiOpacity component
+    match (maybe_computed_opacity, opacity < 1.) {
+        (Some(mut computed_opacity), true) => {
+            if computed_opacity.0 != opacity {
+                computed_opacity.0 = opacity;
+            }
+        }
+        (Some(_), false) => {
+            commands.entity(entity).remove::<ComputedUiOpacity>();
+        }
+        (None, true) => {
+            commands
+                .entity(entity)
+                .try_insert(ComputedUiOpacity(opacity));
+        }
+        (None, false) => {}
+    }
+
+    for child in ui_children.iter_ui_children(entity) {
+        update_ui_opacity(commands, ui_children, node_query, child, opacity);
+    }
+}
+
 /// Updates clipping for all nodes
 pub fn update_clipping_system(
     mut commands: Commands,
@@ -216,3 +264,56 @@ fn update_children_target_camera(
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::{schedule::Schedule, world::World};
+    use bevy_hierarchy::BuildChildren;
+
+    use super::update_ui_opacity_system;
+    use crate::{ComputedUiOpacity, Node, UiOpacity};
+
+    #[test]
+    fn ui_opacity_multiplies_with_the_ancestors() {
+        let mut world = World::new();
+        let mut schedule = Schedule::default();
+        schedule.add_systems(update_ui_opacity_system);
+
+        let mut child = None;
+        let mut grandchild = None;
+        let root = world
+            .spawn((Node::default(), UiOpacity(0.5)))
+            .with_children(|builder| {
+                child = Some(
+                    builder
+                        .spawn((Node::default(), UiOpacity(0.5)))
+                        .with_children(|builder| {
+                            grandchild = Some(builder.spawn(Node::default()).id());
+                        })
+                        .id(),
+                );
+            })
+            .id();
+        let (child, grandchild) = (child.unwrap(), grandchild.unwrap());
+        schedule.run(&mut world);
+
+        let computed_opacity =
+            |world: &World, entity| world.get::<ComputedUiOpacity>(entity).copied();
+        assert_eq!(computed_opacity(&world, root), Some(ComputedUiOpacity(0.5)));
+        assert_eq!(
+            computed_opacity(&world, child),
+            Some(ComputedUiOpacity(0.25))
+        );
+        assert_eq!(
+            computed_opacity(&world, grandchild),
+            Some(ComputedUiOpacity(0.25))
+        );
+
+        // Fully opaque nodes don't keep the component.
+        world.entity_mut(root).insert(UiOpacity(1.));
+        world.entity_mut(child).insert(UiOpacity(1.));
+        schedule.run(&mut world);
+        assert_eq!(computed_opacity(&world, root), None);
+        assert_eq!(computed_opacity(&world, grandchild), None);
+    }
+}
@@ -146,7 +146,9 @@ pub enum NodeImageMode {
     Auto,
     /// The image will be resized to match the size of the node. The image's original size and aspect ratio will be ignored.
     Stretch,
-    /// The texture will be cut in 9 slices, keeping the texture in proportions on resize
+    /// The texture will be cut in 9 slices, keeping the texture in proportions on resize.
+    ///
+    /// The center and sides can be stretched or tiled along each axis, see [`SliceScaleMode`](bevy_sprite::SliceScaleMode).
     Sliced(TextureSlicer),
     /// The texture will be repeated if stretched beyond `stretched_value`
     Tiled {
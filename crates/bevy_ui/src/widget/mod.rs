@@ -3,11 +3,13 @@
 mod button;
 mod image;
 mod label;
+mod virtual_list;
 
 mod text;
 
 pub use button::*;
 pub use image::*;
 pub use label::*;
+pub use virtual_list::*;
 
 pub use text::*;
@@ -0,0 +1,343 @@
+use core::{marker::PhantomData, ops::Range};
+
+use crate::{ComputedNode, Display, Node, PositionType, ScrollPosition, UiSystem, Val};
+use bevy_app::{App, Plugin, PostUpdate};
+use bevy_ecs::prelude::*;
+use bevy_hierarchy::BuildChildren;
+use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+use bevy_utils::HashSet;
+
+/// A scrollable list or grid that only spawns UI nodes for the items in view.
+///
+/// The list lays out [`VirtualList::item_count`] items in rows of [`VirtualList::columns`] items,
+/// each [`VirtualList::item_height`] logical pixels high, and spawns a child node with a
+/// [`VirtualListItem`] for each item that is visible at the current [`ScrollPosition`] of the list.
+/// When an item scrolls out of view, its node is recycled for an item that scrolls into view by
+/// changing its [`VirtualListItem::index`], so that thousands of items only cost as many nodes as
+/// fit in the list.
+///
+/// The content of the items can be filled in by systems that react to added or changed
+/// [`VirtualListItem`]s, or by binding a [`VirtualListSource`] to the list.
+///
+/// The list should be given a size and `overflow: Overflow::scroll_y()` in its [`Node`], and
+/// shouldn't have other children.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component, Default, Debug)]
+#[require(Node)]
+pub struct VirtualList {
+    /// The number of items in the list.
+    pub item_count: usize,
+    /// The height of each row of items, in logical pixels.
+    pub item_height: f32,
+    /// The number of items in each row. Lists have `1` column and grids more.
+    pub columns: usize,
+    /// The number of rows that are spawned beyond each edge of the view, so that scrolling doesn't
+    /// show rows that aren't ready yet.
+    pub overscan: usize,
+    /// The item nodes of the list, visible or not.
+    #[reflect(ignore)]
+    items: Vec<Entity>,
+    /// The node that gives the list the height of all its rows, so that it scrolls as far.
+    #[reflect(ignore)]
+    spacer: Option<Entity>,
+}
+
+impl Default for VirtualList {
+    fn default() -> Self {
+        Self {
+            item_count: 0,
+            item_height: 20.,
+            columns: 1,
+            overscan: 2,
+            items: Vec::new(),
+            spacer: None,
+        }
+    }
+}
+
+impl VirtualList {
+    /// Makes a list of `item_count` items, each `item_height` logical pixels high.
+    pub fn new(item_count: usize, item_height: f32) -> Self {
+        Self {
+            item_count,
+            item_height,
+            ..Default::default()
+        }
+    }
+
+    /// Returns this list with the specified number of items in each row.
+    pub fn with_columns(mut self, columns: usize) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    /// Returns this list with the specified number of rows spawned beyond the view.
+    pub fn with_overscan(mut self, overscan: usize) -> Self {
+        self.overscan = overscan;
+        self
+    }
+
+    /// The number of rows of the list.
+    pub fn rows(&self) -> usize {
+        self.item_count.div_ceil(self.columns.max(1))
+    }
+
+    /// The indices of the items in view when the list is scrolled down by `offset` logical pixels
+    /// and is `height` logical pixels high, including the overscan.
+    pub fn visible_items(&self, offset: f32, height: f32) -> Range<usize> {
+        if self.item_height <= 0. || self.item_count == 0 {
+            return 0..0;
+        }
+        let columns = self.columns.max(1);
+        let first_row = (offset.max(0.) / self.item_height) as usize;
+        let last_row = ((offset.max(0.) + height.max(0.)) / self.item_height).ceil() as usize;
+        let first_row = first_row.saturating_sub(self.overscan);
+        let last_row = (last_row + self.overscan).min(self.rows());
+        (first_row * columns).min(self.item_count)..(last_row * columns).min(self.item_count)
+    }
+
+    /// Returns the item nodes of the list, including the hidden ones that wait to be recycled.
+    pub fn item_entities(&self) -> &[Entity] {
+        &self.items
+    }
+
+    /// The node of the item with the given index, if it's in view.
+    fn item_node(&self, index: usize) -> Node {
+        let columns = self.columns.max(1);
+        let width = 100. / columns as f32;
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px((index / columns) as f32 * self.item_height),
+            left: Val::Percent((index % columns) as f32 * width),
+            width: Val::Percent(width),
+            height: Val::Px(self.item_height),
+            ..Default::default()
+        }
+    }
+
+    /// The node that gives the list the height of all its rows.
+    fn spacer_node(&self) -> Node {
+        Node {
+            width: Val::Percent(100.),
+            height: Val::Px(self.rows() as f32 * self.item_height),
+            ..Default::default()
+        }
+    }
+}
+
+/// An item node of a [`VirtualList`].
+///
+/// Item nodes are recycled: when the item scrolls out of view, the node is shown for another
+/// index, or hidden with [`Display::None`] and its index is set to `None`. React to changes of
+/// this component to update the content of the node.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component, Debug, PartialEq)]
+#[require(Node)]
+pub struct VirtualListItem {
+    /// The entity of the list.
+    pub list: Entity,
+    /// The index of the item that the node shows, or `None` if the node is hidden.
+    pub index: Option<usize>,
+}
+
+/// Marks the node that gives a [`VirtualList`] the height of its rows.
+#[derive(Component, Debug, Clone, Copy, Default)]
+struct VirtualListSpacer;
+
+/// Spawns, places and recycles the item nodes of [`VirtualList`]s to cover the items in view.
+///
+/// Runs in [`UiSystem::Prepare`], using the size of the lists from the last layout.
+pub fn update_virtual_lists(
+    mut commands: Commands,
+    mut lists: Query<(Entity, &mut VirtualList, &ComputedNode, &ScrollPosition)>,
+    mut items: Query<(&mut VirtualListItem, &mut Node), Without<VirtualList>>,
+    mut spacers: Query<
+        &mut Node,
+        (
+            With<VirtualListSpacer>,
+            Without<VirtualList>,
+            Without<VirtualListItem>,
+        ),
+    >,
+) {
+    for (list_entity, mut list, computed_node, scroll_position) in &mut lists {
+        let height = computed_node.size().y * computed_node.inverse_scale_factor();
+        let visible = list.visible_items(scroll_position.offset_y, height);
+
+        let spacer_node = list.spacer_node();
+        match list.spacer.and_then(|spacer| spacers.get_mut(spacer).ok()) {
+            Some(mut node) => {
+                node.set_if_neq(spacer_node);
+            }
+            None => {
+                let spacer = commands.spawn((VirtualListSpacer, spacer_node)).id();
+                commands.entity(list_entity).add_child(spacer);
+                list.bypass_change_detection().spacer = Some(spacer);
+            }
+        }
+
+        // Keep the nodes of items that are still in view, and free the others.
+        let mut shown = HashSet::default();
+        let mut free = Vec::new();
+        list.bypass_change_detection()
+            .items
+            .retain(|entity| items.contains(*entity));
+        for &entity in &list.items {
+            let Ok((item, mut node)) = items.get_mut(entity) else {
+                continue;
+            };
+            match item.index {
+                Some(index) if visible.contains(&index) && shown.insert(index) => {
+                    node.set_if_neq(list.item_node(index));
+                }
+                _ => free.push(entity),
+            }
+        }
+
+        // Show the items that came into view, recycling free nodes before spawning new ones.
+        for index in visible.filter(|index| !shown.contains(index)) {
+            let item = VirtualListItem {
+                list: list_entity,
+                index: Some(index),
+            };
+            match free.pop().and_then(|entity| items.get_mut(entity).ok()) {
+                Some((mut recycled, mut node)) => {
+                    *recycled = item;
+                    node.set_if_neq(list.item_node(index));
+                }
+                None => {
+                    let entity = commands.spawn((item, list.item_node(index))).id();
+                    commands.entity(list_entity).add_child(entity);
+                    list.bypass_change_detection().items.push(entity);
+                }
+            }
+        }
+
+        // Hide the nodes that are left.
+        for entity in free {
+            let Ok((mut item, mut node)) = items.get_mut(entity) else {
+                continue;
+            };
+            if item.index.is_some() {
+                item.index = None;
+                node.display = Display::None;
+            }
+        }
+    }
+}
+
+/// A source of data for the items of a [`VirtualList`], on the same entity as the list.
+///
+/// Add a [`VirtualListSourcePlugin`] for the source to keep the number of items of the list in
+/// sync with [`VirtualListSource::len`], and to [`bind`](VirtualListSource::bind) the item nodes
+/// to the data of their item whenever they show another item or the source changes.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_hierarchy::{BuildChildren, DespawnRecursiveExt};
+/// # use bevy_ui::widget::{Text, VirtualListSource};
+/// #[derive(Component)]
+/// struct Inventory(Vec<String>);
+///
+/// impl VirtualListSource for Inventory {
+///     fn len(&self) -> usize {
+///         self.0.len()
+///     }
+///
+///     fn bind(&self, index: usize, item: Entity, commands: &mut Commands) {
+///         commands
+///             .entity(item)
+///             .despawn_descendants()
+///             .with_child(Text::new(self.0[index].clone()));
+///     }
+/// }
+/// ```
+pub trait VirtualListSource: Component {
+    /// Returns the number of items.
+    fn len(&self) -> usize;
+
+    /// Returns true if there are no items.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Fills in the node of an item with the data of the item at `index`.
+    fn bind(&self, index: usize, item: Entity, commands: &mut Commands);
+}
+
+/// Binds lists to the [`VirtualListSource`] `S` on the same entity.
+pub struct VirtualListSourcePlugin<S: VirtualListSource>(PhantomData<S>);
+
+impl<S: VirtualListSource> Default for VirtualListSourcePlugin<S> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<S: VirtualListSource> Plugin for VirtualListSourcePlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            (
+                sync_virtual_list_len::<S>.before(update_virtual_lists),
+                bind_virtual_list_items::<S>.after(update_virtual_lists),
+            )
+                .in_set(UiSystem::Prepare),
+        );
+    }
+}
+
+/// Sets the number of items of lists to the length of their source.
+fn sync_virtual_list_len<S: VirtualListSource>(
+    mut lists: Query<(&S, &mut VirtualList), Changed<S>>,
+) {
+    for (source, mut list) in &mut lists {
+        let item_count = source.len();
+        if list.item_count != item_count {
+            list.item_count = item_count;
+        }
+    }
+}
+
+/// Binds the items that show another index, and all the items of lists whose source changed.
+fn bind_virtual_list_items<S: VirtualListSource>(
+    mut commands: Commands,
+    sources: Query<(Ref<S>, &VirtualList)>,
+    items: Query<Ref<VirtualListItem>>,
+) {
+    for (source, list) in &sources {
+        for item_entity in list.item_entities() {
+            let Ok(item) = items.get(*item_entity) else {
+                continue;
+            };
+            let Some(index) = item.index else {
+                continue;
+            };
+            if (source.is_changed() || item.is_changed()) && index < source.len() {
+                source.bind(index, *item_entity, &mut commands);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VirtualList;
+
+    #[test]
+    fn visible_items_of_list() {
+        let list = VirtualList::new(100, 10.).with_overscan(1);
+        assert_eq!(list.visible_items(0., 45.), 0..6);
+        assert_eq!(list.visible_items(100., 45.), 9..16);
+        assert_eq!(list.visible_items(990., 45.), 98..100);
+        assert_eq!(VirtualList::new(0, 10.).visible_items(0., 45.), 0..0);
+    }
+
+    #[test]
+    fn visible_items_of_grid() {
+        let list = VirtualList::new(10, 10.).with_columns(3).with_overscan(0);
+        assert_eq!(list.rows(), 4);
+        assert_eq!(list.visible_items(5., 10.), 0..6);
+        assert_eq!(list.visible_items(30., 10.), 9..10);
+    }
+}
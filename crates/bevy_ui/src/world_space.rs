@@ -0,0 +1,389 @@
+//! UI trees rendered in world space, on quads attached to entities.
+//!
+//! This is useful for health bars, name plates and interaction prompts that follow entities of
+//! the 3D scene, without projecting their positions to the screen every frame.
+
+use crate::{TargetCamera, UiSystem};
+use bevy_app::prelude::*;
+use bevy_asset::{Assets, Handle, RenderAssetUsages};
+use bevy_color::Color;
+use bevy_core_pipeline::core_2d::Camera2d;
+use bevy_core_pipeline::core_3d::Camera3d;
+use bevy_ecs::prelude::*;
+use bevy_hierarchy::{BuildChildren, DespawnRecursiveExt, Parent};
+use bevy_image::Image;
+use bevy_math::{primitives::Rectangle, Quat, Ray3d, UVec2, Vec2, Vec3};
+use bevy_pbr::{MeshMaterial3d, StandardMaterial};
+use bevy_reflect::prelude::*;
+use bevy_render::{
+    alpha::AlphaMode,
+    camera::{Camera, ClearColorConfig, RenderTarget},
+    mesh::{Mesh, Mesh3d},
+    render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
+    view::RenderLayers,
+};
+use bevy_transform::{prelude::*, TransformSystem};
+
+/// A plugin that renders UI trees with a [`WorldSpaceUi`] in world space.
+pub struct WorldSpaceUiPlugin;
+
+impl Plugin for WorldSpaceUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<WorldSpaceUi>()
+            .register_type::<WorldSpaceUiOrientation>()
+            .register_type::<WorldSpaceUiTarget>()
+            .add_systems(
+                PostUpdate,
+                (
+                    (despawn_world_space_ui_targets, spawn_world_space_ui_targets)
+                        .chain()
+                        .before(UiSystem::Prepare),
+                    update_world_space_ui_targets.before(TransformSystem::TransformPropagate),
+                ),
+            );
+
+        #[cfg(feature = "bevy_ui_picking_backend")]
+        app.add_systems(
+            PreUpdate,
+            forward_pointers_to_world_space_ui
+                .in_set(bevy_picking::PickSet::Backend)
+                .before(crate::picking_backend::ui_picking),
+        );
+    }
+}
+
+/// Renders a UI root node and its descendants in world space, on a quad attached to an entity.
+///
+/// The UI is rendered to a texture by a camera of its own, and the texture is drawn on an unlit
+/// quad that is a child of the [`anchor`](Self::anchor) entity, so that the UI is depth tested
+/// against the rest of the scene like any other mesh. The root node should cover the texture, for
+/// instance with a `width` and `height` of `Val::Percent(100.)`.
+///
+/// With the `bevy_ui_picking_backend` feature, the pointers of 3D cameras that hit the quad are
+/// forwarded to the UI, so the nodes receive picking events as if they were on the screen.
+///
+/// The [`TargetCamera`] of the node is managed by this component, see [`WorldSpaceUiTarget`].
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component, Debug)]
+pub struct WorldSpaceUi {
+    /// The entity that the UI is attached to.
+    pub anchor: Entity,
+    /// The position of the center of the quad relative to the anchor.
+    pub offset: Vec3,
+    /// The size of the texture that the UI is rendered to, in physical pixels.
+    pub size: UVec2,
+    /// How many pixels of the texture cover one unit of world space.
+    pub pixels_per_unit: f32,
+    /// How the quad is oriented.
+    pub orientation: WorldSpaceUiOrientation,
+}
+
+impl WorldSpaceUi {
+    /// Makes a world-space UI of `size` physical pixels, attached to the `anchor` entity.
+    pub fn new(anchor: Entity, size: UVec2) -> Self {
+        Self {
+            anchor,
+            offset: Vec3::ZERO,
+            size,
+            pixels_per_unit: 100.,
+            orientation: WorldSpaceUiOrientation::default(),
+        }
+    }
+
+    /// Returns this world-space UI with its quad at `offset` from the anchor.
+    pub fn with_offset(mut self, offset: Vec3) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Returns this world-space UI with the given orientation.
+    pub fn with_orientation(mut self, orientation: WorldSpaceUiOrientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    /// The size of the quad in its local space.
+    pub fn world_size(&self) -> Vec2 {
+        self.size.as_vec2() / self.pixels_per_unit
+    }
+
+    /// Returns the distance along the ray to the quad, and the position of the hit on the UI in
+    /// physical pixels, if the ray hits the front or back of the quad.
+    pub fn ray_hit(&self, quad_transform: &GlobalTransform, ray: Ray3d) -> Option<(f32, Vec2)> {
+        let origin = quad_transform.translation();
+        let plane = bevy_math::primitives::InfinitePlane3d::new(quad_transform.back());
+        let distance = ray.intersect_plane(origin, plane)?;
+        let local = quad_transform
+            .affine()
+            .inverse()
+            .transform_point3(ray.get_point(distance));
+        let uv = Vec2::new(local.x, -local.y) / self.world_size() + 0.5;
+        if uv.cmplt(Vec2::ZERO).any() || uv.cmpgt(Vec2::ONE).any() {
+            return None;
+        }
+        Some((distance, uv * self.size.as_vec2()))
+    }
+}
+
+/// How the quad of a [`WorldSpaceUi`] is oriented.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Reflect)]
+#[reflect(Debug, PartialEq, Default)]
+pub enum WorldSpaceUiOrientation {
+    /// The quad always faces the active 3D camera with the lowest order, like a billboard.
+    #[default]
+    Billboard,
+    /// The quad has the given rotation relative to the anchor. The UI faces `+Z` without
+    /// rotation.
+    Fixed(Quat),
+}
+
+/// The entities that render a [`WorldSpaceUi`], added to the UI root node by the
+/// [`WorldSpaceUiPlugin`].
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component, Debug)]
+pub struct WorldSpaceUiTarget {
+    /// The camera that renders the UI to the texture.
+    pub camera: Entity,
+    /// The quad entity that shows the texture, a child of the anchor.
+    pub quad: Entity,
+    /// The texture that the UI is rendered to.
+    pub image: Handle<Image>,
+    /// The material of the quad.
+    pub material: Handle<StandardMaterial>,
+}
+
+/// Marks the camera and the quad of a [`WorldSpaceUi`], with the entity of its root node.
+#[derive(Component, Debug, Clone, Copy)]
+struct WorldSpaceUiPart(Entity);
+
+fn spawn_world_space_ui_targets(
+    mut commands: Commands,
+    uis: Query<(Entity, &WorldSpaceUi), Without<WorldSpaceUiTarget>>,
+    mut images: ResMut<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (entity, ui) in &uis {
+        let mut image = Image::new_fill(
+            Extent3d {
+                width: ui.size.x.max(1),
+                height: ui.size.y.max(1),
+                ..Default::default()
+            },
+            TextureDimension::D2,
+            &[0, 0, 0, 0],
+            TextureFormat::Bgra8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+        image.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
+            | TextureUsages::COPY_DST
+            | TextureUsages::RENDER_ATTACHMENT;
+        let image = images.add(image);
+
+        let camera = commands
+            .spawn((
+                WorldSpaceUiPart(entity),
+                Camera2d,
+                Camera {
+                    target: RenderTarget::Image(image.clone().into()),
+                    clear_color: ClearColorConfig::Custom(Color::NONE),
+                    // Render the texture before the cameras that show it.
+                    order: -1,
+                    ..Default::default()
+                },
+                // Only render the UI, not the 2D entities of the world.
+                RenderLayers::none(),
+            ))
+            .id();
+
+        let material = materials.add(StandardMaterial {
+            base_color_texture: Some(image.clone()),
+            unlit: true,
+            alpha_mode: AlphaMode::Blend,
+            double_sided: true,
+            cull_mode: None,
+            ..Default::default()
+        });
+        let mut quad_commands = commands.spawn((
+            WorldSpaceUiPart(entity),
+            Mesh3d(meshes.add(Rectangle::from_size(ui.world_size()))),
+            MeshMaterial3d(material.clone()),
+            Transform::from_translation(ui.offset),
+        ));
+        // The UI under the quad is picked instead of the quad itself.
+        #[cfg(feature = "bevy_ui_picking_backend")]
+        quad_commands.insert(bevy_picking::Pickable::IGNORE);
+        let quad = quad_commands.id();
+        if let Some(mut anchor) = commands.get_entity(ui.anchor) {
+            anchor.add_child(quad);
+        }
+
+        commands.entity(entity).insert((
+            TargetCamera(camera),
+            WorldSpaceUiTarget {
+                camera,
+                quad,
+                image,
+                material,
+            },
+        ));
+    }
+}
+
+/// Despawns the cameras and quads of world-space UI that was removed or despawned.
+fn despawn_world_space_ui_targets(
+    mut commands: Commands,
+    parts: Query<(Entity, &WorldSpaceUiPart)>,
+    uis: Query<(), With<WorldSpaceUi>>,
+) {
+    for (entity, part) in &parts {
+        if !uis.contains(part.0) {
+            commands.entity(entity).despawn_recursive();
+            if let Some(mut ui) = commands.get_entity(part.0) {
+                ui.remove::<(WorldSpaceUiTarget, TargetCamera)>();
+            }
+        }
+    }
+}
+
+/// Resizes the textures and quads of changed world-space UI, and orients the quads.
+fn update_world_space_ui_targets(
+    uis: Query<(Ref<WorldSpaceUi>, &WorldSpaceUiTarget)>,
+    mut quads: Query<(&mut Transform, &Mesh3d, Option<&Parent>), With<WorldSpaceUiPart>>,
+    global_transforms: Query<&GlobalTransform>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    mut images: ResMut<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let billboard_rotation = cameras
+        .iter()
+        .filter(|(camera, _)| camera.is_active)
+        .min_by_key(|(camera, _)| camera.order)
+        .map(|(_, transform)| transform.rotation());
+
+    for (ui, target) in &uis {
+        let Ok((mut transform, mesh, parent)) = quads.get_mut(target.quad) else {
+            continue;
+        };
+
+        if ui.is_changed() {
+            let size = Extent3d {
+                width: ui.size.x.max(1),
+                height: ui.size.y.max(1),
+                ..Default::default()
+            };
+            if let Some(image) = images.get_mut(&target.image) {
+                if image.texture_descriptor.size != size {
+                    image.resize(size);
+                }
+            }
+            if let Some(mesh) = meshes.get_mut(&mesh.0) {
+                *mesh = Rectangle::from_size(ui.world_size()).into();
+            }
+            transform.translation = ui.offset;
+        }
+
+        let rotation = match ui.orientation {
+            WorldSpaceUiOrientation::Fixed(rotation) => rotation,
+            WorldSpaceUiOrientation::Billboard => {
+                let Some(camera_rotation) = billboard_rotation else {
+                    continue;
+                };
+                // Undo the rotation of the anchor, as of the last transform propagation.
+                let anchor_rotation = parent
+                    .and_then(|parent| global_transforms.get(parent.get()).ok())
+                    .map_or(Quat::IDENTITY, GlobalTransform::rotation);
+                anchor_rotation.inverse() * camera_rotation
+            }
+        };
+        if transform.rotation != rotation {
+            transform.rotation = rotation;
+        }
+    }
+}
+
+/// Forwards the pointers whose rays hit the quad of a world-space UI to the UI camera.
+#[cfg(feature = "bevy_ui_picking_backend")]
+fn forward_pointers_to_world_space_ui(
+    ray_map: Res<bevy_picking::backend::ray::RayMap>,
+    uis: Query<(&WorldSpaceUi, &WorldSpaceUiTarget)>,
+    transforms: Query<&GlobalTransform>,
+    cameras: Query<&Camera>,
+    mut forwarded: ResMut<crate::picking_backend::ForwardedUiPointers>,
+) {
+    use crate::picking_backend::ForwardedUiPointer;
+    use bevy_utils::HashMap;
+
+    // Only the nearest world-space UI under each pointer is forwarded to.
+    let mut nearest = HashMap::default();
+    for (ray_id, ray) in ray_map.iter() {
+        let Ok(order) = cameras.get(ray_id.camera).map(|camera| camera.order as f32) else {
+            continue;
+        };
+        for (ui, target) in &uis {
+            let Ok(quad_transform) = transforms.get(target.quad) else {
+                continue;
+            };
+            let Some((depth, position)) = ui.ray_hit(quad_transform, *ray) else {
+                continue;
+            };
+            let pointer = ForwardedUiPointer {
+                position,
+                depth,
+                order,
+            };
+            nearest
+                .entry((ray_id.camera, ray_id.pointer))
+                .and_modify(|nearest: &mut (Entity, ForwardedUiPointer)| {
+                    if depth < nearest.1.depth {
+                        *nearest = (target.camera, pointer);
+                    }
+                })
+                .or_insert((target.camera, pointer));
+        }
+    }
+
+    for ((_, pointer_id), (ui_camera, pointer)) in nearest {
+        forwarded.0.insert((ui_camera, pointer_id), pointer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WorldSpaceUi;
+    use bevy_ecs::entity::Entity;
+    use bevy_math::{Dir3, Quat, Ray3d, UVec2, Vec2, Vec3};
+    use bevy_transform::components::{GlobalTransform, Transform};
+
+    #[test]
+    fn ray_hit_maps_to_ui_pixels() {
+        let ui = WorldSpaceUi::new(Entity::PLACEHOLDER, UVec2::new(200, 100));
+        let quad = GlobalTransform::from(Transform::from_xyz(0., 0., -5.));
+
+        // The center of the quad is the center of the UI.
+        let ray = Ray3d::new(Vec3::ZERO, Dir3::NEG_Z);
+        let (depth, position) = ui.ray_hit(&quad, ray).unwrap();
+        assert!((depth - 5.).abs() < 1e-5);
+        assert!(position.abs_diff_eq(Vec2::new(100., 50.), 1e-3));
+
+        // The top left corner of the quad is the origin of the UI.
+        let ray = Ray3d::new(Vec3::new(-0.9, 0.4, 0.), Dir3::NEG_Z);
+        let (_, position) = ui.ray_hit(&quad, ray).unwrap();
+        assert!(position.abs_diff_eq(Vec2::new(10., 10.), 1e-3));
+
+        // Rays beside the quad or parallel to it miss.
+        assert!(ui
+            .ray_hit(&quad, Ray3d::new(Vec3::new(1.5, 0., 0.), Dir3::NEG_Z))
+            .is_none());
+        assert!(ui.ray_hit(&quad, Ray3d::new(Vec3::ZERO, Dir3::X)).is_none());
+
+        // Rotated quads are hit in their local space.
+        let quad = GlobalTransform::from(
+            Transform::from_xyz(0., 0., -5.)
+                .with_rotation(Quat::from_rotation_z(core::f32::consts::FRAC_PI_2)),
+        );
+        let ray = Ray3d::new(Vec3::new(0., 0.9, 0.), Dir3::NEG_Z);
+        let (_, position) = ui.ray_hit(&quad, ray).unwrap();
+        assert!(position.abs_diff_eq(Vec2::new(190., 50.), 1e-3));
+    }
+}
@@ -177,6 +177,10 @@ pub struct Window {
     pub name: Option<String>,
     /// How the alpha channel of textures should be handled while compositing.
     pub composite_alpha_mode: CompositeAlphaMode,
+    /// Whether the window is presented in HDR, see [`HdrOutput`].
+    ///
+    /// Changing this at runtime reconfigures the surface of the window.
+    pub hdr_output: HdrOutput,
     /// The limits of the window's logical size
     /// (found in its [`resolution`](WindowResolution)) when resizing.
     pub resize_constraints: WindowResizeConstraints,
@@ -445,6 +449,7 @@ impl Default for Window {
             resolution: Default::default(),
             internal: Default::default(),
             composite_alpha_mode: Default::default(),
+            hdr_output: Default::default(),
             resize_constraints: Default::default(),
             ime_enabled: Default::default(),
             ime_position: Default::default(),
@@ -1262,6 +1267,87 @@ pub enum CompositeAlphaMode {
     Inherit = 4,
 }
 
+/// The HDR output settings of a [`Window`].
+///
+/// When HDR output is enabled and the surface of the window supports it, the window is presented
+/// in the requested [`HdrOutputFormat`], where colors brighter than SDR white are shown up to the
+/// peak luminance of the display. Otherwise the window keeps the usual SDR output. If the
+/// surface only supports the other HDR format, that one is used instead.
+///
+/// HDR highlights are only kept by cameras that render in HDR: the others are shown as SDR
+/// content, at [`HdrOutput::paper_white`].
+///
+/// The formats supported by the surface of each window can be queried from the
+/// `HdrOutputSupport` resource of `bevy_render` once the surface is created.
+///
+/// ## Platform-specific
+///
+/// Requires a surface that supports 16-bit float formats for
+/// [`HdrOutputFormat::ExtendedLinearSrgb`], such as DX12 and Vulkan swapchains on an HDR display on
+/// **`Windows`**, or Metal layers on **`macOS`**. See [`HdrOutputFormat::Hdr10`] for its caveats.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(Reflect),
+    reflect(Debug, PartialEq, Default)
+)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    all(feature = "serialize", feature = "bevy_reflect"),
+    reflect(Serialize, Deserialize)
+)]
+pub struct HdrOutput {
+    /// Requests HDR output for the window.
+    pub enabled: bool,
+    /// The format the window is presented in when HDR output is enabled.
+    pub format: HdrOutputFormat,
+    /// The luminance of SDR white, in nits, at which UI and other SDR content are shown.
+    ///
+    /// Defaults to 203 nits, the reference white of ITU-R BT.2408.
+    pub paper_white: f32,
+    /// The peak luminance of the display, in nits, to which HDR highlights are compressed.
+    ///
+    /// Defaults to 1000 nits.
+    pub max_luminance: f32,
+}
+
+impl Default for HdrOutput {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            format: HdrOutputFormat::default(),
+            paper_white: 203.0,
+            max_luminance: 1000.0,
+        }
+    }
+}
+
+/// The format of a [`Window`] presented in HDR, see [`HdrOutput`].
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(Reflect),
+    reflect(Debug, PartialEq, Hash, Default)
+)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    all(feature = "serialize", feature = "bevy_reflect"),
+    reflect(Serialize, Deserialize)
+)]
+pub enum HdrOutputFormat {
+    /// Extended linear sRGB (scRGB) in a 16-bit float surface, where 1.0 is shown at 80 nits.
+    #[default]
+    ExtendedLinearSrgb,
+    /// HDR10: BT.2020 primaries encoded with the PQ transfer function, in a 10-bit surface.
+    ///
+    /// ## Platform-specific
+    ///
+    /// wgpu doesn't let the color space of surfaces be selected, so the display only shows the
+    /// output as HDR10 on platforms that present 10-bit surfaces in that color space. Elsewhere,
+    /// the output looks washed out.
+    Hdr10,
+}
+
 /// Defines the way a [`Window`] is displayed.
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "bevy_reflect", derive(Reflect), reflect(Debug, PartialEq))]
@@ -31,6 +31,8 @@ fn main() -> ExitCode {
             DefaultPlugins,
             MeshletPlugin {
                 cluster_buffer_slots: 8192,
+                software_raster_max_cluster_size:
+                    MeshletPlugin::DEFAULT_SOFTWARE_RASTER_MAX_CLUSTER_SIZE,
             },
             MaterialPlugin::<MeshletDebugMaterial>::default(),
             CameraControllerPlugin,
@@ -2,7 +2,7 @@
 //! camera using the [`MotionBlur`] component.z
 
 use bevy::{
-    core_pipeline::motion_blur::MotionBlur,
+    core_pipeline::motion_blur::{MotionBlur, MotionBlurQuality},
     image::{ImageAddressMode, ImageFilterMode, ImageSampler, ImageSamplerDescriptor},
     math::ops,
     prelude::*,
@@ -22,13 +22,14 @@ fn setup_camera(mut commands: Commands) {
         Camera3d::default(),
         // Add the `MotionBlur` component to a camera to enable motion blur.
         // Motion blur requires the depth and motion vector prepass, which this bundle adds.
-        // Configure the amount and quality of motion blur per-camera using this component.
+        // Configure the amount of motion blur per-camera using this component.
         MotionBlur {
             shutter_angle: 1.0,
             samples: 2,
-            #[cfg(all(feature = "webgl2", target_arch = "wasm32", not(feature = "webgpu")))]
-            _webgl2_padding: Default::default(),
+            ..default()
         },
+        // Blur fast objects past their silhouettes.
+        MotionBlurQuality::Medium,
         // MSAA and Motion Blur together are not compatible on WebGL
         #[cfg(all(feature = "webgl2", target_arch = "wasm32", not(feature = "webgpu")))]
         Msaa::Off,
@@ -247,21 +248,24 @@ fn setup_ui(mut commands: Commands) {
             },
         ))
         .with_children(|p| {
+            p.spawn(TextSpan::default());
             p.spawn(TextSpan::default());
             p.spawn(TextSpan::default());
             p.spawn(TextSpan::new("1/2: -/+ shutter angle (blur amount)\n"));
             p.spawn(TextSpan::new("3/4: -/+ sample count (blur quality)\n"));
+            p.spawn(TextSpan::new("5: cycle quality tier\n"));
             p.spawn(TextSpan::new("Spacebar: cycle camera\n"));
         });
 }
 
 fn keyboard_inputs(
-    mut motion_blur: Single<&mut MotionBlur>,
+    motion_blur: Single<(&mut MotionBlur, &mut MotionBlurQuality)>,
     presses: Res<ButtonInput<KeyCode>>,
     text: Single<Entity, With<Text>>,
     mut writer: TextUiWriter,
     mut camera: ResMut<CameraMode>,
 ) {
+    let (mut motion_blur, mut quality) = motion_blur.into_inner();
     if presses.just_pressed(KeyCode::Digit1) {
         motion_blur.shutter_angle -= 0.25;
     } else if presses.just_pressed(KeyCode::Digit2) {
@@ -270,6 +274,12 @@ fn keyboard_inputs(
         motion_blur.samples = motion_blur.samples.saturating_sub(1);
     } else if presses.just_pressed(KeyCode::Digit4) {
         motion_blur.samples += 1;
+    } else if presses.just_pressed(KeyCode::Digit5) {
+        *quality = match *quality {
+            MotionBlurQuality::Low => MotionBlurQuality::Medium,
+            MotionBlurQuality::Medium => MotionBlurQuality::High,
+            MotionBlurQuality::High => MotionBlurQuality::Low,
+        };
     } else if presses.just_pressed(KeyCode::Space) {
         *camera = match *camera {
             CameraMode::Track => CameraMode::Chase,
@@ -281,6 +291,7 @@ fn keyboard_inputs(
     let entity = *text;
     *writer.text(entity, 1) = format!("Shutter angle: {:.2}\n", motion_blur.shutter_angle);
     *writer.text(entity, 2) = format!("Samples: {:.5}\n", motion_blur.samples);
+    *writer.text(entity, 3) = format!("Quality: {:?}\n", *quality);
 }
 
 /// Parametric function for a looping race track. `offset` will return the point offset
@@ -5,7 +5,9 @@
 //! [`OrderIndependentTransparencyPlugin`]: bevy::render::pipeline::OrderIndependentTransparencyPlugin
 use bevy::{
     color::palettes::css::{BLUE, GREEN, RED},
-    core_pipeline::oit::OrderIndependentTransparencySettings,
+    core_pipeline::oit::{
+        OrderIndependentTransparencyMethod, OrderIndependentTransparencySettings,
+    },
     prelude::*,
     render::view::RenderLayers,
 };
@@ -14,7 +16,7 @@ fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
         .add_systems(Startup, setup)
-        .add_systems(Update, (toggle_oit, cycle_scenes))
+        .add_systems(Update, (toggle_oit, toggle_oit_method, cycle_scenes))
         .run();
 }
 
@@ -61,6 +63,8 @@ fn setup(
             p.spawn(TextSpan::new("Press T to toggle OIT\n"));
             p.spawn(TextSpan::new("OIT Enabled"));
             p.spawn(TextSpan::new("\nPress C to cycle test scenes"));
+            p.spawn(TextSpan::new("\nPress M to switch the OIT method\n"));
+            p.spawn(TextSpan::new("Per-pixel lists"));
         });
 
     // spawn default scene
@@ -93,6 +97,33 @@ fn toggle_oit(
     }
 }
 
+fn toggle_oit_method(
+    mut commands: Commands,
+    text: Single<Entity, With<Text>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    q: Query<(Entity, Option<&OrderIndependentTransparencyMethod>), With<Camera3d>>,
+    mut text_writer: TextUiWriter,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyM) {
+        for (e, method) in &q {
+            // Weighted blended OIT approximates the blending, but it uses less memory,
+            // doesn't need any sorting and doesn't drop fragments when there are too many layers.
+            let (method, label) = match method.copied().unwrap_or_default() {
+                OrderIndependentTransparencyMethod::PerPixelLists => (
+                    OrderIndependentTransparencyMethod::WeightedBlended,
+                    "Weighted blended",
+                ),
+                OrderIndependentTransparencyMethod::WeightedBlended => (
+                    OrderIndependentTransparencyMethod::PerPixelLists,
+                    "Per-pixel lists",
+                ),
+            };
+            commands.entity(e).insert(method);
+            *text_writer.text(*text, 5) = label.to_string();
+        }
+    }
+}
+
 fn cycle_scenes(
     mut commands: Commands,
     keyboard_input: Res<ButtonInput<KeyCode>>,
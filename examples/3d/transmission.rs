@@ -44,7 +44,10 @@ fn main() {
 
     app.add_plugins(DefaultPlugins)
         .insert_resource(ClearColor(Color::BLACK))
-        .insert_resource(PointLightShadowMap { size: 2048 })
+        .insert_resource(PointLightShadowMap {
+            size: 2048,
+            ..default()
+        })
         .insert_resource(AmbientLight {
             brightness: 0.0,
             ..default()
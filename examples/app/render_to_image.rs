@@ -0,0 +1,107 @@
+//! This example renders a scene without a window into an image, and saves a frame as a thumbnail
+//! once the scene is fully rendered.
+//!
+//! This uses the [`RenderToImagePlugin`], which reads the frames rendered by cameras with a
+//! [`RenderToImage`] component back to the CPU. The same workflow can compare frames against
+//! reference images in automated visual tests, or save every frame to encode them into a video.
+
+use bevy::{
+    app::ScheduleRunnerPlugin,
+    prelude::*,
+    render::render_to_image::{ImageRendered, RenderToImage, RenderToImagePlugin},
+    window::ExitCondition,
+    winit::WinitPlugin,
+};
+use std::time::Duration;
+
+/// The size of the thumbnail, in pixels.
+const SIZE: UVec2 = UVec2::new(512, 512);
+
+/// The number of frames to skip before saving the thumbnail.
+///
+/// The first frames are blank or only show parts of the scene, while its meshes are uploaded and
+/// its shaders are compiled. The exact number of frames depends on the device and the scene.
+const PRE_ROLL_FRAMES: u32 = 40;
+
+/// Where the thumbnail is saved.
+const PATH: &str = "thumbnail.png";
+
+fn main() -> AppExit {
+    App::new()
+        .add_plugins((
+            DefaultPlugins
+                .set(WindowPlugin {
+                    primary_window: None,
+                    // Don't exit because there is no window.
+                    exit_condition: ExitCondition::DontExit,
+                    ..default()
+                })
+                // WinitPlugin will panic in environments without a display server.
+                .disable::<WinitPlugin>(),
+            // ScheduleRunnerPlugin runs the app in a loop without a window, instead of WinitPlugin.
+            ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(1.0 / 60.0)),
+            RenderToImagePlugin,
+        ))
+        .add_systems(Startup, setup)
+        .add_systems(Update, save_thumbnail)
+        .run()
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    // circular base
+    commands.spawn((
+        Mesh3d(meshes.add(Circle::new(4.0))),
+        MeshMaterial3d(materials.add(Color::WHITE)),
+        Transform::from_rotation(Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2)),
+    ));
+    // cube
+    commands.spawn((
+        Mesh3d(meshes.add(Cuboid::new(1.0, 1.0, 1.0))),
+        MeshMaterial3d(materials.add(Color::srgb_u8(124, 144, 255))),
+        Transform::from_xyz(0.0, 0.5, 0.0),
+    ));
+    // light
+    commands.spawn((
+        PointLight {
+            shadows_enabled: true,
+            ..default()
+        },
+        Transform::from_xyz(4.0, 8.0, 4.0),
+    ));
+    // camera, rendering into a new image
+    commands.spawn((
+        Camera3d::default(),
+        RenderToImage::create_image(SIZE, &mut images),
+        Transform::from_xyz(-2.5, 4.5, 9.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+}
+
+fn save_thumbnail(mut rendered: EventReader<ImageRendered>, mut exit: EventWriter<AppExit>) {
+    for ImageRendered { frame, image, .. } in rendered.read() {
+        if *frame < PRE_ROLL_FRAMES {
+            continue;
+        }
+        match image.clone().try_into_dynamic() {
+            Ok(thumbnail) => match thumbnail.to_rgba8().save(PATH) {
+                Ok(()) => {
+                    info!("Saved the thumbnail to {PATH}");
+                    exit.send(AppExit::Success);
+                }
+                Err(err) => {
+                    error!("Failed to save the thumbnail: {err}");
+                    exit.send(AppExit::error());
+                }
+            },
+            Err(err) => {
+                error!("Failed to convert the thumbnail: {err}");
+                exit.send(AppExit::error());
+            }
+        }
+        return;
+    }
+}
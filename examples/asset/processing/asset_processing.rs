@@ -1,14 +1,17 @@
 //! This example illustrates how to define custom `AssetLoader`s, `AssetTransformer`s, and `AssetSaver`s, how to configure them, and how to register asset processors.
+//!
+//! It also shows how to process the assets headlessly, for build pipelines, with `--process-assets`.
 
 use bevy::{
     asset::{
         embedded_asset,
         io::{Reader, Writer},
-        processor::LoadTransformAndSave,
+        processor::{LoadTransformAndSave, ProcessAssetsArgs},
         saver::{AssetSaver, SavedAsset},
         transformer::{AssetTransformer, TransformedAsset},
         AssetLoader, AsyncWriteExt, LoadContext,
     },
+    log::LogPlugin,
     prelude::*,
     reflect::TypePath,
 };
@@ -16,7 +19,38 @@ use serde::{Deserialize, Serialize};
 use std::convert::Infallible;
 use thiserror::Error;
 
-fn main() {
+fn main() -> AppExit {
+    // Running this example with `-- --process-assets` processes the assets without opening a
+    // window, and exits. This is how a build pipeline can bake the assets of a project on a
+    // machine without a GPU or a display. The arguments that follow select the processors and
+    // the platform to process the assets for, see `ProcessAssetsArgs::USAGE`.
+    let mut args = std::env::args().skip(1).peekable();
+    if args.next_if(|arg| arg == "--process-assets").is_some() {
+        let args = ProcessAssetsArgs {
+            // This is just overriding the default paths to scope this to the correct example folder
+            file_path: "examples/asset/processing/assets".to_string(),
+            processed_root: "examples/asset/processing/imported_assets".to_string(),
+            ..default()
+        }
+        .with_args(args);
+        let args = match args {
+            Ok(args) => args,
+            Err(err) => {
+                eprintln!("{err}\n{}", ProcessAssetsArgs::USAGE);
+                return AppExit::error();
+            }
+        };
+        return App::new()
+            .add_plugins((
+                TaskPoolPlugin::default(),
+                LogPlugin::default(),
+                args.asset_plugin(),
+                TextPlugin,
+                args.plugin(),
+            ))
+            .run();
+    }
+
     App::new()
         // Using the "processed" mode will configure the AssetPlugin to use asset processing.
         // If you also enable the `asset_processor` cargo feature, this will run the AssetProcessor
@@ -40,7 +74,7 @@ fn main() {
         ))
         .add_systems(Startup, setup)
         .add_systems(Update, print_text)
-        .run();
+        .run()
 }
 
 /// This [`TextPlugin`] defines two assets types:
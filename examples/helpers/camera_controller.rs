@@ -1,11 +1,14 @@
-//! A freecam-style camera controller plugin.
+//! A freecam-style camera controller plugin, with an orbit mode for inspecting assets.
 //! To use in your own application:
 //! - Copy the code for the [`CameraControllerPlugin`] and add the plugin to your App.
 //! - Attach the [`CameraController`] component to an entity with a [`Camera3d`].
+//! - Optionally, set [`CameraController::selection`] to the entity to frame when focusing.
 
 use bevy::{
     input::mouse::{AccumulatedMouseMotion, AccumulatedMouseScroll, MouseScrollUnit},
+    math::ops,
     prelude::*,
+    render::primitives::{Aabb, Sphere},
     window::CursorGrabMode,
 };
 use std::{f32::consts::*, fmt};
@@ -23,10 +26,21 @@ impl Plugin for CameraControllerPlugin {
 /// it because it felt nice.
 pub const RADIANS_PER_DOT: f32 = 1.0 / 180.0;
 
+/// How the [`CameraController`] moves the camera.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CameraControllerMode {
+    /// Fly around freely, looking around with the mouse.
+    #[default]
+    Fly,
+    /// Turn around a focus point like a turntable, panning and zooming towards the cursor.
+    Orbit,
+}
+
 #[derive(Component)]
 pub struct CameraController {
     pub enabled: bool,
     pub initialized: bool,
+    pub mode: CameraControllerMode,
     pub sensitivity: f32,
     pub key_forward: KeyCode,
     pub key_back: KeyCode,
@@ -35,15 +49,29 @@ pub struct CameraController {
     pub key_up: KeyCode,
     pub key_down: KeyCode,
     pub key_run: KeyCode,
+    pub key_toggle_mode: KeyCode,
+    pub key_focus: KeyCode,
     pub mouse_key_cursor_grab: MouseButton,
+    pub mouse_key_pan: MouseButton,
     pub keyboard_key_toggle_cursor_grab: KeyCode,
     pub walk_speed: f32,
     pub run_speed: f32,
     pub scroll_factor: f32,
     pub friction: f32,
+    /// How quickly the camera moves to frame the selection, as the decay rate of the remaining
+    /// distance.
+    pub focus_speed: f32,
     pub pitch: f32,
     pub yaw: f32,
     pub velocity: Vec3,
+    /// The point the camera looks at and turns around in orbit mode.
+    pub orbit_focus: Vec3,
+    /// The distance of the camera to the [`orbit_focus`](Self::orbit_focus).
+    pub orbit_distance: f32,
+    /// The entity that focusing frames, along with its descendants, or all the meshes if `None`.
+    pub selection: Option<Entity>,
+    /// The focus and distance that the camera is moving to, to frame the selection.
+    pub focus_target: Option<(Vec3, f32)>,
 }
 
 impl Default for CameraController {
@@ -51,6 +79,7 @@ impl Default for CameraController {
         Self {
             enabled: true,
             initialized: false,
+            mode: CameraControllerMode::Fly,
             sensitivity: 1.0,
             key_forward: KeyCode::KeyW,
             key_back: KeyCode::KeyS,
@@ -59,19 +88,34 @@ impl Default for CameraController {
             key_up: KeyCode::KeyE,
             key_down: KeyCode::KeyQ,
             key_run: KeyCode::ShiftLeft,
+            key_toggle_mode: KeyCode::F7,
+            key_focus: KeyCode::Home,
             mouse_key_cursor_grab: MouseButton::Left,
+            mouse_key_pan: MouseButton::Middle,
             keyboard_key_toggle_cursor_grab: KeyCode::KeyM,
             walk_speed: 5.0,
             run_speed: 15.0,
             scroll_factor: 0.1,
             friction: 0.5,
+            focus_speed: 10.0,
             pitch: 0.0,
             yaw: 0.0,
             velocity: Vec3::ZERO,
+            orbit_focus: Vec3::ZERO,
+            orbit_distance: 5.0,
+            selection: None,
+            focus_target: None,
         }
     }
 }
 
+impl CameraController {
+    /// The position of the camera in orbit mode, for its current rotation.
+    fn orbit_position(&self, rotation: Quat) -> Vec3 {
+        self.orbit_focus + rotation * Vec3::Z * self.orbit_distance
+    }
+}
+
 impl fmt::Display for CameraController {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -85,7 +129,15 @@ Freecam Controls:
     {:?} & {:?}\t- Fly forward & backwards
     {:?} & {:?}\t- Fly sideways left & right
     {:?} & {:?}\t- Fly up & down
-    {:?}\t- Fly faster while held",
+    {:?}\t- Fly faster while held
+
+Orbit Controls:
+    Mouse\t- Turn around the focus point while the cursor is grabbed
+    {:?}\t- Hold to pan
+    Scroll\t- Zoom towards the cursor
+
+    {:?}\t- Switch between freecam and orbit
+    {:?}\t- Focus on the selection, or on the whole scene",
             self.mouse_key_cursor_grab,
             self.keyboard_key_toggle_cursor_grab,
             self.key_forward,
@@ -95,6 +147,9 @@ Freecam Controls:
             self.key_up,
             self.key_down,
             self.key_run,
+            self.mouse_key_pan,
+            self.key_toggle_mode,
+            self.key_focus,
         )
     }
 }
@@ -108,11 +163,21 @@ fn run_camera_controller(
     key_input: Res<ButtonInput<KeyCode>>,
     mut toggle_cursor_grab: Local<bool>,
     mut mouse_cursor_grab: Local<bool>,
-    mut query: Query<(&mut Transform, &mut CameraController), With<Camera>>,
+    mut query: Query<(
+        &mut Transform,
+        &mut CameraController,
+        &Camera,
+        &GlobalTransform,
+        Option<&Projection>,
+    )>,
+    children: Query<&Children>,
+    meshes: Query<(&GlobalTransform, &Aabb), With<Mesh3d>>,
 ) {
     let dt = time.delta_secs();
 
-    let Ok((mut transform, mut controller)) = query.get_single_mut() else {
+    let Ok((mut transform, mut controller, camera, global_transform, projection)) =
+        query.get_single_mut()
+    else {
         return;
     };
 
@@ -120,6 +185,10 @@ fn run_camera_controller(
         let (yaw, pitch, _roll) = transform.rotation.to_euler(EulerRot::YXZ);
         controller.yaw = yaw;
         controller.pitch = pitch;
+        if controller.mode == CameraControllerMode::Fly {
+            controller.orbit_focus =
+                transform.translation + *transform.forward() * controller.orbit_distance;
+        }
         controller.initialized = true;
         info!("{}", *controller);
     }
@@ -127,6 +196,38 @@ fn run_camera_controller(
         return;
     }
 
+    if key_input.just_pressed(controller.key_toggle_mode) {
+        controller.mode = match controller.mode {
+            CameraControllerMode::Fly => {
+                // Turn around the point in front of the camera.
+                controller.orbit_focus =
+                    transform.translation + *transform.forward() * controller.orbit_distance;
+                CameraControllerMode::Orbit
+            }
+            CameraControllerMode::Orbit => CameraControllerMode::Fly,
+        };
+        controller.velocity = Vec3::ZERO;
+        info!("Camera controller mode: {:?}", controller.mode);
+    }
+
+    if key_input.just_pressed(controller.key_focus) {
+        let bounds = match controller.selection {
+            Some(selection) => world_bounds(
+                core::iter::once(selection)
+                    .chain(children.iter_descendants(selection))
+                    .filter_map(|entity| meshes.get(entity).ok()),
+            ),
+            None => world_bounds(meshes.iter()),
+        };
+        match bounds {
+            Some(sphere) => {
+                let distance = framing_distance(sphere.radius, projection);
+                controller.focus_target = Some((Vec3::from(sphere.center), distance));
+            }
+            None => warn!("Nothing to focus on: there are no meshes with bounds"),
+        }
+    }
+
     let mut scroll = 0.0;
 
     let amount = match accumulated_mouse_scroll.unit {
@@ -134,8 +235,10 @@ fn run_camera_controller(
         MouseScrollUnit::Pixel => accumulated_mouse_scroll.delta.y / 16.0,
     };
     scroll += amount;
-    controller.walk_speed += scroll * controller.scroll_factor * controller.walk_speed;
-    controller.run_speed = controller.walk_speed * 3.0;
+    if controller.mode == CameraControllerMode::Fly {
+        controller.walk_speed += scroll * controller.scroll_factor * controller.walk_speed;
+        controller.run_speed = controller.walk_speed * 3.0;
+    }
 
     // Handle key input
     let mut axis_input = Vec3::ZERO;
@@ -174,25 +277,29 @@ fn run_camera_controller(
     let cursor_grab = *mouse_cursor_grab || *toggle_cursor_grab;
 
     // Apply movement update
-    if axis_input != Vec3::ZERO {
-        let max_speed = if key_input.pressed(controller.key_run) {
-            controller.run_speed
+    if controller.mode == CameraControllerMode::Fly {
+        if axis_input != Vec3::ZERO {
+            let max_speed = if key_input.pressed(controller.key_run) {
+                controller.run_speed
+            } else {
+                controller.walk_speed
+            };
+            controller.velocity = axis_input.normalize() * max_speed;
+            // Flying away cancels focusing.
+            controller.focus_target = None;
         } else {
-            controller.walk_speed
-        };
-        controller.velocity = axis_input.normalize() * max_speed;
-    } else {
-        let friction = controller.friction.clamp(0.0, 1.0);
-        controller.velocity *= 1.0 - friction;
-        if controller.velocity.length_squared() < 1e-6 {
-            controller.velocity = Vec3::ZERO;
+            let friction = controller.friction.clamp(0.0, 1.0);
+            controller.velocity *= 1.0 - friction;
+            if controller.velocity.length_squared() < 1e-6 {
+                controller.velocity = Vec3::ZERO;
+            }
         }
+        let forward = *transform.forward();
+        let right = *transform.right();
+        transform.translation += controller.velocity.x * dt * right
+            + controller.velocity.y * dt * Vec3::Y
+            + controller.velocity.z * dt * forward;
     }
-    let forward = *transform.forward();
-    let right = *transform.right();
-    transform.translation += controller.velocity.x * dt * right
-        + controller.velocity.y * dt * Vec3::Y
-        + controller.velocity.z * dt * forward;
 
     // Handle cursor grab
     if cursor_grab_change {
@@ -223,4 +330,104 @@ fn run_camera_controller(
             accumulated_mouse_motion.delta.x * RADIANS_PER_DOT * controller.sensitivity;
         transform.rotation = Quat::from_euler(EulerRot::ZYX, 0.0, controller.yaw, controller.pitch);
     }
+
+    if controller.mode == CameraControllerMode::Orbit {
+        // Pan by moving the focus in the plane of the view, about as fast as the cursor at the
+        // distance of the focus.
+        if mouse_button_input.pressed(controller.mouse_key_pan)
+            && accumulated_mouse_motion.delta != Vec2::ZERO
+        {
+            let delta = accumulated_mouse_motion.delta
+                * controller.orbit_distance
+                * RADIANS_PER_DOT
+                * controller.sensitivity;
+            let pan = *transform.left() * delta.x + *transform.up() * delta.y;
+            controller.orbit_focus += pan;
+            controller.focus_target = None;
+        }
+
+        // Zoom towards the point under the cursor, in the plane of the focus facing the camera,
+        // so that the point stays under the cursor.
+        if scroll != 0.0 {
+            let zoom = ops::exp(-scroll * controller.scroll_factor);
+            let cursor = windows
+                .iter()
+                .filter(|window| window.focused)
+                .find_map(Window::cursor_position);
+            let target = cursor
+                .and_then(|cursor| camera.viewport_to_world(global_transform, cursor).ok())
+                .and_then(|ray| {
+                    let plane = InfinitePlane3d {
+                        normal: transform.back(),
+                    };
+                    let distance = ray.intersect_plane(controller.orbit_focus, plane)?;
+                    Some(ray.get_point(distance))
+                })
+                .unwrap_or(controller.orbit_focus);
+            controller.orbit_focus = target.lerp(controller.orbit_focus, zoom);
+            controller.orbit_distance *= zoom;
+            controller.focus_target = None;
+        }
+    }
+
+    // Move towards the selection.
+    if let Some((focus, distance)) = controller.focus_target {
+        let decay_rate = controller.focus_speed;
+        controller.orbit_focus.smooth_nudge(&focus, decay_rate, dt);
+        controller
+            .orbit_distance
+            .smooth_nudge(&distance, decay_rate, dt);
+        if controller.orbit_focus.distance(focus) < distance * 1e-3
+            && ops::abs(controller.orbit_distance - distance) < distance * 1e-3
+        {
+            controller.orbit_focus = focus;
+            controller.orbit_distance = distance;
+            controller.focus_target = None;
+        }
+        transform.translation = controller.orbit_position(transform.rotation);
+    } else if controller.mode == CameraControllerMode::Orbit {
+        transform.translation = controller.orbit_position(transform.rotation);
+    }
+}
+
+/// The world-space bounding sphere of the given meshes.
+fn world_bounds<'a>(
+    meshes: impl Iterator<Item = (&'a GlobalTransform, &'a Aabb)>,
+) -> Option<Sphere> {
+    let mut min = Vec3A::splat(f32::MAX);
+    let mut max = Vec3A::splat(f32::MIN);
+    for (transform, aabb) in meshes {
+        // Go through a sphere to get conservative bounds of a rotated Aabb.
+        let sphere = Sphere {
+            center: Vec3A::from(transform.transform_point(Vec3::from(aabb.center))),
+            radius: transform.radius_vec3a(aabb.half_extents),
+        };
+        let aabb = Aabb::from(sphere);
+        min = min.min(aabb.min());
+        max = max.max(aabb.max());
+    }
+    min.cmple(max).all().then(|| Sphere {
+        center: (min + max) * 0.5,
+        radius: ((max - min) * 0.5).length(),
+    })
+}
+
+/// The distance at which a sphere of `radius` fits in the view of the camera.
+fn framing_distance(radius: f32, projection: Option<&Projection>) -> f32 {
+    let radius = radius.max(0.01);
+    match projection {
+        Some(Projection::Perspective(perspective)) => {
+            let half_fov = perspective.fov * 0.5;
+            // Fit the sphere in the narrowest of the vertical and horizontal fields of view.
+            let half_fov = if perspective.aspect_ratio < 1.0 {
+                ops::atan(ops::tan(half_fov) * perspective.aspect_ratio)
+            } else {
+                half_fov
+            };
+            radius / ops::sin(half_fov)
+        }
+        // The distance doesn't change the size of the view of other projections, only keep the
+        // sphere in front of the camera.
+        _ => radius * 2.0,
+    }
 }
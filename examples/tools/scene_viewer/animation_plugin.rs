@@ -1,10 +1,36 @@
 //! Control animations of entities in the loaded scene.
-use std::collections::HashMap;
+//!
+//! A timeline at the bottom of the window lists the clips of every animation
+//! player, along with the playback position and speed of the current clip.
+//! Click or drag on its scrub bar to seek within the current clips.
+use std::{collections::HashMap, fmt::Write};
 
-use bevy::{animation::AnimationTarget, ecs::entity::EntityHashMap, gltf::Gltf, prelude::*};
+use bevy::{
+    animation::{graph::AnimationNodeType, AnimationTarget},
+    ecs::entity::EntityHashMap,
+    gltf::Gltf,
+    input::common_conditions::input_just_pressed,
+    prelude::*,
+    ui::RelativeCursorPosition,
+};
 
 use crate::scene_viewer_plugin::SceneHandle;
 
+const FONT_SIZE: f32 = 13.0;
+
+/// The time that the step keys move the current clips by, in seconds.
+const STEP_SECONDS: f32 = 1.0 / 30.0;
+
+/// The factor that the speed keys multiply or divide the playback speed by.
+const SPEED_FACTOR: f32 = 1.25;
+
+const TIMELINE_INSTRUCTIONS: &str = "\
+Animation Timeline:
+    [ / ]       - decrease / increase playback speed
+    , / .       - step backward / forward by a frame
+    Click bar   - scrub the current animations
+";
+
 /// Controls animation clips for a unique entity.
 #[derive(Component)]
 struct Clips {
@@ -44,6 +70,10 @@ fn assign_clips(
     mut commands: Commands,
     mut setup: Local<bool>,
 ) {
+    if !scene_handle.is_loaded {
+        // A new scene is being loaded, assign its clips once it is ready.
+        *setup = false;
+    }
     if scene_handle.is_loaded && !*setup {
         *setup = true;
     } else {
@@ -187,9 +217,362 @@ fn handle_inputs(
     }
 }
 
+/// Marks the panel that shows the animation graphs.
+#[derive(Component)]
+struct AnimationGraphPanel;
+
+fn setup_animation_graph_panel(mut commands: Commands) {
+    commands.spawn((
+        AnimationGraphPanel,
+        Text::default(),
+        TextFont {
+            font_size: FONT_SIZE,
+            ..default()
+        },
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.0),
+            right: Val::Px(12.0),
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        },
+        BackgroundColor(Color::BLACK.with_alpha(0.6)),
+        Visibility::Hidden,
+    ));
+}
+
+fn toggle_animation_graph_panel(mut panels: Query<&mut Visibility, With<AnimationGraphPanel>>) {
+    for mut visibility in &mut panels {
+        visibility.toggle_visible_hidden();
+    }
+}
+
+/// Shows the animation graph of every animation player as a tree, along with
+/// the current state of its nodes.
+fn update_animation_graph_panel(
+    players: Query<(
+        Entity,
+        &AnimationPlayer,
+        &AnimationGraphHandle,
+        Option<&Name>,
+    )>,
+    graphs: Res<Assets<AnimationGraph>>,
+    mut panels: Query<(&mut Text, &Visibility), With<AnimationGraphPanel>>,
+) {
+    let Ok((mut text, visibility)) = panels.get_single_mut() else {
+        return;
+    };
+    if *visibility == Visibility::Hidden {
+        return;
+    }
+
+    let mut output = String::from("Animation Graphs (F2 to hide)\n");
+    for (entity, player, graph_handle, name) in &players {
+        let Some(graph) = graphs.get(graph_handle) else {
+            continue;
+        };
+        let _ = match name {
+            Some(name) => writeln!(output, "\n{name}:"),
+            None => writeln!(output, "\nentity {entity}:"),
+        };
+        write_graph_node(&mut output, graph, player, graph.root, 0);
+    }
+
+    if text.0 != output {
+        text.0 = output;
+    }
+}
+
+fn write_graph_node(
+    output: &mut String,
+    graph: &AnimationGraph,
+    player: &AnimationPlayer,
+    node_index: AnimationNodeIndex,
+    depth: usize,
+) {
+    let node = &graph[node_index];
+    let description = match node.node_type {
+        AnimationNodeType::Clip(ref clip) => match clip.path() {
+            Some(path) => format!("clip {path}"),
+            None => format!("clip {:?}", clip.id()),
+        },
+        AnimationNodeType::Blend => "blend".to_string(),
+        AnimationNodeType::Add => "additive blend".to_string(),
+        AnimationNodeType::BlendSpace1d(_) => format!(
+            "1D blend space at {}",
+            player.blend_space_parameter(node_index).x
+        ),
+        AnimationNodeType::BlendSpace2d(_) => format!(
+            "2D blend space at {}",
+            player.blend_space_parameter(node_index)
+        ),
+    };
+    let _ = write!(
+        output,
+        "{:indent$}#{} {description}, weight {}",
+        "",
+        node_index.index(),
+        node.weight,
+        indent = depth * 2
+    );
+    let blend_weight = player.blend_weight(node_index);
+    if blend_weight != 1.0 {
+        let _ = write!(output, " (x {blend_weight:.2} in blend space)");
+    }
+    if node.mask != 0 {
+        let _ = write!(output, ", mask {:#b}", node.mask);
+    }
+    if let Some(joint_mask) = node.joint_mask.as_ref() {
+        match joint_mask.path() {
+            Some(path) => {
+                let _ = write!(output, ", joint mask {path}");
+            }
+            None => output.push_str(", joint mask"),
+        }
+    }
+    if let Some(animation) = player.animation(node_index) {
+        let state = if animation.is_paused() {
+            "paused"
+        } else {
+            "playing"
+        };
+        let _ = write!(output, ", {state} at {:.2}s", animation.seek_time());
+    }
+    output.push('\n');
+
+    let mut children: Vec<_> = graph.graph.neighbors(node_index).collect();
+    children.sort_unstable();
+    for child in children {
+        write_graph_node(output, graph, player, child, depth + 1);
+    }
+}
+
+/// Marks the text of the timeline panel.
+#[derive(Component)]
+struct TimelineText;
+
+/// Marks the scrub bar of the timeline panel.
+#[derive(Component)]
+struct TimelineScrubBar;
+
+/// Marks the part of the scrub bar that shows the playback position.
+#[derive(Component)]
+struct TimelineProgress;
+
+fn setup_timeline(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(12.0),
+                left: Val::Px(12.0),
+                right: Val::Px(12.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(6.0),
+                padding: UiRect::all(Val::Px(6.0)),
+                ..default()
+            },
+            BackgroundColor(Color::BLACK.with_alpha(0.6)),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TimelineText,
+                Text::new(TIMELINE_INSTRUCTIONS),
+                TextFont {
+                    font_size: FONT_SIZE,
+                    ..default()
+                },
+            ));
+            parent
+                .spawn((
+                    TimelineScrubBar,
+                    Interaction::default(),
+                    RelativeCursorPosition::default(),
+                    Node {
+                        width: Val::Percent(100.0),
+                        height: Val::Px(12.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::WHITE.with_alpha(0.2)),
+                ))
+                .with_child((
+                    TimelineProgress,
+                    Node {
+                        width: Val::Percent(0.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::WHITE.with_alpha(0.8)),
+                ));
+        });
+}
+
+/// Returns the clip played by the given node, if it's a clip node.
+fn node_clip<'a>(
+    graph: &AnimationGraph,
+    node_index: AnimationNodeIndex,
+    clips: &'a Assets<AnimationClip>,
+) -> Option<(&'a AnimationClip, AssetId<AnimationClip>)> {
+    match graph.get(node_index)?.node_type {
+        AnimationNodeType::Clip(ref handle) => Some((clips.get(handle)?, handle.id())),
+        _ => None,
+    }
+}
+
+fn control_timeline(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    scrub_bars: Query<(&Interaction, &RelativeCursorPosition), With<TimelineScrubBar>>,
+    mut players: Query<(&mut AnimationPlayer, &Clips, &AnimationGraphHandle)>,
+    graphs: Res<Assets<AnimationGraph>>,
+    clips: Res<Assets<AnimationClip>>,
+) {
+    let scrub_position = scrub_bars
+        .iter()
+        .find(|(interaction, _)| **interaction == Interaction::Pressed)
+        .and_then(|(_, cursor)| cursor.normalized)
+        .map(|position| position.x.clamp(0.0, 1.0));
+    let step = match (
+        keyboard_input.just_pressed(KeyCode::Comma),
+        keyboard_input.just_pressed(KeyCode::Period),
+    ) {
+        (true, false) => -STEP_SECONDS,
+        (false, true) => STEP_SECONDS,
+        _ => 0.0,
+    };
+    let speed_factor = match (
+        keyboard_input.just_pressed(KeyCode::BracketLeft),
+        keyboard_input.just_pressed(KeyCode::BracketRight),
+    ) {
+        (true, false) => 1.0 / SPEED_FACTOR,
+        (false, true) => SPEED_FACTOR,
+        _ => 1.0,
+    };
+    if scrub_position.is_none() && step == 0.0 && speed_factor == 1.0 {
+        return;
+    }
+
+    for (mut player, clip_nodes, graph_handle) in &mut players {
+        let Some((clip, _)) = graphs
+            .get(graph_handle)
+            .and_then(|graph| node_clip(graph, clip_nodes.current(), &clips))
+        else {
+            continue;
+        };
+        let duration = clip.duration();
+        let Some(animation) = player.animation_mut(clip_nodes.current()) else {
+            continue;
+        };
+
+        if let Some(position) = scrub_position {
+            animation.seek_to(position * duration);
+        }
+        if step != 0.0 {
+            let seek_time = (animation.seek_time() + step).rem_euclid(duration.max(f32::EPSILON));
+            animation.seek_to(seek_time);
+        }
+        if speed_factor != 1.0 {
+            let speed = animation.speed() * speed_factor;
+            animation.set_speed(speed);
+        }
+    }
+}
+
+fn update_timeline(
+    players: Query<(
+        Entity,
+        &AnimationPlayer,
+        &Clips,
+        &AnimationGraphHandle,
+        Option<&Name>,
+    )>,
+    graphs: Res<Assets<AnimationGraph>>,
+    clips: Res<Assets<AnimationClip>>,
+    scene_handle: Res<SceneHandle>,
+    gltf_assets: Res<Assets<Gltf>>,
+    mut texts: Query<&mut Text, With<TimelineText>>,
+    mut progress_bars: Query<&mut Node, With<TimelineProgress>>,
+) {
+    let Ok(mut text) = texts.get_single_mut() else {
+        return;
+    };
+
+    let clip_names: HashMap<_, _> = gltf_assets
+        .get(&scene_handle.gltf_handle)
+        .map(|gltf| {
+            gltf.named_animations
+                .iter()
+                .map(|(name, handle)| (handle.id(), name.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut panel = String::from(TIMELINE_INSTRUCTIONS);
+    let mut progress = None;
+    for (entity, player, clip_nodes, graph_handle, name) in &players {
+        let Some(graph) = graphs.get(graph_handle) else {
+            continue;
+        };
+        let _ = match name {
+            Some(name) => writeln!(panel, "\n{name}:"),
+            None => writeln!(panel, "\nentity {entity}:"),
+        };
+
+        for (index, &node_index) in clip_nodes.nodes.iter().enumerate() {
+            let Some((clip, clip_id)) = node_clip(graph, node_index, &clips) else {
+                continue;
+            };
+            let clip_name = clip_names
+                .get(&clip_id)
+                .cloned()
+                .unwrap_or_else(|| format!("#{}", node_index.index()));
+            let current = index == clip_nodes.current;
+            let cursor = if current { ">" } else { " " };
+            let _ = write!(panel, "{cursor} {clip_name:<24} {:>7.2}s", clip.duration());
+
+            if let Some(animation) = player.animation(node_index).filter(|_| current) {
+                let state = if animation.is_paused() {
+                    "paused"
+                } else {
+                    "playing"
+                };
+                let _ = write!(
+                    panel,
+                    "  at {:>6.2}s, speed {:.2}, {state}",
+                    animation.seek_time(),
+                    animation.speed()
+                );
+                if progress.is_none() && clip.duration() > 0.0 {
+                    progress = Some((animation.seek_time() / clip.duration()).clamp(0.0, 1.0));
+                }
+            }
+            panel.push('\n');
+        }
+    }
+
+    if text.0 != panel {
+        text.0 = panel;
+    }
+    for mut node in &mut progress_bars {
+        node.width = Val::Percent(progress.unwrap_or(0.0) * 100.0);
+    }
+}
+
 pub struct AnimationManipulationPlugin;
 impl Plugin for AnimationManipulationPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (handle_inputs, assign_clips));
+        app.add_systems(Startup, (setup_timeline, setup_animation_graph_panel))
+            .add_systems(
+                Update,
+                (
+                    handle_inputs,
+                    assign_clips,
+                    (
+                        toggle_animation_graph_panel.run_if(input_just_pressed(KeyCode::F2)),
+                        update_animation_graph_panel,
+                    )
+                        .chain(),
+                    (control_timeline, update_timeline).chain(),
+                ),
+            );
     }
 }
@@ -0,0 +1,75 @@
+//! Cycle through the debug views of the cameras, to inspect the normals, UVs, lightmaps,
+//! overdraw and shadow cascades of the scene.
+
+use bevy::{
+    pbr::DebugViewMode,
+    prelude::*,
+    render::{renderer::RenderDevice, settings::WgpuFeatures},
+};
+
+const INSTRUCTIONS: &str = "
+Debug View Controls:
+    F6 / Shift+F6   - next / previous debug view
+";
+
+/// The debug view of the cameras of the viewer.
+#[derive(Resource, Default)]
+struct ViewerDebugView(DebugViewMode);
+
+pub struct DebugViewPlugin;
+
+impl Plugin for DebugViewPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ViewerDebugView>()
+            .add_systems(Startup, || info!("{INSTRUCTIONS}"))
+            .add_systems(
+                Update,
+                (cycle_debug_view, apply_debug_view.after(cycle_debug_view)),
+            );
+    }
+}
+
+fn cycle_debug_view(
+    key_input: Res<ButtonInput<KeyCode>>,
+    render_device: Res<RenderDevice>,
+    mut debug_view: ResMut<ViewerDebugView>,
+) {
+    if !key_input.just_pressed(KeyCode::F6) {
+        return;
+    }
+    let backwards = key_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let mut mode = debug_view.0;
+    loop {
+        mode = if backwards {
+            mode.previous()
+        } else {
+            mode.next()
+        };
+        // Drawing the edges of the triangles isn't supported everywhere, notably on WebGL2.
+        if mode != DebugViewMode::Wireframe
+            || render_device
+                .features()
+                .contains(WgpuFeatures::POLYGON_MODE_LINE)
+        {
+            break;
+        }
+    }
+    info!("Debug view: {mode:?}");
+    debug_view.0 = mode;
+}
+
+fn apply_debug_view(
+    mut commands: Commands,
+    debug_view: Res<ViewerDebugView>,
+    cameras: Query<Entity, With<Camera3d>>,
+    added_cameras: Query<Entity, Added<Camera3d>>,
+) {
+    let cameras = if debug_view.is_changed() {
+        cameras.iter().collect::<Vec<_>>()
+    } else {
+        added_cameras.iter().collect()
+    };
+    for camera in cameras {
+        commands.entity(camera).insert(debug_view.0);
+    }
+}
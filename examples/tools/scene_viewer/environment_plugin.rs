@@ -0,0 +1,358 @@
+//! Switch the environment map lighting the scene, rotate it, and adjust the exposure of the
+//! cameras, to check materials under different lighting.
+//!
+//! Environment maps are pairs of prefiltered diffuse and specular KTX2 cubemaps, such as the
+//! ones in `assets/environment_maps`. `.hdr` panoramas aren't supported: they have to be
+//! prefiltered into such a pair first, as described in `assets/environment_maps/info.txt`.
+//!
+//! Additional environment maps can be given on the command line with
+//! `--environment diffuse.ktx2,specular.ktx2`, optionally followed by `,skybox.ktx2` to use a
+//! sharper cubemap for the skybox than the specular map. The flag can be repeated, and the
+//! first environment map given is shown at startup. `--exposure <ev100>` sets the exposure.
+//!
+//! Environment maps can also be added while the viewer runs, by dropping their diffuse and
+//! specular cubemaps onto the window, together or one after the other. Which one is which is
+//! told from their file names, which must contain `diffuse` or `specular`.
+
+use bevy::{core_pipeline::Skybox, prelude::*, render::camera::Exposure, window::FileDragAndDrop};
+use std::{
+    f32::consts::PI,
+    path::{Path, PathBuf},
+};
+
+const INSTRUCTIONS: &str = "
+Environment Controls:
+    F4 / Shift+F4   - next / previous environment map
+    ; / '           - rotate the environment map
+    - / =           - decrease / increase exposure
+    F5              - toggle the skybox
+    Drop a *diffuse*.ktx2 and a *specular*.ktx2 file onto the window to add an environment map
+";
+
+/// The intensity of the environment maps, in cd/m^2.
+const INTENSITY: f32 = 150.0;
+
+/// How fast the environment map rotates while its key is held, in radians per second.
+const ROTATION_PER_SECOND: f32 = PI / 2.0;
+
+/// How much the exposure changes per key press, in EV.
+const EXPOSURE_STEP: f32 = 0.5;
+
+/// The paths of an environment map given on the command line.
+#[derive(Clone, Debug)]
+pub struct EnvironmentPaths {
+    pub diffuse: String,
+    pub specular: String,
+    pub skybox: Option<String>,
+}
+
+impl EnvironmentPaths {
+    /// Parses the value of an `--environment` flag, `diffuse,specular[,skybox]`.
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut paths = value.split(',').map(str::to_string);
+        let diffuse = paths.next().filter(|path| !path.is_empty())?;
+        let specular = paths.next().filter(|path| !path.is_empty())?;
+        let skybox = paths.next();
+        paths.next().is_none().then_some(Self {
+            diffuse,
+            specular,
+            skybox,
+        })
+    }
+}
+
+/// Returns whether `path` has the extension `extension`, ignoring its case.
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension()
+        .and_then(|path_extension| path_extension.to_str())
+        .is_some_and(|path_extension| path_extension.eq_ignore_ascii_case(extension))
+}
+
+/// Returns whether `path` could be a prefiltered cubemap, and warns if it's an `.hdr` panorama
+/// instead, which has to be prefiltered first.
+fn check_prefiltered(path: &Path) -> bool {
+    if has_extension(path, "hdr") {
+        warn!(
+            "{} is an .hdr panorama, which has to be prefiltered into diffuse and specular KTX2 \
+            cubemaps first, as described in assets/environment_maps/info.txt",
+            path.display()
+        );
+        return false;
+    }
+    true
+}
+
+/// A loaded environment map.
+struct Environment {
+    name: String,
+    diffuse: Handle<Image>,
+    specular: Handle<Image>,
+    skybox: Handle<Image>,
+}
+
+impl Environment {
+    fn load(asset_server: &AssetServer, paths: &EnvironmentPaths) -> Self {
+        for path in [&paths.diffuse, &paths.specular]
+            .into_iter()
+            .chain(&paths.skybox)
+        {
+            check_prefiltered(Path::new(path));
+        }
+        let specular = asset_server.load(&paths.specular);
+        Self {
+            name: paths.specular.clone(),
+            diffuse: asset_server.load(&paths.diffuse),
+            skybox: match &paths.skybox {
+                Some(skybox) => asset_server.load(skybox),
+                None => specular.clone(),
+            },
+            specular,
+        }
+    }
+}
+
+/// The lighting of the cameras of the viewer.
+#[derive(Resource)]
+struct ViewerEnvironment {
+    environments: Vec<Environment>,
+    /// The index of the displayed environment map, if any.
+    current: Option<usize>,
+    /// The rotation of the environment map around the vertical axis, in radians.
+    rotation: f32,
+    skybox: bool,
+    exposure: f32,
+}
+
+impl ViewerEnvironment {
+    fn current(&self) -> Option<&Environment> {
+        self.environments.get(self.current?)
+    }
+
+    /// Selects the next environment map, or the previous one if `backwards`, going through no
+    /// environment map after the last one.
+    fn cycle(&mut self, backwards: bool) {
+        let len = self.environments.len();
+        // The environment maps, followed by no environment map.
+        let current = self.current.unwrap_or(len);
+        let next = if backwards {
+            (current + len) % (len + 1)
+        } else {
+            (current + 1) % (len + 1)
+        };
+        self.current = (next < len).then_some(next);
+    }
+
+    fn log(&self) {
+        match self.current() {
+            Some(environment) => info!(
+                "Environment map: {}, rotation {:.0} degrees, exposure {} EV100, skybox {}",
+                environment.name,
+                self.rotation.to_degrees(),
+                self.exposure,
+                if self.skybox { "on" } else { "off" },
+            ),
+            None => info!("No environment map, exposure {} EV100", self.exposure),
+        }
+    }
+}
+
+pub struct EnvironmentPlugin {
+    /// Environment maps to add to the built-in one, shown first.
+    pub environments: Vec<EnvironmentPaths>,
+    /// The initial exposure of the cameras, in EV100.
+    pub exposure: Option<f32>,
+}
+
+impl Plugin for EnvironmentPlugin {
+    fn build(&self, app: &mut App) {
+        let environments = self.environments.clone();
+        let exposure = self.exposure.unwrap_or(Exposure::default().ev100);
+        app.add_systems(
+            Startup,
+            move |mut commands: Commands, asset_server: Res<AssetServer>| {
+                let pisa = Environment {
+                    name: "pisa".to_string(),
+                    ..Environment::load(
+                        &asset_server,
+                        &EnvironmentPaths {
+                            diffuse: "assets/environment_maps/pisa_diffuse_rgb9e5_zstd.ktx2".into(),
+                            specular: "assets/environment_maps/pisa_specular_rgb9e5_zstd.ktx2"
+                                .into(),
+                            skybox: None,
+                        },
+                    )
+                };
+                let mut loaded: Vec<Environment> = environments
+                    .iter()
+                    .map(|paths| Environment::load(&asset_server, paths))
+                    .collect();
+                loaded.push(pisa);
+
+                info!("{INSTRUCTIONS}");
+                commands.insert_resource(ViewerEnvironment {
+                    environments: loaded,
+                    current: Some(0),
+                    rotation: 0.0,
+                    skybox: false,
+                    exposure,
+                });
+            },
+        )
+        .add_systems(
+            Update,
+            (
+                (control_environment, load_dropped_environment),
+                apply_environment,
+            )
+                .chain(),
+        );
+    }
+}
+
+fn control_environment(
+    key_input: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    mut environment: ResMut<ViewerEnvironment>,
+) {
+    let shift = key_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let mut changed = false;
+    if key_input.just_pressed(KeyCode::F4) {
+        environment.cycle(shift);
+        changed = true;
+    }
+    if key_input.just_pressed(KeyCode::F5) {
+        environment.skybox ^= true;
+        changed = true;
+    }
+    if key_input.just_pressed(KeyCode::Minus) {
+        environment.exposure -= EXPOSURE_STEP;
+        changed = true;
+    }
+    if key_input.just_pressed(KeyCode::Equal) {
+        environment.exposure += EXPOSURE_STEP;
+        changed = true;
+    }
+    let direction = match (
+        key_input.pressed(KeyCode::Semicolon),
+        key_input.pressed(KeyCode::Quote),
+    ) {
+        (true, false) => -1.0,
+        (false, true) => 1.0,
+        _ => 0.0,
+    };
+    if direction != 0.0 {
+        let rotation = environment.rotation + direction * ROTATION_PER_SECOND * time.delta_secs();
+        environment.rotation = rotation.rem_euclid(2.0 * PI);
+    }
+    // Only log once a rotation is done, instead of every frame.
+    if changed
+        || (key_input.any_just_released([KeyCode::Semicolon, KeyCode::Quote])
+            && !key_input.any_pressed([KeyCode::Semicolon, KeyCode::Quote]))
+    {
+        environment.log();
+    }
+}
+
+/// The cubemaps of an environment map dropped onto the window, until both have been dropped.
+#[derive(Default)]
+struct DroppedEnvironment {
+    diffuse: Option<PathBuf>,
+    specular: Option<PathBuf>,
+}
+
+/// Adds an environment map from the diffuse and specular cubemaps dropped onto the window, and
+/// shows it.
+fn load_dropped_environment(
+    mut drop_events: EventReader<FileDragAndDrop>,
+    mut dropped: Local<DroppedEnvironment>,
+    asset_server: Res<AssetServer>,
+    mut environment: ResMut<ViewerEnvironment>,
+) {
+    for event in drop_events.read() {
+        let FileDragAndDrop::DroppedFile { path_buf, .. } = event else {
+            continue;
+        };
+        if !check_prefiltered(path_buf) || !has_extension(path_buf, "ktx2") {
+            continue;
+        }
+        let name = path_buf
+            .file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if name.contains("diffuse") {
+            dropped.diffuse = Some(path_buf.clone());
+        } else if name.contains("specular") {
+            dropped.specular = Some(path_buf.clone());
+        } else {
+            warn!(
+                "Ignoring dropped cubemap {}: its name must contain `diffuse` or `specular`",
+                path_buf.display()
+            );
+            continue;
+        }
+
+        let (Some(diffuse), Some(specular)) = (&dropped.diffuse, &dropped.specular) else {
+            let missing = if dropped.diffuse.is_none() {
+                "diffuse"
+            } else {
+                "specular"
+            };
+            info!("Drop the {missing} cubemap of the environment map to add it");
+            continue;
+        };
+        let paths = EnvironmentPaths {
+            diffuse: diffuse.to_string_lossy().into_owned(),
+            specular: specular.to_string_lossy().into_owned(),
+            skybox: None,
+        };
+        *dropped = DroppedEnvironment::default();
+        let loaded = Environment::load(&asset_server, &paths);
+        environment.environments.push(loaded);
+        environment.current = Some(environment.environments.len() - 1);
+        environment.log();
+    }
+}
+
+/// Applies the environment to all the 3D cameras, including the ones loaded from the scene.
+fn apply_environment(
+    environment: Res<ViewerEnvironment>,
+    cameras: Query<Entity, With<Camera3d>>,
+    added_cameras: Query<Entity, Added<Camera3d>>,
+    mut commands: Commands,
+) {
+    let cameras = if environment.is_changed() {
+        cameras.iter().collect::<Vec<_>>()
+    } else {
+        added_cameras.iter().collect()
+    };
+    let rotation = Quat::from_rotation_y(environment.rotation);
+    for camera in cameras {
+        let mut camera = commands.entity(camera);
+        camera.insert(Exposure {
+            ev100: environment.exposure,
+        });
+        match environment.current() {
+            Some(current) => {
+                camera.insert(EnvironmentMapLight {
+                    diffuse_map: current.diffuse.clone(),
+                    specular_map: current.specular.clone(),
+                    intensity: INTENSITY,
+                    rotation,
+                    ..default()
+                });
+                if environment.skybox {
+                    camera.insert(Skybox {
+                        image: current.skybox.clone(),
+                        brightness: INTENSITY,
+                        rotation,
+                    });
+                } else {
+                    camera.remove::<Skybox>();
+                }
+            }
+            None => {
+                camera.remove::<(EnvironmentMapLight, Skybox)>();
+            }
+        }
+    }
+}
@@ -0,0 +1,377 @@
+//! Browse the node tree of the loaded scene in a collapsible panel, and select its entities.
+//!
+//! Click `+` / `-` next to a node to expand or collapse it, and click its name to select it.
+//! The selection is outlined in the scene, its transform, mesh and material are shown under the
+//! tree, and the camera controller frames it when focusing.
+
+use bevy::{color::palettes::css::YELLOW, prelude::*, render::primitives::Aabb};
+use std::{collections::HashSet, fmt::Write};
+
+use super::{camera_controller::CameraController, scene_viewer_plugin::SceneHandle};
+
+const FONT_SIZE: f32 = 13.0;
+
+/// How far each level of the tree is indented.
+const INDENT: f32 = 12.0;
+
+const TITLE: &str = "Hierarchy (F1 to collapse)";
+
+const ROW_COLOR: Color = Color::NONE;
+const HOVERED_ROW_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.1);
+const SELECTED_ROW_COLOR: Color = Color::srgba(1.0, 1.0, 0.0, 0.3);
+
+/// A node of the scene, as listed in the panel.
+#[derive(Clone, PartialEq)]
+struct Row {
+    entity: Entity,
+    depth: usize,
+    name: String,
+    has_children: bool,
+    expanded: bool,
+}
+
+/// The state of the hierarchy panel.
+#[derive(Resource, Default)]
+struct HierarchyPanel {
+    collapsed: bool,
+    /// The nodes whose children are listed.
+    expanded: HashSet<Entity>,
+    selected: Option<Entity>,
+    /// The rows currently displayed, to only rebuild them when the tree changes.
+    rows: Vec<Row>,
+}
+
+/// Marks the node containing the rows of the panel.
+#[derive(Component)]
+struct HierarchyRows;
+
+/// Marks the text showing the components of the selection.
+#[derive(Component)]
+struct HierarchyDetails;
+
+/// A button selecting an entity of the scene.
+#[derive(Component)]
+struct SelectButton(Entity);
+
+/// A button expanding or collapsing an entity of the scene.
+#[derive(Component)]
+struct ExpandButton(Entity);
+
+fn text_font() -> TextFont {
+    TextFont {
+        font_size: FONT_SIZE,
+        ..default()
+    }
+}
+
+fn setup_panel(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(12.0),
+                left: Val::Px(12.0),
+                max_height: Val::Percent(60.0),
+                max_width: Val::Percent(40.0),
+                flex_direction: FlexDirection::Column,
+                overflow: Overflow::clip(),
+                padding: UiRect::all(Val::Px(4.0)),
+                ..default()
+            },
+            BackgroundColor(Color::BLACK.with_alpha(0.6)),
+        ))
+        .with_children(|panel| {
+            panel.spawn((Text::new(TITLE), text_font()));
+            panel.spawn((
+                HierarchyRows,
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    flex_shrink: 1.0,
+                    overflow: Overflow::clip(),
+                    ..default()
+                },
+            ));
+            panel.spawn((HierarchyDetails, Text::default(), text_font()));
+        });
+}
+
+fn toggle_panel(
+    key_input: Res<ButtonInput<KeyCode>>,
+    mut panel: ResMut<HierarchyPanel>,
+    mut nodes: Query<&mut Node, Or<(With<HierarchyRows>, With<HierarchyDetails>)>>,
+) {
+    if !key_input.just_pressed(KeyCode::F1) {
+        return;
+    }
+    panel.collapsed ^= true;
+    let display = if panel.collapsed {
+        Display::None
+    } else {
+        Display::Flex
+    };
+    for mut node in &mut nodes {
+        node.display = display;
+    }
+}
+
+/// Appends the rows of `entity` and of its listed descendants to `rows`.
+fn collect_rows(
+    entity: Entity,
+    depth: usize,
+    panel: &HierarchyPanel,
+    nodes: &Query<(Option<&Name>, Option<&Children>)>,
+    rows: &mut Vec<Row>,
+) {
+    let Ok((name, children)) = nodes.get(entity) else {
+        return;
+    };
+    let children = children.map(|children| &children[..]).unwrap_or_default();
+    let expanded = panel.expanded.contains(&entity);
+    rows.push(Row {
+        entity,
+        depth,
+        name: match name {
+            Some(name) => name.to_string(),
+            None => format!("{entity}"),
+        },
+        has_children: !children.is_empty(),
+        expanded,
+    });
+    if expanded {
+        for &child in children {
+            collect_rows(child, depth + 1, panel, nodes, rows);
+        }
+    }
+}
+
+/// Rebuilds the rows of the panel when the tree of the scene, or the expanded nodes, change.
+fn update_rows(
+    mut panel: ResMut<HierarchyPanel>,
+    scene_handle: Res<SceneHandle>,
+    nodes: Query<(Option<&Name>, Option<&Children>)>,
+    container: Query<Entity, With<HierarchyRows>>,
+    mut commands: Commands,
+) {
+    let mut rows = Vec::new();
+    if let Some((_, Some(children))) = scene_handle
+        .scene_root()
+        .and_then(|root| nodes.get(root).ok())
+    {
+        for &child in children {
+            collect_rows(child, 0, &panel, &nodes, &mut rows);
+        }
+    }
+    if rows == panel.rows {
+        return;
+    }
+
+    // Forget about the entities that were despawned, such as when another scene is loaded.
+    if panel
+        .selected
+        .is_some_and(|entity| nodes.get(entity).is_err())
+    {
+        panel.selected = None;
+    }
+    panel.expanded.retain(|entity| nodes.contains(*entity));
+
+    let Ok(container) = container.get_single() else {
+        return;
+    };
+    commands
+        .entity(container)
+        .despawn_descendants()
+        .with_children(|container| {
+            for row in &rows {
+                container
+                    .spawn(Node {
+                        padding: UiRect::left(Val::Px(row.depth as f32 * INDENT)),
+                        column_gap: Val::Px(4.0),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        let marker = match (row.has_children, row.expanded) {
+                            (false, _) => " ",
+                            (true, false) => "+",
+                            (true, true) => "-",
+                        };
+                        let mut expand = parent.spawn((Text::new(marker), text_font()));
+                        if row.has_children {
+                            expand.insert((Button, ExpandButton(row.entity)));
+                        }
+                        parent.spawn((
+                            Button,
+                            SelectButton(row.entity),
+                            Text::new(row.name.clone()),
+                            text_font(),
+                            BackgroundColor(ROW_COLOR),
+                        ));
+                    });
+            }
+        });
+    panel.rows = rows;
+}
+
+fn handle_clicks(
+    mut panel: ResMut<HierarchyPanel>,
+    select_buttons: Query<(&Interaction, &SelectButton), Changed<Interaction>>,
+    expand_buttons: Query<(&Interaction, &ExpandButton), Changed<Interaction>>,
+    mut controllers: Query<&mut CameraController>,
+) {
+    for (interaction, ExpandButton(entity)) in &expand_buttons {
+        if *interaction == Interaction::Pressed && !panel.expanded.remove(entity) {
+            panel.expanded.insert(*entity);
+        }
+    }
+    for (interaction, SelectButton(entity)) in &select_buttons {
+        if *interaction == Interaction::Pressed {
+            panel.selected = Some(*entity);
+            for mut controller in &mut controllers {
+                controller.selection = Some(*entity);
+            }
+        }
+    }
+}
+
+fn highlight_rows(
+    panel: Res<HierarchyPanel>,
+    mut buttons: Query<(&Interaction, &SelectButton, &mut BackgroundColor)>,
+) {
+    for (interaction, SelectButton(entity), mut background) in &mut buttons {
+        let color = if panel.selected == Some(*entity) {
+            SELECTED_ROW_COLOR
+        } else if *interaction == Interaction::Hovered {
+            HOVERED_ROW_COLOR
+        } else {
+            ROW_COLOR
+        };
+        if background.0 != color {
+            background.0 = color;
+        }
+    }
+}
+
+/// Outlines the meshes of the selection with their bounding boxes, or shows the axes of the
+/// selection if it has no meshes.
+fn outline_selection(
+    panel: Res<HierarchyPanel>,
+    children: Query<&Children>,
+    meshes: Query<(&GlobalTransform, &Aabb), With<Mesh3d>>,
+    transforms: Query<&GlobalTransform>,
+    mut gizmos: Gizmos,
+) {
+    let Some(selected) = panel.selected else {
+        return;
+    };
+    let mut has_meshes = false;
+    for entity in std::iter::once(selected).chain(children.iter_descendants(selected)) {
+        let Ok((transform, aabb)) = meshes.get(entity) else {
+            continue;
+        };
+        has_meshes = true;
+        let aabb_transform = Transform::from_translation(aabb.center.into())
+            .with_scale((aabb.half_extents * 2.0).into());
+        gizmos.cuboid(*transform * GlobalTransform::from(aabb_transform), YELLOW);
+    }
+    if !has_meshes {
+        if let Ok(transform) = transforms.get(selected) {
+            gizmos.axes(*transform, 0.5);
+        }
+    }
+}
+
+fn update_details(
+    panel: Res<HierarchyPanel>,
+    entities: Query<(
+        Option<&Name>,
+        Option<&Transform>,
+        Option<&Mesh3d>,
+        Option<&MeshMaterial3d<StandardMaterial>>,
+        Option<&Children>,
+    )>,
+    meshes: Res<Assets<Mesh>>,
+    materials: Res<Assets<StandardMaterial>>,
+    mut texts: Query<&mut Text, With<HierarchyDetails>>,
+) {
+    let Ok(mut text) = texts.get_single_mut() else {
+        return;
+    };
+
+    let mut details = String::new();
+    let selected = panel
+        .selected
+        .and_then(|entity| Some((entity, entities.get(entity).ok()?)));
+    if let Some((entity, (name, transform, mesh, material, children))) = selected {
+        let _ = match name {
+            Some(name) => writeln!(details, "\nSelected: {name} ({entity})"),
+            None => writeln!(details, "\nSelected: {entity}"),
+        };
+        if let Some(transform) = transform {
+            let (x, y, z) = transform.rotation.to_euler(EulerRot::XYZ);
+            let _ = writeln!(details, "Translation: {:.3}", transform.translation);
+            let _ = writeln!(
+                details,
+                "Rotation: [{:.1}, {:.1}, {:.1}] degrees",
+                x.to_degrees(),
+                y.to_degrees(),
+                z.to_degrees()
+            );
+            let _ = writeln!(details, "Scale: {:.3}", transform.scale);
+        }
+        if let Some(mesh) = mesh {
+            let _ = write!(details, "Mesh: {:?}", mesh.id());
+            if let Some(mesh) = meshes.get(mesh) {
+                let _ = write!(
+                    details,
+                    ", {} vertices, {:?}",
+                    mesh.count_vertices(),
+                    mesh.primitive_topology()
+                );
+            }
+            details.push('\n');
+        }
+        if let Some(material) = material {
+            let _ = write!(details, "Material: {:?}", material.id());
+            if let Some(material) = materials.get(material) {
+                let _ = write!(
+                    details,
+                    ", base color {:?}, metallic {:.3}, roughness {:.3}",
+                    material.base_color.to_srgba(),
+                    material.metallic,
+                    material.perceptual_roughness
+                );
+            }
+            details.push('\n');
+        }
+        let _ = writeln!(
+            details,
+            "Children: {}",
+            children.map_or(0, |children| children.len())
+        );
+    } else {
+        details.push_str("\nNo entity selected\n");
+    }
+
+    if text.0 != details {
+        text.0 = details;
+    }
+}
+
+pub struct HierarchyPlugin;
+
+impl Plugin for HierarchyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HierarchyPanel>()
+            .add_systems(Startup, setup_panel)
+            .add_systems(
+                Update,
+                (
+                    toggle_panel,
+                    handle_clicks,
+                    update_rows.after(handle_clicks),
+                    highlight_rows.after(update_rows),
+                    outline_selection.after(update_rows),
+                    update_details.after(update_rows),
+                ),
+            );
+    }
+}
@@ -4,6 +4,11 @@
 //! replacing the path as appropriate.
 //! In case of multiple scenes, you can select which to display by adapting the file path: `/path/to/model.gltf#Scene1`.
 //! With no arguments it will load the `FlightHelmet` glTF model from the repository assets subdirectory.
+//! Once running, a different `.gltf` or `.glb` file can be viewed by dropping it onto the window.
+//!
+//! Other environment maps to light the scene with can be given with
+//! `--environment diffuse.ktx2,specular.ktx2`, or by dropping both files onto the window, and
+//! the exposure with `--exposure <ev100>`.
 //!
 //! If you want to hot reload asset changes, enable the `file_watcher` cargo feature.
 
@@ -15,7 +20,10 @@ use bevy::{
 
 #[cfg(feature = "bevy_dev_tools")]
 use bevy::{
-    dev_tools::fps_overlay::{FpsOverlayConfig, FpsOverlayPlugin},
+    dev_tools::{
+        fps_overlay::{FpsOverlayConfig, FpsOverlayPlugin},
+        skeleton_gizmos::{SkeletonGizmoConfigGroup, SkeletonGizmoPlugin},
+    },
     text::{FontSmoothing, LineHeight},
 };
 
@@ -24,15 +32,63 @@ mod camera_controller;
 
 #[cfg(feature = "animation")]
 mod animation_plugin;
+mod debug_view_plugin;
+mod environment_plugin;
+mod hierarchy_plugin;
+mod material_inspector_plugin;
 mod morph_viewer_plugin;
 mod scene_viewer_plugin;
+mod validation_plugin;
 
 use bevy_render::view::VisibilityRange;
 use camera_controller::{CameraController, CameraControllerPlugin};
+use debug_view_plugin::DebugViewPlugin;
+use environment_plugin::{EnvironmentPaths, EnvironmentPlugin};
+use hierarchy_plugin::HierarchyPlugin;
+use material_inspector_plugin::MaterialInspectorPlugin;
 use morph_viewer_plugin::MorphViewerPlugin;
-use scene_viewer_plugin::{SceneHandle, SceneViewerPlugin};
+use scene_viewer_plugin::{load_gltf, SceneHandle, SceneViewerPlugin};
+use validation_plugin::ValidationPlugin;
+
+/// The command line arguments of the scene viewer.
+#[derive(Resource)]
+struct Args {
+    scene_path: Option<String>,
+}
+
+/// Parses the command line arguments, returning the arguments of the [`EnvironmentPlugin`] and
+/// the [`ValidationPlugin`] separately.
+fn parse_args() -> (Args, EnvironmentPlugin, ValidationPlugin) {
+    let mut args = Args { scene_path: None };
+    let mut environment = EnvironmentPlugin {
+        environments: Vec::new(),
+        exposure: None,
+    };
+    let mut validation = ValidationPlugin { report_path: None };
+    let mut arguments = std::env::args().skip(1);
+    while let Some(argument) = arguments.next() {
+        match argument.as_str() {
+            "--environment" => match arguments.next().as_deref().map(EnvironmentPaths::parse) {
+                Some(Some(paths)) => environment.environments.push(paths),
+                _ => eprintln!("Expected `--environment diffuse.ktx2,specular.ktx2[,skybox.ktx2]`"),
+            },
+            "--exposure" => match arguments.next().and_then(|value| value.parse().ok()) {
+                Some(exposure) => environment.exposure = Some(exposure),
+                None => eprintln!("Expected `--exposure <ev100>`"),
+            },
+            "--validation-report" => match arguments.next() {
+                Some(path) => validation.report_path = Some(path.into()),
+                None => eprintln!("Expected `--validation-report <report.json>`"),
+            },
+            _ if args.scene_path.is_none() => args.scene_path = Some(argument),
+            _ => eprintln!("Ignoring unexpected argument `{argument}`"),
+        }
+    }
+    (args, environment, validation)
+}
 
 fn main() {
+    let (args, environment_plugin, validation_plugin) = parse_args();
     let mut app = App::new();
     app.add_plugins((
         DefaultPlugins
@@ -50,6 +106,11 @@ fn main() {
         CameraControllerPlugin,
         SceneViewerPlugin,
         MorphViewerPlugin,
+        MaterialInspectorPlugin,
+        HierarchyPlugin,
+        environment_plugin,
+        DebugViewPlugin,
+        validation_plugin,
         #[cfg(feature = "bevy_dev_tools")]
         FpsOverlayPlugin {
             config: FpsOverlayConfig {
@@ -68,6 +129,7 @@ fn main() {
             },
         },
     ))
+    .insert_resource(args)
     .add_systems(Startup, (setup,).chain())
     .add_systems(PreUpdate, setup_scene_after_load)
     .add_systems(PostUpdate, debug_visibility);
@@ -75,9 +137,29 @@ fn main() {
     #[cfg(feature = "animation")]
     app.add_plugins(animation_plugin::AnimationManipulationPlugin);
 
+    #[cfg(feature = "bevy_dev_tools")]
+    app.add_plugins(SkeletonGizmoPlugin)
+        .add_systems(Update, toggle_skeletons);
+
     app.run();
 }
 
+#[cfg(feature = "bevy_dev_tools")]
+fn toggle_skeletons(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut config_store: ResMut<GizmoConfigStore>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F3) {
+        return;
+    }
+    let (_, config) = config_store.config_mut::<SkeletonGizmoConfigGroup>();
+    if keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        config.joint_names ^= true;
+    } else {
+        config.draw_all ^= true;
+    }
+}
+
 fn parse_scene(scene_path: String) -> (String, usize) {
     if scene_path.contains('#') {
         let gltf_and_scene = scene_path.split('#').collect::<Vec<_>>();
@@ -93,21 +175,24 @@ fn parse_scene(scene_path: String) -> (String, usize) {
     (scene_path, 0)
 }
 
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let scene_path = std::env::args()
-        .nth(1)
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, args: Res<Args>) {
+    let scene_path = args
+        .scene_path
+        .clone()
         .unwrap_or_else(|| "assets/models/animated/MorphStressTest.gltf".to_string());
     info!("Loading {}", scene_path);
     let (file_path, scene_index) = parse_scene(scene_path);
 
-    commands.insert_resource(SceneHandle::new(asset_server.load(file_path), scene_index));
+    commands.insert_resource(SceneHandle::new(
+        load_gltf(&asset_server, file_path),
+        scene_index,
+    ));
 }
 
 fn setup_scene_after_load(
     mut commands: Commands,
     mut setup: Local<bool>,
     mut scene_handle: ResMut<SceneHandle>,
-    asset_server: Res<AssetServer>,
     meshes: Query<(&GlobalTransform, Option<&Aabb>), With<Mesh3d>>,
 ) {
     if scene_handle.is_loaded && !*setup {
@@ -145,6 +230,8 @@ fn setup_scene_after_load(
         let camera_controller = CameraController {
             walk_speed,
             run_speed: 0.1 * walk_speed,
+            // Orbit around the center of the scene, where the camera looks.
+            orbit_distance: size * Vec3::new(0.5, 0.25, 0.5).length(),
             ..default()
         };
 
@@ -166,14 +253,6 @@ fn setup_scene_after_load(
                 is_active: false,
                 ..default()
             },
-            EnvironmentMapLight {
-                diffuse_map: asset_server
-                    .load("assets/environment_maps/pisa_diffuse_rgb9e5_zstd.ktx2"),
-                specular_map: asset_server
-                    .load("assets/environment_maps/pisa_specular_rgb9e5_zstd.ktx2"),
-                intensity: 150.0,
-                ..default()
-            },
             camera_controller,
         ));
 
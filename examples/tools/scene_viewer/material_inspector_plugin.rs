@@ -0,0 +1,352 @@
+//! Inspect and tweak the [`StandardMaterial`] of meshes in the loaded scene.
+//!
+//! Select a mesh by right-clicking it (requires the `bevy_mesh_picking_backend` feature)
+//! or by cycling through all meshes with `Tab`. The material of the selected mesh is shown
+//! in an on-screen panel, where its fields can be edited live with the arrow keys.
+//!
+//! Note that glTF materials are shared between all the meshes that use them,
+//! so editing the material of one mesh affects all of them.
+
+use bevy::prelude::*;
+use std::fmt::Write;
+
+const FONT_SIZE: f32 = 13.0;
+
+/// How much a scalar field changes per second while its key is held.
+const CHANGE_PER_SECOND: f32 = 0.5;
+
+const INSTRUCTIONS: &str = "\
+Material Inspector:
+    Tab / Shift+Tab   - select next / previous mesh
+    Right click       - select the clicked mesh
+    Up / Down         - select field
+    Left / Right      - edit field
+    Escape            - clear selection
+";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Field {
+    BaseColorRed,
+    BaseColorGreen,
+    BaseColorBlue,
+    BaseColorAlpha,
+    Metallic,
+    Roughness,
+    EmissiveRed,
+    EmissiveGreen,
+    EmissiveBlue,
+    AlphaMode,
+}
+
+impl Field {
+    const ALL: [Field; 10] = [
+        Field::BaseColorRed,
+        Field::BaseColorGreen,
+        Field::BaseColorBlue,
+        Field::BaseColorAlpha,
+        Field::Metallic,
+        Field::Roughness,
+        Field::EmissiveRed,
+        Field::EmissiveGreen,
+        Field::EmissiveBlue,
+        Field::AlphaMode,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Field::BaseColorRed => "base color r",
+            Field::BaseColorGreen => "base color g",
+            Field::BaseColorBlue => "base color b",
+            Field::BaseColorAlpha => "base color a",
+            Field::Metallic => "metallic",
+            Field::Roughness => "perceptual roughness",
+            Field::EmissiveRed => "emissive r",
+            Field::EmissiveGreen => "emissive g",
+            Field::EmissiveBlue => "emissive b",
+            Field::AlphaMode => "alpha mode",
+        }
+    }
+
+    /// Writes the current value of this field of `material` into `out`.
+    fn write_value(self, material: &StandardMaterial, out: &mut String) -> std::fmt::Result {
+        let base_color = material.base_color.to_srgba();
+        let emissive = material.emissive;
+        match self {
+            Field::BaseColorRed => write!(out, "{:.3}", base_color.red),
+            Field::BaseColorGreen => write!(out, "{:.3}", base_color.green),
+            Field::BaseColorBlue => write!(out, "{:.3}", base_color.blue),
+            Field::BaseColorAlpha => write!(out, "{:.3}", base_color.alpha),
+            Field::Metallic => write!(out, "{:.3}", material.metallic),
+            Field::Roughness => write!(out, "{:.3}", material.perceptual_roughness),
+            Field::EmissiveRed => write!(out, "{:.3}", emissive.red),
+            Field::EmissiveGreen => write!(out, "{:.3}", emissive.green),
+            Field::EmissiveBlue => write!(out, "{:.3}", emissive.blue),
+            Field::AlphaMode => write!(out, "{:?}", material.alpha_mode),
+        }
+    }
+
+    /// Changes a scalar field of `material` by `delta`.
+    fn apply_delta(self, material: &mut StandardMaterial, delta: f32) {
+        let mut base_color = material.base_color.to_srgba();
+        match self {
+            Field::BaseColorRed => base_color.red = (base_color.red + delta).clamp(0.0, 1.0),
+            Field::BaseColorGreen => base_color.green = (base_color.green + delta).clamp(0.0, 1.0),
+            Field::BaseColorBlue => base_color.blue = (base_color.blue + delta).clamp(0.0, 1.0),
+            Field::BaseColorAlpha => base_color.alpha = (base_color.alpha + delta).clamp(0.0, 1.0),
+            Field::Metallic => material.metallic = (material.metallic + delta).clamp(0.0, 1.0),
+            Field::Roughness => {
+                material.perceptual_roughness =
+                    (material.perceptual_roughness + delta).clamp(0.089, 1.0);
+            }
+            // Emissive is HDR, so it is only bounded below.
+            Field::EmissiveRed => material.emissive.red = (material.emissive.red + delta).max(0.0),
+            Field::EmissiveGreen => {
+                material.emissive.green = (material.emissive.green + delta).max(0.0);
+            }
+            Field::EmissiveBlue => {
+                material.emissive.blue = (material.emissive.blue + delta).max(0.0);
+            }
+            Field::AlphaMode => {}
+        }
+        material.base_color = base_color.into();
+    }
+}
+
+/// Cycles through the alpha modes, keeping the threshold of an existing [`AlphaMode::Mask`].
+fn next_alpha_mode(alpha_mode: AlphaMode, forward: bool) -> AlphaMode {
+    let modes = [
+        AlphaMode::Opaque,
+        AlphaMode::Mask(match alpha_mode {
+            AlphaMode::Mask(threshold) => threshold,
+            _ => 0.5,
+        }),
+        AlphaMode::Blend,
+        AlphaMode::Premultiplied,
+        AlphaMode::AlphaToCoverage,
+        AlphaMode::Add,
+        AlphaMode::Multiply,
+    ];
+    let current = modes
+        .iter()
+        .position(|mode| *mode == alpha_mode)
+        .unwrap_or(0);
+    let next = if forward {
+        (current + 1) % modes.len()
+    } else {
+        (current + modes.len() - 1) % modes.len()
+    };
+    modes[next]
+}
+
+/// The mesh whose material is currently inspected, and the selected field.
+#[derive(Resource, Default)]
+struct MaterialInspector {
+    selected: Option<Entity>,
+    field: usize,
+}
+
+impl MaterialInspector {
+    fn field(&self) -> Field {
+        Field::ALL[self.field]
+    }
+}
+
+/// Marks the text of the inspector panel.
+#[derive(Component)]
+struct MaterialInspectorText;
+
+fn setup_panel(mut commands: Commands) {
+    commands.spawn((
+        MaterialInspectorText,
+        Text::new(INSTRUCTIONS),
+        TextFont {
+            font_size: FONT_SIZE,
+            ..default()
+        },
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.0),
+            right: Val::Px(12.0),
+            ..default()
+        },
+        BackgroundColor(Color::BLACK.with_alpha(0.6)),
+    ));
+}
+
+/// Moves the selection to `entity`, keeping the bounding box gizmo on the selected mesh only.
+fn select(inspector: &mut MaterialInspector, entity: Option<Entity>, commands: &mut Commands) {
+    if inspector.selected == entity {
+        return;
+    }
+    if let Some(previous) = inspector.selected {
+        if let Some(mut entity_commands) = commands.get_entity(previous) {
+            entity_commands.remove::<ShowAabbGizmo>();
+        }
+    }
+    if let Some(entity) = entity {
+        commands.entity(entity).insert(ShowAabbGizmo {
+            color: Some(Color::WHITE),
+        });
+    }
+    inspector.selected = entity;
+}
+
+fn cycle_selection(
+    mut inspector: ResMut<MaterialInspector>,
+    key_input: Res<ButtonInput<KeyCode>>,
+    meshes: Query<Entity, With<MeshMaterial3d<StandardMaterial>>>,
+    mut commands: Commands,
+) {
+    if key_input.just_pressed(KeyCode::Escape) {
+        select(&mut inspector, None, &mut commands);
+        return;
+    }
+    if !key_input.just_pressed(KeyCode::Tab) {
+        return;
+    }
+
+    let mut entities: Vec<Entity> = meshes.iter().collect();
+    if entities.is_empty() {
+        return;
+    }
+    entities.sort_unstable();
+
+    let backwards = key_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let next = match inspector
+        .selected
+        .and_then(|selected| entities.iter().position(|entity| *entity == selected))
+    {
+        Some(i) if backwards => (i + entities.len() - 1) % entities.len(),
+        Some(i) => (i + 1) % entities.len(),
+        None if backwards => entities.len() - 1,
+        None => 0,
+    };
+    select(&mut inspector, Some(entities[next]), &mut commands);
+}
+
+#[cfg(feature = "bevy_mesh_picking_backend")]
+fn select_on_click(
+    mut trigger: Trigger<Pointer<Click>>,
+    mut inspector: ResMut<MaterialInspector>,
+    meshes: Query<(), With<MeshMaterial3d<StandardMaterial>>>,
+    mut commands: Commands,
+) {
+    if trigger.event().button != PointerButton::Secondary {
+        return;
+    }
+    // Clicks bubble up the hierarchy, only handle them on the mesh that was hit.
+    trigger.propagate(false);
+    let target = trigger.event().target;
+    if meshes.contains(target) {
+        select(&mut inspector, Some(target), &mut commands);
+    }
+}
+
+fn edit_material(
+    mut inspector: ResMut<MaterialInspector>,
+    key_input: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    meshes: Query<&MeshMaterial3d<StandardMaterial>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let field_count = Field::ALL.len();
+    if key_input.just_pressed(KeyCode::ArrowDown) {
+        inspector.field = (inspector.field + 1) % field_count;
+    }
+    if key_input.just_pressed(KeyCode::ArrowUp) {
+        inspector.field = (inspector.field + field_count - 1) % field_count;
+    }
+
+    let Some(material_handle) = inspector
+        .selected
+        .and_then(|entity| meshes.get(entity).ok())
+    else {
+        return;
+    };
+
+    let field = inspector.field();
+    if field == Field::AlphaMode {
+        let forward = key_input.just_pressed(KeyCode::ArrowRight);
+        if !forward && !key_input.just_pressed(KeyCode::ArrowLeft) {
+            return;
+        }
+        if let Some(material) = materials.get_mut(material_handle) {
+            material.alpha_mode = next_alpha_mode(material.alpha_mode, forward);
+        }
+        return;
+    }
+
+    let direction = match (
+        key_input.pressed(KeyCode::ArrowLeft),
+        key_input.pressed(KeyCode::ArrowRight),
+    ) {
+        (true, false) => -1.0,
+        (false, true) => 1.0,
+        _ => return,
+    };
+    if let Some(material) = materials.get_mut(material_handle) {
+        field.apply_delta(material, direction * CHANGE_PER_SECOND * time.delta_secs());
+    }
+}
+
+fn update_panel(
+    inspector: Res<MaterialInspector>,
+    meshes: Query<(&MeshMaterial3d<StandardMaterial>, Option<&Name>)>,
+    materials: Res<Assets<StandardMaterial>>,
+    mut texts: Query<&mut Text, With<MaterialInspectorText>>,
+) {
+    let Ok(mut text) = texts.get_single_mut() else {
+        return;
+    };
+
+    let mut panel = String::from(INSTRUCTIONS);
+    let selected = inspector
+        .selected
+        .and_then(|entity| Some((entity, meshes.get(entity).ok()?)));
+    if let Some((entity, (material_handle, name))) = selected {
+        let _ = match name {
+            Some(name) => writeln!(panel, "\nMesh: {name} ({entity})"),
+            None => writeln!(panel, "\nMesh: {entity}"),
+        };
+        let _ = writeln!(panel, "Material: {:?}", material_handle.id());
+        if let Some(material) = materials.get(material_handle) {
+            for (i, field) in Field::ALL.into_iter().enumerate() {
+                let cursor = if i == inspector.field { ">" } else { " " };
+                let _ = write!(panel, "{cursor} {}: ", field.label());
+                let _ = field.write_value(material, &mut panel);
+                panel.push('\n');
+            }
+        }
+    } else {
+        panel.push_str("\nNo mesh selected\n");
+    }
+
+    if text.0 != panel {
+        text.0 = panel;
+    }
+}
+
+pub struct MaterialInspectorPlugin;
+
+impl Plugin for MaterialInspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MaterialInspector>()
+            .add_systems(Startup, setup_panel)
+            .add_systems(
+                Update,
+                (
+                    cycle_selection,
+                    edit_material.after(cycle_selection),
+                    update_panel.after(edit_material),
+                ),
+            );
+
+        #[cfg(feature = "bevy_mesh_picking_backend")]
+        {
+            if !app.is_plugin_added::<MeshPickingPlugin>() {
+                app.add_plugins(MeshPickingPlugin);
+            }
+            app.add_observer(select_on_click);
+        }
+    }
+}
@@ -156,6 +156,10 @@ struct WeightsControl {
     weights: Vec<Target>,
 }
 
+/// Marks the text listing the morph target controls.
+#[derive(Component)]
+struct WeightsControlText;
+
 struct MorphKey {
     name: &'static str,
     modifiers: &'static [KeyCode],
@@ -181,7 +185,7 @@ impl MorphKey {
 }
 fn update_text(
     controls: Option<ResMut<WeightsControl>>,
-    texts: Query<Entity, With<Text>>,
+    texts: Query<Entity, With<WeightsControlText>>,
     morphs: Query<&MorphWeights>,
     mut writer: TextUiWriter,
 ) {
@@ -241,8 +245,17 @@ fn detect_morphs(
     meshes: Res<Assets<Mesh>>,
     scene_handle: Res<SceneHandle>,
     render_device: Res<RenderDevice>,
+    texts: Query<Entity, With<WeightsControlText>>,
     mut setup: Local<bool>,
 ) {
+    if !scene_handle.is_loaded && *setup {
+        // A new scene is being loaded, remove the controls of the previous one.
+        *setup = false;
+        commands.remove_resource::<WeightsControl>();
+        for text in &texts {
+            commands.entity(text).despawn_recursive();
+        }
+    }
     let no_morphing = morphs.iter().len() == 0;
     if no_morphing {
         return;
@@ -287,6 +300,7 @@ fn detect_morphs(
     commands.insert_resource(WeightsControl { weights: detected });
     commands
         .spawn((
+            WeightsControlText,
             Text::default(),
             Node {
                 position_type: PositionType::Absolute,
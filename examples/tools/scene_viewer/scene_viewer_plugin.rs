@@ -2,27 +2,43 @@
 //! To use in your own application:
 //! - Copy the code for the `SceneViewerPlugin` and add the plugin to your App.
 //! - Insert an initialized `SceneHandle` resource into your App's `AssetServer`.
+//!
+//! Dropping a `.gltf` or `.glb` file onto the window replaces the displayed scene,
+//! keeping the camera controller and any lights spawned by the viewer.
 
 use bevy::{
-    gltf::Gltf,
+    asset::AssetPath,
+    gltf::{Gltf, GltfLoaderSettings},
     input::common_conditions::input_just_pressed,
     prelude::*,
     scene::{InstanceId, SceneInstance, SceneInstanceReady},
+    window::FileDragAndDrop,
 };
 use bevy_render::{
-    primitives::Aabb,
-    view::{NoCpuCulling, NoFrustumCulling},
+    primitives::{Aabb, Sphere},
+    view::{
+        screenshot::{save_to_disk, Screenshot},
+        NoCpuCulling, NoFrustumCulling,
+    },
 };
 
 use std::{f32::consts::*, fmt};
 
 use super::camera_controller::*;
 
+/// Loads a glTF file, with its validation report.
+pub fn load_gltf<'a>(asset_server: &AssetServer, path: impl Into<AssetPath<'a>>) -> Handle<Gltf> {
+    asset_server.load_with_settings(path, |settings: &mut GltfLoaderSettings| {
+        settings.validate = true;
+    })
+}
+
 #[derive(Resource)]
 pub struct SceneHandle {
     pub gltf_handle: Handle<Gltf>,
     scene_index: usize,
     instance_id: Option<InstanceId>,
+    scene_root: Option<Entity>,
     pub is_loaded: bool,
     pub has_light: bool,
 }
@@ -33,10 +49,16 @@ impl SceneHandle {
             gltf_handle,
             scene_index,
             instance_id: None,
+            scene_root: None,
             is_loaded: false,
             has_light: false,
         }
     }
+
+    /// The entity that the scene is spawned under.
+    pub fn scene_root(&self) -> Option<Entity> {
+        self.scene_root
+    }
 }
 
 #[cfg(not(feature = "animation"))]
@@ -45,6 +67,11 @@ Scene Controls:
     L           - animate light direction
     U           - toggle shadows
     C           - cycle through the camera controller and any cameras loaded from the scene
+    F12         - save a screenshot
+    Shift+F12   - record a 360° turntable around the scene
+    F3          - toggle skeletons (Shift+F3 for joint names), requires the bevy_dev_tools feature
+
+    Drop a .gltf or .glb file onto the window to load it.
 
     compile with "--features animation" for animation controls.
 "#;
@@ -56,9 +83,16 @@ Scene Controls:
     U           - toggle shadows
     B           - toggle bounding boxes
     C           - cycle through the camera controller and any cameras loaded from the scene
+    F12         - save a screenshot
+    Shift+F12   - record a 360° turntable around the scene
+    F3          - toggle skeletons (Shift+F3 for joint names), requires the bevy_dev_tools feature
+
+    Drop a .gltf or .glb file onto the window to load it.
 
     Space       - Play/Pause animation
     Enter       - Cycle through animations
+    F2          - Show the animation graphs of the animation players
+    [ ] , .     - Change speed and step through animations in the timeline
 ";
 
 impl fmt::Display for SceneHandle {
@@ -72,7 +106,8 @@ pub struct SceneViewerPlugin;
 impl Plugin for SceneViewerPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<CameraTracker>()
-            .add_systems(PreUpdate, scene_load_check)
+            .init_resource::<Turntable>()
+            .add_systems(PreUpdate, (load_dropped_scene, scene_load_check).chain())
             .add_systems(
                 Update,
                 (
@@ -80,6 +115,7 @@ impl Plugin for SceneViewerPlugin {
                     camera_tracker,
                     // show_visibility_sphere,
                     toggle_bounding_boxes.run_if(input_just_pressed(KeyCode::KeyB)),
+                    capture_screenshots.after(camera_tracker),
                 ),
             );
     }
@@ -150,6 +186,7 @@ fn scene_load_check(
                 let parent = commands
                     .spawn(SceneRoot(gltf_scene_handle.clone_weak()))
                     .id();
+                scene_handle.scene_root = Some(parent);
                 commands.entity(parent).observe(
                     |trigger: Trigger<SceneInstanceReady>,
                      mut scene_handle: ResMut<SceneHandle>,
@@ -182,6 +219,59 @@ fn scene_load_check(
     }
 }
 
+/// Replaces the displayed scene with a glTF file dropped onto the window.
+fn load_dropped_scene(
+    mut drop_events: EventReader<FileDragAndDrop>,
+    mut scene_handle: ResMut<SceneHandle>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    let Some(path_buf) = drop_events
+        .read()
+        .filter_map(|event| match event {
+            FileDragAndDrop::DroppedFile { path_buf, .. } => Some(path_buf),
+            _ => None,
+        })
+        .last()
+    else {
+        return;
+    };
+
+    let is_gltf = path_buf
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            extension.eq_ignore_ascii_case("gltf") || extension.eq_ignore_ascii_case("glb")
+        });
+    if !is_gltf {
+        // Cubemaps are loaded as environment maps by the `EnvironmentPlugin`.
+        let is_environment = path_buf
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| {
+                extension.eq_ignore_ascii_case("ktx2") || extension.eq_ignore_ascii_case("hdr")
+            });
+        if is_environment {
+            return;
+        }
+        warn!(
+            "Ignoring dropped file {}: only .gltf and .glb files are supported",
+            path_buf.display()
+        );
+        return;
+    }
+
+    if let Some(scene_root) = scene_handle.scene_root.take() {
+        commands.entity(scene_root).despawn_recursive();
+    }
+
+    info!("Loading {}", path_buf.display());
+    // The new scene may not contain lights, so remember whether a default one was spawned.
+    let has_light = scene_handle.has_light;
+    *scene_handle = SceneHandle::new(load_gltf(&asset_server, path_buf.clone()), 0);
+    scene_handle.has_light = has_light;
+}
+
 fn update_lights(
     key_input: Res<ButtonInput<KeyCode>>,
     time: Res<Time>,
@@ -230,6 +320,26 @@ impl CameraTracker {
         self.active_index.map(|i| self.cameras[i])
     }
 
+    /// Stops tracking a despawned camera. Returns the camera that should become active if the
+    /// removed camera was the active one.
+    fn untrack_camera(&mut self, entity: Entity) -> Option<Entity> {
+        let index = self.cameras.iter().position(|camera| *camera == entity)?;
+        self.cameras.remove(index);
+        let active_index = self.active_index?;
+        if self.cameras.is_empty() {
+            self.active_index = None;
+            None
+        } else if index == active_index {
+            self.active_index = Some(index % self.cameras.len());
+            self.active_camera()
+        } else {
+            if index < active_index {
+                self.active_index = Some(active_index - 1);
+            }
+            None
+        }
+    }
+
     fn set_next_active(&mut self) -> Option<Entity> {
         let active_index = self.active_index?;
         let new_i = (active_index + 1) % self.cameras.len();
@@ -241,12 +351,22 @@ impl CameraTracker {
 fn camera_tracker(
     mut camera_tracker: ResMut<CameraTracker>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut removed_cameras: RemovedComponents<Camera>,
     mut queries: ParamSet<(
         Query<(Entity, &mut Camera), (Added<Camera>, Without<CameraController>)>,
         Query<(Entity, &mut Camera), (Added<Camera>, With<CameraController>)>,
         Query<&mut Camera>,
     )>,
 ) {
+    // forget cameras of unloaded scenes, falling back to another camera if the active one is gone
+    for entity in removed_cameras.read() {
+        if let Some(e) = camera_tracker.untrack_camera(entity) {
+            if let Ok(mut camera) = queries.p2().get_mut(e) {
+                camera.is_active = true;
+            }
+        }
+    }
+
     // track added scene camera entities first, to ensure they are preferred for the
     // default active camera
     for (entity, mut camera) in queries.p0().iter_mut() {
@@ -274,3 +394,137 @@ fn camera_tracker(
         }
     }
 }
+
+/// Number of frames captured by a turntable recording.
+const TURNTABLE_FRAMES: u32 = 120;
+
+/// A turntable recording in progress.
+struct TurntableCapture {
+    camera: Entity,
+    /// The world-space center of the scene, which the camera orbits around.
+    center: Vec3,
+    /// The world-space transform of the camera when the recording started.
+    start: Transform,
+    /// Whether the camera controller was enabled before the recording started.
+    controller_enabled: Option<bool>,
+    recording: u32,
+    frame: u32,
+}
+
+#[derive(Resource, Default)]
+struct Turntable {
+    capture: Option<TurntableCapture>,
+    recordings: u32,
+    screenshots: u32,
+}
+
+/// Computes the world-space center of the bounding box of all meshes in the scene.
+fn scene_center(meshes: &Query<(&GlobalTransform, &Aabb), With<Mesh3d>>) -> Option<Vec3> {
+    let mut min = Vec3A::splat(f32::MAX);
+    let mut max = Vec3A::splat(f32::MIN);
+    for (transform, aabb) in meshes {
+        // Go through a sphere to get conservative bounds of a rotated Aabb.
+        let sphere = Sphere {
+            center: Vec3A::from(transform.transform_point(Vec3::from(aabb.center))),
+            radius: transform.radius_vec3a(aabb.half_extents),
+        };
+        let aabb = Aabb::from(sphere);
+        min = min.min(aabb.min());
+        max = max.max(aabb.max());
+    }
+    (min.cmple(max).all()).then(|| Vec3::from((min + max) * 0.5))
+}
+
+fn capture_screenshots(
+    key_input: Res<ButtonInput<KeyCode>>,
+    mut turntable: ResMut<Turntable>,
+    camera_tracker: Res<CameraTracker>,
+    meshes: Query<(&GlobalTransform, &Aabb), With<Mesh3d>>,
+    mut cameras: Query<(
+        &mut Transform,
+        &GlobalTransform,
+        Option<&Parent>,
+        Option<&mut CameraController>,
+    )>,
+    global_transforms: Query<&GlobalTransform>,
+    mut commands: Commands,
+) {
+    let shift = key_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if key_input.just_pressed(KeyCode::F12) && turntable.capture.is_none() {
+        if !shift {
+            let path = format!("./screenshot-{}.png", turntable.screenshots);
+            turntable.screenshots += 1;
+            info!("Saving screenshot to {path}");
+            commands
+                .spawn(Screenshot::primary_window())
+                .observe(save_to_disk(path));
+            return;
+        }
+
+        let Some(camera) = camera_tracker.active_camera() else {
+            return;
+        };
+        let Some(center) = scene_center(&meshes) else {
+            warn!("Can't record a turntable: the scene has no meshes");
+            return;
+        };
+        let Ok((_, global_transform, _, controller)) = cameras.get_mut(camera) else {
+            return;
+        };
+        // Keep the camera controller from fighting over the camera transform.
+        let controller_enabled = controller.map(|mut controller| {
+            let enabled = controller.enabled;
+            controller.enabled = false;
+            enabled
+        });
+        info!(
+            "Recording a {TURNTABLE_FRAMES} frame turntable around {center} to ./turntable-{}-*.png",
+            turntable.recordings
+        );
+        turntable.capture = Some(TurntableCapture {
+            camera,
+            center,
+            start: global_transform.compute_transform(),
+            controller_enabled,
+            recording: turntable.recordings,
+            frame: 0,
+        });
+        turntable.recordings += 1;
+    }
+
+    let Some(capture) = turntable.capture.as_mut() else {
+        return;
+    };
+    let Ok((mut transform, _, parent, controller)) = cameras.get_mut(capture.camera) else {
+        warn!("Turntable camera was removed, stopping the recording");
+        turntable.capture = None;
+        return;
+    };
+
+    let parent_transform = parent.and_then(|parent| global_transforms.get(parent.get()).ok());
+    let to_local = |world: Transform| match parent_transform {
+        Some(parent_transform) => GlobalTransform::from(world).reparented_to(parent_transform),
+        None => world,
+    };
+
+    if capture.frame == TURNTABLE_FRAMES {
+        info!("...turntable recording done");
+        *transform = to_local(capture.start);
+        if let (Some(mut controller), Some(enabled)) = (controller, capture.controller_enabled) {
+            controller.enabled = enabled;
+        }
+        turntable.capture = None;
+        return;
+    }
+
+    let angle = capture.frame as f32 / TURNTABLE_FRAMES as f32 * TAU;
+    let mut world = capture.start;
+    world.rotate_around(capture.center, Quat::from_rotation_y(angle));
+    *transform = to_local(world);
+
+    let path = format!("./turntable-{}-{:04}.png", capture.recording, capture.frame);
+    commands
+        .spawn(Screenshot::primary_window())
+        .observe(save_to_disk(path));
+    capture.frame += 1;
+}
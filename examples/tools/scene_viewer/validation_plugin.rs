@@ -0,0 +1,148 @@
+//! Report the problems of the loaded glTF file that affect how it renders or performs, found by
+//! the glTF loader and checked against the render device, once its scene is spawned.
+//!
+//! The issues are logged, and with `--validation-report report.json` they're also written to a
+//! JSON file, after which the viewer exits, with a failure code if any issue is an error. This
+//! lets CI check assets with the scene viewer.
+
+use bevy::{
+    gltf::{Gltf, GltfSkin, GltfValidationIssueKind, GltfValidationReport, GltfValidationSeverity},
+    pbr::{max_joints_per_skin, SkinSettings},
+    prelude::*,
+    render::renderer::RenderDevice,
+};
+use serde::Serialize;
+use std::path::PathBuf;
+
+use super::scene_viewer_plugin::SceneHandle;
+
+/// The report written with `--validation-report`.
+#[derive(Serialize)]
+struct ValidationReport<'a> {
+    /// The path of the glTF file.
+    scene: String,
+    #[serde(flatten)]
+    report: &'a GltfValidationReport,
+}
+
+fn log(scene: &str, report: &GltfValidationReport) {
+    for issue in &report.issues {
+        match issue.severity {
+            GltfValidationSeverity::Warning => {
+                warn!("{:?} {}: {}", issue.kind, issue.subject, issue.message);
+            }
+            GltfValidationSeverity::Error => {
+                error!("{:?} {}: {}", issue.kind, issue.subject, issue.message);
+            }
+        }
+    }
+    info!(
+        "Validated {}: {} issue(s), {} error(s)",
+        scene,
+        report.issues.len(),
+        report
+            .issues
+            .iter()
+            .filter(|issue| issue.severity == GltfValidationSeverity::Error)
+            .count()
+    );
+}
+
+pub struct ValidationPlugin {
+    /// Where to write the report as JSON before exiting, if anywhere.
+    pub report_path: Option<PathBuf>,
+}
+
+#[derive(Resource)]
+struct ValidationReportPath(Option<PathBuf>);
+
+impl Plugin for ValidationPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ValidationReportPath(self.report_path.clone()))
+            .add_systems(Update, validate_scene);
+    }
+}
+
+fn validate_scene(
+    mut validated: Local<Option<AssetId<Gltf>>>,
+    scene_handle: Res<SceneHandle>,
+    report_path: Res<ValidationReportPath>,
+    asset_server: Res<AssetServer>,
+    gltfs: Res<Assets<Gltf>>,
+    skins: Res<Assets<GltfSkin>>,
+    skin_settings: Res<SkinSettings>,
+    render_device: Res<RenderDevice>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let id = scene_handle.gltf_handle.id();
+    if *validated == Some(id)
+        || !scene_handle.is_loaded
+        || !asset_server.is_loaded_with_dependencies(id)
+    {
+        return;
+    }
+    let Some(gltf) = gltfs.get(id) else {
+        return;
+    };
+    *validated = Some(id);
+
+    // The loader checks everything that doesn't depend on the render device.
+    let mut report = gltf.validation_report.clone().unwrap_or_default();
+    let max_joints = max_joints_per_skin(&skin_settings, &render_device);
+    for (index, skin) in gltf.skins.iter().enumerate() {
+        let Some(skin) = skins.get(skin) else {
+            continue;
+        };
+        let joints = skin.joints.len();
+        if joints > max_joints {
+            report.push(
+                GltfValidationSeverity::Error,
+                GltfValidationIssueKind::TooManyJoints,
+                format!("Skin{index}"),
+                format!(
+                    "the skin has {joints} joints, but only {max_joints} are supported here, so \
+                     the vertices influenced by the other joints aren't skinned correctly"
+                ),
+            );
+        }
+    }
+
+    let scene = name(&scene_handle.gltf_handle);
+    log(&scene, &report);
+
+    let Some(path) = &report_path.0 else {
+        return;
+    };
+    let report = ValidationReport {
+        scene,
+        report: &report,
+    };
+    let written = serde_json::to_string_pretty(&report)
+        .map_err(|err| err.to_string())
+        .and_then(|json| std::fs::write(path, json).map_err(|err| err.to_string()));
+    match written {
+        Ok(()) => {
+            info!("Wrote the validation report to {}", path.display());
+            exit.send(if report.report.has_errors() {
+                AppExit::error()
+            } else {
+                AppExit::Success
+            });
+        }
+        Err(err) => {
+            error!(
+                "Failed to write the validation report to {}: {err}",
+                path.display()
+            );
+            exit.send(AppExit::error());
+        }
+    }
+}
+
+/// The asset path of an asset, or its id if it wasn't loaded from a path.
+fn name<A: Asset>(handle: &Handle<A>) -> String {
+    match handle.path() {
+        Some(path) => path.to_string(),
+        None => format!("{:?}", handle.id()),
+    }
+}